@@ -10,6 +10,7 @@ use crate::easing::Easing;
 use crate::keyframe::{Keyframe, KeyframeAnimation};
 use crate::spring::{Spring, SpringConfig};
 use crate::timeline::Timeline;
+use junita_core::clock::{self, Clock};
 use junita_core::AnimationAccess;
 use slotmap::{new_key_type, SlotMap};
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -125,8 +126,63 @@ struct SchedulerInner {
     keyframes: SlotMap<KeyframeId, KeyframeAnimation>,
     timelines: SlotMap<TimelineId, Timeline>,
     tick_callbacks: SlotMap<TickCallbackId, TickCallback>,
-    last_frame: Instant,
+    /// Clock time of the last step
+    last_frame: Duration,
     target_fps: u32,
+    /// Time source; `None` reads the process-wide clock
+    clock: Option<Arc<dyn Clock>>,
+}
+
+impl SchedulerInner {
+    fn now(&self) -> Duration {
+        match &self.clock {
+            Some(clock) => clock.now(),
+            None => clock::now(),
+        }
+    }
+
+    /// Whether someone other than the background thread moves time forward
+    fn is_externally_driven(&self) -> bool {
+        match &self.clock {
+            Some(clock) => !clock.is_realtime(),
+            None => !clock::is_realtime(),
+        }
+    }
+
+    /// Seconds since the last step, marking now as the last step
+    fn take_dt(&mut self) -> f32 {
+        let now = self.now();
+        let dt = now.saturating_sub(self.last_frame).as_secs_f32();
+        self.last_frame = now;
+        dt
+    }
+
+    fn tick_callbacks(&self) -> Vec<TickCallback> {
+        self.tick_callbacks
+            .iter()
+            .map(|(_, cb)| Arc::clone(cb))
+            .collect()
+    }
+}
+
+/// Step every spring, keyframe animation and timeline by `dt` seconds
+fn step_animations(inner: &mut SchedulerInner, dt: f32) {
+    let dt_ms = dt * 1000.0;
+
+    // Update all springs
+    for (_, spring) in inner.springs.iter_mut() {
+        spring.step(dt);
+    }
+
+    // Update all keyframe animations
+    for (_, keyframe) in inner.keyframes.iter_mut() {
+        keyframe.tick(dt_ms);
+    }
+
+    // Update all timelines
+    for (_, timeline) in inner.timelines.iter_mut() {
+        timeline.tick(dt_ms);
+    }
 }
 
 /// Callback type for waking up the main thread from the animation thread
@@ -173,8 +229,9 @@ impl AnimationScheduler {
                 keyframes: SlotMap::with_key(),
                 timelines: SlotMap::with_key(),
                 tick_callbacks: SlotMap::with_key(),
-                last_frame: Instant::now(),
+                last_frame: clock::now(),
                 target_fps: 120,
+                clock: None,
             })),
             stop_flag: Arc::new(AtomicBool::new(false)),
             needs_redraw: Arc::new(AtomicBool::new(false)),
@@ -236,32 +293,17 @@ impl AnimationScheduler {
                 // Tick animations and check if any are active
                 let (has_active, tick_callbacks_to_call, dt) = {
                     let mut inner = inner.lock().unwrap();
-                    let now = Instant::now();
-                    let dt = (now - inner.last_frame).as_secs_f32();
-                    let dt_ms = dt * 1000.0;
-                    inner.last_frame = now;
-
-                    // Update all springs
-                    for (_, spring) in inner.springs.iter_mut() {
-                        spring.step(dt);
-                    }
 
-                    // Update all keyframe animations
-                    for (_, keyframe) in inner.keyframes.iter_mut() {
-                        keyframe.tick(dt_ms);
-                    }
-
-                    // Update all timelines
-                    for (_, timeline) in inner.timelines.iter_mut() {
-                        timeline.tick(dt_ms);
-                    }
-
-                    // Collect tick callbacks to call (we'll call them after releasing the lock)
-                    let callbacks: Vec<_> = inner
-                        .tick_callbacks
-                        .iter()
-                        .map(|(_, cb)| Arc::clone(cb))
-                        .collect();
+                    // A virtual clock owns time; the frame loop steps
+                    // animations via step_frame()
+                    let (callbacks, dt) = if inner.is_externally_driven() {
+                        (Vec::new(), 0.0)
+                    } else {
+                        let dt = inner.take_dt();
+                        step_animations(&mut inner, dt);
+                        // Collect tick callbacks to call (we'll call them after releasing the lock)
+                        (inner.tick_callbacks(), dt)
+                    };
 
                     // NOTE: We do NOT remove animations here!
                     // Springs, keyframes, and timelines are only removed when:
@@ -347,9 +389,13 @@ impl AnimationScheduler {
     /// Manually request a redraw
     ///
     /// This sets the needs_redraw flag, which will be picked up by the
-    /// main thread on its next event loop iteration.
+    /// main thread on its next event loop iteration, and calls the wake
+    /// callback so an idle event loop gets one. Safe to call from any thread.
     pub fn request_redraw(&self) {
         self.needs_redraw.store(true, Ordering::Release);
+        if let Some(ref callback) = self.wake_callback {
+            callback();
+        }
     }
 
     /// Enable continuous redraw mode
@@ -385,29 +431,12 @@ impl AnimationScheduler {
     /// Returns true if any animations are still active (need another tick).
     pub fn tick(&self) -> bool {
         let mut inner = self.inner.lock().unwrap();
-        let now = Instant::now();
-        let dt = (now - inner.last_frame).as_secs_f32();
-        let dt_ms = dt * 1000.0;
-        inner.last_frame = now;
-
-        // Update all springs
-        for (_, spring) in inner.springs.iter_mut() {
-            spring.step(dt);
-        }
-
-        // Update all keyframe animations
-        for (_, keyframe) in inner.keyframes.iter_mut() {
-            keyframe.tick(dt_ms);
-        }
-
-        // Update all timelines
-        for (_, timeline) in inner.timelines.iter_mut() {
-            timeline.tick(dt_ms);
-        }
+        let dt = inner.take_dt();
 
         // NOTE: We do NOT remove animations here!
         // Springs, keyframes, and timelines are only removed when their wrappers drop.
         // This ensures animations can be restarted after completing.
+        step_animations(&mut inner, dt);
 
         // Return true if there are still active (playing, not just present) animations
         inner.springs.iter().any(|(_, s)| !s.is_settled())
@@ -415,6 +444,48 @@ impl AnimationScheduler {
             || inner.timelines.iter().any(|(_, t)| t.is_playing())
     }
 
+    /// Read time from `clock` instead of the process-wide clock
+    ///
+    /// See [`junita_core::clock`]. With a clock that is not realtime the
+    /// background thread stops stepping animations and the frame loop is
+    /// expected to call [`step_frame`](Self::step_frame) instead.
+    pub fn set_clock(&self, clock: Arc<dyn Clock>) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.last_frame = clock.now();
+        inner.clock = Some(clock);
+    }
+
+    /// Current time of the scheduler's clock
+    pub fn now(&self) -> Duration {
+        self.inner.lock().unwrap().now()
+    }
+
+    /// Step all animations and tick callbacks by the time the clock moved
+    /// since the last step
+    ///
+    /// This is how the frame loop drives animations under a virtual clock:
+    /// advance the clock, then call this once per frame. Returns true if
+    /// any animations are still active afterwards.
+    pub fn step_frame(&self) -> bool {
+        let (callbacks, dt) = {
+            let mut inner = self.inner.lock().unwrap();
+            let dt = inner.take_dt();
+            step_animations(&mut inner, dt);
+            (inner.tick_callbacks(), dt)
+        };
+        self.run_tick_callbacks(callbacks, dt);
+        self.has_active_animations()
+    }
+
+    /// Call tick callbacks outside the lock to avoid deadlocks
+    fn run_tick_callbacks(&self, callbacks: Vec<TickCallback>, dt: f32) {
+        for callback in callbacks {
+            if let Ok(mut cb) = callback.lock() {
+                cb(dt);
+            }
+        }
+    }
+
     /// Check if any animations are still active
     pub fn has_active_animations(&self) -> bool {
         let inner = self.inner.lock().unwrap();
@@ -695,6 +766,16 @@ pub struct SchedulerHandle {
 }
 
 impl SchedulerHandle {
+    /// Current time of the scheduler's clock
+    ///
+    /// Falls back to the process-wide clock once the scheduler is dropped.
+    pub fn now(&self) -> Duration {
+        match self.inner.upgrade() {
+            Some(inner) => inner.lock().unwrap().now(),
+            None => clock::now(),
+        }
+    }
+
     // =========================================================================
     // Spring Operations
     // =========================================================================
@@ -705,7 +786,7 @@ impl SchedulerHandle {
             let mut guard = inner.lock().unwrap();
             // Reset last_frame to now to prevent huge dt on first tick
             // This ensures new springs start animating smoothly from their current frame
-            guard.last_frame = std::time::Instant::now();
+            guard.last_frame = guard.now();
             guard.springs.insert(spring)
         })
     }
@@ -1147,8 +1228,8 @@ pub struct AnimatedKeyframe {
     reversed: bool,
     /// Delay before animation starts (ms)
     delay_ms: u32,
-    /// Time when animation started (for delay tracking)
-    start_time: Option<std::time::Instant>,
+    /// Scheduler clock time when animation started (for delay tracking)
+    start_time: Option<Duration>,
 }
 
 impl AnimatedKeyframe {
@@ -1240,7 +1321,7 @@ impl AnimatedKeyframe {
 
         // Track start time for delay
         if self.delay_ms > 0 {
-            self.start_time = Some(std::time::Instant::now());
+            self.start_time = Some(self.handle.now());
         } else {
             self.start_time = None;
         }
@@ -1288,7 +1369,7 @@ impl AnimatedKeyframe {
     fn check_and_update(&mut self) -> bool {
         // Handle delay
         if let Some(start_time) = self.start_time {
-            let elapsed = start_time.elapsed().as_millis() as u32;
+            let elapsed = self.handle.now().saturating_sub(start_time).as_millis() as u32;
             if elapsed < self.delay_ms {
                 return true; // Still in delay period
             }
@@ -1772,6 +1853,94 @@ mod tests {
         assert!(value > 0.0);
     }
 
+    #[test]
+    fn test_manual_clock() {
+        let time = junita_core::clock::ManualClock::new();
+        let scheduler = AnimationScheduler::new();
+        scheduler.set_clock(Arc::new(time.clone()));
+
+        let id = scheduler.add_spring(Spring::new(SpringConfig::stiff(), 0.0));
+        scheduler.set_spring_target(id, 100.0);
+
+        // Wall time passing leaves the spring alone
+        std::thread::sleep(Duration::from_millis(2));
+        assert!(scheduler.tick());
+        assert_eq!(scheduler.get_spring_value(id), Some(0.0));
+
+        // Moving the clock moves it, and enough frames settle it
+        time.advance(Duration::from_millis(16));
+        assert!(scheduler.step_frame());
+        assert!(scheduler.get_spring_value(id).unwrap() > 0.0);
+        for _ in 0..200 {
+            time.advance(Duration::from_millis(16));
+            scheduler.step_frame();
+        }
+        assert!(!scheduler.has_active_animations());
+        assert_eq!(scheduler.get_spring_value(id), Some(100.0));
+    }
+
+    #[test]
+    fn test_virtual_clock_step_frame() {
+        let time = junita_core::clock::ManualClock::new();
+        let scheduler = AnimationScheduler::new();
+        scheduler.set_clock(Arc::new(time.clone()));
+
+        let ticks = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&ticks);
+        scheduler
+            .handle()
+            .register_tick_callback(move |dt| seen.lock().unwrap().push(dt));
+
+        let id = scheduler.add_spring(Spring::new(SpringConfig::stiff(), 0.0));
+        scheduler.set_spring_target(id, 100.0);
+
+        // Nothing moves until the clock does
+        assert!(scheduler.step_frame());
+        assert_eq!(scheduler.get_spring_value(id), Some(0.0));
+
+        time.advance(Duration::from_millis(16));
+        scheduler.step_frame();
+        let first = scheduler.get_spring_value(id).unwrap();
+        assert!(first > 0.0);
+        assert_eq!(*ticks.lock().unwrap(), vec![0.0, 0.016]);
+
+        // The same steps on a fresh scheduler give the same values
+        let replay_time = junita_core::clock::ManualClock::new();
+        let replay = AnimationScheduler::new();
+        replay.set_clock(Arc::new(replay_time.clone()));
+        let replay_id = replay.add_spring(Spring::new(SpringConfig::stiff(), 0.0));
+        replay.set_spring_target(replay_id, 100.0);
+        replay_time.advance(Duration::from_millis(16));
+        replay.step_frame();
+        assert_eq!(replay.get_spring_value(replay_id), Some(first));
+    }
+
+    #[test]
+    fn test_keyframe_delay_uses_scheduler_clock() {
+        let time = junita_core::clock::ManualClock::new();
+        let scheduler = AnimationScheduler::new();
+        scheduler.set_clock(Arc::new(time.clone()));
+
+        let mut anim = AnimatedKeyframe::new(scheduler.handle(), 100)
+            .keyframe(0.0, 0.0, Easing::Linear)
+            .keyframe(1.0, 1.0, Easing::Linear)
+            .delay(50)
+            .auto_start(true)
+            .build();
+
+        // Wall time passing does not end the delay
+        std::thread::sleep(Duration::from_millis(60));
+        scheduler.step_frame();
+        assert_eq!(anim.get(), 0.0);
+
+        time.advance(Duration::from_millis(50));
+        scheduler.step_frame();
+        anim.get();
+        time.advance(Duration::from_millis(50));
+        scheduler.step_frame();
+        assert!((anim.get() - 0.5).abs() < 1e-3);
+    }
+
     #[test]
     fn test_animated_value() {
         let scheduler = AnimationScheduler::new();
@@ -224,6 +224,45 @@ impl JunitaApp {
             .render_tree_with_motion(tree, render_state, width, height, target)
     }
 
+    /// Damage to render a frame with, given the tree's damage
+    ///
+    /// See [`RenderContext::frame_damage`]. An empty result means the
+    /// previous frame is still on screen and needn't be presented again.
+    pub fn frame_damage(
+        &mut self,
+        tree: &RenderTree,
+        damage: &junita_layout::DamageRegion,
+        width: u32,
+        height: u32,
+    ) -> junita_layout::DamageRegion {
+        self.ctx.frame_damage(tree, damage, width, height)
+    }
+
+    /// Render a tree, repainting only the damaged region
+    ///
+    /// See [`RenderContext::render_tree_with_damage`]. `target` is the
+    /// surface texture, which must be configured with `COPY_DST` usage.
+    pub fn render_tree_with_damage(
+        &mut self,
+        tree: &RenderTree,
+        render_state: &junita_layout::RenderState,
+        damage: &junita_layout::DamageRegion,
+        target: &wgpu::Texture,
+        width: u32,
+        height: u32,
+    ) -> Result<()> {
+        self.ctx
+            .render_tree_with_damage(tree, render_state, damage, width, height, target)
+    }
+
+    /// Texture usages supported by a surface created for this app
+    ///
+    /// Partial redraw needs `COPY_DST`; surfaces without it fall back to
+    /// repainting every frame.
+    pub fn surface_usages(&self, surface: &wgpu::Surface<'_>) -> wgpu::TextureUsages {
+        self.ctx.surface_usages(surface)
+    }
+
     /// Render an overlay tree on top of existing content (no clear)
     ///
     /// This is used for rendering modal/dialog/toast overlays on top of the main UI.
@@ -14,9 +14,11 @@ use junita_layout::div::{FontFamily, FontWeight, GenericFont, TextAlign, TextVer
 use junita_layout::prelude::*;
 use junita_layout::render_state::Overlay;
 use junita_layout::renderer::ElementType;
+use junita_layout::ImagePlayback;
 use junita_svg::{RasterizedSvg, SvgDocument};
 use lru::LruCache;
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::num::NonZeroUsize;
 use std::sync::{Arc, Mutex};
@@ -45,8 +47,14 @@ pub struct RenderContext {
     backdrop_texture: Option<CachedTexture>,
     // Cached MSAA texture for anti-aliased rendering
     msaa_texture: Option<CachedTexture>,
+    // Retained frame for partial redraw (keeps the previous frame's pixels)
+    retained_frame: Option<CachedTexture>,
     // LRU cache for images (prevents unbounded memory growth)
     image_cache: LruCache<String, GpuImage>,
+    // Frame state for animated images in `image_cache`, keyed by source
+    animated_images: HashMap<String, AnimatedImageState>,
+    // Playbacks of animated images that were on screen last frame
+    visible_playbacks: Vec<ImagePlayback>,
     // LRU cache for parsed SVG documents (avoids re-parsing)
     svg_cache: LruCache<u64, SvgDocument>,
     // LRU cache for rasterized SVG textures (CPU-rasterized with proper AA)
@@ -58,6 +66,18 @@ pub struct RenderContext {
     scratch_images: Vec<ImageElement>,
 }
 
+/// Playback state of an animated image
+///
+/// The GPU texture lives in `image_cache`; it's rewritten with the composited
+/// canvas whenever the displayed frame changes.
+struct AnimatedImageState {
+    compositor: junita_image::FrameCompositor,
+    /// Playback shared by images of this source without their own
+    playback: ImagePlayback,
+    /// Frame picked when computing damage, so the upload matches it
+    pending_frame: Option<usize>,
+}
+
 struct CachedTexture {
     texture: wgpu::Texture,
     view: wgpu::TextureView,
@@ -134,6 +154,23 @@ struct ImageElement {
     border_width: f32,
     /// Border color
     border_color: junita_core::Color,
+    /// Playback control for animated images (`None` = shared autoplay)
+    playback: Option<ImagePlayback>,
+}
+
+impl ImageElement {
+    /// Whether the image intersects its clip region, or the viewport if it
+    /// isn't clipped, grown by `buffer` pixels on each side
+    fn is_visible(&self, viewport_width: f32, viewport_height: f32, buffer: f32) -> bool {
+        let [left, top, width, height] =
+            self.clip_bounds
+                .unwrap_or([0.0, 0.0, viewport_width, viewport_height]);
+
+        self.x < left + width + buffer
+            && self.x + self.width > left - buffer
+            && self.y < top + height + buffer
+            && self.y + self.height > top - buffer
+    }
 }
 
 /// SVG element data for rendering
@@ -183,7 +220,10 @@ impl RenderContext {
             sample_count,
             backdrop_texture: None,
             msaa_texture: None,
+            retained_frame: None,
             image_cache: LruCache::new(NonZeroUsize::new(IMAGE_CACHE_CAPACITY).unwrap()),
+            animated_images: HashMap::new(),
+            visible_playbacks: Vec::new(),
             svg_cache: LruCache::new(NonZeroUsize::new(SVG_CACHE_CAPACITY).unwrap()),
             rasterized_svg_cache: LruCache::new(
                 NonZeroUsize::new(RASTERIZED_SVG_CACHE_CAPACITY).unwrap(),
@@ -240,6 +280,7 @@ impl RenderContext {
 
         // Pre-load all images into cache before rendering
         self.preload_images(&images, width as f32, height as f32);
+        self.advance_animated_images(&images, width as f32, height as f32, true);
 
         // Prepare text glyphs
         let mut all_glyphs = Vec::new();
@@ -816,66 +857,133 @@ impl RenderContext {
             }
 
             // Check if lazy loading is enabled (loading_strategy == 1)
-            if image.loading_strategy == 1 {
-                // If image has clip bounds from a scroll container, use those for visibility check
-                // The clip bounds represent the visible area of the parent scroll container
-                let is_visible = if let Some([clip_x, clip_y, clip_w, clip_h]) = image.clip_bounds {
-                    // Check if image intersects with its clip region (+ buffer for prefetching)
-                    let clip_left = clip_x - VISIBILITY_BUFFER;
-                    let clip_top = clip_y - VISIBILITY_BUFFER;
-                    let clip_right = clip_x + clip_w + VISIBILITY_BUFFER;
-                    let clip_bottom = clip_y + clip_h + VISIBILITY_BUFFER;
-
-                    let image_right = image.x + image.width;
-                    let image_bottom = image.y + image.height;
-
-                    image.x < clip_right
-                        && image_right > clip_left
-                        && image.y < clip_bottom
-                        && image_bottom > clip_top
-                } else {
-                    // No clip bounds - check against viewport
-                    let viewport_left = -VISIBILITY_BUFFER;
-                    let viewport_top = -VISIBILITY_BUFFER;
-                    let viewport_right = viewport_width + VISIBILITY_BUFFER;
-                    let viewport_bottom = viewport_height + VISIBILITY_BUFFER;
-
-                    let image_right = image.x + image.width;
-                    let image_bottom = image.y + image.height;
-
-                    image.x < viewport_right
-                        && image_right > viewport_left
-                        && image.y < viewport_bottom
-                        && image_bottom > viewport_top
-                };
-
-                if !is_visible {
-                    // Skip loading - image is not yet visible
-                    continue;
-                }
+            if image.loading_strategy == 1
+                && !image.is_visible(viewport_width, viewport_height, VISIBILITY_BUFFER)
+            {
+                // Skip loading - image is not yet visible
+                continue;
             }
 
             // Try to load the image - use from_uri to handle emoji://, data:, and file paths
+            // Animated formats keep all their frames
             let source = junita_image::ImageSource::from_uri(&image.source);
-            let image_data = match junita_image::ImageData::load(source) {
-                Ok(data) => data,
+            let animated = match junita_image::AnimatedImage::load(source) {
+                Ok(animated) => animated,
                 Err(e) => {
                     tracing::trace!("Failed to load image '{}': {:?}", image.source, e);
                     continue; // Skip images that fail to load
                 }
             };
 
-            // Create GPU texture
+            // Create GPU texture from the first frame
+            let (width, height) = animated.dimensions();
+            let mut compositor = junita_image::FrameCompositor::new(animated);
             let gpu_image = self.image_ctx.create_image_labeled(
-                image_data.pixels(),
-                image_data.width(),
-                image_data.height(),
+                compositor.frame(0),
+                width,
+                height,
                 &image.source,
             );
 
+            if compositor.image().is_animated() {
+                self.animated_images.insert(
+                    image.source.clone(),
+                    AnimatedImageState {
+                        compositor,
+                        playback: ImagePlayback::new(),
+                        pending_frame: None,
+                    },
+                );
+            } else {
+                self.animated_images.remove(&image.source);
+            }
+
             // LruCache::put evicts oldest entry if at capacity
             self.image_cache.put(image.source.clone(), gpu_image);
         }
+
+        // Drop frame state of animated images evicted from the cache
+        let image_cache = &self.image_cache;
+        self.animated_images
+            .retain(|source, _| image_cache.contains(source));
+    }
+
+    /// Advance animated images to the frame their playback is at
+    ///
+    /// Images on screen are marked visible so their playback ticks. With
+    /// `hide_unseen`, playbacks that were on screen last frame but aren't
+    /// anymore are paused until they come back.
+    fn advance_animated_images(
+        &mut self,
+        images: &[ImageElement],
+        viewport_width: f32,
+        viewport_height: f32,
+        hide_unseen: bool,
+    ) {
+        let mut visible = Vec::new();
+        for image in images {
+            let Some(state) = self.animated_images.get_mut(&image.source) else {
+                continue;
+            };
+            let playback = image
+                .playback
+                .clone()
+                .unwrap_or_else(|| state.playback.clone());
+            playback.set_duration(state.compositor.image().total_duration());
+
+            if !image.is_visible(viewport_width, viewport_height, 0.0) {
+                continue;
+            }
+            playback.set_visible(true);
+
+            let frame = state
+                .pending_frame
+                .take()
+                .unwrap_or_else(|| state.compositor.image().frame_at(playback.position()));
+            if state.compositor.current_frame() != Some(frame) {
+                if let Some(gpu_image) = self.image_cache.peek(&image.source) {
+                    self.image_ctx
+                        .update_image(gpu_image, state.compositor.frame(frame));
+                }
+            }
+            visible.push(playback);
+        }
+
+        if hide_unseen {
+            for playback in self.visible_playbacks.drain(..) {
+                if !visible.iter().any(|seen| seen.ptr_eq(&playback)) {
+                    playback.set_visible(false);
+                }
+            }
+            self.visible_playbacks = visible;
+        } else {
+            self.visible_playbacks.extend(visible);
+        }
+    }
+
+    /// Damage animated images whose displayed frame is about to change
+    fn damage_animated_images(
+        &mut self,
+        tree: &RenderTree,
+        damage: &mut junita_layout::DamageRegion,
+    ) {
+        if self.animated_images.is_empty() {
+            return;
+        }
+        for (image, bounds) in tree.image_elements() {
+            let Some(state) = self.animated_images.get_mut(&image.source) else {
+                continue;
+            };
+            let position = match &image.playback {
+                Some(playback) => playback.position(),
+                None => state.playback.position(),
+            };
+            let frame = state.compositor.image().frame_at(position);
+            if state.compositor.current_frame() != Some(frame) {
+                state.pending_frame = Some(frame);
+                damage.add(Rect::new(bounds.x, bounds.y, bounds.width, bounds.height));
+            }
+        }
     }
 
     /// Render images to target (images must be preloaded first)
@@ -1775,6 +1883,7 @@ impl RenderContext {
                             .props
                             .border_color
                             .unwrap_or(junita_core::Color::TRANSPARENT),
+                        playback: image_data.playback.clone(),
                     });
                 }
                 // Canvas elements are rendered inline during tree traversal (in render_layer)
@@ -2020,6 +2129,130 @@ impl RenderContext {
         self.renderer.texture_format()
     }
 
+    /// Texture usages supported by a surface on this context's adapter
+    pub fn surface_usages(&self, surface: &wgpu::Surface<'_>) -> wgpu::TextureUsages {
+        self.renderer.surface_usages(surface)
+    }
+
+    /// Damage to render a frame with, given the tree's damage
+    ///
+    /// Adds animated images whose frame advanced, and damages everything
+    /// when the retained frame has to be recreated (first frame or resize).
+    /// An empty result means the previous frame is still current, so the
+    /// surface doesn't need to be acquired or presented at all.
+    pub fn frame_damage(
+        &mut self,
+        tree: &RenderTree,
+        damage: &junita_layout::DamageRegion,
+        width: u32,
+        height: u32,
+    ) -> junita_layout::DamageRegion {
+        let mut damage = damage.clone();
+        self.damage_animated_images(tree, &mut damage);
+        if self.ensure_retained_frame(width, height) {
+            damage.add_full();
+        }
+        damage
+    }
+
+    /// Render a tree, repainting only the damaged region
+    ///
+    /// Frames are rendered into a retained texture that keeps the previous
+    /// frame, then copied into `target`, which must be created with
+    /// `COPY_DST` usage. `damage` should come from [`Self::frame_damage`];
+    /// an empty one skips rendering and copies the retained frame again.
+    ///
+    /// Scenes with glass, layer effects or 3D viewports always repaint in
+    /// full, since those passes sample or overwrite content outside the
+    /// damaged region.
+    pub fn render_tree_with_damage(
+        &mut self,
+        tree: &RenderTree,
+        render_state: &junita_layout::RenderState,
+        damage: &junita_layout::DamageRegion,
+        width: u32,
+        height: u32,
+        target: &wgpu::Texture,
+    ) -> Result<()> {
+        let fresh = self.ensure_retained_frame(width, height);
+        let retained = self
+            .retained_frame
+            .take()
+            .expect("retained frame was just created");
+
+        let result = if fresh || !damage.is_empty() {
+            let scissor = if fresh {
+                None
+            } else {
+                damage.scissor(tree.scale_factor(), width, height)
+            };
+            self.renderer.set_damage_scissor(scissor);
+            let result =
+                self.render_tree_with_motion(tree, render_state, width, height, &retained.view);
+            self.renderer.set_damage_scissor(None);
+            result
+        } else {
+            Ok(())
+        };
+
+        let mut encoder = self
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+                label: Some("Retained Frame Copy Encoder"),
+            });
+        encoder.copy_texture_to_texture(
+            retained.texture.as_image_copy(),
+            target.as_image_copy(),
+            wgpu::Extent3d {
+                width: width.min(target.width()),
+                height: height.min(target.height()),
+                depth_or_array_layers: 1,
+            },
+        );
+        self.queue.submit(std::iter::once(encoder.finish()));
+
+        self.retained_frame = Some(retained);
+        result
+    }
+
+    /// Ensure the retained frame texture exists and matches the surface size
+    ///
+    /// Returns true when the texture was (re)created, in which case its
+    /// contents are undefined and the next frame must be painted in full.
+    fn ensure_retained_frame(&mut self, width: u32, height: u32) -> bool {
+        let up_to_date = self
+            .retained_frame
+            .as_ref()
+            .map(|t| t.width == width && t.height == height)
+            .unwrap_or(false);
+        if up_to_date {
+            return false;
+        }
+
+        let texture = self.device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Retained Frame"),
+            size: wgpu::Extent3d {
+                width: width.max(1),
+                height: height.max(1),
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: self.renderer.texture_format(),
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+        self.retained_frame = Some(CachedTexture {
+            texture,
+            view,
+            width,
+            height,
+        });
+        true
+    }
+
     /// Render a layout tree with dynamic render state overlays
     ///
     /// This method renders:
@@ -2064,9 +2297,13 @@ impl RenderContext {
         // Get scale factor for HiDPI rendering
         let scale_factor = tree.scale_factor();
 
+        // Cached layers still resident from earlier frames skip their subtrees
+        let resident_layers = self.renderer.prepare_layer_cache();
+
         // Create a single paint context for all layers with text rendering support
         let mut ctx =
             GpuPaintContext::with_text_context(width as f32, height as f32, &mut self.text_ctx);
+        ctx.set_resident_layers(resident_layers);
 
         // Render with motion animations applied (all layers to same context)
         tree.render_with_motion(&mut ctx, render_state);
@@ -2080,6 +2317,7 @@ impl RenderContext {
 
         // Pre-load all images into cache before rendering
         self.preload_images(&images, width as f32, height as f32);
+        self.advance_animated_images(&images, width as f32, height as f32, true);
 
         // Prepare text glyphs with z_layer information
         // Store (z_layer, glyphs) to enable interleaved rendering
@@ -2221,7 +2459,20 @@ impl RenderContext {
         self.renderer.resize(width, height);
 
         let has_glass = batch.glass_count() > 0;
-        let has_layer_effects_in_batch = batch.has_layer_effects();
+        let has_layer_effects_in_batch = batch.needs_layer_compositing();
+
+        // These passes sample or overwrite content outside any damage scissor
+        let debug = DebugMode::from_env();
+        if has_glass
+            || has_layer_effects_in_batch
+            || !batch.viewports_3d.is_empty()
+            || !batch.particle_viewports.is_empty()
+            || debug.text
+            || debug.layout
+            || debug.motion
+        {
+            self.renderer.set_damage_scissor(None);
+        }
 
         // Only allocate glass textures when glass is actually used
         if has_glass {
@@ -2312,7 +2563,7 @@ impl RenderContext {
             let max_text_z = glyphs_by_layer.keys().cloned().max().unwrap_or(0);
             let max_decoration_z = decorations_by_layer.keys().cloned().max().unwrap_or(0);
             let max_layer = max_z.max(max_text_z).max(max_decoration_z);
-            let has_layer_effects = batch.has_layer_effects();
+            let has_layer_effects = batch.needs_layer_compositing();
 
             if max_layer > 0 && !has_layer_effects {
                 // Interleaved z-layer rendering for proper Stack z-ordering
@@ -2422,7 +2673,6 @@ impl RenderContext {
         self.render_overlays(render_state, width, height, target);
 
         // Render debug visualization if enabled (JUNITA_DEBUG=text|layout|all)
-        let debug = DebugMode::from_env();
         if debug.text {
             self.render_text_debug(target, &texts);
         }
@@ -2471,6 +2721,7 @@ impl RenderContext {
 
         // Pre-load all images into cache before rendering
         self.preload_images(&images, width as f32, height as f32);
+        self.advance_animated_images(&images, width as f32, height as f32, false);
 
         // Prepare text glyphs with z_layer information
         let mut glyphs_by_layer: std::collections::BTreeMap<u32, Vec<GpuGlyph>> =
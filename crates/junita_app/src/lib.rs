@@ -137,6 +137,10 @@ mod text_measurer;
 ))]
 pub mod windowed;
 
+// Live attach for junita_debugger, driven by the windowed runner
+#[cfg(feature = "recorder")]
+pub mod live_inspect;
+
 #[cfg(all(feature = "android", target_os = "android"))]
 pub mod android;
 #[cfg(all(feature = "android", target_os = "android"))]
@@ -165,8 +169,8 @@ pub use junita_layout::RenderTree;
 // Re-export platform types for windowed applications
 pub use junita_platform::WindowConfig;
 
-// Re-export derive macro
-pub use junita_macros::JunitaComponent;
+// Re-export macros
+pub use junita_macros::{view, JunitaComponent};
 
 /// Prelude module - import everything commonly needed
 pub mod prelude {
@@ -188,8 +192,8 @@ pub mod prelude {
     // Platform types
     pub use junita_platform::WindowConfig;
 
-    // Derive macro for components
-    pub use junita_macros::JunitaComponent;
+    // Component derive and markup macros
+    pub use junita_macros::{view, JunitaComponent};
 
     // Theme types
     pub use junita_theme::{ColorScheme, ColorToken, RadiusToken, SpacingToken, ThemeState};
@@ -0,0 +1,457 @@
+//! Live inspection of a running app from junita_debugger
+//!
+//! Start a debug server with [`start_live_debug_server`] and attach the
+//! debugger to its socket. The windowed runner feeds every frame through the
+//! global [`LiveInspector`], which:
+//!
+//! - captures tree snapshots while a client is subscribed
+//! - draws a highlight over the element hovered in the debugger
+//! - turns the next click into a pick while picking is enabled
+//! - reapplies style edits made from the inspector after every rebuild
+//! - answers computed style (with the source of each value) and layout
+//!   queries on the UI thread
+//! - toggles the layout debug overlays from the debugger's preview
+//! - sets signals edited in the reactive inspector on the UI thread
+//!
+//! # Example
+//!
+//! ```ignore
+//! use junita_recorder::{RecordingConfig, SharedRecordingSession};
+//!
+//! let session = Arc::new(SharedRecordingSession::new(RecordingConfig::debug()));
+//! let _server = junita_app::live_inspect::start_live_debug_server("my_app", session)?;
+//! WindowedApp::run(WindowConfig::default(), |ctx| build_ui(ctx))
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use junita_core::reactive::{ReactiveSnapshot, SharedReactiveGraph, SignalId};
+use junita_core::{Color, DirtyFlag};
+use junita_layout::debug_overlay;
+use junita_layout::prelude::*;
+use junita_layout::recorder_bridge::{self, LiveEdits, SnapshotRect, TreeSnapshotData};
+use junita_layout::tree::LayoutNodeId;
+use junita_layout::EventRouter;
+use junita_recorder::{
+    ComputedStyle, DebugOverlays, DebugServer, DebugServerConfig, Edges, ElementSnapshot,
+    LayoutInfo, LiveTarget, Rect, ServerHandle, SharedRecordingSession, Timestamp, TreeSnapshot,
+    VisualProps,
+};
+
+/// How long a query waits for the UI thread before giving up
+const QUERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Highlight fill and outline colors
+const HIGHLIGHT_FILL: Color = Color::rgba(0.26, 0.52, 0.96, 0.25);
+const HIGHLIGHT_BORDER: Color = Color::rgba(0.26, 0.52, 0.96, 0.9);
+
+/// Start a debug server that lets junita_debugger attach to this app
+///
+/// The server listens on the socket for `app_name` (see
+/// [`DebugServerConfig::socket_path`]) and stops when the handle is dropped.
+pub fn start_live_debug_server(
+    app_name: impl Into<String>,
+    session: Arc<SharedRecordingSession>,
+) -> std::io::Result<ServerHandle> {
+    DebugServer::new(DebugServerConfig::new(app_name), session)
+        .with_target(LiveInspector::global())
+        .start()
+}
+
+enum Query {
+    Style(String),
+    Layout(String),
+    SetSignal(u64, String),
+}
+
+enum Answer {
+    Style(Option<ComputedStyle>),
+    Layout(Option<LayoutInfo>),
+    SetSignal(Result<(), String>),
+}
+
+#[derive(Default)]
+struct InspectorState {
+    live: bool,
+    snapshot: Option<TreeSnapshot>,
+    highlight: Option<String>,
+    /// Highlight drawn in the previous frame, to repaint when it moves or clears
+    drawn_highlight: Option<SnapshotRect>,
+    picking: bool,
+    picked: Option<String>,
+    edits: LiveEdits,
+    edits_changed: bool,
+    queries: Vec<(u64, Query)>,
+    answers: HashMap<u64, Answer>,
+    next_query: u64,
+}
+
+/// Result of [`LiveInspector::process_frame`]
+pub struct LiveFrame {
+    /// The whole window must be repainted (highlight moved or styles edited)
+    pub full_repaint: bool,
+    /// Overlay tree drawing the highlight, if an element is highlighted
+    pub highlight: Option<RenderTree>,
+    /// The highlighted element, which the box model overlay inspects
+    pub highlighted: Option<LayoutNodeId>,
+}
+
+/// [`LiveTarget`] implementation for windowed apps
+///
+/// Server threads only touch the shared state; all work on the render
+/// tree happens in [`process_frame`](Self::process_frame) on the UI thread.
+pub struct LiveInspector {
+    state: Mutex<InspectorState>,
+    answered: Condvar,
+    wake: Mutex<Option<Box<dyn Fn() + Send + Sync>>>,
+    reactive: Mutex<Option<(SharedReactiveGraph, DirtyFlag)>>,
+    started: Instant,
+}
+
+impl LiveInspector {
+    fn new() -> Self {
+        Self {
+            state: Mutex::new(InspectorState::default()),
+            answered: Condvar::new(),
+            wake: Mutex::new(None),
+            reactive: Mutex::new(None),
+            started: Instant::now(),
+        }
+    }
+
+    /// The inspector used by the windowed runner
+    pub fn global() -> Arc<LiveInspector> {
+        static INSPECTOR: OnceLock<Arc<LiveInspector>> = OnceLock::new();
+        INSPECTOR
+            .get_or_init(|| Arc::new(LiveInspector::new()))
+            .clone()
+    }
+
+    /// Set the callback used to wake the event loop when a frame is needed
+    pub fn set_wake_callback<F>(&self, callback: F)
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        *self.wake.lock().unwrap() = Some(Box::new(callback));
+    }
+
+    /// Set the app's reactive graph and the flag that triggers a rebuild
+    pub fn set_reactive(&self, graph: SharedReactiveGraph, dirty_flag: DirtyFlag) {
+        *self.reactive.lock().unwrap() = Some((graph, dirty_flag));
+    }
+
+    /// Set a signal from its string form and schedule a rebuild
+    fn apply_signal(&self, signal: u64, value: &str) -> Result<(), String> {
+        let Some((graph, dirty_flag)) = self.reactive.lock().unwrap().clone() else {
+            return Err("No reactive graph attached".to_string());
+        };
+        let id = SignalId::from_raw(signal);
+        graph.lock().unwrap().set_from_str(id, value)?;
+        junita_layout::check_stateful_deps(&[id]);
+        dirty_flag.store(true, std::sync::atomic::Ordering::SeqCst);
+        Ok(())
+    }
+
+    fn wake(&self) {
+        if let Some(wake) = self.wake.lock().unwrap().as_ref() {
+            wake();
+        }
+    }
+
+    /// Whether a click at this point should be consumed as a pick
+    ///
+    /// Returns `true` (and records the picked element) while picking is enabled.
+    pub fn try_pick(&self, tree: &RenderTree, router: &EventRouter, x: f32, y: f32) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if !state.picking {
+            return false;
+        }
+        state.picking = false;
+        state.picked = recorder_bridge::element_at(tree, router, x, y);
+        state.highlight = state.picked.clone();
+        drop(state);
+        self.wake();
+        true
+    }
+
+    /// Highlight the element under the pointer while picking
+    pub fn track_pointer(&self, tree: &RenderTree, router: &EventRouter, x: f32, y: f32) {
+        let mut state = self.state.lock().unwrap();
+        if !state.picking {
+            return;
+        }
+        let hovered = recorder_bridge::element_at(tree, router, x, y);
+        if hovered != state.highlight {
+            state.highlight = hovered;
+            drop(state);
+            self.wake();
+        }
+    }
+
+    /// Apply edits, answer queries and capture a snapshot for this frame
+    ///
+    /// Called by the windowed runner after layout and before rendering.
+    pub fn process_frame(
+        &self,
+        tree: &mut RenderTree,
+        focused: Option<LayoutNodeId>,
+        hovered: &HashSet<LayoutNodeId>,
+        window_size: (u32, u32),
+        viewport: (f32, f32),
+    ) -> LiveFrame {
+        let mut state = self.state.lock().unwrap();
+        let mut full_repaint = std::mem::take(&mut state.edits_changed);
+
+        // Edits are reapplied every frame so they survive rebuilds
+        if !state.edits.is_empty() {
+            state.edits.apply(tree);
+        }
+
+        if !state.queries.is_empty() {
+            for (id, query) in std::mem::take(&mut state.queries) {
+                let answer = match query {
+                    Query::Style(element_id) => Answer::Style(
+                        recorder_bridge::find_node(tree, &element_id).and_then(|node| {
+                            Some(ComputedStyle {
+                                properties: recorder_bridge::computed_style(tree, node)?,
+                                sources: recorder_bridge::style_sources(tree, node, &state.edits)?,
+                                element_id,
+                            })
+                        }),
+                    ),
+                    Query::Layout(element_id) => Answer::Layout(
+                        recorder_bridge::find_node(tree, &element_id)
+                            .and_then(|node| recorder_bridge::layout_info(tree, node))
+                            .map(|info| to_layout_info(element_id, info)),
+                    ),
+                    Query::SetSignal(signal, value) => {
+                        Answer::SetSignal(self.apply_signal(signal, &value))
+                    }
+                };
+                state.answers.insert(id, answer);
+            }
+            self.answered.notify_all();
+        }
+
+        if state.live {
+            let data = recorder_bridge::capture_tree_snapshot(
+                tree,
+                focused,
+                hovered,
+                window_size.0,
+                window_size.1,
+            );
+            state.snapshot = Some(to_snapshot(
+                data,
+                Timestamp::from_duration(self.started.elapsed()),
+            ));
+        }
+
+        let highlighted = state
+            .highlight
+            .as_deref()
+            .and_then(|id| recorder_bridge::find_node(tree, id));
+        let bounds = highlighted.and_then(|node| recorder_bridge::absolute_bounds(tree, node));
+        let moved = match (&bounds, &state.drawn_highlight) {
+            (Some(a), Some(b)) => (a.x, a.y, a.width, a.height) != (b.x, b.y, b.width, b.height),
+            (None, None) => false,
+            _ => true,
+        };
+        // The highlight is drawn on top of the frame, so any frame that shows
+        // (or just stopped showing) it must be painted in full
+        full_repaint |= moved || bounds.is_some();
+        state.drawn_highlight = bounds.clone();
+
+        LiveFrame {
+            full_repaint,
+            highlight: bounds.map(|b| highlight_tree(&b, viewport, tree.scale_factor())),
+            highlighted,
+        }
+    }
+
+    /// Queue a query for the UI thread and wait for the answer
+    fn query(&self, query: Query) -> Option<Answer> {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_query;
+        state.next_query += 1;
+        state.queries.push((id, query));
+        drop(state);
+        self.wake();
+
+        let state = self.state.lock().unwrap();
+        let (mut state, _) = self
+            .answered
+            .wait_timeout_while(state, QUERY_TIMEOUT, |s| !s.answers.contains_key(&id))
+            .unwrap();
+        state.answers.remove(&id)
+    }
+}
+
+impl LiveTarget for LiveInspector {
+    fn snapshot(&self) -> Option<TreeSnapshot> {
+        self.state.lock().unwrap().snapshot.clone()
+    }
+
+    fn set_live(&self, enabled: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.live = enabled;
+        if !enabled {
+            state.snapshot = None;
+        }
+        drop(state);
+        self.wake();
+    }
+
+    fn highlight(&self, element_id: Option<&str>) {
+        self.state.lock().unwrap().highlight = element_id.map(str::to_string);
+        self.wake();
+    }
+
+    fn set_picking(&self, enabled: bool) {
+        self.state.lock().unwrap().picking = enabled;
+    }
+
+    fn take_picked(&self) -> Option<String> {
+        self.state.lock().unwrap().picked.take()
+    }
+
+    fn set_visual_prop(&self, element_id: &str, property: &str, value: &str) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        state.edits.set_visual_prop(element_id, property, value)?;
+        state.edits_changed = true;
+        drop(state);
+        self.wake();
+        Ok(())
+    }
+
+    fn set_css_rule(&self, selector: &str, declarations: &str) -> Result<(), String> {
+        let mut state = self.state.lock().unwrap();
+        state.edits.set_css_rule(selector, declarations)?;
+        state.edits_changed = true;
+        drop(state);
+        self.wake();
+        Ok(())
+    }
+
+    fn computed_style(&self, element_id: &str) -> Option<ComputedStyle> {
+        match self.query(Query::Style(element_id.to_string()))? {
+            Answer::Style(style) => style,
+            _ => None,
+        }
+    }
+
+    fn layout_info(&self, element_id: &str) -> Option<LayoutInfo> {
+        match self.query(Query::Layout(element_id.to_string()))? {
+            Answer::Layout(layout) => layout,
+            _ => None,
+        }
+    }
+
+    fn reactive_snapshot(&self) -> Option<ReactiveSnapshot> {
+        let (graph, _) = self.reactive.lock().unwrap().clone()?;
+        let snapshot = graph.lock().unwrap().snapshot();
+        Some(snapshot)
+    }
+
+    fn set_signal(&self, signal: u64, value: &str) -> Result<(), String> {
+        // Stateful refresh callbacks must run on the UI thread
+        match self.query(Query::SetSignal(signal, value.to_string())) {
+            Some(Answer::SetSignal(result)) => result,
+            _ => Err("Timed out waiting for the app".to_string()),
+        }
+    }
+
+    fn set_debug_overlays(&self, overlays: DebugOverlays) -> Result<(), String> {
+        debug_overlay::set_debug_overlays(debug_overlay::DebugOverlays {
+            box_model: overlays.box_model,
+            flex: overlays.flex,
+            overflow: overlays.overflow,
+            clips: overlays.clips,
+            layers: overlays.layers,
+            hit_regions: overlays.hit_regions,
+        });
+        self.wake();
+        Ok(())
+    }
+}
+
+/// Build the overlay tree that outlines the highlighted element
+fn highlight_tree(bounds: &SnapshotRect, viewport: (f32, f32), scale_factor: f32) -> RenderTree {
+    let ui = div()
+        .w(viewport.0)
+        .h(viewport.1)
+        .pointer_events_none()
+        .child(
+            div()
+                .absolute()
+                .left(bounds.x)
+                .top(bounds.y)
+                .w(bounds.width)
+                .h(bounds.height)
+                .bg(HIGHLIGHT_FILL)
+                .border(2.0, HIGHLIGHT_BORDER),
+        );
+    let mut tree = RenderTree::from_element(&ui);
+    tree.set_scale_factor(scale_factor);
+    tree.compute_layout(viewport.0, viewport.1);
+    tree
+}
+
+fn to_rect(r: SnapshotRect) -> Rect {
+    Rect::new(r.x, r.y, r.width, r.height)
+}
+
+fn to_edges([top, right, bottom, left]: [f32; 4]) -> Edges {
+    Edges::new(top, right, bottom, left)
+}
+
+fn to_layout_info(element_id: String, info: recorder_bridge::LayoutInfoData) -> LayoutInfo {
+    LayoutInfo {
+        element_id,
+        bounds: to_rect(info.bounds),
+        content_size: info.content_size,
+        padding: to_edges(info.padding),
+        border: to_edges(info.border),
+        margin: to_edges(info.margin),
+        style: info.style,
+    }
+}
+
+/// Convert the layout crate's snapshot into the recorder's format
+fn to_snapshot(data: TreeSnapshotData, timestamp: Timestamp) -> TreeSnapshot {
+    let mut snapshot = TreeSnapshot::new(timestamp, data.window_size, data.scale_factor);
+    snapshot.root_id = data.root_id;
+    snapshot.focused_element = data.focused_element;
+    snapshot.hovered_element = data.hovered_element;
+    snapshot.elements = data
+        .elements
+        .into_iter()
+        .map(|(id, e)| {
+            let element = ElementSnapshot {
+                id: e.id,
+                stable_id: e.stable_id,
+                element_type: e.element_type,
+                bounds: to_rect(e.bounds),
+                is_visible: e.is_visible,
+                is_focused: e.is_focused,
+                is_hovered: e.is_hovered,
+                is_interactive: e.is_interactive,
+                children: e.children,
+                parent: e.parent,
+                visual_props: e.visual_props.map(|p| VisualProps {
+                    background_color: p.background_color,
+                    border_color: p.border_color,
+                    border_width: p.border_width,
+                    border_radius: p.border_radius,
+                    opacity: p.opacity,
+                    ..Default::default()
+                }),
+                text_content: e.text_content,
+            };
+            (id, element)
+        })
+        .collect();
+    snapshot
+}
@@ -33,6 +33,7 @@ use junita_animation::{
     SharedAnimatedTimeline, SharedAnimatedValue, SpringConfig,
 };
 use junita_core::context_state::{HookState, JunitaContextState, SharedHookState, StateKey};
+use junita_core::profiler;
 use junita_core::reactive::{
     Derived, ReactiveGraph, Signal, SignalId, State, StatefulDepsCallback,
 };
@@ -429,7 +430,9 @@ impl WindowedContext {
             Signal::from_id(signal_id)
         } else {
             // First time - create a new signal and store it
-            let signal = self.reactive.lock().unwrap().create_signal(init());
+            let mut reactive = self.reactive.lock().unwrap();
+            let signal = reactive.create_signal(init());
+            reactive.label_signal(signal, key);
             let raw_id = signal.id().to_raw();
             hooks.insert(state_key, raw_id);
             signal
@@ -484,7 +487,9 @@ impl WindowedContext {
             Signal::from_id(signal_id)
         } else {
             // First time - create a new signal and store it
-            let signal = self.reactive.lock().unwrap().create_signal(init());
+            let mut reactive = self.reactive.lock().unwrap();
+            let signal = reactive.create_signal(init());
+            reactive.label_signal(signal, key);
             let raw_id = signal.id().to_raw();
             hooks.insert(state_key, raw_id);
             signal
@@ -1468,6 +1473,11 @@ impl WindowedApp {
         // Initialize the theme system with platform detection
         Self::init_theme();
 
+        // JUNITA_PROFILE=1 records frame timings from the first frame
+        if std::env::var_os("JUNITA_PROFILE").is_some_and(|v| v != "0") {
+            profiler::set_enabled(true);
+        }
+
         let platform = DesktopPlatform::new().map_err(|e| JunitaError::Platform(e.to_string()))?;
         let event_loop = platform
             .create_event_loop_with_config(config)
@@ -1476,6 +1486,14 @@ impl WindowedApp {
         // Get a wake proxy to allow the animation thread to wake up the event loop
         let wake_proxy = event_loop.wake_proxy();
 
+        // Let an attached debugger request frames (highlights, queries, edits)
+        #[cfg(feature = "recorder")]
+        {
+            let live_wake_proxy = wake_proxy.clone();
+            crate::live_inspect::LiveInspector::global()
+                .set_wake_callback(move || live_wake_proxy.wake());
+        }
+
         // We need to defer JunitaApp creation until we have a window
         let mut app: Option<JunitaApp> = None;
         let mut surface: Option<wgpu::Surface<'static>> = None;
@@ -1496,6 +1514,11 @@ impl WindowedApp {
         // Shared hook state for use_state persistence
         let hooks: SharedHookState = Arc::new(Mutex::new(HookState::new()));
 
+        // Let an attached debugger inspect signals and set their values
+        #[cfg(feature = "recorder")]
+        crate::live_inspect::LiveInspector::global()
+            .set_reactive(Arc::clone(&reactive), Arc::clone(&ref_dirty_flag));
+
         // Initialize global context state singleton (if not already initialized)
         // This allows components to create internal state without context parameters
         if !JunitaContextState::is_initialized() {
@@ -1573,6 +1596,14 @@ impl WindowedApp {
         // This includes cursor blink, animated colors, hover states, etc.
         let mut render_state: Option<junita_layout::RenderState> = None;
 
+        // Damage tracking for partial redraw. Only set when the surface can be
+        // a copy destination, since partial frames are rendered into a retained
+        // texture and copied to the surface.
+        let mut damage_tracker: Option<junita_layout::DamageTracker> = None;
+        // Whether layout debug overlays were drawn last frame, to repaint
+        // the window once after they are turned off
+        let mut debug_overlays_drawn = false;
+
         // Shared motion states for query API access
         // This allows components to query motion animation state via query_motion()
         let shared_motion_states = junita_layout::create_shared_motion_states();
@@ -1611,8 +1642,19 @@ impl WindowedApp {
                                     let (width, height) = window.size();
                                     // Use the same texture format that the renderer's pipelines use
                                     let format = junita_app.texture_format();
+                                    let partial_redraw = junita_app
+                                        .surface_usages(&surf)
+                                        .contains(wgpu::TextureUsages::COPY_DST);
+                                    let usage = if partial_redraw {
+                                        wgpu::TextureUsages::RENDER_ATTACHMENT
+                                            | wgpu::TextureUsages::COPY_DST
+                                    } else {
+                                        wgpu::TextureUsages::RENDER_ATTACHMENT
+                                    };
+                                    damage_tracker =
+                                        partial_redraw.then(junita_layout::DamageTracker::new);
                                     let config = wgpu::SurfaceConfiguration {
-                                        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+                                        usage,
                                         format,
                                         width,
                                         height,
@@ -1880,17 +1922,31 @@ impl WindowedApp {
                                             .get_cursor_at(router, lx, ly)
                                             .unwrap_or(CursorStyle::Default);
                                         window.set_cursor(convert_cursor_style(cursor));
+
+                                        // Show what a debugger pick would select
+                                        #[cfg(feature = "recorder")]
+                                        crate::live_inspect::LiveInspector::global()
+                                            .track_pointer(tree, router, lx, ly);
                                     }
                                     MouseEvent::ButtonPressed { button, x, y } => {
                                         let lx = x / scale;
                                         let ly = y / scale;
                                         let btn = convert_mouse_button(button);
 
+                                        // While the debugger is picking, the click selects an
+                                        // element instead of reaching the app
+                                        #[cfg(feature = "recorder")]
+                                        let picked = crate::live_inspect::LiveInspector::global()
+                                            .try_pick(tree, router, lx, ly);
+                                        #[cfg(not(feature = "recorder"))]
+                                        let picked = false;
+
                                         // Check for backdrop clicks (dismisses overlays)
                                         // This still needs special handling because backdrop clicks should
                                         // not propagate to elements behind the overlay
-                                        let overlay_dismissed = if windowed_ctx.overlay_manager.has_blocking_overlay()
-                                            || windowed_ctx.overlay_manager.has_dismissable_overlay()
+                                        let overlay_dismissed = if !picked
+                                            && (windowed_ctx.overlay_manager.has_blocking_overlay()
+                                                || windowed_ctx.overlay_manager.has_dismissable_overlay())
                                         {
                                             windowed_ctx.overlay_manager.handle_click_at(lx, ly)
                                         } else {
@@ -1898,7 +1954,7 @@ impl WindowedApp {
                                         };
 
                                         // If overlay was dismissed by backdrop click, don't process further
-                                        if !overlay_dismissed {
+                                        if !overlay_dismissed && !picked {
                                             // Blur any focused text inputs BEFORE processing mouse down
                                             // This mimics HTML behavior where clicking anywhere blurs inputs,
                                             // and clicking on an input then re-focuses it via its own handler
@@ -2055,6 +2111,18 @@ impl WindowedApp {
                                     };
 
                                     match kb_event.state {
+                                        // Ctrl/Cmd+Shift+L toggles the layout debug overlays
+                                        // instead of reaching the app
+                                        KeyState::Pressed
+                                            if kb_event.key == Key::L
+                                                && mods.shift
+                                                && (mods.ctrl || mods.meta) =>
+                                        {
+                                            let overlays =
+                                                junita_layout::debug_overlay::toggle_debug_overlays();
+                                            tracing::debug!("Layout debug overlays: {:?}", overlays);
+                                            window.request_redraw();
+                                        }
                                         KeyState::Pressed => {
                                             // Handle Escape key for overlays first
                                             // If an overlay handles it, don't propagate further
@@ -2362,26 +2430,9 @@ impl WindowedApp {
                             Some(ref mut rs),
                         ) = (&mut app, &surface, &surface_config, &mut ctx, &mut render_state)
                         {
-                            // Get current frame
-                            let frame = match surf.get_current_texture() {
-                                Ok(f) => f,
-                                Err(wgpu::SurfaceError::Lost) => {
-                                    surf.configure(&junita_app.device(), config);
-                                    return ControlFlow::Continue;
-                                }
-                                Err(wgpu::SurfaceError::OutOfMemory) => {
-                                    tracing::error!("Out of GPU memory");
-                                    return ControlFlow::Exit;
-                                }
-                                Err(e) => {
-                                    tracing::warn!("Surface error: {:?}", e);
-                                    return ControlFlow::Continue;
-                                }
-                            };
-
-                            let view = frame
-                                .texture
-                                .create_view(&wgpu::TextureViewDescriptor::default());
+                            profiler::begin_frame();
+                            // Signal writes and effect runs are attributed to this frame
+                            reactive.lock().unwrap().advance_frame();
 
                             // Update context from window
                             windowed_ctx.update_from_window(window);
@@ -2390,6 +2441,14 @@ impl WindowedApp {
                             // Uses logical pixels (width/height) as that's what layout uses
                             rs.set_viewport_size(windowed_ctx.width, windowed_ctx.height);
 
+                            // Under a virtual clock (replays, frame-by-frame capture)
+                            // time only moves once per frame, and animations are
+                            // stepped here rather than on the background thread
+                            if !junita_core::clock::is_realtime() {
+                                junita_core::clock::begin_frame();
+                                windowed_ctx.animations.lock().unwrap().step_frame();
+                            }
+
                             // Get current time for animation updates (used in multiple phases)
                             let current_time = elapsed_ms();
 
@@ -2549,6 +2608,8 @@ impl WindowedApp {
                             rs.begin_stable_motion_frame();
 
                             if needs_rebuild || render_tree.is_none() {
+                                let _rebuild_scope = profiler::scope("rebuild");
+
                                 // Reset call counters for stable key generation
                                 reset_call_counters();
 
@@ -2560,7 +2621,10 @@ impl WindowedApp {
                                 // so build_overlay_layer() has correct dimensions
 
                                 // Build UI element tree
-                                let user_ui = ui_builder(windowed_ctx);
+                                let user_ui = {
+                                    let _scope = profiler::scope("build_ui");
+                                    ui_builder(windowed_ctx)
+                                };
 
                                 // Compose user UI with overlay layer using a regular Div container
                                 // We use position:relative with the overlay absolutely positioned on top.
@@ -2706,6 +2770,8 @@ impl WindowedApp {
                             // This must happen AFTER tree rebuild so motions are initialized
                             // =========================================================
 
+                            let animations_scope = profiler::scope("animations");
+
                             // Process any pending motion exit cancellations
                             // This must happen before tick() so cancelled motions don't continue exiting
                             rs.process_global_motion_exit_cancels();
@@ -2726,29 +2792,209 @@ impl WindowedApp {
                             // Tick theme animation (handles color interpolation during theme transitions)
                             let theme_animating = junita_theme::ThemeState::get().tick();
 
+                            drop(animations_scope);
+
                             // Note: scroll physics tick moved to before PHASE 1 (before any rebuilds)
                             // so that ScrollRef has up-to-date values when stateful components rebuild
 
+                            // Let an attached debugger edit styles, answer queries
+                            // and capture the tree before this frame is drawn
+                            #[cfg(feature = "recorder")]
+                            let live_frame = render_tree.as_mut().map(|tree| {
+                                let hovered = windowed_ctx.event_router.hovered_nodes().collect();
+                                crate::live_inspect::LiveInspector::global().process_frame(
+                                    tree,
+                                    windowed_ctx.event_router.focused(),
+                                    &hovered,
+                                    (
+                                        windowed_ctx.physical_width as u32,
+                                        windowed_ctx.physical_height as u32,
+                                    ),
+                                    (windowed_ctx.width, windowed_ctx.height),
+                                )
+                            });
+
+                            // Layout debug overlays, toggled in-app or from the debugger.
+                            // The box model follows the debugger's highlight, or else
+                            // the element under the pointer.
+                            let debug_overlays = junita_layout::debug_overlay::debug_overlays();
+                            let debug_overlay_tree = match render_tree.as_ref() {
+                                Some(tree) if !debug_overlays.is_empty() => {
+                                    #[cfg(feature = "recorder")]
+                                    let highlighted = live_frame.as_ref().and_then(|f| f.highlighted);
+                                    #[cfg(not(feature = "recorder"))]
+                                    let highlighted = None;
+                                    let inspected = highlighted.or_else(|| {
+                                        let (mx, my) = windowed_ctx.event_router.mouse_position();
+                                        windowed_ctx
+                                            .event_router
+                                            .hit_test(tree, mx, my)
+                                            .map(|hit| hit.node)
+                                    });
+                                    let shapes = junita_layout::debug_overlay::debug_shapes(
+                                        tree,
+                                        debug_overlays,
+                                        inspected,
+                                    );
+                                    Some(junita_layout::debug_overlay::debug_overlay_tree(
+                                        &shapes,
+                                        (windowed_ctx.width, windowed_ctx.height),
+                                        tree.scale_factor(),
+                                    ))
+                                }
+                                _ => None,
+                            };
+                            // Overlays are drawn on top of the frame, so frames that show
+                            // (or just stopped showing) them are painted in full
+                            let debug_overlays_repaint =
+                                debug_overlays_drawn || debug_overlay_tree.is_some();
+                            debug_overlays_drawn = debug_overlay_tree.is_some();
+
                             // =========================================================
                             // PHASE 4: Render
                             // Combines stable tree structure with dynamic render state
                             // =========================================================
 
-                            if let Some(ref tree) = render_tree {
-                                // Render with motion animations
-                                // Use physical pixel dimensions for the render surface
-                                let result = junita_app.render_tree_with_motion(
-                                    tree,
-                                    rs,
-                                    &view,
-                                    windowed_ctx.physical_width as u32,
-                                    windowed_ctx.physical_height as u32,
-                                );
-                                if let Err(e) = result {
-                                    tracing::error!("Render error: {}", e);
+                            let render_scope = profiler::scope("render");
+
+                            // Damage is known before the surface texture is acquired:
+                            // wgpu can't hand damage rectangles to the compositor, so
+                            // a frame with nothing to repaint is not presented at all
+                            // and the window keeps showing the previous one
+                            let damage = match (&render_tree, &mut damage_tracker) {
+                                (Some(tree), Some(tracker)) => {
+                                    // Theme transitions recolour everything at once
+                                    if theme_animating || debug_overlays_repaint {
+                                        tracker.invalidate_all();
+                                    }
+                                    #[cfg(feature = "recorder")]
+                                    if live_frame.as_ref().is_some_and(|f| f.full_repaint) {
+                                        tracker.invalidate_all();
+                                    }
+                                    let damage = tracker.compute(
+                                        tree,
+                                        rs,
+                                        (windowed_ctx.width, windowed_ctx.height),
+                                    );
+
+                                    #[cfg(feature = "recorder")]
+                                    if junita_layout::recorder_bridge::is_recording_snapshots() {
+                                        let hovered = windowed_ctx.event_router.hovered_nodes().collect();
+                                        let snapshot = junita_layout::recorder_bridge::capture_tree_snapshot(
+                                            tree,
+                                            windowed_ctx.event_router.focused(),
+                                            &hovered,
+                                            windowed_ctx.physical_width as u32,
+                                            windowed_ctx.physical_height as u32,
+                                        )
+                                        .with_damage(&damage, (windowed_ctx.width, windowed_ctx.height));
+                                        junita_layout::recorder_bridge::record_snapshot(snapshot);
+                                    }
+
+                                    let damage = junita_app.frame_damage(
+                                        tree,
+                                        &damage,
+                                        windowed_ctx.physical_width as u32,
+                                        windowed_ctx.physical_height as u32,
+                                    );
+                                    tracing::trace!(
+                                        "Frame damage: full={}, rects={}",
+                                        damage.is_full(),
+                                        damage.rects().len()
+                                    );
+                                    Some(damage)
+                                }
+                                _ => None,
+                            };
+                            #[cfg(feature = "recorder")]
+                            let highlighting = live_frame.as_ref().is_some_and(|f| f.highlight.is_some());
+                            #[cfg(not(feature = "recorder"))]
+                            let highlighting = false;
+                            let unchanged = damage.as_ref().is_some_and(|d| d.is_empty())
+                                && debug_overlay_tree.is_none()
+                                && !highlighting;
+
+                            let frame = if unchanged {
+                                None
+                            } else {
+                                let _scope = profiler::scope("acquire");
+                                match surf.get_current_texture() {
+                                    Ok(f) => Some(f),
+                                    Err(wgpu::SurfaceError::Lost) => {
+                                        surf.configure(junita_app.device(), config);
+                                        return ControlFlow::Continue;
+                                    }
+                                    Err(wgpu::SurfaceError::OutOfMemory) => {
+                                        tracing::error!("Out of GPU memory");
+                                        return ControlFlow::Exit;
+                                    }
+                                    Err(e) => {
+                                        tracing::warn!("Surface error: {:?}", e);
+                                        return ControlFlow::Continue;
+                                    }
+                                }
+                            };
+
+                            if let Some(ref frame) = frame {
+                                let view = frame
+                                    .texture
+                                    .create_view(&wgpu::TextureViewDescriptor::default());
+
+                                if let Some(ref tree) = render_tree {
+                                    // Render with motion animations
+                                    // Use physical pixel dimensions for the render surface
+                                    let result = if let Some(ref damage) = damage {
+                                        junita_app.render_tree_with_damage(
+                                            tree,
+                                            rs,
+                                            damage,
+                                            &frame.texture,
+                                            windowed_ctx.physical_width as u32,
+                                            windowed_ctx.physical_height as u32,
+                                        )
+                                    } else {
+                                        junita_app.render_tree_with_motion(
+                                            tree,
+                                            rs,
+                                            &view,
+                                            windowed_ctx.physical_width as u32,
+                                            windowed_ctx.physical_height as u32,
+                                        )
+                                    };
+                                    if let Err(e) = result {
+                                        tracing::error!("Render error: {}", e);
+                                    }
+                                }
+
+                                // Draw the debugger's element highlight on top
+                                #[cfg(feature = "recorder")]
+                                if let Some(overlay) = live_frame.and_then(|f| f.highlight) {
+                                    if let Err(e) = junita_app.render_overlay_tree_with_motion(
+                                        &overlay,
+                                        rs,
+                                        &view,
+                                        windowed_ctx.physical_width as u32,
+                                        windowed_ctx.physical_height as u32,
+                                    ) {
+                                        tracing::error!("Highlight render error: {}", e);
+                                    }
+                                }
+
+                                if let Some(ref overlay) = debug_overlay_tree {
+                                    if let Err(e) = junita_app.render_overlay_tree_with_motion(
+                                        overlay,
+                                        rs,
+                                        &view,
+                                        windowed_ctx.physical_width as u32,
+                                        windowed_ctx.physical_height as u32,
+                                    ) {
+                                        tracing::error!("Debug overlay render error: {}", e);
+                                    }
                                 }
                             }
 
+                            drop(render_scope);
+
                             // =========================================================
                             // PHASE 4b: Overlay state management (overlays now in main tree)
                             // Overlays are composed into the main tree via build_overlay_layer()
@@ -2763,7 +3009,13 @@ impl WindowedApp {
                             let has_visible_overlays = windowed_ctx.overlay_manager.has_visible_overlays();
                             windowed_ctx.had_visible_overlays = has_visible_overlays;
 
-                            frame.present();
+                            {
+                                let _scope = profiler::scope("present");
+                                if let Some(frame) = frame {
+                                    frame.present();
+                                }
+                            }
+                            profiler::end_frame();
 
                             // =========================================================
                             // PHASE 5: Request next frame if animations are active
@@ -0,0 +1,383 @@
+//! What the tooling knows about without reading the user's code
+//!
+//! Built-in elements, props and events mirror what
+//! `junita_interpreter::elements` renders; theme tokens are read from the
+//! `junita_theme` token enums. Props are listed in the order `junita fmt`
+//! sorts them into.
+
+use std::sync::OnceLock;
+
+use junita_theme::tokens::{
+    AnimationToken, ColorToken, RadiusToken, ShadowToken, SpacingToken, TypographyToken,
+};
+
+/// A built-in element
+pub struct Element {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub doc: &'static str,
+}
+
+/// A prop or event every built-in element accepts
+pub struct Prop {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub doc: &'static str,
+}
+
+pub const ELEMENTS: &[Element] = &[
+    Element {
+        name: "Column",
+        aliases: &["VStack"],
+        doc: "Lays out its children top to bottom.",
+    },
+    Element {
+        name: "Row",
+        aliases: &["HStack"],
+        doc: "Lays out its children left to right.",
+    },
+    Element {
+        name: "Center",
+        aliases: &[],
+        doc: "Fills its parent and centers its children on both axes.",
+    },
+    Element {
+        name: "Window",
+        aliases: &["Screen"],
+        doc: "Full-size column used as the root of a screen.",
+    },
+    Element {
+        name: "Text",
+        aliases: &["Label"],
+        doc: "Displays `content`. Interpolate state with `\"{name}\"`.",
+    },
+    Element {
+        name: "Button",
+        aliases: &[],
+        doc: "Clickable, padded box showing `label`. Handle presses with `on_click`.",
+    },
+    Element {
+        name: "Spacer",
+        aliases: &[],
+        doc: "Grows to take up the free space in a row or column.",
+    },
+    Element {
+        name: "Box",
+        aliases: &["Container", "Stack"],
+        doc: "Plain container with no layout of its own.",
+    },
+];
+
+pub const PROPS: &[Prop] = &[
+    Prop {
+        name: "content",
+        aliases: &["text", "label"],
+        doc: "Text shown by the element.",
+    },
+    Prop {
+        name: "font_size",
+        aliases: &["size"],
+        doc: "Font size in pixels.",
+    },
+    Prop {
+        name: "color",
+        aliases: &["text_color"],
+        doc: "Text color.",
+    },
+    Prop {
+        name: "font_weight",
+        aliases: &["weight"],
+        doc: "`thin`, `light`, `normal`, `medium`, `semibold`, `bold` or `black`.",
+    },
+    Prop {
+        name: "opacity",
+        aliases: &[],
+        doc: "Opacity from `0.0` to `1.0`.",
+    },
+    Prop {
+        name: "width",
+        aliases: &["w"],
+        doc: "Width in pixels.",
+    },
+    Prop {
+        name: "height",
+        aliases: &["h"],
+        doc: "Height in pixels.",
+    },
+    Prop {
+        name: "min_width",
+        aliases: &[],
+        doc: "Minimum width in pixels.",
+    },
+    Prop {
+        name: "min_height",
+        aliases: &[],
+        doc: "Minimum height in pixels.",
+    },
+    Prop {
+        name: "spacing",
+        aliases: &["gap"],
+        doc: "Space between children, in pixels.",
+    },
+    Prop {
+        name: "padding",
+        aliases: &[],
+        doc: "Padding on all sides, in pixels.",
+    },
+    Prop {
+        name: "padding_x",
+        aliases: &[],
+        doc: "Left and right padding, in pixels.",
+    },
+    Prop {
+        name: "padding_y",
+        aliases: &[],
+        doc: "Top and bottom padding, in pixels.",
+    },
+    Prop {
+        name: "margin",
+        aliases: &[],
+        doc: "Margin on all sides, in pixels.",
+    },
+    Prop {
+        name: "margin_top",
+        aliases: &[],
+        doc: "Top margin.",
+    },
+    Prop {
+        name: "margin_bottom",
+        aliases: &[],
+        doc: "Bottom margin.",
+    },
+    Prop {
+        name: "margin_left",
+        aliases: &[],
+        doc: "Left margin.",
+    },
+    Prop {
+        name: "margin_right",
+        aliases: &[],
+        doc: "Right margin.",
+    },
+    Prop {
+        name: "grow",
+        aliases: &["flex"],
+        doc: "Flex grow factor.",
+    },
+    Prop {
+        name: "direction",
+        aliases: &[],
+        doc: "`row` or `column`.",
+    },
+    Prop {
+        name: "align",
+        aliases: &[],
+        doc: "Cross-axis alignment: `start`, `center`, `end` or `stretch`.",
+    },
+    Prop {
+        name: "justify",
+        aliases: &[],
+        doc: "Main-axis distribution: `start`, `center`, `end`, `between`, `around` or `evenly`.",
+    },
+    Prop {
+        name: "background",
+        aliases: &["bg"],
+        doc: "Background color.",
+    },
+    Prop {
+        name: "border_radius",
+        aliases: &["radius", "rounded"],
+        doc: "Corner radius in pixels.",
+    },
+    Prop {
+        name: "border_color",
+        aliases: &[],
+        doc: "Border color.",
+    },
+    Prop {
+        name: "border_width",
+        aliases: &[],
+        doc: "Border width in pixels.",
+    },
+];
+
+pub const EVENTS: &[Prop] = &[
+    Prop {
+        name: "on_click",
+        aliases: &["on_press", "on_tap"],
+        doc: "Runs when the element is clicked.",
+    },
+    Prop {
+        name: "on_mouse_down",
+        aliases: &["on_press_start"],
+        doc: "Runs when a button is pressed over the element.",
+    },
+    Prop {
+        name: "on_mouse_up",
+        aliases: &["on_press_end"],
+        doc: "Runs when a button is released over the element.",
+    },
+    Prop {
+        name: "on_hover_enter",
+        aliases: &["on_hover", "on_pointer_enter"],
+        doc: "Runs when the pointer enters the element.",
+    },
+    Prop {
+        name: "on_hover_leave",
+        aliases: &["on_pointer_leave"],
+        doc: "Runs when the pointer leaves the element.",
+    },
+];
+
+/// Documentation for each decorator
+pub const DECORATORS: &[(&str, &str)] = &[
+    (
+        "@widget",
+        "Declares a widget: its props, state and render tree.",
+    ),
+    (
+        "@prop",
+        "A value passed in by the parent: `@prop label: String = \"\"`.",
+    ),
+    (
+        "@state",
+        "Reactive state owned by the widget: `@state count: i32 = 0`.",
+    ),
+    (
+        "@derived",
+        "A value computed from other members: `@derived doubled: i32 = count * 2`.",
+    ),
+    (
+        "@machine",
+        "A state machine: `initial: idle` and transitions like `idle -> active: pointer_enter`.",
+    ),
+    (
+        "@animation",
+        "A keyframe animation with `duration`, `easing` and keyframes.",
+    ),
+    (
+        "@spring",
+        "A spring-animated value with `stiffness`, `damping` and `mass`.",
+    ),
+    (
+        "@effect",
+        "Side effects that run when the values they read change.",
+    ),
+    ("@render", "The widget's element tree."),
+    ("@paint", "Custom drawing on the widget's canvas."),
+    (
+        "@action",
+        "An event handler: `on_click: @action { count += 1 }`.",
+    ),
+];
+
+/// Font family fields of `TypographyTokens`, which have no `TypographyToken`
+const FONT_FAMILIES: &[&str] = &["font_sans", "font_serif", "font_mono"];
+
+/// Easing fields of `AnimationTokens`, which have no `AnimationToken`
+const EASINGS: &[&str] = &["ease_default", "ease_in", "ease_out", "ease_in_out"];
+
+/// Theme token groups, read as `theme.<token>`
+pub fn theme_tokens() -> &'static [(&'static str, Vec<&'static str>)] {
+    static TOKENS: OnceLock<Vec<(&'static str, Vec<&'static str>)>> = OnceLock::new();
+    TOKENS.get_or_init(|| {
+        let typography = TypographyToken::ALL.iter().map(|t| t.name());
+        let animation = AnimationToken::ALL.iter().map(|t| t.name());
+        vec![
+            ("color", ColorToken::ALL.iter().map(|t| t.name()).collect()),
+            (
+                "spacing",
+                SpacingToken::ALL.iter().map(|t| t.name()).collect(),
+            ),
+            (
+                "radius",
+                RadiusToken::ALL.iter().map(|t| t.name()).collect(),
+            ),
+            (
+                "typography",
+                FONT_FAMILIES.iter().copied().chain(typography).collect(),
+            ),
+            (
+                "shadow",
+                ShadowToken::ALL.iter().map(|t| t.name()).collect(),
+            ),
+            (
+                "animation",
+                animation.chain(EASINGS.iter().copied()).collect(),
+            ),
+        ]
+    })
+}
+
+/// Built-in element called `name`, aliases included
+pub fn element(name: &str) -> Option<&'static Element> {
+    ELEMENTS
+        .iter()
+        .find(|e| e.name == name || e.aliases.contains(&name))
+}
+
+/// Built-in prop or event called `name`, aliases included
+pub fn prop(name: &str) -> Option<&'static Prop> {
+    PROPS
+        .iter()
+        .chain(EVENTS)
+        .find(|p| p.name == name || p.aliases.contains(&name))
+}
+
+pub fn decorator(name: &str) -> Option<&'static str> {
+    DECORATORS
+        .iter()
+        .find(|(d, _)| *d == name)
+        .map(|(_, doc)| *doc)
+}
+
+/// Group of the theme token called `name`
+pub fn theme_token(name: &str) -> Option<&'static str> {
+    theme_tokens()
+        .iter()
+        .find(|(_, tokens)| tokens.contains(&name))
+        .map(|(group, _)| *group)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_theme_tokens_cover_token_enums() {
+        let groups: [(&str, Vec<&str>); 6] = [
+            ("color", ColorToken::ALL.iter().map(|t| t.name()).collect()),
+            (
+                "spacing",
+                SpacingToken::ALL.iter().map(|t| t.name()).collect(),
+            ),
+            (
+                "typography",
+                TypographyToken::ALL.iter().map(|t| t.name()).collect(),
+            ),
+            (
+                "shadow",
+                ShadowToken::ALL.iter().map(|t| t.name()).collect(),
+            ),
+            (
+                "radius",
+                RadiusToken::ALL.iter().map(|t| t.name()).collect(),
+            ),
+            (
+                "animation",
+                AnimationToken::ALL.iter().map(|t| t.name()).collect(),
+            ),
+        ];
+        for (group, names) in groups {
+            for name in names {
+                assert_eq!(theme_token(name), Some(group), "theme.{}", name);
+            }
+        }
+
+        assert_eq!(theme_token("text_primary"), Some("color"));
+        assert_eq!(theme_token("radius_2xl"), Some("radius"));
+        assert_eq!(theme_token("font_mono"), Some("typography"));
+        assert_eq!(theme_token("ease_in_out"), Some("animation"));
+        assert_eq!(theme_token("primary_color"), None);
+    }
+}
@@ -3,105 +3,22 @@
 //! Parses .junita/.bl files and generates compilation artifacts.
 //! This is a working implementation of the Junita grammar, ready to be
 //! upgraded to use the full Zyntax system when Grammar2 is available.
+//!
+//! Parsing is done by [`crate::syntax`]; this module lowers the syntax tree
+//! into a [`CompiledArtifact`].
 
 use anyhow::{anyhow, Result};
-use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::fs;
 use tracing::{info, debug, warn};
-use regex::Regex;
-
-/// Compiled artifact from Junita compiler
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct CompiledArtifact {
-    /// Source file that was compiled
-    pub source_file: PathBuf,
-    /// Compiled widget definitions
-    pub widgets: Vec<WidgetDefinition>,
-    /// State machines defined in the file
-    pub machines: Vec<MachineDef>,
-    /// Animations defined in the file
-    pub animations: Vec<AnimationDef>,
-    /// Springs defined in the file
-    pub springs: Vec<SpringDef>,
-    /// Timestamp of compilation
-    pub timestamp: u64,
-    /// Checksum for detecting changes
-    pub checksum: String,
-}
-
-/// Parsed widget definition from .junita file
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct WidgetDefinition {
-    pub name: String,
-    pub properties: Vec<PropDef>,
-    pub state_vars: Vec<StateVar>,
-    pub derived_vars: Vec<DerivedVar>,
-    pub machines: Vec<String>,
-    pub animations: Vec<String>,
-    pub springs: Vec<String>,
-    pub render_body: Option<String>,
-    pub paint_body: Option<String>,
-}
-
-/// Property definition
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PropDef {
-    pub name: String,
-    pub prop_type: String,
-    pub default_value: Option<String>,
-}
-
-/// State variable definition
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct StateVar {
-    pub name: String,
-    pub var_type: String,
-    pub initial_value: String,
-}
-
-/// Derived value definition
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DerivedVar {
-    pub name: String,
-    pub var_type: String,
-    pub expression: String,
-    pub dependencies: Vec<String>,
-}
-
-/// State machine definition
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct MachineDef {
-    pub name: String,
-    pub states: Vec<String>,
-    pub initial_state: String,
-    pub transitions: Vec<Transition>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Transition {
-    pub from: String,
-    pub to: String,
-    pub event: String,
-}
 
-/// Animation definition
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct AnimationDef {
-    pub name: String,
-    pub duration_ms: u32,
-    pub easing: String,
-}
+use crate::syntax::{self, Diagnostic, SyntaxKind, SyntaxNode};
 
-/// Spring animation definition
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SpringDef {
-    pub name: String,
-    pub stiffness: f32,
-    pub damping: f32,
-    pub mass: f32,
-}
+pub use junita_interpreter::artifact::{
+    AnimationDef, CompiledArtifact, DerivedVar, MachineDef, PropDef, SpringDef, StateVar,
+    Transition, WidgetDefinition,
+};
 
 /// Junita DSL Compiler with real parsing
 pub struct JunitaCompiler {
@@ -142,7 +59,18 @@ impl JunitaCompiler {
         }
 
         // Parse with real Junita DSL parser
-        let artifact = self.parse_junita(&source, source_path)?;
+        let (artifact, diagnostics) = self.compile_source(&source, source_path);
+        let name = source_path.display().to_string();
+        if diagnostics.iter().any(Diagnostic::is_error) {
+            return Err(anyhow!(
+                "Failed to compile {}:\n{}",
+                name,
+                syntax::render(&name, &source, &diagnostics)
+            ));
+        }
+        if !diagnostics.is_empty() {
+            warn!("{}", syntax::render(&name, &source, &diagnostics));
+        }
 
         // Cache the result
         self.cache.insert(source_path.to_path_buf(), artifact.clone());
@@ -163,62 +91,39 @@ impl JunitaCompiler {
         Ok(artifacts)
     }
 
-    /// Real Junita DSL parser
-    fn parse_junita(&self, source: &str, source_path: &Path) -> Result<CompiledArtifact> {
+    /// Compile source text, returning the artifact and any diagnostics
+    ///
+    /// The artifact is lowered from whatever parsed, so tooling can use it
+    /// even when the source has errors.
+    pub fn compile_source(
+        &self,
+        source: &str,
+        source_path: &Path,
+    ) -> (CompiledArtifact, Vec<Diagnostic>) {
+        let parse = syntax::parse(source);
+        let mut diagnostics = parse.diagnostics;
+
         let mut widgets = Vec::new();
         let mut machines = Vec::new();
         let mut animations = Vec::new();
         let mut springs = Vec::new();
 
-        // Token-based parser for Junita DSL
-        let tokens = self.tokenize(source)?;
-        let mut pos = 0;
-
-        while pos < tokens.len() {
-            let token = &tokens[pos];
-            
-            match token.as_str() {
-                "@widget" => {
-                    if let Ok((widget, new_pos)) = self.parse_widget(&tokens, pos) {
+        for node in parse.root.nodes() {
+            match node.kind {
+                SyntaxKind::WIDGET => {
+                    let nested = (&mut machines, &mut animations, &mut springs);
+                    if let Some(widget) = Self::lower_widget(node, source, nested, &mut diagnostics) {
                         widgets.push(widget);
-                        pos = new_pos;
-                    } else {
-                        pos += 1;
-                    }
-                }
-                "@machine" => {
-                    if let Ok((machine, new_pos)) = self.parse_machine(&tokens, pos) {
-                        machines.push(machine);
-                        pos = new_pos;
-                    } else {
-                        pos += 1;
                     }
                 }
-                "@animation" => {
-                    if let Ok((anim, new_pos)) = self.parse_animation(&tokens, pos) {
-                        animations.push(anim);
-                        pos = new_pos;
-                    } else {
-                        pos += 1;
-                    }
-                }
-                "@spring" => {
-                    if let Ok((spring, new_pos)) = self.parse_spring(&tokens, pos) {
-                        springs.push(spring);
-                        pos = new_pos;
-                    } else {
-                        pos += 1;
-                    }
-                }
-                _ => {
-                    pos += 1;
-                }
+                SyntaxKind::MACHINE => machines.extend(Self::lower_machine(node)),
+                SyntaxKind::ANIMATION => animations.extend(Self::lower_animation(node)),
+                SyntaxKind::SPRING => springs.extend(Self::lower_spring(node)),
+                _ => {}
             }
         }
 
-        let checksum = Self::file_checksum(source_path)?;
-
-        Ok(CompiledArtifact {
+        let artifact = CompiledArtifact {
             source_file: source_path.to_path_buf(),
             widgets,
             machines,
@@ -228,469 +133,265 @@ impl JunitaCompiler {
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap_or_default()
                 .as_secs(),
-            checksum,
-        })
+            checksum: Self::checksum(source),
+        };
+        (artifact, diagnostics)
     }
 
-    /// Tokenize Junita source
-    fn tokenize(&self, source: &str) -> Result<Vec<String>> {
-        // Simple tokenizer that splits on whitespace and special characters
-        let re = Regex::new(r"(@\w+|[{}\[\](),=:]|\w+|[^\s])")?;
-        
-        let tokens: Vec<String> = re
-            .find_iter(source)
-            .map(|m| m.as_str().to_string())
-            .filter(|t| !t.is_empty() && !t.chars().all(char::is_whitespace))
-            .collect();
-
-        Ok(tokens)
-    }
-
-    /// Parse @widget declaration
-    fn parse_widget(&self, tokens: &[String], start: usize) -> Result<(WidgetDefinition, usize)> {
-        if tokens[start] != "@widget" {
-            return Err(anyhow!("Expected @widget"));
-        }
-
-        let name = tokens.get(start + 1)
-            .ok_or_else(|| anyhow!("Expected widget name"))?
-            .clone();
-
-        // Find opening brace
-        let mut brace_pos = start + 2;
-        while brace_pos < tokens.len() && tokens[brace_pos] != "{" {
-            brace_pos += 1;
-        }
-
-        let mut properties = Vec::new();
-        let mut state_vars = Vec::new();
-        let mut derived_vars = Vec::new();
-        let mut machines = Vec::new();
-        let mut animations = Vec::new();
-        let mut springs = Vec::new();
-        let mut render_body = None;
-        let mut paint_body = None;
-
-        // Parse widget body
-        let mut pos = brace_pos + 1;
-        let mut depth = 1;
-
-        while pos < tokens.len() && depth > 0 {
-            match tokens[pos].as_str() {
-                "{" => depth += 1,
-                "}" => {
-                    depth -= 1;
-                    if depth == 0 {
-                        break;
-                    }
-                }
-                "@prop" => {
-                    if let Ok((prop, new_pos)) = self.parse_prop(&tokens, pos) {
-                        properties.push(prop);
-                        pos = new_pos;
-                        continue;
-                    }
-                }
-                "@state" => {
-                    if let Ok((state, new_pos)) = self.parse_state(&tokens, pos) {
-                        state_vars.push(state);
-                        pos = new_pos;
-                        continue;
-                    }
-                }
-                "@derived" => {
-                    if let Ok((derived, new_pos)) = self.parse_derived(&tokens, pos) {
-                        derived_vars.push(derived);
-                        pos = new_pos;
-                        continue;
+    /// Lower a `@widget` node
+    ///
+    /// Machines, animations and springs declared inside the widget are added
+    /// to the artifact-level lists in `nested`.
+    fn lower_widget(
+        node: &SyntaxNode,
+        source: &str,
+        nested: (&mut Vec<MachineDef>, &mut Vec<AnimationDef>, &mut Vec<SpringDef>),
+        diagnostics: &mut Vec<Diagnostic>,
+    ) -> Option<WidgetDefinition> {
+        let (nested_machines, nested_animations, nested_springs) = nested;
+        let mut widget = WidgetDefinition {
+            name: node.name()?.text.clone(),
+            properties: Vec::new(),
+            state_vars: Vec::new(),
+            derived_vars: Vec::new(),
+            machines: Vec::new(),
+            animations: Vec::new(),
+            springs: Vec::new(),
+            render_body: None,
+            paint_body: None,
+        };
+
+        for member in node.nodes() {
+            let name = member.name().map(|n| n.text.clone());
+            let var_type = member.node(SyntaxKind::TYPE).map(SyntaxNode::text);
+            let value = member.expr().map(SyntaxNode::text);
+
+            match member.kind {
+                SyntaxKind::PROP_DECL => widget.properties.push(PropDef {
+                    name: name?,
+                    prop_type: var_type.unwrap_or_default(),
+                    default_value: value,
+                }),
+                SyntaxKind::STATE_DECL => widget.state_vars.push(StateVar {
+                    name: name?,
+                    var_type: var_type.unwrap_or_default(),
+                    initial_value: value.unwrap_or_default(),
+                }),
+                SyntaxKind::DERIVED_DECL => widget.derived_vars.push(DerivedVar {
+                    name: name?,
+                    var_type: var_type.unwrap_or_default(),
+                    expression: value.unwrap_or_default(),
+                    dependencies: member.expr().map(Self::dependencies).unwrap_or_default(),
+                }),
+                SyntaxKind::MACHINE => {
+                    if let Some(machine) = Self::lower_machine(member) {
+                        widget.machines.push(machine.name.clone());
+                        nested_machines.push(machine);
                     }
                 }
-                "@machine" => {
-                    if let Some(name) = tokens.get(pos + 1) {
-                        machines.push(name.clone());
+                SyntaxKind::ANIMATION => {
+                    if let Some(animation) = Self::lower_animation(member) {
+                        widget.animations.push(animation.name.clone());
+                        nested_animations.push(animation);
                     }
                 }
-                "@animation" => {
-                    if let Some(name) = tokens.get(pos + 1) {
-                        animations.push(name.clone());
+                SyntaxKind::SPRING => {
+                    if let Some(spring) = Self::lower_spring(member) {
+                        widget.springs.push(spring.name.clone());
+                        nested_springs.push(spring);
                     }
                 }
-                "@spring" => {
-                    if let Some(name) = tokens.get(pos + 1) {
-                        springs.push(name.clone());
-                    }
+                // Bodies are kept verbatim for the interpreter
+                SyntaxKind::RENDER => {
+                    widget.render_body = member
+                        .node(SyntaxKind::ELEMENT_LIST)
+                        .and_then(|list| list.braced_text(source))
+                        .map(str::to_string);
                 }
-                "@render" => {
-                    // Capture render body
-                    let mut body = String::new();
-                    let mut inner_depth = 0;
-                    let mut capturing = false;
-                    
-                    for i in (pos + 1)..tokens.len() {
-                        if tokens[i] == "{" {
-                            inner_depth += 1;
-                            capturing = true;
-                        } else if tokens[i] == "}" {
-                            inner_depth -= 1;
-                            if inner_depth == 0 && capturing {
-                                render_body = Some(body.trim().to_string());
-                                pos = i;
-                                break;
-                            }
-                        }
-                        if capturing {
-                            body.push_str(&tokens[i]);
-                            body.push(' ');
-                        }
-                    }
-                }
-                "@paint" => {
-                    // Similar to render
-                    let mut body = String::new();
-                    let mut inner_depth = 0;
-                    let mut capturing = false;
-                    
-                    for i in (pos + 1)..tokens.len() {
-                        if tokens[i] == "{" {
-                            inner_depth += 1;
-                            capturing = true;
-                        } else if tokens[i] == "}" {
-                            inner_depth -= 1;
-                            if inner_depth == 0 && capturing {
-                                paint_body = Some(body.trim().to_string());
-                                pos = i;
-                                break;
-                            }
-                        }
-                        if capturing {
-                            body.push_str(&tokens[i]);
-                            body.push(' ');
-                        }
-                    }
+                SyntaxKind::PAINT => {
+                    widget.paint_body = member
+                        .node(SyntaxKind::BLOCK)
+                        .and_then(|block| block.braced_text(source))
+                        .map(str::to_string);
                 }
+                SyntaxKind::EFFECT => diagnostics.push(
+                    Diagnostic::warning("`@effect` blocks are not compiled yet")
+                        .with_label(member.span.start..member.span.start + 7, "ignored")
+                        .with_help("drive the value from a `@derived` or an event handler instead"),
+                ),
                 _ => {}
             }
-            pos += 1;
         }
 
-        Ok((
-            WidgetDefinition {
-                name,
-                properties,
-                state_vars,
-                derived_vars,
-                machines,
-                animations,
-                springs,
-                render_body,
-                paint_body,
-            },
-            pos + 1,
-        ))
+        Some(widget)
     }
 
-    /// Parse @prop declaration
-    fn parse_prop(&self, tokens: &[String], start: usize) -> Result<(PropDef, usize)> {
-        if tokens[start] != "@prop" {
-            return Err(anyhow!("Expected @prop"));
-        }
-
-        let name = tokens.get(start + 1)
-            .ok_or_else(|| anyhow!("Expected property name"))?
-            .clone();
-
-        // Skip colon
-        let type_pos = start + 3;
-        let prop_type = tokens.get(type_pos)
-            .ok_or_else(|| anyhow!("Expected type"))?
-            .clone();
-
-        // Try to find default value (after =)
-        let mut default_value = None;
-        for i in (start + 4)..tokens.len() {
-            if tokens[i] == "=" {
-                if let Some(val) = tokens.get(i + 1) {
-                    default_value = Some(val.clone());
-                }
-                return Ok((
-                    PropDef {
-                        name,
-                        prop_type,
-                        default_value,
-                    },
-                    i + 2,
-                ));
-            } else if tokens[i] == "@" || tokens[i] == "}" {
-                break;
+    /// Names an expression reads, in order of first use
+    fn dependencies(expr: &SyntaxNode) -> Vec<String> {
+        let mut dependencies: Vec<String> = Vec::new();
+        let names = std::iter::once(expr)
+            .chain(expr.descendants())
+            .filter(|n| n.kind == SyntaxKind::NAME_REF);
+        for name in names {
+            let name = name.text();
+            if !dependencies.contains(&name) {
+                dependencies.push(name);
             }
         }
-
-        Ok((
-            PropDef {
-                name,
-                prop_type,
-                default_value,
-            },
-            start + 4,
-        ))
+        dependencies
     }
 
-    /// Parse @state declaration
-    fn parse_state(&self, tokens: &[String], start: usize) -> Result<(StateVar, usize)> {
-        if tokens[start] != "@state" {
-            return Err(anyhow!("Expected @state"));
-        }
-
-        let name = tokens.get(start + 1)
-            .ok_or_else(|| anyhow!("Expected state name"))?
-            .clone();
-
-        let var_type = tokens.get(start + 3)
-            .ok_or_else(|| anyhow!("Expected type"))?
-            .clone();
-
-        // Find = sign
-        let mut eq_pos = start + 4;
-        while eq_pos < tokens.len() && tokens[eq_pos] != "=" {
-            eq_pos += 1;
-        }
-
-        let initial_value = tokens.get(eq_pos + 1)
-            .ok_or_else(|| anyhow!("Expected initial value"))?
-            .clone();
-
-        Ok((
-            StateVar {
-                name,
-                var_type,
-                initial_value,
-            },
-            eq_pos + 2,
-        ))
+    /// Value of the `key: value` property directly inside `node`
+    fn property(node: &SyntaxNode, key: &str) -> Option<String> {
+        node.nodes()
+            .filter(|n| n.kind == SyntaxKind::PROPERTY)
+            .find(|n| n.name().is_some_and(|name| name.text == key))
+            .and_then(SyntaxNode::expr)
+            .map(SyntaxNode::text)
     }
 
-    /// Parse @derived declaration
-    fn parse_derived(&self, tokens: &[String], start: usize) -> Result<(DerivedVar, usize)> {
-        if tokens[start] != "@derived" {
-            return Err(anyhow!("Expected @derived"));
-        }
-
-        let name = tokens.get(start + 1)
-            .ok_or_else(|| anyhow!("Expected derived name"))?
-            .clone();
-
-        let var_type = tokens.get(start + 3)
-            .ok_or_else(|| anyhow!("Expected type"))?
-            .clone();
-
-        // Find = sign and gather expression
-        let mut eq_pos = start + 4;
-        while eq_pos < tokens.len() && tokens[eq_pos] != "=" {
-            eq_pos += 1;
-        }
-
-        let mut expr = String::new();
-        let mut pos = eq_pos + 1;
-        while pos < tokens.len() && tokens[pos] != "@" && tokens[pos] != "}" {
-            expr.push_str(&tokens[pos]);
-            expr.push(' ');
-            pos += 1;
-        }
-
-        // Simple dependency extraction from expression
-        let dependencies: Vec<String> = tokens[start + 1..eq_pos]
-            .iter()
-            .filter(|t| t.chars().next().map_or(false, |c| c.is_alphabetic()))
-            .cloned()
-            .collect();
-
-        Ok((
-            DerivedVar {
-                name,
-                var_type,
-                expression: expr.trim().to_string(),
-                dependencies,
-            },
-            pos,
-        ))
-    }
-
-    /// Parse @machine declaration
-    fn parse_machine(&self, tokens: &[String], start: usize) -> Result<(MachineDef, usize)> {
-        if tokens[start] != "@machine" {
-            return Err(anyhow!("Expected @machine"));
-        }
-
-        let name = tokens.get(start + 1)
-            .ok_or_else(|| anyhow!("Expected machine name"))?
-            .clone();
-
-        // Find opening brace
-        let mut brace_pos = start + 2;
-        while brace_pos < tokens.len() && tokens[brace_pos] != "{" {
-            brace_pos += 1;
-        }
-
-        let mut states = Vec::new();
-        let mut transitions = Vec::new();
-        let mut initial_state = String::new();
-
-        // Simple state machine parser
-        let mut pos = brace_pos + 1;
-        while pos < tokens.len() && tokens[pos] != "}" {
-            if tokens[pos].chars().all(|c| c.is_alphabetic() || c == '_') {
-                states.push(tokens[pos].clone());
+    /// Lower a `@machine` node
+    ///
+    /// Both transition forms are accepted:
+    ///
+    /// ```text
+    /// initial: idle
+    /// idle -> active: pointer_enter
+    /// states { idle { on pointer_enter => active } }
+    /// ```
+    fn lower_machine(node: &SyntaxNode) -> Option<MachineDef> {
+        let mut machine = MachineDef {
+            name: node.name()?.text.clone(),
+            states: Vec::new(),
+            initial_state: String::new(),
+            transitions: Vec::new(),
+        };
+        let add_state = |states: &mut Vec<String>, state: &str| {
+            if !states.iter().any(|s| s == state) {
+                states.push(state.to_string());
             }
-            pos += 1;
-        }
-
-        if !states.is_empty() {
-            initial_state = states[0].clone();
-        }
-
-        Ok((
-            MachineDef {
-                name,
-                states,
-                initial_state,
-                transitions,
-            },
-            pos + 1,
-        ))
-    }
-
-    /// Parse @animation declaration
-    fn parse_animation(&self, tokens: &[String], start: usize) -> Result<(AnimationDef, usize)> {
-        if tokens[start] != "@animation" {
-            return Err(anyhow!("Expected @animation"));
-        }
-
-        let name = tokens.get(start + 1)
-            .ok_or_else(|| anyhow!("Expected animation name"))?
-            .clone();
-
-        let mut duration_ms = 300u32;
-        let mut easing = "ease-out".to_string();
-
-        // Find values in the body
-        for i in (start + 2)..tokens.len() {
-            if tokens[i].contains("duration") {
-                if let Some(val_str) = tokens.get(i + 1) {
-                    if let Ok(val) = val_str.replace("ms", "").replace("s", "00").parse::<u32>() {
-                        duration_ms = val;
+        };
+        let idents = |node: &SyntaxNode| -> Vec<String> {
+            node.tokens()
+                .filter(|t| t.kind == SyntaxKind::IDENT)
+                .map(|t| t.text.clone())
+                .collect()
+        };
+
+        for item in node.nodes() {
+            match item.kind {
+                SyntaxKind::PROPERTY => {
+                    let (Some(key), Some(value)) = (item.name(), item.expr()) else {
+                        continue;
+                    };
+                    match key.text.as_str() {
+                        "initial" => {
+                            machine.initial_state = value.text();
+                            add_state(&mut machine.states, &machine.initial_state);
+                        }
+                        "state" => add_state(&mut machine.states, &value.text()),
+                        _ => {}
                     }
                 }
-            }
-            if tokens[i].contains("easing") || tokens[i].contains("ease") {
-                if let Some(val) = tokens.get(i + 1) {
-                    easing = val.trim_matches(|c| c == '"' || c == '\'').to_string();
+                SyntaxKind::TRANSITION => {
+                    if let [from, to, event] = idents(item).as_slice() {
+                        add_state(&mut machine.states, from);
+                        add_state(&mut machine.states, to);
+                        machine.transitions.push(Transition {
+                            from: from.clone(),
+                            to: to.clone(),
+                            event: event.clone(),
+                        });
+                    }
                 }
-            }
-            if tokens[i] == "}" {
-                break;
+                SyntaxKind::PROPERTY_BLOCK => {
+                    let states = item.nodes().filter(|n| n.kind == SyntaxKind::STATE_BLOCK);
+                    for state in states {
+                        let Some(from) = state.name() else { continue };
+                        add_state(&mut machine.states, &from.text);
+                        let transitions = state.nodes().filter(|n| n.kind == SyntaxKind::TRANSITION);
+                        for transition in transitions {
+                            // `on event => target`
+                            if let [_, event, to] = idents(transition).as_slice() {
+                                machine.transitions.push(Transition {
+                                    from: from.text.clone(),
+                                    to: to.clone(),
+                                    event: event.clone(),
+                                });
+                            }
+                        }
+                    }
+                    for transition in &machine.transitions {
+                        add_state(&mut machine.states, &transition.to);
+                    }
+                }
+                _ => {}
             }
         }
 
-        // Find closing brace
-        let mut pos = start + 2;
-        let mut depth = 0;
-        while pos < tokens.len() {
-            if tokens[pos] == "{" {
-                depth += 1;
-            } else if tokens[pos] == "}" {
-                depth -= 1;
-                if depth == 0 {
-                    break;
-                }
-            }
-            pos += 1;
+        if machine.initial_state.is_empty() {
+            machine.initial_state = machine.states.first().cloned().unwrap_or_default();
         }
+        Some(machine)
+    }
 
-        Ok((
-            AnimationDef {
-                name,
-                duration_ms,
-                easing,
-            },
-            pos + 1,
-        ))
+    /// Lower an `@animation` node
+    fn lower_animation(node: &SyntaxNode) -> Option<AnimationDef> {
+        let duration_ms = Self::property(node, "duration")
+            .and_then(|d| Self::parse_duration(&d))
+            .unwrap_or(300);
+        let easing = Self::property(node, "easing")
+            .map(|e| e.trim_matches(|c| c == '"' || c == '\'').to_string())
+            .unwrap_or_else(|| "ease-out".to_string());
+
+        Some(AnimationDef {
+            name: node.name()?.text.clone(),
+            duration_ms,
+            easing,
+        })
     }
 
-    /// Parse @spring declaration
-    fn parse_spring(&self, tokens: &[String], start: usize) -> Result<(SpringDef, usize)> {
-        if tokens[start] != "@spring" {
-            return Err(anyhow!("Expected @spring"));
+    /// `300ms`, `2s` or a bare number of milliseconds
+    fn parse_duration(text: &str) -> Option<u32> {
+        if let Some(ms) = text.strip_suffix("ms") {
+            return ms.trim().parse().ok();
         }
-
-        let name = tokens.get(start + 1)
-            .ok_or_else(|| anyhow!("Expected spring name"))?
-            .clone();
-
-        let mut stiffness = 100.0f32;
-        let mut damping = 10.0f32;
-        let mut mass = 1.0f32;
-
-        // Extract spring parameters
-        for i in (start + 2)..tokens.len() {
-            if tokens[i] == "}" {
-                break;
-            }
-            if tokens[i].contains("stiffness") {
-                if let Some(val_str) = tokens.get(i + 1) {
-                    if let Ok(val) = val_str.parse::<f32>() {
-                        stiffness = val;
-                    }
-                }
-            }
-            if tokens[i].contains("damping") {
-                if let Some(val_str) = tokens.get(i + 1) {
-                    if let Ok(val) = val_str.parse::<f32>() {
-                        damping = val;
-                    }
-                }
-            }
-            if tokens[i].contains("mass") {
-                if let Some(val_str) = tokens.get(i + 1) {
-                    if let Ok(val) = val_str.parse::<f32>() {
-                        mass = val;
-                    }
-                }
-            }
-        }
-
-        let mut pos = start + 2;
-        let mut depth = 0;
-        while pos < tokens.len() {
-            if tokens[pos] == "{" {
-                depth += 1;
-            } else if tokens[pos] == "}" {
-                depth -= 1;
-                if depth == 0 {
-                    break;
-                }
-            }
-            pos += 1;
+        if let Some(s) = text.strip_suffix('s') {
+            return s.trim().parse::<f32>().ok().map(|s| (s * 1000.0) as u32);
         }
+        text.parse().ok()
+    }
 
-        Ok((
-            SpringDef {
-                name,
-                stiffness,
-                damping,
-                mass,
-            },
-            pos + 1,
-        ))
+    /// Lower a `@spring` node
+    fn lower_spring(node: &SyntaxNode) -> Option<SpringDef> {
+        let number = |key: &str, default: f32| {
+            Self::property(node, key)
+                .and_then(|v| v.parse::<f32>().ok())
+                .unwrap_or(default)
+        };
+
+        Some(SpringDef {
+            name: node.name()?.text.clone(),
+            stiffness: number("stiffness", 100.0),
+            damping: number("damping", 10.0),
+            mass: number("mass", 1.0),
+            // Optional starting value: `@spring opacity: f32 = 1.0 { .. }`
+            initial_value: node.expr().and_then(|v| v.text().parse().ok()),
+        })
     }
 
     fn file_checksum(path: &Path) -> Result<String> {
+        let source = fs::read_to_string(path)?;
+        Ok(Self::checksum(&source))
+    }
+
+    fn checksum(source: &str) -> String {
         use std::collections::hash_map::DefaultHasher;
         use std::hash::{Hash, Hasher};
 
-        let source = fs::read_to_string(path)?;
         let mut hasher = DefaultHasher::new();
         source.hash(&mut hasher);
-        Ok(format!("{:x}", hasher.finish()))
+        format!("{:x}", hasher.finish())
     }
 
     /// Clear compilation cache
@@ -704,6 +405,36 @@ impl JunitaCompiler {
     }
 }
 
+/// `.junita` and `.bl` files under `dir`, skipping hidden and build
+/// directories
+pub fn find_sources(dir: &Path) -> Result<Vec<PathBuf>> {
+    find_files(dir, &["junita", "bl"])
+}
+
+/// Files with one of `extensions` under `dir`, skipping hidden and build
+/// directories
+pub fn find_files(dir: &Path, extensions: &[&str]) -> Result<Vec<PathBuf>> {
+    let mut sources = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let path = entry?.path();
+            let name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
+            if path.is_dir() {
+                if !name.starts_with('.') && !matches!(name, "target" | "build" | "node_modules") {
+                    pending.push(path);
+                }
+            } else if path.extension().and_then(|e| e.to_str()).is_some_and(|e| extensions.contains(&e)) {
+                sources.push(path);
+            }
+        }
+    }
+
+    sources.sort();
+    Ok(sources)
+}
+
 impl Default for JunitaCompiler {
     fn default() -> Self {
         Self::new()
@@ -715,12 +446,42 @@ mod tests {
     use super::*;
 
     #[test]
-    fn test_tokenize() {
+    fn test_compile_source_reports_diagnostics() {
         let compiler = JunitaCompiler::new();
-        let source = "@widget Counter { @state count: Int = 0 }";
-        let tokens = compiler.tokenize(source).unwrap();
-        assert!(!tokens.is_empty());
-        assert_eq!(tokens[0], "@widget");
+        let source = "@widget Counter {\n    @state count i32 = 0\n    @render { Text { content: \"{count}\" } }\n}\n";
+        let (artifact, diagnostics) = compiler.compile_source(source, Path::new("main.junita"));
+
+        // The typo is reported, and the rest of the widget still compiles
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "expected `:`, found `i32`");
+        let counter = artifact.widget("Counter").unwrap();
+        assert_eq!(counter.state_vars[0].var_type, "i32");
+        assert_eq!(counter.render_body.as_deref(), Some("Text { content: \"{count}\" }"));
+    }
+
+    #[test]
+    fn test_lower_machines_and_animations() {
+        let compiler = JunitaCompiler::new();
+        let source = r#"
+            @machine toggle {
+                initial: off
+                states {
+                    off { on click => on }
+                    on { on click => off }
+                }
+            }
+            @animation fade { duration: 1.5s, easing: "linear" }
+        "#;
+        let (artifact, diagnostics) = compiler.compile_source(source, Path::new("main.junita"));
+        assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+
+        let toggle = artifact.machine("toggle").unwrap();
+        assert_eq!(toggle.initial_state, "off");
+        assert_eq!(toggle.states, vec!["off", "on"]);
+        assert_eq!(toggle.transitions[1].from, "on");
+        assert_eq!(toggle.transitions[1].to, "off");
+        assert_eq!(artifact.animations[0].duration_ms, 1500);
+        assert_eq!(artifact.animations[0].easing, "linear");
     }
 
     #[tokio::test]
@@ -735,5 +496,48 @@ mod tests {
             assert!(!artifact.widgets.is_empty(), "Should parse widgets from demo file");
         }
     }
+
+    #[tokio::test]
+    async fn test_compile_counter_example() {
+        let mut compiler = JunitaCompiler::new();
+        let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("../../examples/counter/src/main.junita");
+        let artifact = compiler.compile(&path).await.unwrap();
+
+        let counter = artifact.widget("Counter").unwrap();
+        assert_eq!(counter.state_vars[0].initial_value, "0");
+        assert_eq!(counter.derived_vars[0].expression, "count * 2");
+        assert_eq!(counter.derived_vars[0].dependencies, vec!["count"]);
+
+        // Render bodies are kept verbatim
+        let render = counter.render_body.as_deref().unwrap();
+        assert!(render.starts_with("Column {"));
+        assert!(render.contains(r#"content: "Doubled: {doubled}""#));
+        assert!(render.contains("color: #666"));
+
+        // Machines and springs declared inside the widget
+        assert_eq!(counter.machines, vec!["state"]);
+        let machine = artifact.machine("state").unwrap();
+        assert_eq!(machine.initial_state, "idle");
+        assert_eq!(machine.transitions.len(), 2);
+        assert_eq!(machine.transitions[0].event, "pointer_enter");
+        let spring = artifact.spring("opacity").unwrap();
+        assert_eq!(spring.initial_value, Some(1.0));
+        assert_eq!(spring.stiffness, 300.0);
+        assert_eq!(spring.damping, 25.0);
+    }
+
+    #[tokio::test]
+    async fn test_examples_run_in_interpreter() {
+        let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../examples");
+        for example in ["counter/src/main.junita", "hot_reload_demo/main.junita"] {
+            let mut compiler = JunitaCompiler::new();
+            let artifact = compiler.compile(&root.join(example)).await.unwrap();
+            let mut interpreter = junita_interpreter::Interpreter::new(artifact);
+            if let Err(e) = interpreter.try_build() {
+                panic!("{} failed to build: {}", example, e);
+            }
+        }
+    }
 }
 
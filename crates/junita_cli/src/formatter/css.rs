@@ -0,0 +1,235 @@
+//! Formatter for stylesheets
+//!
+//! Works on the source nodes from [`Stylesheet::parse_syntax`]: one
+//! declaration per line with four-space indentation and a blank line between
+//! top-level blocks. Declarations are sorted with custom properties first,
+//! then the properties `css_parser` understands in [`PROPERTY_ORDER`], then
+//! anything else in the order written. A shorthand shares its longhands'
+//! place so whichever was written last still wins.
+//!
+//! Values get single spaces, `, ` between arguments and lowercase hex
+//! colors; quoted strings are left alone.
+
+use junita_layout::css_parser::{CssNode, ParseError, Stylesheet};
+
+const INDENT: &str = "    ";
+
+/// Sort order of known properties; `background-color` sorts with
+/// `background` and every `animation-*` with `animation`
+const PROPERTY_ORDER: &[&str] = &[
+    "render-layer",
+    "z-index",
+    "transform",
+    "opacity",
+    "background",
+    "border-radius",
+    "box-shadow",
+    "animation",
+];
+
+/// Format a stylesheet, or return the first parse error
+pub fn format(source: &str) -> Result<String, Box<ParseError>> {
+    let nodes = Stylesheet::parse_syntax(source)?;
+    let mut out = String::new();
+    write_nodes(&mut out, &nodes, 0);
+    Ok(out)
+}
+
+/// A node with the comments written around it
+struct Entry<'n> {
+    leading: Vec<&'n str>,
+    node: &'n CssNode,
+    trailing: Option<&'n str>,
+}
+
+fn write_nodes(out: &mut String, nodes: &[CssNode], depth: usize) {
+    let mut entries: Vec<Entry> = Vec::new();
+    let mut leading = Vec::new();
+    for node in nodes {
+        match node {
+            CssNode::Comment { text, trailing } => match entries.last_mut() {
+                Some(last) if *trailing && leading.is_empty() && last.trailing.is_none() => {
+                    last.trailing = Some(text)
+                }
+                _ => leading.push(text.as_str()),
+            },
+            _ => entries.push(Entry {
+                leading: std::mem::take(&mut leading),
+                node,
+                trailing: None,
+            }),
+        }
+    }
+    if entries
+        .iter()
+        .all(|e| matches!(e.node, CssNode::Declaration { .. }))
+    {
+        entries.sort_by_key(|e| match e.node {
+            CssNode::Declaration { name, .. } => rank(name),
+            _ => 0,
+        });
+    }
+
+    let indent = INDENT.repeat(depth);
+    for (i, entry) in entries.iter().enumerate() {
+        if depth == 0 && i > 0 {
+            out.push('\n');
+        }
+        for comment in &entry.leading {
+            out.push_str(&indent);
+            out.push_str(comment);
+            out.push('\n');
+        }
+        out.push_str(&indent);
+        match entry.node {
+            CssNode::Declaration { name, value } => {
+                out.push_str(name);
+                out.push_str(": ");
+                out.push_str(&normalize_value(value));
+                out.push(';');
+            }
+            CssNode::Block { prelude, children } if children.is_empty() => {
+                out.push_str(prelude);
+                out.push_str(" {}");
+            }
+            CssNode::Block { prelude, children } => {
+                out.push_str(prelude);
+                out.push_str(" {\n");
+                write_nodes(out, children, depth + 1);
+                out.push_str(&indent);
+                out.push('}');
+            }
+            CssNode::Comment { .. } => unreachable!("comments are attached to entries"),
+        }
+        if let Some(comment) = entry.trailing {
+            out.push(' ');
+            out.push_str(comment);
+        }
+        out.push('\n');
+    }
+
+    for (i, comment) in leading.iter().enumerate() {
+        if depth == 0 && i == 0 && !entries.is_empty() {
+            out.push('\n');
+        }
+        out.push_str(&indent);
+        out.push_str(comment);
+        out.push('\n');
+    }
+}
+
+fn rank(name: &str) -> usize {
+    if name.starts_with("--") {
+        return 0;
+    }
+    PROPERTY_ORDER
+        .iter()
+        .position(|p| name == *p || name.strip_prefix(p).is_some_and(|s| s.starts_with('-')))
+        .map_or(PROPERTY_ORDER.len() + 1, |i| i + 1)
+}
+
+/// Collapse spaces, space arguments and lowercase hex colors outside quotes
+fn normalize_value(value: &str) -> String {
+    let mut out = String::new();
+    let mut chars = value.chars().peekable();
+    let mut quote = None;
+    while let Some(c) = chars.next() {
+        if let Some(q) = quote {
+            out.push(c);
+            if c == '\\' {
+                out.extend(chars.next());
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => {
+                quote = Some(c);
+                out.push(c);
+            }
+            c if c.is_whitespace() => {
+                if !out.ends_with([' ', '(']) {
+                    out.push(' ');
+                }
+            }
+            ',' => {
+                out.truncate(out.trim_end().len());
+                out.push_str(", ");
+            }
+            ')' => {
+                out.truncate(out.trim_end().len());
+                out.push(')');
+            }
+            '#' => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_alphanumeric()) {
+                    word.push(c);
+                    chars.next();
+                }
+                out.push('#');
+                if word.chars().all(|c| c.is_ascii_hexdigit()) {
+                    out.push_str(&word.to_ascii_lowercase());
+                } else {
+                    out.push_str(&word);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out.truncate(out.trim_end().len());
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(source: &str) -> String {
+        let formatted = format(source).unwrap();
+        assert_eq!(format(&formatted).unwrap(), formatted, "not idempotent");
+        formatted
+    }
+
+    #[test]
+    fn test_layout_and_order() {
+        let source = "/* Theme */\n:root{--accent:#FF00AA}\n#card:hover { animation-duration: 2s;  box-shadow:0 4px  8px rgba( 0,0,0,0.5 ) ; /* soft */\n  animation: fade 1s; opacity:0.5; content: \"A  #FFF\"; --local: 1 }\n@keyframes fade{from{opacity:0}  to { opacity : 1 } }\n#empty {}\n/* The end */";
+        assert_eq!(
+            fmt(source),
+            "/* Theme */
+:root {
+    --accent: #ff00aa;
+}
+
+#card:hover {
+    --local: 1;
+    opacity: 0.5;
+    box-shadow: 0 4px 8px rgba(0, 0, 0, 0.5); /* soft */
+    animation-duration: 2s;
+    animation: fade 1s;
+    content: \"A  #FFF\";
+}
+
+@keyframes fade {
+    from {
+        opacity: 0;
+    }
+    to {
+        opacity: 1;
+    }
+}
+
+#empty {}
+
+/* The end */
+"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = format("#a {\n  opacity 1;\n}").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert_eq!(format("").unwrap(), "");
+    }
+}
@@ -0,0 +1,594 @@
+//! Formatter for `.junita` source
+//!
+//! Prints the lossless syntax tree back out with four-space indentation,
+//! one item per line in blocks, single spaces between tokens and at most one
+//! blank line in a row. Comments stay with the item they precede or trail.
+//!
+//! Props of an element come first, sorted into the order of
+//! [`catalog::PROPS`] and then [`catalog::EVENTS`]; props the catalog doesn't
+//! know keep their order after those, and child elements keep theirs after
+//! all props. Color literals are lowercased.
+//!
+//! A block written on one line stays on one line where that reads well, as in
+//! `on_click: { count += 1 }`; declarations, `@effect` and `@render` bodies
+//! always break. Hyphenated names like `ease-in-out` are kept as written.
+
+use std::mem;
+
+use crate::catalog;
+use crate::syntax::{
+    self, Diagnostic, SyntaxElement,
+    SyntaxKind::{self, *},
+    SyntaxNode, SyntaxToken,
+};
+
+const INDENT: &str = "    ";
+
+/// Format a source file, or return its syntax errors
+pub fn format(source: &str) -> Result<String, Vec<Diagnostic>> {
+    let parse = syntax::parse_syntax(source);
+    let errors: Vec<_> = parse
+        .diagnostics
+        .into_iter()
+        .filter(Diagnostic::is_error)
+        .collect();
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+
+    let mut printer = Printer::new(source);
+    let (items, dangling) = group(&parse.root.children);
+    printer.lines(&items, &dangling, SOURCE_FILE, true);
+    let mut out = printer.out;
+    out.truncate(out.trim_end().len());
+    if !out.is_empty() {
+        out.push('\n');
+    }
+
+    // A printing bug must never cost anyone their file
+    if syntax::parse_syntax(&out)
+        .diagnostics
+        .iter()
+        .any(Diagnostic::is_error)
+    {
+        return Err(vec![Diagnostic::error(
+            "formatting produced invalid syntax; the file was left unchanged",
+        )
+        .with_help("please report this as a bug, with the file")]);
+    }
+    Ok(out)
+}
+
+/// A comment on a line of its own
+struct Comment<'t> {
+    token: &'t SyntaxToken,
+    blank_before: bool,
+}
+
+/// An item of a block or list with the comments and separator around it
+struct Item<'t> {
+    element: &'t SyntaxElement,
+    leading: Vec<Comment<'t>>,
+    separator: Option<&'t SyntaxToken>,
+    trailing: Option<&'t SyntaxToken>,
+    blank_before: bool,
+}
+
+impl Item<'_> {
+    fn kind(&self) -> SyntaxKind {
+        match self.element {
+            SyntaxElement::Node(node) => node.kind,
+            SyntaxElement::Token(token) => token.kind,
+        }
+    }
+
+    fn has_comments(&self) -> bool {
+        !self.leading.is_empty() || self.trailing.is_some()
+    }
+
+    /// Where the item sorts among the contents of an element
+    fn rank(&self) -> (usize, usize) {
+        let SyntaxElement::Node(node) = self.element else {
+            return (1, 0);
+        };
+        if node.kind != PROPERTY {
+            return (1, 0);
+        }
+        let Some(name) = node.name() else {
+            return (0, usize::MAX);
+        };
+        let position = |props: &[catalog::Prop]| {
+            props
+                .iter()
+                .position(|p| p.name == name.text || p.aliases.contains(&name.text.as_str()))
+        };
+        let rank = position(catalog::PROPS)
+            .or_else(|| position(catalog::EVENTS).map(|i| catalog::PROPS.len() + i))
+            .unwrap_or(usize::MAX);
+        (0, rank)
+    }
+}
+
+/// Split the contents of a block or list into items, returning comments
+/// after the last item separately
+fn group(elements: &[SyntaxElement]) -> (Vec<Item<'_>>, Vec<Comment<'_>>) {
+    let mut items: Vec<Item> = Vec::new();
+    let mut leading = Vec::new();
+    let mut newlines = 0;
+    for element in elements {
+        match element {
+            SyntaxElement::Token(token) if token.kind == WHITESPACE => {
+                newlines += token.text.matches('\n').count();
+            }
+            SyntaxElement::Token(token) if token.kind == COMMENT => {
+                match items.last_mut() {
+                    Some(last)
+                        if newlines == 0 && leading.is_empty() && last.trailing.is_none() =>
+                    {
+                        last.trailing = Some(token)
+                    }
+                    _ => leading.push(Comment {
+                        token,
+                        blank_before: newlines >= 2,
+                    }),
+                }
+                newlines = 0;
+            }
+            SyntaxElement::Token(token) if matches!(token.kind, COMMA | SEMICOLON) => {
+                match items.last_mut() {
+                    Some(last)
+                        if leading.is_empty()
+                            && last.separator.is_none()
+                            && last.trailing.is_none() =>
+                    {
+                        last.separator = Some(token)
+                    }
+                    // Stray separators carry nothing worth keeping
+                    _ => {}
+                }
+            }
+            _ => {
+                items.push(Item {
+                    element,
+                    leading: mem::take(&mut leading),
+                    separator: None,
+                    trailing: None,
+                    blank_before: newlines >= 2,
+                });
+                newlines = 0;
+            }
+        }
+    }
+    (items, leading)
+}
+
+struct Printer<'s> {
+    source: &'s str,
+    out: String,
+    indent: usize,
+    /// Last token on the current line and the kind of its parent
+    last: Option<(SyntaxKind, SyntaxKind)>,
+    /// A `//` comment ended the line in the middle of an item
+    line_comment: bool,
+    /// The next token is glued to the last one, as in `ease-in-out`
+    glue: bool,
+}
+
+impl<'s> Printer<'s> {
+    fn new(source: &'s str) -> Self {
+        Self {
+            source,
+            out: String::new(),
+            indent: 0,
+            last: None,
+            line_comment: false,
+            glue: false,
+        }
+    }
+
+    /// End the current line and indent the next, optionally after a blank one
+    fn begin_line(&mut self, blank: bool) {
+        self.out.truncate(self.out.trim_end_matches(' ').len());
+        self.out.push('\n');
+        if blank {
+            self.out.push('\n');
+        }
+        for _ in 0..self.indent {
+            self.out.push_str(INDENT);
+        }
+        self.last = None;
+        self.line_comment = false;
+        self.glue = false;
+    }
+
+    fn token(&mut self, token: &SyntaxToken, parent: SyntaxKind) {
+        let hyphen = token.kind == MINUS && parent == BINARY_EXPR && self.is_hyphen(token);
+        if self.line_comment {
+            self.indent += 1;
+            self.begin_line(false);
+            self.indent -= 1;
+        } else if let Some(last) = self.last {
+            if !hyphen && !self.glue && needs_space(last, (token.kind, parent)) {
+                self.out.push(' ');
+            }
+        }
+        if token.kind == COLOR {
+            self.out.push_str(&token.text.to_ascii_lowercase());
+        } else {
+            self.out.push_str(&token.text);
+        }
+        self.last = Some((token.kind, parent));
+        self.line_comment = token.kind == COMMENT && token.text.starts_with("//");
+        self.glue = hyphen;
+    }
+
+    /// `-` written between two names with no space, as in `ease-in-out`
+    fn is_hyphen(&self, token: &SyntaxToken) -> bool {
+        let before = self.source[..token.span.start].chars().next_back();
+        let after = self.source[token.span.end..].chars().next();
+        before.is_some_and(|c| c.is_alphanumeric() || c == '_')
+            && after.is_some_and(|c| c.is_alphabetic() || c == '_')
+    }
+
+    fn element(&mut self, element: &SyntaxElement, parent: SyntaxKind) {
+        match element {
+            SyntaxElement::Node(node) => self.node(node, parent),
+            SyntaxElement::Token(token) => self.token(token, parent),
+        }
+    }
+
+    fn node(&mut self, node: &SyntaxNode, parent: SyntaxKind) {
+        let children = &node.children;
+        let mut i = 0;
+        while i < children.len() {
+            match &children[i] {
+                SyntaxElement::Token(token) if token.kind == WHITESPACE => {}
+                SyntaxElement::Token(open) if is_open(node.kind, open.kind) => {
+                    let close = closing(children, i);
+                    let SyntaxElement::Token(close_token) = &children[close] else {
+                        unreachable!("closing() only returns tokens")
+                    };
+                    let inner = &children[i + 1..close];
+                    if open.kind == L_BRACE {
+                        self.braced(node.kind, parent, open, inner, close_token);
+                    } else {
+                        self.delimited(node.kind, parent, open, inner, close_token);
+                    }
+                    i = close;
+                }
+                child => self.element(child, node.kind),
+            }
+            i += 1;
+        }
+    }
+
+    /// `{ items }`
+    fn braced(
+        &mut self,
+        kind: SyntaxKind,
+        parent: SyntaxKind,
+        open: &SyntaxToken,
+        inner: &[SyntaxElement],
+        close: &SyntaxToken,
+    ) {
+        self.token(open, kind);
+        let (mut items, dangling) = group(inner);
+        if items.is_empty() && dangling.is_empty() {
+            self.out.push_str(&close.text);
+            self.last = Some((R_BRACE, kind));
+            return;
+        }
+        if kind == ELEMENT_LIST {
+            sort(&mut items);
+        }
+
+        let one_line = !self.source[open.span.end..close.span.start].contains('\n')
+            && dangling.is_empty()
+            && !items.iter().any(Item::has_comments);
+        if one_line && may_stay_inline(kind, parent) {
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 && kind == ELEMENT_LIST {
+                    self.out.push(',');
+                    self.last = Some((COMMA, kind));
+                }
+                self.element(item.element, kind);
+                if kind != ELEMENT_LIST {
+                    if let Some(separator) = item.separator {
+                        self.token(separator, kind);
+                    }
+                }
+            }
+            self.token(close, kind);
+            return;
+        }
+
+        self.indent += 1;
+        self.lines(&items, &dangling, kind, false);
+        self.indent -= 1;
+        self.begin_line(false);
+        self.token(close, kind);
+    }
+
+    /// `(args)` or `[items]`, on one line or one item per line
+    fn delimited(
+        &mut self,
+        kind: SyntaxKind,
+        parent: SyntaxKind,
+        open: &SyntaxToken,
+        inner: &[SyntaxElement],
+        close: &SyntaxToken,
+    ) {
+        self.token(open, kind);
+        let (mut items, dangling) = group(inner);
+        if kind == ARG_LIST && parent == ELEMENT {
+            sort(&mut items);
+        }
+
+        let one_line = !self.source[open.span.end..close.span.start].contains('\n')
+            && dangling.is_empty()
+            && !items.iter().any(Item::has_comments);
+        if one_line {
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    self.out.push_str(", ");
+                    self.last = None;
+                }
+                self.element(item.element, kind);
+            }
+            self.last = None;
+            self.token(close, kind);
+            return;
+        }
+
+        self.indent += 1;
+        for (i, item) in items.iter().enumerate() {
+            for comment in &item.leading {
+                self.begin_line(i > 0 && comment.blank_before);
+                self.token(comment.token, kind);
+            }
+            self.begin_line(i > 0 && item.blank_before && item.leading.is_empty());
+            self.element(item.element, kind);
+            self.out.push(',');
+            self.last = Some((COMMA, kind));
+            if let Some(trailing) = item.trailing {
+                self.token(trailing, kind);
+            }
+        }
+        for comment in &dangling {
+            self.begin_line(comment.blank_before);
+            self.token(comment.token, kind);
+        }
+        self.indent -= 1;
+        self.begin_line(false);
+        self.token(close, kind);
+    }
+
+    /// One item per line; at the top level declarations are separated by a
+    /// blank line, except between imports
+    fn lines(&mut self, items: &[Item], dangling: &[Comment], kind: SyntaxKind, top: bool) {
+        let mut first = true;
+        let mut previous: Option<SyntaxKind> = None;
+        for item in items {
+            let mut blank = top
+                && previous.is_some_and(|previous| !(previous == IMPORT && item.kind() == IMPORT));
+            for comment in &item.leading {
+                self.line(first, top, blank || comment.blank_before);
+                self.token(comment.token, kind);
+                first = false;
+                blank = false;
+            }
+            self.line(first, top, blank || item.blank_before);
+            first = false;
+            self.element(item.element, kind);
+            if kind != ELEMENT_LIST {
+                if let Some(separator) = item.separator {
+                    self.token(separator, kind);
+                }
+            }
+            if let Some(trailing) = item.trailing {
+                self.token(trailing, kind);
+            }
+            previous = Some(item.kind());
+        }
+        for comment in dangling {
+            self.line(first, top, comment.blank_before);
+            self.token(comment.token, kind);
+            first = false;
+        }
+    }
+
+    fn line(&mut self, first: bool, top: bool, blank: bool) {
+        match (first, top) {
+            (true, true) => {}
+            (true, false) => self.begin_line(false),
+            (false, _) => self.begin_line(blank),
+        }
+    }
+}
+
+/// Props before everything else, in catalog order
+fn sort(items: &mut [Item]) {
+    items.sort_by_key(Item::rank);
+}
+
+/// Whether `open` starts a list the printer lays out itself
+fn is_open(kind: SyntaxKind, open: SyntaxKind) -> bool {
+    match open {
+        L_BRACE => matches!(
+            kind,
+            WIDGET
+                | MACHINE
+                | STATE_BLOCK
+                | ANIMATION
+                | SPRING
+                | PROPERTY_BLOCK
+                | TRANSITION
+                | ELEMENT_LIST
+                | BLOCK
+        ),
+        L_PAREN => kind == ARG_LIST,
+        L_BRACKET => kind == ARRAY_EXPR,
+        _ => false,
+    }
+}
+
+/// Index of the token closing the list opened at `open`
+fn closing(children: &[SyntaxElement], open: usize) -> usize {
+    let SyntaxElement::Token(token) = &children[open] else {
+        unreachable!("lists open with a token")
+    };
+    let close = match token.kind {
+        L_BRACE => R_BRACE,
+        L_PAREN => R_PAREN,
+        _ => R_BRACKET,
+    };
+    children
+        .iter()
+        .rposition(|child| matches!(child, SyntaxElement::Token(t) if t.kind == close))
+        .expect("well-formed trees close every list")
+}
+
+/// Blocks that may stay on one line when written that way
+fn may_stay_inline(kind: SyntaxKind, parent: SyntaxKind) -> bool {
+    match kind {
+        ELEMENT_LIST => parent != RENDER,
+        BLOCK => !matches!(parent, EFFECT | PAINT),
+        PROPERTY_BLOCK | STATE_BLOCK | TRANSITION => true,
+        _ => false,
+    }
+}
+
+fn needs_space(
+    (last, last_parent): (SyntaxKind, SyntaxKind),
+    (next, next_parent): (SyntaxKind, SyntaxKind),
+) -> bool {
+    if matches!(
+        next,
+        COMMA | SEMICOLON | R_PAREN | R_BRACKET | DOT | COLON | COLON2
+    ) || matches!(last, L_PAREN | L_BRACKET | DOT | COLON2)
+    {
+        return false;
+    }
+    match (last, next) {
+        (MINUS | BANG, _) if last_parent == UNARY_EXPR => false,
+        (_, L_PAREN) => !(next_parent == ARG_LIST || last == DECORATOR),
+        (_, L_BRACKET) => next_parent != INDEX_EXPR,
+        (_, PERCENT) => next_parent != PROPERTY_BLOCK,
+        (LT, _) if last_parent == TYPE => false,
+        (_, LT) | (_, GT) if next_parent == TYPE => false,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fmt(source: &str) -> String {
+        let formatted = format(source).unwrap();
+        assert_eq!(format(&formatted).unwrap(), formatted, "not idempotent");
+        formatted
+    }
+
+    #[test]
+    fn test_layout() {
+        let source = "import \"a.junita\"\nimport \"b.junita\"\n@widget Counter{\n  @state count:i32=0\n\n\n\n  @render {\n    Column { Text { content: \"{count}\" } Button(label:\"+\",on_click:{ count+=1 }) }\n  }\n}\n@widget App { @render { Counter {} } }";
+        assert_eq!(
+            fmt(source),
+            "import \"a.junita\"
+import \"b.junita\"
+
+@widget Counter {
+    @state count: i32 = 0
+
+    @render {
+        Column { Text { content: \"{count}\" }, Button(label: \"+\", on_click: { count += 1 }) }
+    }
+}
+
+@widget App {
+    @render {
+        Counter {}
+    }
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_comments_and_props() {
+        let source = "// Header
+
+@widget A { // trails the brace
+    @render {
+        Text {
+            // Children stay last
+            Icon {}
+            color: #FFAA00 // loud
+            /* size */ font_size: 12,
+            content: \"hi\";
+            step: -1
+        }
+    }
+    // Dangling
+}
+";
+        assert_eq!(
+            fmt(source),
+            "// Header
+
+@widget A {
+    // trails the brace
+    @render {
+        Text {
+            content: \"hi\"
+            /* size */
+            font_size: 12
+            color: #ffaa00 // loud
+            step: -1
+            // Children stay last
+            Icon {}
+        }
+    }
+    // Dangling
+}
+"
+        );
+    }
+
+    #[test]
+    fn test_expressions() {
+        assert_eq!(
+            fmt("@animation Fade {\n  easing: ease-in-out\n  delay: a-1\n  keyframes { 0 % { opacity: 0 } }\n}"),
+            "@animation Fade {\n    easing: ease-in-out\n    delay: a - 1\n    keyframes { 0% { opacity: 0 } }\n}\n"
+        );
+        assert_eq!(
+            fmt("@widget A {\n@prop items: List < String > = [ 1,2 ]\n@derived b: bool = ! done && items [0] . len() > 0\n}"),
+            "@widget A {\n    @prop items: List<String> = [1, 2]\n    @derived b: bool = !done && items[0].len() > 0\n}\n"
+        );
+        // Arguments written across lines get one per line
+        assert_eq!(
+            fmt("@widget A {\n@render {\nBox(\n  height: 2, width: 1) {}\n}\n}"),
+            "@widget A {\n    @render {\n        Box(\n            width: 1,\n            height: 2,\n        ) {}\n    }\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_examples_are_stable() {
+        let root = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("../..");
+        for path in [
+            "examples/counter/src/main.junita",
+            "toolchain/templates/default/src/main.junita",
+        ] {
+            let source = std::fs::read_to_string(root.join(path)).unwrap();
+            assert_eq!(fmt(&source), source, "{} is not formatted", path);
+        }
+    }
+
+    #[test]
+    fn test_syntax_errors() {
+        let errors = format("@widget A {").unwrap_err();
+        assert!(errors.iter().all(Diagnostic::is_error));
+        assert!(!errors.is_empty());
+    }
+}
@@ -0,0 +1,32 @@
+//! `junita fmt`
+//!
+//! Pretty-printers for `.junita` source and the stylesheets read by
+//! `junita_layout::css_parser`, built on the parsers those already have.
+//! Output depends only on the input, and formatting formatted code changes
+//! nothing. Files that don't parse are reported and left alone.
+
+mod css;
+mod junita;
+
+use std::path::Path;
+
+use junita_layout::css_parser::ParseError;
+
+use crate::syntax::Diagnostic;
+
+/// Extensions of the files `junita fmt` picks up in a directory
+pub const EXTENSIONS: &[&str] = &["junita", "bl", "css"];
+
+/// Why a file couldn't be formatted
+pub enum Error {
+    Junita(Vec<Diagnostic>),
+    Css(Box<ParseError>),
+}
+
+/// Format a file's contents, picking the language from its extension
+pub fn format(path: &Path, source: &str) -> Result<String, Error> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("css") => css::format(source).map_err(Error::Css),
+        _ => junita::format(source).map_err(Error::Junita),
+    }
+}
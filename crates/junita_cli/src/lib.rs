@@ -0,0 +1,9 @@
+//! Junita CLI library
+//!
+//! The DSL compiler behind the `junita` binary, and the parser it builds on
+//! (re-exported from `junita_syntax`), for tools and fuzz targets that need
+//! them without the CLI.
+
+pub mod compiler;
+
+pub use junita_syntax as syntax;
@@ -0,0 +1,113 @@
+//! Open documents
+//!
+//! The client owns the text; the server keeps the latest version of each
+//! open file with its tree and diagnostics, and converts between byte
+//! offsets and LSP positions (lines and UTF-16 columns).
+
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::compiler::JunitaCompiler;
+use crate::syntax::{self, Diagnostic, SyntaxNode};
+
+use super::protocol::{self, Position};
+
+/// Open documents by URI
+pub type Documents = HashMap<String, Document>;
+
+pub struct Document {
+    pub uri: String,
+    pub text: String,
+    pub root: SyntaxNode,
+    pub diagnostics: Vec<Diagnostic>,
+    line_starts: Vec<usize>,
+}
+
+impl Document {
+    pub fn new(compiler: &JunitaCompiler, uri: String, text: String) -> Self {
+        let root = syntax::parse(&text).root;
+        let path = Path::new(uri.strip_prefix("file://").unwrap_or(&uri));
+        let (_, diagnostics) = compiler.compile_source(&text, path);
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+        Self {
+            uri,
+            text,
+            root,
+            diagnostics,
+            line_starts,
+        }
+    }
+
+    /// Position of a byte offset
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = offset.min(self.text.len());
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        let character = self.text[self.line_starts[line]..offset]
+            .encode_utf16()
+            .count();
+        Position {
+            line: line as u32,
+            character: character as u32,
+        }
+    }
+
+    /// Byte offset of a position, clamped to the line it's on
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(&start) = self.line_starts.get(position.line as usize) else {
+            return self.text.len();
+        };
+        let mut column = 0;
+        for (i, c) in self.text[start..].char_indices() {
+            if c == '\n' || column >= position.character as usize {
+                return start + i;
+            }
+            column += c.len_utf16();
+        }
+        self.text.len()
+    }
+
+    pub fn range(&self, span: Range<usize>) -> protocol::Range {
+        protocol::Range {
+            start: self.position(span.start),
+            end: self.position(span.end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_positions() {
+        let compiler = JunitaCompiler::new();
+        let text = "// ✓ 🎉\n@widget A {}\n".to_string();
+        let doc = Document::new(&compiler, "file:///a.junita".into(), text);
+
+        let a = doc.text.find('A').unwrap();
+        let position = doc.position(a);
+        assert_eq!((position.line, position.character), (1, 8));
+        assert_eq!(doc.offset(position), a);
+
+        // The emoji is two UTF-16 units
+        let end = doc.position(doc.text.find('\n').unwrap());
+        assert_eq!((end.line, end.character), (0, 7));
+        assert_eq!(
+            doc.offset(Position {
+                line: 0,
+                character: 99
+            }),
+            11
+        );
+        assert_eq!(
+            doc.offset(Position {
+                line: 9,
+                character: 0
+            }),
+            doc.text.len()
+        );
+    }
+}
@@ -0,0 +1,624 @@
+//! Language features
+//!
+//! Everything is computed from the syntax trees of the open documents on
+//! each request; files are small enough that nothing needs caching.
+
+use std::ops::Range;
+
+use crate::catalog;
+use crate::syntax::{
+    Severity,
+    SyntaxKind::{self, *},
+    SyntaxNode, SyntaxToken, TOP_LEVEL_DECORATORS, WIDGET_DECORATORS,
+};
+
+use super::document::{Document, Documents};
+use super::protocol::{
+    self, CompletionItem, CompletionKind, DiagnosticRelatedInformation, DocumentSymbol, Location,
+    SymbolKind, TextEdit,
+};
+
+/// Types offered after `@state name:`
+const TYPES: &[&str] = &["i32", "i64", "f32", "f64", "bool", "String", "Color", "Vec"];
+
+/// Words that can't be used as names
+const KEYWORDS: &[&str] = &[
+    "if", "else", "when", "let", "mut", "on", "true", "false", "theme", "import",
+];
+
+/// What a name in the source refers to
+enum Target<'a> {
+    /// A widget, machine, animation, spring or widget member
+    Decl {
+        doc: &'a Document,
+        node: &'a SyntaxNode,
+        name: &'a SyntaxToken,
+    },
+    Element(&'static catalog::Element),
+    Prop(&'static catalog::Prop),
+    Decorator(&'static str, &'static str),
+    ThemeToken(String, &'static str),
+}
+
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+/// Compiler diagnostics for `publishDiagnostics`
+pub fn diagnostics(doc: &Document) -> Vec<protocol::Diagnostic> {
+    doc.diagnostics
+        .iter()
+        .map(|d| {
+            let mut message = d.message.clone();
+            for help in &d.help {
+                message.push_str("\nhelp: ");
+                message.push_str(help);
+            }
+            let related_information = d
+                .labels
+                .iter()
+                .filter(|l| !l.primary)
+                .map(|l| DiagnosticRelatedInformation {
+                    location: Location {
+                        uri: doc.uri.clone(),
+                        range: doc.range(l.span.clone()),
+                    },
+                    message: l.message.clone(),
+                })
+                .collect();
+            protocol::Diagnostic {
+                range: doc.range(d.span().unwrap_or(0..0)),
+                severity: match d.severity {
+                    Severity::Error => 1,
+                    Severity::Warning => 2,
+                },
+                source: "junita",
+                message,
+                related_information,
+            }
+        })
+        .collect()
+}
+
+// ============================================================================
+// Completion
+// ============================================================================
+
+pub fn completion(docs: &Documents, doc: &Document, offset: usize) -> Vec<CompletionItem> {
+    let typed = &doc.text[..offset];
+    let word_start = typed
+        .trim_end_matches(|c: char| c.is_alphanumeric() || c == '_')
+        .len();
+    let before = &typed[..word_start];
+    let path = doc.root.ancestors_at(offset);
+    let in_widget = path.iter().any(|n| n.kind == WIDGET);
+
+    if before.ends_with('@') {
+        let decorators = if !in_widget {
+            TOP_LEVEL_DECORATORS
+        } else if path.iter().any(|n| value_context(n.kind)) {
+            &["@action"]
+        } else {
+            WIDGET_DECORATORS
+        };
+        return decorators.iter().map(|d| decorator_item(d)).collect();
+    }
+    if before.ends_with("theme.") {
+        return catalog::theme_tokens()
+            .iter()
+            .flat_map(|(group, tokens)| {
+                tokens
+                    .iter()
+                    .map(move |t| item(t, CompletionKind::Constant, format!("{} token", group)))
+            })
+            .collect();
+    }
+
+    let Some(&node) = path.last() else {
+        return Vec::new();
+    };
+    let after = |kind: SyntaxKind| node.token(kind).is_some_and(|t| offset >= t.span.end);
+    match node.kind {
+        SOURCE_FILE => TOP_LEVEL_DECORATORS
+            .iter()
+            .map(|d| decorator_item(d))
+            .collect(),
+        WIDGET => WIDGET_DECORATORS
+            .iter()
+            .map(|d| decorator_item(d))
+            .collect(),
+        ELEMENT_LIST => list_items(docs, doc, &path),
+        // Still typing the element's name
+        ELEMENT if node.name().is_some_and(|n| offset <= n.span.end) => {
+            list_items(docs, doc, &path[..path.len() - 1])
+        }
+        PROPERTY if !after(COLON) => list_items(docs, doc, &path[..path.len() - 1]),
+        PROP_DECL | STATE_DECL | DERIVED_DECL | SPRING if !after(EQ) => {
+            if after(COLON) {
+                TYPES
+                    .iter()
+                    .map(|t| item(t, CompletionKind::Keyword, "type".to_string()))
+                    .collect()
+            } else {
+                Vec::new()
+            }
+        }
+        _ if path.iter().any(|n| value_context(n.kind)) => value_items(&path),
+        _ => Vec::new(),
+    }
+}
+
+/// Nodes whose contents are expressions or statements
+fn value_context(kind: SyntaxKind) -> bool {
+    !matches!(kind, ELEMENT | ERROR_NODE)
+        && (kind.is_expr()
+            || matches!(
+                kind,
+                PROPERTY
+                    | PROP_DECL
+                    | STATE_DECL
+                    | DERIVED_DECL
+                    | LET_STMT
+                    | ASSIGN_STMT
+                    | EXPR_STMT
+                    | IF_STMT
+                    | CONDITIONAL
+                    | ARG_LIST
+            ))
+}
+
+fn item(label: &str, kind: CompletionKind, detail: String) -> CompletionItem {
+    CompletionItem {
+        label: label.to_string(),
+        kind,
+        detail: Some(detail),
+        documentation: None,
+        insert_text: None,
+    }
+}
+
+fn decorator_item(decorator: &str) -> CompletionItem {
+    CompletionItem {
+        label: decorator.to_string(),
+        kind: CompletionKind::Keyword,
+        detail: None,
+        documentation: catalog::decorator(decorator).map(str::to_string),
+        insert_text: Some(decorator.trim_start_matches('@').to_string()),
+    }
+}
+
+/// Elements, plus props when the list belongs to an element
+fn list_items(docs: &Documents, doc: &Document, path: &[&SyntaxNode]) -> Vec<CompletionItem> {
+    let mut items: Vec<_> = catalog::ELEMENTS
+        .iter()
+        .map(|e| CompletionItem {
+            documentation: Some(e.doc.to_string()),
+            ..item(e.name, CompletionKind::Class, "element".to_string())
+        })
+        .collect();
+    for widget in docs
+        .values()
+        .flat_map(|d| d.root.nodes().filter(|n| n.kind == WIDGET))
+    {
+        if let Some(name) = widget.name() {
+            if !items.iter().any(|i| i.label == name.text) {
+                items.push(item(
+                    &name.text,
+                    CompletionKind::Class,
+                    "widget".to_string(),
+                ));
+            }
+        }
+    }
+
+    let owner = match path {
+        [.., owner, list]
+            if matches!(list.kind, ELEMENT_LIST | ARG_LIST) && owner.kind == ELEMENT =>
+        {
+            owner
+        }
+        _ => return items,
+    };
+    let Some(element) = owner.name() else {
+        return items;
+    };
+    match find_widget(docs, doc, &element.text) {
+        Some((_, widget)) => {
+            for prop in widget.nodes().filter(|n| n.kind == PROP_DECL) {
+                if let Some(name) = prop.name() {
+                    items.push(item(&name.text, CompletionKind::Property, signature(prop)));
+                }
+            }
+        }
+        None => {
+            for (props, kind) in [
+                (catalog::PROPS, CompletionKind::Property),
+                (catalog::EVENTS, CompletionKind::Event),
+            ] {
+                items.extend(props.iter().map(|p| CompletionItem {
+                    documentation: Some(p.doc.to_string()),
+                    ..item(p.name, kind, "prop".to_string())
+                }));
+            }
+        }
+    }
+    items
+}
+
+/// Members of the enclosing widget and `theme`
+fn value_items(path: &[&SyntaxNode]) -> Vec<CompletionItem> {
+    let mut items = vec![item(
+        "theme",
+        CompletionKind::Keyword,
+        "theme tokens".to_string(),
+    )];
+    if let Some(widget) = path.iter().find(|n| n.kind == WIDGET) {
+        for member in members(widget) {
+            if let Some(name) = member.name() {
+                let kind = match member.kind {
+                    PROP_DECL => CompletionKind::Property,
+                    DERIVED_DECL => CompletionKind::Field,
+                    _ => CompletionKind::Variable,
+                };
+                items.push(item(&name.text, kind, signature(member)));
+            }
+        }
+    }
+    items
+}
+
+// ============================================================================
+// Definition and hover
+// ============================================================================
+
+pub fn definition(docs: &Documents, doc: &Document, offset: usize) -> Option<Location> {
+    match resolve(docs, doc, offset)?.1 {
+        Target::Decl { doc, name, .. } => Some(Location {
+            uri: doc.uri.clone(),
+            range: doc.range(name.span.clone()),
+        }),
+        _ => None,
+    }
+}
+
+/// Markdown for the name at `offset`, with the span it covers
+pub fn hover(docs: &Documents, doc: &Document, offset: usize) -> Option<(String, Range<usize>)> {
+    let (span, target) = resolve(docs, doc, offset)?;
+    let markdown = match target {
+        Target::Decl { node, .. } => {
+            let mut markdown = format!("```junita\n{}\n```", signature(node));
+            if node.kind == WIDGET {
+                let props: Vec<_> = node
+                    .nodes()
+                    .filter(|n| n.kind == PROP_DECL)
+                    .map(|n| format!("- `{}`", signature(n)))
+                    .collect();
+                if !props.is_empty() {
+                    markdown.push_str("\n\nProps:\n");
+                    markdown.push_str(&props.join("\n"));
+                }
+            }
+            markdown
+        }
+        Target::Element(element) => {
+            let mut markdown = format!("```junita\n{}\n```\n{}", element.name, element.doc);
+            if !element.aliases.is_empty() {
+                let aliases: Vec<_> = element.aliases.iter().map(|a| format!("`{}`", a)).collect();
+                markdown.push_str(&format!("\n\nAlso available as {}.", aliases.join(", ")));
+            }
+            markdown
+        }
+        Target::Prop(prop) => format!("`{}`: {}", prop.name, prop.doc),
+        Target::Decorator(name, doc) => format!("`{}`: {}", name, doc),
+        Target::ThemeToken(name, group) => format!("`theme.{}`: {} token", name, group),
+    };
+    Some((markdown, span))
+}
+
+/// Find what the name at `offset` refers to
+fn resolve<'a>(
+    docs: &'a Documents,
+    doc: &'a Document,
+    offset: usize,
+) -> Option<(Range<usize>, Target<'a>)> {
+    let token = doc.root.token_at(offset)?;
+    let path = doc.root.ancestors_at(offset);
+    let node = *path.last()?;
+    let span = token.span.clone();
+
+    match token.kind {
+        DECORATOR => {
+            let (name, text) = catalog::DECORATORS.iter().find(|(d, _)| *d == token.text)?;
+            return Some((span, Target::Decorator(name, text)));
+        }
+        STRING => {
+            let span = interpolations(token)
+                .into_iter()
+                .find(|s| s.start <= offset && offset <= s.end)?;
+            let name = &doc.text[span.clone()];
+            let widget = path.iter().find(|n| n.kind == WIDGET)?;
+            return member(doc, widget, name).map(|target| (span, target));
+        }
+        IDENT => {}
+        _ => return None,
+    }
+
+    let is_name = node.name().is_some_and(|n| n.span == token.span);
+    let target = match node.kind {
+        WIDGET | PROP_DECL | STATE_DECL | DERIVED_DECL | MACHINE | ANIMATION | SPRING
+            if is_name =>
+        {
+            Target::Decl {
+                doc,
+                node,
+                name: token,
+            }
+        }
+        ELEMENT if is_name => match find_widget(docs, doc, &token.text) {
+            Some((doc, widget)) => decl(doc, widget)?,
+            None => Target::Element(catalog::element(&token.text)?),
+        },
+        NAME_REF => {
+            let widget = path.iter().find(|n| n.kind == WIDGET);
+            match widget.and_then(|w| member(doc, w, &token.text)) {
+                Some(target) => target,
+                None => {
+                    let top = doc.root.nodes().find(|n| {
+                        matches!(n.kind, MACHINE | ANIMATION | SPRING)
+                            && n.name().is_some_and(|name| name.text == token.text)
+                    })?;
+                    decl(doc, top)?
+                }
+            }
+        }
+        FIELD_EXPR => {
+            let receiver = node.nodes().next()?;
+            if receiver.kind != NAME_REF || receiver.text().trim() != "theme" {
+                return None;
+            }
+            let group = catalog::theme_token(&token.text)?;
+            Target::ThemeToken(token.text.clone(), group)
+        }
+        PROPERTY if is_name => {
+            // Props belong to the element that owns the list they're in
+            let element = match path.as_slice() {
+                [.., element, list, _] if matches!(list.kind, ELEMENT_LIST | ARG_LIST) => element,
+                _ => return None,
+            };
+            if element.kind != ELEMENT {
+                return None;
+            }
+            let element_name = element.name()?;
+            match find_widget(docs, doc, &element_name.text) {
+                Some((doc, widget)) => {
+                    let prop = widget.nodes().find(|n| {
+                        n.kind == PROP_DECL && n.name().is_some_and(|n| n.text == token.text)
+                    })?;
+                    decl(doc, prop)?
+                }
+                None => Target::Prop(catalog::prop(&token.text)?),
+            }
+        }
+        _ => return None,
+    };
+    Some((span, target))
+}
+
+fn decl<'a>(doc: &'a Document, node: &'a SyntaxNode) -> Option<Target<'a>> {
+    Some(Target::Decl {
+        doc,
+        node,
+        name: node.name()?,
+    })
+}
+
+/// Declarations a widget's expressions can read by name
+fn members(widget: &SyntaxNode) -> impl Iterator<Item = &SyntaxNode> {
+    widget.nodes().filter(|n| {
+        matches!(
+            n.kind,
+            PROP_DECL | STATE_DECL | DERIVED_DECL | MACHINE | ANIMATION | SPRING
+        )
+    })
+}
+
+fn member<'a>(doc: &'a Document, widget: &'a SyntaxNode, name: &str) -> Option<Target<'a>> {
+    let node = members(widget).find(|n| n.name().is_some_and(|n| n.text == name))?;
+    decl(doc, node)
+}
+
+/// Widget called `name`, looking in `doc` before the other open documents
+fn find_widget<'a>(
+    docs: &'a Documents,
+    doc: &'a Document,
+    name: &str,
+) -> Option<(&'a Document, &'a SyntaxNode)> {
+    std::iter::once(doc).chain(docs.values()).find_map(|doc| {
+        doc.root
+            .nodes()
+            .find(|n| n.kind == WIDGET && n.name().is_some_and(|n| n.text == name))
+            .map(|widget| (doc, widget))
+    })
+}
+
+/// Spans of the names read inside `{..}` interpolations of a string
+fn interpolations(token: &SyntaxToken) -> Vec<Range<usize>> {
+    let mut spans = Vec::new();
+    let mut inside = false;
+    let mut start = None;
+    let mut previous = '"';
+    for (i, c) in token.text.char_indices().chain([(token.text.len(), '"')]) {
+        if let Some(s) = start {
+            if c.is_alphanumeric() || c == '_' {
+                continue;
+            }
+            spans.push(token.span.start + s..token.span.start + i);
+            start = None;
+        }
+        match c {
+            '{' => inside = true,
+            '}' => inside = false,
+            // Field names after a `.` aren't members
+            c if inside && (c.is_alphabetic() || c == '_') && previous != '.' => start = Some(i),
+            _ => {}
+        }
+        previous = c;
+    }
+    spans
+}
+
+/// First line of a declaration, without its body
+fn signature(node: &SyntaxNode) -> String {
+    let text = node.text();
+    let text = text.trim();
+    let line = text.lines().next().unwrap_or_default();
+    line.split(" {")
+        .next()
+        .unwrap_or(line)
+        .trim_end()
+        .to_string()
+}
+
+// ============================================================================
+// Document symbols
+// ============================================================================
+
+pub fn document_symbols(doc: &Document) -> Vec<DocumentSymbol> {
+    doc.root
+        .nodes()
+        .filter_map(|node| symbol(doc, node))
+        .collect()
+}
+
+fn symbol(doc: &Document, node: &SyntaxNode) -> Option<DocumentSymbol> {
+    let kind = match node.kind {
+        WIDGET => SymbolKind::Class,
+        PROP_DECL => SymbolKind::Property,
+        STATE_DECL | SPRING => SymbolKind::Variable,
+        DERIVED_DECL => SymbolKind::Field,
+        MACHINE => SymbolKind::Enum,
+        ANIMATION => SymbolKind::Function,
+        RENDER | PAINT | EFFECT => SymbolKind::Method,
+        _ => return None,
+    };
+    let (name, selection) = match node.name() {
+        Some(name) => (name.text.clone(), name.span.clone()),
+        None => {
+            let decorator = node.token(DECORATOR)?;
+            (decorator.text.clone(), decorator.span.clone())
+        }
+    };
+    let children = if node.kind == WIDGET {
+        node.nodes().filter_map(|n| symbol(doc, n)).collect()
+    } else {
+        Vec::new()
+    };
+    Some(DocumentSymbol {
+        name,
+        detail: node.node(TYPE).map(|t| t.text().trim().to_string()),
+        kind,
+        range: doc.range(trimmed_span(node)),
+        selection_range: doc.range(selection),
+        children,
+    })
+}
+
+/// Span of a node without its leading and trailing trivia
+fn trimmed_span(node: &SyntaxNode) -> Range<usize> {
+    let text = node.text();
+    let start = node.span.start + (text.len() - text.trim_start().len());
+    let end = node.span.end - (text.len() - text.trim_end().len());
+    start..end.max(start)
+}
+
+// ============================================================================
+// Rename
+// ============================================================================
+
+/// The `@state` declaration named at `offset`, and the widget it's in
+fn state_at<'a>(
+    docs: &'a Documents,
+    doc: &'a Document,
+    offset: usize,
+) -> Result<(Range<usize>, &'a SyntaxNode, &'a SyntaxToken), String> {
+    let not_state = || "only `@state` variables can be renamed".to_string();
+    let (span, target) = resolve(docs, doc, offset).ok_or_else(not_state)?;
+    match target {
+        Target::Decl { node, name, .. } if node.kind == STATE_DECL => {
+            let widget = doc
+                .root
+                .nodes()
+                .find(|w| {
+                    w.kind == WIDGET
+                        && w.span.start <= node.span.start
+                        && node.span.end <= w.span.end
+                })
+                .ok_or_else(not_state)?;
+            Ok((span, widget, name))
+        }
+        _ => Err(not_state()),
+    }
+}
+
+/// Span and current name of the state variable at `offset`
+pub fn prepare_rename(
+    docs: &Documents,
+    doc: &Document,
+    offset: usize,
+) -> Result<(Range<usize>, String), String> {
+    let (span, _, name) = state_at(docs, doc, offset)?;
+    Ok((span, name.text.clone()))
+}
+
+/// Edits renaming the state variable at `offset` everywhere in its widget
+pub fn rename(
+    docs: &Documents,
+    doc: &Document,
+    offset: usize,
+    new_name: &str,
+) -> Result<Vec<TextEdit>, String> {
+    let (_, widget, name) = state_at(docs, doc, offset)?;
+
+    let mut chars = new_name.chars();
+    let valid = chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_');
+    if !valid || KEYWORDS.contains(&new_name) {
+        return Err(format!("`{}` is not a valid name", new_name));
+    }
+    if members(widget).any(|m| m.name().is_some_and(|n| n.text == new_name)) {
+        let widget_name = widget.name().map_or("", |n| n.text.as_str());
+        return Err(format!(
+            "`{}` is already declared in `{}`",
+            new_name, widget_name
+        ));
+    }
+
+    let mut spans = vec![name.span.clone()];
+    for node in widget.descendants() {
+        match node.kind {
+            NAME_REF => spans.extend(
+                node.tokens()
+                    .filter(|t| t.kind == IDENT && t.text == name.text)
+                    .map(|t| t.span.clone()),
+            ),
+            LITERAL => {
+                for string in node.tokens().filter(|t| t.kind == STRING) {
+                    spans.extend(
+                        interpolations(string)
+                            .into_iter()
+                            .filter(|s| doc.text[s.clone()] == name.text),
+                    );
+                }
+            }
+            _ => {}
+        }
+    }
+    spans.sort_by_key(|s| s.start);
+    Ok(spans
+        .into_iter()
+        .map(|span| TextEdit {
+            range: doc.range(span),
+            new_text: new_name.to_string(),
+        })
+        .collect())
+}
@@ -0,0 +1,653 @@
+//! Language server for `.junita` files
+//!
+//! `junita lsp` speaks the Language Server Protocol over stdio. It is built
+//! on the same parser and compiler as `junita check`, so the editor shows
+//! exactly the diagnostics the build reports, and adds completion,
+//! go-to-definition, hover, document symbols and renaming of `@state`
+//! variables.
+//!
+//! [`Server::handle`] maps one incoming message to the messages to send
+//! back, with no I/O, so tests drive it directly as an in-process client.
+//! [`run_stdio`] wraps it in the base protocol's framing.
+
+mod document;
+mod features;
+mod protocol;
+mod transport;
+
+use std::io::{self, BufRead, Write};
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use tracing::{debug, warn};
+
+use crate::compiler::JunitaCompiler;
+
+use document::{Document, Documents};
+use protocol::{DidChangeParams, DidOpenParams, DocumentParams, PositionParams, RenameParams};
+
+/// JSON-RPC and LSP error codes
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_NOT_INITIALIZED: i64 = -32002;
+const INVALID_REQUEST: i64 = -32600;
+const REQUEST_FAILED: i64 = -32803;
+
+/// An error response to a request
+struct ResponseError {
+    code: i64,
+    message: String,
+}
+
+impl ResponseError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+type RequestResult = Result<Value, ResponseError>;
+
+/// Serve over stdin and stdout until the client exits
+///
+/// Returns the process exit code: 0 if the client asked for shutdown
+/// before exiting, 1 otherwise.
+pub fn run_stdio() -> io::Result<i32> {
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+    Server::new().run(&mut stdin.lock(), &mut stdout.lock())
+}
+
+pub struct Server {
+    compiler: JunitaCompiler,
+    documents: Documents,
+    initialized: bool,
+    shutdown: bool,
+    exited: bool,
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self {
+            compiler: JunitaCompiler::new(),
+            documents: Documents::new(),
+            initialized: false,
+            shutdown: false,
+            exited: false,
+        }
+    }
+
+    /// Handle messages from `reader` until `exit` or end of input
+    pub fn run(&mut self, reader: &mut impl BufRead, writer: &mut impl Write) -> io::Result<i32> {
+        while let Some(message) = transport::read_message(reader)? {
+            for outgoing in self.handle(message) {
+                transport::write_message(writer, &outgoing)?;
+            }
+            if self.exited {
+                break;
+            }
+        }
+        Ok(if self.shutdown { 0 } else { 1 })
+    }
+
+    /// Handle one message, returning the responses and notifications to send
+    pub fn handle(&mut self, message: Value) -> Vec<Value> {
+        let method = message.get("method").and_then(Value::as_str);
+        let params = message.get("params").cloned().unwrap_or(Value::Null);
+        match (message.get("id").cloned(), method) {
+            (Some(id), Some(method)) => {
+                debug!("request {}: {}", id, method);
+                let response = match self.request(method, params) {
+                    Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+                    Err(error) => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": {"code": error.code, "message": error.message},
+                    }),
+                };
+                vec![response]
+            }
+            (None, Some(method)) => {
+                debug!("notification: {}", method);
+                self.notification(method, params)
+            }
+            // Responses to requests we never send
+            _ => Vec::new(),
+        }
+    }
+
+    fn request(&mut self, method: &str, params: Value) -> RequestResult {
+        match method {
+            "initialize" => {
+                self.initialized = true;
+                return Ok(capabilities());
+            }
+            _ if !self.initialized => {
+                return Err(ResponseError::new(
+                    SERVER_NOT_INITIALIZED,
+                    "server not initialized",
+                ))
+            }
+            _ if self.shutdown => {
+                return Err(ResponseError::new(
+                    INVALID_REQUEST,
+                    "server is shutting down",
+                ))
+            }
+            "shutdown" => {
+                self.shutdown = true;
+                return Ok(Value::Null);
+            }
+            _ => {}
+        }
+
+        match method {
+            "textDocument/completion" => {
+                let params: PositionParams = parse_params(params)?;
+                let (doc, offset) = self.locate(&params)?;
+                let items = features::completion(&self.documents, doc, offset);
+                Ok(json!(items))
+            }
+            "textDocument/definition" => {
+                let params: PositionParams = parse_params(params)?;
+                let (doc, offset) = self.locate(&params)?;
+                Ok(json!(features::definition(&self.documents, doc, offset)))
+            }
+            "textDocument/hover" => {
+                let params: PositionParams = parse_params(params)?;
+                let (doc, offset) = self.locate(&params)?;
+                Ok(match features::hover(&self.documents, doc, offset) {
+                    Some((markdown, span)) => json!({
+                        "contents": {"kind": "markdown", "value": markdown},
+                        "range": doc.range(span),
+                    }),
+                    None => Value::Null,
+                })
+            }
+            "textDocument/documentSymbol" => {
+                let params: DocumentParams = parse_params(params)?;
+                let doc = self.document(&params.text_document.uri)?;
+                Ok(json!(features::document_symbols(doc)))
+            }
+            "textDocument/prepareRename" => {
+                let params: PositionParams = parse_params(params)?;
+                let (doc, offset) = self.locate(&params)?;
+                let (span, name) = features::prepare_rename(&self.documents, doc, offset)
+                    .map_err(|message| ResponseError::new(REQUEST_FAILED, message))?;
+                Ok(json!({"range": doc.range(span), "placeholder": name}))
+            }
+            "textDocument/rename" => {
+                let params: RenameParams = parse_params(params)?;
+                let doc = self.document(&params.text_document.uri)?;
+                let offset = doc.offset(params.position);
+                let edits = features::rename(&self.documents, doc, offset, &params.new_name)
+                    .map_err(|message| ResponseError::new(REQUEST_FAILED, message))?;
+                let mut changes = serde_json::Map::new();
+                changes.insert(doc.uri.clone(), json!(edits));
+                Ok(json!({"changes": changes}))
+            }
+            _ => Err(ResponseError::new(
+                METHOD_NOT_FOUND,
+                format!("unsupported method `{}`", method),
+            )),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: Value) -> Vec<Value> {
+        match method {
+            "exit" => {
+                self.exited = true;
+                Vec::new()
+            }
+            "textDocument/didOpen" => {
+                let Ok(params) = parse_params::<DidOpenParams>(params) else {
+                    return Vec::new();
+                };
+                let item = params.text_document;
+                self.open(item.uri, item.version, item.text)
+            }
+            "textDocument/didChange" => {
+                let Ok(params) = parse_params::<DidChangeParams>(params) else {
+                    return Vec::new();
+                };
+                // Full sync: the last change holds the whole document
+                match params.content_changes.into_iter().last() {
+                    Some(change) => {
+                        let id = params.text_document;
+                        self.open(id.uri, id.version, change.text)
+                    }
+                    None => Vec::new(),
+                }
+            }
+            "textDocument/didClose" => {
+                let Ok(params) = parse_params::<DocumentParams>(params) else {
+                    return Vec::new();
+                };
+                let uri = params.text_document.uri;
+                self.documents.remove(&uri);
+                vec![publish(&uri, None, Vec::new())]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    /// Store a new version of a document and publish its diagnostics
+    fn open(&mut self, uri: String, version: i32, text: String) -> Vec<Value> {
+        let doc = Document::new(&self.compiler, uri.clone(), text);
+        let diagnostics = features::diagnostics(&doc);
+        self.documents.insert(uri.clone(), doc);
+        vec![publish(&uri, Some(version), diagnostics)]
+    }
+
+    fn document(&self, uri: &str) -> Result<&Document, ResponseError> {
+        self.documents.get(uri).ok_or_else(|| {
+            warn!("request for unopened document {}", uri);
+            ResponseError::new(INVALID_PARAMS, format!("document `{}` is not open", uri))
+        })
+    }
+
+    fn locate(&self, params: &PositionParams) -> Result<(&Document, usize), ResponseError> {
+        let doc = self.document(&params.text_document.uri)?;
+        Ok((doc, doc.offset(params.position)))
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: Value) -> Result<T, ResponseError> {
+    serde_json::from_value(params)
+        .map_err(|e| ResponseError::new(INVALID_PARAMS, format!("invalid params: {}", e)))
+}
+
+fn publish(uri: &str, version: Option<i32>, diagnostics: Vec<protocol::Diagnostic>) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "textDocument/publishDiagnostics",
+        "params": {"uri": uri, "version": version, "diagnostics": diagnostics},
+    })
+}
+
+fn capabilities() -> Value {
+    json!({
+        "capabilities": {
+            // Full document sync
+            "textDocumentSync": 1,
+            "completionProvider": {"triggerCharacters": ["@", ".", ":"]},
+            "definitionProvider": true,
+            "hoverProvider": true,
+            "documentSymbolProvider": true,
+            "renameProvider": {"prepareProvider": true},
+        },
+        "serverInfo": {"name": "junita", "version": env!("CARGO_PKG_VERSION")},
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const URI: &str = "file:///app/src/main.junita";
+
+    const SOURCE: &str = r#"@widget Counter {
+    @prop step: i32 = 1
+    @state count: i32 = 0
+    @derived doubled: i32 = count * 2
+
+    @machine hover {
+        initial: idle
+        idle -> active: pointer_enter
+    }
+
+    @render {
+        Column {
+            spacing: 16
+
+            Text {
+                content: "Count: {count}"
+            }
+
+            Button {
+                label: "+"
+                on_click: { count += step }
+            }
+        }
+    }
+}
+
+@widget App {
+    @render {
+        Counter {
+            step: 2
+        }
+    }
+}
+"#;
+
+    /// In-process client
+    struct Client {
+        server: Server,
+        next_id: i64,
+    }
+
+    impl Client {
+        fn new() -> Self {
+            let mut client = Self {
+                server: Server::new(),
+                next_id: 0,
+            };
+            let result = client.request("initialize", json!({"capabilities": {}}));
+            assert_eq!(result["capabilities"]["textDocumentSync"], 1);
+            client.notify("initialized", json!({}));
+            client
+        }
+
+        fn request(&mut self, method: &str, params: Value) -> Value {
+            let response = self.send(method, params);
+            assert!(response.get("error").is_none(), "{}", response);
+            response["result"].clone()
+        }
+
+        fn send(&mut self, method: &str, params: Value) -> Value {
+            self.next_id += 1;
+            let mut responses = self.server.handle(json!({
+                "jsonrpc": "2.0",
+                "id": self.next_id,
+                "method": method,
+                "params": params,
+            }));
+            assert_eq!(responses.len(), 1);
+            let response = responses.remove(0);
+            assert_eq!(response["id"], self.next_id);
+            response
+        }
+
+        fn notify(&mut self, method: &str, params: Value) -> Vec<Value> {
+            self.server
+                .handle(json!({"jsonrpc": "2.0", "method": method, "params": params}))
+        }
+
+        fn open(&mut self, uri: &str, text: &str) -> Vec<Value> {
+            let mut published = self.notify(
+                "textDocument/didOpen",
+                json!({"textDocument": {
+                    "uri": uri, "languageId": "junita", "version": 1, "text": text,
+                }}),
+            );
+            assert_eq!(published.len(), 1);
+            assert_eq!(published[0]["params"]["uri"], uri);
+            published.remove(0)["params"]["diagnostics"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default()
+        }
+
+        /// Params pointing at the `nth` occurrence of `needle`, plus `shift`
+        fn at(&self, needle: &str, nth: usize, shift: usize) -> Value {
+            let doc = &self.server.documents[URI];
+            let offset = doc.text.match_indices(needle).nth(nth).unwrap().0 + shift;
+            json!({"textDocument": {"uri": URI}, "position": doc.position(offset)})
+        }
+    }
+
+    fn labels(items: &Value) -> Vec<String> {
+        items
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| i["label"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_lifecycle() {
+        let mut client = Client {
+            server: Server::new(),
+            next_id: 0,
+        };
+        let early = client.send("textDocument/hover", json!({}));
+        assert_eq!(early["error"]["code"], SERVER_NOT_INITIALIZED);
+
+        client.request("initialize", json!({}));
+        let unknown = client.send("textDocument/formatting", json!({}));
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+
+        // Responses from the client produce nothing
+        assert!(client
+            .server
+            .handle(json!({"id": 7, "result": null}))
+            .is_empty());
+
+        let framed = |method: &str, id: Option<i64>| {
+            let body = json!({"jsonrpc": "2.0", "id": id, "method": method}).to_string();
+            format!("Content-Length: {}\r\n\r\n{}", body.len(), body)
+        };
+        let input = framed("shutdown", Some(2)) + &framed("exit", None);
+        let mut output = Vec::new();
+        let code = client
+            .server
+            .run(&mut Cursor::new(input.into_bytes()), &mut output)
+            .unwrap();
+        assert_eq!(code, 0);
+        let reply = transport::read_message(&mut Cursor::new(output))
+            .unwrap()
+            .unwrap();
+        assert_eq!(reply["id"], 2);
+        assert_eq!(reply["result"], Value::Null);
+    }
+
+    #[test]
+    fn test_publishes_diagnostics() {
+        let mut client = Client::new();
+        assert!(client.open(URI, SOURCE).is_empty());
+
+        let broken = "@widget A {\n    @state x i32 = 0\n}\n@widget A {}\n";
+        let diagnostics = client.open(URI, broken);
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(diagnostics[0]["severity"], 1);
+        assert_eq!(
+            diagnostics[0]["range"]["start"],
+            json!({"line": 1, "character": 13})
+        );
+        assert!(diagnostics[0]["message"]
+            .as_str()
+            .unwrap()
+            .starts_with("expected `:`"));
+        let related = &diagnostics[1]["relatedInformation"][0];
+        assert_eq!(related["message"], "first defined here");
+        assert_eq!(related["location"]["range"]["start"]["line"], 0);
+
+        let closed = client.notify(
+            "textDocument/didClose",
+            json!({"textDocument": {"uri": URI}}),
+        );
+        assert_eq!(closed[0]["params"]["diagnostics"], json!([]));
+    }
+
+    #[test]
+    fn test_completion() {
+        let mut client = Client::new();
+        let source = SOURCE.replace("spacing: 16", "spacing: theme.sp\n            Te");
+        client.open(URI, &source);
+
+        let tokens = client.request("textDocument/completion", client.at("theme.sp", 0, 8));
+        let tokens = labels(&tokens);
+        assert!(tokens.contains(&"space_4".to_string()));
+        assert!(tokens.contains(&"primary".to_string()));
+
+        let elements = client.request("textDocument/completion", client.at("Te\n", 0, 2));
+        let elements = labels(&elements);
+        for expected in ["Text", "Column", "Counter", "App", "padding", "on_click"] {
+            assert!(elements.contains(&expected.to_string()), "{}", expected);
+        }
+
+        // Values see the widget's members
+        let values = client.request("textDocument/completion", client.at("count * 2", 0, 0));
+        let values = labels(&values);
+        for expected in ["count", "step", "doubled", "hover", "theme"] {
+            assert!(values.contains(&expected.to_string()), "{}", expected);
+        }
+
+        // A widget's own props when using it
+        let props = client.request("textDocument/completion", client.at("step: 2", 0, 0));
+        assert!(labels(&props).contains(&"step".to_string()));
+        assert!(!labels(&props).contains(&"padding".to_string()));
+    }
+
+    #[test]
+    fn test_decorator_completion() {
+        let mut client = Client::new();
+        client.open(URI, "@\n@widget A {\n    @\n}\n");
+        let top = client.request("textDocument/completion", client.at("@", 0, 1));
+        assert_eq!(
+            labels(&top),
+            vec!["@widget", "@machine", "@animation", "@spring"]
+        );
+        let nested = client.request("textDocument/completion", client.at("@\n}", 0, 1));
+        assert!(labels(&nested).contains(&"@state".to_string()));
+        assert_eq!(nested[1]["insertText"], "state");
+    }
+
+    #[test]
+    fn test_definition() {
+        let mut client = Client::new();
+        client.open(URI, SOURCE);
+        let declared = |client: &Client, needle: &str| client.at(needle, 0, 0)["position"].clone();
+
+        // Widget used as an element
+        let location = client.request("textDocument/definition", client.at("Counter {", 0, 2));
+        assert_eq!(location["uri"], URI);
+        assert_eq!(location["range"]["start"], declared(&client, "Counter {"));
+
+        // State read in an expression and in a string
+        let location = client.request("textDocument/definition", client.at("count += step", 0, 1));
+        assert_eq!(location["range"]["start"], declared(&client, "count: i32"));
+        let location = client.request("textDocument/definition", client.at("{count}", 0, 3));
+        assert_eq!(location["range"]["start"], declared(&client, "count: i32"));
+
+        // A widget prop set by its user
+        let location = client.request("textDocument/definition", client.at("step: 2", 0, 0));
+        assert_eq!(location["range"]["start"], declared(&client, "step: i32"));
+
+        // Built-ins have no definition
+        let location = client.request("textDocument/definition", client.at("Column", 0, 0));
+        assert_eq!(location, Value::Null);
+    }
+
+    #[test]
+    fn test_definition_across_documents() {
+        let mut client = Client::new();
+        client.open("file:///app/src/counter.junita", "@widget Badge {}\n");
+        client.open(URI, "@widget App { @render { Badge {} } }\n");
+        let location = client.request("textDocument/definition", client.at("Badge", 0, 0));
+        assert_eq!(location["uri"], "file:///app/src/counter.junita");
+    }
+
+    #[test]
+    fn test_hover() {
+        let mut client = Client::new();
+        client.open(URI, SOURCE);
+        let hover = |client: &mut Client, needle: &str| {
+            let params = client.at(needle, 0, 1);
+            let result = client.request("textDocument/hover", params);
+            result["contents"]["value"]
+                .as_str()
+                .unwrap_or_default()
+                .to_string()
+        };
+
+        let column = hover(&mut client, "Column");
+        assert!(column.contains("top to bottom"));
+        assert!(column.contains("`VStack`"));
+        assert!(hover(&mut client, "spacing").contains("between children"));
+        assert!(hover(&mut client, "count *").contains("@state count: i32 = 0"));
+        assert!(hover(&mut client, "@state").contains("Reactive state"));
+
+        let counter = hover(&mut client, "Counter {\n            step");
+        assert!(counter.contains("@widget Counter"));
+        assert!(counter.contains("- `@prop step: i32 = 1`"));
+    }
+
+    #[test]
+    fn test_document_symbols() {
+        let mut client = Client::new();
+        client.open(URI, SOURCE);
+        let symbols = client.request(
+            "textDocument/documentSymbol",
+            json!({"textDocument": {"uri": URI}}),
+        );
+        assert_eq!(labels_by(&symbols, "name"), vec!["Counter", "App"]);
+
+        let counter = &symbols[0];
+        assert_eq!(counter["kind"], 5);
+        assert_eq!(
+            labels_by(&counter["children"], "name"),
+            vec!["step", "count", "doubled", "hover", "@render"]
+        );
+        assert_eq!(counter["children"][1]["detail"], "i32");
+        assert_eq!(
+            counter["range"]["start"],
+            json!({"line": 0, "character": 0})
+        );
+    }
+
+    fn labels_by(items: &Value, key: &str) -> Vec<String> {
+        items
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|i| i[key].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_rename_state() {
+        let mut client = Client::new();
+        client.open(URI, SOURCE);
+
+        let prepared = client.request("textDocument/prepareRename", client.at("count +=", 0, 0));
+        assert_eq!(prepared["placeholder"], "count");
+
+        let mut params = client.at("count: i32", 0, 0);
+        params["newName"] = json!("total");
+        let edit = client.request("textDocument/rename", params);
+        let edits = edit["changes"][URI].as_array().unwrap();
+        // Declaration, derived value, interpolation and handler
+        assert_eq!(edits.len(), 4);
+
+        let doc = &client.server.documents[URI];
+        let mut text = doc.text.clone();
+        for edit in edits.iter().rev() {
+            let start = doc.offset(serde_json::from_value(edit["range"]["start"].clone()).unwrap());
+            let end = doc.offset(serde_json::from_value(edit["range"]["end"].clone()).unwrap());
+            text.replace_range(start..end, edit["newText"].as_str().unwrap());
+        }
+        assert!(text.contains("@state total: i32 = 0"));
+        assert!(text.contains("total * 2"));
+        assert!(text.contains("\"Count: {total}\""));
+        assert!(text.contains("total += step"));
+        assert!(!text.contains("count"));
+    }
+
+    #[test]
+    fn test_rename_rejections() {
+        let mut client = Client::new();
+        client.open(URI, SOURCE);
+
+        // Props aren't state
+        let response = client.send("textDocument/prepareRename", client.at("step: i32", 0, 0));
+        assert_eq!(response["error"]["code"], REQUEST_FAILED);
+
+        let mut params = client.at("count: i32", 0, 0);
+        params["newName"] = json!("doubled");
+        let response = client.send("textDocument/rename", params.clone());
+        assert_eq!(
+            response["error"]["message"],
+            "`doubled` is already declared in `Counter`"
+        );
+
+        params["newName"] = json!("2x");
+        let response = client.send("textDocument/rename", params);
+        assert_eq!(response["error"]["message"], "`2x` is not a valid name");
+    }
+}
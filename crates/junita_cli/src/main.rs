@@ -13,7 +13,11 @@ mod config;
 mod doctor;
 mod project;
 mod hot_reload;
-mod compiler;
+mod catalog;
+mod formatter;
+mod lsp;
+
+use junita_cli::{compiler, syntax};
 
 use config::JunitaConfig;
 
@@ -119,6 +123,26 @@ enum Commands {
         source: String,
     },
 
+    /// Format .junita files and stylesheets
+    Fmt {
+        /// Source file or directory
+        #[arg(default_value = ".")]
+        source: String,
+
+        /// Report unformatted files instead of rewriting them
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Work with recorded regression tests
+    Test {
+        #[command(subcommand)]
+        command: TestCommands,
+    },
+
+    /// Run the language server over stdio
+    Lsp,
+
     /// Show toolchain and target information
     Info,
 
@@ -126,6 +150,52 @@ enum Commands {
     Doctor,
 }
 
+#[derive(Subcommand)]
+enum TestCommands {
+    /// Generate a Rust regression test from a recording
+    Gen {
+        /// Recording exported by the recorder or debugger (JSON or binary)
+        recording: String,
+
+        /// Output file (prints to stdout if omitted)
+        #[arg(short, long)]
+        output: Option<String>,
+
+        /// Test function name (defaults to the recording file name)
+        #[arg(short, long)]
+        name: Option<String>,
+
+        /// Path of the function returning the app under test
+        #[arg(long, default_value = "app_under_test")]
+        app: String,
+
+        /// Allowed bounds difference in pixels
+        #[arg(long, default_value = "0.5")]
+        tolerance: f32,
+
+        /// Property to leave out of assertions (repeatable)
+        #[arg(long)]
+        ignore: Vec<String>,
+
+        /// Also assert on elements without an ID or text
+        #[arg(long)]
+        all: bool,
+
+        /// Maximum number of snapshot assertions
+        #[arg(long, default_value = "16")]
+        max_snapshots: usize,
+    },
+
+    /// Convert a recording between the JSON and binary formats
+    Convert {
+        /// Recording to convert; binary files become JSON and vice versa
+        input: String,
+
+        /// Output file
+        output: String,
+    },
+}
+
 #[derive(Subcommand)]
 enum PluginCommands {
     /// Build a plugin
@@ -156,8 +226,18 @@ fn main() -> Result<()> {
         EnvFilter::new("info")
     };
 
+    // The language server owns stdout, so its logs go to stderr, where
+    // editors show them verbatim. Generated tests may be printed to stdout
+    // too.
+    let lsp = matches!(cli.command, Commands::Lsp);
+    let writer = if lsp || matches!(cli.command, Commands::Test { .. }) {
+        fmt::writer::BoxMakeWriter::new(std::io::stderr)
+    } else {
+        fmt::writer::BoxMakeWriter::new(std::io::stdout)
+    };
+
     tracing_subscriber::registry()
-        .with(fmt::layer())
+        .with(fmt::layer().with_writer(writer).with_ansi(!lsp))
         .with(filter)
         .init();
 
@@ -194,6 +274,32 @@ fn main() -> Result<()> {
 
         Commands::Check { source } => cmd_check(&source),
 
+        Commands::Fmt { source, check } => cmd_fmt(&source, check),
+
+        Commands::Test { command } => match command {
+            TestCommands::Gen {
+                recording,
+                output,
+                name,
+                app,
+                tolerance,
+                ignore,
+                all,
+                max_snapshots,
+            } => {
+                let mut config = junita_recorder::TestGenConfig::default()
+                    .with_app_factory(app)
+                    .with_bounds_tolerance(tolerance)
+                    .with_unnamed(all)
+                    .with_max_snapshots(max_snapshots);
+                config.ignored_properties = ignore;
+                cmd_test_gen(&recording, output.as_deref(), name, config)
+            }
+            TestCommands::Convert { input, output } => cmd_test_convert(&input, &output),
+        },
+
+        Commands::Lsp => cmd_lsp(),
+
         Commands::Info => cmd_info(),
 
         Commands::Doctor => cmd_doctor(),
@@ -238,6 +344,8 @@ fn cmd_build(source: &str, target: &str, release: bool, output: Option<&str>) ->
 }
 
 fn cmd_dev(source: &str, target: &str, port: u16, device: Option<&str>) -> Result<()> {
+    use junita_runtime::interpreter::{self, WindowConfig};
+
     let path = PathBuf::from(source);
     let config = JunitaConfig::load_from_dir(&path)?;
 
@@ -250,32 +358,50 @@ fn cmd_dev(source: &str, target: &str, port: u16, device: Option<&str>) -> Resul
         info!("Running on device: {}", dev);
     }
 
-    // Initialize hot reload system
+    if target != "desktop" {
+        anyhow::bail!(
+            "Dev mode runs the app in a desktop window; target '{}' is not supported yet",
+            target
+        );
+    }
+
+    let entry = entry_file(&path)?;
     let runtime = tokio::runtime::Runtime::new()?;
-    runtime.block_on(async {
-        if let Err(e) = start_dev_server(&path, target, port).await {
+    let mut compiler = compiler::JunitaCompiler::new();
+    let artifact = runtime.block_on(compiler.compile(&entry))?;
+
+    // Watch and recompile on the runtime's threads; the window needs this one
+    let (reloader, reloads) = interpreter::reload_channel();
+    runtime.spawn(async move {
+        if let Err(e) = start_dev_server(&path, &entry, compiler, reloader).await {
             error!("Dev server error: {}", e);
-            return Err(e);
         }
-        Ok(())
-    })
+    });
+
+    let window = WindowConfig {
+        title: config.project.name,
+        ..WindowConfig::default()
+    };
+    interpreter::run(artifact, window, Some(reloads))
 }
 
-async fn start_dev_server(project_path: &Path, target: &str, port: u16) -> Result<()> {
-    use crate::hot_reload::HotReloadConfig;
+/// Recompile `entry` whenever a source under `project_path` changes and
+/// hand the result to the running window
+async fn start_dev_server(
+    project_path: &Path,
+    entry: &Path,
+    mut compiler: compiler::JunitaCompiler,
+    reloader: junita_runtime::interpreter::Reloader,
+) -> Result<()> {
+    use crate::hot_reload::{FileWatcher, HotReloadConfig, HotReloadMessage};
+    use tokio::sync::broadcast::error::RecvError;
 
     info!("Initializing hot reload server...");
 
-    // Create hot reload server
-    let watch_dir = project_path.to_path_buf();
     let config = HotReloadConfig {
-        watch_dir: watch_dir.clone(),
+        watch_dir: project_path.to_path_buf(),
         debounce_ms: 300,
-        watch_extensions: vec![
-            "junita".to_string(),
-            "rs".to_string(),
-            "toml".to_string(),
-        ],
+        watch_extensions: vec!["junita".to_string(), "bl".to_string()],
         ..Default::default()
     };
 
@@ -284,29 +410,77 @@ async fn start_dev_server(project_path: &Path, target: &str, port: u16) -> Resul
     info!("  Debounce: {}ms", config.debounce_ms);
     info!("  Extensions: {:?}", config.watch_extensions);
 
-    // TODO: When Zyntax is ready:
-    // 1. Initial project compilation
-    // 2. Start the rendering window/app
-    // 3. Connect hot reload receiver
-    // 4. Poll for updates and apply diffs
-
-    warn!("Dev server waiting for Zyntax Grammar2 integration");
-    info!("File watching is configured and ready");
+    let (watcher, mut updates) = FileWatcher::new(config)?;
+    tokio::spawn(async move {
+        if let Err(e) = watcher.start().await {
+            error!("File watcher error: {}", e);
+        }
+    });
     info!("Waiting for file changes...");
 
-    // For now, just log that we're ready
-    info!("Dev server ready on port {}", port);
-
-    Ok(())
+    loop {
+        let changed_files = match updates.recv().await {
+            Ok(HotReloadMessage::Update { changed_files, .. }) => changed_files,
+            Ok(_) | Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return Ok(()),
+        };
+        info!(
+            "Recompiling {} after {} change(s)",
+            entry.display(),
+            changed_files.len()
+        );
+        match compiler.compile(entry).await {
+            Ok(artifact) => {
+                if !reloader.reload(artifact) {
+                    // The window was closed
+                    return Ok(());
+                }
+            }
+            // The window keeps the last version that compiled
+            Err(e) => error!("{}", e),
+        }
+    }
 }
 
 fn cmd_run(source: &str) -> Result<()> {
-    info!("Running {}", source);
-
-    // TODO: Execute compiled binary or interpret source
-    warn!("Run not yet implemented - waiting for Zyntax Runtime2");
+    use junita_runtime::interpreter::{self, WindowConfig};
+
+    let entry = entry_file(Path::new(source))?;
+    info!("Running {}", entry.display());
+
+    let artifact = futures::executor::block_on(compiler::JunitaCompiler::new().compile(&entry))?;
+    let title = entry
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or("Junita")
+        .to_string();
+    let window = WindowConfig {
+        title,
+        ..WindowConfig::default()
+    };
+    interpreter::run(artifact, window, None)
+}
 
-    Ok(())
+/// The `.junita` file to run for `source`: the file itself, a project's
+/// `src/main.junita` or `main.junita`, or its only source file
+fn entry_file(source: &Path) -> Result<PathBuf> {
+    if source.is_file() {
+        return Ok(source.to_path_buf());
+    }
+    for candidate in ["src/main.junita", "main.junita"] {
+        let path = source.join(candidate);
+        if path.is_file() {
+            return Ok(path);
+        }
+    }
+    match compiler::find_sources(source)?.as_slice() {
+        [only] => Ok(only.clone()),
+        [] => anyhow::bail!("No .junita files in {}", source.display()),
+        _ => anyhow::bail!(
+            "{} has several .junita files and no main.junita; pass the one to run",
+            source.display()
+        ),
+    }
 }
 
 fn cmd_plugin_build(path: &str, mode: &str) -> Result<()> {
@@ -407,14 +581,186 @@ fn cmd_init(template: &str, org: &str) -> Result<()> {
 }
 
 fn cmd_check(source: &str) -> Result<()> {
+    use anyhow::Context;
+    use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+    use std::io::IsTerminal;
+
     let path = PathBuf::from(source);
-    let config = JunitaConfig::load_from_dir(&path)?;
+    let files = if path.is_file() {
+        vec![path]
+    } else {
+        let config = JunitaConfig::load_from_dir(&path)?;
+        info!("Checking project: {}", config.project.name);
+        compiler::find_sources(&path)?
+    };
+
+    let compiler = compiler::JunitaCompiler::new();
+    let color = if std::io::stderr().is_terminal() {
+        ColorChoice::Auto
+    } else {
+        ColorChoice::Never
+    };
+    let mut stderr = StandardStream::stderr(color);
+    let mut errors = 0;
+    let mut warnings = 0;
+
+    for file in &files {
+        let source = fs::read_to_string(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+        let (_, diagnostics) = compiler.compile_source(&source, file);
+        syntax::emit(&mut stderr, &file.display().to_string(), &source, &diagnostics)?;
+
+        let file_errors = diagnostics.iter().filter(|d| d.is_error()).count();
+        errors += file_errors;
+        warnings += diagnostics.len() - file_errors;
+    }
+
+    info!(
+        "Checked {} file(s): {} error(s), {} warning(s)",
+        files.len(),
+        errors,
+        warnings
+    );
+    if errors > 0 {
+        anyhow::bail!("Check failed with {} error(s)", errors);
+    }
+
+    Ok(())
+}
+
+fn cmd_fmt(source: &str, check: bool) -> Result<()> {
+    use anyhow::Context;
+    use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
+    use std::io::{IsTerminal, Write};
+
+    let path = PathBuf::from(source);
+    let files = if path.is_file() {
+        vec![path]
+    } else {
+        compiler::find_files(&path, formatter::EXTENSIONS)?
+    };
+
+    let color = if std::io::stderr().is_terminal() {
+        ColorChoice::Auto
+    } else {
+        ColorChoice::Never
+    };
+    let mut stderr = StandardStream::stderr(color);
+    let mut failed = 0;
+    let mut unformatted = 0;
+
+    for file in &files {
+        let source = fs::read_to_string(file)
+            .with_context(|| format!("Failed to read {}", file.display()))?;
+        let formatted = match formatter::format(file, &source) {
+            Ok(formatted) => formatted,
+            Err(formatter::Error::Junita(diagnostics)) => {
+                syntax::emit(&mut stderr, &file.display().to_string(), &source, &diagnostics)?;
+                failed += 1;
+                continue;
+            }
+            Err(formatter::Error::Css(err)) => {
+                writeln!(stderr, "{}: {}", file.display(), err)?;
+                failed += 1;
+                continue;
+            }
+        };
+        if formatted == source {
+            continue;
+        }
+
+        unformatted += 1;
+        if check {
+            println!("{}", file.display());
+        } else {
+            fs::write(file, formatted)
+                .with_context(|| format!("Failed to write {}", file.display()))?;
+            info!("Formatted {}", file.display());
+        }
+    }
+
+    if failed > 0 {
+        anyhow::bail!("{} file(s) could not be parsed", failed);
+    }
+    if check && unformatted > 0 {
+        anyhow::bail!("{} of {} file(s) need formatting", unformatted, files.len());
+    }
+    if check {
+        info!("{} file(s) already formatted", files.len());
+    } else {
+        info!("Formatted {} of {} file(s)", unformatted, files.len());
+    }
+    Ok(())
+}
+
+fn cmd_test_gen(
+    recording: &str,
+    output: Option<&str>,
+    name: Option<String>,
+    mut config: junita_recorder::TestGenConfig,
+) -> Result<()> {
+    use anyhow::Context;
+
+    let path = Path::new(recording);
+    let export = junita_recorder::load_recording(path)
+        .with_context(|| format!("Failed to read recording {}", recording))?;
+
+    let stem = path.file_stem().map(|s| s.to_string_lossy().into_owned());
+    if let Some(name) = name.or(stem) {
+        config.test_name = name;
+    }
+    if let Some(name) = path.file_name() {
+        config.source = Some(name.to_string_lossy().into_owned());
+    }
 
-    info!("Checking project: {}", config.project.name);
+    if export.snapshots.is_empty() {
+        warn!("{} has no tree snapshots; the test will only replay input", recording);
+    }
+    let source = junita_recorder::generate_test(&export, &config);
+
+    match output {
+        Some(out) => {
+            fs::write(out, source).with_context(|| format!("Failed to write {}", out))?;
+            info!(
+                "Generated test from {} event(s) and {} snapshot(s) in {}",
+                export.events.len(),
+                export.snapshots.len(),
+                out
+            );
+        }
+        None => print!("{}", source),
+    }
+    Ok(())
+}
+
+fn cmd_test_convert(input: &str, output: &str) -> Result<()> {
+    use anyhow::Context;
+    use junita_recorder::format;
+
+    let binary = format::is_binary_recording_file(input)
+        .with_context(|| format!("Failed to read {}", input))?;
+    if binary {
+        format::binary_to_json(input, output)
+    } else {
+        format::json_to_binary(input, output)
+    }
+    .with_context(|| format!("Failed to convert {}", input))?;
 
-    // TODO: Parse and validate all .junita files
-    warn!("Check not yet implemented - waiting for Zyntax Grammar2");
+    info!(
+        "Converted {} to {} ({})",
+        input,
+        output,
+        if binary { "JSON" } else { "binary" }
+    );
+    Ok(())
+}
 
+fn cmd_lsp() -> Result<()> {
+    info!("Starting language server");
+    let code = lsp::run_stdio()?;
+    if code != 0 {
+        std::process::exit(code);
+    }
     Ok(())
 }
 
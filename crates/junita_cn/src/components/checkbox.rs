@@ -264,6 +264,10 @@ impl ElementBuilder for Checkbox {
     fn layout_style(&self) -> Option<&taffy::Style> {
         self.inner.layout_style()
     }
+
+    fn event_handlers(&self) -> Option<&junita_layout::event_handler::EventHandlers> {
+        ElementBuilder::event_handlers(&self.inner)
+    }
 }
 
 /// Internal configuration for building a Checkbox
@@ -418,6 +422,10 @@ impl ElementBuilder for CheckboxBuilder {
     fn layout_style(&self) -> Option<&taffy::Style> {
         self.get_or_build().layout_style()
     }
+
+    fn event_handlers(&self) -> Option<&junita_layout::event_handler::EventHandlers> {
+        self.get_or_build().event_handlers()
+    }
 }
 
 /// Create a checkbox with state from context
@@ -459,4 +467,23 @@ mod tests {
         assert_eq!(CheckboxSize::Medium.checkmark_size(), 12.0);
         assert_eq!(CheckboxSize::Large.checkmark_size(), 16.0);
     }
+
+    #[test]
+    fn test_clicking_label_toggles_state() {
+        use junita_test::{state, Query, TestApp};
+
+        let checked = state(false);
+        let mut app = TestApp::mount(300.0, 100.0, {
+            let checked = checked.clone();
+            move || div().child(checkbox(&checked).label("Accept terms"))
+        });
+
+        app.click(Query::by_text("Accept terms"));
+        app.settle();
+        assert!(checked.get());
+
+        app.click(Query::by_text("Accept terms"));
+        app.settle();
+        assert!(!checked.get());
+    }
 }
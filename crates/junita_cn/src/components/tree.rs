@@ -152,12 +152,16 @@ impl ElementBuilder for TreeView {
 /// Callback for selection events
 type SelectCallback = Arc<dyn Fn(&str) + Send + Sync>;
 
+/// Callback for hover events (`None` when the pointer leaves a row)
+type HoverCallback = Arc<dyn Fn(Option<&str>) + Send + Sync>;
+
 /// Builder for creating TreeView components
 pub struct TreeViewBuilder {
     instance_key: InstanceKey,
     nodes: Vec<TreeNodeConfig>,
     selected_key: Option<String>,
     on_select: Option<SelectCallback>,
+    on_hover: Option<HoverCallback>,
     indent_size: f32,
     show_guides: bool,
     built: OnceCell<TreeView>,
@@ -171,6 +175,7 @@ impl TreeViewBuilder {
             nodes: Vec::new(),
             selected_key: None,
             on_select: None,
+            on_hover: None,
             indent_size: 4.0,
             show_guides: false,
             built: OnceCell::new(),
@@ -212,6 +217,18 @@ impl TreeViewBuilder {
         self
     }
 
+    /// Set hover callback
+    ///
+    /// Called with the row's key when the pointer enters it, and with `None`
+    /// when it leaves.
+    pub fn on_hover<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(Option<&str>) + Send + Sync + 'static,
+    {
+        self.on_hover = Some(Arc::new(callback));
+        self
+    }
+
     /// Set indent size per level (default: 16.0)
     pub fn indent(mut self, size: f32) -> Self {
         self.indent_size = size;
@@ -295,6 +312,7 @@ impl TreeViewBuilder {
         let indent_size = self.indent_size;
         let show_guides = self.show_guides;
         let on_select = self.on_select.clone();
+        let on_hover = self.on_hover.clone();
         let container_key = format!("{}_container", self.instance_key.get());
 
         let container_state = use_shared_state_with(&container_key, ());
@@ -326,6 +344,7 @@ impl TreeViewBuilder {
                         expand_states: &[(String, State<bool>, SharedAnimatedValue)],
                         selected: &State<Option<String>>,
                         on_select: &Option<SelectCallback>,
+                        on_hover: &Option<HoverCallback>,
                         text_primary: Color,
                         text_secondary: Color,
                         text_tertiary: Color,
@@ -407,6 +426,15 @@ impl TreeViewBuilder {
                                 }
                             });
 
+                        if let Some(cb) = on_hover {
+                            let enter_key = node.key.clone();
+                            let on_enter = cb.clone();
+                            let on_leave = cb.clone();
+                            row = row
+                                .on_hover_enter(move |_| on_enter(Some(&enter_key)))
+                                .on_hover_leave(move |_| on_leave(None));
+                        }
+
                         // Expand/collapse chevron (if has children)
                         if has_children {
                             let chevron = if is_expanded {
@@ -519,6 +547,7 @@ impl TreeViewBuilder {
                                     expand_states,
                                     selected,
                                     on_select,
+                                    on_hover,
                                     text_primary,
                                     text_secondary,
                                     text_tertiary,
@@ -546,6 +575,7 @@ impl TreeViewBuilder {
                             &expand_states,
                             &selected,
                             &on_select,
+                            &on_hover,
                             text_primary,
                             text_secondary,
                             text_tertiary,
@@ -0,0 +1,267 @@
+//! Injectable time source
+//!
+//! Everything that moves on its own reads time from here rather than from
+//! `Instant::now()`: the animation scheduler and its tick callbacks, keyframe
+//! delays, the text cursor blink, scroll physics and overlay timers. The
+//! default is the system clock.
+//!
+//! Installing a [`ManualClock`] freezes time until it is advanced. Because
+//! every frame then sees exactly the time it was given, headless tests and
+//! recording replays produce the same frames on every run. Virtual clocks
+//! report [`is_realtime`] as `false`, which tells the app loop to step
+//! animations once per frame instead of on the background thread.
+//!
+//! # Example
+//!
+//! ```rust
+//! use junita_core::clock::{self, ManualClock};
+//! use std::sync::Arc;
+//! use std::time::Duration;
+//!
+//! let manual = ManualClock::new();
+//! clock::set_clock(Arc::new(manual.clone()));
+//!
+//! manual.advance(Duration::from_millis(250));
+//! assert_eq!(clock::now_ms(), 250);
+//!
+//! clock::reset_clock();
+//! assert!(clock::is_realtime());
+//! ```
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+/// A source of monotonic time
+pub trait Clock: Send + Sync {
+    /// Time elapsed since the clock's origin
+    fn now(&self) -> Duration;
+
+    /// Whether time moves on its own
+    ///
+    /// Virtual clocks return `false`; the app loop then advances them with
+    /// [`begin_frame`](Clock::begin_frame) and steps animations itself.
+    fn is_realtime(&self) -> bool {
+        true
+    }
+
+    /// Called by the app loop at the start of every frame
+    fn begin_frame(&self) {}
+}
+
+/// Wall-clock time since the clock was created
+#[derive(Debug)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    /// Create a clock whose origin is now
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+/// A virtual clock that only moves when told to
+///
+/// Clones share the same time, so a test or replay driver can keep one
+/// handle while the app reads another through the global clock.
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock {
+    micros: Arc<AtomicU64>,
+    frame_step_micros: Arc<AtomicU64>,
+}
+
+impl ManualClock {
+    /// Create a clock at time zero
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a clock that advances by `step` at the start of every frame
+    ///
+    /// Lets the windowed app loop run at a fixed virtual frame rate.
+    pub fn with_frame_step(step: Duration) -> Self {
+        let clock = Self::new();
+        clock
+            .frame_step_micros
+            .store(step.as_micros() as u64, Ordering::Relaxed);
+        clock
+    }
+
+    /// Move time forward by `duration`
+    pub fn advance(&self, duration: Duration) {
+        self.micros
+            .fetch_add(duration.as_micros() as u64, Ordering::AcqRel);
+    }
+
+    /// Jump to `time`
+    ///
+    /// Moving backwards is allowed; consumers treat a negative delta as zero.
+    pub fn set(&self, time: Duration) {
+        self.micros
+            .store(time.as_micros() as u64, Ordering::Release);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        Duration::from_micros(self.micros.load(Ordering::Acquire))
+    }
+
+    fn is_realtime(&self) -> bool {
+        false
+    }
+
+    fn begin_frame(&self) {
+        let step = self.frame_step_micros.load(Ordering::Relaxed);
+        if step > 0 {
+            self.advance(Duration::from_micros(step));
+        }
+    }
+}
+
+/// Installed clock, or `None` for the system clock
+static CLOCK: RwLock<Option<Arc<dyn Clock>>> = RwLock::new(None);
+
+fn system_clock() -> &'static Arc<SystemClock> {
+    static SYSTEM: OnceLock<Arc<SystemClock>> = OnceLock::new();
+    SYSTEM.get_or_init(|| Arc::new(SystemClock::new()))
+}
+
+/// Replace the process-wide clock
+pub fn set_clock(clock: Arc<dyn Clock>) {
+    *CLOCK.write().unwrap_or_else(|e| e.into_inner()) = Some(clock);
+}
+
+/// Go back to the system clock
+pub fn reset_clock() {
+    *CLOCK.write().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// Replace the process-wide clock until the returned guard is dropped
+///
+/// Dropping the guard puts back whatever was installed before, so code that
+/// needs a virtual clock for a while doesn't leak it to the rest of the
+/// process. Guards must be dropped in reverse order of creation.
+pub fn install_clock(clock: Arc<dyn Clock>) -> ClockGuard {
+    let previous = CLOCK
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .replace(clock);
+    ClockGuard { previous }
+}
+
+/// Restores the previous process-wide clock on drop, see [`install_clock`]
+#[must_use = "the clock is restored as soon as the guard is dropped"]
+pub struct ClockGuard {
+    previous: Option<Arc<dyn Clock>>,
+}
+
+impl Drop for ClockGuard {
+    fn drop(&mut self) {
+        *CLOCK.write().unwrap_or_else(|e| e.into_inner()) = self.previous.take();
+    }
+}
+
+/// The process-wide clock
+pub fn clock() -> Arc<dyn Clock> {
+    let installed = CLOCK.read().unwrap_or_else(|e| e.into_inner()).clone();
+    installed.unwrap_or_else(|| Arc::clone(system_clock()) as Arc<dyn Clock>)
+}
+
+/// Current time of the process-wide clock
+pub fn now() -> Duration {
+    match CLOCK.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(clock) => clock.now(),
+        None => system_clock().now(),
+    }
+}
+
+/// Current time of the process-wide clock in milliseconds
+pub fn now_ms() -> u64 {
+    now().as_millis() as u64
+}
+
+/// Whether the process-wide clock follows wall time
+pub fn is_realtime() -> bool {
+    match CLOCK.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        Some(clock) => clock.is_realtime(),
+        None => true,
+    }
+}
+
+/// Notify the process-wide clock that a frame is starting
+pub fn begin_frame() {
+    if let Some(clock) = CLOCK.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+        clock.begin_frame();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manual_clock_moves_only_when_told() {
+        let clock = ManualClock::new();
+        assert_eq!(clock.now(), Duration::ZERO);
+        assert!(!clock.is_realtime());
+
+        clock.advance(Duration::from_millis(16));
+        clock.clone().advance(Duration::from_millis(16));
+        assert_eq!(clock.now(), Duration::from_millis(32));
+
+        clock.set(Duration::from_millis(5));
+        assert_eq!(clock.now(), Duration::from_millis(5));
+
+        // No frame step configured
+        clock.begin_frame();
+        assert_eq!(clock.now(), Duration::from_millis(5));
+    }
+
+    #[test]
+    fn test_frame_step() {
+        let clock = ManualClock::with_frame_step(Duration::from_micros(16_667));
+        clock.begin_frame();
+        clock.begin_frame();
+        assert_eq!(clock.now(), Duration::from_micros(33_334));
+    }
+
+    #[test]
+    fn test_system_clock_is_monotonic() {
+        let clock = SystemClock::new();
+        let a = clock.now();
+        let b = clock.now();
+        assert!(b >= a);
+        assert!(clock.is_realtime());
+    }
+
+    #[test]
+    fn test_install_clock_restores_previous() {
+        let outer = ManualClock::new();
+        outer.set(Duration::from_millis(7));
+        let guard = install_clock(Arc::new(outer));
+        {
+            let _inner = install_clock(Arc::new(ManualClock::new()));
+            assert_eq!(now(), Duration::ZERO);
+        }
+        assert_eq!(now(), Duration::from_millis(7));
+        drop(guard);
+        assert!(is_realtime());
+    }
+}
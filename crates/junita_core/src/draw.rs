@@ -33,9 +33,9 @@
 //! ```
 
 use crate::layer::{
-    Affine2D, BillboardFacing, BlendMode, Brush, Camera, ClipShape, Color, CornerRadius,
-    Environment, LayerId, Light, Mat4, ParticleSystemData, Point, Rect, Sdf3DViewport, Shadow,
-    Size, Vec2,
+    Affine2D, BillboardFacing, BlendMode, Brush, CachePolicy, Camera, ClipShape, Color,
+    CornerRadius, Environment, LayerId, Light, Mat4, ParticleSystemData, Point, Rect,
+    Sdf3DViewport, Shadow, Size, Vec2,
 };
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -895,6 +895,13 @@ pub struct LayerConfig {
     pub depth: bool,
     /// Post-processing effects to apply when layer is composited
     pub effects: Vec<LayerEffect>,
+    /// Whether the rasterized layer may be retained across frames
+    ///
+    /// Cached layers need an `id`; the backend keeps their texture until
+    /// `content_version` changes.
+    pub cache_policy: CachePolicy,
+    /// Version of the layer's content, compared against the retained texture
+    pub content_version: u64,
 }
 
 impl LayerConfig {
@@ -945,6 +952,18 @@ impl LayerConfig {
         self
     }
 
+    /// Retain the rasterized layer until `content_version` changes
+    pub fn cached(mut self, policy: CachePolicy, content_version: u64) -> Self {
+        self.cache_policy = policy;
+        self.content_version = content_version;
+        self
+    }
+
+    /// Whether this layer asks to be retained across frames
+    pub fn is_cached(&self) -> bool {
+        self.id.is_some() && self.cache_policy != CachePolicy::None
+    }
+
     /// Add a blur effect
     pub fn blur(self, radius: f32) -> Self {
         self.effect(LayerEffect::blur(radius))
@@ -1316,6 +1335,15 @@ pub trait DrawContext {
     /// Sample from a named layer's output
     fn sample_layer(&mut self, id: LayerId, source_rect: Rect, dest_rect: Rect);
 
+    /// Check whether a cached layer is already resident at `content_version`
+    ///
+    /// When this returns `true` the caller may push the layer and pop it
+    /// again without drawing its content; the backend recomposites the
+    /// retained texture instead.
+    fn is_layer_cached(&self, _id: LayerId, _content_version: u64) -> bool {
+        false
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // State Queries
     // ─────────────────────────────────────────────────────────────────────────
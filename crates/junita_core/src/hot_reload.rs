@@ -4,8 +4,8 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex, RwLock};
 use std::fmt;
+use std::sync::{Arc, Mutex, RwLock};
 use tracing::debug;
 
 /// Unique identifier for a widget instance
@@ -66,9 +66,7 @@ pub enum WidgetDiff {
         parent_id: Option<WidgetId>,
     },
     /// Widget removed
-    Removed {
-        id: WidgetId,
-    },
+    Removed { id: WidgetId },
     /// Children reordered
     Reordered {
         parent_id: WidgetId,
@@ -105,6 +103,11 @@ impl HotReloadManager {
         snapshot
     }
 
+    /// Save a snapshot captured by the caller (e.g. an interpreter's live state)
+    pub fn save_snapshot(&self, snapshot: StateSnapshot) {
+        self.state_snapshots.lock().unwrap().push(snapshot);
+    }
+
     /// Restore state after successful recompilation
     pub fn restore_state(&self) -> Option<StateSnapshot> {
         self.state_snapshots.lock().unwrap().pop()
@@ -141,7 +144,11 @@ impl HotReloadManager {
     }
 
     /// Recursively diff two widget trees
-    fn tree_diff(old: &WidgetNode, new: &WidgetNode, parent_id: Option<WidgetId>) -> Vec<WidgetDiff> {
+    fn tree_diff(
+        old: &WidgetNode,
+        new: &WidgetNode,
+        parent_id: Option<WidgetId>,
+    ) -> Vec<WidgetDiff> {
         let mut diffs = Vec::new();
 
         if old.id != new.id {
@@ -273,10 +280,7 @@ impl UpdateApplier {
 
     async fn apply_diff(&self, diff: WidgetDiff) -> anyhow::Result<()> {
         match diff {
-            WidgetDiff::Updated {
-                id,
-                changed_props,
-            } => {
+            WidgetDiff::Updated { id, changed_props } => {
                 // Update widget properties (requires integration with rendering engine)
                 debug!("Updating widget {:?} with {:?}", id, changed_props);
             }
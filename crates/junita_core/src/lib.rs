@@ -7,6 +7,7 @@
 //! - **Event Dispatch**: Unified event handling across platforms
 //! - **Layer Model**: Unified visual content representation (2D, 3D, composition)
 //! - **Draw Context**: Unified rendering API for 2D/3D content
+//! - **Clock**: Injectable time source for deterministic tests and replays
 //!
 //! # Example
 //!
@@ -33,6 +34,7 @@
 //! assert_eq!(graph.get_derived(doubled), Some(10));
 //! ```
 
+pub mod clock;
 pub mod context;
 pub mod context_state;
 pub mod draw;
@@ -41,6 +43,7 @@ pub mod fsm;
 pub mod hot_reload;
 pub mod layer;
 pub mod native_bridge;
+pub mod profiler;
 pub mod reactive;
 pub mod rendering;
 pub mod runtime;
@@ -65,8 +68,9 @@ pub use layer::{
     Shadow, Size, TextureFormat, UiNode, Vec2, Vec3,
 };
 pub use reactive::{
-    Derived, DerivedId, DirtyFlag, Effect, EffectId, ReactiveGraph, SharedReactiveGraph, Signal,
-    SignalId, State, StatefulDepsCallback,
+    Derived, DerivedId, DerivedInfo, DirtyFlag, Effect, EffectId, EffectInfo, ReactiveGraph,
+    ReactiveSnapshot, SharedReactiveGraph, Signal, SignalId, SignalInfo, State,
+    StatefulDepsCallback,
 };
 pub use runtime::JunitaReactiveRuntime;
 pub use value::{
@@ -86,8 +90,9 @@ pub use context_state::{
 
 // Re-export store types
 pub use store::{
-    clear_all_stores, create_store, create_store_with, get_store_state, kv_delete, kv_get, kv_set,
-    remove_store, set_store_state, update_store_state, KVStore, Store, SubscriptionHandle,
+    clear_all_stores, create_store, create_store_with, get_store_state, inspect_stores, kv_delete,
+    kv_get, kv_set, remove_store, set_store_state, update_store_state, KVStore, Store, StoreInfo,
+    StoreKeyInfo, SubscriptionHandle,
 };
 
 // Re-export native bridge types
@@ -0,0 +1,630 @@
+//! Frame profiler
+//!
+//! Scoped timing instrumentation for the frame pipeline. Each frame collects
+//! a flat list of timed spans (rebuild, diff, layout, text shaping, batching,
+//! GPU submit, ...) plus named counters, and finished frames are kept in a
+//! ring buffer that can be inspected, streamed to the debugger, or exported
+//! as Chrome trace-event JSON (`chrome://tracing`, Perfetto).
+//!
+//! Profiling is off by default. While disabled, [`scope`] and [`count`] cost
+//! a single atomic load. Desktop apps also enable it at startup when the
+//! `JUNITA_PROFILE` environment variable is set.
+//!
+//! # Example
+//!
+//! ```rust
+//! use junita_core::profiler;
+//!
+//! profiler::set_enabled(true);
+//! profiler::begin_frame();
+//! {
+//!     let _layout = profiler::scope("layout");
+//!     profiler::count("nodes_rebuilt", 12);
+//! }
+//! profiler::end_frame();
+//!
+//! let frames = profiler::frames();
+//! let trace = profiler::to_chrome_trace(&frames);
+//! # profiler::set_enabled(false);
+//! # assert!(trace.contains("layout"));
+//! ```
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+/// Frame budget at 60 Hz, in microseconds
+pub const DEFAULT_FRAME_BUDGET_US: u64 = 16_667;
+
+/// Default number of frames kept in the ring buffer
+pub const DEFAULT_CAPACITY: usize = 300;
+
+/// Spans recorded per frame before further spans are dropped
+const MAX_SPANS_PER_FRAME: usize = 10_000;
+
+/// Counter names used by the built-in instrumentation
+pub mod counters {
+    /// Render nodes created by tree builds and subtree rebuilds
+    pub const NODES_REBUILT: &str = "nodes_rebuilt";
+    /// Glyphs produced by text shaping
+    pub const GLYPHS_SHAPED: &str = "glyphs_shaped";
+    /// GPU primitives in the frame's batches
+    pub const PRIMITIVES: &str = "primitives";
+    /// Command buffers submitted to the GPU queue
+    pub const GPU_SUBMITS: &str = "gpu_submits";
+    /// Spans dropped because the frame hit its span limit
+    pub const DROPPED_SPANS: &str = "dropped_spans";
+}
+
+/// A timed region within a frame
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ProfileSpan {
+    /// Scope name (e.g. `layout`)
+    pub name: Cow<'static, str>,
+    /// Start time in microseconds since the profiler was created
+    pub start_us: u64,
+    /// Duration in microseconds
+    pub duration_us: u64,
+    /// Nesting depth on the recording thread (0 = outermost)
+    pub depth: u32,
+    /// Recording thread (small sequential IDs, 1 = first thread seen)
+    pub thread: u64,
+}
+
+impl ProfileSpan {
+    /// End time in microseconds since the profiler was created
+    pub fn end_us(&self) -> u64 {
+        self.start_us + self.duration_us
+    }
+}
+
+/// Timings and counters for a single frame
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct FrameProfile {
+    /// Sequential frame number
+    pub frame: u64,
+    /// Start time in microseconds since the profiler was created
+    pub start_us: u64,
+    /// Total frame duration in microseconds
+    pub duration_us: u64,
+    /// Spans in the order they finished
+    pub spans: Vec<ProfileSpan>,
+    /// Named counters (e.g. `nodes_rebuilt`, `glyphs_shaped`)
+    pub counters: BTreeMap<String, u64>,
+}
+
+impl FrameProfile {
+    /// Whether the frame took longer than `budget_us`
+    pub fn is_jank(&self, budget_us: u64) -> bool {
+        self.duration_us > budget_us
+    }
+
+    /// Value of a counter, or 0 if it was never incremented
+    pub fn counter(&self, name: &str) -> u64 {
+        self.counters.get(name).copied().unwrap_or(0)
+    }
+
+    /// Total time spent in spans with the given name
+    ///
+    /// Nested spans with the same name are counted once.
+    pub fn total_us(&self, name: &str) -> u64 {
+        let mut total = 0;
+        let mut covered_until = 0;
+        let mut spans: Vec<_> = self.spans.iter().filter(|s| s.name == name).collect();
+        spans.sort_by_key(|s| (s.thread, s.start_us));
+        let mut thread = None;
+        for span in spans {
+            if thread != Some(span.thread) {
+                thread = Some(span.thread);
+                covered_until = 0;
+            }
+            let start = span.start_us.max(covered_until);
+            if span.end_us() > start {
+                total += span.end_us() - start;
+                covered_until = span.end_us();
+            }
+        }
+        total
+    }
+
+    /// Deepest span nesting level in the frame
+    pub fn max_depth(&self) -> u32 {
+        self.spans.iter().map(|s| s.depth).max().unwrap_or(0)
+    }
+}
+
+/// Frame currently being recorded
+struct FrameBuilder {
+    profile: FrameProfile,
+    start: Instant,
+}
+
+struct ProfilerState {
+    current: Option<FrameBuilder>,
+    frames: VecDeque<FrameProfile>,
+    capacity: usize,
+    next_frame: u64,
+}
+
+/// Collects per-frame spans and counters into a ring buffer
+///
+/// Most code uses the process-wide profiler through the free functions in
+/// this module; separate instances are mainly useful for tests.
+pub struct Profiler {
+    enabled: AtomicBool,
+    epoch: Instant,
+    state: Mutex<ProfilerState>,
+}
+
+impl Default for Profiler {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+thread_local! {
+    static DEPTH: Cell<u32> = const { Cell::new(0) };
+    static THREAD_ID: u64 = {
+        static NEXT: AtomicU64 = AtomicU64::new(1);
+        NEXT.fetch_add(1, Ordering::Relaxed)
+    };
+}
+
+impl Profiler {
+    /// Create a disabled profiler with the default capacity
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            epoch: Instant::now(),
+            state: Mutex::new(ProfilerState {
+                current: None,
+                frames: VecDeque::new(),
+                capacity: DEFAULT_CAPACITY,
+                next_frame: 0,
+            }),
+        }
+    }
+
+    /// The process-wide profiler
+    pub fn global() -> &'static Profiler {
+        static GLOBAL: OnceLock<Profiler> = OnceLock::new();
+        GLOBAL.get_or_init(Profiler::new)
+    }
+
+    /// Enable or disable recording
+    ///
+    /// Disabling drops the frame in progress but keeps finished frames.
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            self.state.lock().unwrap().current = None;
+        }
+    }
+
+    /// Whether recording is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Set how many finished frames are kept
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.capacity = capacity.max(1);
+        while state.frames.len() > state.capacity {
+            state.frames.pop_front();
+        }
+    }
+
+    /// Start recording a frame, finishing any frame still in progress
+    pub fn begin_frame(&self) {
+        if !self.is_enabled() {
+            return;
+        }
+        let now = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        Self::finish_frame(&mut state, now);
+
+        let frame = state.next_frame;
+        state.next_frame += 1;
+        state.current = Some(FrameBuilder {
+            profile: FrameProfile {
+                frame,
+                start_us: self.micros(now),
+                ..Default::default()
+            },
+            start: now,
+        });
+    }
+
+    /// Finish the current frame and push it into the ring buffer
+    pub fn end_frame(&self) {
+        if !self.is_enabled() {
+            return;
+        }
+        let now = Instant::now();
+        Self::finish_frame(&mut self.state.lock().unwrap(), now);
+    }
+
+    fn finish_frame(state: &mut ProfilerState, now: Instant) {
+        let Some(builder) = state.current.take() else {
+            return;
+        };
+        let mut profile = builder.profile;
+        profile.duration_us = now.duration_since(builder.start).as_micros() as u64;
+
+        if state.frames.len() >= state.capacity {
+            state.frames.pop_front();
+        }
+        state.frames.push_back(profile);
+    }
+
+    /// Time a region until the returned guard is dropped
+    ///
+    /// Spans outside [`begin_frame`](Self::begin_frame) /
+    /// [`end_frame`](Self::end_frame) are discarded.
+    pub fn scope(&self, name: &'static str) -> ProfileScope<'_> {
+        self.scope_named(Cow::Borrowed(name))
+    }
+
+    /// Like [`scope`](Self::scope) with a name built at runtime
+    pub fn scope_named(&self, name: impl Into<Cow<'static, str>>) -> ProfileScope<'_> {
+        if !self.is_enabled() {
+            return ProfileScope { active: None };
+        }
+        let depth = DEPTH.with(|d| {
+            let depth = d.get();
+            d.set(depth + 1);
+            depth
+        });
+        ProfileScope {
+            active: Some(ActiveScope {
+                profiler: self,
+                name: name.into(),
+                start: Instant::now(),
+                depth,
+            }),
+        }
+    }
+
+    /// Add `n` to a counter on the current frame
+    pub fn count(&self, name: &str, n: u64) {
+        if !self.is_enabled() {
+            return;
+        }
+        let mut state = self.state.lock().unwrap();
+        if let Some(current) = state.current.as_mut() {
+            *current
+                .profile
+                .counters
+                .entry(name.to_string())
+                .or_default() += n;
+        }
+    }
+
+    fn record(&self, scope: &mut ActiveScope<'_>) {
+        let end = Instant::now();
+        let mut state = self.state.lock().unwrap();
+        let Some(current) = state.current.as_mut() else {
+            return;
+        };
+        // Spans started before the frame began are clipped to the frame
+        let start = scope.start.max(current.start);
+
+        if current.profile.spans.len() >= MAX_SPANS_PER_FRAME {
+            *current
+                .profile
+                .counters
+                .entry(counters::DROPPED_SPANS.to_string())
+                .or_default() += 1;
+            return;
+        }
+        current.profile.spans.push(ProfileSpan {
+            name: std::mem::take(&mut scope.name),
+            start_us: self.micros(start),
+            duration_us: end.duration_since(start).as_micros() as u64,
+            depth: scope.depth,
+            thread: THREAD_ID.with(|id| *id),
+        });
+    }
+
+    /// Finished frames, oldest first
+    pub fn frames(&self) -> Vec<FrameProfile> {
+        self.state.lock().unwrap().frames.iter().cloned().collect()
+    }
+
+    /// Finished frames with a frame number of at least `frame`, oldest first
+    pub fn frames_since(&self, frame: u64) -> Vec<FrameProfile> {
+        self.state
+            .lock()
+            .unwrap()
+            .frames
+            .iter()
+            .filter(|f| f.frame >= frame)
+            .cloned()
+            .collect()
+    }
+
+    /// Drop all finished frames
+    pub fn clear(&self) {
+        self.state.lock().unwrap().frames.clear();
+    }
+
+    fn micros(&self, instant: Instant) -> u64 {
+        instant.duration_since(self.epoch).as_micros() as u64
+    }
+}
+
+/// Guard returned by [`scope`]; records the span when dropped
+#[must_use = "the span ends when the scope guard is dropped"]
+pub struct ProfileScope<'a> {
+    active: Option<ActiveScope<'a>>,
+}
+
+struct ActiveScope<'a> {
+    profiler: &'a Profiler,
+    name: Cow<'static, str>,
+    start: Instant,
+    depth: u32,
+}
+
+impl Drop for ProfileScope<'_> {
+    fn drop(&mut self) {
+        if let Some(mut active) = self.active.take() {
+            DEPTH.with(|d| d.set(d.get().saturating_sub(1)));
+            active.profiler.record(&mut active);
+        }
+    }
+}
+
+/// Enable or disable the global profiler
+pub fn set_enabled(enabled: bool) {
+    Profiler::global().set_enabled(enabled);
+}
+
+/// Whether the global profiler is recording
+pub fn is_enabled() -> bool {
+    Profiler::global().is_enabled()
+}
+
+/// Start a frame on the global profiler
+pub fn begin_frame() {
+    Profiler::global().begin_frame();
+}
+
+/// Finish the current frame on the global profiler
+pub fn end_frame() {
+    Profiler::global().end_frame();
+}
+
+/// Time a region on the global profiler until the guard is dropped
+pub fn scope(name: &'static str) -> ProfileScope<'static> {
+    Profiler::global().scope(name)
+}
+
+/// Add `n` to a counter on the global profiler's current frame
+pub fn count(name: &str, n: u64) {
+    Profiler::global().count(name, n);
+}
+
+/// Finished frames from the global profiler, oldest first
+pub fn frames() -> Vec<FrameProfile> {
+    Profiler::global().frames()
+}
+
+/// Finished frames from the global profiler starting at frame number `frame`
+pub fn frames_since(frame: u64) -> Vec<FrameProfile> {
+    Profiler::global().frames_since(frame)
+}
+
+/// Serialize frames as Chrome trace-event JSON
+///
+/// Frames and spans become complete (`X`) events, counters become counter
+/// (`C`) events, and frames over [`DEFAULT_FRAME_BUDGET_US`] get a `jank`
+/// instant event. Load the output in `chrome://tracing` or Perfetto.
+pub fn to_chrome_trace(frames: &[FrameProfile]) -> String {
+    let mut events = Vec::new();
+    for frame in frames {
+        let jank = frame.is_jank(DEFAULT_FRAME_BUDGET_US);
+        events.push(TraceEvent {
+            name: Cow::Owned(format!("frame {}", frame.frame)),
+            cat: "frame",
+            ph: "X",
+            ts: frame.start_us,
+            dur: Some(frame.duration_us),
+            pid: 1,
+            tid: 0,
+            s: None,
+            args: BTreeMap::from([("jank".to_string(), serde_json::json!(jank))]),
+        });
+
+        for span in &frame.spans {
+            events.push(TraceEvent {
+                name: span.name.clone(),
+                cat: "junita",
+                ph: "X",
+                ts: span.start_us,
+                dur: Some(span.duration_us),
+                pid: 1,
+                tid: span.thread,
+                s: None,
+                args: BTreeMap::new(),
+            });
+        }
+
+        if !frame.counters.is_empty() {
+            events.push(TraceEvent {
+                name: Cow::Borrowed("counters"),
+                cat: "junita",
+                ph: "C",
+                ts: frame.start_us,
+                dur: None,
+                pid: 1,
+                tid: 0,
+                s: None,
+                args: frame
+                    .counters
+                    .iter()
+                    .map(|(k, v)| (k.clone(), serde_json::json!(v)))
+                    .collect(),
+            });
+        }
+
+        if jank {
+            events.push(TraceEvent {
+                name: Cow::Borrowed("jank"),
+                cat: "frame",
+                ph: "i",
+                ts: frame.start_us + frame.duration_us,
+                dur: None,
+                pid: 1,
+                tid: 0,
+                s: Some("g"),
+                args: BTreeMap::new(),
+            });
+        }
+    }
+
+    serde_json::json!({
+        "traceEvents": events,
+        "displayTimeUnit": "ms",
+    })
+    .to_string()
+}
+
+#[derive(Serialize)]
+struct TraceEvent {
+    name: Cow<'static, str>,
+    cat: &'static str,
+    ph: &'static str,
+    ts: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    dur: Option<u64>,
+    pid: u32,
+    tid: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    s: Option<&'static str>,
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    args: BTreeMap<String, serde_json::Value>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record_frame(profiler: &Profiler) {
+        profiler.begin_frame();
+        {
+            let _outer = profiler.scope("rebuild");
+            let _inner = profiler.scope("diff");
+            profiler.count("nodes_rebuilt", 3);
+        }
+        profiler.count("nodes_rebuilt", 2);
+        profiler.end_frame();
+    }
+
+    #[test]
+    fn test_disabled_profiler_records_nothing() {
+        let profiler = Profiler::new();
+        record_frame(&profiler);
+        assert!(profiler.frames().is_empty());
+    }
+
+    #[test]
+    fn test_spans_and_counters() {
+        let profiler = Profiler::new();
+        profiler.set_enabled(true);
+        record_frame(&profiler);
+
+        let frames = profiler.frames();
+        assert_eq!(frames.len(), 1);
+        let frame = &frames[0];
+        assert_eq!(frame.counter("nodes_rebuilt"), 5);
+        assert_eq!(frame.counter("glyphs_shaped"), 0);
+
+        // Inner scope finishes first
+        assert_eq!(frame.spans.len(), 2);
+        assert_eq!(frame.spans[0].name, "diff");
+        assert_eq!(frame.spans[0].depth, 1);
+        assert_eq!(frame.spans[1].name, "rebuild");
+        assert_eq!(frame.spans[1].depth, 0);
+        assert!(frame.spans[1].start_us <= frame.spans[0].start_us);
+        assert!(frame.spans[1].end_us() >= frame.spans[0].end_us());
+        assert!(frame.duration_us >= frame.spans[1].duration_us);
+    }
+
+    #[test]
+    fn test_spans_outside_frame_are_dropped() {
+        let profiler = Profiler::new();
+        profiler.set_enabled(true);
+        drop(profiler.scope("orphan"));
+        profiler.count("orphan", 1);
+        profiler.begin_frame();
+        profiler.end_frame();
+
+        let frames = profiler.frames();
+        assert!(frames[0].spans.is_empty());
+        assert!(frames[0].counters.is_empty());
+    }
+
+    #[test]
+    fn test_ring_buffer_capacity() {
+        let profiler = Profiler::new();
+        profiler.set_enabled(true);
+        profiler.set_capacity(3);
+        for _ in 0..5 {
+            record_frame(&profiler);
+        }
+
+        let numbers: Vec<u64> = profiler.frames().iter().map(|f| f.frame).collect();
+        assert_eq!(numbers, vec![2, 3, 4]);
+        assert_eq!(profiler.frames_since(4).len(), 1);
+    }
+
+    #[test]
+    fn test_total_us_merges_nested_spans() {
+        let span = |start_us, duration_us, depth| ProfileSpan {
+            name: Cow::Borrowed("layout"),
+            start_us,
+            duration_us,
+            depth,
+            thread: 1,
+        };
+        let frame = FrameProfile {
+            spans: vec![span(10, 5, 1), span(0, 20, 0), span(30, 10, 0)],
+            ..Default::default()
+        };
+        assert_eq!(frame.total_us("layout"), 30);
+        assert_eq!(frame.max_depth(), 1);
+    }
+
+    #[test]
+    fn test_chrome_trace_format() {
+        let frame = FrameProfile {
+            frame: 7,
+            start_us: 100,
+            duration_us: 20_000,
+            spans: vec![ProfileSpan {
+                name: Cow::Borrowed("gpu_submit"),
+                start_us: 110,
+                duration_us: 50,
+                depth: 0,
+                thread: 1,
+            }],
+            counters: BTreeMap::from([("glyphs_shaped".to_string(), 42)]),
+        };
+
+        let trace: serde_json::Value = serde_json::from_str(&to_chrome_trace(&[frame])).unwrap();
+        let events = trace["traceEvents"].as_array().unwrap();
+        let phases: Vec<&str> = events.iter().map(|e| e["ph"].as_str().unwrap()).collect();
+        assert_eq!(phases, vec!["X", "X", "C", "i"]);
+        assert_eq!(events[0]["name"], "frame 7");
+        assert_eq!(events[0]["args"]["jank"], true);
+        assert_eq!(events[1]["name"], "gpu_submit");
+        assert_eq!(events[1]["dur"], 50);
+        assert_eq!(events[2]["args"]["glyphs_shaped"], 42);
+    }
+}
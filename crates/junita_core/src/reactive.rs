@@ -27,12 +27,24 @@
 //! // Update the value and rebuild UI tree
 //! counter.set_rebuild(value + 1);
 //! ```
-
-use slotmap::{new_key_type, SlotMap};
+//!
+//! # Inspection
+//!
+//! [`ReactiveGraph::snapshot`] lists every signal, derived value and effect
+//! with its dependency edges, run counts and the frame it last changed in.
+//! Values are only shown for nodes that opted in with a `Debug` formatter
+//! ([`ReactiveGraph::debug_signal`], [`State::debug`]), and signals opted in
+//! with [`ReactiveGraph::debug_signal_editable`] can be set from a string,
+//! which is how the debugger edits state in a running app.
+
+use serde::{Deserialize, Serialize};
+use slotmap::{new_key_type, Key, SlotMap};
 use smallvec::SmallVec;
 use std::any::Any;
 use std::cell::{Cell, RefCell};
 use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::str::FromStr;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 
@@ -89,7 +101,6 @@ impl<T> Signal<T> {
 impl SignalId {
     /// Convert to raw u64 for storage
     pub fn to_raw(&self) -> u64 {
-        use slotmap::Key;
         // SlotMap key data contains version + index
         self.data().as_ffi()
     }
@@ -133,6 +144,36 @@ impl Effect {
     }
 }
 
+/// Formats a type-erased node value for inspection
+type FormatFn = Box<dyn Fn(&dyn Any) -> Option<String> + Send>;
+
+/// Parses a string into a type-erased signal value
+type ParseFn = Box<dyn Fn(&str) -> Result<Box<dyn Any + Send>, String> + Send>;
+
+/// Inspection metadata kept for every node
+#[derive(Default)]
+struct NodeDebug {
+    /// Human-readable name (e.g. the `use_state_keyed` key)
+    label: Option<String>,
+    /// Opt-in value formatter
+    format: Option<FormatFn>,
+    /// Writes (signals), recomputes (derived) or runs (effects)
+    runs: u64,
+    /// Frame of the last write, recompute or run
+    last_frame: Option<u64>,
+}
+
+impl NodeDebug {
+    fn record(&mut self, frame: u64) {
+        self.runs += 1;
+        self.last_frame = Some(frame);
+    }
+
+    fn format(&self, value: &dyn Any) -> Option<String> {
+        self.format.as_ref().and_then(|format| format(value))
+    }
+}
+
 /// Internal signal node storage
 struct SignalNode {
     /// The signal value (type-erased)
@@ -141,6 +182,10 @@ struct SignalNode {
     version: u64,
     /// Subscribers to notify on change
     subscribers: SmallVec<[SubscriberId; 4]>,
+    /// Inspection metadata
+    debug: NodeDebug,
+    /// Opt-in parser for setting the value from the debugger
+    parse: Option<ParseFn>,
 }
 
 /// Internal derived node storage
@@ -159,6 +204,8 @@ struct DerivedNode {
     dirty: Cell<bool>,
     /// Depth in the dependency graph (for topological ordering)
     depth: u32,
+    /// Inspection metadata
+    debug: NodeDebug,
 }
 
 /// Internal effect node storage
@@ -171,6 +218,8 @@ struct EffectNode {
     dirty: Cell<bool>,
     /// Depth in the dependency graph
     depth: u32,
+    /// Inspection metadata
+    debug: NodeDebug,
 }
 
 /// The reactive graph that manages all signals, derived values, and effects
@@ -186,6 +235,8 @@ pub struct ReactiveGraph {
     tracking: RefCell<Option<Vec<SignalId>>>,
     /// Global version counter
     global_version: Cell<u64>,
+    /// Current frame number, for inspection (see [`advance_frame`](Self::advance_frame))
+    frame: u64,
 }
 
 impl ReactiveGraph {
@@ -199,6 +250,7 @@ impl ReactiveGraph {
             batch_depth: Cell::new(0),
             tracking: RefCell::new(None),
             global_version: Cell::new(0),
+            frame: 0,
         }
     }
 
@@ -212,6 +264,8 @@ impl ReactiveGraph {
             value: Box::new(initial),
             version: 0,
             subscribers: SmallVec::new(),
+            debug: NodeDebug::default(),
+            parse: None,
         });
         Signal {
             id,
@@ -245,9 +299,14 @@ impl ReactiveGraph {
 
     /// Set the value of a signal, triggering reactive updates
     pub fn set<T: Send + 'static>(&mut self, signal: Signal<T>, value: T) {
-        if let Some(node) = self.signals.get_mut(signal.id) {
-            node.value = Box::new(value);
+        self.set_boxed(signal.id, Box::new(value));
+    }
+
+    fn set_boxed(&mut self, id: SignalId, value: Box<dyn Any + Send>) {
+        if let Some(node) = self.signals.get_mut(id) {
+            node.value = value;
             node.version += 1;
+            node.debug.record(self.frame);
             self.global_version.set(self.global_version.get() + 1);
 
             // Mark all subscribers as dirty
@@ -274,6 +333,13 @@ impl ReactiveGraph {
         }
     }
 
+    /// Dispose of a signal, dropping its value
+    ///
+    /// Effects and derived values that read it see `None` from then on.
+    pub fn dispose_signal<T>(&mut self, signal: Signal<T>) {
+        self.signals.remove(signal.id);
+    }
+
     /// Get the version of a signal (for change detection)
     pub fn signal_version(&self, id: SignalId) -> Option<u64> {
         self.signals.get(id).map(|n| n.version)
@@ -301,6 +367,7 @@ impl ReactiveGraph {
             subscribers: SmallVec::new(),
             dirty: Cell::new(true), // Start dirty to force initial computation
             depth: 0,
+            debug: NodeDebug::default(),
         });
 
         Derived {
@@ -375,6 +442,7 @@ impl ReactiveGraph {
             node.dependencies = deps.into_iter().collect();
             node.depth = max_dep_depth + 1;
             node.cached_version = self.global_version.get();
+            node.debug.record(self.frame);
 
             let result = value.downcast_ref::<T>().cloned();
             node.value = Some(value);
@@ -398,6 +466,7 @@ impl ReactiveGraph {
             dependencies: SmallVec::new(),
             dirty: Cell::new(true), // Run immediately
             depth: 0,
+            debug: NodeDebug::default(),
         });
 
         // Schedule initial run
@@ -454,6 +523,143 @@ impl ReactiveGraph {
         result
     }
 
+    // =========================================================================
+    // INSPECTION
+    // =========================================================================
+
+    /// Start a new frame; writes and runs after this are attributed to it
+    pub fn advance_frame(&mut self) {
+        self.frame += 1;
+    }
+
+    /// Current frame number
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+
+    /// Name a signal in inspection snapshots
+    pub fn label_signal<T>(&mut self, signal: Signal<T>, label: impl Into<String>) {
+        if let Some(node) = self.signals.get_mut(signal.id) {
+            node.debug.label = Some(label.into());
+        }
+    }
+
+    /// Name a derived value in inspection snapshots
+    pub fn label_derived<T>(&mut self, derived: Derived<T>, label: impl Into<String>) {
+        if let Some(node) = self.derived.get_mut(derived.id) {
+            node.debug.label = Some(label.into());
+        }
+    }
+
+    /// Name an effect in inspection snapshots
+    pub fn label_effect(&mut self, effect: Effect, label: impl Into<String>) {
+        if let Some(node) = self.effects.get_mut(effect.id) {
+            node.debug.label = Some(label.into());
+        }
+    }
+
+    /// Show a signal's value in inspection snapshots
+    pub fn debug_signal<T: Debug + 'static>(&mut self, signal: Signal<T>) {
+        if let Some(node) = self.signals.get_mut(signal.id) {
+            node.debug.format = Some(debug_formatter::<T>());
+        }
+    }
+
+    /// Show a signal's value and allow setting it with [`set_from_str`](Self::set_from_str)
+    pub fn debug_signal_editable<T>(&mut self, signal: Signal<T>)
+    where
+        T: Debug + FromStr + Send + 'static,
+        T::Err: std::fmt::Display,
+    {
+        if let Some(node) = self.signals.get_mut(signal.id) {
+            node.debug.format = Some(debug_formatter::<T>());
+            node.parse = Some(Box::new(|s| {
+                s.trim()
+                    .parse::<T>()
+                    .map(|v| Box::new(v) as Box<dyn Any + Send>)
+                    .map_err(|e| e.to_string())
+            }));
+        }
+    }
+
+    /// Show a derived value in inspection snapshots
+    pub fn debug_derived<T: Debug + 'static>(&mut self, derived: Derived<T>) {
+        if let Some(node) = self.derived.get_mut(derived.id) {
+            node.debug.format = Some(debug_formatter::<T>());
+        }
+    }
+
+    /// Set a signal from its string form, triggering reactive updates
+    ///
+    /// Only works for signals registered with
+    /// [`debug_signal_editable`](Self::debug_signal_editable).
+    pub fn set_from_str(&mut self, id: SignalId, value: &str) -> Result<(), String> {
+        let node = self
+            .signals
+            .get(id)
+            .ok_or_else(|| format!("Unknown signal: {}", id.to_raw()))?;
+        let parse = node
+            .parse
+            .as_ref()
+            .ok_or_else(|| format!("Signal {} is not editable", id.to_raw()))?;
+        let value = parse(value)?;
+        self.set_boxed(id, value);
+        Ok(())
+    }
+
+    /// Every signal, derived value and effect with its edges and counters
+    pub fn snapshot(&self) -> ReactiveSnapshot {
+        let signals = self
+            .signals
+            .iter()
+            .map(|(id, node)| SignalInfo {
+                id: id.to_raw(),
+                label: node.debug.label.clone(),
+                value: node.debug.format(node.value.as_ref()),
+                editable: node.parse.is_some(),
+                version: node.version,
+                changed_frame: node.debug.last_frame,
+                subscribers: node.subscribers.len(),
+            })
+            .collect();
+
+        let derived = self
+            .derived
+            .iter()
+            .map(|(id, node)| DerivedInfo {
+                id: id.data().as_ffi(),
+                label: node.debug.label.clone(),
+                value: node
+                    .value
+                    .as_ref()
+                    .and_then(|value| node.debug.format(value.as_ref())),
+                dirty: node.dirty.get(),
+                dependencies: node.dependencies.iter().map(SignalId::to_raw).collect(),
+                recomputes: node.debug.runs,
+                last_run_frame: node.debug.last_frame,
+            })
+            .collect();
+
+        let effects = self
+            .effects
+            .iter()
+            .map(|(id, node)| EffectInfo {
+                id: id.data().as_ffi(),
+                label: node.debug.label.clone(),
+                dependencies: node.dependencies.iter().map(SignalId::to_raw).collect(),
+                runs: node.debug.runs,
+                last_run_frame: node.debug.last_frame,
+            })
+            .collect();
+
+        ReactiveSnapshot {
+            frame: self.frame,
+            signals,
+            derived,
+            effects,
+        }
+    }
+
     // =========================================================================
     // INTERNAL
     // =========================================================================
@@ -552,6 +758,7 @@ impl ReactiveGraph {
             }
 
             node.dependencies = deps.into_iter().collect();
+            node.debug.record(self.frame);
         }
     }
 
@@ -583,6 +790,65 @@ pub struct ReactiveStats {
     pub global_version: u64,
 }
 
+fn debug_formatter<T: Debug + 'static>() -> FormatFn {
+    Box::new(|value| value.downcast_ref::<T>().map(|v| format!("{:?}", v)))
+}
+
+/// Every node in a reactive graph, from [`ReactiveGraph::snapshot`]
+///
+/// Node IDs are the raw slot keys (see [`SignalId::to_raw`]).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReactiveSnapshot {
+    /// Frame the snapshot was taken in
+    pub frame: u64,
+    pub signals: Vec<SignalInfo>,
+    pub derived: Vec<DerivedInfo>,
+    pub effects: Vec<EffectInfo>,
+}
+
+/// A signal in a [`ReactiveSnapshot`]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct SignalInfo {
+    pub id: u64,
+    pub label: Option<String>,
+    /// Formatted value, if the signal opted in with a `Debug` formatter
+    pub value: Option<String>,
+    /// Whether the value can be set with [`ReactiveGraph::set_from_str`]
+    pub editable: bool,
+    /// Number of writes
+    pub version: u64,
+    /// Frame of the last write
+    pub changed_frame: Option<u64>,
+    /// Derived values and effects notified on change
+    pub subscribers: usize,
+}
+
+/// A derived value in a [`ReactiveSnapshot`]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DerivedInfo {
+    pub id: u64,
+    pub label: Option<String>,
+    /// Formatted cached value, if the derived opted in with a `Debug` formatter
+    pub value: Option<String>,
+    /// Whether the cached value is stale
+    pub dirty: bool,
+    /// Signals read by the last computation
+    pub dependencies: Vec<u64>,
+    pub recomputes: u64,
+    pub last_run_frame: Option<u64>,
+}
+
+/// An effect in a [`ReactiveSnapshot`]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct EffectInfo {
+    pub id: u64,
+    pub label: Option<String>,
+    /// Signals read by the last run
+    pub dependencies: Vec<u64>,
+    pub runs: u64,
+    pub last_run_frame: Option<u64>,
+}
+
 // =============================================================================
 // STATE - High-level API for component state management
 // =============================================================================
@@ -724,6 +990,28 @@ impl<T: Clone + Send + 'static> State<T> {
     pub fn signal_id(&self) -> SignalId {
         self.signal.id()
     }
+
+    /// Show this state's value in the reactive inspector
+    pub fn debug(self) -> Self
+    where
+        T: Debug,
+    {
+        self.reactive.lock().unwrap().debug_signal(self.signal);
+        self
+    }
+
+    /// Show this state's value in the reactive inspector and allow editing it
+    pub fn debug_editable(self) -> Self
+    where
+        T: Debug + FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.reactive
+            .lock()
+            .unwrap()
+            .debug_signal_editable(self.signal);
+        self
+    }
 }
 
 #[cfg(test)]
@@ -873,6 +1161,15 @@ mod tests {
         assert_eq!(*effect_runs.lock().unwrap(), 2);
     }
 
+    #[test]
+    fn test_dispose_signal() {
+        let mut graph = ReactiveGraph::new();
+        let name = graph.create_signal(String::from("junita"));
+        graph.dispose_signal(name);
+        assert_eq!(graph.get(name), None);
+        assert_eq!(graph.stats().signal_count, 0);
+    }
+
     #[test]
     fn test_multiple_signals() {
         let mut graph = ReactiveGraph::new();
@@ -903,4 +1200,88 @@ mod tests {
         assert_eq!(stats.signal_count, 2);
         assert_eq!(stats.derived_count, 1);
     }
+
+    #[test]
+    fn test_snapshot_values_are_opt_in() {
+        let mut graph = ReactiveGraph::new();
+
+        let hidden = graph.create_signal(1i32);
+        let shown = graph.create_signal(String::from("junita"));
+        graph.label_signal(shown, "name");
+        graph.debug_signal(shown);
+
+        let snapshot = graph.snapshot();
+        let info = |id: SignalId| {
+            snapshot
+                .signals
+                .iter()
+                .find(|s| s.id == id.to_raw())
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(info(hidden.id()).value, None);
+        assert_eq!(info(shown.id()).label.as_deref(), Some("name"));
+        assert_eq!(info(shown.id()).value.as_deref(), Some("\"junita\""));
+        assert!(!info(shown.id()).editable);
+    }
+
+    #[test]
+    fn test_snapshot_edges_and_counters() {
+        let mut graph = ReactiveGraph::new();
+
+        let count = graph.create_signal(1i32);
+        let doubled = graph.create_derived(move |g| g.get(count).unwrap_or(0) * 2);
+        graph.debug_derived(doubled);
+        let effect = graph.create_effect(move |g| {
+            let _ = g.get(count);
+        });
+        graph.label_effect(effect, "logger");
+        graph.get_derived(doubled);
+
+        graph.advance_frame();
+        graph.set(count, 5);
+        graph.get_derived(doubled);
+
+        let snapshot = graph.snapshot();
+        assert_eq!(snapshot.frame, 1);
+
+        let signal = &snapshot.signals[0];
+        assert_eq!(signal.version, 1);
+        assert_eq!(signal.changed_frame, Some(1));
+        assert_eq!(signal.subscribers, 2);
+
+        let derived = &snapshot.derived[0];
+        assert_eq!(derived.dependencies, vec![count.id().to_raw()]);
+        assert_eq!(derived.recomputes, 2);
+        assert_eq!(derived.last_run_frame, Some(1));
+        assert_eq!(derived.value.as_deref(), Some("10"));
+
+        let effect = &snapshot.effects[0];
+        assert_eq!(effect.label.as_deref(), Some("logger"));
+        assert_eq!(effect.runs, 2);
+        assert_eq!(effect.dependencies, vec![count.id().to_raw()]);
+    }
+
+    #[test]
+    fn test_set_from_str() {
+        let mut graph = ReactiveGraph::new();
+        let runs = Arc::new(Mutex::new(Vec::new()));
+
+        let count = graph.create_signal(0i32);
+        let runs_clone = runs.clone();
+        let _effect = graph.create_effect(move |g| {
+            runs_clone.lock().unwrap().push(g.get(count).unwrap_or(0));
+        });
+
+        assert!(graph.set_from_str(count.id(), "3").is_err());
+
+        graph.debug_signal_editable(count);
+        graph.set_from_str(count.id(), " 42 ").unwrap();
+        assert_eq!(graph.get(count), Some(42));
+        assert_eq!(*runs.lock().unwrap(), vec![0, 42]);
+
+        assert!(graph.set_from_str(count.id(), "forty-two").is_err());
+        assert_eq!(graph.get(count), Some(42));
+        assert!(graph.snapshot().signals[0].editable);
+    }
 }
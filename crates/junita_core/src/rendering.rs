@@ -23,9 +23,9 @@
 
 use crate::hot_reload::{WidgetDiff, WidgetNode};
 use anyhow::Result;
-use tracing::{info, debug};
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use tracing::{debug, info};
 
 /// GPU Backend Interface (trait for testing and platform independence)
 pub trait GpuBackend: Send + Sync {
@@ -97,9 +97,7 @@ impl RenderingAdapter {
     }
 
     /// Create with a custom GPU backend (for integration with junita_gpu)
-    pub fn with_gpu_backend(
-        gpu_backend: Arc<Mutex<Box<dyn GpuBackend>>>,
-    ) -> Self {
+    pub fn with_gpu_backend(gpu_backend: Arc<Mutex<Box<dyn GpuBackend>>>) -> Self {
         Self {
             scene_nodes: Default::default(),
             root_id: None,
@@ -110,11 +108,9 @@ impl RenderingAdapter {
     /// Apply a diff to the scene graph (async for GPU integration)
     pub async fn apply_diff(&mut self, diff: &WidgetDiff) -> Result<()> {
         match diff {
-            WidgetDiff::Updated {
-                id,
-                changed_props,
-            } => {
-                self.update_widget_properties_async(id.0, changed_props).await?;
+            WidgetDiff::Updated { id, changed_props } => {
+                self.update_widget_properties_async(id.0, changed_props)
+                    .await?;
                 info!("Updated widget {:?} properties", id);
             }
             WidgetDiff::Added {
@@ -122,7 +118,8 @@ impl RenderingAdapter {
                 widget,
                 parent_id,
             } => {
-                self.add_widget_async(id.0, &widget.widget_type, parent_id.map(|p| p.0)).await?;
+                self.add_widget_async(id.0, &widget.widget_type, parent_id.map(|p| p.0))
+                    .await?;
                 info!("Added widget {:?} to parent {:?}", id, parent_id);
             }
             WidgetDiff::Removed { id } => {
@@ -163,10 +160,7 @@ impl RenderingAdapter {
 
             Ok(())
         } else {
-            Err(anyhow::anyhow!(
-                "Widget {} not found in scene graph",
-                id
-            ))
+            Err(anyhow::anyhow!("Widget {} not found in scene graph", id))
         }
     }
 
@@ -217,28 +211,18 @@ impl RenderingAdapter {
             debug!("Removed scene node {}", id);
             Ok(())
         } else {
-            Err(anyhow::anyhow!(
-                "Widget {} not found in scene graph",
-                id
-            ))
+            Err(anyhow::anyhow!("Widget {} not found in scene graph", id))
         }
     }
 
     /// Reorder children of a widget
-    fn reorder_children(
-        &mut self,
-        parent_id: u32,
-        new_order: &[u32],
-    ) -> Result<()> {
+    fn reorder_children(&mut self, parent_id: u32, new_order: &[u32]) -> Result<()> {
         if let Some(parent) = self.scene_nodes.get_mut(&parent_id) {
             parent.children = new_order.to_vec();
             debug!("Reordered children of widget {}", parent_id);
             Ok(())
         } else {
-            Err(anyhow::anyhow!(
-                "Parent widget {} not found",
-                parent_id
-            ))
+            Err(anyhow::anyhow!("Parent widget {} not found", parent_id))
         }
     }
 
@@ -275,12 +259,7 @@ impl RenderingAdapter {
     }
 
     // Synchronous wrappers for backwards compatibility
-    pub fn add_widget(
-        &mut self,
-        id: u32,
-        widget_type: &str,
-        parent_id: Option<u32>,
-    ) -> Result<()> {
+    pub fn add_widget(&mut self, id: u32, widget_type: &str, parent_id: Option<u32>) -> Result<()> {
         let node = SceneNode {
             id,
             widget_type: widget_type.to_string(),
@@ -314,10 +293,7 @@ impl RenderingAdapter {
             }
             Ok(())
         } else {
-            Err(anyhow::anyhow!(
-                "Widget {} not found in scene graph",
-                id
-            ))
+            Err(anyhow::anyhow!("Widget {} not found in scene graph", id))
         }
     }
 
@@ -329,10 +305,7 @@ impl RenderingAdapter {
             debug!("Removed scene node {}", id);
             Ok(())
         } else {
-            Err(anyhow::anyhow!(
-                "Widget {} not found in scene graph",
-                id
-            ))
+            Err(anyhow::anyhow!("Widget {} not found in scene graph", id))
         }
     }
 }
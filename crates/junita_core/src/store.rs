@@ -41,9 +41,17 @@
 //!     println!("State changed: {:?}", state);
 //! });
 //! ```
+//!
+//! # Inspection
+//!
+//! [`inspect_stores`] lists every registered store and the global KV store
+//! with their keys and subscription counts. Values are shown for stores that
+//! opted in with [`Store::enable_debug`].
 
 use rustc_hash::FxHashMap;
+use serde::{Deserialize, Serialize};
 use std::any::{Any, TypeId};
+use std::fmt::Debug;
 use std::sync::{Arc, Mutex, RwLock};
 
 /// A typed store for a specific state type
@@ -51,11 +59,19 @@ pub struct Store<T: Clone + Send + Sync + 'static> {
     /// State instances keyed by string ID
     instances: RwLock<FxHashMap<String, T>>,
     /// Subscribers for each instance
-    subscribers: RwLock<FxHashMap<String, Vec<Box<dyn Fn(&T) + Send + Sync>>>>,
+    subscribers: RwLock<FxHashMap<String, Vec<Subscriber<T>>>>,
     /// Factory function for creating default state
     default_factory: Box<dyn Fn() -> T + Send + Sync>,
+    /// Opt-in value formatter for inspection
+    debug_format: RwLock<Option<DebugFormat<T>>>,
 }
 
+/// Formats a store's state for [`inspect_stores`]
+type DebugFormat<T> = fn(&T) -> String;
+
+/// Callback run when a key's state changes
+type Subscriber<T> = Box<dyn Fn(&T) + Send + Sync>;
+
 impl<T: Clone + Send + Sync + Default + 'static> Store<T> {
     /// Create a new store with Default as the factory
     pub fn new() -> Self {
@@ -63,6 +79,7 @@ impl<T: Clone + Send + Sync + Default + 'static> Store<T> {
             instances: RwLock::new(FxHashMap::default()),
             subscribers: RwLock::new(FxHashMap::default()),
             default_factory: Box::new(T::default),
+            debug_format: RwLock::new(None),
         }
     }
 }
@@ -77,6 +94,7 @@ impl<T: Clone + Send + Sync + 'static> Store<T> {
             instances: RwLock::new(FxHashMap::default()),
             subscribers: RwLock::new(FxHashMap::default()),
             default_factory: Box::new(factory),
+            debug_format: RwLock::new(None),
         }
     }
 
@@ -194,6 +212,44 @@ impl<T: Clone + Send + Sync + 'static> Store<T> {
     pub fn contains(&self, key: &str) -> bool {
         self.instances.read().unwrap().contains_key(key)
     }
+
+    /// Number of subscribers for a key
+    pub fn subscriber_count(&self, key: &str) -> usize {
+        self.subscribers
+            .read()
+            .unwrap()
+            .get(key)
+            .map_or(0, |subs| subs.len())
+    }
+
+    /// Keys, subscription counts and (if enabled) values of this store
+    pub fn inspect(&self, name: &str) -> StoreInfo {
+        let format = *self.debug_format.read().unwrap();
+        let instances = self.instances.read().unwrap();
+        let mut keys: Vec<StoreKeyInfo> = instances
+            .iter()
+            .map(|(key, state)| StoreKeyInfo {
+                key: key.clone(),
+                subscribers: self.subscriber_count(key),
+                value: format.map(|format| format(state)),
+                type_name: None,
+            })
+            .collect();
+        keys.sort_by(|a, b| a.key.cmp(&b.key));
+
+        StoreInfo {
+            name: name.to_string(),
+            type_name: std::any::type_name::<T>().to_string(),
+            keys,
+        }
+    }
+}
+
+impl<T: Clone + Send + Sync + Debug + 'static> Store<T> {
+    /// Show this store's values in [`inspect_stores`]
+    pub fn enable_debug(&self) {
+        *self.debug_format.write().unwrap() = Some(|state| format!("{:?}", state));
+    }
 }
 
 impl<T: Clone + Send + Sync + Default + 'static> Default for Store<T> {
@@ -216,16 +272,24 @@ pub struct SubscriptionHandle {
 /// Type-erased store for the registry
 trait AnyStore: Send + Sync {
     fn as_any(&self) -> &dyn Any;
+    fn inspect(&self, name: &str) -> StoreInfo;
 }
 
 impl<T: Clone + Send + Sync + 'static> AnyStore for Store<T> {
     fn as_any(&self) -> &dyn Any {
         self
     }
+
+    fn inspect(&self, name: &str) -> StoreInfo {
+        Store::inspect(self, name)
+    }
 }
 
+/// Stores keyed by state type and name
+type StoreRegistry = FxHashMap<(TypeId, String), Arc<dyn AnyStore>>;
+
 /// Global registry of stores by name and type
-static STORE_REGISTRY: std::sync::LazyLock<Mutex<FxHashMap<(TypeId, String), Arc<dyn AnyStore>>>> =
+static STORE_REGISTRY: std::sync::LazyLock<Mutex<StoreRegistry>> =
     std::sync::LazyLock::new(|| Mutex::new(FxHashMap::default()));
 
 /// Create or get a store for a specific type and name
@@ -310,6 +374,41 @@ pub fn clear_all_stores() {
     STORE_REGISTRY.lock().unwrap().clear();
 }
 
+/// A store in [`inspect_stores`]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StoreInfo {
+    /// Registry name (`kv` for the global KV store)
+    pub name: String,
+    /// Rust type of the store's state
+    pub type_name: String,
+    /// Keys, sorted
+    pub keys: Vec<StoreKeyInfo>,
+}
+
+/// A key in a [`StoreInfo`]
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct StoreKeyInfo {
+    pub key: String,
+    pub subscribers: usize,
+    /// Formatted state, if the store opted in with [`Store::enable_debug`]
+    pub value: Option<String>,
+    /// Rust type of the value, for KV store entries
+    pub type_name: Option<String>,
+}
+
+/// List every registered store and the global KV store, sorted by name
+pub fn inspect_stores() -> Vec<StoreInfo> {
+    let mut stores: Vec<StoreInfo> = STORE_REGISTRY
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|((_, name), store)| store.inspect(name))
+        .collect();
+    stores.sort_by(|a, b| a.name.cmp(&b.name).then(a.type_name.cmp(&b.type_name)));
+    stores.push(GLOBAL_KV.inspect("kv"));
+    stores
+}
+
 // =============================================================================
 // CONVENIENCE FUNCTIONS
 // =============================================================================
@@ -364,9 +463,12 @@ where
 /// This is useful for heterogeneous state that doesn't fit into typed stores.
 /// Each value is stored as a type-erased `Box<dyn Any>`.
 pub struct KVStore {
-    values: RwLock<FxHashMap<String, Box<dyn Any + Send + Sync>>>,
+    values: RwLock<FxHashMap<String, KVEntry>>,
 }
 
+/// A [`KVStore`] value with its type name, shown by [`KVStore::inspect`]
+type KVEntry = (Box<dyn Any + Send + Sync>, &'static str);
+
 impl KVStore {
     pub fn new() -> Self {
         Self {
@@ -379,14 +481,14 @@ impl KVStore {
             .read()
             .unwrap()
             .get(key)
-            .and_then(|v| v.downcast_ref::<T>().cloned())
+            .and_then(|(v, _)| v.downcast_ref::<T>().cloned())
     }
 
     pub fn set<T: Send + Sync + 'static>(&self, key: &str, value: T) {
-        self.values
-            .write()
-            .unwrap()
-            .insert(key.to_string(), Box::new(value));
+        self.values.write().unwrap().insert(
+            key.to_string(),
+            (Box::new(value), std::any::type_name::<T>()),
+        );
     }
 
     pub fn delete(&self, key: &str) {
@@ -400,6 +502,29 @@ impl KVStore {
     pub fn clear(&self) {
         self.values.write().unwrap().clear();
     }
+
+    /// Keys and value types of this store
+    pub fn inspect(&self, name: &str) -> StoreInfo {
+        let mut keys: Vec<StoreKeyInfo> = self
+            .values
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(key, (_, type_name))| StoreKeyInfo {
+                key: key.clone(),
+                subscribers: 0,
+                value: None,
+                type_name: Some(type_name.to_string()),
+            })
+            .collect();
+        keys.sort_by(|a, b| a.key.cmp(&b.key));
+
+        StoreInfo {
+            name: name.to_string(),
+            type_name: "KVStore".to_string(),
+            keys,
+        }
+    }
 }
 
 impl Default for KVStore {
@@ -594,4 +719,43 @@ mod tests {
         );
         assert_eq!(call_count.load(Ordering::SeqCst), 2);
     }
+
+    #[test]
+    fn test_inspect_store() {
+        let store = Store::<TestState>::new();
+        store.set("b", TestState::default());
+        store.set(
+            "a",
+            TestState {
+                count: 3,
+                name: "x".into(),
+            },
+        );
+        let _handle = store.subscribe("a", |_| {});
+
+        let info = store.inspect("tabs");
+        assert_eq!(info.name, "tabs");
+        assert!(info.type_name.ends_with("TestState"));
+        let keys: Vec<&str> = info.keys.iter().map(|k| k.key.as_str()).collect();
+        assert_eq!(keys, vec!["a", "b"]);
+        assert_eq!(info.keys[0].subscribers, 1);
+        assert_eq!(info.keys[0].value, None);
+
+        store.enable_debug();
+        let info = store.inspect("tabs");
+        assert_eq!(
+            info.keys[0].value.as_deref(),
+            Some("TestState { count: 3, name: \"x\" }")
+        );
+    }
+
+    #[test]
+    fn test_inspect_kv_store() {
+        let kv = KVStore::new();
+        kv.set("answer", 42u8);
+
+        let info = kv.inspect("kv");
+        assert_eq!(info.keys.len(), 1);
+        assert_eq!(info.keys[0].type_name.as_deref(), Some("u8"));
+    }
 }
@@ -0,0 +1,244 @@
+//! Brush evaluation
+//!
+//! SDF primitives sample brushes per pixel with [`sample_brush`], while
+//! paths hand tiny-skia a shader built by [`brush_to_shader`]. Both paths
+//! resolve gradient coordinates the same way so a gradient looks identical
+//! whether it fills a rounded rect or an arbitrary path.
+
+use junita_core::{
+    Brush, Color, Gradient, GradientSpace, GradientSpread, GradientStop, Point, Rect,
+};
+
+/// Resolve a point given in gradient space to local coordinates
+fn resolve(point: Point, space: GradientSpace, bounds: Rect) -> Point {
+    match space {
+        GradientSpace::UserSpace => point,
+        GradientSpace::ObjectBoundingBox => Point::new(
+            bounds.x() + point.x * bounds.width(),
+            bounds.y() + point.y * bounds.height(),
+        ),
+    }
+}
+
+/// Apply a spread method to a raw gradient parameter
+fn spread(t: f32, spread: GradientSpread) -> f32 {
+    match spread {
+        GradientSpread::Pad => t.clamp(0.0, 1.0),
+        GradientSpread::Repeat => t - t.floor(),
+        GradientSpread::Reflect => {
+            let m = t.rem_euclid(2.0);
+            if m > 1.0 {
+                2.0 - m
+            } else {
+                m
+            }
+        }
+    }
+}
+
+/// Interpolate the colour at `t` (0.0-1.0) along a list of stops
+pub fn color_at(stops: &[GradientStop], t: f32) -> Color {
+    let Some(first) = stops.first() else {
+        return Color::TRANSPARENT;
+    };
+    if t <= first.offset {
+        return first.color;
+    }
+    for pair in stops.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        if t <= b.offset {
+            let span = (b.offset - a.offset).max(f32::EPSILON);
+            return Color::lerp(&a.color, &b.color, (t - a.offset) / span);
+        }
+    }
+    stops.last().map(|s| s.color).unwrap_or(first.color)
+}
+
+/// Sample a gradient at a local point
+pub fn sample_gradient(gradient: &Gradient, p: Point, bounds: Rect) -> Color {
+    match gradient {
+        Gradient::Linear {
+            start,
+            end,
+            stops,
+            space,
+            spread: mode,
+        } => {
+            let s = resolve(*start, *space, bounds);
+            let e = resolve(*end, *space, bounds);
+            let dx = e.x - s.x;
+            let dy = e.y - s.y;
+            let len_sq = (dx * dx + dy * dy).max(f32::EPSILON);
+            let t = ((p.x - s.x) * dx + (p.y - s.y) * dy) / len_sq;
+            color_at(stops, spread(t, *mode))
+        }
+        Gradient::Radial {
+            center,
+            radius,
+            stops,
+            space,
+            spread: mode,
+            ..
+        } => {
+            let c = resolve(*center, *space, bounds);
+            let r = match space {
+                GradientSpace::UserSpace => *radius,
+                GradientSpace::ObjectBoundingBox => radius * bounds.width().max(bounds.height()),
+            };
+            let dist = ((p.x - c.x).powi(2) + (p.y - c.y).powi(2)).sqrt();
+            color_at(stops, spread(dist / r.max(f32::EPSILON), *mode))
+        }
+        Gradient::Conic {
+            center,
+            start_angle,
+            stops,
+            space,
+        } => {
+            let c = resolve(*center, *space, bounds);
+            let angle = (p.y - c.y).atan2(p.x - c.x) - start_angle;
+            let t = angle.rem_euclid(std::f32::consts::TAU) / std::f32::consts::TAU;
+            color_at(stops, t)
+        }
+    }
+}
+
+/// Sample a brush at a local point
+///
+/// Glass and blur brushes have no backdrop to sample on the CPU, so they
+/// are approximated by their tint. Image brushes are resolved by the
+/// context before reaching here and sample as transparent.
+pub fn sample_brush(brush: &Brush, p: Point, bounds: Rect) -> Color {
+    match brush {
+        Brush::Solid(color) => *color,
+        Brush::Gradient(gradient) => sample_gradient(gradient, p, bounds),
+        Brush::Glass(glass) => glass.tint,
+        Brush::Blur(blur) => blur.tint.unwrap_or(Color::TRANSPARENT),
+        Brush::Image(_) => Color::TRANSPARENT,
+    }
+}
+
+/// Whether a brush samples to the same colour everywhere
+pub fn is_uniform(brush: &Brush) -> bool {
+    !matches!(brush, Brush::Gradient(_))
+}
+
+/// Convert a colour to a tiny-skia colour, scaling alpha by `opacity`
+pub fn to_skia_color(color: Color, opacity: f32) -> tiny_skia::Color {
+    tiny_skia::Color::from_rgba(
+        color.r.clamp(0.0, 1.0),
+        color.g.clamp(0.0, 1.0),
+        color.b.clamp(0.0, 1.0),
+        (color.a * opacity).clamp(0.0, 1.0),
+    )
+    .unwrap_or(tiny_skia::Color::TRANSPARENT)
+}
+
+fn to_skia_stops(stops: &[GradientStop], opacity: f32) -> Vec<tiny_skia::GradientStop> {
+    stops
+        .iter()
+        .map(|s| tiny_skia::GradientStop::new(s.offset, to_skia_color(s.color, opacity)))
+        .collect()
+}
+
+fn to_skia_spread(spread: GradientSpread) -> tiny_skia::SpreadMode {
+    match spread {
+        GradientSpread::Pad => tiny_skia::SpreadMode::Pad,
+        GradientSpread::Repeat => tiny_skia::SpreadMode::Repeat,
+        GradientSpread::Reflect => tiny_skia::SpreadMode::Reflect,
+    }
+}
+
+/// Build a tiny-skia shader for a brush in local coordinates
+///
+/// Conic gradients have no tiny-skia equivalent and fall back to their
+/// first stop colour, like the GPU path falls back to a radial gradient.
+pub fn brush_to_shader(brush: &Brush, bounds: Rect, opacity: f32) -> tiny_skia::Shader<'static> {
+    let solid = |c: Color| tiny_skia::Shader::SolidColor(to_skia_color(c, opacity));
+    match brush {
+        Brush::Gradient(Gradient::Linear {
+            start,
+            end,
+            stops,
+            space,
+            spread,
+        }) => {
+            let s = resolve(*start, *space, bounds);
+            let e = resolve(*end, *space, bounds);
+            tiny_skia::LinearGradient::new(
+                tiny_skia::Point::from_xy(s.x, s.y),
+                tiny_skia::Point::from_xy(e.x, e.y),
+                to_skia_stops(stops, opacity),
+                to_skia_spread(*spread),
+                tiny_skia::Transform::identity(),
+            )
+            .unwrap_or_else(|| solid(stops.first().map(|s| s.color).unwrap_or_default()))
+        }
+        Brush::Gradient(Gradient::Radial {
+            center,
+            radius,
+            focal,
+            stops,
+            space,
+            spread,
+        }) => {
+            let c = resolve(*center, *space, bounds);
+            let f = focal.map(|f| resolve(f, *space, bounds)).unwrap_or(c);
+            let r = match space {
+                GradientSpace::UserSpace => *radius,
+                GradientSpace::ObjectBoundingBox => radius * bounds.width().max(bounds.height()),
+            };
+            tiny_skia::RadialGradient::new(
+                tiny_skia::Point::from_xy(f.x, f.y),
+                tiny_skia::Point::from_xy(c.x, c.y),
+                r,
+                to_skia_stops(stops, opacity),
+                to_skia_spread(*spread),
+                tiny_skia::Transform::identity(),
+            )
+            .unwrap_or_else(|| solid(stops.first().map(|s| s.color).unwrap_or_default()))
+        }
+        Brush::Gradient(gradient @ Gradient::Conic { .. }) => solid(gradient.first_color()),
+        other => solid(sample_brush(other, bounds.center(), bounds)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_linear_gradient_sampling() {
+        let gradient = Gradient::linear(
+            Point::new(0.0, 0.0),
+            Point::new(100.0, 0.0),
+            Color::BLACK,
+            Color::WHITE,
+        );
+        let bounds = Rect::new(0.0, 0.0, 100.0, 10.0);
+
+        let mid = sample_gradient(&gradient, Point::new(50.0, 5.0), bounds);
+        assert!((mid.r - 0.5).abs() < 1e-3);
+
+        // Pad spread clamps past the end point
+        let past = sample_gradient(&gradient, Point::new(200.0, 5.0), bounds);
+        assert_eq!(past, Color::WHITE);
+    }
+
+    #[test]
+    fn test_bounding_box_space() {
+        let gradient = Gradient::Linear {
+            start: Point::new(0.0, 0.0),
+            end: Point::new(1.0, 0.0),
+            stops: vec![
+                GradientStop::new(0.0, Color::BLACK),
+                GradientStop::new(1.0, Color::WHITE),
+            ],
+            space: GradientSpace::ObjectBoundingBox,
+            spread: GradientSpread::Pad,
+        };
+        let bounds = Rect::new(100.0, 0.0, 200.0, 10.0);
+
+        let mid = sample_gradient(&gradient, Point::new(200.0, 5.0), bounds);
+        assert!((mid.r - 0.5).abs() < 1e-3);
+    }
+}
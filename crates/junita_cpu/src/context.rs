@@ -0,0 +1,1145 @@
+//! CPU paint context - software implementation of DrawContext
+//!
+//! `CpuPaintContext` rasterizes draw calls immediately into a tiny-skia
+//! pixmap. SDF primitives (rounded rects, circles, shadows, borders) are
+//! shaded per pixel with the same distance functions as the GPU shader;
+//! arbitrary paths, clips and image patterns go through tiny-skia.
+
+use std::collections::HashMap;
+
+use junita_core::{
+    Affine2D, BillboardFacing, BlendMode, Brush, Camera, ClipShape, Color, CornerRadius,
+    DrawCommand, DrawContext, Environment, FontWeight, ImageBrush, ImageFit, ImageId, ImageOptions,
+    LayerConfig, LayerId, Light, Mat4, MaterialId, MeshId, MeshInstance, Path, Point,
+    RecordingContext, Rect, SdfBuilder, Shadow, Size, Stroke, TextAlign, TextBaseline, TextStyle,
+    Transform,
+};
+use junita_recorder::testing::CapturedFrame;
+use junita_text::{TextAlignment, TextAnchor};
+
+use crate::brush::{brush_to_shader, is_uniform, sample_brush, to_skia_color};
+use crate::image::{demultiply, CpuImageStore};
+use crate::path::{clip_to_path, rect_path, to_skia_path, to_skia_stroke};
+use crate::sdf::{coverage, gaussian_shadow, outset, outset_radius, sd_circle, sd_rounded_rect};
+use crate::text::{CpuTextContext, TextRequest};
+
+/// Largest render target edge in physical pixels
+///
+/// Viewports beyond this are clamped rather than failing to allocate; content
+/// past the edge is clipped.
+pub const MAX_TARGET_DIMENSION: u32 = 16384;
+
+/// Offscreen layer saved by `push_layer`
+struct LayerState {
+    /// The parent render target, restored on `pop_layer`
+    parent: tiny_skia::Pixmap,
+    config: LayerConfig,
+}
+
+/// Physical edge length for a target, clamped to what `Pixmap` can allocate
+fn target_dimension(physical: f32) -> u32 {
+    // `as` saturates, and maps NaN to 0
+    (physical.ceil() as u32).clamp(1, MAX_TARGET_DIMENSION)
+}
+
+/// Software draw context rendering into an RGBA pixmap
+pub struct CpuPaintContext {
+    target: tiny_skia::Pixmap,
+    transform_stack: Vec<Affine2D>,
+    clip_stack: Vec<Option<tiny_skia::Mask>>,
+    opacity_stack: Vec<f32>,
+    blend_mode_stack: Vec<BlendMode>,
+    layer_stack: Vec<LayerState>,
+    named_layers: HashMap<LayerId, tiny_skia::Pixmap>,
+    viewport: Size,
+    scale_factor: f32,
+    z_layer: u32,
+    text: CpuTextContext,
+    images: CpuImageStore,
+}
+
+impl CpuPaintContext {
+    /// Create a context with the given logical viewport size at 1x scale
+    pub fn new(width: f32, height: f32) -> Self {
+        Self::with_scale_factor(width, height, 1.0)
+    }
+
+    /// Create a context with a HiDPI scale factor
+    ///
+    /// Drawing uses logical coordinates; the pixmap is `viewport * scale_factor`
+    /// physical pixels, clamped to `1..=MAX_TARGET_DIMENSION` on each edge so
+    /// zero-sized (minimized) and oversized viewports never fail to allocate.
+    pub fn with_scale_factor(width: f32, height: f32, scale_factor: f32) -> Self {
+        let scale_factor = scale_factor.max(0.01);
+        let pw = target_dimension(width * scale_factor);
+        let ph = target_dimension(height * scale_factor);
+        Self {
+            target: tiny_skia::Pixmap::new(pw, ph)
+                .expect("target dimensions are clamped to a valid pixmap size"),
+            transform_stack: vec![Affine2D::scale(scale_factor, scale_factor)],
+            clip_stack: vec![None],
+            opacity_stack: vec![1.0],
+            blend_mode_stack: vec![BlendMode::Normal],
+            layer_stack: Vec::new(),
+            named_layers: HashMap::new(),
+            viewport: Size::new(width, height),
+            scale_factor,
+            z_layer: 0,
+            text: CpuTextContext::new(),
+            images: CpuImageStore::new(),
+        }
+    }
+
+    /// Physical width of the render target in pixels
+    pub fn physical_width(&self) -> u32 {
+        self.target.width()
+    }
+
+    /// Physical height of the render target in pixels
+    pub fn physical_height(&self) -> u32 {
+        self.target.height()
+    }
+
+    /// Scale factor between logical and physical pixels
+    pub fn scale_factor(&self) -> f32 {
+        self.scale_factor
+    }
+
+    /// Fill the whole target with a colour, ignoring clips and transforms
+    pub fn clear(&mut self, color: Color) {
+        self.target.fill(to_skia_color(color, 1.0));
+    }
+
+    /// Fill a physical-pixel rectangle `[x, y, width, height]` with a
+    /// colour, replacing what was there and ignoring clips and transforms
+    pub fn clear_rect(&mut self, [x, y, width, height]: [u32; 4], color: Color) {
+        let Some(rect) =
+            tiny_skia::Rect::from_xywh(x as f32, y as f32, width as f32, height as f32)
+        else {
+            return;
+        };
+        let mut paint = tiny_skia::Paint::default();
+        paint.set_color(to_skia_color(color, 1.0));
+        paint.blend_mode = tiny_skia::BlendMode::Source;
+        self.target
+            .fill_rect(rect, &paint, tiny_skia::Transform::identity(), None);
+    }
+
+    /// Reset all state stacks and clear the target to transparent
+    pub fn reset(&mut self) {
+        self.target.fill(tiny_skia::Color::TRANSPARENT);
+        self.transform_stack = vec![Affine2D::scale(self.scale_factor, self.scale_factor)];
+        self.clip_stack = vec![None];
+        self.opacity_stack = vec![1.0];
+        self.blend_mode_stack = vec![BlendMode::Normal];
+        self.layer_stack.clear();
+        self.z_layer = 0;
+    }
+
+    /// Text context used for glyph shaping (load fonts through this)
+    pub fn text(&mut self) -> &mut CpuTextContext {
+        &mut self.text
+    }
+
+    /// Image store used by `draw_image` and image brushes
+    pub fn images(&mut self) -> &mut CpuImageStore {
+        &mut self.images
+    }
+
+    /// Borrow the premultiplied pixmap
+    pub fn pixmap(&self) -> &tiny_skia::Pixmap {
+        &self.target
+    }
+
+    /// Straight-alpha RGBA8 copy of the rendered pixels
+    pub fn to_rgba(&self) -> Vec<u8> {
+        let mut data = self.target.data().to_vec();
+        demultiply(&mut data);
+        data
+    }
+
+    /// Consume the context and return its pixels as a captured frame
+    pub fn into_frame(self) -> CapturedFrame {
+        let (width, height) = (self.target.width(), self.target.height());
+        let mut data = self.target.take();
+        demultiply(&mut data);
+        CapturedFrame::new(data, width, height)
+    }
+
+    /// Replay recorded draw commands into this context
+    ///
+    /// This lets a `RecordingContext` (or `PaintContext`) command list be
+    /// rasterized without a GPU.
+    pub fn replay(&mut self, commands: &[DrawCommand]) {
+        for command in commands {
+            match command {
+                DrawCommand::PushTransform(t) => self.push_transform(t.clone()),
+                DrawCommand::PopTransform => self.pop_transform(),
+                DrawCommand::PushClip(shape) => self.push_clip(shape.clone()),
+                DrawCommand::PopClip => self.pop_clip(),
+                DrawCommand::PushOpacity(o) => self.push_opacity(*o),
+                DrawCommand::PopOpacity => self.pop_opacity(),
+                DrawCommand::PushBlendMode(m) => self.push_blend_mode(*m),
+                DrawCommand::PopBlendMode => self.pop_blend_mode(),
+                DrawCommand::FillPath { path, brush } => self.fill_path(path, brush.clone()),
+                DrawCommand::StrokePath {
+                    path,
+                    stroke,
+                    brush,
+                } => self.stroke_path(path, stroke, brush.clone()),
+                DrawCommand::FillRect {
+                    rect,
+                    corner_radius,
+                    brush,
+                } => self.fill_rect(*rect, *corner_radius, brush.clone()),
+                DrawCommand::StrokeRect {
+                    rect,
+                    corner_radius,
+                    stroke,
+                    brush,
+                } => self.stroke_rect(*rect, *corner_radius, stroke, brush.clone()),
+                DrawCommand::FillCircle {
+                    center,
+                    radius,
+                    brush,
+                } => self.fill_circle(*center, *radius, brush.clone()),
+                DrawCommand::StrokeCircle {
+                    center,
+                    radius,
+                    stroke,
+                    brush,
+                } => self.stroke_circle(*center, *radius, stroke, brush.clone()),
+                DrawCommand::DrawText {
+                    text,
+                    origin,
+                    style,
+                } => self.draw_text(text, *origin, style),
+                DrawCommand::DrawImage {
+                    image,
+                    rect,
+                    options,
+                } => self.draw_image(*image, *rect, options),
+                DrawCommand::DrawShadow {
+                    rect,
+                    corner_radius,
+                    shadow,
+                } => self.draw_shadow(*rect, *corner_radius, *shadow),
+                DrawCommand::DrawInnerShadow {
+                    rect,
+                    corner_radius,
+                    shadow,
+                } => self.draw_inner_shadow(*rect, *corner_radius, *shadow),
+                DrawCommand::DrawCircleShadow {
+                    center,
+                    radius,
+                    shadow,
+                } => self.draw_circle_shadow(*center, *radius, *shadow),
+                DrawCommand::DrawCircleInnerShadow {
+                    center,
+                    radius,
+                    shadow,
+                } => self.draw_circle_inner_shadow(*center, *radius, *shadow),
+                DrawCommand::SetCamera(camera) => self.set_camera(camera),
+                DrawCommand::DrawMesh {
+                    mesh,
+                    material,
+                    transform,
+                } => self.draw_mesh(*mesh, *material, *transform),
+                DrawCommand::DrawMeshInstanced { mesh, instances } => {
+                    self.draw_mesh_instanced(*mesh, instances)
+                }
+                DrawCommand::AddLight(light) => self.add_light(light.clone()),
+                DrawCommand::SetEnvironment(env) => self.set_environment(env),
+                DrawCommand::PushLayer(config) => self.push_layer(config.clone()),
+                DrawCommand::PopLayer => self.pop_layer(),
+                DrawCommand::SampleLayer {
+                    id,
+                    source_rect,
+                    dest_rect,
+                } => self.sample_layer(*id, *source_rect, *dest_rect),
+            }
+        }
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // State helpers
+    // ─────────────────────────────────────────────────────────────────────────
+
+    fn current_affine(&self) -> Affine2D {
+        self.transform_stack
+            .last()
+            .copied()
+            .unwrap_or(Affine2D::IDENTITY)
+    }
+
+    fn skia_transform(&self) -> tiny_skia::Transform {
+        let [a, b, c, d, tx, ty] = self.current_affine().elements;
+        tiny_skia::Transform::from_row(a, b, c, d, tx, ty)
+    }
+
+    fn clip_mask(&self) -> Option<&tiny_skia::Mask> {
+        self.clip_stack.last().and_then(|m| m.as_ref())
+    }
+
+    fn skia_blend_mode(&self) -> tiny_skia::BlendMode {
+        to_skia_blend_mode(self.current_blend_mode())
+    }
+
+    fn paint(&self, brush: &Brush, bounds: Rect) -> tiny_skia::Paint<'static> {
+        tiny_skia::Paint {
+            shader: brush_to_shader(brush, bounds, self.current_opacity()),
+            blend_mode: self.skia_blend_mode(),
+            anti_alias: true,
+            ..Default::default()
+        }
+    }
+
+    /// Shade every pixel covered by `local_bounds` with a per-pixel function
+    ///
+    /// `shader` receives the pixel centre in local coordinates and the size
+    /// of one device pixel in local units, and returns premultiplied RGBA.
+    /// The result is composited with the current opacity, blend mode and clip.
+    fn shade<F>(&mut self, local_bounds: Rect, mut shader: F)
+    where
+        F: FnMut(Point, f32) -> [f32; 4],
+    {
+        let affine = self.current_affine();
+        let Some(inverse) = invert(&affine) else {
+            return;
+        };
+        let [a, b, c, d, _, _] = affine.elements;
+        let px = 1.0 / (a * d - b * c).abs().sqrt().max(f32::EPSILON);
+
+        let corners = [
+            local_bounds.origin,
+            Point::new(local_bounds.x() + local_bounds.width(), local_bounds.y()),
+            Point::new(local_bounds.x(), local_bounds.y() + local_bounds.height()),
+            Point::new(
+                local_bounds.x() + local_bounds.width(),
+                local_bounds.y() + local_bounds.height(),
+            ),
+        ];
+        let (mut min_x, mut min_y) = (f32::INFINITY, f32::INFINITY);
+        let (mut max_x, mut max_y) = (f32::NEG_INFINITY, f32::NEG_INFINITY);
+        for corner in corners {
+            let p = affine.transform_point(corner);
+            min_x = min_x.min(p.x);
+            min_y = min_y.min(p.y);
+            max_x = max_x.max(p.x);
+            max_y = max_y.max(p.y);
+        }
+        if !(min_x.is_finite() && min_y.is_finite() && max_x.is_finite() && max_y.is_finite()) {
+            return;
+        }
+
+        let x0 = (min_x.floor() as i64).max(0);
+        let y0 = (min_y.floor() as i64).max(0);
+        let x1 = (max_x.ceil() as i64).min(self.target.width() as i64);
+        let y1 = (max_y.ceil() as i64).min(self.target.height() as i64);
+        if x1 <= x0 || y1 <= y0 {
+            return;
+        }
+
+        let (w, h) = ((x1 - x0) as u32, (y1 - y0) as u32);
+        let Some(mut tile) = tiny_skia::Pixmap::new(w, h) else {
+            return;
+        };
+        let data = tile.data_mut();
+        for iy in 0..h {
+            for ix in 0..w {
+                let device = Point::new(x0 as f32 + ix as f32 + 0.5, y0 as f32 + iy as f32 + 0.5);
+                let [r, g, b, alpha] = shader(inverse.transform_point(device), px);
+                if alpha <= 0.0 {
+                    continue;
+                }
+                let alpha = alpha.min(1.0);
+                let idx = ((iy * w + ix) * 4) as usize;
+                data[idx] = to_u8(r.min(alpha));
+                data[idx + 1] = to_u8(g.min(alpha));
+                data[idx + 2] = to_u8(b.min(alpha));
+                data[idx + 3] = to_u8(alpha);
+            }
+        }
+
+        let paint = tiny_skia::PixmapPaint {
+            opacity: self.current_opacity().clamp(0.0, 1.0),
+            blend_mode: self.skia_blend_mode(),
+            quality: tiny_skia::FilterQuality::Nearest,
+        };
+        let mask = self.clip_stack.last().and_then(|m| m.as_ref());
+        self.target.draw_pixmap(
+            x0 as i32,
+            y0 as i32,
+            tile.as_ref(),
+            &paint,
+            tiny_skia::Transform::identity(),
+            mask,
+        );
+    }
+
+    /// Fill a shape described by a signed distance function with a brush
+    fn shade_brush<F>(&mut self, bounds: Rect, brush: &Brush, sd: F)
+    where
+        F: Fn(Point) -> f32,
+    {
+        let uniform = is_uniform(brush).then(|| sample_brush(brush, bounds.center(), bounds));
+        self.shade(outset(bounds, 2.0), |p, px| {
+            let cov = coverage(sd(p), px);
+            if cov <= 0.0 {
+                return [0.0; 4];
+            }
+            let color = uniform.unwrap_or_else(|| sample_brush(brush, p, bounds));
+            premultiply(color, cov)
+        });
+    }
+
+    /// Fill a rounded rect with an image brush
+    fn fill_image_brush(&mut self, rect: Rect, corner_radius: CornerRadius, brush: &ImageBrush) {
+        let Some(id) = self.images.resolve_source(&brush.source) else {
+            return;
+        };
+        let Some(pixmap) = self.images.get(id).cloned() else {
+            return;
+        };
+        let pixmap = match tint_pixmap(&pixmap, brush.tint) {
+            Some(tinted) => tinted,
+            None => pixmap,
+        };
+
+        let (iw, ih) = (pixmap.width() as f32, pixmap.height() as f32);
+        let (sx, sy) = match brush.fit {
+            ImageFit::Fill => (rect.width() / iw, rect.height() / ih),
+            ImageFit::Cover => {
+                let s = (rect.width() / iw).max(rect.height() / ih);
+                (s, s)
+            }
+            ImageFit::Contain => {
+                let s = (rect.width() / iw).min(rect.height() / ih);
+                (s, s)
+            }
+            ImageFit::Tile => (1.0, 1.0),
+        };
+        let ox = rect.x() + (rect.width() - iw * sx) * brush.position.x;
+        let oy = rect.y() + (rect.height() - ih * sy) * brush.position.y;
+        let spread = if brush.fit == ImageFit::Tile {
+            tiny_skia::SpreadMode::Repeat
+        } else {
+            tiny_skia::SpreadMode::Pad
+        };
+
+        let shader = tiny_skia::Pattern::new(
+            pixmap.as_ref(),
+            spread,
+            tiny_skia::FilterQuality::Bilinear,
+            (brush.opacity * self.current_opacity()).clamp(0.0, 1.0),
+            tiny_skia::Transform::from_row(sx, 0.0, 0.0, sy, ox, oy),
+        );
+        let paint = tiny_skia::Paint {
+            shader,
+            blend_mode: self.skia_blend_mode(),
+            anti_alias: true,
+            ..Default::default()
+        };
+
+        // Contain leaves letterbox space uncovered rather than padding edges
+        let draw_rect = if brush.fit == ImageFit::Contain {
+            Rect::new(ox, oy, iw * sx, ih * sy)
+                .intersection(&rect)
+                .unwrap_or(Rect::ZERO)
+        } else {
+            rect
+        };
+        let Some(path) = to_skia_path(&Path::rounded_rect(draw_rect, corner_radius)) else {
+            return;
+        };
+        let transform = self.skia_transform();
+        let mask = self.clip_stack.last().and_then(|m| m.as_ref());
+        self.target
+            .fill_path(&path, &paint, tiny_skia::FillRule::Winding, transform, mask);
+    }
+
+    /// Draw text laid out by the text context
+    pub(crate) fn draw_text_request(&mut self, request: &TextRequest<'_>) {
+        let glyphs = match self.text.layout(request) {
+            Ok(glyphs) => glyphs,
+            Err(err) => {
+                tracing::debug!("junita_cpu: text layout failed: {}", err);
+                return;
+            }
+        };
+
+        // Temporarily take the text context so glyph sampling can borrow it
+        // while `shade` borrows the render target
+        let text = std::mem::take(&mut self.text);
+        for glyph in &glyphs {
+            let [gx, gy, gw, gh] = glyph.bounds;
+            if gw <= 0.0 || gh <= 0.0 {
+                continue;
+            }
+            let [u0, v0, u1, v1] = glyph.uv_bounds;
+            let bounds = Rect::new(gx, gy, gw, gh);
+            self.shade(bounds, |p, _| {
+                let tx = (p.x - gx) / gw;
+                let ty = (p.y - gy) / gh;
+                if !(0.0..1.0).contains(&tx) || !(0.0..1.0).contains(&ty) {
+                    return [0.0; 4];
+                }
+                text.sample(glyph, u0 + (u1 - u0) * tx, v0 + (v1 - v0) * ty)
+            });
+        }
+        self.text = text;
+    }
+}
+
+impl DrawContext for CpuPaintContext {
+    fn push_transform(&mut self, transform: Transform) {
+        let current = self.current_affine();
+        let next = match transform {
+            Transform::Affine2D(affine) => current.then(&affine),
+            // 3D transforms have no meaning in the 2D software renderer
+            Transform::Mat4(_) => current,
+        };
+        self.transform_stack.push(next);
+    }
+
+    fn pop_transform(&mut self) {
+        if self.transform_stack.len() > 1 {
+            self.transform_stack.pop();
+        }
+    }
+
+    fn current_transform(&self) -> Transform {
+        Transform::Affine2D(self.current_affine())
+    }
+
+    fn push_clip(&mut self, shape: ClipShape) {
+        let transform = self.skia_transform();
+        let (w, h) = (self.target.width(), self.target.height());
+        let mask = match (clip_to_path(&shape), self.clip_mask()) {
+            (Some(path), Some(parent)) => {
+                let mut mask = parent.clone();
+                mask.intersect_path(&path, tiny_skia::FillRule::Winding, true, transform);
+                Some(mask)
+            }
+            (Some(path), None) => tiny_skia::Mask::new(w, h).map(|mut mask| {
+                mask.fill_path(&path, tiny_skia::FillRule::Winding, true, transform);
+                mask
+            }),
+            // Degenerate clip shapes clip everything away
+            (None, _) => tiny_skia::Mask::new(w, h),
+        };
+        self.clip_stack.push(mask);
+    }
+
+    fn pop_clip(&mut self) {
+        if self.clip_stack.len() > 1 {
+            self.clip_stack.pop();
+        }
+    }
+
+    fn push_opacity(&mut self, opacity: f32) {
+        let current = self.current_opacity();
+        self.opacity_stack.push(current * opacity);
+    }
+
+    fn pop_opacity(&mut self) {
+        if self.opacity_stack.len() > 1 {
+            self.opacity_stack.pop();
+        }
+    }
+
+    fn push_blend_mode(&mut self, mode: BlendMode) {
+        self.blend_mode_stack.push(mode);
+    }
+
+    fn pop_blend_mode(&mut self) {
+        if self.blend_mode_stack.len() > 1 {
+            self.blend_mode_stack.pop();
+        }
+    }
+
+    fn set_z_layer(&mut self, layer: u32) {
+        // Draw calls are rasterized immediately in submission order, so
+        // z-layers need no extra bookkeeping beyond reporting them back
+        self.z_layer = layer;
+    }
+
+    fn z_layer(&self) -> u32 {
+        self.z_layer
+    }
+
+    fn fill_path(&mut self, path: &Path, brush: Brush) {
+        if let Brush::Image(image) = &brush {
+            self.push_clip(ClipShape::Path(path.clone()));
+            self.fill_image_brush(path.bounds(), CornerRadius::ZERO, image);
+            self.pop_clip();
+            return;
+        }
+        let Some(skia_path) = to_skia_path(path) else {
+            return;
+        };
+        let paint = self.paint(&brush, path.bounds());
+        let transform = self.skia_transform();
+        let mask = self.clip_stack.last().and_then(|m| m.as_ref());
+        self.target.fill_path(
+            &skia_path,
+            &paint,
+            tiny_skia::FillRule::Winding,
+            transform,
+            mask,
+        );
+    }
+
+    fn stroke_path(&mut self, path: &Path, stroke: &Stroke, brush: Brush) {
+        let Some(skia_path) = to_skia_path(path) else {
+            return;
+        };
+        let paint = self.paint(&brush, path.bounds());
+        let transform = self.skia_transform();
+        let mask = self.clip_stack.last().and_then(|m| m.as_ref());
+        self.target
+            .stroke_path(&skia_path, &paint, &to_skia_stroke(stroke), transform, mask);
+    }
+
+    fn fill_rect(&mut self, rect: Rect, corner_radius: CornerRadius, brush: Brush) {
+        if let Brush::Image(image) = &brush {
+            self.fill_image_brush(rect, corner_radius, image);
+            return;
+        }
+        self.shade_brush(rect, &brush, |p| sd_rounded_rect(p, rect, corner_radius));
+    }
+
+    fn fill_rect_with_per_side_border(
+        &mut self,
+        rect: Rect,
+        corner_radius: CornerRadius,
+        brush: Brush,
+        border_widths: [f32; 4],
+        border_color: Color,
+    ) {
+        if let Brush::Image(image) = &brush {
+            self.fill_image_brush(rect, corner_radius, image);
+        }
+        let [top, right, bottom, left] = border_widths;
+        let inner = Rect::new(
+            rect.x() + left,
+            rect.y() + top,
+            (rect.width() - left - right).max(0.0),
+            (rect.height() - top - bottom).max(0.0),
+        );
+        let inner_radius = CornerRadius::new(
+            (corner_radius.top_left - left.max(top)).max(0.0),
+            (corner_radius.top_right - right.max(top)).max(0.0),
+            (corner_radius.bottom_right - right.max(bottom)).max(0.0),
+            (corner_radius.bottom_left - left.max(bottom)).max(0.0),
+        );
+        let fill_brush = (!matches!(brush, Brush::Image(_))).then_some(&brush);
+        let uniform = fill_brush
+            .filter(|b| is_uniform(b))
+            .map(|b| sample_brush(b, rect.center(), rect));
+
+        self.shade(outset(rect, 2.0), |p, px| {
+            let outer = coverage(sd_rounded_rect(p, rect, corner_radius), px);
+            if outer <= 0.0 {
+                return [0.0; 4];
+            }
+            let inside = coverage(sd_rounded_rect(p, inner, inner_radius), px).min(outer);
+            let border = premultiply(border_color, outer - inside);
+            let fill = match fill_brush {
+                Some(b) => premultiply(uniform.unwrap_or_else(|| sample_brush(b, p, rect)), inside),
+                None => [0.0; 4],
+            };
+            [
+                fill[0] + border[0],
+                fill[1] + border[1],
+                fill[2] + border[2],
+                fill[3] + border[3],
+            ]
+        });
+    }
+
+    fn stroke_rect(
+        &mut self,
+        rect: Rect,
+        corner_radius: CornerRadius,
+        stroke: &Stroke,
+        brush: Brush,
+    ) {
+        let half = stroke.width * 0.5;
+        self.shade_brush(outset(rect, half), &brush, |p| {
+            sd_rounded_rect(p, rect, corner_radius).abs() - half
+        });
+    }
+
+    fn fill_circle(&mut self, center: Point, radius: f32, brush: Brush) {
+        let bounds = Rect::from_center(center, Size::new(radius * 2.0, radius * 2.0));
+        if let Brush::Image(image) = &brush {
+            self.fill_image_brush(bounds, CornerRadius::uniform(radius), image);
+            return;
+        }
+        self.shade_brush(bounds, &brush, |p| sd_circle(p, center, radius));
+    }
+
+    fn stroke_circle(&mut self, center: Point, radius: f32, stroke: &Stroke, brush: Brush) {
+        let half = stroke.width * 0.5;
+        let bounds = Rect::from_center(
+            center,
+            Size::new((radius + half) * 2.0, (radius + half) * 2.0),
+        );
+        self.shade_brush(bounds, &brush, |p| {
+            sd_circle(p, center, radius).abs() - half
+        });
+    }
+
+    fn draw_text(&mut self, text: &str, origin: Point, style: &TextStyle) {
+        let mut request = TextRequest::new(
+            text,
+            origin.x,
+            origin.y,
+            style.size,
+            [style.color.r, style.color.g, style.color.b, style.color.a],
+        );
+        request.alignment = match style.align {
+            TextAlign::Left => TextAlignment::Left,
+            TextAlign::Center => TextAlignment::Center,
+            TextAlign::Right => TextAlignment::Right,
+        };
+        request.anchor = match style.baseline {
+            TextBaseline::Top => TextAnchor::Top,
+            TextBaseline::Middle => TextAnchor::Center,
+            TextBaseline::Alphabetic | TextBaseline::Bottom => TextAnchor::Baseline,
+        };
+        request.weight = match style.weight {
+            FontWeight::Thin => 100,
+            FontWeight::Light => 300,
+            FontWeight::Regular => 400,
+            FontWeight::Medium => 500,
+            FontWeight::Bold => 700,
+            FontWeight::Black => 900,
+        };
+        let family = style.family.as_str();
+        if !family.is_empty() && family != "system-ui" {
+            request.font_name = Some(family);
+        }
+        self.draw_text_request(&request);
+    }
+
+    fn draw_image(&mut self, image: ImageId, rect: Rect, options: &ImageOptions) {
+        let Some(pixmap) = self.images.get(image) else {
+            tracing::debug!("junita_cpu: unknown image {:?}", image);
+            return;
+        };
+        let pixmap = match options.tint {
+            Some(tint) => tint_pixmap(pixmap, tint).unwrap_or_else(|| pixmap.clone()),
+            None => pixmap.clone(),
+        };
+
+        let src = options.source_rect.unwrap_or(Rect::new(
+            0.0,
+            0.0,
+            pixmap.width() as f32,
+            pixmap.height() as f32,
+        ));
+        if src.width() <= 0.0 || src.height() <= 0.0 {
+            return;
+        }
+        let sx = rect.width() / src.width();
+        let sy = rect.height() / src.height();
+        let shader = tiny_skia::Pattern::new(
+            pixmap.as_ref(),
+            tiny_skia::SpreadMode::Pad,
+            tiny_skia::FilterQuality::Bilinear,
+            (options.opacity * self.current_opacity()).clamp(0.0, 1.0),
+            tiny_skia::Transform::from_row(
+                sx,
+                0.0,
+                0.0,
+                sy,
+                rect.x() - src.x() * sx,
+                rect.y() - src.y() * sy,
+            ),
+        );
+        let paint = tiny_skia::Paint {
+            shader,
+            blend_mode: self.skia_blend_mode(),
+            anti_alias: true,
+            ..Default::default()
+        };
+        let Some(path) = rect_path(rect) else {
+            return;
+        };
+        let transform = self.skia_transform();
+        let mask = self.clip_stack.last().and_then(|m| m.as_ref());
+        self.target
+            .fill_path(&path, &paint, tiny_skia::FillRule::Winding, transform, mask);
+    }
+
+    fn draw_shadow(&mut self, rect: Rect, corner_radius: CornerRadius, shadow: Shadow) {
+        let shadow_rect = outset(rect.offset(shadow.offset_x, shadow.offset_y), shadow.spread);
+        let shadow_radius = outset_radius(corner_radius, shadow.spread);
+        let sigma = shadow.blur.max(0.0);
+        self.shade(outset(shadow_rect, sigma * 3.0 + 1.0), |p, px| {
+            let d = sd_rounded_rect(p, shadow_rect, shadow_radius);
+            premultiply(shadow.color, gaussian_shadow(d, sigma, px))
+        });
+    }
+
+    fn draw_inner_shadow(&mut self, rect: Rect, corner_radius: CornerRadius, shadow: Shadow) {
+        let inner_rect = outset(
+            rect.offset(shadow.offset_x, shadow.offset_y),
+            -shadow.spread,
+        );
+        let inner_radius = outset_radius(corner_radius, -shadow.spread);
+        let sigma = shadow.blur.max(0.0);
+        self.shade(outset(rect, 1.0), |p, px| {
+            let shape = coverage(sd_rounded_rect(p, rect, corner_radius), px);
+            if shape <= 0.0 {
+                return [0.0; 4];
+            }
+            let lit = gaussian_shadow(sd_rounded_rect(p, inner_rect, inner_radius), sigma, px);
+            premultiply(shadow.color, shape * (1.0 - lit))
+        });
+    }
+
+    fn draw_circle_shadow(&mut self, center: Point, radius: f32, shadow: Shadow) {
+        let c = Point::new(center.x + shadow.offset_x, center.y + shadow.offset_y);
+        let r = (radius + shadow.spread).max(0.0);
+        let sigma = shadow.blur.max(0.0);
+        let extent = (r + sigma * 3.0 + 1.0) * 2.0;
+        self.shade(Rect::from_center(c, Size::new(extent, extent)), |p, px| {
+            premultiply(shadow.color, gaussian_shadow(sd_circle(p, c, r), sigma, px))
+        });
+    }
+
+    fn draw_circle_inner_shadow(&mut self, center: Point, radius: f32, shadow: Shadow) {
+        let c = Point::new(center.x + shadow.offset_x, center.y + shadow.offset_y);
+        let r = (radius - shadow.spread).max(0.0);
+        let sigma = shadow.blur.max(0.0);
+        let extent = (radius + 1.0) * 2.0;
+        self.shade(
+            Rect::from_center(center, Size::new(extent, extent)),
+            |p, px| {
+                let shape = coverage(sd_circle(p, center, radius), px);
+                if shape <= 0.0 {
+                    return [0.0; 4];
+                }
+                let lit = gaussian_shadow(sd_circle(p, c, r), sigma, px);
+                premultiply(shadow.color, shape * (1.0 - lit))
+            },
+        );
+    }
+
+    fn sdf_build(&mut self, f: &mut dyn FnMut(&mut dyn SdfBuilder)) {
+        // Let the recording context lower SDF shapes to rects, circles and
+        // shadows, then rasterize those in local coordinates
+        let mut recording = RecordingContext::new(self.viewport);
+        recording.sdf_build(f);
+        self.replay(recording.commands());
+    }
+
+    fn set_camera(&mut self, _camera: &Camera) {
+        tracing::trace!("junita_cpu: 3D camera ignored by software renderer");
+    }
+
+    fn draw_mesh(&mut self, _mesh: MeshId, _material: MaterialId, _transform: Mat4) {
+        tracing::trace!("junita_cpu: mesh drawing not supported by software renderer");
+    }
+
+    fn draw_mesh_instanced(&mut self, _mesh: MeshId, _instances: &[MeshInstance]) {
+        tracing::trace!("junita_cpu: mesh drawing not supported by software renderer");
+    }
+
+    fn add_light(&mut self, _light: Light) {}
+
+    fn set_environment(&mut self, _env: &Environment) {}
+
+    fn billboard_draw(
+        &mut self,
+        _size: Size,
+        _transform: Mat4,
+        _facing: BillboardFacing,
+        f: &mut dyn FnMut(&mut dyn DrawContext),
+    ) {
+        // No 3D projection: draw billboard content flat in the current space
+        f(self);
+    }
+
+    fn viewport_3d_draw(
+        &mut self,
+        _rect: Rect,
+        _camera: &Camera,
+        _f: &mut dyn FnMut(&mut dyn DrawContext),
+    ) {
+        tracing::trace!("junita_cpu: 3D viewport skipped by software renderer");
+    }
+
+    fn push_layer(&mut self, config: LayerConfig) {
+        let (w, h) = (self.target.width(), self.target.height());
+        let Some(layer) = tiny_skia::Pixmap::new(w, h) else {
+            return;
+        };
+        let parent = std::mem::replace(&mut self.target, layer);
+        self.layer_stack.push(LayerState { parent, config });
+    }
+
+    fn pop_layer(&mut self) {
+        let Some(LayerState { parent, config }) = self.layer_stack.pop() else {
+            return;
+        };
+        let layer = std::mem::replace(&mut self.target, parent);
+        let paint = tiny_skia::PixmapPaint {
+            opacity: config.opacity.clamp(0.0, 1.0),
+            blend_mode: to_skia_blend_mode(config.blend_mode),
+            quality: tiny_skia::FilterQuality::Nearest,
+        };
+        self.target.draw_pixmap(
+            0,
+            0,
+            layer.as_ref(),
+            &paint,
+            tiny_skia::Transform::identity(),
+            None,
+        );
+        if let Some(id) = config.id {
+            self.named_layers.insert(id, layer);
+        }
+    }
+
+    fn sample_layer(&mut self, id: LayerId, source_rect: Rect, dest_rect: Rect) {
+        let Some(layer) = self.named_layers.get(&id) else {
+            return;
+        };
+        if source_rect.width() <= 0.0 || source_rect.height() <= 0.0 {
+            return;
+        }
+        // Layer pixels are in device space; source_rect is in logical units
+        let s = self.scale_factor;
+        let sx = dest_rect.width() / (source_rect.width() * s);
+        let sy = dest_rect.height() / (source_rect.height() * s);
+        let shader = tiny_skia::Pattern::new(
+            layer.as_ref(),
+            tiny_skia::SpreadMode::Pad,
+            tiny_skia::FilterQuality::Bilinear,
+            self.current_opacity().clamp(0.0, 1.0),
+            tiny_skia::Transform::from_row(
+                sx,
+                0.0,
+                0.0,
+                sy,
+                dest_rect.x() - source_rect.x() * s * sx,
+                dest_rect.y() - source_rect.y() * s * sy,
+            ),
+        );
+        let paint = tiny_skia::Paint {
+            shader,
+            blend_mode: self.skia_blend_mode(),
+            anti_alias: true,
+            ..Default::default()
+        };
+        let Some(path) = rect_path(dest_rect) else {
+            return;
+        };
+        let transform = self.skia_transform();
+        let mask = self.clip_stack.last().and_then(|m| m.as_ref());
+        self.target
+            .fill_path(&path, &paint, tiny_skia::FillRule::Winding, transform, mask);
+    }
+
+    fn viewport_size(&self) -> Size {
+        self.viewport
+    }
+
+    fn is_3d_context(&self) -> bool {
+        false
+    }
+
+    fn current_opacity(&self) -> f32 {
+        *self.opacity_stack.last().unwrap_or(&1.0)
+    }
+
+    fn current_blend_mode(&self) -> BlendMode {
+        self.blend_mode_stack
+            .last()
+            .copied()
+            .unwrap_or(BlendMode::Normal)
+    }
+}
+
+fn to_skia_blend_mode(mode: BlendMode) -> tiny_skia::BlendMode {
+    match mode {
+        BlendMode::Normal => tiny_skia::BlendMode::SourceOver,
+        BlendMode::Multiply => tiny_skia::BlendMode::Multiply,
+        BlendMode::Screen => tiny_skia::BlendMode::Screen,
+        BlendMode::Overlay => tiny_skia::BlendMode::Overlay,
+        BlendMode::Darken => tiny_skia::BlendMode::Darken,
+        BlendMode::Lighten => tiny_skia::BlendMode::Lighten,
+        BlendMode::ColorDodge => tiny_skia::BlendMode::ColorDodge,
+        BlendMode::ColorBurn => tiny_skia::BlendMode::ColorBurn,
+        BlendMode::HardLight => tiny_skia::BlendMode::HardLight,
+        BlendMode::SoftLight => tiny_skia::BlendMode::SoftLight,
+        BlendMode::Difference => tiny_skia::BlendMode::Difference,
+        BlendMode::Exclusion => tiny_skia::BlendMode::Exclusion,
+    }
+}
+
+fn invert(affine: &Affine2D) -> Option<Affine2D> {
+    let [a, b, c, d, tx, ty] = affine.elements;
+    let det = a * d - b * c;
+    if det.abs() < 1e-12 {
+        return None;
+    }
+    let inv = 1.0 / det;
+    Some(Affine2D {
+        elements: [
+            d * inv,
+            -b * inv,
+            -c * inv,
+            a * inv,
+            (c * ty - d * tx) * inv,
+            (b * tx - a * ty) * inv,
+        ],
+    })
+}
+
+fn premultiply(color: Color, coverage: f32) -> [f32; 4] {
+    let a = color.a * coverage;
+    [color.r * a, color.g * a, color.b * a, a]
+}
+
+fn to_u8(v: f32) -> u8 {
+    (v * 255.0 + 0.5).clamp(0.0, 255.0) as u8
+}
+
+/// Multiply a premultiplied pixmap by a tint colour
+fn tint_pixmap(pixmap: &tiny_skia::Pixmap, tint: Color) -> Option<tiny_skia::Pixmap> {
+    if tint == Color::WHITE {
+        return None;
+    }
+    let mut tinted = pixmap.clone();
+    let factors = [tint.r * tint.a, tint.g * tint.a, tint.b * tint.a, tint.a];
+    for px in tinted.data_mut().chunks_exact_mut(4) {
+        for (channel, factor) in px.iter_mut().zip(factors) {
+            *channel = to_u8(*channel as f32 / 255.0 * factor.clamp(0.0, 1.0));
+        }
+    }
+    Some(tinted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pixel(ctx: &CpuPaintContext, x: u32, y: u32) -> [u8; 4] {
+        let rgba = ctx.to_rgba();
+        let idx = ((y * ctx.physical_width() + x) * 4) as usize;
+        [rgba[idx], rgba[idx + 1], rgba[idx + 2], rgba[idx + 3]]
+    }
+
+    #[test]
+    fn test_fill_rect_and_transform() {
+        let mut ctx = CpuPaintContext::new(64.0, 64.0);
+        ctx.push_transform(Transform::translate(10.0, 10.0));
+        ctx.fill_rect(
+            Rect::new(0.0, 0.0, 20.0, 20.0),
+            CornerRadius::ZERO,
+            Color::RED.into(),
+        );
+        ctx.pop_transform();
+
+        assert_eq!(pixel(&ctx, 20, 20), [255, 0, 0, 255]);
+        assert_eq!(pixel(&ctx, 5, 5)[3], 0);
+        assert_eq!(pixel(&ctx, 35, 35)[3], 0);
+    }
+
+    #[test]
+    fn test_rounded_corners_are_cut() {
+        let mut ctx = CpuPaintContext::new(40.0, 40.0);
+        ctx.fill_rect(
+            Rect::new(0.0, 0.0, 40.0, 40.0),
+            CornerRadius::uniform(16.0),
+            Color::BLUE.into(),
+        );
+
+        assert_eq!(pixel(&ctx, 0, 0)[3], 0);
+        assert_eq!(pixel(&ctx, 20, 20), [0, 0, 255, 255]);
+    }
+
+    #[test]
+    fn test_clip_and_opacity() {
+        let mut ctx = CpuPaintContext::new(40.0, 40.0);
+        ctx.push_clip(ClipShape::rect(Rect::new(0.0, 0.0, 20.0, 40.0)));
+        ctx.push_opacity(0.5);
+        ctx.fill_rect(
+            Rect::new(0.0, 0.0, 40.0, 40.0),
+            CornerRadius::ZERO,
+            Color::WHITE.into(),
+        );
+        ctx.pop_opacity();
+        ctx.pop_clip();
+
+        let inside = pixel(&ctx, 10, 10);
+        assert!((inside[3] as i32 - 128).abs() <= 1);
+        assert_eq!(pixel(&ctx, 30, 10)[3], 0);
+    }
+
+    #[test]
+    fn test_shadow_falls_off() {
+        let mut ctx = CpuPaintContext::new(100.0, 100.0);
+        ctx.draw_shadow(
+            Rect::new(30.0, 30.0, 40.0, 40.0),
+            CornerRadius::ZERO,
+            Shadow::new(0.0, 0.0, 6.0, Color::BLACK),
+        );
+
+        let center = pixel(&ctx, 50, 50)[3];
+        let edge = pixel(&ctx, 30, 50)[3];
+        let far = pixel(&ctx, 5, 50)[3];
+        assert!(center > 250);
+        assert!(edge > 100 && edge < 160);
+        assert_eq!(far, 0);
+    }
+
+    #[test]
+    fn test_fill_path_and_layer() {
+        let mut ctx = CpuPaintContext::with_scale_factor(20.0, 20.0, 2.0);
+        assert_eq!(ctx.physical_width(), 40);
+
+        ctx.push_layer(LayerConfig::new().opacity(1.0));
+        ctx.fill_path(
+            &Path::rect(Rect::new(0.0, 0.0, 10.0, 10.0)),
+            Color::GREEN.into(),
+        );
+        ctx.pop_layer();
+
+        assert_eq!(pixel(&ctx, 10, 10), [0, 255, 0, 255]);
+        assert_eq!(pixel(&ctx, 30, 30)[3], 0);
+    }
+
+    #[test]
+    fn test_draw_image() {
+        let mut ctx = CpuPaintContext::new(8.0, 8.0);
+        let id = ctx
+            .images()
+            .add_rgba(&[255, 255, 0, 255].repeat(4), 2, 2)
+            .unwrap();
+        ctx.draw_image(id, Rect::new(0.0, 0.0, 8.0, 8.0), &ImageOptions::new());
+
+        assert_eq!(pixel(&ctx, 4, 4), [255, 255, 0, 255]);
+    }
+
+    #[test]
+    fn test_degenerate_viewports_are_clamped() {
+        let empty = CpuPaintContext::new(0.0, 0.0);
+        assert_eq!((empty.physical_width(), empty.physical_height()), (1, 1));
+
+        let invalid = CpuPaintContext::new(f32::NAN, -10.0);
+        assert_eq!(
+            (invalid.physical_width(), invalid.physical_height()),
+            (1, 1)
+        );
+
+        let huge = CpuPaintContext::with_scale_factor(1.0e9, 4.0, 2.0);
+        assert_eq!(huge.physical_width(), MAX_TARGET_DIMENSION);
+        assert_eq!(huge.physical_height(), 8);
+    }
+}
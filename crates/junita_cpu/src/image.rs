@@ -0,0 +1,180 @@
+//! CPU-side image storage
+//!
+//! `DrawContext::draw_image` refers to images by [`ImageId`]; the GPU
+//! renderer keeps textures for those ids, and [`CpuImageStore`] keeps
+//! premultiplied pixmaps instead. Image elements in a `RenderTree` refer to
+//! their source string, which is decoded once and cached by source.
+
+use std::collections::HashMap;
+
+use junita_core::ImageId;
+
+/// Images available to a [`CpuPaintContext`](crate::CpuPaintContext)
+#[derive(Default)]
+pub struct CpuImageStore {
+    images: HashMap<ImageId, tiny_skia::Pixmap>,
+    sources: HashMap<String, Option<ImageId>>,
+    next_id: u64,
+}
+
+impl CpuImageStore {
+    /// Create an empty image store
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register straight-alpha RGBA8 pixels under an explicit id
+    ///
+    /// Returns `false` if the pixel buffer does not match the dimensions.
+    pub fn insert_rgba(&mut self, id: ImageId, rgba: &[u8], width: u32, height: u32) -> bool {
+        match pixmap_from_rgba(rgba, width, height) {
+            Some(pixmap) => {
+                self.next_id = self.next_id.max(id.0 + 1);
+                self.images.insert(id, pixmap);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Register straight-alpha RGBA8 pixels and allocate a new id
+    pub fn add_rgba(&mut self, rgba: &[u8], width: u32, height: u32) -> Option<ImageId> {
+        let id = ImageId(self.next_id);
+        self.insert_rgba(id, rgba, width, height).then_some(id)
+    }
+
+    /// Look up an image by id
+    pub fn get(&self, id: ImageId) -> Option<&tiny_skia::Pixmap> {
+        self.images.get(&id)
+    }
+
+    /// Remove an image
+    pub fn remove(&mut self, id: ImageId) {
+        self.images.remove(&id);
+        self.sources.retain(|_, v| *v != Some(id));
+    }
+
+    /// Number of stored images
+    pub fn len(&self) -> usize {
+        self.images.len()
+    }
+
+    /// Whether the store is empty
+    pub fn is_empty(&self) -> bool {
+        self.images.is_empty()
+    }
+
+    /// Register pixels for an element source string (path, URL or data URI)
+    ///
+    /// Use this to provide image content in tests without touching the
+    /// filesystem; later lookups for `source` return the registered image.
+    pub fn insert_source(
+        &mut self,
+        source: impl Into<String>,
+        rgba: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Option<ImageId> {
+        let id = self.add_rgba(rgba, width, height);
+        self.sources.insert(source.into(), id);
+        id
+    }
+
+    /// Resolve an element source string to an image, decoding it on first use
+    ///
+    /// Failed loads are cached too, so a missing file is only reported once.
+    pub fn resolve_source(&mut self, source: &str) -> Option<ImageId> {
+        if let Some(id) = self.sources.get(source) {
+            return *id;
+        }
+        let id = self.decode_source(source);
+        self.sources.insert(source.to_string(), id);
+        id
+    }
+
+    #[cfg(feature = "images")]
+    fn decode_source(&mut self, source: &str) -> Option<ImageId> {
+        match junita_image::ImageData::load(junita_image::ImageSource::from_uri(source)) {
+            Ok(data) => self.add_rgba(data.pixels(), data.width(), data.height()),
+            Err(err) => {
+                tracing::warn!("junita_cpu: failed to load image '{}': {}", source, err);
+                None
+            }
+        }
+    }
+
+    #[cfg(not(feature = "images"))]
+    fn decode_source(&mut self, source: &str) -> Option<ImageId> {
+        tracing::debug!(
+            "junita_cpu: image decoding disabled, skipping '{}' (enable the `images` feature)",
+            source
+        );
+        None
+    }
+}
+
+/// Build a premultiplied pixmap from straight-alpha RGBA8 pixels
+pub fn pixmap_from_rgba(rgba: &[u8], width: u32, height: u32) -> Option<tiny_skia::Pixmap> {
+    if rgba.len() != (width as usize) * (height as usize) * 4 {
+        return None;
+    }
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)?;
+    for (dst, src) in pixmap
+        .data_mut()
+        .chunks_exact_mut(4)
+        .zip(rgba.chunks_exact(4))
+    {
+        let a = src[3] as u16;
+        dst[0] = ((src[0] as u16 * a + 127) / 255) as u8;
+        dst[1] = ((src[1] as u16 * a + 127) / 255) as u8;
+        dst[2] = ((src[2] as u16 * a + 127) / 255) as u8;
+        dst[3] = src[3];
+    }
+    Some(pixmap)
+}
+
+/// Convert premultiplied RGBA8 pixels to straight alpha in place
+pub fn demultiply(data: &mut [u8]) {
+    for px in data.chunks_exact_mut(4) {
+        let a = px[3] as u32;
+        if a == 0 {
+            px[0] = 0;
+            px[1] = 0;
+            px[2] = 0;
+        } else if a < 255 {
+            px[0] = ((px[0] as u32 * 255 + a / 2) / a).min(255) as u8;
+            px[1] = ((px[1] as u32 * 255 + a / 2) / a).min(255) as u8;
+            px[2] = ((px[2] as u32 * 255 + a / 2) / a).min(255) as u8;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_premultiply_roundtrip() {
+        let rgba = [255, 128, 0, 128, 10, 20, 30, 255];
+        let pixmap = pixmap_from_rgba(&rgba, 2, 1).unwrap();
+        assert_eq!(&pixmap.data()[4..8], &[10, 20, 30, 255]);
+
+        let mut data = pixmap.data().to_vec();
+        demultiply(&mut data);
+        assert_eq!(data[3], 128);
+        assert!((data[0] as i32 - 255).abs() <= 1);
+        assert!((data[1] as i32 - 128).abs() <= 1);
+    }
+
+    #[test]
+    fn test_source_registration() {
+        let mut store = CpuImageStore::new();
+        let id = store.insert_source("avatar.png", &[255; 16], 2, 2).unwrap();
+
+        assert_eq!(store.resolve_source("avatar.png"), Some(id));
+        assert!(store.get(id).is_some());
+
+        // Mismatched buffers are rejected
+        assert!(store.add_rgba(&[0; 3], 1, 1).is_none());
+    }
+}
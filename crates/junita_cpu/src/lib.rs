@@ -0,0 +1,44 @@
+//! CPU reference renderer for Junita
+//!
+//! This crate provides a software implementation of `junita_core::DrawContext`
+//! built on tiny-skia, so render trees can be turned into pixels on machines
+//! without a GPU adapter (CI runners, containers, headless servers).
+//!
+//! # Coverage
+//!
+//! - SDF rounded rects, circles, borders and shadows, shaded with the same
+//!   distance functions as the GPU shader
+//! - Linear and radial gradients, paths, strokes and clipping
+//! - Images, SVG icons and text sampled from the shared glyph atlas
+//! - Offscreen layers with opacity and blend modes
+//!
+//! Glass and blur brushes are approximated by their tint, and 3D content is
+//! skipped. Output is intended for pixel tests, not for presenting frames.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use junita_cpu::{CpuPaintContext, CpuRenderer};
+//!
+//! // Draw directly
+//! let mut ctx = CpuPaintContext::new(200.0, 100.0);
+//! ctx.fill_rect(rect, 8.0.into(), Color::BLUE.into());
+//! let frame = ctx.into_frame();
+//!
+//! // Or render a laid-out tree
+//! let mut renderer = CpuRenderer::new(400.0, 300.0);
+//! let frame = renderer.render_tree(&tree);
+//! ```
+
+mod brush;
+mod context;
+mod image;
+mod path;
+mod renderer;
+mod sdf;
+mod text;
+
+pub use context::{CpuPaintContext, MAX_TARGET_DIMENSION};
+pub use image::CpuImageStore;
+pub use renderer::{render_to_frame, CpuRenderer};
+pub use text::{CpuTextContext, PlacedGlyph, TextRequest};
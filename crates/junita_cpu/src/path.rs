@@ -0,0 +1,264 @@
+//! Conversion of junita paths, strokes and clip shapes to tiny-skia
+
+use junita_core::{ClipShape, LineCap, LineJoin, Path, PathCommand, Point, Rect, Stroke, Vec2};
+
+/// Convert a junita path into a tiny-skia path
+///
+/// Returns `None` for empty or degenerate paths.
+pub fn to_skia_path(path: &Path) -> Option<tiny_skia::Path> {
+    let mut pb = tiny_skia::PathBuilder::new();
+    let mut current = Point::ZERO;
+    let mut start = Point::ZERO;
+    let mut open = false;
+
+    for cmd in path.commands() {
+        match cmd {
+            PathCommand::MoveTo(p) => {
+                pb.move_to(p.x, p.y);
+                current = *p;
+                start = *p;
+                open = true;
+            }
+            PathCommand::LineTo(p) => {
+                if !open {
+                    pb.move_to(current.x, current.y);
+                    open = true;
+                }
+                pb.line_to(p.x, p.y);
+                current = *p;
+            }
+            PathCommand::QuadTo { control, end } => {
+                if !open {
+                    pb.move_to(current.x, current.y);
+                    open = true;
+                }
+                pb.quad_to(control.x, control.y, end.x, end.y);
+                current = *end;
+            }
+            PathCommand::CubicTo {
+                control1,
+                control2,
+                end,
+            } => {
+                if !open {
+                    pb.move_to(current.x, current.y);
+                    open = true;
+                }
+                pb.cubic_to(control1.x, control1.y, control2.x, control2.y, end.x, end.y);
+                current = *end;
+            }
+            PathCommand::ArcTo {
+                radii,
+                rotation,
+                large_arc,
+                sweep,
+                end,
+            } => {
+                if !open {
+                    pb.move_to(current.x, current.y);
+                    open = true;
+                }
+                let cubics = arc_to_cubics(current, *radii, *rotation, *large_arc, *sweep, *end);
+                if cubics.is_empty() {
+                    pb.line_to(end.x, end.y);
+                }
+                for (c1, c2, p) in cubics {
+                    pb.cubic_to(c1.x, c1.y, c2.x, c2.y, p.x, p.y);
+                }
+                current = *end;
+            }
+            PathCommand::Close => {
+                if open {
+                    pb.close();
+                }
+                current = start;
+                open = false;
+            }
+        }
+    }
+
+    pb.finish()
+}
+
+/// Convert a junita stroke into a tiny-skia stroke
+pub fn to_skia_stroke(stroke: &Stroke) -> tiny_skia::Stroke {
+    tiny_skia::Stroke {
+        width: stroke.width,
+        miter_limit: stroke.miter_limit,
+        line_cap: match stroke.cap {
+            LineCap::Butt => tiny_skia::LineCap::Butt,
+            LineCap::Round => tiny_skia::LineCap::Round,
+            LineCap::Square => tiny_skia::LineCap::Square,
+        },
+        line_join: match stroke.join {
+            LineJoin::Miter => tiny_skia::LineJoin::Miter,
+            LineJoin::Round => tiny_skia::LineJoin::Round,
+            LineJoin::Bevel => tiny_skia::LineJoin::Bevel,
+        },
+        dash: if stroke.dash.len() >= 2 {
+            tiny_skia::StrokeDash::new(stroke.dash.clone(), stroke.dash_offset)
+        } else {
+            None
+        },
+    }
+}
+
+/// Build the outline of a clip shape as a path
+pub fn clip_to_path(shape: &ClipShape) -> Option<tiny_skia::Path> {
+    match shape {
+        ClipShape::Rect(rect) => rect_path(*rect),
+        ClipShape::RoundedRect {
+            rect,
+            corner_radius,
+        } => to_skia_path(&Path::rounded_rect(*rect, *corner_radius)),
+        ClipShape::Circle { center, radius } => {
+            tiny_skia::PathBuilder::from_circle(center.x, center.y, *radius)
+        }
+        ClipShape::Ellipse { center, radii } => tiny_skia::Rect::from_xywh(
+            center.x - radii.x,
+            center.y - radii.y,
+            radii.x * 2.0,
+            radii.y * 2.0,
+        )
+        .and_then(tiny_skia::PathBuilder::from_oval),
+        ClipShape::Path(path) => to_skia_path(path),
+    }
+}
+
+/// Build a rectangle path
+pub fn rect_path(rect: Rect) -> Option<tiny_skia::Path> {
+    tiny_skia::Rect::from_xywh(rect.x(), rect.y(), rect.width(), rect.height())
+        .map(tiny_skia::PathBuilder::from_rect)
+}
+
+/// Convert an SVG-style elliptical arc into cubic Bézier segments
+///
+/// Follows the endpoint-to-center conversion from the SVG spec (F.6.5),
+/// splitting the sweep into segments of at most 90 degrees.
+fn arc_to_cubics(
+    from: Point,
+    radii: Vec2,
+    x_rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: Point,
+) -> Vec<(Point, Point, Point)> {
+    let mut curves = Vec::new();
+    if (from.x - to.x).abs() < f32::EPSILON && (from.y - to.y).abs() < f32::EPSILON {
+        return curves;
+    }
+
+    let mut rx = radii.x.abs();
+    let mut ry = radii.y.abs();
+    if rx == 0.0 || ry == 0.0 {
+        return curves;
+    }
+
+    let (sin_phi, cos_phi) = x_rotation.sin_cos();
+    let dx = (from.x - to.x) / 2.0;
+    let dy = (from.y - to.y) / 2.0;
+    let x1p = cos_phi * dx + sin_phi * dy;
+    let y1p = -sin_phi * dx + cos_phi * dy;
+
+    let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let rx_sq = rx * rx;
+    let ry_sq = ry * ry;
+    let numer = (rx_sq * ry_sq - rx_sq * y1p * y1p - ry_sq * x1p * x1p).max(0.0);
+    let denom = rx_sq * y1p * y1p + ry_sq * x1p * x1p;
+    let coef = if denom > 0.0 {
+        (numer / denom).sqrt()
+    } else {
+        0.0
+    };
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let cxp = sign * coef * rx * y1p / ry;
+    let cyp = sign * coef * -ry * x1p / rx;
+
+    let cx = cos_phi * cxp - sin_phi * cyp + (from.x + to.x) / 2.0;
+    let cy = sin_phi * cxp + cos_phi * cyp + (from.y + to.y) / 2.0;
+
+    let angle = |ux: f32, uy: f32, vx: f32, vy: f32| {
+        let dot = ux * vx + uy * vy;
+        let len = (ux * ux + uy * uy).sqrt() * (vx * vx + vy * vy).sqrt();
+        let a = (dot / len).clamp(-1.0, 1.0).acos();
+        if ux * vy - uy * vx < 0.0 {
+            -a
+        } else {
+            a
+        }
+    };
+
+    let ux = (x1p - cxp) / rx;
+    let uy = (y1p - cyp) / ry;
+    let theta1 = angle(1.0, 0.0, ux, uy);
+    let mut dtheta = angle(ux, uy, (-x1p - cxp) / rx, (-y1p - cyp) / ry);
+    if sweep && dtheta < 0.0 {
+        dtheta += std::f32::consts::TAU;
+    } else if !sweep && dtheta > 0.0 {
+        dtheta -= std::f32::consts::TAU;
+    }
+
+    let segments = ((dtheta.abs() / std::f32::consts::FRAC_PI_2).ceil() as usize).max(1);
+    let step = dtheta / segments as f32;
+    let alpha = (step / 4.0).tan() * 4.0 / 3.0;
+
+    // Point and derivative on the (rotated) ellipse at angle t
+    let point_at = |t: f32| {
+        let (s, c) = t.sin_cos();
+        Point::new(
+            cx + rx * c * cos_phi - ry * s * sin_phi,
+            cy + rx * c * sin_phi + ry * s * cos_phi,
+        )
+    };
+    let tangent_at = |t: f32| {
+        let (s, c) = t.sin_cos();
+        Point::new(
+            -rx * s * cos_phi - ry * c * sin_phi,
+            -rx * s * sin_phi + ry * c * cos_phi,
+        )
+    };
+
+    for i in 0..segments {
+        let t1 = theta1 + i as f32 * step;
+        let t2 = t1 + step;
+        let p0 = point_at(t1);
+        let p3 = point_at(t2);
+        let d0 = tangent_at(t1);
+        let d3 = tangent_at(t2);
+        curves.push((
+            Point::new(p0.x + alpha * d0.x, p0.y + alpha * d0.y),
+            Point::new(p3.x - alpha * d3.x, p3.y - alpha * d3.y),
+            p3,
+        ));
+    }
+
+    curves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_arc_endpoints() {
+        let from = Point::new(0.0, 0.0);
+        let to = Point::new(100.0, 0.0);
+        let cubics = arc_to_cubics(from, Vec2::new(50.0, 50.0), 0.0, false, true, to);
+
+        assert_eq!(cubics.len(), 2);
+        let end = cubics.last().unwrap().2;
+        assert!((end.x - 100.0).abs() < 1e-3);
+        assert!(end.y.abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_empty_path() {
+        assert!(to_skia_path(&Path::new()).is_none());
+        assert!(to_skia_path(&Path::rect(Rect::new(0.0, 0.0, 10.0, 10.0))).is_some());
+    }
+}
@@ -0,0 +1,431 @@
+//! Software `LayoutRenderer` for render trees
+//!
+//! `CpuRenderer` drives `RenderTree::render_to` with a single
+//! [`CpuPaintContext`] and follows the GPU pass order: div layers, then
+//! images, then text and SVGs on top.
+
+use junita_core::{ClipShape, Color, CornerRadius, DrawContext, Rect};
+use junita_layout::damage::DamageRegion;
+use junita_layout::div::{FontWeight, TextAlign};
+use junita_layout::renderer::{ImageData, LayoutRenderer, RenderTree};
+use junita_recorder::testing::CapturedFrame;
+use junita_svg::RasterizedSvg;
+use junita_text::{TextAlignment, TextAnchor};
+
+use crate::context::CpuPaintContext;
+use crate::image::demultiply;
+use crate::text::TextRequest;
+
+/// Text or SVG content deferred until after image elements are drawn
+enum Deferred {
+    Text {
+        content: String,
+        bounds: Rect,
+        font_size: f32,
+        color: [f32; 4],
+        align: TextAlign,
+        weight: FontWeight,
+    },
+    Svg {
+        source: String,
+        bounds: Rect,
+        tint: Option<Color>,
+    },
+}
+
+/// Renders a `RenderTree` to pixels without a GPU
+///
+/// # Example
+///
+/// ```ignore
+/// use junita_cpu::CpuRenderer;
+///
+/// let mut tree = RenderTree::from_element(&ui);
+/// tree.compute_layout(400.0, 300.0);
+///
+/// let mut renderer = CpuRenderer::new(400.0, 300.0);
+/// let frame = renderer.render_tree(&tree);
+/// ```
+pub struct CpuRenderer {
+    ctx: CpuPaintContext,
+    background: Color,
+    deferred: Vec<Deferred>,
+}
+
+impl CpuRenderer {
+    /// Create a renderer with a logical viewport size at 1x scale
+    pub fn new(width: f32, height: f32) -> Self {
+        Self::with_scale_factor(width, height, 1.0)
+    }
+
+    /// Create a renderer with a HiDPI scale factor
+    ///
+    /// The target is clamped like [`CpuPaintContext::with_scale_factor`].
+    pub fn with_scale_factor(width: f32, height: f32, scale_factor: f32) -> Self {
+        Self {
+            ctx: CpuPaintContext::with_scale_factor(width, height, scale_factor),
+            background: Color::TRANSPARENT,
+            deferred: Vec::new(),
+        }
+    }
+
+    /// Set the colour the target is cleared to before each render
+    pub fn with_background(mut self, color: Color) -> Self {
+        self.background = color;
+        self
+    }
+
+    /// Access the underlying paint context (fonts, images, raw drawing)
+    pub fn context(&mut self) -> &mut CpuPaintContext {
+        &mut self.ctx
+    }
+
+    /// Render a laid-out tree and capture the result
+    ///
+    /// The tree must have had `compute_layout` called. Layout coordinates
+    /// are logical; the renderer's scale factor maps them to pixels.
+    pub fn render_tree(&mut self, tree: &RenderTree) -> CapturedFrame {
+        self.ctx.reset();
+        self.ctx.clear(self.background);
+        self.draw_tree(tree);
+        self.capture()
+    }
+
+    /// Repaint only the damaged region of the previous frame
+    ///
+    /// Follows the GPU partial redraw: the damage scissor is cleared to the
+    /// background and the tree is redrawn clipped to it, so pixels outside
+    /// the scissor keep the previous frame. A full region renders the whole
+    /// frame and an empty one captures the previous frame unchanged.
+    pub fn render_tree_with_damage(
+        &mut self,
+        tree: &RenderTree,
+        damage: &DamageRegion,
+    ) -> CapturedFrame {
+        let scale = self.ctx.scale_factor();
+        let (width, height) = (self.ctx.physical_width(), self.ctx.physical_height());
+        let Some(scissor @ [x, y, w, h]) = damage.scissor(scale, width, height) else {
+            return self.render_tree(tree);
+        };
+
+        if w > 0 && h > 0 {
+            self.ctx.clear_rect(scissor, self.background);
+            self.ctx.push_clip(ClipShape::rect(Rect::new(
+                x as f32 / scale,
+                y as f32 / scale,
+                w as f32 / scale,
+                h as f32 / scale,
+            )));
+            self.draw_tree(tree);
+            self.ctx.pop_clip();
+        }
+        self.capture()
+    }
+
+    /// Draw a tree over the current target contents
+    fn draw_tree(&mut self, tree: &RenderTree) {
+        self.deferred.clear();
+
+        tree.render_to(self);
+
+        for (image, bounds) in tree.image_elements() {
+            let rect = Rect::new(bounds.x, bounds.y, bounds.width, bounds.height);
+            self.draw_image_element(&image, rect);
+        }
+
+        for item in std::mem::take(&mut self.deferred) {
+            match item {
+                Deferred::Text {
+                    content,
+                    bounds,
+                    font_size,
+                    color,
+                    align,
+                    weight,
+                } => self.draw_text_element(&content, bounds, font_size, color, align, weight),
+                Deferred::Svg {
+                    source,
+                    bounds,
+                    tint,
+                } => self.draw_svg_element(&source, bounds, tint),
+            }
+        }
+    }
+
+    /// Capture the current contents of the render target
+    pub fn capture(&self) -> CapturedFrame {
+        CapturedFrame::new(
+            self.ctx.to_rgba(),
+            self.ctx.physical_width(),
+            self.ctx.physical_height(),
+        )
+    }
+
+    fn draw_text_element(
+        &mut self,
+        content: &str,
+        bounds: Rect,
+        font_size: f32,
+        color: [f32; 4],
+        align: TextAlign,
+        weight: FontWeight,
+    ) {
+        let mut request = TextRequest::new(content, bounds.x(), bounds.y(), font_size, color);
+        request.anchor = TextAnchor::Top;
+        request.alignment = match align {
+            TextAlign::Left => TextAlignment::Left,
+            TextAlign::Center => TextAlignment::Center,
+            TextAlign::Right => TextAlignment::Right,
+        };
+        request.width = Some(bounds.width());
+        request.weight = weight.weight();
+        request.layout_height = Some(bounds.height());
+        self.ctx.draw_text_request(&request);
+    }
+
+    fn draw_svg_element(&mut self, source: &str, bounds: Rect, tint: Option<Color>) {
+        // Rasterize at physical resolution so icons stay crisp on HiDPI
+        let scale = self.ctx.scale_factor();
+        let w = (bounds.width() * scale).ceil() as u32;
+        let h = (bounds.height() * scale).ceil() as u32;
+        if w == 0 || h == 0 {
+            return;
+        }
+
+        let (pixels, premultiplied) = match tint {
+            Some(tint) => (RasterizedSvg::from_str_with_tint(source, w, h, tint), true),
+            None => (RasterizedSvg::from_str(source, w, h), false),
+        };
+        let rasterized = match pixels {
+            Ok(r) => r,
+            Err(err) => {
+                tracing::debug!("junita_cpu: failed to rasterize SVG: {}", err);
+                return;
+            }
+        };
+
+        // Tinted output is premultiplied; the store expects straight alpha
+        let id = if premultiplied {
+            let mut straight = rasterized.data().to_vec();
+            demultiply(&mut straight);
+            self.ctx.images().add_rgba(&straight, w, h)
+        } else {
+            self.ctx.images().add_rgba(rasterized.data(), w, h)
+        };
+        let Some(id) = id else {
+            return;
+        };
+        self.ctx
+            .draw_image(id, bounds, &junita_core::ImageOptions::new());
+        self.ctx.images().remove(id);
+    }
+
+    fn draw_image_element(&mut self, image: &ImageData, rect: Rect) {
+        let Some(id) = self.ctx.images().resolve_source(&image.source) else {
+            return;
+        };
+        let Some((iw, ih)) = self
+            .ctx
+            .images()
+            .get(id)
+            .map(|p| (p.width() as f32, p.height() as f32))
+        else {
+            return;
+        };
+
+        let (sx, sy) = (rect.width() / iw, rect.height() / ih);
+        let (w, h) = match image.object_fit {
+            // cover
+            0 => {
+                let s = sx.max(sy);
+                (iw * s, ih * s)
+            }
+            // contain
+            1 => {
+                let s = sx.min(sy);
+                (iw * s, ih * s)
+            }
+            // scale-down
+            3 => {
+                let s = sx.min(sy).min(1.0);
+                (iw * s, ih * s)
+            }
+            // none
+            4 => (iw, ih),
+            // fill
+            _ => (rect.width(), rect.height()),
+        };
+        let [px, py] = image.object_position;
+        let dest = Rect::new(
+            rect.x() + (rect.width() - w) * px,
+            rect.y() + (rect.height() - h) * py,
+            w,
+            h,
+        );
+
+        let [r, g, b, a] = image.tint;
+        let tint = Color::rgba(r, g, b, a);
+        let mut options = junita_core::ImageOptions::new().with_opacity(image.opacity);
+        if tint != Color::WHITE && a > 0.0 {
+            options = options.with_tint(tint);
+        }
+
+        self.ctx.push_clip(junita_core::ClipShape::rounded_rect(
+            rect,
+            CornerRadius::uniform(image.border_radius),
+        ));
+        self.ctx.draw_image(id, dest, &options);
+        self.ctx.pop_clip();
+    }
+}
+
+impl LayoutRenderer for CpuRenderer {
+    fn background(&mut self) -> &mut dyn DrawContext {
+        &mut self.ctx
+    }
+
+    fn foreground(&mut self) -> &mut dyn DrawContext {
+        // Glass is approximated by its tint, so both passes share one target
+        &mut self.ctx
+    }
+
+    fn render_text_foreground(
+        &mut self,
+        content: &str,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        font_size: f32,
+        color: [f32; 4],
+        align: TextAlign,
+        weight: FontWeight,
+    ) {
+        self.deferred.push(Deferred::Text {
+            content: content.to_string(),
+            bounds: Rect::new(x, y, width, height),
+            font_size,
+            color,
+            align,
+            weight,
+        });
+    }
+
+    fn render_text_background(
+        &mut self,
+        content: &str,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        font_size: f32,
+        color: [f32; 4],
+        align: TextAlign,
+        weight: FontWeight,
+    ) {
+        self.render_text_foreground(
+            content, x, y, width, height, font_size, color, align, weight,
+        );
+    }
+
+    fn render_svg_foreground(
+        &mut self,
+        source: &str,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        tint: Option<Color>,
+    ) {
+        self.deferred.push(Deferred::Svg {
+            source: source.to_string(),
+            bounds: Rect::new(x, y, width, height),
+            tint,
+        });
+    }
+
+    fn render_svg_background(
+        &mut self,
+        source: &str,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        tint: Option<Color>,
+    ) {
+        self.render_svg_foreground(source, x, y, width, height, tint);
+    }
+}
+
+/// Rasterize a drawing closure into a captured frame
+///
+/// Convenience for pixel tests that draw directly against `DrawContext`.
+pub fn render_to_frame(
+    width: f32,
+    height: f32,
+    f: impl FnOnce(&mut dyn DrawContext),
+) -> CapturedFrame {
+    let mut ctx = CpuPaintContext::new(width, height);
+    f(&mut ctx);
+    ctx.into_frame()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    use junita_animation::AnimationScheduler;
+    use junita_layout::damage::DamageTracker;
+    use junita_layout::div::{div, Div};
+    use junita_layout::RenderState;
+
+    fn ui(color: Color) -> Div {
+        div()
+            .w(120.0)
+            .h(80.0)
+            .flex_row()
+            .child(div().w(40.0).h(40.0).bg(Color::BLUE))
+            .child(div().w(40.0).h(40.0).bg(color))
+    }
+
+    fn pixel(frame: &CapturedFrame, x: u32, y: u32) -> [u8; 4] {
+        frame.get_pixel(x, y).expect("pixel inside the frame")
+    }
+
+    #[test]
+    fn test_partial_redraw_keeps_undamaged_pixels() {
+        let rs = RenderState::new(Arc::new(Mutex::new(AnimationScheduler::new())));
+        let mut tree = RenderTree::from_element(&ui(Color::RED));
+        tree.compute_layout(120.0, 80.0);
+
+        let mut renderer =
+            CpuRenderer::with_scale_factor(120.0, 80.0, 2.0).with_background(Color::WHITE);
+        let mut tracker = DamageTracker::new();
+        let damage = tracker.compute(&tree, &rs, (120.0, 80.0));
+        let before = renderer.render_tree_with_damage(&tree, &damage);
+
+        tree.incremental_update(&ui(Color::GREEN));
+        let damage = tracker.compute(&tree, &rs, (120.0, 80.0));
+        let [x, y, w, h] = damage
+            .scissor(2.0, before.width, before.height)
+            .expect("a single changed node is a partial redraw");
+        let partial = renderer.render_tree_with_damage(&tree, &damage);
+        let full = CpuRenderer::with_scale_factor(120.0, 80.0, 2.0)
+            .with_background(Color::WHITE)
+            .render_tree(&tree);
+
+        assert_eq!(pixel(&partial, 120, 40), [0, 255, 0, 255]);
+        for py in 0..partial.height {
+            for px in 0..partial.width {
+                let inside = (x..x + w).contains(&px) && (y..y + h).contains(&py);
+                let expected = if inside {
+                    pixel(&full, px, py)
+                } else {
+                    pixel(&before, px, py)
+                };
+                assert_eq!(pixel(&partial, px, py), expected, "pixel ({}, {})", px, py);
+            }
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Signed distance functions evaluated per pixel
+//!
+//! These mirror the functions in the GPU SDF shader (`junita_gpu::shaders`)
+//! so that CPU and GPU output agree on corner shapes, anti-aliasing width
+//! and shadow falloff.
+
+use junita_core::{CornerRadius, Point, Rect};
+
+/// Signed distance to a rounded rectangle with per-corner radii
+///
+/// Negative inside, positive outside.
+pub fn sd_rounded_rect(p: Point, rect: Rect, radius: CornerRadius) -> f32 {
+    let half_w = rect.width() * 0.5;
+    let half_h = rect.height() * 0.5;
+    let center = rect.center();
+    let rx = p.x - center.x;
+    let ry = p.y - center.y;
+
+    // Pick the corner radius for the quadrant the point lies in
+    let r = match (rx < 0.0, ry < 0.0) {
+        (true, true) => radius.top_left,
+        (false, true) => radius.top_right,
+        (false, false) => radius.bottom_right,
+        (true, false) => radius.bottom_left,
+    };
+    let r = r.max(0.0).min(half_w.min(half_h));
+
+    let qx = rx.abs() - half_w + r;
+    let qy = ry.abs() - half_h + r;
+    let outside = (qx.max(0.0).powi(2) + qy.max(0.0).powi(2)).sqrt();
+    outside + qx.max(qy).min(0.0) - r
+}
+
+/// Signed distance to a circle
+pub fn sd_circle(p: Point, center: Point, radius: f32) -> f32 {
+    ((p.x - center.x).powi(2) + (p.y - center.y).powi(2)).sqrt() - radius
+}
+
+/// Anti-aliased coverage for a signed distance
+///
+/// `px` is the size of one device pixel in the distance's units.
+pub fn coverage(distance: f32, px: f32) -> f32 {
+    (0.5 - distance / px.max(f32::EPSILON)).clamp(0.0, 1.0)
+}
+
+/// Error function approximation (Abramowitz & Stegun 7.1.26)
+pub fn erf(x: f32) -> f32 {
+    let s = x.signum();
+    let a = x.abs();
+    let t = 1.0 / (1.0 + 0.327_591_1 * a);
+    let y = 1.0
+        - (((((1.061_405_4 * t - 1.453_152_1) * t) + 1.421_413_8) * t - 0.284_496_74) * t
+            + 0.254_829_6)
+            * t
+            * (-a * a).exp();
+    s * y
+}
+
+/// Gaussian shadow intensity for a signed distance and blur sigma
+///
+/// Matches `shadow_rounded_rect` in the GPU shader: 1.0 inside the shape
+/// with a Gaussian falloff outside.
+pub fn gaussian_shadow(distance: f32, sigma: f32, px: f32) -> f32 {
+    if sigma < 0.001 {
+        return coverage(distance, px);
+    }
+    let d = 0.5 * std::f32::consts::SQRT_2 * sigma;
+    0.5 * (1.0 + erf(-distance / d))
+}
+
+/// Grow a rect by `amount` on every side
+pub fn outset(rect: Rect, amount: f32) -> Rect {
+    Rect::new(
+        rect.x() - amount,
+        rect.y() - amount,
+        (rect.width() + amount * 2.0).max(0.0),
+        (rect.height() + amount * 2.0).max(0.0),
+    )
+}
+
+/// Grow every corner radius by `amount`, clamping at zero
+pub fn outset_radius(radius: CornerRadius, amount: f32) -> CornerRadius {
+    CornerRadius::new(
+        (radius.top_left + amount).max(0.0),
+        (radius.top_right + amount).max(0.0),
+        (radius.bottom_right + amount).max(0.0),
+        (radius.bottom_left + amount).max(0.0),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rounded_rect_distance() {
+        let rect = Rect::new(0.0, 0.0, 100.0, 50.0);
+        let r = CornerRadius::uniform(10.0);
+
+        assert!(sd_rounded_rect(Point::new(50.0, 25.0), rect, r) < 0.0);
+        assert!(sd_rounded_rect(Point::new(150.0, 25.0), rect, r) > 0.0);
+        // Corner is cut off by the radius
+        assert!(sd_rounded_rect(Point::new(1.0, 1.0), rect, r) > 0.0);
+        // Straight edge sits exactly on the boundary
+        assert!(sd_rounded_rect(Point::new(50.0, 0.0), rect, r).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_erf_symmetry() {
+        assert!(erf(0.0).abs() < 1e-6);
+        assert!((erf(1.0) + erf(-1.0)).abs() < 1e-6);
+        assert!((erf(3.0) - 1.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_gaussian_shadow_falloff() {
+        assert!(gaussian_shadow(-20.0, 4.0, 1.0) > 0.99);
+        assert!((gaussian_shadow(0.0, 4.0, 1.0) - 0.5).abs() < 1e-3);
+        assert!(gaussian_shadow(20.0, 4.0, 1.0) < 0.01);
+    }
+}
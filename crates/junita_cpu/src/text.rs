@@ -0,0 +1,224 @@
+//! Text support for the CPU renderer
+//!
+//! Glyphs are shaped and rasterized by `junita_text::TextRenderer` into the
+//! same glyph atlas the GPU renderer uploads as a texture. The CPU renderer
+//! samples that atlas directly, so both renderers draw identical glyph
+//! bitmaps.
+
+use junita_text::{
+    GenericFont, LayoutOptions, LineBreakMode, TextAlignment, TextAnchor, TextRenderer,
+};
+
+/// A glyph positioned in local coordinates
+#[derive(Debug, Clone, Copy)]
+pub struct PlacedGlyph {
+    /// Position and size (x, y, width, height)
+    pub bounds: [f32; 4],
+    /// Normalized UV bounds in the atlas (u_min, v_min, u_max, v_max)
+    pub uv_bounds: [f32; 4],
+    /// Straight-alpha RGBA colour
+    pub color: [f32; 4],
+    /// Whether the glyph lives in the colour (emoji) atlas
+    pub is_color: bool,
+}
+
+/// Parameters for laying out a run of text
+#[derive(Debug, Clone)]
+pub struct TextRequest<'a> {
+    /// Text to render
+    pub text: &'a str,
+    /// X position
+    pub x: f32,
+    /// Y position (interpreted according to `anchor`)
+    pub y: f32,
+    /// Font size in pixels
+    pub font_size: f32,
+    /// Straight-alpha RGBA colour
+    pub color: [f32; 4],
+    /// Vertical anchor
+    pub anchor: TextAnchor,
+    /// Horizontal alignment
+    pub alignment: TextAlignment,
+    /// Optional width for alignment and wrapping
+    pub width: Option<f32>,
+    /// Whether to wrap at `width`
+    pub wrap: bool,
+    /// Optional font name
+    pub font_name: Option<&'a str>,
+    /// Font weight (100-900)
+    pub weight: u16,
+    /// Italic variant
+    pub italic: bool,
+    /// Layout-assigned height used to centre `Top`-anchored text
+    pub layout_height: Option<f32>,
+}
+
+impl<'a> TextRequest<'a> {
+    /// Create a request with default styling
+    pub fn new(text: &'a str, x: f32, y: f32, font_size: f32, color: [f32; 4]) -> Self {
+        Self {
+            text,
+            x,
+            y,
+            font_size,
+            color,
+            anchor: TextAnchor::Top,
+            alignment: TextAlignment::Left,
+            width: None,
+            wrap: false,
+            font_name: None,
+            weight: 400,
+            italic: false,
+            layout_height: None,
+        }
+    }
+}
+
+/// Text shaping and glyph atlas access for the CPU renderer
+pub struct CpuTextContext {
+    renderer: TextRenderer,
+}
+
+impl CpuTextContext {
+    /// Create a text context using the global font registry
+    pub fn new() -> Self {
+        Self {
+            renderer: TextRenderer::new(),
+        }
+    }
+
+    /// Create a text context from an existing text renderer
+    pub fn with_renderer(renderer: TextRenderer) -> Self {
+        Self { renderer }
+    }
+
+    /// Access the underlying text renderer
+    pub fn renderer(&self) -> &TextRenderer {
+        &self.renderer
+    }
+
+    /// Mutable access to the underlying text renderer (e.g. to load fonts)
+    pub fn renderer_mut(&mut self) -> &mut TextRenderer {
+        &mut self.renderer
+    }
+
+    /// Load a default font from raw font data
+    ///
+    /// Headless CI machines often have no system fonts; tests should embed
+    /// a font and load it here for deterministic output.
+    pub fn load_font_data(&mut self, data: Vec<u8>) -> Result<(), junita_text::TextError> {
+        self.renderer.load_default_font_data(data)
+    }
+
+    /// Shape a run of text and place its glyphs
+    ///
+    /// Positioning follows the GPU text context so both renderers place
+    /// glyphs at the same coordinates.
+    pub fn layout(
+        &mut self,
+        request: &TextRequest<'_>,
+    ) -> Result<Vec<PlacedGlyph>, junita_text::TextError> {
+        let mut options = LayoutOptions {
+            anchor: request.anchor,
+            alignment: request.alignment,
+            max_width: request.width,
+            ..LayoutOptions::default()
+        };
+        if !request.wrap {
+            options.line_break = LineBreakMode::None;
+        }
+
+        let prepared = self.renderer.prepare_text_with_style(
+            request.text,
+            request.font_size,
+            request.color,
+            &options,
+            request.font_name,
+            GenericFont::System,
+            request.weight,
+            request.italic,
+        )?;
+
+        let glyph_extent = prepared.ascender - prepared.descender;
+        let centering_height = if prepared.height > glyph_extent * 1.5 {
+            prepared.height
+        } else {
+            glyph_extent
+        };
+
+        let y_offset = match request.anchor {
+            TextAnchor::Top => match request.layout_height {
+                Some(h) => request.y + (h - centering_height) / 2.0,
+                None => request.y,
+            },
+            TextAnchor::Center => request.y - centering_height / 2.0,
+            TextAnchor::Baseline => request.y - prepared.ascender,
+        };
+
+        Ok(prepared
+            .glyphs
+            .iter()
+            .map(|g| PlacedGlyph {
+                bounds: [
+                    g.bounds[0] + request.x,
+                    g.bounds[1] + y_offset,
+                    g.bounds[2],
+                    g.bounds[3],
+                ],
+                uv_bounds: g.uv_bounds,
+                color: g.color,
+                is_color: g.is_color,
+            })
+            .collect())
+    }
+
+    /// Sample glyph coverage/colour at normalized atlas coordinates
+    ///
+    /// Returns premultiplied RGBA in 0.0-1.0. Uses nearest filtering, like
+    /// the GPU glyph sampler.
+    pub fn sample(&self, glyph: &PlacedGlyph, u: f32, v: f32) -> [f32; 4] {
+        if glyph.is_color {
+            let (w, h) = self.renderer.color_atlas_dimensions();
+            let pixels = self.renderer.color_atlas_pixels();
+            let idx = texel_index(u, v, w, h) * 4;
+            match pixels.get(idx..idx + 4) {
+                Some(px) => {
+                    let a = px[3] as f32 / 255.0 * glyph.color[3];
+                    [
+                        px[0] as f32 / 255.0 * a,
+                        px[1] as f32 / 255.0 * a,
+                        px[2] as f32 / 255.0 * a,
+                        a,
+                    ]
+                }
+                None => [0.0; 4],
+            }
+        } else {
+            let (w, h) = self.renderer.atlas_dimensions();
+            let pixels = self.renderer.atlas_pixels();
+            let coverage = pixels
+                .get(texel_index(u, v, w, h))
+                .map(|c| *c as f32 / 255.0)
+                .unwrap_or(0.0);
+            let a = coverage * glyph.color[3];
+            [
+                glyph.color[0] * a,
+                glyph.color[1] * a,
+                glyph.color[2] * a,
+                a,
+            ]
+        }
+    }
+}
+
+impl Default for CpuTextContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn texel_index(u: f32, v: f32, width: u32, height: u32) -> usize {
+    let x = ((u * width as f32) as i64).clamp(0, width as i64 - 1) as usize;
+    let y = ((v * height as f32) as i64).clamp(0, height as i64 - 1) as usize;
+    y * width as usize + x
+}
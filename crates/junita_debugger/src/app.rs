@@ -6,18 +6,30 @@
 //! - Preview Panel (center): UI preview
 //! - Inspector Panel (right): Element properties
 //! - Timeline Panel (bottom): Event timeline with scrubber
+//!
+//! When attached to a live app, a Profiler Panel sits above the timeline
+//! and a Reactive Panel to the right of the inspector.
 
+use crate::live::{self, LiveSession};
 use crate::panels::{
-    InspectorPanel, PreviewConfig, PreviewPanel, TimelinePanel, TimelinePanelState, TreePanel,
-    TreePanelState,
+    InspectorEditState, InspectorPanel, PreviewConfig, PreviewPanel, ProfilerPanel,
+    ProfilerPanelState, ReactivePanel, ReactivePanelState, TimelinePanel, TimelinePanelState,
+    TreePanel, TreePanelState,
 };
 use crate::theme::DebuggerColors;
 use anyhow::Result;
 use junita_app::windowed::{WindowedApp, WindowedContext};
 use junita_app::WindowConfig;
+use junita_core::profiler;
 use junita_layout::prelude::*;
+use junita_recorder::format::{self, ChunkKind};
 use junita_recorder::replay::{ReplayConfig, ReplayPlayer, ReplayState};
-use junita_recorder::{ElementSnapshot, RecordingExport, TreeSnapshot};
+use junita_recorder::{
+    ClientCommand, ComputedStyle, ElementSnapshot, LayoutInfo, RecordingExport, RecordingReader,
+    Timestamp, TreeSnapshot,
+};
+use std::fs::File;
+use std::io::BufReader;
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex, RwLock};
 
@@ -27,6 +39,8 @@ pub struct AppState {
     pub recording: Option<RecordingExport>,
     /// Replay player (if recording loaded)
     pub player: Option<Arc<Mutex<ReplayPlayer>>>,
+    /// Binary recording that snapshots are loaded from on demand
+    pub recording_file: Option<RecordingReader<BufReader<File>>>,
     /// Current tree snapshot
     pub current_snapshot: Option<TreeSnapshot>,
     /// Selected element ID
@@ -41,6 +55,22 @@ pub struct AppState {
     pub connected: bool,
     /// Server address
     pub server_addr: Option<String>,
+    /// Live connection to a running app (if attached)
+    pub live: Option<LiveSession>,
+    /// Waiting for an element to be clicked in the live app
+    pub picking: bool,
+    /// Computed style of the selected element (live only)
+    pub computed_style: Option<ComputedStyle>,
+    /// Layout of the selected element (live only)
+    pub layout_info: Option<LayoutInfo>,
+    /// Last error reported by the live app
+    pub live_error: Option<String>,
+    /// Inspector edit inputs
+    pub edit_state: InspectorEditState,
+    /// Frame profiles streamed from the live app
+    pub profiler: ProfilerPanelState,
+    /// Reactive graph and stores of the live app
+    pub reactive: ReactivePanelState,
 }
 
 impl Default for AppState {
@@ -48,6 +78,7 @@ impl Default for AppState {
         Self {
             recording: None,
             player: None,
+            recording_file: None,
             current_snapshot: None,
             selected_element_id: None,
             tree_state: TreePanelState::default(),
@@ -55,34 +86,97 @@ impl Default for AppState {
             timeline_state: TimelinePanelState::default(),
             connected: false,
             server_addr: None,
+            live: None,
+            picking: false,
+            computed_style: None,
+            layout_info: None,
+            live_error: None,
+            edit_state: InspectorEditState::default(),
+            profiler: ProfilerPanelState::default(),
+            reactive: ReactivePanelState::default(),
         }
     }
 }
 
 impl AppState {
     /// Load a recording from file
+    ///
+    /// JSON recordings are read whole. For binary recordings only the events
+    /// are read up front; snapshots are decoded from the file as the timeline
+    /// moves.
     pub fn load_recording(&mut self, path: &PathBuf) -> Result<()> {
-        let contents = std::fs::read_to_string(path)?;
-        let export: RecordingExport = serde_json::from_str(&contents)?;
+        let (export, file, first_snapshot) = if format::is_binary_recording_file(path)? {
+            let mut reader = RecordingReader::open(path)?;
+            let first = reader
+                .chunks()
+                .iter()
+                .find(|c| c.kind == ChunkKind::Snapshots)
+                .map(|c| c.start);
+            let first_snapshot = match first {
+                Some(start) => reader.snapshot_at(start)?,
+                None => None,
+            };
+            let export = RecordingExport {
+                config: reader.header().config.clone(),
+                events: reader.read_events()?,
+                snapshots: Vec::new(),
+                stats: reader.stats(),
+            };
+            (export, Some(reader), first_snapshot)
+        } else {
+            let contents = std::fs::read_to_string(path)?;
+            let export: RecordingExport = serde_json::from_str(&contents)?;
+            let first_snapshot = export.snapshots.first().cloned();
+            (export, None, first_snapshot)
+        };
 
         let player = ReplayPlayer::new(export.clone(), ReplayConfig::interactive());
 
         // Update timeline state with duration
-        self.timeline_state.duration = player.duration();
+        let file_duration = file.as_ref().map(|f| f.duration()).unwrap_or_default();
+        self.timeline_state.duration = player.duration().max(file_duration);
+        self.timeline_state.position = Timestamp::zero();
         self.timeline_state.playback_state = ReplayState::Idle;
 
         // Load initial snapshot if available
-        if let Some(snapshot) = export.snapshots.first() {
-            self.current_snapshot = Some(snapshot.clone());
+        if first_snapshot.is_some() {
+            self.current_snapshot = first_snapshot;
         }
 
         self.recording = Some(export);
+        self.recording_file = file;
         self.player = Some(Arc::new(Mutex::new(player)));
 
         log::info!("Loaded recording from {}", path.display());
         Ok(())
     }
 
+    /// Move the timeline to `position` and show the snapshot at that time
+    pub fn seek(&mut self, position: Timestamp) {
+        let position = position.min(self.timeline_state.duration);
+        self.timeline_state.position = position;
+        if let Some(player) = &self.player {
+            player.lock().unwrap().seek(position);
+        }
+
+        let snapshot = match &mut self.recording_file {
+            Some(file) => file.snapshot_at(position).unwrap_or_else(|e| {
+                log::warn!("Failed to load snapshot at {:?}: {}", position, e);
+                None
+            }),
+            None => self.recording.as_ref().and_then(|r| {
+                r.snapshots
+                    .iter()
+                    .rev()
+                    .find(|s| s.timestamp <= position)
+                    .cloned()
+            }),
+        };
+        if snapshot.is_some() {
+            self.current_snapshot = snapshot;
+        }
+    }
+
     /// Get the selected element snapshot
     pub fn selected_element(&self) -> Option<&ElementSnapshot> {
         let snapshot = self.current_snapshot.as_ref()?;
@@ -90,6 +184,22 @@ impl AppState {
         snapshot.elements.get(id)
     }
 
+    /// Select an element, querying its style and layout when live
+    pub fn select_element(&mut self, id: Option<String>) {
+        self.selected_element_id = id.clone();
+        self.tree_state.selected_id = id.clone();
+        self.computed_style = None;
+        self.layout_info = None;
+        self.live_error = None;
+
+        if let (Some(live), Some(element_id)) = (&self.live, id) {
+            live.send(ClientCommand::QueryComputedStyle {
+                element_id: element_id.clone(),
+            });
+            live.send(ClientCommand::QueryLayout { element_id });
+        }
+    }
+
     /// Get cursor position during replay
     pub fn cursor_position(&self) -> Option<(f32, f32)> {
         // TODO: Get from replay player's simulator
@@ -101,7 +211,14 @@ impl AppState {
 pub type SharedAppState = Arc<RwLock<AppState>>;
 
 /// Run the debugger application
-pub fn run(width: u32, height: u32, file: Option<PathBuf>, connect: Option<String>) -> Result<()> {
+pub fn run(
+    width: u32,
+    height: u32,
+    file: Option<PathBuf>,
+    connect: Option<String>,
+    attach: Option<String>,
+    save: Option<PathBuf>,
+) -> Result<()> {
     // Create shared application state
     let app_state = Arc::new(RwLock::new(AppState::default()));
 
@@ -117,6 +234,19 @@ pub fn run(width: u32, height: u32, file: Option<PathBuf>, connect: Option<Strin
         app_state.write().unwrap().server_addr = Some(addr.clone());
     }
 
+    // Attach to a running app
+    if let Some(ref target) = attach {
+        let session = LiveSession::attach(target, app_state.clone())?;
+        if let Some(path) = save {
+            // The app writes the file, so give it a path that doesn't
+            // depend on its working directory
+            let path = std::env::current_dir()?.join(path);
+            log::info!("Saving the recording to {}", path.display());
+            session.send(ClientCommand::SaveRecording { path });
+        }
+        app_state.write().unwrap().live = Some(session);
+    }
+
     // Configure the window
     let config = WindowConfig {
         title: "Junita Debugger".to_string(),
@@ -148,26 +278,186 @@ fn build_debugger_ui(ctx: &WindowedContext, app_state: &SharedAppState) -> impl
                 .flex_grow()
                 .flex_row()
                 // Tree Panel (left)
-                .child(TreePanel::new(
-                    state.current_snapshot.as_ref(),
-                    &state.tree_state,
-                ))
+                .child(tree_panel(&state, app_state))
                 // Preview Panel (center)
-                .child(PreviewPanel::new(
-                    state.current_snapshot.as_ref(),
-                    &state.preview_config,
-                    state.cursor_position(),
-                ))
+                .child(preview_panel(&state))
                 // Inspector Panel (right)
-                .child(InspectorPanel::new(state.selected_element())),
+                .child(inspector_panel(&state))
+                // Reactive Panel (live only)
+                .when(state.live.is_some(), |d| {
+                    d.child(reactive_panel(&state, app_state))
+                }),
         )
+        // Profiler Panel (live only)
+        .when(state.live.is_some(), |d| {
+            d.child(profiler_panel(&state, app_state, ctx.width))
+        })
         // Timeline Panel (bottom)
-        .child(TimelinePanel::new(
-            state
-                .recording
-                .as_ref()
-                .map(|r| r.events.as_slice())
-                .unwrap_or(&[]),
-            &state.timeline_state,
-        ))
+        .child(timeline_panel(&state, app_state))
+}
+
+fn timeline_panel(state: &AppState, app_state: &SharedAppState) -> TimelinePanel {
+    let seek_state = app_state.clone();
+    TimelinePanel::new(
+        state
+            .recording
+            .as_ref()
+            .map(|r| r.events.as_slice())
+            .unwrap_or(&[]),
+        &state.timeline_state,
+    )
+    .on_seek(move |position| {
+        seek_state.write().unwrap().seek(position);
+        live::request_frame();
+    })
+}
+
+fn preview_panel(state: &AppState) -> PreviewPanel {
+    let panel = PreviewPanel::new(
+        state.current_snapshot.as_ref(),
+        &state.preview_config,
+        state.cursor_position(),
+    );
+
+    let Some(session) = state.live.clone() else {
+        return panel;
+    };
+
+    panel.on_debug_overlays(move |overlays| {
+        session.send(ClientCommand::SetDebugOverlays { overlays });
+    })
+}
+
+fn tree_panel(state: &AppState, app_state: &SharedAppState) -> TreePanel {
+    let select_state = app_state.clone();
+    let panel =
+        TreePanel::new(state.current_snapshot.as_ref(), &state.tree_state).on_select(move |id| {
+            select_state
+                .write()
+                .unwrap()
+                .select_element(Some(id.to_string()));
+            live::request_frame();
+        });
+
+    let Some(session) = state.live.clone() else {
+        return panel;
+    };
+
+    let hover_session = session.clone();
+    let pick_state = app_state.clone();
+    panel
+        .on_hover(move |id| {
+            hover_session.send(ClientCommand::Highlight {
+                element_id: id.map(str::to_string),
+            });
+        })
+        .on_pick(state.picking, move |enabled| {
+            pick_state.write().unwrap().picking = enabled;
+            session.send(ClientCommand::PickElement { enabled });
+            live::request_frame();
+        })
+}
+
+fn profiler_panel(state: &AppState, app_state: &SharedAppState, width: f32) -> ProfilerPanel {
+    let panel = ProfilerPanel::new(&state.profiler, width);
+
+    let Some(session) = state.live.clone() else {
+        return panel;
+    };
+
+    let select_state = app_state.clone();
+    let toggle_state = app_state.clone();
+    let export_state = app_state.clone();
+    panel
+        .on_select_frame(move |frame| {
+            select_state.write().unwrap().profiler.selected_frame = Some(frame);
+            live::request_frame();
+        })
+        .on_toggle(move |enabled| {
+            toggle_state.write().unwrap().profiler.recording = enabled;
+            session.send(ClientCommand::Profile { enabled });
+            live::request_frame();
+        })
+        .on_export(move || {
+            let frames: Vec<_> = export_state
+                .read()
+                .unwrap()
+                .profiler
+                .frames
+                .iter()
+                .cloned()
+                .collect();
+            let path = PathBuf::from("junita-profile.json");
+            match std::fs::write(&path, profiler::to_chrome_trace(&frames)) {
+                Ok(()) => log::info!("Exported {} frames to {}", frames.len(), path.display()),
+                Err(e) => log::warn!("Failed to export profile to {}: {}", path.display(), e),
+            }
+        })
+}
+
+fn reactive_panel(state: &AppState, app_state: &SharedAppState) -> ReactivePanel {
+    let panel = ReactivePanel::new(&state.reactive);
+
+    let Some(session) = state.live.clone() else {
+        return panel;
+    };
+
+    let refresh_session = session.clone();
+    let follow_state = app_state.clone();
+    let follow_session = session.clone();
+    let select_state = app_state.clone();
+    panel
+        .on_refresh(move || refresh_session.send(ClientCommand::QueryReactive))
+        .on_follow(move |follow| {
+            follow_state.write().unwrap().reactive.follow = follow;
+            if follow {
+                follow_session.send(ClientCommand::QueryReactive);
+            }
+            live::request_frame();
+        })
+        .on_select_signal(move |signal| {
+            select_state.write().unwrap().reactive.selected_signal = Some(signal);
+            live::request_frame();
+        })
+        .on_set_signal(move |signal, value| {
+            session.send(ClientCommand::SetSignal {
+                signal,
+                value: value.to_string(),
+            });
+            // Show what reran
+            session.send(ClientCommand::QueryReactive);
+        })
+}
+
+fn inspector_panel(state: &AppState) -> InspectorPanel {
+    let panel = InspectorPanel::new(state.selected_element());
+
+    let Some(session) = state.live.clone() else {
+        return panel;
+    };
+
+    let rule_session = session.clone();
+    panel
+        .computed_style(state.computed_style.as_ref())
+        .layout(state.layout_info.as_ref())
+        .error(state.live_error.as_deref())
+        .live_edit(
+            &state.edit_state,
+            move |element_id, property, value| {
+                session.send(ClientCommand::SetVisualProp {
+                    element_id: element_id.to_string(),
+                    property: property.to_string(),
+                    value: value.to_string(),
+                });
+                session.send(ClientCommand::QueryComputedStyle {
+                    element_id: element_id.to_string(),
+                });
+            },
+            move |selector, declarations| {
+                rule_session.send(ClientCommand::SetCssRule {
+                    selector: selector.to_string(),
+                    declarations: declarations.to_string(),
+                });
+            },
+        )
 }
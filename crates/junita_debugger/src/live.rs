@@ -0,0 +1,198 @@
+//! Live attach to a running app
+//!
+//! Connects to an app's debug server (see `junita_app::live_inspect`),
+//! keeps the app state's tree in sync with the streamed snapshots and
+//! patches, and forwards highlight, pick and edit commands from the panels.
+//! While the reactive panel follows the app, every tree patch also
+//! re-queries the reactive graph.
+
+use crate::app::SharedAppState;
+use anyhow::Result;
+use junita_core::context_state::JunitaContextState;
+use junita_recorder::ClientCommand;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+
+#[cfg(unix)]
+use anyhow::Context;
+#[cfg(unix)]
+use junita_recorder::{DebugClient, DebugServerConfig, ServerMessage};
+#[cfg(unix)]
+use std::path::PathBuf;
+#[cfg(unix)]
+use std::sync::mpsc::{self, Receiver};
+#[cfg(unix)]
+use std::thread;
+#[cfg(unix)]
+use std::time::Duration;
+
+/// How long the connection thread waits for server messages before
+/// sending queued commands
+#[cfg(unix)]
+const POLL_INTERVAL: Duration = Duration::from_millis(30);
+
+/// Handle to a live connection
+///
+/// Commands are queued and sent from the connection thread, so panels can
+/// call [`send`](Self::send) from event handlers without blocking.
+#[derive(Clone)]
+pub struct LiveSession {
+    commands: Sender<ClientCommand>,
+}
+
+impl LiveSession {
+    /// Attach to an app by name or socket path
+    #[cfg(unix)]
+    pub fn attach(target: &str, state: SharedAppState) -> Result<Self> {
+        let path = socket_path(target);
+        let mut client = DebugClient::connect(&path)
+            .with_context(|| format!("Failed to connect to {}", path.display()))?;
+        client.send(&ClientCommand::Subscribe)?;
+
+        log::info!("Attached to {}", path.display());
+
+        let (commands, queue) = mpsc::channel();
+        let session = Self { commands };
+        thread::spawn(move || {
+            if let Err(e) = run_connection(client, queue, &state) {
+                log::warn!("Live connection closed: {}", e);
+            }
+            state.write().unwrap().connected = false;
+            request_frame();
+        });
+
+        Ok(session)
+    }
+
+    /// Attach to an app by name or socket path
+    #[cfg(not(unix))]
+    pub fn attach(_target: &str, _state: SharedAppState) -> Result<Self> {
+        anyhow::bail!("Live attach is only supported on Unix")
+    }
+
+    /// Queue a command for the app
+    pub fn send(&self, command: ClientCommand) {
+        let _ = self.commands.send(command);
+    }
+}
+
+/// Resolve an attach target: anything containing a path separator is a
+/// socket path, otherwise it's an app name
+#[cfg(unix)]
+fn socket_path(target: &str) -> PathBuf {
+    if target.contains(std::path::MAIN_SEPARATOR) {
+        PathBuf::from(target)
+    } else {
+        DebugServerConfig::new(target).socket_path()
+    }
+}
+
+#[cfg(unix)]
+fn run_connection(
+    mut client: DebugClient,
+    queue: Receiver<ClientCommand>,
+    state: &SharedAppState,
+) -> Result<()> {
+    loop {
+        for command in queue.try_iter() {
+            client.send(&command)?;
+        }
+
+        let Some(message) = client.recv(POLL_INTERVAL)? else {
+            continue;
+        };
+        let refresh_reactive =
+            matches!(message, ServerMessage::TreePatch(_)) && state.read().unwrap().reactive.follow;
+        if apply_message(message, state) {
+            request_frame();
+        }
+        if refresh_reactive {
+            client.send(&ClientCommand::QueryReactive)?;
+        }
+    }
+}
+
+/// Update the app state from a server message
+///
+/// Returns whether the UI needs rebuilding.
+#[cfg(unix)]
+fn apply_message(message: ServerMessage, state: &SharedAppState) -> bool {
+    let mut state = state.write().unwrap();
+    match message {
+        ServerMessage::Hello { app_name, .. } => {
+            state.connected = true;
+            state.server_addr = Some(app_name);
+        }
+        ServerMessage::TreeSnapshot(snapshot) => {
+            state.current_snapshot = Some(snapshot);
+        }
+        ServerMessage::TreePatch(patch) => match state.current_snapshot.as_mut() {
+            Some(snapshot) => patch.apply(snapshot),
+            None => return false,
+        },
+        ServerMessage::ElementPicked { element_id } => {
+            state.picking = false;
+            state.select_element(Some(element_id));
+        }
+        ServerMessage::ComputedStyle(style) => {
+            if state.selected_element_id.as_deref() == Some(style.element_id.as_str()) {
+                state.computed_style = Some(style);
+            }
+        }
+        ServerMessage::Layout(layout) => {
+            if state.selected_element_id.as_deref() == Some(layout.element_id.as_str()) {
+                state.layout_info = Some(layout);
+            }
+        }
+        ServerMessage::FrameProfiles { frames } => {
+            state.profiler.push_frames(frames);
+        }
+        ServerMessage::Reactive { graph, stores } => {
+            state.reactive.update(graph, stores);
+        }
+        ServerMessage::Error { message } => {
+            log::warn!("Debug server: {}", message);
+            state.live_error = Some(message);
+        }
+        ServerMessage::Ack { .. } | ServerMessage::Pong | ServerMessage::StateChange { .. } => {
+            return false
+        }
+        ServerMessage::Export(_) | ServerMessage::Stats { .. } => return false,
+    }
+    true
+}
+
+/// Rebuild the debugger UI from outside the event loop
+pub fn request_frame() {
+    if let Some(ctx) = JunitaContextState::try_get() {
+        ctx.request_rebuild();
+    }
+
+    // Rebuilds only happen on frames; a one-shot tick callback makes the
+    // animation thread wake the event loop for one
+    let Some(scheduler) = junita_animation::try_get_scheduler() else {
+        return;
+    };
+    let slot = Arc::new(Mutex::new(None));
+    let fired = Arc::new(AtomicBool::new(false));
+    let id = scheduler.register_tick_callback({
+        let slot = slot.clone();
+        let fired = fired.clone();
+        let scheduler = scheduler.clone();
+        move |_| {
+            fired.store(true, Ordering::SeqCst);
+            if let Some(id) = slot.lock().unwrap().take() {
+                scheduler.remove_tick_callback(id);
+            }
+        }
+    });
+    if let Some(id) = id {
+        let mut slot = slot.lock().unwrap();
+        if fired.load(Ordering::SeqCst) {
+            scheduler.remove_tick_callback(id);
+        } else {
+            *slot = Some(id);
+        }
+    }
+}
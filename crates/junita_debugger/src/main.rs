@@ -6,10 +6,12 @@
 //! - UI preview with debug overlay
 //! - Element inspector panel
 //! - Event timeline with playback controls
+//! - Live attach to a running app (`--attach`)
 //!
 //! Layout based on Phase 12 of the junita_recorder implementation plan.
 
 mod app;
+mod live;
 mod panels;
 mod theme;
 
@@ -24,7 +26,7 @@ use std::path::PathBuf;
 #[command(about = "Visual debugger for Junita UI recordings")]
 #[command(version)]
 struct Args {
-    /// Recording file to open (optional)
+    /// Recording file to open, JSON or binary (optional)
     #[arg(short, long)]
     file: Option<PathBuf>,
 
@@ -32,6 +34,15 @@ struct Args {
     #[arg(short, long, default_value = "127.0.0.1:9999")]
     connect: Option<String>,
 
+    /// Attach to a running app by name or debug socket path
+    #[arg(short, long, value_name = "APP_OR_SOCKET")]
+    attach: Option<String>,
+
+    /// Have the attached app save its recording to this binary file; it
+    /// keeps streaming into it until the app stops recording
+    #[arg(long, value_name = "FILE", requires = "attach")]
+    save: Option<PathBuf>,
+
     /// Window width
     #[arg(long, default_value = "1280")]
     width: u32,
@@ -61,6 +72,17 @@ fn main() -> Result<()> {
         log::info!("Will connect to debug server at: {}", addr);
     }
 
+    if let Some(ref target) = args.attach {
+        log::info!("Will attach to live app: {}", target);
+    }
+
     // Run the app
-    app::run(args.width, args.height, args.file, args.connect)
+    app::run(
+        args.width,
+        args.height,
+        args.file,
+        args.connect,
+        args.attach,
+        args.save,
+    )
 }
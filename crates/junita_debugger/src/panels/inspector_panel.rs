@@ -1,23 +1,63 @@
 //! Inspector Panel - Selected element properties
 
 use std::cell::OnceCell;
+use std::sync::Arc;
 
+use junita_cn::components::button::{button, ButtonSize, ButtonVariant};
+use junita_cn::components::input::{input, InputSize};
 use junita_cn::components::separator::separator;
 use junita_layout::div::{Div, ElementBuilder, FontWeight};
 use junita_layout::element::RenderProps;
 use junita_layout::event_handler::EventHandlers;
 use junita_layout::prelude::*;
 use junita_layout::tree::{LayoutNodeId, LayoutTree};
-use junita_recorder::ElementSnapshot;
+use junita_layout::widgets::text_input::{text_input_data_with_placeholder, SharedTextInputData};
+use junita_recorder::{ComputedStyle, Edges, ElementSnapshot, LayoutInfo};
 use junita_theme::{ColorToken, ThemeState};
 
 use crate::theme::DebuggerTokens;
 
+/// Text inputs for live edits
+///
+/// Owned by the app state so typed text survives rebuilds.
+#[derive(Clone)]
+pub struct InspectorEditState {
+    pub property: SharedTextInputData,
+    pub value: SharedTextInputData,
+    pub selector: SharedTextInputData,
+    pub declarations: SharedTextInputData,
+}
+
+impl Default for InspectorEditState {
+    fn default() -> Self {
+        Self {
+            property: text_input_data_with_placeholder("property"),
+            value: text_input_data_with_placeholder("value"),
+            selector: text_input_data_with_placeholder("selector"),
+            declarations: text_input_data_with_placeholder("declarations"),
+        }
+    }
+}
+
+type SetPropCallback = Arc<dyn Fn(&str, &str, &str) + Send + Sync>;
+type SetRuleCallback = Arc<dyn Fn(&str, &str) + Send + Sync>;
+
+struct LiveEditConfig {
+    inputs: InspectorEditState,
+    on_set_prop: SetPropCallback,
+    on_set_rule: SetRuleCallback,
+}
+
 struct InspectorPanelConfig {
     element_id: Option<String>,
+    element_type: Option<String>,
     element_bounds: Option<junita_recorder::capture::Rect>,
     is_visible: bool,
     is_focused: bool,
+    computed_style: Option<ComputedStyle>,
+    layout: Option<LayoutInfo>,
+    error: Option<String>,
+    edit: Option<LiveEditConfig>,
 }
 
 struct BuiltInspectorPanel {
@@ -58,34 +98,198 @@ impl BuiltInspectorPanel {
     }
 
     fn content(config: &InspectorPanelConfig) -> Scroll {
-        let inner = if config.element_id.is_some() {
+        let mut inner = if config.element_id.is_some() {
             Self::render_element_info(config)
         } else {
             Self::render_empty_state()
         };
 
+        if let Some(edit) = &config.edit {
+            inner = div()
+                .flex_col()
+                .gap(12.0)
+                .child(inner)
+                .child(Self::rule_editor(edit));
+        }
+
         scroll().flex_grow().vertical().p(8.0).child(inner)
     }
 
     fn render_element_info(config: &InspectorPanelConfig) -> Div {
         let element_id = config.element_id.as_deref().unwrap_or("unknown");
+        let element_type = config.element_type.as_deref().unwrap_or("div");
+
+        let mut container = div().flex_col().gap(12.0);
+
+        if let Some(error) = &config.error {
+            let theme = ThemeState::get();
+            container =
+                container.child(text(error).size(12.0).color(theme.color(ColorToken::Error)));
+        }
 
-        let mut container = div().flex_col().gap(12.0).child(Self::section(
+        container = container.child(Self::section(
             "Element",
-            vec![("ID", element_id), ("Type", "div")],
+            vec![("ID", element_id), ("Type", element_type)],
         ));
 
         if let Some(bounds) = &config.element_bounds {
             container = container.child(Self::bounds_section(bounds));
         }
 
-        container.child(Self::section(
+        container = container.child(Self::section(
             "State",
             vec![
                 ("Visible", if config.is_visible { "Yes" } else { "No" }),
                 ("Focused", if config.is_focused { "Yes" } else { "No" }),
             ],
-        ))
+        ));
+
+        if let Some(layout) = &config.layout {
+            container = container.child(Self::layout_section(layout));
+        }
+
+        if let Some(style) = &config.computed_style {
+            container = container.child(Self::computed_style_section(style));
+        }
+
+        if let (Some(edit), Some(id)) = (&config.edit, &config.element_id) {
+            container = container.child(Self::prop_editor(edit, id));
+        }
+
+        container
+    }
+
+    fn layout_section(layout: &LayoutInfo) -> Div {
+        let content = format!(
+            "{:.1} × {:.1}",
+            layout.content_size.0, layout.content_size.1
+        );
+        let padding = format_edges(&layout.padding);
+        let border = format_edges(&layout.border);
+        let margin = format_edges(&layout.margin);
+
+        let mut properties = vec![
+            ("Content", content.as_str()),
+            ("Padding", padding.as_str()),
+            ("Border", border.as_str()),
+            ("Margin", margin.as_str()),
+        ];
+        properties.extend(layout.style.iter().map(|(k, v)| (k.as_str(), v.as_str())));
+
+        Self::section("Layout", properties)
+    }
+
+    /// Computed values, each with the builder, theme token or rule that produced it
+    fn computed_style_section(style: &ComputedStyle) -> Div {
+        let theme = ThemeState::get();
+        let mut props = div().flex_col().gap(2.0);
+
+        for (key, value) in &style.properties {
+            let mut row = div().flex_col().items_end().child(
+                text(value)
+                    .size(12.0)
+                    .color(theme.color(ColorToken::TextPrimary)),
+            );
+            if let Some(source) = style.sources.get(key) {
+                row = row.child(
+                    text(source)
+                        .size(10.0)
+                        .color(theme.color(ColorToken::TextTertiary)),
+                );
+            }
+            props = props.child(
+                div()
+                    .flex_row()
+                    .justify_between()
+                    .child(
+                        text(key)
+                            .size(12.0)
+                            .color(theme.color(ColorToken::TextSecondary)),
+                    )
+                    .child(row),
+            );
+        }
+
+        div()
+            .flex_col()
+            .gap(4.0)
+            .child(
+                text("Computed Style")
+                    .size(11.0)
+                    .color(theme.color(ColorToken::TextTertiary))
+                    .weight(FontWeight::SemiBold),
+            )
+            .child(props)
+    }
+
+    /// Inputs for overriding a single visual property on the selected element
+    fn prop_editor(edit: &LiveEditConfig, element_id: &str) -> Div {
+        let property = edit.inputs.property.clone();
+        let value = edit.inputs.value.clone();
+        let on_set_prop = edit.on_set_prop.clone();
+        let element_id = element_id.to_string();
+
+        Self::editor_section(
+            "Edit Property",
+            &edit.inputs.property,
+            &edit.inputs.value,
+            move || {
+                let property = property.lock().unwrap().value.clone();
+                let value = value.lock().unwrap().value.clone();
+                if !property.trim().is_empty() {
+                    on_set_prop(&element_id, property.trim(), value.trim());
+                }
+            },
+        )
+    }
+
+    /// Inputs for adding or replacing a CSS rule in the app's stylesheet
+    fn rule_editor(edit: &LiveEditConfig) -> Div {
+        let selector = edit.inputs.selector.clone();
+        let declarations = edit.inputs.declarations.clone();
+        let on_set_rule = edit.on_set_rule.clone();
+
+        Self::editor_section(
+            "CSS Rule",
+            &edit.inputs.selector,
+            &edit.inputs.declarations,
+            move || {
+                let selector = selector.lock().unwrap().value.clone();
+                let declarations = declarations.lock().unwrap().value.clone();
+                if !selector.trim().is_empty() {
+                    on_set_rule(selector.trim(), &declarations);
+                }
+            },
+        )
+    }
+
+    fn editor_section<F>(
+        title: &str,
+        first: &SharedTextInputData,
+        second: &SharedTextInputData,
+        on_apply: F,
+    ) -> Div
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        let theme = ThemeState::get();
+        div()
+            .flex_col()
+            .gap(4.0)
+            .child(
+                text(title)
+                    .size(11.0)
+                    .color(theme.color(ColorToken::TextTertiary))
+                    .weight(FontWeight::SemiBold),
+            )
+            .child(input(first).size(InputSize::Small))
+            .child(input(second).size(InputSize::Small))
+            .child(
+                button("Apply")
+                    .variant(ButtonVariant::Secondary)
+                    .size(ButtonSize::Small)
+                    .on_click(move |_| on_apply()),
+            )
     }
 
     fn section(title: &str, properties: Vec<(&str, &str)>) -> Div {
@@ -189,14 +393,59 @@ impl InspectorPanel {
         Self {
             config: InspectorPanelConfig {
                 element_id: selected.map(|e| e.id.clone()),
+                element_type: selected.map(|e| e.element_type.clone()),
                 element_bounds: selected.map(|e| e.bounds.clone()),
                 is_visible: selected.map(|e| e.is_visible).unwrap_or(false),
                 is_focused: selected.map(|e| e.is_focused).unwrap_or(false),
+                computed_style: None,
+                layout: None,
+                error: None,
+                edit: None,
             },
             built: OnceCell::new(),
         }
     }
 
+    /// Show the computed style reported by a live app
+    pub fn computed_style(mut self, style: Option<&ComputedStyle>) -> Self {
+        self.config.computed_style = style.cloned();
+        self
+    }
+
+    /// Show the box model reported by a live app
+    pub fn layout(mut self, layout: Option<&LayoutInfo>) -> Self {
+        self.config.layout = layout.cloned();
+        self
+    }
+
+    /// Show the last error reported by a live app
+    pub fn error(mut self, error: Option<&str>) -> Self {
+        self.config.error = error.map(str::to_string);
+        self
+    }
+
+    /// Enable live editing
+    ///
+    /// `on_set_prop` is called with (element ID, property, value) and
+    /// `on_set_rule` with (selector, declarations).
+    pub fn live_edit<P, R>(
+        mut self,
+        inputs: &InspectorEditState,
+        on_set_prop: P,
+        on_set_rule: R,
+    ) -> Self
+    where
+        P: Fn(&str, &str, &str) + Send + Sync + 'static,
+        R: Fn(&str, &str) + Send + Sync + 'static,
+    {
+        self.config.edit = Some(LiveEditConfig {
+            inputs: inputs.clone(),
+            on_set_prop: Arc::new(on_set_prop),
+            on_set_rule: Arc::new(on_set_rule),
+        });
+        self
+    }
+
     fn get_or_build(&self) -> &BuiltInspectorPanel {
         self.built
             .get_or_init(|| BuiltInspectorPanel::from_config(&self.config))
@@ -229,3 +478,10 @@ impl ElementBuilder for InspectorPanel {
         }
     }
 }
+
+fn format_edges(edges: &Edges) -> String {
+    format!(
+        "{:.0} {:.0} {:.0} {:.0}",
+        edges.top, edges.right, edges.bottom, edges.left
+    )
+}
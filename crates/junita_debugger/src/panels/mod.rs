@@ -5,13 +5,19 @@
 //! - Preview Panel: Live/recorded UI preview
 //! - Inspector Panel: Selected element properties
 //! - Timeline Panel: Event timeline with scrubber
+//! - Profiler Panel: Frame timings from a live app
+//! - Reactive Panel: Signals, effects and stores of a live app
 
 pub mod inspector_panel;
 pub mod preview_panel;
+pub mod profiler_panel;
+pub mod reactive_panel;
 pub mod timeline_panel;
 pub mod tree_panel;
 
-pub use inspector_panel::InspectorPanel;
+pub use inspector_panel::{InspectorEditState, InspectorPanel};
 pub use preview_panel::{PreviewConfig, PreviewPanel};
+pub use profiler_panel::{ProfilerPanel, ProfilerPanelState};
+pub use reactive_panel::{ReactivePanel, ReactivePanelState};
 pub use timeline_panel::{TimelinePanel, TimelinePanelState};
 pub use tree_panel::{TreePanel, TreePanelState};
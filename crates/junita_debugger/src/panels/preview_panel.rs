@@ -1,6 +1,7 @@
 //! Preview Panel - Live/recorded UI preview
 
 use std::cell::OnceCell;
+use std::sync::Arc;
 
 use junita_cn::components::select::{select, SelectSize};
 use junita_cn::components::separator::separator;
@@ -12,13 +13,26 @@ use junita_layout::element::RenderProps;
 use junita_layout::event_handler::EventHandlers;
 use junita_layout::prelude::*;
 use junita_layout::tree::{LayoutNodeId, LayoutTree};
-use junita_recorder::TreeSnapshot;
+use junita_recorder::{DebugOverlays, TreeSnapshot};
 use junita_theme::{ColorToken, ThemeState};
 
+type OverlaysCallback = Arc<dyn Fn(DebugOverlays) + Send + Sync>;
+
+/// Toolbar switches for the live app's layout debug overlays: state key and label
+const OVERLAY_SWITCHES: [(&str, &str); 6] = [
+    ("overlay_box_model", "Box"),
+    ("overlay_flex", "Flex"),
+    ("overlay_overflow", "Overflow"),
+    ("overlay_clips", "Clips"),
+    ("overlay_layers", "Layers"),
+    ("overlay_hit_regions", "Hit"),
+];
+
 #[derive(Clone)]
 pub struct PreviewConfig {
     pub show_bounds: bool,
     pub show_cursor: bool,
+    pub show_damage: bool,
     pub zoom: f32,
 }
 
@@ -27,6 +41,7 @@ impl Default for PreviewConfig {
         Self {
             show_bounds: false,
             show_cursor: true,
+            show_damage: false,
             zoom: 1.0,
         }
     }
@@ -36,8 +51,11 @@ struct PreviewPanelConfig {
     has_snapshot: bool,
     show_bounds: bool,
     show_cursor: bool,
+    show_damage: bool,
     zoom: f32,
     cursor_position: Option<(f32, f32)>,
+    dirty_regions: Vec<junita_recorder::Rect>,
+    on_debug_overlays: Option<OverlaysCallback>,
 }
 
 struct BuiltPreviewPanel {
@@ -48,14 +66,16 @@ impl BuiltPreviewPanel {
     fn from_config(config: &PreviewPanelConfig) -> Self {
         let theme = ThemeState::get();
 
-        let inner = div()
+        let mut inner = div()
             .flex_grow()
             .h_full()
             .bg(theme.color(ColorToken::Background))
             .flex_col()
-            .child(Self::toolbar(config))
-            .child(separator())
-            .child(Self::preview_area(config));
+            .child(Self::toolbar(config));
+        if let Some(on_change) = config.on_debug_overlays.clone() {
+            inner = inner.child(Self::overlay_bar(on_change));
+        }
+        let inner = inner.child(separator()).child(Self::preview_area(config));
 
         BuiltPreviewPanel { inner }
     }
@@ -67,6 +87,7 @@ impl BuiltPreviewPanel {
         // Get states from context
         let bounds_state = ctx.use_state_keyed("preview_bounds", || config.show_bounds);
         let cursor_state = ctx.use_state_keyed("preview_cursor", || config.show_cursor);
+        let damage_state = ctx.use_state_keyed("preview_damage", || config.show_damage);
         let zoom_str = format!("{}", (config.zoom * 100.0) as i32);
         let zoom_state = ctx.use_state_keyed("preview_zoom", || zoom_str.clone());
 
@@ -99,6 +120,11 @@ impl BuiltPreviewPanel {
                             .size(SwitchSize::Small)
                             .label("Cursor"),
                     )
+                    .child(
+                        switch(&damage_state)
+                            .size(SwitchSize::Small)
+                            .label("Damage"),
+                    )
                     .child(
                         select(&zoom_state)
                             .size(SelectSize::Small)
@@ -112,6 +138,51 @@ impl BuiltPreviewPanel {
             )
     }
 
+    /// Switches that toggle layout debug overlays in the live app's window
+    fn overlay_bar(on_change: OverlaysCallback) -> Div {
+        let theme = ThemeState::get();
+        let ctx = JunitaContextState::get();
+        let states: Vec<_> = OVERLAY_SWITCHES
+            .iter()
+            .map(|(key, _)| ctx.use_state_keyed(key, || false))
+            .collect();
+
+        let mut bar = div()
+            .h(32.0)
+            .px(12.0)
+            .bg(theme.color(ColorToken::SurfaceElevated))
+            .flex_row()
+            .items_center()
+            .gap(12.0)
+            .child(
+                text("Overlays")
+                    .size(12.0)
+                    .color(theme.color(ColorToken::TextTertiary)),
+            );
+
+        for (index, (_, label)) in OVERLAY_SWITCHES.iter().enumerate() {
+            let states = states.clone();
+            let on_change = on_change.clone();
+            bar = bar.child(
+                switch(&states[index])
+                    .size(SwitchSize::Small)
+                    .label(*label)
+                    .on_change(move |enabled| {
+                        let value = |i: usize| if i == index { enabled } else { states[i].get() };
+                        on_change(DebugOverlays {
+                            box_model: value(0),
+                            flex: value(1),
+                            overflow: value(2),
+                            clips: value(3),
+                            layers: value(4),
+                            hit_regions: value(5),
+                        });
+                    }),
+            );
+        }
+        bar
+    }
+
     fn preview_area(config: &PreviewPanelConfig) -> Div {
         let content = if config.has_snapshot {
             Self::render_preview(config)
@@ -145,6 +216,16 @@ impl BuiltPreviewPanel {
                     .color(theme.color(ColorToken::TextTertiary)),
             );
 
+        // Read the toolbar switch so toggling takes effect on the next rebuild
+        let show_damage = JunitaContextState::get()
+            .use_state_keyed("preview_damage", || config.show_damage)
+            .get();
+        if show_damage {
+            for region in &config.dirty_regions {
+                preview = preview.child(Self::render_dirty_region(region, config.zoom));
+            }
+        }
+
         if config.show_cursor {
             if let Some((x, y)) = config.cursor_position {
                 preview = preview.child(Self::render_cursor(x, y, config.zoom));
@@ -167,6 +248,20 @@ impl BuiltPreviewPanel {
             .border(2.0, Color::WHITE)
     }
 
+    /// Translucent highlight over a region repainted in this frame
+    fn render_dirty_region(region: &junita_recorder::Rect, zoom: f32) -> Div {
+        let theme = ThemeState::get();
+        let color = theme.color(ColorToken::Error);
+        div()
+            .absolute()
+            .left(region.x * zoom)
+            .top(region.y * zoom)
+            .w(region.width * zoom)
+            .h(region.height * zoom)
+            .bg(color.with_alpha(0.2))
+            .border(1.0, color)
+    }
+
     fn render_empty_state() -> Div {
         let theme = ThemeState::get();
         div()
@@ -203,13 +298,27 @@ impl PreviewPanel {
                 has_snapshot: snapshot.is_some(),
                 show_bounds: config.show_bounds,
                 show_cursor: config.show_cursor,
+                show_damage: config.show_damage,
                 zoom: config.zoom,
                 cursor_position,
+                dirty_regions: snapshot
+                    .map(|s| s.dirty_regions.clone())
+                    .unwrap_or_default(),
+                on_debug_overlays: None,
             },
             built: OnceCell::new(),
         }
     }
 
+    /// Show overlay switches, called with the new set when one is toggled
+    pub fn on_debug_overlays<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(DebugOverlays) + Send + Sync + 'static,
+    {
+        self.config.on_debug_overlays = Some(Arc::new(callback));
+        self
+    }
+
     fn get_or_build(&self) -> &BuiltPreviewPanel {
         self.built
             .get_or_init(|| BuiltPreviewPanel::from_config(&self.config))
@@ -0,0 +1,345 @@
+//! Profiler Panel - Frame timings with flame chart and jank markers
+
+use std::cell::OnceCell;
+use std::collections::VecDeque;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+use junita_cn::components::button::{button, ButtonSize, ButtonVariant};
+use junita_cn::components::separator::separator;
+use junita_core::profiler::{counters, FrameProfile, DEFAULT_FRAME_BUDGET_US};
+use junita_core::Color;
+use junita_icons::icons;
+use junita_layout::div::{Div, ElementBuilder, FontWeight};
+use junita_layout::element::RenderProps;
+use junita_layout::event_handler::EventHandlers;
+use junita_layout::prelude::*;
+use junita_layout::tree::{LayoutNodeId, LayoutTree};
+use junita_theme::{ColorToken, ThemeState};
+
+use crate::theme::DebuggerTokens;
+
+/// Frames kept by the debugger for the strip chart
+pub const MAX_PROFILE_FRAMES: usize = 300;
+
+/// Bars shown in the frame strip
+const STRIP_FRAMES: usize = 120;
+const STRIP_HEIGHT: f32 = 56.0;
+const FLAME_ROW_HEIGHT: f32 = 16.0;
+const FLAME_MAX_ROWS: u32 = 6;
+
+/// State for the profiler panel
+#[derive(Default)]
+pub struct ProfilerPanelState {
+    /// Frames received from the app, oldest first
+    pub frames: VecDeque<FrameProfile>,
+    /// Frame shown in the flame chart (latest if `None`)
+    pub selected_frame: Option<u64>,
+    /// Whether the app is streaming frame profiles
+    pub recording: bool,
+}
+
+impl ProfilerPanelState {
+    /// Append frames, dropping the oldest past [`MAX_PROFILE_FRAMES`]
+    pub fn push_frames(&mut self, frames: Vec<FrameProfile>) {
+        self.frames.extend(frames);
+        while self.frames.len() > MAX_PROFILE_FRAMES {
+            self.frames.pop_front();
+        }
+    }
+
+    /// The frame shown in the flame chart
+    pub fn shown_frame(&self) -> Option<&FrameProfile> {
+        match self.selected_frame {
+            Some(n) => self.frames.iter().find(|f| f.frame == n),
+            None => self.frames.back(),
+        }
+    }
+}
+
+type SelectFrameCallback = Arc<dyn Fn(u64) + Send + Sync>;
+type ToggleCallback = Arc<dyn Fn(bool) + Send + Sync>;
+type ExportCallback = Arc<dyn Fn() + Send + Sync>;
+
+struct ProfilerPanelConfig {
+    width: f32,
+    strip: Vec<FrameProfile>,
+    shown: Option<FrameProfile>,
+    recording: bool,
+    on_select_frame: Option<SelectFrameCallback>,
+    on_toggle: Option<ToggleCallback>,
+    on_export: Option<ExportCallback>,
+}
+
+struct BuiltProfilerPanel {
+    inner: Div,
+}
+
+impl BuiltProfilerPanel {
+    fn from_config(config: &ProfilerPanelConfig) -> Self {
+        let theme = ThemeState::get();
+
+        let inner = div()
+            .w_full()
+            .h(DebuggerTokens::PROFILER_HEIGHT)
+            .bg(theme.color(ColorToken::SurfaceElevated))
+            .flex_col()
+            .child(separator())
+            .child(Self::header(config))
+            .child(
+                div()
+                    .px(12.0)
+                    .flex_col()
+                    .gap(8.0)
+                    .child(Self::frame_strip(config))
+                    .child(Self::flame_chart(config)),
+            );
+
+        BuiltProfilerPanel { inner }
+    }
+
+    fn header(config: &ProfilerPanelConfig) -> Div {
+        let theme = ThemeState::get();
+
+        let mut actions = div().flex_row().items_center().gap(2.0);
+        if let Some(on_toggle) = config.on_toggle.clone() {
+            let recording = config.recording;
+            actions = actions.child(
+                button("")
+                    .variant(if recording {
+                        ButtonVariant::Primary
+                    } else {
+                        ButtonVariant::Ghost
+                    })
+                    .size(ButtonSize::Icon)
+                    .icon(icons::ACTIVITY)
+                    .on_click(move |_| on_toggle(!recording)),
+            );
+        }
+        if let Some(on_export) = config.on_export.clone() {
+            actions = actions.child(
+                button("")
+                    .variant(ButtonVariant::Ghost)
+                    .size(ButtonSize::Icon)
+                    .icon(icons::DOWNLOAD)
+                    .disabled(config.strip.is_empty())
+                    .on_click(move |_| on_export()),
+            );
+        }
+
+        div()
+            .w_full()
+            .h(40.0)
+            .px(12.0)
+            .flex_row()
+            .items_center()
+            .justify_between()
+            .child(
+                div()
+                    .flex_row()
+                    .items_center()
+                    .gap(12.0)
+                    .child(
+                        text("Profiler")
+                            .size(13.0)
+                            .color(theme.color(ColorToken::TextPrimary))
+                            .weight(FontWeight::SemiBold),
+                    )
+                    .child(
+                        text(Self::summary(config.shown.as_ref()))
+                            .size(12.0)
+                            .color(theme.color(ColorToken::TextSecondary)),
+                    ),
+            )
+            .child(actions)
+    }
+
+    fn summary(frame: Option<&FrameProfile>) -> String {
+        let Some(frame) = frame else {
+            return "No frames recorded".to_string();
+        };
+        format!(
+            "Frame {} · {:.2} ms · {} nodes rebuilt · {} glyphs shaped · {} primitives · {} submits",
+            frame.frame,
+            frame.duration_us as f64 / 1000.0,
+            frame.counter(counters::NODES_REBUILT),
+            frame.counter(counters::GLYPHS_SHAPED),
+            frame.counter(counters::PRIMITIVES),
+            frame.counter(counters::GPU_SUBMITS),
+        )
+    }
+
+    /// One bar per frame; bars over budget are drawn as jank
+    fn frame_strip(config: &ProfilerPanelConfig) -> Div {
+        let theme = ThemeState::get();
+        // Bars are scaled so the budget line sits at half height
+        let scale = STRIP_HEIGHT / (2.0 * DEFAULT_FRAME_BUDGET_US as f32);
+        let bar_width = (config.width / STRIP_FRAMES as f32 - 1.0).clamp(1.0, 6.0);
+        let selected = config.shown.as_ref().map(|f| f.frame);
+
+        let mut bars = div().h(STRIP_HEIGHT).flex_row().items_end().gap(1.0);
+        for frame in &config.strip {
+            let color = if Some(frame.frame) == selected {
+                theme.color(ColorToken::Accent)
+            } else if frame.is_jank(DEFAULT_FRAME_BUDGET_US) {
+                theme.color(ColorToken::Error)
+            } else {
+                theme.color(ColorToken::Primary)
+            };
+            let height = (frame.duration_us as f32 * scale).clamp(1.0, STRIP_HEIGHT);
+
+            let mut bar = div().w(bar_width).h(height).bg(color);
+            if let Some(on_select) = config.on_select_frame.clone() {
+                let number = frame.frame;
+                bar = bar.on_click(move |_| on_select(number));
+            }
+            bars = bars.child(bar);
+        }
+
+        div().w_full().h(STRIP_HEIGHT).relative().child(bars).child(
+            // Frame budget line
+            div()
+                .absolute()
+                .left(0.0)
+                .top(STRIP_HEIGHT / 2.0)
+                .w(config.width)
+                .h(1.0)
+                .bg(theme.color(ColorToken::TextTertiary).with_alpha(0.5)),
+        )
+    }
+
+    /// Spans of the shown frame, one row per nesting depth
+    fn flame_chart(config: &ProfilerPanelConfig) -> Div {
+        let theme = ThemeState::get();
+        let height = FLAME_ROW_HEIGHT * FLAME_MAX_ROWS as f32;
+        let mut chart = div().w(config.width).h(height).relative().overflow_clip();
+
+        let Some(frame) = config.shown.as_ref().filter(|f| f.duration_us > 0) else {
+            return chart;
+        };
+
+        let scale = config.width / frame.duration_us as f32;
+        for span in frame.spans.iter().filter(|s| s.depth < FLAME_MAX_ROWS) {
+            let left = span.start_us.saturating_sub(frame.start_us) as f32 * scale;
+            let width = (span.duration_us as f32 * scale).max(1.0);
+
+            let mut block = div()
+                .absolute()
+                .left(left)
+                .top(span.depth as f32 * FLAME_ROW_HEIGHT)
+                .w(width)
+                .h(FLAME_ROW_HEIGHT - 1.0)
+                .bg(span_color(&span.name))
+                .overflow_clip()
+                .px(2.0)
+                .items_center();
+            if width > 48.0 {
+                block = block.child(
+                    text(format!(
+                        "{} {:.2}ms",
+                        span.name,
+                        span.duration_us as f64 / 1000.0
+                    ))
+                    .size(10.0)
+                    .color(theme.color(ColorToken::TextInverse)),
+                );
+            }
+            chart = chart.child(block);
+        }
+        chart
+    }
+}
+
+/// Stable color per span name
+fn span_color(name: &str) -> Color {
+    let theme = ThemeState::get();
+    let palette = [
+        ColorToken::Primary,
+        ColorToken::Info,
+        ColorToken::Success,
+        ColorToken::Warning,
+        ColorToken::Accent,
+        ColorToken::Secondary,
+    ];
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    name.hash(&mut hasher);
+    theme.color(palette[hasher.finish() as usize % palette.len()])
+}
+
+pub struct ProfilerPanel {
+    config: ProfilerPanelConfig,
+    built: OnceCell<BuiltProfilerPanel>,
+}
+
+impl ProfilerPanel {
+    pub fn new(state: &ProfilerPanelState, width: f32) -> Self {
+        let skip = state.frames.len().saturating_sub(STRIP_FRAMES);
+        Self {
+            config: ProfilerPanelConfig {
+                width: (width - 24.0).max(0.0),
+                strip: state.frames.iter().skip(skip).cloned().collect(),
+                shown: state.shown_frame().cloned(),
+                recording: state.recording,
+                on_select_frame: None,
+                on_toggle: None,
+                on_export: None,
+            },
+            built: OnceCell::new(),
+        }
+    }
+
+    /// Called with the frame number when a bar in the strip is clicked
+    pub fn on_select_frame<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(u64) + Send + Sync + 'static,
+    {
+        self.config.on_select_frame = Some(Arc::new(callback));
+        self
+    }
+
+    /// Show the record toggle; called with the new recording state
+    pub fn on_toggle<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(bool) + Send + Sync + 'static,
+    {
+        self.config.on_toggle = Some(Arc::new(callback));
+        self
+    }
+
+    /// Show the export button
+    pub fn on_export<F>(mut self, callback: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.config.on_export = Some(Arc::new(callback));
+        self
+    }
+
+    fn get_or_build(&self) -> &BuiltProfilerPanel {
+        self.built
+            .get_or_init(|| BuiltProfilerPanel::from_config(&self.config))
+    }
+}
+
+impl ElementBuilder for ProfilerPanel {
+    fn build(&self, tree: &mut LayoutTree) -> LayoutNodeId {
+        self.get_or_build().inner.build(tree)
+    }
+
+    fn render_props(&self) -> RenderProps {
+        self.get_or_build().inner.render_props()
+    }
+
+    fn children_builders(&self) -> &[Box<dyn ElementBuilder>] {
+        self.get_or_build().inner.children_builders()
+    }
+
+    fn event_handlers(&self) -> Option<&EventHandlers> {
+        let handlers = self.get_or_build().inner.event_handlers();
+        if handlers.is_empty() {
+            None
+        } else {
+            Some(handlers)
+        }
+    }
+}
@@ -0,0 +1,549 @@
+//! Reactive Panel - Signals, derived values, effects and stores of a live app
+
+use std::cell::OnceCell;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use junita_cn::components::button::{button, ButtonSize, ButtonVariant};
+use junita_cn::components::input::{input, InputSize};
+use junita_cn::components::separator::separator;
+use junita_core::reactive::ReactiveSnapshot;
+use junita_core::store::StoreInfo;
+use junita_core::Color;
+use junita_icons::icons;
+use junita_layout::div::{Div, ElementBuilder, FontWeight};
+use junita_layout::element::RenderProps;
+use junita_layout::event_handler::EventHandlers;
+use junita_layout::prelude::*;
+use junita_layout::tree::{LayoutNodeId, LayoutTree};
+use junita_layout::widgets::text_input::{text_input_data_with_placeholder, SharedTextInputData};
+use junita_theme::{ColorToken, ThemeState};
+
+use crate::theme::DebuggerTokens;
+
+/// State for the reactive panel
+pub struct ReactivePanelState {
+    /// Latest graph received from the app
+    pub graph: Option<ReactiveSnapshot>,
+    /// Graph before the latest one, to show what reran
+    pub previous: Option<ReactiveSnapshot>,
+    /// Stores received with the latest graph
+    pub stores: Vec<StoreInfo>,
+    /// Signal being edited
+    pub selected_signal: Option<u64>,
+    /// New value for the selected signal
+    pub value: SharedTextInputData,
+    /// Re-query the graph whenever the app's tree changes
+    pub follow: bool,
+}
+
+impl Default for ReactivePanelState {
+    fn default() -> Self {
+        Self {
+            graph: None,
+            previous: None,
+            stores: Vec::new(),
+            selected_signal: None,
+            value: text_input_data_with_placeholder("value"),
+            follow: false,
+        }
+    }
+}
+
+impl ReactivePanelState {
+    /// Replace the graph, keeping the old one for rerun markers
+    pub fn update(&mut self, graph: ReactiveSnapshot, stores: Vec<StoreInfo>) {
+        self.previous = self.graph.replace(graph);
+        self.stores = stores;
+    }
+}
+
+/// How much each node ran between the previous and latest graph
+#[derive(Default)]
+struct Reruns {
+    signals: HashMap<u64, u64>,
+    derived: HashMap<u64, u64>,
+    effects: HashMap<u64, u64>,
+}
+
+impl Reruns {
+    fn between(previous: Option<&ReactiveSnapshot>, latest: &ReactiveSnapshot) -> Self {
+        let Some(previous) = previous else {
+            return Self::default();
+        };
+        let delta = |before: Option<u64>, after: u64| after.saturating_sub(before.unwrap_or(0));
+
+        let signals = previous
+            .signals
+            .iter()
+            .map(|s| (s.id, s.version))
+            .collect::<HashMap<_, _>>();
+        let derived = previous
+            .derived
+            .iter()
+            .map(|d| (d.id, d.recomputes))
+            .collect::<HashMap<_, _>>();
+        let effects = previous
+            .effects
+            .iter()
+            .map(|e| (e.id, e.runs))
+            .collect::<HashMap<_, _>>();
+
+        Self {
+            signals: latest
+                .signals
+                .iter()
+                .map(|s| (s.id, delta(signals.get(&s.id).copied(), s.version)))
+                .filter(|(_, n)| *n > 0)
+                .collect(),
+            derived: latest
+                .derived
+                .iter()
+                .map(|d| (d.id, delta(derived.get(&d.id).copied(), d.recomputes)))
+                .filter(|(_, n)| *n > 0)
+                .collect(),
+            effects: latest
+                .effects
+                .iter()
+                .map(|e| (e.id, delta(effects.get(&e.id).copied(), e.runs)))
+                .filter(|(_, n)| *n > 0)
+                .collect(),
+        }
+    }
+}
+
+type RefreshCallback = Arc<dyn Fn() + Send + Sync>;
+type FollowCallback = Arc<dyn Fn(bool) + Send + Sync>;
+type SelectSignalCallback = Arc<dyn Fn(u64) + Send + Sync>;
+type SetSignalCallback = Arc<dyn Fn(u64, &str) + Send + Sync>;
+
+struct ReactivePanelConfig {
+    graph: Option<ReactiveSnapshot>,
+    previous: Option<ReactiveSnapshot>,
+    stores: Vec<StoreInfo>,
+    selected_signal: Option<u64>,
+    value: SharedTextInputData,
+    follow: bool,
+    on_refresh: Option<RefreshCallback>,
+    on_follow: Option<FollowCallback>,
+    on_select_signal: Option<SelectSignalCallback>,
+    on_set_signal: Option<SetSignalCallback>,
+}
+
+struct BuiltReactivePanel {
+    inner: Div,
+}
+
+impl BuiltReactivePanel {
+    fn from_config(config: &ReactivePanelConfig) -> Self {
+        let theme = ThemeState::get();
+
+        let inner = div()
+            .w(DebuggerTokens::REACTIVE_PANEL_WIDTH)
+            .h_full()
+            .bg(theme.color(ColorToken::SurfaceElevated))
+            .flex_col()
+            .child(Self::header(config))
+            .child(separator())
+            .child(Self::content(config));
+
+        BuiltReactivePanel { inner }
+    }
+
+    fn header(config: &ReactivePanelConfig) -> Div {
+        let theme = ThemeState::get();
+
+        let mut actions = div().flex_row().items_center().gap(2.0);
+        if let Some(on_follow) = config.on_follow.clone() {
+            let follow = config.follow;
+            actions = actions.child(
+                button("")
+                    .variant(if follow {
+                        ButtonVariant::Primary
+                    } else {
+                        ButtonVariant::Ghost
+                    })
+                    .size(ButtonSize::Icon)
+                    .icon(icons::RADIO)
+                    .on_click(move |_| on_follow(!follow)),
+            );
+        }
+        if let Some(on_refresh) = config.on_refresh.clone() {
+            actions = actions.child(
+                button("")
+                    .variant(ButtonVariant::Ghost)
+                    .size(ButtonSize::Icon)
+                    .icon(icons::REFRESH_CW)
+                    .on_click(move |_| on_refresh()),
+            );
+        }
+
+        div()
+            .h(44.0)
+            .px(12.0)
+            .flex_row()
+            .items_center()
+            .justify_between()
+            .child(
+                text("Reactive")
+                    .size(13.0)
+                    .color(theme.color(ColorToken::TextPrimary))
+                    .weight(FontWeight::SemiBold),
+            )
+            .child(actions)
+    }
+
+    fn content(config: &ReactivePanelConfig) -> Scroll {
+        let Some(graph) = &config.graph else {
+            return scroll()
+                .flex_grow()
+                .vertical()
+                .p(8.0)
+                .child(Self::render_empty_state());
+        };
+
+        let reruns = Reruns::between(config.previous.as_ref(), graph);
+        let names: HashMap<u64, String> = graph
+            .signals
+            .iter()
+            .map(|s| (s.id, node_name(s.label.as_deref(), s.id)))
+            .collect();
+        let dependency_names = |deps: &[u64]| {
+            deps.iter()
+                .map(|id| names.get(id).cloned().unwrap_or_else(|| format!("#{}", id)))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+
+        let mut container = div().flex_col().gap(12.0);
+
+        if let Some(editor) = Self::signal_editor(config, graph) {
+            container = container.child(editor);
+        }
+
+        let mut signals = Self::section(&format!("Signals ({})", graph.signals.len()));
+        for signal in &graph.signals {
+            let name = node_name(signal.label.as_deref(), signal.id);
+            let meta = format!(
+                "v{} · {} subs{}",
+                signal.version,
+                signal.subscribers,
+                frame_suffix(signal.changed_frame)
+            );
+            let mut row = Self::node_row(
+                &name,
+                signal.value.as_deref(),
+                &meta,
+                reruns.signals.get(&signal.id).copied(),
+                config.selected_signal == Some(signal.id),
+            );
+            if let (true, Some(on_select)) = (signal.editable, config.on_select_signal.clone()) {
+                let id = signal.id;
+                row = row.cursor_pointer().on_click(move |_| on_select(id));
+            }
+            signals = signals.child(row);
+        }
+        container = container.child(signals);
+
+        let mut derived = Self::section(&format!("Derived ({})", graph.derived.len()));
+        for node in &graph.derived {
+            let meta = format!(
+                "{}× ← {}{}{}",
+                node.recomputes,
+                dependency_names(&node.dependencies),
+                if node.dirty { " · dirty" } else { "" },
+                frame_suffix(node.last_run_frame)
+            );
+            derived = derived.child(Self::node_row(
+                &node_name(node.label.as_deref(), node.id),
+                node.value.as_deref(),
+                &meta,
+                reruns.derived.get(&node.id).copied(),
+                false,
+            ));
+        }
+        container = container.child(derived);
+
+        let mut effects = Self::section(&format!("Effects ({})", graph.effects.len()));
+        for node in &graph.effects {
+            let meta = format!(
+                "{}× ← {}{}",
+                node.runs,
+                dependency_names(&node.dependencies),
+                frame_suffix(node.last_run_frame)
+            );
+            effects = effects.child(Self::node_row(
+                &node_name(node.label.as_deref(), node.id),
+                None,
+                &meta,
+                reruns.effects.get(&node.id).copied(),
+                false,
+            ));
+        }
+        container = container.child(effects);
+
+        let mut stores = Self::section(&format!("Stores ({})", config.stores.len()));
+        for store in &config.stores {
+            stores = stores.child(Self::store_rows(store));
+        }
+        container = container.child(stores);
+
+        scroll().flex_grow().vertical().p(8.0).child(container)
+    }
+
+    /// Input for setting the selected signal
+    fn signal_editor(config: &ReactivePanelConfig, graph: &ReactiveSnapshot) -> Option<Div> {
+        let theme = ThemeState::get();
+        let signal = graph
+            .signals
+            .iter()
+            .find(|s| Some(s.id) == config.selected_signal && s.editable)?;
+        let on_set = config.on_set_signal.clone()?;
+
+        let id = signal.id;
+        let value = config.value.clone();
+        Some(
+            div()
+                .flex_col()
+                .gap(4.0)
+                .child(
+                    text(format!(
+                        "Set {}",
+                        node_name(signal.label.as_deref(), signal.id)
+                    ))
+                    .size(11.0)
+                    .color(theme.color(ColorToken::TextTertiary))
+                    .weight(FontWeight::SemiBold),
+                )
+                .child(input(&config.value).size(InputSize::Small))
+                .child(
+                    button("Apply")
+                        .variant(ButtonVariant::Secondary)
+                        .size(ButtonSize::Small)
+                        .on_click(move |_| {
+                            let value = value.lock().unwrap().value.clone();
+                            on_set(id, &value);
+                        }),
+                ),
+        )
+    }
+
+    fn section(title: &str) -> Div {
+        let theme = ThemeState::get();
+        div().flex_col().gap(2.0).child(
+            text(title)
+                .size(11.0)
+                .color(theme.color(ColorToken::TextTertiary))
+                .weight(FontWeight::SemiBold),
+        )
+    }
+
+    /// One node: name and value on the first line, counters below
+    ///
+    /// Nodes that ran since the previous graph are marked with the run count.
+    fn node_row(
+        name: &str,
+        value: Option<&str>,
+        meta: &str,
+        reran: Option<u64>,
+        selected: bool,
+    ) -> Div {
+        let theme = ThemeState::get();
+        let name_color = if reran.is_some() {
+            theme.color(ColorToken::Warning)
+        } else {
+            theme.color(ColorToken::TextSecondary)
+        };
+
+        let mut first_line = div()
+            .flex_row()
+            .justify_between()
+            .gap(8.0)
+            .child(text(name).size(12.0).color(name_color))
+            .child(
+                text(value.unwrap_or("—"))
+                    .size(12.0)
+                    .color(theme.color(ColorToken::TextPrimary)),
+            );
+        if let Some(n) = reran {
+            first_line = first_line.child(
+                text(format!("+{}", n))
+                    .size(11.0)
+                    .color(theme.color(ColorToken::Warning)),
+            );
+        }
+
+        div()
+            .flex_col()
+            .px(4.0)
+            .py(2.0)
+            .rounded(4.0)
+            .bg(if selected {
+                theme.color(ColorToken::Primary).with_alpha(0.15)
+            } else {
+                Color::TRANSPARENT
+            })
+            .child(first_line)
+            .child(
+                text(meta)
+                    .size(10.0)
+                    .color(theme.color(ColorToken::TextTertiary)),
+            )
+    }
+
+    fn store_rows(store: &StoreInfo) -> Div {
+        let theme = ThemeState::get();
+        let mut rows = div().flex_col().gap(2.0).child(
+            text(format!(
+                "{} · {}",
+                store.name,
+                short_type_name(&store.type_name)
+            ))
+            .size(12.0)
+            .color(theme.color(ColorToken::TextSecondary)),
+        );
+        for key in &store.keys {
+            let value = key
+                .value
+                .clone()
+                .or_else(|| key.type_name.as_deref().map(short_type_name))
+                .unwrap_or_else(|| "—".to_string());
+            rows = rows.child(
+                div()
+                    .flex_row()
+                    .justify_between()
+                    .gap(8.0)
+                    .pl(12.0)
+                    .child(
+                        text(format!("{} ({} subs)", key.key, key.subscribers))
+                            .size(11.0)
+                            .color(theme.color(ColorToken::TextTertiary)),
+                    )
+                    .child(
+                        text(value)
+                            .size(11.0)
+                            .color(theme.color(ColorToken::TextPrimary)),
+                    ),
+            );
+        }
+        rows
+    }
+
+    fn render_empty_state() -> Div {
+        let theme = ThemeState::get();
+        div()
+            .w_full()
+            .h_full()
+            .items_center()
+            .justify_center()
+            .child(
+                text("Refresh to load the reactive graph")
+                    .size(13.0)
+                    .color(theme.color(ColorToken::TextTertiary)),
+            )
+    }
+}
+
+fn node_name(label: Option<&str>, id: u64) -> String {
+    label.map_or_else(|| format!("#{}", id), str::to_string)
+}
+
+fn frame_suffix(frame: Option<u64>) -> String {
+    frame.map(|f| format!(" · frame {}", f)).unwrap_or_default()
+}
+
+/// `alloc::string::String` -> `String`
+fn short_type_name(name: &str) -> String {
+    let base = name.split('<').next().unwrap_or(name);
+    let short = base.rsplit("::").next().unwrap_or(base);
+    format!("{}{}", short, &name[base.len()..])
+}
+
+pub struct ReactivePanel {
+    config: ReactivePanelConfig,
+    built: OnceCell<BuiltReactivePanel>,
+}
+
+impl ReactivePanel {
+    pub fn new(state: &ReactivePanelState) -> Self {
+        Self {
+            config: ReactivePanelConfig {
+                graph: state.graph.clone(),
+                previous: state.previous.clone(),
+                stores: state.stores.clone(),
+                selected_signal: state.selected_signal,
+                value: state.value.clone(),
+                follow: state.follow,
+                on_refresh: None,
+                on_follow: None,
+                on_select_signal: None,
+                on_set_signal: None,
+            },
+            built: OnceCell::new(),
+        }
+    }
+
+    /// Show the refresh button
+    pub fn on_refresh<F>(mut self, callback: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.config.on_refresh = Some(Arc::new(callback));
+        self
+    }
+
+    /// Show the follow toggle; called with the new follow state
+    pub fn on_follow<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(bool) + Send + Sync + 'static,
+    {
+        self.config.on_follow = Some(Arc::new(callback));
+        self
+    }
+
+    /// Called with the signal ID when an editable signal is clicked
+    pub fn on_select_signal<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(u64) + Send + Sync + 'static,
+    {
+        self.config.on_select_signal = Some(Arc::new(callback));
+        self
+    }
+
+    /// Called with (signal ID, value) when a new value is applied
+    pub fn on_set_signal<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(u64, &str) + Send + Sync + 'static,
+    {
+        self.config.on_set_signal = Some(Arc::new(callback));
+        self
+    }
+
+    fn get_or_build(&self) -> &BuiltReactivePanel {
+        self.built
+            .get_or_init(|| BuiltReactivePanel::from_config(&self.config))
+    }
+}
+
+impl ElementBuilder for ReactivePanel {
+    fn build(&self, tree: &mut LayoutTree) -> LayoutNodeId {
+        self.get_or_build().inner.build(tree)
+    }
+
+    fn render_props(&self) -> RenderProps {
+        self.get_or_build().inner.render_props()
+    }
+
+    fn children_builders(&self) -> &[Box<dyn ElementBuilder>] {
+        self.get_or_build().inner.children_builders()
+    }
+
+    fn event_handlers(&self) -> Option<&EventHandlers> {
+        let handlers = self.get_or_build().inner.event_handlers();
+        if handlers.is_empty() {
+            None
+        } else {
+            Some(handlers)
+        }
+    }
+}
@@ -1,6 +1,7 @@
 //! Timeline Panel - Event timeline with scrubber
 
 use std::cell::OnceCell;
+use std::sync::Arc;
 
 use junita_cn::components::button::{button, ButtonSize, ButtonVariant};
 use junita_cn::components::select::{select, SelectSize};
@@ -20,6 +21,8 @@ use junita_theme::{ColorToken, ThemeState};
 
 use crate::theme::DebuggerTokens;
 
+type SeekCallback = Arc<dyn Fn(Timestamp) + Send + Sync>;
+
 #[derive(Clone)]
 pub struct TimelinePanelState {
     pub position: Timestamp,
@@ -44,6 +47,7 @@ struct TimelinePanelConfig {
     duration: Timestamp,
     playback_state: ReplayState,
     speed: f64,
+    on_seek: Option<SeekCallback>,
 }
 
 struct BuiltTimelinePanel {
@@ -162,6 +166,18 @@ impl BuiltTimelinePanel {
         let position_state =
             JunitaContextState::get().use_state_keyed("timeline_position", || position_norm);
 
+        let mut scrubber = slider(&position_state)
+            .min(0.0)
+            .max(1.0)
+            .size(SliderSize::Small)
+            .w(740.0);
+        if let Some(on_seek) = config.on_seek.clone() {
+            let duration = config.duration.as_micros() as f64;
+            scrubber = scrubber.on_change(move |value| {
+                on_seek(Timestamp::from_micros((value as f64 * duration) as u64));
+            });
+        }
+
         div()
             .w_full()
             .padding_x_px(24.0)
@@ -172,14 +188,7 @@ impl BuiltTimelinePanel {
             .items_center()
             .justify_center()
             .child(Self::event_markers())
-            .child(
-                slider(&position_state)
-                    .min(0.0)
-                    .max(1.0)
-                    .size(SliderSize::Small)
-                    .w(740.0)
-                    .build_final(),
-            )
+            .child(scrubber.build_final())
             .child(Self::time_labels(config.duration))
     }
 
@@ -248,11 +257,21 @@ impl TimelinePanel {
                 duration: state.duration,
                 playback_state: state.playback_state,
                 speed: state.speed,
+                on_seek: None,
             },
             built: OnceCell::new(),
         }
     }
 
+    /// Called with the new position when the scrubber is dragged
+    pub fn on_seek<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(Timestamp) + Send + Sync + 'static,
+    {
+        self.config.on_seek = Some(Arc::new(callback));
+        self
+    }
+
     fn get_or_build(&self) -> &BuiltTimelinePanel {
         self.built
             .get_or_init(|| BuiltTimelinePanel::from_config(&self.config))
@@ -1,17 +1,20 @@
 //! Tree Panel - Element tree with diff visualization
 
 use std::cell::OnceCell;
+use std::sync::Arc;
 
+use junita_cn::components::button::{button, ButtonSize, ButtonVariant};
 use junita_cn::components::input::{input, InputSize};
 use junita_cn::components::separator::separator;
-use junita_cn::components::tree::{tree_view, TreeNodeDiff};
+use junita_cn::components::tree::{tree_view, TreeNodeConfig};
+use junita_icons::icons;
 use junita_layout::div::{Div, ElementBuilder};
 use junita_layout::element::RenderProps;
 use junita_layout::event_handler::EventHandlers;
 use junita_layout::prelude::*;
 use junita_layout::tree::{LayoutNodeId, LayoutTree};
 use junita_layout::widgets::text_input::text_input_data;
-use junita_recorder::TreeSnapshot;
+use junita_recorder::{ElementSnapshot, TreeSnapshot};
 use junita_theme::{ColorToken, ThemeState};
 
 use crate::theme::DebuggerTokens;
@@ -24,8 +27,19 @@ pub struct TreePanelState {
     pub filter_text: String,
 }
 
+type SelectCallback = Arc<dyn Fn(&str) + Send + Sync>;
+type HoverCallback = Arc<dyn Fn(Option<&str>) + Send + Sync>;
+type PickCallback = Arc<dyn Fn(bool) + Send + Sync>;
+
 struct TreePanelConfig {
     has_snapshot: bool,
+    nodes: Vec<TreeNodeConfig>,
+    selected_id: Option<String>,
+    picking: bool,
+    on_select: Option<SelectCallback>,
+    on_hover: Option<HoverCallback>,
+    /// Set when attached to a live app; toggles element picking
+    on_pick: Option<PickCallback>,
 }
 
 struct BuiltTreePanel {
@@ -41,29 +55,47 @@ impl BuiltTreePanel {
             .h_full()
             .bg(theme.color(ColorToken::SurfaceElevated))
             .flex_col()
-            .child(Self::header())
+            .child(Self::header(config))
             .child(separator())
             .child(Self::search_bar())
-            .child(Self::tree_content(config.has_snapshot))
+            .child(Self::tree_content(config))
             .child(separator());
 
         BuiltTreePanel { inner }
     }
 
-    fn header() -> Div {
+    fn header(config: &TreePanelConfig) -> Div {
         let theme = ThemeState::get();
-        div()
+        let mut header = div()
             .h(44.0)
             .px(12.0)
             .py(2.0)
             .flex_row()
             .items_center()
+            .justify_between()
             .child(
                 text("Element Tree")
                     .size(13.0)
                     .color(theme.color(ColorToken::TextPrimary))
                     .weight(junita_layout::div::FontWeight::SemiBold),
-            )
+            );
+
+        if let Some(on_pick) = config.on_pick.clone() {
+            let picking = config.picking;
+            header = header.child(
+                button("")
+                    .variant(if picking {
+                        ButtonVariant::Primary
+                    } else {
+                        ButtonVariant::Ghost
+                    })
+                    .size(ButtonSize::Icon)
+                    .icon(icons::CROSSHAIR)
+                    .on_click(move |_| on_pick(!picking)),
+            );
+        }
+
+        header
     }
 
     fn search_bar() -> Div {
@@ -75,9 +107,9 @@ impl BuiltTreePanel {
         )
     }
 
-    fn tree_content(has_snapshot: bool) -> Scroll {
-        let content = if has_snapshot {
-            Self::render_tree_placeholder()
+    fn tree_content(config: &TreePanelConfig) -> Scroll {
+        let content = if config.has_snapshot {
+            Self::render_tree(config)
         } else {
             Self::render_empty_state()
         };
@@ -85,23 +117,21 @@ impl BuiltTreePanel {
         scroll().flex_grow().child(content)
     }
 
-    fn render_tree_placeholder() -> Div {
-        div().child(
-            tree_view()
-                .indent(12.0)
-                .node("root", "root", |n| {
-                    n.expanded()
-                        .child("header", "header", |c| c)
-                        .child("main", "main", |c| {
-                            c.expanded()
-                                .diff(TreeNodeDiff::Modified)
-                                .child("sidebar", "sidebar", |c| c)
-                                .child("content", "content", |c| c.diff(TreeNodeDiff::Added))
-                        })
-                        .child("footer", "footer", |c| c)
-                })
-                .on_select(|_key| {}),
-        )
+    fn render_tree(config: &TreePanelConfig) -> Div {
+        let mut tree = tree_view().indent(12.0);
+        for node in &config.nodes {
+            tree = tree.add_node(node.clone());
+        }
+        if let Some(selected) = &config.selected_id {
+            tree = tree.selected(selected.clone());
+        }
+        if let Some(on_select) = config.on_select.clone() {
+            tree = tree.on_select(move |key| on_select(key));
+        }
+        if let Some(on_hover) = config.on_hover.clone() {
+            tree = tree.on_hover(move |key| on_hover(key));
+        }
+        div().child(tree)
     }
 
     fn render_empty_state() -> Div {
@@ -125,15 +155,56 @@ pub struct TreePanel {
 }
 
 impl TreePanel {
-    pub fn new(_snapshot: Option<&TreeSnapshot>, _state: &TreePanelState) -> Self {
+    pub fn new(snapshot: Option<&TreeSnapshot>, state: &TreePanelState) -> Self {
+        let nodes = snapshot
+            .and_then(|s| {
+                let root = s.root_id.as_ref()?;
+                Some(vec![tree_node(s, s.get(root)?).expanded()])
+            })
+            .unwrap_or_default();
+
         Self {
             config: TreePanelConfig {
-                has_snapshot: _snapshot.is_some(),
+                has_snapshot: snapshot.is_some(),
+                nodes,
+                selected_id: state.selected_id.clone(),
+                picking: false,
+                on_select: None,
+                on_hover: None,
+                on_pick: None,
             },
             built: OnceCell::new(),
         }
     }
 
+    /// Called with the element ID when a row is selected
+    pub fn on_select<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&str) + Send + Sync + 'static,
+    {
+        self.config.on_select = Some(Arc::new(callback));
+        self
+    }
+
+    /// Called with the element ID under the pointer, or `None` when it leaves
+    pub fn on_hover<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(Option<&str>) + Send + Sync + 'static,
+    {
+        self.config.on_hover = Some(Arc::new(callback));
+        self
+    }
+
+    /// Show the pick toggle; called with the new picking state
+    pub fn on_pick<F>(mut self, picking: bool, callback: F) -> Self
+    where
+        F: Fn(bool) + Send + Sync + 'static,
+    {
+        self.config.picking = picking;
+        self.config.on_pick = Some(Arc::new(callback));
+        self
+    }
+
     fn get_or_build(&self) -> &BuiltTreePanel {
         self.built
             .get_or_init(|| BuiltTreePanel::from_config(&self.config))
@@ -166,3 +237,27 @@ impl ElementBuilder for TreePanel {
         }
     }
 }
+
+/// Build the tree view node for an element and its descendants
+fn tree_node(snapshot: &TreeSnapshot, element: &ElementSnapshot) -> TreeNodeConfig {
+    let label = match &element.text_content {
+        Some(text) => format!("{} \"{}\"", element.element_type, truncate(text, 24)),
+        None => element.element_type.clone(),
+    };
+
+    let mut node = TreeNodeConfig::new(element.id.clone(), label);
+    node.children = element
+        .children
+        .iter()
+        .filter_map(|id| snapshot.get(id))
+        .map(|child| tree_node(snapshot, child))
+        .collect();
+    node
+}
+
+fn truncate(text: &str, max_chars: usize) -> String {
+    match text.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text.to_string(),
+    }
+}
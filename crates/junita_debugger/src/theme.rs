@@ -199,7 +199,9 @@ impl DebuggerTokens {
     // Panel dimensions (debugger-specific layout constants)
     pub const TREE_PANEL_WIDTH: f32 = 280.0;
     pub const INSPECTOR_WIDTH: f32 = 300.0;
+    pub const REACTIVE_PANEL_WIDTH: f32 = 300.0;
     pub const TIMELINE_HEIGHT: f32 = 150.0;
+    pub const PROFILER_HEIGHT: f32 = 220.0;
     pub const HEADER_HEIGHT: f32 = 48.0;
     pub const PANEL_GAP: f32 = 8.0;
     pub const CARD_PADDING: f32 = 16.0;
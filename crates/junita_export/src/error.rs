@@ -0,0 +1,23 @@
+//! Export error types
+
+use std::io;
+use thiserror::Error;
+
+/// Errors that can occur while exporting a frame
+#[derive(Error, Debug)]
+pub enum ExportError {
+    /// IO error when writing the output
+    #[error("IO error: {0}")]
+    Io(#[from] io::Error),
+
+    /// Image data could not be encoded
+    #[error("Image encoding error: {0}")]
+    Image(String),
+
+    /// The document has no pages or a frame has no size
+    #[error("Empty document: {0}")]
+    Empty(String),
+}
+
+/// Result type for export operations
+pub type Result<T> = std::result::Result<T, ExportError>;
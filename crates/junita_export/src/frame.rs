@@ -0,0 +1,393 @@
+//! Frames to export
+//!
+//! An [`ExportFrame`] is a recorded `DrawCommand` stream plus the pixel data
+//! for any images it references. Frames come from a `RecordingContext`, a
+//! laid-out `RenderTree`, or directly from an element subtree.
+
+use std::collections::HashMap;
+
+use junita_core::{
+    Brush, Color, DrawCommand, DrawContext, FontWeight, ImageId, ImageOptions, Point,
+    RecordingContext, Rect, Size, TextAlign, TextBaseline, TextStyle,
+};
+use junita_layout::div::{self, ElementBuilder};
+use junita_layout::renderer::{ImageData, LayoutRenderer, RenderTree};
+use junita_svg::SvgDocument;
+
+/// Straight-alpha RGBA8 image referenced by a frame
+#[derive(Clone, Debug)]
+pub struct ExportImage {
+    /// Width in pixels
+    pub width: u32,
+    /// Height in pixels
+    pub height: u32,
+    /// Straight-alpha RGBA8 pixels
+    pub rgba: Vec<u8>,
+}
+
+impl ExportImage {
+    /// Create an image, returning `None` if the buffer size does not match
+    pub fn new(width: u32, height: u32, rgba: Vec<u8>) -> Option<Self> {
+        (rgba.len() == width as usize * height as usize * 4).then_some(Self {
+            width,
+            height,
+            rgba,
+        })
+    }
+}
+
+/// A recorded frame ready for export
+#[derive(Clone, Debug)]
+pub struct ExportFrame {
+    /// Logical size of the frame
+    pub size: Size,
+    /// Recorded draw commands
+    pub commands: Vec<DrawCommand>,
+    /// Pixel data for images referenced by `DrawImage` commands
+    pub images: HashMap<ImageId, ExportImage>,
+}
+
+impl ExportFrame {
+    /// Create a frame from a list of draw commands
+    pub fn new(size: Size, commands: Vec<DrawCommand>) -> Self {
+        Self {
+            size,
+            commands,
+            images: HashMap::new(),
+        }
+    }
+
+    /// Create a frame from a recording context
+    pub fn from_recording(recording: &RecordingContext) -> Self {
+        Self::new(recording.viewport_size(), recording.commands().to_vec())
+    }
+
+    /// Record a laid-out render tree
+    ///
+    /// Divs, text, SVG icons and image elements are all captured. The tree
+    /// must have had `compute_layout` called.
+    pub fn from_tree(tree: &RenderTree, size: Size) -> Self {
+        let mut recorder = TreeRecorder::new(size);
+        tree.render_to(&mut recorder);
+        for (image, bounds) in tree.image_elements() {
+            recorder.record_image(
+                &image,
+                Rect::new(bounds.x, bounds.y, bounds.width, bounds.height),
+            );
+        }
+        recorder.finish()
+    }
+
+    /// Lay out and record an element subtree at the given size
+    pub fn from_element<E: ElementBuilder>(element: &E, width: f32, height: f32) -> Self {
+        let mut tree = RenderTree::from_element(element);
+        tree.compute_layout(width, height);
+        Self::from_tree(&tree, Size::new(width, height))
+    }
+
+    /// Register pixels for an image id used by `DrawImage` commands
+    pub fn with_image(mut self, id: ImageId, image: ExportImage) -> Self {
+        self.images.insert(id, image);
+        self
+    }
+
+    /// Register pixels for an image id used by `DrawImage` commands
+    pub fn insert_image(&mut self, id: ImageId, image: ExportImage) {
+        self.images.insert(id, image);
+    }
+}
+
+/// Deferred text or SVG content, drawn after image elements
+enum Deferred {
+    Text {
+        content: String,
+        bounds: Rect,
+        font_size: f32,
+        color: [f32; 4],
+        align: div::TextAlign,
+        weight: div::FontWeight,
+    },
+    Svg {
+        source: String,
+        bounds: Rect,
+        tint: Option<Color>,
+    },
+}
+
+/// `LayoutRenderer` that records everything into one command stream
+struct TreeRecorder {
+    recording: RecordingContext,
+    images: HashMap<ImageId, ExportImage>,
+    sources: HashMap<String, Option<ImageId>>,
+    deferred: Vec<Deferred>,
+    next_image: u64,
+}
+
+impl TreeRecorder {
+    fn new(size: Size) -> Self {
+        Self {
+            recording: RecordingContext::new(size),
+            images: HashMap::new(),
+            sources: HashMap::new(),
+            deferred: Vec::new(),
+            next_image: 0,
+        }
+    }
+
+    fn finish(mut self) -> ExportFrame {
+        for item in std::mem::take(&mut self.deferred) {
+            match item {
+                Deferred::Text {
+                    content,
+                    bounds,
+                    font_size,
+                    color,
+                    align,
+                    weight,
+                } => self.record_text(&content, bounds, font_size, color, align, weight),
+                Deferred::Svg {
+                    source,
+                    bounds,
+                    tint,
+                } => self.record_svg(&source, bounds, tint),
+            }
+        }
+        let size = self.recording.viewport_size();
+        ExportFrame {
+            size,
+            commands: self.recording.take_commands(),
+            images: self.images,
+        }
+    }
+
+    fn record_text(
+        &mut self,
+        content: &str,
+        bounds: Rect,
+        font_size: f32,
+        color: [f32; 4],
+        align: div::TextAlign,
+        weight: div::FontWeight,
+    ) {
+        let (x, align) = match align {
+            div::TextAlign::Left => (bounds.x(), TextAlign::Left),
+            div::TextAlign::Center => (bounds.x() + bounds.width() / 2.0, TextAlign::Center),
+            div::TextAlign::Right => (bounds.x() + bounds.width(), TextAlign::Right),
+        };
+        let style = TextStyle {
+            size: font_size,
+            weight: core_weight(weight.weight()),
+            color: Color::rgba(color[0], color[1], color[2], color[3]),
+            align,
+            baseline: TextBaseline::Middle,
+            ..TextStyle::default()
+        };
+        let origin = Point::new(x, bounds.y() + bounds.height() / 2.0);
+        self.recording.draw_text(content, origin, &style);
+    }
+
+    fn record_svg(&mut self, source: &str, bounds: Rect, tint: Option<Color>) {
+        let doc = match SvgDocument::from_str(source) {
+            Ok(doc) => doc,
+            Err(err) => {
+                tracing::debug!("junita_export: failed to parse SVG: {}", err);
+                return;
+            }
+        };
+        let Some(tint) = tint else {
+            doc.render_fit(&mut self.recording, bounds);
+            return;
+        };
+
+        // Tinted icons replace every paint with the tint colour
+        let mut icon = RecordingContext::new(self.recording.viewport_size());
+        doc.render_fit(&mut icon, bounds);
+        for command in icon.take_commands() {
+            match command {
+                DrawCommand::FillPath { path, .. } => {
+                    self.recording.fill_path(&path, Brush::Solid(tint))
+                }
+                DrawCommand::StrokePath { path, stroke, .. } => {
+                    self.recording
+                        .stroke_path(&path, &stroke, Brush::Solid(tint))
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn record_image(&mut self, image: &ImageData, rect: Rect) {
+        let Some(id) = self.resolve_source(&image.source) else {
+            return;
+        };
+        let Some((iw, ih)) = self
+            .images
+            .get(&id)
+            .map(|i| (i.width as f32, i.height as f32))
+        else {
+            return;
+        };
+
+        let (sx, sy) = (rect.width() / iw, rect.height() / ih);
+        let (w, h) = match image.object_fit {
+            // cover
+            0 => (iw * sx.max(sy), ih * sx.max(sy)),
+            // contain
+            1 => (iw * sx.min(sy), ih * sx.min(sy)),
+            // scale-down
+            3 => {
+                let s = sx.min(sy).min(1.0);
+                (iw * s, ih * s)
+            }
+            // none
+            4 => (iw, ih),
+            // fill
+            _ => (rect.width(), rect.height()),
+        };
+        let [px, py] = image.object_position;
+        let dest = Rect::new(
+            rect.x() + (rect.width() - w) * px,
+            rect.y() + (rect.height() - h) * py,
+            w,
+            h,
+        );
+
+        let [r, g, b, a] = image.tint;
+        let tint = Color::rgba(r, g, b, a);
+        let mut options = ImageOptions::new().with_opacity(image.opacity);
+        if tint != Color::WHITE && a > 0.0 {
+            options = options.with_tint(tint);
+        }
+
+        self.recording
+            .push_clip(junita_core::ClipShape::rounded_rect(
+                rect,
+                image.border_radius,
+            ));
+        self.recording.draw_image(id, dest, &options);
+        self.recording.pop_clip();
+    }
+
+    fn resolve_source(&mut self, source: &str) -> Option<ImageId> {
+        if let Some(id) = self.sources.get(source) {
+            return *id;
+        }
+        let id = decode_source(source).map(|image| {
+            let id = ImageId(self.next_image);
+            self.next_image += 1;
+            self.images.insert(id, image);
+            id
+        });
+        self.sources.insert(source.to_string(), id);
+        id
+    }
+}
+
+#[cfg(feature = "images")]
+fn decode_source(source: &str) -> Option<ExportImage> {
+    match junita_image::ImageData::load(junita_image::ImageSource::from_uri(source)) {
+        Ok(data) => ExportImage::new(data.width(), data.height(), data.pixels().to_vec()),
+        Err(err) => {
+            tracing::warn!("junita_export: failed to load image '{}': {}", source, err);
+            None
+        }
+    }
+}
+
+#[cfg(not(feature = "images"))]
+fn decode_source(source: &str) -> Option<ExportImage> {
+    tracing::debug!(
+        "junita_export: image decoding disabled, skipping '{}' (enable the `images` feature)",
+        source
+    );
+    None
+}
+
+/// Map a numeric weight (100-900) to the nearest core font weight
+fn core_weight(weight: u16) -> FontWeight {
+    match weight {
+        0..=199 => FontWeight::Thin,
+        200..=349 => FontWeight::Light,
+        350..=449 => FontWeight::Regular,
+        450..=599 => FontWeight::Medium,
+        600..=799 => FontWeight::Bold,
+        _ => FontWeight::Black,
+    }
+}
+
+impl LayoutRenderer for TreeRecorder {
+    fn background(&mut self) -> &mut dyn DrawContext {
+        &mut self.recording
+    }
+
+    fn foreground(&mut self) -> &mut dyn DrawContext {
+        &mut self.recording
+    }
+
+    fn render_text_foreground(
+        &mut self,
+        content: &str,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        font_size: f32,
+        color: [f32; 4],
+        align: div::TextAlign,
+        weight: div::FontWeight,
+    ) {
+        self.deferred.push(Deferred::Text {
+            content: content.to_string(),
+            bounds: Rect::new(x, y, width, height),
+            font_size,
+            color,
+            align,
+            weight,
+        });
+    }
+
+    fn render_text_background(
+        &mut self,
+        content: &str,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        font_size: f32,
+        color: [f32; 4],
+        align: div::TextAlign,
+        weight: div::FontWeight,
+    ) {
+        self.render_text_foreground(
+            content, x, y, width, height, font_size, color, align, weight,
+        );
+    }
+
+    fn render_svg_foreground(
+        &mut self,
+        source: &str,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        tint: Option<Color>,
+    ) {
+        self.deferred.push(Deferred::Svg {
+            source: source.to_string(),
+            bounds: Rect::new(x, y, width, height),
+            tint,
+        });
+    }
+
+    fn render_svg_background(
+        &mut self,
+        source: &str,
+        x: f32,
+        y: f32,
+        width: f32,
+        height: f32,
+        tint: Option<Color>,
+    ) {
+        self.render_svg_foreground(source, x, y, width, height, tint);
+    }
+}
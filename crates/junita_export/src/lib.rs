@@ -0,0 +1,46 @@
+//! Vector export for Junita
+//!
+//! Turns recorded `DrawCommand` streams into resolution-independent SVG and
+//! PDF documents, for print, documentation screenshots and design hand-off.
+//!
+//! # Coverage
+//!
+//! - Paths, rects and circles with solid and linear/radial gradient brushes
+//! - Strokes with caps, joins and dashes
+//! - Clips (nested), transforms, opacity and blend modes
+//! - Text as glyph outlines (or `<text>` elements in SVG)
+//! - Images embedded as PNG (SVG) or compressed image XObjects (PDF)
+//! - Shadows as Gaussian blur filters (SVG) or stacked layers (PDF)
+//!
+//! Conic gradients, glass and blur brushes are approximated by a solid
+//! colour. 3D content and layer sampling have no vector equivalent and are
+//! skipped.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use junita_export::{export_svg, ExportFrame, PdfDocument, SvgOptions};
+//!
+//! let frame = ExportFrame::from_element(&ui, 800.0, 600.0);
+//!
+//! let svg = export_svg(&frame, &SvgOptions::new())?;
+//! std::fs::write("ui.svg", svg)?;
+//!
+//! let mut pdf = PdfDocument::new();
+//! pdf.add_paginated(&frame, 1100.0)?;
+//! std::fs::write("ui.pdf", pdf.finish()?)?;
+//! ```
+
+mod error;
+mod frame;
+mod paint;
+mod path;
+mod pdf;
+mod scene;
+mod svg;
+mod text;
+
+pub use error::{ExportError, Result};
+pub use frame::{ExportFrame, ExportImage};
+pub use pdf::{export_pdf, PdfDocument};
+pub use svg::{export_svg, SvgOptions, TextMode};
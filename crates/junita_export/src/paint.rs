@@ -0,0 +1,184 @@
+//! Brush resolution shared by the exporters
+//!
+//! Both formats support solid colours and linear/radial gradients. Other
+//! brushes are approximated: conic gradients by their average colour, and
+//! glass/blur backdrops by their tint, since neither format can sample the
+//! content behind a shape.
+
+use std::borrow::Cow;
+
+use junita_core::{
+    Brush, Color, Gradient, GradientSpace, GradientSpread, GradientStop, Point, Rect,
+};
+
+use crate::frame::ExportImage;
+
+/// A brush resolved to user-space coordinates
+#[derive(Clone, Debug)]
+pub(crate) enum Paint {
+    Solid(Color),
+    Linear {
+        start: Point,
+        end: Point,
+        stops: Vec<GradientStop>,
+        spread: GradientSpread,
+    },
+    Radial {
+        center: Point,
+        radius: f32,
+        focal: Point,
+        stops: Vec<GradientStop>,
+        spread: GradientSpread,
+    },
+}
+
+impl Paint {
+    /// Resolve a brush against the bounds of the shape it paints
+    ///
+    /// Returns `None` for brushes with nothing to draw.
+    pub(crate) fn resolve(brush: &Brush, bounds: Rect) -> Option<Self> {
+        let paint = match brush {
+            Brush::Solid(color) => Paint::Solid(*color),
+            Brush::Gradient(gradient) => resolve_gradient(gradient, bounds),
+            Brush::Glass(glass) => Paint::Solid(glass.tint),
+            Brush::Blur(blur) => Paint::Solid(blur.tint?),
+            Brush::Image(_) => {
+                tracing::debug!("junita_export: image brushes are not exported");
+                return None;
+            }
+        };
+        match &paint {
+            Paint::Solid(color) if color.a <= 0.0 => None,
+            _ => Some(paint),
+        }
+    }
+
+    /// Representative colour, for places that only take a solid colour
+    pub(crate) fn fallback_color(&self) -> Color {
+        match self {
+            Paint::Solid(color) => *color,
+            Paint::Linear { stops, .. } | Paint::Radial { stops, .. } => average_color(stops),
+        }
+    }
+}
+
+fn resolve_gradient(gradient: &Gradient, bounds: Rect) -> Paint {
+    let to_user = |space: GradientSpace, p: Point| match space {
+        GradientSpace::UserSpace => p,
+        GradientSpace::ObjectBoundingBox => Point::new(
+            bounds.x() + p.x * bounds.width(),
+            bounds.y() + p.y * bounds.height(),
+        ),
+    };
+    match gradient {
+        Gradient::Linear {
+            start,
+            end,
+            stops,
+            space,
+            spread,
+        } => Paint::Linear {
+            start: to_user(*space, *start),
+            end: to_user(*space, *end),
+            stops: stops.clone(),
+            spread: *spread,
+        },
+        Gradient::Radial {
+            center,
+            radius,
+            focal,
+            stops,
+            space,
+            spread,
+        } => {
+            let radius = match space {
+                GradientSpace::UserSpace => *radius,
+                GradientSpace::ObjectBoundingBox => radius * bounds.width().max(bounds.height()),
+            };
+            Paint::Radial {
+                center: to_user(*space, *center),
+                radius,
+                focal: to_user(*space, focal.unwrap_or(*center)),
+                stops: stops.clone(),
+                spread: *spread,
+            }
+        }
+        Gradient::Conic { stops, .. } => Paint::Solid(average_color(stops)),
+    }
+}
+
+fn average_color(stops: &[GradientStop]) -> Color {
+    if stops.is_empty() {
+        return Color::TRANSPARENT;
+    }
+    let n = stops.len() as f32;
+    let sum = stops.iter().fold([0.0f32; 4], |acc, s| {
+        [
+            acc[0] + s.color.r,
+            acc[1] + s.color.g,
+            acc[2] + s.color.b,
+            acc[3] + s.color.a,
+        ]
+    });
+    Color::rgba(sum[0] / n, sum[1] / n, sum[2] / n, sum[3] / n)
+}
+
+/// Image pixels with the `ImageOptions` tint multiplied in
+pub(crate) fn image_pixels(image: &ExportImage, tint: Option<Color>) -> Cow<'_, [u8]> {
+    match tint {
+        Some(tint) if tint != Color::WHITE => {
+            let factors = [tint.r, tint.g, tint.b, tint.a];
+            let pixels = image
+                .rgba
+                .chunks_exact(4)
+                .flat_map(|px| {
+                    let mut out = [0u8; 4];
+                    for (i, v) in out.iter_mut().enumerate() {
+                        *v = (px[i] as f32 * factors[i]).round().clamp(0.0, 255.0) as u8;
+                    }
+                    out
+                })
+                .collect();
+            Cow::Owned(pixels)
+        }
+        _ => Cow::Borrowed(&image.rgba),
+    }
+}
+
+/// Colour component as an 8-bit value
+pub(crate) fn channel(v: f32) -> u8 {
+    (v.clamp(0.0, 1.0) * 255.0).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bounding_box_gradient_is_resolved() {
+        let brush = Brush::Gradient(Gradient::Linear {
+            start: Point::new(0.0, 0.5),
+            end: Point::new(1.0, 0.5),
+            stops: vec![
+                GradientStop::new(0.0, Color::RED),
+                GradientStop::new(1.0, Color::BLUE),
+            ],
+            space: GradientSpace::ObjectBoundingBox,
+            spread: GradientSpread::Pad,
+        });
+        let paint = Paint::resolve(&brush, Rect::new(10.0, 20.0, 100.0, 40.0)).unwrap();
+        match paint {
+            Paint::Linear { start, end, .. } => {
+                assert_eq!((start.x, start.y), (10.0, 40.0));
+                assert_eq!((end.x, end.y), (110.0, 40.0));
+            }
+            other => panic!("expected linear paint, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_transparent_brush_is_skipped() {
+        let bounds = Rect::new(0.0, 0.0, 1.0, 1.0);
+        assert!(Paint::resolve(&Brush::Solid(Color::TRANSPARENT), bounds).is_none());
+    }
+}
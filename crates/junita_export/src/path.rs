@@ -0,0 +1,398 @@
+//! Path helpers shared by the exporters
+
+use std::fmt::Write;
+
+use junita_core::{Path, PathCommand, Point, Vec2};
+
+/// Path commands with every `ArcTo` replaced by cubic Béziers
+///
+/// PDF has no arc operator, and arcs under a non-uniform transform are no
+/// longer arcs, so both cases need this.
+pub(crate) fn without_arcs(path: &Path) -> Vec<PathCommand> {
+    let mut out = Vec::with_capacity(path.commands().len());
+    let mut current = Point::ZERO;
+    let mut start = Point::ZERO;
+
+    for cmd in path.commands() {
+        match cmd {
+            PathCommand::MoveTo(p) => {
+                current = *p;
+                start = *p;
+                out.push(cmd.clone());
+            }
+            PathCommand::LineTo(p) => {
+                current = *p;
+                out.push(cmd.clone());
+            }
+            PathCommand::QuadTo { end, .. } | PathCommand::CubicTo { end, .. } => {
+                current = *end;
+                out.push(cmd.clone());
+            }
+            PathCommand::ArcTo {
+                radii,
+                rotation,
+                large_arc,
+                sweep,
+                end,
+            } => {
+                let cubics = arc_to_cubics(current, *radii, *rotation, *large_arc, *sweep, *end);
+                if cubics.is_empty() {
+                    out.push(PathCommand::LineTo(*end));
+                }
+                for (control1, control2, end) in cubics {
+                    out.push(PathCommand::CubicTo {
+                        control1,
+                        control2,
+                        end,
+                    });
+                }
+                current = *end;
+            }
+            PathCommand::Close => {
+                current = start;
+                out.push(PathCommand::Close);
+            }
+        }
+    }
+    out
+}
+
+/// Translate a path by an offset
+pub(crate) fn translate_path(path: &Path, dx: f32, dy: f32) -> Path {
+    let t = |p: &Point| Point::new(p.x + dx, p.y + dy);
+    let commands = path
+        .commands()
+        .iter()
+        .map(|cmd| match cmd {
+            PathCommand::MoveTo(p) => PathCommand::MoveTo(t(p)),
+            PathCommand::LineTo(p) => PathCommand::LineTo(t(p)),
+            PathCommand::QuadTo { control, end } => PathCommand::QuadTo {
+                control: t(control),
+                end: t(end),
+            },
+            PathCommand::CubicTo {
+                control1,
+                control2,
+                end,
+            } => PathCommand::CubicTo {
+                control1: t(control1),
+                control2: t(control2),
+                end: t(end),
+            },
+            PathCommand::ArcTo {
+                radii,
+                rotation,
+                large_arc,
+                sweep,
+                end,
+            } => PathCommand::ArcTo {
+                radii: *radii,
+                rotation: *rotation,
+                large_arc: *large_arc,
+                sweep: *sweep,
+                end: t(end),
+            },
+            PathCommand::Close => PathCommand::Close,
+        })
+        .collect();
+    Path::from_commands(commands)
+}
+
+/// Append several paths into one
+pub(crate) fn concat_paths<'a>(paths: impl IntoIterator<Item = &'a Path>) -> Path {
+    let mut commands = Vec::new();
+    for path in paths {
+        commands.extend_from_slice(path.commands());
+    }
+    Path::from_commands(commands)
+}
+
+/// Format a number compactly for SVG/PDF output
+pub(crate) fn num(v: f32) -> String {
+    if !v.is_finite() {
+        return "0".to_string();
+    }
+    let rounded = (v * 1000.0).round() / 1000.0;
+    if rounded == rounded.trunc() {
+        format!("{}", rounded as i64)
+    } else {
+        let s = format!("{:.3}", rounded);
+        s.trim_end_matches('0').to_string()
+    }
+}
+
+/// SVG path data (`d` attribute) for a path
+pub(crate) fn svg_path_data(path: &Path) -> String {
+    let mut d = String::new();
+    for cmd in path.commands() {
+        if !d.is_empty() {
+            d.push(' ');
+        }
+        let _ = match cmd {
+            PathCommand::MoveTo(p) => write!(d, "M{} {}", num(p.x), num(p.y)),
+            PathCommand::LineTo(p) => write!(d, "L{} {}", num(p.x), num(p.y)),
+            PathCommand::QuadTo { control, end } => write!(
+                d,
+                "Q{} {} {} {}",
+                num(control.x),
+                num(control.y),
+                num(end.x),
+                num(end.y)
+            ),
+            PathCommand::CubicTo {
+                control1,
+                control2,
+                end,
+            } => write!(
+                d,
+                "C{} {} {} {} {} {}",
+                num(control1.x),
+                num(control1.y),
+                num(control2.x),
+                num(control2.y),
+                num(end.x),
+                num(end.y)
+            ),
+            PathCommand::ArcTo {
+                radii,
+                rotation,
+                large_arc,
+                sweep,
+                end,
+            } => write!(
+                d,
+                "A{} {} {} {} {} {} {}",
+                num(radii.x),
+                num(radii.y),
+                num(rotation.to_degrees()),
+                u8::from(*large_arc),
+                u8::from(*sweep),
+                num(end.x),
+                num(end.y)
+            ),
+            PathCommand::Close => write!(d, "Z"),
+        };
+    }
+    d
+}
+
+/// PDF path construction operators (`m`, `l`, `c`, `h`) for a path
+///
+/// Quadratic segments are elevated to cubics since PDF only has the latter.
+pub(crate) fn pdf_path_ops(path: &Path) -> String {
+    let mut ops = String::new();
+    let mut current = Point::ZERO;
+    let mut start = Point::ZERO;
+    for cmd in without_arcs(path) {
+        let _ = match cmd {
+            PathCommand::MoveTo(p) => {
+                current = p;
+                start = p;
+                writeln!(ops, "{} {} m", num(p.x), num(p.y))
+            }
+            PathCommand::LineTo(p) => {
+                current = p;
+                writeln!(ops, "{} {} l", num(p.x), num(p.y))
+            }
+            PathCommand::QuadTo { control, end } => {
+                let c1 = Point::new(
+                    current.x + (control.x - current.x) * 2.0 / 3.0,
+                    current.y + (control.y - current.y) * 2.0 / 3.0,
+                );
+                let c2 = Point::new(
+                    end.x + (control.x - end.x) * 2.0 / 3.0,
+                    end.y + (control.y - end.y) * 2.0 / 3.0,
+                );
+                current = end;
+                writeln!(
+                    ops,
+                    "{} {} {} {} {} {} c",
+                    num(c1.x),
+                    num(c1.y),
+                    num(c2.x),
+                    num(c2.y),
+                    num(end.x),
+                    num(end.y)
+                )
+            }
+            PathCommand::CubicTo {
+                control1,
+                control2,
+                end,
+            } => {
+                current = end;
+                writeln!(
+                    ops,
+                    "{} {} {} {} {} {} c",
+                    num(control1.x),
+                    num(control1.y),
+                    num(control2.x),
+                    num(control2.y),
+                    num(end.x),
+                    num(end.y)
+                )
+            }
+            PathCommand::ArcTo { end, .. } => {
+                // without_arcs never yields arcs; keep the pen consistent anyway
+                current = end;
+                writeln!(ops, "{} {} l", num(end.x), num(end.y))
+            }
+            PathCommand::Close => {
+                current = start;
+                writeln!(ops, "h")
+            }
+        };
+    }
+    ops
+}
+
+/// Convert an SVG-style elliptical arc into cubic Bézier segments
+///
+/// Follows the endpoint-to-center conversion from the SVG spec (F.6.5),
+/// splitting the sweep into segments of at most 90 degrees.
+fn arc_to_cubics(
+    from: Point,
+    radii: Vec2,
+    x_rotation: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: Point,
+) -> Vec<(Point, Point, Point)> {
+    let mut curves = Vec::new();
+    if (from.x - to.x).abs() < f32::EPSILON && (from.y - to.y).abs() < f32::EPSILON {
+        return curves;
+    }
+
+    let mut rx = radii.x.abs();
+    let mut ry = radii.y.abs();
+    if rx == 0.0 || ry == 0.0 {
+        return curves;
+    }
+
+    let (sin_phi, cos_phi) = x_rotation.sin_cos();
+    let dx = (from.x - to.x) / 2.0;
+    let dy = (from.y - to.y) / 2.0;
+    let x1p = cos_phi * dx + sin_phi * dy;
+    let y1p = -sin_phi * dx + cos_phi * dy;
+
+    let lambda = (x1p * x1p) / (rx * rx) + (y1p * y1p) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+
+    let rx_sq = rx * rx;
+    let ry_sq = ry * ry;
+    let numer = (rx_sq * ry_sq - rx_sq * y1p * y1p - ry_sq * x1p * x1p).max(0.0);
+    let denom = rx_sq * y1p * y1p + ry_sq * x1p * x1p;
+    let coef = if denom > 0.0 {
+        (numer / denom).sqrt()
+    } else {
+        0.0
+    };
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let cxp = sign * coef * rx * y1p / ry;
+    let cyp = sign * coef * -ry * x1p / rx;
+
+    let cx = cos_phi * cxp - sin_phi * cyp + (from.x + to.x) / 2.0;
+    let cy = sin_phi * cxp + cos_phi * cyp + (from.y + to.y) / 2.0;
+
+    let angle = |ux: f32, uy: f32, vx: f32, vy: f32| {
+        let dot = ux * vx + uy * vy;
+        let len = (ux * ux + uy * uy).sqrt() * (vx * vx + vy * vy).sqrt();
+        let a = (dot / len).clamp(-1.0, 1.0).acos();
+        if ux * vy - uy * vx < 0.0 {
+            -a
+        } else {
+            a
+        }
+    };
+
+    let ux = (x1p - cxp) / rx;
+    let uy = (y1p - cyp) / ry;
+    let theta1 = angle(1.0, 0.0, ux, uy);
+    let mut dtheta = angle(ux, uy, (-x1p - cxp) / rx, (-y1p - cyp) / ry);
+    if sweep && dtheta < 0.0 {
+        dtheta += std::f32::consts::TAU;
+    } else if !sweep && dtheta > 0.0 {
+        dtheta -= std::f32::consts::TAU;
+    }
+
+    let segments = ((dtheta.abs() / std::f32::consts::FRAC_PI_2).ceil() as usize).max(1);
+    let step = dtheta / segments as f32;
+    let alpha = (step / 4.0).tan() * 4.0 / 3.0;
+
+    let point_at = |t: f32| {
+        let (s, c) = t.sin_cos();
+        Point::new(
+            cx + rx * c * cos_phi - ry * s * sin_phi,
+            cy + rx * c * sin_phi + ry * s * cos_phi,
+        )
+    };
+    let tangent_at = |t: f32| {
+        let (s, c) = t.sin_cos();
+        Point::new(
+            -rx * s * cos_phi - ry * c * sin_phi,
+            -rx * s * sin_phi + ry * c * cos_phi,
+        )
+    };
+
+    for i in 0..segments {
+        let t1 = theta1 + i as f32 * step;
+        let t2 = t1 + step;
+        let p0 = point_at(t1);
+        let p3 = point_at(t2);
+        let d0 = tangent_at(t1);
+        let d3 = tangent_at(t2);
+        curves.push((
+            Point::new(p0.x + alpha * d0.x, p0.y + alpha * d0.y),
+            Point::new(p3.x - alpha * d3.x, p3.y - alpha * d3.y),
+            p3,
+        ));
+    }
+
+    curves
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use junita_core::Rect;
+
+    #[test]
+    fn test_number_formatting() {
+        assert_eq!(num(1.0), "1");
+        assert_eq!(num(0.5), "0.5");
+        assert_eq!(num(-2.12345), "-2.123");
+        assert_eq!(num(f32::NAN), "0");
+    }
+
+    #[test]
+    fn test_arcs_become_cubics() {
+        let path = Path::new().move_to(0.0, 0.0).arc_to(
+            Vec2::new(50.0, 50.0),
+            0.0,
+            false,
+            true,
+            100.0,
+            0.0,
+        );
+        let commands = without_arcs(&path);
+
+        assert!(commands
+            .iter()
+            .all(|c| !matches!(c, PathCommand::ArcTo { .. })));
+        assert!(matches!(
+            commands.last(),
+            Some(PathCommand::CubicTo { end, .. }) if (end.x - 100.0).abs() < 1e-3
+        ));
+    }
+
+    #[test]
+    fn test_svg_path_data() {
+        let d = svg_path_data(&Path::rect(Rect::new(0.0, 0.0, 10.0, 5.5)));
+        assert!(d.starts_with("M0 0"));
+        assert!(d.contains("5.5"));
+        assert!(d.ends_with('Z'));
+    }
+}
@@ -0,0 +1,883 @@
+//! PDF export
+//!
+//! Frames are written as PDF 1.4 pages. Each item is drawn inside its own
+//! `q`/`Q` pair: clip chain first (in page space), then the item transform,
+//! then an ExtGState for opacity and blend mode. Content streams and images
+//! are Flate-compressed.
+//!
+//! PDF has no blur, so shadows are approximated by stacking translucent
+//! copies of the shadow shape at increasing spread. Text is always written
+//! as outlines.
+
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::sync::Arc;
+
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use junita_core::{
+    BlendMode, Color, GradientStop, ImageId, ImageOptions, LineCap, LineJoin, Path, Rect, Shadow,
+    Stroke,
+};
+use junita_text::FontFace;
+
+use crate::error::{ExportError, Result};
+use crate::frame::ExportFrame;
+use crate::paint::{channel, image_pixels, Paint};
+use crate::path::{num, pdf_path_ops};
+use crate::scene::{Item, Primitive, Scene, ShadowShape};
+use crate::text::TextOutliner;
+
+/// Number of stacked layers used to approximate a blurred shadow
+const SHADOW_LAYERS: usize = 8;
+
+const CATALOG_ID: usize = 1;
+const PAGES_ID: usize = 2;
+
+/// A multi-page PDF document built from export frames
+///
+/// ```ignore
+/// let mut pdf = PdfDocument::new().with_title("Report");
+/// pdf.add_page(&ExportFrame::from_element(&ui, 800.0, 600.0))?;
+/// std::fs::write("report.pdf", pdf.finish()?)?;
+/// ```
+pub struct PdfDocument {
+    /// Serialized objects; object number is index + 1
+    objects: Vec<Option<Vec<u8>>>,
+    pages: Vec<usize>,
+    scale: f32,
+    title: Option<String>,
+    outliner: TextOutliner,
+    gstates: HashMap<(u32, u32, &'static str), usize>,
+    images: HashMap<(ImageId, [u8; 4]), usize>,
+}
+
+impl Default for PdfDocument {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PdfDocument {
+    /// Create an empty document at 0.75pt per pixel (96 DPI)
+    pub fn new() -> Self {
+        Self {
+            // Catalog and page tree are written by `finish`
+            objects: vec![None, None],
+            pages: Vec::new(),
+            scale: 0.75,
+            title: None,
+            outliner: TextOutliner::new(None),
+            gstates: HashMap::new(),
+            images: HashMap::new(),
+        }
+    }
+
+    /// Set the number of PDF points per logical pixel
+    pub fn with_scale(mut self, points_per_pixel: f32) -> Self {
+        self.scale = points_per_pixel.max(f32::EPSILON);
+        self
+    }
+
+    /// Set the document title metadata
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Outline all text with a specific font instead of system fonts
+    pub fn with_font(mut self, font: Arc<FontFace>) -> Self {
+        self.outliner = TextOutliner::new(Some(font));
+        self
+    }
+
+    /// Number of pages added so far
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Add a frame as a single page the size of the frame
+    pub fn add_page(&mut self, frame: &ExportFrame) -> Result<()> {
+        check_size(frame)?;
+        let scene = Scene::build(&frame.commands);
+        self.write_page(frame, &scene, 0.0, frame.size.height)
+    }
+
+    /// Split a tall frame into pages of `page_height` logical pixels
+    ///
+    /// Returns the number of pages added.
+    pub fn add_paginated(&mut self, frame: &ExportFrame, page_height: f32) -> Result<usize> {
+        check_size(frame)?;
+        if page_height <= 0.0 {
+            return Err(ExportError::Empty(format!("page height {}", page_height)));
+        }
+        let scene = Scene::build(&frame.commands);
+        let count = (frame.size.height / page_height).ceil().max(1.0) as usize;
+        for page in 0..count {
+            let top = page as f32 * page_height;
+            let height = page_height.min(frame.size.height - top);
+            self.write_page(frame, &scene, top, height)?;
+        }
+        Ok(count)
+    }
+
+    /// Serialize the document
+    pub fn finish(mut self) -> Result<Vec<u8>> {
+        if self.pages.is_empty() {
+            return Err(ExportError::Empty("document has no pages".to_string()));
+        }
+
+        let kids: Vec<String> = self.pages.iter().map(|id| format!("{} 0 R", id)).collect();
+        self.set(
+            PAGES_ID,
+            format!(
+                "<< /Type /Pages /Kids [{}] /Count {} >>",
+                kids.join(" "),
+                self.pages.len()
+            )
+            .into_bytes(),
+        );
+        self.set(
+            CATALOG_ID,
+            format!("<< /Type /Catalog /Pages {} 0 R >>", PAGES_ID).into_bytes(),
+        );
+        let mut info = String::from("<< /Producer (Junita)");
+        if let Some(title) = &self.title {
+            let _ = write!(info, " /Title ({})", escape_string(title));
+        }
+        info.push_str(" >>");
+        let info_id = self.add(info.into_bytes());
+
+        let mut out: Vec<u8> = b"%PDF-1.4\n%\xE2\xE3\xCF\xD3\n".to_vec();
+        let mut offsets = Vec::with_capacity(self.objects.len());
+        for (index, object) in self.objects.iter().enumerate() {
+            offsets.push(out.len());
+            let _ = writeln!(out, "{} 0 obj", index + 1);
+            out.extend_from_slice(object.as_deref().unwrap_or(b"null"));
+            out.extend_from_slice(b"\nendobj\n");
+        }
+
+        let xref = out.len();
+        let _ = write!(out, "xref\n0 {}\n0000000000 65535 f \n", offsets.len() + 1);
+        for offset in &offsets {
+            let _ = writeln!(out, "{:010} 00000 n ", offset);
+        }
+        let _ = write!(
+            out,
+            "trailer\n<< /Size {} /Root {} 0 R /Info {} 0 R >>\nstartxref\n{}\n%%EOF\n",
+            offsets.len() + 1,
+            CATALOG_ID,
+            info_id,
+            xref
+        );
+        Ok(out)
+    }
+
+    fn add(&mut self, object: Vec<u8>) -> usize {
+        self.objects.push(Some(object));
+        self.objects.len()
+    }
+
+    fn set(&mut self, id: usize, object: Vec<u8>) {
+        self.objects[id - 1] = Some(object);
+    }
+
+    fn add_stream(&mut self, dict: &str, data: &[u8]) -> Result<usize> {
+        let compressed = deflate(data)?;
+        let mut object = format!(
+            "<< {} /Filter /FlateDecode /Length {} >>\nstream\n",
+            dict,
+            compressed.len()
+        )
+        .into_bytes();
+        object.extend_from_slice(&compressed);
+        object.extend_from_slice(b"\nendstream");
+        Ok(self.add(object))
+    }
+
+    fn write_page(
+        &mut self,
+        frame: &ExportFrame,
+        scene: &Scene<'_>,
+        top: f32,
+        height: f32,
+    ) -> Result<()> {
+        let s = self.scale;
+        let mut page = PageWriter::default();
+
+        // Flip to y-down logical pixels and scroll to this page's slice
+        let _ = writeln!(
+            page.content,
+            "{} 0 0 {} 0 {} cm",
+            num(s),
+            num(-s),
+            num(height * s)
+        );
+        if top != 0.0 || height < frame.size.height {
+            let _ = writeln!(page.content, "1 0 0 1 0 {} cm", num(-top));
+            let _ = writeln!(
+                page.content,
+                "0 {} {} {} re W n",
+                num(top),
+                num(frame.size.width),
+                num(height)
+            );
+        }
+        let visible = Rect::new(0.0, top, frame.size.width, height);
+
+        for item in &scene.items {
+            if !item_visible(item, visible) {
+                continue;
+            }
+            self.item(frame, scene, item, &mut page)?;
+        }
+
+        let content_id = self.add_stream("", page.content.as_bytes())?;
+        let mut resources = String::from("<<");
+        for (kind, entries) in [
+            ("ExtGState", &page.gstates),
+            ("Shading", &page.shadings),
+            ("XObject", &page.xobjects),
+        ] {
+            if entries.is_empty() {
+                continue;
+            }
+            let _ = write!(resources, " /{} <<", kind);
+            for (name, id) in entries {
+                let _ = write!(resources, " /{} {} 0 R", name, id);
+            }
+            resources.push_str(" >>");
+        }
+        resources.push_str(" >>");
+
+        let page_id = self.add(
+            format!(
+                "<< /Type /Page /Parent {} 0 R /MediaBox [0 0 {} {}] /Resources {} /Contents {} 0 R >>",
+                PAGES_ID,
+                num(frame.size.width * s),
+                num(height * s),
+                resources,
+                content_id
+            )
+            .into_bytes(),
+        );
+        self.pages.push(page_id);
+        Ok(())
+    }
+
+    fn item(
+        &mut self,
+        frame: &ExportFrame,
+        scene: &Scene<'_>,
+        item: &Item<'_>,
+        page: &mut PageWriter,
+    ) -> Result<()> {
+        let mut ops = String::new();
+        for clip in scene.clip_chain(item.clip) {
+            ops.push_str(&pdf_path_ops(&scene.clips[clip].path));
+            ops.push_str("W n\n");
+        }
+        let [a, b, c, d, e, f] = item.transform.elements;
+        let _ = writeln!(
+            ops,
+            "{} {} {} {} {} {} cm",
+            num(a),
+            num(b),
+            num(c),
+            num(d),
+            num(e),
+            num(f)
+        );
+
+        let drawn = match &item.primitive {
+            Primitive::Fill { path, brush } => match Paint::resolve(brush, path.bounds()) {
+                Some(paint) => self.fill(path, &paint, item, page, &mut ops)?,
+                None => false,
+            },
+            Primitive::Stroke {
+                path,
+                stroke,
+                brush,
+            } => match Paint::resolve(brush, path.bounds()) {
+                Some(paint) => {
+                    let color = paint.fallback_color();
+                    let gs = self.gstate(page, 1.0, item.opacity * color.a, item.blend_mode);
+                    ops.push_str(&gs);
+                    ops.push_str(&stroke_ops(stroke));
+                    let _ = writeln!(ops, "{} RG", rgb(color));
+                    ops.push_str(&pdf_path_ops(path));
+                    ops.push_str("S\n");
+                    true
+                }
+                None => false,
+            },
+            Primitive::Text {
+                text,
+                origin,
+                style,
+            } => match self.outliner.outline(text, *origin, style) {
+                Some(path) if style.color.a > 0.0 => {
+                    self.fill(&path, &Paint::Solid(style.color), item, page, &mut ops)?
+                }
+                _ => false,
+            },
+            Primitive::Image {
+                image,
+                rect,
+                options,
+            } => self.image(frame, *image, *rect, options, item, page, &mut ops)?,
+            Primitive::Shadow {
+                shape,
+                shadow,
+                inset,
+            } => self.shadow(shape, shadow, *inset, item, page, &mut ops),
+        };
+
+        if drawn {
+            page.content.push_str("q\n");
+            page.content.push_str(&ops);
+            page.content.push_str("Q\n");
+        }
+        Ok(())
+    }
+
+    fn fill(
+        &mut self,
+        path: &Path,
+        paint: &Paint,
+        item: &Item<'_>,
+        page: &mut PageWriter,
+        ops: &mut String,
+    ) -> Result<bool> {
+        match paint {
+            Paint::Solid(color) => {
+                let gs = self.gstate(page, item.opacity * color.a, 1.0, item.blend_mode);
+                ops.push_str(&gs);
+                let _ = writeln!(ops, "{} rg", rgb(*color));
+                ops.push_str(&pdf_path_ops(path));
+                ops.push_str("f\n");
+            }
+            Paint::Linear {
+                start, end, stops, ..
+            } => {
+                let coords = format!(
+                    "{} {} {} {}",
+                    num(start.x),
+                    num(start.y),
+                    num(end.x),
+                    num(end.y)
+                );
+                self.shading(2, &coords, stops, item, page, path, ops);
+            }
+            Paint::Radial {
+                center,
+                radius,
+                focal,
+                stops,
+                ..
+            } => {
+                let coords = format!(
+                    "{} {} 0 {} {} {}",
+                    num(focal.x),
+                    num(focal.y),
+                    num(center.x),
+                    num(center.y),
+                    num(*radius)
+                );
+                self.shading(3, &coords, stops, item, page, path, ops);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Fill `path` with an axial (2) or radial (3) shading
+    ///
+    /// Stop alpha is not representable in a shading, so gradients take the
+    /// average stop alpha as a constant opacity.
+    #[allow(clippy::too_many_arguments)]
+    fn shading(
+        &mut self,
+        shading_type: u8,
+        coords: &str,
+        stops: &[GradientStop],
+        item: &Item<'_>,
+        page: &mut PageWriter,
+        path: &Path,
+        ops: &mut String,
+    ) {
+        let function = self.stops_function(stops);
+        let id = self.add(
+            format!(
+                "<< /ShadingType {} /ColorSpace /DeviceRGB /Coords [{}] /Function {} 0 R /Extend [true true] >>",
+                shading_type, coords, function
+            )
+            .into_bytes(),
+        );
+        let name = format!("Sh{}", id);
+        page.shadings.push((name.clone(), id));
+
+        let alpha = if stops.is_empty() {
+            1.0
+        } else {
+            stops.iter().map(|s| s.color.a).sum::<f32>() / stops.len() as f32
+        };
+        let gs = self.gstate(page, item.opacity * alpha, 1.0, item.blend_mode);
+        ops.push_str(&gs);
+        ops.push_str(&pdf_path_ops(path));
+        let _ = writeln!(ops, "W n /{} sh", name);
+    }
+
+    /// Colour function over [0, 1] for a list of gradient stops
+    fn stops_function(&mut self, stops: &[GradientStop]) -> usize {
+        let mut stops: Vec<GradientStop> = stops.to_vec();
+        stops.sort_by(|a, b| a.offset.total_cmp(&b.offset));
+        match (stops.first().copied(), stops.last().copied()) {
+            (Some(first), Some(last)) => {
+                if first.offset > 0.0 {
+                    stops.insert(0, GradientStop::new(0.0, first.color));
+                }
+                if last.offset < 1.0 {
+                    stops.push(GradientStop::new(1.0, last.color));
+                }
+            }
+            _ => {
+                stops = vec![
+                    GradientStop::new(0.0, Color::BLACK),
+                    GradientStop::new(1.0, Color::BLACK),
+                ];
+            }
+        }
+        if stops.len() == 1 {
+            stops.push(GradientStop::new(1.0, stops[0].color));
+        }
+
+        let segments: Vec<usize> = stops
+            .windows(2)
+            .map(|pair| {
+                self.add(
+                    format!(
+                        "<< /FunctionType 2 /Domain [0 1] /C0 [{}] /C1 [{}] /N 1 >>",
+                        rgb(pair[0].color),
+                        rgb(pair[1].color)
+                    )
+                    .into_bytes(),
+                )
+            })
+            .collect();
+        if segments.len() == 1 {
+            return segments[0];
+        }
+
+        let functions: Vec<String> = segments.iter().map(|id| format!("{} 0 R", id)).collect();
+        let bounds: Vec<String> = stops[1..stops.len() - 1]
+            .iter()
+            .map(|s| num(s.offset))
+            .collect();
+        let encode: Vec<&str> = segments.iter().map(|_| "0 1").collect();
+        self.add(
+            format!(
+                "<< /FunctionType 3 /Domain [0 1] /Functions [{}] /Bounds [{}] /Encode [{}] >>",
+                functions.join(" "),
+                bounds.join(" "),
+                encode.join(" ")
+            )
+            .into_bytes(),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn image(
+        &mut self,
+        frame: &ExportFrame,
+        id: ImageId,
+        rect: Rect,
+        options: &ImageOptions,
+        item: &Item<'_>,
+        page: &mut PageWriter,
+        ops: &mut String,
+    ) -> Result<bool> {
+        let Some(image) = frame.images.get(&id) else {
+            tracing::debug!("junita_export: no pixels registered for {:?}", id);
+            return Ok(false);
+        };
+        let tint = options.tint.unwrap_or(Color::WHITE);
+        let key = (
+            id,
+            [
+                channel(tint.r),
+                channel(tint.g),
+                channel(tint.b),
+                channel(tint.a),
+            ],
+        );
+        let xobject = match self.images.get(&key) {
+            Some(xobject) => *xobject,
+            None => {
+                let pixels = image_pixels(image, options.tint);
+                let mut rgb = Vec::with_capacity(pixels.len() / 4 * 3);
+                let mut alpha = Vec::with_capacity(pixels.len() / 4);
+                for px in pixels.chunks_exact(4) {
+                    rgb.extend_from_slice(&px[..3]);
+                    alpha.push(px[3]);
+                }
+                let dims = format!(
+                    "/Type /XObject /Subtype /Image /Width {} /Height {} /BitsPerComponent 8",
+                    image.width, image.height
+                );
+                let mask = self.add_stream(&format!("{} /ColorSpace /DeviceGray", dims), &alpha)?;
+                let xobject = self.add_stream(
+                    &format!("{} /ColorSpace /DeviceRGB /SMask {} 0 R", dims, mask),
+                    &rgb,
+                )?;
+                self.images.insert(key, xobject);
+                xobject
+            }
+        };
+        let name = format!("Im{}", xobject);
+        if !page.xobjects.iter().any(|(n, _)| *n == name) {
+            page.xobjects.push((name.clone(), xobject));
+        }
+
+        // Map the source rect onto `rect`, drawing the whole image scaled
+        // to match and clipping to `rect`
+        let (iw, ih) = (image.width as f32, image.height as f32);
+        let src = options
+            .source_rect
+            .unwrap_or_else(|| Rect::new(0.0, 0.0, iw, ih));
+        if src.width() <= 0.0 || src.height() <= 0.0 {
+            return Ok(false);
+        }
+        let (sx, sy) = (rect.width() / src.width(), rect.height() / src.height());
+        let (x, y) = (rect.x() - src.x() * sx, rect.y() - src.y() * sy);
+        let (w, h) = (iw * sx, ih * sy);
+
+        let gs = self.gstate(page, item.opacity * options.opacity, 1.0, item.blend_mode);
+        ops.push_str(&gs);
+        if options.source_rect.is_some() {
+            let _ = writeln!(
+                ops,
+                "{} {} {} {} re W n",
+                num(rect.x()),
+                num(rect.y()),
+                num(rect.width()),
+                num(rect.height())
+            );
+        }
+        // Image space has its first row at y = 1; flip it into y-down space
+        let _ = writeln!(
+            ops,
+            "{} 0 0 {} {} {} cm /{} Do",
+            num(w),
+            num(-h),
+            num(x),
+            num(y + h),
+            name
+        );
+        Ok(true)
+    }
+
+    fn shadow(
+        &mut self,
+        shape: &ShadowShape,
+        shadow: &Shadow,
+        inset: bool,
+        item: &Item<'_>,
+        page: &mut PageWriter,
+        ops: &mut String,
+    ) -> bool {
+        if shadow.color.a <= 0.0 {
+            return false;
+        }
+        let blur = shadow.blur.max(0.0);
+        let layers = if blur > 0.0 { SHADOW_LAYERS } else { 1 };
+        // Per-layer alpha such that the fully covered core reaches the
+        // shadow colour's alpha
+        let alpha = 1.0 - (1.0 - shadow.color.a.min(0.999)).powf(1.0 / layers as f32);
+        let gs = self.gstate(page, item.opacity * alpha, 1.0, item.blend_mode);
+        ops.push_str(&gs);
+        let _ = writeln!(ops, "{} rg", rgb(shadow.color));
+
+        let spread_at = |i: usize| {
+            let t = (i as f32 + 0.5) / layers as f32;
+            blur * 1.5 * (1.0 - 2.0 * t)
+        };
+
+        if !inset {
+            for i in 0..layers {
+                let layer = Shadow {
+                    spread: shadow.spread + spread_at(i),
+                    ..*shadow
+                };
+                ops.push_str(&pdf_path_ops(&shape.cast(&layer)));
+                ops.push_str("f\n");
+            }
+            return true;
+        }
+
+        let bounds = shape.bounds();
+        let reach =
+            blur * 3.0 + shadow.spread.abs() + shadow.offset_x.abs().max(shadow.offset_y.abs());
+        let outer = Path::rect(Rect::new(
+            bounds.x() - reach,
+            bounds.y() - reach,
+            bounds.width() + reach * 2.0,
+            bounds.height() + reach * 2.0,
+        ));
+        ops.push_str(&pdf_path_ops(&shape.outline()));
+        ops.push_str("W n\n");
+        for i in 0..layers {
+            let hole = Shadow {
+                spread: -(shadow.spread + spread_at(i)),
+                ..*shadow
+            };
+            ops.push_str(&pdf_path_ops(&outer));
+            ops.push_str(&pdf_path_ops(&shape.cast(&hole)));
+            ops.push_str("f*\n");
+        }
+        true
+    }
+
+    /// `gs` operator for the given fill/stroke alpha and blend mode
+    ///
+    /// Returns an empty string for the default state.
+    fn gstate(
+        &mut self,
+        page: &mut PageWriter,
+        fill_alpha: f32,
+        stroke_alpha: f32,
+        blend_mode: BlendMode,
+    ) -> String {
+        let fill_alpha = fill_alpha.clamp(0.0, 1.0);
+        let stroke_alpha = stroke_alpha.clamp(0.0, 1.0);
+        if fill_alpha >= 1.0 && stroke_alpha >= 1.0 && blend_mode == BlendMode::Normal {
+            return String::new();
+        }
+        let key = (
+            fill_alpha.to_bits(),
+            stroke_alpha.to_bits(),
+            blend_mode_name(blend_mode),
+        );
+        let id = match self.gstates.get(&key) {
+            Some(id) => *id,
+            None => {
+                let id = self.add(
+                    format!(
+                        "<< /Type /ExtGState /ca {} /CA {} /BM /{} >>",
+                        num(fill_alpha),
+                        num(stroke_alpha),
+                        blend_mode_name(blend_mode)
+                    )
+                    .into_bytes(),
+                );
+                self.gstates.insert(key, id);
+                id
+            }
+        };
+        let name = format!("GS{}", id);
+        if !page.gstates.iter().any(|(n, _)| *n == name) {
+            page.gstates.push((name.clone(), id));
+        }
+        format!("/{} gs\n", name)
+    }
+}
+
+/// Export a frame as a single-page PDF
+pub fn export_pdf(frame: &ExportFrame) -> Result<Vec<u8>> {
+    let mut pdf = PdfDocument::new();
+    pdf.add_page(frame)?;
+    pdf.finish()
+}
+
+/// Content and resources for the page being written
+#[derive(Default)]
+struct PageWriter {
+    content: String,
+    gstates: Vec<(String, usize)>,
+    shadings: Vec<(String, usize)>,
+    xobjects: Vec<(String, usize)>,
+}
+
+fn check_size(frame: &ExportFrame) -> Result<()> {
+    if frame.size.width <= 0.0 || frame.size.height <= 0.0 {
+        return Err(ExportError::Empty(format!(
+            "frame size {}x{}",
+            frame.size.width, frame.size.height
+        )));
+    }
+    Ok(())
+}
+
+/// Conservative visibility test used to skip items on other pages
+fn item_visible(item: &Item<'_>, visible: Rect) -> bool {
+    let local = match &item.primitive {
+        Primitive::Fill { path, .. } => path.bounds(),
+        Primitive::Stroke { path, stroke, .. } => {
+            let b = path.bounds();
+            let w = stroke.width;
+            Rect::new(
+                b.x() - w,
+                b.y() - w,
+                b.width() + w * 2.0,
+                b.height() + w * 2.0,
+            )
+        }
+        Primitive::Image { rect, .. } => *rect,
+        Primitive::Shadow { shape, shadow, .. } => {
+            let b = shape.bounds();
+            let reach = shadow.blur.max(0.0) * 3.0
+                + shadow.spread.abs()
+                + shadow.offset_x.abs().max(shadow.offset_y.abs());
+            Rect::new(
+                b.x() - reach,
+                b.y() - reach,
+                b.width() + reach * 2.0,
+                b.height() + reach * 2.0,
+            )
+        }
+        // Text extents are only known after shaping
+        Primitive::Text { .. } => return true,
+    };
+
+    let corners = [
+        (local.x(), local.y()),
+        (local.x() + local.width(), local.y()),
+        (local.x(), local.y() + local.height()),
+        (local.x() + local.width(), local.y() + local.height()),
+    ];
+    let (mut min_y, mut max_y) = (f32::MAX, f32::MIN);
+    for (x, y) in corners {
+        let p = item
+            .transform
+            .transform_point(junita_core::Point::new(x, y));
+        min_y = min_y.min(p.y);
+        max_y = max_y.max(p.y);
+    }
+    max_y >= visible.y() && min_y <= visible.y() + visible.height()
+}
+
+fn stroke_ops(stroke: &Stroke) -> String {
+    let cap = match stroke.cap {
+        LineCap::Butt => 0,
+        LineCap::Round => 1,
+        LineCap::Square => 2,
+    };
+    let join = match stroke.join {
+        LineJoin::Miter => 0,
+        LineJoin::Round => 1,
+        LineJoin::Bevel => 2,
+    };
+    let dash: Vec<String> = stroke.dash.iter().map(|d| num(*d)).collect();
+    format!(
+        "{} w {} J {} j {} M [{}] {} d\n",
+        num(stroke.width),
+        cap,
+        join,
+        num(stroke.miter_limit),
+        dash.join(" "),
+        num(stroke.dash_offset)
+    )
+}
+
+fn rgb(color: Color) -> String {
+    format!(
+        "{} {} {}",
+        num(color.r.clamp(0.0, 1.0)),
+        num(color.g.clamp(0.0, 1.0)),
+        num(color.b.clamp(0.0, 1.0))
+    )
+}
+
+fn blend_mode_name(mode: BlendMode) -> &'static str {
+    match mode {
+        BlendMode::Normal => "Normal",
+        BlendMode::Multiply => "Multiply",
+        BlendMode::Screen => "Screen",
+        BlendMode::Overlay => "Overlay",
+        BlendMode::Darken => "Darken",
+        BlendMode::Lighten => "Lighten",
+        BlendMode::ColorDodge => "ColorDodge",
+        BlendMode::ColorBurn => "ColorBurn",
+        BlendMode::HardLight => "HardLight",
+        BlendMode::SoftLight => "SoftLight",
+        BlendMode::Difference => "Difference",
+        BlendMode::Exclusion => "Exclusion",
+    }
+}
+
+fn escape_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            c if c.is_ascii() && !c.is_ascii_control() => out.push(c),
+            _ => out.push('?'),
+        }
+    }
+    out
+}
+
+fn deflate(data: &[u8]) -> Result<Vec<u8>> {
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(data)?;
+    Ok(encoder.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use junita_core::{Brush, CornerRadius, DrawContext, Gradient, Point, RecordingContext, Size};
+
+    fn tall_frame() -> ExportFrame {
+        let mut ctx = RecordingContext::new(Size::new(200.0, 500.0));
+        ctx.fill_rect(
+            Rect::new(10.0, 10.0, 100.0, 50.0),
+            CornerRadius::uniform(6.0),
+            Brush::Gradient(Gradient::linear(
+                Point::new(10.0, 0.0),
+                Point::new(110.0, 0.0),
+                Color::RED,
+                Color::BLUE,
+            )),
+        );
+        ctx.push_opacity(0.5);
+        ctx.fill_circle(Point::new(100.0, 400.0), 30.0, Color::GREEN.into());
+        ctx.pop_opacity();
+        ExportFrame::from_recording(&ctx)
+    }
+
+    #[test]
+    fn test_single_page_structure() {
+        let bytes = export_pdf(&tall_frame()).unwrap();
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.starts_with("%PDF-1.4"));
+        assert!(text.trim_end().ends_with("%%EOF"));
+        assert!(text.contains("/Count 1"));
+        assert!(text.contains("/MediaBox [0 0 150 375]"));
+        assert!(text.contains("/ShadingType 2"));
+        assert!(text.contains("/ca 0.5"));
+    }
+
+    #[test]
+    fn test_pagination() {
+        let mut pdf = PdfDocument::new().with_title("Tall (frame)");
+        let pages = pdf.add_paginated(&tall_frame(), 200.0).unwrap();
+        assert_eq!(pages, 3);
+        assert_eq!(pdf.page_count(), 3);
+
+        let bytes = pdf.finish().unwrap();
+        let text = String::from_utf8_lossy(&bytes);
+        assert!(text.contains("/Count 3"));
+        assert!(text.contains(r"/Title (Tall \(frame\))"));
+    }
+
+    #[test]
+    fn test_empty_document_is_an_error() {
+        assert!(PdfDocument::new().finish().is_err());
+        let empty = ExportFrame::new(Size::new(0.0, 10.0), Vec::new());
+        assert!(export_pdf(&empty).is_err());
+    }
+}
@@ -0,0 +1,471 @@
+//! Flattening of draw command streams
+//!
+//! Vector formats handle nesting differently (SVG groups vs PDF graphics
+//! state), and recorded streams may pop stacks in a different order than
+//! they were pushed. Both exporters therefore consume a flat list of
+//! [`Item`]s, each carrying its fully resolved transform, clip chain,
+//! opacity and blend mode.
+
+use junita_core::{
+    Affine2D, BlendMode, Brush, ClipShape, CornerRadius, DrawCommand, ImageId, ImageOptions, Path,
+    PathCommand, Point, Rect, Shadow, Stroke, TextStyle, Transform, Vec2,
+};
+
+/// Shape a shadow is cast from
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum ShadowShape {
+    RoundedRect {
+        rect: Rect,
+        corner_radius: CornerRadius,
+    },
+    Circle {
+        center: Point,
+        radius: f32,
+    },
+}
+
+impl ShadowShape {
+    /// The shape grown by `spread` and moved by the shadow offset
+    pub(crate) fn cast(&self, shadow: &Shadow) -> Path {
+        let (dx, dy, s) = (shadow.offset_x, shadow.offset_y, shadow.spread);
+        match *self {
+            ShadowShape::RoundedRect {
+                rect,
+                corner_radius,
+            } => {
+                let rect = Rect::new(
+                    rect.x() + dx - s,
+                    rect.y() + dy - s,
+                    (rect.width() + s * 2.0).max(0.0),
+                    (rect.height() + s * 2.0).max(0.0),
+                );
+                Path::rounded_rect(rect, grow_radius(corner_radius, s))
+            }
+            ShadowShape::Circle { center, radius } => Path::circle(
+                Point::new(center.x + dx, center.y + dy),
+                (radius + s).max(0.0),
+            ),
+        }
+    }
+
+    /// Outline of the shape itself
+    pub(crate) fn outline(&self) -> Path {
+        match *self {
+            ShadowShape::RoundedRect {
+                rect,
+                corner_radius,
+            } => Path::rounded_rect(rect, corner_radius),
+            ShadowShape::Circle { center, radius } => Path::circle(center, radius),
+        }
+    }
+
+    /// Shape bounds
+    pub(crate) fn bounds(&self) -> Rect {
+        match *self {
+            ShadowShape::RoundedRect { rect, .. } => rect,
+            ShadowShape::Circle { center, radius } => Rect::new(
+                center.x - radius,
+                center.y - radius,
+                radius * 2.0,
+                radius * 2.0,
+            ),
+        }
+    }
+}
+
+fn grow_radius(r: CornerRadius, amount: f32) -> CornerRadius {
+    CornerRadius {
+        top_left: (r.top_left + amount).max(0.0),
+        top_right: (r.top_right + amount).max(0.0),
+        bottom_right: (r.bottom_right + amount).max(0.0),
+        bottom_left: (r.bottom_left + amount).max(0.0),
+    }
+}
+
+/// A drawable primitive in local coordinates
+#[derive(Clone, Debug)]
+pub(crate) enum Primitive<'a> {
+    Fill {
+        path: Path,
+        brush: &'a Brush,
+    },
+    Stroke {
+        path: Path,
+        stroke: &'a Stroke,
+        brush: &'a Brush,
+    },
+    Text {
+        text: &'a str,
+        origin: Point,
+        style: &'a TextStyle,
+    },
+    Image {
+        image: ImageId,
+        rect: Rect,
+        options: &'a ImageOptions,
+    },
+    Shadow {
+        shape: ShadowShape,
+        shadow: Shadow,
+        inset: bool,
+    },
+}
+
+/// A pushed clip, with its shape captured in page coordinates
+#[derive(Clone, Debug)]
+pub(crate) struct Clip {
+    /// Index of the enclosing clip, if any
+    pub parent: Option<usize>,
+    /// Clip outline already transformed to page space
+    pub path: Path,
+}
+
+/// A primitive with resolved graphics state
+#[derive(Clone, Debug)]
+pub(crate) struct Item<'a> {
+    pub primitive: Primitive<'a>,
+    /// Local-to-page transform
+    pub transform: Affine2D,
+    /// Innermost clip (index into `Scene::clips`)
+    pub clip: Option<usize>,
+    pub opacity: f32,
+    pub blend_mode: BlendMode,
+}
+
+/// A flattened command stream
+#[derive(Debug, Default)]
+pub(crate) struct Scene<'a> {
+    pub items: Vec<Item<'a>>,
+    pub clips: Vec<Clip>,
+}
+
+impl<'a> Scene<'a> {
+    /// Resolve a command stream into flat items
+    pub(crate) fn build(commands: &'a [DrawCommand]) -> Self {
+        let mut scene = Scene::default();
+        let mut transforms = vec![Affine2D::IDENTITY];
+        let mut clips: Vec<Option<usize>> = vec![None];
+        let mut opacities = vec![1.0f32];
+        let mut blends = vec![BlendMode::Normal];
+        // What each PushLayer pushed, so PopLayer can undo it
+        let mut layers: Vec<(bool, bool)> = Vec::new();
+
+        for command in commands {
+            let transform = *transforms.last().unwrap_or(&Affine2D::IDENTITY);
+            let primitive = match command {
+                DrawCommand::PushTransform(t) => {
+                    let next = match t {
+                        Transform::Affine2D(affine) => transform.then(affine),
+                        // 3D transforms have no vector equivalent
+                        Transform::Mat4(_) => transform,
+                    };
+                    transforms.push(next);
+                    continue;
+                }
+                DrawCommand::PopTransform => {
+                    if transforms.len() > 1 {
+                        transforms.pop();
+                    }
+                    continue;
+                }
+                DrawCommand::PushClip(shape) => {
+                    let parent = clips.last().copied().flatten();
+                    scene.clips.push(Clip {
+                        parent,
+                        path: transform_path(&clip_path(shape), &transform),
+                    });
+                    clips.push(Some(scene.clips.len() - 1));
+                    continue;
+                }
+                DrawCommand::PopClip => {
+                    if clips.len() > 1 {
+                        clips.pop();
+                    }
+                    continue;
+                }
+                DrawCommand::PushOpacity(o) => {
+                    let current = *opacities.last().unwrap_or(&1.0);
+                    opacities.push(current * o);
+                    continue;
+                }
+                DrawCommand::PopOpacity => {
+                    if opacities.len() > 1 {
+                        opacities.pop();
+                    }
+                    continue;
+                }
+                DrawCommand::PushBlendMode(mode) => {
+                    blends.push(*mode);
+                    continue;
+                }
+                DrawCommand::PopBlendMode => {
+                    if blends.len() > 1 {
+                        blends.pop();
+                    }
+                    continue;
+                }
+                DrawCommand::PushLayer(config) => {
+                    // Same interpretation as the GPU renderer: layers only
+                    // carry opacity and blend mode
+                    let pushes_blend = config.blend_mode != BlendMode::Normal;
+                    let pushes_opacity = config.opacity < 1.0;
+                    if pushes_blend {
+                        blends.push(config.blend_mode);
+                    }
+                    if pushes_opacity {
+                        let current = *opacities.last().unwrap_or(&1.0);
+                        opacities.push(current * config.opacity);
+                    }
+                    layers.push((pushes_blend, pushes_opacity));
+                    continue;
+                }
+                DrawCommand::PopLayer => {
+                    if let Some((pushed_blend, pushed_opacity)) = layers.pop() {
+                        if pushed_opacity && opacities.len() > 1 {
+                            opacities.pop();
+                        }
+                        if pushed_blend && blends.len() > 1 {
+                            blends.pop();
+                        }
+                    }
+                    continue;
+                }
+                DrawCommand::SampleLayer { .. } => {
+                    tracing::debug!("junita_export: layer sampling has no vector equivalent");
+                    continue;
+                }
+                DrawCommand::SetCamera(_)
+                | DrawCommand::DrawMesh { .. }
+                | DrawCommand::DrawMeshInstanced { .. }
+                | DrawCommand::AddLight(_)
+                | DrawCommand::SetEnvironment(_) => continue,
+
+                DrawCommand::FillPath { path, brush } => Primitive::Fill {
+                    path: path.clone(),
+                    brush,
+                },
+                DrawCommand::StrokePath {
+                    path,
+                    stroke,
+                    brush,
+                } => Primitive::Stroke {
+                    path: path.clone(),
+                    stroke,
+                    brush,
+                },
+                DrawCommand::FillRect {
+                    rect,
+                    corner_radius,
+                    brush,
+                } => Primitive::Fill {
+                    path: Path::rounded_rect(*rect, *corner_radius),
+                    brush,
+                },
+                DrawCommand::StrokeRect {
+                    rect,
+                    corner_radius,
+                    stroke,
+                    brush,
+                } => Primitive::Stroke {
+                    path: Path::rounded_rect(*rect, *corner_radius),
+                    stroke,
+                    brush,
+                },
+                DrawCommand::FillCircle {
+                    center,
+                    radius,
+                    brush,
+                } => Primitive::Fill {
+                    path: Path::circle(*center, *radius),
+                    brush,
+                },
+                DrawCommand::StrokeCircle {
+                    center,
+                    radius,
+                    stroke,
+                    brush,
+                } => Primitive::Stroke {
+                    path: Path::circle(*center, *radius),
+                    stroke,
+                    brush,
+                },
+                DrawCommand::DrawText {
+                    text,
+                    origin,
+                    style,
+                } => Primitive::Text {
+                    text,
+                    origin: *origin,
+                    style,
+                },
+                DrawCommand::DrawImage {
+                    image,
+                    rect,
+                    options,
+                } => Primitive::Image {
+                    image: *image,
+                    rect: *rect,
+                    options,
+                },
+                DrawCommand::DrawShadow {
+                    rect,
+                    corner_radius,
+                    shadow,
+                } => Primitive::Shadow {
+                    shape: ShadowShape::RoundedRect {
+                        rect: *rect,
+                        corner_radius: *corner_radius,
+                    },
+                    shadow: *shadow,
+                    inset: false,
+                },
+                DrawCommand::DrawInnerShadow {
+                    rect,
+                    corner_radius,
+                    shadow,
+                } => Primitive::Shadow {
+                    shape: ShadowShape::RoundedRect {
+                        rect: *rect,
+                        corner_radius: *corner_radius,
+                    },
+                    shadow: *shadow,
+                    inset: true,
+                },
+                DrawCommand::DrawCircleShadow {
+                    center,
+                    radius,
+                    shadow,
+                } => Primitive::Shadow {
+                    shape: ShadowShape::Circle {
+                        center: *center,
+                        radius: *radius,
+                    },
+                    shadow: *shadow,
+                    inset: false,
+                },
+                DrawCommand::DrawCircleInnerShadow {
+                    center,
+                    radius,
+                    shadow,
+                } => Primitive::Shadow {
+                    shape: ShadowShape::Circle {
+                        center: *center,
+                        radius: *radius,
+                    },
+                    shadow: *shadow,
+                    inset: true,
+                },
+            };
+
+            scene.items.push(Item {
+                primitive,
+                transform,
+                clip: clips.last().copied().flatten(),
+                opacity: *opacities.last().unwrap_or(&1.0),
+                blend_mode: *blends.last().unwrap_or(&BlendMode::Normal),
+            });
+        }
+
+        scene
+    }
+
+    /// Clip chain for an item, innermost first
+    pub(crate) fn clip_chain(&self, clip: Option<usize>) -> Vec<usize> {
+        let mut chain = Vec::new();
+        let mut next = clip;
+        while let Some(idx) = next {
+            chain.push(idx);
+            next = self.clips.get(idx).and_then(|c| c.parent);
+        }
+        chain
+    }
+}
+
+/// Outline of a clip shape as a path
+pub(crate) fn clip_path(shape: &ClipShape) -> Path {
+    match shape {
+        ClipShape::Rect(rect) => Path::rect(*rect),
+        ClipShape::RoundedRect {
+            rect,
+            corner_radius,
+        } => Path::rounded_rect(*rect, *corner_radius),
+        ClipShape::Circle { center, radius } => Path::circle(*center, *radius),
+        ClipShape::Ellipse { center, radii } => ellipse_path(*center, *radii),
+        ClipShape::Path(path) => path.clone(),
+    }
+}
+
+fn ellipse_path(center: Point, radii: Vec2) -> Path {
+    let k = 0.552_284_8;
+    let (cx, cy, rx, ry) = (center.x, center.y, radii.x, radii.y);
+    Path::new()
+        .move_to(cx + rx, cy)
+        .cubic_to(cx + rx, cy + ry * k, cx + rx * k, cy + ry, cx, cy + ry)
+        .cubic_to(cx - rx * k, cy + ry, cx - rx, cy + ry * k, cx - rx, cy)
+        .cubic_to(cx - rx, cy - ry * k, cx - rx * k, cy - ry, cx, cy - ry)
+        .cubic_to(cx + rx * k, cy - ry, cx + rx, cy - ry * k, cx + rx, cy)
+        .close()
+}
+
+/// Apply an affine transform to every point of a path
+///
+/// Arcs are converted to cubics first since a skewed arc is not an arc.
+pub(crate) fn transform_path(path: &Path, transform: &Affine2D) -> Path {
+    if transform.elements == Affine2D::IDENTITY.elements {
+        return path.clone();
+    }
+    let t = |p: &Point| transform.transform_point(*p);
+    let commands = crate::path::without_arcs(path)
+        .into_iter()
+        .map(|cmd| match cmd {
+            PathCommand::MoveTo(p) => PathCommand::MoveTo(t(&p)),
+            PathCommand::LineTo(p) => PathCommand::LineTo(t(&p)),
+            PathCommand::QuadTo { control, end } => PathCommand::QuadTo {
+                control: t(&control),
+                end: t(&end),
+            },
+            PathCommand::CubicTo {
+                control1,
+                control2,
+                end,
+            } => PathCommand::CubicTo {
+                control1: t(&control1),
+                control2: t(&control2),
+                end: t(&end),
+            },
+            other => other,
+        })
+        .collect();
+    Path::from_commands(commands)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use junita_core::{Color, DrawContext, RecordingContext, Size};
+
+    #[test]
+    fn test_state_is_resolved_per_item() {
+        let mut ctx = RecordingContext::new(Size::new(100.0, 100.0));
+        ctx.push_transform(Transform::translate(10.0, 0.0));
+        ctx.push_opacity(0.5);
+        ctx.push_clip(ClipShape::rect(Rect::new(0.0, 0.0, 20.0, 20.0)));
+        ctx.fill_rect(Rect::new(0.0, 0.0, 5.0, 5.0), 0.0.into(), Color::RED.into());
+        // Pop out of order
+        ctx.pop_transform();
+        ctx.pop_clip();
+        ctx.fill_circle(Point::new(0.0, 0.0), 4.0, Color::BLUE.into());
+        ctx.pop_opacity();
+
+        let scene = Scene::build(ctx.commands());
+        assert_eq!(scene.items.len(), 2);
+        assert_eq!(scene.items[0].transform.elements[4], 10.0);
+        assert_eq!(scene.items[0].clip, Some(0));
+        assert_eq!(scene.items[1].transform.elements[4], 0.0);
+        assert_eq!(scene.items[1].clip, None);
+        assert_eq!(scene.items[1].opacity, 0.5);
+
+        // Clip outline is captured in page space
+        assert_eq!(scene.clips[0].path.bounds().x(), 10.0);
+    }
+}
@@ -0,0 +1,733 @@
+//! SVG export
+//!
+//! Every item becomes one SVG element carrying its own `transform`. Runs of
+//! items that share a clip, opacity and blend mode are wrapped in a single
+//! `<g>`, and clips, gradients and shadow blurs are emitted into `<defs>`.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+use std::sync::Arc;
+
+use base64::Engine;
+use junita_core::{
+    Affine2D, BlendMode, Color, FontWeight, GradientSpread, GradientStop, ImageId, ImageOptions,
+    LineCap, LineJoin, Path, Point, Rect, Shadow, Stroke, TextAlign, TextBaseline, TextStyle,
+};
+use junita_text::FontFace;
+
+use crate::error::{ExportError, Result};
+use crate::frame::{ExportFrame, ExportImage};
+use crate::paint::{channel, image_pixels, Paint};
+use crate::path::{num, svg_path_data};
+use crate::scene::{Item, Primitive, Scene, ShadowShape};
+use crate::text::TextOutliner;
+
+/// How text is written to SVG
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextMode {
+    /// Convert glyphs to paths (renders identically everywhere)
+    #[default]
+    Outlines,
+    /// Emit `<text>` elements (selectable, but depends on installed fonts)
+    Text,
+}
+
+/// Options for SVG export
+#[derive(Clone, Debug, Default)]
+pub struct SvgOptions {
+    /// How text is written
+    pub text_mode: TextMode,
+    /// Background fill behind the frame (transparent if `None`)
+    pub background: Option<Color>,
+    /// Font used for all text outlines instead of system fonts
+    pub font: Option<Arc<FontFace>>,
+}
+
+impl SvgOptions {
+    /// Create default options (outlined text, transparent background)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how text is written
+    pub fn text_mode(mut self, mode: TextMode) -> Self {
+        self.text_mode = mode;
+        self
+    }
+
+    /// Fill the frame with a background colour
+    pub fn background(mut self, color: Color) -> Self {
+        self.background = Some(color);
+        self
+    }
+
+    /// Outline all text with a specific font
+    pub fn font(mut self, font: Arc<FontFace>) -> Self {
+        self.font = Some(font);
+        self
+    }
+}
+
+/// Export a frame as an SVG document
+pub fn export_svg(frame: &ExportFrame, options: &SvgOptions) -> Result<String> {
+    if frame.size.width <= 0.0 || frame.size.height <= 0.0 {
+        return Err(ExportError::Empty(format!(
+            "frame size {}x{}",
+            frame.size.width, frame.size.height
+        )));
+    }
+
+    let scene = Scene::build(&frame.commands);
+    let mut writer = SvgWriter {
+        frame,
+        options,
+        scene: &scene,
+        outliner: TextOutliner::new(options.font.clone()),
+        defs: String::new(),
+        body: String::new(),
+        next_id: 0,
+        emitted_clips: HashSet::new(),
+        image_uris: HashMap::new(),
+    };
+
+    if let Some(bg) = options.background {
+        let _ = writeln!(
+            writer.body,
+            r#"<rect width="{}" height="{}"{}/>"#,
+            num(frame.size.width),
+            num(frame.size.height),
+            fill_attrs(bg)
+        );
+    }
+
+    let mut group: Option<GroupKey> = None;
+    for item in &scene.items {
+        let key = GroupKey::of(item);
+        if group != Some(key) {
+            if group.is_some_and(|g| !g.is_plain()) {
+                writer.body.push_str("</g>\n");
+            }
+            if !key.is_plain() {
+                writer.open_group(key);
+            }
+            group = Some(key);
+        }
+        writer.item(item)?;
+    }
+    if group.is_some_and(|g| !g.is_plain()) {
+        writer.body.push_str("</g>\n");
+    }
+
+    let mut svg = String::new();
+    let (w, h) = (num(frame.size.width), num(frame.size.height));
+    let _ = writeln!(svg, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        svg,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#
+    );
+    if !writer.defs.is_empty() {
+        svg.push_str("<defs>\n");
+        svg.push_str(&writer.defs);
+        svg.push_str("</defs>\n");
+    }
+    svg.push_str(&writer.body);
+    svg.push_str("</svg>\n");
+    Ok(svg)
+}
+
+/// Shared state of a run of consecutive items
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct GroupKey {
+    clip: Option<usize>,
+    opacity: f32,
+    blend_mode: BlendMode,
+}
+
+impl GroupKey {
+    fn of(item: &Item<'_>) -> Self {
+        Self {
+            clip: item.clip,
+            opacity: item.opacity,
+            blend_mode: item.blend_mode,
+        }
+    }
+
+    fn is_plain(&self) -> bool {
+        self.clip.is_none() && self.opacity >= 1.0 && self.blend_mode == BlendMode::Normal
+    }
+}
+
+struct SvgWriter<'a> {
+    frame: &'a ExportFrame,
+    options: &'a SvgOptions,
+    scene: &'a Scene<'a>,
+    outliner: TextOutliner,
+    defs: String,
+    body: String,
+    next_id: usize,
+    emitted_clips: HashSet<usize>,
+    image_uris: HashMap<(ImageId, [u8; 4]), Arc<str>>,
+}
+
+impl SvgWriter<'_> {
+    fn id(&mut self, prefix: &str) -> String {
+        self.next_id += 1;
+        format!("{}{}", prefix, self.next_id)
+    }
+
+    fn open_group(&mut self, key: GroupKey) {
+        let mut attrs = String::new();
+        if let Some(clip) = key.clip {
+            self.clip_def(clip);
+            let _ = write!(attrs, r#" clip-path="url(#clip{})""#, clip);
+        }
+        if key.opacity < 1.0 {
+            let _ = write!(attrs, r#" opacity="{}""#, num(key.opacity));
+        }
+        if let Some(mode) = blend_mode_css(key.blend_mode) {
+            let _ = write!(attrs, r#" style="mix-blend-mode:{}""#, mode);
+        }
+        let _ = writeln!(self.body, "<g{}>", attrs);
+    }
+
+    /// Emit a scene clip (and its parents) into `<defs>`
+    fn clip_def(&mut self, index: usize) {
+        if !self.emitted_clips.insert(index) {
+            return;
+        }
+        let clip = &self.scene.clips[index];
+        let mut attrs = String::new();
+        if let Some(parent) = clip.parent {
+            self.clip_def(parent);
+            let _ = write!(attrs, r#" clip-path="url(#clip{})""#, parent);
+        }
+        let _ = writeln!(
+            self.defs,
+            r#"<clipPath id="clip{}" clipPathUnits="userSpaceOnUse"{}><path d="{}"/></clipPath>"#,
+            index,
+            attrs,
+            svg_path_data(&clip.path)
+        );
+    }
+
+    fn item(&mut self, item: &Item<'_>) -> Result<()> {
+        let transform = transform_attr(&item.transform);
+        match &item.primitive {
+            Primitive::Fill { path, brush } => {
+                let Some(paint) = Paint::resolve(brush, path.bounds()) else {
+                    return Ok(());
+                };
+                let fill = self.paint_attrs(&paint, "fill");
+                let _ = writeln!(
+                    self.body,
+                    r#"<path d="{}"{}{}/>"#,
+                    svg_path_data(path),
+                    fill,
+                    transform
+                );
+            }
+            Primitive::Stroke {
+                path,
+                stroke,
+                brush,
+            } => {
+                let Some(paint) = Paint::resolve(brush, path.bounds()) else {
+                    return Ok(());
+                };
+                let paint = self.paint_attrs(&paint, "stroke");
+                let _ = writeln!(
+                    self.body,
+                    r#"<path d="{}" fill="none"{}{}{}/>"#,
+                    svg_path_data(path),
+                    paint,
+                    stroke_attrs(stroke),
+                    transform
+                );
+            }
+            Primitive::Text {
+                text,
+                origin,
+                style,
+            } => self.text(text, *origin, style, &transform),
+            Primitive::Image {
+                image,
+                rect,
+                options,
+            } => self.image(*image, *rect, options, &transform)?,
+            Primitive::Shadow {
+                shape,
+                shadow,
+                inset,
+            } => self.shadow(shape, shadow, *inset, &transform),
+        }
+        Ok(())
+    }
+
+    fn text(&mut self, text: &str, origin: Point, style: &TextStyle, transform: &str) {
+        if style.color.a <= 0.0 {
+            return;
+        }
+        if self.options.text_mode == TextMode::Outlines {
+            if let Some(path) = self.outliner.outline(text, origin, style) {
+                let _ = writeln!(
+                    self.body,
+                    r#"<path d="{}"{}{}/>"#,
+                    svg_path_data(&path),
+                    fill_attrs(style.color),
+                    transform
+                );
+                return;
+            }
+        }
+
+        let anchor = match style.align {
+            TextAlign::Left => "start",
+            TextAlign::Center => "middle",
+            TextAlign::Right => "end",
+        };
+        let baseline = match style.baseline {
+            TextBaseline::Top => "text-before-edge",
+            TextBaseline::Middle => "central",
+            TextBaseline::Alphabetic => "alphabetic",
+            TextBaseline::Bottom => "text-after-edge",
+        };
+        let _ = write!(
+            self.body,
+            r#"<text x="{}" y="{}" font-family="{}" font-size="{}" font-weight="{}" text-anchor="{}" dominant-baseline="{}"{}"#,
+            num(origin.x),
+            num(origin.y),
+            escape(&style.family),
+            num(style.size),
+            css_weight(style.weight),
+            anchor,
+            baseline,
+            fill_attrs(style.color),
+        );
+        if style.letter_spacing != 0.0 {
+            let _ = write!(
+                self.body,
+                r#" letter-spacing="{}""#,
+                num(style.letter_spacing)
+            );
+        }
+        let _ = writeln!(self.body, r#"{}>{}</text>"#, transform, escape(text));
+    }
+
+    fn image(
+        &mut self,
+        id: ImageId,
+        rect: Rect,
+        options: &ImageOptions,
+        transform: &str,
+    ) -> Result<()> {
+        let Some(image) = self.frame.images.get(&id) else {
+            tracing::debug!("junita_export: no pixels registered for {:?}", id);
+            return Ok(());
+        };
+        let tint = options.tint.unwrap_or(Color::WHITE);
+        let key = (
+            id,
+            [
+                channel(tint.r),
+                channel(tint.g),
+                channel(tint.b),
+                channel(tint.a),
+            ],
+        );
+        let uri = match self.image_uris.get(&key) {
+            Some(uri) => uri.clone(),
+            None => {
+                let uri: Arc<str> = png_data_uri(image, options.tint)?.into();
+                self.image_uris.insert(key, uri.clone());
+                uri
+            }
+        };
+
+        let mut attrs = String::new();
+        if options.opacity < 1.0 {
+            let _ = write!(attrs, r#" opacity="{}""#, num(options.opacity));
+        }
+        let (iw, ih) = (num(image.width as f32), num(image.height as f32));
+        match options.source_rect {
+            // Nested viewport crops to the source rect
+            Some(src) => {
+                let _ = writeln!(
+                    self.body,
+                    r#"<svg x="{}" y="{}" width="{}" height="{}" viewBox="{} {} {} {}" preserveAspectRatio="none" overflow="hidden"{}{}><image width="{}" height="{}" xlink:href="{}"/></svg>"#,
+                    num(rect.x()),
+                    num(rect.y()),
+                    num(rect.width()),
+                    num(rect.height()),
+                    num(src.x()),
+                    num(src.y()),
+                    num(src.width()),
+                    num(src.height()),
+                    attrs,
+                    transform,
+                    iw,
+                    ih,
+                    uri
+                );
+            }
+            None => {
+                let _ = writeln!(
+                    self.body,
+                    r#"<image x="{}" y="{}" width="{}" height="{}" preserveAspectRatio="none" xlink:href="{}"{}{}/>"#,
+                    num(rect.x()),
+                    num(rect.y()),
+                    num(rect.width()),
+                    num(rect.height()),
+                    uri,
+                    attrs,
+                    transform
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn shadow(&mut self, shape: &ShadowShape, shadow: &Shadow, inset: bool, transform: &str) {
+        if shadow.color.a <= 0.0 {
+            return;
+        }
+        let blur = shadow.blur.max(0.0);
+        let reach =
+            blur * 3.0 + shadow.spread.abs() + shadow.offset_x.abs().max(shadow.offset_y.abs());
+        let bounds = shape.bounds();
+        let region = Rect::new(
+            bounds.x() - reach,
+            bounds.y() - reach,
+            bounds.width() + reach * 2.0,
+            bounds.height() + reach * 2.0,
+        );
+
+        let filter = if blur > 0.0 {
+            let id = self.id("blur");
+            let _ = writeln!(
+                self.defs,
+                r#"<filter id="{}" filterUnits="userSpaceOnUse" x="{}" y="{}" width="{}" height="{}"><feGaussianBlur stdDeviation="{}"/></filter>"#,
+                id,
+                num(region.x()),
+                num(region.y()),
+                num(region.width()),
+                num(region.height()),
+                num(blur)
+            );
+            format!(r#" filter="url(#{})""#, id)
+        } else {
+            String::new()
+        };
+
+        if !inset {
+            let _ = writeln!(
+                self.body,
+                r#"<path d="{}"{}{}{}/>"#,
+                svg_path_data(&shape.cast(shadow)),
+                fill_attrs(shadow.color),
+                filter,
+                transform
+            );
+            return;
+        }
+
+        // Inner shadow: a ring around the (offset, shrunk) shape, blurred
+        // and clipped to the shape itself
+        let hole = shape.cast(&Shadow {
+            spread: -shadow.spread,
+            ..*shadow
+        });
+        let clip_id = self.id("inset");
+        let _ = writeln!(
+            self.defs,
+            r#"<clipPath id="{}" clipPathUnits="userSpaceOnUse"><path d="{}"/></clipPath>"#,
+            clip_id,
+            svg_path_data(&shape.outline())
+        );
+        let _ = writeln!(
+            self.body,
+            r#"<g clip-path="url(#{})"{}><path d="{} {}" fill-rule="evenodd"{}{}/></g>"#,
+            clip_id,
+            transform,
+            svg_path_data(&Path::rect(region)),
+            svg_path_data(&hole),
+            fill_attrs(shadow.color),
+            filter
+        );
+    }
+
+    /// `fill`/`stroke` attributes for a paint, emitting gradients to defs
+    fn paint_attrs(&mut self, paint: &Paint, attr: &str) -> String {
+        match paint {
+            Paint::Solid(color) => color_attrs(*color, attr),
+            Paint::Linear {
+                start,
+                end,
+                stops,
+                spread,
+            } => {
+                let id = self.id("grad");
+                let _ = writeln!(
+                    self.defs,
+                    r#"<linearGradient id="{}" gradientUnits="userSpaceOnUse" x1="{}" y1="{}" x2="{}" y2="{}" spreadMethod="{}">{}</linearGradient>"#,
+                    id,
+                    num(start.x),
+                    num(start.y),
+                    num(end.x),
+                    num(end.y),
+                    spread_method(*spread),
+                    stop_elements(stops)
+                );
+                format!(r#" {}="url(#{})""#, attr, id)
+            }
+            Paint::Radial {
+                center,
+                radius,
+                focal,
+                stops,
+                spread,
+            } => {
+                let id = self.id("grad");
+                let _ = writeln!(
+                    self.defs,
+                    r#"<radialGradient id="{}" gradientUnits="userSpaceOnUse" cx="{}" cy="{}" r="{}" fx="{}" fy="{}" spreadMethod="{}">{}</radialGradient>"#,
+                    id,
+                    num(center.x),
+                    num(center.y),
+                    num(*radius),
+                    num(focal.x),
+                    num(focal.y),
+                    spread_method(*spread),
+                    stop_elements(stops)
+                );
+                format!(r#" {}="url(#{})""#, attr, id)
+            }
+        }
+    }
+}
+
+fn transform_attr(t: &Affine2D) -> String {
+    if t.elements == Affine2D::IDENTITY.elements {
+        return String::new();
+    }
+    let [a, b, c, d, e, f] = t.elements;
+    format!(
+        r#" transform="matrix({} {} {} {} {} {})""#,
+        num(a),
+        num(b),
+        num(c),
+        num(d),
+        num(e),
+        num(f)
+    )
+}
+
+fn hex(color: Color) -> String {
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b)
+    )
+}
+
+fn color_attrs(color: Color, attr: &str) -> String {
+    if color.a >= 1.0 {
+        format!(r#" {}="{}""#, attr, hex(color))
+    } else {
+        format!(
+            r#" {attr}="{}" {attr}-opacity="{}""#,
+            hex(color),
+            num(color.a)
+        )
+    }
+}
+
+fn fill_attrs(color: Color) -> String {
+    color_attrs(color, "fill")
+}
+
+fn stroke_attrs(stroke: &Stroke) -> String {
+    let mut attrs = format!(r#" stroke-width="{}""#, num(stroke.width));
+    match stroke.cap {
+        LineCap::Butt => {}
+        LineCap::Round => attrs.push_str(r#" stroke-linecap="round""#),
+        LineCap::Square => attrs.push_str(r#" stroke-linecap="square""#),
+    }
+    match stroke.join {
+        LineJoin::Miter => {
+            let _ = write!(attrs, r#" stroke-miterlimit="{}""#, num(stroke.miter_limit));
+        }
+        LineJoin::Round => attrs.push_str(r#" stroke-linejoin="round""#),
+        LineJoin::Bevel => attrs.push_str(r#" stroke-linejoin="bevel""#),
+    }
+    if !stroke.dash.is_empty() {
+        let dash: Vec<String> = stroke.dash.iter().map(|d| num(*d)).collect();
+        let _ = write!(attrs, r#" stroke-dasharray="{}""#, dash.join(" "));
+        if stroke.dash_offset != 0.0 {
+            let _ = write!(attrs, r#" stroke-dashoffset="{}""#, num(stroke.dash_offset));
+        }
+    }
+    attrs
+}
+
+fn stop_elements(stops: &[GradientStop]) -> String {
+    let mut out = String::new();
+    for stop in stops {
+        let _ = write!(
+            out,
+            r#"<stop offset="{}" stop-color="{}""#,
+            num(stop.offset),
+            hex(stop.color)
+        );
+        if stop.color.a < 1.0 {
+            let _ = write!(out, r#" stop-opacity="{}""#, num(stop.color.a));
+        }
+        out.push_str("/>");
+    }
+    out
+}
+
+fn spread_method(spread: GradientSpread) -> &'static str {
+    match spread {
+        GradientSpread::Pad => "pad",
+        GradientSpread::Reflect => "reflect",
+        GradientSpread::Repeat => "repeat",
+    }
+}
+
+fn css_weight(weight: FontWeight) -> u16 {
+    match weight {
+        FontWeight::Thin => 100,
+        FontWeight::Light => 300,
+        FontWeight::Regular => 400,
+        FontWeight::Medium => 500,
+        FontWeight::Bold => 700,
+        FontWeight::Black => 900,
+    }
+}
+
+fn blend_mode_css(mode: BlendMode) -> Option<&'static str> {
+    Some(match mode {
+        BlendMode::Normal => return None,
+        BlendMode::Multiply => "multiply",
+        BlendMode::Screen => "screen",
+        BlendMode::Overlay => "overlay",
+        BlendMode::Darken => "darken",
+        BlendMode::Lighten => "lighten",
+        BlendMode::ColorDodge => "color-dodge",
+        BlendMode::ColorBurn => "color-burn",
+        BlendMode::HardLight => "hard-light",
+        BlendMode::SoftLight => "soft-light",
+        BlendMode::Difference => "difference",
+        BlendMode::Exclusion => "exclusion",
+    })
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn png_data_uri(image: &ExportImage, tint: Option<Color>) -> Result<String> {
+    let pixels = image_pixels(image, tint);
+    let mut data = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut data, image.width, image.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| ExportError::Image(e.to_string()))?;
+        writer
+            .write_image_data(&pixels)
+            .map_err(|e| ExportError::Image(e.to_string()))?;
+    }
+    Ok(format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(data)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use junita_core::{
+        Brush, ClipShape, CornerRadius, DrawContext, Gradient, RecordingContext, Size, Transform,
+    };
+
+    fn frame(draw: impl FnOnce(&mut RecordingContext)) -> ExportFrame {
+        let mut ctx = RecordingContext::new(Size::new(100.0, 80.0));
+        draw(&mut ctx);
+        ExportFrame::from_recording(&ctx)
+    }
+
+    #[test]
+    fn test_shapes_gradients_and_clips() {
+        let frame = frame(|ctx| {
+            ctx.push_clip(ClipShape::rect(Rect::new(0.0, 0.0, 50.0, 50.0)));
+            ctx.push_transform(Transform::translate(5.0, 5.0));
+            ctx.fill_rect(
+                Rect::new(0.0, 0.0, 40.0, 20.0),
+                CornerRadius::uniform(4.0),
+                Brush::Gradient(Gradient::linear(
+                    Point::new(0.0, 0.0),
+                    Point::new(40.0, 0.0),
+                    Color::RED,
+                    Color::BLUE,
+                )),
+            );
+            ctx.pop_transform();
+            ctx.pop_clip();
+            ctx.fill_circle(Point::new(70.0, 40.0), 10.0, Color::GREEN.into());
+        });
+
+        let svg = export_svg(&frame, &SvgOptions::new()).unwrap();
+        assert!(svg.contains(r#"viewBox="0 0 100 80""#));
+        assert!(svg.contains("<linearGradient"));
+        assert!(svg.contains(r#"<clipPath id="clip0""#));
+        assert!(svg.contains(r#"<g clip-path="url(#clip0)">"#));
+        assert!(svg.contains(r#"transform="matrix(1 0 0 1 5 5)""#));
+        assert_eq!(svg.matches("<path").count(), 3);
+    }
+
+    #[test]
+    fn test_shadows_and_images() {
+        let mut frame = frame(|ctx| {
+            ctx.draw_shadow(
+                Rect::new(10.0, 10.0, 30.0, 30.0),
+                CornerRadius::uniform(4.0),
+                Shadow::new(0.0, 2.0, 6.0, Color::BLACK),
+            );
+            ctx.draw_image(
+                ImageId(7),
+                Rect::new(50.0, 10.0, 20.0, 20.0),
+                &ImageOptions::new(),
+            );
+        });
+        frame.insert_image(ImageId(7), ExportImage::new(2, 2, vec![255; 16]).unwrap());
+
+        let svg = export_svg(&frame, &SvgOptions::new()).unwrap();
+        assert!(svg.contains("<feGaussianBlur"));
+        assert!(svg.contains("data:image/png;base64,"));
+    }
+
+    #[test]
+    fn test_text_elements_are_escaped() {
+        let frame = frame(|ctx| {
+            ctx.draw_text("a < b", Point::new(10.0, 20.0), &TextStyle::default());
+        });
+        let options = SvgOptions::new().text_mode(TextMode::Text);
+        let svg = export_svg(&frame, &options).unwrap();
+        assert!(svg.contains(">a &lt; b</text>"));
+    }
+}
@@ -0,0 +1,118 @@
+//! Text to vector outlines
+//!
+//! Exported text is converted to glyph outlines so documents render the same
+//! everywhere, independent of which fonts the viewer has installed.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use junita_core::{FontWeight, Path, Point, TextAlign, TextBaseline, TextStyle};
+use junita_text::{global_font_registry, FontFace, GenericFont, TextShaper};
+
+use crate::path::{concat_paths, translate_path};
+
+/// Converts `DrawText` runs into filled outline paths
+pub(crate) struct TextOutliner {
+    shaper: TextShaper,
+    /// Font used for every run when set
+    font_override: Option<Arc<FontFace>>,
+    faces: HashMap<(String, u16), Option<Arc<FontFace>>>,
+}
+
+impl TextOutliner {
+    pub(crate) fn new(font_override: Option<Arc<FontFace>>) -> Self {
+        Self {
+            shaper: TextShaper::new(),
+            font_override,
+            faces: HashMap::new(),
+        }
+    }
+
+    /// Outline a text run positioned like the GPU text context would
+    ///
+    /// Multi-line text is laid out line by line using the style's line
+    /// height. Returns `None` when no font is available or the text has no
+    /// visible glyphs.
+    pub(crate) fn outline(&mut self, text: &str, origin: Point, style: &TextStyle) -> Option<Path> {
+        let face = self.face(&style.family, style.weight)?;
+        let size = style.size;
+        let ascender = face.metrics().ascender_px(size);
+        let descender = face.metrics().descender_px(size);
+
+        let first_baseline = match style.baseline {
+            TextBaseline::Top => origin.y + ascender,
+            TextBaseline::Middle => origin.y + (ascender + descender) / 2.0,
+            TextBaseline::Alphabetic => origin.y,
+            TextBaseline::Bottom => origin.y + descender,
+        };
+        let line_advance = size * style.line_height;
+
+        let mut glyphs = Vec::new();
+        for (line_index, line) in text.lines().enumerate() {
+            let shaped = self.shaper.shape(line, &face, size);
+            let spacing = style.letter_spacing * shaped.glyphs.len().saturating_sub(1) as f32;
+            let width = shaped.width_px() + spacing;
+            let mut pen_x = match style.align {
+                TextAlign::Left => origin.x,
+                TextAlign::Center => origin.x - width / 2.0,
+                TextAlign::Right => origin.x - width,
+            };
+            let baseline = first_baseline + line_index as f32 * line_advance;
+
+            for glyph in &shaped.glyphs {
+                if let Some(outline) = face.glyph_outline(glyph.glyph_id, size) {
+                    glyphs.push(translate_path(
+                        &outline,
+                        pen_x + shaped.scale(glyph.x_offset),
+                        baseline - shaped.scale(glyph.y_offset),
+                    ));
+                }
+                pen_x += shaped.scale(glyph.x_advance) + style.letter_spacing;
+            }
+        }
+
+        if glyphs.is_empty() {
+            None
+        } else {
+            Some(concat_paths(&glyphs))
+        }
+    }
+
+    fn face(&mut self, family: &str, weight: FontWeight) -> Option<Arc<FontFace>> {
+        if let Some(face) = &self.font_override {
+            return Some(face.clone());
+        }
+        let weight = weight_number(weight);
+        self.faces
+            .entry((family.to_string(), weight))
+            .or_insert_with(|| {
+                let (name, generic) = match family {
+                    "" | "system-ui" | "sans-serif" => (None, GenericFont::System),
+                    "monospace" => (None, GenericFont::Monospace),
+                    "serif" => (None, GenericFont::Serif),
+                    name => (Some(name), GenericFont::System),
+                };
+                let registry = global_font_registry();
+                let mut registry = registry.lock().ok()?;
+                match registry.load_with_fallback_styled(name, generic, weight, false) {
+                    Ok(face) => Some(face),
+                    Err(err) => {
+                        tracing::warn!("junita_export: no font for '{}': {}", family, err);
+                        None
+                    }
+                }
+            })
+            .clone()
+    }
+}
+
+fn weight_number(weight: FontWeight) -> u16 {
+    match weight {
+        FontWeight::Thin => 100,
+        FontWeight::Light => 300,
+        FontWeight::Regular => 400,
+        FontWeight::Medium => 500,
+        FontWeight::Bold => 700,
+        FontWeight::Black => 900,
+    }
+}
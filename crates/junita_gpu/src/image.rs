@@ -57,6 +57,31 @@ impl GpuImage {
         }
     }
 
+    /// Replace the image's pixels with new RGBA data of the same size
+    ///
+    /// Used to advance animated images without reallocating the texture.
+    pub fn write_rgba(&self, queue: &wgpu::Queue, pixels: &[u8]) {
+        queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &self.texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            pixels,
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(self.width * 4),
+                rows_per_image: Some(self.height),
+            },
+            wgpu::Extent3d {
+                width: self.width,
+                height: self.height,
+                depth_or_array_layers: 1,
+            },
+        );
+    }
+
     /// Get the texture view for binding
     pub fn view(&self) -> &wgpu::TextureView {
         &self.view
@@ -281,6 +306,11 @@ impl ImageRenderingContext {
         )
     }
 
+    /// Replace a GPU image's pixels with new RGBA data of the same size
+    pub fn update_image(&self, image: &GpuImage, pixels: &[u8]) {
+        image.write_rgba(&self.queue, pixels);
+    }
+
     /// Get the linear sampler
     pub fn sampler_linear(&self) -> &wgpu::Sampler {
         &self.sampler_linear
@@ -41,12 +41,16 @@
 //! renderer.render(&target, &batch);
 //! ```
 
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
 use junita_core::{
-    Affine2D, BillboardFacing, BlendMode, Brush, Camera, ClipShape, Color, CornerRadius,
-    DrawCommand, DrawContext, Environment, ImageId, ImageOptions, LayerConfig, LayerId, Light,
-    Mat4, MaterialId, MeshId, MeshInstance, ParticleBlendMode, ParticleEmitterShape, ParticleForce,
-    ParticleSystemData, Path, Point, Rect, Sdf3DViewport, SdfBuilder, Shadow, ShapeId, Size,
-    Stroke, TextStyle, Transform,
+    Affine2D, BillboardFacing, BlendMode, Brush, CachePolicy, Camera, ClipShape, Color,
+    CornerRadius, DrawCommand, DrawContext, Environment, ImageId, ImageOptions, LayerConfig,
+    LayerId, Light, Mat4, MaterialId, MeshId, MeshInstance, ParticleBlendMode,
+    ParticleEmitterShape, ParticleForce, ParticleSystemData, Path, Point, Rect, Sdf3DViewport,
+    SdfBuilder, Shadow, ShapeId, Size, Stroke, TextStyle, Transform,
 };
 
 use crate::path::{extract_brush_info, tessellate_fill, tessellate_stroke};
@@ -104,6 +108,10 @@ struct LayerState {
     foreground_path_start: usize,
     /// Parent state stack indices (transform, opacity, blend, clip)
     parent_state_indices: (usize, usize, usize, usize),
+    /// Index of this layer's push command in the batch
+    command_index: usize,
+    /// Parent opacity stack, set aside while a cached layer is recorded
+    saved_opacity: Option<Vec<f32>>,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -141,6 +149,8 @@ pub struct GpuPaintContext<'a> {
     z_layer: u32,
     /// Stack of active layers for offscreen rendering
     layer_stack: Vec<LayerState>,
+    /// Cached layers the renderer still holds, as `(id, content version)`
+    resident_layers: HashSet<(LayerId, u64)>,
 }
 
 impl<'a> GpuPaintContext<'a> {
@@ -160,6 +170,7 @@ impl<'a> GpuPaintContext<'a> {
             is_foreground: false,
             z_layer: 0,
             layer_stack: Vec::new(),
+            resident_layers: HashSet::new(),
         }
     }
 
@@ -191,9 +202,18 @@ impl<'a> GpuPaintContext<'a> {
             is_foreground: false,
             z_layer: 0,
             layer_stack: Vec::new(),
+            resident_layers: HashSet::new(),
         }
     }
 
+    /// Set the cached layers that are resident in the renderer
+    ///
+    /// Pass the set returned by `GpuRenderer::prepare_layer_cache` before
+    /// painting so `is_layer_cached` can report hits.
+    pub fn set_resident_layers(&mut self, layers: HashSet<(LayerId, u64)>) {
+        self.resident_layers = layers;
+    }
+
     /// Set the text rendering context
     pub fn set_text_context(&mut self, text_ctx: &'a mut TextRenderingContext) {
         self.text_ctx = Some(text_ctx);
@@ -207,6 +227,48 @@ impl<'a> GpuPaintContext<'a> {
             .unwrap_or(Affine2D::IDENTITY)
     }
 
+    /// Content version of a cached layer pushed at the current state
+    ///
+    /// Cached textures are positioned by the layer's screen origin, so
+    /// translation is free; everything else that ends up baked into the
+    /// texture - the linear part of the transform, the active clip relative
+    /// to the origin and the blend mode - is folded into the version.
+    fn cached_layer_version(&self, content_version: u64) -> u64 {
+        let affine = self.current_affine();
+        let origin = self.transform_point(Point::new(0.0, 0.0));
+        let mut hasher = DefaultHasher::new();
+        content_version.hash(&mut hasher);
+        affine.elements[..4]
+            .iter()
+            .for_each(|v| v.to_bits().hash(&mut hasher));
+        if let Some(clip) = self.clip_stack.last() {
+            let bounds = clip.bounds();
+            for v in [
+                bounds.x() - origin.x,
+                bounds.y() - origin.y,
+                bounds.width(),
+                bounds.height(),
+            ] {
+                v.to_bits().hash(&mut hasher);
+            }
+        }
+        std::mem::discriminant(&self.current_blend_mode()).hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Whether a cached layer can be honoured at the current state
+    ///
+    /// Cached layers are composited from the background batch, so they can't
+    /// live in the foreground pass or inside a layer that is itself
+    /// post-processed.
+    fn can_cache_layer(&self) -> bool {
+        !self.is_foreground
+            && self
+                .layer_stack
+                .iter()
+                .all(|layer| layer.config.effects.is_empty() && !layer.config.is_cached())
+    }
+
     /// Get the current combined opacity
     fn combined_opacity(&self) -> f32 {
         self.opacity_stack.iter().product()
@@ -2088,7 +2150,21 @@ impl<'a> DrawContext for GpuPaintContext<'a> {
         self.batch.push_particle_viewport(viewport);
     }
 
-    fn push_layer(&mut self, config: LayerConfig) {
+    fn push_layer(&mut self, mut config: LayerConfig) {
+        // Cached layers are rasterized at full opacity and composited with the
+        // combined opacity, so the texture stays valid while they fade
+        let mut saved_opacity = None;
+        if config.is_cached() {
+            if self.can_cache_layer() {
+                config.content_version = self.cached_layer_version(config.content_version);
+                config.position = Some(self.transform_point(Point::new(0.0, 0.0)));
+                config.opacity *= self.combined_opacity();
+                saved_opacity = Some(std::mem::replace(&mut self.opacity_stack, vec![1.0]));
+            } else {
+                config.cache_policy = CachePolicy::None;
+            }
+        }
+
         // Record current state indices for restoration on pop
         let state = LayerState {
             config: config.clone(),
@@ -2102,7 +2178,10 @@ impl<'a> DrawContext for GpuPaintContext<'a> {
                 self.blend_mode_stack.len(),
                 self.clip_stack.len(),
             ),
+            command_index: self.batch.layer_commands.len(),
+            saved_opacity,
         };
+        let cached = state.saved_opacity.is_some();
         self.layer_stack.push(state);
 
         // Apply layer's blend mode if not Normal
@@ -2111,7 +2190,7 @@ impl<'a> DrawContext for GpuPaintContext<'a> {
         }
 
         // Apply layer's opacity if less than 1.0
-        if config.opacity < 1.0 {
+        if config.opacity < 1.0 && !cached {
             self.opacity_stack.push(config.opacity);
         }
 
@@ -2141,6 +2220,21 @@ impl<'a> DrawContext for GpuPaintContext<'a> {
             if self.clip_stack.len() > clip_idx {
                 self.clip_stack.truncate(clip_idx);
             }
+            if let Some(opacity_stack) = state.saved_opacity {
+                self.opacity_stack = opacity_stack;
+
+                // Paths are drawn outside the layer passes, so a layer that
+                // tessellated any can't be replayed from its texture
+                if self.batch.path_vertex_count() > state.path_start {
+                    if let Some(crate::primitives::LayerCommandEntry {
+                        command: crate::primitives::LayerCommand::Push { config },
+                        ..
+                    }) = self.batch.layer_commands.get_mut(state.command_index)
+                    {
+                        config.cache_policy = CachePolicy::None;
+                    }
+                }
+            }
 
             // Record layer command for GPU renderer to process
             self.batch
@@ -2158,6 +2252,13 @@ impl<'a> DrawContext for GpuPaintContext<'a> {
             });
     }
 
+    fn is_layer_cached(&self, id: LayerId, content_version: u64) -> bool {
+        self.can_cache_layer()
+            && self
+                .resident_layers
+                .contains(&(id, self.cached_layer_version(content_version)))
+    }
+
     fn viewport_size(&self) -> Size {
         self.viewport
     }
@@ -2464,6 +2565,7 @@ mod tests {
             opacity: 0.5,
             depth: false,
             effects: Vec::new(),
+            ..Default::default()
         };
         ctx.push_layer(config);
 
@@ -2502,6 +2604,7 @@ mod tests {
             opacity: 0.8,
             depth: false,
             effects: Vec::new(),
+            ..Default::default()
         };
         ctx.push_layer(config1);
         assert_eq!(ctx.layer_stack.len(), 1);
@@ -2516,6 +2619,7 @@ mod tests {
             opacity: 0.5,
             depth: false,
             effects: Vec::new(),
+            ..Default::default()
         };
         ctx.push_layer(config2);
         assert_eq!(ctx.layer_stack.len(), 2);
@@ -2533,4 +2637,38 @@ mod tests {
         assert_eq!(ctx.layer_stack.len(), 0);
         assert_eq!(ctx.current_opacity(), 1.0);
     }
+
+    #[test]
+    fn test_cached_layer() {
+        let mut ctx = GpuPaintContext::new(800.0, 600.0);
+        let id = LayerId::new(7);
+        ctx.push_transform(Transform::translate(40.0, 30.0));
+
+        let version = ctx.cached_layer_version(1);
+        assert!(!ctx.is_layer_cached(id, 1));
+        ctx.set_resident_layers(HashSet::from([(id, version)]));
+        assert!(ctx.is_layer_cached(id, 1));
+        assert!(!ctx.is_layer_cached(id, 2));
+
+        // Cached content is recorded at full opacity and placed at its screen origin
+        ctx.push_layer(
+            LayerConfig::new()
+                .id(id)
+                .opacity(0.5)
+                .cached(CachePolicy::Content, 1),
+        );
+        assert_eq!(ctx.current_opacity(), 1.0);
+        ctx.pop_layer();
+        assert_eq!(ctx.current_opacity(), 1.0);
+
+        let batch = ctx.take_batch();
+        let crate::primitives::LayerCommand::Push { config } = &batch.layer_commands[0].command
+        else {
+            panic!("expected a layer push");
+        };
+        assert!(config.is_cached());
+        assert_eq!(config.content_version, version);
+        assert_eq!(config.opacity, 0.5);
+        assert_eq!(config.position, Some(Point::new(40.0, 30.0)));
+    }
 }
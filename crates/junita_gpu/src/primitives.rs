@@ -1214,6 +1214,22 @@ impl PrimitiveBatch {
         })
     }
 
+    /// Whether any layer asks to be retained across frames
+    pub fn has_cached_layers(&self) -> bool {
+        self.layer_commands.iter().any(
+            |entry| matches!(&entry.command, LayerCommand::Push { config } if config.is_cached()),
+        )
+    }
+
+    /// Whether the batch has layers that must be composited offscreen
+    ///
+    /// Batches with layer effects or cached layers have to go through the
+    /// layer-aware render path; the z-layer and overlay shortcuts ignore
+    /// layer commands.
+    pub fn needs_layer_compositing(&self) -> bool {
+        self.has_layer_effects() || self.has_cached_layers()
+    }
+
     pub fn push(&mut self, primitive: GpuPrimitive) {
         self.primitives.push(primitive);
     }
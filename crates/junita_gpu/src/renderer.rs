@@ -6,6 +6,7 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
+use junita_core::profiler;
 use wgpu::util::DeviceExt;
 
 use crate::gradient_texture::GradientTextureCache;
@@ -825,6 +826,26 @@ pub struct GpuRenderer {
     sdf_3d_resources: Option<Sdf3DResources>,
     /// Cached particle systems for GPU particle rendering (keyed by hash of emitter config)
     particle_systems: std::collections::HashMap<u64, crate::particles::ParticleSystemGpu>,
+    /// Physical-pixel region (x, y, width, height) that frame passes are limited to
+    damage_scissor: Option<[u32; 4]>,
+    /// Retained textures of cached layers, reused while their content version matches
+    cached_layers: std::collections::HashMap<junita_core::LayerId, CachedLayer>,
+}
+
+/// A layer texture retained across frames
+struct CachedLayer {
+    /// Rasterized layer content
+    texture: LayerTexture,
+    /// Used portion of the texture in pixels
+    content_size: (u32, u32),
+    /// Offset of the texture from the layer's screen origin
+    offset: (f32, f32),
+    /// Size of the texture content on screen
+    size: (f32, f32),
+    /// Content version the texture was rasterized at
+    version: u64,
+    /// Whether the layer was composited since the last `prepare_layer_cache`
+    used: bool,
 }
 
 /// Image rendering pipeline (created lazily on first image render)
@@ -855,6 +876,13 @@ struct BindGroupLayouts {
     glow: wgpu::BindGroupLayout,
 }
 
+/// Submit an encoder's commands to the queue
+fn submit(queue: &wgpu::Queue, encoder: wgpu::CommandEncoder) {
+    let _scope = profiler::scope("gpu_submit");
+    profiler::count(profiler::counters::GPU_SUBMITS, 1);
+    queue.submit(std::iter::once(encoder.finish()));
+}
+
 impl GpuRenderer {
     /// Get the preferred backend for the current platform
     ///
@@ -1365,6 +1393,8 @@ impl GpuRenderer {
             layer_texture_cache: LayerTextureCache::new(texture_format),
             sdf_3d_resources: None,
             particle_systems: std::collections::HashMap::new(),
+            damage_scissor: None,
+            cached_layers: std::collections::HashMap::new(),
         })
     }
 
@@ -2810,6 +2840,39 @@ impl GpuRenderer {
         self.viewport_size = (width, height);
     }
 
+    /// Limit frame rendering to a damaged region
+    ///
+    /// While set, `render_with_clear` keeps the existing target contents and
+    /// only clears and redraws `[x, y, width, height]` (physical pixels); the
+    /// overlay, text and image passes are scissored to the same region. The
+    /// target must therefore hold the previous frame, so this is only useful
+    /// with a retained render target. Layer effects, glass and 3D viewports
+    /// are not scissored and need a full redraw. Pass `None` to render whole
+    /// frames again.
+    pub fn set_damage_scissor(&mut self, rect: Option<[u32; 4]>) {
+        self.damage_scissor = rect;
+    }
+
+    /// Current damage scissor, if partial redraw is active
+    pub fn damage_scissor(&self) -> Option<[u32; 4]> {
+        self.damage_scissor
+    }
+
+    /// Damage scissor clamped to the viewport
+    ///
+    /// Returns `None` when no scissor is set or when it covers the whole
+    /// viewport, so callers can skip the partial-redraw path entirely.
+    fn clamped_damage_scissor(&self) -> Option<[u32; 4]> {
+        clamp_scissor(self.damage_scissor?, self.viewport_size)
+    }
+
+    /// Restrict a frame pass to the damage scissor, if one is set
+    fn apply_damage_scissor(&self, render_pass: &mut wgpu::RenderPass<'_>) {
+        if let Some([x, y, w, h]) = self.clamped_damage_scissor() {
+            render_pass.set_scissor_rect(x, y, w, h);
+        }
+    }
+
     /// Update the frame time (for animations)
     pub fn update_time(&mut self, time: f32) {
         self.time = time;
@@ -2840,6 +2903,14 @@ impl GpuRenderer {
         self.texture_format
     }
 
+    /// Texture usages a surface created on this renderer's adapter supports
+    ///
+    /// Partial redraw presents by copying a retained frame into the surface
+    /// texture, which needs `COPY_DST`.
+    pub fn surface_usages(&self, surface: &wgpu::Surface<'_>) -> wgpu::TextureUsages {
+        surface.get_capabilities(&self.adapter).usages
+    }
+
     /// Returns true if unified text/SDF rendering is enabled
     ///
     /// When enabled, text glyphs are converted to SDF primitives and rendered
@@ -2891,18 +2962,18 @@ impl GpuRenderer {
         batch: &PrimitiveBatch,
         clear_color: [f64; 4],
     ) {
+        let _scope = profiler::scope("gpu_render");
+        profiler::count(
+            profiler::counters::PRIMITIVES,
+            (batch.primitives.len() + batch.foreground_primitives.len()) as u64,
+        );
+
         // Evict oversized textures from the pool at frame start
         // This prevents memory bloat from accumulated large textures
         self.layer_texture_cache.evict_oversized();
 
-        // Check if we have layer commands with effects that need processing
-        let has_layer_effects = batch.layer_commands.iter().any(|entry| {
-            if let crate::primitives::LayerCommand::Push { config } = &entry.command {
-                !config.effects.is_empty()
-            } else {
-                false
-            }
-        });
+        // Check if we have layer commands with effects or cached layers that need processing
+        let has_layer_effects = batch.needs_layer_compositing();
 
         tracing::trace!(
             "render_with_clear: {} primitives, {} layer commands, has_layer_effects={}",
@@ -2936,19 +3007,55 @@ impl GpuRenderer {
         self.queue
             .write_buffer(&self.buffers.uniforms, 0, bytemuck::bytes_of(&uniforms));
 
+        // Partial redraw: the target keeps the previous frame, so the damaged
+        // region is "cleared" by an opaque rect drawn as the first primitive.
+        // A translucent clear colour can't be emulated that way, so fall back
+        // to a full redraw for the rest of the frame.
+        if self.damage_scissor.is_some() && clear_color[3] < 1.0 {
+            tracing::debug!(
+                "render_with_clear: translucent clear colour, disabling damage scissor"
+            );
+            self.damage_scissor = None;
+        }
+        let partial = self.clamped_damage_scissor().is_some();
+        let clear_rect = partial.then(|| {
+            GpuPrimitive::rect(
+                0.0,
+                0.0,
+                self.viewport_size.0 as f32,
+                self.viewport_size.1 as f32,
+            )
+            .with_color(
+                clear_color[0] as f32,
+                clear_color[1] as f32,
+                clear_color[2] as f32,
+                1.0,
+            )
+        });
+
         // Update primitives buffer (with safety limit to prevent buffer overflow)
-        if !batch.primitives.is_empty() {
+        let mut primitive_count = 0u32;
+        if !batch.primitives.is_empty() || clear_rect.is_some() {
             let max_primitives = self.config.max_primitives;
-            let primitives_to_write = if batch.primitives.len() > max_primitives {
+            // Only copy the batch when a clear rect has to go in front of it
+            let primitives: std::borrow::Cow<'_, [GpuPrimitive]> = match clear_rect {
+                Some(rect) => std::iter::once(rect)
+                    .chain(batch.primitives.iter().copied())
+                    .collect::<Vec<_>>()
+                    .into(),
+                None => (&batch.primitives[..]).into(),
+            };
+            let primitives_to_write = if primitives.len() > max_primitives {
                 tracing::warn!(
                     "Primitive count {} exceeds buffer capacity {}, truncating",
-                    batch.primitives.len(),
+                    primitives.len(),
                     max_primitives
                 );
-                &batch.primitives[..max_primitives]
+                &primitives[..max_primitives]
             } else {
-                &batch.primitives
+                &primitives[..]
             };
+            primitive_count = primitives_to_write.len() as u32;
             self.queue.write_buffer(
                 &self.buffers.primitives,
                 0,
@@ -2977,12 +3084,16 @@ impl GpuRenderer {
                     view: target,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: clear_color[0],
-                            g: clear_color[1],
-                            b: clear_color[2],
-                            a: clear_color[3],
-                        }),
+                        load: if partial {
+                            wgpu::LoadOp::Load
+                        } else {
+                            wgpu::LoadOp::Clear(wgpu::Color {
+                                r: clear_color[0],
+                                g: clear_color[1],
+                                b: clear_color[2],
+                                a: clear_color[3],
+                            })
+                        },
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -2990,13 +3101,14 @@ impl GpuRenderer {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            self.apply_damage_scissor(&mut render_pass);
 
             // Render SDF primitives
-            if !batch.primitives.is_empty() {
+            if primitive_count > 0 {
                 render_pass.set_pipeline(&self.pipelines.sdf);
                 render_pass.set_bind_group(0, &self.bind_groups.sdf, &[]);
                 // 6 vertices per quad (2 triangles), one instance per primitive
-                render_pass.draw(0..6, 0..batch.primitives.len() as u32);
+                render_pass.draw(0..6, 0..primitive_count);
             }
 
             // Render paths
@@ -3014,7 +3126,7 @@ impl GpuRenderer {
         }
 
         // Submit commands
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
 
         // Render SDF 3D viewports (after main content, so they render on top)
         if !batch.viewports_3d.is_empty() {
@@ -3030,9 +3142,10 @@ impl GpuRenderer {
     /// Render with layer effect processing
     ///
     /// This implements a correct layer effect system:
-    /// 1. Identify primitive ranges for effect layers
-    /// 2. Render non-effect primitives to target (skipping those in effect layers)
-    /// 3. For each effect layer, render to viewport-sized texture, apply effects, blit at position
+    /// 1. Identify primitive ranges for effect layers and cached layers
+    /// 2. Render the primitives between layers to target, in paint order
+    /// 3. For each effect layer, render to a tight texture, apply effects, blit at position
+    /// 4. For each cached layer, blit the retained texture, rasterizing it first on a miss
     fn render_with_layer_effects(
         &mut self,
         target: &wgpu::TextureView,
@@ -3041,7 +3154,7 @@ impl GpuRenderer {
     ) {
         use crate::primitives::LayerCommand;
 
-        // Build list of effect layers with their primitive ranges
+        // Build list of composited layers with their primitive ranges
         let mut effect_layers: Vec<(usize, usize, junita_core::LayerConfig)> = Vec::new();
         let mut layer_stack: Vec<(usize, junita_core::LayerConfig)> = Vec::new();
 
@@ -3052,7 +3165,7 @@ impl GpuRenderer {
                 }
                 LayerCommand::Pop => {
                     if let Some((start_idx, config)) = layer_stack.pop() {
-                        if !config.effects.is_empty() {
+                        if !config.effects.is_empty() || config.is_cached() {
                             effect_layers.push((start_idx, entry.primitive_index, config));
                         }
                     }
@@ -3067,7 +3180,11 @@ impl GpuRenderer {
             return;
         }
 
-        // Build set of primitive indices that belong to effect layers (to skip in first pass)
+        // Composite layers in paint order so content drawn after a layer stays on top.
+        // The sort is stable, so nested layers sharing a start keep their pop order.
+        effect_layers.sort_by_key(|(start, _, _)| *start);
+
+        // Build set of primitive indices that belong to effect layers (to skip in segment passes)
         let mut effect_primitives = std::collections::HashSet::new();
         for (start, end, _) in &effect_layers {
             for i in *start..*end {
@@ -3075,12 +3192,27 @@ impl GpuRenderer {
             }
         }
 
-        // First pass: render primitives that are NOT in effect layers
-        self.render_primitives_excluding(target, batch, &effect_primitives, clear_color);
-        drop(effect_primitives); // Free HashSet immediately - not needed after first pass
+        // The first segment clears the target and draws all paths
+        let mut segment_start = 0;
+        let mut clear = Some(clear_color);
 
         // Process each effect layer
         for (start_idx, end_idx, config) in effect_layers {
+            if start_idx > segment_start || clear.is_some() {
+                self.render_primitives_excluding(
+                    target,
+                    batch,
+                    segment_start..start_idx.max(segment_start),
+                    &effect_primitives,
+                    clear.take(),
+                );
+            }
+            segment_start = segment_start.max(end_idx);
+
+            if config.is_cached() && self.composite_cached_layer(target, &config) {
+                continue;
+            }
+
             if start_idx >= end_idx || end_idx > batch.primitives.len() {
                 continue;
             }
@@ -3180,7 +3312,22 @@ impl GpuRenderer {
                     config.blend_mode,
                     layer_clip,
                 );
-                self.layer_texture_cache.release(layer_texture);
+
+                // Retain cached layers unless the texture had to be cut to the viewport
+                let fits = layer_size.0 <= vp_w && layer_size.1 <= vp_h;
+                match (config.id, config.position) {
+                    (Some(id), Some(origin)) if config.is_cached() && fits => {
+                        self.store_cached_layer(
+                            id,
+                            &config,
+                            layer_texture,
+                            tight_size,
+                            (expanded_pos.0 - origin.x, expanded_pos.1 - origin.y),
+                            expanded_size,
+                        );
+                    }
+                    _ => self.layer_texture_cache.release(layer_texture),
+                }
             } else {
                 // Apply effects to the tight texture
                 let effected = self.apply_layer_effects(&layer_texture, &config.effects);
@@ -3201,32 +3348,161 @@ impl GpuRenderer {
                 self.layer_texture_cache.release(effected);
             }
         }
+
+        // Primitives after the last layer
+        if segment_start < batch.primitives.len() || clear.is_some() {
+            self.render_primitives_excluding(
+                target,
+                batch,
+                segment_start..batch.primitives.len().max(segment_start),
+                &effect_primitives,
+                clear,
+            );
+        }
+    }
+
+    /// Composite a cached layer from its retained texture
+    ///
+    /// Returns `false` when the layer isn't resident at the config's content
+    /// version, in which case it has to be rasterized from its primitives.
+    fn composite_cached_layer(
+        &mut self,
+        target: &wgpu::TextureView,
+        config: &junita_core::LayerConfig,
+    ) -> bool {
+        let (Some(id), Some(origin)) = (config.id, config.position) else {
+            return false;
+        };
+        let Some(cached) = self.cached_layers.remove(&id) else {
+            return false;
+        };
+        if cached.version != config.content_version {
+            self.cached_layers.insert(id, cached);
+            return false;
+        }
+
+        self.blit_tight_texture_to_target(
+            &cached.texture.view,
+            cached.content_size,
+            target,
+            (origin.x + cached.offset.0, origin.y + cached.offset.1),
+            cached.size,
+            config.opacity,
+            config.blend_mode,
+            None,
+        );
+        self.cached_layers.insert(
+            id,
+            CachedLayer {
+                used: true,
+                ..cached
+            },
+        );
+        true
+    }
+
+    /// Retain a rasterized layer texture for later frames
+    fn store_cached_layer(
+        &mut self,
+        id: junita_core::LayerId,
+        config: &junita_core::LayerConfig,
+        texture: LayerTexture,
+        content_size: (u32, u32),
+        offset: (f32, f32),
+        size: (f32, f32),
+    ) {
+        let cached = CachedLayer {
+            texture,
+            content_size,
+            offset,
+            size,
+            version: config.content_version,
+            used: true,
+        };
+        if let Some(old) = self.cached_layers.insert(id, cached) {
+            self.layer_texture_cache.release(old.texture);
+        }
+    }
+
+    /// Start a frame of retained layer caching
+    ///
+    /// Releases cached layers that weren't composited since the previous call
+    /// and returns the `(id, content version)` pairs that are still resident.
+    /// Call this once per frame and hand the result to
+    /// `GpuPaintContext::set_resident_layers` before painting, so subtrees
+    /// whose layer is resident can skip drawing.
+    pub fn prepare_layer_cache(
+        &mut self,
+    ) -> std::collections::HashSet<(junita_core::LayerId, u64)> {
+        let stale: Vec<_> = self
+            .cached_layers
+            .iter()
+            .filter(|(_, cached)| !cached.used)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in stale {
+            if let Some(cached) = self.cached_layers.remove(&id) {
+                self.layer_texture_cache.release(cached.texture);
+            }
+        }
+
+        self.cached_layers
+            .iter_mut()
+            .map(|(id, cached)| {
+                cached.used = false;
+                (*id, cached.version)
+            })
+            .collect()
+    }
+
+    /// Drop all retained layer textures
+    pub fn clear_layer_cache(&mut self) {
+        for (_, cached) in self.cached_layers.drain() {
+            self.layer_texture_cache.release(cached.texture);
+        }
     }
 
-    /// Render primitives excluding those in the given set
+    /// Render a range of primitives, skipping those in the given set
+    ///
+    /// With a clear color the target is cleared first and all path geometry is
+    /// drawn as well; otherwise the primitives are drawn over the existing
+    /// contents.
     fn render_primitives_excluding(
         &mut self,
         target: &wgpu::TextureView,
         batch: &PrimitiveBatch,
+        range: std::ops::Range<usize>,
         exclude: &std::collections::HashSet<usize>,
-        clear_color: [f64; 4],
+        clear_color: Option<[f64; 4]>,
     ) {
-        // If nothing to exclude, use simple path
-        if exclude.is_empty() {
-            self.render_with_clear_simple(target, batch, clear_color);
-            return;
-        }
-
         // Build list of primitives to render (excluding those in effect layers)
-        let included_primitives: Vec<GpuPrimitive> = batch
-            .primitives
+        let included_primitives: Vec<GpuPrimitive> = batch.primitives[range.clone()]
             .iter()
             .enumerate()
-            .filter(|(i, _)| !exclude.contains(i))
+            .filter(|(i, _)| !exclude.contains(&(range.start + i)))
             .map(|(_, p)| *p)
             .collect();
 
-        if included_primitives.is_empty() && batch.paths.vertices.is_empty() {
+        let load = match clear_color {
+            Some(clear_color) => wgpu::LoadOp::Clear(wgpu::Color {
+                r: clear_color[0],
+                g: clear_color[1],
+                b: clear_color[2],
+                a: clear_color[3],
+            }),
+            None => wgpu::LoadOp::Load,
+        };
+
+        // Paths can't be split by layer, so they are drawn with the first segment
+        let has_paths = clear_color.is_some()
+            && !batch.paths.vertices.is_empty()
+            && !batch.paths.indices.is_empty();
+
+        if included_primitives.is_empty() && !has_paths {
+            if clear_color.is_none() {
+                return;
+            }
+
             // Just clear the target
             let mut encoder = self
                 .device
@@ -3240,12 +3516,7 @@ impl GpuRenderer {
                         view: target,
                         resolve_target: None,
                         ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(wgpu::Color {
-                                r: clear_color[0],
-                                g: clear_color[1],
-                                b: clear_color[2],
-                                a: clear_color[3],
-                            }),
+                            load,
                             store: wgpu::StoreOp::Store,
                         },
                     })],
@@ -3254,7 +3525,7 @@ impl GpuRenderer {
                     occlusion_query_set: None,
                 });
             }
-            self.queue.submit(std::iter::once(encoder.finish()));
+            submit(&self.queue, encoder);
             return;
         }
 
@@ -3276,7 +3547,6 @@ impl GpuRenderer {
         }
 
         // Update path buffers if we have path geometry
-        let has_paths = !batch.paths.vertices.is_empty() && !batch.paths.indices.is_empty();
         if has_paths {
             self.update_path_buffers(batch);
         }
@@ -3296,12 +3566,7 @@ impl GpuRenderer {
                     view: target,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: clear_color[0],
-                            g: clear_color[1],
-                            b: clear_color[2],
-                            a: clear_color[3],
-                        }),
+                        load,
                         store: wgpu::StoreOp::Store,
                     },
                 })],
@@ -3332,7 +3597,7 @@ impl GpuRenderer {
         }
 
         // Submit commands
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Update path vertex and index buffers
@@ -3497,7 +3762,7 @@ impl GpuRenderer {
         }
 
         // Submit commands
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Render glass primitives (requires backdrop texture)
@@ -3668,7 +3933,7 @@ impl GpuRenderer {
         }
 
         // Submit commands
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Render primitives to a backdrop texture for glass blur sampling
@@ -3735,7 +4000,7 @@ impl GpuRenderer {
         }
 
         // Submit commands
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
         // Note: No need to restore uniforms since we're already using main_uniforms
     }
 
@@ -3977,7 +4242,7 @@ impl GpuRenderer {
         }
 
         // Submit background and glass passes first
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
 
         // Pass 4: Render foreground primitives (on top of glass)
         // This requires a separate submission because we need to overwrite the primitives buffer
@@ -4015,7 +4280,7 @@ impl GpuRenderer {
             render_pass.draw(0..6, 0..batch.foreground_primitives.len() as u32);
 
             drop(render_pass);
-            self.queue.submit(std::iter::once(encoder.finish()));
+            submit(&self.queue, encoder);
         }
 
         // Pass 5: Render paths (SVGs) on top of glass
@@ -4057,7 +4322,7 @@ impl GpuRenderer {
                 render_pass.draw_indexed(0..batch.paths.indices.len() as u32, 0, 0..1);
 
                 drop(render_pass);
-                self.queue.submit(std::iter::once(encoder.finish()));
+                submit(&self.queue, encoder);
             }
         }
     }
@@ -4134,6 +4399,7 @@ impl GpuRenderer {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            self.apply_damage_scissor(&mut render_pass);
 
             // Render paths first (they're typically backgrounds)
             if has_paths {
@@ -4157,7 +4423,7 @@ impl GpuRenderer {
         }
 
         // Submit commands
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Render overlay with layer effect processing
@@ -4295,6 +4561,7 @@ impl GpuRenderer {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            self.apply_damage_scissor(&mut render_pass);
 
             // Render paths first
             if has_paths {
@@ -4318,7 +4585,7 @@ impl GpuRenderer {
         }
 
         // Submit commands
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Render a slice of primitives as overlay (LoadOp::Load, keeps existing content)
@@ -4372,6 +4639,7 @@ impl GpuRenderer {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            self.apply_damage_scissor(&mut render_pass);
 
             // Render SDF primitives
             render_pass.set_pipeline(&self.pipelines.sdf_overlay);
@@ -4380,7 +4648,7 @@ impl GpuRenderer {
         }
 
         // Submit commands
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Render paths (tessellated geometry like SVGs) as an overlay
@@ -4418,6 +4686,7 @@ impl GpuRenderer {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            self.apply_damage_scissor(&mut render_pass);
 
             // Use overlay path pipeline (1x sampled)
             render_pass.set_pipeline(&self.pipelines.path_overlay);
@@ -4427,7 +4696,7 @@ impl GpuRenderer {
             render_pass.draw_indexed(0..batch.paths.indices.len() as u32, 0, 0..1);
 
             drop(render_pass);
-            self.queue.submit(std::iter::once(encoder.finish()));
+            submit(&self.queue, encoder);
         }
     }
 
@@ -4537,6 +4806,7 @@ impl GpuRenderer {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            self.apply_damage_scissor(&mut render_pass);
 
             // Render SDF primitives (including text glyphs)
             render_pass.set_pipeline(&self.pipelines.sdf_overlay);
@@ -4545,7 +4815,7 @@ impl GpuRenderer {
         }
 
         // Submit commands
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Render overlay primitives with MSAA anti-aliasing
@@ -4792,13 +5062,14 @@ impl GpuRenderer {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            self.apply_damage_scissor(&mut render_pass);
 
             render_pass.set_pipeline(&self.pipelines.composite_overlay);
             render_pass.set_bind_group(0, &cached.composite_bind_group, &[]);
             render_pass.draw(0..3, 0..1); // Fullscreen triangle
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Render only paths with MSAA anti-aliasing
@@ -5014,13 +5285,14 @@ impl GpuRenderer {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            self.apply_damage_scissor(&mut render_pass);
 
             render_pass.set_pipeline(&self.pipelines.composite_overlay);
             render_pass.set_bind_group(0, &cached.composite_bind_group, &[]);
             render_pass.draw(0..3, 0..1);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Render text glyphs with a provided atlas texture
@@ -5126,6 +5398,7 @@ impl GpuRenderer {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            self.apply_damage_scissor(&mut render_pass);
 
             // Use text_overlay pipeline since we're rendering to 1x sampled texture
             render_pass.set_pipeline(&self.pipelines.text_overlay);
@@ -5134,7 +5407,7 @@ impl GpuRenderer {
         }
 
         // Submit commands
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Create the image rendering pipeline (lazily initialized)
@@ -5393,6 +5666,7 @@ impl GpuRenderer {
                 timestamp_writes: None,
                 occlusion_query_set: None,
             });
+            self.apply_damage_scissor(&mut render_pass);
 
             render_pass.set_pipeline(&image_pipeline.pipeline);
             render_pass.set_bind_group(0, &bind_group, &[]);
@@ -5400,7 +5674,7 @@ impl GpuRenderer {
             render_pass.draw(0..6, 0..instances.len() as u32);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     // ─────────────────────────────────────────────────────────────────────────
@@ -5693,7 +5967,7 @@ impl GpuRenderer {
             render_pass.draw(0..6, 0..1);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Apply multi-pass Kawase blur
@@ -5742,7 +6016,7 @@ impl GpuRenderer {
                     depth_or_array_layers: 1,
                 },
             );
-            self.queue.submit(std::iter::once(encoder.finish()));
+            submit(&self.queue, encoder);
             return output;
         }
 
@@ -5863,7 +6137,7 @@ impl GpuRenderer {
             render_pass.draw(0..6, 0..1);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Apply drop shadow effect
@@ -5948,7 +6222,7 @@ impl GpuRenderer {
             render_pass.draw(0..6, 0..1);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Apply glow effect to a texture
@@ -6028,7 +6302,7 @@ impl GpuRenderer {
             render_pass.draw(0..6, 0..1);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Helper to create common color matrices
@@ -6208,7 +6482,7 @@ impl GpuRenderer {
                     depth_or_array_layers: 1,
                 },
             );
-            self.queue.submit(std::iter::once(encoder.finish()));
+            submit(&self.queue, encoder);
             return output;
         }
 
@@ -6241,7 +6515,7 @@ impl GpuRenderer {
                     depth_or_array_layers: 1,
                 },
             );
-            self.queue.submit(std::iter::once(encoder.finish()));
+            submit(&self.queue, encoder);
         }
 
         for effect in effects {
@@ -6391,7 +6665,7 @@ impl GpuRenderer {
             render_pass.draw(0..6, 0..1);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Render a range of primitives to a target
@@ -6464,7 +6738,7 @@ impl GpuRenderer {
             render_pass.draw(0..6, 0..primitive_count as u32);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Render a range of primitives to a tight-fit texture with offset
@@ -6583,7 +6857,7 @@ impl GpuRenderer {
             render_pass.draw(0..6, 0..primitive_count);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
 
         // Restore viewport uniforms for subsequent operations
         let restore_uniforms = Uniforms {
@@ -6747,7 +7021,7 @@ impl GpuRenderer {
             render_pass.draw(0..6, 0..1);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Blit a texture to the target with blending
@@ -6830,7 +7104,7 @@ impl GpuRenderer {
             render_pass.draw(0..6, 0..1);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     /// Blit a specific region from source texture to target at given position
@@ -6959,7 +7233,7 @@ impl GpuRenderer {
             render_pass.draw(0..6, 0..1);
         }
 
-        self.queue.submit(std::iter::once(encoder.finish()));
+        submit(&self.queue, encoder);
     }
 
     // ─────────────────────────────────────────────────────────────────────────────
@@ -7193,7 +7467,7 @@ impl GpuRenderer {
             }
 
             // Submit
-            self.queue.submit(std::iter::once(encoder.finish()));
+            submit(&self.queue, encoder);
         }
     }
 
@@ -7261,7 +7535,7 @@ impl GpuRenderer {
             system.update(&self.queue, &mut encoder, &particle_viewport);
 
             // Submit compute work first
-            self.queue.submit(std::iter::once(encoder.finish()));
+            submit(&self.queue, encoder);
 
             // Create render encoder
             let mut render_encoder =
@@ -7303,7 +7577,7 @@ impl GpuRenderer {
             }
 
             // Submit render work
-            self.queue.submit(std::iter::once(render_encoder.finish()));
+            submit(&self.queue, render_encoder);
         }
     }
 }
@@ -7316,10 +7590,51 @@ impl Default for GpuRenderer {
     }
 }
 
+/// Clamp a scissor rectangle `[x, y, width, height]` to a viewport
+///
+/// Returns `None` when the clamped rectangle covers the whole viewport.
+fn clamp_scissor([x, y, w, h]: [u32; 4], (vw, vh): (u32, u32)) -> Option<[u32; 4]> {
+    let x = x.min(vw);
+    let y = y.min(vh);
+    let w = w.min(vw - x);
+    let h = h.min(vh - y);
+    if x == 0 && y == 0 && w == vw && h == vh {
+        None
+    } else {
+        Some([x, y, w, h])
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    // ─────────────────────────────────────────────────────────────────────────────
+    // Damage Scissor Tests
+    // ─────────────────────────────────────────────────────────────────────────────
+
+    #[test]
+    fn damage_scissor_is_clamped_to_viewport() {
+        // Inside the viewport: unchanged
+        assert_eq!(
+            clamp_scissor([10, 20, 30, 40], (800, 600)),
+            Some([10, 20, 30, 40])
+        );
+        // Overhanging the right and bottom edges: cut at the edge
+        assert_eq!(
+            clamp_scissor([700, 500, 300, 300], (800, 600)),
+            Some([700, 500, 100, 100])
+        );
+        // Entirely outside (e.g. after the window shrank): empty
+        assert_eq!(
+            clamp_scissor([900, 700, 50, 50], (800, 600)),
+            Some([800, 600, 0, 0])
+        );
+        // Covering the viewport: no scissor, render the whole frame
+        assert_eq!(clamp_scissor([0, 0, 800, 600], (800, 600)), None);
+        assert_eq!(clamp_scissor([0, 0, 4000, 4000], (800, 600)), None);
+    }
+
     // ─────────────────────────────────────────────────────────────────────────────
     // LayerTextureCache Tests
     // ─────────────────────────────────────────────────────────────────────────────
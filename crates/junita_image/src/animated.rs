@@ -0,0 +1,725 @@
+//! Animated image decoding (GIF, APNG, animated WebP)
+//!
+//! [`AnimatedImage`] keeps every frame of an animation as it's stored in the
+//! file: a sub-rectangle of the canvas with its own delay, disposal and blend
+//! ops. [`FrameCompositor`] replays those ops to produce the full canvas for
+//! any frame, so only one canvas needs to live in memory during playback.
+//!
+//! Still images load as a single frame, so callers can decode everything
+//! through [`AnimatedImage::load`] and check [`AnimatedImage::is_animated`].
+//!
+//! # Example
+//!
+//! ```ignore
+//! use junita_image::{AnimatedImage, FrameCompositor, ImageSource};
+//! use std::time::Duration;
+//!
+//! let image = AnimatedImage::load(ImageSource::file("spinner.gif"))?;
+//! let index = image.frame_at(Duration::from_millis(250));
+//!
+//! let mut compositor = FrameCompositor::new(image);
+//! let rgba = compositor.frame(index);
+//! ```
+
+use std::io::Cursor;
+use std::time::Duration;
+
+use image::{AnimationDecoder, ImageFormat};
+
+use crate::error::{ImageError, Result};
+use crate::loader::{decode_base64, read_file, ImageData};
+use crate::source::ImageSource;
+
+/// Delay used for frames that ask for (almost) no delay
+///
+/// Matches browsers, which treat GIF delays of 10ms or less as 100ms.
+const DEFAULT_FRAME_DELAY: Duration = Duration::from_millis(100);
+
+/// What happens to a frame's region before the next frame is drawn
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DisposalOp {
+    /// Leave the frame on the canvas
+    #[default]
+    None,
+    /// Clear the frame's region to transparent
+    Background,
+    /// Restore the frame's region to what it was before the frame was drawn
+    Previous,
+}
+
+/// How a frame's pixels are combined with the canvas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlendOp {
+    /// Replace the canvas pixels in the frame's region
+    #[default]
+    Source,
+    /// Alpha-composite the frame over the canvas
+    Over,
+}
+
+/// How many times an animation plays
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopCount {
+    /// Loop forever
+    #[default]
+    Infinite,
+    /// Play this many times, then hold the last frame
+    Finite(u32),
+}
+
+/// A single frame of an animated image
+#[derive(Debug, Clone)]
+pub struct AnimationFrame {
+    /// Frame pixels, covering only the frame's region
+    image: ImageData,
+    /// Horizontal offset of the region on the canvas
+    x: u32,
+    /// Vertical offset of the region on the canvas
+    y: u32,
+    /// How long the frame is shown
+    delay: Duration,
+    /// Disposal applied after the frame is shown
+    disposal: DisposalOp,
+    /// How the frame is drawn onto the canvas
+    blend: BlendOp,
+}
+
+impl AnimationFrame {
+    /// Create a frame drawn at `(x, y)` on the canvas
+    pub fn new(image: ImageData, x: u32, y: u32, delay: Duration) -> Self {
+        Self {
+            image,
+            x,
+            y,
+            delay,
+            disposal: DisposalOp::default(),
+            blend: BlendOp::default(),
+        }
+    }
+
+    /// Set the disposal op
+    pub fn with_disposal(mut self, disposal: DisposalOp) -> Self {
+        self.disposal = disposal;
+        self
+    }
+
+    /// Set the blend op
+    pub fn with_blend(mut self, blend: BlendOp) -> Self {
+        self.blend = blend;
+        self
+    }
+
+    /// Get the frame pixels
+    pub fn image(&self) -> &ImageData {
+        &self.image
+    }
+
+    /// Get the frame's offset on the canvas as (x, y)
+    pub fn offset(&self) -> (u32, u32) {
+        (self.x, self.y)
+    }
+
+    /// Get how long the frame is shown
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Get the disposal op
+    pub fn disposal(&self) -> DisposalOp {
+        self.disposal
+    }
+
+    /// Get the blend op
+    pub fn blend(&self) -> BlendOp {
+        self.blend
+    }
+}
+
+/// Decoded animated image with raw frames and timing
+#[derive(Debug, Clone)]
+pub struct AnimatedImage {
+    /// Canvas width in pixels
+    width: u32,
+    /// Canvas height in pixels
+    height: u32,
+    /// Frames in display order
+    frames: Vec<AnimationFrame>,
+    /// How many times the animation plays
+    loop_count: LoopCount,
+}
+
+impl AnimatedImage {
+    /// Create an animated image from frames
+    ///
+    /// Fails if there are no frames or a frame doesn't fit on the canvas.
+    pub fn new(
+        width: u32,
+        height: u32,
+        frames: Vec<AnimationFrame>,
+        loop_count: LoopCount,
+    ) -> Result<Self> {
+        if frames.is_empty() {
+            return Err(ImageError::Decode("Animation has no frames".to_string()));
+        }
+        for (index, frame) in frames.iter().enumerate() {
+            let (frame_width, frame_height) = frame.image.dimensions();
+            if frame.x + frame_width > width || frame.y + frame_height > height {
+                return Err(ImageError::Decode(format!(
+                    "Frame {} ({}x{} at {},{}) exceeds the {}x{} canvas",
+                    index, frame_width, frame_height, frame.x, frame.y, width, height
+                )));
+            }
+        }
+        Ok(Self {
+            width,
+            height,
+            frames,
+            loop_count,
+        })
+    }
+
+    /// Wrap a still image as a single-frame animation
+    pub fn from_still(image: ImageData) -> Self {
+        let (width, height) = image.dimensions();
+        Self {
+            width,
+            height,
+            frames: vec![AnimationFrame::new(image, 0, 0, Duration::ZERO)],
+            loop_count: LoopCount::Finite(1),
+        }
+    }
+
+    /// Load an animated image from a source (synchronous)
+    ///
+    /// Sources without an encoded file (emoji, raw RGBA) load as still images.
+    pub fn load(source: ImageSource) -> Result<Self> {
+        match source {
+            ImageSource::File(path) => Self::from_bytes(&read_file(&path)?),
+            ImageSource::Base64(data) => Self::from_base64(&data),
+            ImageSource::Bytes { data, format: _ } => Self::from_bytes(&data),
+            other => ImageData::load(other).map(Self::from_still),
+        }
+    }
+
+    /// Load an animated image from a source (asynchronous)
+    #[cfg(feature = "network")]
+    pub async fn load_async(source: ImageSource) -> Result<Self> {
+        match source {
+            ImageSource::Url(url) => Self::from_bytes(&crate::loader::fetch_url(&url).await?),
+            other => Self::load(other),
+        }
+    }
+
+    /// Decode an animated image from raw bytes
+    ///
+    /// GIF, APNG and animated WebP keep all their frames; any other format
+    /// decodes as a still image.
+    pub fn from_bytes(data: &[u8]) -> Result<Self> {
+        match image::guess_format(data) {
+            Ok(ImageFormat::Gif) => decode_gif(data),
+            Ok(ImageFormat::Png) => decode_apng(data),
+            Ok(ImageFormat::WebP) => decode_webp(data),
+            _ => ImageData::from_bytes(data).map(Self::from_still),
+        }
+    }
+
+    /// Decode an animated image from base64 or a data URI
+    pub fn from_base64(data: &str) -> Result<Self> {
+        Self::from_bytes(&decode_base64(data)?)
+    }
+
+    /// Get the canvas width
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Get the canvas height
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get canvas dimensions as (width, height)
+    pub fn dimensions(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Get the frames in display order
+    pub fn frames(&self) -> &[AnimationFrame] {
+        &self.frames
+    }
+
+    /// Get the number of frames
+    pub fn frame_count(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Get how many times the animation plays
+    pub fn loop_count(&self) -> LoopCount {
+        self.loop_count
+    }
+
+    /// Whether there's more than one frame to play
+    pub fn is_animated(&self) -> bool {
+        self.frames.len() > 1
+    }
+
+    /// Duration of a single play through all frames
+    pub fn duration(&self) -> Duration {
+        self.frames.iter().map(|frame| frame.delay).sum()
+    }
+
+    /// Total playback duration, or `None` if the animation loops forever
+    pub fn total_duration(&self) -> Option<Duration> {
+        match self.loop_count {
+            LoopCount::Infinite => None,
+            LoopCount::Finite(plays) => Some(self.duration() * plays.max(1)),
+        }
+    }
+
+    /// Index of the frame shown `elapsed` after playback started
+    ///
+    /// Once a finite animation has finished this is the last frame.
+    pub fn frame_at(&self, elapsed: Duration) -> usize {
+        let duration = self.duration();
+        if duration.is_zero() {
+            return 0;
+        }
+        if self.total_duration().is_some_and(|total| elapsed >= total) {
+            return self.frames.len() - 1;
+        }
+
+        let mut remaining = Duration::from_nanos((elapsed.as_nanos() % duration.as_nanos()) as u64);
+        for (index, frame) in self.frames.iter().enumerate() {
+            if remaining < frame.delay {
+                return index;
+            }
+            remaining -= frame.delay;
+        }
+        self.frames.len() - 1
+    }
+}
+
+/// Replays disposal and blend ops to render full canvas frames
+///
+/// Stepping forward one frame at a time only draws the new frame; seeking
+/// backwards replays the animation from the first frame.
+#[derive(Debug, Clone)]
+pub struct FrameCompositor {
+    /// The animation being composited
+    image: AnimatedImage,
+    /// RGBA canvas holding the current frame
+    canvas: Vec<u8>,
+    /// Index of the frame currently on the canvas
+    current: Option<usize>,
+    /// Canvas region saved for the current frame's `DisposalOp::Previous`
+    saved: Vec<u8>,
+}
+
+impl FrameCompositor {
+    /// Create a compositor for an animated image
+    pub fn new(image: AnimatedImage) -> Self {
+        let canvas = vec![0; image.width as usize * image.height as usize * 4];
+        Self {
+            image,
+            canvas,
+            current: None,
+            saved: Vec::new(),
+        }
+    }
+
+    /// Get the animation being composited
+    pub fn image(&self) -> &AnimatedImage {
+        &self.image
+    }
+
+    /// Index of the frame currently on the canvas
+    pub fn current_frame(&self) -> Option<usize> {
+        self.current
+    }
+
+    /// Render the full canvas for a frame and return its RGBA pixels
+    ///
+    /// Indices past the last frame are clamped.
+    pub fn frame(&mut self, index: usize) -> &[u8] {
+        let index = index.min(self.image.frames.len() - 1);
+        let start = match self.current {
+            Some(current) if current == index => return &self.canvas,
+            Some(current) if current < index => current + 1,
+            _ => {
+                self.canvas.fill(0);
+                self.current = None;
+                0
+            }
+        };
+        for next in start..=index {
+            self.draw_frame(next);
+        }
+        &self.canvas
+    }
+
+    /// Dispose the current frame and draw the next one
+    fn draw_frame(&mut self, index: usize) {
+        let width = self.image.width as usize;
+
+        if let Some(current) = self.current {
+            let frame = &self.image.frames[current];
+            match frame.disposal {
+                DisposalOp::None => {}
+                DisposalOp::Background => {
+                    for row in frame_rows(frame, width) {
+                        self.canvas[row].fill(0);
+                    }
+                }
+                DisposalOp::Previous => {
+                    let mut saved = self.saved.chunks_exact(frame.image.width() as usize * 4);
+                    for row in frame_rows(frame, width) {
+                        if let Some(pixels) = saved.next() {
+                            self.canvas[row].copy_from_slice(pixels);
+                        }
+                    }
+                }
+            }
+        }
+
+        let frame = &self.image.frames[index];
+        if frame.disposal == DisposalOp::Previous {
+            self.saved.clear();
+            for row in frame_rows(frame, width) {
+                self.saved.extend_from_slice(&self.canvas[row]);
+            }
+        }
+
+        let row_bytes = frame.image.width() as usize * 4;
+        if row_bytes > 0 {
+            for (row, src) in
+                frame_rows(frame, width).zip(frame.image.pixels().chunks_exact(row_bytes))
+            {
+                let dst = &mut self.canvas[row];
+                match frame.blend {
+                    BlendOp::Source => dst.copy_from_slice(src),
+                    BlendOp::Over => {
+                        for (dst, src) in dst.chunks_exact_mut(4).zip(src.chunks_exact(4)) {
+                            blend_over(dst, src);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.current = Some(index);
+    }
+}
+
+/// Byte ranges of each canvas row covered by a frame
+fn frame_rows(
+    frame: &AnimationFrame,
+    canvas_width: usize,
+) -> impl Iterator<Item = std::ops::Range<usize>> {
+    let x = frame.x as usize;
+    let y = frame.y as usize;
+    let width = frame.image.width() as usize;
+    (y..y + frame.image.height() as usize).map(move |row| {
+        let start = (row * canvas_width + x) * 4;
+        start..start + width * 4
+    })
+}
+
+/// Composite a straight-alpha RGBA pixel over another
+fn blend_over(dst: &mut [u8], src: &[u8]) {
+    match src[3] {
+        0 => {}
+        255 => dst.copy_from_slice(src),
+        src_alpha => {
+            let src_a = src_alpha as f32 / 255.0;
+            let dst_a = dst[3] as f32 / 255.0 * (1.0 - src_a);
+            let out_a = src_a + dst_a;
+            for channel in 0..3 {
+                let value = (src[channel] as f32 * src_a + dst[channel] as f32 * dst_a) / out_a;
+                dst[channel] = value.round() as u8;
+            }
+            dst[3] = (out_a * 255.0).round() as u8;
+        }
+    }
+}
+
+/// Frame delay, substituting the default for (near) zero delays
+fn frame_delay(delay: Duration) -> Duration {
+    if delay <= Duration::from_millis(10) {
+        DEFAULT_FRAME_DELAY
+    } else {
+        delay
+    }
+}
+
+fn decode_error(err: impl std::fmt::Display) -> ImageError {
+    ImageError::Decode(err.to_string())
+}
+
+/// Decode GIF frames with their raw disposal ops
+fn decode_gif(data: &[u8]) -> Result<AnimatedImage> {
+    let mut options = gif::DecodeOptions::new();
+    options.set_color_output(gif::ColorOutput::RGBA);
+    let mut decoder = options.read_info(Cursor::new(data)).map_err(decode_error)?;
+    let (width, height) = (decoder.width() as u32, decoder.height() as u32);
+
+    let mut frames = Vec::new();
+    while let Some(frame) = decoder.read_next_frame().map_err(decode_error)? {
+        let image = ImageData::from_rgba(
+            frame.buffer.to_vec(),
+            frame.width as u32,
+            frame.height as u32,
+        )?;
+        let disposal = match frame.dispose {
+            gif::DisposalMethod::Any | gif::DisposalMethod::Keep => DisposalOp::None,
+            gif::DisposalMethod::Background => DisposalOp::Background,
+            gif::DisposalMethod::Previous => DisposalOp::Previous,
+        };
+        // GIF delays are in hundredths of a second
+        let delay = frame_delay(Duration::from_millis(frame.delay as u64 * 10));
+        frames.push(
+            AnimationFrame::new(image, frame.left as u32, frame.top as u32, delay)
+                .with_disposal(disposal)
+                .with_blend(BlendOp::Over),
+        );
+    }
+
+    let loop_count = match decoder.repeat() {
+        gif::Repeat::Finite(0) | gif::Repeat::Infinite => LoopCount::Infinite,
+        gif::Repeat::Finite(plays) => LoopCount::Finite(plays as u32),
+    };
+    AnimatedImage::new(width, height, frames, loop_count)
+}
+
+/// Decode APNG frames with their raw disposal and blend ops
+///
+/// Plain PNGs decode as a still image.
+fn decode_apng(data: &[u8]) -> Result<AnimatedImage> {
+    let mut decoder = png::Decoder::new(Cursor::new(data));
+    decoder.set_transformations(png::Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info().map_err(decode_error)?;
+
+    let Some(animation) = reader.info().animation_control().copied() else {
+        return ImageData::from_bytes(data).map(AnimatedImage::from_still);
+    };
+    let (width, height) = (reader.info().width, reader.info().height);
+    let mut buffer = vec![
+        0;
+        reader
+            .output_buffer_size()
+            .ok_or_else(|| decode_error("PNG too large"))?
+    ];
+
+    // The default image is only part of the animation if a frame control
+    // chunk precedes it
+    if reader.info().frame_control().is_none() {
+        reader.next_frame(&mut buffer).map_err(decode_error)?;
+    }
+
+    let mut frames = Vec::with_capacity(animation.num_frames as usize);
+    for index in 0..animation.num_frames {
+        let output = reader.next_frame(&mut buffer).map_err(decode_error)?;
+        let control = *reader
+            .info()
+            .frame_control()
+            .ok_or_else(|| decode_error("APNG frame without frame control"))?;
+
+        let pixels = to_rgba(&buffer[..output.buffer_size()], output.color_type)?;
+        let image = ImageData::from_rgba(pixels, output.width, output.height)?;
+
+        let delay_den = if control.delay_den == 0 {
+            100
+        } else {
+            control.delay_den
+        };
+        let delay = frame_delay(Duration::from_secs_f64(
+            control.delay_num as f64 / delay_den as f64,
+        ));
+        // A first frame can't restore to a previous canvas
+        let disposal = match control.dispose_op {
+            png::DisposeOp::None => DisposalOp::None,
+            png::DisposeOp::Previous if index > 0 => DisposalOp::Previous,
+            png::DisposeOp::Background | png::DisposeOp::Previous => DisposalOp::Background,
+        };
+        let blend = match control.blend_op {
+            png::BlendOp::Source => BlendOp::Source,
+            png::BlendOp::Over => BlendOp::Over,
+        };
+        frames.push(
+            AnimationFrame::new(image, control.x_offset, control.y_offset, delay)
+                .with_disposal(disposal)
+                .with_blend(blend),
+        );
+    }
+
+    let loop_count = match animation.num_plays {
+        0 => LoopCount::Infinite,
+        plays => LoopCount::Finite(plays),
+    };
+    AnimatedImage::new(width, height, frames, loop_count)
+}
+
+/// Expand 8-bit PNG output to RGBA
+fn to_rgba(data: &[u8], color_type: png::ColorType) -> Result<Vec<u8>> {
+    let pixels = match color_type {
+        png::ColorType::Rgba => data.to_vec(),
+        png::ColorType::Rgb => data
+            .chunks_exact(3)
+            .flat_map(|p| [p[0], p[1], p[2], 255])
+            .collect(),
+        png::ColorType::GrayscaleAlpha => data
+            .chunks_exact(2)
+            .flat_map(|p| [p[0], p[0], p[0], p[1]])
+            .collect(),
+        png::ColorType::Grayscale => data.iter().flat_map(|&v| [v, v, v, 255]).collect(),
+        png::ColorType::Indexed => {
+            return Err(ImageError::UnsupportedFormat(
+                "Unexpanded indexed APNG frame".to_string(),
+            ))
+        }
+    };
+    Ok(pixels)
+}
+
+/// Decode animated WebP
+///
+/// The WebP decoder only exposes composited frames, so each frame covers
+/// the full canvas and replaces it.
+fn decode_webp(data: &[u8]) -> Result<AnimatedImage> {
+    let decoder = image::codecs::webp::WebPDecoder::new(Cursor::new(data))?;
+    if !decoder.has_animation() {
+        return ImageData::from_bytes(data).map(AnimatedImage::from_still);
+    }
+
+    let loop_count = match decoder.loop_count() {
+        image::metadata::LoopCount::Infinite => LoopCount::Infinite,
+        image::metadata::LoopCount::Finite(plays) => LoopCount::Finite(plays.get()),
+    };
+
+    let mut frames = Vec::new();
+    for frame in decoder.into_frames() {
+        let frame = frame?;
+        let (numer, denom) = frame.delay().numer_denom_ms();
+        let delay = frame_delay(Duration::from_secs_f64(
+            numer as f64 / denom.max(1) as f64 / 1000.0,
+        ));
+        let buffer = frame.into_buffer();
+        let (width, height) = buffer.dimensions();
+        let image = ImageData::from_rgba(buffer.into_raw(), width, height)?;
+        frames.push(AnimationFrame::new(image, 0, 0, delay));
+    }
+
+    let (width, height) = frames
+        .first()
+        .map(|frame| frame.image.dimensions())
+        .unwrap_or_default();
+    AnimatedImage::new(width, height, frames, loop_count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, rgba: [u8; 4]) -> ImageData {
+        let pixels = rgba.repeat((width * height) as usize);
+        ImageData::from_rgba(pixels, width, height).unwrap()
+    }
+
+    fn pixel(canvas: &[u8], width: u32, x: u32, y: u32) -> [u8; 4] {
+        let i = ((y * width + x) * 4) as usize;
+        [canvas[i], canvas[i + 1], canvas[i + 2], canvas[i + 3]]
+    }
+
+    #[test]
+    fn test_frame_at_loops() {
+        let frames = vec![
+            AnimationFrame::new(
+                solid(1, 1, [255, 0, 0, 255]),
+                0,
+                0,
+                Duration::from_millis(100),
+            ),
+            AnimationFrame::new(
+                solid(1, 1, [0, 255, 0, 255]),
+                0,
+                0,
+                Duration::from_millis(50),
+            ),
+        ];
+        let image = AnimatedImage::new(1, 1, frames, LoopCount::Finite(2)).unwrap();
+
+        assert_eq!(image.duration(), Duration::from_millis(150));
+        assert_eq!(image.frame_at(Duration::from_millis(99)), 0);
+        assert_eq!(image.frame_at(Duration::from_millis(120)), 1);
+        assert_eq!(image.frame_at(Duration::from_millis(160)), 0);
+        // Holds the last frame once both plays are done
+        assert_eq!(image.frame_at(Duration::from_millis(400)), 1);
+    }
+
+    #[test]
+    fn test_frame_outside_canvas_is_rejected() {
+        let frame = AnimationFrame::new(solid(2, 2, [0; 4]), 1, 0, Duration::from_millis(100));
+        assert!(AnimatedImage::new(2, 2, vec![frame], LoopCount::Infinite).is_err());
+    }
+
+    #[test]
+    fn test_compositor_disposal() {
+        let red = [255, 0, 0, 255];
+        let blue = [0, 0, 255, 255];
+        let delay = Duration::from_millis(100);
+        let frames = vec![
+            AnimationFrame::new(solid(2, 2, red), 0, 0, delay),
+            AnimationFrame::new(solid(1, 1, blue), 1, 1, delay).with_disposal(DisposalOp::Previous),
+            AnimationFrame::new(solid(1, 1, blue), 0, 0, delay)
+                .with_disposal(DisposalOp::Background),
+            AnimationFrame::new(solid(1, 1, [0; 4]), 1, 0, delay).with_blend(BlendOp::Over),
+        ];
+        let image = AnimatedImage::new(2, 2, frames, LoopCount::Infinite).unwrap();
+        let mut compositor = FrameCompositor::new(image);
+
+        assert_eq!(pixel(compositor.frame(1), 2, 1, 1), blue);
+        // Frame 1 restores its region before frame 2 draws
+        let canvas = compositor.frame(2);
+        assert_eq!(pixel(canvas, 2, 1, 1), red);
+        assert_eq!(pixel(canvas, 2, 0, 0), blue);
+        // Frame 2 clears its region; frame 3 blends a transparent pixel
+        let canvas = compositor.frame(3);
+        assert_eq!(pixel(canvas, 2, 0, 0), [0; 4]);
+        assert_eq!(pixel(canvas, 2, 1, 0), red);
+        // Seeking backwards replays from the start
+        assert_eq!(pixel(compositor.frame(0), 2, 0, 0), red);
+    }
+
+    #[test]
+    fn test_decode_gif_frames() {
+        let mut data = Vec::new();
+        {
+            let mut encoder = gif::Encoder::new(&mut data, 2, 2, &[]).unwrap();
+            encoder.set_repeat(gif::Repeat::Finite(3)).unwrap();
+            for (rgba, dispose) in [
+                ([255, 0, 0, 255], gif::DisposalMethod::Keep),
+                ([0, 0, 255, 255], gif::DisposalMethod::Background),
+            ] {
+                let mut pixels = rgba.repeat(4);
+                let mut frame = gif::Frame::from_rgba_speed(2, 2, &mut pixels, 10);
+                frame.delay = 5;
+                frame.dispose = dispose;
+                encoder.write_frame(&frame).unwrap();
+            }
+        }
+
+        let image = AnimatedImage::from_bytes(&data).unwrap();
+        assert_eq!(image.frame_count(), 2);
+        assert_eq!(image.loop_count(), LoopCount::Finite(3));
+        assert_eq!(image.frames()[0].delay(), Duration::from_millis(50));
+        assert_eq!(image.frames()[1].disposal(), DisposalOp::Background);
+        assert_eq!(image.frames()[0].blend(), BlendOp::Over);
+    }
+    #[test]
+    fn test_still_image_is_single_frame() {
+        // 1x1 red PNG
+        let data_uri = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAYAAAAfFcSJAAAADUlEQVR42mP8z8DwHwAFBQIAX8jx0gAAAABJRU5ErkJggg==";
+        let image = AnimatedImage::from_base64(data_uri).unwrap();
+        assert!(!image.is_animated());
+        assert_eq!(image.dimensions(), (1, 1));
+    }
+}
@@ -6,6 +6,7 @@
 //!
 //! - Load images from file paths, URLs, and base64 data
 //! - Support for PNG, JPEG, GIF, WebP, BMP formats
+//! - Animated GIF, APNG and WebP playback via [`AnimatedImage`]
 //! - CSS-style object-fit options (cover, contain, fill, etc.)
 //! - Image filters: grayscale, sepia, brightness, contrast, blur, etc.
 //!
@@ -24,10 +25,14 @@
 //! let data = ImageData::load_async(ImageSource::Url("https://example.com/image.png".into())).await?;
 //! ```
 
+mod animated;
 mod error;
 mod loader;
 mod source;
 
+pub use animated::{
+    AnimatedImage, AnimationFrame, BlendOp, DisposalOp, FrameCompositor, LoopCount,
+};
 pub use error::{ImageError, Result};
 pub use loader::ImageData;
 pub use source::ImageSource;
@@ -4,6 +4,7 @@ use crate::error::{ImageError, Result};
 use crate::source::ImageSource;
 use base64::Engine;
 use image::{DynamicImage, GenericImageView};
+use std::path::Path;
 
 /// Decoded image data ready for GPU upload
 #[derive(Debug, Clone)]
@@ -43,30 +44,7 @@ impl ImageData {
     /// without it. Use `load_async` for URL sources.
     pub fn load(source: ImageSource) -> Result<Self> {
         match source {
-            ImageSource::File(path) => {
-                // Try platform asset loader first (works cross-platform)
-                #[cfg(feature = "platform")]
-                {
-                    if let Some(loader) = junita_platform::assets::global_asset_loader() {
-                        let asset_path =
-                            junita_platform::AssetPath::from(path.to_string_lossy().to_string());
-                        match loader.load(&asset_path) {
-                            Ok(data) => return Self::from_bytes(&data),
-                            Err(e) => {
-                                tracing::debug!(
-                                    "Platform asset loader failed, trying filesystem: {}",
-                                    e
-                                );
-                            }
-                        }
-                    }
-                }
-
-                // Fallback to direct filesystem access (desktop only)
-                let data = std::fs::read(&path)
-                    .map_err(|e| ImageError::FileLoad(format!("{}: {}", path.display(), e)))?;
-                Self::from_bytes(&data)
-            }
+            ImageSource::File(path) => Self::from_bytes(&read_file(&path)?),
 
             ImageSource::Base64(data) => Self::from_base64(&data),
 
@@ -151,25 +129,7 @@ impl ImageData {
     #[cfg(feature = "network")]
     pub async fn load_async(source: ImageSource) -> Result<Self> {
         match source {
-            ImageSource::Url(url) => {
-                let response = reqwest::get(&url)
-                    .await
-                    .map_err(|e| ImageError::Network(e.to_string()))?;
-
-                if !response.status().is_success() {
-                    return Err(ImageError::Network(format!(
-                        "HTTP error: {}",
-                        response.status()
-                    )));
-                }
-
-                let bytes = response
-                    .bytes()
-                    .await
-                    .map_err(|e| ImageError::Network(e.to_string()))?;
-
-                Self::from_bytes(&bytes)
-            }
+            ImageSource::Url(url) => Self::from_bytes(&fetch_url(&url).await?),
 
             // For non-URL sources, delegate to sync load
             other => Self::load(other),
@@ -188,19 +148,7 @@ impl ImageData {
     /// - `iVBORw0KGgo...` (plain base64)
     /// - `data:image/png;base64,iVBORw0KGgo...` (data URI)
     pub fn from_base64(data: &str) -> Result<Self> {
-        // Handle data URI format
-        let base64_data = if data.starts_with("data:") {
-            // Find the base64 marker
-            data.find(";base64,")
-                .map(|pos| &data[pos + 8..])
-                .ok_or_else(|| ImageError::Base64("Invalid data URI format".to_string()))?
-        } else {
-            data
-        };
-
-        // Decode base64
-        let bytes = base64::engine::general_purpose::STANDARD.decode(base64_data)?;
-        Self::from_bytes(&bytes)
+        Self::from_bytes(&decode_base64(data)?)
     }
 
     /// Convert a DynamicImage to ImageData
@@ -252,6 +200,65 @@ impl ImageData {
     }
 }
 
+/// Read an image file, preferring the platform asset loader
+///
+/// Falls back to direct filesystem access (desktop only) when no asset
+/// loader is configured or it can't find the path.
+pub(crate) fn read_file(path: &Path) -> Result<Vec<u8>> {
+    // Try platform asset loader first (works cross-platform)
+    #[cfg(feature = "platform")]
+    {
+        if let Some(loader) = junita_platform::assets::global_asset_loader() {
+            let asset_path = junita_platform::AssetPath::from(path.to_string_lossy().to_string());
+            match loader.load(&asset_path) {
+                Ok(data) => return Ok(data),
+                Err(e) => {
+                    tracing::debug!("Platform asset loader failed, trying filesystem: {}", e);
+                }
+            }
+        }
+    }
+
+    std::fs::read(path).map_err(|e| ImageError::FileLoad(format!("{}: {}", path.display(), e)))
+}
+
+/// Decode plain base64 or a `data:` URI into raw bytes
+pub(crate) fn decode_base64(data: &str) -> Result<Vec<u8>> {
+    // Handle data URI format
+    let base64_data = if data.starts_with("data:") {
+        // Find the base64 marker
+        data.find(";base64,")
+            .map(|pos| &data[pos + 8..])
+            .ok_or_else(|| ImageError::Base64("Invalid data URI format".to_string()))?
+    } else {
+        data
+    };
+
+    Ok(base64::engine::general_purpose::STANDARD.decode(base64_data)?)
+}
+
+/// Download the raw bytes of an image URL
+#[cfg(feature = "network")]
+pub(crate) async fn fetch_url(url: &str) -> Result<Vec<u8>> {
+    let response = reqwest::get(url)
+        .await
+        .map_err(|e| ImageError::Network(e.to_string()))?;
+
+    if !response.status().is_success() {
+        return Err(ImageError::Network(format!(
+            "HTTP error: {}",
+            response.status()
+        )));
+    }
+
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| ImageError::Network(e.to_string()))?;
+
+    Ok(bytes.to_vec())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,119 @@
+//! Compiled artifact types
+//!
+//! Output of the Junita compiler (`junita_cli`), consumed by the interpreter.
+//! Expressions and render bodies are kept as source text and parsed when the
+//! artifact is instantiated.
+
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+/// Compiled artifact from Junita compiler
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompiledArtifact {
+    /// Source file that was compiled
+    pub source_file: PathBuf,
+    /// Compiled widget definitions
+    pub widgets: Vec<WidgetDefinition>,
+    /// State machines defined in the file
+    pub machines: Vec<MachineDef>,
+    /// Animations defined in the file
+    pub animations: Vec<AnimationDef>,
+    /// Springs defined in the file
+    pub springs: Vec<SpringDef>,
+    /// Timestamp of compilation
+    pub timestamp: u64,
+    /// Checksum for detecting changes
+    pub checksum: String,
+}
+
+impl CompiledArtifact {
+    /// Find a widget by name
+    pub fn widget(&self, name: &str) -> Option<&WidgetDefinition> {
+        self.widgets.iter().find(|w| w.name == name)
+    }
+
+    /// Find a state machine by name
+    pub fn machine(&self, name: &str) -> Option<&MachineDef> {
+        self.machines.iter().find(|m| m.name == name)
+    }
+
+    /// Find a spring by name
+    pub fn spring(&self, name: &str) -> Option<&SpringDef> {
+        self.springs.iter().find(|s| s.name == name)
+    }
+}
+
+/// Parsed widget definition from .junita file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WidgetDefinition {
+    pub name: String,
+    pub properties: Vec<PropDef>,
+    pub state_vars: Vec<StateVar>,
+    pub derived_vars: Vec<DerivedVar>,
+    pub machines: Vec<String>,
+    pub animations: Vec<String>,
+    pub springs: Vec<String>,
+    pub render_body: Option<String>,
+    pub paint_body: Option<String>,
+}
+
+/// Property definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PropDef {
+    pub name: String,
+    pub prop_type: String,
+    pub default_value: Option<String>,
+}
+
+/// State variable definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StateVar {
+    pub name: String,
+    pub var_type: String,
+    pub initial_value: String,
+}
+
+/// Derived value definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DerivedVar {
+    pub name: String,
+    pub var_type: String,
+    pub expression: String,
+    pub dependencies: Vec<String>,
+}
+
+/// State machine definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MachineDef {
+    pub name: String,
+    pub states: Vec<String>,
+    pub initial_state: String,
+    pub transitions: Vec<Transition>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transition {
+    pub from: String,
+    pub to: String,
+    pub event: String,
+}
+
+/// Animation definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnimationDef {
+    pub name: String,
+    pub duration_ms: u32,
+    pub easing: String,
+}
+
+/// Spring animation definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpringDef {
+    pub name: String,
+    pub stiffness: f32,
+    pub damping: f32,
+    pub mass: f32,
+    /// Value the spring starts at (`@spring name: f32 = 1.0 { .. }`)
+    #[serde(default)]
+    pub initial_value: Option<f32>,
+}
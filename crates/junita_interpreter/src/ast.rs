@@ -0,0 +1,106 @@
+//! AST the interpreter walks
+//!
+//! Render bodies and expressions are parsed with `junita_syntax` and lowered
+//! to these types by [`crate::lower`].
+
+use std::sync::Arc;
+
+use crate::value::Value;
+
+/// Expression
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Expr {
+    Literal(Value),
+    /// String with `{expr}` segments
+    Interpolated(Vec<Segment>),
+    Ident(String),
+    Unary(UnaryOp, Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    /// `receiver.method(args)`
+    Method(Box<Expr>, String, Vec<Expr>),
+    /// `function(args)`
+    Call(String, Vec<Expr>),
+    /// `{ statements }` or `@action { statements }`, run by event handlers
+    Handler(Arc<Vec<Stmt>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Segment {
+    Lit(String),
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum UnaryOp {
+    Neg,
+    Not,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum BinaryOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    And,
+    Or,
+}
+
+/// Statement in a handler block
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Stmt {
+    Assign {
+        target: String,
+        op: AssignOp,
+        value: Expr,
+    },
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum AssignOp {
+    Set,
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl AssignOp {
+    /// Binary operator applied by compound assignment
+    pub(crate) fn binary(self) -> Option<BinaryOp> {
+        match self {
+            AssignOp::Set => None,
+            AssignOp::Add => Some(BinaryOp::Add),
+            AssignOp::Sub => Some(BinaryOp::Sub),
+            AssignOp::Mul => Some(BinaryOp::Mul),
+            AssignOp::Div => Some(BinaryOp::Div),
+        }
+    }
+}
+
+/// Node of a render body
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Node {
+    /// `Name { props.. children.. }`
+    Element {
+        name: String,
+        props: Vec<(String, Expr)>,
+        children: Vec<Node>,
+    },
+    /// `if cond { .. } else { .. }` (`when` is accepted as an alias)
+    If {
+        condition: Expr,
+        then: Vec<Node>,
+        otherwise: Vec<Node>,
+    },
+    /// Bare string child, shorthand for `Text { content: ".." }`
+    Text(Expr),
+}
@@ -0,0 +1,298 @@
+//! Built-in elements
+//!
+//! Maps DSL element names and props onto `Div` builders. Every element is a
+//! `Div`; `Text` and `Button` add a text node for their content.
+//!
+//! | Element | Builds |
+//! |---|---|
+//! | `Column`, `VStack` | `div().flex_col()` |
+//! | `Row`, `HStack` | `div().flex_row()` |
+//! | `Center` | full-size column centering its children |
+//! | `Window`, `Screen` | full-size column |
+//! | `Text`, `Label` | `div()` around `text(content)` |
+//! | `Button` | clickable row around `text(label)` |
+//! | `Spacer` | `div().flex_grow()` |
+//! | `Box`, `Container`, `Stack` | `div()` |
+
+use std::rc::Rc;
+use std::sync::Arc;
+
+use junita_core::Color;
+use junita_layout::prelude::*;
+
+use crate::ast::Stmt;
+use crate::instance::WidgetInstance;
+use crate::value::Value;
+
+/// Evaluated prop of a built-in element
+pub(crate) enum PropValue {
+    Value(Value),
+    Handler(Arc<Vec<Stmt>>),
+}
+
+/// Text properties collected while applying props
+struct TextProps {
+    content: Option<String>,
+    size: Option<f32>,
+    color: Option<Color>,
+    weight: Option<FontWeight>,
+    opacity: f32,
+}
+
+/// Build a built-in element
+pub(crate) fn build_element(
+    name: &str,
+    props: Vec<(String, PropValue)>,
+    children: Vec<Div>,
+    instance: &Rc<WidgetInstance>,
+) -> Div {
+    let mut text_props = TextProps {
+        content: None,
+        size: None,
+        color: None,
+        weight: None,
+        opacity: 1.0,
+    };
+
+    let mut el = match name {
+        "Column" | "VStack" => div().flex_col(),
+        "Row" | "HStack" => div().flex_row(),
+        "Center" => div()
+            .flex_col()
+            .items_center()
+            .justify_center()
+            .w_full()
+            .h_full(),
+        "Window" | "Screen" => div().flex_col().w_full().h_full(),
+        "Button" => {
+            text_props.color = Some(Color::WHITE);
+            div()
+                .flex_row()
+                .items_center()
+                .justify_center()
+                .padding_x_px(16.0)
+                .padding_y_px(8.0)
+                .rounded(6.0)
+                .bg(Color::from_hex(0x3b82f6))
+                .cursor_pointer()
+        }
+        "Spacer" => div().flex_grow(),
+        "Text" | "Label" | "Box" | "Container" | "Stack" => div(),
+        _ => {
+            tracing::debug!("Unknown element `{}`, rendering as a box", name);
+            div()
+        }
+    };
+
+    for (prop, value) in props {
+        el = match value {
+            PropValue::Handler(stmts) => attach_handler(el, &prop, stmts, instance),
+            PropValue::Value(value) => apply_prop(el, &prop, &value, &mut text_props),
+        };
+    }
+
+    if let Some(content) = text_props.content.take() {
+        let mut node = text(content);
+        if let Some(size) = text_props.size {
+            node = node.size(size);
+        }
+        if let Some(weight) = text_props.weight {
+            node = node.weight(weight);
+        }
+        if let Some(color) = text_props.color {
+            node = node.color(color);
+        }
+        el = el.child(node);
+    }
+    if text_props.opacity < 1.0 {
+        el = el.opacity(text_props.opacity);
+    }
+
+    for child in children {
+        el = el.child(child);
+    }
+    el
+}
+
+/// Wrap a bare string child
+pub(crate) fn text_element(content: String) -> Div {
+    div().child(text(content))
+}
+
+fn attach_handler(
+    el: Div,
+    prop: &str,
+    stmts: Arc<Vec<Stmt>>,
+    instance: &Rc<WidgetInstance>,
+) -> Div {
+    let instance = Rc::clone(instance);
+    let handler = move |_: &EventContext| {
+        if let Err(err) = instance.run(&stmts) {
+            tracing::warn!("Handler failed: {}", err);
+        }
+    };
+    match prop {
+        "on_click" | "on_press" | "on_tap" => el.on_click(handler),
+        "on_mouse_down" | "on_press_start" => el.on_mouse_down(handler),
+        "on_mouse_up" | "on_press_end" => el.on_mouse_up(handler),
+        "on_hover" | "on_hover_enter" | "on_pointer_enter" => el.on_hover_enter(handler),
+        "on_hover_leave" | "on_pointer_leave" => el.on_hover_leave(handler),
+        _ => {
+            tracing::warn!("Unknown event `{}`", prop);
+            el
+        }
+    }
+}
+
+fn apply_prop(el: Div, prop: &str, value: &Value, text_props: &mut TextProps) -> Div {
+    let needs_number = matches!(
+        prop,
+        "font_size"
+            | "size"
+            | "opacity"
+            | "width"
+            | "w"
+            | "height"
+            | "h"
+            | "min_width"
+            | "min_height"
+            | "padding"
+            | "padding_x"
+            | "padding_y"
+            | "margin"
+            | "margin_top"
+            | "margin_bottom"
+            | "margin_left"
+            | "margin_right"
+            | "spacing"
+            | "gap"
+            | "grow"
+            | "flex"
+            | "border_radius"
+            | "radius"
+            | "rounded"
+            | "border_width"
+    );
+    let needs_color = matches!(
+        prop,
+        "color" | "text_color" | "background" | "bg" | "border_color"
+    );
+
+    let number = value.as_f32();
+    let color = value.as_color();
+    if (needs_number && number.is_none()) || (needs_color && color.is_none()) {
+        return invalid(el, prop, value);
+    }
+    let n = number.unwrap_or_default();
+    let c = color.unwrap_or_default();
+
+    match prop {
+        // Content
+        "content" | "text" | "label" => {
+            text_props.content = Some(value.to_string());
+            el
+        }
+        "font_size" | "size" => {
+            text_props.size = Some(n);
+            el
+        }
+        "color" | "text_color" => {
+            text_props.color = Some(c);
+            el
+        }
+        "font_weight" | "weight" => match font_weight(value) {
+            Some(weight) => {
+                text_props.weight = Some(weight);
+                el
+            }
+            None => invalid(el, prop, value),
+        },
+        "opacity" => {
+            text_props.opacity = n.clamp(0.0, 1.0);
+            el
+        }
+
+        // Box model
+        "width" | "w" => el.w(n),
+        "height" | "h" => el.h(n),
+        "min_width" => el.min_w(n),
+        "min_height" => el.min_h(n),
+        "padding" => el.p_px(n),
+        "padding_x" => el.padding_x_px(n),
+        "padding_y" => el.padding_y_px(n),
+        "margin" => el.m_px(n),
+        "margin_top" => el.mt(n / 4.0),
+        "margin_bottom" => el.mb(n / 4.0),
+        "margin_left" => el.ml(n / 4.0),
+        "margin_right" => el.mr(n / 4.0),
+
+        // Flex
+        "spacing" | "gap" => el.gap_px(n),
+        "grow" | "flex" => el.flex_grow_value(n),
+        "direction" => match value.to_string().as_str() {
+            "row" => el.flex_row(),
+            "column" | "col" => el.flex_col(),
+            _ => invalid(el, prop, value),
+        },
+        "align" => match value.to_string().as_str() {
+            "start" => el.items_start(),
+            "center" => el.items_center(),
+            "end" => el.items_end(),
+            "stretch" => el.items_stretch(),
+            _ => invalid(el, prop, value),
+        },
+        "justify" => match value.to_string().as_str() {
+            "start" => el.justify_start(),
+            "center" => el.justify_center(),
+            "end" => el.justify_end(),
+            "between" | "space_between" => el.justify_between(),
+            "around" | "space_around" => el.justify_around(),
+            "evenly" | "space_evenly" => el.justify_evenly(),
+            _ => invalid(el, prop, value),
+        },
+        // Flexbox is the only layout
+        "layout" => el,
+
+        // Visuals
+        "background" | "bg" => el.bg(c),
+        "border_radius" | "radius" | "rounded" => el.rounded(n),
+        "border_color" => el.border_color(c),
+        "border_width" => el.border_width(n),
+
+        _ => {
+            tracing::debug!("Unknown prop `{}`", prop);
+            el
+        }
+    }
+}
+
+fn invalid(el: Div, prop: &str, value: &Value) -> Div {
+    tracing::warn!("Invalid value `{}` for prop `{}`", value, prop);
+    el
+}
+
+fn font_weight(value: &Value) -> Option<FontWeight> {
+    if let Some(n) = value.as_f64() {
+        return Some(match n as u32 {
+            0..=149 => FontWeight::Thin,
+            150..=249 => FontWeight::ExtraLight,
+            250..=349 => FontWeight::Light,
+            350..=449 => FontWeight::Normal,
+            450..=549 => FontWeight::Medium,
+            550..=649 => FontWeight::SemiBold,
+            650..=749 => FontWeight::Bold,
+            750..=849 => FontWeight::ExtraBold,
+            _ => FontWeight::Black,
+        });
+    }
+    Some(match value.to_string().as_str() {
+        "thin" => FontWeight::Thin,
+        "light" => FontWeight::Light,
+        "normal" | "regular" => FontWeight::Normal,
+        "medium" => FontWeight::Medium,
+        "semibold" => FontWeight::SemiBold,
+        "bold" => FontWeight::Bold,
+        "black" => FontWeight::Black,
+        _ => return None,
+    })
+}
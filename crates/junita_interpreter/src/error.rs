@@ -0,0 +1,51 @@
+//! Interpreter error types
+
+use thiserror::Error;
+
+/// Errors that can occur while parsing or running an artifact
+#[derive(Error, Debug)]
+pub enum InterpreterError {
+    /// Render body or expression failed to parse
+    #[error("Parse error at offset {offset}: {message}")]
+    Parse { message: String, offset: usize },
+
+    /// The artifact has no widget with this name
+    #[error("Unknown widget `{0}`")]
+    UnknownWidget(String),
+
+    /// The widget has no `@render` block
+    #[error("Widget `{0}` has no @render block")]
+    MissingRender(String),
+
+    /// A widget renders itself, directly or through its children
+    #[error("Widget `{0}` instantiates itself")]
+    Recursive(String),
+
+    /// Identifier is not a prop, state, derived value, spring or machine
+    #[error("Unknown identifier `{0}`")]
+    UnknownIdentifier(String),
+
+    /// Assignment to something other than a state variable or spring
+    #[error("Cannot assign to `{0}`")]
+    InvalidAssignment(String),
+
+    /// Operation on values of the wrong type
+    #[error("Type error: {0}")]
+    Type(String),
+
+    /// State snapshot could not be encoded
+    #[error("Snapshot error: {0}")]
+    Snapshot(#[from] serde_json::Error),
+}
+
+impl InterpreterError {
+    pub(crate) fn parse(message: impl Into<String>, offset: usize) -> Self {
+        Self::Parse {
+            message: message.into(),
+            offset,
+        }
+    }
+}
+
+/// Result type for interpreter operations
+pub type Result<T> = std::result::Result<T, InterpreterError>;
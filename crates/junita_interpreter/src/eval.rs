@@ -0,0 +1,242 @@
+//! Expression evaluation
+
+use crate::ast::{BinaryOp, Expr, Segment, UnaryOp};
+use crate::error::{InterpreterError, Result};
+use crate::value::Value;
+
+/// Resolves identifiers during evaluation
+pub(crate) trait Scope {
+    fn lookup(&self, name: &str) -> Result<Value>;
+}
+
+/// Scope with no identifiers, for literals and defaults
+pub(crate) struct EmptyScope;
+
+impl Scope for EmptyScope {
+    fn lookup(&self, name: &str) -> Result<Value> {
+        Err(InterpreterError::UnknownIdentifier(name.to_string()))
+    }
+}
+
+/// Evaluate an expression
+pub(crate) fn eval(expr: &Expr, scope: &dyn Scope) -> Result<Value> {
+    match expr {
+        Expr::Literal(value) => Ok(value.clone()),
+        Expr::Interpolated(segments) => {
+            let mut out = String::new();
+            for segment in segments {
+                match segment {
+                    Segment::Lit(text) => out.push_str(text),
+                    Segment::Expr(expr) => out.push_str(&eval(expr, scope)?.to_string()),
+                }
+            }
+            Ok(Value::Str(out))
+        }
+        Expr::Ident(name) => scope.lookup(name),
+        Expr::Unary(op, operand) => {
+            let value = eval(operand, scope)?;
+            match (op, &value) {
+                (UnaryOp::Not, _) => Ok(Value::Bool(!value.is_truthy())),
+                (UnaryOp::Neg, Value::Int(i)) => Ok(Value::Int(-i)),
+                (UnaryOp::Neg, Value::Float(f)) => Ok(Value::Float(-f)),
+                (UnaryOp::Neg, _) => Err(InterpreterError::Type(format!(
+                    "cannot negate {}",
+                    value.type_name()
+                ))),
+            }
+        }
+        Expr::Binary(op, lhs, rhs) => {
+            // Short-circuit logical operators
+            match op {
+                BinaryOp::And => {
+                    let lhs = eval(lhs, scope)?;
+                    if !lhs.is_truthy() {
+                        return Ok(Value::Bool(false));
+                    }
+                    return Ok(Value::Bool(eval(rhs, scope)?.is_truthy()));
+                }
+                BinaryOp::Or => {
+                    let lhs = eval(lhs, scope)?;
+                    if lhs.is_truthy() {
+                        return Ok(Value::Bool(true));
+                    }
+                    return Ok(Value::Bool(eval(rhs, scope)?.is_truthy()));
+                }
+                _ => {}
+            }
+            let lhs = eval(lhs, scope)?;
+            let rhs = eval(rhs, scope)?;
+            binary(*op, &lhs, &rhs)
+        }
+        Expr::Method(receiver, method, args) => {
+            let receiver = eval(receiver, scope)?;
+            let args = args
+                .iter()
+                .map(|arg| eval(arg, scope))
+                .collect::<Result<Vec<_>>>()?;
+            call_method(&receiver, method, &args)
+        }
+        Expr::Call(function, args) => {
+            let args = args
+                .iter()
+                .map(|arg| eval(arg, scope))
+                .collect::<Result<Vec<_>>>()?;
+            call_function(function, &args)
+        }
+        Expr::Handler(_) => Err(InterpreterError::Type(
+            "a handler block can only be used as an event prop".to_string(),
+        )),
+    }
+}
+
+/// Apply a (non short-circuiting) binary operator
+pub(crate) fn binary(op: BinaryOp, lhs: &Value, rhs: &Value) -> Result<Value> {
+    use std::cmp::Ordering;
+
+    match op {
+        BinaryOp::Add => lhs.add(rhs),
+        BinaryOp::Sub => lhs.arithmetic("-", rhs, i64::checked_sub, |a, b| a - b),
+        BinaryOp::Mul => lhs.arithmetic("*", rhs, i64::checked_mul, |a, b| a * b),
+        BinaryOp::Div => lhs.arithmetic("/", rhs, i64::checked_div, |a, b| a / b),
+        BinaryOp::Rem => lhs.arithmetic("%", rhs, i64::checked_rem, |a, b| a % b),
+        BinaryOp::Eq => Ok(Value::Bool(lhs.loose_eq(rhs))),
+        BinaryOp::Ne => Ok(Value::Bool(!lhs.loose_eq(rhs))),
+        BinaryOp::Lt => Ok(Value::Bool(lhs.compare("<", rhs)? == Ordering::Less)),
+        BinaryOp::Le => Ok(Value::Bool(lhs.compare("<=", rhs)? != Ordering::Greater)),
+        BinaryOp::Gt => Ok(Value::Bool(lhs.compare(">", rhs)? == Ordering::Greater)),
+        BinaryOp::Ge => Ok(Value::Bool(lhs.compare(">=", rhs)? != Ordering::Less)),
+        BinaryOp::And => Ok(Value::Bool(lhs.is_truthy() && rhs.is_truthy())),
+        BinaryOp::Or => Ok(Value::Bool(lhs.is_truthy() || rhs.is_truthy())),
+    }
+}
+
+fn number_arg(function: &str, args: &[Value], index: usize) -> Result<f64> {
+    args.get(index).and_then(Value::as_f64).ok_or_else(|| {
+        InterpreterError::Type(format!(
+            "`{}` expects a number as argument {}",
+            function,
+            index + 1
+        ))
+    })
+}
+
+fn call_method(receiver: &Value, method: &str, args: &[Value]) -> Result<Value> {
+    match (method, receiver) {
+        ("to_string", _) => Ok(Value::Str(receiver.to_string())),
+        ("len", Value::Str(s)) => Ok(Value::Int(s.chars().count() as i64)),
+        ("to_uppercase", Value::Str(s)) => Ok(Value::Str(s.to_uppercase())),
+        ("to_lowercase", Value::Str(s)) => Ok(Value::Str(s.to_lowercase())),
+        ("abs", Value::Int(i)) => Ok(Value::Int(i.abs())),
+        ("abs", Value::Float(f)) => Ok(Value::Float(f.abs())),
+        ("round", Value::Float(f)) => Ok(Value::Float(f.round())),
+        ("floor", Value::Float(f)) => Ok(Value::Float(f.floor())),
+        ("ceil", Value::Float(f)) => Ok(Value::Float(f.ceil())),
+        ("round" | "floor" | "ceil", Value::Int(_)) => Ok(receiver.clone()),
+        ("min" | "max", _) => {
+            let mut all = vec![receiver.clone()];
+            all.extend_from_slice(args);
+            call_function(method, &all)
+        }
+        _ => Err(InterpreterError::Type(format!(
+            "{} has no method `{}`",
+            receiver.type_name(),
+            method
+        ))),
+    }
+}
+
+fn call_function(function: &str, args: &[Value]) -> Result<Value> {
+    match function {
+        "rgb" | "rgba" => {
+            let channel = |i| number_arg(function, args, i).map(|v| (v / 255.0) as f32);
+            let alpha = if function == "rgba" {
+                number_arg(function, args, 3)? as f32
+            } else {
+                1.0
+            };
+            Ok(Value::Color([channel(0)?, channel(1)?, channel(2)?, alpha]))
+        }
+        "min" | "max" if args.len() >= 2 => {
+            let mut best = args[0].clone();
+            for arg in &args[1..] {
+                let ordering = arg.compare(function, &best)?;
+                let better = if function == "min" {
+                    ordering.is_lt()
+                } else {
+                    ordering.is_gt()
+                };
+                if better {
+                    best = arg.clone();
+                }
+            }
+            Ok(best)
+        }
+        "clamp" => {
+            let value = number_arg(function, args, 0)?;
+            let min = number_arg(function, args, 1)?;
+            let max = number_arg(function, args, 2)?;
+            Ok(match &args[0] {
+                Value::Int(_) => Value::Int(value.clamp(min, max) as i64),
+                _ => Value::Float(value.clamp(min, max)),
+            })
+        }
+        _ => Err(InterpreterError::UnknownIdentifier(function.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lower::parse_expr;
+
+    struct Vars;
+
+    impl Scope for Vars {
+        fn lookup(&self, name: &str) -> Result<Value> {
+            match name {
+                "count" => Ok(Value::Int(3)),
+                "label" => Ok(Value::Str("Clicks".into())),
+                _ => EmptyScope.lookup(name),
+            }
+        }
+    }
+
+    fn run(source: &str) -> Result<Value> {
+        eval(&parse_expr(source).unwrap(), &Vars)
+    }
+
+    #[test]
+    fn test_eval_expressions() {
+        assert_eq!(run("count * 2 + 1").unwrap(), Value::Int(7));
+        assert_eq!(run("count / 2.0").unwrap(), Value::Float(1.5));
+        assert_eq!(run("-count % 2").unwrap(), Value::Int(-1));
+        assert_eq!(
+            run("count >= 3 && label != \"\"").unwrap(),
+            Value::Bool(true)
+        );
+        assert_eq!(
+            run("\"{label}: {count}\"").unwrap(),
+            Value::Str("Clicks: 3".into())
+        );
+        assert_eq!(
+            run("\"Count: \" + count.to_string()").unwrap(),
+            Value::Str("Count: 3".into())
+        );
+        assert_eq!(run("clamp(count, 0, 2)").unwrap(), Value::Int(2));
+        assert_eq!(run("max(count, 10)").unwrap(), Value::Int(10));
+        assert_eq!(
+            run("rgb(255, 0, 0)").unwrap(),
+            Value::Color([1.0, 0.0, 0.0, 1.0])
+        );
+    }
+
+    #[test]
+    fn test_eval_errors() {
+        assert!(matches!(
+            run("missing + 1"),
+            Err(InterpreterError::UnknownIdentifier(_))
+        ));
+        assert!(matches!(run("count / 0"), Err(InterpreterError::Type(_))));
+        assert!(matches!(run("true - 1"), Err(InterpreterError::Type(_))));
+    }
+}
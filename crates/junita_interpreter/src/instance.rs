@@ -0,0 +1,508 @@
+//! Live widget instances
+//!
+//! A [`WidgetInstance`] is one use of a widget in the tree. It owns the
+//! reactive bindings declared by the widget:
+//!
+//! - props and `@state` vars are `Signal<Value>`s in the shared graph
+//! - `@derived` vars are `Derived<Value>`s reading those signals
+//! - `@machine`s are `FsmRuntime` machines driven by pointer events
+//! - `@spring`s are `AnimatedValue`s on the global animation scheduler
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::rc::Rc;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use junita_animation::{AnimatedValue, SchedulerHandle, SpringConfig};
+use junita_core::fsm::{FsmId, FsmRuntime, StateMachine};
+use junita_core::hot_reload::StateSnapshot;
+use junita_core::reactive::{Derived, DirtyFlag, ReactiveGraph, SharedReactiveGraph, Signal};
+
+use crate::artifact::{CompiledArtifact, WidgetDefinition};
+use crate::ast::{AssignOp, Expr, Stmt};
+use crate::error::{InterpreterError, Result};
+use crate::eval::{binary, eval, EmptyScope, Scope};
+use crate::lower::parse_expr;
+use crate::value::Value;
+
+/// Derived values referencing each other deeper than this are treated as a cycle
+const MAX_DERIVED_DEPTH: u32 = 32;
+
+/// State shared by every instance of an interpreter
+pub(crate) struct Runtime {
+    pub graph: SharedReactiveGraph,
+    pub dirty: DirtyFlag,
+    pub fsm: RefCell<FsmRuntime>,
+    pub scheduler: Option<SchedulerHandle>,
+}
+
+impl Runtime {
+    /// Ask the host to rebuild the UI
+    pub fn request_rebuild(&self) {
+        self.dirty.store(true, Ordering::SeqCst);
+    }
+}
+
+/// A `@machine` bound to an `FsmRuntime` machine
+///
+/// States and events are numbered by their position in these lists.
+struct MachineBinding {
+    id: FsmId,
+    states: Vec<String>,
+    events: Vec<String>,
+}
+
+/// A `@spring`, animated when a scheduler is available
+enum SpringBinding {
+    Animated(AnimatedValue),
+    /// No scheduler running (headless use): assignments jump immediately
+    Static(f32),
+}
+
+impl SpringBinding {
+    fn get(&self) -> f32 {
+        match self {
+            SpringBinding::Animated(value) => value.get(),
+            SpringBinding::Static(value) => *value,
+        }
+    }
+
+    fn target(&self) -> f32 {
+        match self {
+            SpringBinding::Animated(value) => value.target(),
+            SpringBinding::Static(value) => *value,
+        }
+    }
+
+    fn set_target(&mut self, target: f32) {
+        match self {
+            SpringBinding::Animated(value) => value.set_target(target),
+            SpringBinding::Static(value) => *value = target,
+        }
+    }
+}
+
+struct StateBinding {
+    signal: Signal<Value>,
+    var_type: String,
+}
+
+/// One instantiated widget
+pub(crate) struct WidgetInstance {
+    key: String,
+    runtime: Rc<Runtime>,
+    props: HashMap<String, Signal<Value>>,
+    state: HashMap<String, StateBinding>,
+    derived: HashMap<String, Derived<Value>>,
+    machines: HashMap<String, MachineBinding>,
+    springs: HashMap<String, RefCell<SpringBinding>>,
+}
+
+impl WidgetInstance {
+    /// Instantiate `widget` with the given prop values
+    ///
+    /// State found under this instance's key in `restore` replaces the
+    /// declared initial values when its type still matches.
+    pub fn new(
+        key: String,
+        widget: &WidgetDefinition,
+        artifact: &CompiledArtifact,
+        runtime: Rc<Runtime>,
+        mut props: HashMap<String, Value>,
+        restore: Option<&StateSnapshot>,
+    ) -> Result<Self> {
+        // Props: passed value, else declared default, else the type's default
+        for prop in &widget.properties {
+            if props.contains_key(&prop.name) {
+                continue;
+            }
+            let value = match &prop.default_value {
+                Some(default) => eval(&parse_expr(default)?, &EmptyScope)?,
+                None => Value::default_for(&prop.prop_type),
+            };
+            props.insert(prop.name.clone(), value.coerce(&prop.prop_type));
+        }
+
+        // State initial values may refer to props
+        let mut initial_state = Vec::with_capacity(widget.state_vars.len());
+        {
+            let prop_scope = ValuesScope(&props);
+            for var in &widget.state_vars {
+                let mut value =
+                    eval(&parse_expr(&var.initial_value)?, &prop_scope)?.coerce(&var.var_type);
+                if let Some(restored) =
+                    restored::<Value>(restore.map(|s| &s.signals), &key, &var.name)
+                {
+                    if restored.same_kind(&value) {
+                        value = restored;
+                    }
+                }
+                initial_state.push((var, value));
+            }
+        }
+
+        let derived_exprs: HashMap<String, Expr> = widget
+            .derived_vars
+            .iter()
+            .map(|var| Ok((var.name.clone(), parse_expr(&var.expression)?)))
+            .collect::<Result<_>>()?;
+        let derived_exprs = Arc::new(derived_exprs);
+
+        let mut instance = {
+            let mut graph = runtime.graph.lock().unwrap();
+
+            let props: HashMap<_, _> = props
+                .into_iter()
+                .map(|(name, value)| (name, graph.create_signal(value)))
+                .collect();
+            let state: HashMap<_, _> = initial_state
+                .into_iter()
+                .map(|(var, value)| {
+                    let binding = StateBinding {
+                        signal: graph.create_signal(value),
+                        var_type: var.var_type.clone(),
+                    };
+                    (var.name.clone(), binding)
+                })
+                .collect();
+
+            // Derived values track the signals they read through the graph
+            let mut signals: HashMap<String, Signal<Value>> = props.clone();
+            signals.extend(state.iter().map(|(name, b)| (name.clone(), b.signal)));
+            let derived = widget
+                .derived_vars
+                .iter()
+                .map(|var| {
+                    let name = var.name.clone();
+                    let var_type = var.var_type.clone();
+                    let signals = signals.clone();
+                    let exprs = Arc::clone(&derived_exprs);
+                    let handle = graph.create_derived(move |graph: &ReactiveGraph| {
+                        let scope = DerivedScope {
+                            graph,
+                            signals: &signals,
+                            exprs: &exprs,
+                            depth: Cell::new(0),
+                        };
+                        match scope.lookup(&name) {
+                            Ok(value) => value.coerce(&var_type),
+                            Err(err) => {
+                                tracing::warn!("Derived value `{}` failed: {}", name, err);
+                                Value::Unit
+                            }
+                        }
+                    });
+                    (var.name.clone(), handle)
+                })
+                .collect();
+
+            WidgetInstance {
+                key,
+                runtime: Rc::clone(&runtime),
+                props,
+                state,
+                derived,
+                machines: HashMap::new(),
+                springs: HashMap::new(),
+            }
+        };
+
+        for name in &widget.machines {
+            let Some(def) = artifact.machine(name) else {
+                tracing::warn!("Widget `{}` uses unknown machine `{}`", widget.name, name);
+                continue;
+            };
+
+            let mut states = def.states.clone();
+            let mut events: Vec<String> = Vec::new();
+            for t in &def.transitions {
+                for state in [&t.from, &t.to] {
+                    if !states.contains(state) {
+                        states.push(state.clone());
+                    }
+                }
+                if !events.contains(&t.event) {
+                    events.push(t.event.clone());
+                }
+            }
+
+            let index = |list: &[String], name: &str| {
+                list.iter().position(|s| s == name).unwrap_or(0) as u32
+            };
+            let restored_state =
+                restored::<String>(restore.map(|s| &s.dynamic_state), &instance.key, name);
+            let initial = restored_state
+                .filter(|state| states.contains(state))
+                .unwrap_or_else(|| def.initial_state.clone());
+
+            let mut builder = StateMachine::builder(index(&states, &initial));
+            for t in &def.transitions {
+                builder = builder.on(
+                    index(&states, &t.from),
+                    index(&events, &t.event),
+                    index(&states, &t.to),
+                );
+            }
+            let id = runtime.fsm.borrow_mut().create(builder.build());
+            instance
+                .machines
+                .insert(name.clone(), MachineBinding { id, states, events });
+        }
+
+        for name in &widget.springs {
+            let def = artifact.spring(name);
+            let initial = restored::<f32>(restore.map(|s| &s.dynamic_state), &instance.key, name)
+                .or_else(|| def.and_then(|d| d.initial_value))
+                .unwrap_or(0.0);
+            let config = def
+                .map(|d| SpringConfig::new(d.stiffness, d.damping, d.mass))
+                .unwrap_or_else(SpringConfig::stiff);
+            let binding = match &runtime.scheduler {
+                Some(handle) => {
+                    SpringBinding::Animated(AnimatedValue::new(handle.clone(), initial, config))
+                }
+                None => SpringBinding::Static(initial),
+            };
+            instance.springs.insert(name.clone(), RefCell::new(binding));
+        }
+
+        Ok(instance)
+    }
+
+    /// Update prop signals with values passed by the parent
+    pub fn update_props(&self, props: HashMap<String, Value>) {
+        let mut graph = self.runtime.graph.lock().unwrap();
+        for (name, value) in props {
+            match self.props.get(&name) {
+                Some(signal) => {
+                    if graph.get_untracked(*signal).as_ref() != Some(&value) {
+                        graph.set(*signal, value);
+                    }
+                }
+                None => tracing::debug!("Ignoring undeclared prop `{}` on {}", name, self.key),
+            }
+        }
+    }
+
+    /// Run handler statements, then request a rebuild
+    pub fn run(&self, stmts: &[Stmt]) -> Result<()> {
+        let result = stmts.iter().try_for_each(|stmt| match stmt {
+            Stmt::Assign { target, op, value } => {
+                let value = eval(value, self)?;
+                self.assign(target, *op, value)
+            }
+            Stmt::Expr(expr) => eval(expr, self).map(drop),
+        });
+        self.runtime.request_rebuild();
+        result
+    }
+
+    fn assign(&self, target: &str, op: AssignOp, value: Value) -> Result<()> {
+        if let Some(binding) = self.state.get(target) {
+            let mut graph = self.runtime.graph.lock().unwrap();
+            let value = match op.binary() {
+                Some(op) => {
+                    let current = graph.get_untracked(binding.signal).unwrap_or_default();
+                    binary(op, &current, &value)?
+                }
+                None => value,
+            };
+            graph.set(binding.signal, value.coerce(&binding.var_type));
+            return Ok(());
+        }
+
+        if let Some(spring) = self.springs.get(target) {
+            let mut spring = spring.borrow_mut();
+            let value = match op.binary() {
+                Some(op) => binary(op, &Value::Float(spring.target() as f64), &value)?,
+                None => value,
+            };
+            let target_value = value.as_f32().ok_or_else(|| {
+                InterpreterError::Type(format!(
+                    "spring `{}` needs a number, got {}",
+                    target,
+                    value.type_name()
+                ))
+            })?;
+            spring.set_target(target_value);
+            return Ok(());
+        }
+
+        Err(InterpreterError::InvalidAssignment(target.to_string()))
+    }
+
+    /// Whether any machine of this widget reacts to `event`
+    pub fn handles_event(&self, event: &str) -> bool {
+        self.machines
+            .values()
+            .any(|m| m.events.iter().any(|e| e == event))
+    }
+
+    /// Send `event` to every machine of this widget
+    ///
+    /// Requests a rebuild if any machine changed state.
+    pub fn send(&self, event: &str) {
+        let mut changed = false;
+        {
+            let mut fsm = self.runtime.fsm.borrow_mut();
+            for machine in self.machines.values() {
+                let Some(event_id) = machine.events.iter().position(|e| e == event) else {
+                    continue;
+                };
+                let before = fsm.current_state(machine.id);
+                let after = fsm.send(machine.id, event_id as u32);
+                changed |= before != after;
+            }
+        }
+        if changed {
+            self.runtime.request_rebuild();
+        }
+    }
+
+    fn machine_state(&self, machine: &MachineBinding) -> Option<String> {
+        let state = self.runtime.fsm.borrow().current_state(machine.id)?;
+        machine.states.get(state as usize).cloned()
+    }
+
+    /// Record state vars, derived values, machine states and spring targets
+    pub fn snapshot_into(&self, snapshot: &mut StateSnapshot) -> Result<()> {
+        let entry = |name: &str| format!("{}.{}", self.key, name);
+        {
+            let mut graph = self.runtime.graph.lock().unwrap();
+            for (name, binding) in &self.state {
+                let value = graph.get_untracked(binding.signal).unwrap_or_default();
+                snapshot
+                    .signals
+                    .insert(entry(name), serde_json::to_vec(&value)?);
+            }
+            for (name, derived) in &self.derived {
+                let value = graph.get_derived(*derived).unwrap_or_default();
+                snapshot
+                    .derived_values
+                    .insert(entry(name), serde_json::to_vec(&value)?);
+            }
+        }
+        for (name, machine) in &self.machines {
+            if let Some(state) = self.machine_state(machine) {
+                snapshot
+                    .dynamic_state
+                    .insert(entry(name), serde_json::to_vec(&state)?);
+            }
+        }
+        for (name, spring) in &self.springs {
+            snapshot
+                .dynamic_state
+                .insert(entry(name), serde_json::to_vec(&spring.borrow().target())?);
+        }
+        Ok(())
+    }
+
+    /// Current value of a state var, prop or derived value
+    pub fn value(&self, name: &str) -> Option<Value> {
+        self.lookup(name).ok()
+    }
+}
+
+impl Scope for WidgetInstance {
+    fn lookup(&self, name: &str) -> Result<Value> {
+        let signal = self
+            .state
+            .get(name)
+            .map(|b| b.signal)
+            .or_else(|| self.props.get(name).copied());
+        if let Some(signal) = signal {
+            let graph = self.runtime.graph.lock().unwrap();
+            return Ok(graph.get_untracked(signal).unwrap_or_default());
+        }
+
+        if let Some(derived) = self.derived.get(name) {
+            let mut graph = self.runtime.graph.lock().unwrap();
+            return Ok(graph.get_derived(*derived).unwrap_or_default());
+        }
+
+        if let Some(spring) = self.springs.get(name) {
+            return Ok(Value::Float(spring.borrow().get() as f64));
+        }
+
+        if let Some(machine) = self.machines.get(name) {
+            return Ok(self.machine_state(machine).map_or(Value::Unit, Value::Str));
+        }
+
+        // Bare state names compare against machines: `state == active`
+        if self
+            .machines
+            .values()
+            .any(|m| m.states.iter().any(|s| s == name))
+        {
+            return Ok(Value::Str(name.to_string()));
+        }
+
+        Err(InterpreterError::UnknownIdentifier(name.to_string()))
+    }
+}
+
+impl Drop for WidgetInstance {
+    fn drop(&mut self) {
+        if let Ok(mut fsm) = self.runtime.fsm.try_borrow_mut() {
+            for machine in self.machines.values() {
+                fsm.remove(machine.id);
+            }
+        }
+    }
+}
+
+/// Decode `key.name` from a snapshot map
+fn restored<T: serde::de::DeserializeOwned>(
+    map: Option<&HashMap<String, Vec<u8>>>,
+    key: &str,
+    name: &str,
+) -> Option<T> {
+    let bytes = map?.get(&format!("{}.{}", key, name))?;
+    serde_json::from_slice(bytes).ok()
+}
+
+/// Plain name/value scope (props while computing initial state)
+struct ValuesScope<'a>(&'a HashMap<String, Value>);
+
+impl Scope for ValuesScope<'_> {
+    fn lookup(&self, name: &str) -> Result<Value> {
+        self.0
+            .get(name)
+            .cloned()
+            .ok_or_else(|| InterpreterError::UnknownIdentifier(name.to_string()))
+    }
+}
+
+/// Scope used inside `Derived` computations
+///
+/// Signals are read through the graph so they're tracked as dependencies.
+/// Derived values referring to other derived values are evaluated inline,
+/// since the graph doesn't track derived-to-derived dependencies.
+struct DerivedScope<'a> {
+    graph: &'a ReactiveGraph,
+    signals: &'a HashMap<String, Signal<Value>>,
+    exprs: &'a HashMap<String, Expr>,
+    depth: Cell<u32>,
+}
+
+impl Scope for DerivedScope<'_> {
+    fn lookup(&self, name: &str) -> Result<Value> {
+        if let Some(signal) = self.signals.get(name) {
+            return Ok(self.graph.get(*signal).unwrap_or_default());
+        }
+        let Some(expr) = self.exprs.get(name) else {
+            return Err(InterpreterError::UnknownIdentifier(name.to_string()));
+        };
+        if self.depth.get() >= MAX_DERIVED_DEPTH {
+            return Err(InterpreterError::Type(format!(
+                "derived value `{}` depends on itself",
+                name
+            )));
+        }
+        self.depth.set(self.depth.get() + 1);
+        let value = eval(expr, self);
+        self.depth.set(self.depth.get() - 1);
+        value
+    }
+}
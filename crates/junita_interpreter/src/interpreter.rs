@@ -0,0 +1,432 @@
+//! Artifact interpreter
+//!
+//! Walks a widget's render body, instantiating nested widgets and built-in
+//! elements into a `Div` tree. Widget instances are keyed by their position
+//! in the tree (`App/Counter`, `App/Counter#2`) and kept across rebuilds, so
+//! state survives until the widget leaves the tree or the artifact is
+//! swapped.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+use std::sync::{Arc, Mutex};
+
+use junita_core::fsm::FsmRuntime;
+use junita_core::hot_reload::{HotReloadManager, StateSnapshot};
+use junita_core::reactive::{DirtyFlag, ReactiveGraph, SharedReactiveGraph};
+use junita_core::Color;
+use junita_layout::prelude::*;
+
+use crate::artifact::CompiledArtifact;
+use crate::ast::{Expr, Node};
+use crate::elements::{build_element, text_element, PropValue};
+use crate::error::{InterpreterError, Result};
+use crate::eval::eval;
+use crate::instance::{Runtime, WidgetInstance};
+use crate::lower::{parse_expr, parse_view};
+use crate::value::Value;
+
+/// Runs a [`CompiledArtifact`] as live UI
+///
+/// Call [`build`](Self::build) from the app's UI builder; handlers and
+/// machine transitions set the dirty flag to request the next rebuild.
+///
+/// ```ignore
+/// let mut interpreter = Interpreter::new(artifact);
+///
+/// WindowedApp::run(WindowConfig::default(), move |ctx| {
+///     interpreter.attach(ctx.reactive(), ctx.dirty_flag());
+///     interpreter.build()
+/// })
+/// ```
+pub struct Interpreter {
+    artifact: CompiledArtifact,
+    root: Option<String>,
+    runtime: Rc<Runtime>,
+    hot_reload: Arc<HotReloadManager>,
+    /// Parsed render bodies, by widget name
+    views: HashMap<String, Rc<Vec<Node>>>,
+    instances: HashMap<String, Rc<WidgetInstance>>,
+    /// State to restore into instances created by the next build
+    pending_restore: Option<StateSnapshot>,
+}
+
+impl Interpreter {
+    /// Create an interpreter with its own reactive graph
+    pub fn new(artifact: CompiledArtifact) -> Self {
+        Self {
+            artifact,
+            root: None,
+            runtime: Rc::new(Runtime {
+                graph: Arc::new(Mutex::new(ReactiveGraph::new())),
+                dirty: Arc::new(AtomicBool::new(false)),
+                fsm: RefCell::new(FsmRuntime::new()),
+                scheduler: junita_animation::try_get_scheduler(),
+            }),
+            hot_reload: Arc::new(HotReloadManager::new(true)),
+            views: HashMap::new(),
+            instances: HashMap::new(),
+            pending_restore: None,
+        }
+    }
+
+    /// Render this widget instead of `App` (or the first widget)
+    pub fn with_root(mut self, widget: impl Into<String>) -> Self {
+        self.root = Some(widget.into());
+        self
+    }
+
+    /// Use a shared hot reload manager for state snapshots
+    pub fn with_hot_reload(mut self, manager: Arc<HotReloadManager>) -> Self {
+        self.hot_reload = manager;
+        self
+    }
+
+    /// Bind to an app's reactive graph and rebuild flag
+    ///
+    /// Existing state is carried over through a snapshot. Does nothing if
+    /// already attached to this graph, so it's cheap to call every build.
+    /// Springs move to the app's animation scheduler, which may not have
+    /// existed when the interpreter was created.
+    pub fn attach(&mut self, graph: SharedReactiveGraph, dirty: DirtyFlag) {
+        if Arc::ptr_eq(&self.runtime.graph, &graph) && Arc::ptr_eq(&self.runtime.dirty, &dirty) {
+            return;
+        }
+        self.carry_state();
+        self.runtime = Rc::new(Runtime {
+            graph,
+            dirty,
+            fsm: RefCell::new(FsmRuntime::new()),
+            scheduler: junita_animation::try_get_scheduler()
+                .or_else(|| self.runtime.scheduler.clone()),
+        });
+    }
+
+    /// The running artifact
+    pub fn artifact(&self) -> &CompiledArtifact {
+        &self.artifact
+    }
+
+    /// Hot reload manager holding state snapshots
+    pub fn hot_reload(&self) -> &Arc<HotReloadManager> {
+        &self.hot_reload
+    }
+
+    /// Flag set when the UI needs rebuilding
+    pub fn dirty_flag(&self) -> DirtyFlag {
+        Arc::clone(&self.runtime.dirty)
+    }
+
+    /// Replace the running artifact, preserving state
+    ///
+    /// The new artifact is checked first; if it doesn't parse, the current
+    /// one keeps running and the error is returned. State vars, machine
+    /// states and spring targets of instances at the same tree position
+    /// carry over when their type is unchanged.
+    pub fn hot_swap(&mut self, artifact: CompiledArtifact) -> Result<()> {
+        let views = parse_views(&artifact)?;
+        check_expressions(&artifact)?;
+
+        self.carry_state();
+        self.artifact = artifact;
+        self.views = views;
+        self.runtime.request_rebuild();
+        Ok(())
+    }
+
+    /// Capture the state of every live instance
+    pub fn snapshot(&self) -> Result<StateSnapshot> {
+        let mut snapshot = StateSnapshot::new();
+        for instance in self.instances.values() {
+            instance.snapshot_into(&mut snapshot)?;
+        }
+        Ok(snapshot)
+    }
+
+    /// Recreate all instances from a snapshot on the next build
+    pub fn restore(&mut self, snapshot: StateSnapshot) {
+        self.instances.clear();
+        self.pending_restore = Some(snapshot);
+        self.runtime.request_rebuild();
+    }
+
+    /// Current value of a state var, prop or derived value
+    ///
+    /// `path` is an instance key followed by the name: `App/Counter.count`.
+    pub fn value(&self, path: &str) -> Option<Value> {
+        let (key, name) = path.rsplit_once('.')?;
+        self.instances.get(key)?.value(name)
+    }
+
+    /// Build the UI, rendering errors in place of the tree
+    pub fn build(&mut self) -> Div {
+        match self.try_build() {
+            Ok(tree) => tree,
+            Err(err) => {
+                tracing::error!(
+                    "Failed to build {}: {}",
+                    self.artifact.source_file.display(),
+                    err
+                );
+                error_view(&err)
+            }
+        }
+    }
+
+    /// Build the UI
+    pub fn try_build(&mut self) -> Result<Div> {
+        let root = self.root_widget()?;
+        let mut visited = HashSet::new();
+        let result = self.render_widget(
+            &root,
+            root.clone(),
+            HashMap::new(),
+            &mut visited,
+            &mut Vec::new(),
+        );
+
+        if result.is_ok() {
+            // Drop instances that left the tree
+            self.instances.retain(|key, _| visited.contains(key));
+            self.pending_restore = None;
+        }
+        result
+    }
+
+    fn root_widget(&self) -> Result<String> {
+        if let Some(root) = &self.root {
+            return Ok(root.clone());
+        }
+        self.artifact
+            .widget("App")
+            .or_else(|| self.artifact.widgets.first())
+            .map(|w| w.name.clone())
+            .ok_or_else(|| InterpreterError::UnknownWidget("App".to_string()))
+    }
+
+    /// Snapshot live state through the hot reload manager for the next build
+    fn carry_state(&mut self) {
+        if self.instances.is_empty() {
+            return;
+        }
+        match self.snapshot() {
+            Ok(snapshot) => {
+                self.hot_reload.save_snapshot(snapshot);
+                self.pending_restore = self.hot_reload.restore_state();
+            }
+            Err(err) => tracing::warn!("Could not snapshot state: {}", err),
+        }
+        self.instances.clear();
+    }
+
+    fn view(&mut self, widget: &str) -> Result<Rc<Vec<Node>>> {
+        if let Some(view) = self.views.get(widget) {
+            return Ok(Rc::clone(view));
+        }
+        let def = self
+            .artifact
+            .widget(widget)
+            .ok_or_else(|| InterpreterError::UnknownWidget(widget.to_string()))?;
+        let body = def
+            .render_body
+            .as_deref()
+            .ok_or_else(|| InterpreterError::MissingRender(widget.to_string()))?;
+        let view = Rc::new(parse_view(body)?);
+        self.views.insert(widget.to_string(), Rc::clone(&view));
+        Ok(view)
+    }
+
+    fn render_widget(
+        &mut self,
+        widget: &str,
+        key: String,
+        props: HashMap<String, Value>,
+        visited: &mut HashSet<String>,
+        stack: &mut Vec<String>,
+    ) -> Result<Div> {
+        if stack.iter().any(|w| w == widget) {
+            return Err(InterpreterError::Recursive(widget.to_string()));
+        }
+        let view = self.view(widget)?;
+
+        let instance = match self.instances.get(&key) {
+            Some(instance) => {
+                instance.update_props(props);
+                Rc::clone(instance)
+            }
+            None => {
+                let def = self
+                    .artifact
+                    .widget(widget)
+                    .ok_or_else(|| InterpreterError::UnknownWidget(widget.to_string()))?;
+                let instance = Rc::new(WidgetInstance::new(
+                    key.clone(),
+                    def,
+                    &self.artifact,
+                    Rc::clone(&self.runtime),
+                    props,
+                    self.pending_restore.as_ref(),
+                )?);
+                self.instances.insert(key.clone(), Rc::clone(&instance));
+                instance
+            }
+        };
+        visited.insert(key.clone());
+
+        stack.push(widget.to_string());
+        let mut occurrences = HashMap::new();
+        let rendered = self.render_nodes(&view, &instance, &key, &mut occurrences, visited, stack);
+        stack.pop();
+
+        let mut children = rendered?;
+        let root = if children.len() == 1 {
+            children.remove(0)
+        } else {
+            children
+                .into_iter()
+                .fold(div().flex_col(), |parent, child| parent.child(child))
+        };
+        Ok(attach_machine_events(root, &instance))
+    }
+
+    fn render_nodes(
+        &mut self,
+        nodes: &[Node],
+        instance: &Rc<WidgetInstance>,
+        key: &str,
+        occurrences: &mut HashMap<String, usize>,
+        visited: &mut HashSet<String>,
+        stack: &mut Vec<String>,
+    ) -> Result<Vec<Div>> {
+        let mut out = Vec::with_capacity(nodes.len());
+        for node in nodes {
+            match node {
+                Node::Element {
+                    name,
+                    props,
+                    children,
+                } if self.artifact.widget(name).is_some() => {
+                    let props = props
+                        .iter()
+                        .map(|(prop, expr)| Ok((prop.clone(), eval(expr, &**instance)?)))
+                        .collect::<Result<HashMap<_, _>>>()?;
+                    if !children.is_empty() {
+                        tracing::debug!("Children of widget `{}` are ignored", name);
+                    }
+
+                    let count = occurrences.entry(name.clone()).or_insert(0);
+                    *count += 1;
+                    let child_key = if *count == 1 {
+                        format!("{}/{}", key, name)
+                    } else {
+                        format!("{}/{}#{}", key, name, count)
+                    };
+                    out.push(self.render_widget(name, child_key, props, visited, stack)?);
+                }
+                Node::Element {
+                    name,
+                    props,
+                    children,
+                } => {
+                    let props = props
+                        .iter()
+                        .map(|(prop, expr)| {
+                            let value = match expr {
+                                Expr::Handler(stmts) => PropValue::Handler(Arc::clone(stmts)),
+                                expr => PropValue::Value(eval(expr, &**instance)?),
+                            };
+                            Ok((prop.clone(), value))
+                        })
+                        .collect::<Result<Vec<_>>>()?;
+                    let children =
+                        self.render_nodes(children, instance, key, occurrences, visited, stack)?;
+                    out.push(build_element(name, props, children, instance));
+                }
+                Node::If {
+                    condition,
+                    then,
+                    otherwise,
+                } => {
+                    let branch = if eval(condition, &**instance)?.is_truthy() {
+                        then
+                    } else {
+                        otherwise
+                    };
+                    out.extend(self.render_nodes(
+                        branch,
+                        instance,
+                        key,
+                        occurrences,
+                        visited,
+                        stack,
+                    )?);
+                }
+                Node::Text(expr) => {
+                    out.push(text_element(eval(expr, &**instance)?.to_string()));
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Drive the widget's machines from pointer events on its root element
+fn attach_machine_events(mut root: Div, instance: &Rc<WidgetInstance>) -> Div {
+    const EVENTS: [&str; 5] = [
+        "pointer_enter",
+        "pointer_leave",
+        "pointer_down",
+        "pointer_up",
+        "click",
+    ];
+    for event in EVENTS {
+        if !instance.handles_event(event) {
+            continue;
+        }
+        let target = Rc::clone(instance);
+        let handler = move |_: &EventContext| target.send(event);
+        root = match event {
+            "pointer_enter" => root.on_hover_enter(handler),
+            "pointer_leave" => root.on_hover_leave(handler),
+            "pointer_down" => root.on_mouse_down(handler),
+            "pointer_up" => root.on_mouse_up(handler),
+            _ => root.on_click(handler),
+        };
+    }
+    root
+}
+
+fn parse_views(artifact: &CompiledArtifact) -> Result<HashMap<String, Rc<Vec<Node>>>> {
+    artifact
+        .widgets
+        .iter()
+        .filter_map(|w| w.render_body.as_deref().map(|body| (w, body)))
+        .map(|(w, body)| Ok((w.name.clone(), Rc::new(parse_view(body)?))))
+        .collect()
+}
+
+fn check_expressions(artifact: &CompiledArtifact) -> Result<()> {
+    for widget in &artifact.widgets {
+        let defaults = widget
+            .properties
+            .iter()
+            .filter_map(|p| p.default_value.as_deref());
+        let initial = widget.state_vars.iter().map(|s| s.initial_value.as_str());
+        let derived = widget.derived_vars.iter().map(|d| d.expression.as_str());
+        for source in defaults.chain(initial).chain(derived) {
+            parse_expr(source)?;
+        }
+    }
+    Ok(())
+}
+
+/// Shown in place of the tree when the build fails
+fn error_view(err: &InterpreterError) -> Div {
+    div()
+        .w_full()
+        .h_full()
+        .p_px(24.0)
+        .bg(Color::from_hex(0x7f1d1d))
+        .child(text(err.to_string()).size(16.0).color(Color::WHITE))
+}
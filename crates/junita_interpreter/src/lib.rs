@@ -0,0 +1,255 @@
+//! Junita interpreter
+//!
+//! Runs compiled `.junita` artifacts as live UI without a Rust toolchain.
+//! The [`Interpreter`] walks each widget's `@render` body and builds a
+//! `Div` tree, binding the widget's declarations to the runtime:
+//!
+//! - `@prop` and `@state` vars become `ReactiveGraph` signals
+//! - `@derived` vars become `Derived` values over those signals
+//! - `@machine`s become `FsmRuntime` machines, driven by `pointer_enter`,
+//!   `pointer_leave`, `pointer_down`, `pointer_up` and `click` events
+//! - `@spring`s become `AnimatedValue`s on the animation scheduler
+//!
+//! Event props (`on_click: { count += 1 }`) run their statements against the
+//! widget's state and request a rebuild through the dirty flag.
+//!
+//! # Hot Reload
+//!
+//! [`Interpreter::hot_swap`] replaces the artifact after a recompile. Live
+//! state is captured as a `StateSnapshot` through the `HotReloadManager` and
+//! restored into the new instances, so edits keep counters, machine states
+//! and spring targets.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use junita_app::prelude::*;
+//! use junita_interpreter::{CompiledArtifact, Interpreter};
+//!
+//! let artifact: CompiledArtifact = serde_json::from_str(&json)?;
+//! let mut interpreter = Interpreter::new(artifact);
+//!
+//! WindowedApp::run(WindowConfig::default(), move |ctx| {
+//!     interpreter.attach(ctx.reactive(), ctx.dirty_flag());
+//!     interpreter.build()
+//! })
+//! ```
+
+pub mod artifact;
+mod ast;
+mod elements;
+mod error;
+mod eval;
+mod instance;
+mod interpreter;
+mod lower;
+mod value;
+
+pub use artifact::{
+    AnimationDef, CompiledArtifact, DerivedVar, MachineDef, PropDef, SpringDef, StateVar,
+    Transition, WidgetDefinition,
+};
+pub use error::{InterpreterError, Result};
+pub use interpreter::Interpreter;
+pub use value::Value;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use junita_core::events::{event_types, EventType};
+    use junita_layout::prelude::*;
+
+    fn widget(name: &str, render: &str) -> WidgetDefinition {
+        WidgetDefinition {
+            name: name.to_string(),
+            properties: Vec::new(),
+            state_vars: Vec::new(),
+            derived_vars: Vec::new(),
+            machines: Vec::new(),
+            animations: Vec::new(),
+            springs: Vec::new(),
+            render_body: Some(render.to_string()),
+            paint_body: None,
+        }
+    }
+
+    fn artifact(widgets: Vec<WidgetDefinition>) -> CompiledArtifact {
+        CompiledArtifact {
+            source_file: "main.junita".into(),
+            widgets,
+            machines: Vec::new(),
+            animations: Vec::new(),
+            springs: Vec::new(),
+            timestamp: 0,
+            checksum: String::new(),
+        }
+    }
+
+    /// Counter with state, a derived value and a hover machine
+    fn counter_artifact(render: &str) -> CompiledArtifact {
+        let mut counter = widget("Counter", render);
+        counter.properties.push(PropDef {
+            name: "step".into(),
+            prop_type: "i32".into(),
+            default_value: Some("1".into()),
+        });
+        counter.state_vars.push(StateVar {
+            name: "count".into(),
+            var_type: "i32".into(),
+            initial_value: "0".into(),
+        });
+        counter.derived_vars.push(DerivedVar {
+            name: "doubled".into(),
+            var_type: "i32".into(),
+            expression: "count * 2".into(),
+            dependencies: vec!["count".into()],
+        });
+        counter.machines.push("hover".into());
+
+        let mut artifact = artifact(vec![
+            widget("App", "Center { Counter { step: 5 } }"),
+            counter,
+        ]);
+        artifact.machines.push(MachineDef {
+            name: "hover".into(),
+            states: vec!["idle".into(), "active".into()],
+            initial_state: "idle".into(),
+            transitions: vec![
+                Transition {
+                    from: "idle".into(),
+                    to: "active".into(),
+                    event: "pointer_enter".into(),
+                },
+                Transition {
+                    from: "active".into(),
+                    to: "idle".into(),
+                    event: "pointer_leave".into(),
+                },
+            ],
+        });
+        artifact
+    }
+
+    const COUNTER: &str = r#"
+        Column {
+            Button { label: "+", on_click: { count += step } }
+            Text { content: "Doubled: {doubled}" }
+        }
+    "#;
+
+    /// Dispatch `event_type` to the handlers of `el`
+    fn fire(el: &dyn ElementBuilder, event_type: EventType) {
+        el.event_handlers()
+            .expect("element has handlers")
+            .dispatch(&EventContext::new(event_type, Default::default()));
+    }
+
+    /// Click the button inside the counter's column
+    fn click_button(tree: &Div) {
+        let counter = &tree.children_builders()[0];
+        let button = &counter.children_builders()[0];
+        fire(button.as_ref(), event_types::POINTER_UP);
+    }
+
+    #[test]
+    fn test_state_handlers_and_derived() {
+        let mut interpreter = Interpreter::new(counter_artifact(COUNTER));
+        let tree = interpreter.try_build().unwrap();
+
+        assert_eq!(interpreter.value("App/Counter.count"), Some(Value::Int(0)));
+        assert_eq!(interpreter.value("App/Counter.step"), Some(Value::Int(5)));
+
+        click_button(&tree);
+        click_button(&tree);
+        assert_eq!(interpreter.value("App/Counter.count"), Some(Value::Int(10)));
+        assert_eq!(
+            interpreter.value("App/Counter.doubled"),
+            Some(Value::Int(20))
+        );
+        assert!(interpreter
+            .dirty_flag()
+            .load(std::sync::atomic::Ordering::SeqCst));
+
+        // Rebuilding keeps the instance
+        interpreter.try_build().unwrap();
+        assert_eq!(interpreter.value("App/Counter.count"), Some(Value::Int(10)));
+    }
+
+    #[test]
+    fn test_machine_follows_pointer_events() {
+        let render = r#"
+            Column {
+                if hover == active { "hovered" } else { "idle" }
+            }
+        "#;
+        let mut interpreter = Interpreter::new(counter_artifact(render));
+        let tree = interpreter.try_build().unwrap();
+        assert_eq!(
+            interpreter.value("App/Counter.hover"),
+            Some(Value::Str("idle".into()))
+        );
+
+        fire(
+            tree.children_builders()[0].as_ref(),
+            event_types::POINTER_ENTER,
+        );
+        assert_eq!(
+            interpreter.value("App/Counter.hover"),
+            Some(Value::Str("active".into()))
+        );
+    }
+
+    #[test]
+    fn test_hot_swap_preserves_state() {
+        let mut interpreter = Interpreter::new(counter_artifact(COUNTER));
+        let tree = interpreter.try_build().unwrap();
+        click_button(&tree);
+        assert_eq!(interpreter.value("App/Counter.count"), Some(Value::Int(5)));
+
+        // New render body, same state
+        let edited = COUNTER.replace("Doubled", "Twice");
+        interpreter.hot_swap(counter_artifact(&edited)).unwrap();
+        interpreter.try_build().unwrap();
+        assert_eq!(interpreter.value("App/Counter.count"), Some(Value::Int(5)));
+
+        // A render body that doesn't parse keeps the old artifact running
+        let err = interpreter
+            .hot_swap(counter_artifact("Column { spacing: }"))
+            .unwrap_err();
+        assert!(matches!(err, InterpreterError::Parse { .. }));
+        assert_eq!(interpreter.value("App/Counter.count"), Some(Value::Int(5)));
+
+        // State whose type changed starts over
+        let mut retyped = counter_artifact(COUNTER);
+        retyped.widgets[1].state_vars[0].var_type = "String".into();
+        retyped.widgets[1].state_vars[0].initial_value = "\"\"".into();
+        interpreter.hot_swap(retyped).unwrap();
+        interpreter.try_build().unwrap();
+        assert_eq!(
+            interpreter.value("App/Counter.count"),
+            Some(Value::Str(String::new()))
+        );
+    }
+
+    #[test]
+    fn test_build_errors() {
+        let mut interpreter = Interpreter::new(artifact(vec![widget("App", "Missing {}")]));
+        assert!(
+            interpreter.try_build().is_ok(),
+            "unknown elements render as boxes"
+        );
+
+        let mut interpreter =
+            Interpreter::new(artifact(vec![widget("App", "Text { content: nope }")]));
+        assert!(matches!(
+            interpreter.try_build(),
+            Err(InterpreterError::UnknownIdentifier(name)) if name == "nope"
+        ));
+
+        let mut interpreter = Interpreter::new(artifact(vec![widget("App", "Column { App {} }")]));
+        assert!(matches!(
+            interpreter.try_build(),
+            Err(InterpreterError::Recursive(_))
+        ));
+    }
+}
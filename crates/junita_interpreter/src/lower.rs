@@ -0,0 +1,504 @@
+//! Render bodies and expressions from the shared parser
+//!
+//! The compiler stores `@render` bodies, defaults and derived expressions as
+//! source text. They're parsed with `junita_syntax`, the same parser the
+//! compiler and language server use, and the tree is lowered to the AST in
+//! [`crate::ast`].
+//!
+//! ```text
+//! Column {
+//!     spacing: 16                      // prop
+//!     Text { content: "{count}" }      // child element
+//!     Button { on_click: { count += 1 } }
+//!     if count > 10 { Text { content: "Big" } } else { .. }
+//!     children: [ Text { .. }, Text { .. } ]
+//! }
+//! ```
+//!
+//! The interpreter runs a subset of the language. Lambdas, `let` and `if`
+//! statements, paths, indexing and elements used as values parse but are
+//! rejected here, with an error at their offset.
+//!
+//! String literals are interpolated: `{expr}` segments are parsed as
+//! expressions, `\{` is a literal brace and `\n` and `\t` are escapes.
+
+use std::ops::Range;
+use std::sync::Arc;
+
+use junita_syntax::SyntaxKind::{self, *};
+use junita_syntax::{Diagnostic, SyntaxNode, SyntaxToken};
+
+use crate::ast::{AssignOp, BinaryOp, Expr, Node, Segment, Stmt, UnaryOp};
+use crate::error::{InterpreterError, Result};
+use crate::value::Value;
+
+/// Unit suffixes accepted (and ignored) after numbers: `16px`, `300ms`
+const NUMBER_SUFFIXES: &[&str] = &["px", "ms"];
+
+/// Parse a render body into its top-level nodes
+pub(crate) fn parse_view(source: &str) -> Result<Vec<Node>> {
+    let parse = junita_syntax::parse_view(source);
+    first_error(&parse.diagnostics, 0)?;
+    let lower = Lower { base: 0 };
+    parse.root.nodes().map(|item| lower.node(item)).collect()
+}
+
+/// Parse a standalone expression (defaults, initial values, derived values)
+pub(crate) fn parse_expr(source: &str) -> Result<Expr> {
+    parse_expr_at(source, 0)
+}
+
+/// Parse an expression found at `base` in the enclosing source
+fn parse_expr_at(source: &str, base: usize) -> Result<Expr> {
+    let parse = junita_syntax::parse_expr(source);
+    first_error(&parse.diagnostics, base)?;
+    Lower { base }.operand(&parse.root)
+}
+
+fn first_error(diagnostics: &[Diagnostic], base: usize) -> Result<()> {
+    match diagnostics.iter().find(|d| d.is_error()) {
+        Some(error) => Err(InterpreterError::parse(
+            error.message.clone(),
+            base + error.span().map_or(0, |s| s.start),
+        )),
+        None => Ok(()),
+    }
+}
+
+fn binary_op(kind: SyntaxKind) -> Option<BinaryOp> {
+    Some(match kind {
+        PIPE2 => BinaryOp::Or,
+        AMP2 => BinaryOp::And,
+        EQ2 => BinaryOp::Eq,
+        NEQ => BinaryOp::Ne,
+        LT => BinaryOp::Lt,
+        LTE => BinaryOp::Le,
+        GT => BinaryOp::Gt,
+        GTE => BinaryOp::Ge,
+        PLUS => BinaryOp::Add,
+        MINUS => BinaryOp::Sub,
+        STAR => BinaryOp::Mul,
+        SLASH => BinaryOp::Div,
+        PERCENT => BinaryOp::Rem,
+        _ => return None,
+    })
+}
+
+fn assign_op(kind: SyntaxKind) -> Option<AssignOp> {
+    Some(match kind {
+        EQ => AssignOp::Set,
+        PLUS_EQ => AssignOp::Add,
+        MINUS_EQ => AssignOp::Sub,
+        STAR_EQ => AssignOp::Mul,
+        SLASH_EQ => AssignOp::Div,
+        _ => return None,
+    })
+}
+
+/// Lowers a tree parsed from source found at `base`
+///
+/// The tree is free of syntax errors, so the nodes each rule expects are
+/// there; unsupported constructs are reported at their offset.
+struct Lower {
+    base: usize,
+}
+
+impl Lower {
+    fn error(&self, message: impl Into<String>, span: &Range<usize>) -> InterpreterError {
+        InterpreterError::parse(message, self.base + span.start)
+    }
+
+    fn name(&self, node: &SyntaxNode) -> Result<String> {
+        node.name()
+            .map(|token| token.text.clone())
+            .ok_or_else(|| self.error("expected identifier", &node.span))
+    }
+
+    // ------------------------------------------------------------------------
+    // Views
+    // ------------------------------------------------------------------------
+
+    fn node(&self, node: &SyntaxNode) -> Result<Node> {
+        match node.kind {
+            ELEMENT => self.element(node),
+            CONDITIONAL => self.conditional(node),
+            LITERAL if node.token(STRING).is_some() => Ok(Node::Text(self.expr(node)?)),
+            _ => Err(self.error("expected element", &node.span)),
+        }
+    }
+
+    fn element(&self, node: &SyntaxNode) -> Result<Node> {
+        let name = self.name(node)?;
+        if let Some(args) = node.node(ARG_LIST) {
+            return Err(self.error("element props go in braces", &args.span));
+        }
+
+        let mut props = Vec::new();
+        let mut children = Vec::new();
+        for item in node
+            .node(ELEMENT_LIST)
+            .into_iter()
+            .flat_map(SyntaxNode::nodes)
+        {
+            if item.kind != PROPERTY {
+                children.push(self.node(item)?);
+                continue;
+            }
+            let prop = self.name(item)?;
+            let value = item
+                .expr()
+                .ok_or_else(|| self.error("expected expression", &item.span))?;
+            if prop == "children" && value.kind == ARRAY_EXPR {
+                for child in value.nodes() {
+                    children.push(self.node(child)?);
+                }
+            } else {
+                props.push((prop, self.expr(value)?));
+            }
+        }
+
+        Ok(Node::Element {
+            name,
+            props,
+            children,
+        })
+    }
+
+    /// `if cond { .. } else { .. }` (`when` is accepted as an alias)
+    fn conditional(&self, node: &SyntaxNode) -> Result<Node> {
+        let condition = self.operand(node)?;
+        let mut branches = node
+            .nodes()
+            .filter(|n| matches!(n.kind, ELEMENT_LIST | CONDITIONAL));
+        let then = match branches.next() {
+            Some(list) => self.children(list)?,
+            None => Vec::new(),
+        };
+        let otherwise = match branches.next() {
+            Some(nested) if nested.kind == CONDITIONAL => vec![self.conditional(nested)?],
+            Some(list) => self.children(list)?,
+            None => Vec::new(),
+        };
+        Ok(Node::If {
+            condition,
+            then,
+            otherwise,
+        })
+    }
+
+    fn children(&self, list: &SyntaxNode) -> Result<Vec<Node>> {
+        list.nodes().map(|item| self.node(item)).collect()
+    }
+
+    // ------------------------------------------------------------------------
+    // Statements
+    // ------------------------------------------------------------------------
+
+    fn block(&self, block: &SyntaxNode) -> Result<Vec<Stmt>> {
+        block.nodes().map(|stmt| self.stmt(stmt)).collect()
+    }
+
+    fn stmt(&self, node: &SyntaxNode) -> Result<Stmt> {
+        match node.kind {
+            EXPR_STMT => Ok(Stmt::Expr(self.operand(node)?)),
+            ASSIGN_STMT => {
+                let mut operands = node.nodes().filter(|n| n.kind.is_expr());
+                let (Some(target), Some(value)) = (operands.next(), operands.next()) else {
+                    return Err(self.error("expected expression", &node.span));
+                };
+                if target.kind != NAME_REF {
+                    return Err(self.error("can only assign to a name", &target.span));
+                }
+                let op = node
+                    .tokens()
+                    .find_map(|t| assign_op(t.kind))
+                    .ok_or_else(|| self.error("expected `=`", &node.span))?;
+                Ok(Stmt::Assign {
+                    target: self.name(target)?,
+                    op,
+                    value: self.expr(value)?,
+                })
+            }
+            _ => Err(self.error("unsupported statement", &node.span)),
+        }
+    }
+
+    // ------------------------------------------------------------------------
+    // Expressions
+    // ------------------------------------------------------------------------
+
+    /// The expression inside `node`
+    fn operand(&self, node: &SyntaxNode) -> Result<Expr> {
+        match node.expr() {
+            Some(expr) => self.expr(expr),
+            None => Err(self.error("expected expression", &node.span)),
+        }
+    }
+
+    fn expr(&self, node: &SyntaxNode) -> Result<Expr> {
+        match node.kind {
+            LITERAL => self.literal(node),
+            NAME_REF => Ok(Expr::Ident(self.name(node)?)),
+            PAREN_EXPR => self.operand(node),
+            UNARY_EXPR => {
+                let op = if node.token(BANG).is_some() {
+                    UnaryOp::Not
+                } else {
+                    UnaryOp::Neg
+                };
+                Ok(Expr::Unary(op, Box::new(self.operand(node)?)))
+            }
+            BINARY_EXPR => {
+                let mut operands = node.nodes().filter(|n| n.kind.is_expr());
+                let (Some(lhs), Some(rhs)) = (operands.next(), operands.next()) else {
+                    return Err(self.error("expected expression", &node.span));
+                };
+                let op = node
+                    .tokens()
+                    .find_map(|t| binary_op(t.kind))
+                    .ok_or_else(|| self.error("expected operator", &node.span))?;
+                Ok(Expr::Binary(
+                    op,
+                    Box::new(self.expr(lhs)?),
+                    Box::new(self.expr(rhs)?),
+                ))
+            }
+            CALL_EXPR => {
+                let callee = node
+                    .expr()
+                    .ok_or_else(|| self.error("expected expression", &node.span))?;
+                let args = self.args(node.node(ARG_LIST))?;
+                match callee.kind {
+                    NAME_REF => Ok(Expr::Call(self.name(callee)?, args)),
+                    FIELD_EXPR => {
+                        let (receiver, method) = self.field(callee)?;
+                        Ok(Expr::Method(Box::new(receiver), method, args))
+                    }
+                    _ => Err(self.error("only functions and methods can be called", &callee.span)),
+                }
+            }
+            // `value.method` without parentheses calls it with no arguments
+            FIELD_EXPR => {
+                let (receiver, method) = self.field(node)?;
+                Ok(Expr::Method(Box::new(receiver), method, Vec::new()))
+            }
+            BLOCK => Ok(Expr::Handler(Arc::new(self.block(node)?))),
+            ACTION_EXPR => {
+                let block = node
+                    .node(BLOCK)
+                    .ok_or_else(|| self.error("expected `{`", &node.span))?;
+                Ok(Expr::Handler(Arc::new(self.block(block)?)))
+            }
+            ELEMENT => Err(self.error("elements can only be children", &node.span)),
+            _ => Err(self.error("unsupported expression", &node.span)),
+        }
+    }
+
+    /// Receiver and name of `receiver.name`
+    fn field(&self, node: &SyntaxNode) -> Result<(Expr, String)> {
+        Ok((self.operand(node)?, self.name(node)?))
+    }
+
+    fn args(&self, list: Option<&SyntaxNode>) -> Result<Vec<Expr>> {
+        let Some(list) = list else {
+            return Ok(Vec::new());
+        };
+        list.nodes()
+            .map(|arg| match arg.kind {
+                PROPERTY => Err(self.error("named arguments are not supported", &arg.span)),
+                _ => self.expr(arg),
+            })
+            .collect()
+    }
+
+    fn literal(&self, node: &SyntaxNode) -> Result<Expr> {
+        let token = node
+            .tokens()
+            .next()
+            .ok_or_else(|| self.error("expected expression", &node.span))?;
+        match token.kind {
+            INT | FLOAT => self.number(token),
+            STRING => self.string(token),
+            COLOR => crate::value::parse_color(&token.text)
+                .map(|color| Expr::Literal(color.into()))
+                .ok_or_else(|| self.error("invalid color literal", &token.span)),
+            _ => Ok(Expr::Literal(Value::Bool(token.text == "true"))),
+        }
+    }
+
+    fn number(&self, token: &SyntaxToken) -> Result<Expr> {
+        let digits = token
+            .text
+            .trim_end_matches(|c: char| c.is_ascii_alphabetic());
+        let suffix = &token.text[digits.len()..];
+        if !suffix.is_empty() && !NUMBER_SUFFIXES.contains(&suffix) {
+            return Err(InterpreterError::parse(
+                format!("unknown unit `{}`", suffix),
+                self.base + token.span.start + digits.len(),
+            ));
+        }
+
+        let value = if token.kind == FLOAT {
+            digits.parse().map(Value::Float).ok()
+        } else {
+            digits.parse().map(Value::Int).ok()
+        };
+        value
+            .map(Expr::Literal)
+            .ok_or_else(|| self.error("number out of range", &token.span))
+    }
+
+    /// A string literal, split into text and `{expr}` segments
+    fn string(&self, token: &SyntaxToken) -> Result<Expr> {
+        // Offset of the text after the opening quote
+        let start = self.base + token.span.start + 1;
+        let body = &token.text[1..token.text.len() - 1];
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = body.char_indices();
+
+        while let Some((i, c)) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some((_, 'n')) => literal.push('\n'),
+                    Some((_, 't')) => literal.push('\t'),
+                    Some((_, other)) => literal.push(other),
+                    None => {}
+                },
+                '{' => {
+                    let mut depth = 1;
+                    let end = chars
+                        .by_ref()
+                        .find(|&(_, c)| {
+                            match c {
+                                '{' => depth += 1,
+                                '}' => depth -= 1,
+                                _ => {}
+                            }
+                            depth == 0
+                        })
+                        .map(|(j, _)| j)
+                        .ok_or_else(|| {
+                            InterpreterError::parse("unterminated interpolation", start + i)
+                        })?;
+                    if !literal.is_empty() {
+                        segments.push(Segment::Lit(std::mem::take(&mut literal)));
+                    }
+                    let expr = parse_expr_at(&body[i + 1..end], start + i + 1)?;
+                    segments.push(Segment::Expr(expr));
+                }
+                _ => literal.push(c),
+            }
+        }
+
+        if segments.is_empty() {
+            return Ok(Expr::Literal(Value::Str(literal)));
+        }
+        if !literal.is_empty() {
+            segments.push(Segment::Lit(literal));
+        }
+        Ok(Expr::Interpolated(segments))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_expr_precedence() {
+        let expr = parse_expr("1 + 2 * count > 4 && !done").unwrap();
+        let Expr::Binary(BinaryOp::And, lhs, rhs) = expr else {
+            panic!("expected &&");
+        };
+        assert!(matches!(*lhs, Expr::Binary(BinaryOp::Gt, _, _)));
+        assert_eq!(
+            *rhs,
+            Expr::Unary(UnaryOp::Not, Box::new(Expr::Ident("done".into())))
+        );
+    }
+
+    #[test]
+    fn test_parse_interpolation() {
+        let expr = parse_expr(r#""Count: {count * 2}!""#).unwrap();
+        let Expr::Interpolated(segments) = expr else {
+            panic!("expected interpolation");
+        };
+        assert_eq!(segments.len(), 3);
+        assert_eq!(segments[0], Segment::Lit("Count: ".into()));
+        assert_eq!(segments[2], Segment::Lit("!".into()));
+
+        assert_eq!(
+            parse_expr(r#""a\{b\n""#).unwrap(),
+            Expr::Literal(Value::Str("a{b\n".into()))
+        );
+    }
+
+    #[test]
+    fn test_parse_literals() {
+        assert_eq!(parse_expr("16px").unwrap(), Expr::Literal(Value::Int(16)));
+        assert_eq!(parse_expr("1.5").unwrap(), Expr::Literal(Value::Float(1.5)));
+        assert!(matches!(
+            parse_expr("count.to_string()").unwrap(),
+            Expr::Method(_, method, args) if method == "to_string" && args.is_empty()
+        ));
+        assert!(matches!(
+            parse_expr("clamp(count, 0, 2)").unwrap(),
+            Expr::Call(name, args) if name == "clamp" && args.len() == 3
+        ));
+    }
+
+    #[test]
+    fn test_parse_view() {
+        let nodes = parse_view(
+            r#"
+            Column {
+                spacing: 16
+                // comment
+                Button {
+                    label: "-"
+                    on_click: { count -= 1 }
+                }
+                Text { content: "{count}", color: #666 }
+                if count > 0 { "positive" } else { Text {} }
+                children: [ Box {}, Box {} ]
+            }
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(nodes.len(), 1);
+        let Node::Element {
+            name,
+            props,
+            children,
+        } = &nodes[0]
+        else {
+            panic!("expected element");
+        };
+        assert_eq!(name, "Column");
+        assert_eq!(props.len(), 1);
+        assert_eq!(children.len(), 5);
+        assert!(matches!(children[2], Node::If { .. }));
+
+        let Node::Element { props, .. } = &children[0] else {
+            panic!("expected button");
+        };
+        assert!(matches!(&props[1].1, Expr::Handler(stmts) if stmts.len() == 1));
+    }
+
+    #[test]
+    fn test_parse_errors_have_offsets() {
+        let err = parse_view("Column { spacing: }").unwrap_err();
+        assert!(matches!(err, InterpreterError::Parse { offset: 18, .. }));
+        assert!(parse_expr("\"open").is_err());
+
+        // Inside an interpolation, offsets are into the whole source
+        let err = parse_expr(r#""a {1 +}""#).unwrap_err();
+        assert!(matches!(err, InterpreterError::Parse { offset: 7, .. }));
+        let err = parse_expr("10em").unwrap_err();
+        assert!(matches!(err, InterpreterError::Parse { offset: 2, .. }));
+        let err = parse_view("Column { on_click: { let x = 1 } }").unwrap_err();
+        assert!(matches!(err, InterpreterError::Parse { offset: 21, .. }));
+    }
+}
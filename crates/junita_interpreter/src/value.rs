@@ -0,0 +1,289 @@
+//! Dynamic values
+//!
+//! Everything the interpreter computes is a [`Value`]: literals, props, state
+//! signals, derived values and spring positions.
+
+use junita_core::Color;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use crate::error::{InterpreterError, Result};
+
+/// A dynamically typed DSL value
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub enum Value {
+    /// No value
+    #[default]
+    Unit,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    Str(String),
+    /// RGBA color (from `#hex` literals and `rgb()`/`rgba()`)
+    Color([f32; 4]),
+}
+
+impl Value {
+    /// Default value for a declared DSL type
+    ///
+    /// Both the DSL spellings (`Int`, `String`) and Rust spellings (`i32`,
+    /// `String`) are accepted.
+    pub fn default_for(type_name: &str) -> Value {
+        match type_name {
+            "Int" | "i8" | "i16" | "i32" | "i64" | "u8" | "u16" | "u32" | "u64" | "usize"
+            | "isize" => Value::Int(0),
+            "Float" | "f32" | "f64" => Value::Float(0.0),
+            "Bool" | "bool" => Value::Bool(false),
+            "String" | "str" | "&str" => Value::Str(String::new()),
+            "Color" => Value::Color([0.0, 0.0, 0.0, 1.0]),
+            _ => Value::Unit,
+        }
+    }
+
+    /// Convert numbers to the representation of a declared type
+    ///
+    /// Other values are returned unchanged.
+    pub fn coerce(self, type_name: &str) -> Value {
+        match (Value::default_for(type_name), self) {
+            (Value::Float(_), Value::Int(i)) => Value::Float(i as f64),
+            (Value::Int(_), Value::Float(f)) => Value::Int(f as i64),
+            (_, value) => value,
+        }
+    }
+
+    /// Whether both values have the same variant
+    pub fn same_kind(&self, other: &Value) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// Name of the value's type, for error messages
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Unit => "unit",
+            Value::Bool(_) => "bool",
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Str(_) => "string",
+            Value::Color(_) => "color",
+        }
+    }
+
+    /// Numeric value, if this is a number
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int(i) => Some(*i as f64),
+            Value::Float(f) => Some(*f),
+            _ => None,
+        }
+    }
+
+    /// Numeric value as `f32`, if this is a number
+    pub fn as_f32(&self) -> Option<f32> {
+        self.as_f64().map(|f| f as f32)
+    }
+
+    /// Truthiness used by conditions: `false`, `0`, `""` and unit are false
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Unit => false,
+            Value::Bool(b) => *b,
+            Value::Int(i) => *i != 0,
+            Value::Float(f) => *f != 0.0,
+            Value::Str(s) => !s.is_empty(),
+            Value::Color(_) => true,
+        }
+    }
+
+    /// Interpret the value as a color
+    ///
+    /// Strings may be `#rgb`, `#rrggbb`, `#rrggbbaa`, `rgb(..)`, `rgba(..)`
+    /// or a basic color name.
+    pub fn as_color(&self) -> Option<Color> {
+        match self {
+            Value::Color([r, g, b, a]) => Some(Color::rgba(*r, *g, *b, *a)),
+            Value::Str(s) => parse_color(s),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn type_error(op: &str, lhs: &Value, rhs: &Value) -> InterpreterError {
+        InterpreterError::Type(format!(
+            "cannot apply `{}` to {} and {}",
+            op,
+            lhs.type_name(),
+            rhs.type_name()
+        ))
+    }
+
+    /// `+`: numeric addition, or concatenation if either side is a string
+    pub(crate) fn add(&self, rhs: &Value) -> Result<Value> {
+        match (self, rhs) {
+            (Value::Str(a), b) => Ok(Value::Str(format!("{}{}", a, b))),
+            (a, Value::Str(b)) => Ok(Value::Str(format!("{}{}", a, b))),
+            _ => self.arithmetic("+", rhs, i64::checked_add, |a, b| a + b),
+        }
+    }
+
+    pub(crate) fn arithmetic(
+        &self,
+        op: &str,
+        rhs: &Value,
+        int_op: fn(i64, i64) -> Option<i64>,
+        float_op: fn(f64, f64) -> f64,
+    ) -> Result<Value> {
+        match (self, rhs) {
+            (Value::Int(a), Value::Int(b)) => int_op(*a, *b).map(Value::Int).ok_or_else(|| {
+                InterpreterError::Type(format!("integer overflow or division by zero in `{}`", op))
+            }),
+            _ => match (self.as_f64(), rhs.as_f64()) {
+                (Some(a), Some(b)) => Ok(Value::Float(float_op(a, b))),
+                _ => Err(Value::type_error(op, self, rhs)),
+            },
+        }
+    }
+
+    /// Ordering for `<`, `<=`, `>`, `>=` (numbers and strings)
+    pub(crate) fn compare(&self, op: &str, rhs: &Value) -> Result<std::cmp::Ordering> {
+        match (self, rhs) {
+            (Value::Str(a), Value::Str(b)) => Ok(a.cmp(b)),
+            _ => match (self.as_f64(), rhs.as_f64()) {
+                (Some(a), Some(b)) => a
+                    .partial_cmp(&b)
+                    .ok_or_else(|| InterpreterError::Type("cannot compare NaN".to_string())),
+                _ => Err(Value::type_error(op, self, rhs)),
+            },
+        }
+    }
+
+    /// Equality for `==`/`!=`, treating `1` and `1.0` as equal
+    pub(crate) fn loose_eq(&self, rhs: &Value) -> bool {
+        match (self.as_f64(), rhs.as_f64()) {
+            (Some(a), Some(b)) => a == b,
+            _ => self == rhs,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Unit => Ok(()),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Float(v) => write!(f, "{}", v),
+            Value::Str(s) => f.write_str(s),
+            Value::Color([r, g, b, a]) => write!(
+                f,
+                "#{:02x}{:02x}{:02x}{:02x}",
+                (r * 255.0).round() as u8,
+                (g * 255.0).round() as u8,
+                (b * 255.0).round() as u8,
+                (a * 255.0).round() as u8
+            ),
+        }
+    }
+}
+
+impl From<Color> for Value {
+    fn from(color: Color) -> Self {
+        Value::Color(color.to_array())
+    }
+}
+
+/// Parse a CSS-style color string
+pub(crate) fn parse_color(input: &str) -> Option<Color> {
+    let input = input.trim();
+
+    if let Some(hex) = input.strip_prefix('#') {
+        return parse_hex_color(hex);
+    }
+
+    if let Some(args) = input
+        .strip_prefix("rgba(")
+        .or_else(|| input.strip_prefix("rgb("))
+        .and_then(|rest| rest.strip_suffix(')'))
+    {
+        let parts: Vec<f32> = args
+            .split(',')
+            .map(|p| p.trim().parse::<f32>())
+            .collect::<std::result::Result<_, _>>()
+            .ok()?;
+        return match parts.as_slice() {
+            [r, g, b] => Some(Color::rgb(r / 255.0, g / 255.0, b / 255.0)),
+            [r, g, b, a] => Some(Color::rgba(r / 255.0, g / 255.0, b / 255.0, *a)),
+            _ => None,
+        };
+    }
+
+    match input.to_ascii_lowercase().as_str() {
+        "white" => Some(Color::WHITE),
+        "black" => Some(Color::BLACK),
+        "red" => Some(Color::RED),
+        "green" => Some(Color::GREEN),
+        "blue" => Some(Color::BLUE),
+        "yellow" => Some(Color::YELLOW),
+        "cyan" => Some(Color::CYAN),
+        "magenta" => Some(Color::MAGENTA),
+        "purple" => Some(Color::PURPLE),
+        "orange" => Some(Color::ORANGE),
+        "gray" | "grey" => Some(Color::GRAY),
+        "transparent" => Some(Color::TRANSPARENT),
+        _ => None,
+    }
+}
+
+/// Parse `rgb`, `rgba`, `rrggbb` or `rrggbbaa` hex digits
+fn parse_hex_color(hex: &str) -> Option<Color> {
+    let digits: Vec<u8> = hex
+        .chars()
+        .map(|c| c.to_digit(16).map(|d| d as u8))
+        .collect::<Option<_>>()?;
+    let channels: Vec<f32> = match digits.len() {
+        3 | 4 => digits.iter().map(|d| (d * 17) as f32 / 255.0).collect(),
+        6 | 8 => digits
+            .chunks(2)
+            .map(|pair| (pair[0] * 16 + pair[1]) as f32 / 255.0)
+            .collect(),
+        _ => return None,
+    };
+    Some(Color::rgba(
+        channels[0],
+        channels[1],
+        channels[2],
+        channels.get(3).copied().unwrap_or(1.0),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color() {
+        assert_eq!(parse_color("#fff"), Some(Color::WHITE));
+        assert_eq!(parse_color("#000000"), Some(Color::BLACK));
+        assert_eq!(parse_color("rgb(255, 0, 0)"), Some(Color::RED));
+        assert_eq!(
+            parse_color("rgba(0, 0, 255, 0.5)"),
+            Some(Color::rgba(0.0, 0.0, 1.0, 0.5))
+        );
+        assert_eq!(parse_color("white"), Some(Color::WHITE));
+        assert_eq!(parse_color("#12"), None);
+    }
+
+    #[test]
+    fn test_arithmetic_and_coercion() {
+        assert_eq!(Value::Int(2).add(&Value::Int(3)).unwrap(), Value::Int(5));
+        assert_eq!(
+            Value::Int(2).add(&Value::Float(0.5)).unwrap(),
+            Value::Float(2.5)
+        );
+        assert_eq!(
+            Value::Str("n = ".into()).add(&Value::Int(1)).unwrap(),
+            Value::Str("n = 1".into())
+        );
+        assert!(Value::Bool(true).add(&Value::Int(1)).is_err());
+        assert_eq!(Value::Int(1).coerce("f32"), Value::Float(1.0));
+        assert!(Value::Int(1).loose_eq(&Value::Float(1.0)));
+    }
+}
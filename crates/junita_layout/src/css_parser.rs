@@ -57,8 +57,8 @@ use junita_theme::{ColorToken, ThemeState};
 use nom::{
     branch::alt,
     bytes::complete::{tag, tag_no_case, take_until, take_while1},
-    character::complete::{char, multispace1},
-    combinator::{cut, opt, value},
+    character::complete::{char, multispace0, multispace1},
+    combinator::{cut, opt, recognize, value},
     error::{context, ParseError as NomParseError, VerboseError, VerboseErrorKind},
     multi::many0,
     number::complete::float,
@@ -834,6 +834,28 @@ pub enum AnimationFillMode {
     Both,
 }
 
+/// A node of a stylesheet as written, for tools that rewrite the source
+///
+/// Unlike [`Stylesheet`], nothing is resolved: comments are kept and
+/// values are the source text.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CssNode {
+    /// A `/* ... */` comment, delimiters included
+    Comment {
+        text: String,
+        /// Written on the same line as the node before it
+        trailing: bool,
+    },
+    /// `name: value;`
+    Declaration { name: String, value: String },
+    /// A rule, `:root`, `@keyframes` block or keyframe stop
+    Block {
+        /// Everything before the `{`, like `#card:hover` or `0%, 100%`
+        prelude: String,
+        children: Vec<CssNode>,
+    },
+}
+
 /// A parsed stylesheet containing styles keyed by element ID
 #[derive(Clone, Default, Debug)]
 pub struct Stylesheet {
@@ -875,13 +897,17 @@ impl Stylesheet {
 
         match parse_stylesheet_with_errors(css, &mut errors, &initial_vars).finish() {
             Ok((remaining, parsed)) => {
-                // Warn if there's unparsed content
-                let remaining = remaining.trim();
-                if !remaining.is_empty() {
+                // Warn if there's unparsed content; only leading whitespace is
+                // trimmed so the rest is still a suffix of `css` for positions
+                let remaining = remaining.trim_start();
+                if !remaining.trim_end().is_empty() {
                     let (line, column, fragment) = calculate_position(css, remaining);
                     errors.push(ParseError {
                         severity: Severity::Warning,
-                        message: format!("Unparsed content remaining ({} chars)", remaining.len()),
+                        message: format!(
+                            "Unparsed content remaining ({} chars)",
+                            remaining.trim_end().len()
+                        ),
                         line,
                         column,
                         fragment,
@@ -956,6 +982,27 @@ impl Stylesheet {
         Self::parse(css).unwrap_or_default()
     }
 
+    /// Parse CSS text into its source nodes, keeping comments
+    ///
+    /// Accepts the same rules as [`parse_with_errors`](Self::parse_with_errors)
+    /// but doesn't interpret declarations, so unknown properties and values
+    /// are not errors. The error is boxed since `ParseError` is large.
+    pub fn parse_syntax(css: &str) -> Result<Vec<CssNode>, Box<ParseError>> {
+        let (remaining, nodes) = syntax_nodes(css, syntax_item)
+            .finish()
+            .map_err(|e| Box::new(ParseError::from_verbose(css, e)))?;
+        if !remaining.is_empty() {
+            let (line, column, _) = calculate_position(css, remaining);
+            return Err(Box::new(ParseError::new(
+                Severity::Error,
+                "Unexpected closing brace",
+                line,
+                column,
+            )));
+        }
+        Ok(nodes)
+    }
+
     /// Get a style by element ID (without the # prefix)
     ///
     /// Returns `None` if no style is defined for the given ID.
@@ -1043,6 +1090,15 @@ impl Stylesheet {
         self.styles.is_empty()
     }
 
+    /// Add all rules, variables and keyframes from `other`
+    ///
+    /// Rules for a selector that already exists are replaced.
+    pub fn extend(&mut self, other: Stylesheet) {
+        self.styles.extend(other.styles);
+        self.variables.extend(other.variables);
+        self.keyframes.extend(other.keyframes);
+    }
+
     // =========================================================================
     // CSS Variables (Custom Properties)
     // =========================================================================
@@ -1492,6 +1548,89 @@ fn keyframe_position(input: &str) -> ParseResult<f32> {
     ))(input)
 }
 
+/// Parse comments and items up to a closing brace or the end of input
+fn syntax_nodes<'a>(
+    mut input: &'a str,
+    item: fn(&'a str) -> ParseResult<'a, CssNode>,
+) -> ParseResult<'a, Vec<CssNode>> {
+    let mut nodes = Vec::new();
+    loop {
+        let (rest, space) = multispace0(input)?;
+        input = rest;
+        if input.is_empty() || input.starts_with('}') {
+            return Ok((input, nodes));
+        }
+        if let Ok((rest, text)) = recognize(parse_comment::<VerboseError<&str>>)(input) {
+            nodes.push(CssNode::Comment {
+                text: text.to_string(),
+                trailing: !nodes.is_empty() && !space.contains('\n'),
+            });
+            input = rest;
+            continue;
+        }
+        let (rest, node) = item(input)?;
+        nodes.push(node);
+        input = rest;
+    }
+}
+
+/// Parse a top-level rule, `:root` or `@keyframes` block
+fn syntax_item(input: &str) -> ParseResult<'_, CssNode> {
+    context(
+        "CSS rule",
+        alt((syntax_keyframes, |i| {
+            let (i, prelude) = alt((recognize(id_selector), tag(":root")))(i)?;
+            syntax_block(i, prelude.to_string(), syntax_declaration)
+        })),
+    )(input)
+}
+
+/// Parse `@keyframes name { stops }`
+fn syntax_keyframes(input: &str) -> ParseResult<'_, CssNode> {
+    let (input, _) = tag("@keyframes")(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, name) = cut(identifier)(input)?;
+    syntax_block(input, format!("@keyframes {}", name), |i| {
+        let (i, positions) = context("keyframe position", recognize(keyframe_positions))(i)?;
+        let prelude = positions
+            .split(',')
+            .map(str::trim)
+            .collect::<Vec<_>>()
+            .join(", ");
+        syntax_block(i, prelude, syntax_declaration)
+    })
+}
+
+/// Parse the braced part of a block whose prelude was already read
+fn syntax_block<'a>(
+    input: &'a str,
+    prelude: String,
+    item: fn(&'a str) -> ParseResult<'a, CssNode>,
+) -> ParseResult<'a, CssNode> {
+    let (input, _) = multispace0(input)?;
+    let (input, _) = context("opening brace", char('{'))(input)?;
+    let (input, children) = syntax_nodes(input, item)?;
+    let (input, _) = context("closing brace", char('}'))(input)?;
+    Ok((input, CssNode::Block { prelude, children }))
+}
+
+/// Parse a declaration without interpreting it
+fn syntax_declaration(input: &str) -> ParseResult<'_, CssNode> {
+    let (input, name) = property_name(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, _) = context("colon after property name", char(':'))(input)?;
+    let (input, _) = multispace0(input)?;
+    let (input, value) = property_value(input)?;
+    let (input, _) = opt(char(';'))(input)?;
+    Ok((
+        input,
+        CssNode::Declaration {
+            name: name.to_string(),
+            value: value.to_string(),
+        },
+    ))
+}
+
 /// Parsed content from a stylesheet - can be either a rule or variables
 enum CssBlock {
     Rule(String, ElementStyle),
@@ -3574,6 +3713,17 @@ mod tests {
         assert!(has_multiline_errors, "Should have errors on lines > 1");
     }
 
+    #[test]
+    fn test_parse_with_errors_unparsed_trailing_content() {
+        // Trailing whitespace after multi-byte leftovers used to slice the
+        // source off a char boundary when locating the warning
+        let result = Stylesheet::parse_with_errors("#a { opacity: 0.5; }\n۞ ");
+        let warning = result.warnings_only().next().expect("unparsed warning");
+        assert_eq!(warning.line, 2);
+        assert_eq!(warning.column, 1);
+        assert_eq!(warning.fragment, "۞ ");
+    }
+
     #[test]
     fn test_parse_with_errors_severity_levels() {
         // Create various error types and check severity
@@ -3615,6 +3765,18 @@ mod tests {
         assert!(!result.has_warnings());
     }
 
+    #[test]
+    fn test_stylesheet_extend_replaces_rules() {
+        let mut base = Stylesheet::parse("#card { opacity: 0.5; } #other { opacity: 0.2; }").unwrap();
+        let edits = Stylesheet::parse("#card { opacity: 0.9; }").unwrap();
+
+        base.extend(edits);
+
+        assert_eq!(base.len(), 2);
+        assert_eq!(base.get("card").unwrap().opacity, Some(0.9));
+        assert_eq!(base.get("other").unwrap().opacity, Some(0.2));
+    }
+
     // ========================================================================
     // CSS Variables Tests
     // ========================================================================
@@ -4915,4 +5077,56 @@ mod tests {
             panic!("Expected Affine2D transform to be parsed");
         }
     }
+
+    #[test]
+    fn test_parse_syntax_keeps_source() {
+        let css = r#"/* Theme */
+:root { --accent: #FF0000; }
+#card:hover {
+    opacity: 0.5; /* dimmed */
+    unknown-prop:  some   value
+}
+@keyframes fade { FROM, 100% { opacity: 0; } }"#;
+        let nodes = Stylesheet::parse_syntax(css).unwrap();
+
+        assert_eq!(nodes.len(), 4);
+        assert_eq!(
+            nodes[0],
+            CssNode::Comment {
+                text: "/* Theme */".into(),
+                trailing: false
+            }
+        );
+        let CssNode::Block { prelude, children } = &nodes[2] else {
+            panic!("expected a rule, got {:?}", nodes[2]);
+        };
+        assert_eq!(prelude, "#card:hover");
+        assert_eq!(
+            children[1],
+            CssNode::Comment {
+                text: "/* dimmed */".into(),
+                trailing: true
+            }
+        );
+        assert_eq!(
+            children[2],
+            CssNode::Declaration {
+                name: "unknown-prop".into(),
+                value: "some   value".into()
+            }
+        );
+        let CssNode::Block { prelude, children } = &nodes[3] else {
+            panic!("expected keyframes, got {:?}", nodes[3]);
+        };
+        assert_eq!(prelude, "@keyframes fade");
+        assert!(matches!(&children[0], CssNode::Block { prelude, .. } if prelude == "FROM, 100%"));
+    }
+
+    #[test]
+    fn test_parse_syntax_errors() {
+        let err = Stylesheet::parse_syntax("#a { opacity: 1; }\n}").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+        assert!(Stylesheet::parse_syntax("#a { opacity 1; }").is_err());
+        assert!(Stylesheet::parse_syntax("#a { opacity: 1;").is_err());
+    }
 }
@@ -0,0 +1,716 @@
+//! Damage tracking for partial redraw
+//!
+//! Computes which parts of the window changed between two frames so the
+//! renderer can limit clearing and drawing to that region instead of
+//! repainting everything.
+//!
+//! Content changes come from the tree itself: incremental updates, subtree
+//! rebuilds and style updates record the [`ChangeCategory`](crate::diff::ChangeCategory) of every node
+//! they touch (see [`RenderTree::changes_since`]), and the [`DamageTracker`]
+//! damages the nodes that changed visually or in layout since the frame it
+//! last saw. What moves without going through the tree - motion values,
+//! transforms, scroll offsets and scrollbars - is compared against a small
+//! per-node record from the previous frame, together with the node's paint
+//! bounds in window space. The old and new bounds of every damaged node are
+//! repainted, plus the bounds of nodes that appeared or disappeared.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use junita_layout::damage::DamageTracker;
+//!
+//! let mut tracker = DamageTracker::new();
+//!
+//! // Every frame, after the tree has been updated and animations ticked:
+//! let damage = tracker.compute(&tree, &render_state, (width, height));
+//! if damage.is_empty() {
+//!     // Nothing changed - the previous frame can be presented again
+//! } else if let Some([x, y, w, h]) = damage.scissor(scale, phys_w, phys_h) {
+//!     // Redraw only the damaged region
+//! } else {
+//!     // Full redraw
+//! }
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+
+use junita_core::{Affine2D, Point, Rect, Transform};
+
+use crate::element::{MotionKeyframe, RenderProps};
+use crate::render_state::{Overlay, RenderState};
+use crate::renderer::{ElementType, RenderTree};
+use crate::tree::LayoutNodeId;
+
+/// Extra margin around damaged bounds to cover anti-aliased edges
+const AA_MARGIN: f32 = 2.0;
+
+/// Above this many rectangles a region collapses into its bounding box
+const MAX_DAMAGE_RECTS: usize = 16;
+
+// ============================================================================
+// Damage Region
+// ============================================================================
+
+/// The set of window areas that need repainting this frame
+///
+/// Rectangles are in logical pixels. Overlapping rectangles are merged as
+/// they are added, and the region collapses into a single bounding box once
+/// it holds more than a handful of rectangles.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DamageRegion {
+    rects: Vec<Rect>,
+    full: bool,
+}
+
+impl DamageRegion {
+    /// Create an empty region (nothing to repaint)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a region covering the whole window
+    pub fn full() -> Self {
+        Self {
+            rects: Vec::new(),
+            full: true,
+        }
+    }
+
+    /// Add a rectangle to the region
+    pub fn add(&mut self, rect: Rect) {
+        if self.full || rect.width() <= 0.0 || rect.height() <= 0.0 {
+            return;
+        }
+
+        // Merge with every rectangle the new one overlaps, repeating until
+        // the merged rectangle no longer touches anything
+        let mut merged = rect;
+        loop {
+            let before = self.rects.len();
+            self.rects.retain(|r| {
+                if r.intersects(&merged) {
+                    merged = merged.union(r);
+                    false
+                } else {
+                    true
+                }
+            });
+            if self.rects.len() == before {
+                break;
+            }
+        }
+        self.rects.push(merged);
+
+        if self.rects.len() > MAX_DAMAGE_RECTS {
+            let bounds = self.bounds();
+            self.rects.clear();
+            self.rects.extend(bounds);
+        }
+    }
+
+    /// Mark the whole window as damaged
+    pub fn add_full(&mut self) {
+        self.full = true;
+        self.rects.clear();
+    }
+
+    /// Whether nothing needs repainting
+    pub fn is_empty(&self) -> bool {
+        !self.full && self.rects.is_empty()
+    }
+
+    /// Whether the whole window needs repainting
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+
+    /// Damaged rectangles in logical pixels (empty for a full region)
+    pub fn rects(&self) -> &[Rect] {
+        &self.rects
+    }
+
+    /// Bounding box of all damaged rectangles
+    pub fn bounds(&self) -> Option<Rect> {
+        let mut rects = self.rects.iter();
+        let first = *rects.next()?;
+        Some(rects.fold(first, |acc, r| acc.union(r)))
+    }
+
+    /// Scissor rectangle `[x, y, width, height]` in physical pixels
+    ///
+    /// Converts the bounding box to physical pixels, rounding outwards and
+    /// clamping to the surface. Returns `None` when the whole surface has to
+    /// be repainted, either because the region is full or because its
+    /// bounding box covers the surface anyway.
+    pub fn scissor(&self, scale_factor: f32, width: u32, height: u32) -> Option<[u32; 4]> {
+        if self.full {
+            return None;
+        }
+        let Some(bounds) = self.bounds() else {
+            return Some([0, 0, 0, 0]);
+        };
+
+        let x0 = (bounds.x() * scale_factor).floor().clamp(0.0, width as f32) as u32;
+        let y0 = (bounds.y() * scale_factor)
+            .floor()
+            .clamp(0.0, height as f32) as u32;
+        let x1 = ((bounds.x() + bounds.width()) * scale_factor)
+            .ceil()
+            .clamp(0.0, width as f32) as u32;
+        let y1 = ((bounds.y() + bounds.height()) * scale_factor)
+            .ceil()
+            .clamp(0.0, height as f32) as u32;
+
+        if x0 == 0 && y0 == 0 && x1 == width && y1 == height {
+            None
+        } else {
+            Some([x0, y0, x1 - x0, y1 - y0])
+        }
+    }
+}
+
+// ============================================================================
+// Damage Tracker
+// ============================================================================
+
+/// What the tracker remembers about a node between frames
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct NodeDamageState {
+    /// Paint bounds in window space (logical pixels), clipped to ancestors
+    bounds: Rect,
+    /// Hash of the per-frame state that changes outside the tree's change
+    /// tracking: motion values, world transform and scrollbar
+    fingerprint: u64,
+}
+
+/// Computes per-frame damage by comparing the render tree against the
+/// previous frame
+///
+/// The tracker needs to see every frame that is rendered; call
+/// [`invalidate_all`](Self::invalidate_all) whenever something outside the
+/// tree changes what is on screen (theme switch, surface recreation, a frame
+/// rendered without the tracker) so the next frame is repainted in full.
+#[derive(Debug, Default)]
+pub struct DamageTracker {
+    nodes: HashMap<LayoutNodeId, NodeDamageState>,
+    overlays: Vec<Rect>,
+    viewport: Option<(f32, f32)>,
+    /// [`RenderTree::tree_id`] and change generation seen last frame
+    seen: Option<(u64, u64)>,
+    needs_full: bool,
+}
+
+impl DamageTracker {
+    /// Create a tracker; the first computed frame is always fully damaged
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Force the next computed frame to be fully damaged
+    pub fn invalidate_all(&mut self) {
+        self.needs_full = true;
+    }
+
+    /// Compute the damage for the current frame
+    ///
+    /// `viewport` is the window size in logical pixels. A viewport change or
+    /// a different tree (a full rebuild) damages the whole window.
+    pub fn compute(
+        &mut self,
+        tree: &RenderTree,
+        render_state: &RenderState,
+        viewport: (f32, f32),
+    ) -> DamageRegion {
+        let mut current = HashMap::with_capacity(self.nodes.len());
+        if let Some(root) = tree.root() {
+            let window = Rect::new(0.0, 0.0, viewport.0, viewport.1);
+            self.collect_node(
+                tree,
+                render_state,
+                root,
+                Some(Affine2D::IDENTITY),
+                window,
+                &mut current,
+            );
+        }
+        let overlays = overlay_bounds(render_state, tree.scale_factor());
+
+        let full = self.needs_full
+            || self.viewport != Some(viewport)
+            || self.seen.map(|(tree_id, _)| tree_id) != Some(tree.tree_id());
+        let mut damage = if full {
+            DamageRegion::full()
+        } else {
+            DamageRegion::new()
+        };
+
+        if !full {
+            let generation = self.seen.map_or(0, |(_, generation)| generation);
+            // Canvas nodes draw through a callback the tree can't see into
+            let changed = |node: LayoutNodeId| -> bool {
+                tree.changes_since(node, generation).needs_repaint()
+                    || tree
+                        .get_render_node(node)
+                        .is_some_and(|n| matches!(n.element_type, ElementType::Canvas(_)))
+            };
+
+            for (node, state) in &current {
+                match self.nodes.get(node) {
+                    Some(prev) if prev == state && !changed(*node) => {}
+                    Some(prev) => {
+                        damage.add(prev.bounds);
+                        damage.add(state.bounds);
+                    }
+                    None => damage.add(state.bounds),
+                }
+            }
+            for (node, prev) in &self.nodes {
+                if !current.contains_key(node) {
+                    damage.add(prev.bounds);
+                }
+            }
+            if overlays != self.overlays {
+                for rect in self.overlays.iter().chain(&overlays) {
+                    damage.add(*rect);
+                }
+            }
+        }
+
+        self.nodes = current;
+        self.overlays = overlays;
+        self.viewport = Some(viewport);
+        self.seen = Some((tree.tree_id(), tree.change_generation()));
+        self.needs_full = false;
+        damage
+    }
+
+    /// Record a node and its subtree
+    ///
+    /// Mirrors the transform stack of `RenderTree::render_with_motion`.
+    /// `parent` is `None` below a 3D transform, where bounds can't be mapped
+    /// and the clip rectangle is used instead.
+    fn collect_node(
+        &self,
+        tree: &RenderTree,
+        render_state: &RenderState,
+        node: LayoutNodeId,
+        parent: Option<Affine2D>,
+        clip: Rect,
+        out: &mut HashMap<LayoutNodeId, NodeDamageState>,
+    ) {
+        let Some(bounds) = tree.get_render_bounds(node, (0.0, 0.0)) else {
+            return;
+        };
+        let Some(render_node) = tree.get_render_node(node) else {
+            return;
+        };
+
+        let removed = match &render_node.props.motion_stable_id {
+            Some(key) => render_state.is_stable_motion_removed(key),
+            None => render_state.is_motion_removed(node),
+        };
+        if removed {
+            return;
+        }
+
+        let motion = match &render_node.props.motion_stable_id {
+            Some(key) => render_state.get_stable_motion_values(key),
+            None => render_state.get_motion_values(node),
+        };
+        let binding_transform = tree.get_motion_transform(node);
+        let binding_scale = tree.get_motion_scale(node);
+        let binding_rotation = tree.get_motion_rotation(node);
+        let binding_opacity = tree.get_motion_opacity(node);
+
+        let (w, h) = (bounds.width, bounds.height);
+        let centered = |t: Affine2D| {
+            Affine2D::translation(w / 2.0, h / 2.0)
+                .then(&t)
+                .then(&Affine2D::translation(-w / 2.0, -h / 2.0))
+        };
+
+        let mut local = Some(Affine2D::translation(bounds.x, bounds.y));
+        let mut push = |t: Option<Affine2D>| {
+            local = match (local, t) {
+                (Some(l), Some(t)) => Some(l.then(&t)),
+                _ => None,
+            };
+        };
+        if let Some(m) = motion {
+            let (tx, ty) = m.resolved_translate();
+            push(Some(Affine2D::translation(tx, ty)));
+            let (sx, sy) = m.resolved_scale();
+            push(Some(centered(Affine2D::scale(sx, sy))));
+        }
+        if let Some(t) = &binding_transform {
+            push(as_affine(t));
+        }
+        if let Some((sx, sy)) = binding_scale {
+            push(Some(centered(Affine2D::scale(sx, sy))));
+        }
+        if let Some(deg) = binding_rotation {
+            push(Some(centered(Affine2D::rotation(deg.to_radians()))));
+        }
+        if let Some(t) = &render_node.props.transform {
+            push(as_affine(t).map(centered));
+        }
+        let world = match (parent, local) {
+            (Some(p), Some(l)) => Some(p.then(&l)),
+            _ => None,
+        };
+
+        let paint = paint_rect(&render_node.props, &render_node.element_type, w, h);
+        let window_bounds = match world {
+            Some(world) => map_rect(&world, paint)
+                .intersection(&clip)
+                .unwrap_or_default(),
+            None => clip,
+        };
+
+        // Props and content are covered by the tree's change tracking
+        let mut hasher = DefaultHasher::new();
+        hash_motion(motion, binding_opacity, &mut hasher);
+        if let Some(world) = world {
+            world.elements.map(f32::to_bits).hash(&mut hasher);
+        }
+        if let Some(info) = tree.get_scrollbar_render_info(node) {
+            info.opacity.to_bits().hash(&mut hasher);
+            std::mem::discriminant(&info.state).hash(&mut hasher);
+        }
+
+        out.insert(
+            node,
+            NodeDamageState {
+                bounds: window_bounds,
+                fingerprint: hasher.finish(),
+            },
+        );
+
+        // Children are clipped by clipping containers and shifted by scroll
+        let child_clip = match world {
+            Some(world) if render_node.props.clips_content => {
+                map_rect(&world, Rect::new(0.0, 0.0, w, h))
+                    .intersection(&clip)
+                    .unwrap_or_default()
+            }
+            _ => clip,
+        };
+        let (scroll_x, scroll_y) = tree.get_scroll_offset(node);
+        let child_world = world.map(|w| w.then(&Affine2D::translation(scroll_x, scroll_y)));
+
+        for child in tree.layout().children(node) {
+            self.collect_node(tree, render_state, child, child_world, child_clip, out);
+        }
+    }
+}
+
+/// 2D part of a transform, `None` for 3D transforms
+fn as_affine(transform: &Transform) -> Option<Affine2D> {
+    match transform {
+        Transform::Affine2D(affine) => Some(*affine),
+        Transform::Mat4(_) => None,
+    }
+}
+
+/// Local-space area a node can paint to, including shadow and borders
+fn paint_rect(props: &RenderProps, element: &ElementType, width: f32, height: f32) -> Rect {
+    let (mut width, mut height) = (width, height);
+    if let ElementType::Text(text) = element {
+        // Unwrapped text can overflow its layout box
+        width = width.max(text.measured_width);
+        height = height.max(text.font_size * text.line_height);
+    }
+
+    let mut rect = Rect::new(0.0, 0.0, width, height).inset(
+        -(props.border_width + AA_MARGIN),
+        -(props.border_width + AA_MARGIN),
+    );
+    if let Some(shadow) = &props.shadow {
+        let spread = shadow.blur * 2.0 + shadow.spread.max(0.0);
+        let shadow_rect = Rect::new(0.0, 0.0, width, height)
+            .offset(shadow.offset_x, shadow.offset_y)
+            .inset(-spread, -spread);
+        rect = rect.union(&shadow_rect);
+    }
+    rect
+}
+
+/// Axis-aligned bounds of a rectangle after a transform
+fn map_rect(transform: &Affine2D, rect: Rect) -> Rect {
+    let corners = [
+        Point::new(rect.x(), rect.y()),
+        Point::new(rect.x() + rect.width(), rect.y()),
+        Point::new(rect.x(), rect.y() + rect.height()),
+        Point::new(rect.x() + rect.width(), rect.y() + rect.height()),
+    ];
+    let first = transform.transform_point(corners[0]);
+    let mut out = Rect::new(first.x, first.y, 0.0, 0.0);
+    for corner in &corners[1..] {
+        out = out.expand_to_include(transform.transform_point(*corner));
+    }
+    out
+}
+
+/// Window-space bounds of the `RenderState` overlays (cursor, focus ring)
+///
+/// Overlays are positioned in physical pixels, so they are scaled back to
+/// logical pixels here.
+fn overlay_bounds(render_state: &RenderState, scale_factor: f32) -> Vec<Rect> {
+    let scale = if scale_factor > 0.0 {
+        scale_factor
+    } else {
+        1.0
+    };
+    let logical = |x: f32, y: f32, w: f32, h: f32, pad: f32| {
+        Rect::new(x / scale, y / scale, w / scale, h / scale).inset(-pad, -pad)
+    };
+
+    let mut rects = Vec::new();
+    for overlay in render_state.overlays() {
+        match overlay {
+            Overlay::Cursor {
+                position,
+                size,
+                opacity,
+                ..
+            } => {
+                if *opacity > 0.0 {
+                    rects.push(logical(position.0, position.1, size.0, size.1, AA_MARGIN));
+                }
+            }
+            Overlay::Selection { rects: sel, .. } => {
+                for &(x, y, w, h) in sel {
+                    rects.push(logical(x, y, w, h, AA_MARGIN));
+                }
+            }
+            Overlay::FocusRing {
+                position,
+                size,
+                thickness,
+                ..
+            } => {
+                rects.push(logical(
+                    position.0,
+                    position.1,
+                    size.0,
+                    size.1,
+                    thickness + AA_MARGIN,
+                ));
+            }
+        }
+    }
+    rects
+}
+
+pub(crate) fn hash_motion(
+    motion: Option<&MotionKeyframe>,
+    binding_opacity: Option<f32>,
+    hasher: &mut impl Hasher,
+) {
+    if let Some(m) = motion {
+        for value in [
+            m.opacity,
+            m.scale_x,
+            m.scale_y,
+            m.translate_x,
+            m.translate_y,
+            m.rotate,
+        ] {
+            value.map(f32::to_bits).hash(hasher);
+        }
+    }
+    binding_opacity.map(f32::to_bits).hash(hasher);
+}
+
+/// Hash the element content that isn't covered by `RenderProps`
+pub(crate) fn hash_element_type(element: &ElementType, hasher: &mut impl Hasher) {
+    std::mem::discriminant(element).hash(hasher);
+    match element {
+        ElementType::Div | ElementType::Canvas(_) => {}
+        ElementType::Text(text) => {
+            text.content.hash(hasher);
+            text.color.map(f32::to_bits).hash(hasher);
+            for value in [
+                text.font_size,
+                text.line_height,
+                text.word_spacing,
+                text.ascender,
+            ] {
+                value.to_bits().hash(hasher);
+            }
+            text.weight.weight().hash(hasher);
+            text.font_family.name.hash(hasher);
+            std::mem::discriminant(&text.font_family.generic).hash(hasher);
+            std::mem::discriminant(&text.align).hash(hasher);
+            std::mem::discriminant(&text.v_align).hash(hasher);
+            (text.italic, text.wrap, text.underline, text.strikethrough).hash(hasher);
+        }
+        ElementType::StyledText(styled) => {
+            styled.content.hash(hasher);
+            styled.default_color.map(f32::to_bits).hash(hasher);
+            styled.font_size.to_bits().hash(hasher);
+            styled.line_height.to_bits().hash(hasher);
+            styled.weight.weight().hash(hasher);
+            styled.italic.hash(hasher);
+            std::mem::discriminant(&styled.align).hash(hasher);
+            for span in &styled.spans {
+                (span.start, span.end).hash(hasher);
+                span.color.map(f32::to_bits).hash(hasher);
+                (span.bold, span.italic, span.underline, span.strikethrough).hash(hasher);
+            }
+        }
+        ElementType::Svg(svg) => {
+            svg.source.hash(hasher);
+            svg.tint
+                .map(|c| c.to_array().map(f32::to_bits))
+                .hash(hasher);
+        }
+        ElementType::Image(image) => {
+            image.source.hash(hasher);
+            (
+                image.object_fit,
+                image.loading_strategy,
+                image.placeholder_type,
+            )
+                .hash(hasher);
+            image.object_position.map(f32::to_bits).hash(hasher);
+            image.tint.map(f32::to_bits).hash(hasher);
+            image.filter.map(f32::to_bits).hash(hasher);
+            image.placeholder_color.map(f32::to_bits).hash(hasher);
+            image.opacity.to_bits().hash(hasher);
+            image.border_radius.to_bits().hash(hasher);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::div::{div, Div};
+    use junita_animation::AnimationScheduler;
+    use junita_core::Color;
+    use std::sync::{Arc, Mutex};
+
+    fn render_state() -> RenderState {
+        RenderState::new(Arc::new(Mutex::new(AnimationScheduler::new())))
+    }
+
+    fn ui(color: Color) -> Div {
+        div()
+            .w(400.0)
+            .h(300.0)
+            .flex_col()
+            .child(div().w(100.0).h(50.0).bg(Color::BLUE))
+            .child(div().w(80.0).h(40.0).bg(color))
+    }
+
+    #[test]
+    fn test_region_merges_overlapping_rects() {
+        let mut region = DamageRegion::new();
+        assert!(region.is_empty());
+
+        region.add(Rect::new(0.0, 0.0, 10.0, 10.0));
+        region.add(Rect::new(5.0, 5.0, 10.0, 10.0));
+        region.add(Rect::new(100.0, 100.0, 10.0, 10.0));
+        assert_eq!(region.rects().len(), 2);
+        assert_eq!(region.bounds(), Some(Rect::new(0.0, 0.0, 110.0, 110.0)));
+
+        // Scissor is rounded outwards into physical pixels
+        assert_eq!(region.scissor(2.0, 1000, 1000), Some([0, 0, 220, 220]));
+        assert_eq!(DamageRegion::full().scissor(2.0, 1000, 1000), None);
+    }
+
+    #[test]
+    fn test_scissor_is_clamped_to_surface() {
+        let scissor = |rect: Rect| {
+            let mut region = DamageRegion::new();
+            region.add(rect);
+            region.scissor(2.0, 200, 100)
+        };
+
+        // Rects hanging off the top-left or bottom-right are cut at the edge
+        assert_eq!(
+            scissor(Rect::new(-10.0, -10.0, 30.0, 30.0)),
+            Some([0, 0, 40, 40])
+        );
+        assert_eq!(
+            scissor(Rect::new(90.0, 40.0, 50.0, 50.0)),
+            Some([180, 80, 20, 20])
+        );
+        // Entirely off-surface damage leaves a zero-width scissor
+        assert_eq!(
+            scissor(Rect::new(150.0, 10.0, 10.0, 10.0)),
+            Some([200, 20, 0, 20])
+        );
+        // Damage covering the surface needs no scissor
+        assert_eq!(scissor(Rect::new(-5.0, -5.0, 110.0, 60.0)), None);
+        // An empty region repaints nothing
+        assert_eq!(
+            DamageRegion::new().scissor(2.0, 200, 100),
+            Some([0, 0, 0, 0])
+        );
+    }
+
+    #[test]
+    fn test_unchanged_frame_has_no_damage() {
+        let rs = render_state();
+        let mut tree = RenderTree::from_element(&ui(Color::RED));
+        tree.compute_layout(400.0, 300.0);
+
+        let mut tracker = DamageTracker::new();
+        assert!(tracker.compute(&tree, &rs, (400.0, 300.0)).is_full());
+        assert!(tracker.compute(&tree, &rs, (400.0, 300.0)).is_empty());
+
+        tracker.invalidate_all();
+        assert!(tracker.compute(&tree, &rs, (400.0, 300.0)).is_full());
+        assert!(tracker.compute(&tree, &rs, (500.0, 300.0)).is_full());
+    }
+
+    #[test]
+    fn test_visual_change_damages_only_changed_node() {
+        let rs = render_state();
+        let mut tree = RenderTree::from_element(&ui(Color::RED));
+        tree.compute_layout(400.0, 300.0);
+
+        let mut tracker = DamageTracker::new();
+        tracker.compute(&tree, &rs, (400.0, 300.0));
+
+        tree.incremental_update(&ui(Color::GREEN));
+        let damage = tracker.compute(&tree, &rs, (400.0, 300.0));
+        let bounds = damage.bounds().expect("changed node should be damaged");
+
+        // The second child sits below the first one; the first isn't touched
+        assert!(bounds.y() >= 50.0 - AA_MARGIN);
+        assert!(bounds.width() <= 80.0 + 2.0 * AA_MARGIN);
+        assert!(bounds.height() <= 40.0 + 2.0 * AA_MARGIN);
+    }
+
+    #[test]
+    fn test_damage_follows_recorded_changes() {
+        let rs = render_state();
+        let mut tree = RenderTree::from_element(&ui(Color::RED));
+        tree.compute_layout(400.0, 300.0);
+        let first = tree.layout().children(tree.root().unwrap())[0];
+
+        let mut tracker = DamageTracker::new();
+        tracker.compute(&tree, &rs, (400.0, 300.0));
+
+        // Writing the same props back records no change
+        let props = tree.get_render_node(first).unwrap().props.clone();
+        tree.update_render_props(first, |p| *p = props);
+        assert!(tracker.compute(&tree, &rs, (400.0, 300.0)).is_empty());
+
+        tree.update_render_props(first, |p| p.opacity = 0.5);
+        let bounds = tracker
+            .compute(&tree, &rs, (400.0, 300.0))
+            .bounds()
+            .expect("changed node should be damaged");
+        assert!(bounds.y() + bounds.height() <= 50.0 + AA_MARGIN);
+
+        // A rebuilt tree shares no node identities with the old one
+        let tree = RenderTree::from_element(&ui(Color::RED));
+        assert!(tracker.compute(&tree, &rs, (400.0, 300.0)).is_full());
+    }
+}
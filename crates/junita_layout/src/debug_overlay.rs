@@ -0,0 +1,655 @@
+//! Layout debugging overlays
+//!
+//! Visualizes why Taffy laid the tree out the way it did. Each overlay is a
+//! set of [`DebugShape`]s computed from the laid-out [`RenderTree`] and drawn
+//! on top of the frame as a separate overlay tree:
+//!
+//! - **Box model**: margin, border, padding and content boxes of one element
+//! - **Flex**: main axis, flex lines and gaps of every flex container
+//! - **Overflow**: content that extends past its element's bounds
+//! - **Clips**: effective clip rect of every clipping element
+//! - **Layers**: elements rendered on the glass or foreground layer
+//! - **Hit regions**: elements with event handlers that receive pointer input
+//!
+//! The enabled set is process-wide, so the windowed runner and an attached
+//! debugger share it:
+//!
+//! ```ignore
+//! use junita_layout::debug_overlay::{self, DebugOverlays};
+//!
+//! debug_overlay::set_debug_overlays(DebugOverlays { flex: true, ..DebugOverlays::NONE });
+//!
+//! let shapes = debug_overlay::debug_shapes(&tree, debug_overlay::debug_overlays(), hovered);
+//! let overlay = debug_overlay::debug_overlay_tree(&shapes, (width, height), scale_factor);
+//! ```
+
+use std::sync::Mutex;
+
+use junita_core::{Color, Rect};
+use taffy::prelude::{Display, FlexDirection, Position};
+
+use crate::div::div;
+use crate::element::RenderLayer;
+use crate::renderer::RenderTree;
+use crate::tree::LayoutNodeId;
+
+/// Which debug overlays are drawn
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DebugOverlays {
+    /// Margin, border, padding and content boxes of the inspected element
+    pub box_model: bool,
+    /// Main axis, lines and gaps of flex containers
+    pub flex: bool,
+    /// Content overflowing its element's bounds
+    pub overflow: bool,
+    /// Effective clip rects
+    pub clips: bool,
+    /// Glass and foreground render layers
+    pub layers: bool,
+    /// Regions that receive pointer events
+    pub hit_regions: bool,
+}
+
+impl DebugOverlays {
+    /// No overlays
+    pub const NONE: DebugOverlays = DebugOverlays {
+        box_model: false,
+        flex: false,
+        overflow: false,
+        clips: false,
+        layers: false,
+        hit_regions: false,
+    };
+
+    /// Every overlay
+    pub fn all() -> Self {
+        Self {
+            box_model: true,
+            flex: true,
+            overflow: true,
+            clips: true,
+            layers: true,
+            hit_regions: true,
+        }
+    }
+
+    /// Check if no overlay is enabled
+    pub fn is_empty(&self) -> bool {
+        *self == Self::NONE
+    }
+}
+
+/// Enabled overlays, and the set restored by the next toggle
+struct OverlayState {
+    enabled: DebugOverlays,
+    last_enabled: DebugOverlays,
+}
+
+static OVERLAYS: Mutex<OverlayState> = Mutex::new(OverlayState {
+    enabled: DebugOverlays::NONE,
+    last_enabled: DebugOverlays::NONE,
+});
+
+/// Overlays currently enabled
+pub fn debug_overlays() -> DebugOverlays {
+    OVERLAYS.lock().unwrap().enabled
+}
+
+/// Set the enabled overlays
+pub fn set_debug_overlays(overlays: DebugOverlays) {
+    let mut state = OVERLAYS.lock().unwrap();
+    state.enabled = overlays;
+    if !overlays.is_empty() {
+        state.last_enabled = overlays;
+    }
+}
+
+/// Turn overlays off, or back on with the last enabled set
+///
+/// The first toggle enables every overlay. Returns the new set.
+pub fn toggle_debug_overlays() -> DebugOverlays {
+    let mut state = OVERLAYS.lock().unwrap();
+    state.enabled = if state.enabled.is_empty() {
+        if state.last_enabled.is_empty() {
+            DebugOverlays::all()
+        } else {
+            state.last_enabled
+        }
+    } else {
+        DebugOverlays::NONE
+    };
+    if !state.enabled.is_empty() {
+        state.last_enabled = state.enabled;
+    }
+    state.enabled
+}
+
+/// What a [`DebugShape`] visualizes
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugShapeKind {
+    /// One side of the margin box
+    Margin,
+    /// One side of the border
+    Border,
+    /// One side of the padding box
+    Padding,
+    /// The content box
+    Content,
+    /// Main axis of a flex container, through its content box
+    FlexAxis,
+    /// Bounds of one flex line
+    FlexLine,
+    /// Space between adjacent flex items or lines
+    FlexGap,
+    /// Content extent of an element whose content overflows it
+    Overflow,
+    /// Effective clip rect of a clipping element
+    Clip,
+    /// Element rendered on a non-background layer
+    Layer(RenderLayer),
+    /// Element that receives pointer events
+    HitRegion,
+}
+
+impl DebugShapeKind {
+    /// Color the shape is drawn with
+    pub fn color(&self) -> Color {
+        match self {
+            DebugShapeKind::Margin => Color::rgba(0.96, 0.70, 0.42, 0.55),
+            DebugShapeKind::Border => Color::rgba(0.99, 0.86, 0.60, 0.65),
+            DebugShapeKind::Padding => Color::rgba(0.58, 0.77, 0.49, 0.55),
+            DebugShapeKind::Content => Color::rgba(0.44, 0.66, 0.86, 0.55),
+            DebugShapeKind::FlexAxis => Color::rgba(0.75, 0.35, 0.95, 0.9),
+            DebugShapeKind::FlexLine => Color::rgba(0.75, 0.35, 0.95, 0.7),
+            DebugShapeKind::FlexGap => Color::rgba(0.75, 0.35, 0.95, 0.25),
+            DebugShapeKind::Overflow => Color::rgba(0.95, 0.25, 0.25, 0.9),
+            DebugShapeKind::Clip => Color::rgba(0.15, 0.85, 0.85, 0.9),
+            DebugShapeKind::Layer(RenderLayer::Glass) => Color::rgba(0.30, 0.60, 1.00, 0.9),
+            DebugShapeKind::Layer(_) => Color::rgba(1.00, 0.85, 0.20, 0.9),
+            DebugShapeKind::HitRegion => Color::rgba(0.20, 0.90, 0.40, 0.8),
+        }
+    }
+
+    /// Whether the shape is filled rather than outlined
+    pub fn is_filled(&self) -> bool {
+        matches!(
+            self,
+            DebugShapeKind::Margin
+                | DebugShapeKind::Border
+                | DebugShapeKind::Padding
+                | DebugShapeKind::Content
+                | DebugShapeKind::FlexAxis
+                | DebugShapeKind::FlexGap
+        )
+    }
+}
+
+/// A shape to draw, in window coordinates
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebugShape {
+    /// What the shape visualizes
+    pub kind: DebugShapeKind,
+    /// Area covered by the shape
+    pub rect: Rect,
+    /// Element the shape belongs to
+    pub node: LayoutNodeId,
+}
+
+/// Compute the shapes for the enabled overlays
+///
+/// The box model is drawn only for `inspected`; the other overlays cover the
+/// whole tree. Shapes are in window coordinates, with scroll offsets applied.
+pub fn debug_shapes(
+    tree: &RenderTree,
+    overlays: DebugOverlays,
+    inspected: Option<LayoutNodeId>,
+) -> Vec<DebugShape> {
+    let mut shapes = Vec::new();
+    if overlays.is_empty() {
+        return shapes;
+    }
+    if let Some(root) = tree.root() {
+        collect_shapes(
+            tree,
+            overlays,
+            inspected,
+            root,
+            (0.0, 0.0),
+            None,
+            &mut shapes,
+        );
+    }
+    shapes
+}
+
+/// Recursive walk for [`debug_shapes`]
+fn collect_shapes(
+    tree: &RenderTree,
+    overlays: DebugOverlays,
+    inspected: Option<LayoutNodeId>,
+    node: LayoutNodeId,
+    offset: (f32, f32),
+    clip: Option<Rect>,
+    shapes: &mut Vec<DebugShape>,
+) {
+    let Some(bounds) = tree.layout().get_bounds(node, offset) else {
+        return;
+    };
+    let rect = Rect::new(bounds.x, bounds.y, bounds.width, bounds.height);
+    let props = tree.get_render_node(node).map(|n| &n.props);
+    let mut push = |kind, rect| shapes.push(DebugShape { kind, rect, node });
+
+    if overlays.box_model && inspected == Some(node) {
+        if let Some(layout) = tree.layout().get_layout(node) {
+            let margin = inflate(rect, layout.margin);
+            let border_inner = deflate(rect, layout.border);
+            let content = deflate(border_inner, layout.padding);
+            for band in bands(margin, rect) {
+                push(DebugShapeKind::Margin, band);
+            }
+            for band in bands(rect, border_inner) {
+                push(DebugShapeKind::Border, band);
+            }
+            for band in bands(border_inner, content) {
+                push(DebugShapeKind::Padding, band);
+            }
+            push(DebugShapeKind::Content, content);
+        }
+    }
+
+    if overlays.overflow {
+        if let Some((width, height)) = tree.layout().get_content_size(node) {
+            // Half a pixel of slack for rounding in Taffy's content size
+            if width > rect.width() + 0.5 || height > rect.height() + 0.5 {
+                push(
+                    DebugShapeKind::Overflow,
+                    Rect::new(
+                        rect.x(),
+                        rect.y(),
+                        width.max(rect.width()),
+                        height.max(rect.height()),
+                    ),
+                );
+            }
+        }
+    }
+
+    let clips = props.is_some_and(|p| p.clips_content);
+    let child_clip = if clips {
+        Some(clip.map_or(rect, |c| intersect(c, rect)))
+    } else {
+        clip
+    };
+    if overlays.clips && clips {
+        if let Some(clip_rect) = child_clip {
+            push(DebugShapeKind::Clip, clip_rect);
+        }
+    }
+
+    if overlays.layers {
+        if let Some(layer) = props.map(|p| p.layer) {
+            if layer != RenderLayer::Background {
+                push(DebugShapeKind::Layer(layer), rect);
+            }
+        }
+    }
+
+    if overlays.hit_regions
+        && !props.is_some_and(|p| p.pointer_events_none)
+        && tree
+            .handler_registry()
+            .get(node)
+            .is_some_and(|handlers| !handlers.is_empty())
+    {
+        push(
+            DebugShapeKind::HitRegion,
+            clip.map_or(rect, |c| intersect(c, rect)),
+        );
+    }
+
+    let scroll = tree.get_scroll_offset(node);
+    let child_offset = (bounds.x + scroll.0, bounds.y + scroll.1);
+
+    if overlays.flex {
+        flex_shapes(tree, node, rect, child_offset, shapes);
+    }
+
+    for child in tree.layout().children(node) {
+        collect_shapes(
+            tree,
+            overlays,
+            inspected,
+            child,
+            child_offset,
+            child_clip,
+            shapes,
+        );
+    }
+}
+
+/// Axis, lines and gaps of a flex container
+fn flex_shapes(
+    tree: &RenderTree,
+    node: LayoutNodeId,
+    rect: Rect,
+    child_offset: (f32, f32),
+    shapes: &mut Vec<DebugShape>,
+) {
+    let (Some(style), Some(layout)) = (
+        tree.layout().get_style(node),
+        tree.layout().get_layout(node),
+    ) else {
+        return;
+    };
+    if style.display != Display::Flex {
+        return;
+    }
+    let horizontal = matches!(
+        style.flex_direction,
+        FlexDirection::Row | FlexDirection::RowReverse
+    );
+    let reverse = matches!(
+        style.flex_direction,
+        FlexDirection::RowReverse | FlexDirection::ColumnReverse
+    );
+    let mut push = |kind, rect| shapes.push(DebugShape { kind, rect, node });
+
+    let content = deflate(deflate(rect, layout.border), layout.padding);
+    let axis = if horizontal {
+        Rect::new(content.x(), content.center().y - 0.5, content.width(), 1.0)
+    } else {
+        Rect::new(content.center().x - 0.5, content.y(), 1.0, content.height())
+    };
+    push(DebugShapeKind::FlexAxis, axis);
+
+    // In-flow items as (main start, main end, cross start, cross end)
+    let items: Vec<(f32, f32, f32, f32)> = tree
+        .layout()
+        .children(node)
+        .into_iter()
+        .filter(|&child| {
+            tree.layout()
+                .get_style(child)
+                .is_some_and(|s| s.position != Position::Absolute && s.display != Display::None)
+        })
+        .filter_map(|child| tree.layout().get_bounds(child, child_offset))
+        .map(|b| {
+            if horizontal {
+                (b.x, b.x + b.width, b.y, b.y + b.height)
+            } else {
+                (b.y, b.y + b.height, b.x, b.x + b.width)
+            }
+        })
+        .collect();
+
+    // A new line starts when an item wraps back against the main axis
+    let mut lines: Vec<Vec<(f32, f32, f32, f32)>> = Vec::new();
+    for item in items {
+        let wraps = lines
+            .last()
+            .and_then(|line| line.last())
+            .is_some_and(|prev| {
+                if reverse {
+                    item.1 > prev.0 + 0.5
+                } else {
+                    item.0 < prev.1 - 0.5
+                }
+            });
+        match lines.last_mut() {
+            Some(line) if !wraps => line.push(item),
+            _ => lines.push(vec![item]),
+        }
+    }
+
+    let to_rect = |main: (f32, f32), cross: (f32, f32)| {
+        if horizontal {
+            Rect::new(main.0, cross.0, main.1 - main.0, cross.1 - cross.0)
+        } else {
+            Rect::new(cross.0, main.0, cross.1 - cross.0, main.1 - main.0)
+        }
+    };
+
+    let mut previous_cross: Option<(f32, f32)> = None;
+    for line in &lines {
+        let main = line.iter().fold((f32::MAX, f32::MIN), |acc, item| {
+            (acc.0.min(item.0), acc.1.max(item.1))
+        });
+        let cross = line.iter().fold((f32::MAX, f32::MIN), |acc, item| {
+            (acc.0.min(item.2), acc.1.max(item.3))
+        });
+        push(DebugShapeKind::FlexLine, to_rect(main, cross));
+
+        for pair in line.windows(2) {
+            let (before, after) = if reverse {
+                (pair[1], pair[0])
+            } else {
+                (pair[0], pair[1])
+            };
+            if after.0 - before.1 > 0.5 {
+                push(DebugShapeKind::FlexGap, to_rect((before.1, after.0), cross));
+            }
+        }
+
+        if let Some(previous) = previous_cross {
+            if cross.0 - previous.1 > 0.5 {
+                push(
+                    DebugShapeKind::FlexGap,
+                    to_rect(content_main(content, horizontal), (previous.1, cross.0)),
+                );
+            }
+        }
+        previous_cross = Some(cross);
+    }
+}
+
+/// Main-axis extent of a content box
+fn content_main(content: Rect, horizontal: bool) -> (f32, f32) {
+    if horizontal {
+        (content.x(), content.x() + content.width())
+    } else {
+        (content.y(), content.y() + content.height())
+    }
+}
+
+fn inflate(rect: Rect, edges: taffy::Rect<f32>) -> Rect {
+    Rect::new(
+        rect.x() - edges.left,
+        rect.y() - edges.top,
+        rect.width() + edges.left + edges.right,
+        rect.height() + edges.top + edges.bottom,
+    )
+}
+
+fn deflate(rect: Rect, edges: taffy::Rect<f32>) -> Rect {
+    Rect::new(
+        rect.x() + edges.left,
+        rect.y() + edges.top,
+        (rect.width() - edges.left - edges.right).max(0.0),
+        (rect.height() - edges.top - edges.bottom).max(0.0),
+    )
+}
+
+fn intersect(a: Rect, b: Rect) -> Rect {
+    let x = a.x().max(b.x());
+    let y = a.y().max(b.y());
+    let right = (a.x() + a.width()).min(b.x() + b.width());
+    let bottom = (a.y() + a.height()).min(b.y() + b.height());
+    Rect::new(x, y, (right - x).max(0.0), (bottom - y).max(0.0))
+}
+
+/// The non-empty sides of the ring between `outer` and `inner`
+fn bands(outer: Rect, inner: Rect) -> Vec<Rect> {
+    let (ox, oy, ow, oh) = (outer.x(), outer.y(), outer.width(), outer.height());
+    let (ix, iy, iw, ih) = (inner.x(), inner.y(), inner.width(), inner.height());
+    [
+        Rect::new(ox, oy, ow, iy - oy),
+        Rect::new(ox, iy + ih, ow, oy + oh - (iy + ih)),
+        Rect::new(ox, iy, ix - ox, ih),
+        Rect::new(ix + iw, iy, ox + ow - (ix + iw), ih),
+    ]
+    .into_iter()
+    .filter(|r| r.width() > 0.0 && r.height() > 0.0)
+    .collect()
+}
+
+/// Build the overlay tree that draws `shapes`
+///
+/// The tree ignores pointer events and is meant to be rendered on top of the
+/// frame.
+pub fn debug_overlay_tree(
+    shapes: &[DebugShape],
+    viewport: (f32, f32),
+    scale_factor: f32,
+) -> RenderTree {
+    let mut ui = div().w(viewport.0).h(viewport.1).pointer_events_none();
+    for shape in shapes {
+        let item = div()
+            .absolute()
+            .left(shape.rect.x())
+            .top(shape.rect.y())
+            .w(shape.rect.width())
+            .h(shape.rect.height());
+        ui = ui.child(if shape.kind.is_filled() {
+            item.bg(shape.kind.color())
+        } else {
+            item.border(1.0, shape.kind.color())
+        });
+    }
+    let mut tree = RenderTree::from_element(&ui);
+    tree.set_scale_factor(scale_factor);
+    tree.compute_layout(viewport.0, viewport.1);
+    tree
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    fn laid_out(ui: Div) -> RenderTree {
+        let mut tree = RenderTree::from_element(&ui);
+        tree.compute_layout(400.0, 300.0);
+        tree
+    }
+
+    fn of_kind(shapes: &[DebugShape], kind: DebugShapeKind) -> Vec<Rect> {
+        shapes
+            .iter()
+            .filter(|s| s.kind == kind)
+            .map(|s| s.rect)
+            .collect()
+    }
+
+    #[test]
+    fn test_box_model_of_inspected_element() {
+        let tree = laid_out(
+            div()
+                .w(400.0)
+                .h(300.0)
+                .child(div().id("card").w(100.0).h(50.0).m_px(10.0).p_px(5.0)),
+        );
+        let card = tree.query_by_id("card").unwrap();
+        let overlays = DebugOverlays {
+            box_model: true,
+            ..DebugOverlays::NONE
+        };
+        let shapes = debug_shapes(&tree, overlays, Some(card));
+
+        assert_eq!(
+            of_kind(&shapes, DebugShapeKind::Content),
+            vec![Rect::new(15.0, 15.0, 90.0, 40.0)]
+        );
+        assert_eq!(of_kind(&shapes, DebugShapeKind::Margin).len(), 4);
+        assert_eq!(of_kind(&shapes, DebugShapeKind::Padding).len(), 4);
+        assert!(of_kind(&shapes, DebugShapeKind::Border).is_empty());
+        assert!(shapes.iter().all(|s| s.node == card));
+    }
+
+    #[test]
+    fn test_flex_lines_and_gaps() {
+        let tree = laid_out(
+            div()
+                .w(250.0)
+                .h(300.0)
+                .flex_row()
+                .flex_wrap()
+                .content_start()
+                .gap_px(10.0)
+                .child(div().w(100.0).h(20.0))
+                .child(div().w(100.0).h(20.0))
+                .child(div().w(100.0).h(20.0)),
+        );
+        let overlays = DebugOverlays {
+            flex: true,
+            ..DebugOverlays::NONE
+        };
+        let shapes = debug_shapes(&tree, overlays, None);
+        let lines = of_kind(&shapes, DebugShapeKind::FlexLine);
+        assert_eq!(
+            lines,
+            vec![
+                Rect::new(0.0, 0.0, 210.0, 20.0),
+                Rect::new(0.0, 30.0, 100.0, 20.0)
+            ]
+        );
+        let gaps = of_kind(&shapes, DebugShapeKind::FlexGap);
+        // One gap between the first two items, one between the lines
+        assert!(gaps.contains(&Rect::new(100.0, 0.0, 10.0, 20.0)));
+        assert!(gaps.contains(&Rect::new(0.0, 20.0, 250.0, 10.0)));
+    }
+
+    #[test]
+    fn test_overflow_clip_and_hit_regions() {
+        let tree = laid_out(
+            div()
+                .w(400.0)
+                .h(300.0)
+                .child(
+                    div().id("clip").w(100.0).h(50.0).overflow_clip().child(
+                        div()
+                            .id("big")
+                            .w(200.0)
+                            .h(40.0)
+                            .flex_shrink_0()
+                            .on_click(|_| {}),
+                    ),
+                )
+                .child(div().w(10.0).h(10.0).pointer_events_none().on_click(|_| {})),
+        );
+        let shapes = debug_shapes(&tree, DebugOverlays::all(), None);
+
+        let clip = tree.query_by_id("clip").unwrap();
+        let overflow: Vec<_> = shapes
+            .iter()
+            .filter(|s| s.kind == DebugShapeKind::Overflow)
+            .collect();
+        assert_eq!(overflow.len(), 1);
+        assert_eq!(overflow[0].node, clip);
+        assert_eq!(overflow[0].rect.width(), 200.0);
+
+        assert_eq!(
+            of_kind(&shapes, DebugShapeKind::Clip),
+            vec![Rect::new(0.0, 0.0, 100.0, 50.0)]
+        );
+        // The clickable child is clipped; the pass-through one is skipped
+        assert_eq!(
+            of_kind(&shapes, DebugShapeKind::HitRegion),
+            vec![Rect::new(0.0, 0.0, 100.0, 40.0)]
+        );
+    }
+
+    #[test]
+    fn test_no_shapes_when_disabled() {
+        let tree = laid_out(div().w(100.0).h(100.0).flex_row().child(div().w(10.0)));
+        assert!(debug_shapes(&tree, DebugOverlays::NONE, tree.root()).is_empty());
+    }
+
+    #[test]
+    fn test_overlay_tree_has_one_child_per_shape() {
+        let tree = laid_out(div().w(100.0).h(100.0).flex_row().child(div().w(10.0)));
+        let shapes = debug_shapes(&tree, DebugOverlays::all(), tree.root());
+        let overlay = debug_overlay_tree(&shapes, (100.0, 100.0), 1.0);
+        let root = overlay.root().unwrap();
+        assert_eq!(overlay.layout().children(root).len(), shapes.len());
+    }
+}
@@ -23,6 +23,7 @@ use std::collections::hash_map::DefaultHasher;
 use std::collections::{HashMap, HashSet};
 use std::hash::{Hash, Hasher};
 
+use junita_core::profiler;
 use junita_core::{
     Brush, Color, CornerRadius, GlassStyle, Gradient, GradientStop, ImageBrush, Shadow, Transform,
 };
@@ -131,6 +132,54 @@ impl ChangeCategory {
     pub fn needs_layout(&self) -> bool {
         self.layout || self.children
     }
+
+    /// Returns true if the node itself has to be repainted.
+    ///
+    /// Layout changes count because the node hash also covers content that
+    /// isn't part of the render props, such as text. Children changes only
+    /// add and remove descendants, and handler changes draw nothing.
+    pub fn needs_repaint(&self) -> bool {
+        self.layout || self.visual
+    }
+}
+
+/// Generation at which each change category last applied to a node.
+///
+/// Lets consumers that sample the tree once per frame, such as damage
+/// tracking, ask which categories changed since the generation they last
+/// saw without the tree having to know about them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ChangeGenerations {
+    layout: u64,
+    visual: u64,
+    children: u64,
+    handlers: u64,
+}
+
+impl ChangeGenerations {
+    /// Record `changes` as having happened at `generation`.
+    pub fn record(&mut self, changes: ChangeCategory, generation: u64) {
+        for (changed, at) in [
+            (changes.layout, &mut self.layout),
+            (changes.visual, &mut self.visual),
+            (changes.children, &mut self.children),
+            (changes.handlers, &mut self.handlers),
+        ] {
+            if changed {
+                *at = generation;
+            }
+        }
+    }
+
+    /// Categories recorded after `generation`.
+    pub fn since(&self, generation: u64) -> ChangeCategory {
+        ChangeCategory {
+            layout: self.layout > generation,
+            visual: self.visual > generation,
+            children: self.children > generation,
+            handlers: self.handlers > generation,
+        }
+    }
 }
 
 // =============================================================================
@@ -501,6 +550,8 @@ pub fn detect_layout_changes(old: &Style, new: &Style) -> bool {
 pub fn detect_visual_changes(old: &Div, new: &Div) -> bool {
     !brush_eq(&old.background, &new.background)
         || old.border_radius != new.border_radius
+        || old.border_color != new.border_color
+        || !f32_eq(old.border_width, new.border_width)
         || old.render_layer != new.render_layer
         || !material_eq(&old.material, &new.material)
         || !shadow_eq(&old.shadow, &new.shadow)
@@ -528,6 +579,7 @@ pub fn reconcile(
     new: &Div,
     node_id: Option<LayoutNodeId>,
 ) -> ReconcileActions {
+    let _scope = profiler::scope("reconcile");
     let mut actions = ReconcileActions::default();
 
     // If only visual changes, queue prop update
@@ -688,7 +740,7 @@ fn hash_element_tree(element: &dyn ElementBuilder, hasher: &mut impl Hasher) {
 }
 
 /// Hash RenderProps.
-fn hash_render_props(props: &RenderProps, hasher: &mut impl Hasher) {
+pub(crate) fn hash_render_props(props: &RenderProps, hasher: &mut impl Hasher) {
     hash_option_brush(&props.background, hasher);
     hash_corner_radius(&props.border_radius, hasher);
     // Hash border properties
@@ -705,6 +757,7 @@ fn hash_render_props(props: &RenderProps, hasher: &mut impl Hasher) {
     hash_option_transform(&props.transform, hasher);
     hash_f32(props.opacity, hasher);
     props.clips_content.hash(hasher);
+    std::mem::discriminant(&props.layer_cache).hash(hasher);
 }
 
 // =============================================================================
@@ -1260,6 +1313,7 @@ pub fn render_props_eq(a: &RenderProps, b: &RenderProps) -> bool {
         && transform_eq(&a.transform, &b.transform)
         && f32_eq(a.opacity, b.opacity)
         && a.clips_content == b.clips_content
+        && a.layer_cache == b.layer_cache
 }
 
 // =============================================================================
@@ -1360,4 +1414,18 @@ mod tests {
             "Opacity change should be detected as visual change"
         );
     }
+
+    #[test]
+    fn test_diff_border_change() {
+        let div1 = div().border(1.0, Color::RED);
+        let div2 = div().border(1.0, Color::BLUE);
+        let div3 = div().border(2.0, Color::RED);
+
+        assert!(diff(&div1, &div2).changes.visual, "Border color is visual");
+        assert!(diff(&div1, &div3).changes.visual, "Border width is visual");
+
+        let mut old = div1;
+        reconcile(&diff(&old, &div3), &mut old, &div3, None);
+        assert_eq!(DivHash::compute(&old), DivHash::compute(&div3));
+    }
 }
@@ -19,7 +19,7 @@ use std::sync::{
 };
 
 use junita_core::{
-    BlurQuality, BlurStyle, Brush, Color, CornerRadius, LayerEffect, Shadow, Transform,
+    BlurQuality, BlurStyle, Brush, CachePolicy, Color, CornerRadius, LayerEffect, Shadow, Transform,
 };
 use junita_theme::ThemeState;
 use taffy::prelude::*;
@@ -383,6 +383,8 @@ pub struct Div {
     pub(crate) pointer_events_none: bool,
     /// Layer effects (blur, drop shadow, glow, color matrix) applied to this element
     pub(crate) layer_effects: Vec<LayerEffect>,
+    /// Retained layer caching for this element's subtree
+    pub(crate) layer_cache: CachePolicy,
     /// Marks this as a stack layer for z-ordering (increments z_layer for interleaved rendering)
     pub(crate) is_stack_layer: bool,
     pub(crate) event_handlers: crate::event_handler::EventHandlers,
@@ -397,6 +399,8 @@ pub struct Div {
     /// When set, motion containers and layout animations will use this key
     /// as a prefix for auto-generated stable keys.
     pub(crate) stateful_context_key: Option<String>,
+    /// Values set from theme tokens, for the debugger's style origins
+    pub(crate) theme_values: Vec<crate::style_origin::ThemeValue>,
 }
 
 impl Default for Div {
@@ -424,12 +428,14 @@ impl Div {
             cursor: None,
             pointer_events_none: false,
             layer_effects: Vec::new(),
+            layer_cache: CachePolicy::None,
             is_stack_layer: false,
             event_handlers: crate::event_handler::EventHandlers::new(),
             element_id: None,
             layout_animation: None,
             visual_animation: None,
             stateful_context_key: None,
+            theme_values: Vec::new(),
         }
     }
 
@@ -454,12 +460,14 @@ impl Div {
             cursor: None,
             pointer_events_none: false,
             layer_effects: Vec::new(),
+            layer_cache: CachePolicy::None,
             is_stack_layer: false,
             event_handlers: crate::event_handler::EventHandlers::new(),
             element_id: None,
             layout_animation: None,
             visual_animation: None,
             stateful_context_key: None,
+            theme_values: Vec::new(),
         }
     }
 
@@ -843,6 +851,29 @@ impl Div {
         }
     }
 
+    /// Note that `property` now holds the value of theme token `token`
+    ///
+    /// Only tracked with the `recorder` feature, where the debugger shows
+    /// which values came from the theme.
+    #[cfg(feature = "recorder")]
+    fn themed(mut self, property: &'static str, token: &'static str) -> Self {
+        let values = crate::style_origin::style_values(&self.render_props(), Some(&self.style));
+        if let Some(value) = values.get(property) {
+            self.theme_values.push(crate::style_origin::ThemeValue {
+                property,
+                token,
+                value: value.clone(),
+            });
+        }
+        self
+    }
+
+    #[cfg(not(feature = "recorder"))]
+    #[inline]
+    fn themed(self, _property: &'static str, _token: &'static str) -> Self {
+        self
+    }
+
     /// Merge properties from another Div into this one
     ///
     /// This applies the other Div's non-default properties on top of this one.
@@ -914,6 +945,9 @@ impl Div {
             self.layout_animation = other.layout_animation;
         }
 
+        // Theme values only count while they match, so keep both sets
+        self.theme_values.extend(other.theme_values);
+
         // Note: event_handlers are NOT merged - they're set on the base element
     }
 
@@ -1452,46 +1486,55 @@ impl Div {
     /// Set gap using theme spacing scale (space_1 = 4px)
     pub fn gap_1(self) -> Self {
         self.gap_px(ThemeState::get().spacing().space_1)
+            .themed("gap", "space-1")
     }
 
     /// Set gap using theme spacing scale (space_2 = 8px)
     pub fn gap_2(self) -> Self {
         self.gap_px(ThemeState::get().spacing().space_2)
+            .themed("gap", "space-2")
     }
 
     /// Set gap using theme spacing scale (space_3 = 12px)
     pub fn gap_3(self) -> Self {
         self.gap_px(ThemeState::get().spacing().space_3)
+            .themed("gap", "space-3")
     }
 
     /// Set gap using theme spacing scale (space_4 = 16px)
     pub fn gap_4(self) -> Self {
         self.gap_px(ThemeState::get().spacing().space_4)
+            .themed("gap", "space-4")
     }
 
     /// Set gap using theme spacing scale (space_5 = 20px)
     pub fn gap_5(self) -> Self {
         self.gap_px(ThemeState::get().spacing().space_5)
+            .themed("gap", "space-5")
     }
 
     /// Set gap using theme spacing scale (space_6 = 24px)
     pub fn gap_6(self) -> Self {
         self.gap_px(ThemeState::get().spacing().space_6)
+            .themed("gap", "space-6")
     }
 
     /// Set gap using theme spacing scale (space_8 = 32px)
     pub fn gap_8(self) -> Self {
         self.gap_px(ThemeState::get().spacing().space_8)
+            .themed("gap", "space-8")
     }
 
     /// Set gap using theme spacing scale (space_10 = 40px)
     pub fn gap_10(self) -> Self {
         self.gap_px(ThemeState::get().spacing().space_10)
+            .themed("gap", "space-10")
     }
 
     /// Set gap using theme spacing scale (space_12 = 48px)
     pub fn gap_12(self) -> Self {
         self.gap_px(ThemeState::get().spacing().space_12)
+            .themed("gap", "space-12")
     }
 
     // -------------------------------------------------------------------------
@@ -1571,54 +1614,63 @@ impl Div {
     pub fn p_1(self) -> Self {
         let px = ThemeState::get().spacing().space_1;
         self.padding(crate::units::Length::Px(px))
+            .themed("padding", "space-1")
     }
 
     /// Set padding using theme spacing scale (space_2 = 8px)
     pub fn p_2(self) -> Self {
         let px = ThemeState::get().spacing().space_2;
         self.padding(crate::units::Length::Px(px))
+            .themed("padding", "space-2")
     }
 
     /// Set padding using theme spacing scale (space_3 = 12px)
     pub fn p_3(self) -> Self {
         let px = ThemeState::get().spacing().space_3;
         self.padding(crate::units::Length::Px(px))
+            .themed("padding", "space-3")
     }
 
     /// Set padding using theme spacing scale (space_4 = 16px)
     pub fn p_4(self) -> Self {
         let px = ThemeState::get().spacing().space_4;
         self.padding(crate::units::Length::Px(px))
+            .themed("padding", "space-4")
     }
 
     /// Set padding using theme spacing scale (space_5 = 20px)
     pub fn p_5(self) -> Self {
         let px = ThemeState::get().spacing().space_5;
         self.padding(crate::units::Length::Px(px))
+            .themed("padding", "space-5")
     }
 
     /// Set padding using theme spacing scale (space_6 = 24px)
     pub fn p_6(self) -> Self {
         let px = ThemeState::get().spacing().space_6;
         self.padding(crate::units::Length::Px(px))
+            .themed("padding", "space-6")
     }
 
     /// Set padding using theme spacing scale (space_8 = 32px)
     pub fn p_8(self) -> Self {
         let px = ThemeState::get().spacing().space_8;
         self.padding(crate::units::Length::Px(px))
+            .themed("padding", "space-8")
     }
 
     /// Set padding using theme spacing scale (space_10 = 40px)
     pub fn p_10(self) -> Self {
         let px = ThemeState::get().spacing().space_10;
         self.padding(crate::units::Length::Px(px))
+            .themed("padding", "space-10")
     }
 
     /// Set padding using theme spacing scale (space_12 = 48px)
     pub fn p_12(self) -> Self {
         let px = ThemeState::get().spacing().space_12;
         self.padding(crate::units::Length::Px(px))
+            .themed("padding", "space-12")
     }
 
     // =========================================================================
@@ -1759,36 +1811,43 @@ impl Div {
     /// Set margin using theme spacing scale (space_1 = 4px)
     pub fn m_1(self) -> Self {
         self.m_px(ThemeState::get().spacing().space_1)
+            .themed("margin", "space-1")
     }
 
     /// Set margin using theme spacing scale (space_2 = 8px)
     pub fn m_2(self) -> Self {
         self.m_px(ThemeState::get().spacing().space_2)
+            .themed("margin", "space-2")
     }
 
     /// Set margin using theme spacing scale (space_3 = 12px)
     pub fn m_3(self) -> Self {
         self.m_px(ThemeState::get().spacing().space_3)
+            .themed("margin", "space-3")
     }
 
     /// Set margin using theme spacing scale (space_4 = 16px)
     pub fn m_4(self) -> Self {
         self.m_px(ThemeState::get().spacing().space_4)
+            .themed("margin", "space-4")
     }
 
     /// Set margin using theme spacing scale (space_5 = 20px)
     pub fn m_5(self) -> Self {
         self.m_px(ThemeState::get().spacing().space_5)
+            .themed("margin", "space-5")
     }
 
     /// Set margin using theme spacing scale (space_6 = 24px)
     pub fn m_6(self) -> Self {
         self.m_px(ThemeState::get().spacing().space_6)
+            .themed("margin", "space-6")
     }
 
     /// Set margin using theme spacing scale (space_8 = 32px)
     pub fn m_8(self) -> Self {
         self.m_px(ThemeState::get().spacing().space_8)
+            .themed("margin", "space-8")
     }
 
     // =========================================================================
@@ -1967,51 +2026,61 @@ impl Div {
     /// Set background to theme primary color
     pub fn bg_primary(self) -> Self {
         self.bg(ThemeState::get().color(junita_theme::ColorToken::Primary))
+            .themed("background", "primary")
     }
 
     /// Set background to theme secondary color
     pub fn bg_secondary(self) -> Self {
         self.bg(ThemeState::get().color(junita_theme::ColorToken::Secondary))
+            .themed("background", "secondary")
     }
 
     /// Set background to theme background color
     pub fn bg_background(self) -> Self {
         self.bg(ThemeState::get().color(junita_theme::ColorToken::Background))
+            .themed("background", "background")
     }
 
     /// Set background to theme surface color
     pub fn bg_surface(self) -> Self {
         self.bg(ThemeState::get().color(junita_theme::ColorToken::Surface))
+            .themed("background", "surface")
     }
 
     /// Set background to theme elevated surface color
     pub fn bg_surface_elevated(self) -> Self {
         self.bg(ThemeState::get().color(junita_theme::ColorToken::SurfaceElevated))
+            .themed("background", "surface-elevated")
     }
 
     /// Set background to theme success color
     pub fn bg_success(self) -> Self {
         self.bg(ThemeState::get().color(junita_theme::ColorToken::SuccessBg))
+            .themed("background", "success-bg")
     }
 
     /// Set background to theme warning color
     pub fn bg_warning(self) -> Self {
         self.bg(ThemeState::get().color(junita_theme::ColorToken::WarningBg))
+            .themed("background", "warning-bg")
     }
 
     /// Set background to theme error color
     pub fn bg_error(self) -> Self {
         self.bg(ThemeState::get().color(junita_theme::ColorToken::ErrorBg))
+            .themed("background", "error-bg")
     }
 
     /// Set background to theme info color
     pub fn bg_info(self) -> Self {
         self.bg(ThemeState::get().color(junita_theme::ColorToken::InfoBg))
+            .themed("background", "info-bg")
     }
 
     /// Set background to theme accent color
     pub fn bg_accent(self) -> Self {
         self.bg(ThemeState::get().color(junita_theme::ColorToken::Accent))
+            .themed("background", "accent")
     }
 
     // -------------------------------------------------------------------------
@@ -2044,36 +2113,43 @@ impl Div {
     /// Set corner radius to theme's small radius
     pub fn rounded_sm(self) -> Self {
         self.rounded(ThemeState::get().radii().radius_sm)
+            .themed("border-radius", "radius-sm")
     }
 
     /// Set corner radius to theme's default radius
     pub fn rounded_default(self) -> Self {
         self.rounded(ThemeState::get().radii().radius_default)
+            .themed("border-radius", "radius-default")
     }
 
     /// Set corner radius to theme's medium radius
     pub fn rounded_md(self) -> Self {
         self.rounded(ThemeState::get().radii().radius_md)
+            .themed("border-radius", "radius-md")
     }
 
     /// Set corner radius to theme's large radius
     pub fn rounded_lg(self) -> Self {
         self.rounded(ThemeState::get().radii().radius_lg)
+            .themed("border-radius", "radius-lg")
     }
 
     /// Set corner radius to theme's extra large radius
     pub fn rounded_xl(self) -> Self {
         self.rounded(ThemeState::get().radii().radius_xl)
+            .themed("border-radius", "radius-xl")
     }
 
     /// Set corner radius to theme's 2xl radius
     pub fn rounded_2xl(self) -> Self {
         self.rounded(ThemeState::get().radii().radius_2xl)
+            .themed("border-radius", "radius-2xl")
     }
 
     /// Set corner radius to theme's 3xl radius
     pub fn rounded_3xl(self) -> Self {
         self.rounded(ThemeState::get().radii().radius_3xl)
+            .themed("border-radius", "radius-3xl")
     }
 
     /// Set corner radius to none (0)
@@ -2301,21 +2377,25 @@ impl Div {
     /// Apply a small drop shadow using theme colors
     pub fn shadow_sm(self) -> Self {
         self.shadow(ThemeState::get().shadows().shadow_sm.into())
+            .themed("box-shadow", "shadow-sm")
     }
 
     /// Apply a medium drop shadow using theme colors
     pub fn shadow_md(self) -> Self {
         self.shadow(ThemeState::get().shadows().shadow_md.into())
+            .themed("box-shadow", "shadow-md")
     }
 
     /// Apply a large drop shadow using theme colors
     pub fn shadow_lg(self) -> Self {
         self.shadow(ThemeState::get().shadows().shadow_lg.into())
+            .themed("box-shadow", "shadow-lg")
     }
 
     /// Apply an extra large drop shadow using theme colors
     pub fn shadow_xl(self) -> Self {
         self.shadow(ThemeState::get().shadows().shadow_xl.into())
+            .themed("box-shadow", "shadow-xl")
     }
 
     // =========================================================================
@@ -2491,6 +2571,38 @@ impl Div {
         self.layer_effect(LayerEffect::saturation(factor))
     }
 
+    // =========================================================================
+    // Layer Caching
+    // =========================================================================
+
+    /// Rasterize this element and its children once and recomposite the result
+    ///
+    /// The subtree is rendered into an offscreen layer that is kept across
+    /// frames and redrawn only when its content changes. Moving, scaling or
+    /// fading this element reuses the cached texture. Useful for large,
+    /// mostly static content under animation, like a dashboard behind a
+    /// moving overlay.
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// div()
+    ///     .cache_as_layer()
+    ///     .child(chart_grid())
+    /// ```
+    pub fn cache_as_layer(self) -> Self {
+        self.layer_cache(CachePolicy::Content)
+    }
+
+    /// Set the layer cache policy for this element's subtree
+    ///
+    /// `CachePolicy::Manual` keeps the cached texture until
+    /// `RenderTree::invalidate_layer_cache` is called for the element.
+    pub fn layer_cache(mut self, policy: CachePolicy) -> Self {
+        self.layer_cache = policy;
+        self
+    }
+
     // =========================================================================
     // Cursor Style
     // =========================================================================
@@ -3086,6 +3198,8 @@ pub struct ImageRenderInfo {
     pub placeholder_image: Option<String>,
     /// Fade-in duration in milliseconds
     pub fade_duration_ms: u32,
+    /// Playback control for animated images (`None` = autoplay)
+    pub playback: Option<crate::image::ImagePlayback>,
 }
 
 impl Default for ImageRenderInfo {
@@ -3103,6 +3217,7 @@ impl Default for ImageRenderInfo {
             placeholder_color: [0.15, 0.15, 0.15, 0.5],       // Default gray
             placeholder_image: None,
             fade_duration_ms: 200,
+            playback: None,
         }
     }
 }
@@ -3298,6 +3413,19 @@ pub trait ElementBuilder {
     fn visual_animation_config(&self) -> Option<crate::visual_animation::VisualAnimationConfig> {
         None
     }
+
+    /// Where this element's style values came from
+    ///
+    /// Called when the element's render node is built or updated. The
+    /// default credits the builder with every value that differs from an
+    /// unstyled element; `Div` also reports values set from theme tokens.
+    #[cfg(feature = "recorder")]
+    fn style_origins(&self) -> crate::style_origin::StyleOrigins {
+        crate::style_origin::builder_origins(&crate::style_origin::style_values(
+            &self.render_props(),
+            self.layout_style(),
+        ))
+    }
 }
 
 impl ElementBuilder for Div {
@@ -3342,6 +3470,7 @@ impl ElementBuilder for Div {
             pointer_events_none: self.pointer_events_none,
             cursor: self.cursor,
             layer_effects: self.layer_effects.clone(),
+            layer_cache: self.layer_cache,
             motion_is_exiting: false,
         }
     }
@@ -3373,6 +3502,22 @@ impl ElementBuilder for Div {
     fn visual_animation_config(&self) -> Option<crate::visual_animation::VisualAnimationConfig> {
         self.visual_animation.clone()
     }
+
+    #[cfg(feature = "recorder")]
+    fn style_origins(&self) -> crate::style_origin::StyleOrigins {
+        let values = crate::style_origin::style_values(&self.render_props(), Some(&self.style));
+        let mut origins = crate::style_origin::builder_origins(&values);
+        // A theme token is credited only while nothing has overwritten its value
+        for theme in &self.theme_values {
+            if values.get(theme.property) == Some(&theme.value) {
+                origins.insert(
+                    theme.property,
+                    crate::style_origin::StyleOrigin::Theme(theme.token),
+                );
+            }
+        }
+        origins
+    }
 }
 
 /// Convenience function to create a new div
@@ -4,8 +4,8 @@
 //! rendered via the DrawContext API.
 
 use junita_core::{
-    BlurQuality, Brush, Color, CornerRadius, DynFloat, DynValue, LayerEffect, Rect, Shadow,
-    Transform, ValueContext,
+    BlurQuality, Brush, CachePolicy, Color, CornerRadius, DynFloat, DynValue, LayerEffect, Rect,
+    Shadow, Transform, ValueContext,
 };
 use taffy::Layout;
 
@@ -591,12 +591,15 @@ pub struct ElementBounds {
 
 impl ElementBounds {
     /// Create bounds from a Taffy Layout with parent offset
+    ///
+    /// Taffy can report a negative size for an item stretched across a
+    /// container narrower than its margins; that is clamped to empty.
     pub fn from_layout(layout: &Layout, parent_offset: (f32, f32)) -> Self {
         Self {
             x: parent_offset.0 + layout.location.x,
             y: parent_offset.1 + layout.location.y,
-            width: layout.size.width,
-            height: layout.size.height,
+            width: layout.size.width.max(0.0),
+            height: layout.size.height.max(0.0),
         }
     }
 
@@ -994,6 +997,8 @@ pub struct RenderProps {
     /// Layer effects applied to this element (blur, drop shadow, glow, color matrix)
     /// Effects are applied during layer composition when the element is rendered
     pub layer_effects: Vec<LayerEffect>,
+    /// Whether this element's subtree is retained as an offscreen layer
+    pub layer_cache: CachePolicy,
     /// DEPRECATED: Whether the motion should start exiting
     ///
     /// This field is deprecated. Motion exit is now triggered explicitly via
@@ -1034,6 +1039,7 @@ impl Default for RenderProps {
             cursor: None,
             pointer_events_none: false,
             layer_effects: Vec::new(),
+            layer_cache: CachePolicy::None,
             motion_is_exiting: false,
         }
     }
@@ -16,8 +16,10 @@
 //!     .placeholder_color(Color::GRAY);
 //! ```
 
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
+use junita_animation::{SchedulerHandle, TickCallbackId};
 use junita_core::{Brush, Color, Shadow, Transform};
 use taffy::prelude::*;
 
@@ -76,6 +78,222 @@ impl From<Brush> for Placeholder {
     }
 }
 
+// ============================================================================
+// Animated Playback
+// ============================================================================
+
+/// Playback control for animated images (GIF, APNG, animated WebP)
+///
+/// Cheap to clone - all clones control the same playback. While playing,
+/// the playback position advances through a tick callback on the global
+/// animation scheduler. The renderer marks the image visible each frame it
+/// is on screen; off-screen images stop ticking until they scroll back
+/// into view, then resume where they left off.
+///
+/// Images without an explicit playback autoplay, sharing one playback per
+/// source.
+///
+/// # Example
+/// ```ignore
+/// let playback = ImagePlayback::new();
+///
+/// img("sticker.gif").playback(playback.clone());
+///
+/// playback.pause();
+/// playback.seek(Duration::from_millis(500));
+/// playback.play();
+/// ```
+#[derive(Clone)]
+pub struct ImagePlayback {
+    inner: Arc<Mutex<PlaybackState>>,
+}
+
+struct PlaybackState {
+    /// Time played since the start of the animation
+    position: Duration,
+    /// Whether playback was requested (play/pause)
+    playing: bool,
+    /// Whether the image was on screen in the last frame
+    visible: bool,
+    /// Total playback duration, or `None` for infinite loops
+    duration: Option<Duration>,
+    /// Registered tick callback while the position is advancing
+    tick: Option<(SchedulerHandle, TickCallbackId)>,
+}
+
+impl PlaybackState {
+    fn is_finished(&self) -> bool {
+        self.duration.is_some_and(|total| self.position >= total)
+    }
+
+    fn stop_ticking(&mut self) {
+        if let Some((handle, id)) = self.tick.take() {
+            handle.remove_tick_callback(id);
+        }
+    }
+}
+
+impl Drop for PlaybackState {
+    fn drop(&mut self) {
+        self.stop_ticking();
+    }
+}
+
+impl ImagePlayback {
+    /// Create a playback that starts playing as soon as the image is shown
+    pub fn new() -> Self {
+        Self::with_playing(true)
+    }
+
+    /// Create a playback that starts paused on the first frame
+    pub fn paused() -> Self {
+        Self::with_playing(false)
+    }
+
+    fn with_playing(playing: bool) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(PlaybackState {
+                position: Duration::ZERO,
+                playing,
+                visible: false,
+                duration: None,
+                tick: None,
+            })),
+        }
+    }
+
+    /// Start or resume playback
+    ///
+    /// A finished animation restarts from the beginning.
+    pub fn play(&self) {
+        let mut state = self.inner.lock().unwrap();
+        if state.is_finished() {
+            state.position = Duration::ZERO;
+        }
+        state.playing = true;
+        self.sync(&mut state);
+    }
+
+    /// Pause playback, keeping the current position
+    pub fn pause(&self) {
+        let mut state = self.inner.lock().unwrap();
+        state.playing = false;
+        self.sync(&mut state);
+    }
+
+    /// Toggle between playing and paused
+    pub fn toggle(&self) {
+        if self.is_playing() {
+            self.pause();
+        } else {
+            self.play();
+        }
+    }
+
+    /// Jump to a position, clamped to the total duration
+    pub fn seek(&self, position: Duration) {
+        let mut state = self.inner.lock().unwrap();
+        state.position = match state.duration {
+            Some(total) => position.min(total),
+            None => position,
+        };
+        self.sync(&mut state);
+    }
+
+    /// Whether playback is running (requested and not yet finished)
+    ///
+    /// Stays true while the image is off-screen.
+    pub fn is_playing(&self) -> bool {
+        let state = self.inner.lock().unwrap();
+        state.playing && !state.is_finished()
+    }
+
+    /// Whether a finite animation has played to the end
+    pub fn is_finished(&self) -> bool {
+        self.inner.lock().unwrap().is_finished()
+    }
+
+    /// Current playback position
+    pub fn position(&self) -> Duration {
+        self.inner.lock().unwrap().position
+    }
+
+    /// Set the total playback duration (`None` for infinite loops)
+    ///
+    /// Called by the renderer once the image is decoded.
+    pub fn set_duration(&self, duration: Option<Duration>) {
+        let mut state = self.inner.lock().unwrap();
+        if state.duration != duration {
+            state.duration = duration;
+            self.sync(&mut state);
+        }
+    }
+
+    /// Mark whether the image is on screen
+    ///
+    /// Called by the renderer every frame; off-screen images don't advance.
+    pub fn set_visible(&self, visible: bool) {
+        let mut state = self.inner.lock().unwrap();
+        if state.visible != visible {
+            state.visible = visible;
+            self.sync(&mut state);
+        }
+    }
+
+    /// Whether two handles control the same playback
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Start or stop the scheduler tick to match the playback state
+    fn sync(&self, state: &mut PlaybackState) {
+        let should_tick = state.playing && state.visible && !state.is_finished();
+        if !should_tick {
+            state.stop_ticking();
+            return;
+        }
+        if state.tick.is_some() {
+            return;
+        }
+        let Some(handle) = junita_animation::try_get_scheduler() else {
+            return;
+        };
+
+        let weak = Arc::downgrade(&self.inner);
+        let id = handle.register_tick_callback(move |dt| {
+            let Some(inner) = weak.upgrade() else {
+                return;
+            };
+            let mut state = inner.lock().unwrap();
+            state.position += Duration::from_secs_f32(dt);
+            if state.is_finished() {
+                if let Some(total) = state.duration {
+                    state.position = total;
+                }
+                state.stop_ticking();
+            }
+        });
+        state.tick = id.map(|id| (handle, id));
+    }
+}
+
+impl Default for ImagePlayback {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for ImagePlayback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.inner.lock().unwrap();
+        f.debug_struct("ImagePlayback")
+            .field("position", &state.position)
+            .field("playing", &state.playing)
+            .field("visible", &state.visible)
+            .finish()
+    }
+}
+
 // ============================================================================
 // Object Fit (mirroring junita_image for layout purposes)
 // ============================================================================
@@ -229,6 +447,8 @@ pub struct Image {
     border_width: f32,
     /// Border color
     border_color: Option<Color>,
+    /// Playback control for animated images
+    playback: Option<ImagePlayback>,
 }
 
 impl Image {
@@ -264,6 +484,7 @@ impl Image {
             fade_duration: Duration::from_millis(200),
             border_width: 0.0,
             border_color: None,
+            playback: None,
         }
     }
 
@@ -746,6 +967,35 @@ impl Image {
         matches!(self.loading, LoadingStrategy::Lazy)
     }
 
+    // =========================================================================
+    // Animation
+    // =========================================================================
+
+    /// Control playback of an animated image (GIF, APNG, animated WebP)
+    ///
+    /// Without this, animated images autoplay and loop as their file says.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let playback = ImagePlayback::paused();
+    /// img("loading.gif").playback(playback.clone());
+    /// playback.play();
+    /// ```
+    pub fn playback(mut self, playback: ImagePlayback) -> Self {
+        self.playback = Some(playback);
+        self
+    }
+
+    /// Show the first frame of an animated image without playing it
+    pub fn paused(self) -> Self {
+        self.playback(ImagePlayback::paused())
+    }
+
+    /// Get the playback control, if one was set
+    pub fn get_playback(&self) -> Option<&ImagePlayback> {
+        self.playback.as_ref()
+    }
+
     // =========================================================================
     // Getters
     // =========================================================================
@@ -795,6 +1045,7 @@ impl ElementBuilder for Image {
             pointer_events_none: false,
             cursor: None,
             layer_effects: Vec::new(),
+            layer_cache: junita_core::CachePolicy::None,
             motion_is_exiting: false,
         }
     }
@@ -858,6 +1109,7 @@ impl ElementBuilder for Image {
             placeholder_color,
             placeholder_image,
             fade_duration_ms: self.fade_duration.as_millis() as u32,
+            playback: self.playback.clone(),
         })
     }
 
@@ -1014,4 +1266,23 @@ mod tests {
         assert_eq!(i.get_fade_duration(), Duration::ZERO);
         assert_eq!(i.image_render_info().unwrap().fade_duration_ms, 0);
     }
+
+    #[test]
+    fn test_playback_controls() {
+        let playback = ImagePlayback::paused();
+        let i = img("sticker.gif").playback(playback.clone());
+        let info = i.image_render_info().unwrap();
+        assert!(info.playback.unwrap().ptr_eq(&playback));
+
+        playback.set_duration(Some(Duration::from_millis(300)));
+        playback.seek(Duration::from_secs(1));
+        assert_eq!(playback.position(), Duration::from_millis(300));
+        assert!(playback.is_finished());
+        assert!(!playback.is_playing());
+
+        // Playing a finished animation restarts it
+        playback.play();
+        assert_eq!(playback.position(), Duration::ZERO);
+        assert!(playback.is_playing());
+    }
 }
@@ -0,0 +1,275 @@
+//! Retained layer caching for static subtrees
+//!
+//! A subtree marked with `Div::cache_as_layer()` is rendered into an
+//! offscreen layer that the GPU backend keeps across frames. While the
+//! subtree's content version stays the same the backend recomposites the
+//! retained texture - with the node's current transform and opacity - and
+//! the render walk skips drawing the subtree entirely, so nothing below the
+//! node is re-tessellated.
+//!
+//! The content version is derived from the same per-node hashes the
+//! incremental diff uses, plus the per-frame state that changes how the
+//! subtree paints (descendant layout bounds, motion values and bindings,
+//! scroll offsets and scrollbar state). Moving, scaling or fading the cached
+//! node itself doesn't change the version; the backend folds in whatever of
+//! that can't be recomposited.
+//!
+//! Some content can't be replayed from a texture: canvases, glass, stack
+//! layers, foreground content and nested layers (opacity or effects). A
+//! subtree containing any of these is rendered normally.
+//!
+//! With [`RenderTree::set_auto_layer_cache`] enabled, nodes animated through
+//! motion values or bindings are cached automatically when their subtree is
+//! static.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use junita_layout::prelude::*;
+//!
+//! // A dashboard that only changes when its data does
+//! div()
+//!     .cache_as_layer()
+//!     .child(chart_grid())
+//!     .child(legend());
+//!
+//! // Repaint a manually cached layer
+//! div().id("chart").layer_cache(CachePolicy::Manual);
+//! if let Some(node) = tree.query_by_id("chart") {
+//!     tree.invalidate_layer_cache(node);
+//! }
+//! ```
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use junita_core::{CachePolicy, LayerId, Transform};
+
+use crate::damage::{hash_element_type, hash_motion};
+use crate::element::{Material, RenderLayer, RenderProps};
+use crate::render_state::RenderState;
+use crate::renderer::{ElementType, RenderTree};
+use crate::tree::LayoutNodeId;
+
+/// Layer ID used for a cached node's retained texture
+pub(crate) fn layer_id(node: LayoutNodeId) -> LayerId {
+    LayerId::new(node.to_raw())
+}
+
+/// Cache policy and content version for a node's layer
+///
+/// Returns `None` when the node isn't cached or its subtree can't be
+/// replayed from a texture.
+pub(crate) fn layer_cache_key(
+    tree: &RenderTree,
+    render_state: &RenderState,
+    node: LayoutNodeId,
+) -> Option<(CachePolicy, u64)> {
+    let render_node = tree.get_render_node(node)?;
+    let policy = match render_node.props.layer_cache {
+        CachePolicy::None if tree.auto_layer_cache() && is_auto_candidate(tree, node) => {
+            CachePolicy::Content
+        }
+        policy => policy,
+    };
+    if policy == CachePolicy::None || !can_cache_root(&render_node.props, &render_node.element_type)
+    {
+        return None;
+    }
+
+    let bounds = tree.get_render_bounds(node, (0.0, 0.0))?;
+    let mut hasher = DefaultHasher::new();
+    bounds.width.to_bits().hash(&mut hasher);
+    bounds.height.to_bits().hash(&mut hasher);
+
+    match policy {
+        CachePolicy::Manual => {
+            tree.layer_cache_generation(node).hash(&mut hasher);
+            // The subtree still has to be replayable
+            let mut ignored = DefaultHasher::new();
+            hash_children(tree, render_state, node, &mut ignored)?;
+        }
+        _ => {
+            hash_node_content(tree, node, &mut hasher);
+            hash_children(tree, render_state, node, &mut hasher)?;
+        }
+    }
+    Some((policy, hasher.finish()))
+}
+
+/// Nodes animated by their own motion with children to cache
+fn is_auto_candidate(tree: &RenderTree, node: LayoutNodeId) -> bool {
+    let animated = tree.get_motion_transform(node).is_some()
+        || tree.get_motion_scale(node).is_some()
+        || tree.get_motion_rotation(node).is_some()
+        || tree.get_motion_opacity(node).is_some()
+        || tree
+            .get_render_node(node)
+            .is_some_and(|n| n.props.motion.is_some());
+    animated && !tree.layout().children(node).is_empty()
+}
+
+/// Whether the cached node itself can be rendered into a retained layer
+fn can_cache_root(props: &RenderProps, element: &ElementType) -> bool {
+    !matches!(element, ElementType::Canvas(_))
+        && !matches!(props.material, Some(Material::Glass(_)))
+        && props.layer != RenderLayer::Foreground
+        && !props.is_stack_layer
+        && props.layer_effects.is_empty()
+}
+
+/// Hash what a node draws by itself, excluding per-frame state
+fn hash_node_content(tree: &RenderTree, node: LayoutNodeId, hasher: &mut impl Hasher) {
+    let Some(render_node) = tree.get_render_node(node) else {
+        return;
+    };
+    tree.node_hashes(node).hash(hasher);
+    crate::diff::hash_render_props(&render_node.props, hasher);
+    hash_element_type(&render_node.element_type, hasher);
+
+    let (scroll_x, scroll_y) = tree.get_scroll_offset(node);
+    scroll_x.to_bits().hash(hasher);
+    scroll_y.to_bits().hash(hasher);
+    if let Some(info) = tree.get_scrollbar_render_info(node) {
+        info.opacity.to_bits().hash(hasher);
+        std::mem::discriminant(&info.state).hash(hasher);
+    }
+}
+
+/// Hash the descendants of a cached node
+///
+/// Returns `None` if a descendant can't be replayed from a texture.
+fn hash_children(
+    tree: &RenderTree,
+    render_state: &RenderState,
+    node: LayoutNodeId,
+    hasher: &mut impl Hasher,
+) -> Option<()> {
+    for child in tree.layout().children(node) {
+        let render_node = tree.get_render_node(child)?;
+        let props = &render_node.props;
+
+        let removed = match &props.motion_stable_id {
+            Some(key) => render_state.is_stable_motion_removed(key),
+            None => render_state.is_motion_removed(child),
+        };
+        removed.hash(hasher);
+        if removed {
+            continue;
+        }
+
+        let motion = match &props.motion_stable_id {
+            Some(key) => render_state.get_stable_motion_values(key),
+            None => render_state.get_motion_values(child),
+        };
+        let binding_opacity = tree.get_motion_opacity(child);
+        let opacity = motion
+            .and_then(|m| m.opacity)
+            .unwrap_or_else(|| binding_opacity.unwrap_or(1.0));
+
+        // Nested layers are composited separately from the cached texture
+        if !can_cache_root(props, &render_node.element_type) || opacity < 1.0 {
+            return None;
+        }
+
+        let bounds = tree.get_render_bounds(child, (0.0, 0.0))?;
+        for value in [bounds.x, bounds.y, bounds.width, bounds.height] {
+            value.to_bits().hash(hasher);
+        }
+        hash_motion(motion, binding_opacity, hasher);
+        if let Some(transform) = tree.get_motion_transform(child) {
+            let Transform::Affine2D(affine) = transform else {
+                return None;
+            };
+            affine.elements.map(f32::to_bits).hash(hasher);
+        }
+        tree.get_motion_scale(child)
+            .map(|(sx, sy)| (sx.to_bits(), sy.to_bits()))
+            .hash(hasher);
+        tree.get_motion_rotation(child)
+            .map(f32::to_bits)
+            .hash(hasher);
+        tree.is_layout_animating(child).hash(hasher);
+
+        hash_node_content(tree, child, hasher);
+        hash_children(tree, render_state, child, hasher)?;
+    }
+    Some(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::div::div;
+    use junita_animation::AnimationScheduler;
+    use junita_core::Color;
+    use std::sync::{Arc, Mutex};
+
+    fn render_state() -> RenderState {
+        RenderState::new(Arc::new(Mutex::new(AnimationScheduler::new())))
+    }
+
+    fn cached_tree(color: Color) -> RenderTree {
+        let ui = div()
+            .w(200.0)
+            .h(100.0)
+            .cache_as_layer()
+            .child(div().w(50.0).h(50.0).bg(color));
+        let mut tree = RenderTree::from_element(&ui);
+        tree.compute_layout(800.0, 600.0);
+        tree
+    }
+
+    #[test]
+    fn test_uncached_node_has_no_key() {
+        let mut tree = RenderTree::from_element(&div().w(10.0).h(10.0));
+        tree.compute_layout(800.0, 600.0);
+        let root = tree.root().unwrap();
+        assert!(layer_cache_key(&tree, &render_state(), root).is_none());
+    }
+
+    #[test]
+    fn test_content_version_tracks_descendants() {
+        let state = render_state();
+        let a = cached_tree(Color::RED);
+        let b = cached_tree(Color::RED);
+        let c = cached_tree(Color::BLUE);
+
+        let key = |tree: &RenderTree| layer_cache_key(tree, &state, tree.root().unwrap());
+        let (policy, version_a) = key(&a).unwrap();
+        assert_eq!(policy, CachePolicy::Content);
+        assert_eq!(Some(version_a), key(&b).map(|(_, v)| v));
+        assert_ne!(Some(version_a), key(&c).map(|(_, v)| v));
+    }
+
+    #[test]
+    fn test_nested_layer_is_not_cacheable() {
+        let ui = div()
+            .w(200.0)
+            .h(100.0)
+            .cache_as_layer()
+            .child(div().w(50.0).h(50.0).blur(4.0));
+        let mut tree = RenderTree::from_element(&ui);
+        tree.compute_layout(800.0, 600.0);
+        let root = tree.root().unwrap();
+        assert!(layer_cache_key(&tree, &render_state(), root).is_none());
+    }
+
+    #[test]
+    fn test_manual_cache_changes_only_on_invalidate() {
+        let ui = div()
+            .w(200.0)
+            .h(100.0)
+            .layer_cache(CachePolicy::Manual)
+            .child(div().w(50.0).h(50.0).bg(Color::RED));
+        let mut tree = RenderTree::from_element(&ui);
+        tree.compute_layout(800.0, 600.0);
+        let root = tree.root().unwrap();
+        let state = render_state();
+
+        let (_, before) = layer_cache_key(&tree, &state, root).unwrap();
+        assert_eq!(before, layer_cache_key(&tree, &state, root).unwrap().1);
+        tree.invalidate_layer_cache(root);
+        assert_ne!(before, layer_cache_key(&tree, &state, root).unwrap().1);
+    }
+}
@@ -30,6 +30,7 @@
 
 pub mod animated;
 pub mod canvas;
+pub mod damage;
 pub mod diff;
 pub mod div;
 pub mod element;
@@ -41,6 +42,7 @@ pub mod event_handler;
 pub mod event_router;
 pub mod image;
 pub mod interactive;
+pub mod layer_cache;
 pub mod layout_animation;
 pub mod motion;
 pub mod render_state;
@@ -50,6 +52,7 @@ pub mod scroll;
 pub mod stack;
 pub mod stateful;
 pub mod style;
+pub mod style_origin;
 pub mod styled_text;
 pub mod svg;
 pub mod syntax;
@@ -75,6 +78,9 @@ pub mod overlay_state;
 #[cfg(feature = "recorder")]
 pub mod recorder_bridge;
 
+// Layout debugging overlays (box model, flex lines, overflow, clips)
+pub mod debug_overlay;
+
 // CSS subset parser for ElementStyle
 pub mod css_parser;
 
@@ -116,10 +122,13 @@ pub use stack::{stack, Stack};
 // Reference binding
 pub use div::{DivRef, ElementRef};
 pub use image::{
-    emoji, emoji_sized, image, img, Image, ImageFilter, LoadingStrategy, ObjectFit, ObjectPosition,
-    Placeholder,
+    emoji, emoji_sized, image, img, Image, ImageFilter, ImagePlayback, LoadingStrategy, ObjectFit,
+    ObjectPosition, Placeholder,
+};
+pub use rich_text::{
+    rich_text, rich_text_styled, Block, BlockKind, DocPosition, Embed, Inline, Mark, RichDocument,
+    RichText, Selection, Transaction,
 };
-pub use rich_text::{rich_text, rich_text_styled, RichText};
 pub use svg::{svg, Svg};
 pub use text::{text, Text};
 
@@ -129,6 +138,9 @@ pub use renderer::{
     RenderTreeDebugStats, StyledTextData, StyledTextSpan, SvgData, TextData, UpdateResult,
 };
 
+// Damage tracking for partial redraw
+pub use damage::{DamageRegion, DamageTracker};
+
 // Canvas element
 pub use canvas::{canvas, Canvas, CanvasBounds, CanvasData, CanvasRenderFn};
 
@@ -198,8 +210,8 @@ pub mod prelude {
     pub use crate::event_router::{EventRouter, HitTestResult, MouseButton};
     // Image element
     pub use crate::image::{
-        emoji, emoji_sized, image, img, Image, ImageFilter, LoadingStrategy, ObjectFit,
-        ObjectPosition, Placeholder,
+        emoji, emoji_sized, image, img, Image, ImageFilter, ImagePlayback, LoadingStrategy,
+        ObjectFit, ObjectPosition, Placeholder,
     };
     // Interactive state management
     pub use crate::interactive::{DirtyTracker, InteractiveContext, NodeState};
@@ -266,6 +278,10 @@ pub mod prelude {
         // Cursor blink timing (for use by app layer)
         elapsed_ms,
         has_focused_text_input,
+        // Rich text editor widget - ready-to-use
+        rich_text_editor,
+        rich_text_editor_state,
+        rich_text_editor_state_with_placeholder,
         // Text area widget - ready-to-use
         text_area,
         text_area_state,
@@ -282,7 +298,11 @@ pub mod prelude {
         CheckboxState,
         InputConstraints,
         InputType,
+        RichTextEditor,
+        RichTextEditorConfig,
+        RichTextEditorState,
         SharedCheckboxState,
+        SharedRichTextEditorState,
         SharedTextAreaState,
         SharedTextInputState,
         TextArea,
@@ -314,15 +334,18 @@ pub mod prelude {
     };
 
     // Code block widget with syntax highlighting
-    pub use crate::widgets::{code, pre, Code, CodeConfig};
+    pub use crate::widgets::{
+        code, code_state, pre, Code, CodeConfig, CodeState, FindOptions, SharedCodeState,
+    };
 
     // CSS-like units for layout dimensions
     pub use crate::units::{pct, px, sp, Length, Unit};
 
     // Syntax highlighting
     pub use crate::syntax::{
-        JsonHighlighter, PlainHighlighter, RustHighlighter, SyntaxConfig, SyntaxHighlighter,
-        TokenHit, TokenRule, TokenType,
+        Grammar, GrammarHighlighter, GrammarSet, GrammarTheme, JsonHighlighter, PlainHighlighter,
+        RustHighlighter, ScopeStyle, SyntaxConfig, SyntaxHighlighter, TokenHit, TokenRule,
+        TokenType,
     };
 
     // Canvas element
@@ -387,7 +410,8 @@ pub mod prelude {
 
     // Markdown rendering
     pub use crate::markdown::{
-        markdown, markdown_light, markdown_with_config, MarkdownConfig, MarkdownRenderer,
+        markdown, markdown_light, markdown_with_config, MarkdownBlock, MarkdownConfig,
+        MarkdownDocument, MarkdownRenderer, MarkdownTag,
     };
 
     // Additional markdown widgets
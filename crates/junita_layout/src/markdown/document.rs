@@ -0,0 +1,217 @@
+//! Rendered documents: content plus front matter and an outline
+
+use std::collections::HashMap;
+
+use pulldown_cmark::{Event, HeadingLevel, MetadataBlockKind, Tag, TagEnd};
+
+use crate::div::Div;
+
+/// The result of [`MarkdownRenderer::render_document`](super::MarkdownRenderer::render_document)
+pub struct MarkdownDocument {
+    /// The rendered document
+    pub content: Div,
+    /// A table of contents linking to each heading's anchor
+    pub toc: Div,
+    /// The metadata block at the top of the document, if any
+    pub front_matter: Option<FrontMatter>,
+    /// Every heading, in document order
+    pub headings: Vec<HeadingEntry>,
+}
+
+/// A heading and the element id it can be scrolled to by
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HeadingEntry {
+    /// Heading level, 1-6
+    pub level: u8,
+    /// Plain text of the heading
+    pub text: String,
+    /// Element id: the `{#id}` attribute or a slug of the text
+    pub anchor: String,
+}
+
+/// Front matter syntax
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FrontMatterKind {
+    /// Between `---` lines
+    Yaml,
+    /// Between `+++` lines
+    Toml,
+}
+
+/// Front matter of a document
+///
+/// The raw source is kept for use with a full YAML or TOML parser; top-level
+/// `key: value` (YAML) or `key = value` (TOML) pairs are also read into
+/// [`fields`](Self::fields) with quotes stripped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrontMatter {
+    kind: FrontMatterKind,
+    raw: String,
+    fields: Vec<(String, String)>,
+}
+
+impl FrontMatter {
+    pub(crate) fn parse(kind: MetadataBlockKind, raw: String) -> Self {
+        let (kind, separator) = match kind {
+            MetadataBlockKind::YamlStyle => (FrontMatterKind::Yaml, ':'),
+            MetadataBlockKind::PlusesStyle => (FrontMatterKind::Toml, '='),
+        };
+        let fields = raw
+            .lines()
+            // Nested values and comments are left to a real parser
+            .filter(|line| !line.starts_with([' ', '\t', '#', '-', '[']))
+            .filter_map(|line| {
+                let (key, value) = line.split_once(separator)?;
+                let key = unquote(key.trim());
+                (!key.is_empty()).then(|| (key.to_string(), unquote(value.trim()).to_string()))
+            })
+            .collect();
+        Self { kind, raw, fields }
+    }
+
+    /// The syntax the front matter was written in
+    pub fn kind(&self) -> FrontMatterKind {
+        self.kind
+    }
+
+    /// The source between the delimiters
+    pub fn raw(&self) -> &str {
+        &self.raw
+    }
+
+    /// The value of a top-level key
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Top-level key/value pairs in source order
+    pub fn fields(&self) -> &[(String, String)] {
+        &self.fields
+    }
+}
+
+fn unquote(s: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = s.strip_prefix(quote).and_then(|s| s.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    s
+}
+
+pub(crate) fn heading_level(level: HeadingLevel) -> u8 {
+    match level {
+        HeadingLevel::H1 => 1,
+        HeadingLevel::H2 => 2,
+        HeadingLevel::H3 => 3,
+        HeadingLevel::H4 => 4,
+        HeadingLevel::H5 => 5,
+        HeadingLevel::H6 => 6,
+    }
+}
+
+/// Collect the headings of a document, assigning each a unique anchor
+pub(crate) fn collect_headings(events: &[Event<'_>]) -> Vec<HeadingEntry> {
+    let mut headings = Vec::new();
+    let mut used: HashMap<String, usize> = HashMap::new();
+    let mut current: Option<(u8, Option<String>, String)> = None;
+    for event in events {
+        match event {
+            Event::Start(Tag::Heading { level, id, .. }) => {
+                current = Some((
+                    heading_level(*level),
+                    id.as_ref().map(|id| id.to_string()),
+                    String::new(),
+                ));
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, _, heading_text)) = &mut current {
+                    heading_text.push_str(text);
+                }
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                if let Some((level, id, text)) = current.take() {
+                    let anchor = id.unwrap_or_else(|| slug(&text));
+                    let anchor = match used.get_mut(&anchor) {
+                        Some(count) => {
+                            *count += 1;
+                            format!("{}-{}", anchor, count)
+                        }
+                        None => {
+                            used.insert(anchor.clone(), 0);
+                            anchor
+                        }
+                    };
+                    headings.push(HeadingEntry {
+                        level,
+                        text,
+                        anchor,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+    headings
+}
+
+/// GitHub-style anchor: lowercase, punctuation dropped, spaces to hyphens
+pub(crate) fn slug(text: &str) -> String {
+    text.trim()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pulldown_cmark::{Options, Parser};
+
+    #[test]
+    fn test_slug() {
+        assert_eq!(slug("Getting Started"), "getting-started");
+        assert_eq!(slug("What's `new` in 2.0?"), "whats-new-in-20");
+    }
+
+    #[test]
+    fn test_collect_headings_dedupes_anchors() {
+        let md = "# Intro\n\n## Usage\n\n## Usage\n\n### Custom {#my-id}\n";
+        let events: Vec<_> = Parser::new_ext(md, Options::ENABLE_HEADING_ATTRIBUTES).collect();
+        let anchors: Vec<_> = collect_headings(&events)
+            .into_iter()
+            .map(|h| (h.level, h.anchor))
+            .collect();
+        assert_eq!(
+            anchors,
+            vec![
+                (1, "intro".to_string()),
+                (2, "usage".to_string()),
+                (2, "usage-1".to_string()),
+                (3, "my-id".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_front_matter_fields() {
+        let raw = "title: \"Hello\"\ntags:\n  - a\ndraft: false\n".to_string();
+        let fm = FrontMatter::parse(MetadataBlockKind::YamlStyle, raw);
+        assert_eq!(fm.kind(), FrontMatterKind::Yaml);
+        assert_eq!(fm.get("title"), Some("Hello"));
+        assert_eq!(fm.get("draft"), Some("false"));
+        assert_eq!(fm.get("tags"), Some(""));
+        assert_eq!(fm.fields().len(), 3);
+
+        let fm = FrontMatter::parse(MetadataBlockKind::PlusesStyle, "title = 'Hi'\n".into());
+        assert_eq!(fm.get("title"), Some("Hi"));
+    }
+}
@@ -0,0 +1,576 @@
+//! TeX math
+//!
+//! Math is typeset by converting TeX to Unicode: Greek letters and symbols
+//! map to their code points, `^`/`_` use superscript and subscript
+//! characters where Unicode has them, and `\frac`/`\sqrt` become inline
+//! forms like `(a+b)/2` and `√x`. This covers the formulas documentation
+//! usually needs without a layout engine for stacked fractions or matrices.
+//!
+//! Delimiters follow Pandoc: inline math is `$...$` where the opening `$`
+//! is not followed by a space and the closing `$` is not preceded by a
+//! space or followed by a digit, so prices like "$5 and $10" stay text.
+//! Display math is a paragraph wrapped in `$$` or a ```` ```math ```` block.
+
+use crate::div::{div, Div};
+use crate::text::text;
+
+use super::config::MarkdownConfig;
+
+/// Convert TeX to Unicode text
+///
+/// # Example
+///
+/// ```
+/// use junita_layout::markdown::tex_to_unicode;
+///
+/// assert_eq!(tex_to_unicode(r"\alpha^2 + \beta_i \leq \infty"), "α² + βᵢ ≤ ∞");
+/// assert_eq!(tex_to_unicode(r"\frac{a+b}{2}"), "(a+b)/2");
+/// ```
+pub fn tex_to_unicode(tex: &str) -> String {
+    let tokens = tokenize(tex);
+    let mut out = String::new();
+    convert(&tokens, &mut 0, &mut out);
+    out
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Command(String),
+    Char(char),
+    Group(Vec<Token>),
+    Optional(Vec<Token>),
+}
+
+fn tokenize(tex: &str) -> Vec<Token> {
+    let chars: Vec<char> = tex.chars().collect();
+    let mut pos = 0;
+    tokenize_until(&chars, &mut pos, None)
+}
+
+fn tokenize_until(chars: &[char], pos: &mut usize, close: Option<char>) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    while *pos < chars.len() {
+        let c = chars[*pos];
+        *pos += 1;
+        match c {
+            c if Some(c) == close => return tokens,
+            '{' => tokens.push(Token::Group(tokenize_until(chars, pos, Some('}')))),
+            '\\' => {
+                let start = *pos;
+                while *pos < chars.len() && chars[*pos].is_ascii_alphabetic() {
+                    *pos += 1;
+                }
+                if *pos == start && *pos < chars.len() {
+                    // Single-character command like `\{` or `\,`
+                    *pos += 1;
+                }
+                let name: String = chars[start..*pos].iter().collect();
+                // `\sqrt[3]{x}`
+                let optional = name == "sqrt" && chars.get(*pos) == Some(&'[');
+                tokens.push(Token::Command(name));
+                if optional {
+                    *pos += 1;
+                    tokens.push(Token::Optional(tokenize_until(chars, pos, Some(']'))));
+                }
+            }
+            c => tokens.push(Token::Char(c)),
+        }
+    }
+    tokens
+}
+
+/// Take the argument starting at `pos`: a group or a single token
+fn argument<'a>(tokens: &'a [Token], pos: &mut usize) -> &'a [Token] {
+    // Skip spaces between a command and its argument
+    while matches!(tokens.get(*pos), Some(Token::Char(' '))) {
+        *pos += 1;
+    }
+    match tokens.get(*pos) {
+        Some(Token::Group(group)) => {
+            *pos += 1;
+            group
+        }
+        Some(_) => {
+            *pos += 1;
+            &tokens[*pos - 1..*pos]
+        }
+        None => &[],
+    }
+}
+
+fn convert(tokens: &[Token], pos: &mut usize, out: &mut String) {
+    while *pos < tokens.len() {
+        let token = &tokens[*pos];
+        *pos += 1;
+        match token {
+            Token::Char('^') => {
+                let arg = to_string(argument(tokens, pos));
+                script(&arg, superscript, '^', out);
+            }
+            Token::Char('_') => {
+                let arg = to_string(argument(tokens, pos));
+                script(&arg, subscript, '_', out);
+            }
+            Token::Char('~') => out.push('\u{a0}'),
+            Token::Char(c) => out.push(*c),
+            Token::Group(group) | Token::Optional(group) => convert(group, &mut 0, out),
+            Token::Command(name) => command(name, tokens, pos, out),
+        }
+    }
+}
+
+fn to_string(tokens: &[Token]) -> String {
+    let mut out = String::new();
+    convert(tokens, &mut 0, &mut out);
+    out
+}
+
+/// Whether `s` reads as one term, so it needs no parentheses
+fn is_simple(s: &str) -> bool {
+    s.chars().count() == 1 || s.chars().all(|c| c.is_ascii_alphanumeric() || c == '.')
+}
+
+fn parenthesize(s: &str) -> String {
+    if is_simple(s) {
+        s.to_string()
+    } else {
+        format!("({})", s)
+    }
+}
+
+fn script(arg: &str, map: fn(char) -> Option<char>, marker: char, out: &mut String) {
+    match arg.chars().map(map).collect::<Option<String>>() {
+        Some(mapped) => out.push_str(&mapped),
+        None => {
+            out.push(marker);
+            out.push_str(&parenthesize(arg));
+        }
+    }
+}
+
+fn command(name: &str, tokens: &[Token], pos: &mut usize, out: &mut String) {
+    match name {
+        "frac" | "dfrac" | "tfrac" => {
+            let numerator = to_string(argument(tokens, pos));
+            let denominator = to_string(argument(tokens, pos));
+            out.push_str(&parenthesize(&numerator));
+            out.push('/');
+            out.push_str(&parenthesize(&denominator));
+        }
+        "sqrt" => {
+            if let Some(Token::Optional(index)) = tokens.get(*pos) {
+                *pos += 1;
+                script(&to_string(index), superscript, '^', out);
+            }
+            let radicand = to_string(argument(tokens, pos));
+            out.push('√');
+            out.push_str(&parenthesize(&radicand));
+        }
+        "hat" | "bar" | "overline" | "vec" | "dot" | "ddot" | "tilde" => {
+            let mark = match name {
+                "hat" => '\u{302}',
+                "bar" | "overline" => '\u{305}',
+                "vec" => '\u{20d7}',
+                "dot" => '\u{307}',
+                "ddot" => '\u{308}',
+                _ => '\u{303}',
+            };
+            for c in to_string(argument(tokens, pos)).chars() {
+                out.push(c);
+                out.push(mark);
+            }
+        }
+        "text" | "textrm" | "mathrm" | "mathit" | "mathbf" | "mathsf" | "mathtt"
+        | "operatorname" => {
+            out.push_str(&to_string(argument(tokens, pos)));
+        }
+        "mathbb" => {
+            for c in to_string(argument(tokens, pos)).chars() {
+                out.push(double_struck(c).unwrap_or(c));
+            }
+        }
+        // Sizing and spacing that has no effect in running text
+        "left" | "right" | "big" | "Big" | "bigg" | "Bigg" | "displaystyle" | "limits" | "!" => {}
+        "," | ":" | ">" => out.push('\u{2009}'),
+        ";" | " " => out.push(' '),
+        "quad" => out.push('\u{2003}'),
+        "qquad" => out.push_str("\u{2003}\u{2003}"),
+        "\\" => out.push('\n'),
+        _ => match symbol(name) {
+            Some(symbol) => out.push_str(symbol),
+            // Escaped punctuation (`\{`, `\%`) and unknown commands print as
+            // their name
+            None => out.push_str(name),
+        },
+    }
+}
+
+fn symbol(name: &str) -> Option<&'static str> {
+    Some(match name {
+        // Greek
+        "alpha" => "α",
+        "beta" => "β",
+        "gamma" => "γ",
+        "delta" => "δ",
+        "epsilon" => "ϵ",
+        "varepsilon" => "ε",
+        "zeta" => "ζ",
+        "eta" => "η",
+        "theta" => "θ",
+        "vartheta" => "ϑ",
+        "iota" => "ι",
+        "kappa" => "κ",
+        "lambda" => "λ",
+        "mu" => "μ",
+        "nu" => "ν",
+        "xi" => "ξ",
+        "pi" => "π",
+        "varpi" => "ϖ",
+        "rho" => "ρ",
+        "sigma" => "σ",
+        "tau" => "τ",
+        "upsilon" => "υ",
+        "phi" => "ϕ",
+        "varphi" => "φ",
+        "chi" => "χ",
+        "psi" => "ψ",
+        "omega" => "ω",
+        "Gamma" => "Γ",
+        "Delta" => "Δ",
+        "Theta" => "Θ",
+        "Lambda" => "Λ",
+        "Xi" => "Ξ",
+        "Pi" => "Π",
+        "Sigma" => "Σ",
+        "Upsilon" => "Υ",
+        "Phi" => "Φ",
+        "Psi" => "Ψ",
+        "Omega" => "Ω",
+        // Big operators
+        "sum" => "∑",
+        "prod" => "∏",
+        "coprod" => "∐",
+        "int" => "∫",
+        "iint" => "∬",
+        "oint" => "∮",
+        "bigcup" => "⋃",
+        "bigcap" => "⋂",
+        // Binary operators and relations
+        "pm" => "±",
+        "mp" => "∓",
+        "times" => "×",
+        "div" => "÷",
+        "cdot" => "·",
+        "ast" => "∗",
+        "circ" => "∘",
+        "bullet" => "∙",
+        "oplus" => "⊕",
+        "otimes" => "⊗",
+        "leq" | "le" => "≤",
+        "geq" | "ge" => "≥",
+        "neq" | "ne" => "≠",
+        "ll" => "≪",
+        "gg" => "≫",
+        "approx" => "≈",
+        "equiv" => "≡",
+        "sim" => "∼",
+        "simeq" => "≃",
+        "cong" => "≅",
+        "propto" => "∝",
+        "in" => "∈",
+        "notin" => "∉",
+        "ni" => "∋",
+        "subset" => "⊂",
+        "subseteq" => "⊆",
+        "supset" => "⊃",
+        "supseteq" => "⊇",
+        "cup" => "∪",
+        "cap" => "∩",
+        "setminus" => "∖",
+        "mid" => "∣",
+        "parallel" => "∥",
+        "perp" => "⊥",
+        "wedge" | "land" => "∧",
+        "vee" | "lor" => "∨",
+        "neg" | "lnot" => "¬",
+        // Arrows
+        "to" | "rightarrow" => "→",
+        "leftarrow" | "gets" => "←",
+        "leftrightarrow" => "↔",
+        "Rightarrow" => "⇒",
+        "Leftarrow" => "⇐",
+        "Leftrightarrow" | "iff" => "⇔",
+        "implies" => "⟹",
+        "mapsto" => "↦",
+        "uparrow" => "↑",
+        "downarrow" => "↓",
+        // Miscellaneous
+        "infty" => "∞",
+        "partial" => "∂",
+        "nabla" => "∇",
+        "forall" => "∀",
+        "exists" => "∃",
+        "emptyset" | "varnothing" => "∅",
+        "hbar" => "ℏ",
+        "ell" => "ℓ",
+        "Re" => "ℜ",
+        "Im" => "ℑ",
+        "aleph" => "ℵ",
+        "angle" => "∠",
+        "triangle" => "△",
+        "degree" => "°",
+        "prime" => "′",
+        "ldots" | "dots" => "…",
+        "cdots" => "⋯",
+        "vdots" => "⋮",
+        "ddots" => "⋱",
+        "langle" => "⟨",
+        "rangle" => "⟩",
+        "lceil" => "⌈",
+        "rceil" => "⌉",
+        "lfloor" => "⌊",
+        "rfloor" => "⌋",
+        "|" => "‖",
+        // Named functions
+        "sin" => "sin",
+        "cos" => "cos",
+        "tan" => "tan",
+        "log" => "log",
+        "ln" => "ln",
+        "exp" => "exp",
+        "lim" => "lim",
+        "max" => "max",
+        "min" => "min",
+        "det" => "det",
+        _ => return None,
+    })
+}
+
+fn superscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '⁰',
+        '1' => '¹',
+        '2' => '²',
+        '3' => '³',
+        '4' => '⁴',
+        '5' => '⁵',
+        '6' => '⁶',
+        '7' => '⁷',
+        '8' => '⁸',
+        '9' => '⁹',
+        '+' => '⁺',
+        '-' | '−' => '⁻',
+        '=' => '⁼',
+        '(' => '⁽',
+        ')' => '⁾',
+        'a' => 'ᵃ',
+        'b' => 'ᵇ',
+        'c' => 'ᶜ',
+        'd' => 'ᵈ',
+        'e' => 'ᵉ',
+        'f' => 'ᶠ',
+        'g' => 'ᵍ',
+        'h' => 'ʰ',
+        'i' => 'ⁱ',
+        'j' => 'ʲ',
+        'k' => 'ᵏ',
+        'l' => 'ˡ',
+        'm' => 'ᵐ',
+        'n' => 'ⁿ',
+        'o' => 'ᵒ',
+        'p' => 'ᵖ',
+        'r' => 'ʳ',
+        's' => 'ˢ',
+        't' => 'ᵗ',
+        'u' => 'ᵘ',
+        'v' => 'ᵛ',
+        'w' => 'ʷ',
+        'x' => 'ˣ',
+        'y' => 'ʸ',
+        'z' => 'ᶻ',
+        '′' => '′',
+        '*' | '∗' => '*',
+        _ => return None,
+    })
+}
+
+fn subscript(c: char) -> Option<char> {
+    Some(match c {
+        '0' => '₀',
+        '1' => '₁',
+        '2' => '₂',
+        '3' => '₃',
+        '4' => '₄',
+        '5' => '₅',
+        '6' => '₆',
+        '7' => '₇',
+        '8' => '₈',
+        '9' => '₉',
+        '+' => '₊',
+        '-' | '−' => '₋',
+        '=' => '₌',
+        '(' => '₍',
+        ')' => '₎',
+        'a' => 'ₐ',
+        'e' => 'ₑ',
+        'h' => 'ₕ',
+        'i' => 'ᵢ',
+        'j' => 'ⱼ',
+        'k' => 'ₖ',
+        'l' => 'ₗ',
+        'm' => 'ₘ',
+        'n' => 'ₙ',
+        'o' => 'ₒ',
+        'p' => 'ₚ',
+        'r' => 'ᵣ',
+        's' => 'ₛ',
+        't' => 'ₜ',
+        'u' => 'ᵤ',
+        'v' => 'ᵥ',
+        'x' => 'ₓ',
+        _ => return None,
+    })
+}
+
+fn double_struck(c: char) -> Option<char> {
+    Some(match c {
+        'C' => 'ℂ',
+        'H' => 'ℍ',
+        'N' => 'ℕ',
+        'P' => 'ℙ',
+        'Q' => 'ℚ',
+        'R' => 'ℝ',
+        'Z' => 'ℤ',
+        _ => return None,
+    })
+}
+
+/// A piece of inline text: plain text or the TeX between `$` delimiters
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum InlinePart<'a> {
+    Text(&'a str),
+    Math(&'a str),
+}
+
+/// Split `text` on inline `$...$` math
+pub(crate) fn split_inline_math(text: &str) -> Vec<InlinePart<'_>> {
+    let bytes = text.as_bytes();
+    let mut parts = Vec::new();
+    let mut text_start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let opens = bytes[i] == b'$'
+            && (i == 0 || bytes[i - 1] != b'\\')
+            && bytes
+                .get(i + 1)
+                .is_some_and(|b| !b.is_ascii_whitespace() && *b != b'$');
+        if !opens {
+            i += 1;
+            continue;
+        }
+        let close = (i + 2..bytes.len()).find(|&j| {
+            bytes[j] == b'$'
+                && bytes[j - 1] != b'\\'
+                && !bytes[j - 1].is_ascii_whitespace()
+                && !bytes.get(j + 1).is_some_and(u8::is_ascii_digit)
+        });
+        let Some(close) = close else {
+            break;
+        };
+        if text_start < i {
+            parts.push(InlinePart::Text(&text[text_start..i]));
+        }
+        parts.push(InlinePart::Math(&text[i + 1..close]));
+        i = close + 1;
+        text_start = i;
+    }
+    if text_start < text.len() {
+        parts.push(InlinePart::Text(&text[text_start..]));
+    }
+    parts
+}
+
+/// The TeX of a paragraph written as `$$ ... $$`
+pub(crate) fn display_math(paragraph: &str) -> Option<&str> {
+    let inner = paragraph
+        .trim()
+        .strip_prefix("$$")?
+        .strip_suffix("$$")?
+        .trim();
+    (!inner.is_empty() && !inner.contains("$$")).then_some(inner)
+}
+
+/// Inline math element, sized to sit on the text baseline
+pub(crate) fn inline_math(tex: &str, config: &MarkdownConfig) -> crate::text::Text {
+    text(tex_to_unicode(tex))
+        .size(config.body_size)
+        .color(config.text_color)
+        .serif()
+        .italic()
+        .line_height(1.5)
+        .v_baseline()
+        .no_wrap()
+}
+
+/// Centered display math, one row per `\\` line
+pub(crate) fn display_math_block(tex: &str, config: &MarkdownConfig) -> Div {
+    let mut block = div().flex_col().items_center().w_full().gap(4.0);
+    for line in tex_to_unicode(tex).lines() {
+        block = block.child(
+            text(line.trim())
+                .size(config.body_size * 1.2)
+                .color(config.text_color)
+                .serif()
+                .italic()
+                .no_wrap(),
+        );
+    }
+    block
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tex_to_unicode() {
+        assert_eq!(tex_to_unicode(r"e^{i\pi} + 1 = 0"), "e^(iπ) + 1 = 0");
+        assert_eq!(tex_to_unicode(r"x_{n+1} = x_n^2"), "xₙ₊₁ = xₙ²");
+        assert_eq!(tex_to_unicode(r"\sqrt{x^2 + y^2}"), "√(x² + y²)");
+        assert_eq!(tex_to_unicode(r"\sqrt[3]{8}"), "³√8");
+        assert_eq!(tex_to_unicode(r"\sum_{i=1}^{n} i"), "∑ᵢ₌₁ⁿ i");
+        assert_eq!(tex_to_unicode(r"\mathbb{R}^n \to \mathbb{R}"), "ℝⁿ → ℝ");
+        assert_eq!(tex_to_unicode(r"\left( \frac{1}{2} \right)"), "( 1/2 )");
+        // No superscript Q, so the script is written out
+        assert_eq!(tex_to_unicode(r"A^{Q}"), "A^Q");
+        assert_eq!(tex_to_unicode(r"\{a\}"), "{a}");
+    }
+
+    #[test]
+    fn test_split_inline_math() {
+        assert_eq!(
+            split_inline_math("area $\\pi r^2$ here"),
+            vec![
+                InlinePart::Text("area "),
+                InlinePart::Math("\\pi r^2"),
+                InlinePart::Text(" here"),
+            ]
+        );
+        // Prices and escaped dollars are text
+        assert_eq!(
+            split_inline_math("costs $5 and $10"),
+            vec![InlinePart::Text("costs $5 and $10")]
+        );
+        assert_eq!(
+            split_inline_math(r"\$x$ y"),
+            vec![InlinePart::Text(r"\$x$ y")]
+        );
+    }
+
+    #[test]
+    fn test_display_math() {
+        assert_eq!(display_math("$$ E = mc^2 $$"), Some("E = mc^2"));
+        assert_eq!(display_math("$$a$$ and $$b$$"), None);
+        assert_eq!(display_math("$x$"), None);
+    }
+}
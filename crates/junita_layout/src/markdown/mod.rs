@@ -22,9 +22,37 @@
 //! // Use in your layout
 //! div().flex_col().child(content)
 //! ```
+//!
+//! # Documents and plugins
+//!
+//! [`MarkdownRenderer::render_document`] also returns the front matter, the
+//! headings with their anchors and a table of contents; a `[TOC]` paragraph
+//! inserts the table of contents inline. Custom renderers for block kinds
+//! and fenced code languages, link and image hooks and TeX math are set on
+//! the renderer with [`MarkdownRenderer::tag_renderer`],
+//! [`MarkdownRenderer::code_renderer`] and friends.
+//!
+//! ```ignore
+//! let doc = MarkdownRenderer::new()
+//!     .math(true)
+//!     .scroll_ref(scroll_ref.clone())
+//!     .render_document(source);
+//!
+//! let title = doc.front_matter.as_ref().and_then(|fm| fm.get("title"));
+//! div().flex_row().child(doc.toc).child(scroll().bind(&scroll_ref).child(doc.content))
+//! ```
 
 mod config;
+mod document;
+mod math;
+mod plugins;
 mod renderer;
 
 pub use config::MarkdownConfig;
+pub(crate) use document::heading_level;
+pub use document::{FrontMatter, FrontMatterKind, HeadingEntry, MarkdownDocument};
+pub use math::tex_to_unicode;
+pub use plugins::{
+    BlockRenderer, CodeRenderer, ImageResolver, LinkHandler, MarkdownBlock, MarkdownTag,
+};
 pub use renderer::{markdown, markdown_light, markdown_with_config, MarkdownRenderer};
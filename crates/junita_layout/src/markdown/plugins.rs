@@ -0,0 +1,122 @@
+//! Renderer extension points
+//!
+//! Plugins replace how a kind of block is built ([`MarkdownTag`]), render
+//! fenced code blocks of a given language (```` ```chart ````), intercept
+//! link clicks and rewrite image sources. They are registered on
+//! [`MarkdownRenderer`](super::MarkdownRenderer):
+//!
+//! ```ignore
+//! use junita_layout::markdown::{MarkdownBlock, MarkdownRenderer, MarkdownTag};
+//!
+//! let renderer = MarkdownRenderer::new()
+//!     .code_renderer("chart", |source, _config| Box::new(my_chart(source)))
+//!     .tag_renderer(MarkdownTag::Image, |block, _config| match block {
+//!         MarkdownBlock::Image { src, .. } => Box::new(img(src).rounded(8.0)),
+//!         _ => unreachable!(),
+//!     })
+//!     .image_resolver(|src| Some(format!("assets/{}", src)))
+//!     .on_link_click(|url, _ctx| println!("clicked {}", url));
+//! ```
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::div::{Div, ElementBuilder};
+use crate::event_handler::EventContext;
+use crate::selector::ScrollRef;
+
+use super::config::MarkdownConfig;
+
+/// Kinds of markdown blocks whose rendering can be replaced
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MarkdownTag {
+    /// `#` headings
+    Heading,
+    /// `>` quotes
+    BlockQuote,
+    /// Fenced and indented code blocks without a language renderer
+    CodeBlock,
+    /// `![alt](src)` images
+    Image,
+    /// Raw HTML blocks
+    HtmlBlock,
+    /// `---` thematic breaks
+    Rule,
+    /// `$...$` and `$$...$$` math, when math is enabled
+    Math,
+}
+
+/// A parsed block handed to a tag renderer
+pub enum MarkdownBlock {
+    /// A heading; the renderer's element is wrapped in a div with id `anchor`
+    Heading {
+        level: u8,
+        text: String,
+        anchor: String,
+    },
+    /// A quote with its rendered content
+    BlockQuote { content: Box<Div> },
+    /// A code block and its info string's first word
+    CodeBlock {
+        language: Option<String>,
+        code: String,
+    },
+    /// An image, after the source resolver ran
+    Image {
+        src: String,
+        alt: String,
+        title: Option<String>,
+    },
+    /// Raw HTML source
+    HtmlBlock { html: String },
+    /// A thematic break
+    Rule,
+    /// TeX source, inline or display
+    Math { tex: String, display: bool },
+}
+
+impl MarkdownBlock {
+    /// The tag this block is rendered for
+    pub fn tag(&self) -> MarkdownTag {
+        match self {
+            MarkdownBlock::Heading { .. } => MarkdownTag::Heading,
+            MarkdownBlock::BlockQuote { .. } => MarkdownTag::BlockQuote,
+            MarkdownBlock::CodeBlock { .. } => MarkdownTag::CodeBlock,
+            MarkdownBlock::Image { .. } => MarkdownTag::Image,
+            MarkdownBlock::HtmlBlock { .. } => MarkdownTag::HtmlBlock,
+            MarkdownBlock::Rule => MarkdownTag::Rule,
+            MarkdownBlock::Math { .. } => MarkdownTag::Math,
+        }
+    }
+}
+
+/// Builds the element for a block
+pub type BlockRenderer =
+    Arc<dyn Fn(MarkdownBlock, &MarkdownConfig) -> Box<dyn ElementBuilder> + Send + Sync>;
+
+/// Builds the element for a fenced code block's source
+pub type CodeRenderer = Arc<dyn Fn(&str, &MarkdownConfig) -> Box<dyn ElementBuilder> + Send + Sync>;
+
+/// Handles a click on a link; receives the link's URL
+pub type LinkHandler = Arc<dyn Fn(&str, &EventContext) + Send + Sync>;
+
+/// Maps an image source to the one to load, or `None` to keep it
+pub type ImageResolver = Arc<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+/// Plugins registered on a renderer
+#[derive(Clone, Default)]
+pub(crate) struct Plugins {
+    pub tags: HashMap<MarkdownTag, BlockRenderer>,
+    pub languages: HashMap<String, CodeRenderer>,
+    pub on_link_click: Option<LinkHandler>,
+    pub image_resolver: Option<ImageResolver>,
+    pub math: bool,
+    pub scroll_ref: Option<ScrollRef>,
+}
+
+impl Plugins {
+    /// The renderer registered for `tag`
+    pub fn tag(&self, tag: MarkdownTag) -> Option<&BlockRenderer> {
+        self.tags.get(&tag)
+    }
+}
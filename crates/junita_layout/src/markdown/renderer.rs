@@ -1,18 +1,28 @@
 //! Markdown to junita layout renderer
 
-use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use std::sync::Arc;
+
+use pulldown_cmark::{CodeBlockKind, Event, MetadataBlockKind, Options, Parser, Tag, TagEnd};
 
 use crate::div::{div, Div, ElementBuilder};
+use crate::event_handler::EventContext;
 use crate::image::img;
+use crate::selector::ScrollRef;
+use crate::syntax::{GrammarHighlighter, GrammarTheme, SyntaxConfig};
 use crate::text::text;
 use crate::typography::{h1, h2, h3, h4, h5, h6};
 use crate::widgets::{
     code, li, link, ol_start_with_config, ol_with_config, striped_tr, table, task_item,
-    task_item_with_config, tbody, td, th, thead, tr, ul_with_config, ListConfig, ListItem,
+    task_item_with_config, tbody, td, th, thead, tr, ul_with_config, Link, ListConfig, ListItem,
     OrderedList, TaskListItem, UnorderedList,
 };
 
 use super::config::MarkdownConfig;
+use super::document::{
+    collect_headings, heading_level, slug, FrontMatter, HeadingEntry, MarkdownDocument,
+};
+use super::math::{self, InlinePart};
+use super::plugins::{MarkdownBlock, MarkdownTag, Plugins};
 
 // Re-export for HTML entity decoding
 use html_escape::decode_html_entities;
@@ -20,19 +30,21 @@ use html_escape::decode_html_entities;
 /// Markdown renderer that converts markdown text to junita layout elements
 pub struct MarkdownRenderer {
     config: MarkdownConfig,
+    plugins: Plugins,
 }
 
 impl MarkdownRenderer {
     /// Create a new markdown renderer with default configuration
     pub fn new() -> Self {
-        Self {
-            config: MarkdownConfig::default(),
-        }
+        Self::with_config(MarkdownConfig::default())
     }
 
     /// Create a renderer with custom configuration
     pub fn with_config(config: MarkdownConfig) -> Self {
-        Self { config }
+        Self {
+            config,
+            plugins: Plugins::default(),
+        }
     }
 
     /// Set the configuration
@@ -41,8 +53,74 @@ impl MarkdownRenderer {
         self
     }
 
+    /// Replace how blocks of a kind are rendered
+    pub fn tag_renderer<F>(mut self, tag: MarkdownTag, renderer: F) -> Self
+    where
+        F: Fn(MarkdownBlock, &MarkdownConfig) -> Box<dyn ElementBuilder> + Send + Sync + 'static,
+    {
+        self.plugins.tags.insert(tag, Arc::new(renderer));
+        self
+    }
+
+    /// Render fenced code blocks tagged `language` with `renderer`
+    ///
+    /// The language is the first word of the info string, so a renderer
+    /// for `chart` also handles ```` ```chart {height=200} ````.
+    pub fn code_renderer<F>(mut self, language: impl Into<String>, renderer: F) -> Self
+    where
+        F: Fn(&str, &MarkdownConfig) -> Box<dyn ElementBuilder> + Send + Sync + 'static,
+    {
+        self.plugins
+            .languages
+            .insert(language.into(), Arc::new(renderer));
+        self
+    }
+
+    /// Handle link clicks instead of opening the URL in the browser
+    ///
+    /// Fragment links (`#anchor`) scroll to their heading instead when a
+    /// scroll ref is set with [`scroll_ref`](Self::scroll_ref).
+    pub fn on_link_click<F>(mut self, handler: F) -> Self
+    where
+        F: Fn(&str, &EventContext) + Send + Sync + 'static,
+    {
+        self.plugins.on_link_click = Some(Arc::new(handler));
+        self
+    }
+
+    /// Rewrite image sources, e.g. to resolve paths relative to the document
+    ///
+    /// Returning `None` keeps the original source.
+    pub fn image_resolver<F>(mut self, resolver: F) -> Self
+    where
+        F: Fn(&str) -> Option<String> + Send + Sync + 'static,
+    {
+        self.plugins.image_resolver = Some(Arc::new(resolver));
+        self
+    }
+
+    /// Render `$...$`, `$$...$$` and ```` ```math ```` as TeX math
+    ///
+    /// Off by default since `$` is common in prose.
+    pub fn math(mut self, enabled: bool) -> Self {
+        self.plugins.math = enabled;
+        self
+    }
+
+    /// Scroll container that fragment links (`#anchor`) scroll within
+    pub fn scroll_ref(mut self, scroll_ref: ScrollRef) -> Self {
+        self.plugins.scroll_ref = Some(scroll_ref);
+        self
+    }
+
     /// Render markdown text to a Div containing all the elements
     pub fn render(&self, markdown_text: &str) -> Div {
+        self.render_document(markdown_text).content
+    }
+
+    /// Render markdown text along with its front matter, headings and a
+    /// table of contents
+    pub fn render_document(&self, markdown_text: &str) -> MarkdownDocument {
         // Set up parser with GFM extensions and additional features
         let mut options = Options::empty();
         options.insert(Options::ENABLE_TABLES);
@@ -50,16 +128,27 @@ impl MarkdownRenderer {
         options.insert(Options::ENABLE_TASKLISTS);
         options.insert(Options::ENABLE_FOOTNOTES);
         options.insert(Options::ENABLE_YAML_STYLE_METADATA_BLOCKS);
+        options.insert(Options::ENABLE_PLUSES_DELIMITED_METADATA_BLOCKS);
         options.insert(Options::ENABLE_HEADING_ATTRIBUTES);
 
         let parser = Parser::new_ext(markdown_text, options);
         let events: Vec<Event<'_>> = parser.collect();
 
+        // Headings are collected up front so a `[TOC]` can list later ones
+        let headings = collect_headings(&events);
+
         // Build the layout
-        let mut renderer = RenderState::new(&self.config);
+        let mut renderer = RenderState::new(&self.config, &self.plugins, &headings);
         renderer.render_events(&events);
 
-        renderer.into_container()
+        let toc = renderer.build_toc();
+        let front_matter = renderer.front_matter.take();
+        MarkdownDocument {
+            content: renderer.into_container(),
+            toc,
+            front_matter,
+            headings,
+        }
     }
 }
 
@@ -118,9 +207,21 @@ struct StyledSegment {
     link_url: Option<String>,
 }
 
+/// An image whose alt text is being collected
+struct PendingImage {
+    src: String,
+    title: Option<String>,
+    alt: String,
+}
+
 /// State during rendering
 struct RenderState<'a> {
     config: &'a MarkdownConfig,
+    plugins: &'a Plugins,
+    /// Headings of the whole document, with their anchors
+    headings: &'a [HeadingEntry],
+    /// Index of the next heading to render
+    heading_index: usize,
     /// Root container for all elements
     container: Div,
     /// Stack of elements being built (for nesting)
@@ -147,16 +248,18 @@ struct RenderState<'a> {
     in_table_head: bool,
     /// Current table body row index (for striped rows)
     table_row_index: usize,
-    /// Inside an image tag (to skip alt text)
-    in_image: bool,
+    /// Inside an image tag (alt text is collected, not rendered)
+    image: Option<PendingImage>,
     /// Footnote definitions collected during parsing (label -> content)
     footnote_defs: Vec<(String, Div)>,
     /// Current footnote being defined
     current_footnote: Option<String>,
     /// Footnote definition counter for numbering
     footnote_counter: usize,
-    /// Inside a metadata block (skip content)
-    in_metadata_block: bool,
+    /// Inside a metadata block, and its source so far
+    metadata_block: Option<(MetadataBlockKind, String)>,
+    /// Parsed metadata block
+    front_matter: Option<FrontMatter>,
     /// Inside an HTML block
     in_html_block: bool,
     /// HTML block content accumulator
@@ -180,9 +283,12 @@ enum StackItem {
 }
 
 impl<'a> RenderState<'a> {
-    fn new(config: &'a MarkdownConfig) -> Self {
+    fn new(config: &'a MarkdownConfig, plugins: &'a Plugins, headings: &'a [HeadingEntry]) -> Self {
         Self {
             config,
+            plugins,
+            headings,
+            heading_index: 0,
             container: div().flex_col().gap(config.paragraph_spacing),
             stack: Vec::new(),
             inline_text: String::new(),
@@ -196,11 +302,12 @@ impl<'a> RenderState<'a> {
             list_start: 1,
             in_table_head: false,
             table_row_index: 0,
-            in_image: false,
+            image: None,
             footnote_defs: Vec::new(),
             current_footnote: None,
             footnote_counter: 0,
-            in_metadata_block: false,
+            metadata_block: None,
+            front_matter: None,
             in_html_block: false,
             html_content: String::new(),
         }
@@ -234,15 +341,7 @@ impl<'a> RenderState<'a> {
                 self.stack.push(StackItem::Paragraph);
             }
             Tag::Heading { level, .. } => {
-                let level = match level {
-                    HeadingLevel::H1 => 1,
-                    HeadingLevel::H2 => 2,
-                    HeadingLevel::H3 => 3,
-                    HeadingLevel::H4 => 4,
-                    HeadingLevel::H5 => 5,
-                    HeadingLevel::H6 => 6,
-                };
-                self.stack.push(StackItem::Heading(level));
+                self.stack.push(StackItem::Heading(heading_level(*level)));
             }
             Tag::BlockQuote => {
                 // Note: blockquote() already has proper styling, just wrap in a div for flexibility
@@ -305,11 +404,15 @@ impl<'a> RenderState<'a> {
                 self.inline_style.link_url = Some(dest_url.to_string());
                 self.stack.push(StackItem::Link(dest_url.to_string()));
             }
-            Tag::Image { dest_url, .. } => {
-                // Handle images inline - mark that we're in an image to skip alt text
-                self.in_image = true;
-                let img_elem = img(dest_url.to_string());
-                self.add_to_current_context(img_elem);
+            Tag::Image {
+                dest_url, title, ..
+            } => {
+                // The image is added at its end tag, once the alt text is known
+                self.image = Some(PendingImage {
+                    src: dest_url.to_string(),
+                    title: (!title.is_empty()).then(|| title.to_string()),
+                    alt: String::new(),
+                });
             }
             Tag::Table(_) => {
                 // Create table with styling - use code_bg for background
@@ -355,9 +458,9 @@ impl<'a> RenderState<'a> {
                     footnote_content,
                 ));
             }
-            Tag::MetadataBlock(_kind) => {
-                // Skip metadata blocks (YAML frontmatter) - just mark that we're in one
-                self.in_metadata_block = true;
+            Tag::MetadataBlock(kind) => {
+                // Front matter is collected rather than rendered
+                self.metadata_block = Some((*kind, String::new()));
             }
             Tag::HtmlBlock => {
                 // Start collecting HTML content
@@ -374,19 +477,18 @@ impl<'a> RenderState<'a> {
                 self.stack.pop();
             }
             TagEnd::Heading(level) => {
-                let level = match level {
-                    HeadingLevel::H1 => 1,
-                    HeadingLevel::H2 => 2,
-                    HeadingLevel::H3 => 3,
-                    HeadingLevel::H4 => 4,
-                    HeadingLevel::H5 => 5,
-                    HeadingLevel::H6 => 6,
-                };
-                self.flush_heading(level);
+                self.flush_heading(heading_level(*level));
                 self.stack.pop();
             }
             TagEnd::BlockQuote => {
                 if let Some(StackItem::Blockquote(bq_content)) = self.stack.pop() {
+                    if let Some(renderer) = self.plugins.tag(MarkdownTag::BlockQuote) {
+                        let block = MarkdownBlock::BlockQuote {
+                            content: Box::new(bq_content),
+                        };
+                        self.add_to_current_context(div().child_box(renderer(block, self.config)));
+                        return;
+                    }
                     // Wrap the accumulated content in a blockquote widget with config colors
                     let bq_config = crate::widgets::BlockquoteConfig {
                         border_color: self.config.blockquote_border,
@@ -491,8 +593,9 @@ impl<'a> RenderState<'a> {
                 self.stack.pop(); // Pop the Link stack item
             }
             TagEnd::Image => {
-                // Done with image, stop skipping alt text
-                self.in_image = false;
+                if let Some(image) = self.image.take() {
+                    self.flush_image(image);
+                }
             }
             TagEnd::Table => {
                 // Close tbody if it's on the stack
@@ -562,14 +665,20 @@ impl<'a> RenderState<'a> {
                 self.current_footnote = None;
             }
             TagEnd::MetadataBlock(_) => {
-                // Done skipping metadata block
-                self.in_metadata_block = false;
+                if let Some((kind, raw)) = self.metadata_block.take() {
+                    self.front_matter = Some(FrontMatter::parse(kind, raw));
+                }
             }
             TagEnd::HtmlBlock => {
                 // Render accumulated HTML as preformatted text (basic support)
                 self.in_html_block = false;
                 let html = std::mem::take(&mut self.html_content);
-                if !html.trim().is_empty() {
+                if let Some(renderer) = self.plugins.tag(MarkdownTag::HtmlBlock) {
+                    if !html.trim().is_empty() {
+                        let element = renderer(MarkdownBlock::HtmlBlock { html }, self.config);
+                        self.add_to_current_context(div().child_box(element));
+                    }
+                } else if !html.trim().is_empty() {
                     // Render HTML blocks as preformatted code-like text
                     let html_block = div().bg(self.config.code_bg).rounded(4.0).p(2.0).child(
                         text(&html)
@@ -584,13 +693,15 @@ impl<'a> RenderState<'a> {
     }
 
     fn handle_text(&mut self, text: &str) {
-        // Skip alt text inside images - it's only for accessibility/fallback
-        if self.in_image {
+        // Alt text is passed to image renderers, not rendered inline
+        if let Some(image) = &mut self.image {
+            image.alt.push_str(text);
             return;
         }
 
-        // Skip text inside metadata blocks (YAML frontmatter)
-        if self.in_metadata_block {
+        // Collect front matter source
+        if let Some((_, raw)) = &mut self.metadata_block {
+            raw.push_str(text);
             return;
         }
 
@@ -643,6 +754,12 @@ impl<'a> RenderState<'a> {
     }
 
     fn handle_rule(&mut self) {
+        if let Some(renderer) = self.plugins.tag(MarkdownTag::Rule) {
+            self.add_to_current_context(
+                div().child_box(renderer(MarkdownBlock::Rule, self.config)),
+            );
+            return;
+        }
         // Use config color and minimal margin
         let rule = crate::widgets::hr_with_config(crate::widgets::HrConfig {
             color: self.config.hr_color,
@@ -674,6 +791,17 @@ impl<'a> RenderState<'a> {
             return;
         }
 
+        if self.plugins.math && self.inline_text.contains('$') {
+            self.flush_inline_math();
+            return;
+        }
+
+        let text = std::mem::take(&mut self.inline_text);
+        self.push_segment(text);
+    }
+
+    /// Add a segment in the current inline style
+    fn push_segment(&mut self, text: String) {
         // Determine color and underline based on whether this is a link
         let (color, underline) = if self.inline_style.link_url.is_some() {
             (self.config.link_color, true)
@@ -683,7 +811,7 @@ impl<'a> RenderState<'a> {
 
         // Create a styled segment with the current text and style
         let segment = StyledSegment {
-            text: std::mem::take(&mut self.inline_text),
+            text,
             bold: self.inline_style.bold,
             italic: self.inline_style.italic,
             strikethrough: self.inline_style.strikethrough,
@@ -695,11 +823,47 @@ impl<'a> RenderState<'a> {
         self.styled_segments.push(segment);
     }
 
+    /// Flush inline text containing `$...$` math
+    fn flush_inline_math(&mut self) {
+        let text = std::mem::take(&mut self.inline_text);
+        let in_heading = matches!(self.stack.last(), Some(StackItem::Heading(_)));
+        for part in math::split_inline_math(&text) {
+            match part {
+                InlinePart::Text(part) => {
+                    self.push_segment(part.to_string());
+                }
+                // Headings are plain text, so math is written out in Unicode
+                InlinePart::Math(tex) if in_heading => {
+                    self.push_segment(math::tex_to_unicode(tex));
+                }
+                InlinePart::Math(tex) => {
+                    self.push_segments_to_elements();
+                    let element: Box<dyn ElementBuilder> = match self.plugins.tag(MarkdownTag::Math)
+                    {
+                        Some(renderer) => {
+                            let block = MarkdownBlock::Math {
+                                tex: tex.to_string(),
+                                display: false,
+                            };
+                            renderer(block, self.config)
+                        }
+                        None => Box::new(math::inline_math(tex, self.config)),
+                    };
+                    self.inline_elements.push(element);
+                }
+            }
+        }
+    }
+
     /// Flush styled segments into the element buffer
     fn flush_segments_to_elements(&mut self) {
         // First flush any remaining inline text to segments
         self.flush_inline_text();
+        self.push_segments_to_elements();
+    }
 
+    /// Convert styled segments into elements, leaving inline text alone
+    fn push_segments_to_elements(&mut self) {
         // Convert segments to text elements and add to buffer
         // Use span() for inline text which preserves natural spacing
         let segments = std::mem::take(&mut self.styled_segments);
@@ -710,7 +874,8 @@ impl<'a> RenderState<'a> {
 
             // If this is a link, use the link widget for proper cursor support
             if let Some(url) = &segment.link_url {
-                let link_elem = link(&segment.text, url)
+                let link_elem = self
+                    .link_element(&segment.text, url)
                     .font_size(self.config.body_size)
                     .text_color(segment.color);
                 self.inline_elements.push(Box::new(link_elem));
@@ -765,6 +930,25 @@ impl<'a> RenderState<'a> {
     }
 
     fn flush_paragraph(&mut self) {
+        // Paragraphs that are a single placeholder or `$$...$$` become blocks
+        if self.styled_segments.is_empty() && self.inline_elements.is_empty() {
+            let paragraph = self.inline_text.trim();
+            if paragraph.eq_ignore_ascii_case("[toc]") || paragraph.eq_ignore_ascii_case("[[toc]]")
+            {
+                self.inline_text.clear();
+                let toc = self.build_toc();
+                self.add_to_current_context(toc);
+                return;
+            }
+            if self.plugins.math {
+                if let Some(tex) = math::display_math(paragraph).map(str::to_string) {
+                    self.inline_text.clear();
+                    self.add_display_math(&tex);
+                    return;
+                }
+            }
+        }
+
         // Build inline content from segments and elements
         if let Some(content) = self.build_inline_content() {
             self.add_to_current_context(content);
@@ -772,6 +956,9 @@ impl<'a> RenderState<'a> {
     }
 
     fn flush_heading(&mut self, level: u8) {
+        let entry = self.headings.get(self.heading_index);
+        self.heading_index += 1;
+
         // Flush any remaining inline text first
         self.flush_inline_text();
 
@@ -797,24 +984,141 @@ impl<'a> RenderState<'a> {
             _ => (h6(&text_content), self.config.h6_size),
         };
 
+        // Wrapped so the heading can be scrolled to by its anchor
+        let anchor = entry.map_or_else(|| slug(&text_content), |entry| entry.anchor.clone());
+        if let Some(renderer) = self.plugins.tag(MarkdownTag::Heading) {
+            let block = MarkdownBlock::Heading {
+                level,
+                text: text_content,
+                anchor: anchor.clone(),
+            };
+            let element = renderer(block, self.config);
+            self.add_to_current_context(div().id(anchor).child_box(element));
+            return;
+        }
+
         let heading = heading.size(size).color(self.config.text_color);
-        self.add_to_current_context(heading);
+        self.add_to_current_context(div().id(anchor).child(heading));
     }
 
     fn flush_code_block(&mut self) {
         let content = std::mem::take(&mut self.code_content);
-        let _lang = self.code_language.take();
+        // The info string's first word names the language (`rust` in
+        // "rust,ignore" or "rust title=main.rs")
+        let language = self.code_language.take().map(|info| {
+            info.split([',', ' ', '{'])
+                .next()
+                .unwrap_or_default()
+                .to_string()
+        });
+
+        if let Some(renderer) = language
+            .as_deref()
+            .and_then(|language| self.plugins.languages.get(language))
+        {
+            let element = renderer(&content, self.config);
+            self.add_to_current_context(div().child_box(element));
+            return;
+        }
+        if self.plugins.math && language.as_deref() == Some("math") {
+            self.add_display_math(&content);
+            return;
+        }
+        if let Some(renderer) = self.plugins.tag(MarkdownTag::CodeBlock) {
+            let block = MarkdownBlock::CodeBlock {
+                language,
+                code: content,
+            };
+            self.add_to_current_context(div().child_box(renderer(block, self.config)));
+            return;
+        }
 
-        // Create code block (without syntax highlighting for now)
         // Note: code() returns a Code struct that derefs to Div
         // We can't chain Div methods after Code methods due to Deref ownership rules
-        let code_block = code(&content)
+        let mut code_block = code(&content)
             .line_numbers(true)
             .font_size(self.config.code_size);
 
+        // Highlight with the grammar the language names
+        let highlighter = language
+            .as_deref()
+            .and_then(GrammarHighlighter::for_language);
+        if let Some(highlighter) = highlighter {
+            let theme = GrammarTheme::dark()
+                .text_color(self.config.code_text)
+                .background(self.config.code_bg);
+            code_block = code_block.syntax(SyntaxConfig::new(highlighter.theme(theme)));
+        }
+
         self.add_to_current_context(code_block);
     }
 
+    fn add_display_math(&mut self, tex: &str) {
+        if let Some(renderer) = self.plugins.tag(MarkdownTag::Math) {
+            let block = MarkdownBlock::Math {
+                tex: tex.trim().to_string(),
+                display: true,
+            };
+            self.add_to_current_context(div().child_box(renderer(block, self.config)));
+        } else {
+            let block = math::display_math_block(tex.trim(), self.config);
+            self.add_to_current_context(block);
+        }
+    }
+
+    fn flush_image(&mut self, image: PendingImage) {
+        let src = self
+            .plugins
+            .image_resolver
+            .as_ref()
+            .and_then(|resolve| resolve(&image.src))
+            .unwrap_or(image.src);
+
+        if let Some(renderer) = self.plugins.tag(MarkdownTag::Image) {
+            let block = MarkdownBlock::Image {
+                src,
+                alt: image.alt,
+                title: image.title,
+            };
+            self.add_to_current_context(div().child_box(renderer(block, self.config)));
+        } else {
+            self.add_to_current_context(img(src));
+        }
+    }
+
+    /// A link wired to the renderer's click handling
+    fn link_element(&self, label: &str, url: &str) -> Link {
+        let element = link(label, url);
+        if let (Some(anchor), Some(scroll_ref)) = (url.strip_prefix('#'), &self.plugins.scroll_ref)
+        {
+            let anchor = anchor.to_string();
+            let scroll_ref = scroll_ref.clone();
+            return element.on_click(move |_url, _ctx| scroll_ref.scroll_to(&anchor));
+        }
+        match &self.plugins.on_link_click {
+            Some(handler) => {
+                let handler = handler.clone();
+                element.on_click(move |url, ctx| handler(url, ctx))
+            }
+            None => element,
+        }
+    }
+
+    /// Links to every heading, indented by level
+    fn build_toc(&self) -> Div {
+        let top_level = self.headings.iter().map(|h| h.level).min().unwrap_or(1);
+        let mut toc = div().flex_col().gap(self.config.list_item_spacing);
+        for heading in self.headings {
+            let entry = self
+                .link_element(&heading.text, &format!("#{}", heading.anchor))
+                .font_size(self.config.body_size)
+                .text_color(self.config.link_color);
+            let indent = (heading.level - top_level) as f32 * self.config.list_indent;
+            toc = toc.child(div().ml(indent).child(entry));
+        }
+        toc
+    }
+
     fn add_to_current_context(&mut self, element: impl ElementBuilder + 'static) {
         // Find the appropriate parent to add to
         for item in self.stack.iter_mut().rev() {
@@ -848,9 +1152,10 @@ impl<'a> RenderState<'a> {
         // Flush any pending text first
         self.flush_segments_to_elements();
 
-        // Render as a link-styled element with pointer cursor
-        // TODO: In the future, this could scroll to the footnote definition
-        let footnote_ref = link(format!("[{}]", label), format!("#footnote-{}", label))
+        // Render as a link-styled element with pointer cursor; with a scroll
+        // ref it scrolls to the definition
+        let footnote_ref = self
+            .link_element(&format!("[{}]", label), &format!("#footnote-{}", label))
             .font_size(self.config.body_size * 0.75) // Smaller, superscript-like
             .text_color(self.config.link_color);
 
@@ -858,6 +1163,12 @@ impl<'a> RenderState<'a> {
     }
 
     fn handle_html(&mut self, html: &str) {
+        // A custom HTML renderer gets the whole block at its end
+        if self.in_html_block && self.plugins.tag(MarkdownTag::HtmlBlock).is_some() {
+            self.html_content.push_str(html);
+            return;
+        }
+
         // Block-level HTML - try to parse known tags, otherwise render as preformatted
         let trimmed = html.trim();
         if trimmed.is_empty() {
@@ -1075,6 +1386,7 @@ impl<'a> RenderState<'a> {
             for (_i, (label, content)) in self.footnote_defs.into_iter().enumerate() {
                 // Create footnote row: number + content
                 let footnote_row = div()
+                    .id(format!("footnote-{}", label))
                     .flex_row()
                     .gap(8.0)
                     .items_start()
@@ -1161,7 +1473,8 @@ mod tests {
         }
 
         let config = super::super::config::MarkdownConfig::default();
-        let mut renderer = RenderState::new(&config);
+        let plugins = Plugins::default();
+        let mut renderer = RenderState::new(&config, &plugins, &[]);
 
         // Process events one by one and trace what happens
         for (i, event) in events.iter().enumerate() {
@@ -1200,7 +1513,8 @@ mod tests {
         }
 
         let config = super::super::config::MarkdownConfig::default();
-        let mut renderer = RenderState::new(&config);
+        let plugins = Plugins::default();
+        let mut renderer = RenderState::new(&config, &plugins, &[]);
 
         // Process events one by one and trace what happens
         for (i, event) in events.iter().enumerate() {
@@ -1245,6 +1559,20 @@ mod tests {
         assert!(tree.len() > 0);
     }
 
+    #[test]
+    fn test_code_block_highlights_info_string_language() {
+        init_theme();
+        let node_count = |source: &str| {
+            let mut tree = LayoutTree::new();
+            markdown(source).build(&mut tree);
+            tree.len()
+        };
+        let plain = node_count("```unknown\nfn main() {}\n```");
+        // Each highlighted token is its own text span
+        assert!(node_count("```rust\nfn main() {}\n```") > plain);
+        assert!(node_count("```rust,ignore\nfn main() {}\n```") > plain);
+    }
+
     #[test]
     fn test_blockquote() {
         init_theme();
@@ -1556,4 +1884,86 @@ Another paragraph."#;
         assert_eq!(content_p, Some("Block-level HTML paragraphs."));
         assert_eq!(content_bq, Some("HTML blockquote content."));
     }
+
+    #[test]
+    fn test_render_document_front_matter_and_headings() {
+        init_theme();
+        let md = "---\ntitle: Guide\n---\n\n# Intro\n\n[TOC]\n\n## Setup\n\n## Setup\n";
+        let doc = MarkdownRenderer::new().render_document(md);
+
+        let front_matter = doc.front_matter.expect("front matter");
+        assert_eq!(front_matter.get("title"), Some("Guide"));
+
+        let anchors: Vec<_> = doc.headings.iter().map(|h| h.anchor.as_str()).collect();
+        assert_eq!(anchors, vec!["intro", "setup", "setup-1"]);
+
+        let mut tree = LayoutTree::new();
+        doc.toc.build(&mut tree);
+        assert!(tree.len() > 3, "TOC should have a row per heading");
+    }
+
+    #[test]
+    fn test_plugins_are_called() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::sync::Mutex;
+
+        init_theme();
+        let charts = Arc::new(AtomicUsize::new(0));
+        let images = Arc::new(Mutex::new(Vec::new()));
+        let charts_seen = charts.clone();
+        let images_seen = images.clone();
+        let renderer = MarkdownRenderer::new()
+            .code_renderer("chart", move |source, _| {
+                assert_eq!(source, "a: 1\n");
+                charts_seen.fetch_add(1, Ordering::SeqCst);
+                Box::new(div())
+            })
+            .image_resolver(|src| Some(format!("docs/{}", src)))
+            .tag_renderer(MarkdownTag::Image, move |block, _| {
+                if let MarkdownBlock::Image { src, alt, .. } = block {
+                    images_seen.lock().unwrap().push((src, alt));
+                }
+                Box::new(div())
+            });
+
+        renderer.render("```chart {height=200}\na: 1\n```\n\n![A diagram](diagram.png)\n");
+        assert_eq!(charts.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            *images.lock().unwrap(),
+            vec![("docs/diagram.png".to_string(), "A diagram".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_math_is_opt_in() {
+        init_theme();
+        let seen = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen_math = seen.clone();
+        let md = "Area is $\\pi r^2$.\n\n$$ E = mc^2 $$\n\n```math\na^2 + b^2\n```\n";
+        let with_math = |enabled: bool| {
+            let seen_math = seen_math.clone();
+            MarkdownRenderer::new()
+                .math(enabled)
+                .tag_renderer(MarkdownTag::Math, move |block, _| {
+                    if let MarkdownBlock::Math { tex, display } = block {
+                        seen_math.lock().unwrap().push((tex, display));
+                    }
+                    Box::new(div())
+                })
+                .render(md)
+        };
+
+        with_math(false);
+        assert!(seen.lock().unwrap().is_empty());
+
+        with_math(true);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec![
+                ("\\pi r^2".to_string(), false),
+                ("E = mc^2".to_string(), true),
+                ("a^2 + b^2".to_string(), true),
+            ]
+        );
+    }
 }
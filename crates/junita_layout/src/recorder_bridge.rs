@@ -183,6 +183,7 @@ pub struct SnapshotVisualProps {
 #[derive(Clone, Debug)]
 pub struct ElementSnapshotData {
     pub id: String,
+    pub stable_id: Option<String>,
     pub element_type: String,
     pub bounds: SnapshotRect,
     pub is_visible: bool,
@@ -204,6 +205,8 @@ pub struct TreeSnapshotData {
     pub hovered_element: Option<String>,
     pub window_size: (u32, u32),
     pub scale_factor: f64,
+    /// Regions repainted this frame (logical pixels)
+    pub dirty_regions: Vec<SnapshotRect>,
 }
 
 impl TreeSnapshotData {
@@ -216,8 +219,30 @@ impl TreeSnapshotData {
             hovered_element: None,
             window_size,
             scale_factor,
+            dirty_regions: Vec::new(),
         }
     }
+
+    /// Attach the frame's damage as dirty regions.
+    ///
+    /// A full-frame damage is recorded as a single rect covering `viewport`
+    /// (logical pixels).
+    pub fn with_damage(
+        mut self,
+        damage: &crate::damage::DamageRegion,
+        viewport: (f32, f32),
+    ) -> Self {
+        self.dirty_regions = if damage.is_full() {
+            vec![SnapshotRect::new(0.0, 0.0, viewport.0, viewport.1)]
+        } else {
+            damage
+                .rects()
+                .iter()
+                .map(|r| SnapshotRect::new(r.x(), r.y(), r.width(), r.height()))
+                .collect()
+        };
+        self
+    }
 }
 
 /// Send a tree snapshot to the recorder if recording is enabled.
@@ -323,6 +348,7 @@ fn capture_node_recursive(
 
     let elem = ElementSnapshotData {
         id: node_id_str.clone(),
+        stable_id: tree.element_registry().get_id(node),
         element_type,
         bounds,
         is_visible: true, // Could check opacity/display later
@@ -354,3 +380,407 @@ fn capture_node_recursive(
         );
     }
 }
+
+// ============================================================================
+// Live Inspection
+// ============================================================================
+
+/// The element ID used for a node in tree snapshots.
+pub fn element_id(node: crate::tree::LayoutNodeId) -> String {
+    format!("{:?}", node)
+}
+
+/// Find the node for an element ID.
+///
+/// Accepts both snapshot IDs (see [`element_id`]) and user-assigned IDs
+/// registered with `.id()`.
+pub fn find_node(
+    tree: &crate::renderer::RenderTree,
+    id: &str,
+) -> Option<crate::tree::LayoutNodeId> {
+    if let Some(node) = tree.query_by_id(id) {
+        return Some(node);
+    }
+
+    let mut stack: Vec<_> = tree.root().into_iter().collect();
+    while let Some(node) = stack.pop() {
+        if element_id(node) == id {
+            return Some(node);
+        }
+        stack.extend(tree.layout().children(node));
+    }
+    None
+}
+
+/// Element ID of the topmost element under a point, for picking.
+pub fn element_at(
+    tree: &crate::renderer::RenderTree,
+    router: &crate::event_router::EventRouter,
+    x: f32,
+    y: f32,
+) -> Option<String> {
+    router.hit_test(tree, x, y).map(|hit| element_id(hit.node))
+}
+
+/// Bounds of a node in window coordinates, accounting for scroll offsets.
+pub fn absolute_bounds(
+    tree: &crate::renderer::RenderTree,
+    node: crate::tree::LayoutNodeId,
+) -> Option<SnapshotRect> {
+    fn visit(
+        tree: &crate::renderer::RenderTree,
+        current: crate::tree::LayoutNodeId,
+        target: crate::tree::LayoutNodeId,
+        offset: (f32, f32),
+    ) -> Option<SnapshotRect> {
+        let bounds = tree.layout().get_bounds(current, offset)?;
+        if current == target {
+            return Some(SnapshotRect::new(
+                bounds.x,
+                bounds.y,
+                bounds.width,
+                bounds.height,
+            ));
+        }
+        let scroll = tree.get_scroll_offset(current);
+        let child_offset = (bounds.x + scroll.0, bounds.y + scroll.1);
+        tree.layout()
+            .children(current)
+            .into_iter()
+            .find_map(|child| visit(tree, child, target, child_offset))
+    }
+
+    visit(tree, tree.root()?, node, (0.0, 0.0))
+}
+
+/// Computed style of a node, as CSS property/value pairs.
+///
+/// Covers the visual props and the layout style (size, padding, margin,
+/// flex and gap), see [`style_values`](crate::style_origin::style_values).
+pub fn computed_style(
+    tree: &crate::renderer::RenderTree,
+    node: crate::tree::LayoutNodeId,
+) -> Option<std::collections::BTreeMap<String, String>> {
+    let props = &tree.get_render_node(node)?.props;
+    let layout = tree.layout().get_style(node);
+    let values = crate::style_origin::style_values(props, layout.as_ref());
+    Some(
+        values
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect(),
+    )
+}
+
+/// Where each computed style value of a node came from.
+///
+/// Keys match [`computed_style`]. Values are the origins the tree recorded
+/// when the styles were applied: `builder`, a theme token such as
+/// `theme(space-4)`, a stylesheet rule such as `#card:hover`, a live rule or
+/// edit, or `default` when nothing set the value.
+pub fn style_sources(
+    tree: &crate::renderer::RenderTree,
+    node: crate::tree::LayoutNodeId,
+    edits: &LiveEdits,
+) -> Option<std::collections::BTreeMap<String, String>> {
+    use crate::style_origin::StyleOrigin;
+
+    let origins = tree.style_origins(node);
+    let sources = computed_style(tree, node)?
+        .into_keys()
+        .map(|name| {
+            let source = match origins.get(name.as_str()) {
+                Some(StyleOrigin::Rule(key)) if edits.rules.ids().any(|rule| rule == key) => {
+                    format!("live rule #{}", key)
+                }
+                Some(origin) => origin.to_string(),
+                None => "default".to_string(),
+            };
+            (name, source)
+        })
+        .collect();
+    Some(sources)
+}
+
+/// Box model and layout style of a node.
+#[derive(Clone, Debug)]
+pub struct LayoutInfoData {
+    /// Border box in window coordinates.
+    pub bounds: SnapshotRect,
+    /// Size of the content box.
+    pub content_size: (f32, f32),
+    /// Padding as `[top, right, bottom, left]`.
+    pub padding: [f32; 4],
+    /// Border widths as `[top, right, bottom, left]`.
+    pub border: [f32; 4],
+    /// Margins as `[top, right, bottom, left]`.
+    pub margin: [f32; 4],
+    /// Taffy style inputs, keyed by CSS property name.
+    pub style: std::collections::BTreeMap<String, String>,
+}
+
+/// Layout details of a node from the Taffy tree.
+pub fn layout_info(
+    tree: &crate::renderer::RenderTree,
+    node: crate::tree::LayoutNodeId,
+) -> Option<LayoutInfoData> {
+    let layout = tree.layout().get_layout(node)?;
+    let taffy_style = tree.layout().get_style(node)?;
+    let edges = |r: taffy::Rect<f32>| [r.top, r.right, r.bottom, r.left];
+
+    let mut style = std::collections::BTreeMap::new();
+    let mut set = |name: &str, value: String| {
+        style.insert(name.to_string(), value);
+    };
+    set("display", format!("{:?}", taffy_style.display));
+    set("position", format!("{:?}", taffy_style.position));
+    set(
+        "flex-direction",
+        format!("{:?}", taffy_style.flex_direction),
+    );
+    set("flex-wrap", format!("{:?}", taffy_style.flex_wrap));
+    set("flex-grow", taffy_style.flex_grow.to_string());
+    set("flex-shrink", taffy_style.flex_shrink.to_string());
+    set("flex-basis", format!("{:?}", taffy_style.flex_basis));
+    set("align-items", format!("{:?}", taffy_style.align_items));
+    set(
+        "justify-content",
+        format!("{:?}", taffy_style.justify_content),
+    );
+    set("gap", format!("{:?}", taffy_style.gap));
+    set("width", format!("{:?}", taffy_style.size.width));
+    set("height", format!("{:?}", taffy_style.size.height));
+    set("min-width", format!("{:?}", taffy_style.min_size.width));
+    set("min-height", format!("{:?}", taffy_style.min_size.height));
+    set("max-width", format!("{:?}", taffy_style.max_size.width));
+    set("max-height", format!("{:?}", taffy_style.max_size.height));
+    set("overflow", format!("{:?}", taffy_style.overflow));
+
+    Some(LayoutInfoData {
+        bounds: absolute_bounds(tree, node)?,
+        content_size: (
+            (layout.size.width
+                - layout.padding.left
+                - layout.padding.right
+                - layout.border.left
+                - layout.border.right)
+                .max(0.0),
+            (layout.size.height
+                - layout.padding.top
+                - layout.padding.bottom
+                - layout.border.top
+                - layout.border.bottom)
+                .max(0.0),
+        ),
+        padding: edges(layout.padding),
+        border: edges(layout.border),
+        margin: edges(layout.margin),
+        style,
+    })
+}
+
+/// Style edits made from a live debugging session.
+///
+/// Edits are kept outside the render tree so they survive rebuilds; call
+/// [`LiveEdits::apply`] after each rebuild (or every frame) to reapply them.
+#[derive(Clone, Debug, Default)]
+pub struct LiveEdits {
+    /// Per-element overrides, keyed by element ID.
+    element_styles: std::collections::HashMap<String, crate::element_style::ElementStyle>,
+    /// Rules added to the stylesheet.
+    rules: crate::css_parser::Stylesheet,
+}
+
+impl LiveEdits {
+    /// Create an empty set of edits.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Check if there are no edits.
+    pub fn is_empty(&self) -> bool {
+        self.element_styles.is_empty() && self.rules.is_empty()
+    }
+
+    /// Override a single property on an element.
+    ///
+    /// `property` and `value` use CSS syntax, e.g. `("opacity", "0.5")`.
+    pub fn set_visual_prop(
+        &mut self,
+        element_id: &str,
+        property: &str,
+        value: &str,
+    ) -> Result<(), String> {
+        let sheet = parse_live_css(&format!("#live {{ {}: {}; }}", property, value))?;
+        let style = sheet
+            .get("live")
+            .ok_or_else(|| format!("Unsupported property: {}", property))?;
+        let entry = self
+            .element_styles
+            .entry(element_id.to_string())
+            .or_default();
+        *entry = entry.merge(style);
+        Ok(())
+    }
+
+    /// Add or replace a stylesheet rule, e.g. `("#card:hover", "opacity: 0.8;")`.
+    pub fn set_css_rule(&mut self, selector: &str, declarations: &str) -> Result<(), String> {
+        let sheet = parse_live_css(&format!("{} {{ {} }}", selector, declarations))?;
+        if sheet.is_empty() {
+            return Err(format!("Unsupported selector: {}", selector));
+        }
+        self.rules.extend(sheet);
+        Ok(())
+    }
+
+    /// Apply the edits to a render tree.
+    pub fn apply(&self, tree: &mut crate::renderer::RenderTree) {
+        if !self.rules.is_empty() {
+            // State rules (`:hover` etc.) are picked up through the tree's stylesheet
+            let mut stylesheet = tree.stylesheet().cloned().unwrap_or_default();
+            stylesheet.extend(self.rules.clone());
+            tree.set_stylesheet(stylesheet);
+
+            for id in self.rules.ids().filter(|id| !id.contains(':')) {
+                if let (Some(node), Some(style)) = (tree.query_by_id(id), self.rules.get(id)) {
+                    tree.update_render_props(node, |props| {
+                        crate::renderer::RenderTree::apply_element_style_to_props(props, style)
+                    });
+                    tree.style_origins_mut(node).edits.extend(
+                        crate::style_origin::element_style_origins(
+                            style,
+                            crate::style_origin::StyleOrigin::Rule(id.to_string()),
+                        ),
+                    );
+                }
+            }
+        }
+
+        for (id, style) in &self.element_styles {
+            if let Some(node) = find_node(tree, id) {
+                tree.update_render_props(node, |props| {
+                    crate::renderer::RenderTree::apply_element_style_to_props(props, style)
+                });
+                tree.style_origins_mut(node).edits.extend(
+                    crate::style_origin::element_style_origins(
+                        style,
+                        crate::style_origin::StyleOrigin::LiveEdit,
+                    ),
+                );
+            }
+        }
+    }
+}
+
+fn parse_live_css(css: &str) -> Result<crate::css_parser::Stylesheet, String> {
+    let result = crate::css_parser::Stylesheet::parse_with_errors(css);
+    match result.errors.first() {
+        Some(error) => Err(error.message.clone()),
+        None => Ok(result.stylesheet),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::div::div;
+    use crate::renderer::RenderTree;
+
+    fn test_tree() -> RenderTree {
+        let ui = div()
+            .w(200.0)
+            .h(200.0)
+            .child(div().p_px(10.0).child(div().id("card").w(50.0).h(40.0)));
+        let mut tree = RenderTree::from_element(&ui);
+        tree.compute_layout(200.0, 200.0);
+        tree
+    }
+
+    #[test]
+    fn test_find_node_and_absolute_bounds() {
+        let tree = test_tree();
+        let card = find_node(&tree, "card").unwrap();
+        assert_eq!(find_node(&tree, &element_id(card)), Some(card));
+
+        let bounds = absolute_bounds(&tree, card).unwrap();
+        assert_eq!((bounds.x, bounds.y), (10.0, 10.0));
+        assert_eq!((bounds.width, bounds.height), (50.0, 40.0));
+
+        let info = layout_info(&tree, card).unwrap();
+        assert_eq!(info.content_size, (50.0, 40.0));
+        assert!(info.style.contains_key("flex-direction"));
+    }
+
+    #[test]
+    fn test_live_edits_survive_rebuild() {
+        let mut edits = LiveEdits::new();
+        edits.set_visual_prop("card", "opacity", "0.5").unwrap();
+        assert!(edits.set_visual_prop("card", "opacity", "[").is_err());
+        edits.set_css_rule("#card", "border-radius: 6px;").unwrap();
+
+        // A rebuilt tree starts from scratch; reapplying restores the edits
+        let mut tree = test_tree();
+        edits.apply(&mut tree);
+        let card = find_node(&tree, "card").unwrap();
+        let style = computed_style(&tree, card).unwrap();
+        assert_eq!(style["opacity"], "0.5");
+        assert_eq!(style["border-radius"], "6px");
+        assert!(tree.stylesheet().unwrap().contains("card"));
+    }
+
+    #[test]
+    fn test_style_sources() {
+        junita_theme::ThemeState::init_default();
+        let ui = div().w(200.0).h(200.0).child(
+            div()
+                .id("card")
+                .w(50.0)
+                .h(40.0)
+                .p_4()
+                .m_2()
+                .m_px(3.0)
+                .bg(junita_core::Color::WHITE)
+                .opacity(0.8),
+        );
+        let mut tree = RenderTree::from_element(&ui);
+        tree.set_stylesheet(
+            crate::css_parser::Stylesheet::parse_with_errors(
+                "#card { border-radius: 4px; } #card:hover { background: #ff0000; }",
+            )
+            .stylesheet,
+        );
+        let card = find_node(&tree, "card").unwrap();
+        tree.apply_state_styles(card, true, false, false);
+
+        let mut edits = LiveEdits::new();
+        edits.set_visual_prop("card", "opacity", "0.5").unwrap();
+        edits.apply(&mut tree);
+
+        let sources = style_sources(&tree, card, &edits).unwrap();
+        assert_eq!(sources["background"], "#card:hover");
+        assert_eq!(sources["border-radius"], "#card");
+        assert_eq!(sources["opacity"], "live edit");
+        assert_eq!(sources["render-layer"], "default");
+        assert_eq!(sources["width"], "builder");
+        assert_eq!(sources["padding"], "theme(space-4)");
+        // A builder call after the theme token wins
+        assert_eq!(sources["margin"], "builder");
+        assert_eq!(sources["flex-grow"], "default");
+        assert_eq!(
+            sources.keys().collect::<Vec<_>>(),
+            computed_style(&tree, card)
+                .unwrap()
+                .keys()
+                .collect::<Vec<_>>()
+        );
+
+        // Leaving the hover state drops the rule's credit
+        tree.apply_state_styles(card, false, false, false);
+        let sources = style_sources(&tree, card, &edits).unwrap();
+        assert_eq!(sources["background"], "builder");
+
+        let style = computed_style(&tree, card).unwrap();
+        assert_eq!(style["width"], "50px");
+        assert_eq!(style["margin"], "3px");
+    }
+}
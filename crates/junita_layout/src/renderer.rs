@@ -5,11 +5,13 @@
 
 use std::any::Any;
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, Weak};
 
 use indexmap::IndexMap;
 use junita_animation::AnimationScheduler;
 
+use junita_core::profiler;
 use junita_core::{
     BlendMode, Brush, ClipShape, Color, CornerRadius, DrawContext, GlassStyle, LayerConfig, Rect,
     Shadow, Stroke, Transform,
@@ -18,7 +20,7 @@ use taffy::prelude::*;
 
 use crate::canvas::CanvasData;
 use crate::css_parser::{ElementState, Stylesheet};
-use crate::diff::{render_props_eq, ChangeCategory, DivHash};
+use crate::diff::{render_props_eq, ChangeCategory, ChangeGenerations, DivHash};
 use crate::div::{ElementBuilder, ElementTypeId};
 use crate::element::{ElementBounds, GlassMaterial, Material, RenderLayer, RenderProps};
 use crate::layout_animation::{LayoutAnimationConfig, LayoutAnimationState};
@@ -223,6 +225,8 @@ pub struct ImageData {
     pub placeholder_type: u8,
     /// Placeholder color [r, g, b, a]
     pub placeholder_color: [f32; 4],
+    /// Playback control for animated images (`None` = autoplay)
+    pub playback: Option<crate::image::ImagePlayback>,
 }
 
 /// Node data for rendering
@@ -369,6 +373,12 @@ pub struct RenderTree {
     /// Per-node hashes for incremental change detection
     /// Maps node_id to (own_hash, tree_hash) - own excludes children, tree includes children
     node_hashes: HashMap<LayoutNodeId, (DivHash, DivHash)>,
+    /// Identity of the built node set, renewed whenever every node is rebuilt
+    tree_id: u64,
+    /// Advanced with every recorded node change
+    change_generation: u64,
+    /// When each change category last applied to a node
+    node_changes: HashMap<LayoutNodeId, ChangeGenerations>,
     /// Layout bounds storages to update after layout computation
     /// Maps node_id to entry with shared storage and optional change callback
     layout_bounds_storages: HashMap<LayoutNodeId, LayoutBoundsEntry>,
@@ -389,6 +399,9 @@ pub struct RenderTree {
     /// Base styles for elements (before state modifiers)
     /// Used to restore original styles when state changes
     base_styles: HashMap<LayoutNodeId, RenderProps>,
+    /// Where each node's style values came from, recorded as styles are
+    /// applied (only with the `recorder` feature)
+    style_origins: HashMap<LayoutNodeId, crate::style_origin::NodeStyleOrigins>,
     /// Layout animation configs for nodes (from element builders)
     /// Maps node_id to the LayoutAnimationConfig specifying which properties to animate
     layout_animation_configs: HashMap<LayoutNodeId, LayoutAnimationConfig>,
@@ -425,6 +438,17 @@ pub struct RenderTree {
     /// Pre-computed animated render bounds for this frame
     /// Calculated after layout, used during rendering
     animated_render_bounds: HashMap<LayoutNodeId, AnimatedRenderBounds>,
+    /// Cache layers automatically for animated nodes with static subtrees
+    auto_layer_cache: bool,
+    /// Invalidation counters for manually cached layers
+    layer_cache_generations: HashMap<LayoutNodeId, u64>,
+}
+
+/// Source of [`RenderTree::tree_id`] values
+static NEXT_TREE_ID: AtomicU64 = AtomicU64::new(1);
+
+fn next_tree_id() -> u64 {
+    NEXT_TREE_ID.fetch_add(1, Ordering::Relaxed)
 }
 
 /// Result of an incremental update attempt
@@ -464,6 +488,9 @@ impl RenderTree {
             animations: Weak::new(),
             tree_hash: None,
             node_hashes: HashMap::new(),
+            tree_id: next_tree_id(),
+            change_generation: 0,
+            node_changes: HashMap::new(),
             layout_bounds_storages: HashMap::new(),
             element_registry: Arc::new(ElementRegistry::new()),
             scroll_refs: HashMap::new(),
@@ -471,6 +498,7 @@ impl RenderTree {
             on_ready_callbacks: HashMap::new(),
             stylesheet: None,
             base_styles: HashMap::new(),
+            style_origins: HashMap::new(),
             layout_animation_configs: HashMap::new(),
             layout_animations: HashMap::new(),
             previous_bounds: HashMap::new(),
@@ -483,6 +511,8 @@ impl RenderTree {
             visual_animations: HashMap::new(),
             previous_visual_bounds: HashMap::new(),
             animated_render_bounds: HashMap::new(),
+            auto_layer_cache: false,
+            layer_cache_generations: HashMap::new(),
         }
     }
 
@@ -507,6 +537,7 @@ impl RenderTree {
 
     /// Build a render tree from an element builder
     pub fn from_element<E: ElementBuilder>(element: &E) -> Self {
+        let _scope = profiler::scope("build_tree");
         let mut tree = Self::new();
         // Compute tree hash for change detection
         tree.tree_hash = Some(DivHash::compute_element_tree(element));
@@ -522,6 +553,7 @@ impl RenderTree {
         element: &E,
         registry: Arc<ElementRegistry>,
     ) -> Self {
+        let _scope = profiler::scope("build_tree");
         let mut tree = Self::new();
         // Clear the shared registry before building to avoid duplicate ID warnings
         registry.clear();
@@ -566,6 +598,9 @@ impl RenderTree {
 
         // Clear existing data that will be repopulated during rebuild
         self.render_nodes.clear();
+        self.style_origins.clear();
+        self.node_changes.clear();
+        self.tree_id = next_tree_id();
         self.handler_registry = crate::event_handler::HandlerRegistry::new();
         self.element_registry.clear();
         // Clear scroll_refs HashMap (node_id keyed) - it will be repopulated during rebuild
@@ -596,6 +631,7 @@ impl RenderTree {
     /// - LayoutChanged: call compute_layout(), then render
     /// - ChildrenChanged: call compute_layout(), then render
     pub fn incremental_update<E: ElementBuilder>(&mut self, element: &E) -> UpdateResult {
+        let _scope = profiler::scope("diff");
         let new_tree_hash = DivHash::compute_element_tree(element);
 
         // Quick path: if tree hash matches, nothing changed
@@ -743,6 +779,13 @@ impl RenderTree {
             self.layout_tree.add_child(parent_id, child_id);
             self.collect_render_props_boxed(child.as_ref(), child_id);
         }
+        self.record_change(
+            parent_id,
+            ChangeCategory {
+                children: true,
+                ..ChangeCategory::none()
+            },
+        );
     }
 
     /// Analyze what categories of changes occurred between stored tree and new element
@@ -754,7 +797,7 @@ impl RenderTree {
         let mut changes = ChangeCategory::none();
 
         // Get stored hash for this node
-        let Some(&(stored_own_hash, stored_tree_hash)) = self.node_hashes.get(&node_id) else {
+        let Some(&(_, stored_tree_hash)) = self.node_hashes.get(&node_id) else {
             // No stored hash - treat as everything changed
             changes.layout = true;
             changes.visual = true;
@@ -762,8 +805,7 @@ impl RenderTree {
             return changes;
         };
 
-        // Compute new hashes
-        let new_own_hash = DivHash::compute_element(element);
+        // Compute new hash
         let new_tree_hash = DivHash::compute_element_tree(element);
 
         // If tree hashes match, nothing changed in this subtree
@@ -771,30 +813,8 @@ impl RenderTree {
             return changes;
         }
 
-        // Tree hash differs - analyze further
-        if stored_own_hash != new_own_hash {
-            // This node's own properties changed
-            // Check render props to distinguish visual vs layout
-            if let Some(old_render_node) = self.render_nodes.get(&node_id) {
-                let new_props = element.render_props();
-                let old_props = &old_render_node.props;
-
-                // Visual change detection: compare render-only properties
-                if !Self::props_visually_equal(old_props, &new_props) {
-                    changes.visual = true;
-                }
-
-                // Layout change: if hash differs but not just visual, assume layout changed
-                // (We can't access Style directly from ElementBuilder, so we infer)
-                if !changes.visual {
-                    changes.layout = true;
-                }
-            } else {
-                // No old render node - everything changed
-                changes.layout = true;
-                changes.visual = true;
-            }
-        }
+        // Tree hash differs - check whether this node's own properties changed
+        changes = self.own_changes(element, node_id);
 
         // Check children
         let child_node_ids = self.layout_tree.children(node_id);
@@ -831,36 +851,20 @@ impl RenderTree {
     ) -> ChangeCategory {
         let mut changes = ChangeCategory::none();
 
-        let Some(&(stored_own_hash, stored_tree_hash)) = self.node_hashes.get(&node_id) else {
+        let Some(&(_, stored_tree_hash)) = self.node_hashes.get(&node_id) else {
             changes.layout = true;
             changes.visual = true;
             changes.children = true;
             return changes;
         };
 
-        let new_own_hash = DivHash::compute_element(element);
         let new_tree_hash = DivHash::compute_element_tree(element);
 
         if stored_tree_hash == new_tree_hash {
             return changes;
         }
 
-        if stored_own_hash != new_own_hash {
-            if let Some(old_render_node) = self.render_nodes.get(&node_id) {
-                let new_props = element.render_props();
-                let old_props = &old_render_node.props;
-
-                if !Self::props_visually_equal(old_props, &new_props) {
-                    changes.visual = true;
-                }
-                if !changes.visual {
-                    changes.layout = true;
-                }
-            } else {
-                changes.layout = true;
-                changes.visual = true;
-            }
-        }
+        changes = self.own_changes(element, node_id);
 
         let child_node_ids = self.layout_tree.children(node_id);
         let child_builders = element.children_builders();
@@ -885,6 +889,95 @@ impl RenderTree {
         changes
     }
 
+    /// Categories in which a node's own properties differ from `element`
+    ///
+    /// Children are not compared. A node without a stored hash or render
+    /// node counts as changed in layout and visuals.
+    fn own_changes(&self, element: &dyn ElementBuilder, node_id: LayoutNodeId) -> ChangeCategory {
+        let mut changes = ChangeCategory::none();
+        let Some(&(stored_own_hash, _)) = self.node_hashes.get(&node_id) else {
+            changes.layout = true;
+            changes.visual = true;
+            return changes;
+        };
+        if stored_own_hash == DivHash::compute_element(element) {
+            return changes;
+        }
+
+        if let Some(old_render_node) = self.render_nodes.get(&node_id) {
+            // Visual change detection: compare render-only properties
+            if !Self::props_visually_equal(&old_render_node.props, &element.render_props()) {
+                changes.visual = true;
+            } else {
+                // Layout change: if hash differs but not just visual, assume layout changed
+                // (We can't access Style directly from ElementBuilder, so we infer)
+                changes.layout = true;
+            }
+        } else {
+            // No old render node - everything changed
+            changes.layout = true;
+            changes.visual = true;
+        }
+        changes
+    }
+
+    /// Record that a node changed, for consumers polling [`Self::changes_since`]
+    fn record_change(&mut self, node_id: LayoutNodeId, changes: ChangeCategory) {
+        if !changes.any() {
+            return;
+        }
+        self.change_generation += 1;
+        self.node_changes
+            .entry(node_id)
+            .or_default()
+            .record(changes, self.change_generation);
+    }
+
+    /// Record a visual change if a node's props no longer equal `old`
+    fn record_props_change(&mut self, node_id: LayoutNodeId, old: &RenderProps) {
+        let changed = self
+            .render_nodes
+            .get(&node_id)
+            .is_some_and(|node| !render_props_eq(old, &node.props));
+        if changed {
+            self.record_change(
+                node_id,
+                ChangeCategory {
+                    visual: true,
+                    ..ChangeCategory::none()
+                },
+            );
+        }
+    }
+
+    /// Identity of this tree's node set
+    ///
+    /// Renewed whenever every node is rebuilt, so node ids from before are
+    /// meaningless and anything keyed by them has to start over.
+    pub fn tree_id(&self) -> u64 {
+        self.tree_id
+    }
+
+    /// Counter that advances with every node change the tree records
+    ///
+    /// Pass a value read here to [`Self::changes_since`] on a later frame.
+    pub fn change_generation(&self) -> u64 {
+        self.change_generation
+    }
+
+    /// Categories a node changed in after `generation`
+    ///
+    /// Changes are recorded by incremental updates, subtree rebuilds,
+    /// stylesheet state styles and [`Self::update_render_props`]. Nodes
+    /// built after `generation` report no changes; compare node sets to
+    /// find them.
+    pub fn changes_since(&self, node_id: LayoutNodeId, generation: u64) -> ChangeCategory {
+        self.node_changes
+            .get(&node_id)
+            .map(|changes| changes.since(generation))
+            .unwrap_or_default()
+    }
+
     /// Compare render props for visual equality
     fn props_visually_equal(old: &RenderProps, new: &RenderProps) -> bool {
         render_props_eq(old, new)
@@ -896,6 +989,8 @@ impl RenderTree {
         element: &E,
         node_id: LayoutNodeId,
     ) {
+        let changes = self.own_changes(element, node_id);
+
         // Update this node's props
         if let Some(render_node) = self.render_nodes.get_mut(&node_id) {
             let mut new_props = element.render_props();
@@ -903,6 +998,13 @@ impl RenderTree {
             // Preserve motion from old props (set by parent)
             new_props.motion = render_node.props.motion.clone();
             render_node.props = new_props;
+            // Text content lives in the element type, not the props
+            if matches!(
+                render_node.element_type,
+                ElementType::Text(_) | ElementType::StyledText(_)
+            ) {
+                render_node.element_type = Self::determine_element_type(element);
+            }
         } else {
             // Render node doesn't exist - create it
             tracing::debug!(
@@ -931,6 +1033,9 @@ impl RenderTree {
         let own_hash = DivHash::compute_element(element);
         let tree_hash = DivHash::compute_element_tree(element);
         self.node_hashes.insert(node_id, (own_hash, tree_hash));
+        self.record_change(node_id, changes);
+        #[cfg(feature = "recorder")]
+        self.record_element_origins(node_id, element);
 
         // Update event handlers
         if let Some(handlers) = element.event_handlers() {
@@ -977,11 +1082,19 @@ impl RenderTree {
         element: &dyn ElementBuilder,
         node_id: LayoutNodeId,
     ) {
+        let changes = self.own_changes(element, node_id);
+
         if let Some(render_node) = self.render_nodes.get_mut(&node_id) {
             let mut new_props = element.render_props();
             new_props.node_id = Some(node_id);
             new_props.motion = render_node.props.motion.clone();
             render_node.props = new_props;
+            if matches!(
+                render_node.element_type,
+                ElementType::Text(_) | ElementType::StyledText(_)
+            ) {
+                render_node.element_type = Self::determine_element_type_boxed(element);
+            }
         } else {
             // Render node doesn't exist - this can happen if the tree structure changed
             // but rebuild_children_in_place wasn't called for this subtree.
@@ -1011,6 +1124,9 @@ impl RenderTree {
         let own_hash = DivHash::compute_element(element);
         let tree_hash = DivHash::compute_element_tree(element);
         self.node_hashes.insert(node_id, (own_hash, tree_hash));
+        self.record_change(node_id, changes);
+        #[cfg(feature = "recorder")]
+        self.record_element_origins(node_id, element);
 
         if let Some(handlers) = element.event_handlers() {
             self.handler_registry.register(node_id, handlers.clone());
@@ -1118,6 +1234,7 @@ impl RenderTree {
         // Determine element type using the trait methods
         let element_type = Self::determine_element_type(element);
 
+        profiler::count(profiler::counters::NODES_REBUILT, 1);
         self.render_nodes.insert(
             node_id,
             RenderNode {
@@ -1130,6 +1247,8 @@ impl RenderTree {
         let own_hash = DivHash::compute_element(element);
         let tree_hash = DivHash::compute_element_tree(element);
         self.node_hashes.insert(node_id, (own_hash, tree_hash));
+        #[cfg(feature = "recorder")]
+        self.record_element_origins(node_id, element);
 
         // Register event handlers if present
         if let Some(handlers) = element.event_handlers() {
@@ -1288,6 +1407,7 @@ impl RenderTree {
                         loading_strategy: info.loading_strategy,
                         placeholder_type: info.placeholder_type,
                         placeholder_color: info.placeholder_color,
+                        playback: info.playback,
                     })
                 } else {
                     ElementType::Div
@@ -1332,6 +1452,7 @@ impl RenderTree {
             ElementTypeId::Motion => ElementType::Div, // Motion is a transparent container
         };
 
+        profiler::count(profiler::counters::NODES_REBUILT, 1);
         self.render_nodes.insert(
             node_id,
             RenderNode {
@@ -1344,6 +1465,8 @@ impl RenderTree {
         let own_hash = DivHash::compute_element(element);
         let tree_hash = DivHash::compute_element_tree(element);
         self.node_hashes.insert(node_id, (own_hash, tree_hash));
+        #[cfg(feature = "recorder")]
+        self.record_element_origins(node_id, element);
 
         // Register event handlers if present
         if let Some(handlers) = element.event_handlers() {
@@ -1573,6 +1696,7 @@ impl RenderTree {
                         loading_strategy: info.loading_strategy,
                         placeholder_type: info.placeholder_type,
                         placeholder_color: info.placeholder_color,
+                        playback: info.playback,
                     })
                 } else {
                     ElementType::Div
@@ -1617,6 +1741,7 @@ impl RenderTree {
             ElementTypeId::Motion => ElementType::Div,
         };
 
+        profiler::count(profiler::counters::NODES_REBUILT, 1);
         self.render_nodes.insert(
             node_id,
             RenderNode {
@@ -1629,6 +1754,8 @@ impl RenderTree {
         let own_hash = DivHash::compute_element(element);
         let tree_hash = DivHash::compute_element_tree(element);
         self.node_hashes.insert(node_id, (own_hash, tree_hash));
+        #[cfg(feature = "recorder")]
+        self.record_element_origins(node_id, element);
 
         // Register event handlers if present
         if let Some(handlers) = element.event_handlers() {
@@ -1734,6 +1861,7 @@ impl RenderTree {
                         loading_strategy: info.loading_strategy,
                         placeholder_type: info.placeholder_type,
                         placeholder_color: info.placeholder_color,
+                        playback: info.playback,
                     })
                 } else {
                     ElementType::Div
@@ -1864,6 +1992,7 @@ impl RenderTree {
                         loading_strategy: info.loading_strategy,
                         placeholder_type: info.placeholder_type,
                         placeholder_color: info.placeholder_color,
+                        playback: info.playback,
                     })
                 } else {
                     ElementType::Div
@@ -1884,6 +2013,7 @@ impl RenderTree {
 
     /// Compute layout for the given viewport size
     pub fn compute_layout(&mut self, width: f32, height: f32) {
+        let _scope = profiler::scope("layout");
         if let Some(root) = self.root {
             // Step 1: Check for existing collapsing animations and apply their constraints
             // This ensures children are laid out at the larger (animated) size during collapse
@@ -3769,6 +3899,54 @@ impl RenderTree {
         (x.round(), y.round())
     }
 
+    /// Get the scrollbar state for a scroll container
+    ///
+    /// Returns `None` for nodes without scroll physics, or when the physics
+    /// state is locked by another thread.
+    pub fn get_scrollbar_render_info(
+        &self,
+        node_id: LayoutNodeId,
+    ) -> Option<crate::widgets::scroll::ScrollbarRenderInfo> {
+        let physics = self.scroll_physics.get(&node_id)?;
+        let p = physics.try_lock().ok()?;
+        Some(p.scrollbar_render_info())
+    }
+
+    /// Cache layers automatically for animated subtrees
+    ///
+    /// When enabled, nodes animated by motion values or bindings are rendered
+    /// through a retained layer if nothing below them changes, as if they had
+    /// called `cache_as_layer()`. Disabled by default.
+    pub fn set_auto_layer_cache(&mut self, enabled: bool) {
+        self.auto_layer_cache = enabled;
+    }
+
+    /// Whether layers are cached automatically for animated subtrees
+    pub fn auto_layer_cache(&self) -> bool {
+        self.auto_layer_cache
+    }
+
+    /// Force a cached layer to be redrawn on the next frame
+    ///
+    /// This is the only way a `CachePolicy::Manual` layer is repainted;
+    /// content-cached layers are invalidated automatically.
+    pub fn invalidate_layer_cache(&mut self, node_id: LayoutNodeId) {
+        *self.layer_cache_generations.entry(node_id).or_insert(0) += 1;
+    }
+
+    /// Invalidation counter of a manually cached layer
+    pub(crate) fn layer_cache_generation(&self, node_id: LayoutNodeId) -> u64 {
+        self.layer_cache_generations
+            .get(&node_id)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Stored own and subtree hashes of a node, as used by the diff
+    pub(crate) fn node_hashes(&self, node_id: LayoutNodeId) -> Option<(DivHash, DivHash)> {
+        self.node_hashes.get(&node_id).copied()
+    }
+
     /// Get the motion translation for a node (if it has motion bindings)
     ///
     /// Returns the current translation transform from any bound AnimatedValue(s).
@@ -4138,7 +4316,9 @@ impl RenderTree {
         F: FnOnce(&mut RenderProps),
     {
         if let Some(render_node) = self.render_nodes.get_mut(&node_id) {
+            let old = render_node.props.clone();
             f(&mut render_node.props);
+            self.record_props_change(node_id, &old);
         }
     }
 
@@ -4230,44 +4410,80 @@ impl RenderTree {
         };
 
         // Reset to base style first
-        render_node.props = base_props;
+        let old_props = std::mem::replace(&mut render_node.props, base_props);
+        #[cfg(feature = "recorder")]
+        let mut origins = crate::style_origin::StyleOrigins::new();
 
         // Apply base stylesheet style (if any)
         if let Some(base_style) = stylesheet.get(&element_id) {
             Self::apply_element_style_to_props(&mut render_node.props, base_style);
+            #[cfg(feature = "recorder")]
+            origins.extend(crate::style_origin::element_style_origins(
+                base_style,
+                crate::style_origin::StyleOrigin::Rule(element_id.clone()),
+            ));
             applied = true;
         }
 
-        // Apply hover style
-        if hovered {
-            if let Some(hover_style) = stylesheet.get_with_state(&element_id, ElementState::Hover) {
-                Self::apply_element_style_to_props(&mut render_node.props, hover_style);
-                applied = true;
+        // Apply hover style, then active/pressed (takes precedence over
+        // hover), then focus
+        for (active, state) in [
+            (hovered, ElementState::Hover),
+            (pressed, ElementState::Active),
+            (focused, ElementState::Focus),
+        ] {
+            if !active {
+                continue;
             }
-        }
-
-        // Apply active/pressed style (takes precedence over hover)
-        if pressed {
-            if let Some(active_style) = stylesheet.get_with_state(&element_id, ElementState::Active)
-            {
-                Self::apply_element_style_to_props(&mut render_node.props, active_style);
+            if let Some(state_style) = stylesheet.get_with_state(&element_id, state) {
+                Self::apply_element_style_to_props(&mut render_node.props, state_style);
+                #[cfg(feature = "recorder")]
+                origins.extend(crate::style_origin::element_style_origins(
+                    state_style,
+                    crate::style_origin::StyleOrigin::Rule(format!("{}:{}", element_id, state)),
+                ));
                 applied = true;
             }
         }
 
-        // Apply focus style
-        if focused {
-            if let Some(focus_style) = stylesheet.get_with_state(&element_id, ElementState::Focus) {
-                Self::apply_element_style_to_props(&mut render_node.props, focus_style);
-                applied = true;
-            }
+        #[cfg(feature = "recorder")]
+        {
+            self.style_origins_mut(node_id).rules = origins;
         }
+        self.record_props_change(node_id, &old_props);
 
         applied
     }
 
+    /// Where each of a node's style values came from
+    ///
+    /// Keyed like [`style_values`](crate::style_origin::style_values). Only
+    /// recorded with the `recorder` feature; properties nothing set are left
+    /// out.
+    pub fn style_origins(&self, node_id: LayoutNodeId) -> crate::style_origin::StyleOrigins {
+        self.style_origins
+            .get(&node_id)
+            .map(|origins| origins.resolve())
+            .unwrap_or_default()
+    }
+
+    /// Recorded style origins of a node, for layers applied outside the tree
+    #[cfg(feature = "recorder")]
+    pub(crate) fn style_origins_mut(
+        &mut self,
+        node_id: LayoutNodeId,
+    ) -> &mut crate::style_origin::NodeStyleOrigins {
+        self.style_origins.entry(node_id).or_default()
+    }
+
+    /// Record the builder and theme origins an element reports for its node
+    #[cfg(feature = "recorder")]
+    fn record_element_origins(&mut self, node_id: LayoutNodeId, element: &dyn ElementBuilder) {
+        self.style_origins_mut(node_id).element = element.style_origins();
+    }
+
     /// Apply ElementStyle properties to RenderProps
-    fn apply_element_style_to_props(
+    pub(crate) fn apply_element_style_to_props(
         props: &mut RenderProps,
         style: &crate::element_style::ElementStyle,
     ) {
@@ -4417,6 +4633,8 @@ impl RenderTree {
 
         // Remove this node's render data
         self.render_nodes.swap_remove(&node_id);
+        self.style_origins.remove(&node_id);
+        self.node_changes.remove(&node_id);
         self.handler_registry.remove(node_id);
         self.node_states.remove(&node_id);
         self.scroll_offsets.remove(&node_id);
@@ -4477,12 +4695,15 @@ impl RenderTree {
                     let mut new_props = rebuild.new_child.render_props();
                     new_props.node_id = Some(rebuild.parent_id);
                     new_props.motion = render_node.props.motion.clone();
-                    render_node.props = new_props;
+                    let old_props = std::mem::replace(&mut render_node.props, new_props);
+                    self.record_props_change(rebuild.parent_id, &old_props);
                 }
                 // Also update the taffy layout style (width, height, padding, etc.)
                 if let Some(style) = rebuild.new_child.layout_style() {
                     self.layout_tree.set_style(rebuild.parent_id, style.clone());
                 }
+                #[cfg(feature = "recorder")]
+                self.record_element_origins(rebuild.parent_id, &rebuild.new_child);
 
                 // Always remove old children first (even if new children is empty)
                 // This fixes the bug where SVG checkmarks would persist after unchecking
@@ -4499,6 +4720,13 @@ impl RenderTree {
                     self.layout_tree.add_child(rebuild.parent_id, child_id);
                     self.collect_render_props_boxed(child.as_ref(), child_id);
                 }
+                self.record_change(
+                    rebuild.parent_id,
+                    ChangeCategory {
+                        children: true,
+                        ..ChangeCategory::none()
+                    },
+                );
             } else {
                 // Visual-only update - just update render props of existing children
                 // Don't remove/rebuild, just walk the tree and update props
@@ -4540,8 +4768,12 @@ impl RenderTree {
                 // Update this child's render props
                 let new_props = new_child.render_props();
                 if let Some(render_node) = self.render_nodes.get_mut(child_id) {
+                    let old_props = render_node.props.clone();
                     render_node.props.merge_from(&new_props);
+                    self.record_props_change(*child_id, &old_props);
                 }
+                #[cfg(feature = "recorder")]
+                self.record_element_origins(*child_id, new_child.as_ref());
 
                 // Recursively update grandchildren
                 if !new_child.children_builders().is_empty() {
@@ -4568,6 +4800,7 @@ impl RenderTree {
 
     /// Render the entire tree to a DrawContext
     pub fn render(&self, ctx: &mut dyn DrawContext) {
+        let _scope = profiler::scope("paint");
         tracing::trace!(
             "render: motion_bindings count = {}",
             self.motion_bindings.len()
@@ -5006,6 +5239,7 @@ impl RenderTree {
         ctx: &mut dyn DrawContext,
         render_state: &crate::render_state::RenderState,
     ) {
+        let _scope = profiler::scope("paint");
         if let Some(root) = self.root {
             // Apply DPI scale factor if set (for HiDPI display support)
             let has_scale = self.scale_factor != 1.0;
@@ -5240,24 +5474,42 @@ impl RenderTree {
         // layer commands across multiple render passes
         let has_layer_effects = !render_node.props.layer_effects.is_empty();
         let has_opacity_layer = node_motion_opacity < 1.0 || has_layer_effects;
-        let should_push_layer = has_opacity_layer && effective_layer == target_layer;
+
+        // Cached layers retain the rasterized subtree across frames. When the
+        // backend still holds it at this content version, nothing below is drawn.
+        let layer_cache =
+            if effective_layer == target_layer && effective_layer == RenderLayer::Background {
+                crate::layer_cache::layer_cache_key(self, render_state, node)
+            } else {
+                None
+            };
+        let layer_id = crate::layer_cache::layer_id(node);
+        let cache_hit =
+            layer_cache.is_some_and(|(_, version)| ctx.is_layer_cached(layer_id, version));
+
+        let should_push_layer =
+            (has_opacity_layer || layer_cache.is_some()) && effective_layer == target_layer;
         if should_push_layer {
+            let (cache_policy, content_version) = layer_cache.unwrap_or_default();
             ctx.push_layer(LayerConfig {
-                id: None,
+                id: layer_cache.map(|_| layer_id),
                 position: Some(junita_core::Point::new(bounds.x, bounds.y)),
                 size: Some(junita_core::Size::new(bounds.width, bounds.height)),
                 blend_mode: BlendMode::Normal,
                 opacity: node_motion_opacity,
                 depth: false,
                 effects: render_node.props.layer_effects.clone(),
+                cache_policy,
+                content_version,
             });
         }
+        let paints_here = effective_layer == target_layer && !cache_hit;
 
         // Draw shadow BEFORE pushing clip (shadows extend beyond element bounds)
         // This must be done before the clip is applied so shadows aren't clipped
         let rect = Rect::new(0.0, 0.0, bounds.width, bounds.height);
         let radius = render_node.props.border_radius;
-        if effective_layer == target_layer {
+        if paints_here {
             // Glass elements have shadows handled by the GPU glass system
             if !matches!(render_node.props.material, Some(Material::Glass(_))) {
                 if let Some(ref shadow) = render_node.props.shadow {
@@ -5285,7 +5537,7 @@ impl RenderTree {
         // Layout animations need clipping to hide content that exceeds animated bounds
         // NOTE: This clip is for the element itself. Children get an INSET clip pushed later
         // to prevent them from rendering over the border.
-        let clips_content = render_node.props.clips_content || has_layout_animation;
+        let clips_content = (render_node.props.clips_content || has_layout_animation) && !cache_hit;
         if clips_content {
             let clip_rect = Rect::new(0.0, 0.0, bounds.width, bounds.height);
             let clip_shape = if radius.is_uniform() && radius.top_left > 0.0 {
@@ -5309,7 +5561,7 @@ impl RenderTree {
             //     eprintln!("  >>> Canvas layer MATCHES - will invoke callback");
             // }
         }
-        if effective_layer == target_layer {
+        if paints_here {
             // Motion opacity is now handled via push_layer when has_opacity_layer=true
             // The opacity layer applies opacity to all content via GPU composition
 
@@ -5462,7 +5714,8 @@ impl RenderTree {
 
         // Apply scroll offset
         let scroll_offset = self.get_scroll_offset(node);
-        let has_scroll = scroll_offset.0.abs() > 0.001 || scroll_offset.1.abs() > 0.001;
+        let has_scroll =
+            (scroll_offset.0.abs() > 0.001 || scroll_offset.1.abs() > 0.001) && !cache_hit;
         if has_scroll {
             ctx.push_transform(Transform::translate(scroll_offset.0, scroll_offset.1));
         }
@@ -5530,7 +5783,12 @@ impl RenderTree {
         } else {
             motion_opacity
         };
-        for child_id in self.layout_tree.children(node) {
+        let children = if cache_hit {
+            Vec::new()
+        } else {
+            self.layout_tree.children(node)
+        };
+        for child_id in children {
             self.render_layer_with_motion(
                 ctx,
                 child_id,
@@ -5556,7 +5814,7 @@ impl RenderTree {
         // Render scrollbar overlay if this is a scroll container
         // Scrollbar is rendered after scroll transform is popped (in viewport space)
         // but before clip is popped (clipped within scroll container)
-        if effective_layer == target_layer {
+        if paints_here {
             if let Some(physics) = self.scroll_physics.get(&node) {
                 if let Ok(p) = physics.try_lock() {
                     let info = p.scrollbar_render_info();
@@ -6712,6 +6970,58 @@ impl RenderTree {
             self.collect_svg_elements(child_id, new_offset, result);
         }
     }
+
+    /// Get all image elements with their computed bounds
+    ///
+    /// Returns (ImageData, ElementBounds) for each image element in the tree.
+    /// Images are drawn by the platform renderer, not by `render_to`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// for (image, bounds) in tree.image_elements() {
+    ///     my_renderer.draw_image(&image.source, bounds.x, bounds.y, bounds.width, bounds.height);
+    /// }
+    /// ```
+    pub fn image_elements(&self) -> Vec<(ImageData, ElementBounds)> {
+        let mut result = Vec::new();
+        if let Some(root) = self.root {
+            self.collect_image_elements(root, (0.0, 0.0), &mut result);
+        }
+        result
+    }
+
+    fn collect_image_elements(
+        &self,
+        node: LayoutNodeId,
+        parent_offset: (f32, f32),
+        result: &mut Vec<(ImageData, ElementBounds)>,
+    ) {
+        let Some(bounds) = self.layout_tree.get_bounds(node, parent_offset) else {
+            return;
+        };
+
+        if let Some(render_node) = self.render_nodes.get(&node) {
+            if let ElementType::Image(image_data) = &render_node.element_type {
+                let abs_bounds = ElementBounds {
+                    x: parent_offset.0 + bounds.x,
+                    y: parent_offset.1 + bounds.y,
+                    width: bounds.width,
+                    height: bounds.height,
+                };
+                result.push((image_data.clone(), abs_bounds));
+            }
+        }
+
+        // Include scroll offset when calculating child positions
+        let scroll_offset = self.get_scroll_offset(node);
+        let new_offset = (
+            parent_offset.0 + bounds.x + scroll_offset.0,
+            parent_offset.1 + bounds.y + scroll_offset.1,
+        );
+        for child_id in self.layout_tree.children(node) {
+            self.collect_image_elements(child_id, new_offset, result);
+        }
+    }
 }
 
 /// Apply opacity to a brush by modifying its alpha component
@@ -0,0 +1,1272 @@
+//! Markdown and HTML serialization of rich text documents
+//!
+//! Both formats map onto the flat block model: consecutive list items,
+//! quote blocks and code lines are written as one list, quote or fenced
+//! block, and read back as one block per item, paragraph or line.
+//!
+//! Marks without a Markdown syntax (underline, color) are written as
+//! inline HTML, which [`RichDocument::from_markdown`] reads back, and so is
+//! emphasis where its `*` delimiters wouldn't be read as emphasis. HTML
+//! import accepts what browsers and word processors put on the clipboard:
+//! styled `<span>`s are read for bold, italic, underline, strikethrough
+//! and color, and unknown tags are treated as plain containers.
+
+use html_escape::{decode_html_entities, encode_double_quoted_attribute, encode_text};
+use junita_core::Color;
+use pulldown_cmark::{CodeBlockKind, Event, Options, Parser, Tag, TagEnd};
+
+use super::document::{add_mark, Block, BlockKind, Embed, Inline, Mark, RichDocument};
+use super::parser::parse_color;
+use crate::markdown::heading_level;
+
+impl RichDocument {
+    /// Serialize to CommonMark (with `~~strikethrough~~`)
+    pub fn to_markdown(&self) -> String {
+        let blocks = self.blocks();
+        let mut out = String::new();
+        // Content column and next number of each open list level
+        let mut columns: Vec<usize> = Vec::new();
+        let mut numbers: Vec<u64> = Vec::new();
+        let mut previous: Option<&BlockKind> = None;
+        let mut i = 0;
+        while i < blocks.len() {
+            let block = &blocks[i];
+            // Markdown has no empty paragraphs
+            if *block.kind() == BlockKind::Paragraph && block.is_empty() {
+                i += 1;
+                continue;
+            }
+            if let Some(previous) = previous.replace(block.kind()) {
+                out.push_str(match (previous, block.kind()) {
+                    (BlockKind::ListItem { .. }, BlockKind::ListItem { .. }) => "\n",
+                    (BlockKind::Quote, BlockKind::Quote) => "\n>\n",
+                    _ => "\n\n",
+                });
+            }
+            if !block.kind().is_list() {
+                columns.clear();
+                numbers.clear();
+            }
+            match block.kind() {
+                BlockKind::Paragraph => out.push_str(&escape_block_start(&inline_markdown(block))),
+                BlockKind::Heading(level) => {
+                    out.push_str(&"#".repeat((*level).clamp(1, 6) as usize));
+                    out.push(' ');
+                    out.push_str(&inline_markdown(block));
+                }
+                BlockKind::Quote => {
+                    out.push_str("> ");
+                    out.push_str(&escape_block_start(&inline_markdown(block)));
+                }
+                BlockKind::ListItem { ordered, indent } => {
+                    let level = *indent as usize;
+                    columns.truncate(level);
+                    numbers.truncate(level + 1);
+                    let lead = columns.last().copied().unwrap_or(0);
+                    while columns.len() < level {
+                        columns.push(lead);
+                    }
+                    let marker = if *ordered {
+                        numbers.resize(level + 1, 0);
+                        numbers[level] += 1;
+                        format!("{}. ", numbers[level])
+                    } else {
+                        numbers.truncate(level);
+                        "- ".to_string()
+                    };
+                    columns.push(lead + marker.len());
+                    out.push_str(&" ".repeat(lead));
+                    out.push_str(&marker);
+                    out.push_str(&escape_block_start(&inline_markdown(block)));
+                }
+                BlockKind::CodeBlock { language } => {
+                    let end = code_run_end(blocks, i);
+                    let lines: Vec<String> = blocks[i..end].iter().map(Block::text).collect();
+                    let longest = lines
+                        .iter()
+                        .flat_map(|line| line.split(|c| c != '`'))
+                        .map(str::len)
+                        .max()
+                        .unwrap_or(0);
+                    let fence = "`".repeat(longest.max(2) + 1);
+                    out.push_str(&fence);
+                    out.push_str(language.as_deref().unwrap_or(""));
+                    out.push('\n');
+                    for line in &lines {
+                        out.push_str(line);
+                        out.push('\n');
+                    }
+                    out.push_str(&fence);
+                    i = end;
+                    continue;
+                }
+            }
+            i += 1;
+        }
+        out.push('\n');
+        out
+    }
+
+    /// Parse CommonMark
+    ///
+    /// Paragraphs, headings, lists, quotes and code blocks map to blocks;
+    /// tables and rules are read as paragraphs of their text.
+    pub fn from_markdown(markdown: &str) -> Self {
+        let mut builder = Builder::default();
+        let mut image: Option<(String, String)> = None;
+        let mut html_block: Option<String> = None;
+        for event in Parser::new_ext(markdown, Options::ENABLE_STRIKETHROUGH) {
+            match event {
+                Event::Start(Tag::Paragraph) => builder.start_block(),
+                Event::Start(Tag::Heading { level, .. }) => {
+                    builder.heading = Some(heading_level(level));
+                    builder.start_block();
+                }
+                Event::Start(Tag::BlockQuote) => builder.quotes += 1,
+                Event::Start(Tag::CodeBlock(kind)) => {
+                    let language = match kind {
+                        CodeBlockKind::Fenced(info) => {
+                            info.split_whitespace().next().map(str::to_string)
+                        }
+                        CodeBlockKind::Indented => None,
+                    };
+                    builder.code = Some(language);
+                    builder.new_block();
+                }
+                Event::Start(Tag::List(start)) => {
+                    builder.finish_block();
+                    builder.lists.push(start.is_some());
+                }
+                Event::Start(Tag::Item) => {
+                    builder.items += 1;
+                    builder.new_block();
+                }
+                Event::Start(Tag::Emphasis) => builder.open_inline("em", vec![Mark::Italic]),
+                Event::Start(Tag::Strong) => builder.open_inline("strong", vec![Mark::Bold]),
+                Event::Start(Tag::Strikethrough) => {
+                    builder.open_inline("del", vec![Mark::Strikethrough])
+                }
+                Event::Start(Tag::Link { dest_url, .. }) => {
+                    builder.open_inline("a", vec![Mark::Link(dest_url.to_string())])
+                }
+                Event::Start(Tag::Image { dest_url, .. }) => {
+                    image = Some((dest_url.to_string(), String::new()))
+                }
+                Event::Start(Tag::HtmlBlock) => html_block = Some(String::new()),
+                Event::End(TagEnd::Paragraph) => builder.finish_block(),
+                Event::End(TagEnd::Heading(_)) => {
+                    builder.finish_block();
+                    builder.heading = None;
+                }
+                Event::End(TagEnd::BlockQuote) => {
+                    builder.finish_block();
+                    builder.quotes -= 1;
+                }
+                Event::End(TagEnd::CodeBlock) => {
+                    builder.finish_block();
+                    builder.code = None;
+                }
+                Event::End(TagEnd::List(_)) => {
+                    builder.lists.pop();
+                }
+                Event::End(TagEnd::Item) => {
+                    builder.finish_block();
+                    builder.items -= 1;
+                }
+                Event::End(TagEnd::Emphasis) => builder.close_inline("em"),
+                Event::End(TagEnd::Strong) => builder.close_inline("strong"),
+                Event::End(TagEnd::Strikethrough) => builder.close_inline("del"),
+                Event::End(TagEnd::Link) => builder.close_inline("a"),
+                Event::End(TagEnd::Image) => {
+                    if let Some((src, alt)) = image.take() {
+                        builder.push_embed(Embed::Image { src, alt });
+                    }
+                }
+                Event::End(TagEnd::HtmlBlock) => {
+                    if let Some(html) = html_block.take() {
+                        builder.finish_block();
+                        builder.read_html(&html);
+                        builder.finish_block();
+                    }
+                }
+                Event::Text(text) => match (&mut image, &mut html_block) {
+                    (Some((_, alt)), _) => alt.push_str(&text),
+                    (_, Some(html)) => html.push_str(&text),
+                    _ if builder.code.is_some() => builder.push_code(&text),
+                    _ => builder.push_text(&text),
+                },
+                Event::Code(code) => {
+                    builder.open_inline("code", vec![Mark::Code]);
+                    builder.push_text(&code);
+                    builder.close_inline("code");
+                }
+                Event::Html(html) => match &mut html_block {
+                    Some(block) => block.push_str(&html),
+                    None => builder.read_html(&html),
+                },
+                Event::InlineHtml(html) => builder.read_html(&html),
+                Event::SoftBreak => builder.push_text(" "),
+                Event::HardBreak => builder.new_block(),
+                _ => {}
+            }
+        }
+        builder.finish()
+    }
+
+    /// Serialize to an HTML fragment
+    pub fn to_html(&self) -> String {
+        let blocks = self.blocks();
+        let mut out = String::new();
+        // Whether each open list is ordered
+        let mut lists: Vec<bool> = Vec::new();
+        let mut i = 0;
+        while i < blocks.len() {
+            let block = &blocks[i];
+            if let BlockKind::ListItem { ordered, indent } = block.kind() {
+                let level = *indent as usize;
+                while lists.len() > level + 1 {
+                    close_list(&mut out, &mut lists);
+                }
+                if lists.len() == level + 1 && lists[level] != *ordered {
+                    close_list(&mut out, &mut lists);
+                }
+                if lists.len() == level + 1 {
+                    out.push_str("</li>");
+                }
+                // Levels skipped over get an item to hold the next level
+                while lists.len() <= level {
+                    let nested_ordered = lists.len() == level && *ordered;
+                    out.push_str(if nested_ordered { "<ol>" } else { "<ul>" });
+                    lists.push(nested_ordered);
+                    if lists.len() <= level {
+                        out.push_str("<li>");
+                    }
+                }
+                out.push_str("<li>");
+                out.push_str(&inline_html(block));
+                i += 1;
+                continue;
+            }
+            while !lists.is_empty() {
+                close_list(&mut out, &mut lists);
+            }
+            match block.kind() {
+                BlockKind::Paragraph => {
+                    out.push_str(&format!("<p>{}</p>", inline_html(block)));
+                }
+                BlockKind::Heading(level) => {
+                    let level = (*level).clamp(1, 6);
+                    out.push_str(&format!("<h{0}>{1}</h{0}>", level, inline_html(block)));
+                }
+                BlockKind::Quote => {
+                    out.push_str("<blockquote>");
+                    while i < blocks.len() && *blocks[i].kind() == BlockKind::Quote {
+                        out.push_str(&format!("<p>{}</p>", inline_html(&blocks[i])));
+                        i += 1;
+                    }
+                    out.push_str("</blockquote>");
+                    continue;
+                }
+                BlockKind::CodeBlock { language } => {
+                    let end = code_run_end(blocks, i);
+                    out.push_str("<pre><code");
+                    if let Some(language) = language {
+                        out.push_str(&format!(
+                            " class=\"language-{}\"",
+                            encode_double_quoted_attribute(language)
+                        ));
+                    }
+                    out.push('>');
+                    let lines: Vec<String> = blocks[i..end].iter().map(Block::text).collect();
+                    out.push_str(&encode_text(&lines.join("\n")));
+                    out.push_str("</code></pre>");
+                    i = end;
+                    continue;
+                }
+                BlockKind::ListItem { .. } => unreachable!(),
+            }
+            i += 1;
+        }
+        while !lists.is_empty() {
+            close_list(&mut out, &mut lists);
+        }
+        out
+    }
+
+    /// Parse an HTML document or fragment
+    pub fn from_html(html: &str) -> Self {
+        let mut builder = Builder::default();
+        builder.read_html(html);
+        builder.finish()
+    }
+}
+
+fn close_list(out: &mut String, lists: &mut Vec<bool>) {
+    let ordered = lists.pop().unwrap_or(false);
+    out.push_str(if ordered { "</li></ol>" } else { "</li></ul>" });
+}
+
+/// End of the run of code blocks with the same language starting at `start`
+fn code_run_end(blocks: &[Block], start: usize) -> usize {
+    let kind = blocks[start].kind();
+    start
+        + blocks[start..]
+            .iter()
+            .take_while(|block| block.kind() == kind)
+            .count()
+}
+
+/// How an output format writes marks and text
+trait Syntax {
+    /// Whether delimiters must be next to non-whitespace
+    const FLANKING: bool;
+
+    fn open(mark: &Mark, out: &mut String);
+    fn close(mark: &Mark, out: &mut String);
+    /// Delimiter written on both sides of `mark` in place of `open` and
+    /// `close`, where it would be read back as that mark
+    fn delimiter(_mark: &Mark) -> Option<&'static str> {
+        None
+    }
+    fn text(text: &str, code: bool, out: &mut String);
+    fn embed(embed: &Embed, out: &mut String);
+}
+
+/// Nesting order of marks, outermost first; code must be innermost in
+/// Markdown since nothing inside it is parsed
+fn nesting(mark: &Mark) -> u8 {
+    match mark {
+        Mark::Link(_) => 0,
+        Mark::Color(_) => 1,
+        Mark::Bold => 2,
+        Mark::Italic => 3,
+        Mark::Underline => 4,
+        Mark::Strikethrough => 5,
+        Mark::Code => 6,
+    }
+}
+
+/// Inline output with the choice of delimiters left open
+enum Piece<'a> {
+    Str(String),
+    /// Start or end of the `span`th marked span, for a mark with a
+    /// delimiter
+    Delim {
+        mark: &'a Mark,
+        span: usize,
+        open: bool,
+    },
+}
+
+/// Write inline content, keeping marks shared by neighbouring runs open
+/// across them
+fn write_inline<S: Syntax>(content: &[Inline]) -> String {
+    let mut pieces = Vec::new();
+    let mut open: Vec<(&Mark, usize)> = Vec::new();
+    let mut spans = 0;
+    for inline in content {
+        let mut marks: Vec<&Mark> = inline.marks().iter().collect();
+        marks.sort_by_key(|m| nesting(m));
+        let keep = open
+            .iter()
+            .zip(&marks)
+            .take_while(|((a, _), b)| a == *b)
+            .count();
+        close_marks::<S>(&mut pieces, &mut open, keep);
+        match inline {
+            Inline::Text { text, .. } => {
+                let body = if S::FLANKING { text.trim_start() } else { text };
+                tail(&mut pieces).push_str(&text[..text.len() - body.len()]);
+                if body.is_empty() {
+                    continue;
+                }
+                for mark in &marks[keep..] {
+                    if S::delimiter(mark).is_some() {
+                        pieces.push(Piece::Delim {
+                            mark,
+                            span: spans,
+                            open: true,
+                        });
+                    } else {
+                        S::open(mark, tail(&mut pieces));
+                    }
+                    open.push((*mark, spans));
+                    spans += 1;
+                }
+                S::text(body, marks.contains(&&Mark::Code), tail(&mut pieces));
+            }
+            Inline::Embed(embed) => S::embed(embed, tail(&mut pieces)),
+        }
+    }
+    close_marks::<S>(&mut pieces, &mut open, 0);
+
+    let delimited = flanking_spans::<S>(&pieces, spans);
+    let mut out = String::new();
+    for piece in &pieces {
+        write_piece::<S>(piece, &delimited, &mut out);
+    }
+    out
+}
+
+/// The string at the end of `pieces`, to write text into
+fn tail<'p>(pieces: &'p mut Vec<Piece>) -> &'p mut String {
+    if !matches!(pieces.last(), Some(Piece::Str(_))) {
+        pieces.push(Piece::Str(String::new()));
+    }
+    match pieces.last_mut() {
+        Some(Piece::Str(s)) => s,
+        _ => unreachable!(),
+    }
+}
+
+fn close_marks<'a, S: Syntax>(
+    pieces: &mut Vec<Piece<'a>>,
+    open: &mut Vec<(&'a Mark, usize)>,
+    keep: usize,
+) {
+    if open.len() <= keep {
+        return;
+    }
+    let trailing = match pieces.last_mut() {
+        Some(Piece::Str(s)) if S::FLANKING => {
+            let len = s.trim_end().len();
+            s.split_off(len)
+        }
+        _ => String::new(),
+    };
+    for (mark, span) in open.drain(keep..).rev() {
+        if S::delimiter(mark).is_some() {
+            pieces.push(Piece::Delim {
+                mark,
+                span,
+                open: false,
+            });
+        } else {
+            S::close(mark, tail(pieces));
+        }
+    }
+    if !trailing.is_empty() {
+        tail(pieces).push_str(&trailing);
+    }
+}
+
+fn write_piece<S: Syntax>(piece: &Piece, delimited: &[bool], out: &mut String) {
+    match piece {
+        Piece::Str(s) => out.push_str(s),
+        Piece::Delim { mark, span, open } => match S::delimiter(mark) {
+            Some(delimiter) if delimited[*span] => out.push_str(delimiter),
+            _ if *open => S::open(mark, out),
+            _ => S::close(mark, out),
+        },
+    }
+}
+
+/// Which spans can be written with their delimiter
+///
+/// CommonMark only reads a run of delimiters as emphasis when it flanks
+/// the text: an opening run must be followed by non-whitespace, and by
+/// punctuation only if whitespace or punctuation comes before it, and the
+/// mirror image for a closing run. So `A*<u>A</u>*` is not emphasis. Runs
+/// that don't flank, or that could be read as opening and closing, fall
+/// back to `open` and `close`, which changes their neighbours' surroundings;
+/// this repeats until every run is settled.
+fn flanking_spans<S: Syntax>(pieces: &[Piece], spans: usize) -> Vec<bool> {
+    let mut delimited = vec![true; spans];
+    loop {
+        let rendered: Vec<String> = pieces
+            .iter()
+            .map(|piece| {
+                let mut out = String::new();
+                write_piece::<S>(piece, &delimited, &mut out);
+                out
+            })
+            .collect();
+        // Delimiter character of each piece written as one; pieces that
+        // write nothing don't separate runs
+        let run_chars: Vec<(usize, Option<char>)> = pieces
+            .iter()
+            .zip(&rendered)
+            .enumerate()
+            .filter(|(_, (_, rendered))| !rendered.is_empty())
+            .map(|(i, (piece, rendered))| match piece {
+                Piece::Delim { span, .. } if delimited[*span] => (i, rendered.chars().next()),
+                _ => (i, None),
+            })
+            .collect();
+
+        let mut settled = true;
+        let mut next = 0;
+        while next < run_chars.len() {
+            let first = next;
+            next = (first..run_chars.len())
+                .find(|&i| run_chars[i].1 != run_chars[first].1)
+                .unwrap_or(run_chars.len());
+            if run_chars[first].1.is_none() {
+                continue;
+            }
+            let (start, end) = (run_chars[first].0, run_chars[next - 1].0 + 1);
+            let before = rendered[..start].iter().flat_map(|s| s.chars()).next_back();
+            let after = rendered[end..].iter().flat_map(|s| s.chars()).next();
+            let left = flanks(before, after);
+            let right = flanks(after, before);
+            let run = &pieces[start..end];
+            // A run that closes one span and opens the next is ambiguous;
+            // write the opening spans out and look at the closing ones again
+            let mixed = run
+                .iter()
+                .any(|p| matches!(p, Piece::Delim { open: true, .. }))
+                && run
+                    .iter()
+                    .any(|p| matches!(p, Piece::Delim { open: false, .. }));
+            for piece in run {
+                let Piece::Delim { span, open, .. } = piece else {
+                    continue;
+                };
+                let ok = if *open {
+                    !mixed && left && !right
+                } else {
+                    mixed || (right && !left)
+                };
+                if !ok && delimited[*span] {
+                    delimited[*span] = false;
+                    settled = false;
+                }
+            }
+        }
+        if settled {
+            return delimited;
+        }
+    }
+}
+
+/// Whether a delimiter run with `outside` on one side and `inside` on the
+/// other flanks `inside`; `None` is the start or end of the text
+fn flanks(outside: Option<char>, inside: Option<char>) -> bool {
+    let punctuation = |c: char| !c.is_alphanumeric() && !c.is_whitespace();
+    match inside {
+        None => false,
+        Some(c) if c.is_whitespace() => false,
+        Some(c) if punctuation(c) => outside.map_or(true, |o| o.is_whitespace() || punctuation(o)),
+        Some(_) => true,
+    }
+}
+
+fn color_css(color: &Color) -> String {
+    let byte = |v: f32| (v.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let hex = format!(
+        "#{:02x}{:02x}{:02x}",
+        byte(color.r),
+        byte(color.g),
+        byte(color.b)
+    );
+    if color.a < 1.0 {
+        format!("{}{:02x}", hex, byte(color.a))
+    } else {
+        hex
+    }
+}
+
+fn embed_html(embed: &Embed, out: &mut String) {
+    match embed {
+        Embed::Image { src, alt } => out.push_str(&format!(
+            "<img src=\"{}\" alt=\"{}\">",
+            encode_double_quoted_attribute(src),
+            encode_double_quoted_attribute(alt)
+        )),
+        Embed::Custom { kind, data } => out.push_str(&format!(
+            "<x-embed kind=\"{}\" data=\"{}\"></x-embed>",
+            encode_double_quoted_attribute(kind),
+            encode_double_quoted_attribute(data)
+        )),
+    }
+}
+
+struct Markdown;
+
+impl Syntax for Markdown {
+    const FLANKING: bool = true;
+
+    fn open(mark: &Mark, out: &mut String) {
+        match mark {
+            Mark::Bold => out.push_str("<strong>"),
+            Mark::Italic => out.push_str("<em>"),
+            Mark::Underline => out.push_str("<u>"),
+            Mark::Strikethrough => out.push_str("<s>"),
+            Mark::Code => out.push('`'),
+            Mark::Link(_) => out.push('['),
+            Mark::Color(color) => {
+                out.push_str(&format!("<span style=\"color: {}\">", color_css(color)))
+            }
+        }
+    }
+
+    fn close(mark: &Mark, out: &mut String) {
+        match mark {
+            Mark::Bold => out.push_str("</strong>"),
+            Mark::Italic => out.push_str("</em>"),
+            Mark::Underline => out.push_str("</u>"),
+            Mark::Strikethrough => out.push_str("</s>"),
+            Mark::Code => out.push('`'),
+            Mark::Link(url) if url.contains([' ', '(', ')']) => {
+                out.push_str(&format!("](<{}>)", url))
+            }
+            Mark::Link(url) => out.push_str(&format!("]({})", url)),
+            Mark::Color(_) => out.push_str("</span>"),
+        }
+    }
+
+    fn delimiter(mark: &Mark) -> Option<&'static str> {
+        match mark {
+            Mark::Bold => Some("**"),
+            Mark::Italic => Some("*"),
+            Mark::Strikethrough => Some("~~"),
+            _ => None,
+        }
+    }
+
+    fn text(text: &str, code: bool, out: &mut String) {
+        if code {
+            out.push_str(text);
+            return;
+        }
+        for c in text.chars() {
+            if matches!(
+                c,
+                '\\' | '`' | '*' | '_' | '[' | ']' | '<' | '>' | '~' | '#' | '&' | '|'
+            ) {
+                out.push('\\');
+            }
+            out.push(c);
+        }
+    }
+
+    fn embed(embed: &Embed, out: &mut String) {
+        match embed {
+            Embed::Image { src, alt } => out.push_str(&format!("![{}]({})", alt, src)),
+            Embed::Custom { .. } => embed_html(embed, out),
+        }
+    }
+}
+
+struct Html;
+
+impl Syntax for Html {
+    const FLANKING: bool = false;
+
+    fn open(mark: &Mark, out: &mut String) {
+        match mark {
+            Mark::Bold => out.push_str("<strong>"),
+            Mark::Italic => out.push_str("<em>"),
+            Mark::Underline => out.push_str("<u>"),
+            Mark::Strikethrough => out.push_str("<s>"),
+            Mark::Code => out.push_str("<code>"),
+            Mark::Link(url) => out.push_str(&format!(
+                "<a href=\"{}\">",
+                encode_double_quoted_attribute(url)
+            )),
+            Mark::Color(color) => {
+                out.push_str(&format!("<span style=\"color: {}\">", color_css(color)))
+            }
+        }
+    }
+
+    fn close(mark: &Mark, out: &mut String) {
+        out.push_str(match mark {
+            Mark::Bold => "</strong>",
+            Mark::Italic => "</em>",
+            Mark::Underline => "</u>",
+            Mark::Strikethrough => "</s>",
+            Mark::Code => "</code>",
+            Mark::Link(_) => "</a>",
+            Mark::Color(_) => "</span>",
+        });
+    }
+
+    fn text(text: &str, _code: bool, out: &mut String) {
+        out.push_str(&encode_text(text));
+    }
+
+    fn embed(embed: &Embed, out: &mut String) {
+        embed_html(embed, out);
+    }
+}
+
+fn inline_markdown(block: &Block) -> String {
+    write_inline::<Markdown>(block.content())
+}
+
+fn inline_html(block: &Block) -> String {
+    write_inline::<Html>(block.content())
+}
+
+/// Escape text that would otherwise start a list item
+fn escape_block_start(line: &str) -> String {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    let after_digits = &line[digits..];
+    if line.starts_with("- ") || line.starts_with("+ ") {
+        format!("\\{}", line)
+    } else if digits > 0 && (after_digits.starts_with(". ") || after_digits.starts_with(") ")) {
+        format!("{}\\{}", &line[..digits], after_digits)
+    } else {
+        line.to_string()
+    }
+}
+
+/// A parsed HTML token
+#[derive(Debug, PartialEq)]
+enum Token {
+    Start {
+        name: String,
+        attrs: Vec<(String, String)>,
+    },
+    End(String),
+    Text(String),
+}
+
+/// Split HTML into tags and text; comments and doctypes are dropped and
+/// stray `<`s are read as text
+fn tokenize(html: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = html;
+    while !rest.is_empty() {
+        let Some(lt) = rest.find('<') else {
+            tokens.push(Token::Text(decode_html_entities(rest).into_owned()));
+            break;
+        };
+        if lt > 0 {
+            tokens.push(Token::Text(decode_html_entities(&rest[..lt]).into_owned()));
+            rest = &rest[lt..];
+        }
+        if let Some(comment) = rest.strip_prefix("<!--") {
+            rest = comment.find("-->").map_or("", |end| &comment[end + 3..]);
+        } else if rest.starts_with("<!") || rest.starts_with("<?") {
+            rest = rest.find('>').map_or("", |end| &rest[end + 1..]);
+        } else if let Some((token, len)) = parse_tag(rest) {
+            tokens.push(token);
+            rest = &rest[len..];
+        } else {
+            tokens.push(Token::Text("<".to_string()));
+            rest = &rest[1..];
+        }
+    }
+    tokens
+}
+
+/// Parse the tag at the start of `input`, returning it and its length
+fn parse_tag(input: &str) -> Option<(Token, usize)> {
+    let (closing, body) = match input.strip_prefix("</") {
+        Some(body) => (true, body),
+        None => (false, input.strip_prefix('<')?),
+    };
+    let name_len = body
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(body.len());
+    if name_len == 0 || !body.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let name = body[..name_len].to_ascii_lowercase();
+    let mut attrs = Vec::new();
+    let mut rest = &body[name_len..];
+    loop {
+        rest = rest.trim_start();
+        if let Some(after) = rest.strip_prefix('>') {
+            let len = input.len() - after.len();
+            let token = if closing {
+                Token::End(name)
+            } else {
+                Token::Start { name, attrs }
+            };
+            return Some((token, len));
+        }
+        if let Some(after) = rest.strip_prefix('/') {
+            rest = after;
+            continue;
+        }
+        let key_len = rest
+            .find(|c: char| c.is_whitespace() || matches!(c, '=' | '>' | '/'))
+            .unwrap_or(rest.len());
+        if key_len == 0 {
+            return None;
+        }
+        let key = rest[..key_len].to_ascii_lowercase();
+        rest = rest[key_len..].trim_start();
+        let value = match rest.strip_prefix('=') {
+            Some(after) => {
+                let after = after.trim_start();
+                let (value, remaining) = match after.chars().next() {
+                    Some(quote @ ('"' | '\'')) => {
+                        let end = after[1..].find(quote)? + 1;
+                        (&after[1..end], &after[end + 1..])
+                    }
+                    _ => {
+                        let end = after
+                            .find(|c: char| c.is_whitespace() || c == '>')
+                            .unwrap_or(after.len());
+                        after.split_at(end)
+                    }
+                };
+                rest = remaining;
+                decode_html_entities(value).into_owned()
+            }
+            None => String::new(),
+        };
+        attrs.push((key, value));
+    }
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], key: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(k, _)| k == key)
+        .map(|(_, v)| v.as_str())
+}
+
+/// Marks an inline element applies: from its tag, then its `style`
+fn inline_marks(name: &str, attrs: &[(String, String)]) -> Vec<Mark> {
+    let mut marks = match name {
+        "b" | "strong" => vec![Mark::Bold],
+        "i" | "em" => vec![Mark::Italic],
+        "u" | "ins" => vec![Mark::Underline],
+        "s" | "strike" | "del" => vec![Mark::Strikethrough],
+        "code" | "kbd" | "samp" | "tt" => vec![Mark::Code],
+        "a" => attr(attrs, "href")
+            .map(|href| vec![Mark::Link(href.to_string())])
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    for declaration in attr(attrs, "style").unwrap_or("").split(';') {
+        let Some((property, value)) = declaration.split_once(':') else {
+            continue;
+        };
+        let value = value.trim().to_ascii_lowercase();
+        match property.trim().to_ascii_lowercase().as_str() {
+            "font-weight" => match value.as_str() {
+                "bold" | "bolder" | "600" | "700" | "800" | "900" => {
+                    add_mark(&mut marks, Mark::Bold)
+                }
+                _ => marks.retain(|m| *m != Mark::Bold),
+            },
+            "font-style" if value == "italic" => add_mark(&mut marks, Mark::Italic),
+            "text-decoration" | "text-decoration-line" => {
+                if value.contains("underline") {
+                    add_mark(&mut marks, Mark::Underline);
+                }
+                if value.contains("line-through") {
+                    add_mark(&mut marks, Mark::Strikethrough);
+                }
+            }
+            "color" => {
+                if let Some(color) = parse_color(&value) {
+                    add_mark(&mut marks, Mark::Color(color));
+                }
+            }
+            _ => {}
+        }
+    }
+    marks
+}
+
+/// Builds a document from parser events
+#[derive(Default)]
+struct Builder {
+    blocks: Vec<Block>,
+    /// The block being filled
+    current: Option<(BlockKind, Vec<Inline>)>,
+    /// Open inline elements and the marks they apply
+    inline: Vec<(String, Vec<Mark>)>,
+    /// Whether each enclosing list is ordered
+    lists: Vec<bool>,
+    /// Enclosing list items
+    items: usize,
+    /// Enclosing quotes
+    quotes: usize,
+    heading: Option<u8>,
+    /// Language of the enclosing code block
+    code: Option<Option<String>>,
+    /// Newlines in code not yet turned into blocks
+    pending_lines: usize,
+    /// Depth inside elements whose content isn't text (`<style>`, ...)
+    skip: usize,
+}
+
+impl Builder {
+    /// The kind of block content goes into here
+    fn kind(&self) -> BlockKind {
+        if let Some(language) = &self.code {
+            BlockKind::CodeBlock {
+                language: language.clone(),
+            }
+        } else if let Some(level) = self.heading {
+            BlockKind::Heading(level)
+        } else if self.items > 0 {
+            BlockKind::ListItem {
+                ordered: self.lists.last().copied().unwrap_or(false),
+                indent: self.lists.len().saturating_sub(1).min(u8::MAX as usize) as u8,
+            }
+        } else if self.quotes > 0 {
+            BlockKind::Quote
+        } else {
+            BlockKind::Paragraph
+        }
+    }
+
+    fn marks(&self) -> Vec<Mark> {
+        let mut marks = Vec::new();
+        for mark in self.inline.iter().flat_map(|(_, marks)| marks) {
+            add_mark(&mut marks, mark.clone());
+        }
+        marks
+    }
+
+    /// Start a block, reusing the current one if nothing was added to it
+    fn start_block(&mut self) {
+        let kind = self.kind();
+        match &mut self.current {
+            Some((current, content)) if content.is_empty() => *current = kind,
+            _ => self.new_block(),
+        }
+    }
+
+    /// Start a block, keeping the current one even if it's empty
+    fn new_block(&mut self) {
+        if let Some((kind, content)) = self.current.take() {
+            self.blocks.push(Block::new(kind, content));
+        }
+        self.current = Some((self.kind(), Vec::new()));
+    }
+
+    /// End the current block; empty blocks are dropped unless a list item
+    /// or code line
+    fn finish_block(&mut self) {
+        if let Some((kind, mut content)) = self.current.take() {
+            let code = matches!(kind, BlockKind::CodeBlock { .. });
+            if let (false, Some(Inline::Text { text, .. })) = (code, content.last_mut()) {
+                let trimmed = text.trim_end().len();
+                text.truncate(trimmed);
+            }
+            let block = Block::new(kind, content);
+            let keep = code || block.kind().is_list();
+            if !block.is_empty() || keep {
+                self.blocks.push(block);
+            }
+        }
+        self.pending_lines = 0;
+    }
+
+    fn content(&mut self) -> &mut Vec<Inline> {
+        if self.current.is_none() {
+            self.current = Some((self.kind(), Vec::new()));
+        }
+        &mut self.current.as_mut().unwrap().1
+    }
+
+    /// Add text, collapsing whitespace as HTML does
+    fn push_text(&mut self, text: &str) {
+        let marks = self.marks();
+        let content = self.content();
+        let mut at_space = match content.last() {
+            None => true,
+            Some(Inline::Text { text, .. }) => text.ends_with(' '),
+            Some(Inline::Embed(_)) => false,
+        };
+        let mut collapsed = String::with_capacity(text.len());
+        for c in text.chars() {
+            if c.is_ascii_whitespace() {
+                if !at_space {
+                    collapsed.push(' ');
+                }
+                at_space = true;
+            } else {
+                collapsed.push(c);
+                at_space = false;
+            }
+        }
+        if !collapsed.is_empty() {
+            content.push(Inline::marked(collapsed, marks));
+        }
+    }
+
+    /// Add preformatted text, one block per line
+    fn push_code(&mut self, text: &str) {
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.pending_lines += 1;
+            }
+            if line.is_empty() {
+                continue;
+            }
+            for _ in 0..std::mem::take(&mut self.pending_lines) {
+                self.new_block();
+            }
+            let marks = self.marks();
+            self.content().push(Inline::marked(line, marks));
+        }
+    }
+
+    fn push_embed(&mut self, embed: Embed) {
+        self.content().push(Inline::Embed(embed));
+    }
+
+    fn open_inline(&mut self, name: &str, marks: Vec<Mark>) {
+        self.inline.push((name.to_string(), marks));
+    }
+
+    fn close_inline(&mut self, name: &str) {
+        if let Some(index) = self.inline.iter().rposition(|(open, _)| open == name) {
+            self.inline.remove(index);
+        }
+    }
+
+    /// Read HTML into the document
+    fn read_html(&mut self, html: &str) {
+        for token in tokenize(html) {
+            match token {
+                Token::Start { name, .. } if self.skip > 0 => {
+                    if matches!(
+                        name.as_str(),
+                        "script" | "style" | "head" | "title" | "template"
+                    ) {
+                        self.skip += 1;
+                    }
+                }
+                Token::End(name) if self.skip > 0 => {
+                    if matches!(
+                        name.as_str(),
+                        "script" | "style" | "head" | "title" | "template"
+                    ) {
+                        self.skip -= 1;
+                    }
+                }
+                Token::Text(_) if self.skip > 0 => {}
+                Token::Start { name, attrs } => self.start_element(&name, &attrs),
+                Token::End(name) => self.end_element(&name),
+                Token::Text(text) if self.code.is_some() => self.push_code(&text),
+                Token::Text(text) => self.push_text(&text),
+            }
+        }
+    }
+
+    fn start_element(&mut self, name: &str, attrs: &[(String, String)]) {
+        match name {
+            "script" | "style" | "head" | "title" | "template" => self.skip += 1,
+            "p" | "div" | "section" | "article" | "header" | "footer" | "tr" | "dt" | "dd"
+            | "figure" | "figcaption" => self.start_block(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.heading = name[1..].parse().ok();
+                self.start_block();
+            }
+            "blockquote" => {
+                self.quotes += 1;
+                self.start_block();
+            }
+            "ul" | "ol" => {
+                self.finish_block();
+                self.lists.push(name == "ol");
+            }
+            "li" => {
+                self.items += 1;
+                self.new_block();
+            }
+            "pre" => {
+                self.code = Some(None);
+                self.new_block();
+            }
+            "code" if self.code.is_some() => {
+                let language = attr(attrs, "class").and_then(|class| {
+                    class
+                        .split_whitespace()
+                        .find_map(|c| c.strip_prefix("language-"))
+                        .map(str::to_string)
+                });
+                if language.is_some() {
+                    self.code = Some(language);
+                    self.start_block();
+                }
+            }
+            "br" if self.code.is_some() => self.pending_lines += 1,
+            "br" => self.new_block(),
+            "img" => {
+                if let Some(src) = attr(attrs, "src") {
+                    self.push_embed(Embed::Image {
+                        src: src.to_string(),
+                        alt: attr(attrs, "alt").unwrap_or("").to_string(),
+                    });
+                }
+            }
+            "x-embed" => self.push_embed(Embed::Custom {
+                kind: attr(attrs, "kind").unwrap_or("").to_string(),
+                data: attr(attrs, "data").unwrap_or("").to_string(),
+            }),
+            _ => {
+                let marks = inline_marks(name, attrs);
+                self.open_inline(name, marks);
+            }
+        }
+    }
+
+    fn end_element(&mut self, name: &str) {
+        match name {
+            "p" | "div" | "section" | "article" | "header" | "footer" | "tr" | "dt" | "dd"
+            | "figure" | "figcaption" => self.finish_block(),
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.finish_block();
+                self.heading = None;
+            }
+            "blockquote" => {
+                self.finish_block();
+                self.quotes = self.quotes.saturating_sub(1);
+            }
+            "ul" | "ol" => {
+                self.finish_block();
+                self.lists.pop();
+            }
+            "li" => {
+                self.finish_block();
+                self.items = self.items.saturating_sub(1);
+            }
+            "pre" => {
+                self.finish_block();
+                self.code = None;
+            }
+            "br" | "img" | "x-embed" => {}
+            _ => self.close_inline(name),
+        }
+    }
+
+    fn finish(mut self) -> RichDocument {
+        self.finish_block();
+        RichDocument::from_blocks(self.blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn doc() -> RichDocument {
+        RichDocument::from_blocks(vec![
+            Block::new(BlockKind::Heading(2), vec![Inline::text("Notes")]),
+            Block::new(
+                BlockKind::Paragraph,
+                vec![
+                    Inline::text("Some "),
+                    Inline::marked("bold ", vec![Mark::Bold]),
+                    Inline::marked("and italic", vec![Mark::Bold, Mark::Italic]),
+                    Inline::text(", a "),
+                    Inline::marked("link", vec![Mark::Link("https://example.com".into())]),
+                    Inline::text(" and "),
+                    Inline::marked("code", vec![Mark::Code]),
+                    Inline::text(" * 2"),
+                ],
+            ),
+            Block::new(BlockKind::bullet(), vec![Inline::text("one")]),
+            Block::new(
+                BlockKind::ListItem {
+                    ordered: true,
+                    indent: 1,
+                },
+                vec![Inline::marked("nested", vec![Mark::Underline])],
+            ),
+            Block::new(BlockKind::bullet(), vec![Inline::text("two")]),
+            Block::new(BlockKind::Quote, vec![Inline::text("quoted")]),
+            Block::new(
+                BlockKind::CodeBlock {
+                    language: Some("rust".into()),
+                },
+                vec![Inline::text("fn main() {}")],
+            ),
+            Block::new(
+                BlockKind::CodeBlock {
+                    language: Some("rust".into()),
+                },
+                vec![],
+            ),
+            Block::new(
+                BlockKind::CodeBlock {
+                    language: Some("rust".into()),
+                },
+                vec![Inline::text("// <end>")],
+            ),
+        ])
+    }
+
+    #[test]
+    fn test_markdown_round_trip() {
+        let markdown = doc().to_markdown();
+        assert!(
+            markdown.contains("Some **bold *and italic***,"),
+            "{}",
+            markdown
+        );
+        assert!(
+            markdown.contains("- one\n  1. <u>nested</u>\n- two"),
+            "{}",
+            markdown
+        );
+        assert!(markdown.contains("\\* 2"), "{}", markdown);
+        assert_eq!(RichDocument::from_markdown(&markdown), doc());
+    }
+
+    #[test]
+    fn test_markdown_adjacent_runs() {
+        let doc = RichDocument::from_blocks(vec![Block::new(
+            BlockKind::Paragraph,
+            vec![
+                Inline::text("A"),
+                Inline::marked("A", vec![Mark::Italic, Mark::Underline]),
+                Inline::marked("b", vec![Mark::Bold]),
+                Inline::text("c "),
+                Inline::marked("d.", vec![Mark::Strikethrough]),
+                Inline::text("e "),
+                Inline::marked("f", vec![Mark::Italic]),
+            ],
+        )]);
+        // Delimiters that wouldn't flank the text are written as HTML
+        let markdown = doc.to_markdown();
+        assert_eq!(
+            markdown.trim_end(),
+            "A<em><u>A</u></em><strong>b</strong>c <s>d.</s>e *f*"
+        );
+        assert_eq!(RichDocument::from_markdown(&markdown), doc);
+    }
+
+    #[test]
+    fn test_html_round_trip() {
+        let html = doc().to_html();
+        assert!(
+            html.contains("<ul><li>one<ol><li><u>nested</u></li></ol></li><li>two</li></ul>"),
+            "{}",
+            html
+        );
+        assert!(html.contains(
+            "<pre><code class=\"language-rust\">fn main() {}\n\n// &lt;end&gt;</code></pre>"
+        ));
+        assert_eq!(RichDocument::from_html(&html), doc());
+    }
+
+    #[test]
+    fn test_from_clipboard_html() {
+        let html = r#"<html><head><style>p { color: red }</style></head><body>
+            <!--StartFragment--><b style="font-weight:normal;" id="docs-internal">
+            <p dir="ltr"><span style="font-weight:700;">Title</span><span> text&nbsp;&amp; more</span></p>
+            <p><span style="color:#ff0000;text-decoration:underline">red</span><br>next
+            line <img src="a.png" alt="A"></p></b><!--EndFragment--></body></html>"#;
+        let doc = RichDocument::from_html(html);
+        assert_eq!(
+            doc.plain_text(),
+            "Title text\u{a0}& more\nred\nnext line \u{fffc}"
+        );
+        assert_eq!(doc.blocks()[0].content()[0].marks(), &[Mark::Bold]);
+        assert_eq!(
+            doc.blocks()[1].content()[0].marks(),
+            &[Mark::Underline, Mark::Color(Color::RED)]
+        );
+        assert_eq!(
+            doc.blocks()[2].content()[1],
+            Inline::Embed(Embed::Image {
+                src: "a.png".into(),
+                alt: "A".into()
+            })
+        );
+    }
+}
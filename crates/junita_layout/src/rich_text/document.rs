@@ -0,0 +1,559 @@
+//! Editable rich text documents
+//!
+//! A [`RichDocument`] is a flat list of [`Block`]s (paragraphs, headings,
+//! list items, quotes, code blocks), each holding inline content: text runs
+//! carrying [`Mark`]s, and [`Embed`]s such as images. Lists are blocks with
+//! an indent level rather than nested trees, so every position in a
+//! document is a `(block, offset)` pair and editing never has to
+//! restructure a tree.
+//!
+//! Offsets count characters; an embed counts as one character.
+//!
+//! Documents are changed by applying [`Transaction`](super::Transaction)s,
+//! which return their inverse for undo.
+
+use std::ops::Range;
+
+use junita_core::Color;
+
+/// Inline formatting applied to a run of text
+#[derive(Clone, Debug, PartialEq)]
+pub enum Mark {
+    Bold,
+    Italic,
+    Underline,
+    Strikethrough,
+    /// Inline code
+    Code,
+    /// Hyperlink to a URL
+    Link(String),
+    /// Text color
+    Color(Color),
+}
+
+impl Mark {
+    /// Whether both marks are the same kind, ignoring link URLs and colors
+    pub fn same_kind(&self, other: &Mark) -> bool {
+        std::mem::discriminant(self) == std::mem::discriminant(other)
+    }
+
+    /// Sort key so mark lists compare equal regardless of insertion order
+    fn order(&self) -> u8 {
+        match self {
+            Mark::Bold => 0,
+            Mark::Italic => 1,
+            Mark::Underline => 2,
+            Mark::Strikethrough => 3,
+            Mark::Code => 4,
+            Mark::Link(_) => 5,
+            Mark::Color(_) => 6,
+        }
+    }
+}
+
+/// Add `mark` to `marks`, replacing a mark of the same kind
+pub(crate) fn add_mark(marks: &mut Vec<Mark>, mark: Mark) {
+    marks.retain(|m| !m.same_kind(&mark));
+    marks.push(mark);
+    marks.sort_by_key(Mark::order);
+}
+
+/// Remove marks of the same kind as `mark`
+pub(crate) fn remove_mark(marks: &mut Vec<Mark>, mark: &Mark) {
+    marks.retain(|m| !m.same_kind(mark));
+}
+
+/// Non-text inline content
+#[derive(Clone, Debug, PartialEq)]
+pub enum Embed {
+    /// An image
+    Image { src: String, alt: String },
+    /// Application-defined content (mentions, widgets, ...); `data` is
+    /// opaque to the editor
+    Custom { kind: String, data: String },
+}
+
+/// A piece of inline content
+#[derive(Clone, Debug, PartialEq)]
+pub enum Inline {
+    /// Text with its marks
+    Text { text: String, marks: Vec<Mark> },
+    /// An embed, one character long
+    Embed(Embed),
+}
+
+impl Inline {
+    /// Unmarked text
+    pub fn text(text: impl Into<String>) -> Self {
+        Inline::Text {
+            text: text.into(),
+            marks: Vec::new(),
+        }
+    }
+
+    /// Text with marks
+    pub fn marked(text: impl Into<String>, marks: Vec<Mark>) -> Self {
+        let mut sorted = Vec::new();
+        for mark in marks {
+            add_mark(&mut sorted, mark);
+        }
+        Inline::Text {
+            text: text.into(),
+            marks: sorted,
+        }
+    }
+
+    /// Length in characters
+    pub fn len(&self) -> usize {
+        match self {
+            Inline::Text { text, .. } => text.chars().count(),
+            Inline::Embed(_) => 1,
+        }
+    }
+
+    /// Whether this is empty text
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Inline::Text { text, .. } if text.is_empty())
+    }
+
+    /// Marks of this content (embeds have none)
+    pub fn marks(&self) -> &[Mark] {
+        match self {
+            Inline::Text { marks, .. } => marks,
+            Inline::Embed(_) => &[],
+        }
+    }
+}
+
+/// Block-level structure
+#[derive(Clone, Debug, PartialEq, Eq, Default)]
+pub enum BlockKind {
+    #[default]
+    Paragraph,
+    /// Heading, level 1-6
+    Heading(u8),
+    /// List item; consecutive items at the same indent form one list
+    ListItem { ordered: bool, indent: u8 },
+    /// Quoted paragraph
+    Quote,
+    /// Preformatted code; newlines stay inside the block
+    CodeBlock { language: Option<String> },
+}
+
+impl BlockKind {
+    /// Unordered list item at indent 0
+    pub fn bullet() -> Self {
+        BlockKind::ListItem {
+            ordered: false,
+            indent: 0,
+        }
+    }
+
+    /// Ordered list item at indent 0
+    pub fn ordered() -> Self {
+        BlockKind::ListItem {
+            ordered: true,
+            indent: 0,
+        }
+    }
+
+    /// Whether this is a list item
+    pub fn is_list(&self) -> bool {
+        matches!(self, BlockKind::ListItem { .. })
+    }
+}
+
+/// A block of inline content
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Block {
+    kind: BlockKind,
+    content: Vec<Inline>,
+}
+
+impl Block {
+    /// Create a block from inline content
+    pub fn new(kind: BlockKind, content: Vec<Inline>) -> Self {
+        let mut block = Self { kind, content };
+        block.normalize();
+        block
+    }
+
+    /// A paragraph of unmarked text
+    pub fn paragraph(text: impl Into<String>) -> Self {
+        Self::new(BlockKind::Paragraph, vec![Inline::text(text)])
+    }
+
+    /// The block's kind
+    pub fn kind(&self) -> &BlockKind {
+        &self.kind
+    }
+
+    pub(crate) fn set_kind(&mut self, kind: BlockKind) -> BlockKind {
+        std::mem::replace(&mut self.kind, kind)
+    }
+
+    /// Inline content, with adjacent runs of equal marks merged
+    pub fn content(&self) -> &[Inline] {
+        &self.content
+    }
+
+    /// Length in characters
+    pub fn len(&self) -> usize {
+        self.content.iter().map(Inline::len).sum()
+    }
+
+    /// Whether the block has no content
+    pub fn is_empty(&self) -> bool {
+        self.content.is_empty()
+    }
+
+    /// Plain text, with embeds as U+FFFC
+    pub fn text(&self) -> String {
+        self.content
+            .iter()
+            .map(|inline| match inline {
+                Inline::Text { text, .. } => text.as_str(),
+                Inline::Embed(_) => "\u{fffc}",
+            })
+            .collect()
+    }
+
+    /// Marks that text typed at `offset` picks up: those of the character
+    /// before it, or after it at the start of the block
+    ///
+    /// Links end at their boundaries, so typing after a link is unlinked.
+    pub fn marks_at(&self, offset: usize) -> Vec<Mark> {
+        let mut pos = 0;
+        for inline in &self.content {
+            let end = pos + inline.len();
+            if offset <= end && (offset > pos || offset == 0) {
+                let mut marks = inline.marks().to_vec();
+                if offset == end || offset == 0 {
+                    marks.retain(|m| !matches!(m, Mark::Link(_)));
+                }
+                return marks;
+            }
+            pos = end;
+        }
+        Vec::new()
+    }
+
+    /// Copy of the content in `range`
+    pub fn slice(&self, range: Range<usize>) -> Vec<Inline> {
+        let mut block = self.clone();
+        block.split_off(range.end);
+        block.split_off(range.start)
+    }
+
+    /// Split the inline at `offset` so a run boundary falls there; returns
+    /// the index of the first inline at or after `offset`
+    fn split_at(&mut self, offset: usize) -> usize {
+        let mut pos = 0;
+        for i in 0..self.content.len() {
+            if pos == offset {
+                return i;
+            }
+            let len = self.content[i].len();
+            if offset < pos + len {
+                if let Inline::Text { text, marks } = &mut self.content[i] {
+                    let byte = char_to_byte(text, offset - pos);
+                    let rest = text.split_off(byte);
+                    let marks = marks.clone();
+                    self.content
+                        .insert(i + 1, Inline::Text { text: rest, marks });
+                }
+                return i + 1;
+            }
+            pos += len;
+        }
+        self.content.len()
+    }
+
+    /// Remove and return the content from `offset` to the end
+    pub(crate) fn split_off(&mut self, offset: usize) -> Vec<Inline> {
+        let index = self.split_at(offset);
+        let tail = self.content.split_off(index);
+        self.normalize();
+        tail
+    }
+
+    pub(crate) fn insert(&mut self, offset: usize, content: Vec<Inline>) {
+        let index = self.split_at(offset);
+        self.content.splice(index..index, content);
+        self.normalize();
+    }
+
+    pub(crate) fn remove(&mut self, range: Range<usize>) -> Vec<Inline> {
+        // Split at the start first so splitting at the end can't shift it
+        let start = self.split_at(range.start);
+        let end = self.split_at(range.end);
+        let removed = self.content.drain(start..end).collect();
+        self.normalize();
+        removed
+    }
+
+    pub(crate) fn append(&mut self, content: Vec<Inline>) {
+        self.content.extend(content);
+        self.normalize();
+    }
+
+    /// Apply `f` to the marks of every text run in `range`
+    pub(crate) fn update_marks(&mut self, range: Range<usize>, mut f: impl FnMut(&mut Vec<Mark>)) {
+        // Split at the start first so splitting at the end can't shift it
+        let start = self.split_at(range.start);
+        let end = self.split_at(range.end);
+        for inline in &mut self.content[start..end] {
+            if let Inline::Text { marks, .. } = inline {
+                f(marks);
+            }
+        }
+        self.normalize();
+    }
+
+    /// Sub-ranges of `range` covering text runs, with each run's mark of the
+    /// same kind as `mark`
+    pub(crate) fn mark_runs(
+        &self,
+        range: Range<usize>,
+        mark: &Mark,
+    ) -> Vec<(Range<usize>, Option<Mark>)> {
+        let mut runs = Vec::new();
+        let mut pos = 0;
+        for inline in &self.content {
+            let len = inline.len();
+            let start = pos.max(range.start);
+            let end = (pos + len).min(range.end);
+            if start < end {
+                if let Inline::Text { marks, .. } = inline {
+                    let existing = marks.iter().find(|m| m.same_kind(mark)).cloned();
+                    runs.push((start..end, existing));
+                }
+            }
+            pos += len;
+        }
+        runs
+    }
+
+    /// Merge adjacent text runs with equal marks and drop empty ones
+    fn normalize(&mut self) {
+        let mut merged: Vec<Inline> = Vec::with_capacity(self.content.len());
+        for inline in self.content.drain(..) {
+            if inline.is_empty() {
+                continue;
+            }
+            if let (
+                Some(Inline::Text { text, marks }),
+                Inline::Text {
+                    text: next,
+                    marks: next_marks,
+                },
+            ) = (merged.last_mut(), &inline)
+            {
+                if marks == next_marks {
+                    text.push_str(next);
+                    continue;
+                }
+            }
+            merged.push(inline);
+        }
+        self.content = merged;
+    }
+}
+
+pub(crate) fn char_to_byte(text: &str, chars: usize) -> usize {
+    text.char_indices()
+        .nth(chars)
+        .map_or(text.len(), |(byte, _)| byte)
+}
+
+/// A position between two characters of a block
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct DocPosition {
+    /// Block index
+    pub block: usize,
+    /// Character offset within the block
+    pub offset: usize,
+}
+
+impl DocPosition {
+    pub fn new(block: usize, offset: usize) -> Self {
+        Self { block, offset }
+    }
+}
+
+/// A selection from `anchor` (where it started) to `head` (where the
+/// cursor is)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct Selection {
+    pub anchor: DocPosition,
+    pub head: DocPosition,
+}
+
+impl Selection {
+    /// A collapsed selection (a cursor)
+    pub fn cursor(at: DocPosition) -> Self {
+        Self {
+            anchor: at,
+            head: at,
+        }
+    }
+
+    pub fn new(anchor: DocPosition, head: DocPosition) -> Self {
+        Self { anchor, head }
+    }
+
+    /// The earlier end
+    pub fn from(&self) -> DocPosition {
+        self.anchor.min(self.head)
+    }
+
+    /// The later end
+    pub fn to(&self) -> DocPosition {
+        self.anchor.max(self.head)
+    }
+
+    /// Whether nothing is selected
+    pub fn is_collapsed(&self) -> bool {
+        self.anchor == self.head
+    }
+}
+
+/// A rich text document
+#[derive(Clone, Debug, PartialEq)]
+pub struct RichDocument {
+    blocks: Vec<Block>,
+}
+
+impl Default for RichDocument {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RichDocument {
+    /// An empty document: one empty paragraph
+    pub fn new() -> Self {
+        Self {
+            blocks: vec![Block::default()],
+        }
+    }
+
+    /// A document of the given blocks
+    pub fn from_blocks(blocks: Vec<Block>) -> Self {
+        if blocks.is_empty() {
+            Self::new()
+        } else {
+            Self { blocks }
+        }
+    }
+
+    /// A document of plain text, one paragraph per line
+    pub fn from_text(text: &str) -> Self {
+        Self::from_blocks(text.lines().map(Block::paragraph).collect())
+    }
+
+    pub fn blocks(&self) -> &[Block] {
+        &self.blocks
+    }
+
+    pub(crate) fn blocks_mut(&mut self) -> &mut Vec<Block> {
+        &mut self.blocks
+    }
+
+    pub fn block(&self, index: usize) -> Option<&Block> {
+        self.blocks.get(index)
+    }
+
+    /// Plain text with blocks separated by newlines
+    pub fn plain_text(&self) -> String {
+        self.blocks
+            .iter()
+            .map(Block::text)
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Whether the document is a single empty block
+    pub fn is_empty(&self) -> bool {
+        self.blocks.len() == 1 && self.blocks[0].is_empty()
+    }
+
+    /// The position after the last character
+    pub fn end(&self) -> DocPosition {
+        let last = self.blocks.len() - 1;
+        DocPosition::new(last, self.blocks[last].len())
+    }
+
+    /// Clamp a position into the document
+    pub fn clamp(&self, pos: DocPosition) -> DocPosition {
+        if pos.block >= self.blocks.len() {
+            return self.end();
+        }
+        DocPosition::new(pos.block, pos.offset.min(self.blocks[pos.block].len()))
+    }
+
+    /// The content between two positions as a document of its own
+    pub fn slice(&self, from: DocPosition, to: DocPosition) -> RichDocument {
+        let (from, to) = (self.clamp(from.min(to)), self.clamp(from.max(to)));
+        let blocks = (from.block..=to.block)
+            .map(|index| {
+                let block = &self.blocks[index];
+                let start = if index == from.block { from.offset } else { 0 };
+                let end = if index == to.block {
+                    to.offset
+                } else {
+                    block.len()
+                };
+                Block::new(block.kind.clone(), block.slice(start..end))
+            })
+            .collect();
+        Self::from_blocks(blocks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_edits_keep_runs_normalized() {
+        let mut block = Block::paragraph("hello world");
+        block.update_marks(0..5, |marks| add_mark(marks, Mark::Bold));
+        assert_eq!(block.content().len(), 2);
+
+        block.insert(5, vec![Inline::marked("!", vec![Mark::Bold])]);
+        assert_eq!(block.text(), "hello! world");
+        assert_eq!(
+            block.content()[0],
+            Inline::marked("hello!", vec![Mark::Bold])
+        );
+
+        let removed = block.remove(3..8);
+        assert_eq!(removed.len(), 2);
+        assert_eq!(block.text(), "helorld");
+
+        block.update_marks(0..7, |marks| remove_mark(marks, &Mark::Bold));
+        assert_eq!(block.content(), &[Inline::text("helorld")]);
+    }
+
+    #[test]
+    fn test_marks_at_excludes_link_boundaries() {
+        let block = Block::new(
+            BlockKind::Paragraph,
+            vec![
+                Inline::marked("link", vec![Mark::Link("u".into()), Mark::Bold]),
+                Inline::text(" after"),
+            ],
+        );
+        assert_eq!(block.marks_at(2), vec![Mark::Bold, Mark::Link("u".into())]);
+        assert_eq!(block.marks_at(4), vec![Mark::Bold]);
+        assert_eq!(block.marks_at(6), vec![]);
+    }
+
+    #[test]
+    fn test_slice_across_blocks() {
+        let doc = RichDocument::from_text("first line\nsecond line");
+        let slice = doc.slice(DocPosition::new(0, 6), DocPosition::new(1, 6));
+        assert_eq!(slice.plain_text(), "line\nsecond");
+    }
+}
@@ -43,7 +43,16 @@ use crate::styled_text::TextSpan;
 use crate::tree::{LayoutNodeId, LayoutTree};
 use crate::widgets::link::open_url;
 
+mod convert;
+mod document;
 mod parser;
+mod transaction;
+
+pub use document::{
+    Block, BlockKind, DocPosition, Embed, Inline, Mark, RichDocument, Selection,
+};
+pub(crate) use document::{add_mark, remove_mark};
+pub use transaction::{Bias, Operation, OperationError, Transaction};
 
 /// A clickable link region within rich text
 #[derive(Clone, Debug)]
@@ -624,6 +633,7 @@ impl ElementBuilder for RichText {
             pointer_events_none: false,
             cursor: self.cursor,
             layer_effects: Vec::new(),
+            layer_cache: junita_core::CachePolicy::None,
             motion_is_exiting: false,
         }
     }
@@ -441,7 +441,7 @@ impl<'a> Parser<'a> {
 }
 
 /// Parse a color string (hex, named, or rgba)
-fn parse_color(s: &str) -> Option<Color> {
+pub(super) fn parse_color(s: &str) -> Option<Color> {
     let s = s.trim();
 
     // Hex color
@@ -0,0 +1,570 @@
+//! Operations and transactions on rich text documents
+//!
+//! Every change to a [`RichDocument`] is an [`Operation`]; a
+//! [`Transaction`] groups the operations of one user action. Applying a
+//! transaction returns its inverse, which is what undo applies.
+//!
+//! Operations are plain data addressed by [`DocPosition`], so they can be
+//! sent to collaborators. A transaction made against an older version of a
+//! document is brought up to date with [`Transaction::rebase`], which maps
+//! its positions through the changes made since; cursors and selections
+//! are kept in place the same way with [`Transaction::map_position`].
+
+use std::fmt;
+use std::ops::Range;
+
+use super::document::{
+    add_mark, remove_mark, Block, BlockKind, DocPosition, Inline, Mark, RichDocument,
+};
+
+/// A single change to a document
+#[derive(Clone, Debug, PartialEq)]
+pub enum Operation {
+    /// Insert inline content at a position
+    Insert {
+        at: DocPosition,
+        content: Vec<Inline>,
+    },
+    /// Delete `len` characters from a position; clamped to the block's end
+    Delete { at: DocPosition, len: usize },
+    /// Add `mark` to, or remove marks of its kind from, text in a block;
+    /// the range is clamped to the block's end
+    Format {
+        block: usize,
+        range: Range<usize>,
+        mark: Mark,
+        add: bool,
+    },
+    /// Split a block at a position; the content after it becomes a new
+    /// block of `kind`
+    SplitBlock { at: DocPosition, kind: BlockKind },
+    /// Join `block` onto the end of the block before it, whose length is
+    /// `offset`
+    MergeBlock { block: usize, offset: usize },
+    /// Change a block's kind
+    SetBlockKind { block: usize, kind: BlockKind },
+}
+
+/// Error applying an operation to a document it doesn't fit
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OperationError {
+    /// The block index is past the end of the document
+    InvalidBlock(usize),
+    /// The offset is past the end of its block
+    InvalidPosition(DocPosition),
+    /// A merge's offset doesn't match the length of the block it joins
+    MergeMismatch { block: usize, offset: usize },
+}
+
+impl fmt::Display for OperationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OperationError::InvalidBlock(block) => write!(f, "no block {}", block),
+            OperationError::InvalidPosition(pos) => {
+                write!(
+                    f,
+                    "offset {} is past the end of block {}",
+                    pos.offset, pos.block
+                )
+            }
+            OperationError::MergeMismatch { block, offset } => write!(
+                f,
+                "cannot merge block {} at offset {}: previous block length differs",
+                block, offset
+            ),
+        }
+    }
+}
+
+impl std::error::Error for OperationError {}
+
+/// Which side of an insertion at exactly its position a mapped position
+/// ends up on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Bias {
+    /// Stay before the inserted content
+    Before,
+    /// Move after the inserted content
+    After,
+}
+
+/// How an operation moves positions
+#[derive(Clone, Copy, Debug)]
+enum StepMap {
+    Insert { at: DocPosition, len: usize },
+    Delete { at: DocPosition, len: usize },
+    Split { at: DocPosition },
+    Merge { block: usize, offset: usize },
+    Identity,
+}
+
+impl StepMap {
+    fn invert(self) -> StepMap {
+        match self {
+            StepMap::Insert { at, len } => StepMap::Delete { at, len },
+            StepMap::Delete { at, len } => StepMap::Insert { at, len },
+            StepMap::Split { at } => StepMap::Merge {
+                block: at.block + 1,
+                offset: at.offset,
+            },
+            StepMap::Merge { block, offset } => StepMap::Split {
+                at: DocPosition::new(block - 1, offset),
+            },
+            StepMap::Identity => StepMap::Identity,
+        }
+    }
+
+    fn map(self, pos: DocPosition, bias: Bias) -> DocPosition {
+        match self {
+            StepMap::Insert { at, len } => {
+                let moves =
+                    pos.offset > at.offset || (pos.offset == at.offset && bias == Bias::After);
+                if pos.block == at.block && moves {
+                    DocPosition::new(pos.block, pos.offset.saturating_add(len))
+                } else {
+                    pos
+                }
+            }
+            StepMap::Delete { at, len } if pos.block == at.block && pos.offset > at.offset => {
+                DocPosition::new(pos.block, pos.offset.saturating_sub(len).max(at.offset))
+            }
+            StepMap::Delete { .. } => pos,
+            StepMap::Split { at } => {
+                let moves =
+                    pos.offset > at.offset || (pos.offset == at.offset && bias == Bias::After);
+                if pos.block == at.block && moves {
+                    DocPosition::new(pos.block + 1, pos.offset - at.offset)
+                } else if pos.block > at.block {
+                    DocPosition::new(pos.block + 1, pos.offset)
+                } else {
+                    pos
+                }
+            }
+            StepMap::Merge { block, offset } => {
+                if pos.block == block {
+                    DocPosition::new(block - 1, offset + pos.offset)
+                } else if pos.block > block {
+                    DocPosition::new(pos.block - 1, pos.offset)
+                } else {
+                    pos
+                }
+            }
+            StepMap::Identity => pos,
+        }
+    }
+}
+
+impl Operation {
+    fn step_map(&self) -> StepMap {
+        match self {
+            Operation::Insert { at, content } => StepMap::Insert {
+                at: *at,
+                len: content.iter().map(Inline::len).sum(),
+            },
+            Operation::Delete { at, len } => StepMap::Delete { at: *at, len: *len },
+            Operation::SplitBlock { at, .. } => StepMap::Split { at: *at },
+            Operation::MergeBlock { block, offset } => StepMap::Merge {
+                block: *block,
+                offset: *offset,
+            },
+            Operation::Format { .. } | Operation::SetBlockKind { .. } => StepMap::Identity,
+        }
+    }
+
+    /// Map a position in the document before this operation to the
+    /// document after it
+    pub fn map_position(&self, pos: DocPosition, bias: Bias) -> DocPosition {
+        self.step_map().map(pos, bias)
+    }
+
+    /// Apply to `doc`, returning the operations that undo it
+    fn apply(&self, doc: &mut RichDocument) -> Result<Vec<Operation>, OperationError> {
+        let blocks = doc.blocks_mut();
+        let check = |blocks: &Vec<Block>, pos: DocPosition| match blocks.get(pos.block) {
+            None => Err(OperationError::InvalidBlock(pos.block)),
+            Some(block) if pos.offset > block.len() => Err(OperationError::InvalidPosition(pos)),
+            Some(_) => Ok(()),
+        };
+        match self {
+            Operation::Insert { at, content } => {
+                check(blocks, *at)?;
+                let len = content.iter().map(Inline::len).sum();
+                blocks[at.block].insert(at.offset, content.clone());
+                Ok(vec![Operation::Delete { at: *at, len }])
+            }
+            Operation::Delete { at, len } => {
+                check(blocks, *at)?;
+                let block = &mut blocks[at.block];
+                let end = at.offset.saturating_add(*len).min(block.len());
+                let content = block.remove(at.offset..end);
+                Ok(vec![Operation::Insert { at: *at, content }])
+            }
+            Operation::Format {
+                block,
+                range,
+                mark,
+                add,
+            } => {
+                check(blocks, DocPosition::new(*block, range.start))?;
+                let target = &mut blocks[*block];
+                let range = range.start..range.end.min(target.len());
+                // Restore each run's previous mark of this kind
+                let inverse = target
+                    .mark_runs(range.clone(), mark)
+                    .into_iter()
+                    .filter_map(|(range, existing)| {
+                        let (mark, add) = match existing {
+                            Some(existing) if *add && existing == *mark => return None,
+                            Some(existing) => (existing, true),
+                            None if *add => (mark.clone(), false),
+                            None => return None,
+                        };
+                        Some(Operation::Format {
+                            block: *block,
+                            range,
+                            mark,
+                            add,
+                        })
+                    })
+                    .collect();
+                target.update_marks(range, |marks| {
+                    if *add {
+                        add_mark(marks, mark.clone());
+                    } else {
+                        remove_mark(marks, mark);
+                    }
+                });
+                Ok(inverse)
+            }
+            Operation::SplitBlock { at, kind } => {
+                check(blocks, *at)?;
+                let tail = blocks[at.block].split_off(at.offset);
+                blocks.insert(at.block + 1, Block::new(kind.clone(), tail));
+                Ok(vec![Operation::MergeBlock {
+                    block: at.block + 1,
+                    offset: at.offset,
+                }])
+            }
+            Operation::MergeBlock { block, offset } => {
+                if *block == 0 || *block >= blocks.len() {
+                    return Err(OperationError::InvalidBlock(*block));
+                }
+                if blocks[block - 1].len() != *offset {
+                    return Err(OperationError::MergeMismatch {
+                        block: *block,
+                        offset: *offset,
+                    });
+                }
+                let merged = blocks.remove(*block);
+                let kind = merged.kind().clone();
+                blocks[block - 1].append(merged.content().to_vec());
+                Ok(vec![Operation::SplitBlock {
+                    at: DocPosition::new(block - 1, *offset),
+                    kind,
+                }])
+            }
+            Operation::SetBlockKind { block, kind } => {
+                let target = blocks
+                    .get_mut(*block)
+                    .ok_or(OperationError::InvalidBlock(*block))?;
+                let previous = target.set_kind(kind.clone());
+                Ok(vec![Operation::SetBlockKind {
+                    block: *block,
+                    kind: previous,
+                }])
+            }
+        }
+    }
+
+    /// This operation moved through `maps`, or `None` if what it applied
+    /// to no longer exists
+    fn map_through(&self, maps: &[StepMap]) -> Option<Operation> {
+        let map = |pos: DocPosition, bias: Bias| maps.iter().fold(pos, |pos, m| m.map(pos, bias));
+        // A range keeps the part in the block its start maps to
+        let map_range = |at: DocPosition, len: usize| {
+            let start = map(at, Bias::After);
+            let end = map(DocPosition::new(at.block, at.offset + len), Bias::Before);
+            let len = if end.block == start.block {
+                end.offset.saturating_sub(start.offset)
+            } else {
+                usize::MAX - start.offset
+            };
+            (len > 0).then_some((start, len))
+        };
+        match self {
+            Operation::Insert { at, content } => Some(Operation::Insert {
+                at: map(*at, Bias::After),
+                content: content.clone(),
+            }),
+            Operation::Delete { at, len } => {
+                let (at, len) = map_range(*at, *len)?;
+                Some(Operation::Delete { at, len })
+            }
+            Operation::Format {
+                block,
+                range,
+                mark,
+                add,
+            } => {
+                let (at, len) = map_range(DocPosition::new(*block, range.start), range.len())?;
+                Some(Operation::Format {
+                    block: at.block,
+                    range: at.offset..at.offset.saturating_add(len),
+                    mark: mark.clone(),
+                    add: *add,
+                })
+            }
+            Operation::SplitBlock { at, kind } => Some(Operation::SplitBlock {
+                at: map(*at, Bias::After),
+                kind: kind.clone(),
+            }),
+            Operation::MergeBlock { block, offset } => {
+                let start = map(DocPosition::new(*block, 0), Bias::Before);
+                let join = map(DocPosition::new(block - 1, *offset), Bias::After);
+                // The block was merged away or split apart concurrently
+                (start.offset == 0 && join.block + 1 == start.block).then_some(
+                    Operation::MergeBlock {
+                        block: start.block,
+                        offset: join.offset,
+                    },
+                )
+            }
+            Operation::SetBlockKind { block, kind } => {
+                let start = map(DocPosition::new(*block, 0), Bias::Before);
+                (start.offset == 0).then_some(Operation::SetBlockKind {
+                    block: start.block,
+                    kind: kind.clone(),
+                })
+            }
+        }
+    }
+}
+
+/// A group of operations applied together and undone together
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Transaction {
+    ops: Vec<Operation>,
+}
+
+impl Transaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A transaction of the given operations, applied in order
+    pub fn from_ops(ops: Vec<Operation>) -> Self {
+        Self { ops }
+    }
+
+    pub fn ops(&self) -> &[Operation] {
+        &self.ops
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+
+    /// Append an operation
+    pub fn push(&mut self, op: Operation) {
+        self.ops.push(op);
+    }
+
+    /// Insert unmarked text
+    pub fn insert_text(self, at: DocPosition, text: impl Into<String>) -> Self {
+        self.op(Operation::Insert {
+            at,
+            content: vec![Inline::text(text)],
+        })
+    }
+
+    /// Insert inline content
+    pub fn insert(self, at: DocPosition, content: Vec<Inline>) -> Self {
+        self.op(Operation::Insert { at, content })
+    }
+
+    /// Delete characters within a block
+    pub fn delete(self, at: DocPosition, len: usize) -> Self {
+        self.op(Operation::Delete { at, len })
+    }
+
+    /// Add a mark to a range of a block
+    pub fn add_mark(self, block: usize, range: Range<usize>, mark: Mark) -> Self {
+        self.op(Operation::Format {
+            block,
+            range,
+            mark,
+            add: true,
+        })
+    }
+
+    /// Remove marks of `mark`'s kind from a range of a block
+    pub fn remove_mark(self, block: usize, range: Range<usize>, mark: Mark) -> Self {
+        self.op(Operation::Format {
+            block,
+            range,
+            mark,
+            add: false,
+        })
+    }
+
+    /// Split a block
+    pub fn split(self, at: DocPosition, kind: BlockKind) -> Self {
+        self.op(Operation::SplitBlock { at, kind })
+    }
+
+    /// Join a block onto the previous one, whose length is `offset`
+    pub fn merge(self, block: usize, offset: usize) -> Self {
+        self.op(Operation::MergeBlock { block, offset })
+    }
+
+    /// Change a block's kind
+    pub fn set_block_kind(self, block: usize, kind: BlockKind) -> Self {
+        self.op(Operation::SetBlockKind { block, kind })
+    }
+
+    fn op(mut self, op: Operation) -> Self {
+        self.ops.push(op);
+        self
+    }
+
+    /// Map a position in the document before this transaction to the
+    /// document after it
+    pub fn map_position(&self, pos: DocPosition, bias: Bias) -> DocPosition {
+        self.ops
+            .iter()
+            .fold(pos, |pos, op| op.map_position(pos, bias))
+    }
+
+    /// Rewrite this transaction, made against the same document as `over`,
+    /// to apply after `over`
+    ///
+    /// Concurrent insertions at the same position put this transaction's
+    /// content after `over`'s. Ranges split apart by `over` keep their
+    /// first part, and changes to blocks `over` merged away are dropped.
+    pub fn rebase(&self, over: &Transaction) -> Transaction {
+        let own: Vec<StepMap> = self.ops.iter().map(Operation::step_map).collect();
+        let over_maps: Vec<StepMap> = over.ops.iter().map(Operation::step_map).collect();
+        let mut rebased: Vec<Operation> = Vec::new();
+        for (i, op) in self.ops.iter().enumerate() {
+            // Back to the shared base, across `over`, then forward through
+            // what has been rebased so far
+            let mut maps: Vec<StepMap> = own[..i].iter().rev().map(|m| m.invert()).collect();
+            maps.extend(over_maps.iter().copied());
+            maps.extend(rebased.iter().map(Operation::step_map));
+            if let Some(op) = op.map_through(&maps) {
+                rebased.push(op);
+            }
+        }
+        Transaction { ops: rebased }
+    }
+}
+
+impl RichDocument {
+    /// Apply a transaction, returning its inverse
+    ///
+    /// If an operation fails, the ones before it are rolled back and the
+    /// document is left unchanged.
+    pub fn apply(&mut self, transaction: &Transaction) -> Result<Transaction, OperationError> {
+        let mut inverse: Vec<Operation> = Vec::new();
+        for op in &transaction.ops {
+            match op.apply(self) {
+                Ok(undo) => inverse.splice(0..0, undo),
+                Err(err) => {
+                    for undo in &inverse {
+                        let _ = undo.apply(self);
+                    }
+                    return Err(err);
+                }
+            };
+        }
+        Ok(Transaction { ops: inverse })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pos(block: usize, offset: usize) -> DocPosition {
+        DocPosition::new(block, offset)
+    }
+
+    #[test]
+    fn test_apply_and_invert() {
+        let mut doc = RichDocument::from_text("hello world");
+        let original = doc.clone();
+        let tx = Transaction::new()
+            .add_mark(0, 0..5, Mark::Bold)
+            .split(pos(0, 5), BlockKind::Paragraph)
+            .delete(pos(1, 0), 1)
+            .insert_text(pos(1, 5), "!")
+            .set_block_kind(0, BlockKind::Heading(1));
+        let inverse = doc.apply(&tx).unwrap();
+        assert_eq!(doc.plain_text(), "hello\nworld!");
+        assert_eq!(doc.blocks()[0].kind(), &BlockKind::Heading(1));
+        assert_eq!(doc.blocks()[0].content()[0].marks(), &[Mark::Bold]);
+
+        doc.apply(&inverse).unwrap();
+        assert_eq!(doc, original);
+    }
+
+    #[test]
+    fn test_format_inverse_restores_previous_values() {
+        let mut doc = RichDocument::from_text("abcdef");
+        doc.apply(&Transaction::new().add_mark(0, 0..3, Mark::Link("a".into())))
+            .unwrap();
+        let before = doc.clone();
+        let inverse = doc
+            .apply(&Transaction::new().add_mark(0, 0..6, Mark::Link("b".into())))
+            .unwrap();
+        doc.apply(&inverse).unwrap();
+        assert_eq!(doc, before);
+    }
+
+    #[test]
+    fn test_failed_transaction_rolls_back() {
+        let mut doc = RichDocument::from_text("abc");
+        let tx = Transaction::new()
+            .insert_text(pos(0, 3), "d")
+            .delete(pos(4, 0), 1);
+        assert_eq!(doc.apply(&tx), Err(OperationError::InvalidBlock(4)));
+        assert_eq!(doc.plain_text(), "abc");
+    }
+
+    #[test]
+    fn test_rebase_concurrent_edits() {
+        let base = RichDocument::from_text("one two");
+
+        // Alice splits the block before "two"; Bob bolds "two" and types
+        // at the end
+        let alice = Transaction::new().split(pos(0, 4), BlockKind::Paragraph);
+        let bob = Transaction::new()
+            .add_mark(0, 4..7, Mark::Bold)
+            .insert_text(pos(0, 7), "!");
+
+        let mut doc = base.clone();
+        doc.apply(&alice).unwrap();
+        doc.apply(&bob.rebase(&alice)).unwrap();
+        assert_eq!(doc.plain_text(), "one \ntwo!");
+        assert_eq!(
+            doc.blocks()[1].content()[0],
+            Inline::marked("two", vec![Mark::Bold])
+        );
+
+        // Applying in the other order converges
+        let mut other = base;
+        other.apply(&bob).unwrap();
+        other.apply(&alice.rebase(&bob)).unwrap();
+        assert_eq!(other, doc);
+    }
+
+    #[test]
+    fn test_map_position() {
+        let tx = Transaction::new()
+            .insert_text(pos(0, 0), "ab")
+            .split(pos(0, 1), BlockKind::Paragraph);
+        assert_eq!(tx.map_position(pos(0, 0), Bias::Before), pos(0, 0));
+        assert_eq!(tx.map_position(pos(0, 0), Bias::After), pos(1, 1));
+        assert_eq!(tx.map_position(pos(1, 3), Bias::After), pos(2, 3));
+    }
+}
@@ -0,0 +1,244 @@
+//! Where style values come from
+//!
+//! The debugger shows next to each computed style value whether a builder
+//! call, a theme token, a stylesheet rule or a live edit set it. Origins are
+//! recorded as styles are applied: elements report builder and theme values
+//! when their render node is built or updated (see
+//! [`ElementBuilder::style_origins`](crate::div::ElementBuilder::style_origins)),
+//! and the render tree notes each stylesheet rule and live edit it applies.
+//! Recording only happens with the `recorder` feature.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use junita_core::Brush;
+use taffy::prelude::*;
+
+use crate::element::RenderProps;
+#[cfg(feature = "recorder")]
+use crate::element_style::ElementStyle;
+
+/// What set a style value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StyleOrigin {
+    /// A builder call on the element, e.g. `.bg()` or `.w()`
+    Builder,
+    /// A theme token, e.g. `space-4` for `.p_4()` or `primary` for `.bg_primary()`
+    Theme(&'static str),
+    /// A stylesheet rule, keyed like the stylesheet (`card` or `card:hover`)
+    Rule(String),
+    /// A per-element edit from a live debugging session
+    LiveEdit,
+}
+
+impl fmt::Display for StyleOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StyleOrigin::Builder => write!(f, "builder"),
+            StyleOrigin::Theme(token) => write!(f, "theme({})", token),
+            StyleOrigin::Rule(key) => write!(f, "#{}", key),
+            StyleOrigin::LiveEdit => write!(f, "live edit"),
+        }
+    }
+}
+
+/// Origins of a node's style values, keyed like [`style_values`]
+pub type StyleOrigins = BTreeMap<&'static str, StyleOrigin>;
+
+/// A value an element took from a theme token
+///
+/// `value` is the formatted value at the time, so a later builder call that
+/// overwrites the property also overrides the theme origin.
+#[derive(Clone, Debug)]
+#[cfg_attr(not(feature = "recorder"), allow(dead_code))]
+pub(crate) struct ThemeValue {
+    pub property: &'static str,
+    pub token: &'static str,
+    pub value: String,
+}
+
+/// Origins recorded for one node, one layer per way a style is applied
+#[derive(Clone, Debug, Default)]
+pub(crate) struct NodeStyleOrigins {
+    /// Reported by the element when its render node was built
+    pub element: StyleOrigins,
+    /// Stylesheet rules applied for the node's current state
+    pub rules: StyleOrigins,
+    /// Live edits, reapplied every frame on top of everything else
+    pub edits: StyleOrigins,
+}
+
+impl NodeStyleOrigins {
+    /// Merge the layers in the order they are applied
+    pub fn resolve(&self) -> StyleOrigins {
+        let mut origins = self.element.clone();
+        origins.extend(self.rules.clone());
+        origins.extend(self.edits.clone());
+        origins
+    }
+}
+
+/// Style values as CSS property/value pairs
+///
+/// Covers the visual render props and, when `layout` is given, the layout
+/// style: size, padding, margin, flex and gap.
+pub fn style_values(props: &RenderProps, layout: Option<&Style>) -> BTreeMap<&'static str, String> {
+    let mut values = BTreeMap::new();
+
+    if let Some(background) = &props.background {
+        values.insert("background", format_brush(background));
+    }
+    let r = props.border_radius;
+    values.insert(
+        "border-radius",
+        if r.top_left == r.top_right && r.top_left == r.bottom_right && r.top_left == r.bottom_left
+        {
+            format!("{}px", r.top_left)
+        } else {
+            format!(
+                "{}px {}px {}px {}px",
+                r.top_left, r.top_right, r.bottom_right, r.bottom_left
+            )
+        },
+    );
+    if props.border_width > 0.0 {
+        values.insert("border-width", format!("{}px", props.border_width));
+    }
+    if let Some(color) = props.border_color {
+        values.insert("border-color", format_color(color.to_array()));
+    }
+    values.insert("opacity", props.opacity.to_string());
+    if let Some(shadow) = &props.shadow {
+        values.insert("box-shadow", format!("{:?}", shadow));
+    }
+    if let Some(transform) = &props.transform {
+        values.insert("transform", format!("{:?}", transform));
+    }
+    values.insert("render-layer", format!("{:?}", props.layer).to_lowercase());
+    if props.clips_content {
+        values.insert("overflow", "hidden".to_string());
+    }
+    if props.pointer_events_none {
+        values.insert("pointer-events", "none".to_string());
+    }
+
+    if let Some(style) = layout {
+        values.insert("width", format_dimension(style.size.width));
+        values.insert("height", format_dimension(style.size.height));
+        values.insert(
+            "padding",
+            format_edges(style.padding.map(LengthPercentageAuto::from)),
+        );
+        values.insert("margin", format_edges(style.margin));
+        values.insert(
+            "flex-direction",
+            format!("{:?}", style.flex_direction).to_lowercase(),
+        );
+        values.insert("flex-grow", style.flex_grow.to_string());
+        values.insert("flex-shrink", style.flex_shrink.to_string());
+        values.insert("flex-basis", format_dimension(style.flex_basis));
+        let (row, column) = (
+            format_length(style.gap.height.into()),
+            format_length(style.gap.width.into()),
+        );
+        values.insert(
+            "gap",
+            if row == column {
+                row
+            } else {
+                format!("{} {}", row, column)
+            },
+        );
+    }
+
+    values
+}
+
+/// Builder origins for every value that differs from an unstyled element
+#[cfg(feature = "recorder")]
+pub(crate) fn builder_origins(values: &BTreeMap<&'static str, String>) -> StyleOrigins {
+    let defaults = style_values(&RenderProps::default(), Some(&Style::default()));
+    values
+        .iter()
+        .filter(|(name, value)| defaults.get(*name) != Some(*value))
+        .map(|(name, _)| (*name, StyleOrigin::Builder))
+        .collect()
+}
+
+/// [`style_values`] properties an element style sets
+#[cfg(feature = "recorder")]
+pub(crate) fn element_style_properties(style: &ElementStyle) -> Vec<&'static str> {
+    let mut names = Vec::new();
+    if style.background.is_some() {
+        names.push("background");
+    }
+    if style.corner_radius.is_some() {
+        names.push("border-radius");
+    }
+    if style.shadow.is_some() {
+        names.push("box-shadow");
+    }
+    if style.transform.is_some() {
+        names.push("transform");
+    }
+    if style.opacity.is_some() {
+        names.push("opacity");
+    }
+    if style.render_layer.is_some() {
+        names.push("render-layer");
+    }
+    names
+}
+
+/// Origins for the properties `style` sets, all credited to `origin`
+#[cfg(feature = "recorder")]
+pub(crate) fn element_style_origins(style: &ElementStyle, origin: StyleOrigin) -> StyleOrigins {
+    element_style_properties(style)
+        .into_iter()
+        .map(|name| (name, origin.clone()))
+        .collect()
+}
+
+fn format_color([r, g, b, a]: [f32; 4]) -> String {
+    format!(
+        "rgba({}, {}, {}, {})",
+        (r * 255.0).round() as u8,
+        (g * 255.0).round() as u8,
+        (b * 255.0).round() as u8,
+        a
+    )
+}
+
+fn format_brush(brush: &Brush) -> String {
+    match brush {
+        Brush::Solid(c) => format_color(c.to_array()),
+        other => format!("{:?}", other),
+    }
+}
+
+fn format_length(length: LengthPercentageAuto) -> String {
+    match length {
+        LengthPercentageAuto::Length(px) => format!("{}px", px),
+        LengthPercentageAuto::Percent(fraction) => format!("{}%", fraction * 100.0),
+        LengthPercentageAuto::Auto => "auto".to_string(),
+    }
+}
+
+fn format_dimension(dimension: Dimension) -> String {
+    match dimension {
+        Dimension::Length(px) => format_length(LengthPercentageAuto::Length(px)),
+        Dimension::Percent(fraction) => format_length(LengthPercentageAuto::Percent(fraction)),
+        Dimension::Auto => format_length(LengthPercentageAuto::Auto),
+    }
+}
+
+/// CSS shorthand order: top right bottom left, collapsed when all equal
+fn format_edges(edges: Rect<LengthPercentageAuto>) -> String {
+    let [top, right, bottom, left] =
+        [edges.top, edges.right, edges.bottom, edges.left].map(format_length);
+    if top == right && top == bottom && top == left {
+        top
+    } else {
+        format!("{} {} {} {}", top, right, bottom, left)
+    }
+}
@@ -0,0 +1,606 @@
+//! TextMate grammars
+//!
+//! Grammars are loaded from the JSON form of the TextMate format (the
+//! `.tmLanguage.json` files VS Code uses). Supported rule keys are `match`,
+//! `begin`/`end`, `while` (treated as ending at the end of its first line),
+//! `name`, `contentName`, `captures`, `beginCaptures`, `endCaptures`,
+//! `patterns`, `include` and `applyEndPatternLast`. Includes may point at
+//! the repository (`#name`), the grammar itself (`$self`), the grammar being
+//! highlighted (`$base`) or another grammar in the same [`GrammarSet`]
+//! (`source.js` or `source.js#expression`), which is how embedded languages
+//! work.
+//!
+//! Patterns are compiled with `fancy-regex`, which covers the Oniguruma
+//! syntax grammars rely on: lookahead, lookbehind, backreferences, atomic
+//! groups, possessive quantifiers, `\G` and `\h`. `\1`..`\9` in an `end`
+//! pattern refer to the `begin` match as in TextMate. The remaining
+//! Oniguruma-only constructs, such as the absent operator `(?~...)`, are not
+//! supported; rules using them never match and are listed by
+//! [`Grammar::unsupported_patterns`].
+//!
+//! Sublime Text's `.sublime-syntax` format is out of scope: its context
+//! stack (`push`, `pop`, `set`) has no TextMate equivalent. Most languages
+//! also publish a `.tmLanguage` grammar, which can be converted to JSON.
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use fancy_regex::{Captures as Groups, Regex};
+use serde_json::{Map, Value};
+
+/// Grammars that ship with the crate
+const BUNDLED: &[&str] = &[
+    include_str!("grammars/rust.tmLanguage.json"),
+    include_str!("grammars/json.tmLanguage.json"),
+    include_str!("grammars/javascript.tmLanguage.json"),
+    include_str!("grammars/typescript.tmLanguage.json"),
+    include_str!("grammars/python.tmLanguage.json"),
+    include_str!("grammars/c.tmLanguage.json"),
+    include_str!("grammars/toml.tmLanguage.json"),
+    include_str!("grammars/yaml.tmLanguage.json"),
+    include_str!("grammars/shell.tmLanguage.json"),
+    include_str!("grammars/css.tmLanguage.json"),
+    include_str!("grammars/html.tmLanguage.json"),
+];
+
+/// Error loading a grammar
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrammarError {
+    /// Where in the grammar the error is, e.g. `repository.strings.begin`
+    pub path: String,
+    /// What is wrong
+    pub message: String,
+}
+
+impl GrammarError {
+    fn new(path: &str, message: impl Into<String>) -> Self {
+        Self {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for GrammarError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "invalid grammar: {}", self.message)
+        } else {
+            write!(f, "invalid grammar at `{}`: {}", self.path, self.message)
+        }
+    }
+}
+
+impl std::error::Error for GrammarError {}
+
+/// Scopes for numbered capture groups
+pub(crate) type Captures = Vec<(usize, String)>;
+
+/// Reference to a rule in a [`GrammarSet`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct RuleId {
+    pub grammar: usize,
+    pub rule: usize,
+}
+
+/// Target of an `include`
+#[derive(Clone, Debug)]
+pub(crate) enum Include {
+    /// A rule in the same grammar
+    Rule(usize),
+    /// `$self`
+    SelfRoot,
+    /// `$base`
+    Base,
+    /// Another grammar by scope name, optionally one of its repository rules
+    Grammar(String, Option<String>),
+}
+
+/// The `end` of a begin/end rule
+#[derive(Clone, Debug)]
+pub(crate) enum EndPattern {
+    Fixed(Arc<Regex>),
+    /// Refers to `begin` captures, compiled once they are known
+    BackRef(String),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) enum RuleKind {
+    Match {
+        regex: Option<Regex>,
+        captures: Captures,
+    },
+    BeginEnd {
+        begin: Option<Regex>,
+        begin_captures: Captures,
+        end: EndPattern,
+        end_captures: Captures,
+        patterns: Vec<Include>,
+        end_last: bool,
+    },
+    /// Only `patterns`, as repository entries grouping other rules often are
+    Patterns(Vec<Include>),
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Rule {
+    pub kind: RuleKind,
+    pub scope: Option<String>,
+    pub content_scope: Option<String>,
+}
+
+/// A compiled TextMate grammar
+#[derive(Clone, Debug)]
+pub struct Grammar {
+    name: String,
+    scope_name: String,
+    file_types: Vec<String>,
+    pub(crate) patterns: Vec<Include>,
+    pub(crate) rules: Vec<Rule>,
+    pub(crate) repository: HashMap<String, usize>,
+    unsupported: Vec<String>,
+}
+
+impl Grammar {
+    /// Load a grammar from TextMate JSON
+    pub fn from_json(json: &str) -> Result<Self, GrammarError> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|err| GrammarError::new("", err.to_string()))?;
+        let root = value
+            .as_object()
+            .ok_or_else(|| GrammarError::new("", "expected an object"))?;
+        let scope_name = string_field(root, "scopeName", "")?
+            .ok_or_else(|| GrammarError::new("scopeName", "missing"))?;
+        let name = string_field(root, "name", "")?.unwrap_or_else(|| scope_name.clone());
+        let file_types = match root.get("fileTypes") {
+            None => Vec::new(),
+            Some(Value::Array(items)) => items
+                .iter()
+                .filter_map(|item| item.as_str().map(str::to_string))
+                .collect(),
+            Some(_) => return Err(GrammarError::new("fileTypes", "expected an array")),
+        };
+
+        let mut grammar = Grammar {
+            name,
+            scope_name,
+            file_types,
+            patterns: Vec::new(),
+            rules: Vec::new(),
+            repository: HashMap::new(),
+            unsupported: Vec::new(),
+        };
+
+        // Reserve repository slots first so rules can include each other
+        let repository = match root.get("repository") {
+            None => Map::new(),
+            Some(Value::Object(map)) => map.clone(),
+            Some(_) => return Err(GrammarError::new("repository", "expected an object")),
+        };
+        for name in repository.keys() {
+            let index = grammar.push_placeholder();
+            grammar.repository.insert(name.clone(), index);
+        }
+        for (name, rule) in &repository {
+            let index = grammar.repository[name];
+            let path = format!("repository.{}", name);
+            grammar.rules[index] = grammar.compile_rule(rule, &path)?;
+        }
+        grammar.patterns = grammar.compile_patterns(root, "")?;
+        Ok(grammar)
+    }
+
+    /// Display name, e.g. `Rust`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Root scope, e.g. `source.rust`
+    pub fn scope_name(&self) -> &str {
+        &self.scope_name
+    }
+
+    /// File extensions and language names the grammar is used for
+    pub fn file_types(&self) -> &[String] {
+        &self.file_types
+    }
+
+    /// Patterns the `regex` crate couldn't compile, as `path: pattern`
+    pub fn unsupported_patterns(&self) -> &[String] {
+        &self.unsupported
+    }
+
+    /// Whether the grammar is for `language`, a name, extension or the last
+    /// part of the scope name (`rust` for `source.rust`)
+    pub fn matches(&self, language: &str) -> bool {
+        let language = language.trim().trim_start_matches('.');
+        !language.is_empty()
+            && (self.name.eq_ignore_ascii_case(language)
+                || self
+                    .file_types
+                    .iter()
+                    .any(|ty| ty.eq_ignore_ascii_case(language))
+                || self
+                    .scope_name
+                    .rsplit('.')
+                    .next()
+                    .is_some_and(|last| last.eq_ignore_ascii_case(language)))
+    }
+
+    fn push_placeholder(&mut self) -> usize {
+        self.rules.push(Rule {
+            kind: RuleKind::Patterns(Vec::new()),
+            scope: None,
+            content_scope: None,
+        });
+        self.rules.len() - 1
+    }
+
+    fn regex(&mut self, pattern: &str, path: &str) -> Option<Regex> {
+        let regex = compile(pattern);
+        if regex.is_none() {
+            self.unsupported.push(format!("{}: {}", path, pattern));
+        }
+        regex
+    }
+
+    fn compile_patterns(
+        &mut self,
+        object: &Map<String, Value>,
+        path: &str,
+    ) -> Result<Vec<Include>, GrammarError> {
+        let Some(patterns) = object.get("patterns") else {
+            return Ok(Vec::new());
+        };
+        let path = join(path, "patterns");
+        let items = patterns
+            .as_array()
+            .ok_or_else(|| GrammarError::new(&path, "expected an array"))?;
+        items
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let path = format!("{}[{}]", path, i);
+                let object = item
+                    .as_object()
+                    .ok_or_else(|| GrammarError::new(&path, "expected an object"))?;
+                match object.get("include") {
+                    Some(include) => {
+                        let target = include
+                            .as_str()
+                            .ok_or_else(|| GrammarError::new(&path, "expected a string"))?;
+                        self.include(target, &path)
+                    }
+                    None => {
+                        let rule = self.compile_rule(item, &path)?;
+                        self.rules.push(rule);
+                        Ok(Include::Rule(self.rules.len() - 1))
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn include(&self, target: &str, path: &str) -> Result<Include, GrammarError> {
+        match target {
+            "$self" => Ok(Include::SelfRoot),
+            "$base" => Ok(Include::Base),
+            _ => match target.strip_prefix('#') {
+                Some(name) => self
+                    .repository
+                    .get(name)
+                    .map(|&index| Include::Rule(index))
+                    .ok_or_else(|| GrammarError::new(path, format!("no rule `{}`", name))),
+                None => {
+                    let (scope, rule) = match target.split_once('#') {
+                        Some((scope, rule)) => (scope, Some(rule.to_string())),
+                        None => (target, None),
+                    };
+                    Ok(Include::Grammar(scope.to_string(), rule))
+                }
+            },
+        }
+    }
+
+    fn compile_rule(&mut self, value: &Value, path: &str) -> Result<Rule, GrammarError> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| GrammarError::new(path, "expected an object"))?;
+        let scope = string_field(object, "name", path)?;
+        let content_scope = string_field(object, "contentName", path)?;
+
+        if let Some(include) = string_field(object, "include", path)? {
+            let include = self.include(&include, path)?;
+            return Ok(Rule {
+                kind: RuleKind::Patterns(vec![include]),
+                scope,
+                content_scope,
+            });
+        }
+
+        if let Some(pattern) = string_field(object, "match", path)? {
+            let regex = self.regex(&pattern, &join(path, "match"));
+            let captures = captures(object, "captures", path)?;
+            return Ok(Rule {
+                kind: RuleKind::Match { regex, captures },
+                scope,
+                content_scope,
+            });
+        }
+
+        if let Some(pattern) = string_field(object, "begin", path)? {
+            let begin = self.regex(&pattern, &join(path, "begin"));
+            let end = match string_field(object, "end", path)? {
+                Some(end) => end,
+                None if object.contains_key("while") => "$".to_string(),
+                None => return Err(GrammarError::new(path, "`begin` without `end`")),
+            };
+            let end = if has_backrefs(&end) {
+                EndPattern::BackRef(end)
+            } else {
+                match self.regex(&end, &join(path, "end")) {
+                    Some(regex) => EndPattern::Fixed(Arc::new(regex)),
+                    // An end that never matches keeps the rule open to the
+                    // end of the document, so fall back to the end of line
+                    None => EndPattern::Fixed(Arc::new(compile("$").unwrap())),
+                }
+            };
+            let shared = captures(object, "captures", path)?;
+            let mut begin_captures = captures(object, "beginCaptures", path)?;
+            let mut end_captures = captures(object, "endCaptures", path)?;
+            if begin_captures.is_empty() {
+                begin_captures = shared.clone();
+            }
+            if end_captures.is_empty() {
+                end_captures = shared;
+            }
+            let end_last = match object.get("applyEndPatternLast") {
+                Some(Value::Bool(flag)) => *flag,
+                Some(Value::Number(n)) => n.as_u64() == Some(1),
+                _ => false,
+            };
+            let patterns = self.compile_patterns(object, path)?;
+            return Ok(Rule {
+                kind: RuleKind::BeginEnd {
+                    begin,
+                    begin_captures,
+                    end,
+                    end_captures,
+                    patterns,
+                    end_last,
+                },
+                scope,
+                content_scope,
+            });
+        }
+
+        let patterns = self.compile_patterns(object, path)?;
+        Ok(Rule {
+            kind: RuleKind::Patterns(patterns),
+            scope,
+            content_scope,
+        })
+    }
+}
+
+/// A set of grammars that can include each other
+#[derive(Clone, Debug, Default)]
+pub struct GrammarSet {
+    grammars: Vec<Grammar>,
+}
+
+impl GrammarSet {
+    /// Create an empty set
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The grammars bundled with the crate
+    ///
+    /// Rust, JSON, JavaScript, TypeScript, Python, C, TOML, YAML, shell, CSS
+    /// and HTML (with embedded JavaScript and CSS).
+    pub fn bundled() -> Arc<GrammarSet> {
+        static BUNDLED_SET: OnceLock<Arc<GrammarSet>> = OnceLock::new();
+        BUNDLED_SET
+            .get_or_init(|| {
+                let mut set = GrammarSet::new();
+                for json in BUNDLED {
+                    set.add_json(json).expect("bundled grammar is valid");
+                }
+                Arc::new(set)
+            })
+            .clone()
+    }
+
+    /// Add a grammar, replacing any with the same scope name
+    pub fn add(&mut self, grammar: Grammar) {
+        match self.index_of(grammar.scope_name()) {
+            Some(index) => self.grammars[index] = grammar,
+            None => self.grammars.push(grammar),
+        }
+    }
+
+    /// Load a grammar from TextMate JSON and add it
+    pub fn add_json(&mut self, json: &str) -> Result<(), GrammarError> {
+        self.add(Grammar::from_json(json)?);
+        Ok(())
+    }
+
+    /// Find the grammar for a language name or file extension, as found in
+    /// a fenced code block's info string
+    pub fn find(&self, language: &str) -> Option<&Grammar> {
+        self.grammars.iter().find(|g| g.matches(language))
+    }
+
+    /// Get a grammar by scope name
+    pub fn grammar(&self, scope_name: &str) -> Option<&Grammar> {
+        self.index_of(scope_name).map(|index| &self.grammars[index])
+    }
+
+    /// All grammars in the set
+    pub fn grammars(&self) -> impl Iterator<Item = &Grammar> {
+        self.grammars.iter()
+    }
+
+    pub(crate) fn index_of(&self, scope_name: &str) -> Option<usize> {
+        self.grammars
+            .iter()
+            .position(|g| g.scope_name == scope_name)
+    }
+
+    pub(crate) fn get(&self, index: usize) -> &Grammar {
+        &self.grammars[index]
+    }
+
+    pub(crate) fn rule(&self, id: RuleId) -> &Rule {
+        &self.grammars[id.grammar].rules[id.rule]
+    }
+}
+
+/// Compile a pattern the way grammars expect: `^` and `$` match at line
+/// boundaries, since each line is matched with its trailing newline
+pub(crate) fn compile(pattern: &str) -> Option<Regex> {
+    Regex::new(&format!("(?m){}", pattern)).ok()
+}
+
+/// Search for `regex` in `haystack` from byte offset `pos`
+///
+/// Text before `pos` is still visible to lookbehind. A pattern that hits the
+/// backtracking limit is treated as not matching.
+pub(crate) fn captures_at<'h>(regex: &Regex, haystack: &'h str, pos: usize) -> Option<Groups<'h>> {
+    regex.captures_from_pos(haystack, pos).ok().flatten()
+}
+
+fn has_backrefs(pattern: &str) -> bool {
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' && chars.next().is_some_and(|next| next.is_ascii_digit()) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Replace `\1`..`\9` in `pattern` with the escaped text of those groups
+pub(crate) fn substitute_backrefs(pattern: &str, groups: &Groups) -> String {
+    let mut out = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(digit @ '0'..='9') => {
+                let index = digit as usize - '0' as usize;
+                if let Some(group) = groups.get(index) {
+                    out.push_str(&fancy_regex::escape(group.as_str()));
+                }
+            }
+            Some(next) => {
+                out.push('\\');
+                out.push(next);
+            }
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", path, key)
+    }
+}
+
+fn string_field(
+    object: &Map<String, Value>,
+    key: &str,
+    path: &str,
+) -> Result<Option<String>, GrammarError> {
+    match object.get(key) {
+        None => Ok(None),
+        Some(Value::String(s)) => Ok(Some(s.clone())),
+        Some(_) => Err(GrammarError::new(&join(path, key), "expected a string")),
+    }
+}
+
+fn captures(object: &Map<String, Value>, key: &str, path: &str) -> Result<Captures, GrammarError> {
+    let Some(value) = object.get(key) else {
+        return Ok(Vec::new());
+    };
+    let path = join(path, key);
+    let map = value
+        .as_object()
+        .ok_or_else(|| GrammarError::new(&path, "expected an object"))?;
+    let mut captures = Vec::new();
+    for (group, capture) in map {
+        let index = group
+            .parse()
+            .map_err(|_| GrammarError::new(&path, format!("`{}` is not a group number", group)))?;
+        if let Some(scope) = capture.get("name").and_then(Value::as_str) {
+            captures.push((index, scope.to_string()));
+        }
+    }
+    captures.sort_by_key(|(index, _)| *index);
+    Ok(captures)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_grammars_compile() {
+        let set = GrammarSet::bundled();
+        assert!(set.grammars().count() >= 11);
+        for grammar in set.grammars() {
+            assert!(
+                grammar.unsupported_patterns().is_empty(),
+                "{}: {:?}",
+                grammar.name(),
+                grammar.unsupported_patterns()
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_by_info_string() {
+        let set = GrammarSet::bundled();
+        assert_eq!(set.find("rust").unwrap().scope_name(), "source.rust");
+        assert_eq!(set.find("rs").unwrap().scope_name(), "source.rust");
+        assert_eq!(set.find("JS").unwrap().scope_name(), "source.js");
+        assert_eq!(set.find("sh").unwrap().scope_name(), "source.shell");
+        assert!(set.find("brainfuck").is_none());
+        assert!(set.find("").is_none());
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = Grammar::from_json(r#"{"name": "x"}"#).unwrap_err();
+        assert_eq!(err.path, "scopeName");
+
+        let err = Grammar::from_json(
+            r##"{"scopeName": "source.x", "patterns": [{"include": "#missing"}]}"##,
+        )
+        .unwrap_err();
+        assert_eq!(err.path, "patterns[0]");
+
+        let grammar =
+            Grammar::from_json(r#"{"scopeName": "source.x", "patterns": [{"match": "(?~ab)"}]}"#)
+                .unwrap();
+        assert_eq!(grammar.unsupported_patterns().len(), 1);
+    }
+
+    #[test]
+    fn test_substitute_backrefs() {
+        let begin = compile(r##"r(#*)""##).unwrap();
+        let groups = captures_at(&begin, r###"r##"raw"##"###, 0).unwrap();
+        assert!(has_backrefs(r#""\1"#));
+        let end = compile(&substitute_backrefs(r#""\1"#, &groups)).unwrap();
+        assert!(end.is_match(r###""##"###).unwrap());
+        assert!(!end.is_match("\"#").unwrap());
+    }
+}
@@ -0,0 +1,813 @@
+//! Grammar-driven highlighting
+//!
+//! [`GrammarHighlighter`] tokenizes line by line, carrying the stack of open
+//! begin/end rules from one line to the next. Each line is cached with the
+//! stack it started from, so after an edit only lines from the first change
+//! onwards are tokenized again, and only until the stack at the start of a
+//! line matches what was cached for it.
+
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+use fancy_regex::{Captures as Groups, Regex};
+use junita_core::Color;
+
+use super::grammar::{
+    captures_at, compile, substitute_backrefs, Captures, EndPattern, GrammarSet, Include, RuleId,
+    RuleKind,
+};
+use super::{SyntaxHighlighter, TokenRule, TokenType};
+use crate::styled_text::{StyledLine, StyledText, TextSpan};
+
+/// How many times the tokenizer may match without advancing before it
+/// skips a character, which guards against grammars that loop on empty
+/// matches
+const MAX_STALLS: usize = 32;
+
+/// Style for tokens in a scope
+#[derive(Clone, Debug)]
+pub struct ScopeStyle {
+    /// Scope selector, matching the scope and any scope below it
+    /// (`string` matches `string.quoted.double`)
+    pub scope: String,
+    /// Text color
+    pub color: Color,
+    /// Whether text should be bold
+    pub bold: bool,
+    /// Token type reported to token click callbacks
+    pub token_type: TokenType,
+}
+
+impl ScopeStyle {
+    /// Create a style for `scope`
+    pub fn new(scope: impl Into<String>, color: Color) -> Self {
+        let scope = scope.into();
+        Self {
+            token_type: TokenType::Custom(scope.clone()),
+            scope,
+            color,
+            bold: false,
+        }
+    }
+
+    /// Set the token type for this style
+    pub fn token_type(mut self, token_type: TokenType) -> Self {
+        self.token_type = token_type;
+        self
+    }
+
+    /// Make tokens bold
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Length of the match if this style applies to `scope`
+    fn matches(&self, scope: &str) -> Option<usize> {
+        let rest = scope.strip_prefix(self.scope.as_str())?;
+        (rest.is_empty() || rest.starts_with('.')).then_some(self.scope.len())
+    }
+}
+
+/// Maps grammar scopes to colors
+///
+/// A token is styled by its innermost scope that any style matches, using
+/// the most specific style for that scope.
+#[derive(Clone, Debug)]
+pub struct GrammarTheme {
+    styles: Vec<ScopeStyle>,
+    text_color: Color,
+    bg_color: Color,
+}
+
+impl GrammarTheme {
+    /// Create a theme with no scope styles
+    pub fn new(text_color: Color, bg_color: Color) -> Self {
+        Self {
+            styles: Vec::new(),
+            text_color,
+            bg_color,
+        }
+    }
+
+    /// Dark theme using the same palette as [`RustHighlighter`](super::RustHighlighter)
+    pub fn dark() -> Self {
+        let keyword_color = Color::rgba(0.77, 0.56, 0.82, 1.0); // Purple
+        let string_color = Color::rgba(0.81, 0.54, 0.44, 1.0); // Orange/brown
+        let comment_color = Color::rgba(0.42, 0.54, 0.35, 1.0); // Green
+        let number_color = Color::rgba(0.71, 0.82, 0.57, 1.0); // Light green
+        let function_color = Color::rgba(0.86, 0.82, 0.65, 1.0); // Yellow
+        let type_color = Color::rgba(0.31, 0.76, 0.77, 1.0); // Cyan
+        let variable_color = Color::rgba(0.61, 0.86, 1.0, 1.0); // Light blue
+        let key_color = Color::rgba(0.61, 0.78, 0.92, 1.0); // Light blue
+        let operator_color = Color::rgba(0.83, 0.83, 0.83, 1.0); // Light gray
+
+        Self::new(
+            Color::rgba(0.9, 0.9, 0.9, 1.0),
+            Color::rgba(0.12, 0.12, 0.14, 1.0),
+        )
+        .style(ScopeStyle::new("comment", comment_color).token_type(TokenType::Comment))
+        .style(ScopeStyle::new("string", string_color).token_type(TokenType::String))
+        .style(ScopeStyle::new("constant.character", string_color).token_type(TokenType::String))
+        .style(ScopeStyle::new("constant.numeric", number_color).token_type(TokenType::Number))
+        .style(ScopeStyle::new("constant.language", keyword_color).token_type(TokenType::Keyword))
+        .style(
+            ScopeStyle::new("keyword", keyword_color)
+                .bold()
+                .token_type(TokenType::Keyword),
+        )
+        .style(
+            ScopeStyle::new("storage", keyword_color)
+                .bold()
+                .token_type(TokenType::Keyword),
+        )
+        .style(ScopeStyle::new("keyword.operator", operator_color).token_type(TokenType::Operator))
+        .style(ScopeStyle::new("entity.name.type", type_color).token_type(TokenType::Type))
+        .style(ScopeStyle::new("support.type", type_color).token_type(TokenType::Type))
+        .style(ScopeStyle::new("entity.name.tag", type_color).token_type(TokenType::Type))
+        .style(
+            ScopeStyle::new("entity.name.function", function_color).token_type(TokenType::Function),
+        )
+        .style(ScopeStyle::new("support.function", function_color).token_type(TokenType::Function))
+        .style(
+            ScopeStyle::new("entity.name.function.macro", type_color).token_type(TokenType::Macro),
+        )
+        .style(ScopeStyle::new("variable", variable_color).token_type(TokenType::Variable))
+        .style(ScopeStyle::new("entity.other.attribute-name", key_color))
+        .style(ScopeStyle::new("support.type.property-name", key_color))
+        .style(
+            ScopeStyle::new("storage.modifier.lifetime", keyword_color)
+                .token_type(TokenType::Lifetime),
+        )
+    }
+
+    /// Add a scope style; later styles win over earlier ones that are as
+    /// specific
+    pub fn style(mut self, style: ScopeStyle) -> Self {
+        self.styles.push(style);
+        self
+    }
+
+    /// Color for text outside any styled scope
+    pub fn text_color(mut self, color: Color) -> Self {
+        self.text_color = color;
+        self
+    }
+
+    /// Background color for the code block
+    pub fn background(mut self, color: Color) -> Self {
+        self.bg_color = color;
+        self
+    }
+
+    fn resolve<'a>(&self, scopes: impl Iterator<Item = &'a str>) -> Option<&ScopeStyle> {
+        for scope in scopes {
+            let best = self
+                .styles
+                .iter()
+                .filter_map(|style| style.matches(scope).map(|len| (len, style)))
+                .max_by_key(|(len, _)| *len);
+            if let Some((_, style)) = best {
+                return Some(style);
+            }
+        }
+        None
+    }
+}
+
+impl Default for GrammarTheme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+/// An open begin/end rule
+#[derive(Clone, Debug)]
+struct Frame {
+    rule: RuleId,
+    end: Arc<Regex>,
+}
+
+/// Tokenizer state between lines
+#[derive(Clone, Debug, Default)]
+struct LineState {
+    frames: Vec<Frame>,
+}
+
+impl PartialEq for LineState {
+    fn eq(&self, other: &Self) -> bool {
+        self.frames.len() == other.frames.len()
+            && self
+                .frames
+                .iter()
+                .zip(&other.frames)
+                .all(|(a, b)| a.rule == b.rule && a.end.as_str() == b.end.as_str())
+    }
+}
+
+#[derive(Clone, Debug)]
+struct CachedLine {
+    start: LineState,
+    end: LineState,
+    line: StyledLine,
+}
+
+/// Where candidate rules come from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Context {
+    Root(usize),
+    Rule(RuleId),
+}
+
+/// Per-document tokenizer state
+#[derive(Debug, Default)]
+struct HighlightCache {
+    lines: Vec<CachedLine>,
+    /// Rules to try in each context, with includes flattened
+    candidates: HashMap<Context, Arc<[RuleId]>>,
+}
+
+/// Which rule matched
+enum Found<'h> {
+    End(Groups<'h>),
+    Rule(RuleId, Groups<'h>),
+}
+
+impl Found<'_> {
+    fn start(&self) -> usize {
+        match self {
+            Found::End(caps) | Found::Rule(_, caps) => caps.get(0).unwrap().start(),
+        }
+    }
+}
+
+struct Tokenizer<'a> {
+    set: &'a GrammarSet,
+    root: usize,
+    theme: &'a GrammarTheme,
+    candidates: &'a mut HashMap<Context, Arc<[RuleId]>>,
+}
+
+impl Tokenizer<'_> {
+    fn candidates(&mut self, context: Context) -> Arc<[RuleId]> {
+        if let Some(candidates) = self.candidates.get(&context) {
+            return Arc::clone(candidates);
+        }
+        let mut out = Vec::new();
+        let mut seen = HashSet::new();
+        match context {
+            Context::Root(grammar) => self.visit_root(grammar, &mut out, &mut seen),
+            Context::Rule(id) => {
+                if let RuleKind::BeginEnd { patterns, .. } = &self.set.rule(id).kind {
+                    self.flatten(id.grammar, patterns, &mut out, &mut seen);
+                }
+            }
+        }
+        let out: Arc<[RuleId]> = out.into();
+        self.candidates.insert(context, Arc::clone(&out));
+        out
+    }
+
+    fn flatten(
+        &self,
+        grammar: usize,
+        includes: &[Include],
+        out: &mut Vec<RuleId>,
+        seen: &mut HashSet<Context>,
+    ) {
+        for include in includes {
+            match include {
+                Include::Rule(rule) => self.visit_rule(
+                    RuleId {
+                        grammar,
+                        rule: *rule,
+                    },
+                    out,
+                    seen,
+                ),
+                Include::SelfRoot => self.visit_root(grammar, out, seen),
+                Include::Base => self.visit_root(self.root, out, seen),
+                Include::Grammar(scope, rule) => {
+                    let Some(grammar) = self.set.index_of(scope) else {
+                        continue;
+                    };
+                    match rule {
+                        Some(name) => {
+                            if let Some(&rule) = self.set.get(grammar).repository.get(name) {
+                                self.visit_rule(RuleId { grammar, rule }, out, seen);
+                            }
+                        }
+                        None => self.visit_root(grammar, out, seen),
+                    }
+                }
+            }
+        }
+    }
+
+    fn visit_rule(&self, id: RuleId, out: &mut Vec<RuleId>, seen: &mut HashSet<Context>) {
+        match &self.set.rule(id).kind {
+            RuleKind::Patterns(includes) => {
+                if seen.insert(Context::Rule(id)) {
+                    self.flatten(id.grammar, includes, out, seen);
+                }
+            }
+            _ => out.push(id),
+        }
+    }
+
+    fn visit_root(&self, grammar: usize, out: &mut Vec<RuleId>, seen: &mut HashSet<Context>) {
+        if seen.insert(Context::Root(grammar)) {
+            self.flatten(grammar, &self.set.get(grammar).patterns, out, seen);
+        }
+    }
+
+    /// Scopes of the open rules, outermost first
+    fn scopes<'s>(&'s self, frames: &[Frame], content: bool) -> Vec<&'s str> {
+        let mut scopes = vec![self.set.get(self.root).scope_name()];
+        for (i, frame) in frames.iter().enumerate() {
+            let rule = self.set.rule(frame.rule);
+            scopes.extend(rule.scope.as_deref());
+            if content || i + 1 < frames.len() {
+                scopes.extend(rule.content_scope.as_deref());
+            }
+        }
+        scopes
+    }
+
+    /// Tokenize `line`, updating `state` to the state after it
+    fn tokenize(&mut self, line: &str, state: &mut LineState) -> StyledLine {
+        let haystack = format!("{}\n", line);
+        let mut out = Spans::new(line.len(), self.theme);
+        let mut pos = 0;
+        let mut stalls = 0;
+
+        while pos <= line.len() {
+            let context = match state.frames.last() {
+                Some(frame) => Context::Rule(frame.rule),
+                None => Context::Root(self.root),
+            };
+            let candidates = self.candidates(context);
+
+            let mut found = None;
+            let mut end_last = false;
+            if let Some(frame) = state.frames.last() {
+                if let RuleKind::BeginEnd { end_last: last, .. } = self.set.rule(frame.rule).kind {
+                    end_last = last;
+                }
+                found = captures_at(&frame.end, &haystack, pos).map(Found::End);
+            }
+            for &id in candidates.iter() {
+                let regex = match &self.set.rule(id).kind {
+                    RuleKind::Match { regex, .. } => regex.as_ref(),
+                    RuleKind::BeginEnd { begin, .. } => begin.as_ref(),
+                    RuleKind::Patterns(_) => None,
+                };
+                let Some(caps) = regex.and_then(|r| captures_at(r, &haystack, pos)) else {
+                    continue;
+                };
+                let start = caps.get(0).unwrap().start();
+                let better = match &found {
+                    None => true,
+                    Some(Found::End(_)) => {
+                        start < found.as_ref().unwrap().start()
+                            || (end_last && start == found.as_ref().unwrap().start())
+                    }
+                    Some(Found::Rule(..)) => start < found.as_ref().unwrap().start(),
+                };
+                if better {
+                    found = Some(Found::Rule(id, caps));
+                }
+            }
+
+            let Some(found) = found else {
+                let scopes = self.scopes(&state.frames, true);
+                out.push(pos, line.len(), &scopes, &[]);
+                break;
+            };
+            let start = found.start();
+            let scopes = self.scopes(&state.frames, true);
+            out.push(pos, start, &scopes, &[]);
+
+            let next = match found {
+                Found::End(caps) => {
+                    let frame = state.frames.pop().unwrap();
+                    let rule = self.set.rule(frame.rule);
+                    let mut scopes = self.scopes(&state.frames, true);
+                    scopes.extend(rule.scope.as_deref());
+                    if let RuleKind::BeginEnd { end_captures, .. } = &rule.kind {
+                        out.push_captures(&caps, &scopes, end_captures);
+                    }
+                    caps.get(0).unwrap().end()
+                }
+                Found::Rule(id, caps) => {
+                    let rule = self.set.rule(id);
+                    let mut scopes = self.scopes(&state.frames, true);
+                    scopes.extend(rule.scope.as_deref());
+                    let end = caps.get(0).unwrap().end();
+                    match &rule.kind {
+                        RuleKind::Match { captures, .. } => {
+                            out.push_captures(&caps, &scopes, captures);
+                            // An empty match changes nothing, so step over
+                            // a character rather than matching it again
+                            if end == pos {
+                                stalls = MAX_STALLS;
+                            }
+                        }
+                        RuleKind::BeginEnd {
+                            begin_captures,
+                            end: end_pattern,
+                            ..
+                        } => {
+                            out.push_captures(&caps, &scopes, begin_captures);
+                            let end_regex =
+                                match end_pattern {
+                                    EndPattern::Fixed(regex) => Arc::clone(regex),
+                                    EndPattern::BackRef(pattern) => {
+                                        let pattern = substitute_backrefs(pattern, &caps);
+                                        Arc::new(compile(&pattern).unwrap_or_else(|| {
+                                            compile("$").expect("valid pattern")
+                                        }))
+                                    }
+                                };
+                            state.frames.push(Frame {
+                                rule: id,
+                                end: end_regex,
+                            });
+                        }
+                        RuleKind::Patterns(_) => {}
+                    }
+                    end
+                }
+            };
+
+            if next > pos {
+                pos = next;
+                stalls = 0;
+            } else {
+                stalls += 1;
+                if stalls >= MAX_STALLS {
+                    let step = line[pos..].chars().next().map_or(1, char::len_utf8);
+                    let scopes = self.scopes(&state.frames, true);
+                    out.push(pos, pos + step, &scopes, &[]);
+                    pos += step;
+                    stalls = 0;
+                }
+            }
+        }
+
+        StyledLine::new(line, out.finish())
+    }
+}
+
+/// Builds a line's spans, merging neighbours with the same style
+struct Spans<'a> {
+    len: usize,
+    theme: &'a GrammarTheme,
+    spans: Vec<TextSpan>,
+}
+
+impl<'a> Spans<'a> {
+    fn new(len: usize, theme: &'a GrammarTheme) -> Self {
+        Self {
+            len,
+            theme,
+            spans: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, start: usize, end: usize, scopes: &[&str], extra: &[&str]) {
+        let end = end.min(self.len);
+        if start >= end {
+            return;
+        }
+        let style = self
+            .theme
+            .resolve(extra.iter().rev().chain(scopes.iter().rev()).copied());
+        let (color, bold, token_type) = match style {
+            Some(style) => (style.color, style.bold, Some(style.token_type.clone())),
+            None => (self.theme.text_color, false, None),
+        };
+        if let Some(last) = self.spans.last_mut() {
+            if last.end == start
+                && last.color == color
+                && last.bold == bold
+                && last.token_type == token_type
+            {
+                last.end = end;
+                return;
+            }
+        }
+        let mut span = TextSpan::new(start, end, color, bold);
+        span.token_type = token_type;
+        self.spans.push(span);
+    }
+
+    /// Push a match, styling capture groups on top of `scopes`
+    fn push_captures(&mut self, caps: &Groups, scopes: &[&str], captures: &Captures) {
+        let whole = caps.get(0).unwrap();
+        let mut scopes = scopes.to_vec();
+        if let Some((_, scope)) = captures.iter().find(|(index, _)| *index == 0) {
+            scopes.push(scope);
+        }
+        let mut groups: Vec<_> = captures
+            .iter()
+            .filter(|(index, _)| *index > 0)
+            .filter_map(|(index, scope)| caps.get(*index).map(|m| (m.range(), scope.as_str())))
+            .collect();
+        groups.sort_by_key(|(range, _)| range.start);
+
+        let mut pos = whole.start();
+        for (range, scope) in groups {
+            // Nested groups are covered by their parent
+            if range.start < pos {
+                continue;
+            }
+            self.push(pos, range.start, &scopes, &[]);
+            self.push(range.start, range.end, &scopes, &[scope]);
+            pos = range.end;
+        }
+        self.push(pos, whole.end(), &scopes, &[]);
+    }
+
+    fn finish(self) -> Vec<TextSpan> {
+        self.spans
+    }
+}
+
+/// Highlights with a TextMate grammar, re-tokenizing incrementally
+///
+/// Keep one highlighter per document (wrap it in an `Arc` and pass
+/// [`SyntaxConfig::shared`](super::SyntaxConfig::shared) to `code()`), so
+/// each edit only re-tokenizes the lines it affects.
+///
+/// # Example
+///
+/// ```ignore
+/// use junita_layout::syntax::{GrammarHighlighter, SyntaxConfig};
+///
+/// let highlighter = GrammarHighlighter::for_language("rust").unwrap();
+/// code(source).syntax(SyntaxConfig::new(highlighter))
+/// ```
+pub struct GrammarHighlighter {
+    set: Arc<GrammarSet>,
+    root: usize,
+    theme: GrammarTheme,
+    cache: Mutex<HighlightCache>,
+}
+
+impl GrammarHighlighter {
+    /// Highlight with the grammar for `scope_name` from `set`
+    pub fn new(set: Arc<GrammarSet>, scope_name: &str) -> Option<Self> {
+        let root = set.index_of(scope_name)?;
+        Some(Self {
+            set,
+            root,
+            theme: GrammarTheme::dark(),
+            cache: Mutex::new(HighlightCache::default()),
+        })
+    }
+
+    /// Highlight with the bundled grammar for a language name or file
+    /// extension, as found in a fenced code block's info string
+    pub fn for_language(language: &str) -> Option<Self> {
+        let set = GrammarSet::bundled();
+        let scope_name = set.find(language)?.scope_name().to_string();
+        Self::new(set, &scope_name)
+    }
+
+    /// Set the theme
+    pub fn theme(mut self, theme: GrammarTheme) -> Self {
+        self.theme = theme;
+        self.cache = Mutex::new(HighlightCache::default());
+        self
+    }
+
+    /// The grammar set this highlighter resolves includes in
+    pub fn grammar_set(&self) -> &Arc<GrammarSet> {
+        &self.set
+    }
+
+    /// Bring the cache up to date with `text`, returning the lines that had
+    /// to be tokenized
+    pub fn update(&self, text: &str) -> Range<usize> {
+        let mut cache = self.cache.lock().unwrap();
+        update(self, &mut cache, text)
+    }
+}
+
+fn update(
+    highlighter: &GrammarHighlighter,
+    cache: &mut HighlightCache,
+    text: &str,
+) -> Range<usize> {
+    let new: Vec<&str> = if text.is_empty() {
+        vec![""]
+    } else {
+        text.lines().collect()
+    };
+    let old = std::mem::take(&mut cache.lines);
+    let old_len = old.len();
+
+    let prefix = old
+        .iter()
+        .zip(&new)
+        .take_while(|(old, new)| old.line.text == **new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old.line.text == **new)
+        .count();
+
+    let mut tokenizer = Tokenizer {
+        set: &highlighter.set,
+        root: highlighter.root,
+        theme: &highlighter.theme,
+        candidates: &mut cache.candidates,
+    };
+    let mut old = old.into_iter();
+    let mut lines: Vec<CachedLine> = old.by_ref().take(prefix).collect();
+    let mut state = lines.last().map(|l| l.end.clone()).unwrap_or_default();
+
+    // Lines after the edit can be reused once the state entering them is
+    // what it was before
+    let reusable: Vec<CachedLine> = old.skip(old_len - prefix - suffix).collect();
+    let first_reusable = new.len() - suffix;
+    let mut end = new.len();
+    for (i, text) in new.iter().enumerate().skip(prefix) {
+        if i >= first_reusable {
+            let cached = &reusable[i - first_reusable];
+            if cached.start == state {
+                lines.extend(reusable.into_iter().skip(i - first_reusable));
+                end = i;
+                break;
+            }
+        }
+        let start = state.clone();
+        let line = tokenizer.tokenize(text, &mut state);
+        lines.push(CachedLine {
+            start,
+            end: state.clone(),
+            line,
+        });
+    }
+
+    cache.lines = lines;
+    prefix..end
+}
+
+impl SyntaxHighlighter for GrammarHighlighter {
+    fn token_rules(&self) -> &[TokenRule] {
+        &[]
+    }
+
+    fn default_color(&self) -> Color {
+        self.theme.text_color
+    }
+
+    fn background_color(&self) -> Color {
+        self.theme.bg_color
+    }
+
+    fn highlight(&self, text: &str) -> StyledText {
+        let mut cache = self.cache.lock().unwrap();
+        update(self, &mut cache, text);
+        StyledText::from_lines(cache.lines.iter().map(|l| l.line.clone()).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Token type at byte `offset` of `line`
+    fn token_at(styled: &StyledText, line: usize, offset: usize) -> Option<TokenType> {
+        styled.lines[line]
+            .spans
+            .iter()
+            .find(|span| span.start <= offset && offset < span.end)
+            .and_then(|span| span.token_type.clone())
+    }
+
+    #[test]
+    fn test_nested_block_comment() {
+        let highlighter = GrammarHighlighter::for_language("rust").unwrap();
+        let styled = highlighter.highlight("/* a /* b */\nstill */ fn main() {}");
+
+        assert_eq!(token_at(&styled, 0, 0), Some(TokenType::Comment));
+        assert_eq!(token_at(&styled, 1, 2), Some(TokenType::Comment));
+        assert_eq!(token_at(&styled, 1, 9), Some(TokenType::Keyword));
+        assert_eq!(token_at(&styled, 1, 12), Some(TokenType::Function));
+    }
+
+    #[test]
+    fn test_raw_string_end_uses_begin_capture() {
+        let highlighter = GrammarHighlighter::for_language("rs").unwrap();
+        let styled = highlighter.highlight(r####"let s = r##"a "# b"##; 1"####);
+
+        assert_eq!(token_at(&styled, 0, 15), Some(TokenType::String));
+        assert_eq!(token_at(&styled, 0, 23), Some(TokenType::Number));
+    }
+
+    #[test]
+    fn test_lookaround_and_backreferences() {
+        let mut set = GrammarSet::new();
+        set.add_json(
+            r#"{
+                "scopeName": "source.demo",
+                "patterns": [
+                    {"match": "(?<=@)\\w+\\b(?!\\()", "name": "keyword.other.demo"},
+                    {"match": "(['\"]).*?\\1", "name": "string.quoted.demo"}
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert!(set
+            .grammar("source.demo")
+            .unwrap()
+            .unsupported_patterns()
+            .is_empty());
+        let highlighter = GrammarHighlighter::new(Arc::new(set), "source.demo").unwrap();
+        let styled = highlighter.highlight(r#"@name @call() 'a"b' x"#);
+
+        assert_eq!(token_at(&styled, 0, 0), None);
+        assert_eq!(token_at(&styled, 0, 1), Some(TokenType::Keyword));
+        assert_eq!(token_at(&styled, 0, 7), None);
+        assert_eq!(token_at(&styled, 0, 16), Some(TokenType::String));
+        assert_eq!(token_at(&styled, 0, 20), None);
+    }
+
+    #[test]
+    fn test_template_interpolation() {
+        let highlighter = GrammarHighlighter::for_language("js").unwrap();
+        let styled = highlighter.highlight("const s = `sum ${ {a: 1}.a + 2 } done`;");
+
+        assert_eq!(token_at(&styled, 0, 11), Some(TokenType::String));
+        assert_eq!(token_at(&styled, 0, 22), Some(TokenType::Number));
+        assert_eq!(token_at(&styled, 0, 34), Some(TokenType::String));
+    }
+
+    #[test]
+    fn test_embedded_language() {
+        let highlighter = GrammarHighlighter::for_language("html").unwrap();
+        let styled =
+            highlighter.highlight("<script>\nlet x = 1;\n</script>\n<p class=\"a\">let</p>");
+
+        assert_eq!(token_at(&styled, 0, 1), Some(TokenType::Type));
+        assert_eq!(token_at(&styled, 1, 0), Some(TokenType::Keyword));
+        assert_eq!(token_at(&styled, 1, 8), Some(TokenType::Number));
+        assert_eq!(token_at(&styled, 3, 13), None);
+    }
+
+    #[test]
+    fn test_incremental_update() {
+        let highlighter = GrammarHighlighter::for_language("rust").unwrap();
+        let lines: Vec<String> = (0..10).map(|i| format!("let x{} = {};", i, i)).collect();
+        let mut text = lines.join("\n");
+        assert_eq!(highlighter.update(&text), 0..10);
+        assert_eq!(highlighter.update(&text), 10..10);
+
+        // An edit inside a line touches only that line
+        text = text.replace("x4 = 4", "x4 = 44");
+        assert_eq!(highlighter.update(&text), 4..5);
+
+        // Opening a comment changes the state of every line after it
+        text = text.replace("let x6", "/* let x6");
+        assert_eq!(highlighter.update(&text), 6..10);
+        let styled = highlighter.highlight(&text);
+        assert_eq!(token_at(&styled, 9, 0), Some(TokenType::Comment));
+
+        // Edits inside the comment leave it open, so the state converges
+        text = text.replace("x8 = 8", "x8 = 88");
+        assert_eq!(highlighter.update(&text), 8..9);
+
+        // Closing it changes the state of every line after it again
+        text = text.replace("let x7", "*/ let x7");
+        assert_eq!(highlighter.update(&text), 7..10);
+        let styled = highlighter.highlight(&text);
+        assert_eq!(token_at(&styled, 8, 0), Some(TokenType::Keyword));
+
+        // Inserted lines shift the cache instead of invalidating it
+        text = format!("fn f() {{}}\n{}", text);
+        assert_eq!(highlighter.update(&text), 0..1);
+        assert_eq!(highlighter.highlight(&text).line_count(), 11);
+    }
+
+    #[test]
+    fn test_custom_theme() {
+        let red = Color::rgba(1.0, 0.0, 0.0, 1.0);
+        let highlighter = GrammarHighlighter::for_language("json")
+            .unwrap()
+            .theme(GrammarTheme::dark().style(ScopeStyle::new("constant.numeric", red)));
+        let styled = highlighter.highlight(r#"{"a": 1}"#);
+
+        let number = styled.lines[0].spans.iter().find(|s| s.start == 6).unwrap();
+        assert_eq!(number.color, red);
+        assert_eq!(
+            number.token_type,
+            Some(TokenType::Custom("constant.numeric".to_string()))
+        );
+    }
+}
@@ -1,7 +1,9 @@
 //! Syntax highlighting for code elements
 //!
-//! This module provides a trait-based syntax highlighting system that uses regex
-//! patterns to match tokens and apply colors.
+//! This module provides a trait-based syntax highlighting system. The simple
+//! highlighters use regex patterns to match tokens and apply colors;
+//! [`GrammarHighlighter`] uses TextMate grammars, which handle nested
+//! constructs and embedded languages and re-highlight incrementally.
 //!
 //! # Example
 //!
@@ -24,11 +26,19 @@
 //!     .config(SyntaxConfig::new(MyHighlighter { rules: vec![...] }))
 //! ```
 
+use std::sync::Arc;
+
 use junita_core::Color;
 use regex::Regex;
 
 use crate::styled_text::{StyledLine, StyledText, TextSpan};
 
+mod grammar;
+mod highlight;
+
+pub use grammar::{Grammar, GrammarError, GrammarSet};
+pub use highlight::{GrammarHighlighter, GrammarTheme, ScopeStyle};
+
 /// Token type identifier for callbacks
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum TokenType {
@@ -207,25 +217,33 @@ pub trait SyntaxHighlighter: Send + Sync {
 
 /// Configuration for syntax highlighting passed to code elements
 pub struct SyntaxConfig {
-    highlighter: Box<dyn SyntaxHighlighter>,
+    highlighter: Arc<dyn SyntaxHighlighter>,
 }
 
 impl SyntaxConfig {
     /// Create a new syntax config with the given highlighter
     pub fn new(highlighter: impl SyntaxHighlighter + 'static) -> Self {
         Self {
-            highlighter: Box::new(highlighter),
+            highlighter: Arc::new(highlighter),
         }
     }
 
+    /// Create a syntax config with a highlighter kept across rebuilds
+    ///
+    /// Use this with [`GrammarHighlighter`] so its line cache survives the
+    /// code element being rebuilt after each edit.
+    pub fn shared(highlighter: Arc<dyn SyntaxHighlighter>) -> Self {
+        Self { highlighter }
+    }
+
     /// Get a reference to the highlighter
     pub fn highlighter(&self) -> &dyn SyntaxHighlighter {
         self.highlighter.as_ref()
     }
 
     /// Convert the config into an Arc-wrapped highlighter
-    pub fn into_arc(self) -> std::sync::Arc<dyn SyntaxHighlighter> {
-        std::sync::Arc::from(self.highlighter)
+    pub fn into_arc(self) -> Arc<dyn SyntaxHighlighter> {
+        self.highlighter
     }
 }
 
@@ -13,6 +13,8 @@ pub enum SelectionSource {
     TextInput,
     /// Selection from a text area widget
     TextArea,
+    /// Selection from a rich text editor widget
+    RichTextEditor,
     /// Selection from static/label text
     StaticText,
 }
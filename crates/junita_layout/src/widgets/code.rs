@@ -1,9 +1,11 @@
 //! Code block widget with syntax highlighting
 //!
 //! A code display/editing widget that supports:
-//! - Syntax highlighting via regex-based token matching
+//! - Syntax highlighting via regex-based token matching or TextMate grammars
 //! - Optional line numbers in the gutter
 //! - Read-only by default, editable with `.edit(true)`
+//! - Find and replace (Ctrl+F / Ctrl+H) and multiple cursors (Alt+click,
+//!   Alt+drag, Ctrl+D) when editable
 //! - All Div layout methods via Deref
 //!
 //! # Example
@@ -25,6 +27,20 @@
 //!     .on_change(|new_content| {
 //!         println!("Content changed: {}", new_content);
 //!     })
+//!
+//! // Keep the text, cursors and find bar across rebuilds
+//! let state = code_state("let x = 42;");
+//! code("")
+//!     .state(&state)
+//!     .edit(true)
+//!
+//! // Grammar highlighting kept across rebuilds, so each edit only
+//! // re-highlights the lines it affects
+//! let highlighter: Arc<dyn SyntaxHighlighter> =
+//!     Arc::new(GrammarHighlighter::for_language("rust").unwrap());
+//! code(source)
+//!     .edit(true)
+//!     .syntax(SyntaxConfig::shared(Arc::clone(&highlighter)))
 //! ```
 
 use std::ops::{Deref, DerefMut};
@@ -41,6 +57,10 @@ use crate::syntax::{SyntaxConfig, SyntaxHighlighter, TokenHit};
 use crate::text::text;
 use crate::tree::{LayoutNodeId, LayoutTree};
 use crate::widgets::cursor::{cursor_state, CursorAnimation, SharedCursorState};
+use crate::widgets::find_replace::{self, FindAction, FindOptions, FindState, KeyOutcome};
+use crate::widgets::multi_cursor::{
+    self, for_each_cursor, ColumnDrag, CursorEditor, CursorSelection, MultiCursor,
+};
 use crate::widgets::text_area::TextPosition;
 use crate::widgets::text_input::{
     decrement_focus_count, increment_focus_count, request_continuous_redraw_pub, request_rebuild,
@@ -106,12 +126,15 @@ impl Default for CodeConfig {
 }
 
 // ============================================================================
-// Internal State (not exposed to users)
+// State
 // ============================================================================
 
-/// Internal state for editable code blocks
+/// State for editable code blocks
+///
+/// Created internally by each `Code`, or shared with [`code_state`] and
+/// [`Code::state`] to keep it across rebuilds.
 #[derive(Debug, Clone)]
-struct CodeState {
+pub struct CodeState {
     /// Lines of text
     lines: Vec<String>,
     /// Cursor position
@@ -122,6 +145,12 @@ struct CodeState {
     focused: bool,
     /// Canvas-based cursor state
     cursor_state: SharedCursorState,
+    /// Cursors besides the primary one
+    cursors: MultiCursor,
+    /// Find bar state
+    find: FindState,
+    /// Line and x offset of the clicked span, set by line handlers
+    clicked: Option<(usize, f32)>,
 }
 
 impl Default for CodeState {
@@ -132,6 +161,9 @@ impl Default for CodeState {
             selection_start: None,
             focused: false,
             cursor_state: cursor_state(),
+            cursors: MultiCursor::default(),
+            find: FindState::default(),
+            clicked: None,
         }
     }
 }
@@ -146,18 +178,91 @@ impl CodeState {
 
         Self {
             lines,
-            cursor: TextPosition::default(),
-            selection_start: None,
-            focused: false,
-            cursor_state: cursor_state(),
+            ..Self::default()
         }
     }
 
     /// Get full text content
-    fn value(&self) -> String {
+    pub fn value(&self) -> String {
         self.lines.join("\n")
     }
 
+    /// All cursors and their selections, in text order
+    pub fn cursors(&self) -> Vec<CursorSelection> {
+        let mut all = self.cursors.secondary().to_vec();
+        all.push(CursorSelection {
+            cursor: self.cursor,
+            selection_start: self.selection_start,
+        });
+        all.sort_by_key(|c| {
+            let (from, _) = c.range();
+            (from.line, from.column)
+        });
+        all
+    }
+
+    /// Add a cursor at `pos`, or remove the one already there (Alt+click)
+    pub fn add_cursor(&mut self, pos: TextPosition) {
+        multi_cursor::toggle_cursor(self, pos);
+    }
+
+    /// Select the word at the cursor, or add a cursor at the next occurrence
+    /// of the selection (Ctrl+D)
+    pub fn add_next_occurrence(&mut self) -> bool {
+        multi_cursor::add_next_occurrence(self)
+    }
+
+    /// Keep only the primary cursor
+    pub fn clear_secondary_cursors(&mut self) {
+        self.cursors.clear();
+    }
+
+    /// Find bar state
+    pub fn find(&self) -> &FindState {
+        &self.find
+    }
+
+    /// Open the find bar, with the replace field if `replace`
+    pub fn open_find(&mut self, replace: bool) {
+        find_replace::open(self, replace);
+    }
+
+    pub fn close_find(&mut self) {
+        find_replace::close(self);
+    }
+
+    /// Set the query and options, updating the matches
+    pub fn set_find_query(&mut self, query: impl Into<String>, options: FindOptions) {
+        self.find.query = query.into();
+        self.find.options = options;
+        self.find.update(&self.lines);
+    }
+
+    /// Set the replacement text
+    pub fn set_replacement(&mut self, replacement: impl Into<String>) {
+        self.find.replacement = replacement.into();
+    }
+
+    /// Select the next match
+    pub fn find_next(&mut self) {
+        find_replace::select_match(self, false);
+    }
+
+    /// Select the previous match
+    pub fn find_previous(&mut self) {
+        find_replace::select_match(self, true);
+    }
+
+    /// Replace the selected match and select the next one
+    pub fn replace_current(&mut self) -> bool {
+        find_replace::replace_current(self)
+    }
+
+    /// Replace every match, returning the number replaced
+    pub fn replace_all(&mut self) -> usize {
+        find_replace::replace_all(self)
+    }
+
     /// Check if empty
     fn is_empty(&self) -> bool {
         self.lines.len() == 1 && self.lines[0].is_empty()
@@ -354,6 +459,60 @@ impl CodeState {
     }
 }
 
+impl CursorEditor for CodeState {
+    fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    fn cursor_selection(&self) -> CursorSelection {
+        CursorSelection {
+            cursor: self.cursor,
+            selection_start: self.selection_start,
+        }
+    }
+
+    fn set_cursor_selection(&mut self, selection: CursorSelection) {
+        self.cursor = selection.cursor;
+        self.selection_start = selection.selection_start;
+    }
+
+    fn insert_text(&mut self, text: &str) {
+        self.insert(text);
+    }
+
+    fn multi_cursor(&mut self) -> &mut MultiCursor {
+        &mut self.cursors
+    }
+
+    fn find_state(&mut self) -> &mut FindState {
+        &mut self.find
+    }
+}
+
+/// Width of the first `chars` characters of a line
+fn prefix_width(line: &str, chars: usize, font_size: f32) -> f32 {
+    let prefix: String = line.chars().take(chars).collect();
+    crate::text_measure::measure_text(&prefix, font_size).width
+}
+
+/// Column nearest to an x offset within a line
+fn column_at(line: &str, x: f32, font_size: f32) -> usize {
+    let count = line.chars().count();
+    let mut previous = 0.0;
+    for column in 1..=count {
+        let width = prefix_width(line, column, font_size);
+        if width >= x {
+            return if x - previous < width - x {
+                column - 1
+            } else {
+                column
+            };
+        }
+        previous = width;
+    }
+    count
+}
+
 /// Convert character position to byte position
 fn char_to_byte_pos(s: &str, char_pos: usize) -> usize {
     s.char_indices()
@@ -371,7 +530,13 @@ fn order_positions(a: TextPosition, b: TextPosition) -> (TextPosition, TextPosit
     }
 }
 
-type SharedCodeState = Arc<Mutex<CodeState>>;
+/// Shared code block state
+pub type SharedCodeState = Arc<Mutex<CodeState>>;
+
+/// Create code block state to keep across rebuilds with [`Code::state`]
+pub fn code_state(content: &str) -> SharedCodeState {
+    Arc::new(Mutex::new(CodeState::new(content)))
+}
 
 // ============================================================================
 // Code Widget
@@ -470,6 +635,16 @@ impl Code {
         self
     }
 
+    /// Use shared state, so the text, cursors and find bar survive rebuilds
+    ///
+    /// The state's text replaces the content passed to `code()`.
+    pub fn state(mut self, state: &SharedCodeState) -> Self {
+        self.content = state.lock().unwrap().value();
+        self.state = Arc::clone(state);
+        self.rebuild_inner();
+        self
+    }
+
     /// Set syntax highlighting configuration
     pub fn syntax(mut self, config: SyntaxConfig) -> Self {
         // Store colors before consuming config
@@ -624,11 +799,81 @@ impl Code {
             .padding_y_px(self.config.padding)
             .relative();
 
+        let font_size = self.config.font_size;
+        let editable = self.config.editable;
+
+        // Find matches and selections, drawn behind the text
+        if editable {
+            let state = self.state.lock().unwrap();
+            let mut ranges = Vec::new();
+            if state.find.is_open() {
+                let primary = state.cursor_selection();
+                let current = state.find.current_match(&primary);
+                let (other_color, current_color) =
+                    find_replace::match_colors(self.config.selection_color);
+                for (i, found) in state.find.matches().iter().enumerate() {
+                    let color = if current == Some(i) {
+                        current_color
+                    } else {
+                        other_color
+                    };
+                    ranges.push((found.start, found.end, color));
+                }
+            }
+            if state.focused {
+                for selection in state.cursors() {
+                    if selection.has_selection() {
+                        let (from, to) = selection.range();
+                        ranges.push((from, to, self.config.selection_color));
+                    }
+                }
+            }
+            let last_line = state.lines.len() - 1;
+            for (from, to, color) in ranges {
+                for line in from.line..=to.line.min(last_line) {
+                    let line_text = &state.lines[line];
+                    let start = if line == from.line { from.column } else { 0 };
+                    let end = if line == to.line {
+                        to.column
+                    } else {
+                        line_text.chars().count()
+                    };
+                    let x0 = prefix_width(line_text, start, font_size);
+                    let mut x1 = prefix_width(line_text, end, font_size);
+                    // Show the selected line break
+                    if line < to.line {
+                        x1 += font_size * 0.3;
+                    }
+                    if x1 > x0 {
+                        code_area = code_area.child(
+                            div()
+                                .absolute()
+                                .left(x0)
+                                .top(line as f32 * line_height_px)
+                                .w(x1 - x0)
+                                .h(line_height_px)
+                                .bg(color),
+                        );
+                    }
+                }
+            }
+        }
+
         // Render each line with styled spans
-        for styled_line in &styled.lines {
+        for (line_idx, styled_line) in styled.lines.iter().enumerate() {
             // Don't use overflow_clip on line divs - rely on outer container's clip
             let mut line_div = div().h(line_height_px).flex_row().items_center();
 
+            if editable {
+                // Clicks past the end of the text land on the line itself
+                let state_for_line = Arc::clone(&self.state);
+                line_div = line_div.on_mouse_down(move |_ctx| {
+                    if let Ok(mut s) = state_for_line.lock() {
+                        s.clicked.get_or_insert((line_idx, 0.0));
+                    }
+                });
+            }
+
             if styled_line.spans.is_empty() {
                 // Empty line - add a space to maintain height
                 line_div = line_div.child(
@@ -651,38 +896,61 @@ impl Code {
 
                     txt = txt.monospace();
 
-                    line_div = line_div.child(txt);
+                    if editable {
+                        // Record where the span starts so clicks on it can be
+                        // mapped to a column
+                        let span_x = crate::text_measure::measure_text(
+                            &styled_line.text[..span.start],
+                            font_size,
+                        )
+                        .width;
+                        let state_for_span = Arc::clone(&self.state);
+                        line_div = line_div.child(div().child(txt).on_mouse_down(move |_ctx| {
+                            if let Ok(mut s) = state_for_span.lock() {
+                                s.clicked = Some((line_idx, span_x));
+                            }
+                        }));
+                    } else {
+                        line_div = line_div.child(txt);
+                    }
                 }
             }
 
             code_area = code_area.child(line_div);
         }
 
-        // Add cursor if editable and focused
-        if self.config.editable {
+        // Add cursors if editable and focused
+        if editable {
             let state = self.state.lock().unwrap();
             if state.focused {
                 let cursor_height = self.config.font_size * 1.2;
-                let cursor_line = state.cursor.line;
-                let cursor_col = state.cursor.column;
-
-                // Calculate cursor x position
-                let cursor_x = if cursor_col > 0 && cursor_line < state.lines.len() {
-                    let line_text = &state.lines[cursor_line];
-                    let text_before: String = line_text.chars().take(cursor_col).collect();
-                    crate::text_measure::measure_text(&text_before, self.config.font_size).width
-                } else {
-                    0.0
+                let cursor_position = |pos: TextPosition| {
+                    let x = match state.lines.get(pos.line) {
+                        Some(line_text) if pos.column > 0 => {
+                            prefix_width(line_text, pos.column, font_size)
+                        }
+                        _ => 0.0,
+                    };
+                    let top =
+                        (pos.line as f32 * line_height_px) + (line_height_px - cursor_height) / 2.0;
+                    (x, top)
                 };
-
-                let cursor_top =
-                    (cursor_line as f32 * line_height_px) + (line_height_px - cursor_height) / 2.0;
-
-                let cursor_state_clone = Arc::clone(&state.cursor_state);
+                let (cursor_x, cursor_top) = cursor_position(state.cursor);
+                let positions: Vec<(f32, f32)> = std::iter::once((cursor_x, cursor_top))
+                    .chain(
+                        state
+                            .cursors
+                            .secondary()
+                            .iter()
+                            .map(|c| cursor_position(c.cursor)),
+                    )
+                    .collect();
+
+                let cursor_state = Arc::clone(&state.cursor_state);
 
                 // Update cursor state
                 {
-                    if let Ok(mut cs) = cursor_state_clone.lock() {
+                    if let Ok(mut cs) = cursor_state.lock() {
                         cs.visible = true;
                         cs.color = self.config.cursor_color;
                         cs.x = cursor_x;
@@ -692,51 +960,56 @@ impl Code {
 
                 drop(state);
 
-                // Add cursor canvas
+                // Add cursor canvases; every cursor blinks with the primary one
                 let cursor_color = self.config.cursor_color;
-                let cursor_canvas = canvas(
-                    move |ctx: &mut dyn junita_core::DrawContext,
-                          bounds: crate::canvas::CanvasBounds| {
-                        let cs = cursor_state_clone.lock().unwrap();
-                        if !cs.visible {
-                            return;
-                        }
-
-                        let opacity = cs.current_opacity();
-                        if opacity < 0.01 {
-                            return;
-                        }
-
-                        let color = Color::rgba(
-                            cursor_color.r,
-                            cursor_color.g,
-                            cursor_color.b,
-                            cursor_color.a * opacity,
-                        );
-
-                        // bounds only has width/height; canvas is positioned at (0,0) in local coords
-                        ctx.fill_rect(
-                            Rect::new(0.0, 0.0, bounds.width, bounds.height),
-                            CornerRadius::default(),
-                            Brush::Solid(color),
-                        );
-                    },
-                )
-                .absolute()
-                .top(cursor_top)
-                .left(cursor_x)
-                .w(2.0)
-                .h(cursor_height);
-
-                code_area = code_area.child(cursor_canvas);
+                for (left, top) in positions {
+                    let cursor_state_clone = Arc::clone(&cursor_state);
+                    let cursor_canvas = canvas(
+                        move |ctx: &mut dyn junita_core::DrawContext,
+                              bounds: crate::canvas::CanvasBounds| {
+                            let cs = cursor_state_clone.lock().unwrap();
+                            if !cs.visible {
+                                return;
+                            }
+
+                            let opacity = cs.current_opacity();
+                            if opacity < 0.01 {
+                                return;
+                            }
+
+                            let color = Color::rgba(
+                                cursor_color.r,
+                                cursor_color.g,
+                                cursor_color.b,
+                                cursor_color.a * opacity,
+                            );
+
+                            // bounds only has width/height; canvas is positioned at (0,0) in local coords
+                            ctx.fill_rect(
+                                Rect::new(0.0, 0.0, bounds.width, bounds.height),
+                                CornerRadius::default(),
+                                Brush::Solid(color),
+                            );
+                        },
+                    )
+                    .absolute()
+                    .top(top)
+                    .left(left)
+                    .w(2.0)
+                    .h(cursor_height);
+
+                    code_area = code_area.child(cursor_canvas);
+                }
             }
         }
 
         container = container.child(code_area);
 
         // Add event handlers if editable
-        if self.config.editable {
+        if editable {
             let state_for_click = Arc::clone(&self.state);
+            let state_for_drag = Arc::clone(&self.state);
+            let state_for_drag_end = Arc::clone(&self.state);
             let state_for_key = Arc::clone(&self.state);
             let state_for_text = Arc::clone(&self.state);
             let state_for_blur = Arc::clone(&self.state);
@@ -744,19 +1017,75 @@ impl Code {
             let on_change_for_text = self.on_change.clone();
 
             container = container
-                .on_mouse_down(move |_ctx| {
+                .on_mouse_down(move |ctx| {
                     let mut s = state_for_click.lock().unwrap();
                     if !s.focused {
                         s.focused = true;
                         increment_focus_count();
                         request_continuous_redraw_pub();
                     }
+
+                    // Clicks on the find bar were already handled by the bar
+                    if std::mem::take(&mut s.find.clicked) {
+                        s.clicked = None;
+                        request_rebuild();
+                        return;
+                    }
+
+                    // Position the cursor when a line was clicked
+                    if let Some((line, span_x)) = s.clicked.take() {
+                        let line = line.min(s.lines.len() - 1);
+                        let x = (span_x + ctx.local_x).max(0.0);
+                        let pos = TextPosition::new(line, column_at(&s.lines[line], x, font_size));
+                        if ctx.alt {
+                            // Alt+click adds a cursor; dragging on makes a column selection
+                            multi_cursor::toggle_cursor(&mut *s, pos);
+                            s.cursors.column_drag = Some(ColumnDrag { row: line, x });
+                        } else {
+                            s.cursors.clear();
+                            s.cursors.column_drag = None;
+                            s.cursor = pos;
+                            s.selection_start = None;
+                        }
+                        if let Ok(mut cs) = s.cursor_state.lock() {
+                            cs.reset_blink();
+                        }
+                    }
+
+                    // Typing goes back to the text
+                    s.find.field = None;
+                    request_rebuild();
+                })
+                // Alt+drag extends a column selection across lines
+                .on_drag(move |ctx| {
+                    let mut s = state_for_drag.lock().unwrap();
+                    let Some(drag) = s.cursors.column_drag else {
+                        return;
+                    };
+                    let last_line = s.lines.len() - 1;
+                    let rows = (ctx.drag_delta_y / line_height_px).round() as isize;
+                    let target = (drag.row as isize + rows).clamp(0, last_line as isize) as usize;
+                    let end_x = (drag.x + ctx.drag_delta_x).max(0.0);
+                    let selections: Vec<_> = (drag.row.min(target)..=drag.row.max(target))
+                        .map(|row| {
+                            let line = &s.lines[row];
+                            (
+                                TextPosition::new(row, column_at(line, drag.x, font_size)),
+                                TextPosition::new(row, column_at(line, end_x, font_size)),
+                            )
+                        })
+                        .collect();
+                    multi_cursor::column_selection(&mut *s, selections.into_iter());
                     request_rebuild();
                 })
+                .on_drag_end(move |_ctx| {
+                    state_for_drag_end.lock().unwrap().cursors.column_drag = None;
+                })
                 .on_blur(move |_ctx| {
                     let mut s = state_for_blur.lock().unwrap();
                     s.focused = false;
                     s.selection_start = None;
+                    s.cursors.clear();
                     if let Ok(mut cs) = s.cursor_state.lock() {
                         cs.visible = false;
                     }
@@ -771,55 +1100,72 @@ impl Code {
 
                     let mut changed = false;
                     let mut cursor_changed = true;
-
-                    match ctx.key_code {
-                        8 => {
-                            // Backspace
-                            s.delete_backward();
-                            changed = true;
-                        }
-                        127 => {
-                            // Delete
-                            s.delete_forward();
-                            changed = true;
-                        }
-                        13 => {
-                            // Enter
-                            s.insert("\n");
-                            changed = true;
-                        }
-                        37 => {
-                            // Left arrow
-                            s.move_left(ctx.shift);
-                        }
-                        39 => {
-                            // Right arrow
-                            s.move_right(ctx.shift);
-                        }
-                        38 => {
-                            // Up arrow
-                            s.move_up(ctx.shift);
-                        }
-                        40 => {
-                            // Down arrow
-                            s.move_down(ctx.shift);
-                        }
-                        36 => {
-                            // Home
-                            s.move_to_line_start(ctx.shift);
-                        }
-                        35 => {
-                            // End
-                            s.move_to_line_end(ctx.shift);
-                        }
-                        9 => {
-                            // Tab - insert spaces
-                            s.insert("    ");
-                            changed = true;
-                        }
-                        _ => {
-                            cursor_changed = false;
-                        }
+                    let shift = ctx.shift;
+
+                    match find_replace::handle_key(&mut *s, ctx) {
+                        KeyOutcome::Handled => {}
+                        KeyOutcome::Edited => changed = true,
+                        KeyOutcome::Ignored => match ctx.key_code {
+                            8 => {
+                                // Backspace
+                                for_each_cursor(&mut *s, |s| s.delete_backward());
+                                changed = true;
+                            }
+                            127 => {
+                                // Delete
+                                for_each_cursor(&mut *s, |s| s.delete_forward());
+                                changed = true;
+                            }
+                            13 => {
+                                // Enter
+                                for_each_cursor(&mut *s, |s| s.insert("\n"));
+                                changed = true;
+                            }
+                            37 => {
+                                // Left arrow
+                                for_each_cursor(&mut *s, |s| s.move_left(shift));
+                            }
+                            39 => {
+                                // Right arrow
+                                for_each_cursor(&mut *s, |s| s.move_right(shift));
+                            }
+                            38 => {
+                                // Up arrow
+                                for_each_cursor(&mut *s, |s| s.move_up(shift));
+                            }
+                            40 => {
+                                // Down arrow
+                                for_each_cursor(&mut *s, |s| s.move_down(shift));
+                            }
+                            36 => {
+                                // Home
+                                for_each_cursor(&mut *s, |s| s.move_to_line_start(shift));
+                            }
+                            35 => {
+                                // End
+                                for_each_cursor(&mut *s, |s| s.move_to_line_end(shift));
+                            }
+                            9 => {
+                                // Tab - insert spaces
+                                for_each_cursor(&mut *s, |s| s.insert("    "));
+                                changed = true;
+                            }
+                            68 if ctx.ctrl || ctx.meta => {
+                                // Ctrl+D - add a cursor at the next occurrence
+                                s.add_next_occurrence();
+                            }
+                            27 => {
+                                // Escape - close find, then drop extra cursors
+                                if s.find.is_open() {
+                                    find_replace::close(&mut *s);
+                                } else {
+                                    s.cursors.clear();
+                                }
+                            }
+                            _ => {
+                                cursor_changed = false;
+                            }
+                        },
                     }
 
                     // Reset cursor blink on keystroke
@@ -830,6 +1176,7 @@ impl Code {
                     }
 
                     if changed {
+                        find_replace::refresh_matches(&mut *s);
                         if let Some(ref callback) = on_change_for_key {
                             callback(&s.value());
                         }
@@ -846,7 +1193,13 @@ impl Code {
                     }
 
                     if let Some(c) = ctx.key_char {
-                        s.insert(&c.to_string());
+                        if find_replace::handle_text_input(&mut *s, c) {
+                            request_rebuild();
+                            return;
+                        }
+
+                        for_each_cursor(&mut *s, |s| s.insert(&c.to_string()));
+                        find_replace::refresh_matches(&mut *s);
 
                         // Reset cursor blink
                         if let Ok(mut cs) = s.cursor_state.lock() {
@@ -860,6 +1213,28 @@ impl Code {
                         request_rebuild();
                     }
                 });
+
+            // Find bar over the top right corner
+            let state = self.state.lock().unwrap();
+            if state.find.is_open() {
+                let state_for_find = Arc::clone(&self.state);
+                let on_change_for_find = self.on_change.clone();
+                let on_action = Arc::new(move |action: FindAction| {
+                    let mut s = state_for_find.lock().unwrap();
+                    if find_replace::apply_action(&mut *s, action) {
+                        if let Some(ref callback) = on_change_for_find {
+                            callback(&s.value());
+                        }
+                    }
+                    request_rebuild();
+                });
+                container = container.relative().child(find_replace::find_bar(
+                    &state.find,
+                    &state.cursor_selection(),
+                    font_size,
+                    on_action,
+                ));
+            }
         }
 
         container
@@ -1031,4 +1406,35 @@ mod tests {
         state.delete_backward();
         assert_eq!(state.value(), "hell");
     }
+
+    #[test]
+    fn test_code_state_multi_cursor_and_find() {
+        let mut state = CodeState::new("x = 1\ny = 1");
+        state.add_cursor(TextPosition::new(1, 0));
+        for_each_cursor(&mut state, |s| s.insert("let "));
+        assert_eq!(state.value(), "let x = 1\nlet y = 1");
+
+        state.clear_secondary_cursors();
+        state.open_find(true);
+        state.set_find_query(
+            r"(\w) = 1",
+            FindOptions {
+                regex: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(state.find().matches().len(), 2);
+        state.set_replacement("$1 = 2");
+        assert_eq!(state.replace_all(), 2);
+        assert_eq!(state.value(), "let x = 2\nlet y = 2");
+    }
+
+    #[test]
+    fn test_column_at() {
+        let line = "hello";
+        let width = prefix_width(line, 2, 13.0);
+        assert_eq!(column_at(line, 0.0, 13.0), 0);
+        assert_eq!(column_at(line, width, 13.0), 2);
+        assert_eq!(column_at(line, 1000.0, 13.0), 5);
+    }
 }
@@ -5,8 +5,9 @@
 //! either a smooth sine wave or spring-based animation.
 
 use std::sync::{Arc, Mutex};
-use std::time::Instant;
+use std::time::Duration;
 
+use junita_core::clock;
 use junita_core::{Brush, Color, CornerRadius, DrawContext, Rect};
 
 use crate::canvas::{canvas, Canvas, CanvasBounds};
@@ -41,9 +42,9 @@ pub struct CursorState {
     pub animation: CursorAnimation,
     /// Blink period in milliseconds
     pub blink_period_ms: u64,
-    /// Time when cursor was last reset (e.g., on keystroke)
+    /// Clock time when cursor was last reset (e.g., on keystroke)
     /// This keeps the cursor visible immediately after typing
-    pub reset_time: Instant,
+    reset_time: Duration,
 }
 
 impl Default for CursorState {
@@ -55,7 +56,7 @@ impl Default for CursorState {
             x: 0.0,
             animation: CursorAnimation::default(),
             blink_period_ms: 530,
-            reset_time: Instant::now(),
+            reset_time: clock::now(),
         }
     }
 }
@@ -92,7 +93,12 @@ impl CursorState {
 
     /// Reset cursor blink (call on keystroke to keep cursor visible)
     pub fn reset_blink(&mut self) {
-        self.reset_time = Instant::now();
+        self.reset_time = clock::now();
+    }
+
+    /// Time on the [`junita_core::clock`] when the blink was last reset
+    pub fn reset_time(&self) -> Duration {
+        self.reset_time
     }
 
     /// Set cursor visibility (focused state)
@@ -114,7 +120,7 @@ impl CursorState {
             return 0.0;
         }
 
-        let elapsed = self.reset_time.elapsed().as_millis() as f64;
+        let elapsed = clock::now().saturating_sub(self.reset_time).as_millis() as f64;
         let period = self.blink_period_ms as f64;
 
         match self.animation {
@@ -0,0 +1,675 @@
+//! Find and replace for the text area and code widgets
+//!
+//! Ctrl+F opens a find bar over the widget and Ctrl+H opens it with a
+//! replace field. The bar has case-sensitive, whole-word and regex
+//! toggles, highlights every match and shows the match count.
+//!
+//! While the bar is open:
+//! - Typing edits the focused field; Tab switches fields
+//! - Enter selects the next match, Shift+Enter the previous one
+//! - Enter in the replace field replaces the current match
+//! - Escape closes the bar
+//!
+//! Regex replacements expand `$1` and `${name}` capture references.
+
+use std::sync::Arc;
+
+use junita_core::Color;
+use junita_theme::{ColorToken, ThemeState};
+use regex::{Regex, RegexBuilder};
+
+use crate::div::{div, Div};
+use crate::event_handler::EventContext;
+use crate::text::text;
+use crate::widgets::multi_cursor::{CursorEditor, CursorSelection};
+use crate::widgets::text_area::TextPosition;
+
+/// Matches past this many are not highlighted or counted
+const MAX_MATCHES: usize = 10_000;
+
+/// How the query is matched
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FindOptions {
+    /// Match letter case exactly
+    pub case_sensitive: bool,
+    /// Only match whole words
+    pub whole_word: bool,
+    /// Treat the query as a regular expression
+    pub regex: bool,
+}
+
+/// A range of text between two positions
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextRange {
+    pub start: TextPosition,
+    pub end: TextPosition,
+}
+
+/// Which field of the find bar has keyboard focus
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FindField {
+    Query,
+    Replacement,
+}
+
+/// A click on the find bar
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FindAction {
+    ToggleCaseSensitive,
+    ToggleWholeWord,
+    ToggleRegex,
+    Next,
+    Previous,
+    Replace,
+    ReplaceAll,
+    Close,
+    Focus(FindField),
+    /// A click on the bar outside its controls
+    Bar,
+}
+
+/// Find bar state
+#[derive(Clone, Debug, Default)]
+pub struct FindState {
+    /// Text or pattern to find
+    pub query: String,
+    /// Replacement text
+    pub replacement: String,
+    pub options: FindOptions,
+    open: bool,
+    show_replace: bool,
+    /// Field receiving typed text; `None` while typing goes to the text
+    pub(crate) field: Option<FindField>,
+    matches: Vec<TextRange>,
+    error: Option<String>,
+    /// Set by find bar clicks so the widget's own click handler leaves the
+    /// cursor alone
+    pub(crate) clicked: bool,
+}
+
+impl FindState {
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Whether the replace field is shown
+    pub fn is_replacing(&self) -> bool {
+        self.open && self.show_replace
+    }
+
+    /// The field typing goes to, if the bar has focus
+    pub fn focused_field(&self) -> Option<FindField> {
+        self.field.filter(|_| self.open)
+    }
+
+    /// Matches of the query, in text order
+    pub fn matches(&self) -> &[TextRange] {
+        &self.matches
+    }
+
+    /// Why the pattern is invalid, in regex mode
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Index of the match the selection covers
+    pub fn current_match(&self, selection: &CursorSelection) -> Option<usize> {
+        let (start, end) = selection.range();
+        self.matches
+            .iter()
+            .position(|m| m.start == start && m.end == end)
+    }
+
+    /// Match count for the bar, e.g. "3 of 12"
+    pub fn status(&self, selection: &CursorSelection) -> String {
+        if self.error.is_some() {
+            return "Invalid pattern".to_string();
+        }
+        let count = if self.matches.len() >= MAX_MATCHES {
+            format!("{}+", MAX_MATCHES)
+        } else {
+            self.matches.len().to_string()
+        };
+        match (self.matches.len(), self.current_match(selection)) {
+            (0, _) if self.query.is_empty() => String::new(),
+            (0, _) => "No results".to_string(),
+            (_, Some(index)) => format!("{} of {}", index + 1, count),
+            (1, None) => "1 match".to_string(),
+            (_, None) => format!("{} matches", count),
+        }
+    }
+
+    /// Re-run the search over `lines`
+    pub fn update(&mut self, lines: &[String]) {
+        self.matches.clear();
+        self.error = None;
+        if self.query.is_empty() {
+            return;
+        }
+        match self.regex() {
+            Ok(regex) => search(lines, &regex, |range, _| self.matches.push(range)),
+            Err(err) => self.error = Some(err.to_string()),
+        }
+    }
+
+    /// Each match with the text that replaces it
+    pub fn replacements(&self, lines: &[String]) -> Vec<(TextRange, String)> {
+        let Ok(regex) = self.regex() else {
+            return Vec::new();
+        };
+        let mut replacements = Vec::new();
+        search(lines, &regex, |range, captures| {
+            let mut replacement = String::new();
+            if self.options.regex {
+                captures.expand(&self.replacement, &mut replacement);
+            } else {
+                replacement.push_str(&self.replacement);
+            }
+            replacements.push((range, replacement));
+        });
+        replacements
+    }
+
+    fn regex(&self) -> Result<Regex, regex::Error> {
+        let pattern = if self.options.regex {
+            self.query.clone()
+        } else {
+            regex::escape(&self.query)
+        };
+        let pattern = if self.options.whole_word {
+            format!(r"\b(?:{})\b", pattern)
+        } else {
+            pattern
+        };
+        RegexBuilder::new(&pattern)
+            .case_insensitive(!self.options.case_sensitive)
+            .multi_line(true)
+            .build()
+    }
+
+    /// The first match at or after `pos`, wrapping around
+    fn next_from(&self, pos: TextPosition) -> Option<TextRange> {
+        let key = |p: TextPosition| (p.line, p.column);
+        self.matches
+            .iter()
+            .find(|m| key(m.start) >= key(pos))
+            .or_else(|| self.matches.first())
+            .copied()
+    }
+
+    /// The last match ending at or before `pos`, wrapping around
+    fn previous_from(&self, pos: TextPosition) -> Option<TextRange> {
+        let key = |p: TextPosition| (p.line, p.column);
+        self.matches
+            .iter()
+            .rev()
+            .find(|m| key(m.end) <= key(pos) && m.start != pos)
+            .or_else(|| self.matches.last())
+            .copied()
+    }
+}
+
+/// Call `f` with every non-empty match of `regex`
+fn search(lines: &[String], regex: &Regex, mut f: impl FnMut(TextRange, &regex::Captures)) {
+    let text = lines.join("\n");
+    let mut line_starts = Vec::with_capacity(lines.len());
+    let mut start = 0;
+    for line in lines {
+        line_starts.push(start);
+        start += line.len() + 1;
+    }
+    let position = |byte: usize| {
+        let line = line_starts.partition_point(|s| *s <= byte) - 1;
+        TextPosition::new(line, text[line_starts[line]..byte].chars().count())
+    };
+
+    for captures in regex.captures_iter(&text).take(MAX_MATCHES) {
+        let whole = captures.get(0).unwrap();
+        if whole.is_empty() {
+            continue;
+        }
+        let range = TextRange {
+            start: position(whole.start()),
+            end: position(whole.end()),
+        };
+        f(range, &captures);
+    }
+}
+
+/// Open the find bar, seeding the query from a single-line selection
+pub(crate) fn open<T: CursorEditor>(editor: &mut T, replace: bool) {
+    let selection = editor.cursor_selection();
+    let (from, to) = selection.range();
+    let seed = (selection.has_selection() && from.line == to.line).then(|| {
+        editor.lines()[from.line]
+            .chars()
+            .skip(from.column)
+            .take(to.column - from.column)
+            .collect::<String>()
+    });
+    let find = editor.find_state();
+    find.open = true;
+    find.show_replace = replace;
+    find.field = Some(if replace && !find.query.is_empty() && seed.is_none() {
+        FindField::Replacement
+    } else {
+        FindField::Query
+    });
+    if let Some(seed) = seed {
+        find.query = seed;
+    }
+    refresh_matches(editor);
+}
+
+/// Close the find bar, returning focus to the text
+pub(crate) fn close<T: CursorEditor>(editor: &mut T) {
+    let find = editor.find_state();
+    find.open = false;
+    find.field = None;
+    find.matches.clear();
+}
+
+/// Re-run the search after the text or query changes
+pub(crate) fn refresh_matches<T: CursorEditor>(editor: &mut T) {
+    if !editor.find_state().open {
+        return;
+    }
+    let lines = editor.lines().to_vec();
+    editor.find_state().update(&lines);
+}
+
+/// Select the next (or previous) match
+pub(crate) fn select_match<T: CursorEditor>(editor: &mut T, backwards: bool) {
+    let selection = editor.cursor_selection();
+    let (from, to) = selection.range();
+    let find = editor.find_state();
+    let found = if backwards {
+        find.previous_from(from)
+    } else {
+        find.next_from(if selection.has_selection() { to } else { from })
+    };
+    if let Some(found) = found {
+        editor.multi_cursor().clear();
+        editor.set_cursor_selection(CursorSelection::with_selection(found.start, found.end));
+    }
+}
+
+/// Replace the selected match and select the next one
+///
+/// With no match selected this only selects the next match. Returns
+/// whether the text changed.
+pub(crate) fn replace_current<T: CursorEditor>(editor: &mut T) -> bool {
+    let selection = editor.cursor_selection();
+    let lines = editor.lines().to_vec();
+    let (from, to) = selection.range();
+    let replacement = editor
+        .find_state()
+        .replacements(&lines)
+        .into_iter()
+        .find(|(range, _)| range.start == from && range.end == to);
+    let Some((_, replacement)) = replacement else {
+        select_match(editor, false);
+        return false;
+    };
+    editor.multi_cursor().clear();
+    editor.insert_text(&replacement);
+    refresh_matches(editor);
+    select_match(editor, false);
+    true
+}
+
+/// Replace every match, returning how many were replaced
+pub(crate) fn replace_all<T: CursorEditor>(editor: &mut T) -> usize {
+    let lines = editor.lines().to_vec();
+    let replacements = editor.find_state().replacements(&lines);
+    editor.multi_cursor().clear();
+    // Last match first, so the ranges before it stay valid
+    for (range, replacement) in replacements.iter().rev() {
+        editor.set_cursor_selection(CursorSelection::with_selection(range.start, range.end));
+        editor.insert_text(replacement);
+    }
+    refresh_matches(editor);
+    replacements.len()
+}
+
+/// Apply a find bar click; returns whether the text changed
+pub(crate) fn apply_action<T: CursorEditor>(editor: &mut T, action: FindAction) -> bool {
+    let find = editor.find_state();
+    find.clicked = true;
+    match action {
+        FindAction::ToggleCaseSensitive => find.options.case_sensitive ^= true,
+        FindAction::ToggleWholeWord => find.options.whole_word ^= true,
+        FindAction::ToggleRegex => find.options.regex ^= true,
+        FindAction::Focus(field) => {
+            find.field = Some(field);
+            return false;
+        }
+        FindAction::Next => {
+            select_match(editor, false);
+            return false;
+        }
+        FindAction::Previous => {
+            select_match(editor, true);
+            return false;
+        }
+        FindAction::Replace => return replace_current(editor),
+        FindAction::ReplaceAll => return replace_all(editor) > 0,
+        FindAction::Close => {
+            close(editor);
+            return false;
+        }
+        FindAction::Bar => return false,
+    }
+    refresh_matches(editor);
+    false
+}
+
+/// What a key press did
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum KeyOutcome {
+    /// Not a find key; the widget handles it
+    Ignored,
+    /// Handled without changing the text
+    Handled,
+    /// Handled and the text changed
+    Edited,
+}
+
+/// Find bar shortcuts, and editing keys while a find field has focus
+pub(crate) fn handle_key<T: CursorEditor>(editor: &mut T, ctx: &EventContext) -> KeyOutcome {
+    let command = ctx.ctrl || ctx.meta;
+    match ctx.key_code {
+        70 if command => {
+            open(editor, false);
+            return KeyOutcome::Handled;
+        }
+        72 if command => {
+            open(editor, true);
+            return KeyOutcome::Handled;
+        }
+        _ => {}
+    }
+
+    let Some(field) = editor.find_state().focused_field() else {
+        // F3 / Shift+F3 keep working from the text
+        if ctx.key_code == 114 && editor.find_state().open {
+            select_match(editor, ctx.shift);
+            return KeyOutcome::Handled;
+        }
+        return KeyOutcome::Ignored;
+    };
+
+    let find = editor.find_state();
+    match ctx.key_code {
+        27 => close(editor),
+        9 if find.show_replace => {
+            find.field = Some(match field {
+                FindField::Query => FindField::Replacement,
+                FindField::Replacement => FindField::Query,
+            });
+        }
+        8 => {
+            match field {
+                FindField::Query => find.query.pop(),
+                FindField::Replacement => find.replacement.pop(),
+            };
+            refresh_matches(editor);
+        }
+        13 | 114 if field == FindField::Replacement && !ctx.shift => {
+            return if replace_current(editor) {
+                KeyOutcome::Edited
+            } else {
+                KeyOutcome::Handled
+            };
+        }
+        13 | 114 => select_match(editor, ctx.shift),
+        // Arrows and other keys still move the text cursor
+        _ => return KeyOutcome::Ignored,
+    }
+    KeyOutcome::Handled
+}
+
+/// Send typed text to the focused find field; returns false when the
+/// text itself should get it
+pub(crate) fn handle_text_input<T: CursorEditor>(editor: &mut T, c: char) -> bool {
+    let find = editor.find_state();
+    match find.focused_field() {
+        Some(FindField::Query) => find.query.push(c),
+        Some(FindField::Replacement) => find.replacement.push(c),
+        None => return false,
+    }
+    refresh_matches(editor);
+    true
+}
+
+/// Colors of match highlights: (other matches, the selected match)
+pub(crate) fn match_colors(selection_color: Color) -> (Color, Color) {
+    let theme = ThemeState::get();
+    let accent = theme.color(ColorToken::Warning);
+    (
+        Color::rgba(accent.r, accent.g, accent.b, 0.25),
+        Color::rgba(
+            selection_color.r,
+            selection_color.g,
+            selection_color.b,
+            selection_color.a.max(0.5),
+        ),
+    )
+}
+
+/// Build the find bar
+///
+/// `on_action` runs for clicks on the bar, before the widget's own mouse
+/// down handler.
+pub(crate) fn find_bar(
+    find: &FindState,
+    selection: &CursorSelection,
+    font_size: f32,
+    on_action: Arc<dyn Fn(FindAction) + Send + Sync>,
+) -> Div {
+    let theme = ThemeState::get();
+    let text_color = theme.color(ColorToken::TextPrimary);
+    let muted = theme.color(ColorToken::TextTertiary);
+    let border = theme.color(ColorToken::Border);
+    let accent = theme.color(ColorToken::Accent);
+    let active_bg = theme.color(ColorToken::AccentSubtle);
+    let field_bg = theme.color(ColorToken::InputBg);
+    let size = font_size * 0.9;
+    let height = size * 1.9;
+
+    let clickable = |content: Div, action: FindAction| {
+        let on_action = Arc::clone(&on_action);
+        content
+            .cursor_pointer()
+            .on_mouse_down(move |_| on_action(action))
+    };
+    let field = |value: &str, placeholder: &str, which: FindField| {
+        let focused = find.focused_field() == Some(which);
+        let (label, color) = if value.is_empty() {
+            (placeholder.to_string(), muted)
+        } else {
+            (value.to_string(), text_color)
+        };
+        let mut field = div()
+            .w(size * 12.0)
+            .h(height)
+            .flex_row()
+            .items_center()
+            .padding_x_px(6.0)
+            .bg(field_bg)
+            .border(1.0, if focused { accent } else { border })
+            .rounded(4.0)
+            .overflow_clip()
+            .child(text(label).size(size).color(color).no_wrap());
+        if focused {
+            field = field.child(div().w(1.5).h(size * 1.2).bg(accent));
+        }
+        clickable(field, FindAction::Focus(which))
+    };
+    let button = |label: &str, active: bool, action: FindAction| {
+        let content = div()
+            .h(height)
+            .min_w(height)
+            .padding_x_px(5.0)
+            .flex_row()
+            .items_center()
+            .justify_center()
+            .rounded(4.0)
+            .bg(if active {
+                active_bg
+            } else {
+                Color::TRANSPARENT
+            })
+            .child(
+                text(label)
+                    .size(size)
+                    .color(if active { accent } else { text_color })
+                    .no_wrap(),
+            );
+        clickable(content, action)
+    };
+
+    let status = find.status(selection);
+    let status_color = if find.error.is_some() {
+        theme.color(ColorToken::Error)
+    } else {
+        muted
+    };
+    let find_row = div()
+        .flex_row()
+        .items_center()
+        .gap_px(4.0)
+        .child(field(&find.query, "Find", FindField::Query))
+        .child(button(
+            "Aa",
+            find.options.case_sensitive,
+            FindAction::ToggleCaseSensitive,
+        ))
+        .child(button(
+            "W",
+            find.options.whole_word,
+            FindAction::ToggleWholeWord,
+        ))
+        .child(button(".*", find.options.regex, FindAction::ToggleRegex))
+        .child(
+            div()
+                .min_w(size * 5.5)
+                .padding_x_px(4.0)
+                .child(text(status).size(size).color(status_color).no_wrap()),
+        )
+        .child(button("↑", false, FindAction::Previous))
+        .child(button("↓", false, FindAction::Next))
+        .child(button("×", false, FindAction::Close));
+
+    let mut bar = div()
+        .absolute()
+        .top(4.0)
+        .right(4.0)
+        .flex_col()
+        .gap_px(4.0)
+        .p(1.5)
+        .bg(theme.color(ColorToken::SurfaceElevated))
+        .border(1.0, border)
+        .rounded(6.0)
+        .shadow_md()
+        // Clicks anywhere on the bar stay off the text
+        .on_mouse_down({
+            let on_action = Arc::clone(&on_action);
+            move |_| on_action(FindAction::Bar)
+        })
+        .child(find_row);
+
+    if find.show_replace {
+        bar = bar.child(
+            div()
+                .flex_row()
+                .items_center()
+                .gap_px(4.0)
+                .child(field(&find.replacement, "Replace", FindField::Replacement))
+                .child(button("Replace", false, FindAction::Replace))
+                .child(button("All", false, FindAction::ReplaceAll)),
+        );
+    }
+    bar
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(text: &str) -> Vec<String> {
+        text.split('\n').map(String::from).collect()
+    }
+
+    fn find(query: &str, options: FindOptions) -> FindState {
+        FindState {
+            query: query.to_string(),
+            options,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_options() {
+        let text = lines("Foo food\nfoo");
+        let mut state = find("foo", FindOptions::default());
+        state.update(&text);
+        assert_eq!(state.matches().len(), 3);
+
+        state.options.case_sensitive = true;
+        state.update(&text);
+        assert_eq!(state.matches().len(), 2);
+
+        state.options.whole_word = true;
+        state.update(&text);
+        assert_eq!(
+            state.matches(),
+            &[TextRange {
+                start: TextPosition::new(1, 0),
+                end: TextPosition::new(1, 3),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_regex_replacements_expand_captures() {
+        let text = lines("port = 80\nhost = \"a\"");
+        let mut state = find(
+            r"(\w+) = (.+)",
+            FindOptions {
+                regex: true,
+                ..Default::default()
+            },
+        );
+        state.replacement = "$1: $2".to_string();
+        let replacements: Vec<String> = state
+            .replacements(&text)
+            .into_iter()
+            .map(|(_, r)| r)
+            .collect();
+        assert_eq!(replacements, vec!["port: 80", "host: \"a\""]);
+
+        state.query = "(".to_string();
+        state.update(&text);
+        assert!(state.error().is_some());
+        assert_eq!(state.status(&CursorSelection::default()), "Invalid pattern");
+    }
+
+    #[test]
+    fn test_status_and_navigation() {
+        let text = lines("a b a\na");
+        let mut state = find("a", FindOptions::default());
+        state.update(&text);
+        assert_eq!(state.status(&CursorSelection::default()), "3 matches");
+
+        let first = state.next_from(TextPosition::new(0, 1)).unwrap();
+        assert_eq!(first.start, TextPosition::new(0, 4));
+        let selection = CursorSelection::with_selection(first.start, first.end);
+        assert_eq!(state.status(&selection), "2 of 3");
+        // Wraps around at the end
+        let next = state.next_from(TextPosition::new(1, 1)).unwrap();
+        assert_eq!(next.start, TextPosition::new(0, 0));
+        let previous = state.previous_from(TextPosition::new(0, 0)).unwrap();
+        assert_eq!(previous.start, TextPosition::new(1, 0));
+    }
+}
@@ -9,6 +9,7 @@
 //! - [`checkbox()`] - Toggle checkbox with label support
 //! - [`text_input()`] - Single-line text input with validation
 //! - [`text_area()`] - Multi-line text area
+//! - [`rich_text_editor()`] - WYSIWYG rich text editor
 //! - [`scroll()`] - Scrollable container with bounce physics
 //! - [`code()`] - Code block with syntax highlighting and line numbers
 //!
@@ -36,10 +37,13 @@ pub mod button;
 pub mod checkbox;
 pub mod code;
 pub mod cursor;
+pub mod find_replace;
 pub mod hr;
 pub mod link;
 pub mod list;
+pub mod multi_cursor;
 pub mod overlay;
+pub mod rich_text_editor;
 pub mod scroll;
 pub mod table;
 pub mod text_area;
@@ -87,6 +91,17 @@ pub use text_area::{
     TextAreaConfig, TextAreaState, TextPosition,
 };
 
+// Re-export find/replace and multi-cursor types
+pub use find_replace::{FindField, FindOptions, FindState, TextRange};
+pub use multi_cursor::{CursorSelection, MultiCursor};
+
+// Re-export rich text editor widget
+pub use rich_text_editor::{
+    rich_text_editor, rich_text_editor_state, rich_text_editor_state_with_placeholder,
+    EmbedRenderer, RichTextEditor, RichTextEditorConfig, RichTextEditorState,
+    SharedRichTextEditorState,
+};
+
 // Re-export scroll widget
 pub use scroll::{
     scroll, scroll_no_bounce, Scroll, ScrollConfig, ScrollDirection, ScrollPhysics,
@@ -101,7 +116,7 @@ pub use cursor::{
 };
 
 // Re-export code widget
-pub use code::{code, pre, Code, CodeConfig};
+pub use code::{code, code_state, pre, Code, CodeConfig, CodeState, SharedCodeState};
 
 // Re-export overlay widget
 pub use overlay::{
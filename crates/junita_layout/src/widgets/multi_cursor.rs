@@ -0,0 +1,442 @@
+//! Multiple cursors and selections for the text area and code widgets
+//!
+//! A widget keeps its own cursor and selection (the primary cursor) and a
+//! [`MultiCursor`] holding any others. Edits and movement run once per
+//! cursor through the widget's existing single-cursor methods, last
+//! cursor first, so each edit leaves the positions before it untouched.
+//!
+//! - Alt+click adds or removes a cursor
+//! - Ctrl+D selects the word under the cursor, then adds the next
+//!   occurrence of the selection
+//! - Alt+drag makes a column selection, one cursor per line
+
+use crate::widgets::find_replace::FindState;
+use crate::widgets::text_area::TextPosition;
+
+/// A cursor and the other end of its selection, if any
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub struct CursorSelection {
+    /// Cursor position (the moving end of the selection)
+    pub cursor: TextPosition,
+    /// Selection start position (if selecting)
+    pub selection_start: Option<TextPosition>,
+}
+
+impl CursorSelection {
+    /// A cursor with nothing selected
+    pub fn new(cursor: TextPosition) -> Self {
+        Self {
+            cursor,
+            selection_start: None,
+        }
+    }
+
+    /// A selection from `start` to `cursor`
+    pub fn with_selection(start: TextPosition, cursor: TextPosition) -> Self {
+        Self {
+            cursor,
+            selection_start: (start != cursor).then_some(start),
+        }
+    }
+
+    /// Start and end of the selection, in text order
+    pub fn range(&self) -> (TextPosition, TextPosition) {
+        order(self.selection_start.unwrap_or(self.cursor), self.cursor)
+    }
+
+    pub fn has_selection(&self) -> bool {
+        self.selection_start
+            .is_some_and(|start| start != self.cursor)
+    }
+}
+
+/// A line-based editor that multiple cursors and find/replace drive
+pub(crate) trait CursorEditor {
+    fn lines(&self) -> &[String];
+    fn cursor_selection(&self) -> CursorSelection;
+    fn set_cursor_selection(&mut self, selection: CursorSelection);
+    /// Replace the selection with text through the editor's insert path
+    fn insert_text(&mut self, text: &str);
+    fn multi_cursor(&mut self) -> &mut MultiCursor;
+    fn find_state(&mut self) -> &mut FindState;
+}
+
+/// An Alt+drag column selection in progress
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ColumnDrag {
+    /// Row the drag started on
+    pub row: usize,
+    /// x the drag started at, relative to the row's text
+    pub x: f32,
+}
+
+/// Cursors besides the primary one
+#[derive(Clone, Debug, Default)]
+pub struct MultiCursor {
+    secondary: Vec<CursorSelection>,
+    /// Set on Alt+mouse down, read by the drag handler
+    pub(crate) column_drag: Option<ColumnDrag>,
+}
+
+impl MultiCursor {
+    /// Cursors besides the primary one, in text order
+    pub fn secondary(&self) -> &[CursorSelection] {
+        &self.secondary
+    }
+
+    /// Whether there is more than one cursor
+    pub fn is_active(&self) -> bool {
+        !self.secondary.is_empty()
+    }
+
+    /// Drop all but the primary cursor
+    pub fn clear(&mut self) {
+        self.secondary.clear();
+    }
+}
+
+/// Order two positions (earlier, later)
+fn order(a: TextPosition, b: TextPosition) -> (TextPosition, TextPosition) {
+    if (a.line, a.column) <= (b.line, b.column) {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+/// Character offset of a position, counting line breaks
+pub(crate) fn offset_of(lines: &[String], pos: TextPosition) -> usize {
+    let line = pos.line.min(lines.len().saturating_sub(1));
+    let before: usize = lines[..line].iter().map(|l| l.chars().count() + 1).sum();
+    before + pos.column.min(lines[line].chars().count())
+}
+
+/// Position of a character offset
+pub(crate) fn position_at(lines: &[String], mut offset: usize) -> TextPosition {
+    for (line, text) in lines.iter().enumerate() {
+        let len = text.chars().count();
+        if offset <= len {
+            return TextPosition::new(line, offset);
+        }
+        offset -= len + 1;
+    }
+    let last = lines.len().saturating_sub(1);
+    TextPosition::new(last, lines.get(last).map_or(0, |l| l.chars().count()))
+}
+
+fn text_len(lines: &[String]) -> usize {
+    lines.iter().map(|l| l.chars().count() + 1).sum::<usize>() - 1
+}
+
+/// All cursors in text order, with the index of the primary one
+pub(crate) fn all_cursors<T: CursorEditor>(editor: &mut T) -> (Vec<CursorSelection>, usize) {
+    let primary = editor.cursor_selection();
+    let mut all = editor.multi_cursor().secondary.clone();
+    all.push(primary);
+    all.sort_by_key(|c| {
+        let (from, _) = c.range();
+        (from.line, from.column)
+    });
+    let index = all.iter().position(|c| *c == primary).unwrap_or(0);
+    (all, index)
+}
+
+/// Replace all cursors; overlapping selections merge
+pub(crate) fn set_cursors<T: CursorEditor>(
+    editor: &mut T,
+    cursors: Vec<CursorSelection>,
+    primary: usize,
+) {
+    let mut tagged: Vec<(bool, CursorSelection)> = cursors
+        .into_iter()
+        .enumerate()
+        .map(|(i, c)| (i == primary, c))
+        .collect();
+    tagged.sort_by_key(|(_, c)| {
+        let (from, _) = c.range();
+        (from.line, from.column)
+    });
+
+    let mut merged: Vec<(bool, CursorSelection)> = Vec::with_capacity(tagged.len());
+    for (is_primary, cursor) in tagged {
+        if let Some((last_primary, last)) = merged.last_mut() {
+            let (last_from, last_to) = last.range();
+            let (from, to) = cursor.range();
+            let overlaps = (from.line, from.column) < (last_to.line, last_to.column)
+                || from == last_to && (!cursor.has_selection() || !last.has_selection());
+            if overlaps {
+                let (_, end) = order(last_to, to);
+                *last = CursorSelection::with_selection(last_from, end);
+                *last_primary |= is_primary;
+                continue;
+            }
+        }
+        merged.push((is_primary, cursor));
+    }
+
+    let index = merged.iter().position(|(p, _)| *p).unwrap_or(0);
+    let (_, primary) = merged.remove(index);
+    editor.set_cursor_selection(primary);
+    editor.multi_cursor().secondary = merged.into_iter().map(|(_, c)| c).collect();
+}
+
+/// Run an edit or movement at every cursor
+///
+/// The operation sees one cursor at a time as the editor's own cursor, so
+/// it goes through the editor's single-cursor paths.
+pub(crate) fn for_each_cursor<T: CursorEditor>(editor: &mut T, mut op: impl FnMut(&mut T)) {
+    if !editor.multi_cursor().is_active() {
+        op(editor);
+        return;
+    }
+    let (cursors, primary) = all_cursors(editor);
+
+    // Offsets of finished cursors; later edits only shift them
+    let mut done: Vec<(usize, usize, Option<usize>)> = Vec::with_capacity(cursors.len());
+    for (index, cursor) in cursors.into_iter().enumerate().rev() {
+        let before = text_len(editor.lines());
+        editor.set_cursor_selection(cursor);
+        op(editor);
+        let delta = text_len(editor.lines()) as isize - before as isize;
+        let shift = |offset: &mut usize| *offset = (*offset as isize + delta).max(0) as usize;
+        for (_, cursor, start) in &mut done {
+            shift(cursor);
+            if let Some(start) = start {
+                shift(start);
+            }
+        }
+        let result = editor.cursor_selection();
+        let lines = editor.lines();
+        done.push((
+            index,
+            offset_of(lines, result.cursor),
+            result.selection_start.map(|s| offset_of(lines, s)),
+        ));
+    }
+
+    let lines = editor.lines();
+    let cursors: Vec<CursorSelection> = done
+        .iter()
+        .map(|&(_, cursor, start)| CursorSelection {
+            cursor: position_at(lines, cursor),
+            selection_start: start.map(|s| position_at(lines, s)),
+        })
+        .collect();
+    let primary = done.iter().position(|(i, _, _)| *i == primary).unwrap_or(0);
+    set_cursors(editor, cursors, primary);
+}
+
+/// Add a cursor at `pos`, or remove the cursor already there
+pub(crate) fn toggle_cursor<T: CursorEditor>(editor: &mut T, pos: TextPosition) {
+    let (mut cursors, mut primary) = all_cursors(editor);
+    if let Some(existing) = cursors.iter().position(|c| c.cursor == pos) {
+        if cursors.len() > 1 {
+            cursors.remove(existing);
+            primary = if existing == primary {
+                cursors.len() - 1
+            } else if existing < primary {
+                primary - 1
+            } else {
+                primary
+            };
+        }
+    } else {
+        cursors.push(CursorSelection::new(pos));
+        primary = cursors.len() - 1;
+    }
+    set_cursors(editor, cursors, primary);
+}
+
+/// Ctrl+D: select the word at the cursor, or add a cursor at the next
+/// occurrence of the selected text
+///
+/// Returns false when there is nothing more to add.
+pub(crate) fn add_next_occurrence<T: CursorEditor>(editor: &mut T) -> bool {
+    let primary = editor.cursor_selection();
+    let lines = editor.lines();
+
+    if !primary.has_selection() {
+        let line: Vec<char> = lines[primary.cursor.line].chars().collect();
+        let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+        let column = primary.cursor.column.min(line.len());
+        let start = line[..column]
+            .iter()
+            .rposition(|c| !is_word(c))
+            .map_or(0, |i| i + 1);
+        let end = line[column..]
+            .iter()
+            .position(|c| !is_word(c))
+            .map_or(line.len(), |i| column + i);
+        if start == end {
+            return false;
+        }
+        let row = primary.cursor.line;
+        editor.set_cursor_selection(CursorSelection::with_selection(
+            TextPosition::new(row, start),
+            TextPosition::new(row, end),
+        ));
+        return true;
+    }
+
+    let (from, to) = primary.range();
+    let text = lines.join("\n");
+    let (start, end) = (offset_of(lines, from), offset_of(lines, to));
+    let needle: String = text.chars().skip(start).take(end - start).collect();
+
+    // Search after the primary selection, wrapping around
+    let byte_at = |chars: usize| {
+        text.char_indices()
+            .nth(chars)
+            .map_or(text.len(), |(b, _)| b)
+    };
+    let after = byte_at(end);
+    let found = text[after..]
+        .find(&needle)
+        .map(|b| after + b)
+        .or_else(|| text[..after].find(&needle));
+    let Some(found) = found else {
+        return false;
+    };
+    let found_start = text[..found].chars().count();
+    let selection = CursorSelection::with_selection(
+        position_at(lines, found_start),
+        position_at(lines, found_start + (end - start)),
+    );
+
+    let (mut cursors, _) = all_cursors(editor);
+    if cursors.iter().any(|c| c.range() == selection.range()) {
+        return false;
+    }
+    cursors.push(selection);
+    let primary = cursors.len() - 1;
+    set_cursors(editor, cursors, primary);
+    true
+}
+
+/// Alt+drag: one selection per row from `start_x` to `end_x`
+///
+/// `rows` gives each row's logical position for an x offset.
+pub(crate) fn column_selection<T: CursorEditor>(
+    editor: &mut T,
+    rows: impl Iterator<Item = (TextPosition, TextPosition)>,
+) {
+    let cursors: Vec<CursorSelection> = rows
+        .map(|(start, end)| CursorSelection::with_selection(start, end))
+        .collect();
+    if cursors.is_empty() {
+        return;
+    }
+    let primary = cursors.len() - 1;
+    set_cursors(editor, cursors, primary);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct Editor {
+        lines: Vec<String>,
+        cursor: CursorSelection,
+        cursors: MultiCursor,
+        find: FindState,
+    }
+
+    impl Editor {
+        fn new(text: &str) -> Self {
+            Self {
+                lines: text.split('\n').map(String::from).collect(),
+                ..Default::default()
+            }
+        }
+
+        fn backspace(&mut self) {
+            let pos = self.cursor.cursor;
+            if pos.column > 0 {
+                let line = &mut self.lines[pos.line];
+                let byte = line.char_indices().nth(pos.column - 1).unwrap().0;
+                line.remove(byte);
+                self.cursor = CursorSelection::new(TextPosition::new(pos.line, pos.column - 1));
+            }
+        }
+    }
+
+    impl CursorEditor for Editor {
+        fn lines(&self) -> &[String] {
+            &self.lines
+        }
+
+        fn cursor_selection(&self) -> CursorSelection {
+            self.cursor
+        }
+
+        fn set_cursor_selection(&mut self, selection: CursorSelection) {
+            self.cursor = selection;
+        }
+
+        fn insert_text(&mut self, text: &str) {
+            let (from, to) = self.cursor.range();
+            let all = self.lines.join("\n");
+            let (start, end) = (offset_of(&self.lines, from), offset_of(&self.lines, to));
+            let mut chars: Vec<char> = all.chars().collect();
+            chars.splice(start..end, text.chars());
+            self.lines = chars
+                .into_iter()
+                .collect::<String>()
+                .split('\n')
+                .map(String::from)
+                .collect();
+            let cursor = position_at(&self.lines, start + text.chars().count());
+            self.cursor = CursorSelection::new(cursor);
+        }
+
+        fn multi_cursor(&mut self) -> &mut MultiCursor {
+            &mut self.cursors
+        }
+
+        fn find_state(&mut self) -> &mut FindState {
+            &mut self.find
+        }
+    }
+
+    #[test]
+    fn test_typing_at_every_cursor() {
+        let mut editor = Editor::new("ab\ncd");
+        editor.cursor = CursorSelection::new(TextPosition::new(0, 1));
+        toggle_cursor(&mut editor, TextPosition::new(0, 2));
+        toggle_cursor(&mut editor, TextPosition::new(1, 0));
+
+        for_each_cursor(&mut editor, |e| e.insert_text("x\ny"));
+        assert_eq!(editor.lines.join("\n"), "ax\nybx\ny\nx\nycd");
+
+        for_each_cursor(&mut editor, |e| e.backspace());
+        assert_eq!(editor.lines.join("\n"), "ax\nbx\n\nx\ncd");
+        assert_eq!(editor.cursors.secondary().len(), 2);
+    }
+
+    #[test]
+    fn test_add_next_occurrence() {
+        let mut editor = Editor::new("foo bar\nfoo foo");
+        editor.cursor = CursorSelection::new(TextPosition::new(0, 1));
+        assert!(add_next_occurrence(&mut editor));
+        assert_eq!(editor.cursor.range().1, TextPosition::new(0, 3));
+        assert!(add_next_occurrence(&mut editor));
+        assert!(add_next_occurrence(&mut editor));
+        // Every occurrence is selected
+        assert!(!add_next_occurrence(&mut editor));
+
+        for_each_cursor(&mut editor, |e| e.insert_text("baz"));
+        assert_eq!(editor.lines.join("\n"), "baz bar\nbaz baz");
+    }
+
+    #[test]
+    fn test_overlapping_cursors_merge() {
+        let mut editor = Editor::new("abcdef");
+        editor.cursor = CursorSelection::new(TextPosition::new(0, 2));
+        toggle_cursor(&mut editor, TextPosition::new(0, 3));
+        for_each_cursor(&mut editor, |e| e.backspace());
+        for_each_cursor(&mut editor, |e| e.backspace());
+        assert_eq!(editor.lines[0], "def");
+        assert!(!editor.cursors.is_active());
+    }
+}
@@ -0,0 +1,2130 @@
+//! Ready-to-use rich text editor widget
+//!
+//! WYSIWYG editing of a [`RichDocument`]:
+//! - Bold, italic, underline, strikethrough, code, links and colors
+//! - Headings, bullet and numbered lists, quotes and code blocks
+//! - Inline images and application-defined embeds
+//! - Undo/redo of whole edits, with typing coalesced
+//! - Paste from HTML, Markdown or plain text
+//!
+//! Every edit is a [`Transaction`]. Local edits are queued for
+//! [`take_changes`](RichTextEditorState::take_changes) and edits from
+//! collaborators are applied with
+//! [`apply_remote`](RichTextEditorState::apply_remote), which keeps the
+//! selection and undo history in place.
+//!
+//! ```ignore
+//! let state = rich_text_editor_state();
+//! state.lock().unwrap().set_document(RichDocument::from_markdown("# Notes\n\n- one\n- two"));
+//!
+//! div()
+//!     .child(button("Bold").on_click({
+//!         let state = state.clone();
+//!         move |_| state.lock().unwrap().toggle_mark(Mark::Bold)
+//!     }))
+//!     .child(rich_text_editor(&state).w(480.0))
+//! ```
+
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex, Weak,
+};
+
+use junita_core::reactive::SignalId;
+use junita_core::Color;
+use junita_theme::{ColorToken, ThemeState};
+
+use crate::canvas::canvas;
+use crate::div::{div, Div, ElementBuilder, GenericFont};
+use crate::element::RenderProps;
+use crate::image::img;
+use crate::rich_text::{
+    add_mark, remove_mark, Bias, BlockKind, DocPosition, Embed, Inline, Mark, OperationError,
+    RichDocument, RichText, Selection, Transaction,
+};
+use crate::stateful::{
+    refresh_stateful, SharedState, StateTransitions, Stateful, StatefulInner, TextFieldState,
+};
+use crate::styled_text::{StyledLine, StyledText, TextSpan};
+use crate::text::text;
+use crate::text_measure::{measure_text_with_options, TextLayoutOptions};
+use crate::text_selection::{clear_selection, set_selection, SelectionSource};
+use crate::tree::{LayoutNodeId, LayoutTree};
+use crate::widgets::cursor::{cursor_state, CursorAnimation, SharedCursorState};
+use crate::widgets::text_input::{
+    blur_all_text_inputs, decrement_focus_count, increment_focus_count,
+    request_continuous_redraw_pub,
+};
+
+/// Undo entries kept per editor
+const MAX_HISTORY: usize = 200;
+
+/// Deepest list nesting
+const MAX_LIST_INDENT: u8 = 8;
+
+static FOCUSED_RICH_TEXT_EDITOR: Mutex<Option<Weak<Mutex<RichTextEditorState>>>> = Mutex::new(None);
+
+fn set_focused_rich_text_editor(state: &SharedRichTextEditorState) {
+    // Blurs text inputs, text areas and any other editor
+    blur_all_text_inputs();
+    *FOCUSED_RICH_TEXT_EDITOR.lock().unwrap() = Some(Arc::downgrade(state));
+}
+
+/// Blur the focused rich text editor, if any
+pub(crate) fn blur_focused_rich_text_editor() {
+    use junita_core::events::event_types;
+
+    let focused = FOCUSED_RICH_TEXT_EDITOR.lock().unwrap().take();
+    let Some(state) = focused.and_then(|weak| weak.upgrade()) else {
+        return;
+    };
+    let stateful = {
+        let Ok(mut s) = state.lock() else {
+            return;
+        };
+        if !s.visual.is_focused() {
+            return;
+        }
+        if let Some(new_state) = s.visual.on_event(event_types::BLUR) {
+            s.visual = new_state;
+            decrement_focus_count();
+        }
+        s.stateful_state.clone()
+    };
+    // Keep the FSM in sync, then refresh after releasing the data lock
+    if let Some(stateful) = stateful {
+        if let Ok(mut shared) = stateful.lock() {
+            if let Some(new_fsm) = shared.state.on_event(event_types::BLUR) {
+                shared.state = new_fsm;
+                shared.needs_visual_update = true;
+            }
+        }
+        refresh_stateful(&stateful);
+    }
+}
+
+/// Renders an [`Embed::Custom`] at the given line height
+pub type EmbedRenderer = Arc<dyn Fn(&Embed, f32) -> Box<dyn ElementBuilder> + Send + Sync>;
+
+/// Rich text editor configuration
+#[derive(Clone)]
+pub struct RichTextEditorConfig {
+    /// Placeholder text shown when empty
+    pub placeholder: String,
+    /// Width of the editor
+    pub width: f32,
+    /// Minimum height; the editor grows with its content
+    pub min_height: f32,
+    /// Body font size; headings scale from it
+    pub font_size: f32,
+    /// Line height multiplier
+    pub line_height: f32,
+    pub text_color: Color,
+    pub placeholder_color: Color,
+    pub link_color: Color,
+    /// Inline code text color
+    pub code_color: Color,
+    /// Code block background
+    pub code_bg_color: Color,
+    /// Quote bar color
+    pub quote_color: Color,
+    pub bg_color: Color,
+    pub hover_bg_color: Color,
+    pub focused_bg_color: Color,
+    pub border_color: Color,
+    pub hover_border_color: Color,
+    pub focused_border_color: Color,
+    pub border_width: f32,
+    pub corner_radius: f32,
+    /// Padding on all sides
+    pub padding: f32,
+    pub cursor_color: Color,
+    pub selection_color: Color,
+    /// Renders custom embeds; by default they are drawn as a small chip
+    pub embed_renderer: Option<EmbedRenderer>,
+}
+
+impl Default for RichTextEditorConfig {
+    fn default() -> Self {
+        let theme = ThemeState::get();
+        Self {
+            placeholder: String::new(),
+            width: 400.0,
+            min_height: 160.0,
+            font_size: 15.0,
+            line_height: 1.5,
+            text_color: theme.color(ColorToken::TextPrimary),
+            placeholder_color: theme.color(ColorToken::TextTertiary),
+            link_color: theme.color(ColorToken::TextLink),
+            code_color: theme.color(ColorToken::Accent),
+            code_bg_color: theme.color(ColorToken::SurfaceElevated),
+            quote_color: theme.color(ColorToken::Border),
+            bg_color: theme.color(ColorToken::InputBg),
+            hover_bg_color: theme.color(ColorToken::InputBgHover),
+            focused_bg_color: theme.color(ColorToken::InputBgFocus),
+            border_color: theme.color(ColorToken::Border),
+            hover_border_color: theme.color(ColorToken::BorderHover),
+            focused_border_color: theme.color(ColorToken::BorderFocus),
+            border_width: 1.5,
+            corner_radius: 8.0,
+            padding: 12.0,
+            cursor_color: theme.color(ColorToken::Accent),
+            selection_color: theme.color(ColorToken::Selection),
+            embed_renderer: None,
+        }
+    }
+}
+
+impl RichTextEditorConfig {
+    /// Width available to text
+    fn content_width(&self) -> f32 {
+        self.width - self.padding * 2.0 - self.border_width * 2.0
+    }
+}
+
+/// How a block's text is laid out
+#[derive(Clone, Copy, Debug)]
+struct BlockStyle {
+    font_size: f32,
+    line_height: f32,
+    bold: bool,
+    monospace: bool,
+    /// Left edge of the text
+    indent: f32,
+}
+
+impl BlockStyle {
+    fn new(kind: &BlockKind, font_size: f32, line_height: f32) -> Self {
+        let mut style = Self {
+            font_size,
+            line_height: font_size * line_height,
+            bold: false,
+            monospace: false,
+            indent: 0.0,
+        };
+        match kind {
+            BlockKind::Paragraph => {}
+            BlockKind::Heading(level) => {
+                let scale = [2.0, 1.6, 1.3, 1.15, 1.0, 0.9][(*level).clamp(1, 6) as usize - 1];
+                style.font_size = font_size * scale;
+                style.line_height = style.font_size * 1.3;
+                style.bold = true;
+            }
+            BlockKind::ListItem { indent, .. } => {
+                style.indent = 24.0 * (*indent as f32 + 1.0);
+            }
+            BlockKind::Quote => style.indent = 16.0,
+            BlockKind::CodeBlock { .. } => {
+                style.monospace = true;
+                style.font_size = font_size * 0.9;
+                style.indent = 12.0;
+            }
+        }
+        style
+    }
+
+    fn options(&self, marks: &[Mark]) -> TextLayoutOptions {
+        TextLayoutOptions {
+            font_weight: if self.bold || marks.contains(&Mark::Bold) {
+                700
+            } else {
+                400
+            },
+            italic: marks.contains(&Mark::Italic),
+            generic_font: if self.monospace || marks.contains(&Mark::Code) {
+                GenericFont::Monospace
+            } else {
+                GenericFont::System
+            },
+            ..TextLayoutOptions::new()
+        }
+    }
+}
+
+/// One rendered row of a block
+#[derive(Clone, Debug)]
+pub(crate) struct VisualLine {
+    block: usize,
+    /// Character range of the block on this row
+    start: usize,
+    end: usize,
+    /// Top, relative to the content area
+    y: f32,
+    style: BlockStyle,
+    /// List marker, on the first row of a list item
+    marker: Option<String>,
+    /// x of each character boundary from `start` to `end`, relative to
+    /// the row's text
+    xs: Vec<f32>,
+}
+
+impl VisualLine {
+    /// The offset in the block nearest to `x`
+    fn offset_at(&self, x: f32) -> usize {
+        let nearest = self
+            .xs
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| (*a - x).abs().total_cmp(&(*b - x).abs()))
+            .map_or(0, |(i, _)| i);
+        self.start + nearest
+    }
+
+    fn x_of(&self, offset: usize) -> f32 {
+        self.xs[offset.clamp(self.start, self.end) - self.start]
+    }
+}
+
+/// An undoable edit: the transaction that reverts it and the selection
+/// before it
+#[derive(Clone, Debug)]
+struct HistoryEntry {
+    transaction: Transaction,
+    selection: Selection,
+    /// Whether this is a run of typed characters that later typing joins
+    typing: bool,
+}
+
+/// Rich text editor state
+pub struct RichTextEditorState {
+    document: RichDocument,
+    selection: Selection,
+    /// Marks for the next typed text, set by toggling a mark with nothing
+    /// selected
+    stored_marks: Option<Vec<Mark>>,
+    undo_stack: Vec<HistoryEntry>,
+    redo_stack: Vec<HistoryEntry>,
+    /// Local transactions not yet taken by `take_changes`
+    outgoing: Vec<Transaction>,
+    /// x the cursor keeps while moving up and down
+    goal_x: Option<f32>,
+    /// Visual state for styling
+    pub visual: TextFieldState,
+    /// Placeholder text
+    pub placeholder: String,
+    /// Whether disabled
+    pub disabled: bool,
+    /// Canvas-based cursor state for smooth animation
+    pub cursor_state: SharedCursorState,
+    /// Rows of the last layout
+    pub(crate) lines: Vec<VisualLine>,
+    /// Width and font the rows were laid out for
+    pub(crate) layout_width: f32,
+    pub(crate) font_size: f32,
+    pub(crate) line_height: f32,
+    /// Row and x of the row's element, set by row click handlers and read
+    /// by the main handler
+    pub(crate) clicked: Option<(usize, f32)>,
+    /// Reference to the Stateful's shared state for triggering incremental updates
+    pub(crate) stateful_state: Option<SharedState<TextFieldState>>,
+    /// Change version counter - increments on each document change
+    pub(crate) change_version: Arc<AtomicU64>,
+    /// Signal ID notified on document changes
+    pub(crate) change_signal_id: Option<SignalId>,
+    /// Layout bounds storage - updated after layout to get actual rendered dimensions
+    pub layout_bounds_storage: crate::renderer::LayoutBoundsStorage,
+}
+
+impl std::fmt::Debug for RichTextEditorState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RichTextEditorState")
+            .field("document", &self.document)
+            .field("selection", &self.selection)
+            .field("visual", &self.visual)
+            .field("placeholder", &self.placeholder)
+            .field("disabled", &self.disabled)
+            // Skip stateful_state since StatefulInner doesn't implement Debug
+            .finish()
+    }
+}
+
+impl Default for RichTextEditorState {
+    fn default() -> Self {
+        Self {
+            document: RichDocument::new(),
+            selection: Selection::default(),
+            stored_marks: None,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            outgoing: Vec::new(),
+            goal_x: None,
+            visual: TextFieldState::Idle,
+            placeholder: String::new(),
+            disabled: false,
+            cursor_state: cursor_state(),
+            lines: Vec::new(),
+            // Replaced from the config on every render
+            layout_width: 400.0,
+            font_size: 15.0,
+            line_height: 1.5,
+            clicked: None,
+            stateful_state: None,
+            change_version: Arc::new(AtomicU64::new(0)),
+            change_signal_id: None,
+            layout_bounds_storage: Arc::new(Mutex::new(None)),
+        }
+    }
+}
+
+impl RichTextEditorState {
+    /// Create an empty editor state
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create with a document, with the cursor at its end
+    pub fn with_document(document: RichDocument) -> Self {
+        let mut state = Self::default();
+        state.set_document(document);
+        state
+    }
+
+    /// Create with placeholder
+    pub fn with_placeholder(placeholder: impl Into<String>) -> Self {
+        Self {
+            placeholder: placeholder.into(),
+            ..Default::default()
+        }
+    }
+
+    // =========================================================================
+    // Content
+    // =========================================================================
+
+    pub fn document(&self) -> &RichDocument {
+        &self.document
+    }
+
+    /// Replace the document, clearing the undo history
+    pub fn set_document(&mut self, document: RichDocument) {
+        self.selection = Selection::cursor(document.end());
+        self.document = document;
+        self.undo_stack.clear();
+        self.redo_stack.clear();
+        self.stored_marks = None;
+        self.lines.clear();
+        self.bump_version();
+    }
+
+    pub fn selection(&self) -> Selection {
+        self.selection
+    }
+
+    /// Set the selection, clamped into the document
+    pub fn set_selection(&mut self, selection: Selection) {
+        self.selection = Selection::new(
+            self.document.clamp(selection.anchor),
+            self.document.clamp(selection.head),
+        );
+        self.stored_marks = None;
+        self.goal_x = None;
+    }
+
+    /// Whether the document is empty
+    pub fn is_empty(&self) -> bool {
+        self.document.is_empty()
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.visual.is_focused()
+    }
+
+    pub fn to_markdown(&self) -> String {
+        self.document.to_markdown()
+    }
+
+    pub fn to_html(&self) -> String {
+        self.document.to_html()
+    }
+
+    /// The selected content
+    pub fn selected_document(&self) -> RichDocument {
+        self.document
+            .slice(self.selection.from(), self.selection.to())
+    }
+
+    /// The selected text, or `None` with nothing selected
+    pub fn selected_text(&self) -> Option<String> {
+        (!self.selection.is_collapsed()).then(|| self.selected_document().plain_text())
+    }
+
+    /// Marks that typed text picks up, and that a toolbar shows as active
+    pub fn active_marks(&self) -> Vec<Mark> {
+        if let Some(marks) = &self.stored_marks {
+            return marks.clone();
+        }
+        let head = self.selection.head;
+        self.document
+            .block(head.block)
+            .map(|block| block.marks_at(head.offset))
+            .unwrap_or_default()
+    }
+
+    /// Whether a mark of `mark`'s kind covers the whole selection, or is
+    /// active at the cursor
+    pub fn has_mark(&self, mark: &Mark) -> bool {
+        if self.selection.is_collapsed() {
+            return self.active_marks().iter().any(|m| m.same_kind(mark));
+        }
+        let mut any_text = false;
+        for (block, range) in self.selected_ranges() {
+            for inline in self.document.blocks()[block].slice(range) {
+                if let Inline::Text { marks, .. } = inline {
+                    if !marks.iter().any(|m| m.same_kind(mark)) {
+                        return false;
+                    }
+                    any_text = true;
+                }
+            }
+        }
+        any_text
+    }
+
+    /// Kind of the block holding the cursor
+    pub fn block_kind(&self) -> BlockKind {
+        self.document
+            .block(self.selection.head.block)
+            .map(|block| block.kind().clone())
+            .unwrap_or_default()
+    }
+
+    // =========================================================================
+    // Editing
+    // =========================================================================
+
+    /// Replace the selection with text; newlines split blocks
+    pub fn insert_text(&mut self, text: &str) {
+        let from = self.selection.from();
+        let marks = self.marks_for_insert();
+        let mut tx = self.delete_range(Transaction::new(), from, self.selection.to());
+        let mut pos = from;
+        for (i, line) in text.split('\n').enumerate() {
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            if i > 0 {
+                tx = tx.split(pos, self.split_kind(from.block, false));
+                pos = DocPosition::new(pos.block + 1, 0);
+            }
+            if !line.is_empty() {
+                tx = tx.insert(pos, vec![Inline::marked(line, marks.clone())]);
+                pos.offset += line.chars().count();
+            }
+        }
+        let typing = self.selection.is_collapsed() && text != "\n" && text.chars().count() == 1;
+        self.apply(tx, Some(Selection::cursor(pos)), typing);
+        // A word and the space after it undo together
+        if typing && text.starts_with(char::is_whitespace) {
+            if let Some(last) = self.undo_stack.last_mut() {
+                last.typing = false;
+            }
+        }
+    }
+
+    /// Insert an embed at the cursor, replacing the selection
+    pub fn insert_embed(&mut self, embed: Embed) {
+        let from = self.selection.from();
+        let tx = self
+            .delete_range(Transaction::new(), from, self.selection.to())
+            .insert(from, vec![Inline::Embed(embed)]);
+        let after = DocPosition::new(from.block, from.offset + 1);
+        self.apply(tx, Some(Selection::cursor(after)), false);
+    }
+
+    /// Delete the selected content
+    pub fn delete_selection(&mut self) {
+        let from = self.selection.from();
+        let tx = self.delete_range(Transaction::new(), from, self.selection.to());
+        self.apply(tx, Some(Selection::cursor(from)), false);
+    }
+
+    /// Backspace: delete the selection or the character before the cursor
+    ///
+    /// At the start of a list item, heading or quote the block becomes a
+    /// paragraph (or a list item is outdented) before blocks are joined.
+    pub fn delete_backward(&mut self) {
+        if !self.selection.is_collapsed() {
+            return self.delete_selection();
+        }
+        let pos = self.selection.head;
+        if pos.offset > 0 {
+            let at = DocPosition::new(pos.block, pos.offset - 1);
+            let tx = Transaction::new().delete(at, 1);
+            self.apply(tx, Some(Selection::cursor(at)), true);
+            return;
+        }
+        let kind = self.document.blocks()[pos.block].kind().clone();
+        let joins_previous = pos.block > 0
+            && matches!(kind, BlockKind::CodeBlock { .. })
+            && *self.document.blocks()[pos.block - 1].kind() == kind;
+        if kind != BlockKind::Paragraph && !joins_previous {
+            let tx = Transaction::new().set_block_kind(pos.block, outdented(&kind));
+            self.apply(tx, None, false);
+        } else if pos.block > 0 {
+            let offset = self.document.blocks()[pos.block - 1].len();
+            let tx = Transaction::new().merge(pos.block, offset);
+            let after = DocPosition::new(pos.block - 1, offset);
+            self.apply(tx, Some(Selection::cursor(after)), false);
+        }
+    }
+
+    /// Delete: delete the selection or the character after the cursor
+    pub fn delete_forward(&mut self) {
+        if !self.selection.is_collapsed() {
+            return self.delete_selection();
+        }
+        let pos = self.selection.head;
+        let len = self.document.blocks()[pos.block].len();
+        if pos.offset < len {
+            self.apply(
+                Transaction::new().delete(pos, 1),
+                Some(Selection::cursor(pos)),
+                false,
+            );
+        } else if pos.block + 1 < self.document.blocks().len() {
+            let tx = Transaction::new().merge(pos.block + 1, len);
+            self.apply(tx, Some(Selection::cursor(pos)), false);
+        }
+    }
+
+    /// Enter: split the block at the cursor
+    ///
+    /// Enter in an empty list item outdents it, leaving the list at the
+    /// top level; Enter at the end of a heading starts a paragraph.
+    pub fn insert_paragraph(&mut self) {
+        let from = self.selection.from();
+        let block = &self.document.blocks()[from.block];
+        let kind = block.kind().clone();
+        let exits = self.selection.is_collapsed()
+            && block.is_empty()
+            && matches!(kind, BlockKind::ListItem { .. } | BlockKind::Quote);
+        if exits {
+            let tx = Transaction::new().set_block_kind(from.block, outdented(&kind));
+            self.apply(tx, None, false);
+            return;
+        }
+        let at_end = self.selection.to() == DocPosition::new(from.block, block.len());
+        let tx = self
+            .delete_range(Transaction::new(), from, self.selection.to())
+            .split(from, self.split_kind(from.block, at_end));
+        let after = DocPosition::new(from.block + 1, 0);
+        self.apply(tx, Some(Selection::cursor(after)), false);
+    }
+
+    /// Toggle a mark on the selection, or for the next typed text
+    pub fn toggle_mark(&mut self, mark: Mark) {
+        if self.has_mark(&mark) {
+            self.remove_mark(&mark);
+        } else {
+            self.set_mark(mark);
+        }
+    }
+
+    /// Apply a mark to the selection, replacing marks of the same kind
+    pub fn set_mark(&mut self, mark: Mark) {
+        if self.selection.is_collapsed() {
+            let mut marks = self.active_marks();
+            add_mark(&mut marks, mark);
+            self.stored_marks = Some(marks);
+            return;
+        }
+        let tx = self
+            .selected_ranges()
+            .into_iter()
+            .fold(Transaction::new(), |tx, (block, range)| {
+                tx.add_mark(block, range, mark.clone())
+            });
+        self.apply(tx, Some(self.selection), false);
+    }
+
+    /// Remove marks of `mark`'s kind from the selection
+    pub fn remove_mark(&mut self, mark: &Mark) {
+        if self.selection.is_collapsed() {
+            let mut marks = self.active_marks();
+            remove_mark(&mut marks, mark);
+            self.stored_marks = Some(marks);
+            return;
+        }
+        let tx = self
+            .selected_ranges()
+            .into_iter()
+            .fold(Transaction::new(), |tx, (block, range)| {
+                tx.remove_mark(block, range, mark.clone())
+            });
+        self.apply(tx, Some(self.selection), false);
+    }
+
+    /// Link the selection to `url`, or unlink it with `None`
+    ///
+    /// With nothing selected, the URL is inserted as linked text.
+    pub fn set_link(&mut self, url: Option<&str>) {
+        match url {
+            Some(url) if self.selection.is_collapsed() => {
+                let at = self.selection.head;
+                let mut marks = self.marks_for_insert();
+                add_mark(&mut marks, Mark::Link(url.to_string()));
+                let tx = Transaction::new().insert(at, vec![Inline::marked(url, marks)]);
+                let after = DocPosition::new(at.block, at.offset + url.chars().count());
+                self.apply(tx, Some(Selection::cursor(after)), false);
+            }
+            Some(url) => self.set_mark(Mark::Link(url.to_string())),
+            None => self.remove_mark(&Mark::Link(String::new())),
+        }
+    }
+
+    /// Set the kind of every selected block
+    pub fn set_block_kind(&mut self, kind: BlockKind) {
+        let tx = self
+            .selected_blocks()
+            .filter(|block| *self.document.blocks()[*block].kind() != kind)
+            .fold(Transaction::new(), |tx, block| {
+                tx.set_block_kind(block, kind.clone())
+            });
+        self.apply(tx, None, false);
+    }
+
+    /// Set the selected blocks to `kind`, or back to paragraphs if they
+    /// all already are
+    pub fn toggle_block_kind(&mut self, kind: BlockKind) {
+        let all = self
+            .selected_blocks()
+            .all(|block| *self.document.blocks()[block].kind() == kind);
+        self.set_block_kind(if all { BlockKind::Paragraph } else { kind });
+    }
+
+    /// Turn the selected blocks into list items, or back into paragraphs if
+    /// they're all items of this kind of list
+    pub fn toggle_list(&mut self, ordered: bool) {
+        let blocks: Vec<usize> = self.selected_blocks().collect();
+        let is_list = |block: &usize| {
+            matches!(self.document.blocks()[*block].kind(),
+                BlockKind::ListItem { ordered: o, .. } if *o == ordered)
+        };
+        let all = blocks.iter().all(is_list);
+        let tx = blocks.iter().fold(Transaction::new(), |tx, &block| {
+            let kind = match self.document.blocks()[block].kind() {
+                _ if all => BlockKind::Paragraph,
+                BlockKind::ListItem { indent, .. } => BlockKind::ListItem {
+                    ordered,
+                    indent: *indent,
+                },
+                _ => BlockKind::ListItem { ordered, indent: 0 },
+            };
+            tx.set_block_kind(block, kind)
+        });
+        self.apply(tx, None, false);
+    }
+
+    /// Nest the selected list items one level deeper
+    pub fn indent(&mut self) {
+        let tx = self
+            .selected_blocks()
+            .fold(Transaction::new(), |tx, block| {
+                match self.document.blocks()[block].kind() {
+                    BlockKind::ListItem { ordered, indent } if *indent < MAX_LIST_INDENT => tx
+                        .set_block_kind(
+                            block,
+                            BlockKind::ListItem {
+                                ordered: *ordered,
+                                indent: indent + 1,
+                            },
+                        ),
+                    _ => tx,
+                }
+            });
+        self.apply(tx, None, false);
+    }
+
+    /// Move the selected list items up a level; top-level items become
+    /// paragraphs
+    pub fn outdent(&mut self) {
+        let tx = self
+            .selected_blocks()
+            .fold(Transaction::new(), |tx, block| {
+                let kind = self.document.blocks()[block].kind();
+                if kind.is_list() {
+                    tx.set_block_kind(block, outdented(kind))
+                } else {
+                    tx
+                }
+            });
+        self.apply(tx, None, false);
+    }
+
+    /// Replace the selection with a document fragment
+    ///
+    /// The first pasted block joins the block at the cursor and the text
+    /// after the cursor joins the last; blocks in between are inserted
+    /// whole.
+    pub fn paste_document(&mut self, fragment: &RichDocument) {
+        let from = self.selection.from();
+        let mut tx = self.delete_range(Transaction::new(), from, self.selection.to());
+        let blocks = fragment.blocks();
+        let first = &blocks[0];
+        if self.document.blocks()[from.block].is_empty() {
+            tx = tx.set_block_kind(from.block, first.kind().clone());
+        }
+        tx = tx.insert(from, first.content().to_vec());
+        let mut end = DocPosition::new(from.block, from.offset + first.len());
+        if let Some(last) = blocks.get(1..).and_then(<[_]>::last) {
+            tx = tx.split(end, last.kind().clone());
+            for (i, block) in blocks[1..blocks.len() - 1].iter().enumerate() {
+                let at = DocPosition::new(from.block + i + 1, 0);
+                tx = tx
+                    .split(end, block.kind().clone())
+                    .insert(at, block.content().to_vec());
+                end = DocPosition::new(at.block, block.len());
+            }
+            let at = DocPosition::new(from.block + blocks.len() - 1, 0);
+            tx = tx.insert(at, last.content().to_vec());
+            end = DocPosition::new(at.block, last.len());
+        }
+        self.apply(tx, Some(Selection::cursor(end)), false);
+    }
+
+    /// Paste HTML, as put on the clipboard by browsers and word processors
+    pub fn paste_html(&mut self, html: &str) {
+        self.paste_document(&RichDocument::from_html(html));
+    }
+
+    /// Paste Markdown
+    pub fn paste_markdown(&mut self, markdown: &str) {
+        self.paste_document(&RichDocument::from_markdown(markdown));
+    }
+
+    /// Paste plain text, taking the formatting at the cursor
+    pub fn paste_text(&mut self, text: &str) {
+        self.insert_text(text);
+    }
+
+    // =========================================================================
+    // History and collaboration
+    // =========================================================================
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_stack.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_stack.is_empty()
+    }
+
+    /// Revert the last edit
+    pub fn undo(&mut self) {
+        if let Some(entry) = self.undo_stack.pop() {
+            let redo = self.revert(entry);
+            self.redo_stack.push(redo);
+        }
+    }
+
+    /// Reapply the last undone edit
+    pub fn redo(&mut self) {
+        if let Some(entry) = self.redo_stack.pop() {
+            let undo = self.revert(entry);
+            self.undo_stack.push(undo);
+        }
+    }
+
+    /// Apply a history entry, returning the entry that reverts it
+    fn revert(&mut self, entry: HistoryEntry) -> HistoryEntry {
+        let inverse = match self.document.apply(&entry.transaction) {
+            Ok(inverse) => inverse,
+            Err(err) => {
+                tracing::warn!("rich text editor: history entry no longer applies: {}", err);
+                Transaction::new()
+            }
+        };
+        let reverted = HistoryEntry {
+            transaction: inverse,
+            selection: self.selection,
+            typing: false,
+        };
+        self.outgoing.push(entry.transaction);
+        self.selection = Selection::new(
+            self.document.clamp(entry.selection.anchor),
+            self.document.clamp(entry.selection.head),
+        );
+        self.after_change();
+        reverted
+    }
+
+    /// Take the local edits made since the last call, for sending to
+    /// collaborators
+    pub fn take_changes(&mut self) -> Vec<Transaction> {
+        std::mem::take(&mut self.outgoing)
+    }
+
+    /// Apply a collaborator's transaction
+    ///
+    /// The transaction must already be rebased onto this document
+    /// (including local changes, which should be taken and sent first).
+    /// The selection and undo history are mapped through it.
+    pub fn apply_remote(&mut self, transaction: &Transaction) -> Result<(), OperationError> {
+        self.document.apply(transaction)?;
+        self.selection = Selection::new(
+            transaction.map_position(self.selection.anchor, Bias::Before),
+            transaction.map_position(self.selection.head, Bias::Before),
+        );
+        // Each undo entry applies after the ones above it were undone, so
+        // rebase the remote change down the stack as it goes
+        let mut over = transaction.clone();
+        for entry in self.undo_stack.iter_mut().rev() {
+            let rebased = entry.transaction.rebase(&over);
+            over = over.rebase(&entry.transaction);
+            entry.selection = Selection::new(
+                over.map_position(entry.selection.anchor, Bias::Before),
+                over.map_position(entry.selection.head, Bias::Before),
+            );
+            entry.transaction = rebased;
+            entry.typing = false;
+        }
+        self.redo_stack.clear();
+        self.after_change();
+        Ok(())
+    }
+
+    // =========================================================================
+    // Cursor movement
+    // =========================================================================
+
+    /// Move left, or collapse the selection to its start
+    pub fn move_left(&mut self, extend: bool) {
+        let head = if !extend && !self.selection.is_collapsed() {
+            self.selection.from()
+        } else {
+            let head = self.selection.head;
+            if head.offset > 0 {
+                DocPosition::new(head.block, head.offset - 1)
+            } else if head.block > 0 {
+                let block = head.block - 1;
+                DocPosition::new(block, self.document.blocks()[block].len())
+            } else {
+                head
+            }
+        };
+        self.move_to(head, extend);
+    }
+
+    /// Move right, or collapse the selection to its end
+    pub fn move_right(&mut self, extend: bool) {
+        let head = if !extend && !self.selection.is_collapsed() {
+            self.selection.to()
+        } else {
+            let head = self.selection.head;
+            if head.offset < self.document.blocks()[head.block].len() {
+                DocPosition::new(head.block, head.offset + 1)
+            } else if head.block + 1 < self.document.blocks().len() {
+                DocPosition::new(head.block + 1, 0)
+            } else {
+                head
+            }
+        };
+        self.move_to(head, extend);
+    }
+
+    /// Move to the row above, keeping the cursor's x
+    pub fn move_up(&mut self, extend: bool) {
+        self.move_vertically(-1, extend);
+    }
+
+    /// Move to the row below, keeping the cursor's x
+    pub fn move_down(&mut self, extend: bool) {
+        self.move_vertically(1, extend);
+    }
+
+    /// Move to the start of the row
+    pub fn move_to_line_start(&mut self, extend: bool) {
+        self.ensure_layout();
+        let line = &self.lines[self.line_of(self.selection.head)];
+        let head = DocPosition::new(line.block, line.start);
+        self.move_to(head, extend);
+    }
+
+    /// Move to the end of the row
+    pub fn move_to_line_end(&mut self, extend: bool) {
+        self.ensure_layout();
+        let line = &self.lines[self.line_of(self.selection.head)];
+        let head = DocPosition::new(line.block, line.end);
+        self.move_to(head, extend);
+    }
+
+    pub fn select_all(&mut self) {
+        self.selection = Selection::new(DocPosition::default(), self.document.end());
+        self.stored_marks = None;
+    }
+
+    fn move_to(&mut self, head: DocPosition, extend: bool) {
+        let anchor = if extend { self.selection.anchor } else { head };
+        self.selection = Selection::new(anchor, head);
+        self.stored_marks = None;
+        self.goal_x = None;
+    }
+
+    fn move_vertically(&mut self, direction: isize, extend: bool) {
+        self.ensure_layout();
+        let head = self.selection.head;
+        let current = self.line_of(head);
+        let goal_x = self
+            .goal_x
+            .unwrap_or_else(|| self.lines[current].x_of(head.offset));
+        let target = current as isize + direction;
+        let head = if target < 0 {
+            DocPosition::default()
+        } else if target as usize >= self.lines.len() {
+            self.document.end()
+        } else {
+            let line = &self.lines[target as usize];
+            DocPosition::new(line.block, line.offset_at(goal_x))
+        };
+        self.move_to(head, extend);
+        self.goal_x = Some(goal_x);
+    }
+
+    // =========================================================================
+    // Change tracking
+    // =========================================================================
+
+    /// Get the change version counter, which increments on every change
+    pub fn change_version(&self) -> u64 {
+        self.change_version.load(Ordering::Relaxed)
+    }
+
+    /// Set the signal notified when the document changes
+    pub fn set_change_signal(&mut self, signal_id: SignalId) {
+        self.change_signal_id = Some(signal_id);
+    }
+
+    /// The signal notified when the document changes, if set
+    pub fn signal_id(&self) -> Option<SignalId> {
+        self.change_signal_id
+    }
+
+    pub fn reset_cursor_blink(&mut self) {
+        if let Ok(mut cs) = self.cursor_state.lock() {
+            cs.reset_blink();
+        }
+    }
+
+    /// Publish the selected text for copy
+    pub fn sync_global_selection(&self) {
+        match self.selected_text() {
+            Some(selected) => set_selection(selected, SelectionSource::RichTextEditor, true),
+            None => clear_selection(),
+        }
+    }
+
+    // =========================================================================
+    // Internals
+    // =========================================================================
+
+    /// Apply a local edit, recording it for undo and for collaborators
+    ///
+    /// Without an explicit selection the current one is mapped through
+    /// the edit.
+    fn apply(&mut self, transaction: Transaction, selection: Option<Selection>, typing: bool) {
+        if transaction.is_empty() {
+            return;
+        }
+        let inverse = match self.document.apply(&transaction) {
+            Ok(inverse) => inverse,
+            Err(err) => {
+                tracing::warn!("rich text editor: edit failed: {}", err);
+                return;
+            }
+        };
+        let before = self.selection;
+        self.selection = selection.unwrap_or_else(|| {
+            Selection::new(
+                transaction.map_position(before.anchor, Bias::Before),
+                transaction.map_position(before.head, Bias::After),
+            )
+        });
+        match self.undo_stack.last_mut() {
+            // Undo a run of typing in one step
+            Some(last) if typing && last.typing => {
+                let ops = inverse.ops().iter().chain(last.transaction.ops());
+                last.transaction = Transaction::from_ops(ops.cloned().collect());
+            }
+            _ => {
+                self.undo_stack.push(HistoryEntry {
+                    transaction: inverse,
+                    selection: before,
+                    typing,
+                });
+                if self.undo_stack.len() > MAX_HISTORY {
+                    self.undo_stack.remove(0);
+                }
+            }
+        }
+        self.redo_stack.clear();
+        self.outgoing.push(transaction);
+        self.after_change();
+    }
+
+    fn after_change(&mut self) {
+        self.stored_marks = None;
+        self.goal_x = None;
+        self.lines.clear();
+        self.bump_version();
+    }
+
+    fn bump_version(&self) {
+        self.change_version.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn marks_for_insert(&self) -> Vec<Mark> {
+        match &self.stored_marks {
+            Some(marks) => marks.clone(),
+            None => {
+                let from = self.selection.from();
+                self.document.blocks()[from.block].marks_at(from.offset)
+            }
+        }
+    }
+
+    /// Kind of the block split off `block`
+    fn split_kind(&self, block: usize, at_end: bool) -> BlockKind {
+        match self.document.blocks()[block].kind() {
+            BlockKind::Heading(_) if at_end => BlockKind::Paragraph,
+            kind => kind.clone(),
+        }
+    }
+
+    /// Add operations deleting `from..to` to a transaction
+    fn delete_range(&self, tx: Transaction, from: DocPosition, to: DocPosition) -> Transaction {
+        if from.block == to.block {
+            if to.offset > from.offset {
+                return tx.delete(from, to.offset - from.offset);
+            }
+            return tx;
+        }
+        let blocks = self.document.blocks();
+        let mut tx = tx.delete(from, blocks[from.block].len() - from.offset);
+        // Each following block moves up to `from.block + 1` as the one
+        // before it is merged
+        let next = DocPosition::new(from.block + 1, 0);
+        for (block, content) in blocks
+            .iter()
+            .enumerate()
+            .take(to.block + 1)
+            .skip(from.block + 1)
+        {
+            let len = if block == to.block {
+                to.offset
+            } else {
+                content.len()
+            };
+            if len > 0 {
+                tx = tx.delete(next, len);
+            }
+            tx = tx.merge(next.block, from.offset);
+        }
+        tx
+    }
+
+    /// Per-block character ranges of the selection
+    fn selected_ranges(&self) -> Vec<(usize, std::ops::Range<usize>)> {
+        let (from, to) = (self.selection.from(), self.selection.to());
+        (from.block..=to.block)
+            .map(|block| {
+                let start = if block == from.block { from.offset } else { 0 };
+                let end = if block == to.block {
+                    to.offset
+                } else {
+                    self.document.blocks()[block].len()
+                };
+                (block, start..end)
+            })
+            .filter(|(_, range)| !range.is_empty())
+            .collect()
+    }
+
+    fn selected_blocks(&self) -> std::ops::RangeInclusive<usize> {
+        self.selection.from().block..=self.selection.to().block
+    }
+
+    /// The row holding a position; a position at a wrap belongs to the
+    /// row it starts
+    fn line_of(&self, pos: DocPosition) -> usize {
+        self.lines
+            .iter()
+            .enumerate()
+            .position(|(i, line)| {
+                let last_in_block = self
+                    .lines
+                    .get(i + 1)
+                    .map_or(true, |next| next.block != line.block);
+                line.block == pos.block
+                    && pos.offset >= line.start
+                    && (pos.offset < line.end || last_in_block)
+            })
+            .unwrap_or(0)
+    }
+
+    fn ensure_layout(&mut self) {
+        if self.lines.is_empty() {
+            self.layout();
+        }
+    }
+
+    /// Break blocks into rows for the current width
+    pub(crate) fn layout(&mut self) {
+        let width = self.layout_width.max(1.0);
+        let mut lines = Vec::new();
+        let mut y = 0.0;
+        let mut number = Vec::<usize>::new();
+        for (index, block) in self.document.blocks().iter().enumerate() {
+            let style = BlockStyle::new(block.kind(), self.font_size, self.line_height);
+            let marker = match block.kind() {
+                BlockKind::ListItem { ordered, indent } => {
+                    let level = *indent as usize;
+                    number.truncate(level + 1);
+                    number.resize(level + 1, 0);
+                    number[level] += 1;
+                    Some(if *ordered {
+                        format!("{}.", number[level])
+                    } else {
+                        ["•", "◦", "▪"][level % 3].to_string()
+                    })
+                }
+                _ => {
+                    number.clear();
+                    None
+                }
+            };
+            let xs = char_positions(block.content(), &style);
+            let text: Vec<char> = block.text().chars().collect();
+            let available = (width - style.indent).max(style.font_size);
+            let mut start = 0;
+            let mut break_at = None;
+            let mut rows = Vec::new();
+            for (i, c) in text.iter().enumerate() {
+                if xs[i + 1] - xs[start] > available && i > start {
+                    let end = break_at.filter(|b| *b > start).unwrap_or(i);
+                    rows.push(start..end);
+                    start = end;
+                    break_at = None;
+                }
+                if c.is_whitespace() {
+                    break_at = Some(i + 1);
+                }
+            }
+            rows.push(start..text.len());
+            for (row, range) in rows.into_iter().enumerate() {
+                lines.push(VisualLine {
+                    block: index,
+                    start: range.start,
+                    end: range.end,
+                    y,
+                    style,
+                    marker: if row == 0 { marker.clone() } else { None },
+                    xs: xs[range.start..=range.end]
+                        .iter()
+                        .map(|x| x - xs[range.start])
+                        .collect(),
+                });
+                y += style.line_height;
+            }
+        }
+        self.lines = lines;
+    }
+}
+
+/// One level up: list items outdent, top-level blocks become paragraphs
+fn outdented(kind: &BlockKind) -> BlockKind {
+    match kind {
+        BlockKind::ListItem { ordered, indent } if *indent > 0 => BlockKind::ListItem {
+            ordered: *ordered,
+            indent: indent - 1,
+        },
+        _ => BlockKind::Paragraph,
+    }
+}
+
+/// x of every character boundary in a block
+fn char_positions(content: &[Inline], style: &BlockStyle) -> Vec<f32> {
+    let mut xs = vec![0.0];
+    let mut run_start = 0.0;
+    for inline in content {
+        match inline {
+            Inline::Text { text, marks } => {
+                let options = style.options(marks);
+                for (i, c) in text.char_indices() {
+                    let prefix = &text[..i + c.len_utf8()];
+                    let width = measure_text_with_options(prefix, style.font_size, &options).width;
+                    xs.push(run_start + width);
+                }
+                run_start = *xs.last().unwrap();
+            }
+            Inline::Embed(_) => {
+                run_start += style.line_height;
+                xs.push(run_start);
+            }
+        }
+    }
+    xs
+}
+
+/// Shared rich text editor state handle
+pub type SharedRichTextEditorState = Arc<Mutex<RichTextEditorState>>;
+
+/// Create a shared rich text editor state
+pub fn rich_text_editor_state() -> SharedRichTextEditorState {
+    Arc::new(Mutex::new(RichTextEditorState::new()))
+}
+
+/// Create a shared rich text editor state with placeholder
+pub fn rich_text_editor_state_with_placeholder(
+    placeholder: impl Into<String>,
+) -> SharedRichTextEditorState {
+    Arc::new(Mutex::new(RichTextEditorState::with_placeholder(
+        placeholder,
+    )))
+}
+
+/// Ready-to-use rich text editor element
+///
+/// Uses FSM-driven state management via `Stateful<TextFieldState>` for
+/// visual states, like [`TextArea`](crate::widgets::text_area::TextArea).
+///
+/// Usage: `rich_text_editor(&state).w(480.0).min_h(240.0)`
+pub struct RichTextEditor {
+    /// Inner Stateful element for FSM-driven visual states
+    inner: Stateful<TextFieldState>,
+    /// Editor state (document, selection, history)
+    state: SharedRichTextEditorState,
+    /// Editor configuration
+    config: Arc<Mutex<RichTextEditorConfig>>,
+}
+
+impl RichTextEditor {
+    /// Create a new editor with shared state
+    pub fn new(state: &SharedRichTextEditorState) -> Self {
+        let config = Arc::new(Mutex::new(RichTextEditorConfig::default()));
+
+        // Reuse the existing stateful_state so state persists across rebuilds
+        let (initial_visual, existing_stateful_state) = {
+            let d = state.lock().unwrap();
+            (d.visual, d.stateful_state.clone())
+        };
+        let shared_state: SharedState<TextFieldState> =
+            existing_stateful_state.unwrap_or_else(|| {
+                let new_state = Arc::new(Mutex::new(StatefulInner::new(initial_visual)));
+                if let Ok(mut d) = state.lock() {
+                    d.stateful_state = Some(Arc::clone(&new_state));
+                }
+                new_state
+            });
+
+        // Clear stale node_id from previous tree builds
+        shared_state.lock().unwrap().node_id = None;
+
+        let inner = Self::create_inner_with_handlers(Arc::clone(&shared_state), Arc::clone(state))
+            .min_w(0.0);
+
+        // Register callback immediately so it's available for incremental diff
+        {
+            let config_for_callback = Arc::clone(&config);
+            let data_for_callback = Arc::clone(state);
+            let mut shared = shared_state.lock().unwrap();
+
+            shared.state_callback = Some(Arc::new(
+                move |visual: &TextFieldState, container: &mut Div| {
+                    let cfg = config_for_callback.lock().unwrap().clone();
+                    let content = {
+                        let mut data = data_for_callback.lock().unwrap();
+                        data.visual = *visual;
+                        data.font_size = cfg.font_size;
+                        data.line_height = cfg.line_height;
+                        data.layout_width = cfg.content_width();
+                        data.layout();
+                        RichTextEditor::build_content(
+                            *visual,
+                            &data,
+                            &cfg,
+                            Arc::clone(&data_for_callback),
+                        )
+                    };
+
+                    let (bg, border) = match visual {
+                        TextFieldState::Focused | TextFieldState::FocusedHovered => {
+                            (cfg.focused_bg_color, cfg.focused_border_color)
+                        }
+                        TextFieldState::Hovered => (cfg.hover_bg_color, cfg.hover_border_color),
+                        TextFieldState::Disabled => (
+                            Color::rgba(0.12, 0.12, 0.15, 0.5),
+                            Color::rgba(0.25, 0.25, 0.3, 0.5),
+                        ),
+                        _ => (cfg.bg_color, cfg.border_color),
+                    };
+                    container.set_bg(bg);
+                    container.set_border(cfg.border_width, border);
+                    container.set_rounded(cfg.corner_radius);
+                    container.set_child(content);
+                },
+            ));
+
+            shared.needs_visual_update = true;
+        }
+
+        inner.ensure_state_handlers_registered();
+
+        Self {
+            inner,
+            state: Arc::clone(state),
+            config,
+        }
+    }
+
+    /// Create the inner Stateful element with all event handlers registered
+    fn create_inner_with_handlers(
+        shared_state: SharedState<TextFieldState>,
+        data: SharedRichTextEditorState,
+    ) -> Stateful<TextFieldState> {
+        use junita_core::events::event_types;
+
+        let data_for_click = Arc::clone(&data);
+        let data_for_text = Arc::clone(&data);
+        let data_for_key = Arc::clone(&data);
+        let shared_for_click = Arc::clone(&shared_state);
+        let shared_for_text = Arc::clone(&shared_state);
+        let shared_for_key = Arc::clone(&shared_state);
+
+        Stateful::with_shared_state(shared_state)
+            // Focus and place the cursor; shift-click extends the selection
+            .on_mouse_down(move |ctx| {
+                let was_focused = match data_for_click.lock() {
+                    Ok(d) if d.disabled => return,
+                    Ok(d) => d.visual.is_focused(),
+                    Err(_) => return,
+                };
+                // Blur other text widgets before taking this one's lock
+                if !was_focused {
+                    set_focused_rich_text_editor(&data_for_click);
+                }
+
+                {
+                    let mut d = match data_for_click.lock() {
+                        Ok(d) => d,
+                        Err(_) => return,
+                    };
+
+                    {
+                        let mut shared = shared_for_click.lock().unwrap();
+                        if !shared.state.is_focused() {
+                            if let Some(new_state) = shared
+                                .state
+                                .on_event(event_types::POINTER_DOWN)
+                                .or_else(|| shared.state.on_event(event_types::FOCUS))
+                            {
+                                shared.state = new_state;
+                                shared.needs_visual_update = true;
+                            }
+                        }
+                    }
+                    if !d.visual.is_focused() {
+                        d.visual = TextFieldState::Focused;
+                        increment_focus_count();
+                        request_continuous_redraw_pub();
+                    }
+
+                    d.ensure_layout();
+                    // local_x is relative to the innermost element: the row
+                    // piece whose x was recorded by its handler
+                    let head = match d.clicked.take() {
+                        Some((line, x)) if line < d.lines.len() => {
+                            let line = &d.lines[line];
+                            DocPosition::new(line.block, line.offset_at(x + ctx.local_x))
+                        }
+                        _ => d.document.end(),
+                    };
+                    d.move_to(head, ctx.shift);
+                    d.sync_global_selection();
+                    d.reset_cursor_blink();
+                } // Lock released here
+
+                refresh_stateful(&shared_for_click);
+            })
+            .on_event(event_types::TEXT_INPUT, move |ctx| {
+                let change_signal = {
+                    let mut d = match data_for_text.lock() {
+                        Ok(d) => d,
+                        Err(_) => return,
+                    };
+                    if d.disabled || !d.visual.is_focused() || ctx.ctrl || ctx.meta {
+                        return;
+                    }
+                    let Some(c) = ctx.key_char else {
+                        return;
+                    };
+                    d.insert_text(c.encode_utf8(&mut [0; 4]));
+                    d.reset_cursor_blink();
+                    d.change_signal_id
+                }; // Lock released here
+
+                refresh_stateful(&shared_for_text);
+                if let Some(signal_id) = change_signal {
+                    crate::stateful::check_stateful_deps(&[signal_id]);
+                }
+            })
+            .on_key_down(move |ctx| {
+                let (should_blur, change_signal) = {
+                    let mut d = match data_for_key.lock() {
+                        Ok(d) => d,
+                        Err(_) => return,
+                    };
+                    if d.disabled || !d.visual.is_focused() {
+                        return;
+                    }
+
+                    let version = d.change_version();
+                    let command = ctx.ctrl || ctx.meta;
+                    match ctx.key_code {
+                        8 => d.delete_backward(),
+                        127 => d.delete_forward(),
+                        13 => d.insert_paragraph(),
+                        9 if ctx.shift => d.outdent(),
+                        9 => d.indent(),
+                        37 => d.move_left(ctx.shift),
+                        39 => d.move_right(ctx.shift),
+                        38 => d.move_up(ctx.shift),
+                        40 => d.move_down(ctx.shift),
+                        36 => d.move_to_line_start(ctx.shift),
+                        35 => d.move_to_line_end(ctx.shift),
+                        27 => {}
+                        65 if command => d.select_all(),
+                        66 if command => d.toggle_mark(Mark::Bold),
+                        73 if command => d.toggle_mark(Mark::Italic),
+                        85 if command => d.toggle_mark(Mark::Underline),
+                        90 if command && ctx.shift => d.redo(),
+                        90 if command => d.undo(),
+                        89 if command => d.redo(),
+                        _ => return,
+                    }
+                    d.sync_global_selection();
+                    d.reset_cursor_blink();
+                    let changed = d.change_version() != version;
+                    (ctx.key_code == 27, d.change_signal_id.filter(|_| changed))
+                }; // Lock released here
+
+                if should_blur {
+                    blur_all_text_inputs();
+                } else {
+                    refresh_stateful(&shared_for_key);
+                }
+                if let Some(signal_id) = change_signal {
+                    crate::stateful::check_stateful_deps(&[signal_id]);
+                }
+            })
+            .cursor_text()
+    }
+
+    /// Build the rows, selection highlight and cursor
+    fn build_content(
+        visual: TextFieldState,
+        data: &RichTextEditorState,
+        config: &RichTextEditorConfig,
+        shared_state: SharedRichTextEditorState,
+    ) -> Div {
+        let width = config.content_width();
+        let mut content = div().flex_col().relative().overflow_visible().w(width);
+
+        // Selection highlight, behind the text
+        let (from, to) = (data.selection.from(), data.selection.to());
+        if !data.selection.is_collapsed() {
+            for line in &data.lines {
+                let block_pos = |offset| DocPosition::new(line.block, offset);
+                if block_pos(line.end) < from || block_pos(line.start) > to {
+                    continue;
+                }
+                let start = if from.block == line.block {
+                    from.offset.max(line.start)
+                } else {
+                    line.start
+                };
+                let end = if to.block == line.block {
+                    to.offset.min(line.end)
+                } else {
+                    line.end
+                };
+                let x0 = line.x_of(start);
+                // Show selected block ends as a little extra width
+                let x1 = if end == line.end && to.block > line.block {
+                    line.x_of(end) + line.style.font_size * 0.3
+                } else {
+                    line.x_of(end)
+                };
+                if x1 > x0 {
+                    content = content.child(
+                        div()
+                            .absolute()
+                            .left(line.style.indent + x0)
+                            .top(line.y)
+                            .w(x1 - x0)
+                            .h(line.style.line_height)
+                            .bg(config.selection_color),
+                    );
+                }
+            }
+        }
+
+        if data.is_empty() {
+            let placeholder = if data.placeholder.is_empty() {
+                &config.placeholder
+            } else {
+                &data.placeholder
+            };
+            let line_height = config.font_size * config.line_height;
+            content = content.child(
+                div()
+                    .h(line_height)
+                    .w(width)
+                    .flex_row()
+                    .items_center()
+                    .on_mouse_down({
+                        let state = Arc::clone(&shared_state);
+                        move |_| {
+                            if let Ok(mut s) = state.lock() {
+                                s.clicked = Some((0, 0.0));
+                            }
+                        }
+                    })
+                    .child(
+                        text(placeholder)
+                            .size(config.font_size)
+                            .color(config.placeholder_color)
+                            .no_wrap(),
+                    ),
+            );
+        } else {
+            for (index, line) in data.lines.iter().enumerate() {
+                let block = &data.document.blocks()[line.block];
+                content = content.child(Self::build_line(
+                    index,
+                    line,
+                    block.kind(),
+                    &block.slice(line.start..line.end),
+                    config,
+                    &shared_state,
+                ));
+            }
+        }
+
+        // Cursor
+        let cursor_state = Arc::clone(&data.cursor_state);
+        if visual.is_focused() && data.selection.is_collapsed() && !data.lines.is_empty() {
+            let line = &data.lines[data.line_of(data.selection.head)];
+            let height = line.style.font_size * 1.2;
+            let x = line.style.indent + line.x_of(data.selection.head.offset);
+            let top = line.y + (line.style.line_height - height) / 2.0;
+            if let Ok(mut cs) = cursor_state.lock() {
+                cs.visible = true;
+                cs.color = config.cursor_color;
+                cs.x = x;
+                cs.animation = CursorAnimation::SmoothFade;
+            }
+            let cursor = canvas(
+                move |ctx: &mut dyn junita_core::DrawContext,
+                      bounds: crate::canvas::CanvasBounds| {
+                    let cs = cursor_state.lock().unwrap();
+                    let opacity = cs.current_opacity();
+                    if !cs.visible || opacity < 0.01 {
+                        return;
+                    }
+                    let color =
+                        Color::rgba(cs.color.r, cs.color.g, cs.color.b, cs.color.a * opacity);
+                    ctx.fill_rect(
+                        junita_core::Rect::new(0.0, 0.0, cs.width, bounds.height),
+                        junita_core::CornerRadius::default(),
+                        junita_core::Brush::Solid(color),
+                    );
+                },
+            )
+            .absolute()
+            .left(x)
+            .top(top)
+            .w(2.0)
+            .h(height);
+            content = content.child(cursor);
+        } else if let Ok(mut cs) = cursor_state.lock() {
+            cs.visible = false;
+        }
+
+        let padding = config.padding;
+        div()
+            .flex_col()
+            .w(config.width)
+            .min_h(config.min_height)
+            .padding_x(crate::units::Length::Px(padding))
+            .padding_y(crate::units::Length::Px(padding))
+            .child(content)
+    }
+
+    /// One row: a gutter holding the list marker or quote bar, then text
+    /// pieces split at embeds
+    fn build_line(
+        index: usize,
+        line: &VisualLine,
+        kind: &BlockKind,
+        content: &[Inline],
+        config: &RichTextEditorConfig,
+        state: &SharedRichTextEditorState,
+    ) -> Div {
+        let style = line.style;
+        let height = style.line_height;
+        let record = |x: f32| {
+            let state = Arc::clone(state);
+            move |_: &crate::event_handler::EventContext| {
+                if let Ok(mut s) = state.lock() {
+                    s.clicked = Some((index, x));
+                }
+            }
+        };
+
+        let mut gutter = div()
+            .w(style.indent)
+            .h(height)
+            .flex_row()
+            .items_center()
+            .justify_end()
+            .on_mouse_down(record(-style.indent));
+        if let Some(marker) = &line.marker {
+            gutter = gutter.child(
+                div().pr(2.0).child(
+                    text(marker)
+                        .size(style.font_size)
+                        .color(config.text_color)
+                        .no_wrap(),
+                ),
+            );
+        } else if *kind == BlockKind::Quote {
+            gutter = gutter.border_left(3.0, config.quote_color);
+        }
+
+        let mut row = div()
+            .flex_row()
+            .items_center()
+            .h(height)
+            .w(config.content_width())
+            .child(gutter);
+        if matches!(kind, BlockKind::CodeBlock { .. }) {
+            row = row.bg(config.code_bg_color);
+        }
+
+        // Group text into pieces between embeds
+        let mut pieces: Vec<(f32, Box<dyn ElementBuilder>)> = Vec::new();
+        let mut text_run: Vec<&Inline> = Vec::new();
+        let mut offset = line.start;
+        let mut piece_start = line.start;
+        for inline in content {
+            if let Inline::Embed(embed) = inline {
+                if !text_run.is_empty() {
+                    let piece = Self::text_piece(&text_run, &style, config);
+                    pieces.push((line.x_of(piece_start), Box::new(piece)));
+                    text_run.clear();
+                }
+                let element: Box<dyn ElementBuilder> = match (embed, &config.embed_renderer) {
+                    (Embed::Image { src, .. }, _) => {
+                        Box::new(img(src.as_str()).w(height).h(height))
+                    }
+                    (Embed::Custom { .. }, Some(renderer)) => renderer(embed, height),
+                    (Embed::Custom { .. }, None) => Box::new(
+                        div()
+                            .w(height * 0.8)
+                            .h(height * 0.8)
+                            .rounded(4.0)
+                            .bg(config.selection_color),
+                    ),
+                };
+                pieces.push((line.x_of(offset), element));
+                offset += 1;
+                piece_start = offset;
+            } else {
+                if text_run.is_empty() {
+                    piece_start = offset;
+                }
+                text_run.push(inline);
+                offset += inline.len();
+            }
+        }
+        if text_run.iter().any(|inline| !inline.is_empty()) {
+            let piece = Self::text_piece(&text_run, &style, config);
+            pieces.push((line.x_of(piece_start), Box::new(piece)));
+        }
+
+        let count = pieces.len();
+        for (i, (x, element)) in pieces.into_iter().enumerate() {
+            let next_x = if i + 1 < count { None } else { Some(()) };
+            let mut holder = div()
+                .h(height)
+                .flex_row()
+                .items_center()
+                .on_mouse_down(record(x))
+                .child_box(element);
+            holder = match next_x {
+                // The last piece takes the rest of the row so clicks past the
+                // text land on it
+                Some(()) => holder.flex_grow(),
+                None => holder.flex_shrink_0(),
+            };
+            row = row.child(holder);
+        }
+        if count == 0 {
+            row = row.child(div().h(height).flex_grow().on_mouse_down(record(0.0)));
+        }
+        row
+    }
+
+    /// A run of text as one element
+    fn text_piece(runs: &[&Inline], style: &BlockStyle, config: &RichTextEditorConfig) -> RichText {
+        let mut content = String::new();
+        let mut spans = Vec::new();
+        for inline in runs {
+            let Inline::Text { text, marks } = inline else {
+                continue;
+            };
+            let start = content.len();
+            content.push_str(text);
+            let mut color = config.text_color;
+            for mark in marks.iter() {
+                match mark {
+                    Mark::Link(_) => color = config.link_color,
+                    Mark::Code => color = config.code_color,
+                    _ => {}
+                }
+            }
+            if let Some(Mark::Color(c)) = marks.iter().find(|m| matches!(m, Mark::Color(_))) {
+                color = *c;
+            }
+            spans.push(TextSpan {
+                start,
+                end: content.len(),
+                color,
+                bold: style.bold || marks.contains(&Mark::Bold),
+                italic: marks.contains(&Mark::Italic),
+                underline: marks
+                    .iter()
+                    .any(|m| matches!(m, Mark::Underline | Mark::Link(_))),
+                strikethrough: marks.contains(&Mark::Strikethrough),
+                // Links are followed from outside the editor; clicks edit
+                link_url: None,
+                token_type: None,
+            });
+        }
+        let styled = StyledText::from_lines(vec![StyledLine::new(content, spans)]);
+        let piece = RichText::from_styled(styled)
+            .size(style.font_size)
+            .default_color(config.text_color)
+            .no_wrap();
+        if style.monospace {
+            piece.monospace()
+        } else {
+            piece
+        }
+    }
+
+    /// Set placeholder text
+    pub fn placeholder(self, text: impl Into<String>) -> Self {
+        let placeholder = text.into();
+        self.config.lock().unwrap().placeholder = placeholder.clone();
+        if let Ok(mut s) = self.state.lock() {
+            s.placeholder = placeholder;
+        }
+        self
+    }
+
+    /// Set the body font size; headings scale from it
+    pub fn font_size(self, size: f32) -> Self {
+        self.config.lock().unwrap().font_size = size;
+        self
+    }
+
+    /// Set disabled state
+    pub fn disabled(self, disabled: bool) -> Self {
+        if let Ok(mut s) = self.state.lock() {
+            s.disabled = disabled;
+            if disabled {
+                s.visual = TextFieldState::Disabled;
+            }
+        }
+        self
+    }
+
+    /// Render custom embeds
+    pub fn embed_renderer<F>(self, renderer: F) -> Self
+    where
+        F: Fn(&Embed, f32) -> Box<dyn ElementBuilder> + Send + Sync + 'static,
+    {
+        self.config.lock().unwrap().embed_renderer = Some(Arc::new(renderer));
+        self
+    }
+
+    /// Notify `signal_id` whenever the document changes
+    pub fn on_change_signal(self, signal_id: SignalId) -> Self {
+        if let Ok(mut state) = self.state.lock() {
+            state.set_change_signal(signal_id);
+        }
+        self
+    }
+
+    // =========================================================================
+    // Builder methods that return Self (shadow Div methods for fluent API)
+    // =========================================================================
+
+    pub fn w(mut self, px: f32) -> Self {
+        self.config.lock().unwrap().width = px;
+        self.inner = std::mem::take(&mut self.inner).w(px);
+        self
+    }
+
+    /// Minimum height; the editor grows with its content
+    pub fn min_h(self, px: f32) -> Self {
+        self.config.lock().unwrap().min_height = px;
+        self
+    }
+
+    pub fn w_full(mut self) -> Self {
+        self.inner = std::mem::take(&mut self.inner).w_full();
+        self
+    }
+
+    pub fn m(mut self, units: f32) -> Self {
+        self.inner = std::mem::take(&mut self.inner).m(units);
+        self
+    }
+
+    pub fn mx(mut self, units: f32) -> Self {
+        self.inner = std::mem::take(&mut self.inner).mx(units);
+        self
+    }
+
+    pub fn my(mut self, units: f32) -> Self {
+        self.inner = std::mem::take(&mut self.inner).my(units);
+        self
+    }
+
+    pub fn flex_grow(mut self) -> Self {
+        self.inner = std::mem::take(&mut self.inner).flex_grow();
+        self
+    }
+
+    pub fn rounded(mut self, radius: f32) -> Self {
+        self.config.lock().unwrap().corner_radius = radius;
+        self.inner = std::mem::take(&mut self.inner).rounded(radius);
+        self
+    }
+
+    pub fn shadow_sm(mut self) -> Self {
+        self.inner = std::mem::take(&mut self.inner).shadow_sm();
+        self
+    }
+}
+
+/// Create a ready-to-use rich text editor
+///
+/// By default, width inherits from parent (w_full). Use .w() to set explicit width.
+///
+/// # Example
+///
+/// ```ignore
+/// let state = rich_text_editor_state_with_placeholder("Write something...");
+/// rich_text_editor(&state).min_h(240.0)
+/// ```
+pub fn rich_text_editor(state: &SharedRichTextEditorState) -> RichTextEditor {
+    RichTextEditor::new(state).w_full()
+}
+
+impl ElementBuilder for RichTextEditor {
+    fn build(&self, tree: &mut LayoutTree) -> LayoutNodeId {
+        // Set base render props for incremental updates
+        {
+            let shared_state = self.inner.shared_state();
+            let mut shared = shared_state.lock().unwrap();
+            shared.base_render_props = Some(self.inner.inner_render_props());
+        }
+        self.inner.build(tree)
+    }
+
+    fn render_props(&self) -> RenderProps {
+        self.inner.render_props()
+    }
+
+    fn children_builders(&self) -> &[Box<dyn ElementBuilder>] {
+        self.inner.children_builders()
+    }
+
+    fn element_type_id(&self) -> crate::div::ElementTypeId {
+        crate::div::ElementTypeId::Div
+    }
+
+    fn event_handlers(&self) -> Option<&crate::event_handler::EventHandlers> {
+        ElementBuilder::event_handlers(&self.inner)
+    }
+
+    fn layout_style(&self) -> Option<&taffy::Style> {
+        self.inner.layout_style()
+    }
+
+    fn layout_bounds_storage(&self) -> Option<crate::renderer::LayoutBoundsStorage> {
+        self.state
+            .lock()
+            .ok()
+            .map(|data| Arc::clone(&data.layout_bounds_storage))
+    }
+
+    fn layout_bounds_callback(&self) -> Option<crate::renderer::LayoutBoundsCallback> {
+        // Re-wrap when the laid-out width changes
+        let config = Arc::clone(&self.config);
+        let stateful_state = self.inner.shared_state();
+        Some(Arc::new(move |bounds| {
+            let changed = match config.lock() {
+                Ok(mut cfg) if (cfg.width - bounds.width).abs() > 1.0 => {
+                    cfg.width = bounds.width;
+                    true
+                }
+                _ => false,
+            };
+            if changed {
+                crate::stateful::refresh_stateful(&stateful_state);
+            }
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rich_text::Block;
+
+    fn pos(block: usize, offset: usize) -> DocPosition {
+        DocPosition::new(block, offset)
+    }
+
+    #[test]
+    fn test_typing_and_undo() {
+        let mut state = RichTextEditorState::new();
+        for c in "hi there".chars() {
+            state.insert_text(&c.to_string());
+        }
+        state.insert_paragraph();
+        state.insert_text("x");
+        assert_eq!(state.document().plain_text(), "hi there\nx");
+
+        state.undo();
+        state.undo();
+        assert_eq!(state.document().plain_text(), "hi there");
+        // Typing is undone a word at a time
+        state.undo();
+        assert_eq!(state.document().plain_text(), "hi ");
+        state.undo();
+        assert!(state.is_empty());
+        assert!(!state.can_undo());
+
+        state.redo();
+        state.redo();
+        assert_eq!(state.document().plain_text(), "hi there");
+        assert_eq!(state.selection(), Selection::cursor(pos(0, 8)));
+    }
+
+    #[test]
+    fn test_marks_and_stored_marks() {
+        let mut state = RichTextEditorState::with_document(RichDocument::from_text("hello"));
+        state.set_selection(Selection::new(pos(0, 0), pos(0, 5)));
+        state.toggle_mark(Mark::Bold);
+        assert!(state.has_mark(&Mark::Bold));
+        state.toggle_mark(Mark::Bold);
+        assert!(!state.has_mark(&Mark::Bold));
+
+        state.set_selection(Selection::cursor(pos(0, 5)));
+        state.toggle_mark(Mark::Italic);
+        state.insert_text("!");
+        assert_eq!(
+            state.document().blocks()[0].content()[1],
+            Inline::marked("!", vec![Mark::Italic])
+        );
+    }
+
+    #[test]
+    fn test_lists() {
+        let mut state = RichTextEditorState::new();
+        state.insert_text("one");
+        state.toggle_list(false);
+        state.insert_paragraph();
+        state.insert_text("two");
+        state.indent();
+        state.insert_paragraph();
+        // Enter in an empty item outdents, then leaves the list
+        state.insert_paragraph();
+        state.insert_paragraph();
+        let kinds: Vec<_> = state
+            .document()
+            .blocks()
+            .iter()
+            .map(|b| b.kind().clone())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                BlockKind::bullet(),
+                BlockKind::ListItem {
+                    ordered: false,
+                    indent: 1
+                },
+                BlockKind::Paragraph,
+            ]
+        );
+        assert_eq!(state.to_markdown(), "- one\n  - two\n");
+    }
+
+    #[test]
+    fn test_paste_html_between_text() {
+        let mut state = RichTextEditorState::with_document(RichDocument::from_text("startend"));
+        state.set_selection(Selection::cursor(pos(0, 5)));
+        state.paste_html("<p>A <b>b</b></p><ul><li>item</li></ul><p>C</p>");
+        assert_eq!(state.document().plain_text(), "startA b\nitem\nCend");
+        assert_eq!(state.document().blocks()[1].kind(), &BlockKind::bullet());
+        assert_eq!(state.selection(), Selection::cursor(pos(2, 1)));
+
+        state.undo();
+        assert_eq!(state.document().plain_text(), "startend");
+    }
+
+    #[test]
+    fn test_delete_across_blocks() {
+        let doc = RichDocument::from_blocks(vec![
+            Block::paragraph("abc"),
+            Block::paragraph("def"),
+            Block::paragraph("ghi"),
+        ]);
+        let mut state = RichTextEditorState::with_document(doc);
+        state.set_selection(Selection::new(pos(0, 1), pos(2, 2)));
+        state.delete_backward();
+        assert_eq!(state.document().plain_text(), "ai");
+        state.undo();
+        assert_eq!(state.document().plain_text(), "abc\ndef\nghi");
+    }
+
+    #[test]
+    fn test_remote_edit_maps_selection_and_history() {
+        let mut state = RichTextEditorState::with_document(RichDocument::from_text("world"));
+        state.insert_text("!");
+        state.take_changes();
+
+        let remote = Transaction::new().insert_text(pos(0, 0), "hello ");
+        state.apply_remote(&remote).unwrap();
+        assert_eq!(state.selection(), Selection::cursor(pos(0, 12)));
+
+        state.undo();
+        assert_eq!(state.document().plain_text(), "hello world");
+        assert_eq!(state.take_changes().len(), 1);
+    }
+
+    #[test]
+    fn test_vertical_movement_keeps_column() {
+        let doc = RichDocument::from_text("abcdef\nab\nabcdef");
+        let mut state = RichTextEditorState::with_document(doc);
+        state.set_selection(Selection::cursor(pos(0, 5)));
+        state.move_down(false);
+        assert_eq!(state.selection().head, pos(1, 2));
+        state.move_down(false);
+        assert_eq!(state.selection().head, pos(2, 5));
+    }
+}
@@ -3,7 +3,8 @@
 //! Multi-line text area with:
 //! - Multi-line text editing
 //! - Row/column sizing (like HTML textarea)
-//! - Cursor and selection
+//! - Cursor and selection, with multiple cursors (Alt+click, Ctrl+D, Alt+drag)
+//! - Find and replace (Ctrl+F, Ctrl+H)
 //! - Visual states: idle, hovered, focused
 //! - Built-in styling that just works
 //! - Inherits ALL Div methods for full layout control via Deref
@@ -26,6 +27,10 @@ use crate::stateful::{
 use crate::text::text;
 use crate::tree::{LayoutNodeId, LayoutTree};
 use crate::widgets::cursor::{cursor_state, CursorAnimation, SharedCursorState};
+use crate::widgets::find_replace::{self, FindAction, FindOptions, FindState, KeyOutcome};
+use crate::widgets::multi_cursor::{
+    self, for_each_cursor, ColumnDrag, CursorEditor, CursorSelection, MultiCursor,
+};
 use crate::widgets::scroll::{Scroll, ScrollDirection, ScrollPhysics, SharedScrollPhysics};
 use crate::widgets::text_input::{
     elapsed_ms, increment_focus_count, request_continuous_redraw_pub, set_focused_text_area,
@@ -226,6 +231,10 @@ pub struct TextAreaState {
     pub(crate) change_signal_id: Option<SignalId>,
     /// Layout bounds storage - updated after layout to get actual rendered dimensions
     pub layout_bounds_storage: crate::renderer::LayoutBoundsStorage,
+    /// Cursors besides `cursor`
+    pub(crate) cursors: MultiCursor,
+    /// Find bar state
+    pub(crate) find: FindState,
 }
 
 impl std::fmt::Debug for TextAreaState {
@@ -234,6 +243,7 @@ impl std::fmt::Debug for TextAreaState {
             .field("lines", &self.lines)
             .field("cursor", &self.cursor)
             .field("selection_start", &self.selection_start)
+            .field("cursors", &self.cursors)
             .field("visual", &self.visual)
             .field("placeholder", &self.placeholder)
             .field("disabled", &self.disabled)
@@ -268,6 +278,8 @@ impl Default for TextAreaState {
             change_version: Arc::new(AtomicU64::new(0)),
             change_signal_id: None,
             layout_bounds_storage: Arc::new(Mutex::new(None)),
+            cursors: MultiCursor::default(),
+            find: FindState::default(),
         }
     }
 }
@@ -322,6 +334,8 @@ impl TextAreaState {
             self.lines.last().map(|l| l.chars().count()).unwrap_or(0),
         );
         self.selection_start = None;
+        self.cursors.clear();
+        find_replace::refresh_matches(self);
     }
 
     /// Get number of lines
@@ -753,6 +767,91 @@ impl TextAreaState {
         })
     }
 
+    // =========================================================================
+    // Multiple cursors
+    // =========================================================================
+
+    /// All cursors and their selections, in text order
+    pub fn cursors(&self) -> Vec<CursorSelection> {
+        let mut all = self.cursors.secondary().to_vec();
+        all.push(CursorSelection {
+            cursor: self.cursor,
+            selection_start: self.selection_start,
+        });
+        all.sort_by_key(|c| {
+            let (from, _) = c.range();
+            (from.line, from.column)
+        });
+        all
+    }
+
+    /// Add a cursor at `pos`, or remove the one already there (Alt+click)
+    pub fn add_cursor(&mut self, pos: TextPosition) {
+        multi_cursor::toggle_cursor(self, pos);
+    }
+
+    /// Select the word at the cursor, or add a cursor at the next occurrence
+    /// of the selection (Ctrl+D)
+    pub fn add_next_occurrence(&mut self) -> bool {
+        multi_cursor::add_next_occurrence(self)
+    }
+
+    /// Keep only the primary cursor
+    pub fn clear_secondary_cursors(&mut self) {
+        self.cursors.clear();
+    }
+
+    // =========================================================================
+    // Find and replace
+    // =========================================================================
+
+    /// Find bar state
+    pub fn find(&self) -> &FindState {
+        &self.find
+    }
+
+    /// Open the find bar, with the replace field if `replace`
+    pub fn open_find(&mut self, replace: bool) {
+        find_replace::open(self, replace);
+    }
+
+    pub fn close_find(&mut self) {
+        find_replace::close(self);
+    }
+
+    /// Set the query and options, updating the matches
+    pub fn set_find_query(&mut self, query: impl Into<String>, options: FindOptions) {
+        self.find.query = query.into();
+        self.find.options = options;
+        let lines = self.lines.clone();
+        self.find.update(&lines);
+    }
+
+    /// Set the replacement text
+    pub fn set_replacement(&mut self, replacement: impl Into<String>) {
+        self.find.replacement = replacement.into();
+    }
+
+    /// Select the next match
+    pub fn find_next(&mut self) {
+        find_replace::select_match(self, false);
+    }
+
+    /// Select the previous match
+    pub fn find_previous(&mut self) {
+        find_replace::select_match(self, true);
+    }
+
+    /// Replace the selected match and select the next one
+    pub fn replace_current(&mut self) -> bool {
+        find_replace::replace_current(self)
+    }
+
+    /// Replace every match, returning the number replaced
+    pub fn replace_all(&mut self) -> usize {
+        find_replace::replace_all(self)
+    }
+
     /// Calculate the number of visual lines a text line takes when wrapped
     ///
     /// Returns 1 for short lines, more for lines that wrap.
@@ -901,8 +1000,13 @@ impl TextAreaState {
     ///
     /// Returns the index into visual_lines where the cursor is located.
     pub fn visual_line_for_cursor(&self) -> usize {
-        let cursor_line = self.cursor.line;
-        let cursor_col = self.cursor.column;
+        self.visual_line_for(self.cursor)
+    }
+
+    /// Get the visual line index for a position
+    fn visual_line_for(&self, pos: TextPosition) -> usize {
+        let cursor_line = pos.line;
+        let cursor_col = pos.column;
 
         for (idx, vl) in self.visual_lines.iter().enumerate() {
             if vl.logical_line == cursor_line {
@@ -932,8 +1036,13 @@ impl TextAreaState {
     ///
     /// Returns the pixel offset from the left edge of the visual line to the cursor.
     pub fn cursor_x_in_visual_line(&self) -> f32 {
-        let cursor_line = self.cursor.line;
-        let cursor_col = self.cursor.column;
+        self.x_in_visual_line(self.cursor)
+    }
+
+    /// Get a position's X offset within its visual line
+    fn x_in_visual_line(&self, pos: TextPosition) -> f32 {
+        let cursor_line = pos.line;
+        let cursor_col = pos.column;
 
         // Find the visual line containing the cursor
         for vl in &self.visual_lines {
@@ -964,6 +1073,48 @@ impl TextAreaState {
         (cursor_x, cursor_visual_y)
     }
 
+    /// Highlight rectangles `(x, y, width)` covering a range, one per
+    /// visual line
+    pub(crate) fn range_rects(&self, from: TextPosition, to: TextPosition) -> Vec<(f32, f32, f32)> {
+        let measure = |text: &str, chars: usize| {
+            let prefix: String = text.chars().take(chars).collect();
+            crate::text_measure::measure_text(&prefix, self.font_size).width
+        };
+        let mut rects = Vec::new();
+        for (idx, vl) in self.visual_lines.iter().enumerate() {
+            if vl.logical_line < from.line || vl.logical_line > to.line {
+                continue;
+            }
+            let start = if vl.logical_line == from.line {
+                from.column.max(vl.start_char)
+            } else {
+                vl.start_char
+            };
+            let end = if vl.logical_line == to.line {
+                to.column.min(vl.end_char)
+            } else {
+                vl.end_char
+            };
+            if start > end {
+                continue;
+            }
+            let x0 = measure(&vl.text, start - vl.start_char);
+            let mut x1 = measure(&vl.text, end - vl.start_char);
+            // Show the selected line break
+            let last_of_line = self
+                .visual_lines
+                .get(idx + 1)
+                .map_or(true, |next| next.logical_line != vl.logical_line);
+            if vl.logical_line < to.line && last_of_line {
+                x1 += self.font_size * 0.3;
+            }
+            if x1 > x0 {
+                rects.push((x0, idx as f32 * self.line_height, x1 - x0));
+            }
+        }
+        rects
+    }
+
     /// Get total visual line count
     pub fn visual_line_count(&self) -> usize {
         if self.visual_lines.is_empty() {
@@ -1180,6 +1331,36 @@ impl TextAreaState {
     }
 }
 
+impl CursorEditor for TextAreaState {
+    fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    fn cursor_selection(&self) -> CursorSelection {
+        CursorSelection {
+            cursor: self.cursor,
+            selection_start: self.selection_start,
+        }
+    }
+
+    fn set_cursor_selection(&mut self, selection: CursorSelection) {
+        self.cursor = selection.cursor;
+        self.selection_start = selection.selection_start;
+    }
+
+    fn insert_text(&mut self, text: &str) {
+        self.insert(text);
+    }
+
+    fn multi_cursor(&mut self) -> &mut MultiCursor {
+        &mut self.cursors
+    }
+
+    fn find_state(&mut self) -> &mut FindState {
+        &mut self.find
+    }
+}
+
 /// Convert character index to byte index
 fn char_to_byte_pos(line: &str, char_pos: usize) -> usize {
     line.char_indices()
@@ -1368,6 +1549,9 @@ impl TextArea {
         let shared_for_click = Arc::clone(&shared_state);
         let shared_for_text = Arc::clone(&shared_state);
         let shared_for_key = Arc::clone(&shared_state);
+        let data_for_drag = Arc::clone(&data);
+        let data_for_drag_end = Arc::clone(&data);
+        let shared_for_drag = Arc::clone(&shared_state);
 
         Stateful::with_shared_state(shared_state)
             // Handle mouse down to focus and position cursor
@@ -1437,6 +1621,14 @@ impl TextArea {
                         d.compute_visual_lines();
                     }
 
+                    // Clicks on the find bar were already handled by the bar
+                    if std::mem::take(&mut d.find.clicked) {
+                        d.clicked_visual_line = None;
+                        drop(d);
+                        refresh_stateful(&shared_for_click);
+                        return;
+                    }
+
                     // Position cursor at click location
                     // Use clicked_visual_line if set by a line element's click handler,
                     // otherwise fall back to y-coordinate calculation
@@ -1451,8 +1643,21 @@ impl TextArea {
                         let text_y = click_y.max(0.0);
                         d.cursor_position_from_xy(text_x, text_y)
                     };
-                    d.cursor = new_pos;
-                    d.selection_start = None; // Clear any selection
+
+                    // Typing goes back to the text
+                    d.find.field = None;
+
+                    if ctx.alt {
+                        // Alt+click adds a cursor; dragging on makes a column selection
+                        multi_cursor::toggle_cursor(&mut *d, new_pos);
+                        let row = d.visual_line_for(new_pos);
+                        d.cursors.column_drag = Some(ColumnDrag { row, x: text_x });
+                    } else {
+                        d.cursors.clear();
+                        d.cursors.column_drag = None;
+                        d.cursor = new_pos;
+                        d.selection_start = None; // Clear any selection
+                    }
                     d.reset_cursor_blink();
 
                     true // needs refresh
@@ -1476,7 +1681,13 @@ impl TextArea {
                     }
 
                     if let Some(c) = ctx.key_char {
-                        d.insert(&c.to_string());
+                        if find_replace::handle_text_input(&mut *d, c) {
+                            drop(d);
+                            refresh_stateful(&shared_for_text);
+                            return;
+                        }
+                        for_each_cursor(&mut *d, |d| d.insert(&c.to_string()));
+                        find_replace::refresh_matches(&mut *d);
                         d.reset_cursor_blink();
                         // Recompute visual lines after text change
                         d.compute_visual_lines();
@@ -1516,60 +1727,75 @@ impl TextArea {
                     let mut cursor_changed = true;
                     let mut should_blur = false;
                     let mut text_changed = false;
-                    match ctx.key_code {
-                        8 => {
-                            // Backspace
-                            d.delete_backward();
-                            text_changed = true;
-                            tracing::debug!("TextArea backspace, value: {}", d.value());
-                        }
-                        127 => {
-                            // Delete
-                            d.delete_forward();
-                            text_changed = true;
-                        }
-                        13 => {
-                            // Enter - insert newline
-                            d.insert_newline();
-                            text_changed = true;
-                            tracing::debug!("TextArea newline, lines: {}", d.line_count());
-                        }
-                        37 => {
-                            // Left arrow
-                            d.move_left(ctx.shift);
-                        }
-                        39 => {
-                            // Right arrow
-                            d.move_right(ctx.shift);
-                        }
-                        38 => {
-                            // Up arrow
-                            d.move_up(ctx.shift);
-                        }
-                        40 => {
-                            // Down arrow
-                            d.move_down(ctx.shift);
-                        }
-                        36 => {
-                            // Home
-                            d.move_to_line_start(ctx.shift);
-                        }
-                        35 => {
-                            // End
-                            d.move_to_line_end(ctx.shift);
-                        }
-                        27 => {
-                            // Escape - blur the textarea
-                            should_blur = true;
-                            cursor_changed = true;
-                        }
-                        _ => {
-                            cursor_changed = false;
-                        }
+                    let shift = ctx.shift;
+                    match find_replace::handle_key(&mut *d, ctx) {
+                        KeyOutcome::Handled => {}
+                        KeyOutcome::Edited => text_changed = true,
+                        KeyOutcome::Ignored => match ctx.key_code {
+                            8 => {
+                                // Backspace
+                                for_each_cursor(&mut *d, |d| d.delete_backward());
+                                text_changed = true;
+                                tracing::debug!("TextArea backspace, value: {}", d.value());
+                            }
+                            127 => {
+                                // Delete
+                                for_each_cursor(&mut *d, |d| d.delete_forward());
+                                text_changed = true;
+                            }
+                            13 => {
+                                // Enter - insert newline
+                                for_each_cursor(&mut *d, |d| d.insert_newline());
+                                text_changed = true;
+                                tracing::debug!("TextArea newline, lines: {}", d.line_count());
+                            }
+                            37 => {
+                                // Left arrow
+                                for_each_cursor(&mut *d, |d| d.move_left(shift));
+                            }
+                            39 => {
+                                // Right arrow
+                                for_each_cursor(&mut *d, |d| d.move_right(shift));
+                            }
+                            38 => {
+                                // Up arrow
+                                for_each_cursor(&mut *d, |d| d.move_up(shift));
+                            }
+                            40 => {
+                                // Down arrow
+                                for_each_cursor(&mut *d, |d| d.move_down(shift));
+                            }
+                            36 => {
+                                // Home
+                                for_each_cursor(&mut *d, |d| d.move_to_line_start(shift));
+                            }
+                            35 => {
+                                // End
+                                for_each_cursor(&mut *d, |d| d.move_to_line_end(shift));
+                            }
+                            68 if ctx.ctrl || ctx.meta => {
+                                // Ctrl+D - add a cursor at the next occurrence
+                                d.add_next_occurrence();
+                            }
+                            27 => {
+                                // Escape - close find, then drop extra cursors, then blur
+                                if d.find.is_open() {
+                                    find_replace::close(&mut *d);
+                                } else if d.cursors.is_active() {
+                                    d.cursors.clear();
+                                } else {
+                                    should_blur = true;
+                                }
+                            }
+                            _ => {
+                                cursor_changed = false;
+                            }
+                        },
                     }
 
                     // Recompute visual lines after text changes
                     if text_changed {
+                        find_replace::refresh_matches(&mut *d);
                         d.compute_visual_lines();
                     }
 
@@ -1604,6 +1830,42 @@ impl TextArea {
                     crate::stateful::check_stateful_deps(&[signal_id]);
                 }
             })
+            // Alt+drag extends a column selection across rows
+            .on_drag(move |ctx| {
+                {
+                    let mut d = match data_for_drag.lock() {
+                        Ok(d) => d,
+                        Err(_) => return,
+                    };
+                    let Some(drag) = d.cursors.column_drag else {
+                        return;
+                    };
+                    if d.visual_lines.is_empty() {
+                        return;
+                    }
+                    let last_row = d.visual_lines.len() - 1;
+                    let rows = (ctx.drag_delta_y / d.line_height).round() as isize;
+                    let target = (drag.row as isize + rows).clamp(0, last_row as isize) as usize;
+                    let (first, last) = (drag.row.min(target), drag.row.max(target));
+                    let end_x = (drag.x + ctx.drag_delta_x).max(0.0);
+                    let selections: Vec<_> = (first..=last)
+                        .map(|row| {
+                            (
+                                d.cursor_position_from_visual_line(row, drag.x),
+                                d.cursor_position_from_visual_line(row, end_x),
+                            )
+                        })
+                        .collect();
+                    multi_cursor::column_selection(&mut *d, selections.into_iter());
+                    d.reset_cursor_blink();
+                }
+                refresh_stateful(&shared_for_drag);
+            })
+            .on_drag_end(move |_| {
+                if let Ok(mut d) = data_for_drag_end.lock() {
+                    d.cursors.column_drag = None;
+                }
+            })
             // Set text cursor (I-beam) for text area
             .cursor_text()
         // Note: Scroll events are handled by the scroll() widget inside build_content
@@ -1665,7 +1927,7 @@ impl TextArea {
 
         // Build cursor canvas element (if focused)
         // The cursor is positioned inside the scroll content so it scrolls with text
-        let cursor_canvas_opt = if is_focused {
+        let cursor_canvases = if is_focused {
             // Cursor top is based on visual line position plus vertical centering within line
             // Shift cursor UP - fonts have descender space at bottom which pushes visible text upward
             let descender_offset = config.font_size * 0.1;
@@ -1682,49 +1944,104 @@ impl TextArea {
                 }
             }
 
-            let cursor_state_clone = Arc::clone(&cursor_state_for_canvas);
-            let cursor_canvas = canvas(
-                move |ctx: &mut dyn junita_core::DrawContext,
-                      bounds: crate::canvas::CanvasBounds| {
-                    let cs = cursor_state_clone.lock().unwrap();
+            // Every cursor blinks with the primary one
+            let cursor_canvas = |left: f32, top: f32| {
+                let cursor_state_clone = Arc::clone(&cursor_state_for_canvas);
+                canvas(
+                    move |ctx: &mut dyn junita_core::DrawContext,
+                          bounds: crate::canvas::CanvasBounds| {
+                        let cs = cursor_state_clone.lock().unwrap();
 
-                    if !cs.visible {
-                        return;
-                    }
-
-                    let opacity = cs.current_opacity();
-                    if opacity < 0.01 {
-                        return;
-                    }
+                        if !cs.visible {
+                            return;
+                        }
 
-                    let color = junita_core::Color::rgba(
-                        cs.color.r,
-                        cs.color.g,
-                        cs.color.b,
-                        cs.color.a * opacity,
-                    );
+                        let opacity = cs.current_opacity();
+                        if opacity < 0.01 {
+                            return;
+                        }
 
-                    ctx.fill_rect(
-                        junita_core::Rect::new(0.0, 0.0, cs.width, bounds.height),
-                        junita_core::CornerRadius::default(),
-                        junita_core::Brush::Solid(color),
-                    );
-                },
-            )
-            .absolute()
-            .left(cursor_left)
-            .top(cursor_top)
-            .w(2.0)
-            .h(cursor_height);
+                        let color = junita_core::Color::rgba(
+                            cs.color.r,
+                            cs.color.g,
+                            cs.color.b,
+                            cs.color.a * opacity,
+                        );
+
+                        ctx.fill_rect(
+                            junita_core::Rect::new(0.0, 0.0, cs.width, bounds.height),
+                            junita_core::CornerRadius::default(),
+                            junita_core::Brush::Solid(color),
+                        );
+                    },
+                )
+                .absolute()
+                .left(left)
+                .top(top)
+                .w(2.0)
+                .h(cursor_height)
+            };
 
-            Some(cursor_canvas)
+            let mut canvases = vec![cursor_canvas(cursor_left, cursor_top)];
+            if !data.visual_lines.is_empty() {
+                for extra in data.cursors.secondary() {
+                    let top = data.visual_line_for(extra.cursor) as f32 * line_height
+                        + (line_height - cursor_height) / 2.0
+                        - descender_offset;
+                    canvases.push(cursor_canvas(data.x_in_visual_line(extra.cursor), top));
+                }
+            }
+            canvases
         } else {
             if let Ok(mut cs) = cursor_state_for_canvas.lock() {
                 cs.visible = false;
             }
-            None
+            Vec::new()
         };
 
+        // Find matches and selections, drawn behind the text
+        let mut highlights = Vec::new();
+        if !data.visual_lines.is_empty() && !data.is_empty() {
+            let mut highlight = |from: TextPosition, to: TextPosition, color: Color| {
+                for (x, y, w) in data.range_rects(from, to) {
+                    highlights.push(
+                        div()
+                            .absolute()
+                            .left(x)
+                            .top(y)
+                            .w(w)
+                            .h(line_height)
+                            .bg(color),
+                    );
+                }
+            };
+            if data.find.is_open() {
+                let primary = CursorSelection {
+                    cursor: data.cursor,
+                    selection_start: data.selection_start,
+                };
+                let current = data.find.current_match(&primary);
+                let (other_color, current_color) =
+                    find_replace::match_colors(config.selection_color);
+                for (i, found) in data.find.matches().iter().enumerate() {
+                    let color = if current == Some(i) {
+                        current_color
+                    } else {
+                        other_color
+                    };
+                    highlight(found.start, found.end, color);
+                }
+            }
+            if is_focused {
+                for selection in data.cursors() {
+                    if selection.has_selection() {
+                        let (from, to) = selection.range();
+                        highlight(from, to, config.selection_color);
+                    }
+                }
+            }
+        }
+
         // Build text content - left-aligned column of text lines
         // Use relative positioning to allow cursor absolute positioning within
         // Note: Don't use w_full() here - each line has explicit width, and w_full would
@@ -1736,6 +2053,9 @@ impl TextArea {
             .items_start()
             .relative()
             .overflow_visible();
+        for highlight in highlights {
+            text_content = text_content.child(highlight);
+        }
 
         if data.is_empty() {
             // Use state's placeholder if available, otherwise fall back to config
@@ -1852,8 +2172,8 @@ impl TextArea {
             }
         }
 
-        // Add cursor inside text_content so it scrolls with the text
-        if let Some(cursor) = cursor_canvas_opt {
+        // Add cursors inside text_content so they scroll with the text
+        for cursor in cursor_canvases {
             text_content = text_content.child(cursor);
         }
 
@@ -1880,8 +2200,9 @@ impl TextArea {
         let content_height = config.effective_height();
         let inner_height = content_height - padding_y * 2.0;
 
-        div()
+        let mut content = div()
             .flex_col()
+            .relative()
             .w(content_width)
             .h(content_height)
             // Top padding spacer
@@ -1900,7 +2221,36 @@ impl TextArea {
                     .child(div().w(padding_x).h(inner_height)),
             )
             // Bottom padding spacer
-            .child(div().h(padding_y).w(content_width))
+            .child(div().h(padding_y).w(content_width));
+
+        if data.find.is_open() {
+            let on_action = Arc::new(move |action: FindAction| {
+                let change_signal = {
+                    let Ok(mut d) = shared_state.lock() else {
+                        return;
+                    };
+                    if !find_replace::apply_action(&mut *d, action) {
+                        return;
+                    }
+                    d.compute_visual_lines();
+                    d.change_signal_id
+                };
+                if let Some(signal_id) = change_signal {
+                    crate::stateful::check_stateful_deps(&[signal_id]);
+                }
+            });
+            let selection = CursorSelection {
+                cursor: data.cursor,
+                selection_start: data.selection_start,
+            };
+            content = content.child(find_replace::find_bar(
+                &data.find,
+                &selection,
+                config.font_size,
+                on_action,
+            ));
+        }
+        content
     }
 
     /// Set placeholder text
@@ -2529,4 +2879,50 @@ mod tests {
         assert_eq!(state.value(), "new");
         assert_eq!(state.line_count(), 1);
     }
+
+    #[test]
+    fn test_text_area_multi_cursor_edit() {
+        let mut state = TextAreaState::with_value("let a = a + a;");
+        state.cursor = TextPosition::new(0, 4);
+        assert!(state.add_next_occurrence());
+        assert!(state.add_next_occurrence());
+        assert!(state.add_next_occurrence());
+        assert_eq!(state.cursors().len(), 3);
+
+        for_each_cursor(&mut state, |s| s.insert("bb"));
+        assert_eq!(state.value(), "let bb = bb + bb;");
+        assert_eq!(state.cursors().len(), 3);
+
+        state.clear_secondary_cursors();
+        assert_eq!(state.cursors().len(), 1);
+    }
+
+    #[test]
+    fn test_text_area_find_replace() {
+        let mut state = TextAreaState::with_value(
+            "Foo foo
+food",
+        );
+        state.open_find(true);
+        state.set_find_query("foo", FindOptions::default());
+        assert_eq!(state.find().matches().len(), 3);
+
+        state.set_find_query(
+            "foo",
+            FindOptions {
+                whole_word: true,
+                ..Default::default()
+            },
+        );
+        assert_eq!(state.find().matches().len(), 2);
+
+        state.set_replacement("bar");
+        assert_eq!(state.replace_all(), 2);
+        assert_eq!(
+            state.value(),
+            "bar bar
+food"
+        );
+        assert!(state.find().matches().is_empty());
+    }
 }
@@ -16,7 +16,7 @@
 //! ```
 
 use std::sync::atomic::{AtomicU64, Ordering};
-use std::sync::{Arc, Mutex, OnceLock, Weak};
+use std::sync::{Arc, Mutex, Weak};
 
 use junita_core::Color;
 use junita_theme::{ColorToken, ThemeState};
@@ -33,10 +33,11 @@ use crate::tree::{LayoutNodeId, LayoutTree};
 use crate::widgets::cursor::{cursor_state, CursorAnimation, SharedCursorState};
 
 /// Get elapsed time in milliseconds since app start (for cursor blinking)
+///
+/// Reads the injectable [`junita_core::clock`], so it follows a virtual
+/// clock during tests and replays.
 pub fn elapsed_ms() -> u64 {
-    static START_TIME: OnceLock<std::time::Instant> = OnceLock::new();
-    let start = START_TIME.get_or_init(std::time::Instant::now);
-    start.elapsed().as_millis() as u64
+    junita_core::clock::now_ms()
 }
 
 /// Standard cursor blink interval in milliseconds
@@ -166,6 +167,7 @@ pub(crate) fn set_focused_text_input(state: &SharedTextInputData) {
     }
 
     blur_focused_text_area();
+    crate::widgets::rich_text_editor::blur_focused_rich_text_editor();
     *focused = Some(Arc::downgrade(state));
 }
 
@@ -213,6 +215,8 @@ pub(crate) fn set_focused_text_area(state: &crate::widgets::text_area::SharedTex
         }
         *focused = Some(Arc::downgrade(state));
     }
+
+    crate::widgets::rich_text_editor::blur_focused_rich_text_editor();
 }
 
 pub(crate) fn clear_focused_text_area(state: &crate::widgets::text_area::SharedTextAreaState) {
@@ -242,7 +246,7 @@ fn blur_focused_text_area() {
     }
 }
 
-/// Blur all focused text inputs and text areas.
+/// Blur all focused text inputs, text areas and rich text editors.
 /// Called when clicking outside any text element.
 pub fn blur_all_text_inputs() {
     use crate::stateful::refresh_stateful;
@@ -311,6 +315,9 @@ pub fn blur_all_text_inputs() {
             }
         }
     }
+
+    // Blur focused RichTextEditor
+    crate::widgets::rich_text_editor::blur_focused_rich_text_editor();
 }
 
 // =============================================================================
@@ -1,6 +1,7 @@
 //! Junita procedural macros
 //!
-//! Provides derive macros for the Junita UI framework.
+//! Provides derive macros for the Junita UI framework, and `view!` for
+//! writing element trees as markup.
 //!
 //! # Platform-Agnostic Design
 //!
@@ -46,6 +47,45 @@ use proc_macro::TokenStream;
 use quote::quote;
 use syn::{parse_macro_input, Data, DeriveInput, Fields};
 
+mod view;
+
+/// Build an element tree from JSX-like markup
+///
+/// Expands to the same `Div`/`ElementBuilder` calls you would write by hand,
+/// so it costs nothing at runtime.
+///
+/// - `<div>..</div>` and `<div />` call `div()`; any function in scope works
+///   as a tag, and `<text("Hi") />` passes arguments to it
+/// - `flex_col`, `gap=4.0` and `p(2.0, 4.0)` call `.flex_col()`,
+///   `.gap(4.0)` and `.p(2.0, 4.0)`; values other than literals, paths and
+///   closures go in braces: `w={width * 2.0}`
+/// - `on_click=|e| { .. }` passes a closure (its body must be a block)
+/// - `key={item.id}` sets the element id, giving list items a stable
+///   identity for queries and diffing
+/// - children are elements, `{expr}`, string literals (wrapped in
+///   `junita_layout::text`), `if`/`else` and `for` blocks
+///
+/// ```ignore
+/// use junita_layout::prelude::*;
+/// use junita_macros::view;
+///
+/// let list = view! {
+///     <div flex_col gap=8.0 p=16.0>
+///         "Todos"
+///         for todo in &todos {
+///             <div flex_row key={todo.id} on_click=move |_| { toggle(todo.id) }>
+///                 <text(&todo.title) />
+///                 if todo.done { "✓" }
+///             </div>
+///         }
+///     </div>
+/// };
+/// ```
+#[proc_macro]
+pub fn view(input: TokenStream) -> TokenStream {
+    parse_macro_input!(input as view::View).expand().into()
+}
+
 /// Check if a field has the #[animation] attribute
 fn has_animation_attr(field: &syn::Field) -> bool {
     field
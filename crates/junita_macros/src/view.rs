@@ -0,0 +1,437 @@
+//! The `view!` macro
+//!
+//! Parses JSX-like markup and expands it to the builder chain that would be
+//! written by hand: `<div flex_col gap=4.0>{label}</div>` becomes
+//! `div().flex_col().gap(4.0).child(label)`. Control flow becomes a block
+//! that threads the element through `if`/`for` and carries on.
+//!
+//! Generated method calls take the span of the attribute or child they came
+//! from, so a misspelt attribute or a value of the wrong type is reported
+//! there.
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{quote, quote_spanned, ToTokens};
+use syn::ext::IdentExt;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{braced, parenthesized, token, Expr, Ident, LitStr, Pat, Path, Token};
+
+/// A single node of markup
+enum Node {
+    Element(Element),
+    Text(LitStr),
+    Expr(Expr),
+    If(IfNode),
+    For(Box<ForNode>),
+}
+
+/// `<path(args) attrs>children</path>` or `<path(args) attrs />`
+struct Element {
+    path: Path,
+    args: Option<Punctuated<Expr, Token![,]>>,
+    attrs: Vec<Attr>,
+    children: Vec<Node>,
+}
+
+/// `name`, `name=value` or `name(args)`
+struct Attr {
+    name: Ident,
+    args: Vec<Expr>,
+}
+
+/// `if cond { nodes } else if .. { nodes } else { nodes }`
+struct IfNode {
+    cond: TokenStream,
+    then: Vec<Node>,
+    otherwise: Option<Box<Else>>,
+}
+
+enum Else {
+    If(IfNode),
+    Nodes(Vec<Node>),
+}
+
+/// `for pat in expr { nodes }`
+struct ForNode {
+    pat: Pat,
+    expr: Expr,
+    body: Vec<Node>,
+}
+
+/// The whole macro input: one root element
+pub struct View {
+    root: Element,
+}
+
+impl Parse for View {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if !input.peek(Token![<]) {
+            return Err(input.error("expected a root element like `<div>`"));
+        }
+        let root = input.parse()?;
+        if !input.is_empty() {
+            return Err(input.error("`view!` takes a single root element"));
+        }
+        Ok(View { root })
+    }
+}
+
+impl Parse for Element {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![<]>()?;
+        let path = Path::parse_mod_style(input)?;
+        let args = if input.peek(token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            Some(content.parse_terminated(Expr::parse, Token![,])?)
+        } else {
+            None
+        };
+
+        let mut attrs = Vec::new();
+        loop {
+            if input.peek(Token![/]) {
+                input.parse::<Token![/]>()?;
+                input.parse::<Token![>]>()?;
+                return Ok(Element {
+                    path,
+                    args,
+                    attrs,
+                    children: Vec::new(),
+                });
+            }
+            if input.peek(Token![>]) {
+                input.parse::<Token![>]>()?;
+                break;
+            }
+            attrs.push(input.parse()?);
+        }
+
+        let children = parse_nodes(input, |input| {
+            input.peek(Token![<]) && input.peek2(Token![/])
+        })?;
+        input.parse::<Token![<]>()?;
+        input.parse::<Token![/]>()?;
+        let close = Path::parse_mod_style(input)?;
+        if close != path {
+            return Err(syn::Error::new_spanned(
+                &close,
+                format!(
+                    "closing tag `</{}>` doesn't match `<{}>`",
+                    close.to_token_stream(),
+                    path.to_token_stream()
+                ),
+            ));
+        }
+        input.parse::<Token![>]>()?;
+        Ok(Element {
+            path,
+            args,
+            attrs,
+            children,
+        })
+    }
+}
+
+impl Parse for Attr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input
+            .call(Ident::parse_any)
+            .map_err(|err| syn::Error::new(err.span(), "expected an attribute, `>` or `/>`"))?;
+        let args = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            vec![parse_value(input)?]
+        } else if input.peek(token::Paren) {
+            let content;
+            parenthesized!(content in input);
+            content
+                .parse_terminated(Expr::parse, Token![,])?
+                .into_iter()
+                .collect()
+        } else {
+            Vec::new()
+        };
+        Ok(Attr { name, args })
+    }
+}
+
+/// An attribute value: a literal, a path, `{expr}` or a closure with a
+/// block body
+///
+/// Anything longer needs braces, which keeps `>` from being read as part of
+/// the value.
+fn parse_value(input: ParseStream) -> syn::Result<Expr> {
+    if input.peek(token::Brace) {
+        return parse_braced_expr(input);
+    }
+    if input.peek(Token![move]) || input.peek(Token![|]) || input.peek(Token![||]) {
+        return parse_closure(input);
+    }
+    if input.peek(Token![-]) || input.peek(syn::Lit) {
+        let mut tokens = TokenStream::new();
+        if input.peek(Token![-]) {
+            input.parse::<Token![-]>()?.to_tokens(&mut tokens);
+        }
+        input.parse::<syn::Lit>()?.to_tokens(&mut tokens);
+        return syn::parse2(tokens);
+    }
+    if input.peek(Ident::peek_any) || input.peek(Token![::]) {
+        return input.parse::<syn::ExprPath>().map(Expr::Path);
+    }
+    Err(input.error("expected a literal, a path, `{expr}` or a closure"))
+}
+
+/// `{ expr }`, unwrapped when it holds a single expression
+fn parse_braced_expr(input: ParseStream) -> syn::Result<Expr> {
+    let block: syn::ExprBlock = input.parse()?;
+    match block.block.stmts.as_slice() {
+        [syn::Stmt::Expr(expr, None)] => Ok(expr.clone()),
+        _ => Ok(Expr::Block(block)),
+    }
+}
+
+/// `move |args| { body }`
+fn parse_closure(input: ParseStream) -> syn::Result<Expr> {
+    let mut tokens = TokenStream::new();
+    if input.peek(Token![move]) {
+        input.parse::<Token![move]>()?.to_tokens(&mut tokens);
+    }
+    if input.peek(Token![||]) {
+        input.parse::<Token![||]>()?.to_tokens(&mut tokens);
+    } else {
+        input.parse::<Token![|]>()?.to_tokens(&mut tokens);
+        while !input.peek(Token![|]) {
+            if input.is_empty() {
+                return Err(input.error("unterminated closure parameters"));
+            }
+            input.parse::<TokenTree>()?.to_tokens(&mut tokens);
+        }
+        input.parse::<Token![|]>()?.to_tokens(&mut tokens);
+    }
+    if !input.peek(token::Brace) {
+        return Err(input.error("closure bodies in attributes must be blocks: `|e| { .. }`"));
+    }
+    input.parse::<TokenTree>()?.to_tokens(&mut tokens);
+    syn::parse2(tokens)
+}
+
+/// Parse nodes until `end` matches or the input runs out
+fn parse_nodes(input: ParseStream, end: fn(ParseStream) -> bool) -> syn::Result<Vec<Node>> {
+    let mut nodes = Vec::new();
+    while !input.is_empty() && !end(input) {
+        nodes.push(input.parse()?);
+    }
+    Ok(nodes)
+}
+
+fn parse_block_nodes(input: ParseStream) -> syn::Result<Vec<Node>> {
+    let content;
+    braced!(content in input);
+    parse_nodes(&content, |_| false)
+}
+
+impl Parse for Node {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![<]) {
+            input.parse().map(Node::Element)
+        } else if input.peek(LitStr) {
+            input.parse().map(Node::Text)
+        } else if input.peek(token::Brace) {
+            parse_braced_expr(input).map(Node::Expr)
+        } else if input.peek(Token![if]) {
+            input.parse().map(Node::If)
+        } else if input.peek(Token![for]) {
+            input.parse().map(|node| Node::For(Box::new(node)))
+        } else {
+            Err(input.error("expected an element, a string, `{expr}`, `if` or `for`"))
+        }
+    }
+}
+
+impl Parse for IfNode {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![if]>()?;
+        let mut cond = TokenStream::new();
+        if input.peek(Token![let]) {
+            input.parse::<Token![let]>()?.to_tokens(&mut cond);
+            Pat::parse_multi_with_leading_vert(input)?.to_tokens(&mut cond);
+            input.parse::<Token![=]>()?.to_tokens(&mut cond);
+        }
+        Expr::parse_without_eager_brace(input)?.to_tokens(&mut cond);
+        let then = parse_block_nodes(input)?;
+        let otherwise = if input.peek(Token![else]) {
+            input.parse::<Token![else]>()?;
+            Some(Box::new(if input.peek(Token![if]) {
+                Else::If(input.parse()?)
+            } else {
+                Else::Nodes(parse_block_nodes(input)?)
+            }))
+        } else {
+            None
+        };
+        Ok(IfNode {
+            cond,
+            then,
+            otherwise,
+        })
+    }
+}
+
+impl Parse for ForNode {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        input.parse::<Token![for]>()?;
+        let pat = Pat::parse_multi_with_leading_vert(input)?;
+        input.parse::<Token![in]>()?;
+        let expr = Expr::parse_without_eager_brace(input)?;
+        let body = parse_block_nodes(input)?;
+        Ok(ForNode { pat, expr, body })
+    }
+}
+
+/// Name of the element being threaded through control flow
+fn element_ident() -> Ident {
+    Ident::new("__view_el", Span::mixed_site())
+}
+
+impl Element {
+    fn expand(&self) -> TokenStream {
+        let path = &self.path;
+        let args = self.args.iter().flat_map(|args| args.iter());
+        let mut chain = quote_spanned!(path.span()=> #path(#(#args),*));
+        for attr in &self.attrs {
+            let name = &attr.name;
+            let args = &attr.args;
+            chain = if name == "key" {
+                quote_spanned!(name.span()=> #chain.id(::std::string::ToString::to_string(&(#(#args),*))))
+            } else {
+                quote_spanned!(name.span()=> #chain.#name(#(#args),*))
+            };
+        }
+        expand_children(chain, &self.children)
+    }
+}
+
+/// Append `nodes` to the element `chain` evaluates to
+fn expand_children(mut chain: TokenStream, nodes: &[Node]) -> TokenStream {
+    let el = element_ident();
+    for node in nodes {
+        chain = match node {
+            Node::Element(element) => {
+                let child = element.expand();
+                quote_spanned!(element.path.span()=> #chain.child(#child))
+            }
+            Node::Text(lit) => {
+                quote_spanned!(lit.span()=> #chain.child(::junita_layout::text(#lit)))
+            }
+            Node::Expr(expr) => quote_spanned!(expr.span()=> #chain.child(#expr)),
+            Node::If(node) => {
+                let branches = node.expand(&el);
+                quote!({
+                    let #el = #chain;
+                    #branches
+                })
+            }
+            Node::For(node) => {
+                let pat = &node.pat;
+                let expr = &node.expr;
+                let body = expand_children(quote!(#el), &node.body);
+                quote!({
+                    let mut #el = #chain;
+                    for #pat in #expr {
+                        #el = #body;
+                    }
+                    #el
+                })
+            }
+        };
+    }
+    chain
+}
+
+impl IfNode {
+    fn expand(&self, el: &Ident) -> TokenStream {
+        let cond = &self.cond;
+        let then = expand_children(quote!(#el), &self.then);
+        let otherwise = match self.otherwise.as_deref() {
+            Some(Else::If(node)) => node.expand(el),
+            Some(Else::Nodes(nodes)) => {
+                let nodes = expand_children(quote!(#el), nodes);
+                quote!({ #nodes })
+            }
+            None => quote!({ #el }),
+        };
+        quote!(if #cond { #then } else #otherwise)
+    }
+}
+
+impl View {
+    pub fn expand(&self) -> TokenStream {
+        self.root.expand()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn expand(input: TokenStream) -> String {
+        syn::parse2::<View>(input).unwrap().expand().to_string()
+    }
+
+    fn error(input: TokenStream) -> String {
+        match syn::parse2::<View>(input) {
+            Ok(_) => panic!("expected an error"),
+            Err(err) => err.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_attributes_and_children() {
+        let expanded = expand(quote! {
+            <div flex_col gap=4.0 bg=Color::WHITE p(2.0) key={item.id} on_click=move |_| { save() }>
+                "Title"
+                <text(label) size=-1 />
+                {badge()}
+            </div>
+        });
+        let expected = quote! {
+            div()
+                .flex_col()
+                .gap(4.0)
+                .bg(Color::WHITE)
+                .p(2.0)
+                .id(::std::string::ToString::to_string(&(item.id)))
+                .on_click(move |_| { save() })
+                .child(::junita_layout::text("Title"))
+                .child(text(label).size(-1))
+                .child(badge())
+        };
+        assert_eq!(expanded, expected.to_string());
+    }
+
+    #[test]
+    fn test_control_flow() {
+        let expanded = expand(quote! {
+            <div>
+                if let Some(user) = user { <text(user) /> } else if busy { "…" }
+                for item in items.iter() { {row(item)} }
+            </div>
+        });
+        assert!(expanded.contains("if let Some (user) = user { __view_el . child (text (user)) }"));
+        assert!(expanded.contains(
+            "else if busy { __view_el . child (:: junita_layout :: text (\"…\")) } else { __view_el }"
+        ));
+        assert!(expanded.contains(
+            "for item in items . iter () { __view_el = __view_el . child (row (item)) ; }"
+        ));
+    }
+
+    #[test]
+    fn test_errors() {
+        assert!(error(quote!(<div></span>)).contains("closing tag `</span>`"));
+        assert!(error(quote!(<div /> <div />)).contains("single root"));
+        assert!(error(quote!(<div on_click=|_| save()></div>)).contains("must be blocks"));
+        assert!(error(quote!(<div w=a.b></div>)).contains("expected an attribute"));
+    }
+}
@@ -0,0 +1,11 @@
+//! `view!` diagnostics for malformed markup
+//!
+//! Each case in `tests/ui` has its expected compiler output next to it, so
+//! the tests also pin down which tokens the errors point at. Regenerate the
+//! `.stderr` files with `TRYBUILD=overwrite cargo test -p junita_macros`.
+
+#[test]
+fn test_view_compile_errors() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,7 @@
+use junita_macros::view;
+
+fn main() {
+    let _ = view! {
+        <div on_click=|_| println!("clicked")></div>
+    };
+}
@@ -0,0 +1,10 @@
+use junita_macros::view;
+
+fn main() {
+    let _ = view! {
+        <div>
+            "Count"
+            42
+        </div>
+    };
+}
@@ -0,0 +1,9 @@
+use junita_macros::view;
+
+fn main() {
+    let _ = view! {
+        <div flex_col>
+            <div>"Title"</span>
+        </div>
+    };
+}
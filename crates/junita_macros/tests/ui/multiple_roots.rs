@@ -0,0 +1,8 @@
+use junita_macros::view;
+
+fn main() {
+    let _ = view! {
+        <div />
+        <div />
+    };
+}
@@ -0,0 +1,10 @@
+use junita_layout::prelude::*;
+use junita_macros::view;
+
+fn main() {
+    let _ = view! {
+        <div flex_col gapp=4.0>
+            "Title"
+        </div>
+    };
+}
@@ -0,0 +1,8 @@
+use junita_layout::prelude::*;
+use junita_macros::view;
+
+fn main() {
+    let _ = view! {
+        <div w="100px" />
+    };
+}
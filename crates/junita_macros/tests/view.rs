@@ -0,0 +1,63 @@
+//! `view!` against the real builder API
+
+use junita_layout::prelude::*;
+use junita_layout::text::text;
+use junita_macros::view;
+
+struct Todo {
+    id: u32,
+    title: &'static str,
+    done: bool,
+}
+
+#[test]
+fn test_view_builds_element_tree() {
+    let todos = [
+        Todo {
+            id: 1,
+            title: "Write macro",
+            done: true,
+        },
+        Todo {
+            id: 2,
+            title: "Test it",
+            done: false,
+        },
+    ];
+    let footer = div().h(20.0);
+
+    let tree = view! {
+        <div flex_col gap=8.0 p(16.0) key="todos">
+            "Todos"
+            for todo in &todos {
+                <div flex_row key={todo.id} on_click=move |_| { let _ = todo.id; }>
+                    <text(todo.title) size=14.0 />
+                    if todo.done { "done" }
+                </div>
+            }
+            if todos.is_empty() { "Nothing to do" } else { {footer} }
+        </div>
+    };
+
+    assert_eq!(tree.element_id(), Some("todos"));
+    let children = tree.children_builders();
+    assert_eq!(children.len(), 4);
+    assert_eq!(children[1].element_id(), Some("1"));
+    assert_eq!(children[1].children_builders().len(), 2);
+    assert_eq!(children[2].element_id(), Some("2"));
+    assert_eq!(children[2].children_builders().len(), 1);
+}
+
+/// String children are wrapped by path, so they work without `text` in scope
+mod string_children {
+    use junita_layout::div::{div, ElementBuilder, ElementTypeId};
+    use junita_macros::view;
+
+    #[test]
+    fn test_string_children_expand_to_text() {
+        let tree = view! { <div>"Hello"</div> };
+        let children = tree.children_builders();
+        assert_eq!(children.len(), 1);
+        assert_eq!(children[0].element_type_id(), ElementTypeId::Text);
+    }
+}
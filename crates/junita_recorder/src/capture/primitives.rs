@@ -1,7 +1,8 @@
 //! Core primitive types for recording.
 
+use junita_core::clock;
 use serde::{Deserialize, Serialize};
-use std::time::{Duration, Instant};
+use std::time::Duration;
 
 /// A timestamp relative to the recording session start.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
@@ -123,27 +124,30 @@ impl From<junita_core::Rect> for Rect {
 }
 
 /// Clock for generating timestamps relative to session start.
+///
+/// Reads the process-wide [`junita_core::clock`], so a session recorded
+/// under a virtual clock gets the same timestamps on every run.
 #[derive(Debug)]
 pub struct RecordingClock {
-    start: Instant,
+    start: Duration,
 }
 
 impl RecordingClock {
     /// Create a new clock starting now.
     pub fn new() -> Self {
         Self {
-            start: Instant::now(),
+            start: clock::now(),
         }
     }
 
     /// Get the current timestamp relative to session start.
     pub fn now(&self) -> Timestamp {
-        Timestamp::from_duration(self.start.elapsed())
+        Timestamp::from_duration(clock::now().saturating_sub(self.start))
     }
 
     /// Reset the clock to start now.
     pub fn reset(&mut self) {
-        self.start = Instant::now();
+        self.start = clock::now();
     }
 }
 
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
 /// A complete snapshot of the element tree at a point in time.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct TreeSnapshot {
     /// When this snapshot was taken.
     pub timestamp: Timestamp,
@@ -21,6 +21,9 @@ pub struct TreeSnapshot {
     pub window_size: (u32, u32),
     /// Scale factor at time of snapshot.
     pub scale_factor: f64,
+    /// Regions repainted for this frame (logical pixels).
+    #[serde(default)]
+    pub dirty_regions: Vec<Rect>,
 }
 
 impl TreeSnapshot {
@@ -34,9 +37,16 @@ impl TreeSnapshot {
             hovered_element: None,
             window_size,
             scale_factor,
+            dirty_regions: Vec::new(),
         }
     }
 
+    /// Set the regions repainted for this frame.
+    pub fn with_dirty_regions(mut self, regions: Vec<Rect>) -> Self {
+        self.dirty_regions = regions;
+        self
+    }
+
     /// Get an element by ID.
     pub fn get(&self, id: &str) -> Option<&ElementSnapshot> {
         self.elements.get(id)
@@ -59,10 +69,13 @@ impl TreeSnapshot {
 }
 
 /// A snapshot of a single element's state.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ElementSnapshot {
     /// The element's string ID.
     pub id: String,
+    /// User-assigned ID (set with `.id()`), stable across rebuilds.
+    #[serde(default)]
+    pub stable_id: Option<String>,
     /// Element type identifier (e.g., "Div", "Text", "Button").
     pub element_type: String,
     /// The element's computed bounds.
@@ -90,6 +103,7 @@ impl ElementSnapshot {
     pub fn new(id: String, element_type: String, bounds: Rect) -> Self {
         Self {
             id,
+            stable_id: None,
             element_type,
             bounds,
             is_visible: true,
@@ -115,7 +129,7 @@ impl ElementSnapshot {
 }
 
 /// Optional visual properties for detailed element inspection.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct VisualProps {
     /// Background color (RGBA).
     pub background_color: Option<[f32; 4]>,
@@ -211,6 +225,74 @@ pub struct PropertyChange {
     pub new_value: Option<String>,
 }
 
+/// An incremental update that turns one tree snapshot into the next.
+///
+/// Unlike a bare [`TreeDiff`], a patch carries the new state of every added
+/// or modified element, so a remote client can keep its own copy of the tree
+/// in sync without receiving a full snapshot every frame.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TreePatch {
+    /// Timestamp of the snapshot this patch produces.
+    pub timestamp: Timestamp,
+    /// What changed between the two snapshots.
+    pub diff: TreeDiff,
+    /// New state of added and modified elements.
+    pub elements: Vec<ElementSnapshot>,
+    /// Root element ID after the patch.
+    pub root_id: Option<String>,
+    /// Focused element ID after the patch.
+    pub focused_element: Option<String>,
+    /// Hovered element ID after the patch.
+    pub hovered_element: Option<String>,
+    /// Window dimensions after the patch.
+    pub window_size: (u32, u32),
+}
+
+impl TreePatch {
+    /// Compute the patch that turns `old` into `new`.
+    pub fn between(old: &TreeSnapshot, new: &TreeSnapshot) -> Self {
+        let diff = diff_trees(old, new);
+        let elements = diff
+            .added
+            .iter()
+            .chain(diff.modified.keys())
+            .filter_map(|id| new.elements.get(id).cloned())
+            .collect();
+
+        Self {
+            timestamp: new.timestamp,
+            diff,
+            elements,
+            root_id: new.root_id.clone(),
+            focused_element: new.focused_element.clone(),
+            hovered_element: new.hovered_element.clone(),
+            window_size: new.window_size,
+        }
+    }
+
+    /// Check if applying this patch would change anything besides the timestamp.
+    pub fn is_empty(&self) -> bool {
+        self.diff.is_empty()
+    }
+
+    /// Apply this patch to a snapshot in place.
+    pub fn apply(&self, snapshot: &mut TreeSnapshot) {
+        for id in &self.diff.removed {
+            snapshot.elements.remove(id);
+        }
+        for element in &self.elements {
+            snapshot
+                .elements
+                .insert(element.id.clone(), element.clone());
+        }
+        snapshot.timestamp = self.timestamp;
+        snapshot.root_id = self.root_id.clone();
+        snapshot.focused_element = self.focused_element.clone();
+        snapshot.hovered_element = self.hovered_element.clone();
+        snapshot.window_size = self.window_size;
+    }
+}
+
 /// Compute the difference between two tree snapshots.
 pub fn diff_trees(old: &TreeSnapshot, new: &TreeSnapshot) -> TreeDiff {
     let mut added = Vec::new();
@@ -302,6 +384,22 @@ fn diff_elements(old: &ElementSnapshot, new: &ElementSnapshot) -> Option<Element
         }
     }
 
+    // Check visual properties and text content
+    if old.visual_props != new.visual_props {
+        changes.push(PropertyChange {
+            property: "visual_props".to_string(),
+            old_value: old.visual_props.as_ref().map(|p| format!("{:?}", p)),
+            new_value: new.visual_props.as_ref().map(|p| format!("{:?}", p)),
+        });
+    }
+    if old.text_content != new.text_content {
+        changes.push(PropertyChange {
+            property: "text".to_string(),
+            old_value: old.text_content.clone(),
+            new_value: new.text_content.clone(),
+        });
+    }
+
     // Check children (structural)
     if old.children != new.children {
         category = ChangeCategory::Structural;
@@ -358,4 +456,59 @@ mod tests {
         assert!(diff.added.is_empty());
         assert_eq!(diff.removed, vec!["old-elem".to_string()]);
     }
+
+    #[test]
+    fn test_tree_patch_round_trip() {
+        let elem = |id: &str, width: f32| {
+            ElementSnapshot::new(
+                id.to_string(),
+                "Div".to_string(),
+                Rect::new(0.0, 0.0, width, 10.0),
+            )
+        };
+
+        let mut old = TreeSnapshot::new(Timestamp::zero(), (800, 600), 1.0);
+        old.elements.insert("a".to_string(), elem("a", 10.0));
+        old.elements.insert("b".to_string(), elem("b", 10.0));
+
+        let mut new = TreeSnapshot::new(Timestamp::from_micros(1000), (800, 600), 1.0);
+        new.elements.insert("a".to_string(), elem("a", 20.0));
+        let mut styled = elem("c", 10.0);
+        styled.visual_props = Some(VisualProps {
+            opacity: Some(0.5),
+            ..Default::default()
+        });
+        new.elements.insert("c".to_string(), styled);
+        new.focused_element = Some("c".to_string());
+
+        let patch = TreePatch::between(&old, &new);
+        assert!(!patch.is_empty());
+        assert_eq!(patch.elements.len(), 2);
+
+        let mut synced = old.clone();
+        patch.apply(&mut synced);
+        assert_eq!(synced.element_count(), 2);
+        assert!(synced.get("b").is_none());
+        assert_eq!(synced.get("a").unwrap().bounds.width, 20.0);
+        assert_eq!(synced.focused_element.as_deref(), Some("c"));
+        assert_eq!(synced.timestamp, new.timestamp);
+        assert!(diff_trees(&synced, &new).is_empty());
+    }
+
+    #[test]
+    fn test_tree_diff_detects_visual_props() {
+        let mut old = TreeSnapshot::new(Timestamp::zero(), (800, 600), 1.0);
+        old.elements.insert(
+            "a".to_string(),
+            ElementSnapshot::new("a".to_string(), "Div".to_string(), Rect::default()),
+        );
+        let mut new = old.clone();
+        new.elements.get_mut("a").unwrap().visual_props = Some(VisualProps {
+            opacity: Some(0.5),
+            ..Default::default()
+        });
+
+        let diff = diff_trees(&old, &new);
+        assert_eq!(diff.modified["a"].changes[0].property, "visual_props");
+    }
 }
@@ -0,0 +1,609 @@
+//! Binary recording container.
+//!
+//! Recordings can be written incrementally while the session runs instead of
+//! being serialized once after `stop()`. The file is a sequence of
+//! self-describing chunks, so a crashed session still leaves every flushed
+//! chunk readable.
+//!
+//! # Layout
+//!
+//! All integers are little-endian.
+//!
+//! ```text
+//! header   "JREC" version:u16 min_reader:u16 flags:u32 len:u32 json[len]
+//! chunk*   "CHNK" kind:u8 codec:u8 reserved:u16 raw_len:u32 data_len:u32
+//!          crc32:u32 count:u32 start_us:u64 end_us:u64 data[data_len]
+//! trailer  "JEND" index_offset:u64
+//! ```
+//!
+//! Chunk payloads are JSON, deflated when `codec` is 1. Event chunks hold
+//! `TimestampedEvent`s and snapshot chunks hold `SnapshotRecord`s: a full
+//! keyframe followed by deltas against the previous snapshot, so every chunk
+//! decodes on its own. The last chunk is the index, which lists every chunk
+//! with its time range and is what the trailer points at.
+//!
+//! Lengths are checked before anything is allocated: a header or chunk
+//! that claims more than the rest of the file, [`MAX_HEADER_LEN`] or
+//! [`MAX_CHUNK_LEN`] is a [`FormatError::Corrupt`].
+//!
+//! # Schema evolution
+//!
+//! - `version` is bumped for every change. Readers accept any file whose
+//!   `min_reader` is at most [`READER_VERSION`].
+//! - Additive changes (new chunk kinds, new JSON fields with
+//!   `#[serde(default)]`) keep `min_reader` unchanged; readers skip chunk
+//!   kinds and ignore fields they don't know.
+//! - Anything an older reader would misinterpret bumps `min_reader`.
+//!
+//! Files without a trailer (crashed or still being written) are recovered by
+//! scanning chunk headers up to the first truncated chunk.
+
+mod reader;
+mod writer;
+
+pub use reader::*;
+pub use writer::*;
+
+use crate::capture::{ElementSnapshot, Rect, Timestamp, TreeSnapshot};
+use crate::session::{RecordingConfig, RecordingExport, SessionStats};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fmt;
+use std::path::Path;
+
+/// Magic bytes at the start of a recording file.
+pub const MAGIC: [u8; 4] = *b"JREC";
+/// Magic bytes at the start of each chunk.
+pub const CHUNK_MAGIC: [u8; 4] = *b"CHNK";
+/// Magic bytes at the start of the trailer.
+pub const TRAILER_MAGIC: [u8; 4] = *b"JEND";
+/// Format version written by this crate.
+pub const FORMAT_VERSION: u16 = 1;
+/// Oldest format version an older reader must understand to open files
+/// written by this crate.
+pub const MIN_READER_VERSION: u16 = 1;
+/// Newest format version this crate can read.
+pub const READER_VERSION: u16 = 1;
+
+/// Size of the fixed part of a chunk header.
+pub(crate) const CHUNK_HEADER_LEN: u64 = 40;
+/// Size of the trailer.
+pub(crate) const TRAILER_LEN: u64 = 12;
+/// Largest file header a reader accepts.
+pub const MAX_HEADER_LEN: u64 = 1 << 20;
+/// Largest chunk payload, stored or decompressed, a reader accepts.
+pub const MAX_CHUNK_LEN: u64 = 256 << 20;
+
+/// Errors reading or writing recording files.
+#[derive(Debug)]
+pub enum FormatError {
+    /// Underlying I/O failure.
+    Io(std::io::Error),
+    /// A JSON payload could not be encoded or decoded.
+    Json(serde_json::Error),
+    /// The file doesn't start with the recording magic.
+    NotARecording,
+    /// The file needs a newer reader.
+    UnsupportedVersion { version: u16, min_reader: u16 },
+    /// A chunk failed its checksum or was malformed.
+    Corrupt(String),
+}
+
+impl fmt::Display for FormatError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FormatError::Io(e) => write!(f, "I/O error: {}", e),
+            FormatError::Json(e) => write!(f, "invalid payload: {}", e),
+            FormatError::NotARecording => write!(f, "not a junita recording"),
+            FormatError::UnsupportedVersion {
+                version,
+                min_reader,
+            } => write!(
+                f,
+                "recording format {} needs reader version {} (this reader supports {})",
+                version, min_reader, READER_VERSION
+            ),
+            FormatError::Corrupt(reason) => write!(f, "corrupt recording: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for FormatError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            FormatError::Io(e) => Some(e),
+            FormatError::Json(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for FormatError {
+    fn from(e: std::io::Error) -> Self {
+        FormatError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for FormatError {
+    fn from(e: serde_json::Error) -> Self {
+        FormatError::Json(e)
+    }
+}
+
+/// Result type for recording file operations.
+pub type Result<T> = std::result::Result<T, FormatError>;
+
+/// Metadata stored in the file header.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FileHeader {
+    /// Configuration the session was recorded with.
+    pub config: RecordingConfig,
+    /// Wall-clock time the file was created (milliseconds since the Unix epoch).
+    #[serde(default)]
+    pub created_unix_ms: u64,
+    /// Name and version of the writer.
+    #[serde(default)]
+    pub writer: String,
+}
+
+impl FileHeader {
+    /// Create a header for a new file.
+    pub fn new(config: RecordingConfig) -> Self {
+        let created_unix_ms = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+        Self {
+            config,
+            created_unix_ms,
+            writer: concat!("junita_recorder ", env!("CARGO_PKG_VERSION")).to_string(),
+        }
+    }
+}
+
+/// What a chunk contains.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ChunkKind {
+    /// Timestamped events.
+    Events,
+    /// Tree snapshots (keyframe plus deltas).
+    Snapshots,
+    /// Chunk index written when the file is finished.
+    Index,
+    /// A kind written by a newer version.
+    Unknown(u8),
+}
+
+impl ChunkKind {
+    pub(crate) fn to_byte(self) -> u8 {
+        match self {
+            ChunkKind::Events => 1,
+            ChunkKind::Snapshots => 2,
+            ChunkKind::Index => 3,
+            ChunkKind::Unknown(b) => b,
+        }
+    }
+
+    pub(crate) fn from_byte(b: u8) -> Self {
+        match b {
+            1 => ChunkKind::Events,
+            2 => ChunkKind::Snapshots,
+            3 => ChunkKind::Index,
+            other => ChunkKind::Unknown(other),
+        }
+    }
+}
+
+/// Seek index entry for one chunk.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChunkInfo {
+    /// Kind of the chunk.
+    pub kind: ChunkKind,
+    /// Byte offset of the chunk header.
+    pub offset: u64,
+    /// Timestamp of the first record.
+    pub start: Timestamp,
+    /// Timestamp of the last record.
+    pub end: Timestamp,
+    /// Number of records.
+    pub count: u32,
+}
+
+/// Contents of the index chunk.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct RecordingIndex {
+    /// Data chunks in file order.
+    pub chunks: Vec<ChunkInfo>,
+    /// Session statistics at the time the file was finished.
+    #[serde(default)]
+    pub stats: Option<SessionStats>,
+}
+
+/// A snapshot as stored in a snapshot chunk.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SnapshotRecord {
+    /// Complete tree.
+    Full(TreeSnapshot),
+    /// Changes against the previous snapshot in the chunk.
+    Delta(SnapshotDelta),
+}
+
+impl SnapshotRecord {
+    /// Timestamp of the snapshot.
+    pub fn timestamp(&self) -> Timestamp {
+        match self {
+            SnapshotRecord::Full(s) => s.timestamp,
+            SnapshotRecord::Delta(d) => d.timestamp,
+        }
+    }
+}
+
+/// Element-level delta between two tree snapshots.
+///
+/// Unlike [`TreePatch`](crate::TreePatch), which tracks individual properties
+/// for display, a delta stores changed elements whole so it reconstructs the
+/// next snapshot exactly.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotDelta {
+    /// Timestamp of the new snapshot.
+    pub timestamp: Timestamp,
+    /// Elements that no longer exist.
+    #[serde(default)]
+    pub removed: Vec<String>,
+    /// Elements that were added or changed.
+    #[serde(default)]
+    pub upserted: Vec<ElementSnapshot>,
+    /// Root element ID.
+    pub root_id: Option<String>,
+    /// Focused element ID.
+    pub focused_element: Option<String>,
+    /// Hovered element ID.
+    pub hovered_element: Option<String>,
+    /// Window size.
+    pub window_size: (u32, u32),
+    /// Scale factor.
+    pub scale_factor: f64,
+    /// Dirty regions of the new snapshot.
+    #[serde(default)]
+    pub dirty_regions: Vec<Rect>,
+}
+
+impl SnapshotDelta {
+    /// Compute the delta that turns `old` into `new`.
+    pub fn between(old: &TreeSnapshot, new: &TreeSnapshot) -> Self {
+        let mut removed: Vec<String> = old
+            .elements
+            .keys()
+            .filter(|id| !new.elements.contains_key(*id))
+            .cloned()
+            .collect();
+        removed.sort();
+
+        let mut upserted: Vec<ElementSnapshot> = new
+            .elements
+            .iter()
+            .filter(|(id, element)| old.elements.get(*id) != Some(*element))
+            .map(|(_, element)| element.clone())
+            .collect();
+        upserted.sort_by(|a, b| a.id.cmp(&b.id));
+
+        Self {
+            timestamp: new.timestamp,
+            removed,
+            upserted,
+            root_id: new.root_id.clone(),
+            focused_element: new.focused_element.clone(),
+            hovered_element: new.hovered_element.clone(),
+            window_size: new.window_size,
+            scale_factor: new.scale_factor,
+            dirty_regions: new.dirty_regions.clone(),
+        }
+    }
+
+    /// Apply the delta to the snapshot it was computed against.
+    pub fn apply(&self, old: &TreeSnapshot) -> TreeSnapshot {
+        let removed: HashSet<&str> = self.removed.iter().map(String::as_str).collect();
+        let mut elements = old.elements.clone();
+        elements.retain(|id, _| !removed.contains(id.as_str()));
+        for element in &self.upserted {
+            elements.insert(element.id.clone(), element.clone());
+        }
+
+        TreeSnapshot {
+            timestamp: self.timestamp,
+            elements,
+            root_id: self.root_id.clone(),
+            focused_element: self.focused_element.clone(),
+            hovered_element: self.hovered_element.clone(),
+            window_size: self.window_size,
+            scale_factor: self.scale_factor,
+            dirty_regions: self.dirty_regions.clone(),
+        }
+    }
+}
+
+/// Check if `bytes` start with the binary recording magic.
+pub fn is_binary_recording(bytes: &[u8]) -> bool {
+    bytes.starts_with(&MAGIC)
+}
+
+/// Check if the file at `path` is a binary recording.
+pub fn is_binary_recording_file(path: impl AsRef<Path>) -> Result<bool> {
+    use std::io::Read;
+
+    let mut magic = [0u8; 4];
+    let mut file = std::fs::File::open(path)?;
+    let read = file.read(&mut magic)?;
+    Ok(is_binary_recording(&magic[..read]))
+}
+
+/// Load a recording from either the binary or the JSON format.
+pub fn load_recording(path: impl AsRef<Path>) -> Result<RecordingExport> {
+    let path = path.as_ref();
+    if is_binary_recording_file(path)? {
+        RecordingReader::open(path)?.into_export()
+    } else {
+        let contents = std::fs::read(path)?;
+        Ok(serde_json::from_slice(&contents)?)
+    }
+}
+
+/// Convert a JSON recording to the binary format.
+pub fn json_to_binary(json: impl AsRef<Path>, binary: impl AsRef<Path>) -> Result<()> {
+    let contents = std::fs::read(json)?;
+    let export: RecordingExport = serde_json::from_slice(&contents)?;
+    RecordingWriter::write_export_to_file(&export, binary)
+}
+
+/// Convert a binary recording to JSON.
+pub fn binary_to_json(binary: impl AsRef<Path>, json: impl AsRef<Path>) -> Result<()> {
+    let export = RecordingReader::open(binary)?.into_export()?;
+    std::fs::write(json, serde_json::to_vec_pretty(&export)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::{
+        Modifiers, MouseButton, MouseEvent, Point, RecordedEvent, TimestampedEvent,
+    };
+    use std::io::Cursor;
+
+    fn snapshot(micros: u64, label: &str, count: usize) -> TreeSnapshot {
+        let mut snapshot = TreeSnapshot::new(Timestamp::from_micros(micros), (800, 600), 2.0);
+        snapshot.root_id = Some("root".to_string());
+        let mut root = ElementSnapshot::new(
+            "root".to_string(),
+            "div".to_string(),
+            Rect::new(0.0, 0.0, 800.0, 600.0),
+        );
+        for i in 0..count {
+            let id = format!("item-{}", i);
+            let mut item = ElementSnapshot::new(
+                id.clone(),
+                "text".to_string(),
+                Rect::new(0.0, i as f32 * 20.0, 800.0, 20.0),
+            );
+            item.parent = Some("root".to_string());
+            item.text_content = Some(format!("{} {}", label, i));
+            root.children.push(id.clone());
+            snapshot.elements.insert(id, item);
+        }
+        snapshot.elements.insert("root".to_string(), root);
+        snapshot
+    }
+
+    fn click(micros: u64) -> TimestampedEvent {
+        TimestampedEvent::new(
+            Timestamp::from_micros(micros),
+            RecordedEvent::Click(MouseEvent {
+                position: Point::new(micros as f32, 10.0),
+                button: MouseButton::Left,
+                modifiers: Modifiers::none(),
+                target_element: None,
+            }),
+        )
+    }
+
+    fn sample_export() -> RecordingExport {
+        let mut snapshots = Vec::new();
+        for i in 0..10u64 {
+            let mut s = snapshot(i * 1000, "row", 20 + i as usize);
+            if i % 3 == 0 {
+                s.focused_element = Some("item-1".to_string());
+            }
+            snapshots.push(s);
+        }
+        RecordingExport {
+            config: RecordingConfig::debug(),
+            events: (0..25u64).map(|i| click(i * 400)).collect(),
+            snapshots,
+            stats: SessionStats {
+                total_events: 25,
+                total_snapshots: 10,
+                ..Default::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_delta_roundtrip() {
+        let old = snapshot(0, "row", 5);
+        let mut new = snapshot(1000, "row", 4);
+        new.elements.get_mut("item-2").unwrap().bounds.y = 99.0;
+        new.hovered_element = Some("item-0".to_string());
+
+        let delta = SnapshotDelta::between(&old, &new);
+        assert_eq!(delta.removed, vec!["item-4".to_string()]);
+        // root (children changed) and item-2
+        assert_eq!(delta.upserted.len(), 2);
+        assert_eq!(delta.apply(&old), new);
+    }
+
+    #[test]
+    fn test_binary_roundtrip() {
+        let export = sample_export();
+        let mut writer = RecordingWriter::new(Cursor::new(Vec::new()), &export.config)
+            .unwrap()
+            .with_chunk_limits(10, 4);
+        for event in &export.events {
+            writer.push_event(event).unwrap();
+        }
+        for snapshot in &export.snapshots {
+            writer.push_snapshot(snapshot).unwrap();
+        }
+        let bytes = writer.finish(&export.stats).unwrap().into_inner();
+        assert!(is_binary_recording(&bytes));
+
+        let reader = RecordingReader::new(Cursor::new(bytes)).unwrap();
+        assert!(reader.is_complete());
+        let events = reader
+            .chunks()
+            .iter()
+            .filter(|c| c.kind == ChunkKind::Events)
+            .count();
+        assert_eq!(events, 3);
+        assert_eq!(reader.duration(), Timestamp::from_micros(9600));
+
+        let loaded = reader.into_export().unwrap();
+        assert_eq!(loaded.events.len(), export.events.len());
+        assert_eq!(loaded.snapshots, export.snapshots);
+        assert_eq!(loaded.stats.total_events, 25);
+    }
+
+    #[test]
+    fn test_lazy_snapshot_lookup() {
+        let export = sample_export();
+        let bytes = RecordingWriter::new(Cursor::new(Vec::new()), &export.config)
+            .unwrap()
+            .with_chunk_limits(10, 4)
+            .write_export(&export)
+            .unwrap()
+            .into_inner();
+
+        let mut reader = RecordingReader::new(Cursor::new(bytes)).unwrap();
+        assert_eq!(
+            reader.snapshot_at(Timestamp::from_micros(5500)).unwrap(),
+            Some(export.snapshots[5].clone())
+        );
+        assert_eq!(
+            reader.snapshot_at(Timestamp::from_micros(100_000)).unwrap(),
+            Some(export.snapshots[9].clone())
+        );
+
+        let events = reader
+            .events_in_range(Timestamp::from_micros(1000), Timestamp::from_micros(2000))
+            .unwrap();
+        assert_eq!(events.len(), 3);
+    }
+
+    #[test]
+    fn test_recovers_without_trailer() {
+        let export = sample_export();
+        let mut writer = RecordingWriter::new(Cursor::new(Vec::new()), &export.config)
+            .unwrap()
+            .with_chunk_limits(10, 4);
+        for event in &export.events {
+            writer.push_event(event).unwrap();
+        }
+        for snapshot in &export.snapshots {
+            writer.push_snapshot(snapshot).unwrap();
+        }
+        // Simulate a crash: take what was flushed plus half of a chunk
+        let mut bytes = writer.into_inner().into_inner();
+        let flushed = bytes.len();
+        bytes.extend_from_slice(&CHUNK_MAGIC);
+        bytes.extend_from_slice(&[2, 1, 0, 0]);
+
+        let reader = RecordingReader::new(Cursor::new(bytes)).unwrap();
+        assert!(!reader.is_complete());
+        assert!(reader.chunks().iter().all(|c| c.offset < flushed as u64));
+
+        let loaded = reader.into_export().unwrap();
+        assert_eq!(loaded.events.len(), 20);
+        assert_eq!(loaded.snapshots, export.snapshots[..8].to_vec());
+        assert_eq!(loaded.stats.total_events, 20);
+    }
+
+    #[test]
+    fn test_rejects_newer_reader_version() {
+        let mut bytes = RecordingWriter::new(Cursor::new(Vec::new()), &RecordingConfig::minimal())
+            .unwrap()
+            .finish(&SessionStats::default())
+            .unwrap()
+            .into_inner();
+        bytes[6..8].copy_from_slice(&(READER_VERSION + 1).to_le_bytes());
+
+        let err = RecordingReader::new(Cursor::new(bytes)).err().unwrap();
+        assert!(matches!(err, FormatError::UnsupportedVersion { .. }));
+        let err = RecordingReader::new(Cursor::new(b"{}".to_vec()))
+            .err()
+            .unwrap();
+        assert!(matches!(err, FormatError::NotARecording));
+    }
+
+    #[test]
+    fn test_rejects_corrupt_lengths() {
+        let export = sample_export();
+        let bytes = RecordingWriter::new(Cursor::new(Vec::new()), &export.config)
+            .unwrap()
+            .with_chunk_limits(10, 4)
+            .write_export(&export)
+            .unwrap()
+            .into_inner();
+        let header_len = u32::from_le_bytes(bytes[12..16].try_into().unwrap()) as usize;
+        let first_chunk = 16 + header_len;
+        let corrupt = |at: usize, value: u32| {
+            let mut bytes = bytes.clone();
+            bytes[at..at + 4].copy_from_slice(&value.to_le_bytes());
+            bytes
+        };
+
+        // Header longer than the limit, or than the file
+        for len in [u32::MAX, bytes.len() as u32] {
+            let err = RecordingReader::new(Cursor::new(corrupt(12, len)))
+                .err()
+                .unwrap();
+            assert!(matches!(err, FormatError::Corrupt(_)), "{}", err);
+        }
+
+        // Chunk payload longer than the file, or inflating past the limit
+        for (at, len) in [(12, u32::MAX), (12, bytes.len() as u32), (8, u32::MAX)] {
+            let mut reader =
+                RecordingReader::new(Cursor::new(corrupt(first_chunk + at, len))).unwrap();
+            let err = reader.read_events().err().unwrap();
+            assert!(matches!(err, FormatError::Corrupt(_)), "{}", err);
+        }
+
+        // Recovery stops at a chunk claiming more than the file holds
+        let mut bytes = corrupt(first_chunk + 12, u32::MAX);
+        bytes.truncate(bytes.len() - TRAILER_LEN as usize);
+        let reader = RecordingReader::new(Cursor::new(bytes)).unwrap();
+        assert!(!reader.is_complete());
+        assert!(reader.chunks().is_empty());
+    }
+
+    #[test]
+    fn test_json_conversion() {
+        let dir = std::env::temp_dir().join("junita_recorder_format_convert");
+        std::fs::create_dir_all(&dir).unwrap();
+        let json = dir.join("session.json");
+        let binary = dir.join("session.jrec");
+        let back = dir.join("back.json");
+
+        let export = sample_export();
+        std::fs::write(&json, serde_json::to_vec(&export).unwrap()).unwrap();
+        json_to_binary(&json, &binary).unwrap();
+        binary_to_json(&binary, &back).unwrap();
+
+        assert!(
+            std::fs::metadata(&binary).unwrap().len() < std::fs::metadata(&json).unwrap().len()
+        );
+        let from_binary = load_recording(&binary).unwrap();
+        let from_json = load_recording(&back).unwrap();
+        assert_eq!(from_binary.snapshots, export.snapshots);
+        assert_eq!(from_json.snapshots, export.snapshots);
+        assert_eq!(from_json.events.len(), export.events.len());
+    }
+}
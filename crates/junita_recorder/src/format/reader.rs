@@ -0,0 +1,397 @@
+//! Lazy reader for binary recordings.
+
+use super::{
+    ChunkInfo, ChunkKind, FileHeader, FormatError, RecordingIndex, Result, SnapshotRecord,
+    CHUNK_HEADER_LEN, CHUNK_MAGIC, MAGIC, MAX_CHUNK_LEN, MAX_HEADER_LEN, READER_VERSION,
+    TRAILER_LEN, TRAILER_MAGIC,
+};
+use crate::capture::{Timestamp, TimestampedEvent, TreeSnapshot};
+use crate::session::{RecordingExport, SessionStats};
+use flate2::read::DeflateDecoder;
+use flate2::Crc;
+use serde::de::DeserializeOwned;
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+/// Fixed part of a chunk header.
+struct ChunkHeader {
+    kind: ChunkKind,
+    codec: u8,
+    raw_len: u32,
+    data_len: u32,
+    crc: u32,
+    count: u32,
+    start: Timestamp,
+    end: Timestamp,
+}
+
+impl ChunkHeader {
+    fn parse(bytes: &[u8; CHUNK_HEADER_LEN as usize]) -> Option<Self> {
+        if bytes[0..4] != CHUNK_MAGIC {
+            return None;
+        }
+        let u32_at = |i: usize| u32::from_le_bytes(bytes[i..i + 4].try_into().unwrap());
+        let u64_at = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap());
+        Some(Self {
+            kind: ChunkKind::from_byte(bytes[4]),
+            codec: bytes[5],
+            raw_len: u32_at(8),
+            data_len: u32_at(12),
+            crc: u32_at(16),
+            count: u32_at(20),
+            start: Timestamp::from_micros(u64_at(24)),
+            end: Timestamp::from_micros(u64_at(32)),
+        })
+    }
+
+    /// Whether the payload is larger than any reader accepts.
+    fn exceeds_limits(&self) -> bool {
+        self.data_len as u64 > MAX_CHUNK_LEN || self.raw_len as u64 > MAX_CHUNK_LEN
+    }
+}
+
+/// Reader for the binary recording format.
+///
+/// Opening a file reads only the header and the chunk index; events and
+/// snapshots are decoded on demand, one chunk at a time.
+pub struct RecordingReader<R: Read + Seek> {
+    input: R,
+    header: FileHeader,
+    version: u16,
+    index: RecordingIndex,
+    complete: bool,
+    /// File size, which every length read from the file is checked against.
+    len: u64,
+}
+
+impl RecordingReader<BufReader<File>> {
+    /// Open a recording file.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read + Seek> RecordingReader<R> {
+    /// Read the header and index from `input`.
+    ///
+    /// If the file has no trailer it is recovered by scanning chunk headers.
+    pub fn new(mut input: R) -> Result<Self> {
+        let mut fixed = [0u8; 16];
+        input
+            .read_exact(&mut fixed)
+            .map_err(|_| FormatError::NotARecording)?;
+        if fixed[0..4] != MAGIC {
+            return Err(FormatError::NotARecording);
+        }
+        let version = u16::from_le_bytes([fixed[4], fixed[5]]);
+        let min_reader = u16::from_le_bytes([fixed[6], fixed[7]]);
+        if min_reader > READER_VERSION {
+            return Err(FormatError::UnsupportedVersion {
+                version,
+                min_reader,
+            });
+        }
+        let file_len = input.seek(SeekFrom::End(0))?;
+        let header_len = u32::from_le_bytes(fixed[12..16].try_into().unwrap()) as u64;
+        if header_len > MAX_HEADER_LEN || 16 + header_len > file_len {
+            return Err(FormatError::Corrupt(format!(
+                "header length {} exceeds the file",
+                header_len
+            )));
+        }
+        input.seek(SeekFrom::Start(16))?;
+        let mut header = vec![0u8; header_len as usize];
+        input.read_exact(&mut header)?;
+        let header: FileHeader = serde_json::from_slice(&header)?;
+        let data_start = 16 + header_len;
+
+        let (index, complete) = match Self::read_trailer(&mut input, data_start, file_len) {
+            Some(index) => (index, true),
+            None => (Self::scan(&mut input, data_start, file_len)?, false),
+        };
+
+        Ok(Self {
+            input,
+            header,
+            version,
+            index,
+            complete,
+            len: file_len,
+        })
+    }
+
+    /// File header.
+    pub fn header(&self) -> &FileHeader {
+        &self.header
+    }
+
+    /// Format version the file was written with.
+    pub fn version(&self) -> u16 {
+        self.version
+    }
+
+    /// Whether the file was finished (has an index and trailer).
+    ///
+    /// Unfinished files were recovered by scanning and may miss the records
+    /// that were still buffered when the writer stopped.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Seek index of all data chunks.
+    pub fn chunks(&self) -> &[ChunkInfo] {
+        &self.index.chunks
+    }
+
+    /// Session statistics, reconstructed from the index for unfinished files.
+    pub fn stats(&self) -> SessionStats {
+        if let Some(stats) = &self.index.stats {
+            return stats.clone();
+        }
+        let mut stats = SessionStats::default();
+        for chunk in &self.index.chunks {
+            match chunk.kind {
+                ChunkKind::Events => {
+                    stats.total_events += chunk.count as u64;
+                    stats.last_event_time = stats.last_event_time.max(Some(chunk.end));
+                }
+                ChunkKind::Snapshots => {
+                    stats.total_snapshots += chunk.count as u64;
+                    stats.last_snapshot_time = stats.last_snapshot_time.max(Some(chunk.end));
+                }
+                _ => {}
+            }
+        }
+        stats
+    }
+
+    /// Timestamp of the last event or snapshot.
+    pub fn duration(&self) -> Timestamp {
+        self.index
+            .chunks
+            .iter()
+            .map(|c| c.end)
+            .max()
+            .unwrap_or_default()
+    }
+
+    /// Decode all events.
+    pub fn read_events(&mut self) -> Result<Vec<TimestampedEvent>> {
+        self.events_in_range(Timestamp::zero(), Timestamp::from_micros(u64::MAX))
+    }
+
+    /// Decode the events between `start` and `end` (inclusive).
+    ///
+    /// Only chunks overlapping the range are read.
+    pub fn events_in_range(
+        &mut self,
+        start: Timestamp,
+        end: Timestamp,
+    ) -> Result<Vec<TimestampedEvent>> {
+        let offsets: Vec<u64> = self
+            .index
+            .chunks
+            .iter()
+            .filter(|c| c.kind == ChunkKind::Events && c.end >= start && c.start <= end)
+            .map(|c| c.offset)
+            .collect();
+
+        let mut events = Vec::new();
+        for offset in offsets {
+            let chunk: Vec<TimestampedEvent> = self.read_chunk(offset)?;
+            events.extend(
+                chunk
+                    .into_iter()
+                    .filter(|e| e.timestamp >= start && e.timestamp <= end),
+            );
+        }
+        Ok(events)
+    }
+
+    /// Decode all snapshots.
+    pub fn read_snapshots(&mut self) -> Result<Vec<TreeSnapshot>> {
+        let offsets: Vec<u64> = self
+            .index
+            .chunks
+            .iter()
+            .filter(|c| c.kind == ChunkKind::Snapshots)
+            .map(|c| c.offset)
+            .collect();
+
+        let mut snapshots = Vec::new();
+        for offset in offsets {
+            let records: Vec<SnapshotRecord> = self.read_chunk(offset)?;
+            let first = snapshots.len();
+            for record in records {
+                let previous = snapshots[first..].last();
+                let snapshot = resolve_record(record, previous)?;
+                snapshots.push(snapshot);
+            }
+        }
+        Ok(snapshots)
+    }
+
+    /// The last snapshot at or before `timestamp`.
+    ///
+    /// Reads a single chunk and applies deltas only up to `timestamp`.
+    pub fn snapshot_at(&mut self, timestamp: Timestamp) -> Result<Option<TreeSnapshot>> {
+        let Some(offset) = self
+            .index
+            .chunks
+            .iter()
+            .rev()
+            .find(|c| c.kind == ChunkKind::Snapshots && c.start <= timestamp)
+            .map(|c| c.offset)
+        else {
+            return Ok(None);
+        };
+
+        let records: Vec<SnapshotRecord> = self.read_chunk(offset)?;
+        let mut current: Option<TreeSnapshot> = None;
+        for record in records {
+            if record.timestamp() > timestamp {
+                break;
+            }
+            current = Some(resolve_record(record, current.as_ref())?);
+        }
+        Ok(current)
+    }
+
+    /// Decode everything into a [`RecordingExport`].
+    pub fn into_export(mut self) -> Result<RecordingExport> {
+        let events = self.read_events()?;
+        let snapshots = self.read_snapshots()?;
+        let stats = self.stats();
+        Ok(RecordingExport {
+            config: self.header.config,
+            events,
+            snapshots,
+            stats,
+        })
+    }
+
+    /// Decode the chunk at `offset`.
+    fn read_chunk<T: DeserializeOwned>(&mut self, offset: u64) -> Result<T> {
+        read_chunk_at(&mut self.input, offset, self.len).map(|(_, payload)| payload)
+    }
+
+    /// Load the index the trailer points at, if the file has one.
+    fn read_trailer(input: &mut R, data_start: u64, file_len: u64) -> Option<RecordingIndex> {
+        if file_len < data_start + TRAILER_LEN {
+            return None;
+        }
+        input.seek(SeekFrom::Start(file_len - TRAILER_LEN)).ok()?;
+        let mut trailer = [0u8; TRAILER_LEN as usize];
+        input.read_exact(&mut trailer).ok()?;
+        if trailer[0..4] != TRAILER_MAGIC {
+            return None;
+        }
+        let offset = u64::from_le_bytes(trailer[4..12].try_into().unwrap());
+        match read_chunk_at(input, offset, file_len) {
+            Ok((ChunkKind::Index, index)) => Some(index),
+            _ => None,
+        }
+    }
+
+    /// Rebuild the index from chunk headers, stopping at the first
+    /// truncated chunk.
+    fn scan(input: &mut R, data_start: u64, file_len: u64) -> Result<RecordingIndex> {
+        let mut index = RecordingIndex::default();
+        let mut offset = data_start;
+
+        while offset + CHUNK_HEADER_LEN <= file_len {
+            input.seek(SeekFrom::Start(offset))?;
+            let mut bytes = [0u8; CHUNK_HEADER_LEN as usize];
+            input.read_exact(&mut bytes)?;
+            let Some(header) = ChunkHeader::parse(&bytes) else {
+                break;
+            };
+            let next = offset + CHUNK_HEADER_LEN + header.data_len as u64;
+            if next > file_len || header.exceeds_limits() {
+                break;
+            }
+            if matches!(header.kind, ChunkKind::Events | ChunkKind::Snapshots) {
+                index.chunks.push(ChunkInfo {
+                    kind: header.kind,
+                    offset,
+                    start: header.start,
+                    end: header.end,
+                    count: header.count,
+                });
+            }
+            offset = next;
+        }
+
+        Ok(index)
+    }
+}
+
+/// Decode the chunk at `offset`, verifying its lengths against `file_len`
+/// and its checksum.
+fn read_chunk_at<R: Read + Seek, T: DeserializeOwned>(
+    input: &mut R,
+    offset: u64,
+    file_len: u64,
+) -> Result<(ChunkKind, T)> {
+    if offset.saturating_add(CHUNK_HEADER_LEN) > file_len {
+        return Err(FormatError::Corrupt(format!(
+            "chunk offset {} is past the end of the file",
+            offset
+        )));
+    }
+    input.seek(SeekFrom::Start(offset))?;
+    let mut bytes = [0u8; CHUNK_HEADER_LEN as usize];
+    input.read_exact(&mut bytes)?;
+    let header = ChunkHeader::parse(&bytes)
+        .ok_or_else(|| FormatError::Corrupt(format!("no chunk at offset {}", offset)))?;
+    if header.exceeds_limits() || offset + CHUNK_HEADER_LEN + header.data_len as u64 > file_len {
+        return Err(FormatError::Corrupt(format!(
+            "chunk at offset {} is larger than the file allows",
+            offset
+        )));
+    }
+
+    let mut data = vec![0u8; header.data_len as usize];
+    input.read_exact(&mut data)?;
+    let raw = match header.codec {
+        0 => data,
+        1 => {
+            // Stop one byte past the expected size so a bad length can't
+            // inflate without bound; the length check below rejects it
+            let mut raw = Vec::with_capacity(header.raw_len as usize);
+            DeflateDecoder::new(data.as_slice())
+                .take(header.raw_len as u64 + 1)
+                .read_to_end(&mut raw)?;
+            raw
+        }
+        codec => {
+            return Err(FormatError::Corrupt(format!(
+                "unknown codec {} at offset {}",
+                codec, offset
+            )))
+        }
+    };
+
+    let mut crc = Crc::new();
+    crc.update(&raw);
+    if raw.len() != header.raw_len as usize || crc.sum() != header.crc {
+        return Err(FormatError::Corrupt(format!(
+            "checksum mismatch at offset {}",
+            offset
+        )));
+    }
+    Ok((header.kind, serde_json::from_slice(&raw)?))
+}
+
+/// Turn a snapshot record into a full snapshot.
+///
+/// `previous` is the snapshot before it in the same chunk.
+fn resolve_record(record: SnapshotRecord, previous: Option<&TreeSnapshot>) -> Result<TreeSnapshot> {
+    match record {
+        SnapshotRecord::Full(snapshot) => Ok(snapshot),
+        SnapshotRecord::Delta(delta) => previous
+            .map(|previous| delta.apply(previous))
+            .ok_or_else(|| FormatError::Corrupt("snapshot chunk starts with a delta".to_string())),
+    }
+}
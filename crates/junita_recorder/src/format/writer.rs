@@ -0,0 +1,240 @@
+//! Streaming writer for binary recordings.
+
+use super::{
+    ChunkInfo, ChunkKind, FileHeader, RecordingIndex, Result, SnapshotDelta, SnapshotRecord,
+    CHUNK_MAGIC, FORMAT_VERSION, MAGIC, MIN_READER_VERSION, TRAILER_MAGIC,
+};
+use crate::capture::{Timestamp, TimestampedEvent, TreeSnapshot};
+use crate::session::{RecordingConfig, RecordingExport, SessionStats};
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use serde::Serialize;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Default number of events per chunk.
+pub const DEFAULT_EVENTS_PER_CHUNK: usize = 512;
+/// Default number of snapshots per chunk (one keyframe plus deltas).
+pub const DEFAULT_SNAPSHOTS_PER_CHUNK: usize = 32;
+
+/// Append-only writer for the binary recording format.
+///
+/// Events and snapshots are buffered and written as a chunk once a chunk
+/// fills up, so at most one chunk of each kind is lost if the process dies.
+/// Call [`finish`](Self::finish) to write the remaining data and the index.
+pub struct RecordingWriter<W: Write> {
+    out: W,
+    position: u64,
+    events: Vec<TimestampedEvent>,
+    snapshots: Vec<SnapshotRecord>,
+    last_snapshot: Option<TreeSnapshot>,
+    index: Vec<ChunkInfo>,
+    events_per_chunk: usize,
+    snapshots_per_chunk: usize,
+    compress: bool,
+}
+
+impl RecordingWriter<BufWriter<File>> {
+    /// Create a recording file at `path`.
+    pub fn create(path: impl AsRef<Path>, config: &RecordingConfig) -> Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), config)
+    }
+
+    /// Write a complete export to `path`.
+    pub fn write_export_to_file(export: &RecordingExport, path: impl AsRef<Path>) -> Result<()> {
+        Self::create(path, &export.config)?.write_export(export)?;
+        Ok(())
+    }
+}
+
+impl<W: Write> RecordingWriter<W> {
+    /// Start a recording on `out` and write the file header.
+    pub fn new(mut out: W, config: &RecordingConfig) -> Result<Self> {
+        let header = serde_json::to_vec(&FileHeader::new(config.clone()))?;
+        out.write_all(&MAGIC)?;
+        out.write_all(&FORMAT_VERSION.to_le_bytes())?;
+        out.write_all(&MIN_READER_VERSION.to_le_bytes())?;
+        out.write_all(&0u32.to_le_bytes())?;
+        out.write_all(&(header.len() as u32).to_le_bytes())?;
+        out.write_all(&header)?;
+        out.flush()?;
+
+        Ok(Self {
+            out,
+            position: 16 + header.len() as u64,
+            events: Vec::new(),
+            snapshots: Vec::new(),
+            last_snapshot: None,
+            index: Vec::new(),
+            events_per_chunk: DEFAULT_EVENTS_PER_CHUNK,
+            snapshots_per_chunk: DEFAULT_SNAPSHOTS_PER_CHUNK,
+            compress: true,
+        })
+    }
+
+    /// Set how many events and snapshots go into one chunk.
+    pub fn with_chunk_limits(mut self, events: usize, snapshots: usize) -> Self {
+        self.events_per_chunk = events.max(1);
+        self.snapshots_per_chunk = snapshots.max(1);
+        self
+    }
+
+    /// Enable or disable deflate compression of chunk payloads.
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
+    }
+
+    /// Chunks written so far.
+    pub fn chunks(&self) -> &[ChunkInfo] {
+        &self.index
+    }
+
+    /// Append an event.
+    pub fn push_event(&mut self, event: &TimestampedEvent) -> Result<()> {
+        self.events.push(event.clone());
+        if self.events.len() >= self.events_per_chunk {
+            self.flush_events()?;
+        }
+        Ok(())
+    }
+
+    /// Append a snapshot, delta-encoded against the previous one.
+    pub fn push_snapshot(&mut self, snapshot: &TreeSnapshot) -> Result<()> {
+        let record = match (&self.last_snapshot, self.snapshots.is_empty()) {
+            (Some(last), false) => SnapshotRecord::Delta(SnapshotDelta::between(last, snapshot)),
+            _ => SnapshotRecord::Full(snapshot.clone()),
+        };
+        self.snapshots.push(record);
+        self.last_snapshot = Some(snapshot.clone());
+        if self.snapshots.len() >= self.snapshots_per_chunk {
+            self.flush_snapshots()?;
+        }
+        Ok(())
+    }
+
+    /// Write all buffered records as chunks and flush the output.
+    pub fn flush(&mut self) -> Result<()> {
+        self.flush_events()?;
+        self.flush_snapshots()?;
+        self.out.flush()?;
+        Ok(())
+    }
+
+    /// Write an export's events and snapshots and finish the file.
+    pub fn write_export(mut self, export: &RecordingExport) -> Result<W> {
+        for event in &export.events {
+            self.push_event(event)?;
+        }
+        for snapshot in &export.snapshots {
+            self.push_snapshot(snapshot)?;
+        }
+        self.finish(&export.stats)
+    }
+
+    /// Write the remaining records, the index and the trailer.
+    pub fn finish(mut self, stats: &SessionStats) -> Result<W> {
+        self.flush()?;
+
+        let index = RecordingIndex {
+            chunks: self.index.clone(),
+            stats: Some(stats.clone()),
+        };
+        let index_offset = self.position;
+        let end = self.index.iter().map(|c| c.end).max().unwrap_or_default();
+        self.write_chunk(ChunkKind::Index, &index, 0, Timestamp::zero(), end)?;
+
+        self.out.write_all(&TRAILER_MAGIC)?;
+        self.out.write_all(&index_offset.to_le_bytes())?;
+        self.out.flush()?;
+        Ok(self.out)
+    }
+
+    /// Return the output without writing buffered records or the index.
+    ///
+    /// The result is what a crash at this point would leave behind.
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+
+    fn flush_events(&mut self) -> Result<()> {
+        if self.events.is_empty() {
+            return Ok(());
+        }
+        let events = std::mem::take(&mut self.events);
+        let start = events.first().map(|e| e.timestamp).unwrap_or_default();
+        let end = events.last().map(|e| e.timestamp).unwrap_or_default();
+        self.write_data_chunk(ChunkKind::Events, &events, events.len(), start, end)
+    }
+
+    fn flush_snapshots(&mut self) -> Result<()> {
+        if self.snapshots.is_empty() {
+            return Ok(());
+        }
+        let snapshots = std::mem::take(&mut self.snapshots);
+        let start = snapshots.first().map(|s| s.timestamp()).unwrap_or_default();
+        let end = snapshots.last().map(|s| s.timestamp()).unwrap_or_default();
+        self.write_data_chunk(
+            ChunkKind::Snapshots,
+            &snapshots,
+            snapshots.len(),
+            start,
+            end,
+        )
+    }
+
+    fn write_data_chunk<T: Serialize>(
+        &mut self,
+        kind: ChunkKind,
+        records: &T,
+        count: usize,
+        start: Timestamp,
+        end: Timestamp,
+    ) -> Result<()> {
+        let offset = self.position;
+        self.write_chunk(kind, records, count as u32, start, end)?;
+        self.out.flush()?;
+        self.index.push(ChunkInfo {
+            kind,
+            offset,
+            start,
+            end,
+            count: count as u32,
+        });
+        Ok(())
+    }
+
+    fn write_chunk<T: Serialize>(
+        &mut self,
+        kind: ChunkKind,
+        payload: &T,
+        count: u32,
+        start: Timestamp,
+        end: Timestamp,
+    ) -> Result<()> {
+        let raw = serde_json::to_vec(payload)?;
+        let mut crc = Crc::new();
+        crc.update(&raw);
+
+        let (codec, data) = if self.compress {
+            let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+            encoder.write_all(&raw)?;
+            (1u8, encoder.finish()?)
+        } else {
+            (0u8, raw.clone())
+        };
+
+        self.out.write_all(&CHUNK_MAGIC)?;
+        self.out.write_all(&[kind.to_byte(), codec, 0, 0])?;
+        self.out.write_all(&(raw.len() as u32).to_le_bytes())?;
+        self.out.write_all(&(data.len() as u32).to_le_bytes())?;
+        self.out.write_all(&crc.sum().to_le_bytes())?;
+        self.out.write_all(&count.to_le_bytes())?;
+        self.out.write_all(&start.as_micros().to_le_bytes())?;
+        self.out.write_all(&end.as_micros().to_le_bytes())?;
+        self.out.write_all(&data)?;
+        self.position += super::CHUNK_HEADER_LEN + data.len() as u64;
+        Ok(())
+    }
+}
@@ -4,6 +4,7 @@
 //! - Event recording for user interactions
 //! - Tree snapshot capture for debugging UI state
 //! - Session management with start/pause/stop lifecycle
+//! - A chunked binary file format that recordings can stream into
 //!
 //! # Quick Start
 //!
@@ -26,6 +27,7 @@
 //! ```
 
 pub mod capture;
+pub mod format;
 pub mod replay;
 pub mod server;
 pub mod session;
@@ -35,23 +37,31 @@ pub use capture::{
     ChangeCategory, CustomEvent, ElementDiff, ElementSnapshot, FocusChangeEvent, HoverEvent, Key,
     KeyEvent, Modifiers, MouseButton, MouseEvent, MouseMoveEvent, Point, PropertyChange,
     RecordedEvent, RecordingClock, Rect, ScrollEvent, TextInputEvent, Timestamp, TimestampedEvent,
-    TreeDiff, TreeSnapshot, VisualProps, WindowResizeEvent,
+    TreeDiff, TreePatch, TreeSnapshot, VisualProps, WindowResizeEvent,
+};
+pub use format::{
+    load_recording, FormatError, RecordingReader, RecordingWriter, SnapshotDelta, SnapshotRecord,
 };
 pub use replay::{
     EventSimulator, FrameUpdate, ReplayConfig, ReplayPlayer, ReplayState, SimulatedInput,
     VirtualClock,
 };
+#[cfg(unix)]
+pub use server::DebugClient;
 pub use server::{
-    start_local_server, start_local_server_named, ClientCommand, DebugServer, DebugServerConfig,
-    ServerHandle, ServerMessage,
+    start_local_server, start_local_server_named, ClientCommand, ComputedStyle, DebugOverlays,
+    DebugServer, DebugServerConfig, Edges, LayoutInfo, LiveTarget, ServerHandle, ServerMessage,
+    PROTOCOL_VERSION,
 };
 pub use session::{
     RecordingConfig, RecordingExport, RecordingSession, SessionState, SessionStats,
     SharedRecordingSession,
 };
 pub use testing::{
-    compare_frames, CapturedFrame, FrameSequence, HeadlessConfig, HeadlessContext,
-    RegressionResult, ScreenshotExporter, TestConfig, TestRunner,
+    compare_frames, generate_test, perceptual_compare, CapturedFrame, ElementExpectation,
+    FrameSequence, HeadlessConfig, HeadlessContext, IgnoreRegion, PerceptualDiff,
+    PerceptualOptions, RegressionResult, ReplayTarget, ScreenshotExporter, SnapshotMatcher,
+    TestConfig, TestGenConfig, TestRunner,
 };
 
 use parking_lot::RwLock;
@@ -4,6 +4,7 @@
 //! with controllable timing and speed.
 
 use super::{EventSimulator, SimulatedInput, VirtualClock};
+use crate::testing::ReplayTarget;
 use crate::{RecordingExport, Timestamp, TimestampedEvent, TreeSnapshot};
 
 /// Configuration for the replay player.
@@ -313,6 +314,27 @@ impl ReplayPlayer {
         update
     }
 
+    /// Replay the whole recording into `target` from the start, one frame
+    /// per step.
+    ///
+    /// Each step dispatches the events recorded during the frame, runs the
+    /// target's frame at the frame's end time and captures its tree. A target
+    /// that reads all time from `now` (see [`junita_core::clock`]) produces
+    /// the same snapshots on every run. Returns one snapshot per frame.
+    pub fn drive(&mut self, target: &mut impl ReplayTarget) -> Vec<TreeSnapshot> {
+        self.reset();
+        let mut frames = Vec::new();
+        while self.state != ReplayState::Finished {
+            let update = self.step();
+            for input in &update.events {
+                target.dispatch(input);
+            }
+            target.frame(self.position());
+            frames.push(target.snapshot());
+        }
+        frames
+    }
+
     /// Step backward by one frame.
     pub fn step_back(&mut self) -> FrameUpdate {
         let current = self.clock.position();
@@ -550,4 +572,48 @@ mod tests {
         let update = player.step();
         assert!(!update.events.is_empty());
     }
+
+    /// Records the time of every frame and the clicks seen before it.
+    #[derive(Default)]
+    struct FrameLog {
+        clicks: u32,
+        now: Timestamp,
+    }
+
+    impl ReplayTarget for FrameLog {
+        fn dispatch(&mut self, input: &SimulatedInput) {
+            if let SimulatedInput::Click { .. } = input {
+                self.clicks += 1;
+            }
+        }
+
+        fn frame(&mut self, now: Timestamp) {
+            self.now = now;
+        }
+
+        fn snapshot(&mut self) -> TreeSnapshot {
+            let mut snapshot = TreeSnapshot::new(self.now, (400, 300), 1.0);
+            snapshot.focused_element = Some(self.clicks.to_string());
+            snapshot
+        }
+    }
+
+    #[test]
+    fn test_drive_steps_frame_by_frame() {
+        let mut player = ReplayPlayer::new(create_test_export(), ReplayConfig::default());
+        let mut target = FrameLog::default();
+
+        let frames = player.drive(&mut target);
+        assert_eq!(player.state(), ReplayState::Finished);
+        // 200ms at ~16.7ms per frame
+        assert_eq!(frames.len(), 12);
+        assert_eq!(frames[0].timestamp.as_micros(), 16_667);
+        assert_eq!(frames[0].focused_element.as_deref(), Some("1"));
+        assert_eq!(frames.last().unwrap().timestamp.as_micros(), 200_000);
+        assert_eq!(target.clicks, 3);
+
+        // Driving again starts over and yields the same frames
+        let mut again = FrameLog::default();
+        assert_eq!(player.drive(&mut again), frames);
+    }
 }
@@ -0,0 +1,99 @@
+//! Client side of the debug protocol.
+//!
+//! Used by tools like junita_debugger to attach to a running application.
+
+use super::local::{ClientCommand, FrameReader, ServerMessage};
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// A connection to an application's debug server.
+pub struct DebugClient {
+    stream: UnixStream,
+    reader: FrameReader,
+    pending: VecDeque<ServerMessage>,
+}
+
+impl DebugClient {
+    /// Connect to the debug server listening at `path`.
+    pub fn connect(path: impl AsRef<Path>) -> io::Result<Self> {
+        let stream = UnixStream::connect(path)?;
+        stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+        Ok(Self {
+            stream,
+            reader: FrameReader::default(),
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// Send a command to the server.
+    pub fn send(&mut self, command: &ClientCommand) -> io::Result<()> {
+        self.stream.write_all(&command.to_bytes())
+    }
+
+    /// Receive the next message, waiting at most `timeout`.
+    ///
+    /// Returns `Ok(None)` if nothing arrived in time.
+    pub fn recv(&mut self, timeout: Duration) -> io::Result<Option<ServerMessage>> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(message) = self.pending.pop_front() {
+                return Ok(Some(message));
+            }
+
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Ok(None);
+            }
+            self.stream.set_read_timeout(Some(remaining))?;
+
+            let mut buf = [0u8; 4096];
+            match self.stream.read(&mut buf) {
+                Ok(0) => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::UnexpectedEof,
+                        "debug server closed the connection",
+                    ))
+                }
+                Ok(n) => {
+                    self.reader.push(&buf[..n]);
+                    while let Some(frame) = self.reader.next_frame() {
+                        match ServerMessage::from_bytes(&frame) {
+                            Some(message) => self.pending.push_back(message),
+                            None => tracing::debug!("Ignoring malformed server message"),
+                        }
+                    }
+                }
+                Err(ref e)
+                    if e.kind() == io::ErrorKind::WouldBlock
+                        || e.kind() == io::ErrorKind::TimedOut =>
+                {
+                    return Ok(None);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Receive messages until one matches `predicate`, discarding the rest.
+    pub fn recv_until<F>(
+        &mut self,
+        timeout: Duration,
+        mut predicate: F,
+    ) -> io::Result<Option<ServerMessage>>
+    where
+        F: FnMut(&ServerMessage) -> bool,
+    {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match self.recv(remaining)? {
+                Some(message) if predicate(&message) => return Ok(Some(message)),
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Live inspection of a running application.
+//!
+//! A [`LiveTarget`] is the application side of a live-attach session. The
+//! debug server forwards inspection and editing commands from connected
+//! clients to the target, and polls it for tree snapshots and picked elements.
+//!
+//! Element IDs are the same strings used in [`TreeSnapshot::elements`].
+
+use crate::{Rect, TreeSnapshot};
+use junita_core::reactive::ReactiveSnapshot;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Application hooks used by the debug server for live attach.
+///
+/// Implementations are called from the server's client threads and must
+/// hand work that touches the UI tree over to the UI thread themselves.
+pub trait LiveTarget: Send + Sync {
+    /// The latest element tree, or `None` if no frame has been captured yet.
+    fn snapshot(&self) -> Option<TreeSnapshot>;
+
+    /// Called when the first client subscribes and after the last one leaves.
+    ///
+    /// Targets can use this to skip snapshot capture while nobody is watching.
+    fn set_live(&self, _enabled: bool) {}
+
+    /// Draw a highlight over an element in the app window, or clear it.
+    fn highlight(&self, element_id: Option<&str>);
+
+    /// Enable or disable element picking by clicking in the app window.
+    ///
+    /// While picking is enabled the click is consumed by the target instead
+    /// of being dispatched to the app.
+    fn set_picking(&self, enabled: bool);
+
+    /// Take the element picked since the last call, if any.
+    fn take_picked(&self) -> Option<String>;
+
+    /// Override a single visual property (e.g. `background`, `opacity`) on an element.
+    ///
+    /// `value` uses the same syntax as the corresponding CSS declaration.
+    fn set_visual_prop(&self, element_id: &str, property: &str, value: &str) -> Result<(), String>;
+
+    /// Add or replace a CSS rule in the app's stylesheet.
+    fn set_css_rule(&self, selector: &str, declarations: &str) -> Result<(), String>;
+
+    /// Computed visual style of an element.
+    fn computed_style(&self, element_id: &str) -> Option<ComputedStyle>;
+
+    /// Layout information for an element.
+    fn layout_info(&self, element_id: &str) -> Option<LayoutInfo>;
+
+    /// Signals, derived values and effects of the app's reactive graph.
+    fn reactive_snapshot(&self) -> Option<ReactiveSnapshot> {
+        None
+    }
+
+    /// Set a signal from its string form (see `ReactiveGraph::set_from_str`).
+    fn set_signal(&self, _signal: u64, _value: &str) -> Result<(), String> {
+        Err("Signal editing is not supported by this target".to_string())
+    }
+
+    /// Enable or disable layout debugging overlays in the app window.
+    fn set_debug_overlays(&self, _overlays: DebugOverlays) -> Result<(), String> {
+        Err("Debug overlays are not supported by this target".to_string())
+    }
+}
+
+/// Layout debugging overlays drawn over the app window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DebugOverlays {
+    /// Margin, border, padding and content boxes of the highlighted element.
+    pub box_model: bool,
+    /// Main axis, lines and gaps of flex containers.
+    pub flex: bool,
+    /// Elements whose content overflows their bounds.
+    pub overflow: bool,
+    /// Effective clip rects.
+    pub clips: bool,
+    /// Elements on the glass and foreground render layers.
+    pub layers: bool,
+    /// Regions that receive pointer events.
+    pub hit_regions: bool,
+}
+
+/// Computed visual style of an element, as CSS-like property/value pairs.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ComputedStyle {
+    /// The element this style belongs to.
+    pub element_id: String,
+    /// Property values, keyed by CSS property name.
+    pub properties: BTreeMap<String, String>,
+    /// What produced each value (`builder`, a theme token, a stylesheet
+    /// rule, a live edit or `default`), keyed like `properties`.
+    #[serde(default)]
+    pub sources: BTreeMap<String, String>,
+}
+
+/// Box model and layout style of an element.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct LayoutInfo {
+    /// The element this layout belongs to.
+    pub element_id: String,
+    /// Border box in window coordinates (logical pixels).
+    pub bounds: Rect,
+    /// Size of the content box.
+    pub content_size: (f32, f32),
+    /// Resolved padding.
+    pub padding: Edges,
+    /// Resolved border widths.
+    pub border: Edges,
+    /// Resolved margins.
+    pub margin: Edges,
+    /// Layout style inputs (display, flex direction, sizes, ...), keyed by CSS property name.
+    pub style: BTreeMap<String, String>,
+}
+
+/// Resolved values for the four sides of a box.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Edges {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl Edges {
+    /// Create edges from individual sides.
+    pub fn new(top: f32, right: f32, bottom: f32, left: f32) -> Self {
+        Self {
+            top,
+            right,
+            bottom,
+            left,
+        }
+    }
+}
@@ -2,15 +2,30 @@
 //!
 //! Provides a cross-platform server that listens for debugger connections
 //! and streams recording data in real-time.
-
-use crate::{RecordingExport, SharedRecordingSession};
+//!
+//! Messages in both directions are JSON objects tagged with a `"type"` field,
+//! each prefixed with its length as a 4-byte little-endian integer. Clients
+//! may also send a bare JSON command without the prefix.
+
+use super::live::{ComputedStyle, DebugOverlays, LayoutInfo, LiveTarget};
+use crate::{RecordingExport, SharedRecordingSession, TreePatch, TreeSnapshot};
+use junita_core::profiler::{self, FrameProfile};
+use junita_core::reactive::ReactiveSnapshot;
+use junita_core::store::{self, StoreInfo};
 use parking_lot::Mutex;
 use serde::{Deserialize, Serialize};
 use std::io::{self, Read, Write};
 use std::path::PathBuf;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::Arc;
 use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Version of the wire protocol, sent in [`ServerMessage::Hello`].
+pub const PROTOCOL_VERSION: u32 = 6;
+
+/// Largest frame accepted from a peer; anything bigger is treated as garbage.
+const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
 
 /// Configuration for the debug server.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -110,6 +125,9 @@ pub struct DebugServer {
     config: DebugServerConfig,
     session: Arc<SharedRecordingSession>,
     clients: Arc<Mutex<Vec<ClientConnection>>>,
+    target: Option<Arc<dyn LiveTarget>>,
+    live_clients: Arc<AtomicUsize>,
+    profile_clients: Arc<ProfileClients>,
 }
 
 struct ClientConnection {
@@ -126,6 +144,28 @@ impl DebugServer {
             config,
             session,
             clients: Arc::new(Mutex::new(Vec::new())),
+            target: None,
+            live_clients: Arc::new(AtomicUsize::new(0)),
+            profile_clients: Arc::new(ProfileClients::default()),
+        }
+    }
+
+    /// Attach a live target so clients can inspect and edit the running app.
+    ///
+    /// Without a target, subscriptions fall back to the session's recorded
+    /// snapshots and live commands are answered with an error.
+    pub fn with_target(mut self, target: Arc<dyn LiveTarget>) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    fn client_context(&self) -> ClientContext {
+        ClientContext {
+            app_name: self.config.app_name.clone(),
+            session: self.session.clone(),
+            target: self.target.clone(),
+            live_clients: self.live_clients.clone(),
+            profile_clients: self.profile_clients.clone(),
         }
     }
 
@@ -166,7 +206,6 @@ impl DebugServer {
     #[cfg(unix)]
     fn run_server(&self, socket_path: &PathBuf, shutdown: Arc<AtomicBool>) -> io::Result<()> {
         use std::os::unix::net::UnixListener;
-        use std::time::Duration;
 
         let listener = UnixListener::bind(socket_path)?;
         listener.set_nonblocking(true)?;
@@ -183,9 +222,9 @@ impl DebugServer {
                     }
 
                     // Handle client in a new thread
-                    let session = self.session.clone();
+                    let ctx = self.client_context();
                     thread::spawn(move || {
-                        if let Err(e) = handle_client(stream, session) {
+                        if let Err(e) = handle_client(stream, ctx) {
                             tracing::debug!("Client disconnected: {}", e);
                         }
                     });
@@ -207,7 +246,6 @@ impl DebugServer {
     #[cfg(windows)]
     fn run_server(&self, _socket_path: &PathBuf, shutdown: Arc<AtomicBool>) -> io::Result<()> {
         use std::net::TcpListener;
-        use std::time::Duration;
 
         // Fallback to TCP on Windows (TODO: implement named pipes)
         let listener = TcpListener::bind("127.0.0.1:0")?;
@@ -225,9 +263,9 @@ impl DebugServer {
                         self.session.start();
                     }
 
-                    let session = self.session.clone();
+                    let ctx = self.client_context();
                     thread::spawn(move || {
-                        if let Err(e) = handle_client_tcp(stream, session) {
+                        if let Err(e) = handle_client_tcp(stream, ctx) {
                             tracing::debug!("Client disconnected: {}", e);
                         }
                     });
@@ -247,7 +285,8 @@ impl DebugServer {
 }
 
 /// Commands that clients can send to the server.
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum ClientCommand {
     /// Start recording.
     Start,
@@ -261,31 +300,62 @@ pub enum ClientCommand {
     Reset,
     /// Request a full export of recorded data.
     RequestExport,
+    /// Save the recording to a binary file at `path` on the app's side.
+    ///
+    /// Buffered records are written right away; later ones are streamed
+    /// into the file until the session stops or is reset.
+    SaveRecording { path: PathBuf },
     /// Request current session stats.
     RequestStats,
     /// Ping to keep connection alive.
     Ping,
+    /// Stream the live element tree: a full snapshot, then patches as it changes.
+    Subscribe,
+    /// Stop streaming the element tree.
+    Unsubscribe,
+    /// Highlight an element in the app window (`None` clears the highlight).
+    Highlight { element_id: Option<String> },
+    /// Toggle element picking; the next click in the app is reported back
+    /// as [`ServerMessage::ElementPicked`].
+    PickElement { enabled: bool },
+    /// Override a visual property on an element.
+    SetVisualProp {
+        element_id: String,
+        property: String,
+        value: String,
+    },
+    /// Add or replace a CSS rule in the app's stylesheet.
+    SetCssRule {
+        selector: String,
+        declarations: String,
+    },
+    /// Request the computed style of an element.
+    QueryComputedStyle { element_id: String },
+    /// Request layout information for an element.
+    QueryLayout { element_id: String },
+    /// Start or stop streaming frame profiles.
+    ///
+    /// The app's profiler records while at least one client is profiling.
+    Profile { enabled: bool },
+    /// Request the app's reactive graph and stores.
+    QueryReactive,
+    /// Set a signal from its string form; the signal must be editable.
+    SetSignal { signal: u64, value: String },
+    /// Enable or disable layout debugging overlays in the app window.
+    SetDebugOverlays { overlays: DebugOverlays },
 }
 
 impl ClientCommand {
     /// Parse a command from JSON bytes.
     pub fn from_bytes(data: &[u8]) -> Option<Self> {
-        // Skip length prefix if present
-        let json_data = if data.len() > 4 {
-            let len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
-            if data.len() >= 4 + len {
-                &data[4..4 + len]
-            } else {
-                data
-            }
-        } else {
-            data
-        };
+        let json_data = strip_length_prefix(data);
 
-        // Try to parse as JSON
-        let s = std::str::from_utf8(json_data).ok()?;
+        if let Ok(cmd) = serde_json::from_slice(json_data) {
+            return Some(cmd);
+        }
 
-        // Simple JSON parsing for command type
+        // Fall back to matching the command name for older clients
+        let s = std::str::from_utf8(json_data).ok()?;
         if s.contains("\"start\"") || s.contains("\"Start\"") {
             Some(ClientCommand::Start)
         } else if s.contains("\"pause\"") || s.contains("\"Pause\"") {
@@ -306,10 +376,16 @@ impl ClientCommand {
             None
         }
     }
+
+    /// Serialize the command to bytes (length prefix + JSON).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        encode_frame(&serde_json::to_vec(self).unwrap_or_default())
+    }
 }
 
 /// Message types sent to clients.
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
 pub enum ServerMessage {
     /// Initial handshake with session info.
     Hello {
@@ -333,68 +409,213 @@ pub enum ServerMessage {
     Error { message: String },
     /// Ping response (pong).
     Pong,
+    /// Full element tree, sent when a client subscribes.
+    TreeSnapshot(TreeSnapshot),
+    /// Incremental element tree update for subscribed clients.
+    TreePatch(TreePatch),
+    /// An element was picked by clicking in the app window.
+    ElementPicked { element_id: String },
+    /// Response to [`ClientCommand::QueryComputedStyle`].
+    ComputedStyle(ComputedStyle),
+    /// Response to [`ClientCommand::QueryLayout`].
+    Layout(LayoutInfo),
+    /// Frames finished since the last batch, for profiling clients.
+    FrameProfiles { frames: Vec<FrameProfile> },
+    /// Response to [`ClientCommand::QueryReactive`].
+    Reactive {
+        graph: ReactiveSnapshot,
+        stores: Vec<StoreInfo>,
+    },
 }
 
 impl ServerMessage {
-    /// Serialize message to bytes.
+    /// Serialize message to bytes (length prefix + JSON).
     pub fn to_bytes(&self) -> Vec<u8> {
-        // Simple format: 4-byte length prefix + JSON payload
-        let json = match self {
-            ServerMessage::Hello {
-                app_name,
-                protocol_version,
-            } => {
-                format!(
-                    r#"{{"type":"hello","app_name":"{}","protocol_version":{}}}"#,
-                    app_name, protocol_version
-                )
-            }
-            ServerMessage::Export(export) => {
-                format!(
-                    r#"{{"type":"export","total_events":{},"total_snapshots":{}}}"#,
-                    export.stats.total_events, export.stats.total_snapshots
-                )
-            }
-            ServerMessage::StateChange {
-                is_recording,
-                is_paused,
-            } => {
-                format!(
-                    r#"{{"type":"state_change","is_recording":{},"is_paused":{}}}"#,
-                    is_recording, is_paused
-                )
-            }
-            ServerMessage::Stats {
-                total_events,
-                total_snapshots,
-                events_dropped,
-                snapshots_dropped,
-            } => {
-                format!(
-                    r#"{{"type":"stats","total_events":{},"total_snapshots":{},"events_dropped":{},"snapshots_dropped":{}}}"#,
-                    total_events, total_snapshots, events_dropped, snapshots_dropped
-                )
-            }
-            ServerMessage::Ack { command } => {
-                format!(r#"{{"type":"ack","command":"{}"}}"#, command)
+        let json = serde_json::to_vec(self).unwrap_or_else(|e| {
+            let error = ServerMessage::Error {
+                message: format!("Failed to encode message: {}", e),
+            };
+            serde_json::to_vec(&error).unwrap_or_default()
+        });
+        encode_frame(&json)
+    }
+
+    /// Parse a message from bytes, with or without the length prefix.
+    pub fn from_bytes(data: &[u8]) -> Option<Self> {
+        serde_json::from_slice(strip_length_prefix(data)).ok()
+    }
+
+    fn ack(command: &str) -> Self {
+        ServerMessage::Ack {
+            command: command.to_string(),
+        }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        ServerMessage::Error {
+            message: message.into(),
+        }
+    }
+}
+
+/// Prefix a JSON payload with its length.
+fn encode_frame(json: &[u8]) -> Vec<u8> {
+    let len = json.len() as u32;
+    let mut result = Vec::with_capacity(4 + json.len());
+    result.extend_from_slice(&len.to_le_bytes());
+    result.extend_from_slice(json);
+    result
+}
+
+/// Skip the length prefix if present.
+fn strip_length_prefix(data: &[u8]) -> &[u8] {
+    if data.len() > 4 && data[0] != b'{' {
+        let len = u32::from_le_bytes([data[0], data[1], data[2], data[3]]) as usize;
+        if data.len() >= 4 + len {
+            return &data[4..4 + len];
+        }
+    }
+    data
+}
+
+/// Splits a byte stream into messages.
+///
+/// Reads may end in the middle of a message or contain several, so bytes are
+/// buffered until a whole length-prefixed frame is available. Data starting
+/// with `{` is an unprefixed JSON command and is taken as-is.
+#[derive(Default)]
+pub(crate) struct FrameReader {
+    buf: Vec<u8>,
+}
+
+impl FrameReader {
+    pub(crate) fn push(&mut self, data: &[u8]) {
+        self.buf.extend_from_slice(data);
+    }
+
+    pub(crate) fn next_frame(&mut self) -> Option<Vec<u8>> {
+        if self.buf.first() == Some(&b'{') {
+            return Some(std::mem::take(&mut self.buf));
+        }
+        if self.buf.len() < 4 {
+            return None;
+        }
+
+        let len = u32::from_le_bytes([self.buf[0], self.buf[1], self.buf[2], self.buf[3]]) as usize;
+        if len > MAX_FRAME_LEN {
+            tracing::warn!("Dropping oversized debug frame ({} bytes)", len);
+            self.buf.clear();
+            return None;
+        }
+        if self.buf.len() < 4 + len {
+            return None;
+        }
+
+        let frame = self.buf[4..4 + len].to_vec();
+        self.buf.drain(..4 + len);
+        Some(frame)
+    }
+}
+
+/// Everything a client thread needs from the server.
+#[derive(Clone)]
+struct ClientContext {
+    app_name: String,
+    session: Arc<SharedRecordingSession>,
+    target: Option<Arc<dyn LiveTarget>>,
+    live_clients: Arc<AtomicUsize>,
+    profile_clients: Arc<ProfileClients>,
+}
+
+/// Clients streaming frame profiles.
+#[derive(Default)]
+struct ProfileClients {
+    count: AtomicUsize,
+    /// Whether the profiler was already on before the first client, e.g. via `JUNITA_PROFILE`
+    was_enabled: AtomicBool,
+}
+
+impl ClientContext {
+    /// Latest tree: from the live target if attached, otherwise the last recorded snapshot.
+    fn current_snapshot(&self) -> Option<TreeSnapshot> {
+        self.target
+            .as_ref()
+            .and_then(|t| t.snapshot())
+            .or_else(|| self.session.with_session(|s| s.last_snapshot().cloned()))
+    }
+
+    fn subscribe(&self, client: &mut ClientState) {
+        if client.subscribed {
+            return;
+        }
+        client.subscribed = true;
+        if self.live_clients.fetch_add(1, Ordering::SeqCst) == 0 {
+            if let Some(target) = &self.target {
+                target.set_live(true);
             }
-            ServerMessage::Error { message } => {
-                format!(r#"{{"type":"error","message":"{}"}}"#, message)
+        }
+    }
+
+    fn unsubscribe(&self, client: &mut ClientState) {
+        if !client.subscribed {
+            return;
+        }
+        client.subscribed = false;
+        client.last_snapshot = None;
+        if self.live_clients.fetch_sub(1, Ordering::SeqCst) == 1 {
+            if let Some(target) = &self.target {
+                target.set_live(false);
+                target.highlight(None);
             }
-            ServerMessage::Pong => r#"{"type":"pong"}"#.to_string(),
-        };
+        }
+    }
 
-        let bytes = json.as_bytes();
-        let len = bytes.len() as u32;
-        let mut result = Vec::with_capacity(4 + bytes.len());
-        result.extend_from_slice(&len.to_le_bytes());
-        result.extend_from_slice(bytes);
-        result
+    fn start_profiling(&self, client: &mut ClientState) {
+        if client.profiling {
+            return;
+        }
+        client.profiling = true;
+        let clients = &self.profile_clients;
+        if clients.count.fetch_add(1, Ordering::SeqCst) == 0 {
+            clients
+                .was_enabled
+                .store(profiler::is_enabled(), Ordering::SeqCst);
+            profiler::set_enabled(true);
+        }
     }
+
+    fn stop_profiling(&self, client: &mut ClientState) {
+        if !client.profiling {
+            return;
+        }
+        client.profiling = false;
+        let clients = &self.profile_clients;
+        if clients.count.fetch_sub(1, Ordering::SeqCst) == 1 {
+            profiler::set_enabled(clients.was_enabled.load(Ordering::SeqCst));
+        }
+    }
+}
+
+/// Per-connection state.
+struct ClientState {
+    last_recording: bool,
+    last_paused: bool,
+    subscribed: bool,
+    picking: bool,
+    /// The tree as the client last saw it; patches are computed against this.
+    last_snapshot: Option<TreeSnapshot>,
+    profiling: bool,
+    /// First frame number not yet sent to the client.
+    next_profile_frame: u64,
 }
 
 /// Process a client command and return a response message.
-fn handle_command(cmd: ClientCommand, session: &Arc<SharedRecordingSession>) -> ServerMessage {
+fn handle_command(
+    cmd: ClientCommand,
+    ctx: &ClientContext,
+    client: &mut ClientState,
+) -> ServerMessage {
+    let session = &ctx.session;
     match cmd {
         ClientCommand::Start | ClientCommand::Resume => {
             session.start();
@@ -424,6 +645,14 @@ fn handle_command(cmd: ClientCommand, session: &Arc<SharedRecordingSession>) ->
             let export = session.export();
             ServerMessage::Export(export)
         }
+        ClientCommand::SaveRecording { path } => match session.stream_to(&path) {
+            Ok(()) => ServerMessage::ack("save_recording"),
+            Err(e) => ServerMessage::error(format!(
+                "Failed to save recording to {}: {}",
+                path.display(),
+                e
+            )),
+        },
         ClientCommand::RequestStats => {
             let stats = session.stats();
             ServerMessage::Stats {
@@ -434,121 +663,237 @@ fn handle_command(cmd: ClientCommand, session: &Arc<SharedRecordingSession>) ->
             }
         }
         ClientCommand::Ping => ServerMessage::Pong,
+        ClientCommand::Subscribe => {
+            ctx.subscribe(client);
+            match ctx.current_snapshot() {
+                Some(snapshot) => {
+                    client.last_snapshot = Some(snapshot.clone());
+                    ServerMessage::TreeSnapshot(snapshot)
+                }
+                None => ServerMessage::ack("subscribe"),
+            }
+        }
+        ClientCommand::Unsubscribe => {
+            ctx.unsubscribe(client);
+            ServerMessage::ack("unsubscribe")
+        }
+        ClientCommand::Profile { enabled } => {
+            if enabled {
+                ctx.start_profiling(client);
+            } else {
+                ctx.stop_profiling(client);
+            }
+            ServerMessage::ack("profile")
+        }
+        cmd => {
+            let Some(target) = &ctx.target else {
+                return ServerMessage::error("No live target attached");
+            };
+            handle_live_command(cmd, target.as_ref(), client)
+        }
     }
 }
 
-#[cfg(unix)]
-fn handle_client(
-    mut stream: std::os::unix::net::UnixStream,
-    session: Arc<SharedRecordingSession>,
+/// Process a command that needs a live target.
+fn handle_live_command(
+    cmd: ClientCommand,
+    target: &dyn LiveTarget,
+    client: &mut ClientState,
+) -> ServerMessage {
+    let edit_result = |result: Result<(), String>, command: &str| match result {
+        Ok(()) => ServerMessage::ack(command),
+        Err(message) => ServerMessage::error(message),
+    };
+
+    match cmd {
+        ClientCommand::Highlight { element_id } => {
+            target.highlight(element_id.as_deref());
+            ServerMessage::ack("highlight")
+        }
+        ClientCommand::PickElement { enabled } => {
+            client.picking = enabled;
+            target.set_picking(enabled);
+            ServerMessage::ack("pick_element")
+        }
+        ClientCommand::SetVisualProp {
+            element_id,
+            property,
+            value,
+        } => edit_result(
+            target.set_visual_prop(&element_id, &property, &value),
+            "set_visual_prop",
+        ),
+        ClientCommand::SetCssRule {
+            selector,
+            declarations,
+        } => edit_result(
+            target.set_css_rule(&selector, &declarations),
+            "set_css_rule",
+        ),
+        ClientCommand::QueryComputedStyle { element_id } => {
+            match target.computed_style(&element_id) {
+                Some(style) => ServerMessage::ComputedStyle(style),
+                None => ServerMessage::error(format!("Unknown element: {}", element_id)),
+            }
+        }
+        ClientCommand::QueryLayout { element_id } => match target.layout_info(&element_id) {
+            Some(layout) => ServerMessage::Layout(layout),
+            None => ServerMessage::error(format!("Unknown element: {}", element_id)),
+        },
+        ClientCommand::QueryReactive => match target.reactive_snapshot() {
+            Some(graph) => ServerMessage::Reactive {
+                graph,
+                stores: store::inspect_stores(),
+            },
+            None => ServerMessage::error("Target has no reactive graph"),
+        },
+        ClientCommand::SetSignal { signal, value } => {
+            edit_result(target.set_signal(signal, &value), "set_signal")
+        }
+        ClientCommand::SetDebugOverlays { overlays } => {
+            edit_result(target.set_debug_overlays(overlays), "set_debug_overlays")
+        }
+        other => ServerMessage::error(format!("Unexpected command: {:?}", other)),
+    }
+}
+
+/// Send messages the client hasn't asked for: state changes, tree patches,
+/// picks and frame profiles.
+fn push_updates<S: Write>(
+    stream: &mut S,
+    ctx: &ClientContext,
+    client: &mut ClientState,
 ) -> io::Result<()> {
-    use std::time::Duration;
+    // Send state change if state has changed
+    let is_recording = ctx.session.is_recording();
+    let is_paused = ctx.session.is_paused();
+    if is_recording != client.last_recording || is_paused != client.last_paused {
+        let state = ServerMessage::StateChange {
+            is_recording,
+            is_paused,
+        };
+        stream.write_all(&state.to_bytes())?;
+        client.last_recording = is_recording;
+        client.last_paused = is_paused;
+    }
 
-    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
-    stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+    if client.subscribed {
+        if let Some(snapshot) = ctx.current_snapshot() {
+            let message = match &client.last_snapshot {
+                Some(last) => {
+                    let patch = TreePatch::between(last, &snapshot);
+                    (!patch.is_empty()).then_some(ServerMessage::TreePatch(patch))
+                }
+                None => Some(ServerMessage::TreeSnapshot(snapshot.clone())),
+            };
+            if let Some(message) = message {
+                stream.write_all(&message.to_bytes())?;
+            }
+            client.last_snapshot = Some(snapshot);
+        }
+    }
 
+    if client.picking {
+        if let Some(element_id) = ctx.target.as_ref().and_then(|t| t.take_picked()) {
+            client.picking = false;
+            stream.write_all(&ServerMessage::ElementPicked { element_id }.to_bytes())?;
+        }
+    }
+
+    if client.profiling {
+        let frames = profiler::frames_since(client.next_profile_frame);
+        if let Some(last) = frames.last() {
+            client.next_profile_frame = last.frame + 1;
+            stream.write_all(&ServerMessage::FrameProfiles { frames }.to_bytes())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Main loop for a connected client: respond to commands and push updates.
+fn serve_client<S: Read + Write>(stream: &mut S, ctx: &ClientContext) -> io::Result<()> {
+    let mut client = ClientState {
+        last_recording: ctx.session.is_recording(),
+        last_paused: ctx.session.is_paused(),
+        subscribed: false,
+        picking: false,
+        last_snapshot: None,
+        profiling: false,
+        next_profile_frame: 0,
+    };
+
+    let result = serve_client_loop(stream, ctx, &mut client);
+
+    // Release the subscription, profiler and any pending pick if the client went away
+    ctx.unsubscribe(&mut client);
+    ctx.stop_profiling(&mut client);
+    if client.picking {
+        if let Some(target) = &ctx.target {
+            target.set_picking(false);
+        }
+    }
+
+    result
+}
+
+fn serve_client_loop<S: Read + Write>(
+    stream: &mut S,
+    ctx: &ClientContext,
+    client: &mut ClientState,
+) -> io::Result<()> {
     // Send hello message
     let hello = ServerMessage::Hello {
-        app_name: "junita_app".to_string(),
-        protocol_version: 1,
+        app_name: ctx.app_name.clone(),
+        protocol_version: PROTOCOL_VERSION,
     };
     stream.write_all(&hello.to_bytes())?;
 
-    // Track last state to avoid sending redundant updates
-    let mut last_recording = session.is_recording();
-    let mut last_paused = session.is_paused();
-
-    // Main loop: respond to commands and send state updates
-    let mut buf = [0u8; 1024];
+    let mut reader = FrameReader::default();
+    let mut buf = [0u8; 4096];
     loop {
-        // Check for incoming commands
+        // Check for incoming commands (the read timeout paces this loop)
         match stream.read(&mut buf) {
             Ok(0) => {
                 // Client disconnected
                 return Ok(());
             }
             Ok(n) => {
-                // Parse and handle command
-                if let Some(cmd) = ClientCommand::from_bytes(&buf[..n]) {
-                    let response = handle_command(cmd, &session);
-                    stream.write_all(&response.to_bytes())?;
+                reader.push(&buf[..n]);
+                while let Some(frame) = reader.next_frame() {
+                    if let Some(cmd) = ClientCommand::from_bytes(&frame) {
+                        let response = handle_command(cmd, ctx, client);
+                        stream.write_all(&response.to_bytes())?;
+                    }
                 }
             }
-            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {
-                // Timeout, continue
+            Err(ref e)
+                if e.kind() == io::ErrorKind::WouldBlock || e.kind() == io::ErrorKind::TimedOut => {
             }
             Err(e) => {
                 return Err(e);
             }
         }
 
-        // Send state change if state has changed
-        let is_recording = session.is_recording();
-        let is_paused = session.is_paused();
-        if is_recording != last_recording || is_paused != last_paused {
-            let state = ServerMessage::StateChange {
-                is_recording,
-                is_paused,
-            };
-            stream.write_all(&state.to_bytes())?;
-            last_recording = is_recording;
-            last_paused = is_paused;
-        }
-
-        thread::sleep(Duration::from_millis(100));
+        push_updates(stream, ctx, client)?;
     }
 }
 
-#[cfg(windows)]
-fn handle_client_tcp(
-    mut stream: std::net::TcpStream,
-    session: Arc<SharedRecordingSession>,
-) -> io::Result<()> {
-    use std::time::Duration;
+/// How long a client read waits before the server pushes pending updates.
+const CLIENT_POLL_INTERVAL: Duration = Duration::from_millis(50);
 
-    stream.set_read_timeout(Some(Duration::from_secs(1)))?;
+#[cfg(unix)]
+fn handle_client(mut stream: std::os::unix::net::UnixStream, ctx: ClientContext) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_POLL_INTERVAL))?;
     stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+    serve_client(&mut stream, &ctx)
+}
 
-    let hello = ServerMessage::Hello {
-        app_name: "junita_app".to_string(),
-        protocol_version: 1,
-    };
-    stream.write_all(&hello.to_bytes())?;
-
-    // Track last state to avoid sending redundant updates
-    let mut last_recording = session.is_recording();
-    let mut last_paused = session.is_paused();
-
-    let mut buf = [0u8; 1024];
-    loop {
-        match stream.read(&mut buf) {
-            Ok(0) => return Ok(()),
-            Ok(n) => {
-                // Parse and handle command
-                if let Some(cmd) = ClientCommand::from_bytes(&buf[..n]) {
-                    let response = handle_command(cmd, &session);
-                    stream.write_all(&response.to_bytes())?;
-                }
-            }
-            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
-            Err(e) => return Err(e),
-        }
-
-        // Send state change if state has changed
-        let is_recording = session.is_recording();
-        let is_paused = session.is_paused();
-        if is_recording != last_recording || is_paused != last_paused {
-            let state = ServerMessage::StateChange {
-                is_recording,
-                is_paused,
-            };
-            stream.write_all(&state.to_bytes())?;
-            last_recording = is_recording;
-            last_paused = is_paused;
-        }
-
-        thread::sleep(Duration::from_millis(100));
-    }
+#[cfg(windows)]
+fn handle_client_tcp(mut stream: std::net::TcpStream, ctx: ClientContext) -> io::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_POLL_INTERVAL))?;
+    stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+    serve_client(&mut stream, &ctx)
 }
 
 /// Start a debug server with the default configuration.
@@ -648,4 +993,480 @@ mod tests {
             Some(ClientCommand::Start)
         ));
     }
+
+    #[test]
+    fn test_live_command_round_trip() {
+        let cmd = ClientCommand::SetVisualProp {
+            element_id: "card".to_string(),
+            property: "background".to_string(),
+            value: "#ff0000".to_string(),
+        };
+        assert_eq!(ClientCommand::from_bytes(&cmd.to_bytes()), Some(cmd));
+
+        let raw = br#"{"type":"highlight","element_id":null}"#;
+        assert_eq!(
+            ClientCommand::from_bytes(raw),
+            Some(ClientCommand::Highlight { element_id: None })
+        );
+
+        let msg = ServerMessage::ElementPicked {
+            element_id: "card".to_string(),
+        };
+        assert!(matches!(
+            ServerMessage::from_bytes(&msg.to_bytes()),
+            Some(ServerMessage::ElementPicked { element_id }) if element_id == "card"
+        ));
+    }
+
+    #[test]
+    fn test_frame_reader_splits_and_joins() {
+        let a = ClientCommand::Ping.to_bytes();
+        let b = ClientCommand::Subscribe.to_bytes();
+        let mut stream = a.clone();
+        stream.extend_from_slice(&b);
+
+        let mut reader = FrameReader::default();
+        reader.push(&stream[..3]);
+        assert!(reader.next_frame().is_none());
+        reader.push(&stream[3..]);
+        assert_eq!(
+            ClientCommand::from_bytes(&reader.next_frame().unwrap()),
+            Some(ClientCommand::Ping)
+        );
+        assert_eq!(
+            ClientCommand::from_bytes(&reader.next_frame().unwrap()),
+            Some(ClientCommand::Subscribe)
+        );
+        assert!(reader.next_frame().is_none());
+    }
+
+    #[cfg(unix)]
+    mod live {
+        use super::*;
+        use crate::server::{DebugClient, LiveTarget};
+        use crate::{ElementSnapshot, RecordingConfig, Rect, Timestamp};
+        use junita_core::reactive::{ReactiveGraph, SignalId};
+        use std::collections::BTreeMap;
+
+        /// In-memory app: one element whose opacity can be edited.
+        #[derive(Default)]
+        struct StubTarget {
+            opacity: parking_lot::Mutex<f32>,
+            highlighted: parking_lot::Mutex<Option<String>>,
+            picked: parking_lot::Mutex<Option<String>>,
+            live: AtomicBool,
+            reactive: parking_lot::Mutex<ReactiveGraph>,
+            overlays: parking_lot::Mutex<DebugOverlays>,
+        }
+
+        impl LiveTarget for StubTarget {
+            fn snapshot(&self) -> Option<TreeSnapshot> {
+                let mut snapshot = TreeSnapshot::new(Timestamp::zero(), (100, 100), 1.0);
+                let mut element = ElementSnapshot::new(
+                    "card".to_string(),
+                    "Div".to_string(),
+                    Rect::new(0.0, 0.0, 50.0, 50.0),
+                );
+                element.visual_props = Some(crate::VisualProps {
+                    opacity: Some(*self.opacity.lock()),
+                    ..Default::default()
+                });
+                snapshot.root_id = Some("card".to_string());
+                snapshot.elements.insert("card".to_string(), element);
+                Some(snapshot)
+            }
+
+            fn set_live(&self, enabled: bool) {
+                self.live.store(enabled, Ordering::SeqCst);
+            }
+
+            fn highlight(&self, element_id: Option<&str>) {
+                *self.highlighted.lock() = element_id.map(str::to_string);
+            }
+
+            fn set_picking(&self, enabled: bool) {
+                // Simulate the user clicking the card right away
+                if enabled {
+                    *self.picked.lock() = Some("card".to_string());
+                }
+            }
+
+            fn take_picked(&self) -> Option<String> {
+                self.picked.lock().take()
+            }
+
+            fn set_visual_prop(
+                &self,
+                element_id: &str,
+                property: &str,
+                value: &str,
+            ) -> Result<(), String> {
+                if element_id != "card" || property != "opacity" {
+                    return Err(format!("Cannot set {} on {}", property, element_id));
+                }
+                *self.opacity.lock() = value.parse().map_err(|_| "Bad opacity".to_string())?;
+                Ok(())
+            }
+
+            fn set_css_rule(&self, _selector: &str, _declarations: &str) -> Result<(), String> {
+                Ok(())
+            }
+
+            fn computed_style(&self, element_id: &str) -> Option<ComputedStyle> {
+                (element_id == "card").then(|| ComputedStyle {
+                    element_id: element_id.to_string(),
+                    properties: BTreeMap::from([(
+                        "opacity".to_string(),
+                        self.opacity.lock().to_string(),
+                    )]),
+                    sources: BTreeMap::from([("opacity".to_string(), "live edit".to_string())]),
+                })
+            }
+
+            fn layout_info(&self, element_id: &str) -> Option<LayoutInfo> {
+                (element_id == "card").then(|| LayoutInfo {
+                    element_id: element_id.to_string(),
+                    bounds: Rect::new(0.0, 0.0, 50.0, 50.0),
+                    ..Default::default()
+                })
+            }
+
+            fn reactive_snapshot(&self) -> Option<ReactiveSnapshot> {
+                Some(self.reactive.lock().snapshot())
+            }
+
+            fn set_signal(&self, signal: u64, value: &str) -> Result<(), String> {
+                self.reactive
+                    .lock()
+                    .set_from_str(SignalId::from_raw(signal), value)
+            }
+
+            fn set_debug_overlays(&self, overlays: DebugOverlays) -> Result<(), String> {
+                *self.overlays.lock() = overlays;
+                Ok(())
+            }
+        }
+
+        fn start_server(
+            name: &str,
+            target: Option<Arc<StubTarget>>,
+        ) -> (ServerHandle, DebugClient) {
+            let session = Arc::new(SharedRecordingSession::new(RecordingConfig::default()));
+            let mut config = DebugServerConfig::new(name);
+            config.socket_path = Some(std::env::temp_dir().join(format!(
+                "junita-{}-{}.sock",
+                name,
+                std::process::id()
+            )));
+            let mut server = DebugServer::new(config, session);
+            if let Some(target) = target {
+                server = server.with_target(target);
+            }
+            let handle = server.start().unwrap();
+
+            let mut attempts = 0;
+            let client = loop {
+                match DebugClient::connect(handle.socket_path()) {
+                    Ok(client) => break client,
+                    Err(_) if attempts < 50 => {
+                        attempts += 1;
+                        thread::sleep(Duration::from_millis(20));
+                    }
+                    Err(e) => panic!("Could not connect to debug server: {}", e),
+                }
+            };
+            (handle, client)
+        }
+
+        const TIMEOUT: Duration = Duration::from_secs(5);
+
+        #[test]
+        fn test_live_attach_end_to_end() {
+            let target = Arc::new(StubTarget::default());
+            *target.opacity.lock() = 1.0;
+            let (_handle, mut client) = start_server("live_e2e", Some(target.clone()));
+
+            let hello = client.recv(TIMEOUT).unwrap();
+            assert!(matches!(
+                hello,
+                Some(ServerMessage::Hello { ref app_name, protocol_version })
+                    if app_name == "live_e2e" && protocol_version == PROTOCOL_VERSION
+            ));
+
+            // Subscribing sends the full tree
+            client.send(&ClientCommand::Subscribe).unwrap();
+            let tree = client
+                .recv_until(TIMEOUT, |m| matches!(m, ServerMessage::TreeSnapshot(_)))
+                .unwrap();
+            let Some(ServerMessage::TreeSnapshot(mut tree)) = tree else {
+                panic!("expected tree snapshot");
+            };
+            assert!(tree.get("card").is_some());
+            assert!(target.live.load(Ordering::SeqCst));
+
+            // Highlight is forwarded to the target
+            client
+                .send(&ClientCommand::Highlight {
+                    element_id: Some("card".to_string()),
+                })
+                .unwrap();
+            client
+                .recv_until(TIMEOUT, |m| matches!(m, ServerMessage::Ack { .. }))
+                .unwrap()
+                .expect("highlight ack");
+            assert_eq!(target.highlighted.lock().as_deref(), Some("card"));
+
+            // Edits show up as a tree patch
+            client
+                .send(&ClientCommand::SetVisualProp {
+                    element_id: "card".to_string(),
+                    property: "opacity".to_string(),
+                    value: "0.5".to_string(),
+                })
+                .unwrap();
+            let patch = client
+                .recv_until(TIMEOUT, |m| matches!(m, ServerMessage::TreePatch(_)))
+                .unwrap();
+            let Some(ServerMessage::TreePatch(patch)) = patch else {
+                panic!("expected tree patch");
+            };
+            patch.apply(&mut tree);
+            let opacity = tree
+                .get("card")
+                .unwrap()
+                .visual_props
+                .as_ref()
+                .unwrap()
+                .opacity;
+            assert_eq!(opacity, Some(0.5));
+
+            // Invalid edits are reported back
+            client
+                .send(&ClientCommand::SetVisualProp {
+                    element_id: "card".to_string(),
+                    property: "width".to_string(),
+                    value: "10px".to_string(),
+                })
+                .unwrap();
+            assert!(client
+                .recv_until(TIMEOUT, |m| matches!(m, ServerMessage::Error { .. }))
+                .unwrap()
+                .is_some());
+
+            // Queries
+            client
+                .send(&ClientCommand::QueryComputedStyle {
+                    element_id: "card".to_string(),
+                })
+                .unwrap();
+            let style = client
+                .recv_until(TIMEOUT, |m| matches!(m, ServerMessage::ComputedStyle(_)))
+                .unwrap();
+            assert!(matches!(
+                style,
+                Some(ServerMessage::ComputedStyle(ref s))
+                    if s.properties["opacity"] == "0.5" && s.sources["opacity"] == "live edit"
+            ));
+
+            let overlays = DebugOverlays {
+                flex: true,
+                hit_regions: true,
+                ..Default::default()
+            };
+            client
+                .send(&ClientCommand::SetDebugOverlays { overlays })
+                .unwrap();
+            client
+                .recv_until(
+                    TIMEOUT,
+                    |m| matches!(m, ServerMessage::Ack { command } if command == "set_debug_overlays"),
+                )
+                .unwrap()
+                .expect("set_debug_overlays ack");
+            assert_eq!(*target.overlays.lock(), overlays);
+
+            client
+                .send(&ClientCommand::QueryLayout {
+                    element_id: "card".to_string(),
+                })
+                .unwrap();
+            let layout = client
+                .recv_until(TIMEOUT, |m| matches!(m, ServerMessage::Layout(_)))
+                .unwrap();
+            assert!(matches!(
+                layout,
+                Some(ServerMessage::Layout(ref l)) if l.bounds.width == 50.0
+            ));
+
+            // Picking reports the clicked element
+            client
+                .send(&ClientCommand::PickElement { enabled: true })
+                .unwrap();
+            let picked = client
+                .recv_until(TIMEOUT, |m| {
+                    matches!(m, ServerMessage::ElementPicked { .. })
+                })
+                .unwrap();
+            assert!(matches!(
+                picked,
+                Some(ServerMessage::ElementPicked { ref element_id }) if element_id == "card"
+            ));
+
+            client.send(&ClientCommand::Unsubscribe).unwrap();
+            client
+                .recv_until(
+                    TIMEOUT,
+                    |m| matches!(m, ServerMessage::Ack { command } if command == "unsubscribe"),
+                )
+                .unwrap()
+                .expect("unsubscribe ack");
+            assert!(!target.live.load(Ordering::SeqCst));
+            assert!(target.highlighted.lock().is_none());
+        }
+
+        #[test]
+        fn test_reactive_inspection() {
+            let target = Arc::new(StubTarget::default());
+            let count = {
+                let mut graph = target.reactive.lock();
+                let count = graph.create_signal(1i32);
+                graph.label_signal(count, "count");
+                graph.debug_signal_editable(count);
+                count
+            };
+            let (_handle, mut client) = start_server("reactive", Some(target.clone()));
+
+            client
+                .send(&ClientCommand::SetSignal {
+                    signal: count.id().to_raw(),
+                    value: "7".to_string(),
+                })
+                .unwrap();
+            client
+                .recv_until(
+                    TIMEOUT,
+                    |m| matches!(m, ServerMessage::Ack { command } if command == "set_signal"),
+                )
+                .unwrap()
+                .expect("set_signal ack");
+            assert_eq!(target.reactive.lock().get(count), Some(7));
+
+            client.send(&ClientCommand::QueryReactive).unwrap();
+            let reply = client
+                .recv_until(TIMEOUT, |m| matches!(m, ServerMessage::Reactive { .. }))
+                .unwrap();
+            let Some(ServerMessage::Reactive { graph, stores }) = reply else {
+                panic!("expected reactive snapshot");
+            };
+            assert_eq!(graph.signals[0].label.as_deref(), Some("count"));
+            assert_eq!(graph.signals[0].value.as_deref(), Some("7"));
+            assert!(stores.iter().any(|s| s.name == "kv"));
+
+            // Bad values are reported back
+            client
+                .send(&ClientCommand::SetSignal {
+                    signal: count.id().to_raw(),
+                    value: "seven".to_string(),
+                })
+                .unwrap();
+            assert!(client
+                .recv_until(TIMEOUT, |m| matches!(m, ServerMessage::Error { .. }))
+                .unwrap()
+                .is_some());
+        }
+
+        #[test]
+        fn test_profile_streaming() {
+            let (_handle, mut client) = start_server("profile_stream", None);
+
+            client
+                .send(&ClientCommand::Profile { enabled: true })
+                .unwrap();
+            client
+                .recv_until(
+                    TIMEOUT,
+                    |m| matches!(m, ServerMessage::Ack { command } if command == "profile"),
+                )
+                .unwrap()
+                .expect("profile ack");
+            assert!(profiler::is_enabled());
+
+            profiler::begin_frame();
+            drop(profiler::scope("layout"));
+            profiler::count(profiler::counters::NODES_REBUILT, 4);
+            profiler::end_frame();
+
+            let reply = client
+                .recv_until(TIMEOUT, |m| {
+                    matches!(m, ServerMessage::FrameProfiles { .. })
+                })
+                .unwrap();
+            let Some(ServerMessage::FrameProfiles { frames }) = reply else {
+                panic!("expected frame profiles");
+            };
+            let frame = frames.last().unwrap();
+            assert_eq!(frame.spans[0].name, "layout");
+            assert_eq!(frame.counter(profiler::counters::NODES_REBUILT), 4);
+
+            client
+                .send(&ClientCommand::Profile { enabled: false })
+                .unwrap();
+            client
+                .recv_until(
+                    TIMEOUT,
+                    |m| matches!(m, ServerMessage::Ack { command } if command == "profile"),
+                )
+                .unwrap()
+                .expect("profile ack");
+            assert!(!profiler::is_enabled());
+        }
+
+        #[test]
+        fn test_save_recording() {
+            let (_handle, mut client) = start_server("save_recording", None);
+            let path = std::env::temp_dir()
+                .join(format!("junita-save-recording-{}.jrec", std::process::id()));
+
+            client.send(&ClientCommand::Start).unwrap();
+            client
+                .send(&ClientCommand::SaveRecording { path: path.clone() })
+                .unwrap();
+            let reply = client
+                .recv_until(
+                    TIMEOUT,
+                    |m| matches!(m, ServerMessage::Ack { command } if command == "save_recording"),
+                )
+                .unwrap();
+            assert!(reply.is_some());
+
+            // Stopping finishes the file
+            client.send(&ClientCommand::Stop).unwrap();
+            client
+                .recv_until(
+                    TIMEOUT,
+                    |m| matches!(m, ServerMessage::Ack { command } if command == "stop"),
+                )
+                .unwrap();
+            assert!(crate::RecordingReader::open(&path).unwrap().is_complete());
+            let _ = std::fs::remove_file(&path);
+        }
+
+        #[test]
+        fn test_live_commands_without_target() {
+            let (_handle, mut client) = start_server("live_no_target", None);
+
+            client
+                .send(&ClientCommand::QueryLayout {
+                    element_id: "card".to_string(),
+                })
+                .unwrap();
+            let reply = client
+                .recv_until(TIMEOUT, |m| matches!(m, ServerMessage::Error { .. }))
+                .unwrap();
+            assert!(matches!(
+                reply,
+                Some(ServerMessage::Error { ref message }) if message.contains("No live target")
+            ));
+        }
+    }
 }
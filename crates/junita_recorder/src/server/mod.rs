@@ -7,9 +7,21 @@
 //! - Unix (Linux/macOS): Unix domain sockets at `/tmp/junita/{app_name}.sock`
 //! - Windows: Named pipes at `\\.\pipe\junita\{app_name}`
 
+//!
+//! Besides streaming recordings, the server supports live attach: when a
+//! [`LiveTarget`] is registered with [`DebugServer::with_target`], clients can
+//! subscribe to tree patches, highlight and pick elements, and edit styles
+//! in the running app.
+
+#[cfg(unix)]
+mod client;
+mod live;
 mod local;
 
+#[cfg(unix)]
+pub use client::DebugClient;
+pub use live::{ComputedStyle, DebugOverlays, Edges, LayoutInfo, LiveTarget};
 pub use local::{
     start_local_server, start_local_server_named, ClientCommand, DebugServer, DebugServerConfig,
-    ServerHandle, ServerMessage,
+    ServerHandle, ServerMessage, PROTOCOL_VERSION,
 };
@@ -1,6 +1,7 @@
 //! Recording configuration presets.
 
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 /// Configuration for a recording session.
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -21,6 +22,10 @@ pub struct RecordingConfig {
     pub capture_text_content: bool,
     /// Application name (for debug server identification).
     pub app_name: String,
+    /// Binary file the session streams into from `start()`, so nothing the
+    /// ring buffers drop is lost.
+    #[serde(default)]
+    pub stream_path: Option<PathBuf>,
 }
 
 impl Default for RecordingConfig {
@@ -41,6 +46,7 @@ impl RecordingConfig {
             capture_visual_props: false,
             capture_text_content: false,
             app_name: "junita_app".to_string(),
+            stream_path: None,
         }
     }
 
@@ -55,6 +61,7 @@ impl RecordingConfig {
             capture_visual_props: true,
             capture_text_content: true,
             app_name: "junita_app".to_string(),
+            stream_path: None,
         }
     }
 
@@ -69,6 +76,7 @@ impl RecordingConfig {
             capture_visual_props: false,
             capture_text_content: false,
             app_name: "junita_app".to_string(),
+            stream_path: None,
         }
     }
 
@@ -83,6 +91,7 @@ impl RecordingConfig {
             capture_visual_props: true,
             capture_text_content: true,
             app_name: "junita_test".to_string(),
+            stream_path: None,
         }
     }
 
@@ -116,6 +125,12 @@ impl RecordingConfig {
         self
     }
 
+    /// Stream the recording into a binary file at `path`.
+    pub fn with_stream_path(mut self, path: impl Into<PathBuf>) -> Self {
+        self.stream_path = Some(path.into());
+        self
+    }
+
     /// Enable or disable visual property capture.
     pub fn with_visual_props(mut self, capture: bool) -> Self {
         self.capture_visual_props = capture;
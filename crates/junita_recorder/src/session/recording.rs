@@ -4,9 +4,16 @@ use super::config::RecordingConfig;
 use crate::capture::{
     RecordedEvent, RecordingClock, Timestamp, TimestampedEvent, TreeDiff, TreeSnapshot,
 };
+use crate::format::{self, RecordingWriter};
 use parking_lot::RwLock;
 use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Writer a session streams into.
+type StreamWriter = RecordingWriter<Box<dyn Write + Send + Sync>>;
 
 /// State of the recording session.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -38,9 +45,11 @@ pub struct RecordingSession {
     /// Accumulated pause duration (for accurate timestamps).
     pause_duration: std::time::Duration,
     /// When the current pause started (if paused).
-    pause_start: Option<std::time::Instant>,
+    pause_start: Option<std::time::Duration>,
     /// Statistics.
     stats: SessionStats,
+    /// Binary file receiving records as they are captured.
+    stream: Option<StreamWriter>,
 }
 
 /// Statistics for a recording session.
@@ -73,6 +82,7 @@ impl RecordingSession {
             pause_duration: std::time::Duration::ZERO,
             pause_start: None,
             stats: SessionStats::default(),
+            stream: None,
         }
     }
 
@@ -107,11 +117,16 @@ impl RecordingSession {
                 self.pause_duration = std::time::Duration::ZERO;
                 self.stats = SessionStats::default();
                 self.state = SessionState::Recording;
+                if let Some(path) = self.config.stream_path.clone() {
+                    if let Err(e) = self.stream_to(&path) {
+                        tracing::warn!("Failed to stream recording to {}: {}", path.display(), e);
+                    }
+                }
             }
             SessionState::Paused => {
                 // Resume from pause
                 if let Some(pause_start) = self.pause_start.take() {
-                    self.pause_duration += pause_start.elapsed();
+                    self.pause_duration += junita_core::clock::now().saturating_sub(pause_start);
                 }
                 self.state = SessionState::Recording;
             }
@@ -122,21 +137,27 @@ impl RecordingSession {
     /// Pause recording (can resume later).
     pub fn pause(&mut self) {
         if self.state == SessionState::Recording {
-            self.pause_start = Some(std::time::Instant::now());
+            self.pause_start = Some(junita_core::clock::now());
             self.state = SessionState::Paused;
         }
     }
 
     /// Stop recording (cannot resume).
+    ///
+    /// Finishes the stream file, if any.
     pub fn stop(&mut self) {
         if self.state == SessionState::Recording || self.state == SessionState::Paused {
             self.state = SessionState::Stopped;
             self.pause_start = None;
+            self.finish_stream();
         }
     }
 
     /// Reset the session to idle state.
+    ///
+    /// Finishes the stream file, if any, before clearing the session.
     pub fn reset(&mut self) {
+        self.finish_stream();
         self.state = SessionState::Idle;
         self.events.clear();
         self.snapshots.clear();
@@ -146,6 +167,57 @@ impl RecordingSession {
         self.stats = SessionStats::default();
     }
 
+    /// Stream the recording into a binary file at `path`.
+    ///
+    /// Records are appended in chunks while recording, so the file survives
+    /// a crash, and the file is finished when the session stops. The ring
+    /// buffers still hold the most recent records for `export()`.
+    pub fn stream_to(&mut self, path: impl AsRef<Path>) -> format::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        self.stream_to_writer(Box::new(file))
+    }
+
+    /// Stream the recording into `out`.
+    ///
+    /// Records already in the buffers are written first. A stream that was
+    /// already open is finished.
+    pub fn stream_to_writer(&mut self, out: Box<dyn Write + Send + Sync>) -> format::Result<()> {
+        self.finish_stream();
+        let mut writer = RecordingWriter::new(out, &self.config)?;
+        for event in &self.events {
+            writer.push_event(event)?;
+        }
+        for snapshot in &self.snapshots {
+            writer.push_snapshot(snapshot)?;
+        }
+        self.stream = Some(writer);
+        Ok(())
+    }
+
+    /// Check if the session is streaming to a file.
+    pub fn is_streaming(&self) -> bool {
+        self.stream.is_some()
+    }
+
+    /// Write the index and trailer of the stream, if any, and close it.
+    fn finish_stream(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            if let Err(e) = stream.finish(&self.stats) {
+                tracing::warn!("Failed to finish recording stream: {}", e);
+            }
+        }
+    }
+
+    /// Run `f` on the stream, dropping the stream if it fails.
+    fn write_stream(&mut self, f: impl FnOnce(&mut StreamWriter) -> format::Result<()>) {
+        if let Some(stream) = &mut self.stream {
+            if let Err(e) = f(stream) {
+                tracing::warn!("Recording stream failed, no longer streaming: {}", e);
+                self.stream = None;
+            }
+        }
+    }
+
     /// Get the current timestamp (accounting for pause time).
     pub fn current_timestamp(&self) -> Timestamp {
         let raw = self.clock.now();
@@ -168,6 +240,7 @@ impl RecordingSession {
             self.stats.events_dropped += 1;
         }
 
+        self.write_stream(|stream| stream.push_event(&timestamped));
         self.events.push_back(timestamped);
         self.stats.total_events += 1;
         self.stats.last_event_time = Some(timestamp);
@@ -194,6 +267,7 @@ impl RecordingSession {
             self.stats.snapshots_dropped += 1;
         }
 
+        self.write_stream(|stream| stream.push_snapshot(&snapshot));
         self.last_snapshot = Some(snapshot.clone());
         self.snapshots.push_back(snapshot);
         self.stats.total_snapshots += 1;
@@ -313,6 +387,18 @@ impl SharedRecordingSession {
         self.inner.read().stats().clone()
     }
 
+    pub fn stream_to(&self, path: impl AsRef<Path>) -> format::Result<()> {
+        self.inner.write().stream_to(path)
+    }
+
+    pub fn stream_to_writer(&self, out: Box<dyn Write + Send + Sync>) -> format::Result<()> {
+        self.inner.write().stream_to_writer(out)
+    }
+
+    pub fn is_streaming(&self) -> bool {
+        self.inner.read().is_streaming()
+    }
+
     pub fn export(&self) -> RecordingExport {
         self.inner.read().export()
     }
@@ -382,4 +468,57 @@ mod tests {
         assert_eq!(session.stats().events_dropped, 2);
         assert_eq!(session.stats().total_events, 7);
     }
+
+    #[test]
+    fn test_streaming_to_file() {
+        use crate::capture::{Modifiers, MouseButton, MouseEvent, Point};
+
+        let path = std::env::temp_dir().join("junita_recorder_stream_test.jrec");
+        let mut session = RecordingSession::new(RecordingConfig::minimal().with_max_events(5));
+        session.start();
+        session.stream_to(&path).unwrap();
+        assert!(session.is_streaming());
+
+        for i in 0..7 {
+            session.record_event(RecordedEvent::Click(MouseEvent {
+                position: Point::new(i as f32 * 10.0, 0.0),
+                button: MouseButton::Left,
+                modifiers: Modifiers::none(),
+                target_element: None,
+            }));
+        }
+        session.record_snapshot(TreeSnapshot::new(Timestamp::zero(), (100, 100), 1.0));
+        session.stop();
+        assert!(!session.is_streaming());
+
+        // The file keeps what the ring buffer dropped
+        let export = crate::format::load_recording(&path).unwrap();
+        assert_eq!(export.events.len(), 7);
+        assert_eq!(export.snapshots.len(), 1);
+        assert_eq!(export.stats.events_dropped, 2);
+    }
+
+    #[test]
+    fn test_stream_finished_on_reset() {
+        let dir = std::env::temp_dir();
+        let configured = dir.join("junita_recorder_stream_config.jrec");
+        let replaced = dir.join("junita_recorder_stream_replaced.jrec");
+        let mut session =
+            RecordingSession::new(RecordingConfig::minimal().with_stream_path(&configured));
+        session.start();
+        assert!(session.is_streaming());
+        session.record_snapshot(TreeSnapshot::new(Timestamp::zero(), (100, 100), 1.0));
+
+        // Streaming somewhere else finishes the first file
+        session.stream_to(&replaced).unwrap();
+        assert!(crate::format::RecordingReader::open(&configured)
+            .unwrap()
+            .is_complete());
+
+        session.reset();
+        assert!(!session.is_streaming());
+        let reader = crate::format::RecordingReader::open(&replaced).unwrap();
+        assert!(reader.is_complete());
+        assert_eq!(reader.into_export().unwrap().snapshots.len(), 1);
+    }
 }
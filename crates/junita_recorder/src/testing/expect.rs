@@ -0,0 +1,450 @@
+//! Snapshot expectations for replayed regression tests.
+//!
+//! Generated tests (see [`generate_test`](super::generate_test)) replay
+//! recorded input into a [`ReplayTarget`] and compare the resulting tree
+//! against [`ElementExpectation`]s using a [`SnapshotMatcher`].
+//!
+//! Elements are addressed by stable IDs rather than node indices:
+//! - `#submit` - an element with a user-assigned ID (`.id("submit")`)
+//! - `#form/Text[1]` - the second `Text` child of `#form`
+//! - `root/Div[0]` - a path from the root when no ancestor has an ID
+
+use crate::capture::{Rect, TreeSnapshot};
+use crate::replay::SimulatedInput;
+use crate::Timestamp;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+/// Tolerance used when comparing colors and opacity.
+const COLOR_EPSILON: f32 = 1.0 / 255.0;
+
+/// An application that recorded input can be replayed into.
+pub trait ReplayTarget {
+    /// Deliver a simulated input event.
+    fn dispatch(&mut self, input: &SimulatedInput);
+
+    /// Run one frame at the given virtual time.
+    ///
+    /// Implementations should use `now` for animations and timers so
+    /// that replay is deterministic.
+    fn frame(&mut self, now: Timestamp);
+
+    /// Capture the current element tree.
+    fn snapshot(&mut self) -> TreeSnapshot;
+}
+
+/// Compute stable IDs for every element reachable from the root.
+///
+/// Returns `(element_id, stable_id)` pairs in depth-first tree order.
+pub fn stable_ids(snapshot: &TreeSnapshot) -> Vec<(String, String)> {
+    let mut ids = Vec::with_capacity(snapshot.element_count());
+    if let Some(root) = &snapshot.root_id {
+        visit(snapshot, root, "root".to_string(), &mut ids);
+    }
+    ids
+}
+
+fn visit(snapshot: &TreeSnapshot, id: &str, path: String, ids: &mut Vec<(String, String)>) {
+    let Some(element) = snapshot.get(id) else {
+        return;
+    };
+    let path = match &element.stable_id {
+        Some(user_id) => format!("#{}", user_id),
+        None => path,
+    };
+
+    let mut seen: HashMap<&str, usize> = HashMap::new();
+    let children: Vec<_> = element
+        .children
+        .iter()
+        .filter_map(|child| snapshot.get(child))
+        .map(|child| {
+            let kind = base_type(&child.element_type);
+            let index = seen.entry(kind).or_insert(0);
+            let child_path = format!("{}/{}[{}]", path, kind, index);
+            *index += 1;
+            (child.id.clone(), child_path)
+        })
+        .collect();
+
+    ids.push((id.to_string(), path));
+    for (child, child_path) in children {
+        visit(snapshot, &child, child_path, ids);
+    }
+}
+
+/// Element type without per-instance details, e.g. `Text` for `Text(12)`.
+fn base_type(element_type: &str) -> &str {
+    element_type
+        .split_once('(')
+        .map(|(kind, _)| kind)
+        .unwrap_or(element_type)
+}
+
+/// Expected state of a single element, addressed by stable ID.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ElementExpectation {
+    /// Stable ID (see [`stable_ids`]).
+    pub id: String,
+    /// Whether the element should exist at all.
+    pub exists: bool,
+    /// Expected bounds.
+    pub bounds: Option<Rect>,
+    /// Expected text content.
+    pub text: Option<String>,
+    /// Expected visibility.
+    pub visible: Option<bool>,
+    /// Expected focus state.
+    pub focused: Option<bool>,
+    /// Expected hover state.
+    pub hovered: Option<bool>,
+    /// Expected background color (RGBA).
+    pub background_color: Option<[f32; 4]>,
+    /// Expected opacity.
+    pub opacity: Option<f32>,
+}
+
+impl ElementExpectation {
+    /// Expect an element with the given stable ID to exist.
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            exists: true,
+            bounds: None,
+            text: None,
+            visible: None,
+            focused: None,
+            hovered: None,
+            background_color: None,
+            opacity: None,
+        }
+    }
+
+    /// Expect no element with the given stable ID.
+    pub fn absent(id: impl Into<String>) -> Self {
+        Self {
+            exists: false,
+            ..Self::new(id)
+        }
+    }
+
+    /// Expect the given bounds.
+    pub fn bounds(mut self, x: f32, y: f32, width: f32, height: f32) -> Self {
+        self.bounds = Some(Rect::new(x, y, width, height));
+        self
+    }
+
+    /// Expect the given text content.
+    pub fn text(mut self, text: impl Into<String>) -> Self {
+        self.text = Some(text.into());
+        self
+    }
+
+    /// Expect the element to be visible or hidden.
+    pub fn visible(mut self, visible: bool) -> Self {
+        self.visible = Some(visible);
+        self
+    }
+
+    /// Expect the element to be focused or not.
+    pub fn focused(mut self, focused: bool) -> Self {
+        self.focused = Some(focused);
+        self
+    }
+
+    /// Expect the element to be hovered or not.
+    pub fn hovered(mut self, hovered: bool) -> Self {
+        self.hovered = Some(hovered);
+        self
+    }
+
+    /// Expect the given background color.
+    pub fn background(mut self, r: f32, g: f32, b: f32, a: f32) -> Self {
+        self.background_color = Some([r, g, b, a]);
+        self
+    }
+
+    /// Expect the given opacity.
+    pub fn opacity(mut self, opacity: f32) -> Self {
+        self.opacity = Some(opacity);
+        self
+    }
+}
+
+/// Compares tree snapshots against expectations.
+///
+/// Property names accepted by [`ignore`](Self::ignore): `bounds`,
+/// `bounds.x`, `bounds.y`, `bounds.width`, `bounds.height`, `text`,
+/// `visible`, `focused`, `hovered`, `background_color` and `opacity`.
+#[derive(Clone, Debug)]
+pub struct SnapshotMatcher {
+    /// Maximum allowed difference for each bounds component, in pixels.
+    pub bounds_tolerance: f32,
+    /// Properties that are never compared.
+    pub ignored: HashSet<String>,
+}
+
+impl Default for SnapshotMatcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SnapshotMatcher {
+    /// Create a matcher that compares bounds exactly.
+    pub fn new() -> Self {
+        Self {
+            bounds_tolerance: 0.0,
+            ignored: HashSet::new(),
+        }
+    }
+
+    /// Set the bounds tolerance in pixels.
+    pub fn with_bounds_tolerance(mut self, tolerance: f32) -> Self {
+        self.bounds_tolerance = tolerance.max(0.0);
+        self
+    }
+
+    /// Skip a property when comparing.
+    pub fn ignore(mut self, property: impl Into<String>) -> Self {
+        self.ignored.insert(property.into());
+        self
+    }
+
+    /// Check whether a property is ignored.
+    pub fn is_ignored(&self, property: &str) -> bool {
+        self.ignored.contains(property)
+            || property
+                .split_once('.')
+                .is_some_and(|(group, _)| self.ignored.contains(group))
+    }
+
+    /// Compare a snapshot against expectations.
+    pub fn check(
+        &self,
+        snapshot: &TreeSnapshot,
+        expectations: &[ElementExpectation],
+    ) -> Result<(), SnapshotMismatch> {
+        let ids: HashMap<String, String> = stable_ids(snapshot)
+            .into_iter()
+            .map(|(element, stable)| (stable, element))
+            .collect();
+
+        let mut failures = Vec::new();
+        for expected in expectations {
+            let element = ids.get(&expected.id).and_then(|id| snapshot.get(id));
+            let Some(element) = element else {
+                if expected.exists {
+                    failures.push(format!("{}: not found", expected.id));
+                }
+                continue;
+            };
+            if !expected.exists {
+                failures.push(format!("{}: expected to be absent", expected.id));
+                continue;
+            }
+
+            let mut fail = |property: &str, expected_value: String, actual: String| {
+                failures.push(format!(
+                    "{}: {} expected {}, got {}",
+                    expected.id, property, expected_value, actual
+                ));
+            };
+
+            if let Some(bounds) = expected.bounds {
+                let actual = element.bounds;
+                for (property, want, got) in [
+                    ("bounds.x", bounds.x, actual.x),
+                    ("bounds.y", bounds.y, actual.y),
+                    ("bounds.width", bounds.width, actual.width),
+                    ("bounds.height", bounds.height, actual.height),
+                ] {
+                    if !self.is_ignored(property) && (want - got).abs() > self.bounds_tolerance {
+                        fail(property, format!("{:?}", want), format!("{:?}", got));
+                    }
+                }
+            }
+
+            if let Some(text) = &expected.text {
+                if !self.is_ignored("text") && element.text_content.as_ref() != Some(text) {
+                    fail(
+                        "text",
+                        format!("{:?}", text),
+                        format!("{:?}", element.text_content),
+                    );
+                }
+            }
+
+            for (property, want, got) in [
+                ("visible", expected.visible, element.is_visible),
+                ("focused", expected.focused, element.is_focused),
+                ("hovered", expected.hovered, element.is_hovered),
+            ] {
+                if let Some(want) = want {
+                    if !self.is_ignored(property) && want != got {
+                        fail(property, want.to_string(), got.to_string());
+                    }
+                }
+            }
+
+            let props = element.visual_props.as_ref();
+            if let Some(color) = expected.background_color {
+                let actual = props.and_then(|p| p.background_color);
+                let matches = actual.is_some_and(|actual| {
+                    color
+                        .iter()
+                        .zip(actual.iter())
+                        .all(|(a, b)| (a - b).abs() <= COLOR_EPSILON)
+                });
+                if !self.is_ignored("background_color") && !matches {
+                    fail(
+                        "background_color",
+                        format!("{:?}", color),
+                        format!("{:?}", actual),
+                    );
+                }
+            }
+
+            if let Some(opacity) = expected.opacity {
+                let actual = props.and_then(|p| p.opacity).unwrap_or(1.0);
+                if !self.is_ignored("opacity") && (opacity - actual).abs() > COLOR_EPSILON {
+                    fail("opacity", format!("{:?}", opacity), format!("{:?}", actual));
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(SnapshotMismatch {
+                timestamp: snapshot.timestamp,
+                failures,
+            })
+        }
+    }
+}
+
+/// Differences found by [`SnapshotMatcher::check`].
+#[derive(Clone, Debug)]
+pub struct SnapshotMismatch {
+    /// Virtual time of the compared snapshot.
+    pub timestamp: Timestamp,
+    /// One message per failed expectation.
+    pub failures: Vec<String>,
+}
+
+impl fmt::Display for SnapshotMismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "snapshot at {}ms does not match ({} difference(s)):",
+            self.timestamp.as_millis(),
+            self.failures.len()
+        )?;
+        for failure in &self.failures {
+            writeln!(f, "  {}", failure)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for SnapshotMismatch {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::{ElementSnapshot, VisualProps};
+
+    fn element(id: &str, element_type: &str, parent: Option<&str>) -> ElementSnapshot {
+        let mut element = ElementSnapshot::new(
+            id.to_string(),
+            element_type.to_string(),
+            Rect::new(0.0, 0.0, 100.0, 20.0),
+        );
+        element.parent = parent.map(str::to_string);
+        element
+    }
+
+    fn snapshot() -> TreeSnapshot {
+        let mut snapshot = TreeSnapshot::new(Timestamp::from_micros(1_000), (800, 600), 1.0);
+        let mut root = element("0v1", "Div", None);
+        root.children = vec!["1v1".into(), "2v1".into()];
+        let mut form = element("1v1", "Div", Some("0v1"));
+        form.stable_id = Some("form".into());
+        form.children = vec!["3v1".into(), "4v1".into()];
+        let mut label = element("3v1", "Text(5)", Some("1v1"));
+        label.text_content = Some("Hello".into());
+        let mut count = element("4v1", "Text(1)", Some("1v1"));
+        count.text_content = Some("3".into());
+        count.visual_props = Some(VisualProps {
+            opacity: Some(0.5),
+            ..Default::default()
+        });
+        let footer = element("2v1", "Div", Some("0v1"));
+
+        for e in [root, form, label, count, footer] {
+            snapshot.elements.insert(e.id.clone(), e);
+        }
+        snapshot.root_id = Some("0v1".into());
+        snapshot
+    }
+
+    #[test]
+    fn test_stable_ids() {
+        let ids: Vec<String> = stable_ids(&snapshot())
+            .into_iter()
+            .map(|(_, stable)| stable)
+            .collect();
+        assert_eq!(
+            ids,
+            vec![
+                "root",
+                "#form",
+                "#form/Text[0]",
+                "#form/Text[1]",
+                "root/Div[1]"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_matcher_tolerance_and_ignore() {
+        let snapshot = snapshot();
+        let expectations = [
+            ElementExpectation::new("#form").bounds(0.4, 0.0, 100.0, 20.0),
+            ElementExpectation::new("#form/Text[1]")
+                .text("3")
+                .opacity(1.0),
+            ElementExpectation::absent("#dialog"),
+        ];
+
+        let strict = SnapshotMatcher::new();
+        let err = strict.check(&snapshot, &expectations).unwrap_err();
+        assert_eq!(err.failures.len(), 2);
+        assert!(err.failures[0].starts_with("#form: bounds.x"));
+        assert!(err.failures[1].starts_with("#form/Text[1]: opacity"));
+
+        let relaxed = SnapshotMatcher::new()
+            .with_bounds_tolerance(0.5)
+            .ignore("opacity");
+        assert!(relaxed.check(&snapshot, &expectations).is_ok());
+    }
+
+    #[test]
+    fn test_matcher_missing_element() {
+        let snapshot = snapshot();
+        let err = SnapshotMatcher::new()
+            .check(
+                &snapshot,
+                &[
+                    ElementExpectation::new("#missing"),
+                    ElementExpectation::absent("#form"),
+                ],
+            )
+            .unwrap_err();
+        assert_eq!(
+            err.failures,
+            vec!["#missing: not found", "#form: expected to be absent"]
+        );
+    }
+}
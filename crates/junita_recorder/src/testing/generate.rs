@@ -0,0 +1,798 @@
+//! Generate regression tests from recordings.
+//!
+//! Converts a [`RecordingExport`] into Rust source for a test that replays
+//! the recorded input on a virtual clock and asserts on key tree
+//! snapshots. Key snapshots are the last snapshot before each input (the
+//! settled state the user acted on) and the final snapshot; snapshots that
+//! would produce the same assertions as the previous one are skipped.
+//!
+//! Focus and hover events are not replayed since the event router derives
+//! them from the replayed pointer and keyboard input.
+
+use super::expect::{stable_ids, ElementExpectation, SnapshotMatcher};
+use crate::capture::{Key, Modifiers, MouseButton, Point, RecordedEvent, TreeSnapshot};
+use crate::replay::{EventSimulator, SimulatedInput};
+use crate::session::RecordingExport;
+use crate::Timestamp;
+use std::collections::{BTreeSet, HashSet};
+use std::fmt::Write;
+
+/// Configuration for [`generate_test`].
+#[derive(Clone, Debug)]
+pub struct TestGenConfig {
+    /// Name of the generated test function.
+    pub test_name: String,
+    /// Path of a function returning the [`ReplayTarget`](super::ReplayTarget)
+    /// under test, e.g. `my_app::testing::app`.
+    pub app_factory: String,
+    /// Maximum allowed difference for each bounds component, in pixels.
+    pub bounds_tolerance: f32,
+    /// Properties left out of the assertions (see [`SnapshotMatcher`]).
+    pub ignored_properties: Vec<String>,
+    /// Assert on elements without a user-assigned ID or text content.
+    pub include_unnamed: bool,
+    /// Maximum number of snapshot assertions; the latest ones are kept.
+    pub max_snapshots: usize,
+    /// Recording file name, mentioned in the generated header.
+    pub source: Option<String>,
+}
+
+impl Default for TestGenConfig {
+    fn default() -> Self {
+        Self {
+            test_name: "recorded_session".to_string(),
+            app_factory: "app_under_test".to_string(),
+            bounds_tolerance: 0.5,
+            ignored_properties: Vec::new(),
+            include_unnamed: false,
+            max_snapshots: 16,
+            source: None,
+        }
+    }
+}
+
+impl TestGenConfig {
+    /// Create a config for a test with the given name.
+    pub fn new(test_name: impl Into<String>) -> Self {
+        Self {
+            test_name: test_name.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Set the function that creates the app under test.
+    pub fn with_app_factory(mut self, path: impl Into<String>) -> Self {
+        self.app_factory = path.into();
+        self
+    }
+
+    /// Set the bounds tolerance in pixels.
+    pub fn with_bounds_tolerance(mut self, tolerance: f32) -> Self {
+        self.bounds_tolerance = tolerance.max(0.0);
+        self
+    }
+
+    /// Leave a property out of the assertions.
+    pub fn ignore(mut self, property: impl Into<String>) -> Self {
+        self.ignored_properties.push(property.into());
+        self
+    }
+
+    /// Also assert on elements without a user-assigned ID or text.
+    pub fn with_unnamed(mut self, include: bool) -> Self {
+        self.include_unnamed = include;
+        self
+    }
+
+    /// Limit the number of snapshot assertions.
+    pub fn with_max_snapshots(mut self, max: usize) -> Self {
+        self.max_snapshots = max;
+        self
+    }
+
+    /// Set the recording file name mentioned in the generated header.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        self.source = Some(source.into());
+        self
+    }
+
+    fn matcher(&self) -> SnapshotMatcher {
+        self.ignored_properties.iter().fold(
+            SnapshotMatcher::new().with_bounds_tolerance(self.bounds_tolerance),
+            |matcher, property| matcher.ignore(property.clone()),
+        )
+    }
+}
+
+/// A step of the generated test.
+enum Step {
+    Input(Timestamp, SimulatedInput),
+    Assert(Timestamp, Vec<ElementExpectation>),
+}
+
+impl Step {
+    fn timestamp(&self) -> Timestamp {
+        match self {
+            Step::Input(at, _) | Step::Assert(at, _) => *at,
+        }
+    }
+}
+
+/// Generate Rust source for a regression test from a recording.
+pub fn generate_test(export: &RecordingExport, config: &TestGenConfig) -> String {
+    let mut simulator = EventSimulator::new();
+    let inputs: Vec<(Timestamp, SimulatedInput)> = export
+        .events
+        .iter()
+        .filter(|e| is_replayable(&e.event))
+        .map(|e| (e.timestamp, simulator.process(e)))
+        .collect();
+
+    let input_times: Vec<Timestamp> = inputs.iter().map(|(at, _)| *at).collect();
+    let mut steps: Vec<Step> = inputs
+        .into_iter()
+        .map(|(at, input)| Step::Input(at, input))
+        .collect();
+    steps.extend(
+        assertions(export, &input_times, config)
+            .into_iter()
+            .map(|(at, expectations)| Step::Assert(at, expectations)),
+    );
+    // Stable sort keeps inputs ahead of snapshots taken at the same time
+    steps.sort_by_key(Step::timestamp);
+
+    let mut imports = BTreeSet::new();
+    let mut body = String::new();
+    for step in &steps {
+        match step {
+            Step::Input(at, input) => {
+                imports.insert("SimulatedInput");
+                imports.insert("Timestamp");
+                let _ = writeln!(body, "        ctx.replay(");
+                let _ = writeln!(body, "            &mut app,");
+                let _ = writeln!(
+                    body,
+                    "            Timestamp::from_micros({}),",
+                    at.as_micros()
+                );
+                for line in input_lines(input, &mut imports) {
+                    let _ = writeln!(body, "            {}", line);
+                }
+                let _ = writeln!(body, "        );");
+            }
+            Step::Assert(at, expectations) => {
+                imports.insert("Timestamp");
+                let _ = writeln!(body, "        ctx.assert_snapshot(");
+                let _ = writeln!(body, "            &mut app,");
+                let _ = writeln!(
+                    body,
+                    "            Timestamp::from_micros({}),",
+                    at.as_micros()
+                );
+                let _ = writeln!(body, "            &matcher,");
+                let _ = writeln!(body, "            &[");
+                for expectation in expectations {
+                    let _ = writeln!(body, "                {},", expectation_expr(expectation));
+                }
+                let _ = writeln!(body, "            ],");
+                let _ = writeln!(body, "        );");
+            }
+        }
+    }
+
+    let (width, height) = export
+        .snapshots
+        .first()
+        .map(|s| s.window_size)
+        .unwrap_or((800, 600));
+
+    let mut out = String::new();
+    match &config.source {
+        Some(source) => {
+            let _ = writeln!(out, "//! Regression test generated from `{}`.", source);
+            let _ = writeln!(out, "//!");
+            let _ = writeln!(out, "//! Regenerate with `junita test gen {}`.", source);
+        }
+        None => {
+            let _ = writeln!(out, "//! Regression test generated from a recording.");
+        }
+    }
+    let _ = writeln!(out);
+    let has_assertions = steps.iter().any(|s| matches!(s, Step::Assert(..)));
+    let _ = writeln!(
+        out,
+        "use junita_recorder::testing::{{{}SnapshotMatcher, TestConfig, TestRunner}};",
+        if has_assertions {
+            "ElementExpectation, "
+        } else {
+            ""
+        }
+    );
+    if !imports.is_empty() {
+        let imports: Vec<&str> = imports.into_iter().collect();
+        let _ = writeln!(out, "use junita_recorder::{{{}}};", imports.join(", "));
+    }
+    let _ = writeln!(out);
+    let _ = writeln!(out, "#[test]");
+    let _ = writeln!(out, "fn {}() {{", test_ident(&config.test_name));
+    let _ = writeln!(
+        out,
+        "    let mut runner = TestRunner::new(TestConfig::fast().with_size({}, {}));",
+        width, height
+    );
+    let _ = write!(out, "    let matcher = SnapshotMatcher::new()");
+    let _ = write!(
+        out,
+        "\n        .with_bounds_tolerance({})",
+        float_expr(config.bounds_tolerance)
+    );
+    for property in &config.ignored_properties {
+        let _ = write!(out, "\n        .ignore({:?})", property);
+    }
+    let _ = writeln!(out, ";");
+    let _ = writeln!(out);
+    let _ = writeln!(out, "    runner.run(|ctx| {{");
+    let _ = writeln!(out, "        let mut app = {}();", config.app_factory);
+    if !body.is_empty() {
+        let _ = writeln!(out);
+        out.push_str(&body);
+    }
+    let _ = writeln!(out, "    }});");
+    let _ = writeln!(out, "}}");
+    out
+}
+
+/// Whether an event is user input rather than state derived from it.
+fn is_replayable(event: &RecordedEvent) -> bool {
+    !matches!(
+        event,
+        RecordedEvent::FocusChange(_) | RecordedEvent::HoverEnter(_) | RecordedEvent::HoverLeave(_)
+    )
+}
+
+/// Pick key snapshots and turn them into expectations.
+fn assertions(
+    export: &RecordingExport,
+    input_times: &[Timestamp],
+    config: &TestGenConfig,
+) -> Vec<(Timestamp, Vec<ElementExpectation>)> {
+    let matcher = config.matcher();
+    let snapshots = &export.snapshots;
+
+    let mut result: Vec<(Timestamp, Vec<ElementExpectation>)> = Vec::new();
+    let mut previous_ids: HashSet<String> = HashSet::new();
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        let is_key = match snapshots.get(i + 1) {
+            // Last snapshot before an input, or the final one
+            Some(next) => input_times
+                .iter()
+                .any(|&at| at >= snapshot.timestamp && at < next.timestamp),
+            None => true,
+        };
+        if !is_key {
+            continue;
+        }
+
+        let mut expectations = expectations(snapshot, &matcher, config);
+        let ids: HashSet<String> = expectations.iter().map(|e| e.id.clone()).collect();
+        let mut gone: Vec<&String> = previous_ids.difference(&ids).collect();
+        gone.sort();
+        expectations.extend(gone.into_iter().map(ElementExpectation::absent));
+
+        let unchanged = result
+            .last()
+            .is_some_and(|(_, last)| last.iter().filter(|e| e.exists).eq(expectations.iter()));
+        previous_ids = ids;
+        if !unchanged && !expectations.is_empty() {
+            result.push((snapshot.timestamp, expectations));
+        }
+    }
+
+    let skip = result.len().saturating_sub(config.max_snapshots);
+    result.drain(..skip);
+    result
+}
+
+/// Expectations for the assertable elements of a snapshot, in tree order.
+fn expectations(
+    snapshot: &TreeSnapshot,
+    matcher: &SnapshotMatcher,
+    config: &TestGenConfig,
+) -> Vec<ElementExpectation> {
+    stable_ids(snapshot)
+        .into_iter()
+        .filter_map(|(id, stable)| {
+            let element = snapshot.get(&id)?;
+            if element.stable_id.is_none()
+                && element.text_content.is_none()
+                && !config.include_unnamed
+            {
+                return None;
+            }
+
+            let mut expectation = ElementExpectation::new(stable);
+            if !matcher.is_ignored("bounds") {
+                let b = element.bounds;
+                expectation = expectation.bounds(b.x, b.y, b.width, b.height);
+            }
+            if let Some(text) = &element.text_content {
+                if !matcher.is_ignored("text") {
+                    expectation = expectation.text(text.clone());
+                }
+            }
+            if !element.is_visible && !matcher.is_ignored("visible") {
+                expectation = expectation.visible(false);
+            }
+            if element.is_focused && !matcher.is_ignored("focused") {
+                expectation = expectation.focused(true);
+            }
+            if element.is_hovered && !matcher.is_ignored("hovered") {
+                expectation = expectation.hovered(true);
+            }
+            if let Some(props) = &element.visual_props {
+                if let Some([r, g, b, a]) = props.background_color {
+                    if !matcher.is_ignored("background_color") {
+                        expectation = expectation.background(r, g, b, a);
+                    }
+                }
+                if let Some(opacity) = props.opacity.filter(|&o| o != 1.0) {
+                    if !matcher.is_ignored("opacity") {
+                        expectation = expectation.opacity(opacity);
+                    }
+                }
+            }
+            Some(expectation)
+        })
+        .collect()
+}
+
+/// Rust expression constructing an expectation.
+fn expectation_expr(e: &ElementExpectation) -> String {
+    if !e.exists {
+        return format!("ElementExpectation::absent({:?})", e.id);
+    }
+    let mut expr = format!("ElementExpectation::new({:?})", e.id);
+    if let Some(b) = e.bounds {
+        let _ = write!(
+            expr,
+            ".bounds({}, {}, {}, {})",
+            float_expr(b.x),
+            float_expr(b.y),
+            float_expr(b.width),
+            float_expr(b.height)
+        );
+    }
+    if let Some(text) = &e.text {
+        let _ = write!(expr, ".text({:?})", text);
+    }
+    if let Some(visible) = e.visible {
+        let _ = write!(expr, ".visible({})", visible);
+    }
+    if let Some(focused) = e.focused {
+        let _ = write!(expr, ".focused({})", focused);
+    }
+    if let Some(hovered) = e.hovered {
+        let _ = write!(expr, ".hovered({})", hovered);
+    }
+    if let Some([r, g, b, a]) = e.background_color {
+        let _ = write!(
+            expr,
+            ".background({}, {}, {}, {})",
+            float_expr(r),
+            float_expr(g),
+            float_expr(b),
+            float_expr(a)
+        );
+    }
+    if let Some(opacity) = e.opacity {
+        let _ = write!(expr, ".opacity({})", float_expr(opacity));
+    }
+    expr
+}
+
+/// Rust source lines constructing a simulated input, ending with a comma.
+fn input_lines(input: &SimulatedInput, imports: &mut BTreeSet<&'static str>) -> Vec<String> {
+    let (variant, fields): (&str, Vec<(&str, String)>) = match input {
+        SimulatedInput::Click {
+            position,
+            button,
+            modifiers,
+        } => (
+            "Click",
+            vec![
+                ("position", point_expr(position, imports)),
+                ("button", button_expr(button, imports)),
+                ("modifiers", modifiers_expr(modifiers, imports)),
+            ],
+        ),
+        SimulatedInput::DoubleClick {
+            position,
+            button,
+            modifiers,
+        } => (
+            "DoubleClick",
+            vec![
+                ("position", point_expr(position, imports)),
+                ("button", button_expr(button, imports)),
+                ("modifiers", modifiers_expr(modifiers, imports)),
+            ],
+        ),
+        SimulatedInput::MouseDown {
+            position,
+            button,
+            modifiers,
+        } => (
+            "MouseDown",
+            vec![
+                ("position", point_expr(position, imports)),
+                ("button", button_expr(button, imports)),
+                ("modifiers", modifiers_expr(modifiers, imports)),
+            ],
+        ),
+        SimulatedInput::MouseUp {
+            position,
+            button,
+            modifiers,
+        } => (
+            "MouseUp",
+            vec![
+                ("position", point_expr(position, imports)),
+                ("button", button_expr(button, imports)),
+                ("modifiers", modifiers_expr(modifiers, imports)),
+            ],
+        ),
+        SimulatedInput::MouseMove {
+            position,
+            hover_element,
+        } => (
+            "MouseMove",
+            vec![
+                ("position", point_expr(position, imports)),
+                ("hover_element", option_string_expr(hover_element)),
+            ],
+        ),
+        SimulatedInput::Scroll {
+            position,
+            delta_x,
+            delta_y,
+            target_element,
+        } => (
+            "Scroll",
+            vec![
+                ("position", point_expr(position, imports)),
+                ("delta_x", float_expr(*delta_x)),
+                ("delta_y", float_expr(*delta_y)),
+                ("target_element", option_string_expr(target_element)),
+            ],
+        ),
+        SimulatedInput::KeyDown {
+            key,
+            modifiers,
+            is_repeat,
+        } => (
+            "KeyDown",
+            vec![
+                ("key", key_expr(key, imports)),
+                ("modifiers", modifiers_expr(modifiers, imports)),
+                ("is_repeat", is_repeat.to_string()),
+            ],
+        ),
+        SimulatedInput::KeyUp { key, modifiers } => (
+            "KeyUp",
+            vec![
+                ("key", key_expr(key, imports)),
+                ("modifiers", modifiers_expr(modifiers, imports)),
+            ],
+        ),
+        SimulatedInput::TextInput { text } => (
+            "TextInput",
+            vec![("text", format!("{:?}.to_string()", text))],
+        ),
+        SimulatedInput::FocusChange { from, to } => (
+            "FocusChange",
+            vec![
+                ("from", option_string_expr(from)),
+                ("to", option_string_expr(to)),
+            ],
+        ),
+        SimulatedInput::HoverEnter {
+            element_id,
+            position,
+        } => (
+            "HoverEnter",
+            vec![
+                ("element_id", format!("{:?}.to_string()", element_id)),
+                ("position", point_expr(position, imports)),
+            ],
+        ),
+        SimulatedInput::HoverLeave {
+            element_id,
+            position,
+        } => (
+            "HoverLeave",
+            vec![
+                ("element_id", format!("{:?}.to_string()", element_id)),
+                ("position", point_expr(position, imports)),
+            ],
+        ),
+        SimulatedInput::WindowResize {
+            width,
+            height,
+            scale_factor,
+        } => (
+            "WindowResize",
+            vec![
+                ("width", width.to_string()),
+                ("height", height.to_string()),
+                (
+                    "scale_factor",
+                    scale_factor.map_or("None".to_string(), |s| format!("Some({})", f64_expr(s))),
+                ),
+            ],
+        ),
+        SimulatedInput::WindowFocus { focused } => {
+            ("WindowFocus", vec![("focused", focused.to_string())])
+        }
+        SimulatedInput::Custom { name, payload } => (
+            "Custom",
+            vec![
+                ("name", format!("{:?}.to_string()", name)),
+                ("payload", option_string_expr(payload)),
+            ],
+        ),
+    };
+
+    let mut lines = vec![format!("SimulatedInput::{} {{", variant)];
+    lines.extend(
+        fields
+            .into_iter()
+            .map(|(name, value)| format!("    {}: {},", name, value)),
+    );
+    lines.push("},".to_string());
+    lines
+}
+
+fn point_expr(point: &Point, imports: &mut BTreeSet<&'static str>) -> String {
+    imports.insert("Point");
+    format!(
+        "Point::new({}, {})",
+        float_expr(point.x),
+        float_expr(point.y)
+    )
+}
+
+fn button_expr(button: &MouseButton, imports: &mut BTreeSet<&'static str>) -> String {
+    imports.insert("MouseButton");
+    format!("MouseButton::{:?}", button)
+}
+
+fn key_expr(key: &Key, imports: &mut BTreeSet<&'static str>) -> String {
+    imports.insert("Key");
+    format!("Key::{:?}", key)
+}
+
+fn modifiers_expr(modifiers: &Modifiers, imports: &mut BTreeSet<&'static str>) -> String {
+    imports.insert("Modifiers");
+    if *modifiers == Modifiers::none() {
+        "Modifiers::none()".to_string()
+    } else {
+        format!(
+            "Modifiers {{ shift: {}, ctrl: {}, alt: {}, meta: {} }}",
+            modifiers.shift, modifiers.ctrl, modifiers.alt, modifiers.meta
+        )
+    }
+}
+
+/// Rust expression for an `f32`; `{:?}` alone writes `NaN` and `inf`.
+fn float_expr(value: f32) -> String {
+    float_literal(format!("{:?}", value), "f32")
+}
+
+fn f64_expr(value: f64) -> String {
+    float_literal(format!("{:?}", value), "f64")
+}
+
+fn float_literal(debug: String, ty: &str) -> String {
+    match debug.as_str() {
+        "NaN" => format!("{}::NAN", ty),
+        "inf" => format!("{}::INFINITY", ty),
+        "-inf" => format!("{}::NEG_INFINITY", ty),
+        _ => debug,
+    }
+}
+
+fn option_string_expr(value: &Option<String>) -> String {
+    match value {
+        Some(s) => format!("Some({:?}.to_string())", s),
+        None => "None".to_string(),
+    }
+}
+
+/// Turn a test name into a valid, snake_case Rust identifier.
+fn test_ident(name: &str) -> String {
+    let mut ident = String::with_capacity(name.len());
+    for c in name.chars() {
+        if c.is_ascii_alphanumeric() {
+            ident.push(c.to_ascii_lowercase());
+        } else if !ident.is_empty() && !ident.ends_with('_') {
+            ident.push('_');
+        }
+    }
+    if ident.ends_with('_') {
+        ident.pop();
+    }
+
+    if ident.is_empty() {
+        "recording".to_string()
+    } else if ident.starts_with(|c: char| c.is_ascii_digit()) {
+        format!("_{}", ident)
+    } else if KEYWORDS.contains(&ident.as_str()) {
+        format!("test_{}", ident)
+    } else {
+        ident
+    }
+}
+
+/// Strict and reserved keywords, which cannot name a function.
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate",
+    "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl",
+    "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref",
+    "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+    "unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::capture::{ElementSnapshot, MouseEvent, Rect, TextInputEvent, TimestampedEvent};
+    use crate::session::{RecordingConfig, SessionStats};
+
+    fn snapshot(micros: u64, count: &str, dialog: bool) -> TreeSnapshot {
+        let mut snapshot = TreeSnapshot::new(Timestamp::from_micros(micros), (400, 300), 1.0);
+        let mut root = ElementSnapshot::new(
+            "0v1".into(),
+            "Div".into(),
+            Rect::new(0.0, 0.0, 400.0, 300.0),
+        );
+        root.children = vec!["1v1".into()];
+        let mut label = ElementSnapshot::new(
+            "1v1".into(),
+            "Text(1)".into(),
+            Rect::new(10.0, 10.0, 20.0, 16.0),
+        );
+        label.parent = Some("0v1".into());
+        label.stable_id = Some("count".into());
+        label.text_content = Some(count.into());
+        if dialog {
+            root.children.push("2v1".into());
+            let mut dialog = ElementSnapshot::new(
+                "2v1".into(),
+                "Div".into(),
+                Rect::new(50.0, 50.0, 200.0, 100.0),
+            );
+            dialog.parent = Some("0v1".into());
+            dialog.stable_id = Some("dialog".into());
+            snapshot.elements.insert(dialog.id.clone(), dialog);
+        }
+        snapshot.elements.insert(root.id.clone(), root);
+        snapshot.elements.insert(label.id.clone(), label);
+        snapshot.root_id = Some("0v1".into());
+        snapshot
+    }
+
+    fn click(micros: u64) -> TimestampedEvent {
+        TimestampedEvent::new(
+            Timestamp::from_micros(micros),
+            RecordedEvent::Click(MouseEvent {
+                position: Point::new(20.0, 18.0),
+                button: MouseButton::Left,
+                modifiers: Modifiers::none(),
+                target_element: None,
+            }),
+        )
+    }
+
+    fn export() -> RecordingExport {
+        RecordingExport {
+            config: RecordingConfig::testing(),
+            events: vec![
+                click(100_000),
+                TimestampedEvent::new(
+                    Timestamp::from_micros(300_000),
+                    RecordedEvent::TextInput(TextInputEvent {
+                        text: "a\"b".into(),
+                        focused_element: None,
+                    }),
+                ),
+            ],
+            snapshots: vec![
+                snapshot(50_000, "0", true),
+                snapshot(80_000, "0", true),
+                snapshot(150_000, "1", false),
+                snapshot(200_000, "1", false),
+                snapshot(350_000, "1", false),
+            ],
+            stats: SessionStats::default(),
+        }
+    }
+
+    #[test]
+    fn test_key_snapshots() {
+        let config = TestGenConfig::default();
+        let times = [
+            Timestamp::from_micros(100_000),
+            Timestamp::from_micros(300_000),
+        ];
+        let asserts = assertions(&export(), &times, &config);
+
+        // 80ms (before the click) and 200ms (before the text input); the
+        // final snapshot matches 200ms and is skipped
+        let at: Vec<u64> = asserts.iter().map(|(at, _)| at.as_micros()).collect();
+        assert_eq!(at, vec![80_000, 200_000]);
+        assert_eq!(
+            asserts[1].1,
+            vec![
+                ElementExpectation::new("#count")
+                    .bounds(10.0, 10.0, 20.0, 16.0)
+                    .text("1"),
+                ElementExpectation::absent("#dialog"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_generate_source() {
+        let config = TestGenConfig::new("Bug 42: counter")
+            .with_app_factory("counter::app")
+            .ignore("bounds")
+            .with_source("bug-42.json");
+        let source = generate_test(&export(), &config);
+
+        assert!(source.starts_with("//! Regression test generated from `bug-42.json`."));
+        assert!(source.contains(
+            "use junita_recorder::{Modifiers, MouseButton, Point, SimulatedInput, Timestamp};"
+        ));
+        assert!(source.contains("fn bug_42_counter() {"));
+        assert!(source.contains("TestConfig::fast().with_size(400, 300)"));
+        assert!(source.contains(".ignore(\"bounds\");"));
+        assert!(source.contains("let mut app = counter::app();"));
+        assert!(source.contains("            SimulatedInput::Click {\n                position: Point::new(20.0, 18.0),"));
+        assert!(source.contains("text: \"a\\\"b\".to_string(),"));
+        assert!(source.contains("ElementExpectation::new(\"#count\").text(\"0\"),"));
+        assert!(source.contains("ElementExpectation::absent(\"#dialog\"),"));
+
+        // Inputs and assertions are interleaved in time order
+        let click = source.find("SimulatedInput::Click").unwrap();
+        let first = source.find("from_micros(80000)").unwrap();
+        let second = source.find("from_micros(200000)").unwrap();
+        let text = source.find("SimulatedInput::TextInput").unwrap();
+        assert!(first < click && click < second && second < text);
+    }
+
+    #[test]
+    fn test_ident_sanitizing() {
+        assert_eq!(test_ident("Login flow"), "login_flow");
+        assert_eq!(test_ident("42-crash"), "_42_crash");
+        assert_eq!(test_ident("Bug 42: counter"), "bug_42_counter");
+        assert_eq!(test_ident(" -- trim me! "), "trim_me");
+        assert_eq!(test_ident(""), "recording");
+        assert_eq!(test_ident("?!"), "recording");
+        assert_eq!(test_ident("Match"), "test_match");
+        assert_eq!(test_ident("type"), "test_type");
+    }
+
+    #[test]
+    fn test_non_finite_floats() {
+        assert_eq!(float_expr(1.5), "1.5");
+        assert_eq!(float_expr(f32::NAN), "f32::NAN");
+        assert_eq!(float_expr(f32::NEG_INFINITY), "f32::NEG_INFINITY");
+        assert_eq!(f64_expr(f64::INFINITY), "f64::INFINITY");
+
+        let expectation = ElementExpectation::new("#spinner").opacity(f32::NAN);
+        assert_eq!(
+            expectation_expr(&expectation),
+            "ElementExpectation::new(\"#spinner\").opacity(f32::NAN)"
+        );
+    }
+}
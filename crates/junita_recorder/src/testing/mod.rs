@@ -4,7 +4,9 @@
 //! - `HeadlessContext` - Run UI without a window for testing
 //! - `TestRunner` - Test harness for running headless tests
 //! - `CapturedFrame` - Framebuffer capture for screenshots and visual testing
+//! - `perceptual_compare` - SSIM and Delta-E frame comparison with ignore masks
 //! - Element assertions for verifying UI state
+//! - `generate_test` - Turn a recording into a replayable regression test
 //!
 //! # Example
 //!
@@ -29,12 +31,22 @@
 //! }
 //! ```
 
+mod expect;
 mod framebuffer;
+mod generate;
 mod headless;
+mod perceptual;
 mod runner;
 
+pub use expect::{stable_ids, ElementExpectation, ReplayTarget, SnapshotMatcher, SnapshotMismatch};
+
 pub use framebuffer::{
     compare_frames, CapturedFrame, FrameSequence, RegressionResult, ScreenshotExporter,
 };
+pub use generate::{generate_test, TestGenConfig};
 pub use headless::{HeadlessConfig, HeadlessContext};
-pub use runner::{TestConfig, TestRunner};
+pub use perceptual::{
+    perceptual_compare, IgnoreRegion, PerceptualDiff, PerceptualOptions, PixelDiff, ToleranceMap,
+    JND_DELTA_E,
+};
+pub use runner::{TestConfig, TestContext, TestRunner};
@@ -0,0 +1,820 @@
+//! Perceptual frame comparison.
+//!
+//! Per-pixel equality flags every anti-aliasing and font hinting difference
+//! between drivers. This module compares frames the way a reviewer would:
+//! - CIEDE2000 color difference (Delta-E) per pixel, with a per-pixel
+//!   tolerance map
+//! - Structural similarity (SSIM) on luminance over sliding windows
+//! - Anti-aliased edge pixels that moved by a sub-pixel amount are forgiven
+//! - Ignore regions exclude content that legitimately varies
+//! - A heatmap frame shows where and how much two frames differ
+
+use super::framebuffer::CapturedFrame;
+
+/// Just noticeable difference for CIEDE2000
+pub const JND_DELTA_E: f32 = 2.3;
+
+/// SSIM window size in pixels
+const SSIM_WINDOW: u32 = 8;
+
+/// Step between SSIM windows
+const SSIM_STEP: u32 = 4;
+
+/// SSIM stabilizers for 8-bit luminance
+const SSIM_C1: f64 = (0.01 * 255.0) * (0.01 * 255.0);
+const SSIM_C2: f64 = (0.03 * 255.0) * (0.03 * 255.0);
+
+/// Minimum luminance step for a neighbour to count as an edge
+const EDGE_CONTRAST: f32 = 24.0;
+
+/// A rectangle of pixels excluded from comparison.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IgnoreRegion {
+    pub x: u32,
+    pub y: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl IgnoreRegion {
+    /// Create an ignore region.
+    pub fn new(x: u32, y: u32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    /// Cover a fractional rectangle, rounding outward to whole pixels.
+    pub fn covering(x: f32, y: f32, width: f32, height: f32) -> Self {
+        let left = x.floor().max(0.0);
+        let top = y.floor().max(0.0);
+        let right = (x + width).ceil().max(left);
+        let bottom = (y + height).ceil().max(top);
+        Self::new(
+            left as u32,
+            top as u32,
+            (right - left) as u32,
+            (bottom - top) as u32,
+        )
+    }
+
+    /// Check whether a pixel lies inside the region.
+    pub fn contains(&self, x: u32, y: u32) -> bool {
+        x >= self.x
+            && y >= self.y
+            && x < self.x.saturating_add(self.width)
+            && y < self.y.saturating_add(self.height)
+    }
+}
+
+/// Per-pixel Delta-E tolerance.
+///
+/// Lets a comparison accept more variation where rendering is known to
+/// differ, such as text or blurred glass, while staying strict elsewhere.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ToleranceMap {
+    width: u32,
+    height: u32,
+    values: Vec<f32>,
+}
+
+impl ToleranceMap {
+    /// Create a map with the same tolerance everywhere.
+    pub fn new(width: u32, height: u32, delta_e: f32) -> Self {
+        Self {
+            width,
+            height,
+            values: vec![delta_e; (width * height) as usize],
+        }
+    }
+
+    /// Build a map from a grayscale mask.
+    ///
+    /// Black pixels get `base` tolerance, white pixels get `max`, and gray
+    /// interpolates between the two.
+    pub fn from_mask(mask: &CapturedFrame, base: f32, max: f32) -> Self {
+        let values = mask
+            .data
+            .chunks_exact(4)
+            .map(|p| {
+                let t = luminance([p[0], p[1], p[2], 255]) / 255.0;
+                base + (max - base) * t
+            })
+            .collect();
+        Self {
+            width: mask.width,
+            height: mask.height,
+            values,
+        }
+    }
+
+    /// Set the tolerance for a rectangle of pixels.
+    pub fn set_region(&mut self, region: IgnoreRegion, delta_e: f32) -> &mut Self {
+        let right = region.x.saturating_add(region.width).min(self.width);
+        let bottom = region.y.saturating_add(region.height).min(self.height);
+        for y in region.y.min(bottom)..bottom {
+            for x in region.x.min(right)..right {
+                self.values[(y * self.width + x) as usize] = delta_e;
+            }
+        }
+        self
+    }
+
+    /// Set the tolerance for a rectangle of pixels.
+    pub fn with_region(mut self, region: IgnoreRegion, delta_e: f32) -> Self {
+        self.set_region(region, delta_e);
+        self
+    }
+
+    /// Get the tolerance at a pixel, if the map covers it.
+    pub fn get(&self, x: u32, y: u32) -> Option<f32> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.values.get((y * self.width + x) as usize).copied()
+    }
+}
+
+/// Options for a perceptual comparison.
+#[derive(Clone, Debug)]
+pub struct PerceptualOptions {
+    /// Delta-E below which pixels count as equal
+    pub delta_e: f32,
+    /// Minimum mean SSIM for the frames to match
+    pub min_ssim: f32,
+    /// Percentage of compared pixels allowed to differ
+    pub max_changed_percent: f32,
+    /// Forgive differences on anti-aliased edges
+    pub antialiasing: bool,
+    /// Regions left out of the comparison
+    pub ignore: Vec<IgnoreRegion>,
+    /// Per-pixel tolerance, overriding `delta_e` where it covers the frame
+    pub tolerance: Option<ToleranceMap>,
+}
+
+impl Default for PerceptualOptions {
+    fn default() -> Self {
+        Self {
+            delta_e: JND_DELTA_E,
+            min_ssim: 0.98,
+            max_changed_percent: 0.1,
+            antialiasing: true,
+            ignore: Vec::new(),
+            tolerance: None,
+        }
+    }
+}
+
+impl PerceptualOptions {
+    /// Create default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the Delta-E tolerance.
+    pub fn with_delta_e(mut self, delta_e: f32) -> Self {
+        self.delta_e = delta_e;
+        self
+    }
+
+    /// Set the minimum SSIM.
+    pub fn with_min_ssim(mut self, min_ssim: f32) -> Self {
+        self.min_ssim = min_ssim;
+        self
+    }
+
+    /// Set the percentage of pixels allowed to differ.
+    pub fn with_max_changed_percent(mut self, percent: f32) -> Self {
+        self.max_changed_percent = percent;
+        self
+    }
+
+    /// Enable or disable anti-aliasing detection.
+    pub fn with_antialiasing(mut self, enabled: bool) -> Self {
+        self.antialiasing = enabled;
+        self
+    }
+
+    /// Exclude a region from the comparison.
+    pub fn ignore_region(mut self, region: IgnoreRegion) -> Self {
+        self.ignore.push(region);
+        self
+    }
+
+    /// Use a per-pixel tolerance map.
+    pub fn with_tolerance_map(mut self, map: ToleranceMap) -> Self {
+        self.tolerance = Some(map);
+        self
+    }
+
+    fn is_ignored(&self, x: u32, y: u32) -> bool {
+        self.ignore.iter().any(|r| r.contains(x, y))
+    }
+
+    fn tolerance_at(&self, x: u32, y: u32) -> f32 {
+        self.tolerance
+            .as_ref()
+            .and_then(|map| map.get(x, y))
+            .unwrap_or(self.delta_e)
+    }
+}
+
+/// How one pixel compared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PixelDiff {
+    /// Within tolerance
+    Same,
+    /// Inside an ignore region
+    Ignored,
+    /// Different, but on an anti-aliased edge
+    AntiAliased,
+    /// Perceptibly different
+    Changed,
+}
+
+/// Result of a perceptual comparison.
+#[derive(Clone, Debug)]
+pub struct PerceptualDiff {
+    /// Width of the compared frames
+    pub width: u32,
+    /// Height of the compared frames
+    pub height: u32,
+    /// Whether the frames had different sizes (nothing else is meaningful then)
+    pub size_mismatch: bool,
+    /// Whether the frames match under the options used
+    pub passed: bool,
+    /// Mean SSIM over luminance (1.0 = identical structure)
+    pub ssim: f32,
+    /// Mean Delta-E over compared pixels
+    pub mean_delta_e: f32,
+    /// Largest Delta-E over compared pixels
+    pub max_delta_e: f32,
+    /// Pixels that differ perceptibly
+    pub changed_pixels: usize,
+    /// Pixels forgiven as anti-aliasing
+    pub antialiased_pixels: usize,
+    /// Pixels inside ignore regions
+    pub ignored_pixels: usize,
+    /// Percentage of compared pixels that changed
+    pub changed_percent: f32,
+    /// Delta-E for every pixel, row-major
+    pub delta_e: Vec<f32>,
+    /// Classification of every pixel, row-major
+    pub pixels: Vec<PixelDiff>,
+}
+
+impl PerceptualDiff {
+    fn mismatched(actual: &CapturedFrame) -> Self {
+        Self {
+            width: actual.width,
+            height: actual.height,
+            size_mismatch: true,
+            passed: false,
+            ssim: 0.0,
+            mean_delta_e: 0.0,
+            max_delta_e: 0.0,
+            changed_pixels: actual.pixel_count(),
+            antialiased_pixels: 0,
+            ignored_pixels: 0,
+            changed_percent: 100.0,
+            delta_e: Vec::new(),
+            pixels: Vec::new(),
+        }
+    }
+
+    /// Get the classification of a pixel.
+    pub fn pixel(&self, x: u32, y: u32) -> Option<PixelDiff> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        self.pixels.get((y * self.width + x) as usize).copied()
+    }
+
+    /// Render a heatmap of the differences over a dimmed copy of `expected`.
+    ///
+    /// Changed pixels go from orange to red with their Delta-E, forgiven
+    /// anti-aliasing is yellow and ignored regions are tinted blue. A size
+    /// mismatch yields a solid red frame.
+    pub fn heatmap(&self, expected: &CapturedFrame) -> CapturedFrame {
+        if self.size_mismatch {
+            let data = [255, 0, 0, 255].repeat((self.width * self.height) as usize);
+            return CapturedFrame::new(data, self.width, self.height);
+        }
+
+        let scale = self.max_delta_e.max(JND_DELTA_E * 4.0);
+        let mut data = Vec::with_capacity(self.pixels.len() * 4);
+        for (i, (class, pixel)) in self
+            .pixels
+            .iter()
+            .zip(expected.data.chunks_exact(4))
+            .enumerate()
+        {
+            let gray = (luminance(composite(pixel)) / 3.0 + 170.0) as u8;
+            let rgba = match class {
+                PixelDiff::Same => [gray, gray, gray, 255],
+                PixelDiff::Ignored => [gray / 2, gray / 2, 200, 255],
+                PixelDiff::AntiAliased => [255, 220, 0, 255],
+                PixelDiff::Changed => {
+                    let t = (self.delta_e[i] / scale).clamp(0.0, 1.0);
+                    [255, (160.0 * (1.0 - t)) as u8, 0, 255]
+                }
+            };
+            data.extend_from_slice(&rgba);
+        }
+        CapturedFrame::new(data, self.width, self.height)
+    }
+}
+
+impl CapturedFrame {
+    /// Compare with an expected frame using perceptual metrics.
+    pub fn perceptual_diff(
+        &self,
+        expected: &CapturedFrame,
+        options: &PerceptualOptions,
+    ) -> PerceptualDiff {
+        perceptual_compare(self, expected, options)
+    }
+}
+
+/// Compare two frames using perceptual metrics.
+pub fn perceptual_compare(
+    actual: &CapturedFrame,
+    expected: &CapturedFrame,
+    options: &PerceptualOptions,
+) -> PerceptualDiff {
+    if actual.width != expected.width
+        || actual.height != expected.height
+        || actual.data.len() < actual.expected_size()
+        || expected.data.len() < expected.expected_size()
+    {
+        return PerceptualDiff::mismatched(actual);
+    }
+
+    let (width, height) = (actual.width, actual.height);
+    let count = actual.pixel_count();
+    let mut delta_e = vec![0.0f32; count];
+    let mut pixels = vec![PixelDiff::Same; count];
+    let (mut changed, mut antialiased, mut ignored) = (0usize, 0usize, 0usize);
+    let (mut sum_delta_e, mut max_delta_e) = (0.0f64, 0.0f32);
+
+    for y in 0..height {
+        for x in 0..width {
+            let i = (y * width + x) as usize;
+            if options.is_ignored(x, y) {
+                pixels[i] = PixelDiff::Ignored;
+                ignored += 1;
+                continue;
+            }
+
+            let a = pixel(actual, x, y);
+            let e = pixel(expected, x, y);
+            let de = if a == e { 0.0 } else { ciede2000(a, e) };
+            delta_e[i] = de;
+            sum_delta_e += de as f64;
+            max_delta_e = max_delta_e.max(de);
+
+            if de <= options.tolerance_at(x, y) {
+                continue;
+            }
+            if options.antialiasing && is_shifted_edge(actual, expected, x, y) {
+                pixels[i] = PixelDiff::AntiAliased;
+                antialiased += 1;
+            } else {
+                pixels[i] = PixelDiff::Changed;
+                changed += 1;
+            }
+        }
+    }
+
+    let compared = count - ignored;
+    let changed_percent = if compared == 0 {
+        0.0
+    } else {
+        changed as f32 / compared as f32 * 100.0
+    };
+    let mean_delta_e = if compared == 0 {
+        0.0
+    } else {
+        (sum_delta_e / compared as f64) as f32
+    };
+    let ssim = mean_ssim(actual, expected, &pixels);
+    let passed = changed_percent <= options.max_changed_percent && ssim >= options.min_ssim;
+
+    PerceptualDiff {
+        width,
+        height,
+        size_mismatch: false,
+        passed,
+        ssim,
+        mean_delta_e,
+        max_delta_e,
+        changed_pixels: changed,
+        antialiased_pixels: antialiased,
+        ignored_pixels: ignored,
+        changed_percent,
+        delta_e,
+        pixels,
+    }
+}
+
+fn pixel(frame: &CapturedFrame, x: u32, y: u32) -> [u8; 4] {
+    let i = ((y * frame.width + x) * 4) as usize;
+    [
+        frame.data[i],
+        frame.data[i + 1],
+        frame.data[i + 2],
+        frame.data[i + 3],
+    ]
+}
+
+/// Blend a pixel over white so transparency compares as visible color
+fn composite(p: &[u8]) -> [u8; 4] {
+    let a = p[3] as f32 / 255.0;
+    let blend = |c: u8| (c as f32 * a + 255.0 * (1.0 - a)).round() as u8;
+    [blend(p[0]), blend(p[1]), blend(p[2]), 255]
+}
+
+/// Rec. 601 luma of a composited pixel (0-255)
+fn luminance(p: [u8; 4]) -> f32 {
+    0.299 * p[0] as f32 + 0.587 * p[1] as f32 + 0.114 * p[2] as f32
+}
+
+fn srgb_to_linear(c: u8) -> f32 {
+    let c = c as f32 / 255.0;
+    if c <= 0.04045 {
+        c / 12.92
+    } else {
+        ((c + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+/// Convert a pixel to CIE L*a*b* (D65), compositing over white first
+fn to_lab(p: [u8; 4]) -> [f32; 3] {
+    let p = composite(&p);
+    let (r, g, b) = (
+        srgb_to_linear(p[0]),
+        srgb_to_linear(p[1]),
+        srgb_to_linear(p[2]),
+    );
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+
+    let f = |t: f32| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+/// CIEDE2000 color difference between two pixels
+fn ciede2000(p1: [u8; 4], p2: [u8; 4]) -> f32 {
+    use std::f32::consts::PI;
+
+    let [l1, a1, b1] = to_lab(p1);
+    let [l2, a2, b2] = to_lab(p2);
+
+    let c1 = (a1 * a1 + b1 * b1).sqrt();
+    let c2 = (a2 * a2 + b2 * b2).sqrt();
+    let c_bar7 = ((c1 + c2) / 2.0).powi(7);
+    let g = 0.5 * (1.0 - (c_bar7 / (c_bar7 + 25f32.powi(7))).sqrt());
+
+    let a1p = a1 * (1.0 + g);
+    let a2p = a2 * (1.0 + g);
+    let c1p = (a1p * a1p + b1 * b1).sqrt();
+    let c2p = (a2p * a2p + b2 * b2).sqrt();
+
+    let hue = |b: f32, a: f32| {
+        if a == 0.0 && b == 0.0 {
+            0.0
+        } else {
+            let h = b.atan2(a).to_degrees();
+            if h < 0.0 {
+                h + 360.0
+            } else {
+                h
+            }
+        }
+    };
+    let h1p = hue(b1, a1p);
+    let h2p = hue(b2, a2p);
+
+    let dlp = l2 - l1;
+    let dcp = c2p - c1p;
+    let dhp = if c1p * c2p == 0.0 {
+        0.0
+    } else if (h2p - h1p).abs() <= 180.0 {
+        h2p - h1p
+    } else if h2p - h1p > 180.0 {
+        h2p - h1p - 360.0
+    } else {
+        h2p - h1p + 360.0
+    };
+    let dhp_big = 2.0 * (c1p * c2p).sqrt() * (dhp.to_radians() / 2.0).sin();
+
+    let lp_bar = (l1 + l2) / 2.0;
+    let cp_bar = (c1p + c2p) / 2.0;
+    let hp_bar = if c1p * c2p == 0.0 {
+        h1p + h2p
+    } else if (h1p - h2p).abs() <= 180.0 {
+        (h1p + h2p) / 2.0
+    } else if h1p + h2p < 360.0 {
+        (h1p + h2p + 360.0) / 2.0
+    } else {
+        (h1p + h2p - 360.0) / 2.0
+    };
+
+    let t = 1.0 - 0.17 * ((hp_bar - 30.0) * PI / 180.0).cos()
+        + 0.24 * ((2.0 * hp_bar) * PI / 180.0).cos()
+        + 0.32 * ((3.0 * hp_bar + 6.0) * PI / 180.0).cos()
+        - 0.20 * ((4.0 * hp_bar - 63.0) * PI / 180.0).cos();
+    let d_theta = 30.0 * (-((hp_bar - 275.0) / 25.0).powi(2)).exp();
+    let cp_bar7 = cp_bar.powi(7);
+    let r_c = 2.0 * (cp_bar7 / (cp_bar7 + 25f32.powi(7))).sqrt();
+    let lp_term = (lp_bar - 50.0).powi(2);
+    let s_l = 1.0 + 0.015 * lp_term / (20.0 + lp_term).sqrt();
+    let s_c = 1.0 + 0.045 * cp_bar;
+    let s_h = 1.0 + 0.015 * cp_bar * t;
+    let r_t = -(2.0 * d_theta * PI / 180.0).sin() * r_c;
+
+    let dl = dlp / s_l;
+    let dc = dcp / s_c;
+    let dh = dhp_big / s_h;
+    (dl * dl + dc * dc + dh * dh + r_t * dc * dh)
+        .max(0.0)
+        .sqrt()
+}
+
+/// Whether a pixel sits on an edge in `frame`
+fn is_edge(frame: &CapturedFrame, x: u32, y: u32) -> bool {
+    let center = luminance(composite(&pixel(frame, x, y)));
+    let (mut darker, mut brighter) = (false, false);
+    for (nx, ny) in neighbours(frame, x, y) {
+        let l = luminance(composite(&pixel(frame, nx, ny)));
+        darker |= l < center - EDGE_CONTRAST;
+        brighter |= l > center + EDGE_CONTRAST;
+    }
+    darker || brighter
+}
+
+/// Whether `color` falls inside the per-channel range of the 3x3 neighbourhood
+fn within_neighbourhood(frame: &CapturedFrame, x: u32, y: u32, color: [u8; 4]) -> bool {
+    let color = composite(&color);
+    let mut lo = composite(&pixel(frame, x, y));
+    let mut hi = lo;
+    for (nx, ny) in neighbours(frame, x, y) {
+        let p = composite(&pixel(frame, nx, ny));
+        for c in 0..3 {
+            lo[c] = lo[c].min(p[c]);
+            hi[c] = hi[c].max(p[c]);
+        }
+    }
+    (0..3).all(|c| color[c] >= lo[c].saturating_sub(2) && color[c] <= hi[c].saturating_add(2))
+}
+
+/// A difference caused by an anti-aliased edge moving by less than a pixel
+///
+/// The pixel has to be on an edge and its color has to be explained by the
+/// other frame's immediate neighbourhood.
+fn is_shifted_edge(actual: &CapturedFrame, expected: &CapturedFrame, x: u32, y: u32) -> bool {
+    (is_edge(actual, x, y) || is_edge(expected, x, y))
+        && within_neighbourhood(expected, x, y, pixel(actual, x, y))
+        && within_neighbourhood(actual, x, y, pixel(expected, x, y))
+}
+
+fn neighbours(frame: &CapturedFrame, x: u32, y: u32) -> impl Iterator<Item = (u32, u32)> {
+    let (w, h) = (frame.width as i64, frame.height as i64);
+    (-1i64..=1)
+        .flat_map(move |dy| (-1i64..=1).map(move |dx| (dx, dy)))
+        .filter(|&(dx, dy)| dx != 0 || dy != 0)
+        .map(move |(dx, dy)| (x as i64 + dx, y as i64 + dy))
+        .filter(move |&(nx, ny)| nx >= 0 && ny >= 0 && nx < w && ny < h)
+        .map(|(nx, ny)| (nx as u32, ny as u32))
+}
+
+/// Mean SSIM over luminance, with ignored pixels taken from `expected`
+fn mean_ssim(actual: &CapturedFrame, expected: &CapturedFrame, pixels: &[PixelDiff]) -> f32 {
+    let (width, height) = (actual.width, actual.height);
+    if width == 0 || height == 0 {
+        return 1.0;
+    }
+
+    let luma = |frame: &CapturedFrame| -> Vec<f64> {
+        frame
+            .data
+            .chunks_exact(4)
+            .map(|p| luminance(composite(p)) as f64)
+            .collect()
+    };
+    let expected_luma = luma(expected);
+    let mut actual_luma = luma(actual);
+    for (i, class) in pixels.iter().enumerate() {
+        if *class == PixelDiff::Ignored {
+            actual_luma[i] = expected_luma[i];
+        }
+    }
+
+    let win_w = SSIM_WINDOW.min(width);
+    let win_h = SSIM_WINDOW.min(height);
+    let starts = |size: u32, win: u32| {
+        let last = size - win;
+        let mut v: Vec<u32> = (0..=last).step_by(SSIM_STEP as usize).collect();
+        if v.last() != Some(&last) {
+            v.push(last);
+        }
+        v
+    };
+
+    let mut total = 0.0f64;
+    let mut windows = 0usize;
+    for &wy in &starts(height, win_h) {
+        for &wx in &starts(width, win_w) {
+            let n = (win_w * win_h) as f64;
+            let (mut sum_a, mut sum_e) = (0.0, 0.0);
+            for y in wy..wy + win_h {
+                for x in wx..wx + win_w {
+                    let i = (y * width + x) as usize;
+                    sum_a += actual_luma[i];
+                    sum_e += expected_luma[i];
+                }
+            }
+            let (mean_a, mean_e) = (sum_a / n, sum_e / n);
+            let (mut var_a, mut var_e, mut cov) = (0.0, 0.0, 0.0);
+            for y in wy..wy + win_h {
+                for x in wx..wx + win_w {
+                    let i = (y * width + x) as usize;
+                    let da = actual_luma[i] - mean_a;
+                    let de = expected_luma[i] - mean_e;
+                    var_a += da * da;
+                    var_e += de * de;
+                    cov += da * de;
+                }
+            }
+            var_a /= n;
+            var_e /= n;
+            cov /= n;
+
+            total += ((2.0 * mean_a * mean_e + SSIM_C1) * (2.0 * cov + SSIM_C2))
+                / ((mean_a * mean_a + mean_e * mean_e + SSIM_C1) * (var_a + var_e + SSIM_C2));
+            windows += 1;
+        }
+    }
+
+    (total / windows as f64) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, color: [u8; 4]) -> CapturedFrame {
+        CapturedFrame::new(color.repeat((width * height) as usize), width, height)
+    }
+
+    fn set(frame: &mut CapturedFrame, x: u32, y: u32, color: [u8; 4]) {
+        let i = ((y * frame.width + x) * 4) as usize;
+        frame.data[i..i + 4].copy_from_slice(&color);
+    }
+
+    /// A black square on white with a gray anti-aliased left edge at `edge_x`
+    fn square(edge_x: u32, edge: [u8; 4]) -> CapturedFrame {
+        let mut frame = solid(32, 32, [255, 255, 255, 255]);
+        for y in 8..24 {
+            set(&mut frame, edge_x, y, edge);
+            for x in edge_x + 1..24 {
+                set(&mut frame, x, y, [0, 0, 0, 255]);
+            }
+        }
+        frame
+    }
+
+    #[test]
+    fn test_identical_frames_pass() {
+        let a = square(8, [128, 128, 128, 255]);
+        let diff = a.perceptual_diff(&a, &PerceptualOptions::default());
+        assert!(diff.passed);
+        assert_eq!(diff.changed_pixels, 0);
+        assert!((diff.ssim - 1.0).abs() < 1e-6);
+        assert_eq!(diff.max_delta_e, 0.0);
+    }
+
+    #[test]
+    fn test_delta_e_known_values() {
+        assert_eq!(ciede2000([10, 20, 30, 255], [10, 20, 30, 255]), 0.0);
+        // Black and white are 100 apart in lightness
+        let bw = ciede2000([0, 0, 0, 255], [255, 255, 255, 255]);
+        assert!((bw - 100.0).abs() < 0.5, "black/white delta-e {}", bw);
+        // One step of gray is below the just noticeable difference
+        assert!(ciede2000([128, 128, 128, 255], [129, 129, 129, 255]) < JND_DELTA_E);
+        // Fully transparent composites to white
+        assert!(ciede2000([0, 0, 0, 0], [255, 255, 255, 255]) < 0.01);
+    }
+
+    #[test]
+    fn test_antialiasing_is_forgiven() {
+        let expected = square(8, [128, 128, 128, 255]);
+        let actual = square(8, [96, 96, 96, 255]);
+
+        let strict = PerceptualOptions::default()
+            .with_antialiasing(false)
+            .with_max_changed_percent(0.0);
+        let diff = actual.perceptual_diff(&expected, &strict);
+        assert_eq!(diff.changed_pixels, 16);
+        assert!(!diff.passed);
+
+        let diff = actual.perceptual_diff(&expected, &strict.clone().with_antialiasing(true));
+        assert_eq!(diff.changed_pixels, 0);
+        assert_eq!(diff.antialiased_pixels, 16);
+        assert_eq!(diff.pixel(8, 10), Some(PixelDiff::AntiAliased));
+        assert!(diff.passed);
+    }
+
+    #[test]
+    fn test_real_change_is_detected() {
+        let expected = square(8, [128, 128, 128, 255]);
+        let mut actual = expected.clone();
+        for y in 0..4 {
+            for x in 0..4 {
+                set(&mut actual, x, y, [255, 0, 0, 255]);
+            }
+        }
+
+        let diff = actual.perceptual_diff(&expected, &PerceptualOptions::default());
+        assert_eq!(diff.changed_pixels, 16);
+        assert_eq!(diff.pixel(0, 0), Some(PixelDiff::Changed));
+        assert!(diff.max_delta_e > 40.0);
+        assert!(diff.ssim < 1.0);
+        assert!(!diff.passed);
+    }
+
+    #[test]
+    fn test_ignore_region_and_tolerance_map() {
+        let expected = solid(16, 16, [200, 200, 200, 255]);
+        let mut actual = expected.clone();
+        set(&mut actual, 2, 2, [0, 0, 0, 255]);
+        set(&mut actual, 12, 12, [180, 180, 180, 255]);
+
+        let options = PerceptualOptions::default()
+            .with_max_changed_percent(0.0)
+            .ignore_region(IgnoreRegion::covering(1.5, 1.5, 2.0, 2.0));
+        let diff = actual.perceptual_diff(&expected, &options);
+        assert_eq!(diff.ignored_pixels, 9);
+        assert_eq!(diff.pixel(2, 2), Some(PixelDiff::Ignored));
+        assert_eq!(diff.pixel(12, 12), Some(PixelDiff::Changed));
+        assert!(!diff.passed);
+
+        let map =
+            ToleranceMap::new(16, 16, JND_DELTA_E).with_region(IgnoreRegion::new(8, 8, 8, 8), 10.0);
+        let diff = actual.perceptual_diff(&expected, &options.with_tolerance_map(map));
+        assert_eq!(diff.changed_pixels, 0);
+        assert!(diff.passed);
+    }
+
+    #[test]
+    fn test_tolerance_map_from_mask() {
+        let mut mask = solid(2, 1, [0, 0, 0, 255]);
+        set(&mut mask, 1, 0, [255, 255, 255, 255]);
+        let map = ToleranceMap::from_mask(&mask, 1.0, 5.0);
+        assert_eq!(map.get(0, 0), Some(1.0));
+        assert_eq!(map.get(1, 0), Some(5.0));
+        assert_eq!(map.get(2, 0), None);
+    }
+
+    #[test]
+    fn test_size_mismatch_fails() {
+        let diff = solid(4, 4, [0, 0, 0, 255])
+            .perceptual_diff(&solid(4, 5, [0, 0, 0, 255]), &PerceptualOptions::default());
+        assert!(diff.size_mismatch);
+        assert!(!diff.passed);
+        assert_eq!(
+            diff.heatmap(&solid(4, 5, [0; 4])).get_pixel(0, 0),
+            Some([255, 0, 0, 255])
+        );
+    }
+
+    #[test]
+    fn test_heatmap_colors() {
+        let expected = square(8, [128, 128, 128, 255]);
+        let mut actual = square(8, [96, 96, 96, 255]);
+        set(&mut actual, 0, 0, [0, 0, 0, 255]);
+        let options = PerceptualOptions::default().ignore_region(IgnoreRegion::new(30, 30, 2, 2));
+        let heatmap = actual
+            .perceptual_diff(&expected, &options)
+            .heatmap(&expected);
+
+        assert_eq!(heatmap.get_pixel(8, 10), Some([255, 220, 0, 255]));
+        let changed = heatmap.get_pixel(0, 0).unwrap();
+        assert_eq!((changed[0], changed[2]), (255, 0));
+        let ignored = heatmap.get_pixel(31, 31).unwrap();
+        assert_eq!(ignored[2], 200);
+        let same = heatmap.get_pixel(4, 20).unwrap();
+        assert_eq!(same[0], same[2]);
+    }
+}
@@ -3,12 +3,17 @@
 //! Provides a structured way to run UI tests with setup, teardown,
 //! and assertion helpers.
 
+use super::expect::{ElementExpectation, ReplayTarget, SnapshotMatcher};
 use super::headless::{HeadlessConfig, HeadlessContext};
 use crate::{
     install_recorder, record_event, RecordedEvent, RecordingConfig, SharedRecordingSession,
+    SimulatedInput, Timestamp, VirtualClock,
 };
 use std::sync::Arc;
 
+/// Virtual time between replayed frames (~60fps).
+const REPLAY_FRAME_MICROS: u64 = 16_667;
+
 /// Configuration for the test runner.
 #[derive(Clone, Debug)]
 pub struct TestConfig {
@@ -117,6 +122,7 @@ impl TestRunner {
         let mut ctx = TestContext {
             headless,
             session: self.session.clone(),
+            clock: VirtualClock::new(Timestamp::from_micros(u64::MAX)),
         };
 
         // Run the test
@@ -143,6 +149,7 @@ impl TestRunner {
 pub struct TestContext {
     headless: HeadlessContext,
     session: Option<Arc<SharedRecordingSession>>,
+    clock: VirtualClock,
 }
 
 impl TestContext {
@@ -213,6 +220,53 @@ impl TestContext {
         record_event(event);
     }
 
+    /// Get the virtual clock used for replay.
+    pub fn clock(&self) -> &VirtualClock {
+        &self.clock
+    }
+
+    /// Advance virtual time to `at`, running a frame every ~16ms.
+    ///
+    /// Does nothing if the clock is already at or past `at`.
+    pub fn advance_to(&mut self, target: &mut impl ReplayTarget, at: Timestamp) {
+        while self.clock.position() < at {
+            let next =
+                (self.clock.position().as_micros() + REPLAY_FRAME_MICROS).min(at.as_micros());
+            self.clock.seek(Timestamp::from_micros(next));
+            target.frame(self.clock.position());
+            self.headless.next_frame();
+        }
+    }
+
+    /// Replay an input at virtual time `at`.
+    pub fn replay(&mut self, target: &mut impl ReplayTarget, at: Timestamp, input: SimulatedInput) {
+        self.advance_to(target, at);
+        target.dispatch(&input);
+    }
+
+    /// Render a frame at virtual time `at` and compare the tree against
+    /// `expectations`.
+    ///
+    /// # Panics
+    ///
+    /// Panics with every difference if the tree does not match.
+    pub fn assert_snapshot(
+        &mut self,
+        target: &mut impl ReplayTarget,
+        at: Timestamp,
+        matcher: &SnapshotMatcher,
+        expectations: &[ElementExpectation],
+    ) {
+        self.advance_to(target, at);
+        // Flush input dispatched since the last frame
+        target.frame(self.clock.position());
+        self.headless.next_frame();
+
+        if let Err(mismatch) = matcher.check(&target.snapshot(), expectations) {
+            panic!("{}", mismatch);
+        }
+    }
+
     /// Get the recording session stats.
     pub fn stats(&self) -> Option<crate::SessionStats> {
         self.session.as_ref().map(|s| s.stats())
@@ -293,6 +347,112 @@ mod tests {
         }
     }
 
+    /// Counter whose label fades in over 100ms after each click.
+    struct Counter {
+        count: u32,
+        clicked_at: Option<Timestamp>,
+        now: Timestamp,
+        frames: u32,
+    }
+
+    impl ReplayTarget for Counter {
+        fn dispatch(&mut self, input: &SimulatedInput) {
+            if let SimulatedInput::Click { .. } = input {
+                self.count += 1;
+                self.clicked_at = Some(self.now);
+            }
+        }
+
+        fn frame(&mut self, now: Timestamp) {
+            self.now = now;
+            self.frames += 1;
+        }
+
+        fn snapshot(&mut self) -> crate::TreeSnapshot {
+            use crate::{ElementSnapshot, Rect, VisualProps};
+
+            let opacity = self.clicked_at.map_or(1.0, |at| {
+                ((self.now.as_micros() - at.as_micros()) as f32 / 100_000.0).min(1.0)
+            });
+            let mut label = ElementSnapshot::new(
+                "0v1".into(),
+                "Text(1)".into(),
+                Rect::new(0.0, 0.0, 10.0 * self.count as f32, 16.0),
+            );
+            label.stable_id = Some("count".into());
+            label.text_content = Some(self.count.to_string());
+            label.visual_props = Some(VisualProps {
+                opacity: Some(opacity),
+                ..Default::default()
+            });
+
+            let mut snapshot = crate::TreeSnapshot::new(self.now, (400, 300), 1.0);
+            snapshot.root_id = Some(label.id.clone());
+            snapshot.elements.insert(label.id.clone(), label);
+            snapshot
+        }
+    }
+
+    #[test]
+    fn test_replay_on_virtual_clock() {
+        use crate::{Modifiers, MouseButton, Point};
+
+        let click = SimulatedInput::Click {
+            position: Point::new(5.0, 5.0),
+            button: MouseButton::Left,
+            modifiers: Modifiers::none(),
+        };
+        let mut app = Counter {
+            count: 0,
+            clicked_at: None,
+            now: Timestamp::zero(),
+            frames: 0,
+        };
+        let matcher = SnapshotMatcher::new().with_bounds_tolerance(0.5);
+
+        TestRunner::fast().run(|ctx| {
+            ctx.replay(&mut app, Timestamp::from_micros(100_000), click.clone());
+            assert_eq!(ctx.clock().position().as_micros(), 100_000);
+            assert_eq!(app.frames, 6);
+
+            ctx.assert_snapshot(
+                &mut app,
+                Timestamp::from_micros(150_000),
+                &matcher,
+                &[ElementExpectation::new("#count")
+                    .bounds(0.0, 0.0, 10.2, 16.0)
+                    .text("1")
+                    .opacity(0.5)],
+            );
+            ctx.assert_snapshot(
+                &mut app,
+                Timestamp::from_micros(250_000),
+                &matcher,
+                &[ElementExpectation::new("#count").text("1").opacity(1.0)],
+            );
+        });
+    }
+
+    #[test]
+    #[should_panic(expected = "#count: text expected \"2\", got Some(\"1\")")]
+    fn test_replay_mismatch_panics() {
+        let mut app = Counter {
+            count: 1,
+            clicked_at: None,
+            now: Timestamp::zero(),
+            frames: 0,
+        };
+
+        TestRunner::fast().run(|ctx| {
+            ctx.assert_snapshot(
+                &mut app,
+                Timestamp::from_micros(16_667),
+                &SnapshotMatcher::new(),
+                &[ElementExpectation::new("#count").text("2")],
+            );
+        });
+    }
+
     #[test]
     fn test_config_builders() {
         let config = TestConfig::default()
@@ -0,0 +1,79 @@
+//! Counter app the checked-in recordings were captured from.
+
+use junita_recorder::{
+    ElementSnapshot, Rect, ReplayTarget, SimulatedInput, Timestamp, TreeSnapshot,
+};
+
+const INCREMENT: Rect = Rect {
+    x: 10.0,
+    y: 10.0,
+    width: 80.0,
+    height: 24.0,
+};
+
+/// A button that increments a counter and a field echoing typed text.
+pub struct Counter {
+    count: u32,
+    name: String,
+    now: Timestamp,
+}
+
+/// Factory used by the generated tests.
+pub fn app() -> Counter {
+    Counter {
+        count: 0,
+        name: String::new(),
+        now: Timestamp::zero(),
+    }
+}
+
+impl ReplayTarget for Counter {
+    fn dispatch(&mut self, input: &SimulatedInput) {
+        match input {
+            SimulatedInput::Click { position, .. } if INCREMENT.contains(*position) => {
+                self.count += 1;
+            }
+            SimulatedInput::TextInput { text } => self.name.push_str(text),
+            _ => {}
+        }
+    }
+
+    fn frame(&mut self, now: Timestamp) {
+        self.now = now;
+    }
+
+    fn snapshot(&mut self) -> TreeSnapshot {
+        let mut snapshot = TreeSnapshot::new(self.now, (200, 120), 1.0);
+        let mut root = ElementSnapshot::new(
+            "0v1".into(),
+            "Div".into(),
+            Rect::new(0.0, 0.0, 200.0, 120.0),
+        );
+        let children = [
+            ("1v1", "increment", INCREMENT, Some("+".to_string())),
+            (
+                "2v1",
+                "count",
+                Rect::new(10.0, 44.0, 60.0, 16.0),
+                Some(self.count.to_string()),
+            ),
+            (
+                "3v1",
+                "name",
+                Rect::new(10.0, 70.0, 180.0, 20.0),
+                Some(self.name.clone()),
+            ),
+        ];
+        for (id, stable_id, bounds, text) in children {
+            let mut element = ElementSnapshot::new(id.into(), "Div".into(), bounds);
+            element.parent = Some(root.id.clone());
+            element.stable_id = Some(stable_id.into());
+            element.text_content = text;
+            root.children.push(element.id.clone());
+            snapshot.elements.insert(element.id.clone(), element);
+        }
+        snapshot.root_id = Some(root.id.clone());
+        snapshot.elements.insert(root.id.clone(), root);
+        snapshot
+    }
+}
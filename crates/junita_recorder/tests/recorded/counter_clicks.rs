@@ -0,0 +1,92 @@
+//! Regression test generated from `counter_clicks.json`.
+//!
+//! Regenerate with `junita test gen counter_clicks.json`.
+
+use junita_recorder::testing::{ElementExpectation, SnapshotMatcher, TestConfig, TestRunner};
+use junita_recorder::{Modifiers, MouseButton, Point, SimulatedInput, Timestamp};
+
+#[test]
+fn counter_clicks() {
+    let mut runner = TestRunner::new(TestConfig::fast().with_size(200, 120));
+    let matcher = SnapshotMatcher::new()
+        .with_bounds_tolerance(0.5);
+
+    runner.run(|ctx| {
+        let mut app = crate::counter::app();
+
+        ctx.assert_snapshot(
+            &mut app,
+            Timestamp::from_micros(50000),
+            &matcher,
+            &[
+                ElementExpectation::new("#increment").bounds(10.0, 10.0, 80.0, 24.0).text("+"),
+                ElementExpectation::new("#count").bounds(10.0, 44.0, 60.0, 16.0).text("0"),
+                ElementExpectation::new("#name").bounds(10.0, 70.0, 180.0, 20.0).text(""),
+            ],
+        );
+        ctx.replay(
+            &mut app,
+            Timestamp::from_micros(100000),
+            SimulatedInput::Click {
+                position: Point::new(50.0, 22.0),
+                button: MouseButton::Left,
+                modifiers: Modifiers::none(),
+            },
+        );
+        ctx.assert_snapshot(
+            &mut app,
+            Timestamp::from_micros(300000),
+            &matcher,
+            &[
+                ElementExpectation::new("#increment").bounds(10.0, 10.0, 80.0, 24.0).text("+"),
+                ElementExpectation::new("#count").bounds(10.0, 44.0, 60.0, 16.0).text("1"),
+                ElementExpectation::new("#name").bounds(10.0, 70.0, 180.0, 20.0).text(""),
+            ],
+        );
+        ctx.replay(
+            &mut app,
+            Timestamp::from_micros(400000),
+            SimulatedInput::Click {
+                position: Point::new(50.5, 22.0),
+                button: MouseButton::Left,
+                modifiers: Modifiers::none(),
+            },
+        );
+        ctx.assert_snapshot(
+            &mut app,
+            Timestamp::from_micros(500000),
+            &matcher,
+            &[
+                ElementExpectation::new("#increment").bounds(10.0, 10.0, 80.0, 24.0).text("+"),
+                ElementExpectation::new("#count").bounds(10.0, 44.0, 60.0, 16.0).text("2"),
+                ElementExpectation::new("#name").bounds(10.0, 70.0, 180.0, 20.0).text(""),
+            ],
+        );
+        ctx.replay(
+            &mut app,
+            Timestamp::from_micros(600000),
+            SimulatedInput::Click {
+                position: Point::new(150.0, 100.0),
+                button: MouseButton::Left,
+                modifiers: Modifiers::none(),
+            },
+        );
+        ctx.replay(
+            &mut app,
+            Timestamp::from_micros(800000),
+            SimulatedInput::TextInput {
+                text: "Ada".to_string(),
+            },
+        );
+        ctx.assert_snapshot(
+            &mut app,
+            Timestamp::from_micros(1000000),
+            &matcher,
+            &[
+                ElementExpectation::new("#increment").bounds(10.0, 10.0, 80.0, 24.0).text("+"),
+                ElementExpectation::new("#count").bounds(10.0, 44.0, 60.0, 16.0).text("2"),
+                ElementExpectation::new("#name").bounds(10.0, 70.0, 180.0, 20.0).text("Ada"),
+            ],
+        );
+    });
+}
@@ -0,0 +1,30 @@
+//! Regression tests generated from recordings of a fixture app.
+//!
+//! Each `.rs` beside a `.json` recording is checked in exactly as
+//! `junita test gen` emits it, so changes to the generator or to the
+//! testing API it targets show up as a stale fixture or a compile error.
+
+mod counter;
+// Kept byte-for-byte as generated
+#[rustfmt::skip]
+mod counter_clicks;
+
+use junita_recorder::{generate_test, load_recording, TestGenConfig};
+use std::path::Path;
+
+#[test]
+fn generated_tests_are_up_to_date() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/recorded");
+    let export = load_recording(dir.join("counter_clicks.json")).unwrap();
+    let config = TestGenConfig::new("counter_clicks")
+        .with_app_factory("crate::counter::app")
+        .with_source("counter_clicks.json");
+
+    let source = generate_test(&export, &config);
+    assert_eq!(
+        source,
+        include_str!("counter_clicks.rs"),
+        "regenerate with `junita test gen tests/recorded/counter_clicks.json \
+         --app crate::counter::app -o tests/recorded/counter_clicks.rs`"
+    );
+}
@@ -0,0 +1,363 @@
+/*
+ * Junita runtime C ABI
+ *
+ * Generated from crates/junita_runtime/src/abi by tests/header.rs; do not
+ * edit. See the `abi` module documentation for ownership rules.
+ */
+
+#ifndef JUNITA_H
+#define JUNITA_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* mod.rs */
+
+// Handle to a signal or derived value
+typedef uint64_t JunitaSignal;
+
+// Handle to an effect
+typedef uint64_t JunitaEffect;
+
+// Handle to a state machine
+typedef uint64_t JunitaFsm;
+
+// Handle to a spring
+typedef uint64_t JunitaSpring;
+
+// Handle to a keyframe animation
+typedef uint64_t JunitaAnimation;
+
+// Handle to a timeline
+typedef uint64_t JunitaTimeline;
+
+// Handle to a widget
+typedef uint64_t JunitaWidget;
+
+// Callback taking only its user data
+typedef void (*JunitaCallback)(void *user_data);
+
+// Message of the last failed call on this thread, or an empty string
+//
+// The pointer stays valid until the next call fails.
+const char *junita_last_error(void);
+
+// Free a string returned by the runtime
+void junita_string_free(char *string);
+
+/* reactive.rs */
+
+// Computes a derived value and stores it with junita_signal_set_*(output, ...)
+typedef void (*JunitaComputeFn)(void *user_data, JunitaSignal output);
+
+// Create a signal holding an int32_t
+JunitaSignal junita_signal_create_i32(int32_t initial);
+
+// Create a signal holding a float
+JunitaSignal junita_signal_create_f32(float initial);
+
+// Create a signal holding a bool
+JunitaSignal junita_signal_create_bool(bool initial);
+
+// Create a signal holding a copy of initial
+JunitaSignal junita_signal_create_string(const char *initial);
+
+// Read a signal as an int32_t
+int32_t junita_signal_get_i32(JunitaSignal signal);
+
+// Read a signal as a float
+float junita_signal_get_f32(JunitaSignal signal);
+
+// Read a signal as a bool
+bool junita_signal_get_bool(JunitaSignal signal);
+
+// Read a signal as a string, or null for a dead handle
+//
+// The string belongs to the caller; free it with junita_string_free.
+char *junita_signal_get_string(JunitaSignal signal);
+
+// Store an int32_t in a signal
+void junita_signal_set_i32(JunitaSignal signal, int32_t value);
+
+// Store a float in a signal
+void junita_signal_set_f32(JunitaSignal signal, float value);
+
+// Store a bool in a signal
+void junita_signal_set_bool(JunitaSignal signal, bool value);
+
+// Store a copy of value in a signal
+void junita_signal_set_string(JunitaSignal signal, const char *value);
+
+// Release a signal; for a derived value this also stops its computation
+void junita_signal_release(JunitaSignal signal);
+
+// Create a derived value
+//
+// Returns a signal that compute fills in, once now and again whenever one
+// of the dep_count signals in deps changes. Release it with
+// junita_signal_release.
+JunitaSignal junita_derived_create(JunitaComputeFn compute, void *user_data, const JunitaSignal *deps, size_t dep_count);
+
+// Create an effect
+//
+// callback runs once now and again whenever one of the dep_count
+// signals in deps changes.
+JunitaEffect junita_effect_create(JunitaCallback callback, void *user_data, const JunitaSignal *deps, size_t dep_count);
+
+// Stop and release an effect
+void junita_effect_release(JunitaEffect effect);
+
+// Hold effects until the matching junita_batch_end
+void junita_batch_start(void);
+
+// Run the effects held since junita_batch_start
+void junita_batch_end(void);
+
+/* fsm.rs */
+
+// Returned by the state machine functions for a dead handle
+#define JUNITA_INVALID_STATE UINT32_MAX
+
+// One row of a transition table
+typedef struct JunitaTransition {
+    uint32_t from;
+    uint32_t event;
+    uint32_t to;
+} JunitaTransition;
+
+// Create a state machine from a transition table
+JunitaFsm junita_fsm_create(uint32_t initial, const JunitaTransition *transitions, size_t count);
+
+// Send an event and return the state the machine is in afterwards
+//
+// Exit callbacks of the old state and entry callbacks of the new one have
+// run by the time this returns. Events with no transition from the current
+// state are ignored.
+uint32_t junita_fsm_send(JunitaFsm fsm, uint32_t event);
+
+// The state a machine is in
+uint32_t junita_fsm_current_state(JunitaFsm fsm);
+
+// Run callback each time the machine enters state
+void junita_fsm_on_enter(JunitaFsm fsm, uint32_t state, JunitaCallback callback, void *user_data);
+
+// Run callback each time the machine leaves state
+void junita_fsm_on_exit(JunitaFsm fsm, uint32_t state, JunitaCallback callback, void *user_data);
+
+// Release a state machine and its callbacks
+void junita_fsm_release(JunitaFsm fsm);
+
+/* animation.rs */
+
+#define JUNITA_EASING_LINEAR 0
+#define JUNITA_EASING_EASE_IN 1
+#define JUNITA_EASING_EASE_OUT 2
+#define JUNITA_EASING_EASE_IN_OUT 3
+#define JUNITA_EASING_EASE_IN_QUAD 4
+#define JUNITA_EASING_EASE_OUT_QUAD 5
+#define JUNITA_EASING_EASE_IN_OUT_QUAD 6
+#define JUNITA_EASING_EASE_IN_CUBIC 7
+#define JUNITA_EASING_EASE_OUT_CUBIC 8
+#define JUNITA_EASING_EASE_IN_OUT_CUBIC 9
+#define JUNITA_EASING_EASE_IN_QUART 10
+#define JUNITA_EASING_EASE_OUT_QUART 11
+#define JUNITA_EASING_EASE_IN_OUT_QUART 12
+
+// A keyframe of a single-value animation
+typedef struct JunitaKeyframe {
+    // Position in the animation, from 0 to 1
+    float time;
+    float value;
+} JunitaKeyframe;
+
+// Create a spring resting at initial
+JunitaSpring junita_spring_create(float stiffness, float damping, float mass, float initial);
+
+// Start a spring moving towards target
+void junita_spring_set_target(JunitaSpring spring, float target);
+
+// Current position of a spring
+float junita_spring_value(JunitaSpring spring);
+
+// Current velocity of a spring
+float junita_spring_velocity(JunitaSpring spring);
+
+// Release a spring
+void junita_spring_release(JunitaSpring spring);
+
+// Create a keyframe animation from count keyframes
+//
+// easing is a JUNITA_EASING_* constant applied between every pair of
+// keyframes. The animation is stopped until junita_keyframe_start.
+JunitaAnimation junita_keyframe_create(uint32_t duration_ms, uint32_t easing, size_t count, const JunitaKeyframe *keyframes);
+
+// Play a keyframe animation from the start
+void junita_keyframe_start(JunitaAnimation animation);
+
+// Current value of a keyframe animation
+float junita_keyframe_value(JunitaAnimation animation);
+
+// Release a keyframe animation
+void junita_keyframe_release(JunitaAnimation animation);
+
+// Create an empty timeline
+JunitaTimeline junita_timeline_create(void);
+
+// Add a value animating from from to to, starting offset_ms into the
+// timeline
+//
+// Returns the entry's index for junita_timeline_value, or -1 on error.
+int32_t junita_timeline_add(JunitaTimeline timeline, int32_t offset_ms, uint32_t duration_ms, float from, float to, uint32_t easing);
+
+// Play a timeline from the start
+void junita_timeline_start(JunitaTimeline timeline);
+
+// Current value of the entry at index
+float junita_timeline_value(JunitaTimeline timeline, uint32_t index);
+
+// Release a timeline
+void junita_timeline_release(JunitaTimeline timeline);
+
+// Advance every animation to the current time
+//
+// Returns whether any are still moving. Not needed inside junita_run.
+bool junita_tick(void);
+
+/* paint.rs */
+
+// Drawing target handed to paint callbacks
+typedef struct JunitaPaintContext JunitaPaintContext;
+
+// Draws a widget of width by height
+typedef void (*JunitaPaintFn)(void *user_data, JunitaPaintContext *ctx, float width, float height);
+
+// Fill a rectangle with rounded corners
+void junita_paint_fill_rect(JunitaPaintContext *ctx, float x, float y, float width, float height, float radius, uint32_t rgba);
+
+// Outline a rectangle with rounded corners
+void junita_paint_stroke_rect(JunitaPaintContext *ctx, float x, float y, float width, float height, float radius, float line_width, uint32_t rgba);
+
+// Fill a circle
+void junita_paint_fill_circle(JunitaPaintContext *ctx, float cx, float cy, float radius, uint32_t rgba);
+
+// Stroke the polyline through point_count points
+//
+// points holds x, y pairs, so it is 2 * point_count floats long.
+void junita_paint_stroke_path(JunitaPaintContext *ctx, const float *points, size_t point_count, bool closed, float line_width, uint32_t rgba);
+
+// Draw a line of text with its top-left corner at x, y
+void junita_paint_draw_text(JunitaPaintContext *ctx, const char *text, float x, float y, float size, uint32_t rgba);
+
+// Push the affine transform [a c tx; b d ty] until the matching pop
+void junita_paint_push_transform(JunitaPaintContext *ctx, float a, float b, float c, float d, float tx, float ty);
+
+// Pop the transform pushed last
+void junita_paint_pop_transform(JunitaPaintContext *ctx);
+
+/* widget.rs */
+
+#define JUNITA_WIDGET_COLUMN 0
+#define JUNITA_WIDGET_ROW 1
+#define JUNITA_WIDGET_CENTER 2
+#define JUNITA_WIDGET_WINDOW 3
+#define JUNITA_WIDGET_TEXT 4
+#define JUNITA_WIDGET_BUTTON 5
+#define JUNITA_WIDGET_SPACER 6
+#define JUNITA_WIDGET_BOX 7
+#define JUNITA_WIDGET_CANVAS 8
+#define JUNITA_PROP_CONTENT 0
+#define JUNITA_PROP_FONT_SIZE 1
+#define JUNITA_PROP_COLOR 2
+#define JUNITA_PROP_FONT_WEIGHT 3
+#define JUNITA_PROP_OPACITY 4
+#define JUNITA_PROP_WIDTH 5
+#define JUNITA_PROP_HEIGHT 6
+#define JUNITA_PROP_MIN_WIDTH 7
+#define JUNITA_PROP_MIN_HEIGHT 8
+#define JUNITA_PROP_PADDING 9
+#define JUNITA_PROP_PADDING_X 10
+#define JUNITA_PROP_PADDING_Y 11
+#define JUNITA_PROP_MARGIN 12
+#define JUNITA_PROP_MARGIN_TOP 13
+#define JUNITA_PROP_MARGIN_BOTTOM 14
+#define JUNITA_PROP_MARGIN_LEFT 15
+#define JUNITA_PROP_MARGIN_RIGHT 16
+#define JUNITA_PROP_SPACING 17
+#define JUNITA_PROP_GROW 18
+#define JUNITA_PROP_DIRECTION 19
+#define JUNITA_PROP_ALIGN 20
+#define JUNITA_PROP_JUSTIFY 21
+#define JUNITA_PROP_BACKGROUND 22
+#define JUNITA_PROP_BORDER_RADIUS 23
+#define JUNITA_PROP_BORDER_COLOR 24
+#define JUNITA_PROP_BORDER_WIDTH 25
+#define JUNITA_EVENT_CLICK 0
+#define JUNITA_EVENT_MOUSE_DOWN 1
+#define JUNITA_EVENT_MOUSE_UP 2
+#define JUNITA_EVENT_HOVER_ENTER 3
+#define JUNITA_EVENT_HOVER_LEAVE 4
+
+// Handles an event at x, y in the widget's local space
+typedef void (*JunitaEventFn)(void *user_data, float x, float y);
+
+// Create a widget of a JUNITA_WIDGET_* type
+JunitaWidget junita_widget_create(uint32_t kind);
+
+// Set a JUNITA_PROP_* to an int32_t
+void junita_widget_set_prop_i32(JunitaWidget widget, uint32_t prop, int32_t value);
+
+// Set a JUNITA_PROP_* to a float
+void junita_widget_set_prop_f32(JunitaWidget widget, uint32_t prop, float value);
+
+// Set a JUNITA_PROP_* to a bool
+void junita_widget_set_prop_bool(JunitaWidget widget, uint32_t prop, bool value);
+
+// Set a JUNITA_PROP_* to a copy of value
+void junita_widget_set_prop_string(JunitaWidget widget, uint32_t prop, const char *value);
+
+// Set a JUNITA_PROP_* to a 0xRRGGBBAA color
+void junita_widget_set_prop_color(JunitaWidget widget, uint32_t prop, uint32_t rgba);
+
+// Move child to the end of parent's children
+//
+// The child's handle is dead afterwards; it is released with its parent.
+void junita_widget_add_child(JunitaWidget parent, JunitaWidget child);
+
+// Make widget the tree the next frame shows, replacing the previous one
+//
+// The handle is dead afterwards; the runtime owns the tree.
+void junita_widget_set_root(JunitaWidget widget);
+
+// Release a widget that was never added to a parent or made the root,
+// along with its children
+void junita_widget_release(JunitaWidget widget);
+
+// Call callback when a JUNITA_EVENT_* happens on widget
+void junita_on_event(JunitaWidget widget, uint32_t event, JunitaEventFn callback, void *user_data);
+
+// Draw widget's content with paint
+//
+// The painter fills the widget and is drawn before its children.
+void junita_widget_on_paint(JunitaWidget widget, JunitaPaintFn paint, void *user_data);
+
+/* app.rs */
+
+// Open a width by height window titled title and run until it closes
+//
+// render is called before each frame is built. Returns 0 when the window
+// closes normally and -1 if the app couldn't start.
+int32_t junita_run(const char *title, uint32_t width, uint32_t height, JunitaCallback render, void *user_data);
+
+// Rebuild the UI before the next frame
+void junita_request_frame(void);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* JUNITA_H */
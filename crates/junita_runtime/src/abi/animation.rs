@@ -0,0 +1,342 @@
+//! Springs, keyframe animations and timelines
+//!
+//! All of them live in the runtime's `AnimationScheduler`. Inside
+//! `junita_run` the app's frame loop advances it; other hosts call
+//! [`junita_tick`] once per frame.
+
+use junita_animation::{
+    Easing, Keyframe, KeyframeAnimation, KeyframeId, Spring, SpringConfig, SpringId, Timeline,
+    TimelineEntryId,
+};
+
+use super::{call, slice_arg, Error, JunitaAnimation, JunitaSpring, JunitaTimeline, Runtime};
+
+pub const JUNITA_EASING_LINEAR: u32 = 0;
+pub const JUNITA_EASING_EASE_IN: u32 = 1;
+pub const JUNITA_EASING_EASE_OUT: u32 = 2;
+pub const JUNITA_EASING_EASE_IN_OUT: u32 = 3;
+pub const JUNITA_EASING_EASE_IN_QUAD: u32 = 4;
+pub const JUNITA_EASING_EASE_OUT_QUAD: u32 = 5;
+pub const JUNITA_EASING_EASE_IN_OUT_QUAD: u32 = 6;
+pub const JUNITA_EASING_EASE_IN_CUBIC: u32 = 7;
+pub const JUNITA_EASING_EASE_OUT_CUBIC: u32 = 8;
+pub const JUNITA_EASING_EASE_IN_OUT_CUBIC: u32 = 9;
+pub const JUNITA_EASING_EASE_IN_QUART: u32 = 10;
+pub const JUNITA_EASING_EASE_OUT_QUART: u32 = 11;
+pub const JUNITA_EASING_EASE_IN_OUT_QUART: u32 = 12;
+
+/// A keyframe of a single-value animation
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct JunitaKeyframe {
+    /// Position in the animation, from 0 to 1
+    pub time: f32,
+    pub value: f32,
+}
+
+fn easing(id: u32) -> Result<Easing, Error> {
+    Ok(match id {
+        JUNITA_EASING_LINEAR => Easing::Linear,
+        JUNITA_EASING_EASE_IN => Easing::EaseIn,
+        JUNITA_EASING_EASE_OUT => Easing::EaseOut,
+        JUNITA_EASING_EASE_IN_OUT => Easing::EaseInOut,
+        JUNITA_EASING_EASE_IN_QUAD => Easing::EaseInQuad,
+        JUNITA_EASING_EASE_OUT_QUAD => Easing::EaseOutQuad,
+        JUNITA_EASING_EASE_IN_OUT_QUAD => Easing::EaseInOutQuad,
+        JUNITA_EASING_EASE_IN_CUBIC => Easing::EaseInCubic,
+        JUNITA_EASING_EASE_OUT_CUBIC => Easing::EaseOutCubic,
+        JUNITA_EASING_EASE_IN_OUT_CUBIC => Easing::EaseInOutCubic,
+        JUNITA_EASING_EASE_IN_QUART => Easing::EaseInQuart,
+        JUNITA_EASING_EASE_OUT_QUART => Easing::EaseOutQuart,
+        JUNITA_EASING_EASE_IN_OUT_QUART => Easing::EaseInOutQuart,
+        id => return Err(Error::UnknownId { kind: "easing", id }),
+    })
+}
+
+fn spring(rt: &Runtime, handle: JunitaSpring) -> Result<SpringId, Error> {
+    rt.springs
+        .get(&handle)
+        .copied()
+        .ok_or(Error::InvalidHandle {
+            kind: "spring",
+            handle,
+        })
+}
+
+fn keyframe(rt: &Runtime, handle: JunitaAnimation) -> Result<KeyframeId, Error> {
+    rt.keyframes
+        .get(&handle)
+        .copied()
+        .ok_or(Error::InvalidHandle {
+            kind: "animation",
+            handle,
+        })
+}
+
+fn timeline<R>(
+    rt: &mut Runtime,
+    handle: JunitaTimeline,
+    f: impl FnOnce(&mut Timeline, &mut Vec<TimelineEntryId>) -> R,
+) -> Result<R, Error> {
+    let scheduler = rt.scheduler.handle();
+    rt.timelines
+        .get_mut(&handle)
+        .and_then(|(id, entries)| scheduler.with_timeline(*id, |timeline| f(timeline, entries)))
+        .ok_or(Error::InvalidHandle {
+            kind: "timeline",
+            handle,
+        })
+}
+
+/// Create a spring resting at `initial`
+#[no_mangle]
+pub extern "C" fn junita_spring_create(
+    stiffness: f32,
+    damping: f32,
+    mass: f32,
+    initial: f32,
+) -> JunitaSpring {
+    call(0, |rt| {
+        let config = SpringConfig::new(stiffness, damping, mass);
+        let id = rt.scheduler.add_spring(Spring::new(config, initial));
+        let handle = rt.next_handle();
+        rt.springs.insert(handle, id);
+        Ok(handle)
+    })
+}
+
+/// Start a spring moving towards `target`
+#[no_mangle]
+pub extern "C" fn junita_spring_set_target(spring: JunitaSpring, target: f32) {
+    call((), |rt| {
+        let id = self::spring(rt, spring)?;
+        rt.scheduler.set_spring_target(id, target);
+        Ok(())
+    })
+}
+
+/// Current position of a spring
+#[no_mangle]
+pub extern "C" fn junita_spring_value(spring: JunitaSpring) -> f32 {
+    call(0.0, |rt| {
+        let id = self::spring(rt, spring)?;
+        Ok(rt.scheduler.get_spring_value(id).unwrap_or_default())
+    })
+}
+
+/// Current velocity of a spring
+#[no_mangle]
+pub extern "C" fn junita_spring_velocity(spring: JunitaSpring) -> f32 {
+    call(0.0, |rt| {
+        let id = self::spring(rt, spring)?;
+        Ok(rt
+            .scheduler
+            .get_spring(id)
+            .map_or(0.0, |spring| spring.velocity()))
+    })
+}
+
+/// Release a spring
+#[no_mangle]
+pub extern "C" fn junita_spring_release(spring: JunitaSpring) {
+    call((), |rt| {
+        let id = self::spring(rt, spring)?;
+        rt.springs.remove(&spring);
+        rt.scheduler.remove_spring(id);
+        Ok(())
+    })
+}
+
+/// Create a keyframe animation from `count` keyframes
+///
+/// `easing` is a `JUNITA_EASING_*` constant applied between every pair of
+/// keyframes. The animation is stopped until [`junita_keyframe_start`].
+///
+/// # Safety
+///
+/// `keyframes` must point to `count` keyframes.
+#[no_mangle]
+pub unsafe extern "C" fn junita_keyframe_create(
+    duration_ms: u32,
+    easing: u32,
+    count: usize,
+    keyframes: *const JunitaKeyframe,
+) -> JunitaAnimation {
+    call(0, |rt| {
+        let easing = self::easing(easing)?;
+        let keyframes = slice_arg(keyframes, count, "keyframes")?
+            .iter()
+            .map(|k| Keyframe {
+                time: k.time,
+                value: k.value,
+                easing,
+            })
+            .collect();
+        let id = rt
+            .scheduler
+            .add_keyframe(KeyframeAnimation::new(duration_ms, keyframes));
+        let handle = rt.next_handle();
+        rt.keyframes.insert(handle, id);
+        Ok(handle)
+    })
+}
+
+/// Play a keyframe animation from the start
+#[no_mangle]
+pub extern "C" fn junita_keyframe_start(animation: JunitaAnimation) {
+    call((), |rt| {
+        let id = keyframe(rt, animation)?;
+        rt.scheduler.start_keyframe(id);
+        Ok(())
+    })
+}
+
+/// Current value of a keyframe animation
+#[no_mangle]
+pub extern "C" fn junita_keyframe_value(animation: JunitaAnimation) -> f32 {
+    call(0.0, |rt| {
+        let id = keyframe(rt, animation)?;
+        Ok(rt.scheduler.get_keyframe_value(id).unwrap_or_default())
+    })
+}
+
+/// Release a keyframe animation
+#[no_mangle]
+pub extern "C" fn junita_keyframe_release(animation: JunitaAnimation) {
+    call((), |rt| {
+        let id = keyframe(rt, animation)?;
+        rt.keyframes.remove(&animation);
+        rt.scheduler.remove_keyframe(id);
+        Ok(())
+    })
+}
+
+/// Create an empty timeline
+#[no_mangle]
+pub extern "C" fn junita_timeline_create() -> JunitaTimeline {
+    call(0, |rt| {
+        let id = rt.scheduler.add_timeline(Timeline::new());
+        let handle = rt.next_handle();
+        rt.timelines.insert(handle, (id, Vec::new()));
+        Ok(handle)
+    })
+}
+
+/// Add a value animating from `from` to `to`, starting `offset_ms` into the
+/// timeline
+///
+/// Returns the entry's index for [`junita_timeline_value`], or -1 on error.
+#[no_mangle]
+pub extern "C" fn junita_timeline_add(
+    timeline: JunitaTimeline,
+    offset_ms: i32,
+    duration_ms: u32,
+    from: f32,
+    to: f32,
+    easing: u32,
+) -> i32 {
+    call(-1, |rt| {
+        let easing = self::easing(easing)?;
+        self::timeline(rt, timeline, |timeline, entries| {
+            entries.push(timeline.add_with_easing(offset_ms, duration_ms, from, to, easing));
+            entries.len() as i32 - 1
+        })
+    })
+}
+
+/// Play a timeline from the start
+#[no_mangle]
+pub extern "C" fn junita_timeline_start(timeline: JunitaTimeline) {
+    call((), |rt| {
+        self::timeline(rt, timeline, |timeline, _| timeline.start())
+    })
+}
+
+/// Current value of the entry at `index`
+#[no_mangle]
+pub extern "C" fn junita_timeline_value(timeline: JunitaTimeline, index: u32) -> f32 {
+    call(0.0, |rt| {
+        self::timeline(rt, timeline, |timeline, entries| {
+            entries
+                .get(index as usize)
+                .and_then(|&entry| timeline.value(entry))
+                .unwrap_or_default()
+        })
+    })
+}
+
+/// Release a timeline
+#[no_mangle]
+pub extern "C" fn junita_timeline_release(timeline: JunitaTimeline) {
+    call((), |rt| {
+        let (id, _) = rt.timelines.remove(&timeline).ok_or(Error::InvalidHandle {
+            kind: "timeline",
+            handle: timeline,
+        })?;
+        rt.scheduler.remove_timeline(id);
+        Ok(())
+    })
+}
+
+/// Advance every animation to the current time
+///
+/// Returns whether any are still moving. Not needed inside `junita_run`.
+#[no_mangle]
+pub extern "C" fn junita_tick() -> bool {
+    call(false, |rt| Ok(rt.scheduler.tick()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spring() {
+        let spring = junita_spring_create(400.0, 30.0, 1.0, 10.0);
+        assert_eq!(junita_spring_value(spring), 10.0);
+        assert_eq!(junita_spring_velocity(spring), 0.0);
+        junita_spring_set_target(spring, 20.0);
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        assert!(junita_tick());
+        assert!(junita_spring_value(spring) > 10.0);
+        junita_spring_release(spring);
+        assert_eq!(junita_spring_value(spring), 0.0);
+    }
+
+    #[test]
+    fn test_keyframes_and_timelines() {
+        unsafe {
+            let keyframes = [
+                JunitaKeyframe {
+                    time: 0.0,
+                    value: 0.5,
+                },
+                JunitaKeyframe {
+                    time: 1.0,
+                    value: 1.0,
+                },
+            ];
+            let animation = junita_keyframe_create(
+                200,
+                JUNITA_EASING_EASE_OUT,
+                keyframes.len(),
+                keyframes.as_ptr(),
+            );
+            assert_eq!(junita_keyframe_value(animation), 0.5);
+            assert_eq!(junita_keyframe_create(200, 99, 0, std::ptr::null()), 0);
+        }
+
+        let timeline = junita_timeline_create();
+        assert_eq!(
+            junita_timeline_add(timeline, 0, 100, 0.0, 1.0, JUNITA_EASING_LINEAR),
+            0
+        );
+        assert_eq!(
+            junita_timeline_add(timeline, 50, 100, 5.0, 6.0, JUNITA_EASING_LINEAR),
+            1
+        );
+        assert_eq!(junita_timeline_value(timeline, 1), 5.0);
+        junita_timeline_release(timeline);
+        assert_eq!(junita_timeline_add(timeline, 0, 1, 0.0, 1.0, 0), -1);
+    }
+}
@@ -0,0 +1,91 @@
+//! Running an app
+//!
+//! [`junita_run`] opens a window and calls the render callback whenever the
+//! UI needs rebuilding: on start, on resize and after anything the UI may
+//! depend on changes (a signal, a state machine, the root widget, a running
+//! animation or [`junita_request_frame`]). The callback builds a widget tree
+//! and passes it to `junita_widget_set_root`.
+
+use std::ffi::{c_char, c_void};
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+use junita_app::windowed::{WindowedApp, WindowedContext};
+use junita_app::WindowConfig;
+use junita_layout::prelude::*;
+
+use super::widget::build_root;
+use super::{call, run_pending, str_arg, Error, JunitaCallback};
+
+/// Set up the runtime for the app's first frame
+fn attach(ctx: &WindowedContext) {
+    let dirty = ctx.dirty_flag();
+    call((), |rt| {
+        rt.dirty = Some(Arc::clone(&dirty));
+        let scheduler = Arc::clone(&rt.scheduler);
+        junita_animation::get_scheduler().register_tick_callback(move |_| {
+            if scheduler.tick() {
+                dirty.store(true, Ordering::SeqCst);
+            }
+        });
+        Ok(())
+    })
+}
+
+/// Open a `width` by `height` window titled `title` and run until it closes
+///
+/// `render` is called before each frame is built. Returns 0 when the window
+/// closes normally and -1 if the app couldn't start.
+///
+/// # Safety
+///
+/// `title` must be a NUL-terminated string and `user_data` must stay valid
+/// until this returns.
+#[no_mangle]
+pub unsafe extern "C" fn junita_run(
+    title: *const c_char,
+    width: u32,
+    height: u32,
+    render: JunitaCallback,
+    user_data: *mut c_void,
+) -> i32 {
+    let title = match str_arg(title, "title") {
+        Ok(title) => title.to_owned(),
+        Err(err) => return call(-1, |_| Err(err)),
+    };
+    let config = WindowConfig {
+        title,
+        width,
+        height,
+        ..WindowConfig::default()
+    };
+
+    let mut attached = false;
+    let result = WindowedApp::run(config, move |ctx| {
+        if !attached {
+            attach(ctx);
+            attached = true;
+        }
+        if let Some(render) = render {
+            render(user_data);
+            run_pending();
+        }
+        let root = div().w(ctx.width).h(ctx.height);
+        match build_root() {
+            Some(tree) => root.child(tree),
+            None => root,
+        }
+    });
+    call(-1, |_| {
+        result.map(|_| 0).map_err(|err| Error::App(err.to_string()))
+    })
+}
+
+/// Rebuild the UI before the next frame
+#[no_mangle]
+pub extern "C" fn junita_request_frame() {
+    call((), |rt| {
+        rt.mark_dirty();
+        Ok(())
+    })
+}
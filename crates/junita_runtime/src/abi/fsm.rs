@@ -0,0 +1,221 @@
+//! State machines
+//!
+//! States and events are the numeric ids the compiler assigns. Guards are
+//! plain expressions in the DSL, so compiled code evaluates them before
+//! calling [`junita_fsm_send`] and only sends events whose guard passed.
+
+use std::ffi::c_void;
+
+use junita_core::{FsmId, StateId, Transition};
+
+use super::{call, slice_arg, Callback, Error, JunitaCallback, JunitaFsm, Runtime};
+
+/// Returned by the state machine functions for a dead handle
+pub const JUNITA_INVALID_STATE: u32 = u32::MAX;
+
+/// One row of a transition table
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct JunitaTransition {
+    pub from: u32,
+    pub event: u32,
+    pub to: u32,
+}
+
+fn machine(rt: &Runtime, fsm: JunitaFsm) -> Result<FsmId, Error> {
+    rt.fsms
+        .get(&fsm)
+        .map(|(id, _)| *id)
+        .ok_or(Error::InvalidHandle {
+            kind: "state machine",
+            handle: fsm,
+        })
+}
+
+unsafe fn on_state(
+    fsm: JunitaFsm,
+    state: StateId,
+    callback: JunitaCallback,
+    user_data: *mut c_void,
+    exit: bool,
+) {
+    call((), |rt| {
+        let callback = callback.ok_or(Error::Null("callback"))?;
+        let id = machine(rt, fsm)?;
+        let handle = rt.next_handle();
+        let pending = rt.pending.clone();
+        let queue = move || pending.lock().unwrap().push_back(handle);
+        let machine = rt.fsm_runtime.get_mut(id).expect("registered machine");
+        if exit {
+            machine.on_exit(state, queue);
+        } else {
+            machine.on_enter(state, queue);
+        }
+        rt.callbacks
+            .insert(handle, Callback::Plain(callback, user_data));
+        rt.fsms
+            .get_mut(&fsm)
+            .expect("registered machine")
+            .1
+            .push(handle);
+        Ok(())
+    })
+}
+
+/// Create a state machine from a transition table
+///
+/// # Safety
+///
+/// `transitions` must point to `count` transitions.
+#[no_mangle]
+pub unsafe extern "C" fn junita_fsm_create(
+    initial: u32,
+    transitions: *const JunitaTransition,
+    count: usize,
+) -> JunitaFsm {
+    call(0, |rt| {
+        let transitions = slice_arg(transitions, count, "transitions")?
+            .iter()
+            .map(|t| Transition::new(t.from, t.event, t.to))
+            .collect();
+        let id = rt.fsm_runtime.create_simple(initial, transitions);
+        let handle = rt.next_handle();
+        rt.fsms.insert(handle, (id, Vec::new()));
+        Ok(handle)
+    })
+}
+
+/// Send an event and return the state the machine is in afterwards
+///
+/// Exit callbacks of the old state and entry callbacks of the new one have
+/// run by the time this returns. Events with no transition from the current
+/// state are ignored.
+#[no_mangle]
+pub extern "C" fn junita_fsm_send(fsm: JunitaFsm, event: u32) -> u32 {
+    call(JUNITA_INVALID_STATE, |rt| {
+        let id = machine(rt, fsm)?;
+        let before = rt.fsm_runtime.current_state(id);
+        let after = rt
+            .fsm_runtime
+            .send(id, event)
+            .unwrap_or(JUNITA_INVALID_STATE);
+        if before != Some(after) {
+            rt.mark_dirty();
+        }
+        Ok(after)
+    })
+}
+
+/// The state a machine is in
+#[no_mangle]
+pub extern "C" fn junita_fsm_current_state(fsm: JunitaFsm) -> u32 {
+    call(JUNITA_INVALID_STATE, |rt| {
+        let id = machine(rt, fsm)?;
+        Ok(rt
+            .fsm_runtime
+            .current_state(id)
+            .unwrap_or(JUNITA_INVALID_STATE))
+    })
+}
+
+/// Run `callback` each time the machine enters `state`
+///
+/// # Safety
+///
+/// `user_data` must stay valid until the machine is released.
+#[no_mangle]
+pub unsafe extern "C" fn junita_fsm_on_enter(
+    fsm: JunitaFsm,
+    state: u32,
+    callback: JunitaCallback,
+    user_data: *mut c_void,
+) {
+    on_state(fsm, state, callback, user_data, false)
+}
+
+/// Run `callback` each time the machine leaves `state`
+///
+/// # Safety
+///
+/// `user_data` must stay valid until the machine is released.
+#[no_mangle]
+pub unsafe extern "C" fn junita_fsm_on_exit(
+    fsm: JunitaFsm,
+    state: u32,
+    callback: JunitaCallback,
+    user_data: *mut c_void,
+) {
+    on_state(fsm, state, callback, user_data, true)
+}
+
+/// Release a state machine and its callbacks
+#[no_mangle]
+pub extern "C" fn junita_fsm_release(fsm: JunitaFsm) {
+    call((), |rt| {
+        let (id, callbacks) = rt.fsms.remove(&fsm).ok_or(Error::InvalidHandle {
+            kind: "state machine",
+            handle: fsm,
+        })?;
+        rt.fsm_runtime.remove(id);
+        for callback in callbacks {
+            rt.callbacks.remove(&callback);
+        }
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const IDLE: u32 = 0;
+    const LOADING: u32 = 1;
+    const FETCH: u32 = 0;
+    const DONE: u32 = 1;
+
+    unsafe extern "C" fn count(user_data: *mut c_void) {
+        *(user_data as *mut u32) += 1;
+    }
+
+    #[test]
+    fn test_transitions_and_callbacks() {
+        unsafe {
+            let table = [
+                JunitaTransition {
+                    from: IDLE,
+                    event: FETCH,
+                    to: LOADING,
+                },
+                JunitaTransition {
+                    from: LOADING,
+                    event: DONE,
+                    to: IDLE,
+                },
+            ];
+            let fsm = junita_fsm_create(IDLE, table.as_ptr(), table.len());
+            let (mut entered, mut exited) = (0u32, 0u32);
+            junita_fsm_on_enter(
+                fsm,
+                LOADING,
+                Some(count),
+                &mut entered as *mut u32 as *mut c_void,
+            );
+            junita_fsm_on_exit(
+                fsm,
+                LOADING,
+                Some(count),
+                &mut exited as *mut u32 as *mut c_void,
+            );
+
+            assert_eq!(junita_fsm_send(fsm, DONE), IDLE);
+            assert_eq!(junita_fsm_send(fsm, FETCH), LOADING);
+            assert_eq!((entered, exited), (1, 0));
+            assert_eq!(junita_fsm_send(fsm, DONE), IDLE);
+            assert_eq!((entered, exited), (1, 1));
+            assert_eq!(junita_fsm_current_state(fsm), IDLE);
+
+            junita_fsm_release(fsm);
+            assert_eq!(junita_fsm_current_state(fsm), JUNITA_INVALID_STATE);
+        }
+    }
+}
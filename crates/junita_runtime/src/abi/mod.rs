@@ -0,0 +1,352 @@
+//! C ABI
+//!
+//! The functions compiled `.junita` code calls, as declared by the `extern`
+//! preamble in `grammars/junita.zyn`, exported with C linkage over
+//! `ReactiveGraph`, `FsmRuntime`, the animation scheduler and `Div`. The C
+//! declarations live in `include/junita.h`, generated from this module by
+//! `tests/header.rs`.
+//!
+//! # Ownership
+//!
+//! - Every object is a 64-bit handle. Handles are never reused and 0 is never
+//!   valid. Functions given a dead handle do nothing and return zero; the
+//!   reason is in [`junita_last_error`].
+//! - The caller owns what it creates until it passes it to the matching
+//!   `junita_*_release`. Widgets are the exception: `junita_widget_add_child`
+//!   moves the child into its parent and `junita_widget_set_root` moves a
+//!   tree into the runtime, after which their handles are dead.
+//! - Strings passed in must be NUL-terminated UTF-8 and are copied. Strings
+//!   returned belong to the caller and are freed with [`junita_string_free`].
+//! - `user_data` is passed back to callbacks untouched and never freed. It
+//!   must stay valid while the handle it was registered with is alive, and
+//!   for widgets while a tree containing them can still be displayed.
+//! - The runtime is per thread: call everything from the UI thread.
+//!
+//! # Callbacks
+//!
+//! Callbacks never run while the runtime is borrowed, so they may call any
+//! function here. Effects and state machine actions are queued and run before
+//! the call that triggered them returns.
+
+pub mod animation;
+pub mod app;
+pub mod fsm;
+pub mod paint;
+pub mod reactive;
+pub mod widget;
+
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, VecDeque};
+use std::ffi::{c_char, c_void, CStr, CString};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use junita_animation::{AnimationScheduler, KeyframeId, SpringId, TimelineEntryId, TimelineId};
+use junita_core::reactive::{Effect, ReactiveGraph, Signal};
+use junita_core::{Color, FsmId, FsmRuntime};
+
+use self::widget::WidgetNode;
+
+/// Handle to a signal or derived value
+pub type JunitaSignal = u64;
+/// Handle to an effect
+pub type JunitaEffect = u64;
+/// Handle to a state machine
+pub type JunitaFsm = u64;
+/// Handle to a spring
+pub type JunitaSpring = u64;
+/// Handle to a keyframe animation
+pub type JunitaAnimation = u64;
+/// Handle to a timeline
+pub type JunitaTimeline = u64;
+/// Handle to a widget
+pub type JunitaWidget = u64;
+
+/// Callback taking only its user data
+pub type JunitaCallback = Option<unsafe extern "C" fn(user_data: *mut c_void)>;
+
+/// Errors reported through [`junita_last_error`]
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum Error {
+    #[error("invalid {kind} handle {handle}")]
+    InvalidHandle { kind: &'static str, handle: u64 },
+    #[error("unknown {kind} id {id}")]
+    UnknownId { kind: &'static str, id: u32 },
+    #[error("`{0}` is null")]
+    Null(&'static str),
+    #[error("`{0}` is not valid UTF-8")]
+    Utf8(&'static str),
+    #[error("the runtime was called from inside a call on the same thread")]
+    Reentrant,
+    #[error("{0}")]
+    App(String),
+}
+
+/// A value held by a signal; getters convert between the four types
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum Value {
+    I32(i32),
+    F32(f32),
+    Bool(bool),
+    String(String),
+}
+
+impl Value {
+    pub(crate) fn as_i32(&self) -> i32 {
+        match self {
+            Value::I32(v) => *v,
+            Value::F32(v) => *v as i32,
+            Value::Bool(v) => *v as i32,
+            Value::String(v) => v.trim().parse::<f64>().map_or(0, |v| v as i32),
+        }
+    }
+
+    pub(crate) fn as_f32(&self) -> f32 {
+        match self {
+            Value::I32(v) => *v as f32,
+            Value::F32(v) => *v,
+            Value::Bool(v) => *v as i32 as f32,
+            Value::String(v) => v.trim().parse().unwrap_or(0.0),
+        }
+    }
+
+    pub(crate) fn as_bool(&self) -> bool {
+        match self {
+            Value::I32(v) => *v != 0,
+            Value::F32(v) => *v != 0.0,
+            Value::Bool(v) => *v,
+            Value::String(v) => !v.is_empty(),
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::I32(v) => write!(f, "{}", v),
+            Value::F32(v) => write!(f, "{}", v),
+            Value::Bool(v) => write!(f, "{}", v),
+            Value::String(v) => f.write_str(v),
+        }
+    }
+}
+
+/// A registered C callback
+#[derive(Clone, Copy)]
+pub(crate) enum Callback {
+    Plain(unsafe extern "C" fn(*mut c_void), *mut c_void),
+    /// Derived value computation writing into its output signal
+    Compute(
+        unsafe extern "C" fn(*mut c_void, JunitaSignal),
+        *mut c_void,
+        JunitaSignal,
+    ),
+}
+
+impl Callback {
+    unsafe fn invoke(self) {
+        match self {
+            Callback::Plain(f, user_data) => f(user_data),
+            Callback::Compute(f, user_data, output) => f(user_data, output),
+        }
+    }
+}
+
+/// State behind the exported functions
+pub(crate) struct Runtime {
+    next_handle: u64,
+    pub(crate) graph: ReactiveGraph,
+    pub(crate) signals: HashMap<JunitaSignal, Signal<Value>>,
+    pub(crate) effects: HashMap<u64, Effect>,
+    pub(crate) callbacks: HashMap<u64, Callback>,
+    /// Callbacks waiting to run, filled from inside the graph and machines
+    pub(crate) pending: Arc<Mutex<VecDeque<u64>>>,
+    pub(crate) fsm_runtime: FsmRuntime,
+    pub(crate) fsms: HashMap<JunitaFsm, (FsmId, Vec<u64>)>,
+    pub(crate) scheduler: Arc<AnimationScheduler>,
+    pub(crate) springs: HashMap<JunitaSpring, SpringId>,
+    pub(crate) keyframes: HashMap<JunitaAnimation, KeyframeId>,
+    pub(crate) timelines: HashMap<JunitaTimeline, (TimelineId, Vec<TimelineEntryId>)>,
+    pub(crate) widgets: HashMap<JunitaWidget, WidgetNode>,
+    pub(crate) root: Option<WidgetNode>,
+    /// Set while an app is running; raising it rebuilds the UI
+    pub(crate) dirty: Option<Arc<AtomicBool>>,
+}
+
+impl Runtime {
+    fn new() -> Self {
+        Self {
+            next_handle: 1,
+            graph: ReactiveGraph::new(),
+            signals: HashMap::new(),
+            effects: HashMap::new(),
+            callbacks: HashMap::new(),
+            pending: Arc::new(Mutex::new(VecDeque::new())),
+            fsm_runtime: FsmRuntime::new(),
+            fsms: HashMap::new(),
+            scheduler: Arc::new(AnimationScheduler::new()),
+            springs: HashMap::new(),
+            keyframes: HashMap::new(),
+            timelines: HashMap::new(),
+            widgets: HashMap::new(),
+            root: None,
+            dirty: None,
+        }
+    }
+
+    pub(crate) fn next_handle(&mut self) -> u64 {
+        let handle = self.next_handle;
+        self.next_handle += 1;
+        handle
+    }
+
+    pub(crate) fn signal(&self, handle: JunitaSignal) -> Result<Signal<Value>, Error> {
+        self.signals
+            .get(&handle)
+            .copied()
+            .ok_or(Error::InvalidHandle {
+                kind: "signal",
+                handle,
+            })
+    }
+
+    /// Ask a running app to rebuild its UI
+    pub(crate) fn mark_dirty(&self) {
+        if let Some(dirty) = &self.dirty {
+            dirty.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+thread_local! {
+    static RUNTIME: RefCell<Runtime> = RefCell::new(Runtime::new());
+    static LAST_ERROR: RefCell<CString> = RefCell::new(CString::default());
+    static DRAINING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Run `f` against this thread's runtime, then any callbacks it queued
+///
+/// Errors are recorded for [`junita_last_error`] and turn into `default`.
+pub(crate) fn call<R>(default: R, f: impl FnOnce(&mut Runtime) -> Result<R, Error>) -> R {
+    let result = RUNTIME.with(|rt| match rt.try_borrow_mut() {
+        Ok(mut rt) => f(&mut rt),
+        Err(_) => Err(Error::Reentrant),
+    });
+    run_pending();
+    match result {
+        Ok(value) => value,
+        Err(err) => {
+            set_error(&err);
+            default
+        }
+    }
+}
+
+/// Record an error for [`junita_last_error`]
+pub(crate) fn set_error(err: &Error) {
+    tracing::debug!("junita ABI call failed: {}", err);
+    let message = CString::new(err.to_string().replace('\0', "")).unwrap_or_default();
+    LAST_ERROR.with(|last| *last.borrow_mut() = message);
+}
+
+/// Invoke queued callbacks until the queue is empty
+///
+/// Callbacks that queue more callbacks are handled by the outermost call.
+pub(crate) fn run_pending() {
+    if DRAINING.with(|d| d.replace(true)) {
+        return;
+    }
+    loop {
+        let next = RUNTIME.with(|rt| {
+            let rt = rt.try_borrow().ok()?;
+            let handle = rt.pending.lock().unwrap().pop_front()?;
+            Some(rt.callbacks.get(&handle).copied())
+        });
+        match next {
+            Some(Some(callback)) => unsafe { callback.invoke() },
+            // Released after it was queued
+            Some(None) => {}
+            None => break,
+        }
+    }
+    DRAINING.with(|d| d.set(false));
+}
+
+/// Read a NUL-terminated UTF-8 argument
+pub(crate) unsafe fn str_arg<'a>(ptr: *const c_char, name: &'static str) -> Result<&'a str, Error> {
+    if ptr.is_null() {
+        return Err(Error::Null(name));
+    }
+    CStr::from_ptr(ptr).to_str().map_err(|_| Error::Utf8(name))
+}
+
+/// Read a pointer and length argument; null is allowed when `len` is 0
+pub(crate) unsafe fn slice_arg<'a, T>(
+    ptr: *const T,
+    len: usize,
+    name: &'static str,
+) -> Result<&'a [T], Error> {
+    if len == 0 {
+        Ok(&[])
+    } else if ptr.is_null() {
+        Err(Error::Null(name))
+    } else {
+        Ok(std::slice::from_raw_parts(ptr, len))
+    }
+}
+
+/// Convert a `0xRRGGBBAA` color
+pub(crate) fn color(rgba: u32) -> Color {
+    let [r, g, b, a] = rgba.to_be_bytes();
+    Color::rgba(
+        r as f32 / 255.0,
+        g as f32 / 255.0,
+        b as f32 / 255.0,
+        a as f32 / 255.0,
+    )
+}
+
+/// Message of the last failed call on this thread, or an empty string
+///
+/// The pointer stays valid until the next call fails.
+#[no_mangle]
+pub extern "C" fn junita_last_error() -> *const c_char {
+    LAST_ERROR.with(|last| last.borrow().as_ptr())
+}
+
+/// Free a string returned by the runtime
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by the runtime that hasn't
+/// been freed.
+#[no_mangle]
+pub unsafe extern "C" fn junita_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_conversions() {
+        assert_eq!(Value::F32(2.9).as_i32(), 2);
+        assert_eq!(Value::String(" 4.5 ".into()).as_f32(), 4.5);
+        assert!(!Value::String(String::new()).as_bool());
+        assert_eq!(Value::Bool(true).to_string(), "true");
+        assert_eq!(color(0xff000080), Color::rgba(1.0, 0.0, 0.0, 128.0 / 255.0));
+    }
+
+    #[test]
+    fn test_errors_are_reported() {
+        unsafe {
+            assert_eq!(reactive::junita_signal_get_i32(9999), 0);
+            let message = CStr::from_ptr(junita_last_error()).to_str().unwrap();
+            assert_eq!(message, "invalid signal handle 9999");
+        }
+    }
+}
@@ -0,0 +1,237 @@
+//! Painting
+//!
+//! A widget's paint callback receives a `JunitaPaintContext` that is only
+//! valid for the duration of the callback. Coordinates are in the widget's
+//! local space and colors are `0xRRGGBBAA`.
+
+use std::ffi::{c_char, c_void};
+
+use junita_core::{
+    Affine2D, CornerRadius, DrawContext, Path, Point, Rect, Stroke, TextStyle, Transform,
+};
+
+use super::{color, set_error, slice_arg, str_arg, Error};
+
+/// Drawing target handed to paint callbacks
+pub struct JunitaPaintContext<'a> {
+    draw: &'a mut dyn DrawContext,
+}
+
+impl<'a> JunitaPaintContext<'a> {
+    pub(crate) fn new(draw: &'a mut dyn DrawContext) -> Self {
+        Self { draw }
+    }
+}
+
+/// Draws a widget of `width` by `height`
+pub type JunitaPaintFn = Option<
+    unsafe extern "C" fn(
+        user_data: *mut c_void,
+        ctx: *mut JunitaPaintContext,
+        width: f32,
+        height: f32,
+    ),
+>;
+
+unsafe fn paint(
+    ctx: *mut JunitaPaintContext,
+    f: impl FnOnce(&mut dyn DrawContext) -> Result<(), Error>,
+) {
+    let result = match ctx.as_mut() {
+        Some(ctx) => f(ctx.draw),
+        None => Err(Error::Null("ctx")),
+    };
+    if let Err(err) = result {
+        set_error(&err);
+    }
+}
+
+/// Fill a rectangle with rounded corners
+///
+/// # Safety
+///
+/// `ctx` must be the context passed to the running paint callback.
+#[no_mangle]
+pub unsafe extern "C" fn junita_paint_fill_rect(
+    ctx: *mut JunitaPaintContext,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    radius: f32,
+    rgba: u32,
+) {
+    paint(ctx, |draw| {
+        let rect = Rect::new(x, y, width, height);
+        draw.fill_rect(rect, CornerRadius::uniform(radius), color(rgba).into());
+        Ok(())
+    })
+}
+
+/// Outline a rectangle with rounded corners
+///
+/// # Safety
+///
+/// `ctx` must be the context passed to the running paint callback.
+#[no_mangle]
+pub unsafe extern "C" fn junita_paint_stroke_rect(
+    ctx: *mut JunitaPaintContext,
+    x: f32,
+    y: f32,
+    width: f32,
+    height: f32,
+    radius: f32,
+    line_width: f32,
+    rgba: u32,
+) {
+    paint(ctx, |draw| {
+        let rect = Rect::new(x, y, width, height);
+        let stroke = Stroke::new(line_width);
+        draw.stroke_rect(
+            rect,
+            CornerRadius::uniform(radius),
+            &stroke,
+            color(rgba).into(),
+        );
+        Ok(())
+    })
+}
+
+/// Fill a circle
+///
+/// # Safety
+///
+/// `ctx` must be the context passed to the running paint callback.
+#[no_mangle]
+pub unsafe extern "C" fn junita_paint_fill_circle(
+    ctx: *mut JunitaPaintContext,
+    cx: f32,
+    cy: f32,
+    radius: f32,
+    rgba: u32,
+) {
+    paint(ctx, |draw| {
+        draw.fill_circle(Point::new(cx, cy), radius, color(rgba).into());
+        Ok(())
+    })
+}
+
+/// Stroke the polyline through `point_count` points
+///
+/// `points` holds `x, y` pairs, so it is `2 * point_count` floats long.
+///
+/// # Safety
+///
+/// `ctx` must be the context passed to the running paint callback and
+/// `points` must point to `2 * point_count` floats.
+#[no_mangle]
+pub unsafe extern "C" fn junita_paint_stroke_path(
+    ctx: *mut JunitaPaintContext,
+    points: *const f32,
+    point_count: usize,
+    closed: bool,
+    line_width: f32,
+    rgba: u32,
+) {
+    paint(ctx, |draw| {
+        let points = slice_arg(points, point_count * 2, "points")?;
+        let mut path = Path::new();
+        for (i, point) in points.chunks_exact(2).enumerate() {
+            path = if i == 0 {
+                path.move_to(point[0], point[1])
+            } else {
+                path.line_to(point[0], point[1])
+            };
+        }
+        if closed {
+            path = path.close();
+        }
+        draw.stroke_path(&path, &Stroke::new(line_width), color(rgba).into());
+        Ok(())
+    })
+}
+
+/// Draw a line of text with its top-left corner at `x, y`
+///
+/// # Safety
+///
+/// `ctx` must be the context passed to the running paint callback and `text`
+/// a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn junita_paint_draw_text(
+    ctx: *mut JunitaPaintContext,
+    text: *const c_char,
+    x: f32,
+    y: f32,
+    size: f32,
+    rgba: u32,
+) {
+    paint(ctx, |draw| {
+        let text = str_arg(text, "text")?;
+        let style = TextStyle::new(size).with_color(color(rgba));
+        draw.draw_text(text, Point::new(x, y), &style);
+        Ok(())
+    })
+}
+
+/// Push the affine transform `[a c tx; b d ty]` until the matching pop
+///
+/// # Safety
+///
+/// `ctx` must be the context passed to the running paint callback.
+#[no_mangle]
+pub unsafe extern "C" fn junita_paint_push_transform(
+    ctx: *mut JunitaPaintContext,
+    a: f32,
+    b: f32,
+    c: f32,
+    d: f32,
+    tx: f32,
+    ty: f32,
+) {
+    paint(ctx, |draw| {
+        draw.push_transform(Transform::Affine2D(Affine2D {
+            elements: [a, b, c, d, tx, ty],
+        }));
+        Ok(())
+    })
+}
+
+/// Pop the transform pushed last
+///
+/// # Safety
+///
+/// `ctx` must be the context passed to the running paint callback.
+#[no_mangle]
+pub unsafe extern "C" fn junita_paint_pop_transform(ctx: *mut JunitaPaintContext) {
+    paint(ctx, |draw| {
+        draw.pop_transform();
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use junita_core::{DrawCommand, RecordingContext, Size};
+
+    use super::*;
+
+    #[test]
+    fn test_paint_records_commands() {
+        let mut recording = RecordingContext::new(Size::new(100.0, 100.0));
+        let mut ctx = JunitaPaintContext::new(&mut recording);
+        let points = [0.0, 0.0, 10.0, 0.0, 10.0, 10.0];
+        unsafe {
+            junita_paint_push_transform(&mut ctx, 1.0, 0.0, 0.0, 1.0, 5.0, 5.0);
+            junita_paint_fill_rect(&mut ctx, 0.0, 0.0, 10.0, 10.0, 2.0, 0xff0000ff);
+            junita_paint_stroke_path(&mut ctx, points.as_ptr(), 3, true, 1.0, 0x000000ff);
+            junita_paint_draw_text(&mut ctx, std::ptr::null(), 0.0, 0.0, 12.0, 0xffffffff);
+            junita_paint_pop_transform(&mut ctx);
+        }
+        let commands = recording.commands();
+        assert!(matches!(commands[0], DrawCommand::PushTransform(_)));
+        assert!(matches!(commands[2], DrawCommand::StrokePath { .. }));
+        assert!(matches!(commands.last(), Some(DrawCommand::PopTransform)));
+        assert_eq!(commands.len(), 4);
+    }
+}
@@ -0,0 +1,345 @@
+//! Signals, derived values and effects
+//!
+//! A signal holds an `int32_t`, `float`, `bool` or string, whichever was set
+//! last, and each getter converts from it. Setting a signal to the value it
+//! already holds does nothing.
+//!
+//! Dependencies are passed explicitly: an effect or derived value reruns when
+//! any signal in its `deps` array changes.
+
+use std::ffi::{c_char, c_void, CString};
+
+use super::{
+    call, slice_arg, str_arg, Callback, Error, JunitaCallback, JunitaEffect, JunitaSignal, Runtime,
+    Value,
+};
+
+/// Computes a derived value and stores it with `junita_signal_set_*(output, ...)`
+pub type JunitaComputeFn =
+    Option<unsafe extern "C" fn(user_data: *mut c_void, output: JunitaSignal)>;
+
+fn create(value: Value) -> JunitaSignal {
+    call(0, |rt| {
+        let signal = rt.graph.create_signal(value);
+        let handle = rt.next_handle();
+        rt.signals.insert(handle, signal);
+        Ok(handle)
+    })
+}
+
+fn get<R>(signal: JunitaSignal, default: R, f: impl FnOnce(&Value) -> R) -> R {
+    call(None, |rt| {
+        let signal = rt.signal(signal)?;
+        Ok(rt.graph.get_untracked(signal).map(|value| f(&value)))
+    })
+    .unwrap_or(default)
+}
+
+fn set(signal: JunitaSignal, value: Value) {
+    call((), |rt| {
+        let signal = rt.signal(signal)?;
+        if rt.graph.get_untracked(signal).as_ref() != Some(&value) {
+            rt.graph.set(signal, value);
+            rt.mark_dirty();
+        }
+        Ok(())
+    })
+}
+
+/// Register `callback` under `handle` and an effect that queues it whenever
+/// one of `deps` changes
+unsafe fn watch(
+    rt: &mut Runtime,
+    handle: u64,
+    callback: Callback,
+    deps: *const JunitaSignal,
+    dep_count: usize,
+) -> Result<(), Error> {
+    let deps = slice_arg(deps, dep_count, "deps")?
+        .iter()
+        .map(|&dep| rt.signal(dep))
+        .collect::<Result<Vec<_>, _>>()?;
+    rt.callbacks.insert(handle, callback);
+    let pending = rt.pending.clone();
+    let effect = rt.graph.create_effect(move |graph| {
+        for &dep in &deps {
+            graph.get(dep);
+        }
+        pending.lock().unwrap().push_back(handle);
+    });
+    rt.effects.insert(handle, effect);
+    Ok(())
+}
+
+/// Create a signal holding an `int32_t`
+#[no_mangle]
+pub extern "C" fn junita_signal_create_i32(initial: i32) -> JunitaSignal {
+    create(Value::I32(initial))
+}
+
+/// Create a signal holding a `float`
+#[no_mangle]
+pub extern "C" fn junita_signal_create_f32(initial: f32) -> JunitaSignal {
+    create(Value::F32(initial))
+}
+
+/// Create a signal holding a `bool`
+#[no_mangle]
+pub extern "C" fn junita_signal_create_bool(initial: bool) -> JunitaSignal {
+    create(Value::Bool(initial))
+}
+
+/// Create a signal holding a copy of `initial`
+///
+/// # Safety
+///
+/// `initial` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn junita_signal_create_string(initial: *const c_char) -> JunitaSignal {
+    match str_arg(initial, "initial") {
+        Ok(initial) => create(Value::String(initial.to_owned())),
+        Err(err) => call(0, |_| Err(err)),
+    }
+}
+
+/// Read a signal as an `int32_t`
+#[no_mangle]
+pub extern "C" fn junita_signal_get_i32(signal: JunitaSignal) -> i32 {
+    get(signal, 0, Value::as_i32)
+}
+
+/// Read a signal as a `float`
+#[no_mangle]
+pub extern "C" fn junita_signal_get_f32(signal: JunitaSignal) -> f32 {
+    get(signal, 0.0, Value::as_f32)
+}
+
+/// Read a signal as a `bool`
+#[no_mangle]
+pub extern "C" fn junita_signal_get_bool(signal: JunitaSignal) -> bool {
+    get(signal, false, Value::as_bool)
+}
+
+/// Read a signal as a string, or null for a dead handle
+///
+/// The string belongs to the caller; free it with `junita_string_free`.
+#[no_mangle]
+pub extern "C" fn junita_signal_get_string(signal: JunitaSignal) -> *mut c_char {
+    get(signal, None, |value| {
+        CString::new(value.to_string().replace('\0', "")).ok()
+    })
+    .map_or(std::ptr::null_mut(), CString::into_raw)
+}
+
+/// Store an `int32_t` in a signal
+#[no_mangle]
+pub extern "C" fn junita_signal_set_i32(signal: JunitaSignal, value: i32) {
+    set(signal, Value::I32(value))
+}
+
+/// Store a `float` in a signal
+#[no_mangle]
+pub extern "C" fn junita_signal_set_f32(signal: JunitaSignal, value: f32) {
+    set(signal, Value::F32(value))
+}
+
+/// Store a `bool` in a signal
+#[no_mangle]
+pub extern "C" fn junita_signal_set_bool(signal: JunitaSignal, value: bool) {
+    set(signal, Value::Bool(value))
+}
+
+/// Store a copy of `value` in a signal
+///
+/// # Safety
+///
+/// `value` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn junita_signal_set_string(signal: JunitaSignal, value: *const c_char) {
+    match str_arg(value, "value") {
+        Ok(value) => set(signal, Value::String(value.to_owned())),
+        Err(err) => call((), |_| Err(err)),
+    }
+}
+
+/// Release a signal; for a derived value this also stops its computation
+#[no_mangle]
+pub extern "C" fn junita_signal_release(signal: JunitaSignal) {
+    call((), |rt| {
+        let handle = signal;
+        let signal = rt.signal(handle)?;
+        rt.signals.remove(&handle);
+        if let Some(effect) = rt.effects.remove(&handle) {
+            rt.graph.dispose_effect(effect);
+            rt.callbacks.remove(&handle);
+        }
+        rt.graph.dispose_signal(signal);
+        Ok(())
+    })
+}
+
+/// Create a derived value
+///
+/// Returns a signal that `compute` fills in, once now and again whenever one
+/// of the `dep_count` signals in `deps` changes. Release it with
+/// `junita_signal_release`.
+///
+/// # Safety
+///
+/// `deps` must point to `dep_count` handles, and `user_data` must stay valid
+/// until the signal is released.
+#[no_mangle]
+pub unsafe extern "C" fn junita_derived_create(
+    compute: JunitaComputeFn,
+    user_data: *mut c_void,
+    deps: *const JunitaSignal,
+    dep_count: usize,
+) -> JunitaSignal {
+    call(0, |rt| {
+        let compute = compute.ok_or(Error::Null("compute"))?;
+        let output = rt.graph.create_signal(Value::I32(0));
+        let handle = rt.next_handle();
+        rt.signals.insert(handle, output);
+        let callback = Callback::Compute(compute, user_data, handle);
+        if let Err(err) = watch(rt, handle, callback, deps, dep_count) {
+            rt.signals.remove(&handle);
+            rt.graph.dispose_signal(output);
+            return Err(err);
+        }
+        Ok(handle)
+    })
+}
+
+/// Create an effect
+///
+/// `callback` runs once now and again whenever one of the `dep_count`
+/// signals in `deps` changes.
+///
+/// # Safety
+///
+/// `deps` must point to `dep_count` handles, and `user_data` must stay valid
+/// until the effect is released.
+#[no_mangle]
+pub unsafe extern "C" fn junita_effect_create(
+    callback: JunitaCallback,
+    user_data: *mut c_void,
+    deps: *const JunitaSignal,
+    dep_count: usize,
+) -> JunitaEffect {
+    call(0, |rt| {
+        let callback = callback.ok_or(Error::Null("callback"))?;
+        let handle = rt.next_handle();
+        watch(
+            rt,
+            handle,
+            Callback::Plain(callback, user_data),
+            deps,
+            dep_count,
+        )?;
+        Ok(handle)
+    })
+}
+
+/// Stop and release an effect
+#[no_mangle]
+pub extern "C" fn junita_effect_release(effect: JunitaEffect) {
+    call((), |rt| {
+        let handle = effect;
+        let effect = rt.effects.remove(&handle).ok_or(Error::InvalidHandle {
+            kind: "effect",
+            handle,
+        })?;
+        rt.graph.dispose_effect(effect);
+        rt.callbacks.remove(&handle);
+        Ok(())
+    })
+}
+
+/// Hold effects until the matching `junita_batch_end`
+#[no_mangle]
+pub extern "C" fn junita_batch_start() {
+    call((), |rt| {
+        rt.graph.batch_start();
+        Ok(())
+    })
+}
+
+/// Run the effects held since `junita_batch_start`
+#[no_mangle]
+pub extern "C" fn junita_batch_end() {
+    call((), |rt| {
+        rt.graph.batch_end();
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CStr;
+
+    use super::super::junita_string_free;
+    use super::*;
+
+    unsafe extern "C" fn count(user_data: *mut c_void) {
+        *(user_data as *mut i32) += 1;
+    }
+
+    unsafe extern "C" fn double(user_data: *mut c_void, output: JunitaSignal) {
+        let input = *(user_data as *const JunitaSignal);
+        junita_signal_set_i32(output, junita_signal_get_i32(input) * 2);
+    }
+
+    #[test]
+    fn test_signals_convert() {
+        unsafe {
+            let name = junita_signal_create_string(b"3.5\0".as_ptr() as *const c_char);
+            assert_eq!(junita_signal_get_f32(name), 3.5);
+            junita_signal_set_bool(name, true);
+            let text = junita_signal_get_string(name);
+            assert_eq!(CStr::from_ptr(text).to_str().unwrap(), "true");
+            junita_string_free(text);
+
+            junita_signal_release(name);
+            assert!(junita_signal_get_string(name).is_null());
+        }
+    }
+
+    #[test]
+    fn test_effects_and_derived() {
+        unsafe {
+            let mut runs = 0i32;
+            let count_signal = junita_signal_create_i32(1);
+            let deps = [count_signal];
+            let effect = junita_effect_create(
+                Some(count),
+                &mut runs as *mut i32 as *mut c_void,
+                deps.as_ptr(),
+                1,
+            );
+            let doubled = junita_derived_create(
+                Some(double),
+                &count_signal as *const JunitaSignal as *mut c_void,
+                deps.as_ptr(),
+                1,
+            );
+            assert_eq!(runs, 1);
+            assert_eq!(junita_signal_get_i32(doubled), 2);
+
+            junita_signal_set_i32(count_signal, 5);
+            junita_signal_set_i32(count_signal, 5);
+            assert_eq!(runs, 2);
+            assert_eq!(junita_signal_get_i32(doubled), 10);
+
+            junita_batch_start();
+            junita_signal_set_i32(count_signal, 6);
+            junita_signal_set_i32(count_signal, 7);
+            junita_batch_end();
+            assert_eq!(runs, 3);
+
+            junita_effect_release(effect);
+            junita_signal_release(doubled);
+            junita_signal_set_i32(count_signal, 8);
+            assert_eq!(runs, 3);
+        }
+    }
+}
@@ -0,0 +1,600 @@
+//! Widgets
+//!
+//! Widgets are descriptions kept by the runtime and turned into `Div`s when
+//! a frame is built, using the same mapping as the `.junita` interpreter:
+//!
+//! | Type | Builds |
+//! |---|---|
+//! | `JUNITA_WIDGET_COLUMN` | `div().flex_col()` |
+//! | `JUNITA_WIDGET_ROW` | `div().flex_row()` |
+//! | `JUNITA_WIDGET_CENTER` | full-size column centering its children |
+//! | `JUNITA_WIDGET_WINDOW` | full-size column |
+//! | `JUNITA_WIDGET_TEXT` | `div()` around `text(content)` |
+//! | `JUNITA_WIDGET_BUTTON` | clickable row around `text(content)` |
+//! | `JUNITA_WIDGET_SPACER` | `div().flex_grow()` |
+//! | `JUNITA_WIDGET_BOX` | `div()` |
+//! | `JUNITA_WIDGET_CANVAS` | `div()` filled by its paint callback |
+//!
+//! Props take whichever setter suits the value: numbers convert between the
+//! numeric setters, and `DIRECTION`, `ALIGN`, `JUSTIFY` and `FONT_WEIGHT`
+//! take the same strings as in `.junita` source.
+
+use std::ffi::{c_char, c_void};
+
+use junita_core::Color;
+use junita_layout::canvas::canvas;
+use junita_layout::prelude::*;
+
+use super::paint::{JunitaPaintContext, JunitaPaintFn};
+use super::{call, color, run_pending, str_arg, Error, JunitaWidget, Runtime, Value};
+
+pub const JUNITA_WIDGET_COLUMN: u32 = 0;
+pub const JUNITA_WIDGET_ROW: u32 = 1;
+pub const JUNITA_WIDGET_CENTER: u32 = 2;
+pub const JUNITA_WIDGET_WINDOW: u32 = 3;
+pub const JUNITA_WIDGET_TEXT: u32 = 4;
+pub const JUNITA_WIDGET_BUTTON: u32 = 5;
+pub const JUNITA_WIDGET_SPACER: u32 = 6;
+pub const JUNITA_WIDGET_BOX: u32 = 7;
+pub const JUNITA_WIDGET_CANVAS: u32 = 8;
+
+pub const JUNITA_PROP_CONTENT: u32 = 0;
+pub const JUNITA_PROP_FONT_SIZE: u32 = 1;
+pub const JUNITA_PROP_COLOR: u32 = 2;
+pub const JUNITA_PROP_FONT_WEIGHT: u32 = 3;
+pub const JUNITA_PROP_OPACITY: u32 = 4;
+pub const JUNITA_PROP_WIDTH: u32 = 5;
+pub const JUNITA_PROP_HEIGHT: u32 = 6;
+pub const JUNITA_PROP_MIN_WIDTH: u32 = 7;
+pub const JUNITA_PROP_MIN_HEIGHT: u32 = 8;
+pub const JUNITA_PROP_PADDING: u32 = 9;
+pub const JUNITA_PROP_PADDING_X: u32 = 10;
+pub const JUNITA_PROP_PADDING_Y: u32 = 11;
+pub const JUNITA_PROP_MARGIN: u32 = 12;
+pub const JUNITA_PROP_MARGIN_TOP: u32 = 13;
+pub const JUNITA_PROP_MARGIN_BOTTOM: u32 = 14;
+pub const JUNITA_PROP_MARGIN_LEFT: u32 = 15;
+pub const JUNITA_PROP_MARGIN_RIGHT: u32 = 16;
+pub const JUNITA_PROP_SPACING: u32 = 17;
+pub const JUNITA_PROP_GROW: u32 = 18;
+pub const JUNITA_PROP_DIRECTION: u32 = 19;
+pub const JUNITA_PROP_ALIGN: u32 = 20;
+pub const JUNITA_PROP_JUSTIFY: u32 = 21;
+pub const JUNITA_PROP_BACKGROUND: u32 = 22;
+pub const JUNITA_PROP_BORDER_RADIUS: u32 = 23;
+pub const JUNITA_PROP_BORDER_COLOR: u32 = 24;
+pub const JUNITA_PROP_BORDER_WIDTH: u32 = 25;
+
+pub const JUNITA_EVENT_CLICK: u32 = 0;
+pub const JUNITA_EVENT_MOUSE_DOWN: u32 = 1;
+pub const JUNITA_EVENT_MOUSE_UP: u32 = 2;
+pub const JUNITA_EVENT_HOVER_ENTER: u32 = 3;
+pub const JUNITA_EVENT_HOVER_LEAVE: u32 = 4;
+
+const LAST_WIDGET: u32 = JUNITA_WIDGET_CANVAS;
+const LAST_PROP: u32 = JUNITA_PROP_BORDER_WIDTH;
+const LAST_EVENT: u32 = JUNITA_EVENT_HOVER_LEAVE;
+
+/// Handles an event at `x, y` in the widget's local space
+pub type JunitaEventFn = Option<unsafe extern "C" fn(user_data: *mut c_void, x: f32, y: f32)>;
+
+/// A prop as set from C
+#[derive(Clone, Debug, PartialEq)]
+enum Prop {
+    Value(Value),
+    Color(Color),
+}
+
+impl Prop {
+    fn number(&self) -> Option<f32> {
+        match self {
+            Prop::Value(Value::String(_)) | Prop::Color(_) => None,
+            Prop::Value(value) => Some(value.as_f32()),
+        }
+    }
+
+    fn color(&self) -> Option<Color> {
+        match self {
+            Prop::Color(color) => Some(*color),
+            Prop::Value(Value::I32(rgba)) => Some(color(*rgba as u32)),
+            Prop::Value(_) => None,
+        }
+    }
+
+    fn text(&self) -> String {
+        match self {
+            Prop::Value(value) => value.to_string(),
+            Prop::Color(color) => format!("{:?}", color),
+        }
+    }
+}
+
+/// A widget waiting to be built
+pub(crate) struct WidgetNode {
+    kind: u32,
+    props: Vec<(u32, Prop)>,
+    handlers: Vec<(
+        u32,
+        unsafe extern "C" fn(*mut c_void, f32, f32),
+        *mut c_void,
+    )>,
+    paint: Option<(
+        unsafe extern "C" fn(*mut c_void, *mut JunitaPaintContext, f32, f32),
+        *mut c_void,
+    )>,
+    children: Vec<WidgetNode>,
+}
+
+/// Text properties collected while applying props
+#[derive(Default)]
+struct TextProps {
+    content: Option<String>,
+    size: Option<f32>,
+    color: Option<Color>,
+    weight: Option<FontWeight>,
+}
+
+impl WidgetNode {
+    pub(crate) fn build(&self) -> Div {
+        let mut text_props = TextProps::default();
+        let mut el = match self.kind {
+            JUNITA_WIDGET_COLUMN => div().flex_col(),
+            JUNITA_WIDGET_ROW => div().flex_row(),
+            JUNITA_WIDGET_CENTER => div()
+                .flex_col()
+                .items_center()
+                .justify_center()
+                .w_full()
+                .h_full(),
+            JUNITA_WIDGET_WINDOW => div().flex_col().w_full().h_full(),
+            JUNITA_WIDGET_BUTTON => {
+                text_props.color = Some(Color::WHITE);
+                div()
+                    .flex_row()
+                    .items_center()
+                    .justify_center()
+                    .padding_x_px(16.0)
+                    .padding_y_px(8.0)
+                    .rounded(6.0)
+                    .bg(Color::from_hex(0x3b82f6))
+                    .cursor_pointer()
+            }
+            JUNITA_WIDGET_SPACER => div().flex_grow(),
+            _ => div(),
+        };
+
+        for (prop, value) in &self.props {
+            el = apply_prop(el, *prop, value, &mut text_props);
+        }
+        for &(event, handler, user_data) in &self.handlers {
+            el = attach_handler(el, event, handler, user_data);
+        }
+
+        if let Some((paint, user_data)) = self.paint {
+            el = el.child(
+                canvas(move |draw, bounds| {
+                    let mut ctx = JunitaPaintContext::new(draw);
+                    unsafe { paint(user_data, &mut ctx, bounds.width, bounds.height) };
+                    run_pending();
+                })
+                .w_full()
+                .h_full(),
+            );
+        }
+        if let Some(content) = text_props.content.take() {
+            let mut node = text(content);
+            if let Some(size) = text_props.size {
+                node = node.size(size);
+            }
+            if let Some(weight) = text_props.weight {
+                node = node.weight(weight);
+            }
+            if let Some(color) = text_props.color {
+                node = node.color(color);
+            }
+            el = el.child(node);
+        }
+        for child in &self.children {
+            el = el.child(child.build());
+        }
+        el
+    }
+}
+
+fn attach_handler(
+    el: Div,
+    event: u32,
+    handler: unsafe extern "C" fn(*mut c_void, f32, f32),
+    user_data: *mut c_void,
+) -> Div {
+    let handler = move |ctx: &EventContext| {
+        unsafe { handler(user_data, ctx.local_x, ctx.local_y) };
+        run_pending();
+    };
+    match event {
+        JUNITA_EVENT_CLICK => el.on_click(handler),
+        JUNITA_EVENT_MOUSE_DOWN => el.on_mouse_down(handler),
+        JUNITA_EVENT_MOUSE_UP => el.on_mouse_up(handler),
+        JUNITA_EVENT_HOVER_ENTER => el.on_hover_enter(handler),
+        _ => el.on_hover_leave(handler),
+    }
+}
+
+fn apply_prop(el: Div, prop: u32, value: &Prop, text_props: &mut TextProps) -> Div {
+    let needs_color = matches!(
+        prop,
+        JUNITA_PROP_COLOR | JUNITA_PROP_BACKGROUND | JUNITA_PROP_BORDER_COLOR
+    );
+    let needs_text = matches!(
+        prop,
+        JUNITA_PROP_CONTENT
+            | JUNITA_PROP_FONT_WEIGHT
+            | JUNITA_PROP_DIRECTION
+            | JUNITA_PROP_ALIGN
+            | JUNITA_PROP_JUSTIFY
+    );
+    let number = value.number();
+    let color = value.color();
+    if (needs_color && color.is_none()) || (!needs_color && !needs_text && number.is_none()) {
+        return invalid(el, prop, value);
+    }
+    let n = number.unwrap_or_default();
+    let c = color.unwrap_or(Color::TRANSPARENT);
+
+    match prop {
+        // Content
+        JUNITA_PROP_CONTENT => {
+            text_props.content = Some(value.text());
+            el
+        }
+        JUNITA_PROP_FONT_SIZE => {
+            text_props.size = Some(n);
+            el
+        }
+        JUNITA_PROP_COLOR => {
+            text_props.color = Some(c);
+            el
+        }
+        JUNITA_PROP_FONT_WEIGHT => match font_weight(value) {
+            Some(weight) => {
+                text_props.weight = Some(weight);
+                el
+            }
+            None => invalid(el, prop, value),
+        },
+        JUNITA_PROP_OPACITY => el.opacity(n.clamp(0.0, 1.0)),
+
+        // Box model
+        JUNITA_PROP_WIDTH => el.w(n),
+        JUNITA_PROP_HEIGHT => el.h(n),
+        JUNITA_PROP_MIN_WIDTH => el.min_w(n),
+        JUNITA_PROP_MIN_HEIGHT => el.min_h(n),
+        JUNITA_PROP_PADDING => el.p_px(n),
+        JUNITA_PROP_PADDING_X => el.padding_x_px(n),
+        JUNITA_PROP_PADDING_Y => el.padding_y_px(n),
+        JUNITA_PROP_MARGIN => el.m_px(n),
+        JUNITA_PROP_MARGIN_TOP => el.mt(n / 4.0),
+        JUNITA_PROP_MARGIN_BOTTOM => el.mb(n / 4.0),
+        JUNITA_PROP_MARGIN_LEFT => el.ml(n / 4.0),
+        JUNITA_PROP_MARGIN_RIGHT => el.mr(n / 4.0),
+
+        // Flex
+        JUNITA_PROP_SPACING => el.gap_px(n),
+        JUNITA_PROP_GROW => el.flex_grow_value(n),
+        JUNITA_PROP_DIRECTION => match value.text().as_str() {
+            "row" => el.flex_row(),
+            "column" | "col" => el.flex_col(),
+            _ => invalid(el, prop, value),
+        },
+        JUNITA_PROP_ALIGN => match value.text().as_str() {
+            "start" => el.items_start(),
+            "center" => el.items_center(),
+            "end" => el.items_end(),
+            "stretch" => el.items_stretch(),
+            _ => invalid(el, prop, value),
+        },
+        JUNITA_PROP_JUSTIFY => match value.text().as_str() {
+            "start" => el.justify_start(),
+            "center" => el.justify_center(),
+            "end" => el.justify_end(),
+            "between" | "space_between" => el.justify_between(),
+            "around" | "space_around" => el.justify_around(),
+            "evenly" | "space_evenly" => el.justify_evenly(),
+            _ => invalid(el, prop, value),
+        },
+
+        // Visuals
+        JUNITA_PROP_BACKGROUND => el.bg(c),
+        JUNITA_PROP_BORDER_RADIUS => el.rounded(n),
+        JUNITA_PROP_BORDER_COLOR => el.border_color(c),
+        _ => el.border_width(n),
+    }
+}
+
+fn invalid(el: Div, prop: u32, value: &Prop) -> Div {
+    tracing::warn!("Invalid value `{:?}` for prop {}", value, prop);
+    el
+}
+
+fn font_weight(value: &Prop) -> Option<FontWeight> {
+    if let Some(n) = value.number() {
+        return Some(match n as u32 {
+            0..=149 => FontWeight::Thin,
+            150..=249 => FontWeight::ExtraLight,
+            250..=349 => FontWeight::Light,
+            350..=449 => FontWeight::Normal,
+            450..=549 => FontWeight::Medium,
+            550..=649 => FontWeight::SemiBold,
+            650..=749 => FontWeight::Bold,
+            750..=849 => FontWeight::ExtraBold,
+            _ => FontWeight::Black,
+        });
+    }
+    Some(match value.text().as_str() {
+        "thin" => FontWeight::Thin,
+        "light" => FontWeight::Light,
+        "normal" | "regular" => FontWeight::Normal,
+        "medium" => FontWeight::Medium,
+        "semibold" => FontWeight::SemiBold,
+        "bold" => FontWeight::Bold,
+        "black" => FontWeight::Black,
+        _ => return None,
+    })
+}
+
+fn widget(rt: &mut Runtime, handle: JunitaWidget) -> Result<&mut WidgetNode, Error> {
+    rt.widgets.get_mut(&handle).ok_or(Error::InvalidHandle {
+        kind: "widget",
+        handle,
+    })
+}
+
+fn set_prop(widget: JunitaWidget, prop: u32, value: Prop) {
+    call((), |rt| {
+        if prop > LAST_PROP {
+            return Err(Error::UnknownId {
+                kind: "prop",
+                id: prop,
+            });
+        }
+        let node = self::widget(rt, widget)?;
+        node.props.retain(|(p, _)| *p != prop);
+        node.props.push((prop, value));
+        Ok(())
+    })
+}
+
+/// Build the tree passed to `junita_widget_set_root`, if any
+pub fn build_root() -> Option<Div> {
+    call(None, |rt| Ok(rt.root.as_ref().map(WidgetNode::build)))
+}
+
+/// Create a widget of a `JUNITA_WIDGET_*` type
+#[no_mangle]
+pub extern "C" fn junita_widget_create(kind: u32) -> JunitaWidget {
+    call(0, |rt| {
+        if kind > LAST_WIDGET {
+            return Err(Error::UnknownId {
+                kind: "widget type",
+                id: kind,
+            });
+        }
+        let handle = rt.next_handle();
+        rt.widgets.insert(
+            handle,
+            WidgetNode {
+                kind,
+                props: Vec::new(),
+                handlers: Vec::new(),
+                paint: None,
+                children: Vec::new(),
+            },
+        );
+        Ok(handle)
+    })
+}
+
+/// Set a `JUNITA_PROP_*` to an `int32_t`
+#[no_mangle]
+pub extern "C" fn junita_widget_set_prop_i32(widget: JunitaWidget, prop: u32, value: i32) {
+    set_prop(widget, prop, Prop::Value(Value::I32(value)))
+}
+
+/// Set a `JUNITA_PROP_*` to a `float`
+#[no_mangle]
+pub extern "C" fn junita_widget_set_prop_f32(widget: JunitaWidget, prop: u32, value: f32) {
+    set_prop(widget, prop, Prop::Value(Value::F32(value)))
+}
+
+/// Set a `JUNITA_PROP_*` to a `bool`
+#[no_mangle]
+pub extern "C" fn junita_widget_set_prop_bool(widget: JunitaWidget, prop: u32, value: bool) {
+    set_prop(widget, prop, Prop::Value(Value::Bool(value)))
+}
+
+/// Set a `JUNITA_PROP_*` to a copy of `value`
+///
+/// # Safety
+///
+/// `value` must be a NUL-terminated string.
+#[no_mangle]
+pub unsafe extern "C" fn junita_widget_set_prop_string(
+    widget: JunitaWidget,
+    prop: u32,
+    value: *const c_char,
+) {
+    match str_arg(value, "value") {
+        Ok(value) => set_prop(widget, prop, Prop::Value(Value::String(value.to_owned()))),
+        Err(err) => call((), |_| Err(err)),
+    }
+}
+
+/// Set a `JUNITA_PROP_*` to a `0xRRGGBBAA` color
+#[no_mangle]
+pub extern "C" fn junita_widget_set_prop_color(widget: JunitaWidget, prop: u32, rgba: u32) {
+    set_prop(widget, prop, Prop::Color(color(rgba)))
+}
+
+/// Move `child` to the end of `parent`'s children
+///
+/// The child's handle is dead afterwards; it is released with its parent.
+#[no_mangle]
+pub extern "C" fn junita_widget_add_child(parent: JunitaWidget, child: JunitaWidget) {
+    call((), |rt| {
+        if parent == child {
+            return Err(Error::InvalidHandle {
+                kind: "child widget",
+                handle: child,
+            });
+        }
+        self::widget(rt, parent)?;
+        let child = rt.widgets.remove(&child).ok_or(Error::InvalidHandle {
+            kind: "widget",
+            handle: child,
+        })?;
+        self::widget(rt, parent)?.children.push(child);
+        Ok(())
+    })
+}
+
+/// Make `widget` the tree the next frame shows, replacing the previous one
+///
+/// The handle is dead afterwards; the runtime owns the tree.
+#[no_mangle]
+pub extern "C" fn junita_widget_set_root(widget: JunitaWidget) {
+    call((), |rt| {
+        let node = rt.widgets.remove(&widget).ok_or(Error::InvalidHandle {
+            kind: "widget",
+            handle: widget,
+        })?;
+        rt.root = Some(node);
+        rt.mark_dirty();
+        Ok(())
+    })
+}
+
+/// Release a widget that was never added to a parent or made the root,
+/// along with its children
+#[no_mangle]
+pub extern "C" fn junita_widget_release(widget: JunitaWidget) {
+    call((), |rt| {
+        rt.widgets.remove(&widget).ok_or(Error::InvalidHandle {
+            kind: "widget",
+            handle: widget,
+        })?;
+        Ok(())
+    })
+}
+
+/// Call `callback` when a `JUNITA_EVENT_*` happens on `widget`
+///
+/// # Safety
+///
+/// `user_data` must stay valid while a tree containing the widget can be
+/// displayed.
+#[no_mangle]
+pub unsafe extern "C" fn junita_on_event(
+    widget: JunitaWidget,
+    event: u32,
+    callback: JunitaEventFn,
+    user_data: *mut c_void,
+) {
+    call((), |rt| {
+        let callback = callback.ok_or(Error::Null("callback"))?;
+        if event > LAST_EVENT {
+            return Err(Error::UnknownId {
+                kind: "event",
+                id: event,
+            });
+        }
+        self::widget(rt, widget)?
+            .handlers
+            .push((event, callback, user_data));
+        Ok(())
+    })
+}
+
+/// Draw `widget`'s content with `paint`
+///
+/// The painter fills the widget and is drawn before its children.
+///
+/// # Safety
+///
+/// `user_data` must stay valid while a tree containing the widget can be
+/// displayed.
+#[no_mangle]
+pub unsafe extern "C" fn junita_widget_on_paint(
+    widget: JunitaWidget,
+    paint: JunitaPaintFn,
+    user_data: *mut c_void,
+) {
+    call((), |rt| {
+        let paint = paint.ok_or(Error::Null("paint"))?;
+        self::widget(rt, widget)?.paint = Some((paint, user_data));
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tree_ownership() {
+        let root = junita_widget_create(JUNITA_WIDGET_COLUMN);
+        let label = junita_widget_create(JUNITA_WIDGET_TEXT);
+        unsafe {
+            junita_widget_set_prop_string(
+                label,
+                JUNITA_PROP_CONTENT,
+                b"Hi\0".as_ptr() as *const c_char,
+            );
+        }
+        junita_widget_set_prop_f32(label, JUNITA_PROP_FONT_SIZE, 18.0);
+        junita_widget_set_prop_color(root, JUNITA_PROP_BACKGROUND, 0x112233ff);
+        junita_widget_add_child(root, label);
+
+        // The child moved into its parent
+        junita_widget_set_prop_i32(label, JUNITA_PROP_WIDTH, 10);
+        call((), |rt| {
+            assert!(!rt.widgets.contains_key(&label));
+            assert_eq!(rt.widgets[&root].children.len(), 1);
+            Ok(())
+        });
+
+        junita_widget_set_root(root);
+        assert_eq!(junita_widget_create(99), 0);
+        call((), |rt| {
+            assert!(rt.widgets.is_empty());
+            let root = rt.root.as_ref().unwrap();
+            assert_eq!(
+                root.props,
+                [(JUNITA_PROP_BACKGROUND, Prop::Color(color(0x112233ff)))]
+            );
+            Ok(())
+        });
+        assert!(build_root().is_some());
+    }
+
+    #[test]
+    fn test_prop_values() {
+        let mut text_props = TextProps::default();
+        let weight = Prop::Value(Value::String("bold".into()));
+        apply_prop(div(), JUNITA_PROP_FONT_WEIGHT, &weight, &mut text_props);
+        apply_prop(
+            div(),
+            JUNITA_PROP_COLOR,
+            &Prop::Value(Value::I32(0xff)),
+            &mut text_props,
+        );
+        apply_prop(
+            div(),
+            JUNITA_PROP_CONTENT,
+            &Prop::Value(Value::I32(3)),
+            &mut text_props,
+        );
+        assert_eq!(text_props.weight, Some(FontWeight::Bold));
+        assert_eq!(text_props.color, Some(Color::rgba(0.0, 0.0, 0.0, 1.0)));
+        assert_eq!(text_props.content.as_deref(), Some("3"));
+    }
+}
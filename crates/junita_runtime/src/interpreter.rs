@@ -0,0 +1,117 @@
+//! Running compiled artifacts
+//!
+//! [`run`] opens a window and renders a `CompiledArtifact` through the
+//! [`Interpreter`], bound to the window's reactive graph, rebuild flag and
+//! animation scheduler. No Rust toolchain is involved, so this is what
+//! `junita run` and `junita dev` use.
+//!
+//! For hot reload, pass the [`Reloads`] half of a [`reload_channel`] to
+//! [`run`] and send recompiled artifacts through the [`Reloader`] from any
+//! thread. Each one is swapped in before the next frame with
+//! [`Interpreter::hot_swap`], keeping widget state; one that fails to parse
+//! is logged and the running version stays up.
+//!
+//! ```ignore
+//! let (reloader, reloads) = junita_runtime::interpreter::reload_channel();
+//! std::thread::spawn(move || {
+//!     for artifact in recompiled_artifacts() {
+//!         if !reloader.reload(artifact) {
+//!             break; // window closed
+//!         }
+//!     }
+//! });
+//! junita_runtime::interpreter::run(artifact, WindowConfig::default(), Some(reloads))?;
+//! ```
+
+use std::sync::atomic::Ordering;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, OnceLock};
+
+use junita_app::windowed::{RefDirtyFlag, SharedAnimationScheduler, WindowedApp};
+use junita_layout::prelude::*;
+
+pub use junita_app::WindowConfig;
+pub use junita_interpreter::{CompiledArtifact, Interpreter};
+
+/// What a reload needs to wake the window, known once it has opened
+type Wake = Arc<OnceLock<(RefDirtyFlag, SharedAnimationScheduler)>>;
+
+/// Sends recompiled artifacts to a window started by [`run`]
+#[derive(Clone)]
+pub struct Reloader {
+    tx: Sender<CompiledArtifact>,
+    wake: Wake,
+}
+
+impl Reloader {
+    /// Swap `artifact` in before the next frame
+    ///
+    /// Returns `false` once the window has closed.
+    pub fn reload(&self, artifact: CompiledArtifact) -> bool {
+        if self.tx.send(artifact).is_err() {
+            return false;
+        }
+        // Before the first frame there's nothing to wake; the first build
+        // picks the artifact up
+        if let Some((dirty, scheduler)) = self.wake.get() {
+            dirty.store(true, Ordering::SeqCst);
+            scheduler.lock().unwrap().request_redraw();
+        }
+        true
+    }
+}
+
+/// Receiving half of a [`reload_channel`], passed to [`run`]
+pub struct Reloads {
+    rx: Receiver<CompiledArtifact>,
+    wake: Wake,
+}
+
+/// Create a channel for hot reloading a running artifact
+pub fn reload_channel() -> (Reloader, Reloads) {
+    let (tx, rx) = mpsc::channel();
+    let wake = Wake::default();
+    (
+        Reloader {
+            tx,
+            wake: Arc::clone(&wake),
+        },
+        Reloads { rx, wake },
+    )
+}
+
+/// Open a window and run `artifact` until the window closes
+///
+/// The root widget is `App`, or the artifact's first widget. Build errors
+/// are logged and rendered in place of the UI.
+pub fn run(
+    artifact: CompiledArtifact,
+    config: WindowConfig,
+    reloads: Option<Reloads>,
+) -> anyhow::Result<()> {
+    let mut interpreter = Interpreter::new(artifact);
+
+    WindowedApp::run(config, move |ctx| {
+        interpreter.attach(ctx.reactive(), ctx.dirty_flag());
+
+        if let Some(reloads) = &reloads {
+            let _ = reloads
+                .wake
+                .set((ctx.dirty_flag(), Arc::clone(&ctx.animations)));
+            while let Ok(artifact) = reloads.rx.try_recv() {
+                let source = artifact.source_file.clone();
+                match interpreter.hot_swap(artifact) {
+                    Ok(()) => tracing::info!("Reloaded {}", source.display()),
+                    Err(err) => tracing::error!(
+                        "Not reloading {}, keeping the running version: {}",
+                        source.display(),
+                        err
+                    ),
+                }
+            }
+        }
+
+        div().w(ctx.width).h(ctx.height).child(interpreter.build())
+    })
+    .map_err(|err| anyhow::anyhow!("{}", err))
+}
@@ -1,6 +1,11 @@
 //! Junita Embedding SDK
 //!
 //! Integrate Junita UI into Rust applications.
+//!
+//! With the `full` feature the crate also builds as a C library exporting
+//! the runtime that compiled `.junita` code links against; see [`abi`].
+//! Compiled artifacts can also run without native code generation through
+//! the interpreter; see [`interpreter`].
 
 #[cfg(feature = "junita_core")]
 pub use junita_core;
@@ -20,8 +25,18 @@ pub use junita_paint;
 // #[cfg(feature = "junita_cn")]
 // pub use junita_cn;
 
+#[cfg(feature = "full")]
+pub mod abi;
+
+#[cfg(feature = "full")]
+pub mod interpreter;
+
 /// Initialize the Junita runtime
+///
+/// There is nothing to prepare up front: a window sets up fonts, the theme
+/// and the animation scheduler when it opens, and `.junita` artifacts are
+/// interpreted as they run. Embedders can still call this as their single
+/// entry point.
 pub fn init() -> anyhow::Result<()> {
-    // TODO: Initialize Zyntax runtime with Junita grammar
     Ok(())
 }
@@ -0,0 +1,333 @@
+//! Generates `include/junita.h` from `src/abi` and checks it is up to date
+//!
+//! Run with `JUNITA_BLESS_HEADER=1` to rewrite the header after changing the
+//! ABI.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use syn::{
+    Attribute, Expr, FnArg, GenericArgument, Item, Lit, Pat, PathArguments, ReturnType, Type,
+};
+
+/// Modules in the order their declarations go into the header
+const MODULES: &[&str] = &[
+    "mod",
+    "reactive",
+    "fsm",
+    "animation",
+    "paint",
+    "widget",
+    "app",
+];
+
+const PREAMBLE: &str = "\
+/*
+ * Junita runtime C ABI
+ *
+ * Generated from crates/junita_runtime/src/abi by tests/header.rs; do not
+ * edit. See the `abi` module documentation for ownership rules.
+ */
+
+#ifndef JUNITA_H
+#define JUNITA_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern \"C\" {
+#endif
+";
+
+const POSTAMBLE: &str = "
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* JUNITA_H */
+";
+
+fn doc_comment(out: &mut String, attrs: &[Attribute]) {
+    let mut lines = Vec::new();
+    for attr in attrs {
+        let syn::Meta::NameValue(meta) = &attr.meta else {
+            continue;
+        };
+        let Expr::Lit(syn::ExprLit {
+            lit: Lit::Str(doc), ..
+        }) = &meta.value
+        else {
+            continue;
+        };
+        if !meta.path.is_ident("doc") {
+            continue;
+        }
+        let line = doc.value();
+        // Rust-only sections end the C comment
+        if line.trim() == "# Safety" {
+            break;
+        }
+        lines.push(line.replace("[`", "").replace("`]", "").replace('`', ""));
+    }
+    while lines.last().is_some_and(|l| l.trim().is_empty()) {
+        lines.pop();
+    }
+    for line in lines {
+        writeln!(out, "//{}", line.trim_end()).unwrap();
+    }
+}
+
+fn c_type(ty: &Type) -> String {
+    match ty {
+        Type::Ptr(ptr) => {
+            let inner = c_type(&ptr.elem);
+            if ptr.const_token.is_some() {
+                format!("const {} *", inner)
+            } else {
+                format!("{} *", inner)
+            }
+        }
+        Type::Tuple(tuple) if tuple.elems.is_empty() => "void".to_string(),
+        Type::Path(path) => {
+            let name = path.path.segments.last().unwrap().ident.to_string();
+            match name.as_str() {
+                "i32" => "int32_t",
+                "u32" => "uint32_t",
+                "i64" => "int64_t",
+                "u64" => "uint64_t",
+                "f32" => "float",
+                "f64" => "double",
+                "bool" => "bool",
+                "usize" => "size_t",
+                "c_char" => "char",
+                "c_void" => "void",
+                _ => return name,
+            }
+            .to_string()
+        }
+        other => panic!("no C type for `{}`", quote_type(other)),
+    }
+}
+
+fn quote_type(ty: &Type) -> String {
+    match ty {
+        Type::Path(path) => path.path.segments.last().unwrap().ident.to_string(),
+        _ => "?".to_string(),
+    }
+}
+
+fn c_return(output: &ReturnType) -> String {
+    match output {
+        ReturnType::Default => "void".to_string(),
+        ReturnType::Type(_, ty) => c_type(ty),
+    }
+}
+
+fn c_param(name: &str, ty: &Type) -> String {
+    let ty = c_type(ty);
+    if ty.ends_with('*') {
+        format!("{}{}", ty, name)
+    } else {
+        format!("{} {}", ty, name)
+    }
+}
+
+fn c_params(params: Vec<String>) -> String {
+    if params.is_empty() {
+        "void".to_string()
+    } else {
+        params.join(", ")
+    }
+}
+
+/// `Option<unsafe extern "C" fn(..) -> R>` as a function pointer typedef
+fn fn_pointer(name: &str, ty: &Type) -> Option<String> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    let Some(GenericArgument::Type(Type::BareFn(f))) = args.args.first() else {
+        return None;
+    };
+    let params = f
+        .inputs
+        .iter()
+        .map(|arg| {
+            let name = arg
+                .name
+                .as_ref()
+                .map_or(String::new(), |(n, _)| n.to_string());
+            c_param(&name, &arg.ty)
+        })
+        .collect();
+    Some(format!(
+        "typedef {} (*{})({});",
+        c_return(&f.output),
+        name,
+        c_params(params)
+    ))
+}
+
+fn is_exported(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|a| a.path().is_ident("no_mangle"))
+}
+
+fn is_repr_c(attrs: &[Attribute]) -> bool {
+    attrs.iter().any(|a| {
+        a.path().is_ident("repr") && a.parse_args::<syn::Ident>().is_ok_and(|ident| ident == "C")
+    })
+}
+
+fn last_define(out: &str) -> bool {
+    out.lines()
+        .last()
+        .is_some_and(|line| line.starts_with("#define"))
+}
+
+/// Generate the header, returning it with the names of exported functions
+fn generate(abi_dir: &Path) -> (String, Vec<String>) {
+    let mut out = String::from(PREAMBLE);
+    let mut functions = Vec::new();
+
+    for module in MODULES {
+        let path = abi_dir.join(format!("{}.rs", module));
+        let source = std::fs::read_to_string(&path).unwrap();
+        let file = syn::parse_file(&source).unwrap();
+        writeln!(
+            out,
+            "\n/* {} */",
+            path.file_name().unwrap().to_string_lossy()
+        )
+        .unwrap();
+
+        for item in &file.items {
+            let mut decl = String::new();
+            match item {
+                Item::Type(alias) if matches!(alias.vis, syn::Visibility::Public(_)) => {
+                    doc_comment(&mut decl, &alias.attrs);
+                    let name = alias.ident.to_string();
+                    match fn_pointer(&name, &alias.ty) {
+                        Some(typedef) => decl.push_str(&typedef),
+                        None => write!(decl, "typedef {} {};", c_type(&alias.ty), name).unwrap(),
+                    }
+                }
+                Item::Const(constant) if matches!(constant.vis, syn::Visibility::Public(_)) => {
+                    doc_comment(&mut decl, &constant.attrs);
+                    let value = match &*constant.expr {
+                        Expr::Lit(syn::ExprLit {
+                            lit: Lit::Int(int), ..
+                        }) => int.base10_digits().to_string(),
+                        Expr::Path(path) => {
+                            let segments: Vec<_> = path
+                                .path
+                                .segments
+                                .iter()
+                                .map(|s| s.ident.to_string())
+                                .collect();
+                            match segments.join("::").as_str() {
+                                "u32::MAX" => "UINT32_MAX".to_string(),
+                                other => panic!("unsupported constant `{}`", other),
+                            }
+                        }
+                        _ => panic!("unsupported constant `{}`", constant.ident),
+                    };
+                    write!(decl, "#define {} {}", constant.ident, value).unwrap();
+                }
+                Item::Struct(item) if matches!(item.vis, syn::Visibility::Public(_)) => {
+                    doc_comment(&mut decl, &item.attrs);
+                    let name = item.ident.to_string();
+                    if is_repr_c(&item.attrs) {
+                        writeln!(decl, "typedef struct {} {{", name).unwrap();
+                        for field in &item.fields {
+                            let mut field_doc = String::new();
+                            doc_comment(&mut field_doc, &field.attrs);
+                            for line in field_doc.lines() {
+                                writeln!(decl, "    {}", line).unwrap();
+                            }
+                            let field_name = field.ident.as_ref().unwrap().to_string();
+                            writeln!(decl, "    {};", c_param(&field_name, &field.ty)).unwrap();
+                        }
+                        write!(decl, "}} {};", name).unwrap();
+                    } else {
+                        // Only ever used behind a pointer
+                        write!(decl, "typedef struct {} {};", name, name).unwrap();
+                    }
+                }
+                Item::Fn(f) if is_exported(&f.attrs) => {
+                    doc_comment(&mut decl, &f.attrs);
+                    let params = f
+                        .sig
+                        .inputs
+                        .iter()
+                        .map(|arg| match arg {
+                            FnArg::Typed(arg) => {
+                                let Pat::Ident(name) = &*arg.pat else {
+                                    panic!("unnamed parameter in `{}`", f.sig.ident)
+                                };
+                                c_param(&name.ident.to_string(), &arg.ty)
+                            }
+                            FnArg::Receiver(_) => panic!("method exported"),
+                        })
+                        .collect();
+                    let ret = c_return(&f.sig.output);
+                    let sep = if ret.ends_with('*') { "" } else { " " };
+                    write!(decl, "{}{}{}({});", ret, sep, f.sig.ident, c_params(params)).unwrap();
+                    functions.push(f.sig.ident.to_string());
+                }
+                _ => continue,
+            }
+            // Runs of constants stay together
+            if !(decl.starts_with("#define") && last_define(&out)) {
+                out.push('\n');
+            }
+            out.push_str(&decl);
+            out.push('\n');
+        }
+    }
+    out.push_str(POSTAMBLE);
+    (out, functions)
+}
+
+#[test]
+fn header_is_current() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let (header, _) = generate(&root.join("src/abi"));
+    let path = root.join("include/junita.h");
+    if std::env::var_os("JUNITA_BLESS_HEADER").is_some() {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, &header).unwrap();
+        return;
+    }
+    let current = std::fs::read_to_string(&path).unwrap_or_default();
+    assert!(
+        current == header,
+        "include/junita.h is out of date; rerun with JUNITA_BLESS_HEADER=1"
+    );
+}
+
+#[test]
+fn preamble_is_exported() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let (_, functions) = generate(&root.join("src/abi"));
+    let grammar = std::fs::read_to_string(root.join("../../grammars/junita.zyn")).unwrap();
+    let start = grammar.find("\"extern\": [").expect("extern preamble");
+    let end = start + grammar[start..].find(']').unwrap();
+    let declared: Vec<_> = grammar[start..end]
+        .split('"')
+        .filter(|s| s.starts_with("junita_"))
+        .collect();
+    assert!(declared.len() > 40);
+    for name in declared {
+        assert!(
+            functions.iter().any(|f| f == name),
+            "`{}` is not exported",
+            name
+        );
+    }
+}
@@ -0,0 +1,247 @@
+//! Concrete syntax tree
+//!
+//! Nodes own their children in source order. Tokens keep their text, so a
+//! node's text is the concatenation of its tokens, trivia included.
+
+use std::ops::Range;
+
+use super::SyntaxKind;
+
+/// A token in the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxToken {
+    pub kind: SyntaxKind,
+    pub span: Range<usize>,
+    pub text: String,
+}
+
+/// A node in the tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SyntaxNode {
+    pub kind: SyntaxKind,
+    pub span: Range<usize>,
+    pub children: Vec<SyntaxElement>,
+}
+
+/// Child of a node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SyntaxElement {
+    Node(SyntaxNode),
+    Token(SyntaxToken),
+}
+
+impl SyntaxElement {
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            SyntaxElement::Node(node) => node.span.clone(),
+            SyntaxElement::Token(token) => token.span.clone(),
+        }
+    }
+}
+
+impl SyntaxNode {
+    /// Source text of the node, trivia included
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        self.write_text(&mut text);
+        text
+    }
+
+    fn write_text(&self, out: &mut String) {
+        for child in &self.children {
+            match child {
+                SyntaxElement::Node(node) => node.write_text(out),
+                SyntaxElement::Token(token) => out.push_str(&token.text),
+            }
+        }
+    }
+
+    /// Child nodes
+    pub fn nodes(&self) -> impl Iterator<Item = &SyntaxNode> {
+        self.children.iter().filter_map(|c| match c {
+            SyntaxElement::Node(node) => Some(node),
+            SyntaxElement::Token(_) => None,
+        })
+    }
+
+    /// Child tokens, trivia excluded
+    pub fn tokens(&self) -> impl Iterator<Item = &SyntaxToken> {
+        self.children.iter().filter_map(|c| match c {
+            SyntaxElement::Token(token) if !token.kind.is_trivia() => Some(token),
+            _ => None,
+        })
+    }
+
+    /// First child node of `kind`
+    pub fn node(&self, kind: SyntaxKind) -> Option<&SyntaxNode> {
+        self.nodes().find(|n| n.kind == kind)
+    }
+
+    /// First child token of `kind`
+    pub fn token(&self, kind: SyntaxKind) -> Option<&SyntaxToken> {
+        self.tokens().find(|t| t.kind == kind)
+    }
+
+    /// The node's name: its first identifier token
+    pub fn name(&self) -> Option<&SyntaxToken> {
+        self.token(SyntaxKind::IDENT)
+    }
+
+    /// First child node that is an expression
+    pub fn expr(&self) -> Option<&SyntaxNode> {
+        self.nodes().find(|n| n.kind.is_expr())
+    }
+
+    /// All nodes below this one, in source order
+    pub fn descendants(&self) -> Vec<&SyntaxNode> {
+        let mut out = Vec::new();
+        let mut stack: Vec<&SyntaxNode> = self.nodes().collect();
+        stack.reverse();
+        while let Some(node) = stack.pop() {
+            out.push(node);
+            let start = stack.len();
+            stack.extend(node.nodes());
+            stack[start..].reverse();
+        }
+        out
+    }
+
+    /// Nodes containing `offset`, from this one down to the innermost
+    ///
+    /// At a boundary between two nodes the one starting at `offset` wins,
+    /// unless only the one ending there contains it (the cursor right after
+    /// a name).
+    pub fn ancestors_at(&self, offset: usize) -> Vec<&SyntaxNode> {
+        let mut path = vec![self];
+        let mut node = self;
+        loop {
+            let inside = |n: &&SyntaxNode| n.span.start <= offset && offset < n.span.end;
+            let ending = |n: &&SyntaxNode| n.span.end == offset && !n.span.is_empty();
+            match node.nodes().find(inside).or_else(|| node.nodes().find(ending)) {
+                Some(child) => {
+                    path.push(child);
+                    node = child;
+                }
+                None => return path,
+            }
+        }
+    }
+
+    /// Significant token at `offset`, preferring names over punctuation
+    /// when the offset sits between two tokens
+    pub fn token_at(&self, offset: usize) -> Option<&SyntaxToken> {
+        let node = *self.ancestors_at(offset).last()?;
+        let mut candidates = node
+            .tokens()
+            .filter(|t| t.span.start <= offset && offset <= t.span.end);
+        let first = candidates.next()?;
+        let named = |t: &SyntaxToken| matches!(t.kind, SyntaxKind::IDENT | SyntaxKind::DECORATOR);
+        match candidates.next() {
+            Some(second) if named(second) && !named(first) => Some(second),
+            _ => Some(first),
+        }
+    }
+
+    /// Source text between the node's first `{` and its matching `}`
+    pub fn braced_text<'s>(&self, source: &'s str) -> Option<&'s str> {
+        let open = self.token(SyntaxKind::L_BRACE)?;
+        let close = self.token(SyntaxKind::R_BRACE)?;
+        Some(source[open.span.end..close.span.start].trim())
+    }
+}
+
+impl SyntaxKind {
+    pub fn is_expr(self) -> bool {
+        use SyntaxKind::*;
+        matches!(
+            self,
+            LITERAL
+                | NAME_REF
+                | PATH_EXPR
+                | PAREN_EXPR
+                | UNARY_EXPR
+                | BINARY_EXPR
+                | CALL_EXPR
+                | FIELD_EXPR
+                | INDEX_EXPR
+                | ARRAY_EXPR
+                | LAMBDA_EXPR
+                | ACTION_EXPR
+                | BLOCK
+                | ELEMENT
+                | ERROR_NODE
+        )
+    }
+}
+
+/// Builds a tree from a flat stream of start, token and finish calls
+pub(super) struct TreeBuilder {
+    stack: Vec<(SyntaxKind, Vec<SyntaxElement>)>,
+    /// End of the last token, used as the span of empty nodes
+    offset: usize,
+}
+
+impl TreeBuilder {
+    pub fn new(root: SyntaxKind) -> Self {
+        Self {
+            stack: vec![(root, Vec::new())],
+            offset: 0,
+        }
+    }
+
+    pub fn start_node(&mut self, kind: SyntaxKind) {
+        self.stack.push((kind, Vec::new()));
+    }
+
+    /// Position to wrap later children from with [`Self::start_node_at`]
+    pub fn checkpoint(&self) -> usize {
+        self.current().len()
+    }
+
+    /// Start a node that takes the children added since `checkpoint`
+    pub fn start_node_at(&mut self, checkpoint: usize, kind: SyntaxKind) {
+        let children = self.current_mut().split_off(checkpoint);
+        self.stack.push((kind, children));
+    }
+
+    pub fn finish_node(&mut self) {
+        let (kind, children) = self.stack.pop().expect("unbalanced finish_node");
+        let node = self.make_node(kind, children);
+        self.current_mut().push(SyntaxElement::Node(node));
+    }
+
+    pub fn token(&mut self, kind: SyntaxKind, span: Range<usize>, text: &str) {
+        self.offset = span.end;
+        self.current_mut().push(SyntaxElement::Token(SyntaxToken {
+            kind,
+            span,
+            text: text.to_string(),
+        }));
+    }
+
+    pub fn finish(mut self) -> SyntaxNode {
+        assert_eq!(self.stack.len(), 1, "unfinished nodes");
+        let (kind, children) = self.stack.pop().expect("builder has a root");
+        self.make_node(kind, children)
+    }
+
+    fn make_node(&self, kind: SyntaxKind, children: Vec<SyntaxElement>) -> SyntaxNode {
+        let span = match (children.first(), children.last()) {
+            (Some(first), Some(last)) => first.span().start..last.span().end,
+            _ => self.offset..self.offset,
+        };
+        SyntaxNode {
+            kind,
+            span,
+            children,
+        }
+    }
+
+    fn current(&self) -> &Vec<SyntaxElement> {
+        &self.stack.last().expect("builder has a root").1
+    }
+
+    fn current_mut(&mut self) -> &mut Vec<SyntaxElement> {
+        &mut self.stack.last_mut().expect("builder has a root").1
+    }
+}
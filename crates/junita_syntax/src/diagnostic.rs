@@ -0,0 +1,174 @@
+//! Diagnostics reported while parsing and checking `.junita` source
+//!
+//! Diagnostics carry byte spans into the source and are rendered with
+//! `codespan-reporting`, so they show the file, line, column and an
+//! underlined snippet:
+//!
+//! ```text
+//! error: expected `:` after the state name
+//!   ┌─ src/main.junita:6:18
+//!   │
+//! 6 │     @state count i32 = 0
+//!   │                  ^^^ expected `:`
+//!   │
+//!   = help: declare the type as `count: i32`
+//! ```
+
+use std::io;
+use std::ops::Range;
+
+use codespan_reporting::diagnostic::{self as codespan, LabelStyle};
+use codespan_reporting::files::SimpleFile;
+use codespan_reporting::term::{self, termcolor::WriteColor};
+
+/// How serious a diagnostic is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// A source range annotated with a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Label {
+    pub span: Range<usize>,
+    pub message: String,
+    /// Primary labels point at the problem, secondary ones at context
+    pub primary: bool,
+}
+
+/// A problem found in a source file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<Label>,
+    /// Suggestions shown below the snippet
+    pub help: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self::new(Severity::Error, message)
+    }
+
+    pub fn warning(message: impl Into<String>) -> Self {
+        Self::new(Severity::Warning, message)
+    }
+
+    fn new(severity: Severity, message: impl Into<String>) -> Self {
+        Self {
+            severity,
+            message: message.into(),
+            labels: Vec::new(),
+            help: Vec::new(),
+        }
+    }
+
+    /// Add the primary label
+    pub fn with_label(mut self, span: Range<usize>, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+            primary: true,
+        });
+        self
+    }
+
+    /// Add a secondary label pointing at related source
+    pub fn with_secondary(mut self, span: Range<usize>, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            span,
+            message: message.into(),
+            primary: false,
+        });
+        self
+    }
+
+    /// Add a suggestion
+    pub fn with_help(mut self, help: impl Into<String>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// Span of the primary label
+    pub fn span(&self) -> Option<Range<usize>> {
+        self.labels
+            .iter()
+            .find(|l| l.primary)
+            .map(|l| l.span.clone())
+    }
+
+    fn to_codespan(&self) -> codespan::Diagnostic<()> {
+        let base = match self.severity {
+            Severity::Error => codespan::Diagnostic::error(),
+            Severity::Warning => codespan::Diagnostic::warning(),
+        };
+        let labels = self
+            .labels
+            .iter()
+            .map(|l| {
+                let style = if l.primary {
+                    LabelStyle::Primary
+                } else {
+                    LabelStyle::Secondary
+                };
+                codespan::Label::new(style, (), l.span.clone()).with_message(&l.message)
+            })
+            .collect();
+        let notes = self.help.iter().map(|h| format!("help: {}", h)).collect();
+        base.with_message(&self.message)
+            .with_labels(labels)
+            .with_notes(notes)
+    }
+}
+
+/// Render `diagnostics` for the file `name` to `writer`
+pub fn emit(
+    writer: &mut dyn WriteColor,
+    name: &str,
+    source: &str,
+    diagnostics: &[Diagnostic],
+) -> io::Result<()> {
+    let file = SimpleFile::new(name, source);
+    let config = term::Config::default();
+    for diagnostic in diagnostics {
+        term::emit(writer, &config, &file, &diagnostic.to_codespan()).map_err(|e| match e {
+            codespan_reporting::files::Error::Io(e) => e,
+            other => io::Error::other(other.to_string()),
+        })?;
+    }
+    Ok(())
+}
+
+/// Render `diagnostics` as plain text
+pub fn render(name: &str, source: &str, diagnostics: &[Diagnostic]) -> String {
+    let mut buffer = term::termcolor::NoColor::new(Vec::new());
+    // Writing into memory can't fail
+    let _ = emit(&mut buffer, name, source, diagnostics);
+    String::from_utf8_lossy(&buffer.into_inner()).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_shows_location_and_help() {
+        let source = "@widget App {\n    @state count i32 = 0\n}\n";
+        let diagnostic = Diagnostic::error("expected `:` after the state name")
+            .with_label(31..34, "expected `:`")
+            .with_secondary(18..24, "in this state declaration")
+            .with_help("declare the type as `count: i32`");
+
+        let text = render("main.junita", source, &[diagnostic]);
+        assert!(text.contains("error: expected `:` after the state name"));
+        assert!(text.contains("main.junita:2:18"));
+        assert!(text.contains("@state count i32 = 0"));
+        assert!(text.contains("help: declare the type as `count: i32`"));
+    }
+}
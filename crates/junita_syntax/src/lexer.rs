@@ -0,0 +1,238 @@
+//! Lexer for `.junita` source
+//!
+//! Produces every byte of the input as a token, including whitespace and
+//! comments, so the parser can build a lossless tree. Lexing never fails:
+//! unknown characters become `ERROR` tokens and unterminated strings and
+//! block comments are reported as diagnostics.
+
+use std::ops::Range;
+
+use super::diagnostic::Diagnostic;
+use super::SyntaxKind::{self, *};
+
+/// A token with its byte range in the source
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub kind: SyntaxKind,
+    pub span: Range<usize>,
+}
+
+/// Multi-character punctuation first so it wins over its prefixes
+const PUNCTUATION: &[(&str, SyntaxKind)] = &[
+    ("->", ARROW),
+    ("=>", FAT_ARROW),
+    ("::", COLON2),
+    ("==", EQ2),
+    ("!=", NEQ),
+    ("<=", LTE),
+    (">=", GTE),
+    ("&&", AMP2),
+    ("||", PIPE2),
+    ("+=", PLUS_EQ),
+    ("-=", MINUS_EQ),
+    ("*=", STAR_EQ),
+    ("/=", SLASH_EQ),
+    ("{", L_BRACE),
+    ("}", R_BRACE),
+    ("(", L_PAREN),
+    (")", R_PAREN),
+    ("[", L_BRACKET),
+    ("]", R_BRACKET),
+    (",", COMMA),
+    (":", COLON),
+    (";", SEMICOLON),
+    (".", DOT),
+    ("=", EQ),
+    ("<", LT),
+    (">", GT),
+    ("+", PLUS),
+    ("-", MINUS),
+    ("*", STAR),
+    ("/", SLASH),
+    ("%", PERCENT),
+    ("!", BANG),
+];
+
+/// Split `source` into tokens
+pub fn tokenize(source: &str) -> (Vec<Token>, Vec<Diagnostic>) {
+    let bytes = source.as_bytes();
+    let mut tokens = Vec::new();
+    let mut diagnostics = Vec::new();
+    let mut pos = 0;
+
+    while pos < bytes.len() {
+        let start = pos;
+        let rest = &source[pos..];
+        let c = rest.chars().next().unwrap_or_default();
+
+        let kind = if c.is_whitespace() {
+            pos += take_while(rest, char::is_whitespace);
+            WHITESPACE
+        } else if rest.starts_with("//") {
+            pos += rest.find('\n').unwrap_or(rest.len());
+            COMMENT
+        } else if let Some(comment) = rest.strip_prefix("/*") {
+            match comment.find("*/") {
+                Some(end) => pos += end + 4,
+                None => {
+                    pos = bytes.len();
+                    diagnostics.push(
+                        Diagnostic::error("unterminated block comment")
+                            .with_label(start..start + 2, "comment starts here")
+                            .with_help("close the comment with `*/`"),
+                    );
+                }
+            }
+            COMMENT
+        } else if c == '"' {
+            let (len, closed) = string_len(rest);
+            pos += len;
+            if !closed {
+                diagnostics.push(
+                    Diagnostic::error("unterminated string literal")
+                        .with_label(start..start + 1, "string starts here")
+                        .with_help("add a closing `\"`"),
+                );
+            }
+            STRING
+        } else if c == '#' && rest[1..].starts_with(|c: char| c.is_ascii_alphanumeric()) {
+            pos += 1 + take_while(&rest[1..], |c| c.is_ascii_alphanumeric());
+            COLOR
+        } else if c == '@' && rest[1..].starts_with(is_ident_start) {
+            pos += 1 + take_while(&rest[1..], is_ident_continue);
+            DECORATOR
+        } else if c.is_ascii_digit() {
+            let (len, kind) = number_len(rest);
+            pos += len;
+            kind
+        } else if is_ident_start(c) {
+            pos += take_while(rest, is_ident_continue);
+            IDENT
+        } else if let Some((text, kind)) = PUNCTUATION.iter().find(|(p, _)| rest.starts_with(p)) {
+            pos += text.len();
+            *kind
+        } else {
+            pos += c.len_utf8();
+            diagnostics.push(
+                Diagnostic::error(format!("unexpected character `{}`", c))
+                    .with_label(start..pos, "not valid here"),
+            );
+            ERROR
+        };
+
+        tokens.push(Token {
+            kind,
+            span: start..pos,
+        });
+    }
+
+    (tokens, diagnostics)
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_ident_continue(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn take_while(s: &str, f: impl Fn(char) -> bool) -> usize {
+    s.find(|c| !f(c)).unwrap_or(s.len())
+}
+
+/// Length of the string literal at the start of `s`, up to the closing
+/// quote or the end of the line, and whether an unescaped quote closed it
+fn string_len(s: &str) -> (usize, bool) {
+    let mut escaped = false;
+    for (i, c) in s.char_indices().skip(1) {
+        match c {
+            '\\' if !escaped => escaped = true,
+            '"' if !escaped => return (i + 1, true),
+            '\n' => return (i, false),
+            _ => escaped = false,
+        }
+    }
+    (s.len(), false)
+}
+
+/// Length and kind of the number at the start of `s`
+///
+/// Unit suffixes (`16px`, `300ms`, `2s`) are part of the token.
+fn number_len(s: &str) -> (usize, SyntaxKind) {
+    let mut len = take_while(s, |c| c.is_ascii_digit());
+    let mut kind = INT;
+    let after = &s[len..];
+    if after.starts_with('.') && after[1..].starts_with(|c: char| c.is_ascii_digit()) {
+        len += 1 + take_while(&after[1..], |c| c.is_ascii_digit());
+        kind = FLOAT;
+    }
+    len += take_while(&s[len..], |c| c.is_ascii_alphabetic());
+    (len, kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<SyntaxKind> {
+        tokenize(source)
+            .0
+            .into_iter()
+            .map(|t| t.kind)
+            .filter(|k| !k.is_trivia())
+            .collect()
+    }
+
+    #[test]
+    fn test_tokens_cover_source() {
+        let source = "@widget Counter { // note\n  @state count: i32 = -1 }";
+        let (tokens, diagnostics) = tokenize(source);
+        assert!(diagnostics.is_empty());
+
+        let text: String = tokens.iter().map(|t| &source[t.span.clone()]).collect();
+        assert_eq!(text, source);
+        assert_eq!(tokens[0].kind, DECORATOR);
+        assert!(tokens.iter().any(|t| t.kind == COMMENT));
+    }
+
+    #[test]
+    fn test_literals_and_punctuation() {
+        assert_eq!(
+            kinds(r#"300ms 1.5 #fff "a \" b" a -> b => c += 1"#),
+            vec![INT, FLOAT, COLOR, STRING, IDENT, ARROW, IDENT, FAT_ARROW, IDENT, PLUS_EQ, INT]
+        );
+        // `1.` followed by a method is an int and a dot
+        assert_eq!(kinds("1.abs()"), vec![INT, DOT, IDENT, L_PAREN, R_PAREN]);
+    }
+
+    #[test]
+    fn test_lex_errors() {
+        let (tokens, diagnostics) = tokenize("\"open\n$ /* never closed");
+        assert_eq!(tokens[0].kind, STRING);
+        assert_eq!(tokens[0].span, 0..5);
+        let messages: Vec<_> = diagnostics.iter().map(|d| d.message.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "unterminated string literal",
+                "unexpected character `$`",
+                "unterminated block comment"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escaped_quote_does_not_close_string() {
+        for source in [r#""abc\""#, "\"abc\\\"\nx"] {
+            let (tokens, diagnostics) = tokenize(source);
+            assert_eq!(tokens[0].kind, STRING);
+            assert_eq!(tokens[0].span, 0..6);
+            assert_eq!(diagnostics.len(), 1, "{:?}", source);
+            assert_eq!(diagnostics[0].message, "unterminated string literal");
+        }
+        // An escaped backslash before the quote still closes it
+        let (_, diagnostics) = tokenize(r#""abc\\""#);
+        assert!(diagnostics.is_empty());
+    }
+}
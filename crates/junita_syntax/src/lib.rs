@@ -0,0 +1,156 @@
+//! `.junita` syntax
+//!
+//! A lossless parser for the DSL described by `grammars/junita.zyn`, plus
+//! the dialect the examples use (`Column { spacing: 16 .. }` render trees and
+//! `idle -> active: pointer_enter` transitions).
+//!
+//! - [`lexer`] splits the source into tokens, trivia included
+//! - [`parser`] builds a concrete syntax tree where every token, comment and
+//!   space is kept with its byte span, so `tree.text() == source`
+//! - [`validate`] checks the tree for problems the grammar can't express,
+//!   like duplicate names
+//!
+//! Parsing never fails. Problems are collected as [`Diagnostic`]s and the
+//! parser recovers at the next declaration or closing brace, so a file with
+//! several typos reports all of them.
+//!
+//! Render bodies and expressions can also be parsed on their own with
+//! [`parse_view`] and [`parse_expr`], for tools like the interpreter that
+//! keep them as text.
+
+mod cst;
+mod diagnostic;
+mod lexer;
+mod parser;
+mod validate;
+
+pub use cst::{SyntaxElement, SyntaxNode, SyntaxToken};
+pub use diagnostic::{emit, render, Diagnostic, Severity};
+pub use parser::{
+    parse, parse_expr, parse_syntax, parse_view, Parse, TOP_LEVEL_DECORATORS, WIDGET_DECORATORS,
+};
+
+/// Kinds of tokens and nodes in the syntax tree
+#[allow(non_camel_case_types, clippy::upper_case_acronyms)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SyntaxKind {
+    // Trivia
+    WHITESPACE,
+    COMMENT,
+
+    // Literals and names
+    IDENT,
+    /// `@widget`, `@state`, `@action`, ..
+    DECORATOR,
+    INT,
+    FLOAT,
+    STRING,
+    /// `#rgb` / `#rrggbb`
+    COLOR,
+
+    // Punctuation
+    L_BRACE,
+    R_BRACE,
+    L_PAREN,
+    R_PAREN,
+    L_BRACKET,
+    R_BRACKET,
+    COMMA,
+    COLON,
+    COLON2,
+    SEMICOLON,
+    DOT,
+    EQ,
+    EQ2,
+    NEQ,
+    LT,
+    LTE,
+    GT,
+    GTE,
+    PLUS,
+    MINUS,
+    STAR,
+    SLASH,
+    PERCENT,
+    BANG,
+    AMP2,
+    PIPE2,
+    ARROW,
+    FAT_ARROW,
+    PLUS_EQ,
+    MINUS_EQ,
+    STAR_EQ,
+    SLASH_EQ,
+
+    /// Unrecognized character
+    ERROR,
+    /// End of input; never stored in the tree
+    EOF,
+
+    // Declarations
+    SOURCE_FILE,
+    /// Root of a render body or expression parsed on its own
+    FRAGMENT,
+    IMPORT,
+    WIDGET,
+    PROP_DECL,
+    STATE_DECL,
+    DERIVED_DECL,
+    MACHINE,
+    /// `idle -> active: pointer_enter` or `on pointer_enter => active`
+    TRANSITION,
+    /// `idle { on pointer_enter => active }`
+    STATE_BLOCK,
+    ANIMATION,
+    SPRING,
+    EFFECT,
+    RENDER,
+    PAINT,
+    /// `name: value`
+    PROPERTY,
+    /// `name { .. }`
+    PROPERTY_BLOCK,
+    TYPE,
+    PARAM_LIST,
+
+    // Render tree
+    /// `Button { label: "+" }`
+    ELEMENT,
+    /// Braced props and children of an element, `@render` or conditional
+    ELEMENT_LIST,
+    /// `if cond { .. } else { .. }` inside a render tree
+    CONDITIONAL,
+
+    // Statements
+    BLOCK,
+    LET_STMT,
+    ASSIGN_STMT,
+    EXPR_STMT,
+    IF_STMT,
+
+    // Expressions
+    LITERAL,
+    NAME_REF,
+    /// `Color::rgb`
+    PATH_EXPR,
+    PAREN_EXPR,
+    UNARY_EXPR,
+    BINARY_EXPR,
+    CALL_EXPR,
+    ARG_LIST,
+    FIELD_EXPR,
+    INDEX_EXPR,
+    ARRAY_EXPR,
+    LAMBDA_EXPR,
+    /// `@action { .. }`
+    ACTION_EXPR,
+
+    /// Tokens the parser skipped while recovering
+    ERROR_NODE,
+}
+
+impl SyntaxKind {
+    pub fn is_trivia(self) -> bool {
+        matches!(self, SyntaxKind::WHITESPACE | SyntaxKind::COMMENT)
+    }
+}
@@ -0,0 +1,1277 @@
+//! Error-recovering parser
+//!
+//! A recursive descent parser over the token stream, with a Pratt loop for
+//! binary operators. Each rule opens a node, consumes its tokens and closes
+//! the node; whitespace and comments are attached between significant
+//! tokens as they are consumed.
+//!
+//! Recovery works at two levels. Inside braces, a token that can't start an
+//! item is wrapped in an `ERROR_NODE` and skipped. A decorator that only
+//! makes sense further out (`@widget` inside a render tree, `@state` inside
+//! an element) closes the current block with an "unclosed `{`" error, so a
+//! missing brace doesn't swallow the rest of the file.
+
+use std::ops::Range;
+
+use super::cst::{SyntaxNode, TreeBuilder};
+use super::diagnostic::Diagnostic;
+use super::lexer::{tokenize, Token};
+use super::validate::validate;
+use super::SyntaxKind::{self, *};
+
+/// Result of parsing a source file
+#[derive(Debug, Clone)]
+pub struct Parse {
+    pub root: SyntaxNode,
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+/// Parse and validate a `.junita` source file
+pub fn parse(source: &str) -> Parse {
+    let mut parse = parse_syntax(source);
+    parse.diagnostics.extend(validate(&parse.root));
+    parse
+        .diagnostics
+        .sort_by_key(|d| d.span().map_or(0, |s| s.start));
+    parse
+}
+
+/// Parse without the checks in [`validate`], for tools that only need a
+/// well-formed tree
+pub fn parse_syntax(source: &str) -> Parse {
+    let (tokens, mut diagnostics) = tokenize(source);
+    let mut parser = Parser {
+        source,
+        tokens,
+        pos: 0,
+        builder: TreeBuilder::new(SOURCE_FILE),
+        diagnostics: Vec::new(),
+    };
+    parser.source_file();
+
+    let Parser {
+        builder,
+        diagnostics: parse_diagnostics,
+        ..
+    } = parser;
+    let root = builder.finish();
+    diagnostics.extend(parse_diagnostics);
+    diagnostics.sort_by_key(|d| d.span().map_or(0, |s| s.start));
+
+    Parse { root, diagnostics }
+}
+
+/// Parse the contents of a render block: elements, conditionals and
+/// string children up to the end of `source`
+///
+/// The root is a `FRAGMENT` holding the items.
+pub fn parse_view(source: &str) -> Parse {
+    parse_fragment(source, Parser::view)
+}
+
+/// Parse a single expression filling all of `source`
+///
+/// The root is a `FRAGMENT` holding the expression.
+pub fn parse_expr(source: &str) -> Parse {
+    parse_fragment(source, Parser::standalone_expr)
+}
+
+fn parse_fragment<'s>(source: &'s str, rule: impl FnOnce(&mut Parser<'s>)) -> Parse {
+    let (tokens, mut diagnostics) = tokenize(source);
+    let mut parser = Parser {
+        source,
+        tokens,
+        pos: 0,
+        builder: TreeBuilder::new(FRAGMENT),
+        diagnostics: Vec::new(),
+    };
+    rule(&mut parser);
+    parser.flush_trivia();
+
+    let root = parser.builder.finish();
+    diagnostics.extend(parser.diagnostics);
+    diagnostics.sort_by_key(|d| d.span().map_or(0, |s| s.start));
+
+    Parse { root, diagnostics }
+}
+
+/// Decorators allowed at the top of a file
+pub const TOP_LEVEL_DECORATORS: &[&str] = &["@widget", "@machine", "@animation", "@spring"];
+
+/// Decorators allowed inside a widget
+pub const WIDGET_DECORATORS: &[&str] = &[
+    "@prop",
+    "@state",
+    "@derived",
+    "@machine",
+    "@animation",
+    "@spring",
+    "@effect",
+    "@render",
+    "@paint",
+];
+
+/// Binding power of binary operators, loosest first
+fn binding_power(kind: SyntaxKind) -> Option<u8> {
+    Some(match kind {
+        PIPE2 => 1,
+        AMP2 => 2,
+        EQ2 | NEQ => 3,
+        LT | LTE | GT | GTE => 4,
+        PLUS | MINUS => 5,
+        STAR | SLASH | PERCENT => 6,
+        _ => return None,
+    })
+}
+
+const PREFIX_BINDING_POWER: u8 = 7;
+
+/// Whether an expression may be a render element (`Text { .. }`)
+///
+/// Off in conditions, where `if a == b { .. }` must not read `b { .. }` as
+/// an element, and in declarations, where `= value { .. }` starts a body.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Elements {
+    Allowed,
+    Forbidden,
+}
+
+struct Parser<'s> {
+    source: &'s str,
+    tokens: Vec<Token>,
+    /// Index of the next unconsumed token, trivia included
+    pos: usize,
+    builder: TreeBuilder,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'s> Parser<'s> {
+    // ========================================================================
+    // Token access
+    // ========================================================================
+
+    /// Index of the `n`th significant token from the cursor
+    fn nth_index(&self, n: usize) -> Option<usize> {
+        (self.pos..self.tokens.len())
+            .filter(|&i| !self.tokens[i].kind.is_trivia())
+            .nth(n)
+    }
+
+    fn nth(&self, n: usize) -> SyntaxKind {
+        self.nth_index(n).map_or(EOF, |i| self.tokens[i].kind)
+    }
+
+    fn nth_text(&self, n: usize) -> &'s str {
+        self.nth_index(n)
+            .map_or("", |i| &self.source[self.tokens[i].span.clone()])
+    }
+
+    fn current(&self) -> SyntaxKind {
+        self.nth(0)
+    }
+
+    fn current_text(&self) -> &'s str {
+        self.nth_text(0)
+    }
+
+    fn at(&self, kind: SyntaxKind) -> bool {
+        self.current() == kind
+    }
+
+    /// At the identifier `keyword`
+    fn at_keyword(&self, keyword: &str) -> bool {
+        self.at(IDENT) && self.current_text() == keyword
+    }
+
+    fn at_decorator(&self, decorators: &[&str]) -> bool {
+        self.at(DECORATOR) && decorators.contains(&self.current_text())
+    }
+
+    /// Span of the next significant token, or an empty span at the end
+    fn current_span(&self) -> Range<usize> {
+        match self.nth_index(0) {
+            Some(i) => self.tokens[i].span.clone(),
+            None => self.source.len()..self.source.len(),
+        }
+    }
+
+    // ========================================================================
+    // Tree building
+    // ========================================================================
+
+    /// Attach pending whitespace and comments to the current node
+    fn flush_trivia(&mut self) {
+        while self.pos < self.tokens.len() && self.tokens[self.pos].kind.is_trivia() {
+            self.push_token();
+        }
+    }
+
+    fn push_token(&mut self) {
+        let token = &self.tokens[self.pos];
+        let text = &self.source[token.span.clone()];
+        self.builder.token(token.kind, token.span.clone(), text);
+        self.pos += 1;
+    }
+
+    /// Consume the next significant token
+    fn bump(&mut self) {
+        self.flush_trivia();
+        if self.pos < self.tokens.len() {
+            self.push_token();
+        }
+    }
+
+    fn eat(&mut self, kind: SyntaxKind) -> bool {
+        if self.at(kind) {
+            self.bump();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn start(&mut self, kind: SyntaxKind) {
+        self.flush_trivia();
+        self.builder.start_node(kind);
+    }
+
+    fn finish(&mut self) {
+        self.builder.finish_node();
+    }
+
+    fn checkpoint(&mut self) -> usize {
+        self.flush_trivia();
+        self.builder.checkpoint()
+    }
+
+    fn start_at(&mut self, checkpoint: usize, kind: SyntaxKind) {
+        self.builder.start_node_at(checkpoint, kind);
+    }
+
+    // ========================================================================
+    // Errors
+    // ========================================================================
+
+    fn error(&mut self, diagnostic: Diagnostic) {
+        self.diagnostics.push(diagnostic);
+    }
+
+    /// How to refer to the next token in a message
+    fn found(&self) -> String {
+        match self.current() {
+            EOF => "end of file".to_string(),
+            IDENT => format!("`{}`", self.current_text()),
+            STRING => "a string".to_string(),
+            INT | FLOAT => format!("the number `{}`", self.current_text()),
+            _ => format!("`{}`", self.current_text()),
+        }
+    }
+
+    /// Report that `expected` is missing at the cursor
+    fn expected(&self, expected: &str) -> Diagnostic {
+        let found = self.found();
+        Diagnostic::error(format!("expected {}, found {}", expected, found))
+            .with_label(self.current_span(), format!("expected {}", expected))
+    }
+
+    /// Consume `kind` or report it missing
+    fn expect(&mut self, kind: SyntaxKind, expected: &str) -> bool {
+        if self.eat(kind) {
+            return true;
+        }
+        let diagnostic = self.expected(expected);
+        self.error(diagnostic);
+        false
+    }
+
+    /// Skip the next token, wrapped in an error node
+    fn bump_error(&mut self) {
+        self.start(ERROR_NODE);
+        self.bump();
+        self.finish();
+    }
+
+    /// Skip the rest of a broken item: up to the end of the line, the next
+    /// decorator or the closing brace
+    fn skip_line(&mut self) {
+        self.start(ERROR_NODE);
+        loop {
+            self.skip_item();
+            if matches!(self.current(), EOF | R_BRACE | DECORATOR) || self.at_line_start() {
+                break;
+            }
+        }
+        self.finish();
+    }
+
+    /// Whether a line break separates the cursor from the next token
+    fn at_line_start(&self) -> bool {
+        let next = self.nth_index(0).unwrap_or(self.tokens.len());
+        self.tokens[self.pos..next]
+            .iter()
+            .any(|t| self.source[t.span.clone()].contains('\n'))
+    }
+
+    /// Skip the next item: a balanced `{ .. }` group or a single token
+    fn skip_item(&mut self) {
+        if !self.at(L_BRACE) {
+            self.bump();
+            return;
+        }
+        let mut depth = 0;
+        while !self.at(EOF) {
+            match self.current() {
+                L_BRACE => depth += 1,
+                R_BRACE => depth -= 1,
+                _ => {}
+            }
+            self.bump();
+            if depth == 0 {
+                break;
+            }
+        }
+    }
+
+    // ========================================================================
+    // Blocks
+    // ========================================================================
+
+    /// Parse `{ item* }`
+    ///
+    /// `item` returns `false` when the next token belongs to an enclosing
+    /// block, which means this one was never closed.
+    fn braced(&mut self, mut item: impl FnMut(&mut Self) -> bool) {
+        let open = self.current_span();
+        if !self.expect(L_BRACE, "`{`") {
+            return;
+        }
+        loop {
+            if self.eat(R_BRACE) {
+                return;
+            }
+            let before = self.pos;
+            let belongs_outside = self.at(EOF) || !item(self);
+            if belongs_outside {
+                let label = if self.at(EOF) {
+                    "file ends here"
+                } else {
+                    "expected `}` before this"
+                };
+                let diagnostic = Diagnostic::error("unclosed `{`")
+                    .with_label(self.current_span(), label)
+                    .with_secondary(open, "this block is never closed")
+                    .with_help("add a `}` to close the block");
+                self.error(diagnostic);
+                return;
+            }
+            if self.pos == before {
+                self.bump_error();
+            }
+            self.eat(COMMA);
+            self.eat(SEMICOLON);
+        }
+    }
+
+    /// Report an unknown decorator and skip its item
+    fn unknown_decorator(&mut self, known: &[&str]) {
+        let name = self.current_text();
+        let mut diagnostic = Diagnostic::error(format!("unknown decorator `{}`", name))
+            .with_label(self.current_span(), "not a known decorator");
+        diagnostic = match suggest(name, known) {
+            Some(suggestion) => diagnostic.with_help(format!("did you mean `{}`?", suggestion)),
+            None => diagnostic.with_help(format!("expected one of {}", list(known))),
+        };
+        self.error(diagnostic);
+
+        self.start(ERROR_NODE);
+        self.bump();
+        while !self.at(EOF) && !self.at(DECORATOR) && !self.at(R_BRACE) {
+            let block = self.at(L_BRACE);
+            self.skip_item();
+            if block {
+                break;
+            }
+        }
+        self.finish();
+    }
+
+    // ========================================================================
+    // Declarations
+    // ========================================================================
+
+    fn source_file(&mut self) {
+        while !self.at(EOF) {
+            match self.current() {
+                DECORATOR => match self.current_text() {
+                    "@widget" => self.widget(),
+                    "@machine" => self.machine(),
+                    "@animation" => self.animation(),
+                    "@spring" => self.spring(),
+                    _ => self.unknown_decorator(TOP_LEVEL_DECORATORS),
+                },
+                IDENT if self.current_text() == "import" => self.import(),
+                _ => self.top_level_error(),
+            }
+        }
+        self.flush_trivia();
+    }
+
+    /// Items of a render body, without the braces around them
+    fn view(&mut self) {
+        while !self.at(EOF) {
+            let before = self.pos;
+            if !self.element_item() {
+                // A declaration decorator has no place in a render body
+                let diagnostic = self.expected("an element");
+                self.error(diagnostic);
+                self.skip_line();
+            } else if self.pos == before {
+                self.bump_error();
+            }
+            self.eat(COMMA);
+            self.eat(SEMICOLON);
+        }
+    }
+
+    fn standalone_expr(&mut self) {
+        self.expr(Elements::Forbidden);
+        if !self.at(EOF) {
+            let diagnostic = self.expected("the end of the expression");
+            self.error(diagnostic);
+            self.start(ERROR_NODE);
+            while !self.at(EOF) {
+                self.bump();
+            }
+            self.finish();
+        }
+    }
+
+    /// Skip to the next top-level declaration
+    fn top_level_error(&mut self) {
+        let diagnostic = self.expected("a declaration").with_help(format!(
+            "files contain `import`s and {} declarations",
+            list(TOP_LEVEL_DECORATORS)
+        ));
+        self.error(diagnostic);
+
+        self.start(ERROR_NODE);
+        while !self.at(EOF) && !self.at(DECORATOR) && !self.at_keyword("import") {
+            self.skip_item();
+        }
+        self.finish();
+    }
+
+    fn import(&mut self) {
+        self.start(IMPORT);
+        self.bump();
+        self.expect(STRING, "a path string");
+        self.finish();
+    }
+
+    /// Name after a declaration's decorator
+    fn decl_name(&mut self, what: &str, example: &str) {
+        if self.eat(IDENT) {
+            return;
+        }
+        let diagnostic = self
+            .expected(&format!("a {} name", what))
+            .with_help(format!("name the {}, e.g. `{}`", what, example));
+        self.error(diagnostic);
+    }
+
+    fn widget(&mut self) {
+        self.start(WIDGET);
+        self.bump();
+        self.decl_name("widget", "@widget Counter { .. }");
+        if self.at(L_BRACE) {
+            self.braced(Self::widget_item);
+        } else {
+            let diagnostic = self.expected("`{`");
+            self.error(diagnostic);
+        }
+        self.finish();
+    }
+
+    fn widget_item(&mut self) -> bool {
+        match self.current() {
+            DECORATOR => match self.current_text() {
+                "@prop" => self.typed_decl(PROP_DECL, "property", false),
+                "@state" => self.typed_decl(STATE_DECL, "state", true),
+                "@derived" => self.typed_decl(DERIVED_DECL, "derived value", true),
+                "@machine" => self.machine(),
+                "@animation" => self.animation(),
+                "@spring" => self.spring(),
+                "@effect" => {
+                    self.start(EFFECT);
+                    self.bump();
+                    self.block();
+                    self.finish();
+                }
+                "@render" => {
+                    self.start(RENDER);
+                    self.bump();
+                    self.element_list();
+                    self.finish();
+                }
+                "@paint" => self.paint(),
+                "@widget" => return false,
+                _ => self.unknown_decorator(WIDGET_DECORATORS),
+            },
+            IDENT if self.nth(1) == COLON => {
+                let name = self.current_text();
+                let diagnostic = Diagnostic::error("expected a declaration inside the widget")
+                    .with_label(self.current_span(), "bare property")
+                    .with_help(format!(
+                        "declare a property with `@prop {}: Type = value`",
+                        name
+                    ));
+                self.error(diagnostic);
+                self.start(ERROR_NODE);
+                self.property(Elements::Forbidden);
+                self.finish();
+            }
+            _ => {
+                let diagnostic = self.expected("a declaration").with_help(format!(
+                    "widgets contain {} declarations",
+                    list(WIDGET_DECORATORS)
+                ));
+                self.error(diagnostic);
+                self.skip_line();
+            }
+        }
+        true
+    }
+
+    /// `@prop`, `@state` or `@derived`: `name: Type = value`
+    fn typed_decl(&mut self, kind: SyntaxKind, what: &str, needs_value: bool) {
+        self.start(kind);
+        let decorator = self.current_span();
+        self.bump();
+        self.decl_name(what, "count: i32");
+        if !self.type_annotation(what) {
+            // Without a type the rest of the line can't be trusted
+            if !self.at_line_start() && !matches!(self.current(), EOF | R_BRACE | DECORATOR) {
+                self.skip_line();
+            }
+            self.finish();
+            return;
+        }
+
+        if self.eat(EQ) {
+            self.expr(Elements::Forbidden);
+        } else if needs_value {
+            let example = if kind == DERIVED_DECL {
+                "= count * 2"
+            } else {
+                "= 0"
+            };
+            let diagnostic = self
+                .expected("`=`")
+                .with_secondary(decorator, format!("this {} needs a value", what))
+                .with_help(format!("add a value, e.g. `{}`", example));
+            self.error(diagnostic);
+        }
+        self.finish();
+    }
+
+    /// `: Type`, recovering when the colon is missing; returns whether a
+    /// type was found
+    fn type_annotation(&mut self, what: &str) -> bool {
+        if self.eat(COLON) {
+            return self.type_expr();
+        }
+        let diagnostic = self
+            .expected("`:`")
+            .with_help(format!("give the {} a type, e.g. `count: i32`", what));
+        self.error(diagnostic);
+        self.at(IDENT) && self.type_expr()
+    }
+
+    fn type_expr(&mut self) -> bool {
+        self.start(TYPE);
+        let named = self.expect(IDENT, "a type");
+        if named && self.eat(LT) {
+            loop {
+                self.type_expr();
+                if !self.eat(COMMA) {
+                    break;
+                }
+            }
+            self.expect(GT, "`>`");
+        }
+        self.finish();
+        named
+    }
+
+    fn machine(&mut self) {
+        self.start(MACHINE);
+        self.bump();
+        self.decl_name("machine", "@machine hover { .. }");
+        self.braced(Self::machine_item);
+        self.finish();
+    }
+
+    fn machine_item(&mut self) -> bool {
+        if self.at(DECORATOR) {
+            return self.nested_item();
+        }
+        match (self.current(), self.nth(1)) {
+            (IDENT, ARROW) => self.arrow_transition(),
+            (IDENT, COLON) => self.property(Elements::Forbidden),
+            (IDENT, L_BRACE) if self.current_text() == "states" => {
+                self.start(PROPERTY_BLOCK);
+                self.bump();
+                self.braced(|p| {
+                    if p.at(DECORATOR) {
+                        return p.nested_item();
+                    }
+                    p.state_block();
+                    true
+                });
+                self.finish();
+            }
+            (IDENT, L_BRACE) => self.property_block(),
+            _ => {
+                let diagnostic = self
+                    .expected("a transition")
+                    .with_help("transitions look like `idle -> active: pointer_enter`");
+                self.error(diagnostic);
+                self.skip_line();
+            }
+        }
+        true
+    }
+
+    /// `idle -> active: pointer_enter`
+    fn arrow_transition(&mut self) {
+        self.start(TRANSITION);
+        self.bump();
+        self.bump();
+        self.expect(IDENT, "the target state");
+        if self.expect(COLON, "`:`") {
+            self.expect(IDENT, "an event name");
+        }
+        self.finish();
+    }
+
+    /// `idle { on pointer_enter => active }`
+    fn state_block(&mut self) {
+        self.start(STATE_BLOCK);
+        self.expect(IDENT, "a state name");
+        self.braced(|p| {
+            if p.at(DECORATOR) {
+                return p.nested_item();
+            }
+            if p.at_keyword("on") {
+                p.start(TRANSITION);
+                p.bump();
+                p.expect(IDENT, "an event name");
+                if p.at_keyword("if") {
+                    p.bump();
+                    p.expr(Elements::Forbidden);
+                }
+                if p.expect(FAT_ARROW, "`=>`") {
+                    p.expect(IDENT, "the target state");
+                }
+                if p.at(L_BRACE) {
+                    p.property_body();
+                }
+                p.finish();
+            } else if p.at(IDENT) && p.nth(1) == COLON {
+                p.property(Elements::Forbidden);
+            } else {
+                let diagnostic = p.expected("`on event => state`");
+                p.error(diagnostic);
+                p.skip_line();
+            }
+            true
+        });
+        self.finish();
+    }
+
+    fn animation(&mut self) {
+        self.start(ANIMATION);
+        self.bump();
+        self.decl_name("animation", "@animation fade { .. }");
+        self.property_body();
+        self.finish();
+    }
+
+    fn spring(&mut self) {
+        self.start(SPRING);
+        self.bump();
+        self.decl_name("spring", "@spring opacity { .. }");
+        if self.eat(COLON) {
+            self.type_expr();
+        }
+        if self.eat(EQ) {
+            self.expr(Elements::Forbidden);
+        }
+        self.property_body();
+        self.finish();
+    }
+
+    fn paint(&mut self) {
+        self.start(PAINT);
+        self.bump();
+        if self.at(L_PAREN) {
+            self.start(PARAM_LIST);
+            self.bump();
+            while self.eat(IDENT) {
+                if !self.eat(COMMA) {
+                    break;
+                }
+            }
+            self.expect(R_PAREN, "`)`");
+            self.finish();
+        }
+        self.block();
+        self.finish();
+    }
+
+    /// Decorator inside a nested block: belongs to the enclosing widget or
+    /// file, so the block is unclosed; anything else is an error
+    fn nested_item(&mut self) -> bool {
+        if self.at_decorator(WIDGET_DECORATORS) || self.at_decorator(TOP_LEVEL_DECORATORS) {
+            return false;
+        }
+        if self.current_text() == "@action" {
+            let diagnostic = self.expected("a property");
+            self.error(diagnostic);
+            self.bump_error();
+            return true;
+        }
+        self.unknown_decorator(WIDGET_DECORATORS);
+        true
+    }
+
+    /// `{ name: value, name { .. } }`
+    fn property_body(&mut self) {
+        self.braced(|p| {
+            if p.at(DECORATOR) {
+                return p.nested_item();
+            }
+            match (p.current(), p.nth(1)) {
+                (IDENT, COLON) => p.property(Elements::Forbidden),
+                (IDENT | INT, L_BRACE) | (INT, PERCENT) => p.property_block(),
+                _ => {
+                    let diagnostic = p.expected("a property");
+                    p.error(diagnostic);
+                    p.skip_line();
+                }
+            }
+            true
+        });
+    }
+
+    /// `name: value`
+    fn property(&mut self, elements: Elements) {
+        self.start(PROPERTY);
+        self.bump();
+        if self.expect(COLON, "`:`") {
+            self.expr(elements);
+        }
+        self.finish();
+    }
+
+    /// `name { .. }` or a keyframe `50% { .. }`
+    fn property_block(&mut self) {
+        self.start(PROPERTY_BLOCK);
+        self.bump();
+        self.eat(PERCENT);
+        self.property_body();
+        self.finish();
+    }
+
+    // ========================================================================
+    // Render trees
+    // ========================================================================
+
+    /// `{ props, children }` of `@render`, an element or a conditional
+    fn element_list(&mut self) {
+        self.start(ELEMENT_LIST);
+        self.braced(Self::element_item);
+        self.finish();
+    }
+
+    fn element_item(&mut self) -> bool {
+        match (self.current(), self.nth(1)) {
+            (DECORATOR, _) => return self.nested_item(),
+            (IDENT, _) if matches!(self.current_text(), "if" | "when") => self.conditional(),
+            (IDENT, COLON) => self.property(Elements::Allowed),
+            (IDENT, _) => self.element(),
+            (STRING, _) => {
+                self.start(LITERAL);
+                self.bump();
+                self.finish();
+            }
+            _ => {
+                let diagnostic = self
+                    .expected("an element or property")
+                    .with_help("elements look like `Text { content: \"Hi\" }`");
+                self.error(diagnostic);
+                self.skip_line();
+            }
+        }
+        true
+    }
+
+    /// `Name { .. }`, `Name(args)` or `Name(args) { .. }`
+    fn element(&mut self) {
+        self.start(ELEMENT);
+        self.bump();
+        if self.at(L_PAREN) {
+            self.arg_list();
+        }
+        if self.at(L_BRACE) {
+            self.element_list();
+        } else if matches!(self.current(), STRING | INT | FLOAT | COLOR) {
+            // `Spacer` alone is fine; `Text "hi"` is missing its braces
+            let diagnostic = self
+                .expected("`{`")
+                .with_help("put content in a prop, e.g. `Text { content: \"hi\" }`");
+            self.error(diagnostic);
+        }
+        self.finish();
+    }
+
+    /// `if cond { .. } else { .. }` in a render tree
+    fn conditional(&mut self) {
+        self.start(CONDITIONAL);
+        self.bump();
+        self.expr(Elements::Forbidden);
+        self.element_list();
+        if self.at_keyword("else") {
+            self.bump();
+            if self.at_keyword("if") || self.at_keyword("when") {
+                self.conditional();
+            } else {
+                self.element_list();
+            }
+        }
+        self.finish();
+    }
+
+    // ========================================================================
+    // Statements
+    // ========================================================================
+
+    /// `{ stmt* }`
+    fn block(&mut self) {
+        self.start(BLOCK);
+        self.braced(Self::stmt);
+        self.finish();
+    }
+
+    fn stmt(&mut self) -> bool {
+        if self.at(DECORATOR) {
+            return self.nested_item();
+        }
+        if self.at_keyword("let") {
+            self.start(LET_STMT);
+            self.bump();
+            if self.at_keyword("mut") {
+                self.bump();
+            }
+            self.expect(IDENT, "a variable name");
+            if self.eat(COLON) {
+                self.type_expr();
+            }
+            if self.expect(EQ, "`=`") {
+                self.expr(Elements::Forbidden);
+            }
+            self.finish();
+        } else if self.at_keyword("if") || self.at_keyword("when") {
+            self.if_stmt();
+        } else {
+            let checkpoint = self.checkpoint();
+            self.expr(Elements::Forbidden);
+            if matches!(self.current(), EQ | PLUS_EQ | MINUS_EQ | STAR_EQ | SLASH_EQ) {
+                self.bump();
+                self.expr(Elements::Forbidden);
+                self.start_at(checkpoint, ASSIGN_STMT);
+            } else {
+                self.start_at(checkpoint, EXPR_STMT);
+            }
+            self.finish();
+        }
+        true
+    }
+
+    fn if_stmt(&mut self) {
+        self.start(IF_STMT);
+        self.bump();
+        self.expr(Elements::Forbidden);
+        self.block();
+        if self.at_keyword("else") {
+            self.bump();
+            if self.at_keyword("if") || self.at_keyword("when") {
+                self.if_stmt();
+            } else {
+                self.block();
+            }
+        }
+        self.finish();
+    }
+
+    // ========================================================================
+    // Expressions
+    // ========================================================================
+
+    fn expr(&mut self, elements: Elements) {
+        self.expr_bp(0, elements);
+    }
+
+    fn expr_bp(&mut self, min_bp: u8, elements: Elements) {
+        let checkpoint = self.checkpoint();
+        if !self.primary(elements) {
+            return;
+        }
+        self.postfix(checkpoint);
+
+        while let Some(bp) = binding_power(self.current()) {
+            if bp <= min_bp {
+                break;
+            }
+            self.bump();
+            self.expr_bp(bp, elements);
+            self.start_at(checkpoint, BINARY_EXPR);
+            self.finish();
+        }
+    }
+
+    /// Field access, calls and indexing after a primary expression
+    fn postfix(&mut self, checkpoint: usize) {
+        loop {
+            match self.current() {
+                DOT => {
+                    self.bump();
+                    self.expect(IDENT, "a field or method name");
+                    self.start_at(checkpoint, FIELD_EXPR);
+                }
+                L_PAREN => {
+                    self.arg_list();
+                    self.start_at(checkpoint, CALL_EXPR);
+                }
+                L_BRACKET => {
+                    self.bump();
+                    self.expr(Elements::Forbidden);
+                    self.expect(R_BRACKET, "`]`");
+                    self.start_at(checkpoint, INDEX_EXPR);
+                }
+                _ => return,
+            }
+            self.finish();
+        }
+    }
+
+    /// Parse a primary expression; returns `false` if there wasn't one
+    fn primary(&mut self, elements: Elements) -> bool {
+        match self.current() {
+            INT | FLOAT | STRING | COLOR => self.literal(),
+            IDENT => match self.current_text() {
+                "true" | "false" => self.literal(),
+                _ if elements == Elements::Allowed
+                    && matches!(self.nth(1), L_BRACE | L_PAREN)
+                    && self.current_text().starts_with(char::is_uppercase) =>
+                {
+                    self.element()
+                }
+                _ if self.nth(1) == COLON2 => {
+                    self.start(PATH_EXPR);
+                    self.bump();
+                    while self.eat(COLON2) {
+                        self.expect(IDENT, "a name");
+                    }
+                    self.finish();
+                }
+                _ => {
+                    self.start(NAME_REF);
+                    self.bump();
+                    self.finish();
+                }
+            },
+            MINUS | BANG => {
+                self.start(UNARY_EXPR);
+                self.bump();
+                self.expr_bp(PREFIX_BINDING_POWER, elements);
+                self.finish();
+            }
+            L_PAREN if self.at_lambda() => {
+                self.start(LAMBDA_EXPR);
+                self.start(PARAM_LIST);
+                self.bump();
+                while self.eat(IDENT) {
+                    if self.eat(COLON) {
+                        self.type_expr();
+                    }
+                    if !self.eat(COMMA) {
+                        break;
+                    }
+                }
+                self.expect(R_PAREN, "`)`");
+                self.finish();
+                self.bump();
+                if self.at(L_BRACE) {
+                    self.block();
+                } else {
+                    self.expr(elements);
+                }
+                self.finish();
+            }
+            L_PAREN => {
+                self.start(PAREN_EXPR);
+                self.bump();
+                self.expr(Elements::Allowed);
+                self.expect(R_PAREN, "`)`");
+                self.finish();
+            }
+            L_BRACKET => {
+                self.start(ARRAY_EXPR);
+                self.bump();
+                while !self.at(R_BRACKET) && !self.at(EOF) && !self.at(R_BRACE) {
+                    let before = self.pos;
+                    self.expr(elements);
+                    if self.pos == before {
+                        self.bump_error();
+                    }
+                    if !self.eat(COMMA) {
+                        break;
+                    }
+                }
+                self.expect(R_BRACKET, "`]`");
+                self.finish();
+            }
+            L_BRACE => self.block(),
+            DECORATOR if self.current_text() == "@action" => {
+                self.start(ACTION_EXPR);
+                self.bump();
+                self.block();
+                self.finish();
+            }
+            _ => {
+                let diagnostic = self.expected("an expression");
+                self.error(diagnostic);
+                if !matches!(
+                    self.current(),
+                    EOF | R_BRACE | R_PAREN | R_BRACKET | COMMA | DECORATOR
+                ) {
+                    self.bump_error();
+                }
+                return false;
+            }
+        }
+        true
+    }
+
+    fn literal(&mut self) {
+        self.start(LITERAL);
+        self.bump();
+        self.finish();
+    }
+
+    /// At `(params) =>`
+    fn at_lambda(&self) -> bool {
+        let mut n = 1;
+        loop {
+            match self.nth(n) {
+                R_PAREN => return self.nth(n + 1) == FAT_ARROW,
+                IDENT | COMMA | COLON | LT | GT => n += 1,
+                _ => return false,
+            }
+        }
+    }
+
+    /// `(a, b)` or `(name: value, ..)`
+    fn arg_list(&mut self) {
+        self.start(ARG_LIST);
+        self.bump();
+        while !self.at(R_PAREN) && !self.at(EOF) && !self.at(R_BRACE) {
+            let before = self.pos;
+            if self.at(IDENT) && self.nth(1) == COLON {
+                self.property(Elements::Allowed);
+            } else {
+                self.expr(Elements::Allowed);
+            }
+            if self.pos == before {
+                self.bump_error();
+            }
+            if !self.eat(COMMA) {
+                break;
+            }
+        }
+        self.expect(R_PAREN, "`)`");
+        self.finish();
+    }
+}
+
+/// Closest of `candidates` to `name`, if it looks like a typo
+pub(super) fn suggest<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|c| (edit_distance(name, c), *c))
+        .filter(|(d, c)| *d <= 2.max(c.len() / 3))
+        .min_by_key(|(d, _)| *d)
+        .map(|(_, c)| c)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut prev = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitute = prev + usize::from(ca != *cb);
+            prev = row[j + 1];
+            row[j + 1] = substitute.min(prev + 1).min(row[j] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// "`@a`, `@b` or `@c`"
+fn list(items: &[&str]) -> String {
+    let quoted: Vec<String> = items.iter().map(|i| format!("`{}`", i)).collect();
+    match quoted.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} or {}", rest.join(", "), last),
+        _ => quoted.join(""),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn messages(source: &str) -> Vec<String> {
+        parse(source)
+            .diagnostics
+            .into_iter()
+            .map(|d| d.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_lossless_round_trip() {
+        for source in [
+            include_str!("../../../examples/counter/src/main.junita"),
+            include_str!("../../../examples/hot_reload_demo/main.junita"),
+            include_str!("../../../examples/diagnostics_demo/demo.junita"),
+            "@widget Broken { @state x i32 = \n @render { Text { ",
+        ] {
+            let parse = parse(source);
+            assert_eq!(parse.root.text(), source);
+            assert_eq!(parse.root.span, 0..source.len());
+        }
+    }
+
+    #[test]
+    fn test_counter_tree() {
+        let source = include_str!("../../../examples/counter/src/main.junita");
+        let parse = parse(source);
+        assert!(parse.diagnostics.is_empty(), "{:?}", parse.diagnostics);
+
+        let widgets: Vec<_> = parse.root.nodes().filter(|n| n.kind == WIDGET).collect();
+        assert_eq!(widgets.len(), 2);
+        assert_eq!(widgets[0].name().unwrap().text, "Counter");
+
+        let derived = widgets[0].node(DERIVED_DECL).unwrap();
+        assert_eq!(derived.expr().unwrap().kind, BINARY_EXPR);
+        assert_eq!(derived.expr().unwrap().text(), "count * 2");
+
+        let machine = widgets[0].node(MACHINE).unwrap();
+        assert_eq!(machine.nodes().filter(|n| n.kind == TRANSITION).count(), 2);
+
+        let render = widgets[0].node(RENDER).unwrap();
+        let column = render.node(ELEMENT_LIST).unwrap().node(ELEMENT).unwrap();
+        assert_eq!(column.name().unwrap().text, "Column");
+    }
+
+    #[test]
+    fn test_expression_precedence() {
+        let parse = parse("@widget A { @derived x: i32 = -a + b * c.len() == 3 || !d }");
+        let derived = parse
+            .root
+            .descendants()
+            .into_iter()
+            .find(|n| n.kind == DERIVED_DECL);
+        let or = derived.unwrap().expr().unwrap();
+        assert_eq!(or.kind, BINARY_EXPR);
+        assert!(or.token(PIPE2).is_some());
+
+        let eq = or.nodes().next().unwrap();
+        assert!(eq.token(EQ2).is_some());
+        let add = eq.nodes().next().unwrap();
+        assert!(add.token(PLUS).is_some());
+        assert_eq!(add.nodes().next().unwrap().kind, UNARY_EXPR);
+        assert_eq!(add.nodes().nth(1).unwrap().text(), "b * c.len()");
+    }
+
+    #[test]
+    fn test_collects_multiple_errors() {
+        let source = r#"
+            @widget Counter {
+                @state count i32 = 0
+                @state label: String
+                @render {
+                    Column {
+                        Text { content: }
+                    }
+                }
+            }
+            @widgetx Other {}
+        "#;
+        assert_eq!(
+            messages(source),
+            vec![
+                "expected `:`, found `i32`",
+                "expected `=`, found `@render`",
+                "expected an expression, found `}`",
+                "unknown decorator `@widgetx`",
+            ]
+        );
+
+        let parse = parse(source);
+        let typo = parse.diagnostics.last().unwrap();
+        assert_eq!(typo.help, vec!["did you mean `@widget`?"]);
+    }
+
+    #[test]
+    fn test_fragments() {
+        let source = "Column { spacing: 8 }\n\"hi\"\nif on { Text {} }";
+        let view = parse_view(source);
+        assert!(view.diagnostics.is_empty(), "{:?}", view.diagnostics);
+        assert_eq!(view.root.kind, FRAGMENT);
+        assert_eq!(view.root.text(), source);
+        let kinds: Vec<_> = view.root.nodes().map(|n| n.kind).collect();
+        assert_eq!(kinds, vec![ELEMENT, LITERAL, CONDITIONAL]);
+
+        let expr = parse_expr("count * 2");
+        assert!(expr.diagnostics.is_empty());
+        assert_eq!(expr.root.expr().unwrap().kind, BINARY_EXPR);
+
+        let trailing = parse_expr("a b");
+        assert_eq!(
+            trailing.diagnostics[0].message,
+            "expected the end of the expression, found `b`"
+        );
+        assert_eq!(trailing.root.text(), "a b");
+    }
+
+    #[test]
+    fn test_unclosed_block_recovers_at_next_widget() {
+        let source = "@widget A {\n  @render {\n    Column {\n}\n@widget B { @render { Text {} } }";
+        let parse = parse(source);
+        assert_eq!(
+            parse
+                .diagnostics
+                .iter()
+                .map(|d| d.message.as_str())
+                .collect::<Vec<_>>(),
+            vec!["unclosed `{`", "unclosed `{`"]
+        );
+        let widgets: Vec<_> = parse.root.nodes().filter(|n| n.kind == WIDGET).collect();
+        assert_eq!(widgets.len(), 2);
+        assert_eq!(widgets[1].name().unwrap().text, "B");
+    }
+}
@@ -0,0 +1,132 @@
+//! Checks on the parsed tree
+//!
+//! The grammar accepts any names; these checks catch declarations that
+//! would clash once compiled.
+
+use std::collections::HashMap;
+
+use super::cst::{SyntaxNode, SyntaxToken};
+use super::diagnostic::Diagnostic;
+use super::SyntaxKind::{self, *};
+
+/// Run all checks on a source file
+pub fn validate(root: &SyntaxNode) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let descendants = root.descendants();
+
+    // Widgets are top-level; machines, animations and springs share one
+    // namespace per kind across the file, wherever they're declared
+    let widgets: Vec<_> = root.nodes().filter(|n| n.kind == WIDGET).collect();
+    check_unique(&widgets, "widget", &mut diagnostics);
+    for kind in [MACHINE, ANIMATION, SPRING] {
+        let nodes: Vec<_> = descendants
+            .iter()
+            .copied()
+            .filter(|n| n.kind == kind)
+            .collect();
+        check_unique(&nodes, describe(kind), &mut diagnostics);
+    }
+
+    for widget in widgets {
+        check_members(widget, &mut diagnostics);
+    }
+    diagnostics
+}
+
+fn describe(kind: SyntaxKind) -> &'static str {
+    match kind {
+        WIDGET => "widget",
+        MACHINE => "machine",
+        ANIMATION => "animation",
+        SPRING => "spring",
+        PROP_DECL => "property",
+        STATE_DECL => "state",
+        DERIVED_DECL => "derived value",
+        _ => "declaration",
+    }
+}
+
+/// Report every declaration whose name was already used
+fn check_unique(nodes: &[&SyntaxNode], what: &str, diagnostics: &mut Vec<Diagnostic>) {
+    let mut seen: HashMap<&str, &SyntaxToken> = HashMap::new();
+    for node in nodes {
+        let Some(name) = node.name() else { continue };
+        match seen.get(name.text.as_str()) {
+            Some(first) => diagnostics.push(
+                Diagnostic::error(format!("duplicate {} name `{}`", what, name.text))
+                    .with_label(name.span.clone(), "redefined here")
+                    .with_secondary(first.span.clone(), "first defined here")
+                    .with_help(format!("rename one of the {}s", what)),
+            ),
+            None => {
+                seen.insert(&name.text, name);
+            }
+        }
+    }
+}
+
+/// Props, state, derived values, machines and springs are all read by name
+/// inside the widget, so they can't share one
+fn check_members(widget: &SyntaxNode, diagnostics: &mut Vec<Diagnostic>) {
+    let widget_name = widget.name().map_or("", |n| n.text.as_str());
+    let mut seen: HashMap<&str, (&SyntaxToken, SyntaxKind)> = HashMap::new();
+    for member in widget.nodes() {
+        if !matches!(
+            member.kind,
+            PROP_DECL | STATE_DECL | DERIVED_DECL | MACHINE | SPRING
+        ) {
+            continue;
+        }
+        let Some(name) = member.name() else { continue };
+        match seen.get(name.text.as_str()) {
+            Some((first, kind)) => diagnostics.push(
+                Diagnostic::error(format!(
+                    "`{}` is already declared in `{}`",
+                    name.text, widget_name
+                ))
+                .with_label(name.span.clone(), "declared again here")
+                .with_secondary(
+                    first.span.clone(),
+                    format!("first declared as a {} here", describe(*kind)),
+                ),
+            ),
+            None => {
+                seen.insert(&name.text, (name, member.kind));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parse;
+
+    #[test]
+    fn test_duplicates() {
+        let source = r#"
+            @widget Button { @state x: i32 = 0  @derived x: i32 = 1 }
+            @widget Button {}
+            @animation fade { duration: 300ms }
+            @widget Card { @animation fade { duration: 100ms } }
+        "#;
+        let parse = parse(source);
+        let messages: Vec<_> = parse
+            .diagnostics
+            .iter()
+            .map(|d| d.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "`x` is already declared in `Button`",
+                "duplicate widget name `Button`",
+                "duplicate animation name `fade`",
+            ]
+        );
+
+        let duplicate = &parse.diagnostics[1];
+        assert_eq!(duplicate.labels.len(), 2);
+        assert_eq!(&source[duplicate.labels[1].span.clone()], "Button");
+        assert!(duplicate.labels[1].span.start < duplicate.labels[0].span.start);
+    }
+}
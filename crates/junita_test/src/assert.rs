@@ -0,0 +1,191 @@
+//! Assertions on elements
+//!
+//! Assertions resolve their query when called, so a chain checks the state
+//! of the tree at that point and panics with the query and an outline of
+//! the UI when it fails.
+
+use crate::harness::TestApp;
+use crate::query::{ElementInfo, Query};
+
+/// Bounds comparisons allow this much layout rounding
+const BOUNDS_TOLERANCE: f32 = 0.5;
+
+/// Chainable assertions about one element
+///
+/// ```ignore
+/// app.assert_element("email")
+///     .is_visible()
+///     .is_focused()
+///     .has_text("user@example.com");
+/// ```
+pub struct ElementAssertion<'a> {
+    app: &'a TestApp,
+    query: Query,
+}
+
+impl TestApp {
+    /// Start assertions about the element matching `query`
+    pub fn assert_element(&self, query: impl Into<Query>) -> ElementAssertion<'_> {
+        ElementAssertion {
+            app: self,
+            query: query.into(),
+        }
+    }
+}
+
+impl ElementAssertion<'_> {
+    fn element(&self) -> ElementInfo {
+        self.app.get(&self.query)
+    }
+
+    fn fail(&self, message: std::fmt::Arguments<'_>) -> ! {
+        panic!(
+            "assertion failed for {}: {}\n\n{}",
+            self.query,
+            message,
+            self.app.debug_tree()
+        )
+    }
+
+    /// Assert exactly one element matches
+    pub fn exists(self) -> Self {
+        self.element();
+        self
+    }
+
+    /// Assert no element matches
+    pub fn does_not_exist(self) -> Self {
+        if let Some(element) = self.app.query(&self.query) {
+            self.fail(format_args!(
+                "expected no match, found element at ({:.1}, {:.1})",
+                element.bounds.x, element.bounds.y
+            ));
+        }
+        self
+    }
+
+    /// Assert the element's combined text equals `expected`
+    pub fn has_text(self, expected: &str) -> Self {
+        let element = self.element();
+        if element.text != expected {
+            self.fail(format_args!(
+                "expected text {:?}, found {:?}",
+                expected, element.text
+            ));
+        }
+        self
+    }
+
+    /// Assert the element's combined text contains `expected`
+    pub fn contains_text(self, expected: &str) -> Self {
+        let element = self.element();
+        if !element.text.contains(expected) {
+            self.fail(format_args!(
+                "expected text containing {:?}, found {:?}",
+                expected, element.text
+            ));
+        }
+        self
+    }
+
+    /// Assert the element is visible in the viewport
+    pub fn is_visible(self) -> Self {
+        if !self.element().visible {
+            self.fail(format_args!("expected element to be visible"));
+        }
+        self
+    }
+
+    /// Assert the element is hidden or not mounted
+    pub fn is_hidden(self) -> Self {
+        if self.app.query(&self.query).is_some_and(|e| e.visible) {
+            self.fail(format_args!("expected element to be hidden"));
+        }
+        self
+    }
+
+    /// Assert the element or one of its descendants has focus
+    pub fn is_focused(self) -> Self {
+        if !self.element().contains_focus {
+            self.fail(format_args!("expected element to be focused"));
+        }
+        self
+    }
+
+    /// Assert neither the element nor its descendants have focus
+    pub fn is_not_focused(self) -> Self {
+        if self.element().contains_focus {
+            self.fail(format_args!("expected element not to be focused"));
+        }
+        self
+    }
+
+    /// Assert the element's viewport bounds, within half a pixel
+    pub fn has_bounds(self, x: f32, y: f32, width: f32, height: f32) -> Self {
+        let b = self.element().bounds;
+        let close = |a: f32, b: f32| (a - b).abs() <= BOUNDS_TOLERANCE;
+        if !(close(b.x, x) && close(b.y, y) && close(b.width, width) && close(b.height, height)) {
+            self.fail(format_args!(
+                "expected bounds ({}, {}, {}x{}), found ({}, {}, {}x{})",
+                x, y, width, height, b.x, b.y, b.width, b.height
+            ));
+        }
+        self
+    }
+
+    /// Assert the element's size, within half a pixel
+    pub fn has_size(self, width: f32, height: f32) -> Self {
+        let b = self.element().bounds;
+        if (b.width - width).abs() > BOUNDS_TOLERANCE || (b.height - height).abs() > BOUNDS_TOLERANCE
+        {
+            self.fail(format_args!(
+                "expected size {}x{}, found {}x{}",
+                width, height, b.width, b.height
+            ));
+        }
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use junita_layout::prelude::*;
+
+    fn sample() -> TestApp {
+        TestApp::mount(200.0, 100.0, || {
+            div()
+                .flex_col()
+                .p_px(10.0)
+                .child(div().id("box").w(40.0).h(20.0).child(text("Hi")))
+                .child(div().id("ghost").w(40.0).h(20.0).opacity(0.0))
+        })
+    }
+
+    #[test]
+    fn test_passing_chain() {
+        let app = sample();
+        app.assert_element("box")
+            .exists()
+            .is_visible()
+            .is_not_focused()
+            .has_text("Hi")
+            .contains_text("H")
+            .has_bounds(10.0, 10.0, 40.0, 20.0)
+            .has_size(40.0, 20.0);
+        app.assert_element("ghost").is_hidden();
+        app.assert_element("nope").does_not_exist().is_hidden();
+    }
+
+    #[test]
+    #[should_panic(expected = "expected bounds (0, 0, 40x20), found (10, 10, 40x20)")]
+    fn test_bounds_mismatch_reports_actual() {
+        sample().assert_element("box").has_bounds(0.0, 0.0, 40.0, 20.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "expected text \"Bye\", found \"Hi\"")]
+    fn test_text_mismatch_reports_actual() {
+        sample().assert_element("box").has_text("Bye");
+    }
+}
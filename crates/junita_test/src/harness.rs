@@ -0,0 +1,636 @@
+//! Headless app harness
+//!
+//! `TestApp` owns a `RenderTree` at a fixed viewport and runs the same frame
+//! phases as the windowed app loop, minus rendering. Time only moves when a
+//! test asks it to, so springs, motions, overlays and scroll physics settle
+//! identically on every run.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+use std::thread::ThreadId;
+use std::time::Duration;
+
+use junita_animation::AnimationScheduler;
+use junita_core::clock::{self, Clock, ClockGuard, ManualClock};
+use junita_core::context_state::{HookState, JunitaContextState};
+use junita_core::reactive::{ReactiveGraph, SignalId, State};
+use junita_core::MotionAnimationState;
+use junita_layout::overlay_state::{get_overlay_manager, OverlayContext};
+use junita_layout::prelude::*;
+use junita_layout::selector::ElementRegistry;
+use junita_layout::widgets::overlay::{
+    overlay_manager, OverlayManager, OverlayManagerExt, OverlayManagerInner, OVERLAY_LAYER_ID,
+};
+use junita_layout::{EventRouter, RenderState, SharedMotionStates, UpdateResult};
+use junita_theme::ThemeState;
+
+/// Length of one virtual frame in milliseconds
+pub const FRAME_MS: u64 = 16;
+
+/// Virtual time after which `settle()` gives up
+const SETTLE_LIMIT_MS: u64 = 10_000;
+
+// =============================================================================
+// Process globals
+// =============================================================================
+
+/// Framework singletons shared by every `TestApp` in the process
+struct Globals {
+    animations: Arc<Mutex<AnimationScheduler>>,
+    clock: ManualClock,
+}
+
+/// Initialize the context, theme, clock, scheduler and overlay singletons once
+///
+/// Components reach these through globals rather than parameters, so they
+/// have to exist before the first element is built. The virtual clock is
+/// only installed process-wide while an app is mounted (see [`SerialGuard`]),
+/// and the scheduler reads whichever clock is installed.
+fn globals() -> &'static Globals {
+    static GLOBALS: OnceLock<Globals> = OnceLock::new();
+    GLOBALS.get_or_init(|| {
+        if !JunitaContextState::is_initialized() {
+            JunitaContextState::init_with_callback(
+                Arc::new(Mutex::new(ReactiveGraph::new())),
+                Arc::new(Mutex::new(HookState::new())),
+                Arc::new(std::sync::atomic::AtomicBool::new(false)),
+                Arc::new(|signal_ids: &[SignalId]| {
+                    junita_layout::check_stateful_deps(signal_ids);
+                }),
+            );
+        }
+
+        if ThemeState::try_get().is_none() {
+            ThemeState::init_default();
+        }
+
+        let clock = ManualClock::new();
+        let animations = Arc::new(Mutex::new(AnimationScheduler::new()));
+        if !junita_animation::is_scheduler_initialized() {
+            junita_animation::set_global_scheduler(animations.lock().unwrap().handle());
+        }
+        ThemeState::get().set_scheduler(&animations);
+
+        if !OverlayContext::is_initialized() {
+            OverlayContext::init(overlay_manager());
+        }
+
+        Globals { animations, clock }
+    })
+}
+
+/// Create a fresh piece of state for a test
+///
+/// Equivalent to `use_state_keyed` with a key no other test can collide
+/// with, so concurrently compiled tests never observe each other's values.
+///
+/// ```ignore
+/// let checked = junita_test::state(false);
+/// let mut app = TestApp::mount(400.0, 300.0, {
+///     let checked = checked.clone();
+///     move || cn::checkbox(&checked).label("Accept terms")
+/// });
+/// ```
+pub fn state<T: Clone + Send + 'static>(initial: T) -> State<T> {
+    static NEXT_KEY: AtomicU64 = AtomicU64::new(0);
+
+    globals();
+    let key = format!(
+        "junita_test::state#{}",
+        NEXT_KEY.fetch_add(1, Ordering::Relaxed)
+    );
+    JunitaContextState::get().use_state_keyed(&key, || initial)
+}
+
+// =============================================================================
+// Serialization
+// =============================================================================
+
+/// Process-wide lock held by each mounted `TestApp`
+///
+/// Rebuild flags, pending subtree rebuilds and the overlay manager are
+/// globals, so two apps ticking at once would steal each other's work. The
+/// test runner uses threads, so apps on different threads wait their turn.
+/// The lock is reentrant so a test may hold two apps at once.
+///
+/// The harness clock is installed process-wide while the lock is held, so
+/// the scheduler, cursor blink and scroll physics only see time a test moves
+/// forward, and tests that never mount an app keep the system clock.
+struct SerialLock {
+    owner: Mutex<Option<Owner>>,
+    released: Condvar,
+}
+
+struct Owner {
+    thread: ThreadId,
+    depth: usize,
+    /// Puts back the previous clock when the outermost guard is released
+    _clock: ClockGuard,
+}
+
+static SERIAL: SerialLock = SerialLock {
+    owner: Mutex::new(None),
+    released: Condvar::new(),
+};
+
+pub(crate) struct SerialGuard;
+
+impl SerialGuard {
+    fn acquire(clock: &ManualClock) -> Self {
+        let me = std::thread::current().id();
+        let mut owner = SERIAL.owner.lock().unwrap_or_else(|e| e.into_inner());
+        loop {
+            match *owner {
+                None => {
+                    *owner = Some(Owner {
+                        thread: me,
+                        depth: 1,
+                        _clock: clock::install_clock(Arc::new(clock.clone())),
+                    });
+                    return SerialGuard;
+                }
+                Some(Owner {
+                    thread,
+                    ref mut depth,
+                    ..
+                }) if thread == me => {
+                    *depth += 1;
+                    return SerialGuard;
+                }
+                Some(_) => {
+                    owner = SERIAL
+                        .released
+                        .wait(owner)
+                        .unwrap_or_else(|e| e.into_inner());
+                }
+            }
+        }
+    }
+}
+
+impl Drop for SerialGuard {
+    fn drop(&mut self) {
+        let mut owner = SERIAL.owner.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(Owner { ref mut depth, .. }) = *owner {
+            *depth -= 1;
+            if *depth == 0 {
+                *owner = None;
+                SERIAL.released.notify_one();
+            }
+        }
+    }
+}
+
+// =============================================================================
+// TestApp
+// =============================================================================
+
+/// A headless app mounted at a fixed viewport
+///
+/// Mount a builder, drive it with [`click`](TestApp::click),
+/// [`type_text`](TestApp::type_text) and friends, move the virtual clock with
+/// [`advance`](TestApp::advance) or [`settle`](TestApp::settle), then assert
+/// with [`assert_element`](TestApp::assert_element).
+///
+/// Every interaction runs one frame afterwards, so rebuilds triggered by
+/// handlers are visible to the next query.
+pub struct TestApp {
+    pub(crate) width: f32,
+    pub(crate) height: f32,
+    /// Builds the user UI wrapped in the viewport-sized root
+    compose: Box<dyn FnMut() -> Div>,
+    pub(crate) tree: RenderTree,
+    pub(crate) render_state: RenderState,
+    pub(crate) router: EventRouter,
+    pub(crate) overlays: OverlayManager,
+    registry: Arc<ElementRegistry>,
+    motion_states: SharedMotionStates,
+    animations: Arc<Mutex<AnimationScheduler>>,
+    clock: ManualClock,
+    frame_count: u64,
+    scroll_animating: bool,
+    // Dropped last so the next app can't start while this one tears down
+    _serial: SerialGuard,
+}
+
+impl TestApp {
+    /// Mount a UI builder into a `width` x `height` viewport
+    ///
+    /// `build` is called again on every rebuild, like the closure passed to
+    /// `WindowedApp::run`, so state read inside it is picked up after
+    /// `set_rebuild()`.
+    pub fn mount<E, F>(width: f32, height: f32, mut build: F) -> Self
+    where
+        E: ElementBuilder + 'static,
+        F: FnMut() -> E + 'static,
+    {
+        let clock = globals().clock.clone();
+        let serial = SerialGuard::acquire(&clock);
+        let animations = Arc::clone(&globals().animations);
+        // Every app starts at time zero, so replays line up with recordings
+        clock.set(Duration::ZERO);
+        animations.lock().unwrap().step_frame();
+        let context = JunitaContextState::get();
+
+        // Drop whatever a previous app left behind in the globals
+        let overlays = get_overlay_manager();
+        *overlays.lock().unwrap() = OverlayManagerInner::new();
+        junita_layout::widgets::blur_all_text_inputs();
+        context.dirty_flag().store(false, Ordering::SeqCst);
+        junita_layout::widgets::take_needs_rebuild();
+        junita_layout::widgets::take_needs_relayout();
+        junita_layout::take_needs_redraw();
+        junita_layout::take_pending_prop_updates();
+        junita_layout::take_pending_subtree_rebuilds();
+
+        let registry = Arc::new(ElementRegistry::new());
+        {
+            let registry_for_query = Arc::clone(&registry);
+            context.set_query_callback(Arc::new(move |id: &str| {
+                registry_for_query.get(id).map(|node_id| node_id.to_raw())
+            }));
+            let registry_for_bounds = Arc::clone(&registry);
+            context
+                .set_bounds_callback(Arc::new(move |id: &str| registry_for_bounds.get_bounds(id)));
+            context.set_element_registry(Arc::clone(&registry) as junita_core::AnyElementRegistry);
+        }
+
+        let motion_states = junita_layout::create_shared_motion_states();
+        {
+            let motion_states_for_callback = Arc::clone(&motion_states);
+            context.set_motion_state_callback(Arc::new(move |key: &str| {
+                motion_states_for_callback
+                    .read()
+                    .ok()
+                    .and_then(|states| states.get(key).copied())
+                    .unwrap_or(MotionAnimationState::NotFound)
+            }));
+        }
+        context.set_viewport_size(width, height);
+
+        let mut render_state = RenderState::new(Arc::clone(&animations));
+        render_state.set_shared_motion_states(Arc::clone(&motion_states));
+        render_state.set_viewport_size(width, height);
+
+        overlays.set_viewport_with_scale(width, height, 1.0);
+
+        let mut compose: Box<dyn FnMut() -> Div> =
+            Box::new(move || div().w(width).h(height).relative().child(build()));
+
+        junita_layout::reset_call_counters();
+        render_state.begin_stable_motion_frame();
+        let ui = compose().child(overlays.build_overlay_layer());
+        let tree = Self::build_tree(
+            &ui,
+            &registry,
+            &animations,
+            &mut render_state,
+            width,
+            height,
+        );
+
+        let mut app = Self {
+            width,
+            height,
+            compose,
+            tree,
+            render_state,
+            router: EventRouter::new(),
+            overlays,
+            registry,
+            motion_states,
+            animations,
+            clock,
+            frame_count: 0,
+            scroll_animating: false,
+            _serial: serial,
+        };
+        app.frame();
+        app
+    }
+
+    /// Build a fresh tree from `ui` and lay it out
+    fn build_tree(
+        ui: &Div,
+        registry: &Arc<ElementRegistry>,
+        animations: &Arc<Mutex<AnimationScheduler>>,
+        render_state: &mut RenderState,
+        width: f32,
+        height: f32,
+    ) -> RenderTree {
+        let mut tree = RenderTree::from_element_with_registry(ui, Arc::clone(registry));
+        tree.set_animations(animations);
+        tree.compute_layout(width, height);
+        tree.initialize_motion_animations(render_state);
+        render_state.end_stable_motion_frame();
+        render_state.process_global_motion_replays();
+        tree
+    }
+
+    /// Viewport size in logical pixels
+    pub fn viewport(&self) -> (f32, f32) {
+        (self.width, self.height)
+    }
+
+    /// Current virtual time since mount
+    pub fn now(&self) -> Duration {
+        self.clock.now()
+    }
+
+    fn now_ms(&self) -> u64 {
+        self.now().as_millis() as u64
+    }
+
+    /// Number of frames run since mount
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// The mounted render tree
+    pub fn tree(&self) -> &RenderTree {
+        &self.tree
+    }
+
+    /// The event router, for focus and hover state
+    pub fn router(&self) -> &EventRouter {
+        &self.router
+    }
+
+    // =========================================================================
+    // Frames and the virtual clock
+    // =========================================================================
+
+    /// Run one frame without moving the clock
+    ///
+    /// Applies pending rebuilds, prop updates and overlay changes in the same
+    /// order as the windowed app loop, then ticks animations.
+    pub fn frame(&mut self) {
+        self.frame_count += 1;
+        let now = self.now_ms();
+
+        self.render_state.clear_overlays();
+        self.scroll_animating = self.tree.tick_scroll_physics(now);
+        self.tree.process_pending_scroll_refs();
+
+        // Phase 1: collect rebuild requests
+        let mut needs_rebuild = self.tree.needs_rebuild();
+        needs_rebuild |= JunitaContextState::get()
+            .dirty_flag()
+            .swap(false, Ordering::SeqCst);
+        needs_rebuild |= junita_layout::widgets::take_needs_rebuild();
+        let needs_relayout = junita_layout::widgets::take_needs_relayout();
+
+        let rs = &mut self.render_state;
+        rs.process_global_motion_exit_starts();
+        rs.process_global_motion_exit_cancels();
+        rs.process_global_motion_starts();
+        rs.sync_shared_motion_states();
+
+        self.overlays
+            .set_viewport_with_scale(self.width, self.height, 1.0);
+        self.overlays.update(now);
+        if self.overlays.is_dirty() {
+            if let Some(overlay_node) = self.registry.get(OVERLAY_LAYER_ID) {
+                junita_layout::queue_subtree_rebuild(
+                    overlay_node,
+                    self.overlays.build_overlay_layer(),
+                );
+            }
+            self.overlays.take_dirty();
+        }
+
+        if junita_layout::take_needs_redraw() || junita_layout::has_pending_subtree_rebuilds() {
+            for (node_id, props) in junita_layout::take_pending_prop_updates() {
+                self.tree.update_render_props(node_id, |p| *p = props);
+            }
+            if self.tree.process_pending_subtree_rebuilds() {
+                self.tree.compute_layout(self.width, self.height);
+                rs.begin_stable_motion_frame();
+                self.tree.initialize_motion_animations(rs);
+                rs.end_stable_motion_frame();
+                rs.process_global_motion_replays();
+            }
+        }
+
+        // Phase 2: rebuild for structural changes
+        rs.begin_stable_motion_frame();
+        if needs_rebuild || needs_relayout {
+            self.rebuild(needs_relayout);
+        } else {
+            self.tree
+                .initialize_motion_animations(&mut self.render_state);
+            self.render_state.end_stable_motion_frame();
+        }
+
+        // Phase 3: tick animations
+        let rs = &mut self.render_state;
+        rs.process_global_motion_exit_cancels();
+        rs.process_global_motion_exit_starts();
+        rs.process_global_motion_starts();
+        rs.tick(now);
+        rs.sync_shared_motion_states();
+        ThemeState::get().tick();
+
+        self.overlays.take_dirty();
+        self.overlays.take_animation_dirty();
+
+        if junita_layout::has_animating_statefuls() {
+            junita_layout::check_stateful_animations();
+        }
+    }
+
+    /// Rebuild the tree from the builder
+    ///
+    /// Uses the incremental diff like the windowed loop, or a full rebuild
+    /// when a relayout was requested.
+    fn rebuild(&mut self, full: bool) {
+        junita_layout::reset_call_counters();
+        self.render_state.reset_stable_motions_for_rebuild();
+
+        let ui = (self.compose)().child(self.overlays.build_overlay_layer());
+        if full {
+            self.tree.clear_layout_bounds_storages();
+            self.tree = Self::build_tree(
+                &ui,
+                &self.registry,
+                &self.animations,
+                &mut self.render_state,
+                self.width,
+                self.height,
+            );
+            return;
+        }
+
+        match self.tree.incremental_update(&ui) {
+            UpdateResult::NoChanges | UpdateResult::VisualOnly => {}
+            UpdateResult::LayoutChanged => {
+                self.tree.compute_layout(self.width, self.height);
+            }
+            UpdateResult::ChildrenChanged => {
+                self.tree.compute_layout(self.width, self.height);
+                self.tree
+                    .initialize_motion_animations(&mut self.render_state);
+                self.render_state.end_stable_motion_frame();
+                self.render_state.process_global_motion_replays();
+            }
+        }
+    }
+
+    /// Move the virtual clock forward, running a frame every [`FRAME_MS`]
+    pub fn advance(&mut self, duration: Duration) {
+        let mut remaining = duration;
+        while !remaining.is_zero() {
+            let step = remaining.min(Duration::from_millis(FRAME_MS));
+            self.clock.advance(step);
+            self.animations.lock().unwrap().step_frame();
+            self.frame();
+            remaining -= step;
+        }
+    }
+
+    /// Check if anything would still change without further input
+    ///
+    /// Covers springs, keyframes, timelines, motion enter/exit, scroll
+    /// physics, overlay transitions, animating stateful elements and
+    /// pending rebuilds.
+    pub fn is_animating(&self) -> bool {
+        self.animations.lock().unwrap().has_active_animations()
+            || self.has_active_motions()
+            || self.scroll_animating
+            || self.overlays.has_animating_overlays()
+            || junita_layout::has_animating_statefuls()
+            || junita_layout::peek_needs_redraw()
+            || junita_layout::has_pending_subtree_rebuilds()
+            || JunitaContextState::get()
+                .dirty_flag()
+                .load(Ordering::SeqCst)
+    }
+
+    /// Motions that are entering, exiting or waiting on a delay
+    ///
+    /// Suspended motions wait for an explicit start and never finish on
+    /// their own, so they don't count.
+    fn has_active_motions(&self) -> bool {
+        let stable_active = self.motion_states.read().is_ok_and(|states| {
+            states.values().any(|state| {
+                matches!(
+                    state,
+                    MotionAnimationState::Waiting
+                        | MotionAnimationState::Entering { .. }
+                        | MotionAnimationState::Exiting { .. }
+                )
+            })
+        });
+        stable_active
+            || crate::query::walk_nodes(&self.tree)
+                .into_iter()
+                .any(|node| {
+                    self.render_state
+                        .get(node)
+                        .is_some_and(|state| state.has_active_motion())
+                })
+    }
+
+    /// Advance frame by frame until nothing is animating
+    ///
+    /// Returns the virtual time it took. Panics if the app is still animating
+    /// after ten seconds, which usually means a looping animation.
+    pub fn settle(&mut self) -> Duration {
+        let start = self.now_ms();
+        while self.is_animating() {
+            if self.now_ms() - start >= SETTLE_LIMIT_MS {
+                panic!(
+                    "app did not settle within {}ms of virtual time; is an animation looping?",
+                    SETTLE_LIMIT_MS
+                );
+            }
+            self.advance(Duration::from_millis(FRAME_MS));
+        }
+        Duration::from_millis(self.now_ms() - start)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mount_lays_out_at_viewport() {
+        let app = TestApp::mount(320.0, 200.0, || div().id("root").w_full().h(50.0));
+        let root = app.get("root");
+        assert_eq!(root.bounds.width, 320.0);
+        assert_eq!(root.bounds.height, 50.0);
+        assert_eq!(app.viewport(), (320.0, 200.0));
+    }
+
+    #[test]
+    fn test_set_rebuild_is_picked_up_next_frame() {
+        let label = state(String::from("before"));
+        let mut app = TestApp::mount(200.0, 100.0, {
+            let label = label.clone();
+            move || div().id("label").child(text(label.get()))
+        });
+        app.assert_element("label").has_text("before");
+
+        label.set_rebuild(String::from("after"));
+        app.frame();
+        app.assert_element("label").has_text("after");
+    }
+
+    #[test]
+    fn test_advance_moves_virtual_clock() {
+        let mut app = TestApp::mount(100.0, 100.0, div);
+        let frames = app.frame_count();
+        app.advance(Duration::from_millis(100));
+        assert_eq!(app.now(), Duration::from_millis(100));
+        // 6 full frames plus a 4ms remainder
+        assert_eq!(app.frame_count() - frames, 7);
+    }
+
+    #[test]
+    fn test_settle_finishes_springs() {
+        let mut app = TestApp::mount(100.0, 100.0, div);
+        let handle = junita_animation::get_scheduler();
+        let id = handle
+            .register_spring(junita_animation::Spring::new(
+                junita_animation::SpringConfig::stiff(),
+                0.0,
+            ))
+            .unwrap();
+        handle.set_spring_target(id, 1.0);
+        assert!(app.is_animating());
+
+        let elapsed = app.settle();
+        assert!(elapsed > Duration::ZERO);
+        assert!(handle.is_spring_settled(id));
+        assert!((handle.get_spring_value(id).unwrap() - 1.0).abs() < 0.01);
+        handle.remove_spring(id);
+    }
+
+    #[test]
+    fn test_clock_restored_after_drop() {
+        let mut app = TestApp::mount(100.0, 100.0, div);
+        app.advance(Duration::from_millis(50));
+        assert!(!clock::is_realtime());
+        assert_eq!(clock::now(), Duration::from_millis(50));
+
+        // A second app on the same thread shares the clock until both drop
+        let nested = TestApp::mount(100.0, 100.0, div);
+        drop(app);
+        assert!(!clock::is_realtime());
+        drop(nested);
+
+        // Another thread may have mounted since; whoever holds the lock owns
+        // the clock, and nobody holding it means the system clock is back
+        let owner = SERIAL.owner.lock().unwrap_or_else(|e| e.into_inner());
+        assert_eq!(owner.is_none(), clock::is_realtime());
+    }
+
+    #[test]
+    fn test_state_keys_are_unique() {
+        let a = state(1);
+        let b = state(1);
+        a.set(5);
+        assert_eq!(b.get(), 1);
+    }
+}
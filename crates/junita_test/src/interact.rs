@@ -0,0 +1,425 @@
+//! User-level interactions
+//!
+//! Each interaction goes through the `EventRouter` and `RenderTree` dispatch
+//! paths the windowed app uses, aims at the center of the target element and
+//! runs a frame afterwards.
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use junita_core::events::{event_types, KeyCode, Modifiers};
+use junita_layout::tree::LayoutNodeId;
+use junita_layout::widgets::overlay::{OverlayManagerExt, OVERLAY_LAYER_ID};
+use junita_layout::{EventRouter, MouseButton, RenderTree};
+
+use crate::harness::TestApp;
+use crate::query::{ElementInfo, Query};
+
+/// Pointer moves per drag are spaced at most this many pixels apart
+const DRAG_STEP: f32 = 10.0;
+
+impl TestApp {
+    /// Run `f` against the router and collect the events it emits
+    fn route<R>(
+        &mut self,
+        f: impl FnOnce(&mut EventRouter, &RenderTree) -> R,
+    ) -> Vec<(LayoutNodeId, u32)> {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let sink = Rc::clone(&events);
+        self.router
+            .set_event_callback(move |node, event_type| sink.borrow_mut().push((node, event_type)));
+        f(&mut self.router, &self.tree);
+        self.router.clear_event_callback();
+        let collected = events.borrow_mut().split_off(0);
+        collected
+    }
+
+    /// Dispatch routed pointer events with per-node bounds
+    fn dispatch_pointer(&mut self, events: Vec<(LayoutNodeId, u32)>, x: f32, y: f32) {
+        let fallback_pos = self.router.last_hit_bounds_pos();
+        let fallback_size = self.router.last_hit_bounds();
+        let (drag_dx, drag_dy) = self.router.drag_delta();
+        for (node, event_type) in events {
+            if event_type == event_types::SCROLL {
+                continue;
+            }
+            let (bx, by, bw, bh) = self.router.get_node_bounds(node).unwrap_or((
+                fallback_pos.0,
+                fallback_pos.1,
+                fallback_size.0,
+                fallback_size.1,
+            ));
+            let (dx, dy) = if event_type == event_types::DRAG || event_type == event_types::DRAG_END
+            {
+                (drag_dx, drag_dy)
+            } else {
+                (0.0, 0.0)
+            };
+            self.tree.dispatch_event_full(
+                node,
+                event_type,
+                x,
+                y,
+                x - bx,
+                y - by,
+                bx,
+                by,
+                bw,
+                bh,
+                dx,
+                dy,
+                1.0,
+            );
+        }
+    }
+
+    /// Resolve `query` to a point the pointer can reach
+    ///
+    /// Panics if the element is hidden or another element covers its center,
+    /// since a user couldn't interact with it either.
+    fn pointer_target(&self, query: &Query) -> (ElementInfo, (f32, f32)) {
+        let element = self.get(query);
+        if !element.visible {
+            panic!(
+                "cannot interact with {}: element is not visible\n\n{}",
+                query,
+                self.debug_tree()
+            );
+        }
+        let (x, y) = element.center();
+        match self.router.hit_test(&self.tree, x, y) {
+            Some(hit) if hit.node == element.node || hit.ancestors.contains(&element.node) => {}
+            hit => {
+                let covering = hit
+                    .and_then(|hit| self.tree.element_registry().get_id(hit.node))
+                    .map_or_else(|| "another element".to_string(), |id| format!("#{}", id));
+                panic!(
+                    "cannot interact with {}: covered by {} at ({:.0}, {:.0})\n\n{}",
+                    query,
+                    covering,
+                    x,
+                    y,
+                    self.debug_tree()
+                );
+            }
+        }
+        (element, (x, y))
+    }
+
+    // =========================================================================
+    // Pointer
+    // =========================================================================
+
+    /// Move the pointer to viewport coordinates
+    pub fn move_mouse(&mut self, x: f32, y: f32) {
+        self.pointer_move(x, y);
+        self.frame();
+    }
+
+    pub(crate) fn pointer_move(&mut self, x: f32, y: f32) {
+        let overlay_bounds = self.overlays.get_visible_overlay_bounds();
+        let overlay_layer = self.tree.query_by_id(OVERLAY_LAYER_ID);
+        let events = self.route(|router, tree| {
+            router.on_mouse_move_with_occlusion(tree, x, y, &overlay_bounds, overlay_layer);
+        });
+        self.dispatch_pointer(events, x, y);
+    }
+
+    pub(crate) fn pointer_down(&mut self, x: f32, y: f32, button: MouseButton) {
+        // Backdrop clicks dismiss overlays without reaching the elements behind
+        if (self.overlays.has_blocking_overlay() || self.overlays.has_dismissable_overlay())
+            && self.overlays.handle_click_at(x, y)
+        {
+            return;
+        }
+        junita_layout::widgets::blur_all_text_inputs();
+        let events = self.route(|router, tree| {
+            router.on_mouse_down(tree, x, y, button);
+        });
+        self.dispatch_pointer(events, x, y);
+    }
+
+    pub(crate) fn pointer_up(&mut self, x: f32, y: f32, button: MouseButton) {
+        let events = self.route(|router, tree| {
+            router.on_mouse_up(tree, x, y, button);
+        });
+        self.dispatch_pointer(events, x, y);
+    }
+
+    /// Move the pointer over the center of an element
+    pub fn hover(&mut self, query: impl Into<Query>) {
+        let (_, (x, y)) = self.pointer_target(&query.into());
+        self.move_mouse(x, y);
+    }
+
+    /// Press and release the primary button over the center of an element
+    pub fn click(&mut self, query: impl Into<Query>) {
+        let (_, (x, y)) = self.pointer_target(&query.into());
+        self.move_mouse(x, y);
+        self.pointer_down(x, y, MouseButton::Left);
+        self.frame();
+        self.pointer_up(x, y, MouseButton::Left);
+        self.frame();
+    }
+
+    /// Press on an element, move by (`dx`, `dy`) and release
+    ///
+    /// The pointer moves in small steps so handlers see a stream of `DRAG`
+    /// events, as they would from a real mouse.
+    pub fn drag(&mut self, query: impl Into<Query>, dx: f32, dy: f32) {
+        let (_, (x, y)) = self.pointer_target(&query.into());
+        self.move_mouse(x, y);
+        self.pointer_down(x, y, MouseButton::Left);
+        self.frame();
+
+        let steps = (dx.abs().max(dy.abs()) / DRAG_STEP).ceil().max(1.0) as usize;
+        for step in 1..=steps {
+            let t = step as f32 / steps as f32;
+            self.move_mouse(x + dx * t, y + dy * t);
+        }
+
+        self.pointer_up(x + dx, y + dy, MouseButton::Left);
+        self.frame();
+    }
+
+    /// Scroll with the pointer over an element
+    ///
+    /// Deltas use the platform convention: a negative `dy` moves content up
+    /// to reveal what is below, like turning the wheel towards you.
+    pub fn scroll(&mut self, query: impl Into<Query>, dx: f32, dy: f32) {
+        let (_, (x, y)) = self.pointer_target(&query.into());
+        self.move_mouse(x, y);
+        self.wheel(x, y, dx, dy);
+        self.frame();
+    }
+
+    /// Deliver one wheel event at viewport coordinates
+    pub(crate) fn wheel(&mut self, x: f32, y: f32, dx: f32, dy: f32) {
+        // Overlays with a backdrop keep the content behind them still
+        if !self.overlays.has_blocking_overlay() {
+            if self.overlays.handle_scroll(dy) {
+                for (element_id, offset_y) in self.overlays.get_scroll_offsets() {
+                    if let Some(node) = self.tree.query_by_id(&element_id) {
+                        self.tree.set_scroll_offset(node, 0.0, offset_y);
+                    }
+                }
+            }
+            if let Some(hit) = self.router.hit_test(&self.tree, x, y) {
+                self.tree
+                    .dispatch_scroll_chain(hit.node, &hit.ancestors, x, y, dx, dy);
+            }
+        }
+        self.tree.on_scroll_end();
+    }
+
+    // =========================================================================
+    // Keyboard
+    // =========================================================================
+
+    /// Click an element to focus it, then type `text` one key at a time
+    ///
+    /// `'\n'` presses Enter; every other character arrives as text input.
+    pub fn type_text(&mut self, query: impl Into<Query>, text: &str) {
+        self.click(query);
+        for c in text.chars() {
+            if c == '\n' {
+                self.press_key(KeyCode::ENTER, Modifiers::NONE);
+                continue;
+            }
+            self.key_down(KeyCode::UNKNOWN, Modifiers::NONE, false);
+            self.text_input(c);
+            self.key_up(KeyCode::UNKNOWN);
+            self.frame();
+        }
+    }
+
+    /// Press and release a key with modifiers held
+    ///
+    /// Letters, digits and space also produce text input unless Ctrl or Meta
+    /// is held. Escape is offered to open overlays first.
+    pub fn press_key(&mut self, key: KeyCode, modifiers: Modifiers) {
+        self.key_down(key, modifiers, true);
+        self.key_up(key);
+        self.frame();
+    }
+
+    /// Route and broadcast a key press
+    ///
+    /// With `typed`, keys that produce a character also deliver it as text
+    /// input unless Ctrl or Meta is held.
+    pub(crate) fn key_down(&mut self, key: KeyCode, modifiers: Modifiers, typed: bool) {
+        if key == KeyCode::ESCAPE {
+            self.overlays.handle_escape();
+        }
+
+        let (shift, ctrl, alt, meta) = (
+            modifiers.shift(),
+            modifiers.ctrl(),
+            modifiers.alt(),
+            modifiers.meta(),
+        );
+        let events = self.route(|router, _| {
+            router.on_key_down(key.0);
+        });
+        self.dispatch_pointer(events, 0.0, 0.0);
+
+        if let Some(c) = key_char(key, shift).filter(|_| typed) {
+            if !ctrl && !meta {
+                self.tree
+                    .broadcast_text_input_event(c, shift, ctrl, alt, meta);
+            }
+        }
+        if key != KeyCode::UNKNOWN {
+            self.tree
+                .broadcast_key_event(event_types::KEY_DOWN, key.0, shift, ctrl, alt, meta);
+        }
+    }
+
+    /// Route a key release
+    pub(crate) fn key_up(&mut self, key: KeyCode) {
+        let events = self.route(|router, _| {
+            router.on_key_up(key.0);
+        });
+        self.dispatch_pointer(events, 0.0, 0.0);
+    }
+
+    /// Deliver one character of text input to the focused element
+    pub(crate) fn text_input(&mut self, c: char) {
+        self.tree
+            .broadcast_text_input_event(c, c.is_uppercase(), false, false, false);
+    }
+}
+
+/// Character typed by a key on a US layout
+fn key_char(key: KeyCode, shift: bool) -> Option<char> {
+    match key.0 {
+        0x41..=0x5A => {
+            let c = char::from(key.0 as u8);
+            Some(if shift { c } else { c.to_ascii_lowercase() })
+        }
+        0x30..=0x39 if !shift => Some(char::from(key.0 as u8)),
+        0x20 => Some(' '),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::state;
+    use junita_layout::prelude::*;
+    use junita_layout::widgets::{text_input, text_input_state};
+
+    #[test]
+    fn test_click_bubbles_from_label() {
+        let count = state(0);
+        let mut app = TestApp::mount(200.0, 100.0, {
+            let count = count.clone();
+            move || {
+                let count_for_click = count.clone();
+                div()
+                    .id("button")
+                    .w(100.0)
+                    .h(40.0)
+                    .on_click(move |_| count_for_click.update_rebuild(|n| n + 1))
+                    .child(text(format!("Clicked {}", count.get())))
+            }
+        });
+
+        app.click(Query::by_text("Clicked 0"));
+        app.click("button");
+        assert_eq!(count.get(), 2);
+        app.assert_element("button").has_text("Clicked 2");
+    }
+
+    #[test]
+    fn test_hover_tracks_pointer() {
+        let mut app = TestApp::mount(200.0, 100.0, || {
+            div()
+                .flex_row()
+                .child(div().id("a").w(50.0).h(50.0))
+                .child(div().id("b").w(50.0).h(50.0))
+        });
+        app.hover("a");
+        assert!(app.get("a").hovered);
+        app.hover("b");
+        assert!(!app.get("a").hovered);
+        assert!(app.get("b").hovered);
+    }
+
+    #[test]
+    #[should_panic(expected = "covered by #cover")]
+    fn test_click_covered_element_panics() {
+        let mut app = TestApp::mount(200.0, 100.0, || {
+            div()
+                .relative()
+                .child(div().id("under").w(50.0).h(50.0))
+                .child(div().id("cover").absolute().w(100.0).h(100.0))
+        });
+        app.click("under");
+    }
+
+    #[test]
+    fn test_type_text_and_keys() {
+        let input = text_input_state();
+        let mut app = TestApp::mount(300.0, 100.0, {
+            let input = input.clone();
+            move || div().id("name").w(200.0).child(text_input(&input))
+        });
+
+        app.type_text("name", "Hello");
+        assert_eq!(input.lock().unwrap().value, "Hello");
+        app.assert_element("name").is_focused();
+
+        app.press_key(KeyCode::BACKSPACE, Modifiers::NONE);
+        app.press_key(KeyCode::A, Modifiers::new(true, false, false, false));
+        assert_eq!(input.lock().unwrap().value, "HellA");
+    }
+
+    #[test]
+    fn test_scroll_moves_content() {
+        let mut app = TestApp::mount(200.0, 100.0, || {
+            div().child(
+                scroll()
+                    .id("list")
+                    .w(200.0)
+                    .h(100.0)
+                    .child(
+                        div()
+                            .flex_col()
+                            .w(200.0)
+                            .child(div().id("first").h(80.0).w_full())
+                            .child(div().id("last").h(400.0).w_full()),
+                    ),
+            )
+        });
+
+        assert_eq!(app.get("first").bounds.y, 0.0);
+        app.scroll("list", 0.0, -50.0);
+        app.settle();
+        let (_, offset_y) = app.tree().get_scroll_offset(app.get("list").node);
+        assert!(offset_y < 0.0);
+        assert_eq!(app.get("first").bounds.y, offset_y);
+    }
+
+    #[test]
+    fn test_drag_reports_delta() {
+        let moved = state((0.0f32, 0.0f32));
+        let mut app = TestApp::mount(300.0, 300.0, {
+            let moved = moved.clone();
+            move || {
+                let moved = moved.clone();
+                div().child(
+                    div()
+                        .id("handle")
+                        .w(40.0)
+                        .h(40.0)
+                        .on_drag(move |ctx| moved.set((ctx.drag_delta_x, ctx.drag_delta_y))),
+                )
+            }
+        });
+
+        app.drag("handle", 60.0, 25.0);
+        assert_eq!(moved.get(), (60.0, 25.0));
+    }
+}
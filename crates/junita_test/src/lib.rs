@@ -0,0 +1,50 @@
+//! Headless UI testing for Junita
+//!
+//! Mount an `ElementBuilder` into a `RenderTree` at a fixed viewport, find
+//! elements the way a user would, interact with them through the real event
+//! routing, and assert on what comes out. Nothing here needs a GPU or a
+//! window, so component behavior can be covered by ordinary `cargo test`.
+//!
+//! # Overview
+//!
+//! - [`TestApp::mount`] builds the UI and runs the app loop's frame phases
+//! - [`Query`] finds elements by id, text, [`Role`] or predicate
+//! - `click`, `hover`, `type_text`, `press_key`, `scroll` and `drag` drive input
+//! - [`TestApp::advance`] and [`TestApp::settle`] move a virtual clock so
+//!   springs, motions and timers finish deterministically
+//! - [`TestApp::assert_element`] checks text, bounds, visibility and focus
+//! - `TestApp` is a `junita_recorder::ReplayTarget`, so `ReplayPlayer::drive`
+//!   replays a recording into it frame by frame on the virtual clock
+//!
+//! Framework state such as the overlay manager and rebuild flags is global,
+//! so mounted apps take a process-wide lock and tests using them run one at
+//! a time.
+//!
+//! # Example
+//!
+//! ```ignore
+//! use junita_test::{state, Query, Role, TestApp};
+//!
+//! let agreed = state(false);
+//! let mut app = TestApp::mount(400.0, 300.0, {
+//!     let agreed = agreed.clone();
+//!     move || cn::checkbox(&agreed).label("Accept terms")
+//! });
+//!
+//! app.click(Query::by_text("Accept terms"));
+//! app.settle();
+//! assert!(agreed.get());
+//! ```
+
+mod assert;
+mod harness;
+mod interact;
+mod query;
+mod replay;
+
+pub use assert::ElementAssertion;
+pub use harness::{state, TestApp, FRAME_MS};
+pub use query::{ElementInfo, Query, Role};
+
+// Key input types used by `press_key`
+pub use junita_core::events::{KeyCode, Modifiers};
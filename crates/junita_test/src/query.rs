@@ -0,0 +1,579 @@
+//! Element queries
+//!
+//! Queries describe elements the way a user perceives them: by the text they
+//! show, the role they play, or an explicit id. Every query resolves against
+//! the current tree, so a query built before an interaction still finds the
+//! element after the rebuild it caused.
+
+use std::fmt;
+use std::sync::Arc;
+
+use junita_core::events::event_types;
+use junita_layout::element::ElementBounds;
+use junita_layout::renderer::ElementType;
+use junita_layout::tree::LayoutNodeId;
+use junita_layout::RenderTree;
+
+use crate::harness::TestApp;
+
+/// What an element is for, inferred from its type and handlers
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// Accepts text input (text inputs, text areas, code editors)
+    TextBox,
+    /// Reacts to clicks
+    Button,
+    /// Scrolls its content
+    ScrollArea,
+    /// Plain or styled text
+    Text,
+    /// Image or SVG
+    Image,
+    /// Custom-drawn canvas
+    Canvas,
+    /// Any other container
+    Group,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Role::TextBox => "textbox",
+            Role::Button => "button",
+            Role::ScrollArea => "scroll area",
+            Role::Text => "text",
+            Role::Image => "image",
+            Role::Canvas => "canvas",
+            Role::Group => "group",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Snapshot of one element as a test sees it
+#[derive(Clone, Debug)]
+pub struct ElementInfo {
+    /// Layout node
+    pub node: LayoutNodeId,
+    /// Element id, if one was set with `.id()`
+    pub id: Option<String>,
+    /// Inferred role
+    pub role: Role,
+    /// Text shown by this element and its descendants, space separated
+    pub text: String,
+    /// Bounds in viewport coordinates, after scrolling
+    pub bounds: ElementBounds,
+    /// Has area, isn't fully transparent and isn't clipped away
+    pub visible: bool,
+    /// The event router's focused element is this element
+    pub focused: bool,
+    /// The focused element is this element or one of its descendants
+    pub contains_focus: bool,
+    /// The pointer is over this element
+    pub hovered: bool,
+}
+
+impl ElementInfo {
+    /// Center of the element's bounds
+    pub fn center(&self) -> (f32, f32) {
+        (
+            self.bounds.x + self.bounds.width / 2.0,
+            self.bounds.y + self.bounds.height / 2.0,
+        )
+    }
+}
+
+#[derive(Clone)]
+enum Filter {
+    Id(String),
+    Text(String),
+    TextContaining(String),
+    Content(String),
+    Role(Role),
+    Predicate(Arc<dyn Fn(&ElementInfo) -> bool + Send + Sync>),
+}
+
+impl Filter {
+    fn matches(&self, element: &ElementInfo, own_text: Option<&str>) -> bool {
+        match self {
+            Filter::Id(id) => element.id.as_deref() == Some(id.as_str()),
+            Filter::Text(text) => own_text.is_some_and(|own| own.trim() == text),
+            Filter::TextContaining(text) => own_text.is_some_and(|own| own.contains(text.as_str())),
+            Filter::Content(text) => element.text == *text,
+            Filter::Role(role) => element.role == *role,
+            Filter::Predicate(predicate) => predicate(element),
+        }
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Filter::Id(id) => write!(f, "id {:?}", id),
+            Filter::Text(text) => write!(f, "text {:?}", text),
+            Filter::TextContaining(text) => write!(f, "text containing {:?}", text),
+            Filter::Content(text) => write!(f, "content {:?}", text),
+            Filter::Role(role) => write!(f, "role {}", role),
+            Filter::Predicate(_) => f.write_str("predicate"),
+        }
+    }
+}
+
+/// Describes which elements to find
+///
+/// Filters combine with AND:
+///
+/// ```ignore
+/// app.click(Query::by_role(Role::Button).with_text("Save"));
+/// app.click("save"); // a bare string is an id
+/// ```
+#[derive(Clone)]
+pub struct Query {
+    filters: Vec<Filter>,
+}
+
+impl Query {
+    /// Element with this id
+    pub fn by_id(id: impl Into<String>) -> Self {
+        Self {
+            filters: vec![Filter::Id(id.into())],
+        }
+    }
+
+    /// Text element whose content equals `text`, ignoring surrounding whitespace
+    pub fn by_text(text: impl Into<String>) -> Self {
+        Self {
+            filters: vec![Filter::Text(text.into())],
+        }
+    }
+
+    /// Text element whose content contains `text`
+    pub fn by_text_containing(text: impl Into<String>) -> Self {
+        Self {
+            filters: vec![Filter::TextContaining(text.into())],
+        }
+    }
+
+    /// Elements with this role
+    pub fn by_role(role: Role) -> Self {
+        Self {
+            filters: vec![Filter::Role(role)],
+        }
+    }
+
+    /// Elements for which `predicate` returns true
+    pub fn by_predicate<F>(predicate: F) -> Self
+    where
+        F: Fn(&ElementInfo) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            filters: vec![Filter::Predicate(Arc::new(predicate))],
+        }
+    }
+
+    /// Also require the element's combined text to equal `text`
+    ///
+    /// Unlike [`by_text`](Query::by_text) this matches containers, which is
+    /// how a button is told apart from the label inside it.
+    pub fn with_text(mut self, text: impl Into<String>) -> Self {
+        self.filters.push(Filter::Content(text.into()));
+        self
+    }
+
+    /// Also require `predicate` to return true
+    pub fn matching<F>(mut self, predicate: F) -> Self
+    where
+        F: Fn(&ElementInfo) -> bool + Send + Sync + 'static,
+    {
+        self.filters.push(Filter::Predicate(Arc::new(predicate)));
+        self
+    }
+
+    fn matches(&self, element: &ElementInfo, own_text: Option<&str>) -> bool {
+        self.filters
+            .iter()
+            .all(|filter| filter.matches(element, own_text))
+    }
+}
+
+impl From<&str> for Query {
+    fn from(id: &str) -> Self {
+        Query::by_id(id)
+    }
+}
+
+impl From<String> for Query {
+    fn from(id: String) -> Self {
+        Query::by_id(id)
+    }
+}
+
+impl From<&Query> for Query {
+    fn from(query: &Query) -> Self {
+        query.clone()
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, filter) in self.filters.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" and ")?;
+            }
+            write!(f, "{}", filter)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Query({})", self)
+    }
+}
+
+// =============================================================================
+// Tree walking
+// =============================================================================
+
+/// All nodes in depth-first order, starting at the root
+pub(crate) fn walk_nodes(tree: &RenderTree) -> Vec<LayoutNodeId> {
+    let mut nodes = Vec::new();
+    let mut stack: Vec<LayoutNodeId> = tree.root().into_iter().collect();
+    while let Some(node) = stack.pop() {
+        nodes.push(node);
+        stack.extend(tree.layout().children(node).into_iter().rev());
+    }
+    nodes
+}
+
+/// Visible region inherited from ancestors, in viewport coordinates
+#[derive(Clone, Copy)]
+struct Clip {
+    x0: f32,
+    y0: f32,
+    x1: f32,
+    y1: f32,
+}
+
+impl Clip {
+    fn intersect(self, bounds: &ElementBounds) -> Self {
+        Self {
+            x0: self.x0.max(bounds.x),
+            y0: self.y0.max(bounds.y),
+            x1: self.x1.min(bounds.x + bounds.width),
+            y1: self.y1.min(bounds.y + bounds.height),
+        }
+    }
+
+    fn overlaps(&self, bounds: &ElementBounds) -> bool {
+        bounds.x < self.x1
+            && bounds.x + bounds.width > self.x0
+            && bounds.y < self.y1
+            && bounds.y + bounds.height > self.y0
+    }
+}
+
+/// Own text of a text node
+fn own_text(tree: &RenderTree, node: LayoutNodeId) -> Option<&str> {
+    match &tree.get_render_node(node)?.element_type {
+        ElementType::Text(data) => Some(&data.content),
+        ElementType::StyledText(data) => Some(&data.content),
+        _ => None,
+    }
+}
+
+fn infer_role(tree: &RenderTree, node: LayoutNodeId) -> Role {
+    let Some(render_node) = tree.get_render_node(node) else {
+        return Role::Group;
+    };
+    match render_node.element_type {
+        ElementType::Text(_) | ElementType::StyledText(_) => Role::Text,
+        ElementType::Image(_) | ElementType::Svg(_) => Role::Image,
+        ElementType::Canvas(_) => Role::Canvas,
+        ElementType::Div => {
+            let handlers = tree.handler_registry();
+            if handlers.has_handler(node, event_types::TEXT_INPUT) {
+                Role::TextBox
+            } else if handlers.has_handler(node, event_types::POINTER_UP) {
+                Role::Button
+            } else if tree.get_scroll_direction(node).is_some() {
+                Role::ScrollArea
+            } else {
+                Role::Group
+            }
+        }
+    }
+}
+
+struct Collector<'a> {
+    app: &'a TestApp,
+    hovered: Vec<LayoutNodeId>,
+    focused: Option<LayoutNodeId>,
+    focus_chain: Vec<LayoutNodeId>,
+    out: Vec<Collected<'a>>,
+}
+
+/// An element with the data only needed while matching and printing
+struct Collected<'a> {
+    info: ElementInfo,
+    own_text: Option<&'a str>,
+    depth: usize,
+}
+
+impl<'a> Collector<'a> {
+    /// Collect `node` and its subtree, returning the subtree's text
+    fn visit(
+        &mut self,
+        node: LayoutNodeId,
+        depth: usize,
+        parent_offset: (f32, f32),
+        clip: Clip,
+        parent_opacity: f32,
+    ) -> String {
+        let tree: &'a RenderTree = &self.app.tree;
+        let Some(bounds) = tree.layout().get_bounds(node, parent_offset) else {
+            return String::new();
+        };
+        let props = tree.get_render_node(node).map(|n| &n.props);
+        let opacity = parent_opacity * props.map_or(1.0, |p| p.opacity);
+
+        // Reserve our slot so parents precede children in the output
+        let slot = self.out.len();
+        let own = own_text(tree, node);
+        self.out.push(Collected {
+            info: ElementInfo {
+                node,
+                id: tree.element_registry().get_id(node),
+                role: infer_role(tree, node),
+                text: String::new(),
+                bounds,
+                visible: bounds.width > 0.0
+                    && bounds.height > 0.0
+                    && opacity > 0.0
+                    && clip.overlaps(&bounds)
+                    && !self.app.render_state.is_motion_removed(node),
+                focused: self.focused == Some(node),
+                contains_focus: self.focused == Some(node) || self.focus_chain.contains(&node),
+                hovered: self.hovered.contains(&node),
+            },
+            own_text: own,
+            depth,
+        });
+
+        let child_clip = if props.is_some_and(|p| p.clips_content) {
+            clip.intersect(&bounds)
+        } else {
+            clip
+        };
+        let scroll = tree.get_scroll_offset(node);
+        let child_offset = (bounds.x + scroll.0, bounds.y + scroll.1);
+
+        let mut texts: Vec<String> = own
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .into_iter()
+            .collect();
+        for child in tree.layout().children(node) {
+            let child_text = self.visit(child, depth + 1, child_offset, child_clip, opacity);
+            if !child_text.is_empty() {
+                texts.push(child_text);
+            }
+        }
+        let text = texts.join(" ");
+        self.out[slot].info.text = text.clone();
+        text
+    }
+}
+
+impl TestApp {
+    /// Snapshot every element in the mounted UI, parents before children
+    ///
+    /// The harness root that wraps the builder is skipped.
+    pub fn elements(&self) -> Vec<ElementInfo> {
+        self.collect().into_iter().map(|c| c.info).collect()
+    }
+
+    fn collect(&self) -> Vec<Collected<'_>> {
+        let Some(root) = self.tree.root() else {
+            return Vec::new();
+        };
+        let focused = self.router.focused();
+        let mut collector = Collector {
+            app: self,
+            hovered: self.router.hovered_nodes().collect(),
+            focused,
+            focus_chain: if focused.is_some() {
+                self.router.focused_ancestors().to_vec()
+            } else {
+                Vec::new()
+            },
+            out: Vec::new(),
+        };
+        let viewport = Clip {
+            x0: 0.0,
+            y0: 0.0,
+            x1: self.width,
+            y1: self.height,
+        };
+        collector.visit(root, 0, (0.0, 0.0), viewport, 1.0);
+        collector.out.remove(0);
+        collector.out
+    }
+
+    /// All elements matching `query`, in tree order
+    pub fn get_all(&self, query: impl Into<Query>) -> Vec<ElementInfo> {
+        let query = query.into();
+        self.collect()
+            .into_iter()
+            .filter(|c| query.matches(&c.info, c.own_text))
+            .map(|c| c.info)
+            .collect()
+    }
+
+    /// The element matching `query`, if exactly one does
+    ///
+    /// Panics if several elements match, since a test acting on the first
+    /// of many usually hides a mistake.
+    pub fn query(&self, query: impl Into<Query>) -> Option<ElementInfo> {
+        let query = query.into();
+        let mut matches = self.get_all(&query);
+        match matches.len() {
+            0 => None,
+            1 => matches.pop(),
+            n => panic!(
+                "expected one element matching {}, found {}\n\n{}",
+                query,
+                n,
+                self.debug_tree()
+            ),
+        }
+    }
+
+    /// The element matching `query`, panicking if none or several do
+    pub fn get(&self, query: impl Into<Query>) -> ElementInfo {
+        let query = query.into();
+        self.query(&query).unwrap_or_else(|| {
+            panic!(
+                "no element matching {}\n\n{}",
+                query,
+                self.debug_tree()
+            )
+        })
+    }
+
+    /// Indented outline of the mounted UI, used in failure messages
+    pub fn debug_tree(&self) -> String {
+        let mut out = String::new();
+        for Collected {
+            info,
+            own_text,
+            depth,
+        } in self.collect()
+        {
+            // Depth 1 is the builder's root once the harness root is skipped
+            out.push_str(&"  ".repeat(depth - 1));
+            out.push_str(&info.role.to_string());
+            if let Some(id) = &info.id {
+                out.push_str(&format!(" #{}", id));
+            }
+            if let Some(own) = own_text {
+                out.push_str(&format!(" {:?}", own));
+            }
+            let b = &info.bounds;
+            out.push_str(&format!(
+                " ({:.0}, {:.0}, {:.0}x{:.0})",
+                b.x, b.y, b.width, b.height
+            ));
+            if !info.visible {
+                out.push_str(" hidden");
+            }
+            if info.focused {
+                out.push_str(" focused");
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use junita_layout::prelude::*;
+
+    fn sample() -> TestApp {
+        TestApp::mount(300.0, 200.0, || {
+            div()
+                .id("panel")
+                .flex_col()
+                .child(div().id("title").child(text("Title")))
+                .child(
+                    div()
+                        .id("save")
+                        .w(80.0)
+                        .h(30.0)
+                        .on_click(|_| {})
+                        .child(text("Save")),
+                )
+                .child(div().id("faded").w(10.0).h(10.0).opacity(0.0))
+                .child(
+                    div()
+                        .id("clip")
+                        .w(50.0)
+                        .h(20.0)
+                        .flex_col()
+                        .overflow_clip()
+                        .child(div().h(20.0).flex_shrink_0())
+                        .child(div().id("clipped").w(10.0).h(10.0)),
+                )
+        })
+    }
+
+    #[test]
+    fn test_find_by_id_text_and_role() {
+        let app = sample();
+        assert_eq!(app.get("title").role, Role::Group);
+        assert_eq!(app.get("title").text, "Title");
+        assert_eq!(app.get(Query::by_text("Title")).role, Role::Text);
+        assert_eq!(
+            app.get(Query::by_role(Role::Button).with_text("Save"))
+                .id
+                .as_deref(),
+            Some("save")
+        );
+        assert!(app.query(Query::by_text("Sav")).is_none());
+        assert!(app.query(Query::by_text_containing("Sav")).is_some());
+        assert_eq!(app.get("panel").text, "Title Save");
+    }
+
+    #[test]
+    fn test_find_by_predicate() {
+        let app = sample();
+        let wide = app.get_all(Query::by_predicate(|e| {
+            e.id.as_deref().is_some_and(|id| id.starts_with("sa"))
+        }));
+        assert_eq!(wide.len(), 1);
+        assert_eq!(wide[0].id.as_deref(), Some("save"));
+    }
+
+    #[test]
+    fn test_visibility() {
+        let app = sample();
+        assert!(app.get("save").visible);
+        assert!(!app.get("faded").visible);
+        assert!(!app.get("clipped").visible);
+    }
+
+    #[test]
+    #[should_panic(expected = "no element matching id \"missing\"")]
+    fn test_get_missing_panics() {
+        sample().get("missing");
+    }
+
+    #[test]
+    #[should_panic(expected = "expected one element matching role text")]
+    fn test_get_ambiguous_panics() {
+        sample().get(Query::by_role(Role::Text));
+    }
+}
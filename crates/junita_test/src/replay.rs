@@ -0,0 +1,311 @@
+//! Replaying recordings into a mounted app
+//!
+//! `TestApp` implements the recorder's [`ReplayTarget`], so a recording can
+//! be played back through the same event routing the user's input went
+//! through. Frames run at the replay's virtual time on the process-wide
+//! virtual clock, so animations, cursor blink and scroll physics reproduce
+//! the same tree snapshots on every run.
+//!
+//! ```ignore
+//! use junita_recorder::{RecordingExport, ReplayConfig, ReplayPlayer};
+//!
+//! let mut app = TestApp::mount(400.0, 300.0, my_app::root);
+//! let mut player = ReplayPlayer::new(export, ReplayConfig::testing());
+//! let frames = player.drive(&mut app);
+//! ```
+
+use std::time::Duration;
+
+use junita_core::events::{KeyCode, Modifiers};
+use junita_layout::recorder_bridge::{self, TreeSnapshotData};
+use junita_layout::MouseButton;
+use junita_recorder::{
+    ElementSnapshot, Key, ReplayTarget, SimulatedInput, Timestamp, TreeSnapshot, VisualProps,
+};
+
+use crate::harness::TestApp;
+
+impl ReplayTarget for TestApp {
+    /// Deliver a recorded input without running a frame
+    ///
+    /// Focus and hover changes are derived from the replayed pointer and key
+    /// input, so they are not applied directly. Resize, window focus and
+    /// custom events are ignored.
+    fn dispatch(&mut self, input: &SimulatedInput) {
+        match input {
+            SimulatedInput::Click {
+                position, button, ..
+            } => {
+                self.pointer_move(position.x, position.y);
+                self.pointer_down(position.x, position.y, mouse_button(*button));
+                self.pointer_up(position.x, position.y, mouse_button(*button));
+            }
+            SimulatedInput::DoubleClick {
+                position, button, ..
+            } => {
+                self.pointer_move(position.x, position.y);
+                for _ in 0..2 {
+                    self.pointer_down(position.x, position.y, mouse_button(*button));
+                    self.pointer_up(position.x, position.y, mouse_button(*button));
+                }
+            }
+            SimulatedInput::MouseDown {
+                position, button, ..
+            } => self.pointer_down(position.x, position.y, mouse_button(*button)),
+            SimulatedInput::MouseUp {
+                position, button, ..
+            } => self.pointer_up(position.x, position.y, mouse_button(*button)),
+            SimulatedInput::MouseMove { position, .. } => {
+                self.pointer_move(position.x, position.y)
+            }
+            SimulatedInput::Scroll {
+                position,
+                delta_x,
+                delta_y,
+                ..
+            } => self.wheel(position.x, position.y, *delta_x, *delta_y),
+            // Recorded text arrives as separate TextInput events
+            SimulatedInput::KeyDown { key, modifiers, .. } => {
+                self.key_down(key_code(key), to_modifiers(*modifiers), false)
+            }
+            SimulatedInput::KeyUp { key, .. } => self.key_up(key_code(key)),
+            SimulatedInput::TextInput { text } => {
+                for c in text.chars() {
+                    self.text_input(c);
+                }
+            }
+            SimulatedInput::FocusChange { .. }
+            | SimulatedInput::HoverEnter { .. }
+            | SimulatedInput::HoverLeave { .. }
+            | SimulatedInput::WindowResize { .. }
+            | SimulatedInput::WindowFocus { .. }
+            | SimulatedInput::Custom { .. } => {}
+        }
+    }
+
+    /// Move the virtual clock to `now` and run frames up to it
+    ///
+    /// Gaps longer than [`FRAME_MS`](crate::FRAME_MS) are split into frames
+    /// as with [`advance`](TestApp::advance); a `now` at or before the
+    /// current time runs a single frame.
+    fn frame(&mut self, now: Timestamp) {
+        let target = Duration::from_micros(now.as_micros());
+        let current = self.now();
+        if target > current {
+            self.advance(target - current);
+        } else {
+            TestApp::frame(self);
+        }
+    }
+
+    /// Capture the mounted tree, timestamped with the virtual clock
+    fn snapshot(&mut self) -> TreeSnapshot {
+        let hovered = self.router.hovered_nodes().collect();
+        let data = recorder_bridge::capture_tree_snapshot(
+            &self.tree,
+            self.router.focused(),
+            &hovered,
+            self.width as u32,
+            self.height as u32,
+        );
+        to_snapshot(data, Timestamp::from_duration(self.now()))
+    }
+}
+
+/// Convert the layout crate's snapshot into the recorder's format
+fn to_snapshot(data: TreeSnapshotData, timestamp: Timestamp) -> TreeSnapshot {
+    let mut snapshot = TreeSnapshot::new(timestamp, data.window_size, data.scale_factor);
+    snapshot.root_id = data.root_id;
+    snapshot.focused_element = data.focused_element;
+    snapshot.hovered_element = data.hovered_element;
+    snapshot.elements = data
+        .elements
+        .into_iter()
+        .map(|(id, e)| {
+            let element = ElementSnapshot {
+                id: e.id,
+                stable_id: e.stable_id,
+                element_type: e.element_type,
+                bounds: junita_recorder::Rect::new(
+                    e.bounds.x,
+                    e.bounds.y,
+                    e.bounds.width,
+                    e.bounds.height,
+                ),
+                is_visible: e.is_visible,
+                is_focused: e.is_focused,
+                is_hovered: e.is_hovered,
+                is_interactive: e.is_interactive,
+                children: e.children,
+                parent: e.parent,
+                visual_props: e.visual_props.map(|p| VisualProps {
+                    background_color: p.background_color,
+                    border_color: p.border_color,
+                    border_width: p.border_width,
+                    border_radius: p.border_radius,
+                    opacity: p.opacity,
+                    ..Default::default()
+                }),
+                text_content: e.text_content,
+            };
+            (id, element)
+        })
+        .collect();
+    snapshot
+}
+
+fn mouse_button(button: junita_recorder::MouseButton) -> MouseButton {
+    match button {
+        junita_recorder::MouseButton::Left => MouseButton::Left,
+        junita_recorder::MouseButton::Right => MouseButton::Right,
+        junita_recorder::MouseButton::Middle => MouseButton::Middle,
+        junita_recorder::MouseButton::Other(n) => MouseButton::Other(n as u16),
+    }
+}
+
+fn to_modifiers(m: junita_recorder::Modifiers) -> Modifiers {
+    Modifiers::new(m.shift, m.ctrl, m.alt, m.meta)
+}
+
+/// Key code the windowed app reports for a recorded key
+fn key_code(key: &Key) -> KeyCode {
+    let code = match key {
+        Key::A => 0x41,
+        Key::B => 0x42,
+        Key::C => 0x43,
+        Key::D => 0x44,
+        Key::E => 0x45,
+        Key::F => 0x46,
+        Key::G => 0x47,
+        Key::H => 0x48,
+        Key::I => 0x49,
+        Key::J => 0x4A,
+        Key::K => 0x4B,
+        Key::L => 0x4C,
+        Key::M => 0x4D,
+        Key::N => 0x4E,
+        Key::O => 0x4F,
+        Key::P => 0x50,
+        Key::Q => 0x51,
+        Key::R => 0x52,
+        Key::S => 0x53,
+        Key::T => 0x54,
+        Key::U => 0x55,
+        Key::V => 0x56,
+        Key::W => 0x57,
+        Key::X => 0x58,
+        Key::Y => 0x59,
+        Key::Z => 0x5A,
+        Key::Num0 => 0x30,
+        Key::Num1 => 0x31,
+        Key::Num2 => 0x32,
+        Key::Num3 => 0x33,
+        Key::Num4 => 0x34,
+        Key::Num5 => 0x35,
+        Key::Num6 => 0x36,
+        Key::Num7 => 0x37,
+        Key::Num8 => 0x38,
+        Key::Num9 => 0x39,
+        Key::Backspace => return KeyCode::BACKSPACE,
+        Key::Tab => return KeyCode::TAB,
+        Key::Enter => return KeyCode::ENTER,
+        Key::Escape => return KeyCode::ESCAPE,
+        Key::Space => return KeyCode::SPACE,
+        Key::Delete => return KeyCode::DELETE,
+        Key::Left => return KeyCode::LEFT,
+        Key::Up => return KeyCode::UP,
+        Key::Right => return KeyCode::RIGHT,
+        Key::Down => return KeyCode::DOWN,
+        Key::Home => return KeyCode::HOME,
+        Key::End => return KeyCode::END,
+        Key::PageUp => return KeyCode::PAGE_UP,
+        Key::PageDown => return KeyCode::PAGE_DOWN,
+        Key::Other(code) => *code,
+        _ => return KeyCode::UNKNOWN,
+    };
+    KeyCode(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harness::state;
+    use junita_animation::{Spring, SpringConfig};
+    use junita_layout::prelude::*;
+    use junita_recorder::{
+        MouseEvent, Point, RecordedEvent, RecordingConfig, RecordingExport, ReplayConfig,
+        ReplayPlayer, TimestampedEvent,
+    };
+
+    fn click_at(ms: u64, x: f32, y: f32) -> TimestampedEvent {
+        TimestampedEvent::new(
+            Timestamp::from_micros(ms * 1000),
+            RecordedEvent::Click(MouseEvent {
+                position: Point::new(x, y),
+                button: junita_recorder::MouseButton::Left,
+                modifiers: junita_recorder::Modifiers::none(),
+                target_element: None,
+            }),
+        )
+    }
+
+    /// Replay two clicks into a fresh app whose click starts a spring
+    fn replay_once() -> (Vec<TreeSnapshot>, f32) {
+        let count = state(0);
+        let handle = junita_animation::get_scheduler();
+        let spring = handle
+            .register_spring(Spring::new(SpringConfig::stiff(), 0.0))
+            .unwrap();
+        let mut app = TestApp::mount(200.0, 100.0, {
+            let count = count.clone();
+            move || {
+                let count_for_click = count.clone();
+                let handle = handle.clone();
+                div()
+                    .id("button")
+                    .w(100.0)
+                    .h(40.0)
+                    .on_click(move |_| {
+                        count_for_click.update_rebuild(|n| n + 1);
+                        handle.set_spring_target(spring, 100.0);
+                    })
+                    .child(text(format!("Clicked {}", count.get())))
+            }
+        });
+
+        let export = RecordingExport {
+            config: RecordingConfig::minimal(),
+            events: vec![click_at(40, 10.0, 10.0), click_at(120, 20.0, 20.0)],
+            snapshots: Vec::new(),
+            stats: Default::default(),
+        };
+        let frames = ReplayPlayer::new(export, ReplayConfig::testing()).drive(&mut app);
+        assert_eq!(count.get(), 2);
+        assert_eq!(app.now(), Duration::from_millis(120));
+
+        let handle = junita_animation::get_scheduler();
+        let value = handle.get_spring_value(spring).unwrap();
+        handle.remove_spring(spring);
+        (frames, value)
+    }
+
+    #[test]
+    fn test_replay_is_deterministic() {
+        let (frames, value) = replay_once();
+        assert_eq!(frames.len(), 8);
+        assert_eq!(frames.last().unwrap().timestamp.as_micros(), 120_000);
+        let button = frames
+            .last()
+            .unwrap()
+            .elements
+            .values()
+            .find(|e| e.text_content.as_deref() == Some("Clicked 2"));
+        assert!(button.is_some());
+        // Mid-flight, driven only by virtual time
+        assert!(value > 0.0 && value < 100.0);
+
+        let (again, again_value) = replay_once();
+        assert_eq!(again, frames);
+        assert_eq!(again_value, value);
+    }
+}
@@ -0,0 +1,297 @@
+//! CPU test harness for visual tests
+//!
+//! Runs draw closures and render trees through the `junita_cpu` software
+//! renderer, so visual regression tests can run on CI machines without a
+//! GPU adapter. References are kept separately from GPU references since
+//! anti-aliasing differs slightly between the two renderers.
+
+use anyhow::{Context, Result};
+use image::RgbaImage;
+use junita_core::{DrawContext, Size};
+use junita_cpu::{CpuPaintContext, CpuRenderer};
+use junita_layout::prelude::*;
+use junita_recorder::testing::{CapturedFrame, PerceptualOptions};
+use std::cell::{Ref, RefCell};
+use std::path::{Path, PathBuf};
+
+use crate::golden::{self, GoldenMode, GoldenPaths};
+use crate::harness::TestResult;
+use crate::report::GoldenReport;
+
+/// Convert a captured frame into an image buffer
+pub fn frame_to_image(frame: CapturedFrame) -> Option<RgbaImage> {
+    RgbaImage::from_raw(frame.width, frame.height, frame.data)
+}
+
+/// Test harness backed by the CPU reference renderer
+pub struct CpuTestHarness {
+    /// Output directory for test results
+    output_dir: PathBuf,
+    /// Reference image directory
+    reference_dir: PathBuf,
+    /// Default viewport size
+    default_size: Size,
+    /// Perceptual comparison options
+    perceptual: PerceptualOptions,
+    /// Compare against or update references
+    mode: GoldenMode,
+    /// Failed and updated goldens from this run
+    report: RefCell<GoldenReport>,
+    /// Font loaded into every context (for deterministic text)
+    font_data: Option<Vec<u8>>,
+}
+
+impl CpuTestHarness {
+    /// Create a CPU harness with default configuration
+    pub fn new() -> Result<Self> {
+        Self::with_dirs("test_output/cpu", "test_output/references/cpu")
+    }
+
+    /// Create a CPU harness writing to custom directories
+    pub fn with_dirs(
+        output_dir: impl AsRef<Path>,
+        reference_dir: impl AsRef<Path>,
+    ) -> Result<Self> {
+        let output_dir = output_dir.as_ref().to_path_buf();
+        let reference_dir = reference_dir.as_ref().to_path_buf();
+        std::fs::create_dir_all(&output_dir).context("Failed to create output directory")?;
+        std::fs::create_dir_all(&reference_dir).context("Failed to create reference directory")?;
+
+        Ok(Self {
+            output_dir,
+            reference_dir,
+            default_size: Size::new(400.0, 300.0),
+            perceptual: PerceptualOptions::default(),
+            mode: GoldenMode::from_env(),
+            report: RefCell::new(GoldenReport::new()),
+            font_data: None,
+        })
+    }
+
+    /// Set the default viewport size
+    pub fn with_size(mut self, width: f32, height: f32) -> Self {
+        self.default_size = Size::new(width, height);
+        self
+    }
+
+    /// Set the fraction of perceptually changed pixels allowed (0.0-1.0)
+    pub fn with_threshold(mut self, threshold: f32) -> Self {
+        self.perceptual.max_changed_percent = threshold * 100.0;
+        self
+    }
+
+    /// Set the perceptual comparison options
+    pub fn with_perceptual(mut self, options: PerceptualOptions) -> Self {
+        self.perceptual = options;
+        self
+    }
+
+    /// Compare against or update references
+    pub fn with_mode(mut self, mode: GoldenMode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// Use a specific font for all text, independent of installed system fonts
+    pub fn with_font_data(mut self, data: Vec<u8>) -> Self {
+        self.font_data = Some(data);
+        self
+    }
+
+    /// Render a draw closure to a frame without comparing it
+    pub fn render<F>(&self, width: f32, height: f32, test_fn: F) -> CapturedFrame
+    where
+        F: FnOnce(&mut dyn DrawContext),
+    {
+        let mut ctx = CpuPaintContext::new(width, height);
+        self.load_font(ctx.text());
+        test_fn(&mut ctx);
+        ctx.into_frame()
+    }
+
+    /// Render a laid-out tree to a frame without comparing it
+    pub fn render_layout(&self, tree: &RenderTree, width: f32, height: f32) -> CapturedFrame {
+        let mut renderer = CpuRenderer::new(width, height);
+        self.load_font(renderer.context().text());
+        renderer.render_tree(tree)
+    }
+
+    /// Run a draw test at the default size
+    pub fn run_test<F>(&self, name: &str, test_fn: F) -> Result<TestResult>
+    where
+        F: FnOnce(&mut dyn DrawContext),
+    {
+        let frame = self.render(self.default_size.width, self.default_size.height, test_fn);
+        self.check(name, frame, &self.perceptual)
+    }
+
+    /// Run a draw test with its own comparison options, e.g. ignore regions
+    pub fn run_test_with_options<F>(
+        &self,
+        name: &str,
+        options: &PerceptualOptions,
+        test_fn: F,
+    ) -> Result<TestResult>
+    where
+        F: FnOnce(&mut dyn DrawContext),
+    {
+        let frame = self.render(self.default_size.width, self.default_size.height, test_fn);
+        self.check(name, frame, options)
+    }
+
+    /// Run a layout test, computing layout at the default size
+    pub fn run_layout_test(&self, name: &str, tree: &mut RenderTree) -> Result<TestResult> {
+        let Size { width, height } = self.default_size;
+        tree.compute_layout(width, height);
+        let frame = self.render_layout(tree, width, height);
+        self.check(name, frame, &self.perceptual)
+    }
+
+    /// Get the reference image path for a test
+    pub fn reference_path(&self, name: &str) -> PathBuf {
+        self.reference_dir.join(format!("{}.png", name))
+    }
+
+    /// Get the output image path for a test
+    pub fn output_path(&self, name: &str) -> PathBuf {
+        self.output_dir.join(format!("{}.png", name))
+    }
+
+    /// Get the diff image path for a test
+    pub fn diff_path(&self, name: &str) -> PathBuf {
+        self.output_dir.join(format!("{}_diff.png", name))
+    }
+
+    fn load_font(&self, text: &mut junita_cpu::CpuTextContext) {
+        if let Some(data) = &self.font_data {
+            if let Err(e) = text.load_font_data(data.clone()) {
+                tracing::warn!("Failed to load test font: {}", e);
+            }
+        }
+    }
+
+    /// Failed and updated goldens so far
+    pub fn report(&self) -> Ref<'_, GoldenReport> {
+        self.report.borrow()
+    }
+
+    /// Write the HTML report of failed and updated goldens
+    pub fn write_report(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.report.borrow().write_html(path)
+    }
+
+    /// Save the frame and compare it against the stored reference
+    fn check(
+        &self,
+        name: &str,
+        frame: CapturedFrame,
+        options: &PerceptualOptions,
+    ) -> Result<TestResult> {
+        let output_img = frame_to_image(frame).context("Captured frame has invalid size")?;
+        let paths = GoldenPaths::new(&self.reference_dir, &self.output_dir, name);
+        output_img
+            .save(&paths.output)
+            .context("Failed to save PNG")?;
+
+        golden::check(
+            "CPU test",
+            name,
+            &paths,
+            self.mode,
+            options,
+            &mut self.report.borrow_mut(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use junita_core::{Color, CornerRadius, Rect};
+
+    fn temp_harness(name: &str) -> CpuTestHarness {
+        let dir = std::env::temp_dir().join(format!("junita_cpu_harness_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        CpuTestHarness::with_dirs(dir.join("out"), dir.join("refs"))
+            .unwrap()
+            .with_size(64.0, 64.0)
+    }
+
+    #[test]
+    fn test_cpu_reference_roundtrip() {
+        let harness = temp_harness("roundtrip");
+        let draw = |ctx: &mut dyn DrawContext| {
+            ctx.fill_rect(
+                Rect::new(8.0, 8.0, 48.0, 48.0),
+                CornerRadius::uniform(8.0),
+                Color::BLUE.into(),
+            );
+        };
+
+        let first = harness.run_test("rect", draw).unwrap();
+        assert!(matches!(first, TestResult::PassedWithNewReference));
+
+        let second = harness.run_test("rect", draw).unwrap();
+        assert!(matches!(second, TestResult::Passed));
+
+        let changed = harness
+            .run_test("rect", |ctx| {
+                ctx.fill_rect(
+                    Rect::new(0.0, 0.0, 64.0, 64.0),
+                    CornerRadius::ZERO,
+                    Color::RED.into(),
+                );
+            })
+            .unwrap();
+        assert!(!changed.is_passed());
+    }
+
+    #[test]
+    fn test_cpu_ignore_region() {
+        use junita_recorder::testing::IgnoreRegion;
+
+        let harness = temp_harness("ignore");
+        let draw = |caret: Color| {
+            move |ctx: &mut dyn DrawContext| {
+                ctx.fill_rect(
+                    Rect::new(0.0, 0.0, 64.0, 64.0),
+                    CornerRadius::ZERO,
+                    Color::WHITE.into(),
+                );
+                ctx.fill_rect(
+                    Rect::new(30.0, 20.0, 2.0, 24.0),
+                    CornerRadius::ZERO,
+                    caret.into(),
+                );
+            }
+        };
+        harness.run_test("caret", draw(Color::BLACK)).unwrap();
+
+        let changed = harness.run_test("caret", draw(Color::WHITE)).unwrap();
+        assert!(!changed.is_passed());
+        assert!(harness.diff_path("caret").exists());
+        assert_eq!(harness.report().failed(), 1);
+
+        let options = PerceptualOptions::default().ignore_region(IgnoreRegion::new(28, 18, 6, 28));
+        let ignored = harness
+            .run_test_with_options("caret", &options, draw(Color::WHITE))
+            .unwrap();
+        assert!(matches!(ignored, TestResult::Passed));
+    }
+
+    #[test]
+    fn test_cpu_layout_render() {
+        let harness = temp_harness("layout");
+        let ui = div()
+            .w(64.0)
+            .h(64.0)
+            .bg(Color::WHITE)
+            .child(div().w(32.0).h(32.0).bg(Color::RED));
+        let mut tree = RenderTree::from_element(&ui);
+        tree.compute_layout(64.0, 64.0);
+
+        let frame = harness.render_layout(&tree, 64.0, 64.0);
+        assert_eq!(frame.get_pixel(8, 8), Some([255, 0, 0, 255]));
+        assert_eq!(frame.get_pixel(48, 48), Some([255, 255, 255, 255]));
+    }
+}
@@ -0,0 +1,261 @@
+//! Golden image workflow
+//!
+//! Rendered output is compared perceptually against a stored reference
+//! ("golden"). In compare mode a mismatch fails the test and writes a diff
+//! heatmap; in update mode the reference is rewritten from the output and the
+//! previous reference is kept next to it so the change can be reviewed in the
+//! HTML report before committing.
+//!
+//! Update mode is selected with `--update` on the visual test runner or by
+//! setting `JUNITA_UPDATE_GOLDENS=1`.
+
+use anyhow::{Context, Result};
+use image::RgbaImage;
+use junita_recorder::testing::{CapturedFrame, PerceptualDiff, PerceptualOptions};
+use std::path::{Path, PathBuf};
+
+use crate::harness::TestResult;
+use crate::report::{GoldenReport, ReportEntry, ReportStatus};
+
+/// Environment variable that switches harnesses to update mode
+pub const UPDATE_ENV: &str = "JUNITA_UPDATE_GOLDENS";
+
+/// What to do when output differs from its reference
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GoldenMode {
+    /// Fail the test and write a diff heatmap
+    #[default]
+    Compare,
+    /// Replace the reference with the new output
+    Update,
+}
+
+impl GoldenMode {
+    /// Update mode if `JUNITA_UPDATE_GOLDENS` is set to anything but `0`
+    pub fn from_env() -> Self {
+        match std::env::var(UPDATE_ENV) {
+            Ok(value) if !value.is_empty() && value != "0" => GoldenMode::Update,
+            _ => GoldenMode::Compare,
+        }
+    }
+}
+
+/// Files belonging to one golden test
+#[derive(Clone, Debug)]
+pub struct GoldenPaths {
+    /// Stored reference image
+    pub reference: PathBuf,
+    /// Output rendered by this run
+    pub output: PathBuf,
+    /// Diff heatmap, written when the images differ
+    pub diff: PathBuf,
+    /// Copy of the reference that update mode replaced
+    pub previous: PathBuf,
+}
+
+impl GoldenPaths {
+    /// Paths for test `name` under the given directories
+    pub fn new(reference_dir: &Path, output_dir: &Path, name: &str) -> Self {
+        Self {
+            reference: reference_dir.join(format!("{}.png", name)),
+            output: output_dir.join(format!("{}.png", name)),
+            diff: output_dir.join(format!("{}_diff.png", name)),
+            previous: output_dir.join(format!("{}_previous.png", name)),
+        }
+    }
+}
+
+/// Convert an image buffer into a captured frame
+pub fn image_to_frame(img: &RgbaImage) -> CapturedFrame {
+    CapturedFrame::new(img.as_raw().clone(), img.width(), img.height())
+}
+
+/// Compare two images perceptually
+pub fn compare(
+    actual: &RgbaImage,
+    expected: &RgbaImage,
+    options: &PerceptualOptions,
+) -> PerceptualDiff {
+    image_to_frame(actual).perceptual_diff(&image_to_frame(expected), options)
+}
+
+/// Check saved output against its reference
+///
+/// `label` prefixes log lines ("Test", "Glass test", ...). Failures and
+/// updates are added to `report`.
+pub(crate) fn check(
+    label: &str,
+    name: &str,
+    paths: &GoldenPaths,
+    mode: GoldenMode,
+    options: &PerceptualOptions,
+    report: &mut GoldenReport,
+) -> Result<TestResult> {
+    if !paths.reference.exists() {
+        std::fs::copy(&paths.output, &paths.reference)
+            .context("Failed to create reference image")?;
+        tracing::info!(
+            "{} '{}' created new reference at {:?}",
+            label,
+            name,
+            paths.reference
+        );
+        return Ok(TestResult::PassedWithNewReference);
+    }
+
+    let output_img = image::open(&paths.output)
+        .context("Failed to open output image")?
+        .to_rgba8();
+    let reference_img = image::open(&paths.reference)
+        .context("Failed to open reference image")?
+        .to_rgba8();
+
+    let reference_frame = image_to_frame(&reference_img);
+    let diff = image_to_frame(&output_img).perceptual_diff(&reference_frame, options);
+
+    if diff.passed {
+        tracing::info!(
+            "{} '{}' PASSED (ssim: {:.4}, changed: {:.4}%)",
+            label,
+            name,
+            diff.ssim,
+            diff.changed_percent
+        );
+        return Ok(TestResult::Passed);
+    }
+
+    let heatmap = diff.heatmap(&reference_frame);
+    if let Some(img) = RgbaImage::from_raw(heatmap.width, heatmap.height, heatmap.data) {
+        img.save(&paths.diff).ok();
+    }
+
+    match mode {
+        GoldenMode::Update => {
+            std::fs::copy(&paths.reference, &paths.previous)
+                .context("Failed to keep previous reference")?;
+            std::fs::copy(&paths.output, &paths.reference)
+                .context("Failed to update reference image")?;
+            tracing::info!(
+                "{} '{}' updated reference (ssim: {:.4}, changed: {:.4}%)",
+                label,
+                name,
+                diff.ssim,
+                diff.changed_percent
+            );
+            report.push(ReportEntry::new(
+                name,
+                ReportStatus::Updated,
+                &paths.previous,
+                &paths.output,
+                &paths.diff,
+                &diff,
+            ));
+            Ok(TestResult::PassedWithNewReference)
+        }
+        GoldenMode::Compare => {
+            tracing::warn!(
+                "{} '{}' FAILED (ssim: {:.4} < {:.4} or changed: {:.4}% > {:.4}%, max delta-e: {:.2})",
+                label,
+                name,
+                diff.ssim,
+                options.min_ssim,
+                diff.changed_percent,
+                options.max_changed_percent,
+                diff.max_delta_e
+            );
+            report.push(ReportEntry::new(
+                name,
+                ReportStatus::Failed,
+                &paths.reference,
+                &paths.output,
+                &paths.diff,
+                &diff,
+            ));
+            Ok(TestResult::Failed {
+                difference: diff.changed_percent / 100.0,
+                diff_path: paths.diff.clone(),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    fn temp_paths(name: &str) -> GoldenPaths {
+        let dir = std::env::temp_dir().join(format!("junita_golden_{}", name));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(dir.join("out")).unwrap();
+        std::fs::create_dir_all(dir.join("refs")).unwrap();
+        GoldenPaths::new(&dir.join("refs"), &dir.join("out"), name)
+    }
+
+    fn save(path: &Path, color: [u8; 4]) {
+        RgbaImage::from_pixel(16, 16, Rgba(color))
+            .save(path)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_compare_then_update() {
+        let paths = temp_paths("update");
+        let options = PerceptualOptions::default();
+        let mut report = GoldenReport::new();
+
+        save(&paths.output, [0, 0, 255, 255]);
+        let created = check(
+            "Test",
+            "update",
+            &paths,
+            GoldenMode::Compare,
+            &options,
+            &mut report,
+        );
+        assert!(matches!(
+            created.unwrap(),
+            TestResult::PassedWithNewReference
+        ));
+
+        save(&paths.output, [255, 0, 0, 255]);
+        let failed = check(
+            "Test",
+            "update",
+            &paths,
+            GoldenMode::Compare,
+            &options,
+            &mut report,
+        );
+        assert!(matches!(failed.unwrap(), TestResult::Failed { .. }));
+        assert!(paths.diff.exists());
+        assert_eq!(report.entries()[0].status, ReportStatus::Failed);
+
+        let updated = check(
+            "Test",
+            "update",
+            &paths,
+            GoldenMode::Update,
+            &options,
+            &mut report,
+        );
+        assert!(matches!(
+            updated.unwrap(),
+            TestResult::PassedWithNewReference
+        ));
+        assert_eq!(report.entries()[1].status, ReportStatus::Updated);
+        let previous = image::open(&paths.previous).unwrap().to_rgba8();
+        assert_eq!(previous.get_pixel(0, 0), &Rgba([0, 0, 255, 255]));
+
+        let passed = check(
+            "Test",
+            "update",
+            &paths,
+            GoldenMode::Compare,
+            &options,
+            &mut report,
+        );
+        assert!(matches!(passed.unwrap(), TestResult::Passed));
+        assert_eq!(report.entries().len(), 2);
+    }
+}
@@ -3,7 +3,7 @@
 //! Provides infrastructure for running visual tests, including:
 //! - GPU context initialization
 //! - Offscreen rendering to PNG files
-//! - Reference image comparison
+//! - Perceptual reference image comparison (see [`crate::golden`])
 
 use anyhow::{Context, Result};
 use image::{ImageBuffer, Rgba, RgbaImage};
@@ -15,12 +15,16 @@ use junita_gpu::{
 use junita_layout::div::FontFamily;
 use junita_layout::prelude::*;
 use junita_layout::renderer::ElementType;
+use junita_recorder::testing::{IgnoreRegion, PerceptualDiff, PerceptualOptions};
 use junita_svg::SvgDocument;
 use junita_text::TextAnchor;
-use std::cell::RefCell;
+use std::cell::{Ref, RefCell};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+use crate::golden::{self, GoldenMode, GoldenPaths};
+use crate::report::GoldenReport;
+
 /// Result of a visual test
 #[derive(Debug)]
 pub enum TestResult {
@@ -76,6 +80,8 @@ pub struct TestContext<'a> {
     pub name: String,
     /// Output directory for reference images
     pub output_dir: PathBuf,
+    /// Regions left out of the reference comparison
+    pub ignore_regions: Vec<IgnoreRegion>,
 }
 
 impl<'a> TestContext<'a> {
@@ -88,9 +94,23 @@ impl<'a> TestContext<'a> {
             size: Size::new(width, height),
             name: name.to_string(),
             output_dir: PathBuf::from("test_output"),
+            ignore_regions: Vec::new(),
         }
     }
 
+    /// Leave a region out of the reference comparison
+    ///
+    /// Use this for content that legitimately varies between runs or
+    /// platforms, such as a blinking caret or a system-font glyph.
+    pub fn ignore_region(&mut self, rect: Rect) {
+        self.ignore_regions.push(IgnoreRegion::covering(
+            rect.x(),
+            rect.y(),
+            rect.width(),
+            rect.height(),
+        ));
+    }
+
     /// Get the paint context for drawing background (behind glass)
     pub fn ctx(&mut self) -> &mut GpuPaintContext<'a> {
         &mut self.paint_ctx
@@ -350,8 +370,12 @@ pub struct TestHarness {
     reference_dir: PathBuf,
     /// Default viewport size
     default_size: Size,
-    /// Difference threshold for visual comparison (0.0-1.0)
-    threshold: f32,
+    /// Perceptual comparison options
+    perceptual: PerceptualOptions,
+    /// Compare against or update references
+    mode: GoldenMode,
+    /// Failed and updated goldens from this run
+    report: RefCell<GoldenReport>,
     /// MSAA sample count
     sample_count: u32,
 }
@@ -421,7 +445,11 @@ impl TestHarness {
             output_dir: config.output_dir,
             reference_dir: config.reference_dir,
             default_size: config.default_size,
-            threshold: config.threshold,
+            perceptual: config
+                .perceptual
+                .with_max_changed_percent(config.threshold * 100.0),
+            mode: config.mode,
+            report: RefCell::new(GoldenReport::new()),
             sample_count: config.sample_count,
         })
     }
@@ -900,7 +928,19 @@ impl TestHarness {
         Ok(())
     }
 
+    /// Compare two images perceptually (SSIM and Delta-E)
+    pub fn compare_images_perceptual(
+        actual: &RgbaImage,
+        expected: &RgbaImage,
+        options: &PerceptualOptions,
+    ) -> PerceptualDiff {
+        golden::compare(actual, expected, options)
+    }
+
     /// Compare two images and return the difference ratio (0.0 = identical, 1.0 = completely different)
+    ///
+    /// This is a plain per-pixel comparison; the harness itself uses
+    /// [`Self::compare_images_perceptual`].
     pub fn compare_images(img1: &RgbaImage, img2: &RgbaImage) -> f32 {
         if img1.dimensions() != img2.dimensions() {
             return 1.0; // Different sizes = completely different
@@ -992,8 +1032,8 @@ impl TestHarness {
 
         let batch = ctx.take_batch();
         let text_commands = ctx.take_text_commands();
+        let ignore_regions = std::mem::take(&mut ctx.ignore_regions);
         let output_path = self.output_path(name);
-        let reference_path = self.reference_path(name);
 
         // Prepare text glyphs if there are text commands
         let mut all_glyphs = Vec::new();
@@ -1049,48 +1089,7 @@ impl TestHarness {
         }
         tracing::info!("Rendered test '{}' to {:?}", name, output_path);
 
-        // Compare with reference if it exists
-        if reference_path.exists() {
-            let output_img = image::open(&output_path)
-                .context("Failed to open output image")?
-                .to_rgba8();
-            let reference_img = image::open(&reference_path)
-                .context("Failed to open reference image")?
-                .to_rgba8();
-
-            let difference = Self::compare_images(&output_img, &reference_img);
-
-            if difference <= self.threshold {
-                tracing::info!("Test '{}' PASSED (diff: {:.4}%)", name, difference * 100.0);
-                Ok(TestResult::Passed)
-            } else {
-                // Generate diff image
-                let diff_path = self.diff_path(name);
-                if let Some(diff_img) = Self::generate_diff_image(&output_img, &reference_img) {
-                    diff_img.save(&diff_path).ok();
-                }
-                tracing::warn!(
-                    "Test '{}' FAILED (diff: {:.4}%, threshold: {:.4}%)",
-                    name,
-                    difference * 100.0,
-                    self.threshold * 100.0
-                );
-                Ok(TestResult::Failed {
-                    difference,
-                    diff_path,
-                })
-            }
-        } else {
-            // No reference exists - copy output as new reference
-            std::fs::copy(&output_path, &reference_path)
-                .context("Failed to create reference image")?;
-            tracing::info!(
-                "Test '{}' created new reference at {:?}",
-                name,
-                reference_path
-            );
-            Ok(TestResult::PassedWithNewReference)
-        }
+        self.check_reference("Test", name, ignore_regions)
     }
 
     /// Run a glass test (uses multi-pass rendering for backdrop blur)
@@ -1123,8 +1122,8 @@ impl TestHarness {
         let batch = ctx.take_batch();
         let foreground_batch = ctx.take_foreground_batch();
         let text_commands = ctx.take_text_commands();
+        let ignore_regions = std::mem::take(&mut ctx.ignore_regions);
         let output_path = self.output_path(name);
-        let reference_path = self.reference_path(name);
 
         // Prepare text glyphs if any
         let mut all_glyphs = Vec::new();
@@ -1185,50 +1184,37 @@ impl TestHarness {
         )?;
         tracing::info!("Rendered glass test '{}' to {:?}", name, output_path);
 
-        // Compare with reference if it exists
-        if reference_path.exists() {
-            let output_img = image::open(&output_path)
-                .context("Failed to open output image")?
-                .to_rgba8();
-            let reference_img = image::open(&reference_path)
-                .context("Failed to open reference image")?
-                .to_rgba8();
-
-            let difference = Self::compare_images(&output_img, &reference_img);
-
-            if difference <= self.threshold {
-                tracing::info!(
-                    "Glass test '{}' PASSED (diff: {:.4}%)",
-                    name,
-                    difference * 100.0
-                );
-                Ok(TestResult::Passed)
-            } else {
-                let diff_path = self.diff_path(name);
-                if let Some(diff_img) = Self::generate_diff_image(&output_img, &reference_img) {
-                    diff_img.save(&diff_path).ok();
-                }
-                tracing::warn!(
-                    "Glass test '{}' FAILED (diff: {:.4}%, threshold: {:.4}%)",
-                    name,
-                    difference * 100.0,
-                    self.threshold * 100.0
-                );
-                Ok(TestResult::Failed {
-                    difference,
-                    diff_path,
-                })
-            }
-        } else {
-            std::fs::copy(&output_path, &reference_path)
-                .context("Failed to create reference image")?;
-            tracing::info!(
-                "Glass test '{}' created new reference at {:?}",
-                name,
-                reference_path
-            );
-            Ok(TestResult::PassedWithNewReference)
-        }
+        self.check_reference("Glass test", name, ignore_regions)
+    }
+
+    /// Compare saved output with its reference, honoring the golden mode
+    fn check_reference(
+        &self,
+        label: &str,
+        name: &str,
+        ignore_regions: Vec<IgnoreRegion>,
+    ) -> Result<TestResult> {
+        let mut options = self.perceptual.clone();
+        options.ignore.extend(ignore_regions);
+        let paths = GoldenPaths::new(&self.reference_dir, &self.output_dir, name);
+        golden::check(
+            label,
+            name,
+            &paths,
+            self.mode,
+            &options,
+            &mut self.report.borrow_mut(),
+        )
+    }
+
+    /// Failed and updated goldens so far
+    pub fn report(&self) -> Ref<'_, GoldenReport> {
+        self.report.borrow()
+    }
+
+    /// Write the HTML report of failed and updated goldens
+    pub fn write_report(&self, path: impl AsRef<Path>) -> Result<()> {
+        self.report.borrow().write_html(path)
     }
 
     /// Render a batch with text to a PNG file
@@ -1368,8 +1354,12 @@ pub struct TestHarnessConfig {
     pub reference_dir: PathBuf,
     /// Default viewport size
     pub default_size: Size,
-    /// Difference threshold for visual comparison (0.0-1.0)
+    /// Fraction of perceptually changed pixels allowed (0.0-1.0)
     pub threshold: f32,
+    /// Delta-E, SSIM and anti-aliasing settings for comparisons
+    pub perceptual: PerceptualOptions,
+    /// Compare against or update references
+    pub mode: GoldenMode,
     /// Maximum primitives per batch
     pub max_primitives: usize,
     /// Maximum glass primitives per batch
@@ -1387,6 +1377,8 @@ impl Default for TestHarnessConfig {
             reference_dir: PathBuf::from("test_output/references"),
             default_size: Size::new(800.0, 600.0), // 2x resolution for better quality
             threshold: 0.001,                      // 0.1% difference allowed
+            perceptual: PerceptualOptions::default(),
+            mode: GoldenMode::from_env(),
             max_primitives: 10_000,
             max_glass_primitives: 1_000,
             max_glyphs: 50_000,
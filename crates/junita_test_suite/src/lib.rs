@@ -10,16 +10,27 @@
 //! # Test Categories
 //!
 //! - **Headless Tests**: Run without display, render to textures
+//! - **CPU Tests**: Render with the software reference renderer, no GPU needed
 //! - **Visual Regression**: Compare rendered output to reference images
+//!   perceptually, with ignore regions, diff heatmaps and an HTML report
+//! - **Property Tests**: Generated layouts, stylesheets and markup checked
+//!   against layout, diff and parser invariants
 //! - **Interactive Tests**: Manual testing with live windows
 //! - **Benchmarks**: Performance testing of rendering pipeline
 
+pub mod cpu;
+pub mod golden;
 pub mod harness;
+pub mod property;
+pub mod report;
 pub mod runner;
 pub mod tests;
 
 #[cfg(feature = "interactive")]
 pub mod window;
 
-pub use harness::{TestContext, TestHarness, TestResult};
+pub use cpu::CpuTestHarness;
+pub use golden::GoldenMode;
+pub use harness::{TestContext, TestHarness, TestHarnessConfig, TestResult};
+pub use report::GoldenReport;
 pub use runner::TestRunner;
@@ -6,9 +6,15 @@
 //!   junita-visual-tests              # Run all tests
 //!   junita-visual-tests --filter foo # Run tests matching "foo"
 //!   junita-visual-tests --list       # List all tests
+//!   junita-visual-tests --update     # Rewrite references that no longer match
+//!   junita-visual-tests --report out/report.html  # HTML report of failures
 
 use anyhow::Result;
-use junita_test_suite::{runner::TestRunner, tests};
+use junita_test_suite::{
+    harness::{TestHarness, TestHarnessConfig},
+    runner::TestRunner,
+    tests, GoldenMode,
+};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
 fn main() -> Result<()> {
@@ -41,12 +47,27 @@ fn main() -> Result<()> {
         .and_then(|i| args.get(i + 1))
         .cloned();
 
+    // Check for --report flag
+    let report_path = args
+        .iter()
+        .position(|a| a == "--report")
+        .and_then(|i| args.get(i + 1))
+        .cloned();
+
+    let mut config = TestHarnessConfig::default();
+    if args.iter().any(|a| a == "--update") {
+        config.mode = GoldenMode::Update;
+    }
+
     println!("╔══════════════════════════════════════════╗");
     println!("║      JUNITA VISUAL REGRESSION TESTS       ║");
     println!("╚══════════════════════════════════════════╝\n");
 
     // Create test runner
-    let mut runner = TestRunner::new()?;
+    if config.mode == GoldenMode::Update {
+        println!("Updating references that no longer match\n");
+    }
+    let mut runner = TestRunner::with_harness(TestHarness::with_config(config)?);
 
     // Add all test suites
     for suite in tests::all_suites() {
@@ -65,6 +86,11 @@ fn main() -> Result<()> {
     // Print summary
     result.print_summary();
 
+    if let Some(ref path) = report_path {
+        runner.harness().write_report(path)?;
+        println!("\nReport written to {}", path);
+    }
+
     // Exit with error code if any tests failed
     if result.all_passed() {
         println!("\nAll tests passed!");
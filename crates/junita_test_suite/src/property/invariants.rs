@@ -0,0 +1,262 @@
+//! Invariants checked against generated input
+//!
+//! Checks return `Err` with a description of the first violation, so they
+//! can be used from `proptest!` bodies with `prop_assert!` or called on a
+//! hand-written case while debugging a shrunk failure.
+
+use junita_core::{Brush, Color, CornerRadius};
+use junita_layout::css_parser::Stylesheet;
+use junita_layout::prelude::*;
+use junita_layout::{diff, reconcile, DivHash, LayoutNodeId, LayoutTree, RichDocument, RichText};
+use taffy::Overflow;
+
+use super::strategies::{MarkupSpec, StylesheetSpec};
+
+/// Slack allowed when comparing layout edges: Taffy rounds positions and
+/// sizes to whole pixels separately from content sizes
+pub const EPSILON: f32 = 1.0;
+
+/// Check every laid-out node in `tree`
+///
+/// - positions, sizes and content sizes are finite and bounds non-negative
+/// - non-empty children of clipping and scrolling containers end inside
+///   the container's content size, so scroll ranges reach all of them
+pub fn check_layout(tree: &RenderTree) -> Result<(), String> {
+    let Some(root) = tree.root() else {
+        return Err("tree has no root".to_string());
+    };
+    check_node(tree.layout(), root, "root")
+}
+
+fn check_node(layout: &LayoutTree, node: LayoutNodeId, path: &str) -> Result<(), String> {
+    let Some(l) = layout.get_layout(node) else {
+        return Err(format!("{}: no layout", path));
+    };
+    let values = [
+        l.location.x,
+        l.location.y,
+        l.size.width,
+        l.size.height,
+        l.content_size.width,
+        l.content_size.height,
+    ];
+    if values.iter().any(|v| !v.is_finite()) {
+        return Err(format!("{}: non-finite layout {:?}", path, l));
+    }
+    if let Some(bounds) = layout.get_bounds(node, (0.0, 0.0)) {
+        if bounds.width < 0.0 || bounds.height < 0.0 {
+            return Err(format!("{}: negative bounds {:?}", path, bounds));
+        }
+    }
+
+    let style = layout.get_style(node);
+    let contains_children = style
+        .as_ref()
+        .is_some_and(|s| s.overflow.x != Overflow::Visible || s.overflow.y != Overflow::Visible);
+    let content = l.content_size;
+
+    for (index, child) in layout.children(node).into_iter().enumerate() {
+        let child_path = format!("{}/{}", path, index);
+        if contains_children {
+            // Taffy leaves empty children out of the content size: there is
+            // nothing in them to scroll to
+            if let Some(c) = layout
+                .get_layout(child)
+                .filter(|c| c.size.width > 0.0 && c.size.height > 0.0)
+            {
+                let right = c.location.x + c.size.width;
+                let bottom = c.location.y + c.size.height;
+                if right > content.width + EPSILON || bottom > content.height + EPSILON {
+                    return Err(format!(
+                        "{}: ends at ({}, {}) outside parent content size {:?}",
+                        child_path, right, bottom, content
+                    ));
+                }
+            }
+        }
+        check_node(layout, child, &child_path)?;
+    }
+    Ok(())
+}
+
+/// Check that reconciling `old` towards `new` leaves nothing to diff
+///
+/// Only the node's own properties are compared: children are rebuilt by
+/// the caller rather than reconciled in place.
+pub fn check_reconcile(mut old: Div, new: &Div) -> Result<(), String> {
+    let result = diff(&old, new);
+    reconcile(&result, &mut old, new, None);
+
+    let after = diff(&old, new);
+    if after.changes.layout || after.changes.visual {
+        return Err(format!(
+            "changes remain after reconcile: {:?} (first diff {:?})",
+            after.changes, result.changes
+        ));
+    }
+    if DivHash::compute(&old) != DivHash::compute(new) {
+        return Err(format!(
+            "hash differs after reconcile (first diff {:?})",
+            result.changes
+        ));
+    }
+    Ok(())
+}
+
+/// Print `spec` as CSS, parse it back and compare every rule
+pub fn check_stylesheet_roundtrip(spec: &StylesheetSpec) -> Result<(), String> {
+    let css = spec.css();
+    let result = Stylesheet::parse_with_errors(&css);
+    if !result.errors.is_empty() {
+        return Err(format!("{:?} parsing\n{}", result.errors, css));
+    }
+
+    for rule in &spec.rules {
+        let key = rule.key();
+        let has_props = rule.background.is_some()
+            || rule.radius.is_some()
+            || rule.opacity.is_some()
+            || rule.layer.is_some();
+        let Some(style) = result.stylesheet.get(&key) else {
+            if has_props {
+                return Err(format!("#{} missing from\n{}", key, css));
+            }
+            continue;
+        };
+
+        let background = match &style.background {
+            Some(Brush::Solid(color)) => Some(*color),
+            None => None,
+            Some(other) => return Err(format!("#{}: unexpected brush {:?}", key, other)),
+        };
+        let same_background = match (rule.background, background) {
+            (Some(a), Some(b)) => color_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        if !same_background {
+            return Err(format!(
+                "#{}: background {:?}, expected {:?}",
+                key, background, rule.background
+            ));
+        }
+
+        let radius = rule.radius.map(|r| CornerRadius::uniform(r as f32));
+        if style.corner_radius != radius {
+            return Err(format!(
+                "#{}: border-radius {:?}, expected {:?}",
+                key, style.corner_radius, radius
+            ));
+        }
+
+        let opacity = rule.opacity.map(|o| o as f32 / 100.0);
+        let same_opacity = match (opacity, style.opacity) {
+            (Some(a), Some(b)) => (a - b).abs() < 1e-6,
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        if !same_opacity {
+            return Err(format!(
+                "#{}: opacity {:?}, expected {:?}",
+                key, style.opacity, opacity
+            ));
+        }
+
+        if style.render_layer != rule.layer {
+            return Err(format!(
+                "#{}: render-layer {:?}, expected {:?}",
+                key, style.render_layer, rule.layer
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Print `spec` as markup, parse it with [`RichText::new`] and check the
+/// text and formatting of every run
+///
+/// Each run must be covered by spans carrying each of its flags and its
+/// color, and no span may carry a flag or color into a run without it.
+pub fn check_markup_roundtrip(spec: &MarkupSpec) -> Result<(), String> {
+    let markup = spec.markup();
+    let rich = RichText::new(markup.as_str());
+    let expected = spec.content();
+    if rich.content() != expected {
+        return Err(format!(
+            "content {:?}, expected {:?} from {:?}",
+            rich.content(),
+            expected,
+            markup
+        ));
+    }
+
+    let mut start = 0;
+    for segment in &spec.segments {
+        let range = start..start + segment.text.len();
+        start = range.end;
+        let style = &segment.style;
+
+        for span in rich.spans() {
+            if span.end <= range.start || span.start >= range.end {
+                continue;
+            }
+            let leaked = (span.bold && !style.bold)
+                || (span.italic && !style.italic)
+                || (span.underline && !style.underline)
+                || (span.strikethrough && !style.strikethrough)
+                || (span.color != Color::TRANSPARENT
+                    && !style.color.is_some_and(|c| color_eq(c, span.color)));
+            if leaked {
+                return Err(format!(
+                    "span {:?} leaks into {:?} from {:?}",
+                    span, segment, markup
+                ));
+            }
+        }
+
+        let covering: Vec<_> = rich
+            .spans()
+            .iter()
+            .filter(|s| s.start <= range.start && s.end >= range.end)
+            .collect();
+        let missing = (style.bold && !covering.iter().any(|s| s.bold))
+            || (style.italic && !covering.iter().any(|s| s.italic))
+            || (style.underline && !covering.iter().any(|s| s.underline))
+            || (style.strikethrough && !covering.iter().any(|s| s.strikethrough))
+            || style
+                .color
+                .is_some_and(|c| !covering.iter().any(|s| color_eq(c, s.color)));
+        if missing {
+            return Err(format!(
+                "{:?} lost formatting in {:?} from {:?}",
+                segment,
+                rich.spans(),
+                markup
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Check that `doc` survives printing to HTML and Markdown and parsing back
+pub fn check_document_roundtrip(doc: &RichDocument) -> Result<(), String> {
+    let html = doc.to_html();
+    let from_html = RichDocument::from_html(&html);
+    if &from_html != doc {
+        return Err(format!("HTML {:?} parsed to {:?}", html, from_html));
+    }
+
+    let markdown = doc.to_markdown();
+    let from_markdown = RichDocument::from_markdown(&markdown);
+    if &from_markdown != doc {
+        return Err(format!(
+            "Markdown {:?} parsed to {:?}",
+            markdown, from_markdown
+        ));
+    }
+    Ok(())
+}
+
+/// Colors equal to within 8-bit channel precision
+fn color_eq(a: Color, b: Color) -> bool {
+    let close = |x: f32, y: f32| (x - y).abs() < 0.5 / 255.0;
+    close(a.r, b.r) && close(a.g, b.g) && close(a.b, b.b) && close(a.a, b.a)
+}
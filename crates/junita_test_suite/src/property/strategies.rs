@@ -0,0 +1,399 @@
+//! Proptest strategies for generated UI input
+//!
+//! Each strategy produces a plain spec value rather than the built element,
+//! so failures shrink to readable cases and the spec can be printed as the
+//! text a parser should read back.
+
+use std::fmt::Write;
+
+use junita_core::Color;
+use junita_layout::css_parser::ElementState;
+use junita_layout::prelude::*;
+use junita_layout::{Block, BlockKind, Inline, Mark, RichDocument};
+use proptest::prelude::*;
+
+/// Main axis of a generated container
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    Row,
+    Column,
+}
+
+/// Overflow behaviour of a generated container
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowSpec {
+    Visible,
+    Clip,
+    Scroll,
+}
+
+/// A generated `Div` and its subtree
+#[derive(Clone, Debug)]
+pub struct DivSpec {
+    pub width: Option<f32>,
+    pub height: Option<f32>,
+    pub padding: f32,
+    pub margin: f32,
+    pub gap: f32,
+    pub axis: Axis,
+    pub wrap: bool,
+    pub grow: bool,
+    pub shrink: bool,
+    pub overflow: OverflowSpec,
+    pub background: Option<Color>,
+    pub radius: f32,
+    pub border: Option<(f32, Color)>,
+    pub opacity: f32,
+    pub layer: RenderLayer,
+    pub children: Vec<DivSpec>,
+}
+
+impl DivSpec {
+    /// Build the element this spec describes
+    pub fn build(&self) -> Div {
+        let mut el = div()
+            .p_px(self.padding)
+            .m_px(self.margin)
+            .gap_px(self.gap)
+            .rounded(self.radius)
+            .opacity(self.opacity)
+            .layer(self.layer);
+        el = match self.axis {
+            Axis::Row => el.flex_row(),
+            Axis::Column => el.flex_col(),
+        };
+        el = match self.overflow {
+            OverflowSpec::Visible => el,
+            OverflowSpec::Clip => el.overflow_clip(),
+            OverflowSpec::Scroll => el.overflow_scroll(),
+        };
+        if let Some(width) = self.width {
+            el = el.w(width);
+        }
+        if let Some(height) = self.height {
+            el = el.h(height);
+        }
+        if self.wrap {
+            el = el.flex_wrap();
+        }
+        if self.grow {
+            el = el.flex_grow();
+        }
+        if !self.shrink {
+            el = el.flex_shrink_0();
+        }
+        if let Some(color) = self.background {
+            el = el.bg(color);
+        }
+        if let Some((width, color)) = self.border {
+            el = el.border(width, color);
+        }
+        for child in &self.children {
+            el = el.child(child.build());
+        }
+        el
+    }
+}
+
+/// An opaque color with 8-bit channels, as CSS and markup can spell it
+pub fn color() -> impl Strategy<Value = Color> {
+    any::<[u8; 3]>()
+        .prop_map(|[r, g, b]| Color::rgb(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0))
+}
+
+/// One of the three render layers
+pub fn render_layer() -> impl Strategy<Value = RenderLayer> {
+    prop_oneof![
+        Just(RenderLayer::Background),
+        Just(RenderLayer::Glass),
+        Just(RenderLayer::Foreground),
+    ]
+}
+
+fn div_props() -> impl Strategy<Value = DivSpec> {
+    let size = prop::option::weighted(0.6, 0.0f32..400.0);
+    let box_model = (size.clone(), size, 0.0f32..24.0, 0.0f32..24.0, 0.0f32..16.0);
+    let flex = (
+        prop_oneof![Just(Axis::Row), Just(Axis::Column)],
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        prop_oneof![
+            2 => Just(OverflowSpec::Visible),
+            1 => Just(OverflowSpec::Clip),
+            1 => Just(OverflowSpec::Scroll),
+        ],
+    );
+    let visual = (
+        prop::option::of(color()),
+        0.0f32..32.0,
+        prop::option::of((0.0f32..4.0, color())),
+        0.0f32..=1.0,
+        render_layer(),
+    );
+    (box_model, flex, visual).prop_map(
+        |(
+            (width, height, padding, margin, gap),
+            (axis, wrap, grow, shrink, overflow),
+            (background, radius, border, opacity, layer),
+        )| DivSpec {
+            width,
+            height,
+            padding,
+            margin,
+            gap,
+            axis,
+            wrap,
+            grow,
+            shrink,
+            overflow,
+            background,
+            radius,
+            border,
+            opacity,
+            layer,
+            children: Vec::new(),
+        },
+    )
+}
+
+/// A `Div` tree up to `depth` levels deep
+///
+/// Margins, paddings and gaps are non-negative and nothing is absolutely
+/// positioned, so every child is laid out inside its parent's content box
+/// or, when it overflows, inside the parent's content size.
+pub fn div_tree(depth: u32) -> impl Strategy<Value = DivSpec> {
+    div_props().prop_recursive(depth, 48, 4, |inner| {
+        (div_props(), prop::collection::vec(inner, 0..4)).prop_map(|(mut spec, children)| {
+            spec.children = children;
+            spec
+        })
+    })
+}
+
+/// A rule of a generated stylesheet
+#[derive(Clone, Debug)]
+pub struct RuleSpec {
+    pub id: String,
+    pub state: Option<ElementState>,
+    pub background: Option<Color>,
+    pub radius: Option<u16>,
+    /// Opacity in hundredths, so it prints exactly
+    pub opacity: Option<u8>,
+    pub layer: Option<RenderLayer>,
+}
+
+impl RuleSpec {
+    /// The selector key the stylesheet stores this rule under
+    pub fn key(&self) -> String {
+        match self.state {
+            Some(state) => format!("{}:{}", self.id, state),
+            None => self.id.clone(),
+        }
+    }
+}
+
+/// A generated stylesheet of `#id` and `#id:state` rules
+#[derive(Clone, Debug)]
+pub struct StylesheetSpec {
+    pub rules: Vec<RuleSpec>,
+}
+
+impl StylesheetSpec {
+    /// Print the stylesheet as CSS
+    pub fn css(&self) -> String {
+        let mut css = String::new();
+        for rule in &self.rules {
+            let _ = writeln!(css, "#{} {{", rule.key());
+            if let Some(color) = rule.background {
+                let _ = writeln!(css, "    background: {};", hex(color));
+            }
+            if let Some(radius) = rule.radius {
+                let _ = writeln!(css, "    border-radius: {}px;", radius);
+            }
+            if let Some(opacity) = rule.opacity {
+                let _ = writeln!(css, "    opacity: {};", opacity as f32 / 100.0);
+            }
+            if let Some(layer) = rule.layer {
+                let layer = match layer {
+                    RenderLayer::Background => "background",
+                    RenderLayer::Glass => "glass",
+                    RenderLayer::Foreground => "foreground",
+                };
+                let _ = writeln!(css, "    render-layer: {};", layer);
+            }
+            css.push_str("}\n");
+        }
+        css
+    }
+}
+
+fn element_state() -> impl Strategy<Value = ElementState> {
+    prop_oneof![
+        Just(ElementState::Hover),
+        Just(ElementState::Active),
+        Just(ElementState::Focus),
+        Just(ElementState::Disabled),
+    ]
+}
+
+/// A stylesheet with up to `max_rules` rules, each for a distinct element
+pub fn stylesheet(max_rules: usize) -> impl Strategy<Value = StylesheetSpec> {
+    let rule = (
+        prop::option::of(element_state()),
+        prop::option::of(color()),
+        prop::option::of(0u16..64),
+        prop::option::of(0u8..=100),
+        prop::option::of(render_layer()),
+    );
+    prop::collection::vec(rule, 0..=max_rules).prop_map(|rules| StylesheetSpec {
+        rules: rules
+            .into_iter()
+            .enumerate()
+            .map(
+                |(i, (state, background, radius, opacity, layer))| RuleSpec {
+                    id: format!("el{}", i),
+                    state,
+                    background,
+                    radius,
+                    opacity,
+                    layer,
+                },
+            )
+            .collect(),
+    })
+}
+
+/// Inline formatting of a generated markup run
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MarkupStyle {
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strikethrough: bool,
+    pub color: Option<Color>,
+}
+
+/// A run of text with one style
+#[derive(Clone, Debug)]
+pub struct MarkupSegment {
+    pub text: String,
+    pub style: MarkupStyle,
+}
+
+/// Generated rich-text markup: runs printed one after another
+#[derive(Clone, Debug)]
+pub struct MarkupSpec {
+    pub segments: Vec<MarkupSegment>,
+}
+
+impl MarkupSpec {
+    /// Print the runs as `RichText` markup, escaping `<` and `&`
+    pub fn markup(&self) -> String {
+        let mut out = String::new();
+        for segment in &self.segments {
+            let style = &segment.style;
+            let mut close = Vec::new();
+            if let Some(color) = style.color {
+                let _ = write!(out, "<span color=\"{}\">", hex(color));
+                close.push("</span>");
+            }
+            for (on, open, end) in [
+                (style.bold, "<b>", "</b>"),
+                (style.italic, "<i>", "</i>"),
+                (style.underline, "<u>", "</u>"),
+                (style.strikethrough, "<s>", "</s>"),
+            ] {
+                if on {
+                    out.push_str(open);
+                    close.push(end);
+                }
+            }
+            for ch in segment.text.chars() {
+                match ch {
+                    '<' => out.push_str("&lt;"),
+                    '&' => out.push_str("&amp;"),
+                    _ => out.push(ch),
+                }
+            }
+            for end in close.iter().rev() {
+                out.push_str(end);
+            }
+        }
+        out
+    }
+
+    /// The plain text the markup should parse to
+    pub fn content(&self) -> String {
+        self.segments.iter().map(|s| s.text.as_str()).collect()
+    }
+}
+
+fn markup_style() -> impl Strategy<Value = MarkupStyle> {
+    (
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        prop::option::of(color()),
+    )
+        .prop_map(
+            |(bold, italic, underline, strikethrough, color)| MarkupStyle {
+                bold,
+                italic,
+                underline,
+                strikethrough,
+                color,
+            },
+        )
+}
+
+/// Markup of up to `max_segments` styled runs, including characters that
+/// must be escaped
+pub fn markup(max_segments: usize) -> impl Strategy<Value = MarkupSpec> {
+    let segment = ("[a-zA-Z0-9 .,!?<>&\"'éß中🙂]{1,12}", markup_style())
+        .prop_map(|(text, style)| MarkupSegment { text, style });
+    prop::collection::vec(segment, 0..=max_segments).prop_map(|segments| MarkupSpec { segments })
+}
+
+/// A document of paragraphs whose runs carry bold, italic, underline and
+/// strikethrough marks
+///
+/// Runs follow each other with no space between them, so marks start and
+/// end mid-word. Runs don't start or end with a space since HTML collapses
+/// repeated whitespace.
+pub fn rich_document(max_blocks: usize) -> impl Strategy<Value = RichDocument> {
+    let marks = (any::<bool>(), any::<bool>(), any::<bool>(), any::<bool>()).prop_map(
+        |(bold, italic, underline, strikethrough)| {
+            [
+                (bold, Mark::Bold),
+                (italic, Mark::Italic),
+                (underline, Mark::Underline),
+                (strikethrough, Mark::Strikethrough),
+            ]
+            .into_iter()
+            .filter_map(|(on, mark)| on.then_some(mark))
+            .collect::<Vec<_>>()
+        },
+    );
+    let run = ("[a-zA-Z0-9]{1,8}( [a-zA-Z0-9]{1,8}){0,2}", marks);
+    let block = prop::collection::vec(run, 1..5).prop_map(|runs| {
+        let content = runs
+            .into_iter()
+            .map(|(text, marks)| Inline::marked(text, marks))
+            .collect();
+        Block::new(BlockKind::Paragraph, content)
+    });
+    prop::collection::vec(block, 1..=max_blocks).prop_map(RichDocument::from_blocks)
+}
+
+/// `#rrggbb` for a color built by [`color`]
+fn hex(color: Color) -> String {
+    let channel = |v: f32| (v * 255.0).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(color.r),
+        channel(color.g),
+        channel(color.b)
+    )
+}
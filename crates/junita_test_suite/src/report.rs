@@ -0,0 +1,257 @@
+//! Static HTML report of golden failures
+//!
+//! Lists every failed or updated golden with its metrics and two views:
+//! expected, actual and heatmap side by side, and an onion skin that fades
+//! the actual image over the expected one with a slider. The page has no
+//! external assets and links images relative to where it is written.
+
+use anyhow::{Context, Result};
+use junita_recorder::testing::PerceptualDiff;
+use std::fmt::Write as _;
+use std::path::{Path, PathBuf};
+
+/// Why a golden ended up in the report
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportStatus {
+    /// Output no longer matches the reference
+    Failed,
+    /// The reference was rewritten in update mode
+    Updated,
+}
+
+impl ReportStatus {
+    fn label(self) -> &'static str {
+        match self {
+            ReportStatus::Failed => "failed",
+            ReportStatus::Updated => "updated",
+        }
+    }
+}
+
+/// One golden in the report
+#[derive(Clone, Debug)]
+pub struct ReportEntry {
+    /// Test name
+    pub name: String,
+    /// Failed or updated
+    pub status: ReportStatus,
+    /// Reference image (the replaced one for updates)
+    pub expected: PathBuf,
+    /// Rendered output
+    pub actual: PathBuf,
+    /// Diff heatmap
+    pub heatmap: PathBuf,
+    /// Mean SSIM
+    pub ssim: f32,
+    /// Largest Delta-E
+    pub max_delta_e: f32,
+    /// Percentage of changed pixels
+    pub changed_percent: f32,
+    /// Whether the images had different sizes
+    pub size_mismatch: bool,
+}
+
+impl ReportEntry {
+    /// Create an entry from a comparison
+    pub fn new(
+        name: &str,
+        status: ReportStatus,
+        expected: &Path,
+        actual: &Path,
+        heatmap: &Path,
+        diff: &PerceptualDiff,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            expected: expected.to_path_buf(),
+            actual: actual.to_path_buf(),
+            heatmap: heatmap.to_path_buf(),
+            ssim: diff.ssim,
+            max_delta_e: diff.max_delta_e,
+            changed_percent: diff.changed_percent,
+            size_mismatch: diff.size_mismatch,
+        }
+    }
+}
+
+/// Goldens collected during a run
+#[derive(Clone, Debug, Default)]
+pub struct GoldenReport {
+    entries: Vec<ReportEntry>,
+}
+
+impl GoldenReport {
+    /// Create an empty report
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an entry
+    pub fn push(&mut self, entry: ReportEntry) {
+        self.entries.push(entry);
+    }
+
+    /// All entries in the order they were added
+    pub fn entries(&self) -> &[ReportEntry] {
+        &self.entries
+    }
+
+    /// Check if nothing failed or was updated
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of failed goldens
+    pub fn failed(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|e| e.status == ReportStatus::Failed)
+            .count()
+    }
+
+    /// Render the report as a standalone HTML page
+    ///
+    /// Image links are made relative to `base_dir`, the directory the page
+    /// will be written to.
+    pub fn render_html(&self, base_dir: &Path) -> String {
+        let mut html = String::new();
+        html.push_str(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Junita visual test report</title>\n<style>\n",
+        );
+        html.push_str(STYLE);
+        html.push_str("</style>\n</head>\n<body>\n<h1>Junita visual test report</h1>\n");
+        let _ = writeln!(
+            html,
+            "<p class=\"summary\">{} failed, {} updated</p>",
+            self.failed(),
+            self.entries.len() - self.failed()
+        );
+
+        for entry in &self.entries {
+            let expected = relative_src(&entry.expected, base_dir);
+            let actual = relative_src(&entry.actual, base_dir);
+            let heatmap = relative_src(&entry.heatmap, base_dir);
+            let expected_label = match entry.status {
+                ReportStatus::Failed => "Expected",
+                ReportStatus::Updated => "Previous",
+            };
+
+            let _ = writeln!(html, "<section class=\"{}\">", entry.status.label());
+            let _ = writeln!(
+                html,
+                "<h2>{} <span class=\"badge\">{}</span></h2>",
+                escape(&entry.name),
+                entry.status.label()
+            );
+            if entry.size_mismatch {
+                html.push_str("<p class=\"metrics\">Image sizes differ</p>\n");
+            } else {
+                let _ = writeln!(
+                    html,
+                    "<p class=\"metrics\">SSIM {:.4} &middot; changed {:.3}% &middot; max &Delta;E {:.2}</p>",
+                    entry.ssim, entry.changed_percent, entry.max_delta_e
+                );
+            }
+
+            html.push_str("<div class=\"side-by-side\">\n");
+            for (label, src) in [
+                (expected_label, &expected),
+                ("Actual", &actual),
+                ("Heatmap", &heatmap),
+            ] {
+                let _ = writeln!(
+                    html,
+                    "<figure><img src=\"{}\" alt=\"{}\"><figcaption>{}</figcaption></figure>",
+                    src, label, label
+                );
+            }
+            html.push_str("</div>\n");
+
+            let _ = writeln!(
+                html,
+                "<div class=\"onion\"><div class=\"stack\"><img src=\"{}\" alt=\"{}\">\
+                 <img class=\"top\" src=\"{}\" alt=\"Actual\"></div>\
+                 <label>{} <input type=\"range\" min=\"0\" max=\"100\" value=\"50\" \
+                 oninput=\"this.closest('.onion').querySelector('.top').style.opacity = this.value / 100\"> Actual</label></div>",
+                expected, expected_label, actual, expected_label
+            );
+            html.push_str("</section>\n");
+        }
+
+        html.push_str("</body>\n</html>\n");
+        html
+    }
+
+    /// Write the HTML report to `path`
+    pub fn write_html(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let base_dir = path.parent().unwrap_or(Path::new("."));
+        if !base_dir.as_os_str().is_empty() {
+            std::fs::create_dir_all(base_dir).context("Failed to create report directory")?;
+        }
+        std::fs::write(path, self.render_html(base_dir)).context("Failed to write report")
+    }
+}
+
+const STYLE: &str = "body { font-family: sans-serif; margin: 2em; background: #f4f4f5; color: #18181b; }
+section { background: white; border-radius: 8px; padding: 1em 1.5em; margin-bottom: 2em; }
+section.failed { border-left: 4px solid #dc2626; }
+section.updated { border-left: 4px solid #2563eb; }
+.badge { font-size: 0.6em; text-transform: uppercase; padding: 0.2em 0.6em; border-radius: 4px; background: #e4e4e7; vertical-align: middle; }
+.metrics { color: #52525b; }
+.side-by-side { display: flex; gap: 1em; flex-wrap: wrap; }
+figure { margin: 0; }
+figcaption { text-align: center; color: #52525b; }
+img { image-rendering: pixelated; border: 1px solid #d4d4d8; max-width: 100%; }
+.onion { margin-top: 1em; }
+.stack { position: relative; display: inline-block; }
+.stack .top { position: absolute; left: 0; top: 0; opacity: 0.5; }
+label { display: block; margin-top: 0.5em; }
+";
+
+/// Image source for `path` relative to `base_dir`, falling back to the full path
+fn relative_src(path: &Path, base_dir: &Path) -> String {
+    let relative = if base_dir.as_os_str().is_empty() {
+        path
+    } else {
+        path.strip_prefix(base_dir).unwrap_or(path)
+    };
+    escape(&relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use junita_recorder::testing::{CapturedFrame, PerceptualOptions};
+
+    #[test]
+    fn test_render_html() {
+        let frame = CapturedFrame::new(vec![0; 16], 2, 2);
+        let diff = frame.perceptual_diff(&frame, &PerceptualOptions::default());
+        let mut report = GoldenReport::new();
+        report.push(ReportEntry::new(
+            "shapes::<rect>",
+            ReportStatus::Failed,
+            Path::new("/out/refs/rect.png"),
+            Path::new("/out/rect.png"),
+            Path::new("/out/rect_diff.png"),
+            &diff,
+        ));
+
+        let html = report.render_html(Path::new("/out"));
+        assert!(html.contains("1 failed, 0 updated"));
+        assert!(html.contains("shapes::&lt;rect&gt;"));
+        assert!(html.contains("src=\"refs/rect.png\""));
+        assert!(html.contains("src=\"rect_diff.png\""));
+        assert!(html.contains("type=\"range\""));
+    }
+}
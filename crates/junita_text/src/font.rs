@@ -304,6 +304,68 @@ impl FontFace {
         self.as_ttf_face()
             .and_then(|face| face.glyph_hor_advance(ttf_parser::GlyphId(glyph_id)))
     }
+
+    /// Get the vector outline of a glyph, scaled to `font_size` pixels
+    ///
+    /// The path is in y-down pixel coordinates with the origin on the
+    /// baseline at the glyph's pen position. Returns `None` for glyphs
+    /// without an outline (spaces, bitmap-only emoji).
+    pub fn glyph_outline(&self, glyph_id: u16, font_size: f32) -> Option<junita_core::Path> {
+        let face = self.as_ttf_face()?;
+        let scale = font_size / self.metrics.units_per_em.max(1) as f32;
+        let mut builder = OutlinePathBuilder {
+            commands: Vec::new(),
+            scale,
+        };
+        face.outline_glyph(ttf_parser::GlyphId(glyph_id), &mut builder)?;
+        Some(junita_core::Path::from_commands(builder.commands))
+    }
+}
+
+/// Collects a ttf-parser outline as junita path commands (y flipped)
+struct OutlinePathBuilder {
+    commands: Vec<junita_core::PathCommand>,
+    scale: f32,
+}
+
+impl OutlinePathBuilder {
+    fn point(&self, x: f32, y: f32) -> junita_core::Point {
+        junita_core::Point::new(x * self.scale, -y * self.scale)
+    }
+}
+
+impl ttf_parser::OutlineBuilder for OutlinePathBuilder {
+    fn move_to(&mut self, x: f32, y: f32) {
+        let p = self.point(x, y);
+        self.commands.push(junita_core::PathCommand::MoveTo(p));
+    }
+
+    fn line_to(&mut self, x: f32, y: f32) {
+        let p = self.point(x, y);
+        self.commands.push(junita_core::PathCommand::LineTo(p));
+    }
+
+    fn quad_to(&mut self, x1: f32, y1: f32, x: f32, y: f32) {
+        let control = self.point(x1, y1);
+        let end = self.point(x, y);
+        self.commands
+            .push(junita_core::PathCommand::QuadTo { control, end });
+    }
+
+    fn curve_to(&mut self, x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32) {
+        let control1 = self.point(x1, y1);
+        let control2 = self.point(x2, y2);
+        let end = self.point(x, y);
+        self.commands.push(junita_core::PathCommand::CubicTo {
+            control1,
+            control2,
+            end,
+        });
+    }
+
+    fn close(&mut self) {
+        self.commands.push(junita_core::PathCommand::Close);
+    }
 }
 
 impl std::fmt::Debug for FontFace {
@@ -4,6 +4,7 @@
 //! kerning, ligatures, and OpenType feature support.
 
 use crate::font::FontFace;
+use junita_core::profiler;
 use rustybuzz::{Face, UnicodeBuffer};
 
 /// A shaped glyph with position information
@@ -64,56 +65,7 @@ impl TextShaper {
 
     /// Shape a text string using the given font
     pub fn shape(&self, text: &str, font_face: &FontFace, font_size: f32) -> ShapedText {
-        // Create rustybuzz Face from font data with correct face index
-        let face = match Face::from_slice(font_face.data(), font_face.face_index()) {
-            Some(f) => f,
-            None => {
-                // Fallback: return basic glyph sequence without shaping
-                return self.fallback_shape(text, font_face, font_size);
-            }
-        };
-
-        // Create and fill the Unicode buffer
-        let mut buffer = UnicodeBuffer::new();
-        buffer.push_str(text);
-
-        // Shape the buffer
-        let output = rustybuzz::shape(&face, &[], buffer);
-
-        // Extract glyph information
-        let glyph_infos = output.glyph_infos();
-        let glyph_positions = output.glyph_positions();
-
-        let mut glyphs = Vec::with_capacity(glyph_infos.len());
-        let mut total_advance = 0i32;
-
-        for (info, pos) in glyph_infos.iter().zip(glyph_positions.iter()) {
-            // Find the original character for this cluster
-            let codepoint = text
-                .char_indices()
-                .find(|(i, _)| *i as u32 == info.cluster)
-                .map(|(_, c)| c)
-                .unwrap_or('\u{FFFD}');
-
-            glyphs.push(ShapedGlyph {
-                glyph_id: info.glyph_id as u16,
-                codepoint,
-                x_offset: pos.x_offset,
-                y_offset: pos.y_offset,
-                x_advance: pos.x_advance,
-                y_advance: pos.y_advance,
-                cluster: info.cluster,
-            });
-
-            total_advance += pos.x_advance;
-        }
-
-        ShapedText {
-            glyphs,
-            total_advance,
-            font_size,
-            units_per_em: font_face.metrics().units_per_em,
-        }
+        self.shape_with_features(text, font_face, font_size, &[])
     }
 
     /// Fallback shaping when rustybuzz fails
@@ -154,6 +106,23 @@ impl TextShaper {
         font_size: f32,
         features: &[rustybuzz::Feature],
     ) -> ShapedText {
+        let _scope = profiler::scope("text_shape");
+        let shaped = self.shape_glyphs(text, font_face, font_size, features);
+        profiler::count(
+            profiler::counters::GLYPHS_SHAPED,
+            shaped.glyphs.len() as u64,
+        );
+        shaped
+    }
+
+    fn shape_glyphs(
+        &self,
+        text: &str,
+        font_face: &FontFace,
+        font_size: f32,
+        features: &[rustybuzz::Feature],
+    ) -> ShapedText {
+        // Create rustybuzz Face from font data with correct face index
         let face = match Face::from_slice(font_face.data(), font_face.face_index()) {
             Some(f) => f,
             None => return self.fallback_shape(text, font_face, font_size),
@@ -13,6 +13,32 @@ pub enum AnimationToken {
     DurationSlowest,
 }
 
+impl AnimationToken {
+    /// Every token, in declaration order
+    pub const ALL: &'static [AnimationToken] = &[
+        AnimationToken::DurationFastest,
+        AnimationToken::DurationFaster,
+        AnimationToken::DurationFast,
+        AnimationToken::DurationNormal,
+        AnimationToken::DurationSlow,
+        AnimationToken::DurationSlower,
+        AnimationToken::DurationSlowest,
+    ];
+
+    /// Name of the token's field in [`AnimationTokens`], e.g. `duration_faster`
+    pub const fn name(self) -> &'static str {
+        match self {
+            AnimationToken::DurationFastest => "duration_fastest",
+            AnimationToken::DurationFaster => "duration_faster",
+            AnimationToken::DurationFast => "duration_fast",
+            AnimationToken::DurationNormal => "duration_normal",
+            AnimationToken::DurationSlow => "duration_slow",
+            AnimationToken::DurationSlower => "duration_slower",
+            AnimationToken::DurationSlowest => "duration_slowest",
+        }
+    }
+}
+
 /// Easing function type
 #[derive(Clone, Copy, Debug, PartialEq, Default)]
 pub enum Easing {
@@ -61,6 +61,92 @@ pub enum ColorToken {
     TooltipText,
 }
 
+impl ColorToken {
+    /// Every token, in declaration order
+    pub const ALL: &'static [ColorToken] = &[
+        ColorToken::Primary,
+        ColorToken::PrimaryHover,
+        ColorToken::PrimaryActive,
+        ColorToken::Secondary,
+        ColorToken::SecondaryHover,
+        ColorToken::SecondaryActive,
+        ColorToken::Success,
+        ColorToken::SuccessBg,
+        ColorToken::Warning,
+        ColorToken::WarningBg,
+        ColorToken::Error,
+        ColorToken::ErrorBg,
+        ColorToken::Info,
+        ColorToken::InfoBg,
+        ColorToken::Background,
+        ColorToken::Surface,
+        ColorToken::SurfaceElevated,
+        ColorToken::SurfaceOverlay,
+        ColorToken::TextPrimary,
+        ColorToken::TextSecondary,
+        ColorToken::TextTertiary,
+        ColorToken::TextInverse,
+        ColorToken::TextLink,
+        ColorToken::Border,
+        ColorToken::BorderHover,
+        ColorToken::BorderFocus,
+        ColorToken::BorderError,
+        ColorToken::InputBg,
+        ColorToken::InputBgHover,
+        ColorToken::InputBgFocus,
+        ColorToken::InputBgDisabled,
+        ColorToken::Selection,
+        ColorToken::SelectionText,
+        ColorToken::Accent,
+        ColorToken::AccentSubtle,
+        ColorToken::TooltipBackground,
+        ColorToken::TooltipText,
+    ];
+
+    /// Name of the token's field in [`ColorTokens`], e.g. `primary_hover`
+    pub const fn name(self) -> &'static str {
+        match self {
+            ColorToken::Primary => "primary",
+            ColorToken::PrimaryHover => "primary_hover",
+            ColorToken::PrimaryActive => "primary_active",
+            ColorToken::Secondary => "secondary",
+            ColorToken::SecondaryHover => "secondary_hover",
+            ColorToken::SecondaryActive => "secondary_active",
+            ColorToken::Success => "success",
+            ColorToken::SuccessBg => "success_bg",
+            ColorToken::Warning => "warning",
+            ColorToken::WarningBg => "warning_bg",
+            ColorToken::Error => "error",
+            ColorToken::ErrorBg => "error_bg",
+            ColorToken::Info => "info",
+            ColorToken::InfoBg => "info_bg",
+            ColorToken::Background => "background",
+            ColorToken::Surface => "surface",
+            ColorToken::SurfaceElevated => "surface_elevated",
+            ColorToken::SurfaceOverlay => "surface_overlay",
+            ColorToken::TextPrimary => "text_primary",
+            ColorToken::TextSecondary => "text_secondary",
+            ColorToken::TextTertiary => "text_tertiary",
+            ColorToken::TextInverse => "text_inverse",
+            ColorToken::TextLink => "text_link",
+            ColorToken::Border => "border",
+            ColorToken::BorderHover => "border_hover",
+            ColorToken::BorderFocus => "border_focus",
+            ColorToken::BorderError => "border_error",
+            ColorToken::InputBg => "input_bg",
+            ColorToken::InputBgHover => "input_bg_hover",
+            ColorToken::InputBgFocus => "input_bg_focus",
+            ColorToken::InputBgDisabled => "input_bg_disabled",
+            ColorToken::Selection => "selection",
+            ColorToken::SelectionText => "selection_text",
+            ColorToken::Accent => "accent",
+            ColorToken::AccentSubtle => "accent_subtle",
+            ColorToken::TooltipBackground => "tooltip_bg",
+            ColorToken::TooltipText => "tooltip_text",
+        }
+    }
+}
+
 /// Complete set of semantic color tokens
 #[derive(Clone, Debug)]
 pub struct ColorTokens {
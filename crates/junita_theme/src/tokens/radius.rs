@@ -14,6 +14,36 @@ pub enum RadiusToken {
     Full,
 }
 
+impl RadiusToken {
+    /// Every token, in declaration order
+    pub const ALL: &'static [RadiusToken] = &[
+        RadiusToken::None,
+        RadiusToken::Sm,
+        RadiusToken::Default,
+        RadiusToken::Md,
+        RadiusToken::Lg,
+        RadiusToken::Xl,
+        RadiusToken::Xxl,
+        RadiusToken::Xxxl,
+        RadiusToken::Full,
+    ];
+
+    /// Name of the token's field in [`RadiusTokens`], e.g. `radius_sm`
+    pub const fn name(self) -> &'static str {
+        match self {
+            RadiusToken::None => "radius_none",
+            RadiusToken::Sm => "radius_sm",
+            RadiusToken::Default => "radius_default",
+            RadiusToken::Md => "radius_md",
+            RadiusToken::Lg => "radius_lg",
+            RadiusToken::Xl => "radius_xl",
+            RadiusToken::Xxl => "radius_2xl",
+            RadiusToken::Xxxl => "radius_3xl",
+            RadiusToken::Full => "radius_full",
+        }
+    }
+}
+
 /// Complete set of border radius tokens
 #[derive(Clone, Debug)]
 pub struct RadiusTokens {
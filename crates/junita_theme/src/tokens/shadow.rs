@@ -15,6 +15,34 @@ pub enum ShadowToken {
     None,
 }
 
+impl ShadowToken {
+    /// Every token, in declaration order
+    pub const ALL: &'static [ShadowToken] = &[
+        ShadowToken::Sm,
+        ShadowToken::Default,
+        ShadowToken::Md,
+        ShadowToken::Lg,
+        ShadowToken::Xl,
+        ShadowToken::Xxl,
+        ShadowToken::Inner,
+        ShadowToken::None,
+    ];
+
+    /// Name of the token's field in [`ShadowTokens`], e.g. `shadow_default`
+    pub const fn name(self) -> &'static str {
+        match self {
+            ShadowToken::Sm => "shadow_sm",
+            ShadowToken::Default => "shadow_default",
+            ShadowToken::Md => "shadow_md",
+            ShadowToken::Lg => "shadow_lg",
+            ShadowToken::Xl => "shadow_xl",
+            ShadowToken::Xxl => "shadow_2xl",
+            ShadowToken::Inner => "shadow_inner",
+            ShadowToken::None => "shadow_none",
+        }
+    }
+}
+
 /// A box shadow definition
 #[derive(Clone, Debug)]
 pub struct Shadow {
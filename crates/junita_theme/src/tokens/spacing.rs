@@ -28,6 +28,64 @@ pub enum SpacingToken {
     Space32,
 }
 
+impl SpacingToken {
+    /// Every token, in declaration order
+    pub const ALL: &'static [SpacingToken] = &[
+        SpacingToken::Space0,
+        SpacingToken::Space0_5,
+        SpacingToken::Space1,
+        SpacingToken::Space1_5,
+        SpacingToken::Space2,
+        SpacingToken::Space2_5,
+        SpacingToken::Space3,
+        SpacingToken::Space3_5,
+        SpacingToken::Space4,
+        SpacingToken::Space5,
+        SpacingToken::Space6,
+        SpacingToken::Space7,
+        SpacingToken::Space8,
+        SpacingToken::Space9,
+        SpacingToken::Space10,
+        SpacingToken::Space11,
+        SpacingToken::Space12,
+        SpacingToken::Space14,
+        SpacingToken::Space16,
+        SpacingToken::Space20,
+        SpacingToken::Space24,
+        SpacingToken::Space28,
+        SpacingToken::Space32,
+    ];
+
+    /// Name of the token's field in [`SpacingTokens`], e.g. `space_0_5`
+    pub const fn name(self) -> &'static str {
+        match self {
+            SpacingToken::Space0 => "space_0",
+            SpacingToken::Space0_5 => "space_0_5",
+            SpacingToken::Space1 => "space_1",
+            SpacingToken::Space1_5 => "space_1_5",
+            SpacingToken::Space2 => "space_2",
+            SpacingToken::Space2_5 => "space_2_5",
+            SpacingToken::Space3 => "space_3",
+            SpacingToken::Space3_5 => "space_3_5",
+            SpacingToken::Space4 => "space_4",
+            SpacingToken::Space5 => "space_5",
+            SpacingToken::Space6 => "space_6",
+            SpacingToken::Space7 => "space_7",
+            SpacingToken::Space8 => "space_8",
+            SpacingToken::Space9 => "space_9",
+            SpacingToken::Space10 => "space_10",
+            SpacingToken::Space11 => "space_11",
+            SpacingToken::Space12 => "space_12",
+            SpacingToken::Space14 => "space_14",
+            SpacingToken::Space16 => "space_16",
+            SpacingToken::Space20 => "space_20",
+            SpacingToken::Space24 => "space_24",
+            SpacingToken::Space28 => "space_28",
+            SpacingToken::Space32 => "space_32",
+        }
+    }
+}
+
 /// Complete set of spacing tokens (4px base scale)
 #[derive(Clone, Debug)]
 pub struct SpacingTokens {
@@ -39,6 +39,72 @@ pub enum TypographyToken {
     TrackingWider,
 }
 
+impl TypographyToken {
+    /// Every token, in declaration order
+    pub const ALL: &'static [TypographyToken] = &[
+        TypographyToken::TextXs,
+        TypographyToken::TextSm,
+        TypographyToken::TextBase,
+        TypographyToken::TextLg,
+        TypographyToken::TextXl,
+        TypographyToken::Text2xl,
+        TypographyToken::Text3xl,
+        TypographyToken::Text4xl,
+        TypographyToken::Text5xl,
+        TypographyToken::FontThin,
+        TypographyToken::FontLight,
+        TypographyToken::FontNormal,
+        TypographyToken::FontMedium,
+        TypographyToken::FontSemibold,
+        TypographyToken::FontBold,
+        TypographyToken::FontBlack,
+        TypographyToken::LeadingNone,
+        TypographyToken::LeadingTight,
+        TypographyToken::LeadingSnug,
+        TypographyToken::LeadingNormal,
+        TypographyToken::LeadingRelaxed,
+        TypographyToken::LeadingLoose,
+        TypographyToken::TrackingTighter,
+        TypographyToken::TrackingTight,
+        TypographyToken::TrackingNormal,
+        TypographyToken::TrackingWide,
+        TypographyToken::TrackingWider,
+    ];
+
+    /// Name of the token's field in [`TypographyTokens`], e.g. `text_sm`
+    pub const fn name(self) -> &'static str {
+        match self {
+            TypographyToken::TextXs => "text_xs",
+            TypographyToken::TextSm => "text_sm",
+            TypographyToken::TextBase => "text_base",
+            TypographyToken::TextLg => "text_lg",
+            TypographyToken::TextXl => "text_xl",
+            TypographyToken::Text2xl => "text_2xl",
+            TypographyToken::Text3xl => "text_3xl",
+            TypographyToken::Text4xl => "text_4xl",
+            TypographyToken::Text5xl => "text_5xl",
+            TypographyToken::FontThin => "font_thin",
+            TypographyToken::FontLight => "font_light",
+            TypographyToken::FontNormal => "font_normal",
+            TypographyToken::FontMedium => "font_medium",
+            TypographyToken::FontSemibold => "font_semibold",
+            TypographyToken::FontBold => "font_bold",
+            TypographyToken::FontBlack => "font_black",
+            TypographyToken::LeadingNone => "leading_none",
+            TypographyToken::LeadingTight => "leading_tight",
+            TypographyToken::LeadingSnug => "leading_snug",
+            TypographyToken::LeadingNormal => "leading_normal",
+            TypographyToken::LeadingRelaxed => "leading_relaxed",
+            TypographyToken::LeadingLoose => "leading_loose",
+            TypographyToken::TrackingTighter => "tracking_tighter",
+            TypographyToken::TrackingTight => "tracking_tight",
+            TypographyToken::TrackingNormal => "tracking_normal",
+            TypographyToken::TrackingWide => "tracking_wide",
+            TypographyToken::TrackingWider => "tracking_wider",
+        }
+    }
+}
+
 /// Font family definition
 #[derive(Clone, Debug)]
 pub struct FontFamily {
@@ -1,42 +1,7 @@
 "use strict";
-var __createBinding = (this && this.__createBinding) || (Object.create ? (function(o, m, k, k2) {
-    if (k2 === undefined) k2 = k;
-    var desc = Object.getOwnPropertyDescriptor(m, k);
-    if (!desc || ("get" in desc ? !m.__esModule : desc.writable || desc.configurable)) {
-      desc = { enumerable: true, get: function() { return m[k]; } };
-    }
-    Object.defineProperty(o, k2, desc);
-}) : (function(o, m, k, k2) {
-    if (k2 === undefined) k2 = k;
-    o[k2] = m[k];
-}));
-var __setModuleDefault = (this && this.__setModuleDefault) || (Object.create ? (function(o, v) {
-    Object.defineProperty(o, "default", { enumerable: true, value: v });
-}) : function(o, v) {
-    o["default"] = v;
-});
-var __importStar = (this && this.__importStar) || (function () {
-    var ownKeys = function(o) {
-        ownKeys = Object.getOwnPropertyNames || function (o) {
-            var ar = [];
-            for (var k in o) if (Object.prototype.hasOwnProperty.call(o, k)) ar[ar.length] = k;
-            return ar;
-        };
-        return ownKeys(o);
-    };
-    return function (mod) {
-        if (mod && mod.__esModule) return mod;
-        var result = {};
-        if (mod != null) for (var k = ownKeys(mod), i = 0; i < k.length; i++) if (k[i] !== "default") __createBinding(result, mod, k[i]);
-        __setModuleDefault(result, mod);
-        return result;
-    };
-})();
 Object.defineProperty(exports, "__esModule", { value: true });
 exports.activate = activate;
 exports.deactivate = deactivate;
-const path = __importStar(require("path"));
-const os = __importStar(require("os"));
 const vscode_1 = require("vscode");
 const node_1 = require("vscode-languageclient/node");
 let client;
@@ -45,19 +10,18 @@ let statusBarItem;
 let outputChannel;
 async function activate(context) {
     console.log('🎉 Junita DSL Extension activated with LSP support');
-    // Determine the LSP server path
-    const platform = os.platform();
-    const isWindows = platform === 'win32';
-    // Try to find the compiled LSP binary
-    let serverPath = path.join(context.extensionPath, '..', '..', 'junita-analyzer', 'target', 'release', isWindows ? 'junita-lsp.exe' : 'junita-lsp');
+    // The server is the Junita CLI's `junita lsp` subcommand
+    const command = vscode_1.workspace.getConfiguration('junita').get('serverPath', 'junita');
     // Server options
     const serverOptions = {
         run: {
-            command: serverPath,
+            command,
+            args: ['lsp'],
             transport: node_1.TransportKind.stdio
         },
         debug: {
-            command: serverPath,
+            command,
+            args: ['lsp', '--verbose'],
             transport: node_1.TransportKind.stdio
         }
     };
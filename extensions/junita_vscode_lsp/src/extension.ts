@@ -1,5 +1,3 @@
-import * as path from 'path';
-import * as os from 'os';
 import { workspace, ExtensionContext, window, commands, StatusBarAlignment } from 'vscode';
 import {
 LanguageClient,
@@ -17,29 +15,19 @@ let outputChannel: any;
 export async function activate(context: ExtensionContext) {
 console.log('🎉 Junita DSL Extension activated with LSP support');
 
-// Determine the LSP server path
-const platform = os.platform();
-const isWindows = platform === 'win32';
-
-// Try to find the compiled LSP binary
-let serverPath = path.join(
-context.extensionPath,
-'..',
-'..',
-'junita-analyzer',
-'target',
-'release',
-isWindows ? 'junita-lsp.exe' : 'junita-lsp'
-);
+// The server is the Junita CLI's `junita lsp` subcommand
+const command = workspace.getConfiguration('junita').get<string>('serverPath', 'junita');
 
 // Server options
 const serverOptions: ServerOptions = {
 run: {
-command: serverPath,
+command,
+args: ['lsp'],
 transport: TransportKind.stdio
 },
 debug: {
-command: serverPath,
+command,
+args: ['lsp', '--verbose'],
 transport: TransportKind.stdio
 }
 };
@@ -0,0 +1,19 @@
+//! `.junita` parsing is lossless and compiling never panics
+//!
+//! The compiler has no separate `parse_junita` step: `compile_source` parses
+//! and lowers in one call, so the syntax tree is checked on its own first.
+
+#![no_main]
+
+use std::path::Path;
+
+use junita_cli::compiler::JunitaCompiler;
+use junita_cli::syntax;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|source: &str| {
+    let parse = syntax::parse(source);
+    assert_eq!(parse.root.text(), source, "syntax tree lost source text");
+
+    let _ = JunitaCompiler::new().compile_source(source, Path::new("fuzz.junita"));
+});
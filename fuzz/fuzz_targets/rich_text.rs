@@ -0,0 +1,17 @@
+//! Rich-text markup parsing never panics and produces spans inside the
+//! parsed text
+
+#![no_main]
+
+use junita_layout::RichText;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|markup: &str| {
+    let rich = RichText::new(markup);
+    let content = rich.content();
+    for span in rich.spans() {
+        assert!(span.start <= span.end, "inverted span {:?}", span);
+        assert!(span.end <= content.len(), "span {:?} past end", span);
+        assert!(content.is_char_boundary(span.start) && content.is_char_boundary(span.end));
+    }
+});
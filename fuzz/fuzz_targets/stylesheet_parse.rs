@@ -0,0 +1,13 @@
+//! CSS parsing with error collection never panics, whatever the input
+
+#![no_main]
+
+use junita_layout::css_parser::Stylesheet;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|css: &str| {
+    let result = Stylesheet::parse_with_errors(css);
+    for error in &result.errors {
+        assert!(error.line >= 1, "line numbers start at 1: {:?}", error);
+    }
+});